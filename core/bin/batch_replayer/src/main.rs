@@ -0,0 +1,86 @@
+use anyhow::Context as _;
+use clap::Parser;
+use tokio::sync::watch;
+use zksync_config::{
+    configs::{chain::NetworkConfig, ObservabilityConfig},
+    PostgresConfig,
+};
+use zksync_core::state_keeper::replay::BatchReplayer;
+use zksync_dal::{ConnectionPool, Core};
+use zksync_env_config::FromEnv;
+use zksync_types::L1BatchNumber;
+
+#[derive(Debug, Parser)]
+#[command(
+    author = "Matter Labs",
+    version,
+    about = "Re-executes sealed L1 batches and compares outputs with the persisted ones",
+    long_about = None
+)]
+struct Cli {
+    /// Number of the L1 batch to replay.
+    #[arg(long)]
+    l1_batch_number: u32,
+    /// Number of the last L1 batch to replay. If not specified, only a single batch is replayed.
+    #[arg(long)]
+    last_l1_batch_number: Option<u32>,
+    /// Enables optional bytecode compression. Should be set when replaying batches executed by an external node.
+    #[arg(long)]
+    optional_bytecode_compression: bool,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let opt = Cli::parse();
+
+    let observability_config =
+        ObservabilityConfig::from_env().context("ObservabilityConfig::from_env()")?;
+    let log_format: vlog::LogFormat = observability_config
+        .log_format
+        .parse()
+        .context("Invalid log format")?;
+    let mut builder = vlog::ObservabilityBuilder::new().with_log_format(log_format);
+    if let Some(sentry_url) = observability_config.sentry_url {
+        builder = builder
+            .with_sentry_url(&sentry_url)
+            .context("Invalid Sentry URL")?
+            .with_sentry_environment(observability_config.sentry_environment);
+    }
+    let _guard = builder.build();
+
+    let network_config = NetworkConfig::from_env().context("NetworkConfig::from_env()")?;
+    let postgres_config = PostgresConfig::from_env().context("PostgresConfig::from_env()")?;
+    let connection_pool = ConnectionPool::<Core>::builder(
+        postgres_config.replica_url()?,
+        postgres_config.max_connections()?,
+    )
+    .build()
+    .await
+    .context("failed to build a connection pool")?;
+
+    let replayer = BatchReplayer::new(
+        connection_pool,
+        network_config.zksync_network_id,
+        opt.optional_bytecode_compression,
+    );
+    let (_stop_sender, stop_receiver) = watch::channel(false);
+    let last_l1_batch_number = opt.last_l1_batch_number.unwrap_or(opt.l1_batch_number);
+    let mut inconsistent_batches = vec![];
+    for l1_batch_number in opt.l1_batch_number..=last_l1_batch_number {
+        let l1_batch_number = L1BatchNumber(l1_batch_number);
+        let report = replayer
+            .replay(l1_batch_number, &stop_receiver)
+            .await
+            .with_context(|| format!("failed replaying L1 batch #{l1_batch_number}"))?;
+        println!("L1 batch #{l1_batch_number}: {report}");
+        if !report.is_consistent() {
+            inconsistent_batches.push(l1_batch_number);
+        }
+    }
+
+    anyhow::ensure!(
+        inconsistent_batches.is_empty(),
+        "replay diverged from persisted data for L1 batches {inconsistent_batches:?}"
+    );
+    Ok(())
+}
@@ -1,14 +1,18 @@
+use std::{path::Path, sync::Arc};
+
 use anyhow::Context as _;
 use clap::{Parser, Subcommand};
 use tokio::io::{self, AsyncReadExt};
 use zksync_config::{
-    configs::ObservabilityConfig, ContractsConfig, DBConfig, ETHConfig, PostgresConfig,
+    configs::{api::Web3JsonRpcConfig, ObservabilityConfig},
+    ContractsConfig, DBConfig, ETHConfig, PostgresConfig,
 };
 use zksync_core::block_reverter::{
     BlockReverter, BlockReverterEthConfig, BlockReverterFlags, L1ExecutedBatchesRevert, NodeRole,
 };
 use zksync_dal::{ConnectionPool, Core};
 use zksync_env_config::FromEnv;
+use zksync_state::{CacheInvalidator, HistoricalState};
 use zksync_types::{L1BatchNumber, U256};
 
 #[derive(Debug, Parser)]
@@ -63,11 +67,33 @@ enum Command {
         allow_executed_block_reversion: bool,
     },
 
+    /// Rolls back only the Merkle tree to the specified L1 batch, leaving Postgres intact. Unlike `rollback-db`,
+    /// the rollback is refused if Postgres is ahead of or behind the L1 batch, or if the tree root hash
+    /// for the L1 batch doesn't match the one in Postgres. The tree must not be running.
+    #[command(name = "rollback-tree")]
+    RollbackTree {
+        /// L1 batch number used to rollback to.
+        #[arg(long)]
+        l1_batch_number: u32,
+    },
+
     /// Clears failed L1 transactions.
     #[command(name = "clear-failed-transactions")]
     ClearFailedL1Transactions,
 }
 
+/// Opens API server caches persisted on disk, which must be rolled back together with Postgres. The persistent tier
+/// of the storage values cache isn't included since it checks its validity against Postgres on server startup.
+fn persistent_api_caches(web3_config: &Web3JsonRpcConfig) -> anyhow::Result<CacheInvalidator> {
+    let cache_invalidator = CacheInvalidator::default();
+    if let Some(path) = &web3_config.historical_state_path {
+        let historical_state = HistoricalState::new(Path::new(path))
+            .context("failed opening historical state; make sure that the API server is stopped")?;
+        cache_invalidator.register(Arc::new(historical_state));
+    }
+    Ok(cache_invalidator)
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let observability_config =
@@ -178,6 +204,10 @@ async fn main() -> anyhow::Result<()> {
             let mut flags = BlockReverterFlags::empty();
             if rollback_postgres {
                 flags |= BlockReverterFlags::POSTGRES;
+                let web3_config =
+                    Web3JsonRpcConfig::from_env().context("Web3JsonRpcConfig::from_env()")?;
+                block_reverter =
+                    block_reverter.with_cache_invalidator(persistent_api_caches(&web3_config)?);
             }
             if rollback_tree {
                 flags |= BlockReverterFlags::TREE;
@@ -190,6 +220,12 @@ async fn main() -> anyhow::Result<()> {
                 .rollback_db(L1BatchNumber(l1_batch_number), flags)
                 .await
         }
+        Command::RollbackTree { l1_batch_number } => {
+            block_reverter
+                .rollback_tree(L1BatchNumber(l1_batch_number))
+                .await
+                .context("failed rolling back Merkle tree")?;
+        }
         Command::ClearFailedL1Transactions => block_reverter.clear_failed_l1_transactions().await,
     }
     Ok(())
@@ -7,17 +7,28 @@ use std::{
 use anyhow::Context;
 use serde::Deserialize;
 use url::Url;
-use zksync_basic_types::{Address, L1ChainId, L2ChainId};
-use zksync_config::{configs::chain::L1BatchCommitDataGeneratorMode, ObjectStoreConfig};
+use zksync_basic_types::{Address, L1ChainId, L2ChainId, H256};
+use zksync_config::{
+    configs::{
+        api::MerkleTreeApiConfig,
+        chain::L1BatchCommitDataGeneratorMode,
+        database::{MerkleTreeCompactionStyle, MerkleTreeRocksdbPreset},
+    },
+    ObjectStoreConfig,
+};
 use zksync_core::{
     api_server::{
         tx_sender::TxSenderConfig,
         web3::{state::InternalApiConfig, Namespace},
     },
     consensus,
+    state_keeper::FeeAccountSelector,
     temp_config_store::decode_yaml,
 };
-use zksync_types::{api::BridgeAddresses, fee_model::FeeParams};
+use zksync_types::{
+    api::{AaValidationRules, BridgeAddresses, TokenBridge},
+    fee_model::FeeParams,
+};
 use zksync_web3_decl::{
     client::L2Client,
     error::ClientRpcContext,
@@ -42,6 +53,10 @@ pub(crate) struct RemoteENConfig {
     pub l2_erc20_bridge_addr: Address,
     pub l1_weth_bridge_proxy_addr: Option<Address>,
     pub l2_weth_bridge_addr: Option<Address>,
+    pub l1_shared_bridge_proxy_addr: Option<Address>,
+    pub l2_shared_bridge_addr: Option<Address>,
+    /// Custom token bridges known to the main node at the time the config was fetched.
+    pub custom_token_bridges: Vec<TokenBridge>,
     pub l2_testnet_paymaster_addr: Option<Address>,
     pub l2_chain_id: L2ChainId,
     pub l1_chain_id: L1ChainId,
@@ -99,6 +114,9 @@ impl RemoteENConfig {
             l2_erc20_bridge_addr: bridges.l2_erc20_default_bridge,
             l1_weth_bridge_proxy_addr: bridges.l1_weth_bridge,
             l2_weth_bridge_addr: bridges.l2_weth_bridge,
+            l1_shared_bridge_proxy_addr: bridges.l1_shared_default_bridge,
+            l2_shared_bridge_addr: bridges.l2_shared_default_bridge,
+            custom_token_bridges: bridges.custom_bridges,
             l2_chain_id,
             l1_chain_id,
             max_pubdata_per_batch,
@@ -141,14 +159,32 @@ pub(crate) struct OptionalENConfig {
     /// Max number of cache misses during one VM execution. If the number of cache misses exceeds this value, the API server panics.
     /// This is a temporary solution to mitigate API request resulting in thousands of DB queries.
     pub vm_execution_cache_misses_limit: Option<usize>,
+    /// Max wall-clock duration of a single VM execution for `eth_call` and `debug_traceCall` (in ms).
+    /// If exceeded, the execution is aborted. If not set, VM executions are only limited by gas.
+    vm_execution_timeout_ms: Option<u64>,
+    /// Max number of `eth_call` results on historical blocks to cache. If not set or set to 0,
+    /// `eth_call` results will not be cached.
+    pub eth_call_cache_size: Option<usize>,
+    /// Max number of `eth_estimateGas` results to cache. If not set or set to 0, estimation results will not be cached.
+    pub estimate_gas_cache_size: Option<usize>,
+    /// Time-to-live for cached `eth_estimateGas` results (in ms).
+    #[serde(default = "OptionalENConfig::default_estimate_gas_cache_ttl_ms")]
+    estimate_gas_cache_ttl_ms: u64,
     /// Note: Deprecated option, no longer in use. Left to display a warning in case someone used them.
     pub transactions_per_sec_limit: Option<u32>,
     /// Limit for fee history block range.
     #[serde(default = "OptionalENConfig::default_fee_history_limit")]
     pub fee_history_limit: u64,
+    /// Maximum number of L1 batches processed by a single `unstable_simulateFeeModel` request.
+    #[serde(default = "OptionalENConfig::default_fee_model_simulation_limit")]
+    pub fee_model_simulation_limit: u64,
     /// Maximum number of requests in a single batch JSON RPC request. Default is 500.
     #[serde(default = "OptionalENConfig::default_max_batch_request_size")]
     pub max_batch_request_size: usize,
+    /// Maximum total cost of calls in a single batch JSON RPC request, with calls weighted by the called method
+    /// (e.g., `debug_trace*` methods cost 50 units, and `eth_chainId` costs 1 unit). Default is 2000.
+    #[serde(default = "OptionalENConfig::default_max_batch_request_cost")]
+    pub max_batch_request_cost: u32,
     /// Maximum response body size in MiBs. Default is 10 MiB.
     #[serde(default = "OptionalENConfig::default_max_response_body_size_mb")]
     pub max_response_body_size_mb: usize,
@@ -160,13 +196,52 @@ pub(crate) struct OptionalENConfig {
         default = "OptionalENConfig::default_polling_interval"
     )]
     polling_interval: u64,
+    /// Whether to wake up pubsub notifiers using Postgres notifications instead of polling the DB.
+    /// If enabled, `pubsub_polling_interval` is used to rate-limit DB queries. The triggers sending notifications
+    /// are enabled by migrations; if an operator has disabled them, notifiers fall back to polling.
+    #[serde(default)]
+    pub pubsub_db_notifications: bool,
+    /// Port for the server-sent events (SSE) endpoint streaming new block headers and logs. The endpoint is started
+    /// alongside the WebSocket server and is disabled if not set.
+    pub sse_port: Option<u16>,
+    /// Secret token required to call methods in privileged namespaces (`debug`, `en`, `admin` and `unstable`)
+    /// on the HTTP server. If not set, calls to these namespaces are not authenticated.
+    pub http_auth_token: Option<String>,
+    /// Secret token required to connect to the WebSocket server if it has privileged namespaces enabled.
+    /// If not set, connections are not authenticated.
+    pub ws_auth_token: Option<String>,
+    /// Path to the Unix domain socket serving the JSON-RPC API (akin to Geth's `--ipcpath`). The IPC endpoint is started
+    /// alongside the WebSocket server, serves the same namespaces except for privileged ones (`debug`, `en`, `admin`
+    /// and `unstable`), and is disabled if not set.
+    pub ipc_path: Option<String>,
     /// Tx nonce: how far ahead from the committed nonce can it be.
     #[serde(default = "OptionalENConfig::default_max_nonce_ahead")]
     pub max_nonce_ahead: u32,
+    /// Maximum number of transactions per account with nonces exceeding `max_nonce_ahead` queued by the API server
+    /// until they can be sent to the main node. If set to 0 (the default), such transactions are rejected.
+    #[serde(default = "OptionalENConfig::default_max_queued_txs_per_account")]
+    pub max_queued_txs_per_account: usize,
+    /// Minimum fee bump (in percent) required to replace a pending transaction with the same initiator and nonce.
+    /// Default is 10%.
+    #[serde(default = "OptionalENConfig::default_tx_replacement_fee_bump_percent")]
+    pub tx_replacement_fee_bump_percent: u32,
+    /// Maximum number of transactions per minute that can be submitted from a single sender address.
+    /// If not set, submissions are not rate-limited per sender.
+    pub max_tx_submissions_per_minute_per_sender: Option<NonZeroU32>,
+    /// Maximum number of pending transactions from a single sender address. If not set, the number of pending
+    /// transactions is not limited.
+    pub max_pending_txs_per_sender: Option<u32>,
     /// Max number of VM instances to be concurrently spawned by the API server.
     /// This option can be tweaked down if the API server is running out of memory.
     #[serde(default = "OptionalENConfig::default_vm_concurrency_limit")]
     pub vm_concurrency_limit: usize,
+    /// Timeout (in ms) for waiting for a VM permit in the API server. Requests that cannot obtain a permit in time
+    /// are rejected as the server is overloaded. If not set, requests wait for a permit indefinitely.
+    vm_permit_timeout_ms: Option<u64>,
+    /// Share of VM permits in use (from 0 to 1) starting from which the API server VM sandbox is reported
+    /// as degraded by the health check. Default value is 0.9.
+    #[serde(default = "OptionalENConfig::default_vm_saturation_threshold")]
+    pub vm_saturation_threshold: f64,
     /// Smart contract bytecode cache size for the API server. Default value is 128 MiB.
     #[serde(default = "OptionalENConfig::default_factory_deps_cache_size_mb")]
     factory_deps_cache_size_mb: usize,
@@ -177,8 +252,32 @@ pub(crate) struct OptionalENConfig {
     /// values cache will be disabled.
     #[serde(default = "OptionalENConfig::default_latest_values_cache_size_mb")]
     latest_values_cache_size_mb: usize,
+    /// Path to RocksDB used as a persistent tier of the latest values cache. The persistent tier retains cached values
+    /// across restarts and is not limited by the in-memory cache size. If not set, only the in-memory cache is used.
+    pub latest_values_cache_path: Option<String>,
+    /// Maximum number of entries in the persistent tier of the latest values cache. Once exceeded, the persistent tier
+    /// is emptied.
+    #[serde(default = "OptionalENConfig::default_latest_values_cache_persistent_max_entries")]
+    pub latest_values_cache_persistent_max_entries: u64,
+    /// Number of latest miniblocks storage logs of which are used to warm up VM caches (storage values and factory deps)
+    /// on API server startup, before the server starts accepting requests. If not set, caches are not warmed up.
+    pub caches_warm_up_miniblocks: Option<u32>,
+    /// Path to RocksDB with the local historical VM state. If set, the state is kept up to date with Postgres
+    /// and is used to serve storage reads for past miniblocks (e.g., in `eth_call` and `eth_getStorageAt`),
+    /// falling back to Postgres only for miniblocks not covered by the state.
+    pub historical_state_path: Option<String>,
+    /// Number of latest miniblocks retained by the historical state. Older miniblocks are pruned from the state;
+    /// reads for them fall back to Postgres. If not set, the state retains all miniblocks with storage logs in Postgres.
+    pub historical_state_retained_miniblocks: Option<u32>,
     /// Enabled JSON RPC API namespaces.
     api_namespaces: Option<Vec<Namespace>>,
+    /// Storage slots (as indices in the contract storage) trusted during AA validation for each bridged
+    /// or white-listed token, in addition to the standard ERC-20 slots.
+    #[serde(default)]
+    pub aa_trusted_token_slots: Vec<H256>,
+    /// Addresses of contracts that can be called during AA validation with unrestricted access to their storage.
+    #[serde(default)]
+    pub aa_trusted_addresses: Vec<Address>,
     /// Whether to support HTTP methods that install filters and query filter changes.
     /// WS methods are unaffected.
     ///
@@ -212,6 +311,11 @@ pub(crate) struct OptionalENConfig {
     /// The max possible number of gas that `eth_estimateGas` is allowed to overestimate.
     #[serde(default = "OptionalENConfig::default_estimate_gas_acceptable_overestimation")]
     pub estimate_gas_acceptable_overestimation: u32,
+    /// Whether to optimize the binary search performed by `eth_estimateGas`. If enabled, the search is seeded
+    /// with the gas used by the transaction in the initial run, and is skipped for simple transfers
+    /// if possible.
+    #[serde(default)]
+    pub estimate_gas_optimize_search: bool,
     /// Whether to use the compatibility mode for gas estimation for L1->L2 transactions.
     /// During the migration to the 1.4.1 fee model, there will be a period, when the server
     /// will already have the 1.4.1 fee model, while the L1 contracts will still expect the transactions
@@ -258,12 +362,53 @@ pub(crate) struct OptionalENConfig {
     /// Timeout to wait for the Merkle tree database to run compaction on stalled writes.
     #[serde(default = "OptionalENConfig::default_merkle_tree_stalled_writes_timeout_sec")]
     merkle_tree_stalled_writes_timeout_sec: u64,
+    /// Number of threads used to hash Merkle tree nodes. If not specified, the global `rayon` thread pool
+    /// will be used (by default, it has a thread per logical CPU core).
+    pub merkle_tree_hashing_thread_count: Option<usize>,
+    /// Retention period for old Merkle tree versions measured by the age of the corresponding L1 batches.
+    /// If not specified, old tree versions are not pruned.
+    merkle_tree_pruning_retention_sec: Option<u64>,
+    /// Preset of RocksDB options for the Merkle tree. If not specified, the preset is chosen based on
+    /// whether tree pruning is enabled.
+    merkle_tree_rocksdb_preset: Option<MerkleTreeRocksdbPreset>,
+    /// Compaction style for large Merkle tree RocksDB column families. Overrides the value from the preset.
+    merkle_tree_compaction_style: Option<MerkleTreeCompactionStyle>,
+    /// Whether to store Merkle tree leaves in a separate RocksDB column family. Only has an effect
+    /// when creating a new database. Enabled by default.
+    merkle_tree_separate_leaves_column_family: Option<bool>,
+    /// Whether to bootstrap the Merkle tree from a tree snapshot exported by the main node when recovering
+    /// from a snapshot. The tree snapshot is read from the snapshots object store; if it's missing,
+    /// the tree is recovered from Postgres as usual.
+    #[serde(default)]
+    pub merkle_tree_recover_from_tree_snapshot: bool,
+    /// Number of L1 batches the Merkle tree intentionally lags behind the last sealed L1 batch. Useful for nodes
+    /// that only need eventual tree consistency (e.g., pruned RPC nodes). By default, the tree doesn't lag.
+    #[serde(default)]
+    pub merkle_tree_lag_l1_batches: u32,
+    /// Latency threshold in milliseconds for loading L1 batch data from Postgres; if exceeded, the Merkle tree
+    /// throttles itself to reduce Postgres load. If not specified, the tree processes L1 batches at full speed.
+    merkle_tree_throttle_latency_threshold_ms: Option<u64>,
+    /// Interval in seconds between background consistency checks of the Merkle tree, which verify a random subtree
+    /// and compare tree root hashes with the ones committed on L1. A detected inconsistency stops the node.
+    /// If not specified, background checks are disabled.
+    merkle_tree_consistency_check_interval_sec: Option<u64>,
 
     // Postgres config (new parameters)
     /// Threshold in milliseconds for the DB connection lifetime to denote it as long-living and log its details.
     database_long_connection_threshold_ms: Option<u64>,
     /// Threshold in milliseconds to denote a DB query as "slow" and log its details.
     database_slow_query_threshold_ms: Option<u64>,
+    /// Fraction of slow queries for which query plans are obtained using `EXPLAIN` and stored for inspection
+    /// via the `admin` Web3 namespace. If not specified, slow queries are not captured.
+    database_slow_query_explain_sample_rate: Option<f64>,
+    /// Timeout in milliseconds for DB queries. Queries exceeding the timeout are cancelled; connections with queries
+    /// dropped by the caller before completion are closed. If not specified, queries don't have a timeout.
+    database_query_timeout_ms: Option<u64>,
+    /// Maximum number of retries for DB transactions failing with a serialization or deadlock error.
+    database_query_max_retries: Option<u32>,
+    /// Whether to use binary `COPY` statements for bulk inserts of storage logs, events and initial writes.
+    #[serde(default)]
+    database_use_binary_copy: bool,
 
     // Other config settings
     /// Port on which the Prometheus exporter server is listening.
@@ -282,6 +427,10 @@ pub(crate) struct OptionalENConfig {
     /// By default, set to `true` as a temporary safety measure.
     #[serde(default = "OptionalENConfig::default_protective_reads_persistence_enabled")]
     pub protective_reads_persistence_enabled: bool,
+    /// Enables backfilling missing call traces by re-executing sealed L1 batches in the background. Call traces
+    /// cannot be backfilled for L1 batches preceding snapshot recovery. Disabled by default.
+    #[serde(default)]
+    pub call_traces_backfiller_enabled: bool,
     /// Address of the L1 diamond proxy contract used by the consistency checker to match with the origin of logs emitted
     /// by commit transactions. If not set, it will not be verified.
     // This is intentionally not a part of `RemoteENConfig` because fetching this info from the main node would defeat
@@ -301,11 +450,18 @@ pub struct ApiComponentConfig {
     /// local tree component running and in this case needs to send requests
     /// to some external tree API.
     pub tree_api_url: Option<String>,
+    /// URL of an archive node (i.e., a node retaining the entire chain history) used to serve `eth_call`
+    /// and `eth_getStorageAt` requests for blocks pruned from this EN storage. If not set, such requests
+    /// return an error.
+    pub archive_node_url: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Deserialize)]
 pub struct TreeComponentConfig {
     pub api_port: Option<u16>,
+    /// Maximum total number of keys in a single proofs request to the Merkle tree API.
+    #[serde(default = "MerkleTreeApiConfig::default_max_proof_keys")]
+    pub api_max_proof_keys: usize,
 }
 
 impl OptionalENConfig {
@@ -349,6 +505,14 @@ impl OptionalENConfig {
         50
     }
 
+    const fn default_max_queued_txs_per_account() -> usize {
+        0
+    }
+
+    const fn default_tx_replacement_fee_bump_percent() -> u32 {
+        10
+    }
+
     const fn default_metadata_calculator_delay() -> u64 {
         100
     }
@@ -364,6 +528,10 @@ impl OptionalENConfig {
         2_048
     }
 
+    const fn default_vm_saturation_threshold() -> f64 {
+        0.9
+    }
+
     const fn default_factory_deps_cache_size_mb() -> usize {
         128
     }
@@ -376,6 +544,10 @@ impl OptionalENConfig {
         128
     }
 
+    const fn default_latest_values_cache_persistent_max_entries() -> u64 {
+        50_000_000
+    }
+
     const fn default_merkle_tree_multi_get_chunk_size() -> usize {
         500
     }
@@ -396,10 +568,18 @@ impl OptionalENConfig {
         1_024
     }
 
+    const fn default_fee_model_simulation_limit() -> u64 {
+        100
+    }
+
     const fn default_max_batch_request_size() -> usize {
         500 // The default limit is chosen to be reasonably permissive.
     }
 
+    const fn default_max_batch_request_cost() -> u32 {
+        2_000 // Allows for a batch of 40 debug traces, or 200 `eth_call`s.
+    }
+
     const fn default_max_response_body_size_mb() -> usize {
         10
     }
@@ -424,6 +604,10 @@ impl OptionalENConfig {
         10_000
     }
 
+    const fn default_estimate_gas_cache_ttl_ms() -> u64 {
+        2_000
+    }
+
     fn default_main_node_rate_limit_rps() -> NonZeroUsize {
         NonZeroUsize::new(100).unwrap()
     }
@@ -432,6 +616,18 @@ impl OptionalENConfig {
         L1BatchCommitDataGeneratorMode::Rollup
     }
 
+    pub fn estimate_gas_cache_ttl(&self) -> Duration {
+        Duration::from_millis(self.estimate_gas_cache_ttl_ms)
+    }
+
+    pub fn vm_execution_timeout(&self) -> Option<Duration> {
+        self.vm_execution_timeout_ms.map(Duration::from_millis)
+    }
+
+    pub fn vm_permit_timeout(&self) -> Option<Duration> {
+        self.vm_permit_timeout_ms.map(Duration::from_millis)
+    }
+
     pub fn polling_interval(&self) -> Duration {
         Duration::from_millis(self.polling_interval)
     }
@@ -470,6 +666,40 @@ impl OptionalENConfig {
         Duration::from_secs(self.merkle_tree_stalled_writes_timeout_sec)
     }
 
+    /// Returns the retention period for old Merkle tree versions, or `None` if tree pruning is disabled.
+    pub fn merkle_tree_pruning_retention(&self) -> Option<Duration> {
+        self.merkle_tree_pruning_retention_sec
+            .map(Duration::from_secs)
+    }
+
+    /// Returns the compaction style for large Merkle tree RocksDB column families.
+    pub fn merkle_tree_compaction_style(&self) -> MerkleTreeCompactionStyle {
+        self.merkle_tree_compaction_style.unwrap_or_else(|| {
+            let preset = self.merkle_tree_rocksdb_preset.unwrap_or_else(|| {
+                MerkleTreeRocksdbPreset::new(self.merkle_tree_pruning_retention_sec.is_some())
+            });
+            preset.compaction_style()
+        })
+    }
+
+    /// Checks whether Merkle tree leaves should be stored in a separate RocksDB column family.
+    pub fn merkle_tree_separate_leaves_column_family(&self) -> bool {
+        self.merkle_tree_separate_leaves_column_family
+            .unwrap_or(true)
+    }
+
+    /// Returns the Postgres latency threshold for Merkle tree throttling, or `None` if throttling is disabled.
+    pub fn merkle_tree_throttle_latency_threshold(&self) -> Option<Duration> {
+        self.merkle_tree_throttle_latency_threshold_ms
+            .map(Duration::from_millis)
+    }
+
+    /// Returns the interval between background Merkle tree consistency checks, or `None` if these checks are disabled.
+    pub fn merkle_tree_consistency_check_interval(&self) -> Option<Duration> {
+        self.merkle_tree_consistency_check_interval_sec
+            .map(Duration::from_secs)
+    }
+
     pub fn long_connection_threshold(&self) -> Option<Duration> {
         self.database_long_connection_threshold_ms
             .map(Duration::from_millis)
@@ -480,6 +710,22 @@ impl OptionalENConfig {
             .map(Duration::from_millis)
     }
 
+    pub fn slow_query_explain_sample_rate(&self) -> Option<f64> {
+        self.database_slow_query_explain_sample_rate
+    }
+
+    pub fn query_timeout(&self) -> Option<Duration> {
+        self.database_query_timeout_ms.map(Duration::from_millis)
+    }
+
+    pub fn query_max_retries(&self) -> Option<u32> {
+        self.database_query_max_retries
+    }
+
+    pub fn use_binary_copy(&self) -> bool {
+        self.database_use_binary_copy
+    }
+
     pub fn api_namespaces(&self) -> Vec<Namespace> {
         self.api_namespaces
             .clone()
@@ -708,10 +954,13 @@ impl From<ExternalNodeConfig> for InternalApiConfig {
                 .optional
                 .estimate_gas_acceptable_overestimation,
             bridge_addresses: BridgeAddresses {
+                l1_shared_default_bridge: config.remote.l1_shared_bridge_proxy_addr,
+                l2_shared_default_bridge: config.remote.l2_shared_bridge_addr,
                 l1_erc20_default_bridge: config.remote.l1_erc20_bridge_proxy_addr,
                 l2_erc20_default_bridge: config.remote.l2_erc20_bridge_addr,
                 l1_weth_bridge: config.remote.l1_weth_bridge_proxy_addr,
                 l2_weth_bridge: config.remote.l2_weth_bridge_addr,
+                custom_bridges: config.remote.custom_token_bridges,
             },
             bridgehub_proxy_addr: config.remote.bridgehub_proxy_addr,
             state_transition_proxy_addr: config.remote.state_transition_proxy_addr,
@@ -720,6 +969,7 @@ impl From<ExternalNodeConfig> for InternalApiConfig {
             l2_testnet_paymaster_addr: config.remote.l2_testnet_paymaster_addr,
             req_entities_limit: config.optional.req_entities_limit,
             fee_history_limit: config.optional.fee_history_limit,
+            fee_model_simulation_limit: config.optional.fee_model_simulation_limit,
             filters_disabled: config.optional.filters_disabled,
             mempool_cache_update_interval: config.optional.mempool_cache_update_interval(),
             mempool_cache_size: config.optional.mempool_cache_size,
@@ -734,12 +984,25 @@ impl From<ExternalNodeConfig> for TxSenderConfig {
         Self {
             // Fee account address does not matter for the EN operation, since
             // actual fee distribution is handled my the main node.
-            fee_account_addr: "0xfee0000000000000000000000000000000000000"
-                .parse()
-                .unwrap(),
+            fee_accounts: FeeAccountSelector::fixed(
+                "0xfee0000000000000000000000000000000000000"
+                    .parse()
+                    .unwrap(),
+            ),
             gas_price_scale_factor: config.optional.gas_price_scale_factor,
             max_nonce_ahead: config.optional.max_nonce_ahead,
+            max_queued_txs_per_account: config.optional.max_queued_txs_per_account,
+            tx_replacement_fee_bump_percent: config.optional.tx_replacement_fee_bump_percent,
+            max_tx_submissions_per_minute_per_sender: config
+                .optional
+                .max_tx_submissions_per_minute_per_sender,
+            max_pending_txs_per_sender: config.optional.max_pending_txs_per_sender,
             vm_execution_cache_misses_limit: config.optional.vm_execution_cache_misses_limit,
+            vm_execution_timeout: config.optional.vm_execution_timeout(),
+            estimate_gas_optimize_search: config.optional.estimate_gas_optimize_search,
+            eth_call_cache_size: config.optional.eth_call_cache_size,
+            estimate_gas_cache_size: config.optional.estimate_gas_cache_size,
+            estimate_gas_cache_ttl: config.optional.estimate_gas_cache_ttl(),
             // We set these values to the maximum since we don't know the actual values
             // and they will be enforced by the main node anyway.
             max_allowed_l2_tx_gas_limit: u64::MAX,
@@ -751,6 +1014,10 @@ impl From<ExternalNodeConfig> for TxSenderConfig {
             max_pubdata_per_batch: config.remote.max_pubdata_per_batch,
             // Does not matter for EN.
             whitelisted_tokens_for_aa: Default::default(),
+            aa_validation_rules: AaValidationRules {
+                trusted_token_slots: config.optional.aa_trusted_token_slots,
+                trusted_addresses: config.optional.aa_trusted_addresses,
+            },
         }
     }
 }
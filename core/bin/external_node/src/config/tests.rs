@@ -8,6 +8,7 @@ fn parsing_optional_config_from_empty_env() {
     assert_eq!(config.filters_limit, 10_000);
     assert_eq!(config.subscriptions_limit, 10_000);
     assert_eq!(config.fee_history_limit, 1_024);
+    assert_eq!(config.fee_model_simulation_limit, 100);
     assert_eq!(config.polling_interval(), Duration::from_millis(200));
     assert_eq!(config.max_tx_size, 1_000_000);
     assert_eq!(
@@ -15,10 +16,27 @@ fn parsing_optional_config_from_empty_env() {
         Duration::from_millis(100)
     );
     assert_eq!(config.max_nonce_ahead, 50);
+    assert_eq!(config.max_queued_txs_per_account, 0);
+    assert_eq!(config.tx_replacement_fee_bump_percent, 10);
+    assert_eq!(config.max_tx_submissions_per_minute_per_sender, None);
+    assert_eq!(config.max_pending_txs_per_sender, None);
+    assert_eq!(config.sse_port, None);
+    assert_eq!(config.http_auth_token, None);
+    assert_eq!(config.ipc_path, None);
     assert_eq!(config.estimate_gas_scale_factor, 1.2);
     assert_eq!(config.vm_concurrency_limit, 2_048);
+    assert_eq!(config.vm_permit_timeout(), None);
+    assert_eq!(config.vm_saturation_threshold, 0.9);
     assert_eq!(config.factory_deps_cache_size(), 128 * BYTES_IN_MEGABYTE);
     assert_eq!(config.latest_values_cache_size(), 128 * BYTES_IN_MEGABYTE);
+    assert_eq!(config.latest_values_cache_path, None);
+    assert_eq!(
+        config.latest_values_cache_persistent_max_entries,
+        50_000_000
+    );
+    assert_eq!(config.caches_warm_up_miniblocks, None);
+    assert_eq!(config.historical_state_path, None);
+    assert_eq!(config.historical_state_retained_miniblocks, None);
     assert_eq!(config.merkle_tree_multi_get_chunk_size, 500);
     assert_eq!(
         config.merkle_tree_block_cache_size(),
@@ -42,10 +60,22 @@ fn parsing_optional_config_from_env() {
         ("EN_MAX_TX_SIZE", "1048576"),
         ("EN_METADATA_CALCULATOR_DELAY", "50"),
         ("EN_MAX_NONCE_AHEAD", "100"),
+        ("EN_MAX_TX_SUBMISSIONS_PER_MINUTE_PER_SENDER", "30"),
+        ("EN_MAX_PENDING_TXS_PER_SENDER", "16"),
+        ("EN_SSE_PORT", "3062"),
+        ("EN_HTTP_AUTH_TOKEN", "secret"),
+        ("EN_IPC_PATH", "/tmp/en.ipc"),
         ("EN_ESTIMATE_GAS_SCALE_FACTOR", "1.5"),
         ("EN_VM_CONCURRENCY_LIMIT", "1000"),
+        ("EN_VM_PERMIT_TIMEOUT_MS", "500"),
+        ("EN_VM_SATURATION_THRESHOLD", "0.75"),
         ("EN_FACTORY_DEPS_CACHE_SIZE_MB", "64"),
         ("EN_LATEST_VALUES_CACHE_SIZE_MB", "50"),
+        ("EN_LATEST_VALUES_CACHE_PATH", "/db/values_cache"),
+        ("EN_LATEST_VALUES_CACHE_PERSISTENT_MAX_ENTRIES", "1000000"),
+        ("EN_CACHES_WARM_UP_MINIBLOCKS", "100"),
+        ("EN_HISTORICAL_STATE_PATH", "/db/historical_state"),
+        ("EN_HISTORICAL_STATE_RETAINED_MINIBLOCKS", "10000"),
         ("EN_MERKLE_TREE_MULTI_GET_CHUNK_SIZE", "1000"),
         ("EN_MERKLE_TREE_BLOCK_CACHE_SIZE_MB", "32"),
         ("EN_MAX_RESPONSE_BODY_SIZE_MB", "1"),
@@ -67,10 +97,31 @@ fn parsing_optional_config_from_env() {
         Duration::from_millis(50)
     );
     assert_eq!(config.max_nonce_ahead, 100);
+    assert_eq!(
+        config.max_tx_submissions_per_minute_per_sender,
+        NonZeroU32::new(30)
+    );
+    assert_eq!(config.max_pending_txs_per_sender, Some(16));
+    assert_eq!(config.sse_port, Some(3062));
+    assert_eq!(config.http_auth_token.as_deref(), Some("secret"));
+    assert_eq!(config.ipc_path.as_deref(), Some("/tmp/en.ipc"));
     assert_eq!(config.estimate_gas_scale_factor, 1.5);
     assert_eq!(config.vm_concurrency_limit, 1_000);
+    assert_eq!(config.vm_permit_timeout(), Some(Duration::from_millis(500)));
+    assert_eq!(config.vm_saturation_threshold, 0.75);
     assert_eq!(config.factory_deps_cache_size(), 64 * BYTES_IN_MEGABYTE);
     assert_eq!(config.latest_values_cache_size(), 50 * BYTES_IN_MEGABYTE);
+    assert_eq!(
+        config.latest_values_cache_path.as_deref(),
+        Some("/db/values_cache")
+    );
+    assert_eq!(config.latest_values_cache_persistent_max_entries, 1_000_000);
+    assert_eq!(config.caches_warm_up_miniblocks, Some(100));
+    assert_eq!(
+        config.historical_state_path.as_deref(),
+        Some("/db/historical_state")
+    );
+    assert_eq!(config.historical_state_retained_miniblocks, Some(10_000));
     assert_eq!(config.merkle_tree_multi_get_chunk_size, 1_000);
     assert_eq!(
         config.merkle_tree_block_cache_size(),
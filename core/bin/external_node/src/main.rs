@@ -1,4 +1,6 @@
-use std::{collections::HashSet, net::Ipv4Addr, str::FromStr, sync::Arc, time::Duration};
+use std::{
+    collections::HashSet, net::Ipv4Addr, path::Path, str::FromStr, sync::Arc, time::Duration,
+};
 
 use anyhow::Context as _;
 use clap::Parser;
@@ -18,9 +20,13 @@ use zksync_core::{
         healthcheck::HealthCheckHandle,
         tree::{TreeApiClient, TreeApiHttpClient},
         tx_sender::{proxy::TxProxy, ApiContracts, TxSenderBuilder},
-        web3::{ApiBuilder, Namespace},
+        web3::{
+            archive::{ArchiveClient, ArchiveNodeClient},
+            ApiBuilder, Namespace, PubSubNotificationSource, WsConnections,
+        },
     },
     block_reverter::{BlockReverter, BlockReverterFlags, L1ExecutedBatchesRevert, NodeRole},
+    call_traces_backfiller::CallTracesBackfiller,
     commitment_generator::CommitmentGenerator,
     consensus,
     consistency_checker::ConsistencyChecker,
@@ -29,12 +35,15 @@ use zksync_core::{
         ValidiumModeL1BatchCommitDataGenerator,
     },
     l1_gas_price::MainNodeFeeParamsFetcher,
-    metadata_calculator::{MetadataCalculator, MetadataCalculatorConfig},
+    metadata_calculator::{MerkleTreeVerifier, MetadataCalculator, MetadataCalculatorConfig},
+    online_migrations::{
+        AddressTransactionsMigration, OnlineMigrationsRunner, TokenBalanceHoldersMigration,
+    },
     reorg_detector::{self, ReorgDetector},
     setup_sigint_handler,
     state_keeper::{
         seal_criteria::NoopSealer, AsyncRocksdbCache, BatchExecutor, MainBatchExecutor,
-        OutputHandler, StateKeeperPersistence, ZkSyncStateKeeper,
+        OutputHandler, RawBlocksPersistence, StateKeeperPersistence, ZkSyncStateKeeper,
     },
     sync_layer::{
         batch_status_updater::BatchStatusUpdater, external_io::ExternalIO, ActionQueue, SyncState,
@@ -47,22 +56,30 @@ use zksync_db_connection::{
 };
 use zksync_eth_client::clients::QueryClient;
 use zksync_health_check::{AppHealthCheck, HealthStatus, ReactiveHealthCheck};
-use zksync_state::PostgresStorageCaches;
+use zksync_object_store::ObjectStoreFactory;
+use zksync_state::{
+    BytecodeCache, CacheInvalidator, HistoricalState, HistoricalStateUpdater, PostgresStorageCaches,
+};
 use zksync_storage::RocksDB;
 use zksync_types::L2ChainId;
 use zksync_utils::wait_for_tasks::ManagedTasks;
 use zksync_web3_decl::{client::L2Client, namespaces::EnNamespaceClient};
 
 use crate::{
-    config::{observability::observability_config_from_env, ExternalNodeConfig},
+    config::{
+        observability::observability_config_from_env, read_snapshots_recovery_config,
+        ExternalNodeConfig,
+    },
     helpers::MainNodeHealthCheck,
     init::ensure_storage_initialized,
+    safe_mode::{safe_mode_health, StartupChecks},
 };
 
 mod config;
 mod helpers;
 mod init;
 mod metrics;
+mod safe_mode;
 mod version_sync_task;
 
 const RELEASE_MANIFEST: &str = include_str!("../../../../.github/release-please/manifest.json");
@@ -78,6 +95,7 @@ async fn build_state_keeper(
     output_handler: OutputHandler,
     stop_receiver: watch::Receiver<bool>,
     chain_id: L2ChainId,
+    bytecode_cache: BytecodeCache,
     task_handles: &mut Vec<task::JoinHandle<anyhow::Result<()>>>,
 ) -> anyhow::Result<ZkSyncStateKeeper> {
     // We only need call traces on the external node if the `debug_` namespace is enabled.
@@ -88,6 +106,7 @@ async fn build_state_keeper(
         state_keeper_db_path,
         config.optional.enum_index_migration_chunk_size,
     );
+    let storage_factory = storage_factory.with_bytecode_cache(bytecode_cache);
     let mut stop_receiver_clone = stop_receiver.clone();
     task_handles.push(tokio::task::spawn(async move {
         let result = task.run(stop_receiver_clone.clone()).await;
@@ -139,26 +158,67 @@ async fn run_tree(
             .merkle_tree_include_indices_and_filters_in_block_cache,
         memtable_capacity: config.optional.merkle_tree_memtable_capacity(),
         stalled_writes_timeout: config.optional.merkle_tree_stalled_writes_timeout(),
+        compaction_style: config.optional.merkle_tree_compaction_style(),
+        separate_leaves_column_family: config.optional.merkle_tree_separate_leaves_column_family(),
+        hashing_thread_count: config.optional.merkle_tree_hashing_thread_count,
+        pruning_retention: config.optional.merkle_tree_pruning_retention(),
+        lag_l1_batches: config.optional.merkle_tree_lag_l1_batches,
+        throttle_latency_threshold: config.optional.merkle_tree_throttle_latency_threshold(),
+        in_memory: false,
     };
-    let metadata_calculator = MetadataCalculator::new(metadata_calculator_config, None)
+    let mut metadata_calculator = MetadataCalculator::new(metadata_calculator_config, None)
         .await
         .context("failed initializing metadata calculator")?;
+    if config.optional.merkle_tree_recover_from_tree_snapshot {
+        let recovery_config = read_snapshots_recovery_config()?;
+        let tree_snapshot_store = ObjectStoreFactory::new(recovery_config.snapshots_object_store)
+            .create_store()
+            .await;
+        metadata_calculator = metadata_calculator.with_tree_snapshot_store(tree_snapshot_store);
+    }
     let tree_reader = Arc::new(metadata_calculator.tree_reader());
     app_health.insert_component(metadata_calculator.tree_health_check());
 
     if let Some(api_config) = api_config {
         let address = (Ipv4Addr::UNSPECIFIED, api_config.port).into();
+        let max_proof_keys = api_config.max_proof_keys;
         let tree_reader = metadata_calculator.tree_reader();
+        let pool = ConnectionPool::singleton(&config.postgres.database_url)
+            .build()
+            .await
+            .context("failed to build connection pool for Merkle tree API")?;
         let stop_receiver = stop_receiver.clone();
         task_futures.push(tokio::spawn(async move {
             tree_reader
                 .wait()
                 .await
-                .run_api_server(address, stop_receiver)
+                .run_api_server(address, pool, max_proof_keys, stop_receiver)
                 .await
         }));
     }
 
+    if let Some(interval) = config.optional.merkle_tree_consistency_check_interval() {
+        let eth_client_url = config
+            .required
+            .eth_client_url()
+            .context("L1 client URL is incorrect")?;
+        let eth_client = QueryClient::new(&eth_client_url).unwrap();
+        let pool = ConnectionPool::singleton(&config.postgres.database_url)
+            .build()
+            .await
+            .context("failed to build connection pool for Merkle tree verifier")?;
+        let verifier = MerkleTreeVerifier::new(
+            metadata_calculator.tree_reader(),
+            pool,
+            Arc::new(eth_client),
+            interval,
+        );
+        // The external node has no circuit breaker checker, so the node is stopped directly.
+        let circuit_breaker = verifier.circuit_breaker();
+        task_futures.push(task::spawn(circuit_breaker.run(stop_receiver.clone())));
+        task_futures.push(task::spawn(verifier.run(stop_receiver.clone())));
+    }
+
     let tree_handle = task::spawn(metadata_calculator.run(tree_pool, stop_receiver));
 
     task_futures.push(tree_handle);
@@ -175,6 +235,7 @@ async fn run_core(
     stop_receiver: watch::Receiver<bool>,
     fee_params_fetcher: Arc<MainNodeFeeParamsFetcher>,
     singleton_pool_builder: &ConnectionPoolBuilder<Core>,
+    bytecode_cache: BytecodeCache,
 ) -> anyhow::Result<SyncState> {
     // Create components.
     let sync_state = SyncState::default();
@@ -196,8 +257,17 @@ async fn run_core(
         persistence = persistence.without_protective_reads();
     }
 
-    let output_handler =
-        OutputHandler::new(Box::new(persistence)).with_handler(Box::new(sync_state.clone()));
+    let (raw_blocks, raw_blocks_persister) = RawBlocksPersistence::new(
+        connection_pool.clone(),
+        config.optional.miniblock_seal_queue_capacity,
+    );
+    task_handles.push(tokio::spawn(
+        raw_blocks_persister.run(stop_receiver.clone()),
+    ));
+
+    let output_handler = OutputHandler::new(Box::new(persistence))
+        .with_handler(Box::new(raw_blocks))
+        .with_handler(Box::new(sync_state.clone()));
     let state_keeper = build_state_keeper(
         action_queue,
         config.required.state_cache_path.clone(),
@@ -207,6 +277,7 @@ async fn run_core(
         output_handler,
         stop_receiver.clone(),
         config.remote.l2_chain_id,
+        bytecode_cache,
         task_handles,
     )
     .await?;
@@ -345,6 +416,32 @@ async fn run_core(
 
     let updater_handle = task::spawn(batch_status_updater.run(stop_receiver.clone()));
 
+    if config.optional.call_traces_backfiller_enabled {
+        let call_traces_backfiller_pool = singleton_pool_builder
+            .build()
+            .await
+            .context("failed to build a call_traces_backfiller_pool")?;
+        // Bytecode compression is optional for transactions executed on external nodes.
+        let call_traces_backfiller =
+            CallTracesBackfiller::new(call_traces_backfiller_pool, config.remote.l2_chain_id, true);
+        app_health.insert_component(call_traces_backfiller.health_check());
+        task_handles.push(tokio::spawn(
+            call_traces_backfiller.run(stop_receiver.clone()),
+        ));
+    }
+
+    let online_migrations_pool = singleton_pool_builder
+        .build()
+        .await
+        .context("failed to build an online_migrations_pool")?;
+    let online_migrations_runner = OnlineMigrationsRunner::new(online_migrations_pool)
+        .with_migration(TokenBalanceHoldersMigration)
+        .with_migration(AddressTransactionsMigration);
+    app_health.insert_component(online_migrations_runner.health_check());
+    task_handles.push(tokio::spawn(
+        online_migrations_runner.run(stop_receiver.clone()),
+    ));
+
     task_handles.extend([
         sk_handle,
         fee_address_migration_handle,
@@ -370,6 +467,8 @@ async fn run_api(
     singleton_pool_builder: ConnectionPoolBuilder<Core>,
     fee_params_fetcher: Arc<MainNodeFeeParamsFetcher>,
     components: &HashSet<Component>,
+    bytecode_cache: BytecodeCache,
+    cache_invalidator: CacheInvalidator,
 ) -> anyhow::Result<()> {
     let tree_reader = match tree_reader {
         Some(tree_reader) => {
@@ -390,11 +489,21 @@ async fn run_api(
             Arc::new(TreeApiHttpClient::new(tree_api_url))
         }
     };
+    let archive_client = match &config.api_component.archive_node_url {
+        Some(url) => {
+            let client = L2Client::http(url)
+                .context("Failed creating JSON-RPC client for archive node")?
+                .build();
+            Some(Arc::new(ArchiveNodeClient::new(client)) as Arc<dyn ArchiveClient>)
+        }
+        None => None,
+    };
 
     let (
         tx_sender,
         vm_barrier,
         cache_update_handle,
+        historical_state_update_handle,
         proxy_cache_updater_handle,
         whitelisted_tokens_update_handle,
     ) = {
@@ -412,7 +521,8 @@ async fn run_api(
             config.clone().into(),
             connection_pool.clone(),
             Arc::new(tx_proxy),
-        );
+        )
+        .with_cache_invalidator(cache_invalidator);
 
         if config.optional.transactions_per_sec_limit.is_some() {
             tracing::warn!("`transactions_per_sec_limit` option is deprecated and ignored");
@@ -420,21 +530,53 @@ async fn run_api(
 
         let max_concurrency = config.optional.vm_concurrency_limit;
         let (vm_concurrency_limiter, vm_barrier) = VmConcurrencyLimiter::new(max_concurrency);
-        let mut storage_caches = PostgresStorageCaches::new(
-            config.optional.factory_deps_cache_size() as u64,
-            config.optional.initial_writes_cache_size() as u64,
-        );
+        let vm_concurrency_limiter = vm_concurrency_limiter
+            .with_permit_timeout(config.optional.vm_permit_timeout())
+            .with_saturation_threshold(config.optional.vm_saturation_threshold);
+        app_health
+            .insert_custom_component(Arc::new(vm_concurrency_limiter.health_check("api_sandbox")));
+        // The factory deps cache is replaced with the process-wide bytecode cache, so it's created empty.
+        let mut storage_caches =
+            PostgresStorageCaches::new(0, config.optional.initial_writes_cache_size() as u64)
+                .with_bytecode_cache(bytecode_cache);
         let latest_values_cache_size = config.optional.latest_values_cache_size() as u64;
-        let cache_update_handle = (latest_values_cache_size > 0).then(|| {
-            task::spawn(
-                storage_caches
-                    .configure_storage_values_cache(
-                        latest_values_cache_size,
-                        connection_pool.clone(),
+        let values_cache_task = if latest_values_cache_size > 0 {
+            let mut values_cache_task = storage_caches
+                .configure_storage_values_cache(latest_values_cache_size, connection_pool.clone());
+            if let Some(path) = &config.optional.latest_values_cache_path {
+                values_cache_task = values_cache_task
+                    .with_persistent_values_cache(
+                        Path::new(path),
+                        config.optional.latest_values_cache_persistent_max_entries,
                     )
-                    .run(stop_receiver.clone()),
-            )
-        });
+                    .context("with_persistent_values_cache()")?;
+            }
+            Some(values_cache_task)
+        } else {
+            None
+        };
+        let historical_state_update_handle = if let Some(path) =
+            &config.optional.historical_state_path
+        {
+            let historical_state = HistoricalState::new(Path::new(path))
+                .context("failed initializing historical state")?;
+            let updater =
+                HistoricalStateUpdater::new(historical_state.clone(), connection_pool.clone())
+                    .with_retained_miniblocks(config.optional.historical_state_retained_miniblocks);
+            storage_caches = storage_caches.with_historical_state(historical_state);
+            Some(task::spawn(updater.run(stop_receiver.clone())))
+        } else {
+            None
+        };
+        if let Some(miniblock_count) = config.optional.caches_warm_up_miniblocks {
+            let mut connection = connection_pool.connection_tagged("api").await?;
+            storage_caches
+                .warm_up(&mut connection, miniblock_count)
+                .await
+                .context("failed warming up VM caches")?;
+        }
+        let cache_update_handle =
+            values_cache_task.map(|task| task::spawn(task.run(stop_receiver.clone())));
 
         let whitelisted_tokens_for_aa_cache = Arc::new(RwLock::new(Vec::new()));
         let whitelisted_tokens_for_aa_cache_clone = whitelisted_tokens_for_aa_cache.clone();
@@ -472,22 +614,35 @@ async fn run_api(
             tx_sender,
             vm_barrier,
             cache_update_handle,
+            historical_state_update_handle,
             proxy_cache_updater_handle,
             whitelisted_tokens_update_task,
         )
     };
 
+    // Shared between HTTP and WS servers, so that WS connections can be managed via the HTTP `admin` namespace.
+    let ws_connections = WsConnections::default();
     if components.contains(&Component::HttpApi) {
         let builder = ApiBuilder::jsonrpsee_backend(config.clone().into(), connection_pool.clone())
             .http(config.required.http_port)
             .with_filter_limit(config.optional.filters_limit)
             .with_batch_request_size_limit(config.optional.max_batch_request_size)
+            .with_batch_request_cost_limit(config.optional.max_batch_request_cost)
             .with_response_body_size_limit(config.optional.max_response_body_size())
             .with_tx_sender(tx_sender.clone())
             .with_vm_barrier(vm_barrier.clone())
             .with_tree_api(tree_reader.clone())
             .with_sync_state(sync_state.clone())
+            .with_ws_connections(ws_connections.clone())
             .enable_api_namespaces(config.optional.api_namespaces());
+        let builder = match &archive_client {
+            Some(archive_client) => builder.with_archive_client(archive_client.clone()),
+            None => builder,
+        };
+        let builder = match &config.optional.http_auth_token {
+            Some(auth_token) => builder.with_auth_token(auth_token.clone()),
+            None => builder,
+        };
 
         let http_server_handles = builder
             .build()
@@ -500,18 +655,43 @@ async fn run_api(
     }
 
     if components.contains(&Component::WsApi) {
+        // The node database has no replicas, so notifications are listened to on the main pool.
+        let pub_sub_notification_source = if config.optional.pubsub_db_notifications {
+            PubSubNotificationSource::db_notifications(connection_pool.clone()).await?
+        } else {
+            PubSubNotificationSource::Polling
+        };
         let builder = ApiBuilder::jsonrpsee_backend(config.clone().into(), connection_pool.clone())
             .ws(config.required.ws_port)
             .with_filter_limit(config.optional.filters_limit)
             .with_subscriptions_limit(config.optional.subscriptions_limit)
             .with_batch_request_size_limit(config.optional.max_batch_request_size)
+            .with_batch_request_cost_limit(config.optional.max_batch_request_cost)
             .with_response_body_size_limit(config.optional.max_response_body_size())
             .with_polling_interval(config.optional.polling_interval())
+            .with_pub_sub_notification_source(pub_sub_notification_source)
             .with_tx_sender(tx_sender)
             .with_vm_barrier(vm_barrier)
             .with_tree_api(tree_reader)
             .with_sync_state(sync_state)
+            .with_ws_connections(ws_connections)
             .enable_api_namespaces(config.optional.api_namespaces());
+        let builder = match archive_client {
+            Some(archive_client) => builder.with_archive_client(archive_client),
+            None => builder,
+        };
+        let builder = match config.optional.sse_port {
+            Some(sse_port) => builder.with_sse_port(sse_port),
+            None => builder,
+        };
+        let builder = match &config.optional.ws_auth_token {
+            Some(auth_token) => builder.with_auth_token(auth_token.clone()),
+            None => builder,
+        };
+        let builder = match &config.optional.ipc_path {
+            Some(ipc_path) => builder.with_ipc_path(ipc_path.into()),
+            None => builder,
+        };
 
         let ws_server_handles = builder
             .build()
@@ -524,12 +704,14 @@ async fn run_api(
     }
 
     task_futures.extend(cache_update_handle);
+    task_futures.extend(historical_state_update_handle);
     task_futures.push(proxy_cache_updater_handle);
     task_futures.push(whitelisted_tokens_update_handle);
 
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn init_tasks(
     config: &ExternalNodeConfig,
     connection_pool: ConnectionPool<Core>,
@@ -538,6 +720,7 @@ async fn init_tasks(
     app_health: &AppHealthCheck,
     stop_receiver: watch::Receiver<bool>,
     components: &HashSet<Component>,
+    cache_invalidator: CacheInvalidator,
 ) -> anyhow::Result<()> {
     let release_manifest: serde_json::Value = serde_json::from_str(RELEASE_MANIFEST)
         .context("releuse manifest is a valid json document")?;
@@ -594,6 +777,7 @@ async fn init_tasks(
                     .tree_component
                     .api_port
                     .context("should contain tree api port")?,
+                max_proof_keys: config.tree_component.api_max_proof_keys,
             })
         } else {
             None
@@ -614,6 +798,9 @@ async fn init_tasks(
     };
 
     let fee_params_fetcher = Arc::new(MainNodeFeeParamsFetcher::new(main_node_client.clone()));
+    // Bytecode cache shared between the state keeper and the API server.
+    let bytecode_cache = BytecodeCache::new(config.optional.factory_deps_cache_size() as u64);
+    cache_invalidator.register(Arc::new(bytecode_cache.clone()));
 
     let sync_state = if components.contains(&Component::Core) {
         run_core(
@@ -625,6 +812,7 @@ async fn init_tasks(
             stop_receiver.clone(),
             fee_params_fetcher.clone(),
             &singleton_pool_builder,
+            bytecode_cache.clone(),
         )
         .await?
     } else {
@@ -652,6 +840,8 @@ async fn init_tasks(
             singleton_pool_builder,
             fee_params_fetcher.clone(),
             components,
+            bytecode_cache,
+            cache_invalidator,
         )
         .await?;
     }
@@ -695,6 +885,11 @@ struct Cli {
     /// Revert the pending L1 batch and exit.
     #[arg(long)]
     revert_pending_l1_batch: bool,
+    /// Revert the node storage to the last consistent L1 batch if startup consistency checks detect storage corruption.
+    /// Without this flag, the node boots into the safe mode (only serving the health check endpoint with the corruption
+    /// report) if corruption is detected.
+    #[arg(long)]
+    revert_to_last_consistent_l1_batch: bool,
     /// Enables consensus-based syncing instead of JSON-RPC based one. This is an experimental and incomplete feature;
     /// do not use unless you know what you're doing.
     #[arg(long)]
@@ -795,9 +990,19 @@ async fn main() -> anyhow::Result<()> {
     if let Some(threshold) = config.optional.slow_query_threshold() {
         ConnectionPool::<Core>::global_config().set_slow_query_threshold(threshold)?;
     }
+    if let Some(rate) = config.optional.slow_query_explain_sample_rate() {
+        ConnectionPool::<Core>::global_config().set_slow_query_explain_sample_rate(rate)?;
+    }
     if let Some(threshold) = config.optional.long_connection_threshold() {
         ConnectionPool::<Core>::global_config().set_long_connection_threshold(threshold)?;
     }
+    if let Some(timeout) = config.optional.query_timeout() {
+        ConnectionPool::<Core>::global_config().set_query_timeout(timeout)?;
+    }
+    if let Some(max_retries) = config.optional.query_max_retries() {
+        ConnectionPool::<Core>::global_config().set_query_max_retries(max_retries);
+    }
+    ConnectionPool::<Core>::global_config().set_use_binary_copy(config.optional.use_binary_copy());
 
     let connection_pool = ConnectionPool::<Core>::builder(
         &config.postgres.database_url,
@@ -878,6 +1083,10 @@ async fn main() -> anyhow::Result<()> {
     .await?;
     let sigint_receiver = setup_sigint_handler();
 
+    // Caches created by the node components are registered in the invalidator, so that they are purged
+    // after a revert or a reorg. Reverts performed below are applied to caches once they are registered
+    // by `init_tasks()`, since some caches (e.g., the historical state) persist across node restarts.
+    let cache_invalidator = CacheInvalidator::default();
     // Revert the storage if needed.
     let reverter = BlockReverter::new(
         NodeRole::External,
@@ -886,7 +1095,8 @@ async fn main() -> anyhow::Result<()> {
         None,
         connection_pool.clone(),
         L1ExecutedBatchesRevert::Allowed,
-    );
+    )
+    .with_cache_invalidator(cache_invalidator.clone());
 
     let mut reorg_detector = ReorgDetector::new(main_node_client.clone(), connection_pool.clone());
     // We're checking for the reorg in the beginning because we expect that if reorg is detected during
@@ -923,6 +1133,48 @@ async fn main() -> anyhow::Result<()> {
         tracing::info!("Rollback successfully completed");
     }
 
+    let mut components = opt.components.0;
+    let startup_checks =
+        StartupChecks::new(connection_pool.clone(), &config.required.merkle_tree_path);
+    let corruption_report = startup_checks
+        .run()
+        .await
+        .context("startup consistency checks")?;
+    match corruption_report {
+        None => {}
+        Some(report) if opt.revert_to_last_consistent_l1_batch => {
+            tracing::warn!("Detected storage corruption: {report:?}");
+            let last_consistent_l1_batch = report.last_consistent_l1_batch.context(
+                "Cannot revert to the last consistent L1 batch since it cannot be determined",
+            )?;
+            tracing::info!("Rolling back to l1 batch number {last_consistent_l1_batch}");
+            reverter
+                .rollback_db(last_consistent_l1_batch, BlockReverterFlags::all())
+                .await;
+            tracing::info!("Rollback successfully completed");
+        }
+        Some(report) => {
+            tracing::error!(
+                "Detected storage corruption: {report:?}. Starting the node in the safe mode; \
+                 restart the node with `--revert-to-last-consistent-l1-batch` to resume normal operation"
+            );
+            // All components read the corrupted storage (e.g., API servers would serve the corrupted state),
+            // so none of them are started.
+            components.clear();
+            let (health_check, health_updater) = safe_mode_health(&report);
+            app_health.insert_component(health_check);
+            EN_METRICS.safe_mode.set(1);
+
+            let mut stop_receiver = stop_receiver.clone();
+            task_handles.push(tokio::spawn(async move {
+                // The health updater must be alive while the node is running.
+                stop_receiver.changed().await.ok();
+                drop(health_updater);
+                Ok(())
+            }));
+        }
+    }
+
     init_tasks(
         &config,
         connection_pool.clone(),
@@ -930,7 +1182,8 @@ async fn main() -> anyhow::Result<()> {
         &mut task_handles,
         &app_health,
         stop_receiver.clone(),
-        &opt.components.0,
+        &components,
+        cache_invalidator,
     )
     .await
     .context("init_tasks")?;
@@ -5,6 +5,8 @@ use vise::{Gauge, LabeledFamily, Metrics};
 pub(crate) struct EnMetrics {
     #[metrics(labels = ["server_version", "protocol_version"])]
     pub version: LabeledFamily<(String, Option<u16>), Gauge<u64>, 2>,
+    /// Set to 1 if the node runs in the safe mode after detecting storage corruption on startup.
+    pub safe_mode: Gauge<u64>,
 }
 
 #[vise::register]
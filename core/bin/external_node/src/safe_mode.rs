@@ -0,0 +1,257 @@
+//! Startup storage consistency checks and the safe mode the node boots into if they fail.
+//!
+//! If the node storage is corrupted (e.g., after a botched DB restore or a disk failure), restarting the node
+//! doesn't help; it would crash-loop on the first component touching the corrupted data. Instead, the node
+//! runs a couple of cheap consistency checks on startup, and if any of them fails, it doesn't start any components
+//! and only reports the detected corruption via the health check endpoint. In particular, JSON-RPC API servers
+//! are not started, since they would serve the corrupted state. To resume normal operation, the operator must explicitly restart the node with
+//! `--revert-to-last-consistent-l1-batch`, which rolls back the storage to the last L1 batch known to be consistent.
+
+use std::path::PathBuf;
+
+use anyhow::Context as _;
+use serde::Serialize;
+use zksync_dal::{Connection, ConnectionPool, Core, CoreDal};
+use zksync_health_check::{Health, HealthStatus, HealthUpdater, ReactiveHealthCheck};
+use zksync_merkle_tree::domain::{ZkSyncTree, ZkSyncTreeReader};
+use zksync_storage::RocksDB;
+use zksync_types::{
+    block::MiniblockHasher, L1BatchNumber, MiniblockNumber, ProtocolVersionId, H256,
+};
+
+/// Number of latest miniblocks for which the hash chain is verified.
+const MINIBLOCKS_TO_CHECK: u32 = 100;
+/// Maximum number of L1 batches traversed when searching for the last L1 batch with the matching tree root hash.
+const MAX_L1_BATCHES_TO_TRAVERSE: u32 = 1_000;
+
+/// Storage corruption detected by [`StartupChecks`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub(crate) enum Corruption {
+    /// Root hash of the Merkle tree doesn't match the state root hash of the corresponding L1 batch in Postgres.
+    TreeRootMismatch {
+        l1_batch_number: L1BatchNumber,
+        tree_root_hash: H256,
+        postgres_root_hash: H256,
+    },
+    /// Miniblock hash stored in Postgres doesn't match the hash computed from the miniblock data
+    /// and the hash of the previous miniblock.
+    MiniblockHashMismatch {
+        miniblock_number: MiniblockNumber,
+        stored_hash: H256,
+        computed_hash: H256,
+    },
+}
+
+/// Report on the storage corruption exposed via the health check endpoint.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct CorruptionReport {
+    #[serde(flatten)]
+    pub corruption: Corruption,
+    /// Last L1 batch for which the storage is known to be consistent, or `None` if it cannot be determined.
+    pub last_consistent_l1_batch: Option<L1BatchNumber>,
+}
+
+/// Storage consistency checks performed on node startup.
+#[derive(Debug)]
+pub(crate) struct StartupChecks {
+    pool: ConnectionPool<Core>,
+    merkle_tree_path: PathBuf,
+}
+
+impl StartupChecks {
+    pub fn new(pool: ConnectionPool<Core>, merkle_tree_path: impl Into<PathBuf>) -> Self {
+        Self {
+            pool,
+            merkle_tree_path: merkle_tree_path.into(),
+        }
+    }
+
+    /// Runs all checks. Returns the first detected corruption, if any.
+    pub async fn run(&self) -> anyhow::Result<Option<CorruptionReport>> {
+        let mut storage = self.pool.connection_tagged("en").await?;
+        if let Some(report) = Self::check_miniblock_hash_chain(&mut storage).await? {
+            return Ok(Some(report));
+        }
+        self.check_tree_root_hash(&mut storage).await
+    }
+
+    /// Checks that the hashes of the latest miniblocks form a valid hash chain.
+    async fn check_miniblock_hash_chain(
+        storage: &mut Connection<'_, Core>,
+    ) -> anyhow::Result<Option<CorruptionReport>> {
+        let Some(sealed_miniblock) = storage.blocks_dal().get_sealed_miniblock_number().await?
+        else {
+            return Ok(None);
+        };
+        let snapshot_recovery = storage
+            .snapshot_recovery_dal()
+            .get_applied_snapshot_status()
+            .await?;
+        // The genesis miniblock has no previous miniblock, so its hash cannot be verified.
+        let earliest_checked_miniblock = snapshot_recovery
+            .as_ref()
+            .map_or(MiniblockNumber(1), |recovery| recovery.miniblock_number + 1);
+        let first_miniblock = sealed_miniblock
+            .0
+            .saturating_sub(MINIBLOCKS_TO_CHECK - 1)
+            .max(earliest_checked_miniblock.0);
+        if first_miniblock > sealed_miniblock.0 {
+            return Ok(None);
+        }
+
+        let prev_miniblock = MiniblockNumber(first_miniblock - 1);
+        let mut prev_hash = match &snapshot_recovery {
+            Some(recovery) if recovery.miniblock_number == prev_miniblock => {
+                recovery.miniblock_hash
+            }
+            _ => storage
+                .blocks_web3_dal()
+                .get_miniblock_hash(prev_miniblock)
+                .await?
+                .with_context(|| format!("miniblock #{prev_miniblock} is missing in Postgres"))?,
+        };
+        for number in first_miniblock..=sealed_miniblock.0 {
+            let number = MiniblockNumber(number);
+            let header = storage
+                .blocks_dal()
+                .get_miniblock_header(number)
+                .await?
+                .with_context(|| format!("miniblock #{number} is missing in Postgres"))?;
+            let tx_hashes = storage
+                .transactions_web3_dal()
+                .get_miniblock_tx_hashes(number)
+                .await?;
+
+            let mut hasher = MiniblockHasher::new(number, header.timestamp, prev_hash);
+            for tx_hash in tx_hashes {
+                hasher.push_tx_hash(tx_hash);
+            }
+            let protocol_version = header
+                .protocol_version
+                .unwrap_or_else(ProtocolVersionId::last_potentially_undefined);
+            let computed_hash = hasher.finalize(protocol_version);
+            if computed_hash != header.hash {
+                let last_consistent_l1_batch =
+                    Self::last_l1_batch_before_miniblock(storage, number).await?;
+                return Ok(Some(CorruptionReport {
+                    corruption: Corruption::MiniblockHashMismatch {
+                        miniblock_number: number,
+                        stored_hash: header.hash,
+                        computed_hash,
+                    },
+                    last_consistent_l1_batch,
+                }));
+            }
+            prev_hash = header.hash;
+        }
+        Ok(None)
+    }
+
+    /// Returns the last L1 batch that doesn't include the specified miniblock.
+    async fn last_l1_batch_before_miniblock(
+        storage: &mut Connection<'_, Core>,
+        miniblock_number: MiniblockNumber,
+    ) -> anyhow::Result<Option<L1BatchNumber>> {
+        let l1_batch_number = storage
+            .blocks_web3_dal()
+            .get_l1_batch_number_of_miniblock(miniblock_number)
+            .await?;
+        Ok(match l1_batch_number {
+            Some(number) => number.0.checked_sub(1).map(L1BatchNumber),
+            // The miniblock is pending, so all sealed L1 batches are consistent.
+            None => storage.blocks_dal().get_sealed_l1_batch_number().await?,
+        })
+    }
+
+    /// Checks that the root hash of the Merkle tree matches the state root hash in Postgres.
+    async fn check_tree_root_hash(
+        &self,
+        storage: &mut Connection<'_, Core>,
+    ) -> anyhow::Result<Option<CorruptionReport>> {
+        if !self.merkle_tree_path.exists() {
+            tracing::info!("Merkle tree not found; skipping tree root hash check");
+            return Ok(None);
+        }
+        let Some(last_l1_batch_with_metadata) = storage
+            .blocks_dal()
+            .get_last_l1_batch_number_with_metadata()
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let merkle_tree_path = self.merkle_tree_path.clone();
+        let tree_reader = tokio::task::spawn_blocking(move || {
+            let db = RocksDB::new(&merkle_tree_path)
+                .context("failed initializing RocksDB for Merkle tree")?;
+            anyhow::Ok(ZkSyncTree::new_lightweight(db.into()).reader())
+        })
+        .await
+        .context("panicked opening Merkle tree")??;
+
+        let Some(last_tree_l1_batch) = tree_reader.next_l1_batch_number().0.checked_sub(1) else {
+            return Ok(None); // The tree is empty
+        };
+        // The tree may be ahead of Postgres (which is fixed by the metadata calculator), or behind it
+        // (if the tree is catching up), so we compare the latest L1 batch present in both.
+        let l1_batch_number = L1BatchNumber(last_tree_l1_batch).min(last_l1_batch_with_metadata);
+        let postgres_root_hash = storage
+            .blocks_dal()
+            .get_l1_batch_state_root(l1_batch_number)
+            .await?
+            .with_context(|| format!("no root hash for L1 batch #{l1_batch_number} in Postgres"))?;
+        let tree_root_hash = tree_reader
+            .root_hash_for_l1_batch(l1_batch_number)
+            .with_context(|| format!("L1 batch #{l1_batch_number} is missing in Merkle tree"))?;
+        if tree_root_hash == postgres_root_hash {
+            return Ok(None);
+        }
+
+        let last_consistent_l1_batch =
+            Self::last_l1_batch_with_matching_root_hash(storage, &tree_reader, l1_batch_number)
+                .await?;
+        Ok(Some(CorruptionReport {
+            corruption: Corruption::TreeRootMismatch {
+                l1_batch_number,
+                tree_root_hash,
+                postgres_root_hash,
+            },
+            last_consistent_l1_batch,
+        }))
+    }
+
+    async fn last_l1_batch_with_matching_root_hash(
+        storage: &mut Connection<'_, Core>,
+        tree_reader: &ZkSyncTreeReader,
+        mismatched_l1_batch: L1BatchNumber,
+    ) -> anyhow::Result<Option<L1BatchNumber>> {
+        let earliest_l1_batch = mismatched_l1_batch
+            .0
+            .saturating_sub(MAX_L1_BATCHES_TO_TRAVERSE);
+        for number in (earliest_l1_batch..mismatched_l1_batch.0).rev() {
+            let number = L1BatchNumber(number);
+            let Some(postgres_root_hash) =
+                storage.blocks_dal().get_l1_batch_state_root(number).await?
+            else {
+                break; // We've reached the start of the node storage (e.g., the snapshot L1 batch)
+            };
+            if tree_reader.root_hash_for_l1_batch(number) == Some(postgres_root_hash) {
+                return Ok(Some(number));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Health check for the node running in the safe mode. The node is reported as affected rather than failing,
+/// so that it isn't restarted by an orchestrator (which wouldn't help) while exposing the corruption report.
+pub(crate) fn safe_mode_health(report: &CorruptionReport) -> (ReactiveHealthCheck, HealthUpdater) {
+    let (health_check, health_updater) = ReactiveHealthCheck::new("safe_mode");
+    let details = serde_json::json!({
+        "corruption": report,
+        "resolution": "restart the node with `--revert-to-last-consistent-l1-batch` to resume normal operation",
+    });
+    health_updater.update(Health::from(HealthStatus::Affected).with_details(details));
+    (health_check, health_updater)
+}
@@ -1,4 +1,4 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, rc::Rc, sync::Arc};
 
 use multivm::{
     interface::{
@@ -213,10 +213,10 @@ pub(super) fn execute_internal_transfer_test() -> u32 {
         hash,
     };
 
-    let base_system_smart_contracts = BaseSystemContracts {
+    let base_system_smart_contracts = Arc::new(BaseSystemContracts {
         bootloader,
         default_aa,
-    };
+    });
 
     let system_env = SystemEnv {
         zk_porter_available: ZKPORTER_IS_AVAILABLE,
@@ -304,7 +304,7 @@ pub(super) fn execute_user_txs_in_test_gas_vm(
     let system_env = SystemEnv {
         zk_porter_available: ZKPORTER_IS_AVAILABLE,
         version: ProtocolVersionId::latest(),
-        base_system_smart_contracts: GAS_TEST_SYSTEM_CONTRACTS.clone(),
+        base_system_smart_contracts: Arc::new(GAS_TEST_SYSTEM_CONTRACTS.clone()),
         bootloader_gas_limit: BATCH_COMPUTATIONAL_GAS_LIMIT,
         execution_mode: TxExecutionMode::VerifyExecute,
         default_validation_computational_gas_limit: BATCH_COMPUTATIONAL_GAS_LIMIT,
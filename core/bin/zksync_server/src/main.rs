@@ -11,7 +11,8 @@ use zksync_config::{
         },
         fri_prover_group::FriProverGroupConfig,
         house_keeper::HouseKeeperConfig,
-        ContractsConfig, FriProofCompressorConfig, FriProverConfig, FriProverGatewayConfig,
+        BatchExporterConfig, CallTracesOffloaderConfig, ChangeStreamConfig, ContractsConfig,
+        FriProofCompressorConfig, FriProverConfig, FriProverGatewayConfig,
         FriWitnessGeneratorConfig, FriWitnessVectorGeneratorConfig, ObservabilityConfig,
         PrometheusConfig, ProofDataHandlerConfig, WitnessGeneratorConfig,
     },
@@ -20,6 +21,7 @@ use zksync_config::{
 };
 use zksync_core::{
     genesis, genesis_init, initialize_components, is_genesis_needed, setup_sigint_handler,
+    state_keeper::isolated_batch_executor,
     temp_config_store::{decode_yaml, decode_yaml_repr, Secrets, TempConfigStore},
     Component, Components,
 };
@@ -85,6 +87,11 @@ impl FromStr for ComponentsToRun {
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    // Must be checked before parsing args and initializing logging: a worker uses stdout for IPC.
+    if isolated_batch_executor::is_worker_process() {
+        isolated_batch_executor::run_worker_process();
+    }
+
     let opt = Cli::parse();
     let sigint_receiver = setup_sigint_handler();
 
@@ -176,7 +183,17 @@ async fn main() -> anyhow::Result<()> {
 
     let postgres_config = configs.postgres_config.clone().context("PostgresConfig")?;
 
-    if opt.genesis || is_genesis_needed(&postgres_config).await {
+    let is_read_only = configs
+        .api_config
+        .as_ref()
+        .is_some_and(|config| config.web3_json_rpc.read_only_main_node_url().is_some());
+    if is_read_only {
+        // Postgres is populated by another node, so the read-only API server must not initialize it.
+        anyhow::ensure!(
+            !opt.genesis && !opt.set_chain_id,
+            "`--genesis` and `--set-chain-id` cannot be used in the read-only API mode"
+        );
+    } else if opt.genesis || is_genesis_needed(&postgres_config).await {
         genesis_init(genesis.clone(), &postgres_config)
             .await
             .context("genesis_init")?;
@@ -278,5 +295,8 @@ fn load_env_config() -> anyhow::Result<TempConfigStore> {
         object_store_config: ObjectStoreConfig::from_env().ok(),
         observability: ObservabilityConfig::from_env().ok(),
         snapshot_creator: SnapshotsCreatorConfig::from_env().ok(),
+        batch_exporter: BatchExporterConfig::from_env().ok(),
+        call_traces_offloader: CallTracesOffloaderConfig::from_env().ok(),
+        change_stream: ChangeStreamConfig::from_env().ok(),
     })
 }
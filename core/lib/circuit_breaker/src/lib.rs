@@ -35,6 +35,8 @@ pub enum CircuitBreakerError {
     FailedL1Transaction,
     #[error("Replication lag ({0}) is above the threshold ({1})")]
     ReplicationLag(u32, u32),
+    #[error("Merkle tree is inconsistent: {0}")]
+    MerkleTreeInconsistency(String),
     #[error("Internal error running circuit breaker checks")]
     Internal(#[from] anyhow::Error),
 }
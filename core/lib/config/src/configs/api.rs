@@ -46,6 +46,12 @@ pub struct Web3JsonRpcConfig {
     pub subscriptions_limit: Option<u32>,
     /// Interval between polling db for pubsub (in ms).
     pub pubsub_polling_interval: Option<u64>,
+    /// Whether to wake up pubsub notifiers using Postgres notifications (`LISTEN` / `NOTIFY`)
+    /// instead of polling the database. If enabled, `pubsub_polling_interval` is used to rate-limit DB queries.
+    /// The triggers sending notifications are enabled by migrations; if an operator has disabled them,
+    /// notifiers fall back to polling.
+    #[serde(default)]
+    pub pubsub_db_notifications: bool,
     /// Tx nonce: how far ahead from the committed nonce can it be.
     pub max_nonce_ahead: u32,
     /// The multiplier to use when suggesting gas price. Should be higher than one,
@@ -59,6 +65,11 @@ pub struct Web3JsonRpcConfig {
     pub estimate_gas_scale_factor: f64,
     /// The max possible number of gas that `eth_estimateGas` is allowed to overestimate.
     pub estimate_gas_acceptable_overestimation: u32,
+    /// Whether to optimize the binary search performed by `eth_estimateGas`. If enabled, the search is seeded
+    /// with the gas used by the transaction in the initial run, and is skipped for simple transfers
+    /// if possible.
+    #[serde(default)]
+    pub estimate_gas_optimize_search: bool,
     /// Whether to use the compatibility mode for gas estimation for L1->L2 transactions.
     /// During the migration to the 1.4.1 fee model, there will be a period, when the server
     /// will already have the 1.4.1 fee model, while the L1 contracts will still expect the transactions
@@ -72,10 +83,26 @@ pub struct Web3JsonRpcConfig {
     /// Max number of cache misses during one VM execution. If the number of cache misses exceeds this value, the API server panics.
     /// This is a temporary solution to mitigate API request resulting in thousands of DB queries.
     pub vm_execution_cache_misses_limit: Option<usize>,
+    /// Max wall-clock duration of a single VM execution for `eth_call` and `debug_traceCall` (in ms).
+    /// If exceeded, the execution is aborted. If not set, VM executions are only limited by gas.
+    pub vm_execution_timeout_ms: Option<u64>,
+    /// Max number of `eth_call` results on historical blocks to cache. If not set or set to 0,
+    /// `eth_call` results will not be cached.
+    pub eth_call_cache_size: Option<usize>,
+    /// Max number of `eth_estimateGas` results to cache. If not set or set to 0, estimation results will not be cached.
+    pub estimate_gas_cache_size: Option<usize>,
+    /// Time-to-live for cached `eth_estimateGas` results (in ms). The default value is 2,000 ms.
+    pub estimate_gas_cache_ttl_ms: Option<u64>,
     /// Max number of VM instances to be concurrently spawned by the API server.
     /// This option can be tweaked down if the API server is running out of memory.
     /// If not set, the VM concurrency limit will be efficiently disabled.
     pub vm_concurrency_limit: Option<usize>,
+    /// Timeout (in ms) for waiting for a VM permit. Requests that cannot obtain a permit in time are rejected
+    /// as the server is overloaded. If not set, requests wait for a permit indefinitely.
+    pub vm_permit_timeout_ms: Option<u64>,
+    /// Share of VM permits in use (from 0 to 1) starting from which the API server VM sandbox is reported
+    /// as degraded by the health check. The default value is 0.9.
+    pub vm_saturation_threshold: Option<f64>,
     /// Smart contract cache size in MiBs. The default value is 128 MiB.
     pub factory_deps_cache_size_mb: Option<usize>,
     /// Initial writes cache size in MiBs. The default value is 32 MiB.
@@ -83,10 +110,55 @@ pub struct Web3JsonRpcConfig {
     /// Latest values cache size in MiBs. The default value is 128 MiB. If set to 0, the latest
     /// values cache will be disabled.
     pub latest_values_cache_size_mb: Option<usize>,
+    /// Path to RocksDB used as a persistent tier of the latest values cache. The persistent tier retains cached values
+    /// across restarts and is not limited by the in-memory cache size. If not set, only the in-memory cache is used.
+    pub latest_values_cache_path: Option<String>,
+    /// Maximum number of entries in the persistent tier of the latest values cache. Once exceeded, the persistent tier
+    /// is emptied. The default value is 50,000,000 entries (~3.5 GB of raw data).
+    pub latest_values_cache_persistent_max_entries: Option<u64>,
+    /// Number of latest miniblocks storage logs of which are used to warm up VM caches (storage values and factory deps)
+    /// on API server startup, before the server starts accepting requests. If not set, caches are not warmed up.
+    pub caches_warm_up_miniblocks: Option<u32>,
+    /// Path to RocksDB with the local historical VM state. If set, the state is kept up to date with Postgres
+    /// and is used to serve storage reads for past miniblocks (e.g., in `eth_call` and `eth_getStorageAt`),
+    /// falling back to Postgres only for miniblocks not covered by the state.
+    pub historical_state_path: Option<String>,
+    /// Number of latest miniblocks retained by the historical state. Older miniblocks are pruned from the state;
+    /// reads for them fall back to Postgres. If not set, the state retains all miniblocks with storage logs in Postgres.
+    pub historical_state_retained_miniblocks: Option<u32>,
     /// Limit for fee history block range.
     pub fee_history_limit: Option<u64>,
+    /// Maximum number of L1 batches processed by a single `unstable_simulateFeeModel` request. Default is 100.
+    pub fee_model_simulation_limit: Option<u64>,
     /// Maximum number of requests in a single batch JSON RPC request. Default is 500.
     pub max_batch_request_size: Option<usize>,
+    /// Maximum total cost of calls in a single batch JSON RPC request. Calls are weighted by the called method
+    /// (e.g., `debug_trace*` methods cost 50 units, and `eth_chainId` costs 1 unit). Default is 2000.
+    pub max_batch_request_cost: Option<u32>,
+    /// Maximum number of transactions per account with nonces exceeding `max_nonce_ahead` queued by the API server
+    /// until they can be added to the mempool. If set to 0 (the default), such transactions are rejected.
+    pub max_queued_txs_per_account: Option<u32>,
+    /// Minimum fee bump (in percent) required to replace a pending transaction with the same initiator and nonce.
+    /// Both the max fee per gas and the max priority fee per gas must be bumped. Default is 10%.
+    pub tx_replacement_fee_bump_percent: Option<u32>,
+    /// URL of the main node JSON-RPC API. If set, the API server runs in the read-only mode against Postgres
+    /// populated by another node: submitted transactions are proxied to the main node, fee parameters are fetched
+    /// from it, and nothing is written to Postgres. In this mode, only HTTP and WS API components can be run,
+    /// and the server only connects to the replica URL; pub-sub subscriptions poll the replica even if
+    /// `pubsub_db_notifications` is set.
+    ///
+    /// Data is served up to the last miniblock replicated to Postgres. Since miniblocks are persisted atomically,
+    /// each server provides a consistent view of the chain, but consistency watermarks *across* servers are
+    /// not maintained: different read-only servers (and the main node) may be at different miniblocks.
+    /// Load balancers should use sticky sessions if clients require monotonic reads.
+    pub read_only_main_node_url: Option<String>,
+    /// Maximum number of transactions per minute that can be submitted from a single sender address.
+    /// If not set, submissions are not rate-limited per sender.
+    pub max_tx_submissions_per_minute_per_sender: Option<NonZeroU32>,
+    /// Maximum number of pending (i.e., not yet included into a miniblock) transactions from a single sender
+    /// address. Transactions replacing pending ones are not subject to this limit. If not set, the number
+    /// of pending transactions is not limited.
+    pub max_pending_txs_per_sender: Option<u32>,
     /// Maximum response body size in MiBs. Default is 10 MiB.
     pub max_response_body_size_mb: Option<usize>,
     /// Maximum number of requests per minute for the WebSocket server.
@@ -104,6 +176,35 @@ pub struct Web3JsonRpcConfig {
     /// (additionally to natively bridged tokens).
     #[serde(default)]
     pub whitelisted_tokens_for_aa: Vec<Address>,
+    /// Storage slots (as indices in the contract storage) trusted during AA validation for each bridged
+    /// or white-listed token, in addition to the standard ERC-20 slots.
+    #[serde(default)]
+    pub aa_trusted_token_slots: Vec<H256>,
+    /// Addresses of contracts that can be called during AA validation with unrestricted access to their storage.
+    #[serde(default)]
+    pub aa_trusted_addresses: Vec<Address>,
+    /// Whether to enable the `admin` namespace on the HTTP API server. The namespace allows to change
+    /// AA validation rules and white-listed tokens at runtime, so it must not be exposed publicly.
+    #[serde(default)]
+    pub admin_namespace_enabled: bool,
+    /// Whether to enable the `unstable` namespace on the HTTP API server. The namespace contains operator-facing
+    /// methods (e.g., priority operation history and fee model simulation), which may be expensive to serve,
+    /// so it must not be exposed publicly.
+    #[serde(default)]
+    pub unstable_namespace_enabled: bool,
+    /// Port for the server-sent events (SSE) endpoint streaming new block headers and logs. The endpoint is started
+    /// alongside the WebSocket server and is disabled if not set.
+    pub sse_port: Option<u16>,
+    /// Secret token required to call methods in privileged namespaces (`debug`, `en`, `admin` and `unstable`)
+    /// on the HTTP server. If not set, calls to these namespaces are not authenticated.
+    pub http_auth_token: Option<String>,
+    /// Secret token required to connect to the WebSocket server if it has privileged namespaces enabled.
+    /// If not set, connections are not authenticated.
+    pub ws_auth_token: Option<String>,
+    /// Path to the Unix domain socket serving the JSON-RPC API (akin to Geth's `--ipcpath`). The IPC endpoint is started
+    /// alongside the WebSocket server, serves the same namespaces except for privileged ones (`debug`, `en`, `admin`
+    /// and `unstable`), and is disabled if not set.
+    pub ipc_path: Option<String>,
 }
 
 impl Web3JsonRpcConfig {
@@ -121,27 +222,55 @@ impl Web3JsonRpcConfig {
             filters_limit: Some(10000),
             subscriptions_limit: Some(10000),
             pubsub_polling_interval: Some(200),
+            pubsub_db_notifications: false,
             max_nonce_ahead: 50,
             gas_price_scale_factor: 1.2,
             request_timeout: Default::default(),
             account_pks: Default::default(),
             estimate_gas_scale_factor: 1.2,
             estimate_gas_acceptable_overestimation: 1000,
+            estimate_gas_optimize_search: false,
             l1_to_l2_transactions_compatibility_mode: true,
             max_tx_size: 1000000,
             vm_execution_cache_misses_limit: Default::default(),
+            vm_execution_timeout_ms: Default::default(),
+            eth_call_cache_size: Default::default(),
+            estimate_gas_cache_size: Default::default(),
+            estimate_gas_cache_ttl_ms: Default::default(),
             vm_concurrency_limit: Default::default(),
+            vm_permit_timeout_ms: None,
+            vm_saturation_threshold: None,
             factory_deps_cache_size_mb: Default::default(),
             initial_writes_cache_size_mb: Default::default(),
             latest_values_cache_size_mb: Default::default(),
+            latest_values_cache_path: None,
+            latest_values_cache_persistent_max_entries: None,
+            caches_warm_up_miniblocks: None,
+            historical_state_path: None,
+            historical_state_retained_miniblocks: None,
             fee_history_limit: Default::default(),
+            fee_model_simulation_limit: None,
             max_batch_request_size: Default::default(),
+            max_batch_request_cost: Default::default(),
+            max_queued_txs_per_account: Default::default(),
+            tx_replacement_fee_bump_percent: Default::default(),
+            read_only_main_node_url: None,
+            max_tx_submissions_per_minute_per_sender: None,
+            max_pending_txs_per_sender: None,
             max_response_body_size_mb: Default::default(),
             websocket_requests_per_minute_limit: Default::default(),
             mempool_cache_update_interval: Default::default(),
             mempool_cache_size: Default::default(),
             tree_api_url: None,
             whitelisted_tokens_for_aa: Default::default(),
+            aa_trusted_token_slots: Default::default(),
+            aa_trusted_addresses: Default::default(),
+            admin_namespace_enabled: false,
+            unstable_namespace_enabled: false,
+            sse_port: None,
+            http_auth_token: None,
+            ws_auth_token: None,
+            ipc_path: None,
         }
     }
 
@@ -169,6 +298,14 @@ impl Web3JsonRpcConfig {
         Duration::from_millis(self.pubsub_polling_interval.unwrap_or(200))
     }
 
+    pub fn vm_execution_timeout(&self) -> Option<Duration> {
+        self.vm_execution_timeout_ms.map(Duration::from_millis)
+    }
+
+    pub fn estimate_gas_cache_ttl(&self) -> Duration {
+        Duration::from_millis(self.estimate_gas_cache_ttl_ms.unwrap_or(2_000))
+    }
+
     pub fn request_timeout(&self) -> Duration {
         Duration::from_secs(self.request_timeout.unwrap_or(10))
     }
@@ -184,6 +321,14 @@ impl Web3JsonRpcConfig {
         self.vm_concurrency_limit.unwrap_or(2_048)
     }
 
+    pub fn vm_permit_timeout(&self) -> Option<Duration> {
+        self.vm_permit_timeout_ms.map(Duration::from_millis)
+    }
+
+    pub fn vm_saturation_threshold(&self) -> f64 {
+        self.vm_saturation_threshold.unwrap_or(0.9)
+    }
+
     /// Returns the size of factory dependencies cache in bytes.
     pub fn factory_deps_cache_size(&self) -> usize {
         self.factory_deps_cache_size_mb.unwrap_or(128) * super::BYTES_IN_MEGABYTE
@@ -199,15 +344,38 @@ impl Web3JsonRpcConfig {
         self.latest_values_cache_size_mb.unwrap_or(128) * super::BYTES_IN_MEGABYTE
     }
 
+    /// Returns the maximum number of entries in the persistent tier of latest values cache.
+    pub fn latest_values_cache_persistent_max_entries(&self) -> u64 {
+        self.latest_values_cache_persistent_max_entries
+            .unwrap_or(50_000_000)
+    }
+
     pub fn fee_history_limit(&self) -> u64 {
         self.fee_history_limit.unwrap_or(1024)
     }
 
+    pub fn fee_model_simulation_limit(&self) -> u64 {
+        self.fee_model_simulation_limit.unwrap_or(100)
+    }
+
     pub fn max_batch_request_size(&self) -> usize {
         // The default limit is chosen to be reasonably permissive.
         self.max_batch_request_size.unwrap_or(500)
     }
 
+    pub fn max_batch_request_cost(&self) -> u32 {
+        // The default limit allows for a batch of 40 debug traces, or 200 `eth_call`s.
+        self.max_batch_request_cost.unwrap_or(2_000)
+    }
+
+    pub fn max_queued_txs_per_account(&self) -> usize {
+        self.max_queued_txs_per_account.unwrap_or(0) as usize
+    }
+
+    pub fn tx_replacement_fee_bump_percent(&self) -> u32 {
+        self.tx_replacement_fee_bump_percent.unwrap_or(10)
+    }
+
     pub fn max_response_body_size(&self) -> usize {
         self.max_response_body_size_mb.unwrap_or(10) * super::BYTES_IN_MEGABYTE
     }
@@ -222,6 +390,10 @@ impl Web3JsonRpcConfig {
         self.tree_api_url.as_deref()
     }
 
+    pub fn read_only_main_node_url(&self) -> Option<&str> {
+        self.read_only_main_node_url.as_deref()
+    }
+
     pub fn mempool_cache_update_interval(&self) -> Duration {
         Duration::from_millis(self.mempool_cache_update_interval.unwrap_or(50))
     }
@@ -277,10 +449,18 @@ pub struct MerkleTreeApiConfig {
     /// Port to bind the Merkle tree API server to.
     #[serde(default = "MerkleTreeApiConfig::default_port")]
     pub port: u16,
+    /// Maximum total number of keys in a single proofs request. For batched requests, each request
+    /// in the batch counts as at least one key.
+    #[serde(default = "MerkleTreeApiConfig::default_max_proof_keys")]
+    pub max_proof_keys: usize,
 }
 
 impl MerkleTreeApiConfig {
     const fn default_port() -> u16 {
         3_072
     }
+
+    pub const fn default_max_proof_keys() -> usize {
+        10_000
+    }
 }
@@ -0,0 +1,25 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::ObjectStoreConfig;
+
+/// Configuration for the batch exporter, which exports sealed L1 batches as Parquet files to an object store.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct BatchExporterConfig {
+    /// Interval between polling the DB for newly sealed L1 batches.
+    #[serde(default = "BatchExporterConfig::default_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+    /// Object store for exported files. If not specified, the main object store is used.
+    pub object_store: Option<ObjectStoreConfig>,
+}
+
+impl BatchExporterConfig {
+    const fn default_poll_interval_ms() -> u64 {
+        5_000
+    }
+
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_millis(self.poll_interval_ms)
+    }
+}
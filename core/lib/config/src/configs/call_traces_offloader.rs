@@ -0,0 +1,32 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::ObjectStoreConfig;
+
+/// Configuration for the call traces offloader, which moves call traces for old L1 batches from Postgres
+/// to an object store.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CallTracesOffloaderConfig {
+    /// Minimum age of an L1 batch (measured using the batch timestamp) for its call traces to be offloaded.
+    pub offload_after_sec: u64,
+    /// Interval between polling the DB for L1 batches to offload.
+    #[serde(default = "CallTracesOffloaderConfig::default_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+    /// Object store for offloaded call traces. If not specified, the main object store is used.
+    pub object_store: Option<ObjectStoreConfig>,
+}
+
+impl CallTracesOffloaderConfig {
+    const fn default_poll_interval_ms() -> u64 {
+        10_000
+    }
+
+    pub fn offload_after(&self) -> Duration {
+        Duration::from_secs(self.offload_after_sec)
+    }
+
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_millis(self.poll_interval_ms)
+    }
+}
@@ -52,6 +52,19 @@ impl Default for FeeModelVersion {
     }
 }
 
+/// Policy of selecting the fee account (i.e., the recipient of fees collected by the operator) for each L1 batch.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Default)]
+pub enum FeeAccountRotation {
+    /// Always use the main fee account.
+    #[default]
+    Disabled,
+    /// Cycle through fee accounts, switching to the next account with each L1 batch.
+    RoundRobin,
+    /// Select a fee account for each L1 batch pseudo-randomly based on the batch number. The selection
+    /// is deterministic, so it's stable across restarts.
+    PerBatch,
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
 pub enum L1BatchCommitDataGeneratorMode {
     #[default]
@@ -100,6 +113,37 @@ pub struct StateKeeperConfig {
     /// sealing will block until some of the miniblocks from the queue are processed.
     /// 0 means that sealing is synchronous; this is mostly useful for performance comparison, testing etc.
     pub miniblock_seal_queue_capacity: usize,
+    /// If set, a miniblock is sealed as soon as it contains this many transactions, and the miniblock sealing
+    /// deadline adapts to load: it grows from `miniblock_min_commit_deadline_ms` for a miniblock with a single
+    /// transaction to `miniblock_commit_deadline_ms` for a miniblock that is almost full.
+    pub miniblock_max_tx_count: Option<usize>,
+    /// Lower bound for the adaptive miniblock sealing deadline in ms. Only used if `miniblock_max_tx_count` is set;
+    /// if not specified, the deadline is always equal to `miniblock_commit_deadline_ms`.
+    pub miniblock_min_commit_deadline_ms: Option<u64>,
+    /// If set, a miniblock is sealed as soon as the cumulative gas used by its transactions reaches this value, so that
+    /// a single miniblock cannot absorb most of the L1 batch gas budget. Since the check is performed after executing
+    /// a transaction, a miniblock may exceed the ceiling by at most one transaction.
+    pub max_gas_per_miniblock: Option<u64>,
+
+    /// Maximum number of priority (L1 -> L2) operations admitted into a single miniblock. Once the limit is reached,
+    /// the mempool I/O only provides L2 transactions until the miniblock is sealed. Unlimited if not specified.
+    pub max_priority_ops_per_miniblock: Option<usize>,
+    /// Maximum number of priority operations admitted into a single L1 batch. Unlimited if not specified.
+    pub max_priority_ops_per_batch: Option<usize>,
+    /// Fairness policy for priority operations: after this many priority operations are executed in a row,
+    /// a pending L2 transaction (if any) is executed before the next priority operation. If not specified,
+    /// priority operations are always executed before L2 transactions.
+    pub max_consecutive_priority_ops: Option<usize>,
+
+    /// Operator-defined ceiling on the gas used by a single transaction. Unlike protocol limits, this limit
+    /// is enforced by the state keeper and the API server only; transactions exceeding it are rejected.
+    /// Unlimited if not specified.
+    pub max_gas_per_tx: Option<u64>,
+    /// Operator-defined ceiling on the pubdata (in bytes) produced by a single transaction. Unlimited if not specified.
+    pub max_pubdata_per_tx: Option<u64>,
+    /// Operator-defined ceiling on the total size (in bytes) of factory dependencies supplied with a single transaction.
+    /// Unlimited if not specified.
+    pub max_factory_deps_size_per_tx: Option<u64>,
 
     /// The max number of gas to spend on an L1 tx before its batch should be sealed by the gas sealer.
     pub max_single_tx_gas: u32,
@@ -124,6 +168,12 @@ pub struct StateKeeperConfig {
     /// Fee account address. Value is deprecated and it's used only for generating wallets struct
     #[deprecated(note = "Use Wallets::fee_account::address instead")]
     pub fee_account_addr: Option<Address>,
+    /// Additional fee accounts that receive collected fees together with the main fee account,
+    /// according to `fee_account_rotation`.
+    #[serde(default)]
+    pub additional_fee_account_addrs: Vec<Address>,
+    /// Policy of selecting the fee account for each L1 batch. If not specified, the main fee account is always used.
+    pub fee_account_rotation: Option<FeeAccountRotation>,
     /// The minimal acceptable L2 gas price, i.e. the price that should include the cost of computation/proving as well
     /// as potentially premium for congestion.
     pub minimal_l2_gas_price: u64,
@@ -154,6 +204,28 @@ pub struct StateKeeperConfig {
 
     /// Number of keys that is processed by enum_index migration in State Keeper each L1 batch.
     pub enum_index_migration_chunk_size: Option<usize>,
+    /// Whether protective reads should be persisted when sealing an L1 batch. If disabled, protective reads
+    /// must be computed in the background by the protective reads writer component, or the node must not run
+    /// a full Merkle tree. Enabled by default.
+    pub protective_reads_persistence_enabled: Option<bool>,
+    /// Whether the open miniblock should be sealed on a graceful shutdown, so that the pending L1 batch
+    /// is resumed with its transactions after a restart. Disabled by default.
+    pub seal_miniblock_on_shutdown: Option<bool>,
+    /// Address (`host:port`) of an NTP server used to detect the skew of the local clock. If the skew exceeds
+    /// `max_clock_skew_ms`, the state keeper stops producing miniblocks until the clock is fixed.
+    /// If not specified, clock skew is not monitored.
+    pub ntp_server: Option<String>,
+    /// Maximum allowed absolute skew of the local clock compared to the NTP server, in ms. Default is 5000 ms.
+    pub max_clock_skew_ms: Option<u64>,
+    /// Whether each L1 batch should additionally be executed using the VM and base system contracts of the upcoming
+    /// protocol version, if one is persisted in Postgres (shadow execution). Divergences between VMs are logged and
+    /// reported via metrics, but don't affect the main VM or sealing. The shadow VM runs on a separate thread with
+    /// a dedicated Postgres connection; it's aborted for the batch if it lags behind. Disabled by default.
+    pub shadow_vm_execution: Option<bool>,
+    /// Whether the VM should run in a separate OS process, so that a VM panic or running out of memory doesn't
+    /// bring down the state keeper. A crashed VM process is restarted; a transaction that repeatedly crashes it
+    /// is rejected. Incompatible with shadow VM execution. Disabled by default.
+    pub batch_executor_process_isolation: Option<bool>,
 
     /// The maximal number of circuits that a batch can support.
     /// Note, that this number corresponds to the "base layer" circuits, i.e. it does not include
@@ -181,6 +253,15 @@ impl StateKeeperConfig {
             block_commit_deadline_ms: 2500,
             miniblock_commit_deadline_ms: 1000,
             miniblock_seal_queue_capacity: 10,
+            miniblock_max_tx_count: None,
+            miniblock_min_commit_deadline_ms: None,
+            max_gas_per_miniblock: None,
+            max_priority_ops_per_miniblock: None,
+            max_priority_ops_per_batch: None,
+            max_consecutive_priority_ops: None,
+            max_gas_per_tx: None,
+            max_pubdata_per_tx: None,
+            max_factory_deps_size_per_tx: None,
             max_single_tx_gas: 6000000,
             max_allowed_l2_tx_gas_limit: 4000000000,
             reject_tx_at_geometry_percentage: 0.95,
@@ -192,6 +273,8 @@ impl StateKeeperConfig {
             fee_account_addr: Some(
                 Address::from_str("0xde03a0B5963f75f1C8485B355fF6D30f3093BDE7").unwrap(),
             ),
+            additional_fee_account_addrs: vec![],
+            fee_account_rotation: None,
             compute_overhead_part: 0.0,
             pubdata_overhead_part: 1.0,
             batch_overhead_l1_gas: 800_000,
@@ -204,6 +287,12 @@ impl StateKeeperConfig {
             virtual_blocks_interval: 1,
             virtual_blocks_per_miniblock: 1,
             enum_index_migration_chunk_size: None,
+            protective_reads_persistence_enabled: None,
+            seal_miniblock_on_shutdown: None,
+            ntp_server: None,
+            max_clock_skew_ms: None,
+            shadow_vm_execution: None,
+            batch_executor_process_isolation: None,
             max_circuits_per_batch: 24100,
             bootloader_hash: None,
             default_aa_hash: None,
@@ -214,6 +303,26 @@ impl StateKeeperConfig {
     pub fn enum_index_migration_chunk_size(&self) -> usize {
         self.enum_index_migration_chunk_size.unwrap_or(1_000)
     }
+
+    pub fn protective_reads_persistence_enabled(&self) -> bool {
+        self.protective_reads_persistence_enabled.unwrap_or(true)
+    }
+
+    pub fn seal_miniblock_on_shutdown(&self) -> bool {
+        self.seal_miniblock_on_shutdown.unwrap_or(false)
+    }
+
+    pub fn shadow_vm_execution(&self) -> bool {
+        self.shadow_vm_execution.unwrap_or(false)
+    }
+
+    pub fn batch_executor_process_isolation(&self) -> bool {
+        self.batch_executor_process_isolation.unwrap_or(false)
+    }
+
+    pub fn max_clock_skew(&self) -> Duration {
+        Duration::from_millis(self.max_clock_skew_ms.unwrap_or(5_000))
+    }
 }
 
 #[derive(Debug, Deserialize, Clone, PartialEq)]
@@ -254,6 +363,34 @@ pub struct MempoolConfig {
     pub stuck_tx_timeout: u64,
     pub remove_stuck_txs: bool,
     pub delay_interval: u64,
+    /// Path to a file or an HTTP(S) URL with the list of denied addresses (one address per line). L2 transactions
+    /// initiated by or sent to these addresses are rejected both by the API server and by the state keeper.
+    /// If not specified, all transactions are allowed.
+    pub tx_deny_list: Option<String>,
+    /// Interval in seconds between deny list reloads. Default is 60 seconds.
+    pub tx_deny_list_reload_interval_sec: Option<u64>,
+    /// Hex-encoded calldata prefixes (e.g., function selectors) of denied L2 transactions. Transactions with calldata
+    /// starting with any of these prefixes are rejected both by the API server and by the state keeper.
+    #[serde(default)]
+    pub tx_denied_calldata_prefixes: Vec<String>,
+    /// Time windows during which only contract deployment L2 transactions are executed, each specified as
+    /// `<start>-<end>` UNIX timestamps in seconds (the end is exclusive). Other L2 transactions are kept
+    /// in the mempool until the window ends.
+    #[serde(default)]
+    pub tx_deployment_only_windows: Vec<String>,
+    /// Hard limit on the number of L2 transactions kept in the mempool. Once exceeded, the mempool evicts
+    /// transactions of senders with the lowest score (evicted transactions stay in Postgres and are loaded
+    /// into the mempool again once it has room). If not specified, the mempool is only bounded by `capacity`,
+    /// which is a soft limit.
+    pub max_l2_transactions: Option<u64>,
+    /// Score points per 1 Mwei (10^6 wei) of the max fee per gas of an L2 transaction. Default is 0, i.e.,
+    /// the fee doesn't influence the order in which transactions are executed.
+    pub score_fee_weight: Option<u64>,
+    /// Score points per second of age of an L2 transaction. Default is 1.
+    pub score_age_weight: Option<u64>,
+    /// Score points deducted from transactions of a sender for each rejected transaction of this sender
+    /// (only tracked while the sender has transactions in the mempool). Default is 0.
+    pub score_rejection_penalty: Option<u64>,
 }
 
 impl MempoolConfig {
@@ -268,4 +405,8 @@ impl MempoolConfig {
     pub fn delay_interval(&self) -> Duration {
         Duration::from_millis(self.delay_interval)
     }
+
+    pub fn tx_deny_list_reload_interval(&self) -> Duration {
+        Duration::from_secs(self.tx_deny_list_reload_interval_sec.unwrap_or(60))
+    }
 }
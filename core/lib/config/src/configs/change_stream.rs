@@ -0,0 +1,36 @@
+use serde::Deserialize;
+
+/// Configuration for the change stream publishing sealed miniblocks and L1 batches to an external message bus.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ChangeStreamConfig {
+    /// URL of the NATS server to publish messages to, e.g. `nats://127.0.0.1:4222`. Messages are published
+    /// via JetStream, so the server must have a stream capturing the published subjects.
+    pub nats_url: String,
+    /// Path to a NATS credentials (`.creds`) file used to authenticate to the server. If not specified,
+    /// the node connects without authentication.
+    pub nats_credentials_path: Option<String>,
+    /// Whether TLS is required for the connection to the NATS server. TLS is also used if the server requires it,
+    /// or if the URL has the `tls://` scheme.
+    #[serde(default)]
+    pub nats_tls_required: bool,
+    /// Path to a PEM file with additional root certificates used to verify the NATS server certificate.
+    pub nats_root_certificates_path: Option<String>,
+    /// Prefix for subjects of published messages. Miniblocks are published to `{prefix}.miniblocks`,
+    /// and L1 batches to `{prefix}.l1_batches`.
+    #[serde(default = "ChangeStreamConfig::default_subject_prefix")]
+    pub subject_prefix: String,
+    /// Maximum number of messages queued for publishing. If the queue is full, the state keeper waits
+    /// until messages are published.
+    #[serde(default = "ChangeStreamConfig::default_queue_capacity")]
+    pub queue_capacity: usize,
+}
+
+impl ChangeStreamConfig {
+    fn default_subject_prefix() -> String {
+        "zksync".to_owned()
+    }
+
+    const fn default_queue_capacity() -> usize {
+        1_024
+    }
+}
@@ -15,6 +15,9 @@ pub struct ContractsConfig {
     pub l2_erc20_bridge_addr: Address,
     pub l1_weth_bridge_proxy_addr: Option<Address>,
     pub l2_weth_bridge_addr: Option<Address>,
+    /// Shared bridge used by chains connected to the bridgehub. If set, the ERC20 bridge is considered legacy.
+    pub l1_shared_bridge_proxy_addr: Option<Address>,
+    pub l2_shared_bridge_addr: Option<Address>,
     pub l2_testnet_paymaster_addr: Option<Address>,
     pub l1_multicall3_addr: Address,
 }
@@ -30,6 +33,8 @@ impl ContractsConfig {
             l2_erc20_bridge_addr: Address::repeat_byte(0x0d),
             l1_weth_bridge_proxy_addr: Some(Address::repeat_byte(0x0e)),
             l2_weth_bridge_addr: Some(Address::repeat_byte(0x0f)),
+            l1_shared_bridge_proxy_addr: Some(Address::repeat_byte(0x14)),
+            l2_shared_bridge_addr: Some(Address::repeat_byte(0x15)),
             l2_testnet_paymaster_addr: Some(Address::repeat_byte(0x11)),
             l1_multicall3_addr: Address::repeat_byte(0x12),
             governance_addr: Address::repeat_byte(0x13),
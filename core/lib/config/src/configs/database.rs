@@ -1,4 +1,4 @@
-use std::time::Duration;
+use std::{collections::BTreeMap, time::Duration};
 
 use anyhow::Context as _;
 use serde::{Deserialize, Serialize};
@@ -18,6 +18,50 @@ pub enum MerkleTreeMode {
     Lightweight,
 }
 
+/// Preset of RocksDB options for the Merkle tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MerkleTreeRocksdbPreset {
+    /// Preset for archive nodes, which retain all tree versions. Uses level compaction to keep space amplification
+    /// of the ever-growing database low.
+    Archive,
+    /// Preset for nodes with tree pruning enabled. Uses universal compaction, which has lower write amplification
+    /// (and thus is less prone to write stalls on large L1 batches and during recovery) at the cost of
+    /// higher space amplification, which is tolerable since the database size is bounded.
+    Pruned,
+}
+
+impl MerkleTreeRocksdbPreset {
+    /// Returns the default preset depending on whether tree pruning is enabled.
+    pub fn new(pruning_enabled: bool) -> Self {
+        if pruning_enabled {
+            Self::Pruned
+        } else {
+            Self::Archive
+        }
+    }
+
+    /// Returns the compaction style for large column families used by this preset.
+    pub fn compaction_style(self) -> MerkleTreeCompactionStyle {
+        match self {
+            Self::Archive => MerkleTreeCompactionStyle::Level,
+            Self::Pruned => MerkleTreeCompactionStyle::Universal,
+        }
+    }
+}
+
+/// Compaction style for large RocksDB column families of the Merkle tree.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MerkleTreeCompactionStyle {
+    /// Level compaction (the RocksDB default).
+    #[default]
+    Level,
+    /// Universal compaction. Reduces write amplification at the cost of space amplification.
+    /// Switching an existing database from universal to level compaction requires a full manual compaction.
+    Universal,
+}
+
 #[derive(Debug, Clone, PartialEq, Deserialize)]
 pub struct MerkleTreeConfig {
     /// Path to the RocksDB data directory for Merkle tree.
@@ -44,6 +88,52 @@ pub struct MerkleTreeConfig {
     /// Maximum number of L1 batches to be processed by the Merkle tree at a time.
     #[serde(default = "MerkleTreeConfig::default_max_l1_batches_per_iter")]
     pub max_l1_batches_per_iter: usize,
+    /// Number of threads used to hash tree nodes when processing L1 batches. If not specified, the global `rayon`
+    /// thread pool will be used (by default, it has a thread per logical CPU core).
+    #[serde(default)]
+    pub hashing_thread_count: Option<usize>,
+    /// Retention period for old tree versions measured by the age of the corresponding L1 batches. If specified,
+    /// tree versions for L1 batches older than this period are pruned in the background, reclaiming RocksDB space.
+    /// The period should be large enough to cover potential L1 batch reverts. If not specified, no versions are pruned.
+    #[serde(default)]
+    pub pruning_retention_sec: Option<u64>,
+    /// Interval between background consistency checks of the tree, which verify a random subtree and compare
+    /// tree root hashes with the ones committed on L1. A detected inconsistency triggers a circuit breaker.
+    /// If not specified, background checks are disabled.
+    #[serde(default)]
+    pub consistency_check_interval_sec: Option<u64>,
+    /// Preset of RocksDB options. If not specified, the preset is chosen based on whether tree pruning is enabled
+    /// (i.e., `pruning_retention_sec` is set).
+    #[serde(default)]
+    pub rocksdb_preset: Option<MerkleTreeRocksdbPreset>,
+    /// Compaction style for large RocksDB column families. Overrides the value from `rocksdb_preset`.
+    #[serde(default)]
+    pub compaction_style: Option<MerkleTreeCompactionStyle>,
+    /// Whether to store leaves and internal tree nodes in separate RocksDB column families. Only has an effect
+    /// when creating a new database; the layout of existing databases is never changed. Enabled by default.
+    #[serde(default)]
+    pub separate_leaves_column_family: Option<bool>,
+    /// Interval between checks for new Postgres snapshots, for which the tree snapshot is exported to the object store.
+    /// Exported tree snapshots allow recovering nodes to bootstrap their Merkle tree without recovering it
+    /// from Postgres. If not specified, tree snapshots are not exported.
+    #[serde(default)]
+    pub snapshot_export_interval_sec: Option<u64>,
+    /// Number of L1 batches the tree intentionally lags behind the last sealed L1 batch. Can be used on nodes
+    /// that only need eventual tree consistency (e.g., pruned RPC nodes) to reduce competition with user traffic.
+    /// Non-zero values are rejected by [`Self::validate()`] since this config is used on the main node,
+    /// where L1 batch commitments depend on tree data. Defaults to 0 (no lag).
+    #[serde(default)]
+    pub lag_l1_batches: Option<u32>,
+    /// If set, the tree throttles itself when loading data for an L1 batch from Postgres takes longer
+    /// than this threshold: it processes a single L1 batch per iteration and waits for the polling interval
+    /// between iterations. If not specified, the tree processes L1 batches at full speed.
+    #[serde(default)]
+    pub throttle_latency_threshold_ms: Option<u64>,
+    /// Whether to keep the tree database entirely in RAM rather than on disk (`path` is then only used as
+    /// the database name). The tree is rebuilt from Postgres on each node restart, so this is only suitable
+    /// for tests and ephemeral dev chains. Disabled by default.
+    #[serde(default)]
+    pub in_memory: bool,
 }
 
 impl Default for MerkleTreeConfig {
@@ -56,6 +146,16 @@ impl Default for MerkleTreeConfig {
             memtable_capacity_mb: Self::default_memtable_capacity_mb(),
             stalled_writes_timeout_sec: Self::default_stalled_writes_timeout_sec(),
             max_l1_batches_per_iter: Self::default_max_l1_batches_per_iter(),
+            hashing_thread_count: None,
+            pruning_retention_sec: None,
+            consistency_check_interval_sec: None,
+            rocksdb_preset: None,
+            compaction_style: None,
+            separate_leaves_column_family: None,
+            snapshot_export_interval_sec: None,
+            lag_l1_batches: None,
+            throttle_latency_threshold_ms: None,
+            in_memory: false,
         }
     }
 }
@@ -99,6 +199,58 @@ impl MerkleTreeConfig {
     pub fn stalled_writes_timeout(&self) -> Duration {
         Duration::from_secs(self.stalled_writes_timeout_sec)
     }
+
+    /// Returns the retention period for old tree versions, or `None` if tree pruning is disabled.
+    pub fn pruning_retention(&self) -> Option<Duration> {
+        self.pruning_retention_sec.map(Duration::from_secs)
+    }
+
+    /// Returns the interval between background tree consistency checks, or `None` if these checks are disabled.
+    pub fn consistency_check_interval(&self) -> Option<Duration> {
+        self.consistency_check_interval_sec.map(Duration::from_secs)
+    }
+
+    /// Returns the interval between checks for new snapshots to export the tree for.
+    pub fn snapshot_export_interval(&self) -> Option<Duration> {
+        self.snapshot_export_interval_sec.map(Duration::from_secs)
+    }
+
+    /// Returns the number of L1 batches the tree intentionally lags behind the last sealed L1 batch.
+    pub fn lag_l1_batches(&self) -> u32 {
+        self.lag_l1_batches.unwrap_or(0)
+    }
+
+    /// Checks that this config doesn't contain options unsupported on the main node.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            self.lag_l1_batches() == 0,
+            "`lag_l1_batches` is not supported on the main node since L1 batch commitments depend on tree data"
+        );
+        Ok(())
+    }
+
+    /// Returns the Postgres latency threshold for tree throttling, or `None` if throttling is disabled.
+    pub fn throttle_latency_threshold(&self) -> Option<Duration> {
+        self.throttle_latency_threshold_ms
+            .map(Duration::from_millis)
+    }
+
+    /// Returns the RocksDB options preset, taking into account whether tree pruning is enabled.
+    pub fn rocksdb_preset(&self) -> MerkleTreeRocksdbPreset {
+        self.rocksdb_preset
+            .unwrap_or_else(|| MerkleTreeRocksdbPreset::new(self.pruning_retention_sec.is_some()))
+    }
+
+    /// Returns the compaction style for large RocksDB column families.
+    pub fn compaction_style(&self) -> MerkleTreeCompactionStyle {
+        self.compaction_style
+            .unwrap_or_else(|| self.rocksdb_preset().compaction_style())
+    }
+
+    /// Checks whether leaves should be stored in a separate RocksDB column family for newly created databases.
+    pub fn separate_leaves_column_family(&self) -> bool {
+        self.separate_leaves_column_family.unwrap_or(true)
+    }
 }
 
 /// Database configuration.
@@ -131,10 +283,28 @@ pub struct PostgresConfig {
     pub replica_url: Option<String>,
     /// URL for the prover database.
     pub prover_url: Option<String>,
+    /// URLs of read-only Postgres replicas of the main database used by the API servers. If specified,
+    /// connections for API methods tolerating replication lag (e.g., fee history and block tracing) are distributed
+    /// among replicas not lagging behind the main database by more than `max_replica_lag_ms`, falling back
+    /// to `replica_url` if there are no such replicas.
+    pub read_replica_urls: Vec<String>,
+    /// Maximum replication lag in milliseconds for a replica from `read_replica_urls` to be used by the API servers.
+    pub max_replica_lag_ms: Option<u64>,
     /// Maximum size of the connection pool.
     pub max_connections: Option<u32>,
     /// Maximum size of the connection pool to master DB.
     pub max_connections_master: Option<u32>,
+    /// Maximum size of the dedicated connection pool for the API servers. If not specified, `max_connections` is used.
+    pub max_connections_api: Option<u32>,
+    /// Maximum size of the dedicated connection pool for the state keeper. If not specified, `max_connections`
+    /// is used.
+    pub max_connections_state_keeper: Option<u32>,
+    /// Maximum size of the dedicated connection pool for the metadata calculator. If not specified,
+    /// `max_connections` is used.
+    pub max_connections_metadata_calculator: Option<u32>,
+    /// Maximum size of the dedicated connection pool for housekeeping tasks. If not specified, `max_connections`
+    /// is used.
+    pub max_connections_housekeeping: Option<u32>,
 
     /// Acquire timeout in seconds for a single connection attempt. There are multiple attempts (currently 3)
     /// before acquire methods will return an error.
@@ -146,6 +316,21 @@ pub struct PostgresConfig {
     pub long_connection_threshold_ms: Option<u64>,
     /// Threshold in milliseconds to denote a DB query as "slow" and log its details.
     pub slow_query_threshold_ms: Option<u64>,
+    /// Fraction of slow queries for which query plans are obtained using `EXPLAIN` and stored for inspection
+    /// (e.g., via the `admin` Web3 namespace). If not specified, slow queries are not captured.
+    pub slow_query_explain_sample_rate: Option<f64>,
+    /// Timeout in milliseconds for DB queries. Queries exceeding the timeout are cancelled; connections with queries
+    /// dropped by the caller before completion are closed. If not specified, queries don't have a timeout.
+    pub query_timeout_ms: Option<u64>,
+    /// Overrides of `query_timeout_ms` for specific components, keyed by the component tag of DB connections
+    /// (e.g., `state_keeper` or `api`). Zero timeout disables timeouts for the component.
+    pub component_query_timeouts_ms: BTreeMap<String, u64>,
+    /// Maximum number of retries for DB transactions failing with a serialization or deadlock error.
+    /// If not specified, such transactions are not retried.
+    pub query_max_retries: Option<u32>,
+    /// Whether to use binary `COPY` statements for bulk inserts of storage logs, events and initial writes.
+    /// If not specified, binary `COPY` is not used.
+    pub use_binary_copy: Option<bool>,
     pub test_server_url: Option<String>,
     pub test_prover_url: Option<String>,
 }
@@ -181,6 +366,14 @@ impl PostgresConfig {
         self.max_connections_master
     }
 
+    /// Returns the maximum replication lag for read-only replicas.
+    pub fn max_replica_lag(&self) -> Duration {
+        const DEFAULT_MAX_REPLICA_LAG: Duration = Duration::from_secs(5);
+
+        self.max_replica_lag_ms
+            .map_or(DEFAULT_MAX_REPLICA_LAG, Duration::from_millis)
+    }
+
     /// Returns the Postgres statement timeout.
     pub fn statement_timeout(&self) -> Option<Duration> {
         self.statement_timeout_sec.map(Duration::from_secs)
@@ -198,4 +391,15 @@ impl PostgresConfig {
     pub fn slow_query_threshold(&self) -> Option<Duration> {
         self.slow_query_threshold_ms.map(Duration::from_millis)
     }
+
+    pub fn query_timeout(&self) -> Option<Duration> {
+        self.query_timeout_ms.map(Duration::from_millis)
+    }
+
+    /// Returns per-component overrides for the query timeout.
+    pub fn component_query_timeouts(&self) -> impl Iterator<Item = (&str, Duration)> + '_ {
+        self.component_query_timeouts_ms
+            .iter()
+            .map(|(component, &timeout_ms)| (component.as_str(), Duration::from_millis(timeout_ms)))
+    }
 }
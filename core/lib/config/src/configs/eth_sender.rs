@@ -1,7 +1,7 @@
-use std::time::Duration;
+use std::{fmt, time::Duration};
 
 use serde::Deserialize;
-use zksync_basic_types::H256;
+use zksync_basic_types::{Address, H256};
 
 use crate::ETHWatchConfig;
 
@@ -14,6 +14,8 @@ pub struct ETHConfig {
     pub gas_adjuster: Option<GasAdjusterConfig>,
     pub watcher: Option<ETHWatchConfig>,
     pub web3_url: String,
+    /// Remote signer used to sign L1 transactions. If set, operator private keys are not required.
+    pub remote_signer: Option<RemoteSignerConfig>,
 }
 
 impl ETHConfig {
@@ -40,6 +42,8 @@ impl ETHConfig {
                 max_acceptable_priority_fee_in_gwei: 100000000000,
                 proof_loading_mode: ProofLoadingMode::OldProofFromDb,
                 pubdata_sending_mode: PubdataSendingMode::Calldata,
+                max_commit_blob_base_fee: None,
+                commit_blob_base_fee_deadline: 3600,
             }),
             gas_adjuster: Some(GasAdjusterConfig {
                 default_priority_fee_per_gas: 1000000000,
@@ -58,12 +62,60 @@ impl ETHConfig {
             watcher: Some(ETHWatchConfig {
                 confirmations_for_eth_event: None,
                 eth_node_poll_interval: 0,
+                governance_alerts_webhook_url: None,
+                governance_alerts_lead_time_sec: None,
             }),
             web3_url: "localhost:8545".to_string(),
+            remote_signer: None,
         }
     }
 }
 
+/// Configuration of a remote service (e.g., web3signer or a KMS / HSM proxy) signing L1 transactions on behalf
+/// of the operator accounts.
+#[derive(Deserialize, Clone, PartialEq)]
+pub struct RemoteSignerConfig {
+    /// URLs of the signer service. URLs must use HTTPS unless they point to a loopback host. If a URL is unreachable,
+    /// the next URL in the list is used.
+    pub urls: Vec<String>,
+    /// Address of the operator account managed by the signer.
+    pub operator_address: Address,
+    /// Address of the blob operator account managed by the signer, if blobs are sent from a separate account.
+    pub blob_operator_address: Option<Address>,
+    /// Private key used to authenticate requests to the signer service. This key only authenticates requests
+    /// and doesn't control any L1 funds. If not set, requests are not authenticated.
+    pub request_signing_key: Option<H256>,
+    /// Timeout for a single signing request in milliseconds.
+    #[serde(default = "RemoteSignerConfig::default_request_timeout_ms")]
+    pub request_timeout_ms: u64,
+}
+
+impl RemoteSignerConfig {
+    const fn default_request_timeout_ms() -> u64 {
+        5_000
+    }
+
+    /// Converts `self.request_timeout_ms` into `Duration`.
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_millis(self.request_timeout_ms)
+    }
+}
+
+impl fmt::Debug for RemoteSignerConfig {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The request signing key is a secret, so we only output whether it is set.
+        let request_signing_key = self.request_signing_key.as_ref().map(|_| "[REDACTED]");
+        formatter
+            .debug_struct("RemoteSignerConfig")
+            .field("urls", &self.urls)
+            .field("operator_address", &self.operator_address)
+            .field("blob_operator_address", &self.blob_operator_address)
+            .field("request_signing_key", &request_signing_key)
+            .field("request_timeout_ms", &self.request_timeout_ms)
+            .finish()
+    }
+}
+
 #[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
 pub enum ProofSendingMode {
     OnlyRealProofs,
@@ -120,6 +172,14 @@ pub struct SenderConfig {
 
     /// The mode in which we send pubdata, either Calldata or Blobs
     pub pubdata_sending_mode: PubdataSendingMode,
+
+    /// Blob base fee (in wei) above which commit operations are postponed if pubdata is sent via blobs.
+    /// If not set, commit operations are not postponed based on the blob base fee.
+    pub max_commit_blob_base_fee: Option<u64>,
+    /// Maximum age of an L1 batch in seconds after which it is committed regardless of the blob base fee.
+    /// Only used if `max_commit_blob_base_fee` is set.
+    #[serde(default = "SenderConfig::default_commit_blob_base_fee_deadline")]
+    pub commit_blob_base_fee_deadline: u64,
 }
 
 impl SenderConfig {
@@ -128,6 +188,10 @@ impl SenderConfig {
         Duration::from_secs(self.tx_poll_period)
     }
 
+    pub const fn default_commit_blob_base_fee_deadline() -> u64 {
+        3_600
+    }
+
     /// Converts `self.aggregate_tx_poll_period` into `Duration`.
     pub fn aggregate_tx_poll_period(&self) -> Duration {
         Duration::from_secs(self.aggregate_tx_poll_period)
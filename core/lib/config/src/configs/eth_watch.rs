@@ -11,6 +11,12 @@ pub struct ETHWatchConfig {
     /// How often we want to poll the Ethereum node.
     /// Value in milliseconds.
     pub eth_node_poll_interval: u64,
+    /// URL of the webhook receiving alerts about governance operations scheduled on L1. If not specified,
+    /// alerts are not sent.
+    pub governance_alerts_webhook_url: Option<String>,
+    /// How long before a governance operation becomes ready for execution the "upcoming operation" alert is sent.
+    /// Value in seconds. If not specified, 1 day is used.
+    pub governance_alerts_lead_time_sec: Option<u64>,
 }
 
 impl ETHWatchConfig {
@@ -18,4 +24,9 @@ impl ETHWatchConfig {
     pub fn poll_interval(&self) -> Duration {
         Duration::from_millis(self.eth_node_poll_interval)
     }
+
+    /// Returns the lead time for the "upcoming governance operation" alerts.
+    pub fn governance_alerts_lead_time(&self) -> Duration {
+        Duration::from_secs(self.governance_alerts_lead_time_sec.unwrap_or(24 * 60 * 60))
+    }
 }
@@ -3,6 +3,7 @@ use crate::{
         chain::{CircuitBreakerConfig, MempoolConfig, OperationsManagerConfig, StateKeeperConfig},
         fri_prover_group::FriProverGroupConfig,
         house_keeper::HouseKeeperConfig,
+        BatchExporterConfig, CallTracesOffloaderConfig, ChangeStreamConfig,
         FriProofCompressorConfig, FriProverConfig, FriProverGatewayConfig,
         FriWitnessGeneratorConfig, FriWitnessVectorGeneratorConfig, ObservabilityConfig,
         PrometheusConfig, ProofDataHandlerConfig,
@@ -32,4 +33,7 @@ pub struct GeneralConfig {
     pub eth: Option<ETHConfig>,
     pub snapshot_creator: Option<SnapshotsCreatorConfig>,
     pub observability: Option<ObservabilityConfig>,
+    pub batch_exporter: Option<BatchExporterConfig>,
+    pub call_traces_offloader: Option<CallTracesOffloaderConfig>,
+    pub change_stream: Option<ChangeStreamConfig>,
 }
@@ -1,6 +1,9 @@
 // Public re-exports
 pub use self::{
     api::ApiConfig,
+    batch_exporter::BatchExporterConfig,
+    call_traces_offloader::CallTracesOffloaderConfig,
+    change_stream::ChangeStreamConfig,
     contract_verifier::ContractVerifierConfig,
     contracts::ContractsConfig,
     database::{DBConfig, PostgresConfig},
@@ -22,6 +25,9 @@ pub use self::{
 };
 
 pub mod api;
+pub mod batch_exporter;
+pub mod call_traces_offloader;
+pub mod change_stream;
 pub mod chain;
 pub mod contract_verifier;
 pub mod contracts;
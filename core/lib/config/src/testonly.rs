@@ -36,6 +36,17 @@ impl Distribution<configs::chain::FeeModelVersion> for EncodeDist {
     }
 }
 
+impl Distribution<configs::chain::FeeAccountRotation> for EncodeDist {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> configs::chain::FeeAccountRotation {
+        type T = configs::chain::FeeAccountRotation;
+        match rng.gen_range(0..3) {
+            0 => T::Disabled,
+            1 => T::RoundRobin,
+            _ => T::PerBatch,
+        }
+    }
+}
+
 impl Distribution<configs::ApiConfig> for EncodeDist {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> configs::ApiConfig {
         configs::ApiConfig {
@@ -59,27 +70,55 @@ impl Distribution<configs::api::Web3JsonRpcConfig> for EncodeDist {
             filters_limit: self.sample(rng),
             subscriptions_limit: self.sample(rng),
             pubsub_polling_interval: self.sample(rng),
+            pubsub_db_notifications: self.sample(rng),
             max_nonce_ahead: self.sample(rng),
             gas_price_scale_factor: self.sample(rng),
             request_timeout: self.sample_opt(|| self.sample(rng)),
             account_pks: self.sample_opt(|| self.sample_range(rng).map(|_| rng.gen()).collect()),
             estimate_gas_scale_factor: self.sample(rng),
             estimate_gas_acceptable_overestimation: self.sample(rng),
+            estimate_gas_optimize_search: self.sample(rng),
             l1_to_l2_transactions_compatibility_mode: self.sample(rng),
             max_tx_size: self.sample(rng),
             vm_execution_cache_misses_limit: self.sample(rng),
+            vm_execution_timeout_ms: self.sample(rng),
+            eth_call_cache_size: self.sample(rng),
+            estimate_gas_cache_size: self.sample(rng),
+            estimate_gas_cache_ttl_ms: self.sample(rng),
             vm_concurrency_limit: self.sample(rng),
+            vm_permit_timeout_ms: self.sample(rng),
+            vm_saturation_threshold: self.sample(rng),
             factory_deps_cache_size_mb: self.sample(rng),
             initial_writes_cache_size_mb: self.sample(rng),
             latest_values_cache_size_mb: self.sample(rng),
+            latest_values_cache_path: self.sample(rng),
+            latest_values_cache_persistent_max_entries: self.sample(rng),
+            caches_warm_up_miniblocks: self.sample(rng),
+            historical_state_path: self.sample(rng),
+            historical_state_retained_miniblocks: self.sample(rng),
             fee_history_limit: self.sample(rng),
+            fee_model_simulation_limit: self.sample(rng),
             max_batch_request_size: self.sample(rng),
+            max_batch_request_cost: self.sample(rng),
+            max_queued_txs_per_account: self.sample(rng),
+            tx_replacement_fee_bump_percent: self.sample(rng),
+            read_only_main_node_url: self.sample(rng),
+            max_tx_submissions_per_minute_per_sender: self.sample(rng),
+            max_pending_txs_per_sender: self.sample(rng),
             max_response_body_size_mb: self.sample(rng),
             websocket_requests_per_minute_limit: self.sample(rng),
             tree_api_url: self.sample(rng),
             mempool_cache_update_interval: self.sample(rng),
             mempool_cache_size: self.sample(rng),
             whitelisted_tokens_for_aa: self.sample_range(rng).map(|_| rng.gen()).collect(),
+            aa_trusted_token_slots: self.sample_range(rng).map(|_| rng.gen()).collect(),
+            aa_trusted_addresses: self.sample_range(rng).map(|_| rng.gen()).collect(),
+            admin_namespace_enabled: self.sample(rng),
+            unstable_namespace_enabled: self.sample(rng),
+            sse_port: self.sample(rng),
+            http_auth_token: self.sample(rng),
+            ws_auth_token: self.sample(rng),
+            ipc_path: self.sample(rng),
         }
     }
 }
@@ -107,6 +146,7 @@ impl Distribution<configs::api::MerkleTreeApiConfig> for EncodeDist {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> configs::api::MerkleTreeApiConfig {
         configs::api::MerkleTreeApiConfig {
             port: self.sample(rng),
+            max_proof_keys: self.sample(rng),
         }
     }
 }
@@ -152,6 +192,15 @@ impl Distribution<configs::chain::StateKeeperConfig> for EncodeDist {
             block_commit_deadline_ms: self.sample(rng),
             miniblock_commit_deadline_ms: self.sample(rng),
             miniblock_seal_queue_capacity: self.sample(rng),
+            miniblock_max_tx_count: self.sample(rng),
+            miniblock_min_commit_deadline_ms: self.sample(rng),
+            max_gas_per_miniblock: self.sample(rng),
+            max_priority_ops_per_miniblock: self.sample(rng),
+            max_priority_ops_per_batch: self.sample(rng),
+            max_consecutive_priority_ops: self.sample(rng),
+            max_gas_per_tx: self.sample(rng),
+            max_pubdata_per_tx: self.sample(rng),
+            max_factory_deps_size_per_tx: self.sample(rng),
             max_single_tx_gas: self.sample(rng),
             max_allowed_l2_tx_gas_limit: self.sample(rng),
             reject_tx_at_geometry_percentage: self.sample(rng),
@@ -172,7 +221,15 @@ impl Distribution<configs::chain::StateKeeperConfig> for EncodeDist {
             virtual_blocks_interval: self.sample(rng),
             virtual_blocks_per_miniblock: self.sample(rng),
             enum_index_migration_chunk_size: self.sample(rng),
+            protective_reads_persistence_enabled: self.sample(rng),
+            seal_miniblock_on_shutdown: self.sample(rng),
+            ntp_server: self.sample(rng),
+            max_clock_skew_ms: self.sample(rng),
+            shadow_vm_execution: self.sample(rng),
+            batch_executor_process_isolation: self.sample(rng),
             max_circuits_per_batch: self.sample(rng),
+            additional_fee_account_addrs: self.sample_range(rng).map(|_| rng.gen()).collect(),
+            fee_account_rotation: self.sample(rng),
             // These values are not involved into files serialization skip them
             fee_account_addr: None,
             bootloader_hash: None,
@@ -210,6 +267,14 @@ impl Distribution<configs::chain::MempoolConfig> for EncodeDist {
             stuck_tx_timeout: self.sample(rng),
             remove_stuck_txs: self.sample(rng),
             delay_interval: self.sample(rng),
+            tx_deny_list: self.sample(rng),
+            tx_deny_list_reload_interval_sec: self.sample(rng),
+            tx_denied_calldata_prefixes: self.sample_collect(rng),
+            tx_deployment_only_windows: self.sample_collect(rng),
+            max_l2_transactions: self.sample(rng),
+            score_fee_weight: self.sample(rng),
+            score_age_weight: self.sample(rng),
+            score_rejection_penalty: self.sample(rng),
         }
     }
 }
@@ -239,6 +304,8 @@ impl Distribution<configs::ContractsConfig> for EncodeDist {
             l2_erc20_bridge_addr: g.gen(),
             l1_weth_bridge_proxy_addr: g.gen(),
             l2_weth_bridge_addr: g.gen(),
+            l1_shared_bridge_proxy_addr: g.gen(),
+            l2_shared_bridge_addr: g.gen(),
             l2_testnet_paymaster_addr: g.gen(),
             l1_multicall3_addr: g.gen(),
         }
@@ -255,6 +322,26 @@ impl Distribution<configs::database::MerkleTreeMode> for EncodeDist {
     }
 }
 
+impl Distribution<configs::database::MerkleTreeRocksdbPreset> for EncodeDist {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> configs::database::MerkleTreeRocksdbPreset {
+        type T = configs::database::MerkleTreeRocksdbPreset;
+        match rng.gen_range(0..2) {
+            0 => T::Archive,
+            _ => T::Pruned,
+        }
+    }
+}
+
+impl Distribution<configs::database::MerkleTreeCompactionStyle> for EncodeDist {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> configs::database::MerkleTreeCompactionStyle {
+        type T = configs::database::MerkleTreeCompactionStyle;
+        match rng.gen_range(0..2) {
+            0 => T::Level,
+            _ => T::Universal,
+        }
+    }
+}
+
 impl Distribution<configs::database::MerkleTreeConfig> for EncodeDist {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> configs::database::MerkleTreeConfig {
         configs::database::MerkleTreeConfig {
@@ -265,6 +352,17 @@ impl Distribution<configs::database::MerkleTreeConfig> for EncodeDist {
             memtable_capacity_mb: self.sample(rng),
             stalled_writes_timeout_sec: self.sample(rng),
             max_l1_batches_per_iter: self.sample(rng),
+            hashing_thread_count: self.sample(rng),
+            pruning_retention_sec: self.sample(rng),
+            consistency_check_interval_sec: self.sample(rng),
+            rocksdb_preset: self.sample_opt(|| self.sample(rng)),
+            compaction_style: self.sample_opt(|| self.sample(rng)),
+            separate_leaves_column_family: self.sample(rng),
+            snapshot_export_interval_sec: self.sample(rng),
+            // Non-zero lag is rejected by config validation.
+            lag_l1_batches: self.sample_opt(|| 0),
+            throttle_latency_threshold_ms: self.sample(rng),
+            in_memory: self.sample(rng),
         }
     }
 }
@@ -284,12 +382,26 @@ impl Distribution<configs::database::PostgresConfig> for EncodeDist {
             master_url: self.sample(rng),
             replica_url: self.sample(rng),
             prover_url: self.sample(rng),
+            read_replica_urls: self.sample_collect(rng),
+            max_replica_lag_ms: self.sample(rng),
             max_connections: self.sample(rng),
             max_connections_master: self.sample(rng),
+            max_connections_api: self.sample(rng),
+            max_connections_state_keeper: self.sample(rng),
+            max_connections_metadata_calculator: self.sample(rng),
+            max_connections_housekeeping: self.sample(rng),
             acquire_timeout_sec: self.sample(rng),
             statement_timeout_sec: self.sample(rng),
             long_connection_threshold_ms: self.sample(rng),
             slow_query_threshold_ms: self.sample(rng),
+            slow_query_explain_sample_rate: self.sample(rng),
+            query_timeout_ms: self.sample(rng),
+            component_query_timeouts_ms: self
+                .sample_range(rng)
+                .map(|_| (self.sample(rng), self.sample(rng)))
+                .collect(),
+            query_max_retries: self.sample(rng),
+            use_binary_copy: self.sample(rng),
             test_server_url: self.sample(rng),
             test_prover_url: self.sample(rng),
         }
@@ -303,6 +415,19 @@ impl Distribution<configs::ETHConfig> for EncodeDist {
             gas_adjuster: self.sample(rng),
             watcher: self.sample(rng),
             web3_url: self.sample(rng),
+            remote_signer: self.sample(rng),
+        }
+    }
+}
+
+impl Distribution<configs::eth_sender::RemoteSignerConfig> for EncodeDist {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> configs::eth_sender::RemoteSignerConfig {
+        configs::eth_sender::RemoteSignerConfig {
+            urls: self.sample_collect(rng),
+            operator_address: rng.gen(),
+            blob_operator_address: self.sample_opt(|| rng.gen()),
+            request_signing_key: self.sample_opt(|| rng.gen()),
+            request_timeout_ms: self.sample(rng),
         }
     }
 }
@@ -359,6 +484,8 @@ impl Distribution<configs::eth_sender::SenderConfig> for EncodeDist {
             max_acceptable_priority_fee_in_gwei: self.sample(rng),
             proof_loading_mode: self.sample(rng),
             pubdata_sending_mode: PubdataSendingMode::Calldata,
+            max_commit_blob_base_fee: self.sample(rng),
+            commit_blob_base_fee_deadline: self.sample(rng),
         }
     }
 }
@@ -387,6 +514,8 @@ impl Distribution<configs::ETHWatchConfig> for EncodeDist {
         configs::ETHWatchConfig {
             confirmations_for_eth_event: self.sample(rng),
             eth_node_poll_interval: self.sample(rng),
+            governance_alerts_webhook_url: self.sample(rng),
+            governance_alerts_lead_time_sec: self.sample(rng),
         }
     }
 }
@@ -624,6 +753,38 @@ impl Distribution<configs::SnapshotsCreatorConfig> for EncodeDist {
     }
 }
 
+impl Distribution<configs::BatchExporterConfig> for EncodeDist {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> configs::BatchExporterConfig {
+        configs::BatchExporterConfig {
+            poll_interval_ms: self.sample(rng),
+            object_store: self.sample(rng),
+        }
+    }
+}
+
+impl Distribution<configs::CallTracesOffloaderConfig> for EncodeDist {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> configs::CallTracesOffloaderConfig {
+        configs::CallTracesOffloaderConfig {
+            offload_after_sec: self.sample(rng),
+            poll_interval_ms: self.sample(rng),
+            object_store: self.sample(rng),
+        }
+    }
+}
+
+impl Distribution<configs::ChangeStreamConfig> for EncodeDist {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> configs::ChangeStreamConfig {
+        configs::ChangeStreamConfig {
+            nats_url: self.sample(rng),
+            nats_credentials_path: self.sample(rng),
+            nats_tls_required: self.sample(rng),
+            nats_root_certificates_path: self.sample(rng),
+            subject_prefix: self.sample(rng),
+            queue_capacity: self.sample(rng),
+        }
+    }
+}
+
 impl Distribution<configs::witness_generator::BasicWitnessGeneratorDataSource> for EncodeDist {
     fn sample<R: Rng + ?Sized>(
         &self,
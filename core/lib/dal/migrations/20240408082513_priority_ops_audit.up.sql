@@ -0,0 +1,20 @@
+CREATE TABLE IF NOT EXISTS priority_ops_audit
+(
+    id                BIGSERIAL PRIMARY KEY,
+    priority_op_id    BIGINT    NOT NULL,
+    tx_hash           BYTEA     NOT NULL,
+    event             TEXT      NOT NULL,
+    l1_tx_hash        BYTEA,
+    l1_block_number   INT,
+    miniblock_number  BIGINT,
+    index_in_block    INT,
+    execution_status  TEXT,
+    refunded_gas      BIGINT,
+    created_at        TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS priority_ops_audit_priority_op_id_idx ON priority_ops_audit (priority_op_id);
+CREATE INDEX IF NOT EXISTS priority_ops_audit_tx_hash_idx ON priority_ops_audit (tx_hash);
+-- A priority operation can only be received from L1 once, but may be executed (and rolled back) several times.
+CREATE UNIQUE INDEX IF NOT EXISTS priority_ops_audit_received_idx ON priority_ops_audit (priority_op_id)
+    WHERE event = 'received';
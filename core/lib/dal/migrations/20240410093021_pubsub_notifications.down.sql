@@ -0,0 +1,4 @@
+DROP TRIGGER IF EXISTS notify_new_transactions ON transactions;
+DROP FUNCTION IF EXISTS notify_new_transactions;
+DROP TRIGGER IF EXISTS notify_new_miniblocks ON miniblocks;
+DROP FUNCTION IF EXISTS notify_new_miniblocks;
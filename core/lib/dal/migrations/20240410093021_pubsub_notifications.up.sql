@@ -0,0 +1,28 @@
+-- Notifications used by the API server pub-sub as an alternative to polling. Notifications are sent
+-- once per statement and have an empty payload; they only signal that new data may be available.
+CREATE OR REPLACE FUNCTION notify_new_miniblocks() RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM pg_notify('new_miniblocks', '');
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER notify_new_miniblocks
+    AFTER INSERT ON miniblocks
+    FOR EACH STATEMENT EXECUTE FUNCTION notify_new_miniblocks();
+
+CREATE OR REPLACE FUNCTION notify_new_transactions() RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM pg_notify('new_transactions', '');
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER notify_new_transactions
+    AFTER INSERT ON transactions
+    FOR EACH STATEMENT EXECUTE FUNCTION notify_new_transactions();
+
+-- Triggers are disabled by default, so that writes don't send notifications if there are no listeners.
+-- They are enabled on server startup if DB notifications are enabled for pub-sub.
+ALTER TABLE miniblocks DISABLE TRIGGER notify_new_miniblocks;
+ALTER TABLE transactions DISABLE TRIGGER notify_new_transactions;
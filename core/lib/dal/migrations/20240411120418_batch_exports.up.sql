@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS batch_exports
+(
+    l1_batch_number BIGINT    PRIMARY KEY REFERENCES l1_batches (number) ON DELETE CASCADE,
+    schema_version  INT       NOT NULL,
+    created_at      TIMESTAMP NOT NULL,
+    updated_at      TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS batch_exports_schema_version_idx ON batch_exports (schema_version);
@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS custom_token_bridges
+(
+    l1_token_address  BYTEA     PRIMARY KEY,
+    -- If not set, the L2 token address is taken from the `tokens` table.
+    l2_token_address  BYTEA,
+    l1_bridge_address BYTEA     NOT NULL,
+    l2_bridge_address BYTEA     NOT NULL,
+    created_at        TIMESTAMP NOT NULL,
+    updated_at        TIMESTAMP NOT NULL
+);
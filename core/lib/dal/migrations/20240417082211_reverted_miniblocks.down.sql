@@ -0,0 +1 @@
+DROP TABLE IF EXISTS reverted_miniblocks;
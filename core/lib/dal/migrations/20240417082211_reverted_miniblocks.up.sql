@@ -0,0 +1,8 @@
+-- Hashes of miniblocks removed from the storage during a chain reorganization. Used to distinguish
+-- non-canonical block hashes from unknown ones in the API.
+CREATE TABLE IF NOT EXISTS reverted_miniblocks
+(
+    hash       BYTEA     PRIMARY KEY,
+    number     BIGINT    NOT NULL,
+    created_at TIMESTAMP NOT NULL
+);
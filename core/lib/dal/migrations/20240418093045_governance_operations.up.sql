@@ -0,0 +1,20 @@
+-- Governance operations scheduled on L1, tracked by the Ethereum watcher to give advance notice of upcoming changes.
+CREATE TABLE IF NOT EXISTS governance_operations
+(
+    id              BYTEA     PRIMARY KEY,
+    kind            TEXT      NOT NULL,
+    status          TEXT      NOT NULL,
+    l1_tx_hash      BYTEA     NOT NULL,
+    l1_block_number INT       NOT NULL,
+    -- L1 timestamps (in seconds) of the operation being scheduled, and it becoming ready for execution.
+    scheduled_at    BIGINT    NOT NULL,
+    ready_at        BIGINT    NOT NULL,
+    calls           JSONB     NOT NULL,
+    -- Last alert sent for the operation.
+    alert           TEXT,
+    created_at      TIMESTAMP NOT NULL,
+    updated_at      TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS governance_operations_pending_idx ON governance_operations (ready_at)
+    WHERE status = 'scheduled';
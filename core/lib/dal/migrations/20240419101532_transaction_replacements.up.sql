@@ -0,0 +1,13 @@
+-- Replacements of pending L2 transactions, used to notify API subscribers.
+CREATE TABLE IF NOT EXISTS transaction_replacements
+(
+    id                BIGSERIAL PRIMARY KEY,
+    replaced_tx_hash  BYTEA     NOT NULL,
+    tx_hash           BYTEA     NOT NULL,
+    initiator_address BYTEA     NOT NULL,
+    nonce             BIGINT    NOT NULL,
+    -- Time the replacing transaction was received at.
+    created_at        TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS transaction_replacements_created_at_idx ON transaction_replacements (created_at);
@@ -0,0 +1 @@
+DROP INDEX IF EXISTS l1_batches_protocol_version_idx;
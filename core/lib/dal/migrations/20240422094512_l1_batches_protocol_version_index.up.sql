@@ -0,0 +1,2 @@
+-- Used to look up the first L1 batch produced with each protocol version.
+CREATE INDEX IF NOT EXISTS l1_batches_protocol_version_idx ON l1_batches (protocol_version, number);
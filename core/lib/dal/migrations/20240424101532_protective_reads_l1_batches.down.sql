@@ -0,0 +1 @@
+DROP TABLE IF EXISTS protective_reads_l1_batches;
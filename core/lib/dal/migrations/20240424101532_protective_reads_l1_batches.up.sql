@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS protective_reads_l1_batches
+(
+    l1_batch_number BIGINT    PRIMARY KEY REFERENCES l1_batches (number) ON DELETE CASCADE,
+    created_at      TIMESTAMP NOT NULL,
+    updated_at      TIMESTAMP NOT NULL
+);
+
+-- Protective reads for all existing L1 batches were persisted by the state keeper.
+INSERT INTO protective_reads_l1_batches (l1_batch_number, created_at, updated_at)
+SELECT number, NOW(), NOW() FROM l1_batches
+ON CONFLICT (l1_batch_number) DO NOTHING;
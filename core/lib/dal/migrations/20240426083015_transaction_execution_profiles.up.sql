@@ -0,0 +1,15 @@
+-- Per-transaction resource usage recorded by the state keeper when sealing miniblocks.
+CREATE TABLE IF NOT EXISTS transaction_execution_profiles
+(
+    tx_hash                BYTEA     PRIMARY KEY REFERENCES transactions (hash) ON DELETE CASCADE,
+    miniblock_number       BIGINT    NOT NULL REFERENCES miniblocks (number) ON DELETE CASCADE,
+    computational_gas_used BIGINT    NOT NULL,
+    pubdata_published      BIGINT    NOT NULL,
+    circuit_statistic      JSONB     NOT NULL,
+    vm_execution_time_us   BIGINT    NOT NULL,
+    created_at             TIMESTAMP NOT NULL,
+    updated_at             TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS transaction_execution_profiles_miniblock_number_idx
+    ON transaction_execution_profiles (miniblock_number);
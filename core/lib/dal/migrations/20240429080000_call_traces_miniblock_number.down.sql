@@ -0,0 +1,8 @@
+ALTER TABLE call_traces DROP CONSTRAINT IF EXISTS call_traces_legacy_partition_bound;
+ALTER TABLE storage_logs DROP CONSTRAINT IF EXISTS storage_logs_legacy_partition_bound;
+ALTER TABLE events DROP CONSTRAINT IF EXISTS events_legacy_partition_bound;
+
+DROP PROCEDURE IF EXISTS backfill_call_traces_miniblock_number;
+DROP TRIGGER IF EXISTS call_traces_set_miniblock_number ON call_traces;
+DROP FUNCTION IF EXISTS call_traces_set_miniblock_number;
+ALTER TABLE call_traces DROP COLUMN IF EXISTS miniblock_number;
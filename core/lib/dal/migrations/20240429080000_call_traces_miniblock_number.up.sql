@@ -0,0 +1,89 @@
+-- Prepares `events`, `storage_logs` and `call_traces` to be converted to partitioned tables by
+-- the `partitioned_events_storage_logs_call_traces` migration. Work that requires scanning the tables
+-- is split across several migrations so that none of them holds locks blocking writes for long:
+--
+-- 1. This migration only changes the catalog: it adds a nullable `miniblock_number` column to `call_traces`
+--    and `NOT VALID` constraints establishing the bounds of the future legacy partitions.
+-- 2. `backfill_call_traces_miniblock_number` fills the new column in batches, each in a separate transaction.
+-- 3. `validate_legacy_partition_bounds` validates the constraints without blocking writes.
+-- 4. `call_traces_miniblock_number_index` builds the new primary key index for `call_traces` concurrently.
+
+ALTER TABLE call_traces ADD COLUMN IF NOT EXISTS miniblock_number BIGINT;
+
+-- Sets the miniblock number for call traces inserted by the server versions unaware of the column.
+-- Call traces are inserted after their transactions are marked as executed, so the miniblock number is known.
+CREATE OR REPLACE FUNCTION call_traces_set_miniblock_number() RETURNS TRIGGER AS $$
+BEGIN
+    NEW.miniblock_number := (SELECT miniblock_number FROM transactions WHERE hash = NEW.tx_hash);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER call_traces_set_miniblock_number
+    BEFORE INSERT ON call_traces
+    FOR EACH ROW
+    WHEN (NEW.miniblock_number IS NULL)
+    EXECUTE FUNCTION call_traces_set_miniblock_number();
+
+-- Backfills `call_traces.miniblock_number` in batches ordered by the primary key, committing after each batch.
+-- Call traces of transactions not included into a miniblock cannot be partitioned and are removed.
+CREATE OR REPLACE PROCEDURE backfill_call_traces_miniblock_number(batch_size INT) AS $$
+DECLARE
+    batch_start BYTEA := '\x'::bytea;
+    batch_end BYTEA;
+BEGIN
+    LOOP
+        SELECT MAX(tx_hash) INTO batch_end
+        FROM (
+            SELECT tx_hash FROM call_traces
+            WHERE tx_hash > batch_start
+            ORDER BY tx_hash
+            LIMIT batch_size
+        ) AS batch;
+        EXIT WHEN batch_end IS NULL;
+
+        UPDATE call_traces
+        SET miniblock_number = transactions.miniblock_number
+        FROM transactions
+        WHERE
+            transactions.hash = call_traces.tx_hash
+            AND call_traces.tx_hash > batch_start AND call_traces.tx_hash <= batch_end
+            AND call_traces.miniblock_number IS NULL;
+        DELETE FROM call_traces
+        WHERE
+            tx_hash > batch_start AND tx_hash <= batch_end
+            AND miniblock_number IS NULL;
+
+        batch_start := batch_end;
+        COMMIT;
+    END LOOP;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Constraints proving the partition constraint of the legacy partitions, so that attaching them doesn't require
+-- scanning the tables. `NOT VALID` constraints are enforced for new rows only, so adding them doesn't scan the tables either.
+-- The bound leaves room for miniblocks created while the remaining migrations are applied.
+DO $$
+DECLARE
+    legacy_end BIGINT;
+BEGIN
+    -- Storage logs may be present without miniblocks if the node was recovered from a snapshot.
+    SELECT (GREATEST(
+        (SELECT MAX(number) FROM miniblocks),
+        (SELECT MAX(miniblock_number) FROM storage_logs),
+        (SELECT MAX(miniblock_number) FROM events),
+        0
+    ) / 1000000 + 2) * 1000000 INTO legacy_end;
+    EXECUTE format(
+        'ALTER TABLE events ADD CONSTRAINT events_legacy_partition_bound CHECK (miniblock_number < %s) NOT VALID',
+        legacy_end
+    );
+    EXECUTE format(
+        'ALTER TABLE storage_logs ADD CONSTRAINT storage_logs_legacy_partition_bound CHECK (miniblock_number < %s) NOT VALID',
+        legacy_end
+    );
+    EXECUTE format(
+        'ALTER TABLE call_traces ADD CONSTRAINT call_traces_legacy_partition_bound CHECK (miniblock_number IS NOT NULL AND miniblock_number < %s) NOT VALID',
+        legacy_end
+    );
+END $$;
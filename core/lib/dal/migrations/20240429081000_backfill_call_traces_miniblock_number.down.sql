@@ -0,0 +1 @@
+-- Nothing to revert; the backfilled column is dropped when reverting the `call_traces_miniblock_number` migration.
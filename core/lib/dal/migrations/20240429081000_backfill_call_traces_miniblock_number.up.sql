@@ -0,0 +1,3 @@
+-- no-transaction
+-- Runs outside a transaction, so that the procedure can commit after each batch.
+CALL backfill_call_traces_miniblock_number(10000);
@@ -0,0 +1,4 @@
+-- Validating a constraint only takes a `SHARE UPDATE EXCLUSIVE` lock, so it doesn't block reads or writes.
+ALTER TABLE events VALIDATE CONSTRAINT events_legacy_partition_bound;
+ALTER TABLE storage_logs VALIDATE CONSTRAINT storage_logs_legacy_partition_bound;
+ALTER TABLE call_traces VALIDATE CONSTRAINT call_traces_legacy_partition_bound;
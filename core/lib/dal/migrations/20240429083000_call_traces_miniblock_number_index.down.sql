@@ -0,0 +1 @@
+DROP INDEX IF EXISTS call_traces_tx_hash_miniblock_number_idx;
@@ -0,0 +1,4 @@
+-- no-transaction
+-- Future primary key of `call_traces`; indexes cannot be built concurrently inside a transaction.
+CREATE UNIQUE INDEX CONCURRENTLY IF NOT EXISTS call_traces_tx_hash_miniblock_number_idx
+    ON call_traces (tx_hash, miniblock_number);
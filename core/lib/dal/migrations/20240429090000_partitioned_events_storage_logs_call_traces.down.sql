@@ -0,0 +1,44 @@
+-- Moves data from all partitions to the legacy ones, and converts the legacy partitions back to ordinary tables.
+DO $$
+DECLARE
+    tbl TEXT;
+    part TEXT;
+BEGIN
+    FOREACH tbl IN ARRAY ARRAY['events', 'storage_logs', 'call_traces'] LOOP
+        EXECUTE format('ALTER TABLE %I DETACH PARTITION %I', tbl, tbl || '_legacy');
+        FOR part IN
+            SELECT child.relname
+            FROM pg_inherits
+            JOIN pg_class parent ON pg_inherits.inhparent = parent.oid
+            JOIN pg_class child ON pg_inherits.inhrelid = child.oid
+            WHERE parent.relname = tbl
+        LOOP
+            EXECUTE format('INSERT INTO %I SELECT * FROM %I', tbl || '_legacy', part);
+        END LOOP;
+        EXECUTE format('DROP TABLE %I', tbl);
+        EXECUTE format('ALTER TABLE %I RENAME TO %I', tbl || '_legacy', tbl);
+    END LOOP;
+END $$;
+
+ALTER INDEX events_legacy_pkey RENAME TO events_pkey;
+ALTER INDEX events_legacy_address_block_event_index_in_block_index RENAME TO events_address_block_event_index_in_block_index;
+ALTER INDEX events_legacy_address_idx RENAME TO events_address_idx;
+ALTER INDEX events_legacy_block_number_tx_index RENAME TO events_block_number_tx_index;
+ALTER INDEX events_legacy_topic1_idx RENAME TO events_topic1_idx;
+ALTER INDEX events_legacy_topic2_idx RENAME TO events_topic2_idx;
+ALTER INDEX events_legacy_topic3_idx RENAME TO events_topic3_idx;
+ALTER INDEX events_legacy_topic4_idx RENAME TO events_topic4_idx;
+ALTER INDEX events_legacy_transfer_from RENAME TO events_transfer_from;
+ALTER INDEX events_legacy_transfer_to RENAME TO events_transfer_to;
+ALTER INDEX events_legacy_tx_hash_idx RENAME TO events_tx_hash_idx;
+ALTER INDEX events_legacy_tx_initiator_address_idx RENAME TO events_tx_initiator_address_idx;
+ALTER INDEX ix_events_legacy_t1 RENAME TO ix_events_t1;
+
+ALTER INDEX storage_logs_legacy_pkey RENAME TO storage_logs_pkey;
+ALTER INDEX storage_logs_legacy_block_number_idx RENAME TO storage_logs_block_number_idx;
+ALTER INDEX storage_logs_legacy_contract_address_tx_hash_idx_upd RENAME TO storage_logs_contract_address_tx_hash_idx_upd;
+
+-- The `miniblock_number` column is dropped when reverting the `call_traces_miniblock_number` migration.
+ALTER TABLE call_traces DROP CONSTRAINT call_traces_legacy_pkey;
+ALTER TABLE call_traces ALTER COLUMN miniblock_number DROP NOT NULL;
+ALTER TABLE call_traces ADD CONSTRAINT call_traces_pkey PRIMARY KEY (tx_hash);
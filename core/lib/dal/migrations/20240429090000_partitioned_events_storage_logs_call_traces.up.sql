@@ -0,0 +1,91 @@
+-- Converts `events`, `storage_logs` and `call_traces` to tables partitioned by miniblock number ranges,
+-- so that old data can be pruned by dropping partitions rather than deleting rows.
+--
+-- Existing tables are not copied; instead, each of them is attached as a `*_legacy` partition covering
+-- all miniblocks below the bound established by the `call_traces_miniblock_number` migration (a multiple of 1,000,000,
+-- the partition size used by `PartitionsDal`). Partitions for subsequent miniblocks are created by the DAL on demand.
+--
+-- The preceding migrations have backfilled the data, validated the constraints and built the indexes required here,
+-- so this migration only changes the catalog and doesn't scan the tables while holding exclusive locks on them.
+
+DROP TRIGGER call_traces_set_miniblock_number ON call_traces;
+DROP FUNCTION call_traces_set_miniblock_number;
+DROP PROCEDURE backfill_call_traces_miniblock_number;
+-- Implied by the validated `call_traces_legacy_partition_bound` constraint, so the table isn't scanned.
+ALTER TABLE call_traces ALTER COLUMN miniblock_number SET NOT NULL;
+ALTER TABLE call_traces DROP CONSTRAINT call_traces_pkey;
+ALTER TABLE call_traces ADD CONSTRAINT call_traces_pkey PRIMARY KEY USING INDEX call_traces_tx_hash_miniblock_number_idx;
+
+ALTER TABLE events RENAME TO events_legacy;
+ALTER INDEX events_pkey RENAME TO events_legacy_pkey;
+ALTER INDEX events_address_block_event_index_in_block_index RENAME TO events_legacy_address_block_event_index_in_block_index;
+ALTER INDEX events_address_idx RENAME TO events_legacy_address_idx;
+ALTER INDEX events_block_number_tx_index RENAME TO events_legacy_block_number_tx_index;
+ALTER INDEX events_topic1_idx RENAME TO events_legacy_topic1_idx;
+ALTER INDEX events_topic2_idx RENAME TO events_legacy_topic2_idx;
+ALTER INDEX events_topic3_idx RENAME TO events_legacy_topic3_idx;
+ALTER INDEX events_topic4_idx RENAME TO events_legacy_topic4_idx;
+ALTER INDEX events_transfer_from RENAME TO events_legacy_transfer_from;
+ALTER INDEX events_transfer_to RENAME TO events_legacy_transfer_to;
+ALTER INDEX events_tx_hash_idx RENAME TO events_legacy_tx_hash_idx;
+ALTER INDEX events_tx_initiator_address_idx RENAME TO events_legacy_tx_initiator_address_idx;
+ALTER INDEX ix_events_t1 RENAME TO ix_events_legacy_t1;
+CREATE TABLE events (LIKE events_legacy INCLUDING DEFAULTS) PARTITION BY RANGE (miniblock_number);
+
+ALTER TABLE storage_logs RENAME TO storage_logs_legacy;
+ALTER INDEX storage_logs_pkey RENAME TO storage_logs_legacy_pkey;
+ALTER INDEX storage_logs_block_number_idx RENAME TO storage_logs_legacy_block_number_idx;
+ALTER INDEX storage_logs_contract_address_tx_hash_idx_upd RENAME TO storage_logs_legacy_contract_address_tx_hash_idx_upd;
+CREATE TABLE storage_logs (LIKE storage_logs_legacy INCLUDING DEFAULTS) PARTITION BY RANGE (miniblock_number);
+
+ALTER TABLE call_traces RENAME TO call_traces_legacy;
+ALTER INDEX call_traces_pkey RENAME TO call_traces_legacy_pkey;
+CREATE TABLE call_traces (LIKE call_traces_legacy INCLUDING DEFAULTS) PARTITION BY RANGE (miniblock_number);
+
+DO $$
+DECLARE
+    legacy_end BIGINT;
+BEGIN
+    SELECT (regexp_match(pg_get_constraintdef(oid), '< (\d+)'))[1]::BIGINT INTO legacy_end
+    FROM pg_constraint
+    WHERE conname = 'events_legacy_partition_bound';
+    -- Validated constraints on the legacy tables imply the partition constraints, so attaching doesn't scan the tables.
+    EXECUTE format('ALTER TABLE events ATTACH PARTITION events_legacy FOR VALUES FROM (MINVALUE) TO (%s)', legacy_end);
+    EXECUTE format('ALTER TABLE storage_logs ATTACH PARTITION storage_logs_legacy FOR VALUES FROM (MINVALUE) TO (%s)', legacy_end);
+    EXECUTE format('ALTER TABLE call_traces ATTACH PARTITION call_traces_legacy FOR VALUES FROM (MINVALUE) TO (%s)', legacy_end);
+END $$;
+
+-- Redundant with the partition constraints.
+ALTER TABLE events_legacy DROP CONSTRAINT events_legacy_partition_bound;
+ALTER TABLE storage_logs_legacy DROP CONSTRAINT storage_logs_legacy_partition_bound;
+ALTER TABLE call_traces_legacy DROP CONSTRAINT call_traces_legacy_partition_bound;
+
+-- Indexes and constraints on the partitioned tables attach the matching ones on the legacy partitions
+-- instead of building them anew. In particular, Postgres doesn't support `NOT VALID` foreign keys on partitioned tables;
+-- the foreign keys below attach the equivalent foreign keys of the legacy tables, which are already validated.
+ALTER TABLE events ADD CONSTRAINT events_pkey PRIMARY KEY (miniblock_number, event_index_in_block);
+CREATE INDEX events_address_block_event_index_in_block_index ON events (address, miniblock_number, event_index_in_block);
+CREATE INDEX events_address_idx ON events (address);
+CREATE INDEX events_block_number_tx_index ON events (miniblock_number, tx_index_in_block);
+CREATE INDEX events_topic1_idx ON events (topic1);
+CREATE INDEX events_topic2_idx ON events (topic2);
+CREATE INDEX events_topic3_idx ON events (topic3);
+CREATE INDEX events_topic4_idx ON events (topic4);
+CREATE INDEX events_transfer_from ON events (topic2, miniblock_number, tx_index_in_block)
+    WHERE topic1 = '\xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef'::bytea;
+CREATE INDEX events_transfer_to ON events (topic3, miniblock_number, tx_index_in_block)
+    WHERE topic1 = '\xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef'::bytea;
+CREATE INDEX events_tx_hash_idx ON events USING hash (tx_hash);
+CREATE INDEX events_tx_initiator_address_idx ON events (tx_initiator_address);
+CREATE INDEX ix_events_t1 ON events (topic1, address, tx_hash);
+ALTER TABLE events ADD CONSTRAINT events_miniblock_number_fkey
+    FOREIGN KEY (miniblock_number) REFERENCES miniblocks (number);
+
+ALTER TABLE storage_logs ADD CONSTRAINT storage_logs_pkey PRIMARY KEY (hashed_key, miniblock_number, operation_number);
+CREATE INDEX storage_logs_block_number_idx ON storage_logs (miniblock_number);
+CREATE INDEX storage_logs_contract_address_tx_hash_idx_upd ON storage_logs (tx_hash)
+    WHERE address = '\x0000000000000000000000000000000000008002'::bytea;
+
+ALTER TABLE call_traces ADD CONSTRAINT call_traces_pkey PRIMARY KEY (tx_hash, miniblock_number);
+ALTER TABLE call_traces ADD CONSTRAINT call_traces_tx_hash_fkey
+    FOREIGN KEY (tx_hash) REFERENCES transactions (hash) ON DELETE CASCADE;
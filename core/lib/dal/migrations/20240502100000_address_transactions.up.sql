@@ -0,0 +1,19 @@
+-- Index of executed transactions by addresses of their senders and recipients maintained by the state keeper
+-- when sealing miniblocks. Allows paging through the transaction history of an address.
+-- Transactions executed before the index was introduced are backfilled by the `address_transactions`
+-- online migration.
+CREATE TABLE IF NOT EXISTS address_transactions
+(
+    address          BYTEA     NOT NULL,
+    miniblock_number BIGINT    NOT NULL REFERENCES miniblocks (number) ON DELETE CASCADE,
+    index_in_block   INT       NOT NULL,
+    tx_hash          BYTEA     NOT NULL,
+    is_sender        BOOLEAN   NOT NULL,
+    is_recipient     BOOLEAN   NOT NULL,
+    created_at       TIMESTAMP NOT NULL,
+    updated_at       TIMESTAMP NOT NULL,
+    PRIMARY KEY (address, miniblock_number, index_in_block)
+);
+
+CREATE INDEX IF NOT EXISTS address_transactions_miniblock_number_idx
+    ON address_transactions (miniblock_number);
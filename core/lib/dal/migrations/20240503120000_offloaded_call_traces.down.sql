@@ -0,0 +1 @@
+DROP TABLE IF EXISTS offloaded_call_traces_l1_batches;
@@ -0,0 +1,7 @@
+-- L1 batches whose call traces were moved from Postgres to the object store by the call traces offloader.
+CREATE TABLE IF NOT EXISTS offloaded_call_traces_l1_batches
+(
+    l1_batch_number BIGINT    PRIMARY KEY REFERENCES l1_batches (number) ON DELETE CASCADE,
+    created_at      TIMESTAMP NOT NULL,
+    updated_at      TIMESTAMP NOT NULL
+);
@@ -0,0 +1,17 @@
+-- Index of token holders maintained by the state keeper from ERC-20 `Transfer` events when sealing miniblocks.
+-- Allows to get balances of an account only for tokens it has ever held, rather than for all known tokens.
+-- Holders from miniblocks sealed before the index was introduced are backfilled by the `token_balance_holders`
+-- online migration.
+CREATE TABLE IF NOT EXISTS token_balance_holders
+(
+    holder_address   BYTEA     NOT NULL,
+    token_address    BYTEA     NOT NULL,
+    -- Miniblock in which the holder has received or sent the token for the first time.
+    miniblock_number BIGINT    NOT NULL REFERENCES miniblocks (number) ON DELETE CASCADE,
+    created_at       TIMESTAMP NOT NULL,
+    updated_at       TIMESTAMP NOT NULL,
+    PRIMARY KEY (holder_address, token_address)
+);
+
+CREATE INDEX IF NOT EXISTS token_balance_holders_miniblock_number_idx
+    ON token_balance_holders (miniblock_number);
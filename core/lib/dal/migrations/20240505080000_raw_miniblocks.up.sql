@@ -0,0 +1,10 @@
+-- Compressed self-contained representations of miniblocks (transactions + execution results) persisted
+-- in the background after sealing miniblocks. Used to serve `zks_getRawBlock` without reconstructing blocks
+-- from normalized tables.
+CREATE TABLE IF NOT EXISTS raw_miniblocks
+(
+    number     BIGINT    NOT NULL PRIMARY KEY REFERENCES miniblocks (number) ON DELETE CASCADE,
+    data       BYTEA     NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP NOT NULL
+);
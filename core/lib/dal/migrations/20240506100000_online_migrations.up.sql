@@ -0,0 +1,13 @@
+-- State of online migrations, i.e. long-running rewrites of large tables performed in the background
+-- by the `OnlineMigrations` server component without downtime.
+CREATE TABLE IF NOT EXISTS online_migrations
+(
+    name           TEXT      NOT NULL PRIMARY KEY,
+    -- One of `backfilling`, `backfill_completed` or `cut_over`.
+    status         TEXT      NOT NULL,
+    -- Key of the last backfilled row; its interpretation is up to the migration. `NULL` if the backfill hasn't started.
+    cursor         BIGINT,
+    processed_rows BIGINT    NOT NULL DEFAULT 0,
+    created_at     TIMESTAMP NOT NULL,
+    updated_at     TIMESTAMP NOT NULL
+);
@@ -0,0 +1 @@
+DROP INDEX IF EXISTS storage_logs_legacy_address_key_idx;
@@ -0,0 +1,6 @@
+-- no-transaction
+-- Builds the index for account-scoped storage key enumeration on the legacy partition of `storage_logs`, which holds
+-- the bulk of the data, without blocking writes. The next migration attaches it to the index on the partitioned table;
+-- indexes cannot be built concurrently inside a transaction.
+CREATE INDEX CONCURRENTLY IF NOT EXISTS storage_logs_legacy_address_key_idx
+    ON storage_logs_legacy (address, key, miniblock_number);
@@ -0,0 +1,3 @@
+-- Attaches the index built by the previous migration on the legacy partition, and builds it on the remaining
+-- (much smaller) partitions. Partitions created later inherit the index.
+CREATE INDEX IF NOT EXISTS storage_logs_address_key_idx ON storage_logs (address, key, miniblock_number);
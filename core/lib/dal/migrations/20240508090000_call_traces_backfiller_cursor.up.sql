@@ -0,0 +1,9 @@
+CREATE TABLE call_traces_backfiller_info
+(
+    last_processed_l1_batch BIGINT NOT NULL,
+    created_at              TIMESTAMP NOT NULL,
+    updated_at              TIMESTAMP NOT NULL
+);
+
+INSERT INTO call_traces_backfiller_info(last_processed_l1_batch, created_at, updated_at)
+VALUES (0, NOW(), NOW());
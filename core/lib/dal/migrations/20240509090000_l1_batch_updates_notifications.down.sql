@@ -0,0 +1,3 @@
+DROP TRIGGER IF EXISTS notify_confirmed_eth_txs ON eth_txs_history;
+DROP TRIGGER IF EXISTS notify_sealed_l1_batches ON l1_batches;
+DROP FUNCTION IF EXISTS notify_l1_batch_updates;
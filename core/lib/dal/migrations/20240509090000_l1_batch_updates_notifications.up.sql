@@ -0,0 +1,21 @@
+-- Notifications used by the API server pub-sub for L1 batch lifecycle updates. Sent when an L1 batch is sealed,
+-- and when an L1 transaction for batches is confirmed (i.e., batches are committed, proven or executed).
+CREATE OR REPLACE FUNCTION notify_l1_batch_updates() RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM pg_notify('l1_batch_updates', '');
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER notify_sealed_l1_batches
+    AFTER INSERT ON l1_batches
+    FOR EACH STATEMENT EXECUTE FUNCTION notify_l1_batch_updates();
+
+-- Confirmed L1 transactions are either inserted as confirmed (on the external node), or confirmed later
+-- (by the main node `eth_sender`).
+CREATE TRIGGER notify_confirmed_eth_txs
+    AFTER INSERT OR UPDATE OF confirmed_at ON eth_txs_history
+    FOR EACH STATEMENT EXECUTE FUNCTION notify_l1_batch_updates();
+
+ALTER TABLE l1_batches DISABLE TRIGGER notify_sealed_l1_batches;
+ALTER TABLE eth_txs_history DISABLE TRIGGER notify_confirmed_eth_txs;
@@ -0,0 +1,4 @@
+ALTER TABLE miniblocks DISABLE TRIGGER notify_new_miniblocks;
+ALTER TABLE transactions DISABLE TRIGGER notify_new_transactions;
+ALTER TABLE l1_batches DISABLE TRIGGER notify_sealed_l1_batches;
+ALTER TABLE eth_txs_history DISABLE TRIGGER notify_confirmed_eth_txs;
@@ -0,0 +1,8 @@
+-- Notifying triggers are enabled unconditionally instead of being toggled on server startup, which required
+-- exclusive locks on the affected tables and allowed a single server to disable notifications for all servers.
+-- Notifications are sent once per statement with an empty payload, so their overhead is negligible even
+-- if there are no listeners.
+ALTER TABLE miniblocks ENABLE TRIGGER notify_new_miniblocks;
+ALTER TABLE transactions ENABLE TRIGGER notify_new_transactions;
+ALTER TABLE l1_batches ENABLE TRIGGER notify_sealed_l1_batches;
+ALTER TABLE eth_txs_history ENABLE TRIGGER notify_confirmed_eth_txs;
@@ -0,0 +1,11 @@
+-- Access-list-like hints attached to L2 transactions on submission. Hints are consumed by the state keeper mempool
+-- once the corresponding transaction is loaded, so the table only holds hints for pending transactions.
+-- Hints are keyed by the transaction hash without a foreign key since a transaction with the same
+-- initiator and nonce can be replaced in place, changing its hash.
+CREATE TABLE IF NOT EXISTS transaction_access_hints
+(
+    tx_hash    BYTEA PRIMARY KEY,
+    addresses  BYTEA[]   NOT NULL,
+    keys       BYTEA[]   NOT NULL,
+    created_at TIMESTAMP NOT NULL
+);
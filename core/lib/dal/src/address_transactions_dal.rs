@@ -0,0 +1,547 @@
+//! Index of executed transactions by sender and recipient addresses.
+
+use std::ops;
+
+use zksync_db_connection::{
+    connection::Connection,
+    error::DalResult,
+    instrument::{InstrumentExt, Instrumented},
+};
+use zksync_types::{tx::TransactionExecutionResult, Address, MiniblockNumber, H256};
+
+use crate::Core;
+
+/// Position of an executed transaction in the chain. Used as a cursor for paging through
+/// [address transactions](AddressTransactionsDal::get_address_transactions()).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TxPosition {
+    pub miniblock_number: MiniblockNumber,
+    pub index_in_block: u32,
+}
+
+/// Filter for the direction of address transactions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum AddressTxsFilter {
+    /// Transactions both sent and received by the address.
+    #[default]
+    All,
+    /// Transactions sent (i.e., initiated) by the address.
+    Sent,
+    /// Transactions received by the address (i.e., with the address as the `to` field).
+    Received,
+}
+
+impl AddressTxsFilter {
+    fn includes_sent(self) -> bool {
+        matches!(self, Self::All | Self::Sent)
+    }
+
+    fn includes_received(self) -> bool {
+        matches!(self, Self::All | Self::Received)
+    }
+}
+
+/// Executed transaction related to a certain address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressTransaction {
+    pub tx_hash: H256,
+    pub position: TxPosition,
+    /// Whether the address is the transaction initiator.
+    pub is_sender: bool,
+    /// Whether the address is the transaction recipient.
+    pub is_recipient: bool,
+}
+
+/// Maintains the index of executed transactions by sender and recipient addresses. The index is updated
+/// when sealing miniblocks and is cleaned up automatically when miniblocks are reverted or pruned.
+/// Transactions executed before the index was introduced are backfilled by the `address_transactions`
+/// online migration; until it is cut over, the index may be incomplete.
+#[derive(Debug)]
+pub struct AddressTransactionsDal<'a, 'c> {
+    pub(crate) storage: &'a mut Connection<'c, Core>,
+}
+
+impl AddressTransactionsDal<'_, '_> {
+    /// Indexes all transactions executed in the specified miniblock. Transactions must be provided
+    /// in the execution order.
+    pub async fn insert_address_transactions(
+        &mut self,
+        miniblock_number: MiniblockNumber,
+        transactions: &[TransactionExecutionResult],
+    ) -> DalResult<()> {
+        if transactions.is_empty() {
+            return Ok(());
+        }
+
+        let mut addresses = Vec::with_capacity(transactions.len() * 2);
+        let mut indices_in_block = Vec::with_capacity(transactions.len() * 2);
+        let mut hashes = Vec::with_capacity(transactions.len() * 2);
+        let mut is_sender = Vec::with_capacity(transactions.len() * 2);
+        let mut is_recipient = Vec::with_capacity(transactions.len() * 2);
+        for (index_in_block, tx_res) in transactions.iter().enumerate() {
+            let initiator = tx_res.transaction.initiator_account();
+            let recipient = tx_res.transaction.recipient_account();
+
+            addresses.push(initiator.as_bytes());
+            indices_in_block.push(index_in_block as i32);
+            hashes.push(tx_res.hash.as_bytes());
+            is_sender.push(true);
+            is_recipient.push(initiator == recipient);
+            if initiator != recipient {
+                addresses.push(recipient.as_bytes());
+                indices_in_block.push(index_in_block as i32);
+                hashes.push(tx_res.hash.as_bytes());
+                is_sender.push(false);
+                is_recipient.push(true);
+            }
+        }
+
+        let instrumentation = Instrumented::new("insert_address_transactions")
+            .with_arg("miniblock_number", &miniblock_number)
+            .with_arg("transactions.len", &transactions.len());
+        let query = sqlx::query!(
+            r#"
+            INSERT INTO
+                address_transactions (
+                    address,
+                    miniblock_number,
+                    index_in_block,
+                    tx_hash,
+                    is_sender,
+                    is_recipient,
+                    created_at,
+                    updated_at
+                )
+            SELECT
+                u.address,
+                $1,
+                u.index_in_block,
+                u.tx_hash,
+                u.is_sender,
+                u.is_recipient,
+                NOW(),
+                NOW()
+            FROM
+                UNNEST($2::bytea[], $3::INT[], $4::bytea[], $5::BOOLEAN[], $6::BOOLEAN[]) AS u (
+                    address,
+                    index_in_block,
+                    tx_hash,
+                    is_sender,
+                    is_recipient
+                )
+            ON CONFLICT (address, miniblock_number, index_in_block) DO
+            UPDATE
+            SET
+                tx_hash = excluded.tx_hash,
+                is_sender = excluded.is_sender,
+                is_recipient = excluded.is_recipient,
+                updated_at = NOW()
+            "#,
+            i64::from(miniblock_number.0),
+            &addresses as &[&[u8]],
+            &indices_in_block,
+            &hashes as &[&[u8]],
+            &is_sender,
+            &is_recipient,
+        );
+
+        instrumentation.with(query).execute(self.storage).await?;
+        Ok(())
+    }
+
+    /// Returns the number of the miniblock containing the `(max_transactions + 1)`th executed transaction
+    /// in the specified miniblock range, or `None` if the range contains fewer transactions. Used to split
+    /// the backfill of the index into batches.
+    pub async fn get_transactions_batch_bound(
+        &mut self,
+        miniblock_range: ops::RangeInclusive<MiniblockNumber>,
+        max_transactions: usize,
+    ) -> DalResult<Option<MiniblockNumber>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                miniblock_number AS "miniblock_number!"
+            FROM
+                transactions
+            WHERE
+                miniblock_number BETWEEN $1 AND $2
+            ORDER BY
+                miniblock_number,
+                index_in_block
+            OFFSET
+                $3
+            LIMIT
+                1
+            "#,
+            i64::from(miniblock_range.start().0),
+            i64::from(miniblock_range.end().0),
+            max_transactions as i64
+        )
+        .instrument("get_transactions_batch_bound")
+        .with_arg("miniblock_range", &miniblock_range)
+        .with_arg("max_transactions", &max_transactions)
+        .report_latency()
+        .fetch_optional(self.storage)
+        .await?;
+
+        Ok(row.map(|row| MiniblockNumber(row.miniblock_number as u32)))
+    }
+
+    /// Indexes transactions persisted for the specified miniblock range. Returns the number of inserted
+    /// index entries.
+    ///
+    /// Unlike [`Self::insert_address_transactions()`], this method may run concurrently with indexing newer
+    /// miniblocks, so it leaves already indexed entries intact.
+    pub async fn backfill_address_transactions(
+        &mut self,
+        miniblock_range: ops::RangeInclusive<MiniblockNumber>,
+    ) -> DalResult<u64> {
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO
+                address_transactions (
+                    address,
+                    miniblock_number,
+                    index_in_block,
+                    tx_hash,
+                    is_sender,
+                    is_recipient,
+                    created_at,
+                    updated_at
+                )
+            SELECT
+                initiator_address,
+                miniblock_number,
+                index_in_block,
+                hash,
+                TRUE,
+                contract_address IS NOT DISTINCT FROM initiator_address,
+                NOW(),
+                NOW()
+            FROM
+                transactions
+            WHERE
+                miniblock_number BETWEEN $1 AND $2
+                AND index_in_block IS NOT NULL
+            UNION ALL
+            SELECT
+                contract_address,
+                miniblock_number,
+                index_in_block,
+                hash,
+                FALSE,
+                TRUE,
+                NOW(),
+                NOW()
+            FROM
+                transactions
+            WHERE
+                miniblock_number BETWEEN $1 AND $2
+                AND index_in_block IS NOT NULL
+                AND contract_address IS NOT NULL
+                AND contract_address <> initiator_address
+            ON CONFLICT (address, miniblock_number, index_in_block) DO NOTHING
+            "#,
+            i64::from(miniblock_range.start().0),
+            i64::from(miniblock_range.end().0)
+        )
+        .instrument("backfill_address_transactions")
+        .with_arg("miniblock_range", &miniblock_range)
+        .report_latency()
+        .execute(self.storage)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Returns a page of transactions related to the specified address, ordered from the newest to the oldest.
+    /// The page starts with the transaction immediately preceding `before` (or the newest transaction
+    /// if `before` is not specified) and contains at most `limit` transactions. To get the next page,
+    /// pass the position of the last returned transaction as `before`.
+    pub async fn get_address_transactions(
+        &mut self,
+        address: Address,
+        filter: AddressTxsFilter,
+        before: Option<TxPosition>,
+        limit: usize,
+    ) -> DalResult<Vec<AddressTransaction>> {
+        let (before_miniblock, before_index) = before.map_or((i64::MAX, i32::MAX), |position| {
+            (
+                i64::from(position.miniblock_number.0),
+                position.index_in_block as i32,
+            )
+        });
+
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                miniblock_number,
+                index_in_block,
+                tx_hash,
+                is_sender,
+                is_recipient
+            FROM
+                address_transactions
+            WHERE
+                address = $1
+                AND (miniblock_number, index_in_block) < ($2, $3)
+                AND (
+                    (
+                        is_sender
+                        AND $4
+                    )
+                    OR (
+                        is_recipient
+                        AND $5
+                    )
+                )
+            ORDER BY
+                miniblock_number DESC,
+                index_in_block DESC
+            LIMIT
+                $6
+            "#,
+            address.as_bytes(),
+            before_miniblock,
+            before_index,
+            filter.includes_sent(),
+            filter.includes_received(),
+            limit as i64
+        )
+        .instrument("get_address_transactions")
+        .with_arg("address", &address)
+        .with_arg("filter", &filter)
+        .with_arg("before", &before)
+        .with_arg("limit", &limit)
+        .fetch_all(self.storage)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| AddressTransaction {
+                tx_hash: H256::from_slice(&row.tx_hash),
+                position: TxPosition {
+                    miniblock_number: MiniblockNumber(row.miniblock_number as u32),
+                    index_in_block: row.index_in_block as u32,
+                },
+                is_sender: row.is_sender,
+                is_recipient: row.is_recipient,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_types::{fee::TransactionExecutionMetrics, Nonce, ProtocolVersion};
+
+    use super::*;
+    use crate::{
+        tests::{create_miniblock_header, mock_execution_result, mock_l2_transaction},
+        ConnectionPool, Core, CoreDal,
+    };
+
+    #[tokio::test]
+    async fn paging_through_address_transactions() {
+        let pool = ConnectionPool::<Core>::test_pool().await;
+        let mut conn = pool.connection().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(&ProtocolVersion::default())
+            .await
+            .unwrap();
+
+        let address = Address::repeat_byte(0x11);
+        let other_address = Address::repeat_byte(0x22);
+        let mut expected_txs = vec![];
+        for miniblock_number in 1..=3 {
+            conn.blocks_dal()
+                .insert_miniblock(&create_miniblock_header(miniblock_number))
+                .await
+                .unwrap();
+
+            let mut sent_tx = mock_l2_transaction();
+            sent_tx.common_data.initiator_address = address;
+            sent_tx.execute.contract_address = other_address;
+            let mut received_tx = mock_l2_transaction();
+            received_tx.execute.contract_address = address;
+            let unrelated_tx = mock_l2_transaction();
+
+            let tx_results: Vec<_> = [sent_tx, received_tx, unrelated_tx]
+                .into_iter()
+                .map(mock_execution_result)
+                .collect();
+            conn.address_transactions_dal()
+                .insert_address_transactions(MiniblockNumber(miniblock_number), &tx_results)
+                .await
+                .unwrap();
+
+            for (index_in_block, is_sender) in [(0, true), (1, false)] {
+                expected_txs.push(AddressTransaction {
+                    tx_hash: tx_results[index_in_block].hash,
+                    position: TxPosition {
+                        miniblock_number: MiniblockNumber(miniblock_number),
+                        index_in_block: index_in_block as u32,
+                    },
+                    is_sender,
+                    is_recipient: !is_sender,
+                });
+            }
+        }
+        expected_txs.reverse();
+
+        let all_txs = conn
+            .address_transactions_dal()
+            .get_address_transactions(address, AddressTxsFilter::All, None, 100)
+            .await
+            .unwrap();
+        assert_eq!(all_txs, expected_txs);
+
+        let mut paged_txs = vec![];
+        let mut before = None;
+        loop {
+            let page = conn
+                .address_transactions_dal()
+                .get_address_transactions(address, AddressTxsFilter::All, before, 4)
+                .await
+                .unwrap();
+            let Some(last_tx) = page.last() else {
+                break;
+            };
+            before = Some(last_tx.position);
+            paged_txs.extend(page);
+        }
+        assert_eq!(paged_txs, expected_txs);
+
+        let sent_txs = conn
+            .address_transactions_dal()
+            .get_address_transactions(address, AddressTxsFilter::Sent, None, 100)
+            .await
+            .unwrap();
+        let expected_sent_txs: Vec<_> = expected_txs
+            .iter()
+            .filter(|tx| tx.is_sender)
+            .cloned()
+            .collect();
+        assert_eq!(sent_txs, expected_sent_txs);
+
+        let received_txs = conn
+            .address_transactions_dal()
+            .get_address_transactions(other_address, AddressTxsFilter::Received, None, 100)
+            .await
+            .unwrap();
+        assert_eq!(received_txs.len(), 3);
+        assert!(received_txs
+            .iter()
+            .all(|tx| tx.is_recipient && !tx.is_sender));
+
+        // Transactions must be removed together with the miniblock.
+        conn.blocks_dal()
+            .delete_miniblocks(MiniblockNumber(1))
+            .await
+            .unwrap();
+        let remaining_txs = conn
+            .address_transactions_dal()
+            .get_address_transactions(address, AddressTxsFilter::All, None, 100)
+            .await
+            .unwrap();
+        assert_eq!(remaining_txs, expected_txs[4..]);
+    }
+
+    #[tokio::test]
+    async fn backfilling_address_transactions() {
+        let pool = ConnectionPool::<Core>::test_pool().await;
+        let mut conn = pool.connection().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(&ProtocolVersion::default())
+            .await
+            .unwrap();
+
+        let address = Address::repeat_byte(0x11);
+        let mut tx_results_by_miniblock = vec![];
+        for miniblock_number in 1..=3 {
+            conn.blocks_dal()
+                .insert_miniblock(&create_miniblock_header(miniblock_number))
+                .await
+                .unwrap();
+
+            let mut sent_tx = mock_l2_transaction();
+            sent_tx.common_data.initiator_address = address;
+            sent_tx.common_data.nonce = Nonce(miniblock_number);
+            let mut received_tx = mock_l2_transaction();
+            received_tx.execute.contract_address = address;
+            for tx in [&sent_tx, &received_tx] {
+                conn.transactions_dal()
+                    .insert_transaction_l2(tx, TransactionExecutionMetrics::default())
+                    .await
+                    .unwrap();
+            }
+            let tx_results: Vec<_> = [sent_tx, received_tx]
+                .into_iter()
+                .map(mock_execution_result)
+                .collect();
+            conn.transactions_dal()
+                .mark_txs_as_executed_in_miniblock(
+                    MiniblockNumber(miniblock_number),
+                    &tx_results,
+                    1.into(),
+                )
+                .await
+                .unwrap();
+            tx_results_by_miniblock.push(tx_results);
+        }
+        // Emulate the last miniblock being indexed by the state keeper before the backfill.
+        conn.address_transactions_dal()
+            .insert_address_transactions(MiniblockNumber(3), &tx_results_by_miniblock[2])
+            .await
+            .unwrap();
+
+        let all_miniblocks = MiniblockNumber(1)..=MiniblockNumber(3);
+        let bound = conn
+            .address_transactions_dal()
+            .get_transactions_batch_bound(all_miniblocks.clone(), 3)
+            .await
+            .unwrap();
+        assert_eq!(bound, Some(MiniblockNumber(2)));
+        let bound = conn
+            .address_transactions_dal()
+            .get_transactions_batch_bound(all_miniblocks.clone(), 6)
+            .await
+            .unwrap();
+        assert_eq!(bound, None);
+
+        let inserted_count = conn
+            .address_transactions_dal()
+            .backfill_address_transactions(MiniblockNumber(1)..=MiniblockNumber(2))
+            .await
+            .unwrap();
+        // Each transaction is indexed both for its sender and its recipient.
+        assert_eq!(inserted_count, 8);
+        // Backfilling is idempotent and doesn't touch miniblocks indexed by the state keeper.
+        let inserted_count = conn
+            .address_transactions_dal()
+            .backfill_address_transactions(all_miniblocks)
+            .await
+            .unwrap();
+        assert_eq!(inserted_count, 0);
+
+        let all_txs = conn
+            .address_transactions_dal()
+            .get_address_transactions(address, AddressTxsFilter::All, None, 100)
+            .await
+            .unwrap();
+        let expected_hashes: Vec<_> = tx_results_by_miniblock
+            .iter()
+            .flatten()
+            .rev()
+            .map(|tx_result| tx_result.hash)
+            .collect();
+        let hashes: Vec<_> = all_txs.iter().map(|tx| tx.tx_hash).collect();
+        assert_eq!(hashes, expected_hashes);
+        for (i, tx) in all_txs.iter().enumerate() {
+            // Received transactions have odd indices in each miniblock, and go first in the reversed order.
+            let is_sender = i % 2 == 1;
+            assert_eq!(tx.is_sender, is_sender, "{tx:?}");
+            assert_eq!(tx.is_recipient, !is_sender, "{tx:?}");
+        }
+    }
+}
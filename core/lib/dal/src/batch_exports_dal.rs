@@ -0,0 +1,462 @@
+use zksync_db_connection::{connection::Connection, error::DalResult, instrument::InstrumentExt};
+use zksync_types::{Address, L1BatchNumber, MiniblockNumber, H256, U256};
+use zksync_utils::bigdecimal_to_u256;
+
+use crate::{Core, CoreDal};
+
+/// Transaction executed in an L1 batch, as exported by the batch exporter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportedTransaction {
+    pub hash: H256,
+    pub miniblock_number: MiniblockNumber,
+    pub index_in_block: u32,
+    pub is_priority: bool,
+    pub initiator_address: Address,
+    pub contract_address: Option<Address>,
+    /// `None` for L1 transactions and protocol upgrades.
+    pub nonce: Option<u32>,
+    pub value: U256,
+    pub gas_limit: Option<U256>,
+    pub refunded_gas: u64,
+    pub error: Option<String>,
+    pub tx_format: Option<i32>,
+}
+
+/// Event emitted in an L1 batch, as exported by the batch exporter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportedEvent {
+    pub miniblock_number: MiniblockNumber,
+    pub tx_hash: H256,
+    pub tx_index_in_block: u32,
+    pub event_index_in_block: u32,
+    pub event_index_in_tx: u32,
+    pub address: Address,
+    /// Indexed topics; empty topics are skipped.
+    pub topics: Vec<H256>,
+    pub value: Vec<u8>,
+}
+
+/// Storage write performed in an L1 batch, as exported by the batch exporter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportedStorageLog {
+    pub miniblock_number: MiniblockNumber,
+    pub operation_number: u32,
+    pub hashed_key: H256,
+    pub address: Address,
+    pub key: H256,
+    pub value: H256,
+}
+
+/// DAL tracking progress of the batch exporter and providing batch data in the export-friendly format.
+#[derive(Debug)]
+pub struct BatchExportsDal<'a, 'c> {
+    pub(crate) storage: &'a mut Connection<'c, Core>,
+}
+
+impl BatchExportsDal<'_, '_> {
+    /// Returns the number of the latest exported L1 batch, regardless of the schema version it was exported with.
+    pub async fn get_last_exported_l1_batch(&mut self) -> DalResult<Option<L1BatchNumber>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                MAX(l1_batch_number) AS "number"
+            FROM
+                batch_exports
+            "#
+        )
+        .instrument("get_last_exported_l1_batch")
+        .fetch_one(self.storage)
+        .await?;
+
+        Ok(row.number.map(|number| L1BatchNumber(number as u32)))
+    }
+
+    /// Returns the earliest L1 batch that was exported with a schema version older than `schema_version`.
+    pub async fn get_l1_batch_with_outdated_schema(
+        &mut self,
+        schema_version: u32,
+    ) -> DalResult<Option<(L1BatchNumber, u32)>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                l1_batch_number,
+                schema_version
+            FROM
+                batch_exports
+            WHERE
+                schema_version < $1
+            ORDER BY
+                l1_batch_number
+            LIMIT
+                1
+            "#,
+            schema_version as i32
+        )
+        .instrument("get_l1_batch_with_outdated_schema")
+        .with_arg("schema_version", &schema_version)
+        .fetch_optional(self.storage)
+        .await?;
+
+        Ok(row.map(|row| {
+            (
+                L1BatchNumber(row.l1_batch_number as u32),
+                row.schema_version as u32,
+            )
+        }))
+    }
+
+    /// Marks the specified L1 batch as exported with the specified schema version. If the batch was exported
+    /// previously, the schema version is overwritten.
+    pub async fn mark_l1_batch_exported(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+        schema_version: u32,
+    ) -> DalResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO
+                batch_exports (l1_batch_number, schema_version, created_at, updated_at)
+            VALUES
+                ($1, $2, NOW(), NOW())
+            ON CONFLICT (l1_batch_number) DO
+            UPDATE
+            SET
+                schema_version = $2,
+                updated_at = NOW()
+            "#,
+            i64::from(l1_batch_number.0),
+            schema_version as i32
+        )
+        .instrument("mark_l1_batch_exported")
+        .with_arg("l1_batch_number", &l1_batch_number)
+        .with_arg("schema_version", &schema_version)
+        .execute(self.storage)
+        .await?;
+        Ok(())
+    }
+
+    /// Returns all transactions executed in the specified L1 batch ordered by execution.
+    pub async fn get_transactions_for_export(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+    ) -> DalResult<Vec<ExportedTransaction>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                hash,
+                miniblock_number AS "miniblock_number!",
+                index_in_block AS "index_in_block!",
+                is_priority,
+                initiator_address,
+                contract_address,
+                nonce,
+                value,
+                gas_limit,
+                refunded_gas,
+                error,
+                tx_format
+            FROM
+                transactions
+            WHERE
+                l1_batch_number = $1
+            ORDER BY
+                miniblock_number,
+                index_in_block
+            "#,
+            i64::from(l1_batch_number.0)
+        )
+        .instrument("get_transactions_for_export")
+        .with_arg("l1_batch_number", &l1_batch_number)
+        .report_latency()
+        .fetch_all(self.storage)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ExportedTransaction {
+                hash: H256::from_slice(&row.hash),
+                miniblock_number: MiniblockNumber(row.miniblock_number as u32),
+                index_in_block: row.index_in_block as u32,
+                is_priority: row.is_priority,
+                initiator_address: Address::from_slice(&row.initiator_address),
+                contract_address: row.contract_address.as_deref().map(Address::from_slice),
+                nonce: row.nonce.map(|nonce| nonce as u32),
+                value: bigdecimal_to_u256(row.value),
+                gas_limit: row.gas_limit.map(bigdecimal_to_u256),
+                refunded_gas: row.refunded_gas as u64,
+                error: row.error,
+                tx_format: row.tx_format,
+            })
+            .collect())
+    }
+
+    /// Returns all events emitted in the specified L1 batch ordered by emission. Returns `None` if the batch
+    /// has no miniblocks.
+    pub async fn get_events_for_export(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+    ) -> DalResult<Option<Vec<ExportedEvent>>> {
+        let Some((from_miniblock, to_miniblock)) = self
+            .storage
+            .blocks_dal()
+            .get_miniblock_range_of_l1_batch(l1_batch_number)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                miniblock_number,
+                tx_hash,
+                tx_index_in_block,
+                event_index_in_block,
+                event_index_in_tx,
+                address,
+                topic1,
+                topic2,
+                topic3,
+                topic4,
+                value
+            FROM
+                events
+            WHERE
+                miniblock_number BETWEEN $1 AND $2
+            ORDER BY
+                miniblock_number,
+                event_index_in_block
+            "#,
+            i64::from(from_miniblock.0),
+            i64::from(to_miniblock.0)
+        )
+        .instrument("get_events_for_export")
+        .with_arg("l1_batch_number", &l1_batch_number)
+        .report_latency()
+        .fetch_all(self.storage)
+        .await?;
+
+        let events = rows.into_iter().map(|row| {
+            let topics = [row.topic1, row.topic2, row.topic3, row.topic4]
+                .into_iter()
+                .filter(|topic| !topic.is_empty())
+                .map(|topic| H256::from_slice(&topic))
+                .collect();
+            ExportedEvent {
+                miniblock_number: MiniblockNumber(row.miniblock_number as u32),
+                tx_hash: H256::from_slice(&row.tx_hash),
+                tx_index_in_block: row.tx_index_in_block as u32,
+                event_index_in_block: row.event_index_in_block as u32,
+                event_index_in_tx: row.event_index_in_tx as u32,
+                address: Address::from_slice(&row.address),
+                topics,
+                value: row.value,
+            }
+        });
+        Ok(Some(events.collect()))
+    }
+
+    /// Returns all storage logs written in the specified L1 batch ordered by application. Returns `None`
+    /// if the batch has no miniblocks.
+    pub async fn get_storage_logs_for_export(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+    ) -> DalResult<Option<Vec<ExportedStorageLog>>> {
+        let Some((from_miniblock, to_miniblock)) = self
+            .storage
+            .blocks_dal()
+            .get_miniblock_range_of_l1_batch(l1_batch_number)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                miniblock_number,
+                operation_number,
+                hashed_key,
+                address,
+                key,
+                value
+            FROM
+                storage_logs
+            WHERE
+                miniblock_number BETWEEN $1 AND $2
+            ORDER BY
+                miniblock_number,
+                operation_number
+            "#,
+            i64::from(from_miniblock.0),
+            i64::from(to_miniblock.0)
+        )
+        .instrument("get_storage_logs_for_export")
+        .with_arg("l1_batch_number", &l1_batch_number)
+        .report_latency()
+        .fetch_all(self.storage)
+        .await?;
+
+        let logs = rows.into_iter().map(|row| ExportedStorageLog {
+            miniblock_number: MiniblockNumber(row.miniblock_number as u32),
+            operation_number: row.operation_number as u32,
+            hashed_key: H256::from_slice(&row.hashed_key),
+            address: Address::from_slice(&row.address),
+            key: H256::from_slice(&row.key),
+            value: H256::from_slice(&row.value),
+        });
+        Ok(Some(logs.collect()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_types::{
+        block::L1BatchHeader, AccountTreeId, ProtocolVersion, ProtocolVersionId, StorageKey,
+        StorageLog,
+    };
+
+    use super::*;
+    use crate::{
+        tests::{create_miniblock_header, mock_execution_result, mock_l2_transaction},
+        ConnectionPool, Core,
+    };
+
+    #[tokio::test]
+    async fn tracking_export_progress() {
+        let pool = ConnectionPool::<Core>::test_pool().await;
+        let mut conn = pool.connection().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(&ProtocolVersion::default())
+            .await
+            .unwrap();
+        for number in 1..=2 {
+            let header = L1BatchHeader::new(
+                L1BatchNumber(number),
+                100,
+                Default::default(),
+                ProtocolVersionId::latest(),
+            );
+            conn.blocks_dal()
+                .insert_mock_l1_batch(&header)
+                .await
+                .unwrap();
+        }
+
+        let mut dal = conn.batch_exports_dal();
+        assert_eq!(dal.get_last_exported_l1_batch().await.unwrap(), None);
+        dal.mark_l1_batch_exported(L1BatchNumber(1), 1)
+            .await
+            .unwrap();
+        dal.mark_l1_batch_exported(L1BatchNumber(2), 2)
+            .await
+            .unwrap();
+        assert_eq!(
+            dal.get_last_exported_l1_batch().await.unwrap(),
+            Some(L1BatchNumber(2))
+        );
+        assert_eq!(
+            dal.get_l1_batch_with_outdated_schema(2).await.unwrap(),
+            Some((L1BatchNumber(1), 1))
+        );
+
+        dal.mark_l1_batch_exported(L1BatchNumber(1), 2)
+            .await
+            .unwrap();
+        assert_eq!(
+            dal.get_l1_batch_with_outdated_schema(2).await.unwrap(),
+            None
+        );
+
+        conn.blocks_dal()
+            .delete_l1_batches(L1BatchNumber(1))
+            .await
+            .unwrap();
+        assert_eq!(
+            conn.batch_exports_dal()
+                .get_last_exported_l1_batch()
+                .await
+                .unwrap(),
+            Some(L1BatchNumber(1))
+        );
+    }
+
+    #[tokio::test]
+    async fn getting_batch_data_for_export() {
+        let pool = ConnectionPool::<Core>::test_pool().await;
+        let mut conn = pool.connection().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(&ProtocolVersion::default())
+            .await
+            .unwrap();
+        conn.blocks_dal()
+            .insert_miniblock(&create_miniblock_header(1))
+            .await
+            .unwrap();
+        let tx = mock_l2_transaction();
+        let tx_hash = tx.hash();
+        conn.transactions_dal()
+            .insert_transaction_l2(&tx, Default::default())
+            .await
+            .unwrap();
+        let tx_result = mock_execution_result(tx);
+        conn.transactions_dal()
+            .mark_txs_as_executed_in_miniblock(MiniblockNumber(1), &[tx_result.clone()], 1.into())
+            .await
+            .unwrap();
+        let storage_key =
+            StorageKey::new(AccountTreeId::new(Address::repeat_byte(1)), H256::zero());
+        let storage_log = StorageLog::new_write_log(storage_key, H256::repeat_byte(2));
+        conn.storage_logs_dal()
+            .insert_storage_logs(MiniblockNumber(1), &[(tx_hash, vec![storage_log])])
+            .await
+            .unwrap();
+
+        let header = L1BatchHeader::new(
+            L1BatchNumber(1),
+            100,
+            Default::default(),
+            ProtocolVersionId::latest(),
+        );
+        conn.blocks_dal()
+            .insert_mock_l1_batch(&header)
+            .await
+            .unwrap();
+        conn.blocks_dal()
+            .mark_miniblocks_as_executed_in_l1_batch(L1BatchNumber(1))
+            .await
+            .unwrap();
+        conn.transactions_dal()
+            .mark_txs_as_executed_in_l1_batch(L1BatchNumber(1), &[tx_result])
+            .await
+            .unwrap();
+
+        let mut dal = conn.batch_exports_dal();
+        let transactions = dal
+            .get_transactions_for_export(L1BatchNumber(1))
+            .await
+            .unwrap();
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(transactions[0].hash, tx_hash);
+        assert_eq!(transactions[0].miniblock_number, MiniblockNumber(1));
+        assert!(!transactions[0].is_priority);
+
+        let events = dal.get_events_for_export(L1BatchNumber(1)).await.unwrap();
+        assert_eq!(events, Some(vec![]));
+        let storage_logs = dal
+            .get_storage_logs_for_export(L1BatchNumber(1))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(storage_logs.len(), 1);
+        assert_eq!(storage_logs[0].hashed_key, storage_key.hashed_key());
+        assert_eq!(storage_logs[0].value, H256::repeat_byte(2));
+
+        assert_eq!(
+            dal.get_storage_logs_for_export(L1BatchNumber(2))
+                .await
+                .unwrap(),
+            None
+        );
+    }
+}
@@ -142,6 +142,36 @@ impl BlocksDal<'_, '_> {
         Ok(row.number.map(|num| L1BatchNumber(num as u32)))
     }
 
+    /// Returns the number of the latest L1 batch with the timestamp strictly less than `timestamp`
+    /// (measured in seconds since UNIX epoch).
+    pub async fn get_latest_l1_batch_before_timestamp(
+        &mut self,
+        timestamp: u64,
+    ) -> DalResult<Option<L1BatchNumber>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                number
+            FROM
+                l1_batches
+            WHERE
+                timestamp < $1
+            ORDER BY
+                number DESC
+            LIMIT
+                1
+            "#,
+            timestamp as i64
+        )
+        .instrument("get_latest_l1_batch_before_timestamp")
+        .with_arg("timestamp", &timestamp)
+        .report_latency()
+        .fetch_optional(self.storage)
+        .await?;
+
+        Ok(row.map(|row| L1BatchNumber(row.number as u32)))
+    }
+
     pub async fn get_last_l1_batch_number_with_metadata(
         &mut self,
     ) -> DalResult<Option<L1BatchNumber>> {
@@ -1863,10 +1893,32 @@ impl BlocksDal<'_, '_> {
     }
 
     /// Deletes all miniblocks from the storage so that the specified miniblock number is the last one left.
+    /// Hashes of the deleted miniblocks are recorded as reverted, so that they can be distinguished from unknown hashes.
     pub async fn delete_miniblocks(
         &mut self,
         last_miniblock_to_keep: MiniblockNumber,
     ) -> DalResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO
+                reverted_miniblocks (hash, number, created_at)
+            SELECT
+                hash,
+                number,
+                NOW()
+            FROM
+                miniblocks
+            WHERE
+                number > $1
+            ON CONFLICT (hash) DO NOTHING
+            "#,
+            i64::from(last_miniblock_to_keep.0)
+        )
+        .instrument("delete_miniblocks#record_reverted")
+        .with_arg("last_miniblock_to_keep", &last_miniblock_to_keep)
+        .execute(self.storage)
+        .await?;
+
         self.delete_miniblocks_inner(Some(last_miniblock_to_keep))
             .await
     }
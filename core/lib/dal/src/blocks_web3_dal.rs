@@ -1,3 +1,5 @@
+use std::str::FromStr;
+
 use zksync_db_connection::{
     connection::Connection, error::DalResult, instrument::InstrumentExt, interpolate_query,
     match_query_as,
@@ -5,6 +7,7 @@ use zksync_db_connection::{
 use zksync_system_constants::EMPTY_UNCLES_HASH;
 use zksync_types::{
     api,
+    fee_model::{BatchFeeInput, L1BatchFeeSummary},
     l2_to_l1_log::L2ToL1Log,
     vm_trace::Call,
     web3::types::{BlockHeader, U64},
@@ -355,6 +358,27 @@ impl BlocksWeb3Dal<'_, '_> {
         Ok(hash)
     }
 
+    /// Checks whether the miniblock with the specified hash was reverted during a chain reorganization (i.e., is known,
+    /// but not canonical).
+    pub async fn is_reverted_miniblock_hash(&mut self, hash: H256) -> DalResult<bool> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                number
+            FROM
+                reverted_miniblocks
+            WHERE
+                hash = $1
+            "#,
+            hash.as_bytes()
+        )
+        .instrument("is_reverted_miniblock_hash")
+        .with_arg("hash", &hash)
+        .fetch_optional(self.storage)
+        .await?;
+        Ok(row.is_some())
+    }
+
     pub async fn get_l2_to_l1_logs(
         &mut self,
         l1_batch_number: L1BatchNumber,
@@ -489,7 +513,8 @@ impl BlocksWeb3Dal<'_, '_> {
                 call_traces
                 INNER JOIN transactions ON tx_hash = transactions.hash
             WHERE
-                transactions.miniblock_number = $1
+                call_traces.miniblock_number = $1
+                AND transactions.miniblock_number = $1
             ORDER BY
                 transactions.index_in_block
             "#,
@@ -539,6 +564,83 @@ impl BlocksWeb3Dal<'_, '_> {
         Ok(result)
     }
 
+    /// Returns fee summaries for up to `batch_count` latest sealed L1 batches, newest batches first.
+    pub async fn get_l1_batch_fee_summaries(
+        &mut self,
+        batch_count: u64,
+    ) -> DalResult<Vec<L1BatchFeeSummary>> {
+        let rows = sqlx::query!(
+            r#"
+            WITH
+                batches AS (
+                    SELECT
+                        number,
+                        l1_gas_price,
+                        l2_fair_gas_price,
+                        fair_pubdata_price,
+                        protocol_version
+                    FROM
+                        l1_batches
+                    ORDER BY
+                        number DESC
+                    LIMIT
+                        $1
+                )
+            SELECT
+                batches.number,
+                batches.l1_gas_price,
+                batches.l2_fair_gas_price,
+                batches.fair_pubdata_price,
+                batches.protocol_version,
+                COALESCE(SUM(transactions.gas_limit - COALESCE(transactions.refunded_gas, 0)), 0) AS "gas_used!",
+                COALESCE(
+                    SUM(
+                        (transactions.gas_limit - COALESCE(transactions.refunded_gas, 0)) * COALESCE(transactions.effective_gas_price, 0)
+                    ),
+                    0
+                ) AS "fees_paid!"
+            FROM
+                batches
+                LEFT JOIN transactions ON transactions.l1_batch_number = batches.number
+            GROUP BY
+                batches.number,
+                batches.l1_gas_price,
+                batches.l2_fair_gas_price,
+                batches.fair_pubdata_price,
+                batches.protocol_version
+            ORDER BY
+                batches.number DESC
+            "#,
+            batch_count as i64
+        )
+        .instrument("get_l1_batch_fee_summaries")
+        .with_arg("batch_count", &batch_count)
+        .fetch_all(self.storage)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let protocol_version = row
+                    .protocol_version
+                    .map(|version| (version as u16).try_into().unwrap())
+                    .unwrap_or_else(ProtocolVersionId::last_potentially_undefined);
+                L1BatchFeeSummary {
+                    number: L1BatchNumber(row.number as u32),
+                    protocol_version,
+                    fee_input: BatchFeeInput::for_protocol_version(
+                        protocol_version,
+                        row.l2_fair_gas_price as u64,
+                        row.fair_pubdata_price.map(|price| price as u64),
+                        row.l1_gas_price as u64,
+                    ),
+                    gas_used: bigdecimal_to_u256(row.gas_used),
+                    fees_paid: bigdecimal_to_u256(row.fees_paid),
+                }
+            })
+            .collect())
+    }
+
     pub async fn get_block_details(
         &mut self,
         block_number: MiniblockNumber,
@@ -676,6 +778,94 @@ impl BlocksWeb3Dal<'_, '_> {
 
         Ok(l1_batch_details.map(Into::into))
     }
+
+    /// Returns updates for L1 batches with numbers greater than `after` that have reached the specified `stage`
+    /// of their lifecycle, ordered by the batch number. Batches are considered committed, proven or executed
+    /// once the corresponding L1 transaction is confirmed.
+    pub async fn get_l1_batch_stage_updates(
+        &mut self,
+        stage: api::L1BatchStage,
+        after: L1BatchNumber,
+    ) -> DalResult<Vec<api::L1BatchStageUpdate>> {
+        let stage_name = match stage {
+            api::L1BatchStage::Sealed => {
+                return self.get_sealed_l1_batch_updates(after).await;
+            }
+            api::L1BatchStage::Committed => "committed",
+            api::L1BatchStage::Proven => "proven",
+            api::L1BatchStage::Executed => "executed",
+        };
+
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                l1_batches.number,
+                eth_txs_history.tx_hash
+            FROM
+                l1_batches
+                INNER JOIN eth_txs_history ON eth_txs_history.eth_tx_id = (
+                    CASE $2
+                        WHEN 'committed' THEN l1_batches.eth_commit_tx_id
+                        WHEN 'proven' THEN l1_batches.eth_prove_tx_id
+                        ELSE l1_batches.eth_execute_tx_id
+                    END
+                )
+            WHERE
+                l1_batches.number > $1
+                AND eth_txs_history.confirmed_at IS NOT NULL
+            ORDER BY
+                l1_batches.number
+            "#,
+            i64::from(after.0),
+            stage_name
+        )
+        .instrument("get_l1_batch_stage_updates")
+        .with_arg("stage", &stage)
+        .with_arg("after", &after)
+        .fetch_all(self.storage)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| api::L1BatchStageUpdate {
+                l1_batch_number: L1BatchNumber(row.number as u32),
+                stage,
+                l1_tx_hash: Some(H256::from_str(&row.tx_hash).expect("Incorrect L1 tx hash")),
+            })
+            .collect())
+    }
+
+    async fn get_sealed_l1_batch_updates(
+        &mut self,
+        after: L1BatchNumber,
+    ) -> DalResult<Vec<api::L1BatchStageUpdate>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                number
+            FROM
+                l1_batches
+            WHERE
+                number > $1
+            ORDER BY
+                number
+            "#,
+            i64::from(after.0)
+        )
+        .instrument("get_sealed_l1_batch_updates")
+        .with_arg("after", &after)
+        .fetch_all(self.storage)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| api::L1BatchStageUpdate {
+                l1_batch_number: L1BatchNumber(row.number as u32),
+                stage: api::L1BatchStage::Sealed,
+                l1_tx_hash: None,
+            })
+            .collect())
+    }
 }
 
 #[cfg(test)]
@@ -886,6 +1076,45 @@ mod tests {
         assert_eq!(miniblock_number.unwrap(), None);
     }
 
+    #[tokio::test]
+    async fn resolving_reverted_block_hash() {
+        let connection_pool = ConnectionPool::<Core>::test_pool().await;
+        let mut conn = connection_pool.connection().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(&ProtocolVersion::default())
+            .await
+            .unwrap();
+        let headers = [create_miniblock_header(0), create_miniblock_header(1)];
+        for header in &headers {
+            conn.blocks_dal().insert_miniblock(header).await.unwrap();
+        }
+        conn.blocks_dal()
+            .delete_miniblocks(MiniblockNumber(0))
+            .await
+            .unwrap();
+
+        let miniblock_number = conn
+            .blocks_web3_dal()
+            .resolve_block_id(api::BlockId::Hash(headers[1].hash))
+            .await;
+        assert_eq!(miniblock_number.unwrap(), None);
+        let is_reverted = conn
+            .blocks_web3_dal()
+            .is_reverted_miniblock_hash(headers[1].hash)
+            .await
+            .unwrap();
+        assert!(is_reverted);
+
+        for hash in [headers[0].hash, H256::repeat_byte(0xff)] {
+            let is_reverted = conn
+                .blocks_web3_dal()
+                .is_reverted_miniblock_hash(hash)
+                .await
+                .unwrap();
+            assert!(!is_reverted);
+        }
+    }
+
     #[tokio::test]
     async fn getting_traces_for_block() {
         let connection_pool = ConnectionPool::<Core>::test_pool().await;
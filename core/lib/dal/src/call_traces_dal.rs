@@ -0,0 +1,343 @@
+//! Bookkeeping for call traces offloaded from Postgres to an object store.
+
+use zksync_db_connection::{connection::Connection, error::DalResult, instrument::InstrumentExt};
+use zksync_types::{vm_trace::Call, L1BatchNumber, MiniblockNumber, ProtocolVersionId, H256};
+
+use crate::{
+    models::{parse_protocol_version, storage_transaction::CallTrace},
+    Core, CoreDal,
+};
+
+/// Manages offloading of call traces for old L1 batches. Offloaded call traces are removed from Postgres;
+/// the offloaded status is tracked per L1 batch, so that readers know where to look for call traces.
+#[derive(Debug)]
+pub struct CallTracesDal<'a, 'c> {
+    pub(crate) storage: &'a mut Connection<'c, Core>,
+}
+
+impl CallTracesDal<'_, '_> {
+    /// Returns the number of the last L1 batch with offloaded call traces.
+    pub async fn get_last_offloaded_l1_batch(&mut self) -> DalResult<Option<L1BatchNumber>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                MAX(l1_batch_number) AS "number"
+            FROM
+                offloaded_call_traces_l1_batches
+            "#
+        )
+        .instrument("get_last_offloaded_l1_batch")
+        .fetch_one(self.storage)
+        .await?;
+
+        Ok(row.number.map(|number| L1BatchNumber(number as u32)))
+    }
+
+    /// Returns the earliest L1 batch after `after_l1_batch` (or the earliest L1 batch in the DB if `after_l1_batch`
+    /// is not specified) sealed no later than `max_timestamp`.
+    pub async fn get_l1_batch_to_offload(
+        &mut self,
+        after_l1_batch: Option<L1BatchNumber>,
+        max_timestamp: u64,
+    ) -> DalResult<Option<L1BatchNumber>> {
+        let after_l1_batch = after_l1_batch.map_or(-1, |number| i64::from(number.0));
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                number
+            FROM
+                l1_batches
+            WHERE
+                number > $1
+                AND timestamp <= $2
+            ORDER BY
+                number
+            LIMIT
+                1
+            "#,
+            after_l1_batch,
+            max_timestamp as i64
+        )
+        .instrument("get_l1_batch_to_offload")
+        .with_arg("after_l1_batch", &after_l1_batch)
+        .with_arg("max_timestamp", &max_timestamp)
+        .fetch_optional(self.storage)
+        .await?;
+
+        Ok(row.map(|row| L1BatchNumber(row.number as u32)))
+    }
+
+    /// Returns call traces persisted in Postgres for all transactions in the specified L1 batch in the order
+    /// of their execution. Each trace is accompanied by the miniblock number and the transaction hash.
+    pub async fn get_call_traces_for_l1_batch(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+    ) -> DalResult<Vec<(MiniblockNumber, H256, Call)>> {
+        let Some((first_miniblock, last_miniblock)) = self
+            .storage
+            .blocks_dal()
+            .get_miniblock_range_of_l1_batch(l1_batch_number)
+            .await?
+        else {
+            return Ok(vec![]);
+        };
+
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                call_traces.miniblock_number,
+                call_traces.tx_hash,
+                call_traces.call_trace,
+                miniblocks.protocol_version
+            FROM
+                call_traces
+                INNER JOIN miniblocks ON miniblocks.number = call_traces.miniblock_number
+                INNER JOIN transactions ON transactions.hash = call_traces.tx_hash
+            WHERE
+                call_traces.miniblock_number BETWEEN $1 AND $2
+            ORDER BY
+                call_traces.miniblock_number,
+                transactions.index_in_block
+            "#,
+            i64::from(first_miniblock.0),
+            i64::from(last_miniblock.0)
+        )
+        .try_map(|row| {
+            let protocol_version = row
+                .protocol_version
+                .map(parse_protocol_version)
+                .transpose()?
+                .unwrap_or_else(ProtocolVersionId::last_potentially_undefined);
+            let call_trace = CallTrace {
+                call_trace: row.call_trace,
+            };
+            Ok((
+                MiniblockNumber(row.miniblock_number as u32),
+                H256::from_slice(&row.tx_hash),
+                call_trace.into_call(protocol_version),
+            ))
+        })
+        .instrument("get_call_traces_for_l1_batch")
+        .with_arg("l1_batch_number", &l1_batch_number)
+        .fetch_all(self.storage)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Marks call traces for the specified L1 batch as offloaded and removes them from Postgres. Should be called
+    /// only after call traces are persisted in the object store.
+    pub async fn mark_l1_batch_offloaded(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+    ) -> DalResult<()> {
+        let miniblock_range = self
+            .storage
+            .blocks_dal()
+            .get_miniblock_range_of_l1_batch(l1_batch_number)
+            .await?;
+
+        let mut transaction = self.storage.start_transaction().await?;
+        sqlx::query!(
+            r#"
+            INSERT INTO
+                offloaded_call_traces_l1_batches (l1_batch_number, created_at, updated_at)
+            VALUES
+                ($1, NOW(), NOW())
+            ON CONFLICT (l1_batch_number) DO NOTHING
+            "#,
+            i64::from(l1_batch_number.0)
+        )
+        .instrument("mark_l1_batch_offloaded#insert")
+        .with_arg("l1_batch_number", &l1_batch_number)
+        .execute(&mut transaction)
+        .await?;
+
+        if let Some((first_miniblock, last_miniblock)) = miniblock_range {
+            sqlx::query!(
+                r#"
+                DELETE FROM call_traces
+                WHERE
+                    miniblock_number BETWEEN $1 AND $2
+                "#,
+                i64::from(first_miniblock.0),
+                i64::from(last_miniblock.0)
+            )
+            .instrument("mark_l1_batch_offloaded#delete")
+            .with_arg("l1_batch_number", &l1_batch_number)
+            .execute(&mut transaction)
+            .await?;
+        }
+        transaction.commit().await
+    }
+
+    /// Returns the L1 batch containing the specified transaction if call traces for this batch are offloaded.
+    pub async fn get_offloaded_l1_batch_for_tx(
+        &mut self,
+        tx_hash: H256,
+    ) -> DalResult<Option<L1BatchNumber>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                offloaded_call_traces_l1_batches.l1_batch_number
+            FROM
+                transactions
+                INNER JOIN offloaded_call_traces_l1_batches ON transactions.l1_batch_number = offloaded_call_traces_l1_batches.l1_batch_number
+            WHERE
+                transactions.hash = $1
+            "#,
+            tx_hash.as_bytes()
+        )
+        .instrument("get_offloaded_l1_batch_for_tx")
+        .with_arg("tx_hash", &tx_hash)
+        .fetch_optional(self.storage)
+        .await?;
+
+        Ok(row.map(|row| L1BatchNumber(row.l1_batch_number as u32)))
+    }
+
+    /// Returns the L1 batch containing the specified miniblock if call traces for this batch are offloaded.
+    pub async fn get_offloaded_l1_batch_for_miniblock(
+        &mut self,
+        miniblock_number: MiniblockNumber,
+    ) -> DalResult<Option<L1BatchNumber>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                offloaded_call_traces_l1_batches.l1_batch_number
+            FROM
+                miniblocks
+                INNER JOIN offloaded_call_traces_l1_batches ON miniblocks.l1_batch_number = offloaded_call_traces_l1_batches.l1_batch_number
+            WHERE
+                miniblocks.number = $1
+            "#,
+            i64::from(miniblock_number.0)
+        )
+        .instrument("get_offloaded_l1_batch_for_miniblock")
+        .with_arg("miniblock_number", &miniblock_number)
+        .fetch_optional(self.storage)
+        .await?;
+
+        Ok(row.map(|row| L1BatchNumber(row.l1_batch_number as u32)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_contracts::BaseSystemContractsHashes;
+    use zksync_types::{block::L1BatchHeader, Address, ProtocolVersion};
+
+    use super::*;
+    use crate::{
+        tests::{create_miniblock_header, mock_execution_result, mock_l2_transaction},
+        ConnectionPool,
+    };
+
+    #[tokio::test]
+    async fn offloading_call_traces() {
+        let pool = ConnectionPool::<Core>::test_pool().await;
+        let mut conn = pool.connection().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(&ProtocolVersion::default())
+            .await
+            .unwrap();
+        conn.blocks_dal()
+            .insert_miniblock(&create_miniblock_header(1))
+            .await
+            .unwrap();
+
+        let tx = mock_l2_transaction();
+        let tx_hash = tx.hash();
+        conn.transactions_dal()
+            .insert_transaction_l2(&tx, Default::default())
+            .await
+            .unwrap();
+        let mut tx_result = mock_execution_result(tx);
+        tx_result.call_traces.push(Call {
+            from: Address::repeat_byte(1),
+            gas: 100,
+            ..Call::default()
+        });
+        let tx_results = [tx_result];
+        conn.transactions_dal()
+            .mark_txs_as_executed_in_miniblock(MiniblockNumber(1), &tx_results, 1.into())
+            .await
+            .unwrap();
+
+        let header = L1BatchHeader::new(
+            L1BatchNumber(1),
+            100,
+            BaseSystemContractsHashes::default(),
+            ProtocolVersionId::default(),
+        );
+        conn.blocks_dal()
+            .insert_mock_l1_batch(&header)
+            .await
+            .unwrap();
+        conn.blocks_dal()
+            .mark_miniblocks_as_executed_in_l1_batch(L1BatchNumber(1))
+            .await
+            .unwrap();
+        conn.transactions_dal()
+            .mark_txs_as_executed_in_l1_batch(L1BatchNumber(1), &tx_results)
+            .await
+            .unwrap();
+
+        let l1_batch = conn
+            .call_traces_dal()
+            .get_l1_batch_to_offload(None, 99)
+            .await
+            .unwrap();
+        assert_eq!(l1_batch, None);
+        let l1_batch = conn
+            .call_traces_dal()
+            .get_l1_batch_to_offload(None, 100)
+            .await
+            .unwrap();
+        assert_eq!(l1_batch, Some(L1BatchNumber(1)));
+
+        let call_traces = conn
+            .call_traces_dal()
+            .get_call_traces_for_l1_batch(L1BatchNumber(1))
+            .await
+            .unwrap();
+        assert_eq!(call_traces.len(), 1);
+        let (miniblock_number, traced_tx_hash, _) = &call_traces[0];
+        assert_eq!(*miniblock_number, MiniblockNumber(1));
+        assert_eq!(*traced_tx_hash, tx_hash);
+
+        conn.call_traces_dal()
+            .mark_l1_batch_offloaded(L1BatchNumber(1))
+            .await
+            .unwrap();
+        let call_trace = conn
+            .transactions_dal()
+            .get_call_trace(tx_hash)
+            .await
+            .unwrap();
+        assert!(call_trace.is_none());
+        let last_offloaded = conn
+            .call_traces_dal()
+            .get_last_offloaded_l1_batch()
+            .await
+            .unwrap();
+        assert_eq!(last_offloaded, Some(L1BatchNumber(1)));
+        let l1_batch = conn
+            .call_traces_dal()
+            .get_l1_batch_to_offload(last_offloaded, 100)
+            .await
+            .unwrap();
+        assert_eq!(l1_batch, None);
+        let offloaded_l1_batch = conn
+            .call_traces_dal()
+            .get_offloaded_l1_batch_for_tx(tx_hash)
+            .await
+            .unwrap();
+        assert_eq!(offloaded_l1_batch, Some(L1BatchNumber(1)));
+        let offloaded_l1_batch = conn
+            .call_traces_dal()
+            .get_offloaded_l1_batch_for_miniblock(MiniblockNumber(1))
+            .await
+            .unwrap();
+        assert_eq!(offloaded_l1_batch, Some(L1BatchNumber(1)));
+    }
+}
@@ -1,8 +1,11 @@
 use std::{collections::HashMap, fmt};
 
-use sqlx::types::chrono::Utc;
+use futures::{stream::BoxStream, StreamExt, TryStreamExt};
+use sqlx::types::chrono::{NaiveDateTime, Utc};
 use zksync_db_connection::{
+    binary_copy::BinaryCopyBuffer,
     connection::Connection,
+    connection_pool::ConnectionPool,
     error::DalResult,
     instrument::{CopyStatement, InstrumentExt},
     write_str, writeln_str,
@@ -18,6 +21,7 @@ use zksync_types::{
 
 use crate::{
     models::storage_event::{StorageL2ToL1Log, StorageWeb3Log},
+    partitions_dal::PartitionedTable,
     Core, CoreDal,
 };
 
@@ -47,8 +51,14 @@ impl EventsDal<'_, '_> {
         block_number: MiniblockNumber,
         all_block_events: &[(IncludedTxLocation, Vec<&VmEvent>)],
     ) -> DalResult<()> {
+        self.storage
+            .partitions_dal()
+            .ensure_partition(PartitionedTable::Events, block_number)
+            .await?;
+
         let events_len = all_block_events.len();
-        let copy = CopyStatement::new(
+        let use_binary_copy = ConnectionPool::<Core>::global_config().use_binary_copy();
+        let statement = if use_binary_copy {
             "COPY events(
                     miniblock_number, tx_hash, tx_index_in_block, address,
                     event_index_in_block, event_index_in_tx,
@@ -56,49 +66,103 @@ impl EventsDal<'_, '_> {
                     tx_initiator_address,
                     created_at, updated_at
                 )
-                FROM STDIN WITH (DELIMITER '|')",
-        )
-        .instrument("save_events")
-        .with_arg("block_number", &block_number)
-        .with_arg("events.len", &events_len)
-        .start(self.storage)
-        .await?;
+                FROM STDIN WITH (FORMAT BINARY)"
+        } else {
+            "COPY events(
+                    miniblock_number, tx_hash, tx_index_in_block, address,
+                    event_index_in_block, event_index_in_tx,
+                    topic1, topic2, topic3, topic4, value,
+                    tx_initiator_address,
+                    created_at, updated_at
+                )
+                FROM STDIN WITH (DELIMITER '|')"
+        };
+        let copy = CopyStatement::new(statement)
+            .instrument("save_events")
+            .with_arg("block_number", &block_number)
+            .with_arg("events.len", &events_len)
+            .with_arg("use_binary_copy", &use_binary_copy)
+            .start(self.storage)
+            .await?;
+
+        let now = Utc::now().naive_utc();
+        let events = all_block_events.iter().flat_map(|(tx_location, events)| {
+            events
+                .iter()
+                .enumerate()
+                .map(move |(event_index_in_tx, event)| (tx_location, event_index_in_tx, event))
+        });
+        let data = if use_binary_copy {
+            Self::encode_events_binary(block_number, events, now)
+        } else {
+            Self::encode_events_text(block_number, events, now)
+        };
+        copy.send(&data).await
+    }
 
+    fn encode_events_binary<'a>(
+        block_number: MiniblockNumber,
+        events: impl Iterator<Item = (&'a IncludedTxLocation, usize, &'a &'a VmEvent)>,
+        now: NaiveDateTime,
+    ) -> Vec<u8> {
+        let mut buffer = BinaryCopyBuffer::new();
+        for (event_index_in_block, (tx_location, event_index_in_tx, event)) in events.enumerate() {
+            let topic = |i: usize| event.indexed_topics.get(i).map_or(&[][..], H256::as_bytes);
+            buffer
+                .row(14)
+                .i64(block_number.0.into())
+                .bytes(tx_location.tx_hash.as_bytes())
+                .i32(tx_location.tx_index_in_miniblock as i32)
+                .bytes(event.address.as_bytes())
+                .i32(event_index_in_block as i32)
+                .i32(event_index_in_tx as i32)
+                .bytes(topic(0))
+                .bytes(topic(1))
+                .bytes(topic(2))
+                .bytes(topic(3))
+                .bytes(&event.value)
+                .bytes(tx_location.tx_initiator_address.as_bytes())
+                .timestamp(now)
+                .timestamp(now);
+        }
+        buffer.finish()
+    }
+
+    fn encode_events_text<'a>(
+        block_number: MiniblockNumber,
+        events: impl Iterator<Item = (&'a IncludedTxLocation, usize, &'a &'a VmEvent)>,
+        now: NaiveDateTime,
+    ) -> Vec<u8> {
         let mut buffer = String::new();
-        let now = Utc::now().naive_utc().to_string();
-        let mut event_index_in_block = 0_u32;
-        for (tx_location, events) in all_block_events {
+        let now = now.to_string();
+        for (event_index_in_block, (tx_location, event_index_in_tx, event)) in events.enumerate() {
             let IncludedTxLocation {
                 tx_hash,
                 tx_index_in_miniblock,
                 tx_initiator_address,
             } = tx_location;
 
-            for (event_index_in_tx, event) in events.iter().enumerate() {
-                write_str!(
-                    &mut buffer,
-                    r"{block_number}|\\x{tx_hash:x}|{tx_index_in_miniblock}|\\x{address:x}|",
-                    address = event.address
-                );
-                write_str!(&mut buffer, "{event_index_in_block}|{event_index_in_tx}|");
-                write_str!(
-                    &mut buffer,
-                    r"\\x{topic0:x}|\\x{topic1:x}|\\x{topic2:x}|\\x{topic3:x}|",
-                    topic0 = EventTopic(event.indexed_topics.get(0)),
-                    topic1 = EventTopic(event.indexed_topics.get(1)),
-                    topic2 = EventTopic(event.indexed_topics.get(2)),
-                    topic3 = EventTopic(event.indexed_topics.get(3))
-                );
-                writeln_str!(
-                    &mut buffer,
-                    r"\\x{value}|\\x{tx_initiator_address:x}|{now}|{now}",
-                    value = hex::encode(&event.value)
-                );
-
-                event_index_in_block += 1;
-            }
+            write_str!(
+                &mut buffer,
+                r"{block_number}|\\x{tx_hash:x}|{tx_index_in_miniblock}|\\x{address:x}|",
+                address = event.address
+            );
+            write_str!(&mut buffer, "{event_index_in_block}|{event_index_in_tx}|");
+            write_str!(
+                &mut buffer,
+                r"\\x{topic0:x}|\\x{topic1:x}|\\x{topic2:x}|\\x{topic3:x}|",
+                topic0 = EventTopic(event.indexed_topics.get(0)),
+                topic1 = EventTopic(event.indexed_topics.get(1)),
+                topic2 = EventTopic(event.indexed_topics.get(2)),
+                topic3 = EventTopic(event.indexed_topics.get(3))
+            );
+            writeln_str!(
+                &mut buffer,
+                r"\\x{value}|\\x{tx_initiator_address:x}|{now}|{now}",
+                value = hex::encode(&event.value)
+            );
         }
-        copy.send(buffer.as_bytes()).await
+        buffer.into_bytes()
     }
 
     /// Removes events with a block number strictly greater than the specified `block_number`.
@@ -346,6 +410,17 @@ impl EventsDal<'_, '_> {
         &mut self,
         l1_batch_number: L1BatchNumber,
     ) -> DalResult<Option<Vec<VmEvent>>> {
+        let Some(events) = self.stream_vm_events_for_l1_batch(l1_batch_number).await? else {
+            return Ok(None);
+        };
+        Ok(Some(events.try_collect().await?))
+    }
+
+    /// Streaming version of [`Self::get_vm_events_for_l1_batch()`]. Returns `None` if the L1 batch doesn't exist.
+    pub async fn stream_vm_events_for_l1_batch(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+    ) -> DalResult<Option<BoxStream<'_, DalResult<VmEvent>>>> {
         let Some((from_miniblock, to_miniblock)) = self
             .storage
             .blocks_dal()
@@ -356,7 +431,7 @@ impl EventsDal<'_, '_> {
         };
 
         let mut tx_index_in_l1_batch = -1;
-        let rows = sqlx::query!(
+        let events = sqlx::query!(
             r#"
             SELECT
                 address,
@@ -380,33 +455,29 @@ impl EventsDal<'_, '_> {
         .instrument("get_vm_events_for_l1_batch")
         .with_arg("l1_batch_number", &l1_batch_number)
         .report_latency()
-        .fetch_all(self.storage)
-        .await?;
-
-        let events = rows
-            .into_iter()
-            .map(|row| {
-                let indexed_topics = vec![row.topic1, row.topic2, row.topic3, row.topic4]
-                    .into_iter()
-                    .filter_map(|topic| {
-                        if !topic.is_empty() {
-                            Some(H256::from_slice(&topic))
-                        } else {
-                            None
-                        }
-                    })
-                    .collect();
-                if row.event_index_in_tx == 0 {
-                    tx_index_in_l1_batch += 1;
-                }
-                VmEvent {
-                    location: (l1_batch_number, tx_index_in_l1_batch as u32),
-                    address: Address::from_slice(&row.address),
-                    indexed_topics,
-                    value: row.value,
-                }
-            })
-            .collect();
+        .fetch(self.storage)
+        .map_ok(move |row| {
+            let indexed_topics = vec![row.topic1, row.topic2, row.topic3, row.topic4]
+                .into_iter()
+                .filter_map(|topic| {
+                    if !topic.is_empty() {
+                        Some(H256::from_slice(&topic))
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+            if row.event_index_in_tx == 0 {
+                tx_index_in_l1_batch += 1;
+            }
+            VmEvent {
+                location: (l1_batch_number, tx_index_in_l1_batch as u32),
+                address: Address::from_slice(&row.address),
+                indexed_topics,
+                value: row.value,
+            }
+        })
+        .boxed();
         Ok(Some(events))
     }
 }
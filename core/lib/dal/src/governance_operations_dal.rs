@@ -0,0 +1,322 @@
+use std::fmt;
+
+use zksync_db_connection::{
+    connection::Connection,
+    error::DalResult,
+    instrument::{InstrumentExt, Instrumented},
+};
+use zksync_types::{
+    api::{GovernanceCall, GovernanceOperationKind, PendingGovernanceOperation},
+    L1BlockNumber, H256,
+};
+
+use crate::Core;
+
+/// Alert sent for a pending governance operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GovernanceOperationAlert {
+    /// Operation was scheduled on L1.
+    Scheduled,
+    /// Operation will soon become ready for execution.
+    Upcoming,
+}
+
+impl GovernanceOperationAlert {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Scheduled => "scheduled",
+            Self::Upcoming => "upcoming",
+        }
+    }
+}
+
+impl fmt::Display for GovernanceOperationAlert {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(self.as_str())
+    }
+}
+
+/// Governance operations scheduled on L1. Operations are inserted and updated by the Ethereum watcher.
+#[derive(Debug)]
+pub struct GovernanceOperationsDal<'a, 'c> {
+    pub(crate) storage: &'a mut Connection<'c, Core>,
+}
+
+impl GovernanceOperationsDal<'_, '_> {
+    /// Inserts a scheduled operation. If an operation with the same ID was scheduled in an earlier L1 block
+    /// (i.e., it was cancelled and then scheduled again), it is overwritten; otherwise, the call is a no-op.
+    pub async fn insert_scheduled_operation(
+        &mut self,
+        operation: &PendingGovernanceOperation,
+    ) -> DalResult<()> {
+        let instrumentation = Instrumented::new("insert_scheduled_operation")
+            .with_arg("id", &operation.id)
+            .with_arg("l1_block_number", &operation.l1_block_number);
+        let calls = serde_json::to_value(&operation.calls)
+            .map_err(|err| instrumentation.arg_error("calls", err))?;
+        let scheduled_at = i64::try_from(operation.scheduled_at)
+            .map_err(|err| instrumentation.arg_error("scheduled_at", err))?;
+        let ready_at = i64::try_from(operation.ready_at)
+            .map_err(|err| instrumentation.arg_error("ready_at", err))?;
+
+        let query = sqlx::query!(
+            r#"
+            INSERT INTO
+                governance_operations (
+                    id,
+                    kind,
+                    status,
+                    l1_tx_hash,
+                    l1_block_number,
+                    scheduled_at,
+                    ready_at,
+                    calls,
+                    created_at,
+                    updated_at
+                )
+            VALUES
+                ($1, $2, 'scheduled', $3, $4, $5, $6, $7, NOW(), NOW())
+            ON CONFLICT (id) DO
+            UPDATE
+            SET
+                kind = excluded.kind,
+                status = 'scheduled',
+                l1_tx_hash = excluded.l1_tx_hash,
+                l1_block_number = excluded.l1_block_number,
+                scheduled_at = excluded.scheduled_at,
+                ready_at = excluded.ready_at,
+                calls = excluded.calls,
+                alert = NULL,
+                updated_at = NOW()
+            WHERE
+                governance_operations.l1_block_number < excluded.l1_block_number
+            "#,
+            operation.id.as_bytes(),
+            operation.kind.as_str(),
+            operation.l1_tx_hash.as_bytes(),
+            operation.l1_block_number.0 as i32,
+            scheduled_at,
+            ready_at,
+            calls
+        );
+        instrumentation.with(query).execute(self.storage).await?;
+        Ok(())
+    }
+
+    /// Marks the specified operation as executed. No-op if the operation is not known.
+    pub async fn mark_operation_executed(&mut self, id: H256) -> DalResult<()> {
+        self.set_operation_status(id, "executed").await
+    }
+
+    /// Marks the specified operation as cancelled. No-op if the operation is not known.
+    pub async fn mark_operation_cancelled(&mut self, id: H256) -> DalResult<()> {
+        self.set_operation_status(id, "cancelled").await
+    }
+
+    async fn set_operation_status(&mut self, id: H256, status: &str) -> DalResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE governance_operations
+            SET
+                status = $2,
+                updated_at = NOW()
+            WHERE
+                id = $1
+            "#,
+            id.as_bytes(),
+            status
+        )
+        .instrument("set_operation_status")
+        .with_arg("id", &id)
+        .with_arg("status", &status)
+        .execute(self.storage)
+        .await?;
+        Ok(())
+    }
+
+    /// Returns all pending (i.e., neither executed nor cancelled) operations ordered by their readiness.
+    pub async fn get_pending_operations(&mut self) -> DalResult<Vec<PendingGovernanceOperation>> {
+        Ok(self
+            .get_pending_operations_with_alerts()
+            .await?
+            .into_iter()
+            .map(|(operation, _)| operation)
+            .collect())
+    }
+
+    /// Returns pending operations together with the next alert that should be sent for each of them, given
+    /// the L1 timestamp `upcoming_deadline` before which operations are considered upcoming.
+    pub async fn get_operations_to_alert(
+        &mut self,
+        upcoming_deadline: u64,
+    ) -> DalResult<Vec<(PendingGovernanceOperation, GovernanceOperationAlert)>> {
+        let operations = self.get_pending_operations_with_alerts().await?;
+        Ok(operations
+            .into_iter()
+            .filter_map(|(operation, last_alert)| {
+                let next_alert = match last_alert {
+                    None => GovernanceOperationAlert::Scheduled,
+                    Some(GovernanceOperationAlert::Scheduled)
+                        if operation.ready_at <= upcoming_deadline =>
+                    {
+                        GovernanceOperationAlert::Upcoming
+                    }
+                    Some(_) => return None,
+                };
+                Some((operation, next_alert))
+            })
+            .collect())
+    }
+
+    async fn get_pending_operations_with_alerts(
+        &mut self,
+    ) -> DalResult<Vec<(PendingGovernanceOperation, Option<GovernanceOperationAlert>)>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                id,
+                kind,
+                l1_tx_hash,
+                l1_block_number,
+                scheduled_at,
+                ready_at,
+                calls,
+                alert
+            FROM
+                governance_operations
+            WHERE
+                status = 'scheduled'
+            ORDER BY
+                ready_at,
+                l1_block_number
+            "#
+        )
+        .instrument("get_pending_operations_with_alerts")
+        .fetch_all(self.storage)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                let kind = row.kind.parse::<GovernanceOperationKind>().ok()?;
+                let calls: Vec<GovernanceCall> = serde_json::from_value(row.calls).ok()?;
+                let alert = match row.alert.as_deref() {
+                    Some("scheduled") => Some(GovernanceOperationAlert::Scheduled),
+                    Some("upcoming") => Some(GovernanceOperationAlert::Upcoming),
+                    _ => None,
+                };
+                let operation = PendingGovernanceOperation {
+                    id: H256::from_slice(&row.id),
+                    kind,
+                    l1_tx_hash: H256::from_slice(&row.l1_tx_hash),
+                    l1_block_number: L1BlockNumber(row.l1_block_number as u32),
+                    scheduled_at: row.scheduled_at as u64,
+                    ready_at: row.ready_at as u64,
+                    calls,
+                };
+                Some((operation, alert))
+            })
+            .collect())
+    }
+
+    /// Records that the specified alert was sent for an operation.
+    pub async fn set_alert(&mut self, id: H256, alert: GovernanceOperationAlert) -> DalResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE governance_operations
+            SET
+                alert = $2,
+                updated_at = NOW()
+            WHERE
+                id = $1
+            "#,
+            id.as_bytes(),
+            alert.as_str()
+        )
+        .instrument("set_alert")
+        .with_arg("id", &id)
+        .with_arg("alert", &alert)
+        .execute(self.storage)
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_types::{Address, U256};
+
+    use super::*;
+    use crate::{ConnectionPool, Core, CoreDal};
+
+    fn mock_operation(id: u8, ready_at: u64) -> PendingGovernanceOperation {
+        PendingGovernanceOperation {
+            id: H256::repeat_byte(id),
+            kind: GovernanceOperationKind::Transparent,
+            l1_tx_hash: H256::repeat_byte(0xff),
+            l1_block_number: L1BlockNumber(1),
+            scheduled_at: 100,
+            ready_at,
+            calls: vec![GovernanceCall {
+                target: Address::repeat_byte(1),
+                value: U256::zero(),
+                data: vec![1, 2, 3, 4].into(),
+            }],
+        }
+    }
+
+    #[tokio::test]
+    async fn tracking_governance_operations() {
+        let pool = ConnectionPool::<Core>::test_pool().await;
+        let mut conn = pool.connection().await.unwrap();
+        let operation = mock_operation(1, 1_000);
+        let other_operation = mock_operation(2, 500);
+        let mut dal = conn.governance_operations_dal();
+        dal.insert_scheduled_operation(&operation).await.unwrap();
+        dal.insert_scheduled_operation(&other_operation)
+            .await
+            .unwrap();
+
+        let pending = dal.get_pending_operations().await.unwrap();
+        assert_eq!(pending, [other_operation.clone(), operation.clone()]);
+
+        let alerts = dal.get_operations_to_alert(0).await.unwrap();
+        assert_eq!(alerts.len(), 2);
+        assert!(alerts
+            .iter()
+            .all(|(_, alert)| *alert == GovernanceOperationAlert::Scheduled));
+        for (operation, alert) in alerts {
+            dal.set_alert(operation.id, alert).await.unwrap();
+        }
+        assert!(dal.get_operations_to_alert(0).await.unwrap().is_empty());
+        let alerts = dal.get_operations_to_alert(500).await.unwrap();
+        assert_eq!(
+            alerts,
+            [(other_operation.clone(), GovernanceOperationAlert::Upcoming)]
+        );
+
+        dal.mark_operation_executed(other_operation.id)
+            .await
+            .unwrap();
+        dal.mark_operation_cancelled(H256::repeat_byte(0xaa))
+            .await
+            .unwrap();
+        let pending = dal.get_pending_operations().await.unwrap();
+        assert_eq!(pending, [operation.clone()]);
+
+        // Repeated insertion must be a no-op.
+        dal.insert_scheduled_operation(&operation).await.unwrap();
+        assert!(dal.get_operations_to_alert(0).await.unwrap().is_empty());
+
+        // Cancelled operations may be scheduled again.
+        dal.mark_operation_cancelled(operation.id).await.unwrap();
+        assert!(dal.get_pending_operations().await.unwrap().is_empty());
+        let operation = PendingGovernanceOperation {
+            l1_block_number: L1BlockNumber(2),
+            ..operation
+        };
+        dal.insert_scheduled_operation(&operation).await.unwrap();
+        let alerts = dal.get_operations_to_alert(0).await.unwrap();
+        assert_eq!(alerts, [(operation, GovernanceOperationAlert::Scheduled)]);
+    }
+}
@@ -9,36 +9,51 @@ pub use zksync_db_connection::{
     connection::Connection,
     connection_pool::ConnectionPool,
     error::{DalError, DalResult},
+    replicas::{ReadPreference, ReplicaPin},
 };
 
 use crate::{
-    basic_witness_input_producer_dal::BasicWitnessInputProducerDal, blocks_dal::BlocksDal,
-    blocks_web3_dal::BlocksWeb3Dal, consensus_dal::ConsensusDal,
+    address_transactions_dal::AddressTransactionsDal,
+    basic_witness_input_producer_dal::BasicWitnessInputProducerDal,
+    batch_exports_dal::BatchExportsDal, blocks_dal::BlocksDal, blocks_web3_dal::BlocksWeb3Dal,
+    call_traces_dal::CallTracesDal, consensus_dal::ConsensusDal,
     contract_verification_dal::ContractVerificationDal, eth_sender_dal::EthSenderDal,
     events_dal::EventsDal, events_web3_dal::EventsWeb3Dal, factory_deps_dal::FactoryDepsDal,
+    governance_operations_dal::GovernanceOperationsDal, online_migrations_dal::OnlineMigrationsDal,
+    partitions_dal::PartitionsDal, priority_ops_audit_dal::PriorityOpsAuditDal,
     proof_generation_dal::ProofGenerationDal, protocol_versions_dal::ProtocolVersionsDal,
     protocol_versions_web3_dal::ProtocolVersionsWeb3Dal,
+    pubsub_notifications_dal::PubSubNotificationsDal, raw_blocks_dal::RawBlocksDal,
     snapshot_recovery_dal::SnapshotRecoveryDal, snapshots_creator_dal::SnapshotsCreatorDal,
     snapshots_dal::SnapshotsDal, storage_logs_dal::StorageLogsDal,
     storage_logs_dedup_dal::StorageLogsDedupDal, storage_web3_dal::StorageWeb3Dal,
-    sync_dal::SyncDal, system_dal::SystemDal, tokens_dal::TokensDal,
-    tokens_web3_dal::TokensWeb3Dal, transactions_dal::TransactionsDal,
-    transactions_web3_dal::TransactionsWeb3Dal,
+    sync_dal::SyncDal, system_dal::SystemDal, token_balances_dal::TokenBalancesDal,
+    tokens_dal::TokensDal, tokens_web3_dal::TokensWeb3Dal, transactions_dal::TransactionsDal,
+    transactions_web3_dal::TransactionsWeb3Dal, tx_execution_profiles_dal::TxExecutionProfilesDal,
 };
 
+pub mod address_transactions_dal;
 pub mod basic_witness_input_producer_dal;
+pub mod batch_exports_dal;
 pub mod blocks_dal;
 pub mod blocks_web3_dal;
+pub mod call_traces_dal;
 pub mod consensus_dal;
 pub mod contract_verification_dal;
 pub mod eth_sender_dal;
 pub mod events_dal;
 pub mod events_web3_dal;
 pub mod factory_deps_dal;
+pub mod governance_operations_dal;
 mod models;
+pub mod online_migrations_dal;
+pub mod partitions_dal;
+pub mod priority_ops_audit_dal;
 pub mod proof_generation_dal;
 pub mod protocol_versions_dal;
 pub mod protocol_versions_web3_dal;
+pub mod pubsub_notifications_dal;
+pub mod raw_blocks_dal;
 pub mod snapshot_recovery_dal;
 pub mod snapshots_creator_dal;
 pub mod snapshots_dal;
@@ -48,10 +63,12 @@ pub mod storage_logs_dedup_dal;
 pub mod storage_web3_dal;
 pub mod sync_dal;
 pub mod system_dal;
+pub mod token_balances_dal;
 pub mod tokens_dal;
 pub mod tokens_web3_dal;
 pub mod transactions_dal;
 pub mod transactions_web3_dal;
+pub mod tx_execution_profiles_dal;
 
 pub mod metrics;
 
@@ -99,6 +116,8 @@ where
 
     fn storage_logs_dedup_dal(&mut self) -> StorageLogsDedupDal<'_, 'a>;
 
+    fn token_balances_dal(&mut self) -> TokenBalancesDal<'_, 'a>;
+
     fn tokens_dal(&mut self) -> TokensDal<'_, 'a>;
 
     fn tokens_web3_dal(&mut self) -> TokensWeb3Dal<'_, 'a>;
@@ -109,12 +128,32 @@ where
 
     fn protocol_versions_web3_dal(&mut self) -> ProtocolVersionsWeb3Dal<'_, 'a>;
 
+    fn raw_blocks_dal(&mut self) -> RawBlocksDal<'_, 'a>;
+
     fn sync_dal(&mut self) -> SyncDal<'_, 'a>;
 
     fn proof_generation_dal(&mut self) -> ProofGenerationDal<'_, 'a>;
 
+    fn priority_ops_audit_dal(&mut self) -> PriorityOpsAuditDal<'_, 'a>;
+
+    fn tx_execution_profiles_dal(&mut self) -> TxExecutionProfilesDal<'_, 'a>;
+
+    fn address_transactions_dal(&mut self) -> AddressTransactionsDal<'_, 'a>;
+
+    fn call_traces_dal(&mut self) -> CallTracesDal<'_, 'a>;
+
+    fn governance_operations_dal(&mut self) -> GovernanceOperationsDal<'_, 'a>;
+
+    fn batch_exports_dal(&mut self) -> BatchExportsDal<'_, 'a>;
+
     fn system_dal(&mut self) -> SystemDal<'_, 'a>;
 
+    fn partitions_dal(&mut self) -> PartitionsDal<'_, 'a>;
+
+    fn online_migrations_dal(&mut self) -> OnlineMigrationsDal<'_, 'a>;
+
+    fn pubsub_notifications_dal(&mut self) -> PubSubNotificationsDal<'_, 'a>;
+
     fn snapshots_dal(&mut self) -> SnapshotsDal<'_, 'a>;
 
     fn snapshots_creator_dal(&mut self) -> SnapshotsCreatorDal<'_, 'a>;
@@ -187,6 +226,10 @@ impl<'a> CoreDal<'a> for Connection<'a, Core> {
         StorageLogsDedupDal { storage: self }
     }
 
+    fn token_balances_dal(&mut self) -> TokenBalancesDal<'_, 'a> {
+        TokenBalancesDal { storage: self }
+    }
+
     fn tokens_dal(&mut self) -> TokensDal<'_, 'a> {
         TokensDal { storage: self }
     }
@@ -207,6 +250,10 @@ impl<'a> CoreDal<'a> for Connection<'a, Core> {
         ProtocolVersionsWeb3Dal { storage: self }
     }
 
+    fn raw_blocks_dal(&mut self) -> RawBlocksDal<'_, 'a> {
+        RawBlocksDal { storage: self }
+    }
+
     fn sync_dal(&mut self) -> SyncDal<'_, 'a> {
         SyncDal { storage: self }
     }
@@ -215,10 +262,46 @@ impl<'a> CoreDal<'a> for Connection<'a, Core> {
         ProofGenerationDal { storage: self }
     }
 
+    fn priority_ops_audit_dal(&mut self) -> PriorityOpsAuditDal<'_, 'a> {
+        PriorityOpsAuditDal { storage: self }
+    }
+
+    fn tx_execution_profiles_dal(&mut self) -> TxExecutionProfilesDal<'_, 'a> {
+        TxExecutionProfilesDal { storage: self }
+    }
+
+    fn address_transactions_dal(&mut self) -> AddressTransactionsDal<'_, 'a> {
+        AddressTransactionsDal { storage: self }
+    }
+
+    fn call_traces_dal(&mut self) -> CallTracesDal<'_, 'a> {
+        CallTracesDal { storage: self }
+    }
+
+    fn governance_operations_dal(&mut self) -> GovernanceOperationsDal<'_, 'a> {
+        GovernanceOperationsDal { storage: self }
+    }
+
+    fn batch_exports_dal(&mut self) -> BatchExportsDal<'_, 'a> {
+        BatchExportsDal { storage: self }
+    }
+
     fn system_dal(&mut self) -> SystemDal<'_, 'a> {
         SystemDal { storage: self }
     }
 
+    fn partitions_dal(&mut self) -> PartitionsDal<'_, 'a> {
+        PartitionsDal { storage: self }
+    }
+
+    fn online_migrations_dal(&mut self) -> OnlineMigrationsDal<'_, 'a> {
+        OnlineMigrationsDal { storage: self }
+    }
+
+    fn pubsub_notifications_dal(&mut self) -> PubSubNotificationsDal<'_, 'a> {
+        PubSubNotificationsDal { storage: self }
+    }
+
     fn snapshots_dal(&mut self) -> SnapshotsDal<'_, 'a> {
         SnapshotsDal { storage: self }
     }
@@ -406,9 +406,14 @@ pub struct StorageTransactionDetails {
     pub error: Option<String>,
     pub effective_gas_price: Option<BigDecimal>,
     pub refunded_gas: i64,
+    pub operator_suggested_refund: Option<i64>,
     pub eth_commit_tx_hash: Option<String>,
     pub eth_prove_tx_hash: Option<String>,
     pub eth_execute_tx_hash: Option<String>,
+    pub profile_computational_gas_used: Option<i64>,
+    pub profile_pubdata_published: Option<i64>,
+    pub profile_circuit_statistic: Option<serde_json::Value>,
+    pub profile_vm_execution_time_us: Option<i64>,
 }
 
 impl StorageTransactionDetails {
@@ -423,11 +428,22 @@ impl StorageTransactionDetails {
             TransactionStatus::Pending
         }
     }
+
+    fn get_execution_profile(&self) -> Option<api::TransactionExecutionProfile> {
+        let circuits_used = serde_json::from_value(self.profile_circuit_statistic.clone()?).ok()?;
+        Some(api::TransactionExecutionProfile {
+            computational_gas_used: self.profile_computational_gas_used? as u32,
+            pubdata_published: self.profile_pubdata_published? as u32,
+            circuits_used,
+            vm_execution_time_us: self.profile_vm_execution_time_us? as u64,
+        })
+    }
 }
 
 impl From<StorageTransactionDetails> for TransactionDetails {
     fn from(tx_details: StorageTransactionDetails) -> Self {
         let status = tx_details.get_transaction_status();
+        let execution_profile = tx_details.get_execution_profile();
 
         let effective_gas_price =
             bigdecimal_to_u256(tx_details.effective_gas_price.unwrap_or_default());
@@ -440,6 +456,13 @@ impl From<StorageTransactionDetails> for TransactionDetails {
         let gas_refunded = U256::from(tx_details.refunded_gas as u64);
         let fee = (gas_limit - gas_refunded) * effective_gas_price;
 
+        let is_executed = tx_details.miniblock_number.is_some();
+        let refunded_gas = is_executed.then_some(gas_refunded);
+        let operator_suggested_refund = tx_details
+            .operator_suggested_refund
+            .filter(|_| is_executed)
+            .map(|refund| U256::from(refund as u64));
+
         let gas_per_pubdata =
             bigdecimal_to_u256(tx_details.gas_per_pubdata_limit.unwrap_or_default());
 
@@ -466,6 +489,9 @@ impl From<StorageTransactionDetails> for TransactionDetails {
             eth_commit_tx_hash,
             eth_prove_tx_hash,
             eth_execute_tx_hash,
+            refunded_gas,
+            operator_suggested_refund,
+            execution_profile,
         }
     }
 }
@@ -0,0 +1,286 @@
+//! Persistent state of online migrations, i.e. rewrites of large tables performed in the background
+//! without server downtime.
+
+use std::{fmt, str::FromStr};
+
+use zksync_db_connection::{
+    connection::Connection,
+    error::DalResult,
+    instrument::{InstrumentExt, Instrumented},
+};
+
+use crate::Core;
+
+/// Status of an online migration.
+///
+/// An online migration goes through the following stages:
+///
+/// 1. **Dual-write window.** Once the code performing the migration is deployed, writers persist data
+///    in both the legacy and the new layout, and readers use the legacy layout. Meanwhile, rows written
+///    before the deployment are copied to the new layout in batches.
+/// 2. **Backfill completed.** All legacy rows are copied; writers still write both layouts, so the server
+///    can be safely rolled back to the previous version if necessary.
+/// 3. **Cutover.** Readers switch to the new layout, and writers stop writing the legacy one.
+///    The legacy layout can be dropped by a regular migration afterwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OnlineMigrationStatus {
+    /// Legacy rows are being backfilled; dual writes are active.
+    Backfilling,
+    /// All legacy rows are backfilled; dual writes are still active.
+    BackfillCompleted,
+    /// Migration is finalized; only the new layout is used.
+    CutOver,
+}
+
+impl OnlineMigrationStatus {
+    /// Returns whether writers should persist data in the legacy layout.
+    pub fn writes_legacy_layout(self) -> bool {
+        !matches!(self, Self::CutOver)
+    }
+
+    /// Returns whether readers should read data from the new layout.
+    pub fn reads_new_layout(self) -> bool {
+        matches!(self, Self::CutOver)
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Backfilling => "backfilling",
+            Self::BackfillCompleted => "backfill_completed",
+            Self::CutOver => "cut_over",
+        }
+    }
+}
+
+impl fmt::Display for OnlineMigrationStatus {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(self.as_str())
+    }
+}
+
+impl FromStr for OnlineMigrationStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "backfilling" => Self::Backfilling,
+            "backfill_completed" => Self::BackfillCompleted,
+            "cut_over" => Self::CutOver,
+            _ => return Err(format!("unknown online migration status: {s}")),
+        })
+    }
+}
+
+/// Progress of an online migration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OnlineMigrationState {
+    pub status: OnlineMigrationStatus,
+    /// Key of the last backfilled row, or `None` if the backfill hasn't started yet.
+    pub cursor: Option<i64>,
+    /// Total number of rows processed by the backfill.
+    pub processed_rows: u64,
+}
+
+#[derive(Debug)]
+pub struct OnlineMigrationsDal<'a, 'c> {
+    pub(crate) storage: &'a mut Connection<'c, Core>,
+}
+
+impl OnlineMigrationsDal<'_, '_> {
+    /// Registers a migration with the [`Backfilling`](OnlineMigrationStatus::Backfilling) status.
+    /// No-op if the migration is already registered.
+    pub async fn register_migration(&mut self, name: &str) -> DalResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO
+                online_migrations (name, status, created_at, updated_at)
+            VALUES
+                ($1, $2, NOW(), NOW())
+            ON CONFLICT (name) DO NOTHING
+            "#,
+            name,
+            OnlineMigrationStatus::Backfilling.as_str()
+        )
+        .instrument("register_migration")
+        .with_arg("name", &name)
+        .execute(self.storage)
+        .await?;
+        Ok(())
+    }
+
+    /// Returns the state of the specified migration, or `None` if it isn't registered.
+    pub async fn get_migration_state(
+        &mut self,
+        name: &str,
+    ) -> DalResult<Option<OnlineMigrationState>> {
+        let Some(row) = sqlx::query!(
+            r#"
+            SELECT
+                status,
+                cursor,
+                processed_rows
+            FROM
+                online_migrations
+            WHERE
+                name = $1
+            "#,
+            name
+        )
+        .instrument("get_migration_state")
+        .with_arg("name", &name)
+        .fetch_optional(self.storage)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let status = row
+            .status
+            .parse()
+            .expect("invalid online migration status in Postgres");
+        Ok(Some(OnlineMigrationState {
+            status,
+            cursor: row.cursor,
+            processed_rows: row.processed_rows as u64,
+        }))
+    }
+
+    /// Returns the status of the specified migration, or `None` if it isn't registered. Intended to be used
+    /// by writers and readers to switch between the legacy and the new data layout; should be called
+    /// in the same transaction as the read / write operation to get consistent results.
+    pub async fn get_migration_status(
+        &mut self,
+        name: &str,
+    ) -> DalResult<Option<OnlineMigrationStatus>> {
+        Ok(self
+            .get_migration_state(name)
+            .await?
+            .map(|state| state.status))
+    }
+
+    /// Records progress of a backfill batch. Should be called in the same transaction as the batch itself,
+    /// so that the progress and the backfilled data are always consistent. Returns `false` if the migration
+    /// is not registered or is not in the [`Backfilling`](OnlineMigrationStatus::Backfilling) status.
+    pub async fn record_backfill_progress(
+        &mut self,
+        name: &str,
+        cursor: i64,
+        processed_rows: u64,
+    ) -> DalResult<bool> {
+        let instrumentation = Instrumented::new("record_backfill_progress")
+            .with_arg("name", &name)
+            .with_arg("cursor", &cursor)
+            .with_arg("processed_rows", &processed_rows);
+        let query = sqlx::query!(
+            r#"
+            UPDATE online_migrations
+            SET
+                cursor = $2,
+                processed_rows = processed_rows + $3,
+                updated_at = NOW()
+            WHERE
+                name = $1
+                AND status = $4
+            "#,
+            name,
+            cursor,
+            processed_rows as i64,
+            OnlineMigrationStatus::Backfilling.as_str()
+        );
+        let result = instrumentation.with(query).execute(self.storage).await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Transitions the specified migration to the new status. Returns `false` if the migration is not registered
+    /// or is not in the `expected_status`.
+    pub async fn transition_migration(
+        &mut self,
+        name: &str,
+        expected_status: OnlineMigrationStatus,
+        new_status: OnlineMigrationStatus,
+    ) -> DalResult<bool> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE online_migrations
+            SET
+                status = $3,
+                updated_at = NOW()
+            WHERE
+                name = $1
+                AND status = $2
+            "#,
+            name,
+            expected_status.as_str(),
+            new_status.as_str()
+        )
+        .instrument("transition_migration")
+        .with_arg("name", &name)
+        .with_arg("expected_status", &expected_status)
+        .with_arg("new_status", &new_status)
+        .execute(self.storage)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConnectionPool, CoreDal};
+
+    #[tokio::test]
+    async fn online_migration_lifecycle() {
+        let pool = ConnectionPool::<Core>::test_pool().await;
+        let mut conn = pool.connection().await.unwrap();
+        let mut dal = conn.online_migrations_dal();
+
+        assert_eq!(dal.get_migration_state("test").await.unwrap(), None);
+        let recorded = dal.record_backfill_progress("test", 10, 10).await.unwrap();
+        assert!(!recorded);
+
+        dal.register_migration("test").await.unwrap();
+        let state = dal.get_migration_state("test").await.unwrap().unwrap();
+        assert_eq!(
+            state,
+            OnlineMigrationState {
+                status: OnlineMigrationStatus::Backfilling,
+                cursor: None,
+                processed_rows: 0,
+            }
+        );
+
+        assert!(dal.record_backfill_progress("test", 10, 8).await.unwrap());
+        assert!(dal.record_backfill_progress("test", 20, 5).await.unwrap());
+        // Repeated registration must not reset progress.
+        dal.register_migration("test").await.unwrap();
+        let state = dal.get_migration_state("test").await.unwrap().unwrap();
+        assert_eq!(state.cursor, Some(20));
+        assert_eq!(state.processed_rows, 13);
+
+        let transitioned = dal
+            .transition_migration(
+                "test",
+                OnlineMigrationStatus::BackfillCompleted,
+                OnlineMigrationStatus::CutOver,
+            )
+            .await
+            .unwrap();
+        assert!(!transitioned);
+        let transitioned = dal
+            .transition_migration(
+                "test",
+                OnlineMigrationStatus::Backfilling,
+                OnlineMigrationStatus::BackfillCompleted,
+            )
+            .await
+            .unwrap();
+        assert!(transitioned);
+        let recorded = dal.record_backfill_progress("test", 30, 1).await.unwrap();
+        assert!(!recorded);
+
+        let status = dal.get_migration_status("test").await.unwrap();
+        assert_eq!(status, Some(OnlineMigrationStatus::BackfillCompleted));
+        assert!(status.unwrap().writes_legacy_layout());
+        assert!(!status.unwrap().reads_new_layout());
+    }
+}
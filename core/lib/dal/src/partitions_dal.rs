@@ -0,0 +1,354 @@
+//! Management of partitions for tables partitioned by miniblock number ranges.
+
+use std::ops;
+
+use zksync_db_connection::{connection::Connection, error::DalResult, instrument::InstrumentExt};
+use zksync_types::MiniblockNumber;
+
+use crate::Core;
+
+/// Number of miniblocks covered by a single partition created by [`PartitionsDal`].
+pub const MINIBLOCKS_PER_PARTITION: u32 = 1_000_000;
+
+/// Table partitioned by miniblock number ranges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PartitionedTable {
+    Events,
+    StorageLogs,
+    CallTraces,
+}
+
+impl PartitionedTable {
+    /// All partitioned tables.
+    pub const ALL: [Self; 3] = [Self::Events, Self::StorageLogs, Self::CallTraces];
+    /// Partitioned tables that can be pruned by dropping partitions. `storage_logs` is not included since old
+    /// partitions contain the latest values of storage slots not modified since, i.e., live state.
+    pub const PRUNABLE: [Self; 2] = [Self::Events, Self::CallTraces];
+
+    /// Returns the name of the table in the database.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Events => "events",
+            Self::StorageLogs => "storage_logs",
+            Self::CallTraces => "call_traces",
+        }
+    }
+}
+
+/// Partition of a [`PartitionedTable`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TablePartition {
+    /// Name of the partition in the database.
+    pub name: String,
+    /// Range of miniblocks covered by the partition. The legacy partition created when converting
+    /// the table to a partitioned one covers all miniblocks starting from 0.
+    pub miniblocks: ops::Range<MiniblockNumber>,
+}
+
+/// Parses a partition bound as returned by `pg_get_expr()`, e.g. `FOR VALUES FROM ('0') TO ('1000000')`.
+/// Returns `None` for unsupported bounds (e.g., for a `DEFAULT` partition).
+fn parse_partition_bound(bound: &str) -> Option<ops::Range<MiniblockNumber>> {
+    fn parse_value(value: &str) -> Option<u32> {
+        let value = value.trim().strip_prefix('(')?.strip_suffix(')')?;
+        match value {
+            "MINVALUE" => Some(0),
+            _ => value.trim_matches('\'').parse().ok(),
+        }
+    }
+
+    let (start, end) = bound.strip_prefix("FOR VALUES FROM ")?.split_once(" TO ")?;
+    let start = parse_value(start)?;
+    let end = parse_value(end)?;
+    Some(MiniblockNumber(start)..MiniblockNumber(end))
+}
+
+/// Manages partitions of tables partitioned by miniblock number ranges (see [`PartitionedTable`]).
+///
+/// Partitions are created on demand when inserting data into partitioned tables, and can be dropped
+/// in order to prune old data. Unlike deleting rows, dropping partitions doesn't bloat the database.
+#[derive(Debug)]
+pub struct PartitionsDal<'a, 'c> {
+    pub(crate) storage: &'a mut Connection<'c, Core>,
+}
+
+impl PartitionsDal<'_, '_> {
+    /// Returns partitions of the specified table ordered by the covered miniblock range.
+    pub async fn get_partitions(
+        &mut self,
+        table: PartitionedTable,
+    ) -> DalResult<Vec<TablePartition>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                child.relname::TEXT AS "name!",
+                PG_GET_EXPR(child.relpartbound, child.oid) AS "bound!"
+            FROM
+                pg_inherits
+                INNER JOIN pg_class parent ON pg_inherits.inhparent = parent.oid
+                INNER JOIN pg_class child ON pg_inherits.inhrelid = child.oid
+            WHERE
+                parent.relname = $1
+                AND parent.relkind = 'p'
+            "#,
+            table.name()
+        )
+        .instrument("get_partitions")
+        .with_arg("table", &table)
+        .fetch_all(self.storage)
+        .await?;
+
+        let mut partitions: Vec<_> = rows
+            .into_iter()
+            .filter_map(|row| {
+                let Some(miniblocks) = parse_partition_bound(&row.bound) else {
+                    tracing::warn!(
+                        "Ignoring partition `{}` of table `{}` with unsupported bound: {}",
+                        row.name,
+                        table.name(),
+                        row.bound
+                    );
+                    return None;
+                };
+                Some(TablePartition {
+                    name: row.name,
+                    miniblocks,
+                })
+            })
+            .collect();
+        partitions.sort_unstable_by_key(|partition| partition.miniblocks.start);
+        Ok(partitions)
+    }
+
+    /// Ensures that the specified table has a partition for the specified miniblock, creating it if necessary.
+    /// Created partitions cover [`MINIBLOCKS_PER_PARTITION`] miniblocks aligned to a multiple of this number
+    /// (or less if the range overlaps with existing partitions).
+    pub async fn ensure_partition(
+        &mut self,
+        table: PartitionedTable,
+        miniblock_number: MiniblockNumber,
+    ) -> DalResult<()> {
+        let partitions = self.get_partitions(table).await?;
+        if partitions
+            .iter()
+            .any(|partition| partition.miniblocks.contains(&miniblock_number))
+        {
+            return Ok(());
+        }
+
+        let aligned_start =
+            miniblock_number.0 / MINIBLOCKS_PER_PARTITION * MINIBLOCKS_PER_PARTITION;
+        let aligned_end = aligned_start.saturating_add(MINIBLOCKS_PER_PARTITION);
+        let start = partitions
+            .iter()
+            .map(|partition| partition.miniblocks.end.0)
+            .filter(|&end| end <= miniblock_number.0)
+            .fold(aligned_start, u32::max);
+        let end = partitions
+            .iter()
+            .map(|partition| partition.miniblocks.start.0)
+            .filter(|&start| start > miniblock_number.0)
+            .fold(aligned_end, u32::min);
+
+        let table_name = table.name();
+        let partition_name = format!("{table_name}_p{start}");
+        tracing::info!(
+            "Creating partition `{partition_name}` of table `{table_name}` for miniblocks {start}..{end}"
+        );
+        let sql = format!(
+            "CREATE TABLE IF NOT EXISTS {partition_name} PARTITION OF {table_name} \
+             FOR VALUES FROM ({start}) TO ({end})"
+        );
+        // The partition is created in a nested transaction (i.e., a savepoint if the connection is already
+        // in a transaction, e.g. when sealing a miniblock), so that a failure doesn't abort the enclosing transaction.
+        let mut transaction = self.storage.start_transaction().await?;
+        let result = sqlx::query(&sql)
+            .instrument("ensure_partition")
+            .with_arg("table", &table)
+            .with_arg("miniblock_number", &miniblock_number)
+            .execute(&mut transaction)
+            .await;
+        if let Err(err) = result {
+            // Roll back to the savepoint.
+            drop(transaction);
+            // The partition may have been created concurrently (e.g., when recovering storage logs from a snapshot
+            // using multiple connections).
+            let partitions = self.get_partitions(table).await?;
+            if !partitions
+                .iter()
+                .any(|partition| partition.miniblocks.contains(&miniblock_number))
+            {
+                return Err(err);
+            }
+        } else {
+            transaction.commit().await?;
+        }
+        Ok(())
+    }
+
+    /// Drops partitions of [prunable tables](PartitionedTable::PRUNABLE) containing only miniblocks
+    /// strictly before the specified one. Partitions containing `miniblock_number` or later miniblocks
+    /// are retained intact. Returns names of the dropped partitions.
+    pub async fn drop_partitions_before(
+        &mut self,
+        miniblock_number: MiniblockNumber,
+    ) -> DalResult<Vec<String>> {
+        let mut dropped_partitions = vec![];
+        for table in PartitionedTable::PRUNABLE {
+            let partitions = self.get_partitions(table).await?;
+            let partitions_to_drop = partitions
+                .into_iter()
+                .filter(|partition| partition.miniblocks.end <= miniblock_number);
+            for partition in partitions_to_drop {
+                tracing::info!(
+                    "Dropping partition `{}` of table `{}` for miniblocks {:?}",
+                    partition.name,
+                    table.name(),
+                    partition.miniblocks
+                );
+                let sql = format!("DROP TABLE {}", partition.name);
+                sqlx::query(&sql)
+                    .instrument("drop_partitions_before")
+                    .with_arg("table", &table)
+                    .with_arg("partition", &partition.name)
+                    .execute(self.storage)
+                    .await?;
+                dropped_partitions.push(partition.name);
+            }
+        }
+        Ok(dropped_partitions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{ConnectionPool, CoreDal};
+
+    #[test]
+    fn parsing_partition_bounds() {
+        assert_eq!(
+            parse_partition_bound("FOR VALUES FROM (MINVALUE) TO ('2000000')"),
+            Some(MiniblockNumber(0)..MiniblockNumber(2_000_000))
+        );
+        assert_eq!(
+            parse_partition_bound("FOR VALUES FROM ('2000000') TO ('3000000')"),
+            Some(MiniblockNumber(2_000_000)..MiniblockNumber(3_000_000))
+        );
+        assert_eq!(parse_partition_bound("DEFAULT"), None);
+    }
+
+    #[tokio::test]
+    async fn creating_and_dropping_partitions() {
+        let pool = ConnectionPool::<Core>::test_pool().await;
+        let mut conn = pool.connection().await.unwrap();
+        let initial_partitions = conn
+            .partitions_dal()
+            .get_partitions(PartitionedTable::Events)
+            .await
+            .unwrap();
+        let legacy_partition = &initial_partitions[0];
+        assert_eq!(legacy_partition.name, "events_legacy");
+        let legacy_end = legacy_partition.miniblocks.end;
+
+        conn.partitions_dal()
+            .ensure_partition(PartitionedTable::Events, MiniblockNumber(0))
+            .await
+            .unwrap();
+        let new_miniblock = legacy_end + MINIBLOCKS_PER_PARTITION + 1;
+        conn.partitions_dal()
+            .ensure_partition(PartitionedTable::Events, new_miniblock)
+            .await
+            .unwrap();
+        // Should be idempotent.
+        conn.partitions_dal()
+            .ensure_partition(PartitionedTable::Events, new_miniblock)
+            .await
+            .unwrap();
+
+        let partitions = conn
+            .partitions_dal()
+            .get_partitions(PartitionedTable::Events)
+            .await
+            .unwrap();
+        assert_eq!(partitions.len(), initial_partitions.len() + 1);
+        let new_partition = partitions.last().unwrap();
+        let expected_start = legacy_end + MINIBLOCKS_PER_PARTITION;
+        assert_eq!(new_partition.name, format!("events_p{}", expected_start.0));
+        assert_eq!(
+            new_partition.miniblocks,
+            expected_start..expected_start + MINIBLOCKS_PER_PARTITION
+        );
+
+        let dropped = conn
+            .partitions_dal()
+            .drop_partitions_before(legacy_end)
+            .await
+            .unwrap();
+        assert_eq!(dropped, ["events_legacy", "call_traces_legacy"]);
+        let partitions = conn
+            .partitions_dal()
+            .get_partitions(PartitionedTable::Events)
+            .await
+            .unwrap();
+        assert_eq!(partitions, [new_partition.clone()]);
+        // Storage logs must not be pruned.
+        let partitions = conn
+            .partitions_dal()
+            .get_partitions(PartitionedTable::StorageLogs)
+            .await
+            .unwrap();
+        assert_eq!(partitions[0].name, "storage_logs_legacy");
+    }
+
+    #[tokio::test]
+    async fn creating_partition_concurrently_within_transaction() {
+        let pool = ConnectionPool::<Core>::test_pool().await;
+        let mut conn = pool.connection().await.unwrap();
+        let legacy_end = conn
+            .partitions_dal()
+            .get_partitions(PartitionedTable::Events)
+            .await
+            .unwrap()[0]
+            .miniblocks
+            .end;
+        let new_miniblock = legacy_end + 1;
+
+        let mut transaction = conn.start_transaction().await.unwrap();
+        transaction
+            .partitions_dal()
+            .ensure_partition(PartitionedTable::Events, new_miniblock)
+            .await
+            .unwrap();
+
+        let other_task = tokio::spawn({
+            let pool = pool.clone();
+            async move {
+                let mut conn = pool.connection().await.unwrap();
+                let mut transaction = conn.start_transaction().await.unwrap();
+                // Creating the partition blocks until the other transaction is committed, and then fails.
+                transaction
+                    .partitions_dal()
+                    .ensure_partition(PartitionedTable::Events, new_miniblock)
+                    .await
+                    .unwrap();
+                // The enclosing transaction must remain usable.
+                let partitions = transaction
+                    .partitions_dal()
+                    .get_partitions(PartitionedTable::Events)
+                    .await
+                    .unwrap();
+                transaction.commit().await.unwrap();
+                partitions
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        transaction.commit().await.unwrap();
+
+        let partitions = other_task.await.unwrap();
+        assert!(partitions
+            .iter()
+            .any(|partition| partition.miniblocks.contains(&new_miniblock)));
+    }
+}
@@ -0,0 +1,329 @@
+use sqlx::types::chrono::{DateTime, Utc};
+use zksync_db_connection::{
+    connection::Connection,
+    error::DalResult,
+    instrument::{InstrumentExt, Instrumented},
+};
+use zksync_types::{
+    api::{PriorityOpAuditEvent, PriorityOpAuditRecord},
+    l1::L1Tx,
+    tx::{tx_execution_info::TxExecutionStatus, TransactionExecutionResult},
+    ExecuteTransactionCommon, L1BlockNumber, MiniblockNumber, PriorityOpId, H256,
+};
+
+use crate::Core;
+
+/// Append-only audit log of priority operation lifecycles. Entries are added by the Ethereum watcher
+/// (when an operation is received from L1), by the state keeper (when it is executed in a miniblock),
+/// and by the block reverter (when the miniblock executing the operation is rolled back).
+#[derive(Debug)]
+pub struct PriorityOpsAuditDal<'a, 'c> {
+    pub(crate) storage: &'a mut Connection<'c, Core>,
+}
+
+impl PriorityOpsAuditDal<'_, '_> {
+    /// Records that a priority operation was received from L1. Repeated calls for the same operation are no-ops.
+    pub async fn insert_received_event(&mut self, tx: &L1Tx) -> DalResult<()> {
+        let tx_hash = tx.hash();
+        sqlx::query!(
+            r#"
+            INSERT INTO
+                priority_ops_audit (
+                    priority_op_id,
+                    tx_hash,
+                    event,
+                    l1_tx_hash,
+                    l1_block_number,
+                    created_at
+                )
+            VALUES
+                ($1, $2, 'received', $3, $4, NOW())
+            ON CONFLICT DO NOTHING
+            "#,
+            tx.serial_id().0 as i64,
+            tx_hash.as_bytes(),
+            tx.common_data.eth_hash.as_bytes(),
+            tx.eth_block().0 as i32
+        )
+        .instrument("insert_received_event")
+        .with_arg("tx_hash", &tx_hash)
+        .execute(self.storage)
+        .await?;
+        Ok(())
+    }
+
+    /// Records execution results for all priority operations in the provided miniblock transactions.
+    pub async fn insert_execution_events(
+        &mut self,
+        miniblock_number: MiniblockNumber,
+        transactions: &[TransactionExecutionResult],
+    ) -> DalResult<()> {
+        let l1_txs_len = transactions
+            .iter()
+            .filter(|tx_res| {
+                matches!(
+                    tx_res.transaction.common_data,
+                    ExecuteTransactionCommon::L1(_)
+                )
+            })
+            .count();
+        if l1_txs_len == 0 {
+            return Ok(());
+        }
+
+        let instrumentation = Instrumented::new("insert_execution_events")
+            .with_arg("miniblock_number", &miniblock_number)
+            .with_arg("l1_txs.len", &l1_txs_len);
+
+        let mut priority_op_ids = Vec::with_capacity(l1_txs_len);
+        let mut hashes = Vec::with_capacity(l1_txs_len);
+        let mut indices_in_block = Vec::with_capacity(l1_txs_len);
+        let mut statuses = Vec::with_capacity(l1_txs_len);
+        let mut refunded_gas = Vec::with_capacity(l1_txs_len);
+        for (index_in_block, tx_res) in transactions.iter().enumerate() {
+            let ExecuteTransactionCommon::L1(common_data) = &tx_res.transaction.common_data else {
+                continue;
+            };
+            let tx_refunded_gas = i64::try_from(tx_res.refunded_gas).map_err(|err| {
+                instrumentation
+                    .arg_error(&format!("transactions[{index_in_block}].refunded_gas"), err)
+            })?;
+
+            priority_op_ids.push(common_data.serial_id.0 as i64);
+            hashes.push(tx_res.hash.as_bytes());
+            indices_in_block.push(index_in_block as i32);
+            statuses.push(match tx_res.execution_status {
+                TxExecutionStatus::Success => "success",
+                TxExecutionStatus::Failure => "failure",
+            });
+            refunded_gas.push(tx_refunded_gas);
+        }
+
+        let query = sqlx::query!(
+            r#"
+            INSERT INTO
+                priority_ops_audit (
+                    priority_op_id,
+                    tx_hash,
+                    event,
+                    miniblock_number,
+                    index_in_block,
+                    execution_status,
+                    refunded_gas,
+                    created_at
+                )
+            SELECT
+                u.priority_op_id,
+                u.tx_hash,
+                'executed',
+                $1,
+                u.index_in_block,
+                u.execution_status,
+                u.refunded_gas,
+                NOW()
+            FROM
+                UNNEST($2::BIGINT[], $3::bytea[], $4::INT[], $5::TEXT[], $6::BIGINT[]) AS u (
+                    priority_op_id,
+                    tx_hash,
+                    index_in_block,
+                    execution_status,
+                    refunded_gas
+                )
+            "#,
+            i64::from(miniblock_number.0),
+            &priority_op_ids,
+            &hashes as &[&[u8]],
+            &indices_in_block,
+            &statuses as &[&str],
+            &refunded_gas,
+        );
+
+        instrumentation.with(query).execute(self.storage).await?;
+        Ok(())
+    }
+
+    /// Records rollback events for all priority operations executed in miniblocks after the specified one.
+    /// Must be called before the transactions state is reset.
+    pub async fn insert_rollback_events(
+        &mut self,
+        last_miniblock_to_keep: MiniblockNumber,
+    ) -> DalResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO
+                priority_ops_audit (
+                    priority_op_id,
+                    tx_hash,
+                    event,
+                    miniblock_number,
+                    created_at
+                )
+            SELECT
+                priority_op_id,
+                hash,
+                'rolled_back',
+                miniblock_number,
+                NOW()
+            FROM
+                transactions
+            WHERE
+                is_priority = TRUE
+                AND miniblock_number > $1
+                AND priority_op_id IS NOT NULL
+            "#,
+            i64::from(last_miniblock_to_keep.0)
+        )
+        .instrument("insert_rollback_events")
+        .with_arg("last_miniblock_to_keep", &last_miniblock_to_keep)
+        .execute(self.storage)
+        .await?;
+        Ok(())
+    }
+
+    /// Returns the full audit history of the specified priority operation, oldest entries first.
+    pub async fn get_priority_op_history(
+        &mut self,
+        priority_op_id: PriorityOpId,
+    ) -> DalResult<Vec<PriorityOpAuditRecord>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                priority_op_id,
+                tx_hash,
+                event,
+                l1_tx_hash,
+                l1_block_number,
+                miniblock_number,
+                index_in_block,
+                execution_status,
+                refunded_gas,
+                created_at
+            FROM
+                priority_ops_audit
+            WHERE
+                priority_op_id = $1
+            ORDER BY
+                id
+            "#,
+            priority_op_id.0 as i64
+        )
+        .instrument("get_priority_op_history")
+        .with_arg("priority_op_id", &priority_op_id)
+        .fetch_all(self.storage)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                let event = row.event.parse::<PriorityOpAuditEvent>().ok()?;
+                Some(PriorityOpAuditRecord {
+                    priority_op_id: PriorityOpId(row.priority_op_id as u64),
+                    tx_hash: H256::from_slice(&row.tx_hash),
+                    event,
+                    l1_tx_hash: row.l1_tx_hash.as_deref().map(H256::from_slice),
+                    l1_block_number: row
+                        .l1_block_number
+                        .map(|number| L1BlockNumber(number as u32)),
+                    miniblock_number: row
+                        .miniblock_number
+                        .map(|number| MiniblockNumber(number as u32)),
+                    index_in_block: row.index_in_block.map(|idx| idx as u32),
+                    success: row.execution_status.map(|status| status == "success"),
+                    refunded_gas: row.refunded_gas.map(|gas| gas as u64),
+                    recorded_at: DateTime::<Utc>::from_naive_utc_and_offset(row.created_at, Utc),
+                })
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_types::{
+        tx::{tx_execution_info::TxExecutionStatus, ExecutionMetrics, TransactionExecutionResult},
+        L1BlockNumber, MiniblockNumber, ProtocolVersion,
+    };
+
+    use super::*;
+    use crate::{
+        tests::{create_miniblock_header, mock_l1_execute},
+        ConnectionPool, Core, CoreDal,
+    };
+
+    #[tokio::test]
+    async fn recording_priority_op_lifecycle() {
+        let pool = ConnectionPool::<Core>::test_pool().await;
+        let mut conn = pool.connection().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(&ProtocolVersion::default())
+            .await
+            .unwrap();
+        conn.blocks_dal()
+            .insert_miniblock(&create_miniblock_header(1))
+            .await
+            .unwrap();
+
+        let tx = mock_l1_execute();
+        let priority_op_id = tx.serial_id();
+        conn.transactions_dal()
+            .insert_transaction_l1(&tx, L1BlockNumber(1))
+            .await
+            .unwrap();
+        conn.priority_ops_audit_dal()
+            .insert_received_event(&tx)
+            .await
+            .unwrap();
+        // Repeated insertion must not produce a duplicate entry.
+        conn.priority_ops_audit_dal()
+            .insert_received_event(&tx)
+            .await
+            .unwrap();
+
+        let tx_result = TransactionExecutionResult {
+            hash: tx.hash(),
+            transaction: tx.clone().into(),
+            execution_info: ExecutionMetrics::default(),
+            execution_status: TxExecutionStatus::Success,
+            refunded_gas: 100,
+            operator_suggested_refund: 0,
+            compressed_bytecodes: vec![],
+            call_traces: vec![],
+            revert_reason: None,
+        };
+        conn.transactions_dal()
+            .mark_txs_as_executed_in_miniblock(MiniblockNumber(1), &[tx_result.clone()], 1.into())
+            .await
+            .unwrap();
+        conn.priority_ops_audit_dal()
+            .insert_execution_events(MiniblockNumber(1), &[tx_result])
+            .await
+            .unwrap();
+        conn.priority_ops_audit_dal()
+            .insert_rollback_events(MiniblockNumber(0))
+            .await
+            .unwrap();
+
+        let history = conn
+            .priority_ops_audit_dal()
+            .get_priority_op_history(priority_op_id)
+            .await
+            .unwrap();
+        let events: Vec<_> = history.iter().map(|record| record.event).collect();
+        assert_eq!(
+            events,
+            [
+                PriorityOpAuditEvent::Received,
+                PriorityOpAuditEvent::Executed,
+                PriorityOpAuditEvent::RolledBack
+            ]
+        );
+        assert!(history.iter().all(|record| record.tx_hash == tx.hash()));
+        assert_eq!(history[0].l1_tx_hash, Some(tx.common_data.eth_hash));
+        assert_eq!(history[0].l1_block_number, Some(L1BlockNumber(1)));
+        assert_eq!(history[1].miniblock_number, Some(MiniblockNumber(1)));
+        assert_eq!(history[1].index_in_block, Some(0));
+        assert_eq!(history[1].success, Some(true));
+        assert_eq!(history[1].refunded_gas, Some(100));
+        assert_eq!(history[2].miniblock_number, Some(MiniblockNumber(1)));
+    }
+}
@@ -1,5 +1,8 @@
-use zksync_db_connection::connection::Connection;
-use zksync_types::api::ProtocolVersion;
+use zksync_db_connection::{connection::Connection, error::DalResult, instrument::InstrumentExt};
+use zksync_types::{
+    api::{ProtocolVersion, ProtocolVersionInfo},
+    L1BatchNumber,
+};
 
 use crate::{models::storage_protocol_version::StorageProtocolVersion, Core};
 
@@ -49,4 +52,114 @@ impl ProtocolVersionsWeb3Dal<'_, '_> {
 
         ProtocolVersion::from(storage_protocol_version)
     }
+
+    /// Returns all known protocol versions ordered by their ID, together with the first L1 batch
+    /// produced with each version.
+    pub async fn get_protocol_versions(&mut self) -> DalResult<Vec<ProtocolVersionInfo>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                protocol_versions.*,
+                (
+                    SELECT
+                        MIN(number)
+                    FROM
+                        l1_batches
+                    WHERE
+                        l1_batches.protocol_version = protocol_versions.id
+                ) AS "first_l1_batch_number?"
+            FROM
+                protocol_versions
+            ORDER BY
+                id
+            "#
+        )
+        .instrument("get_protocol_versions")
+        .fetch_all(self.storage)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let version = StorageProtocolVersion {
+                    id: row.id,
+                    timestamp: row.timestamp,
+                    recursion_scheduler_level_vk_hash: row.recursion_scheduler_level_vk_hash,
+                    recursion_node_level_vk_hash: row.recursion_node_level_vk_hash,
+                    recursion_leaf_level_vk_hash: row.recursion_leaf_level_vk_hash,
+                    recursion_circuits_set_vks_hash: row.recursion_circuits_set_vks_hash,
+                    bootloader_code_hash: row.bootloader_code_hash,
+                    default_account_code_hash: row.default_account_code_hash,
+                    verifier_address: row.verifier_address,
+                    created_at: row.created_at,
+                    upgrade_tx_hash: row.upgrade_tx_hash,
+                };
+                ProtocolVersionInfo {
+                    version: version.into(),
+                    first_l1_batch_number: row
+                        .first_l1_batch_number
+                        .map(|number| L1BatchNumber(number as u32)),
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_contracts::BaseSystemContractsHashes;
+    use zksync_types::{block::L1BatchHeader, protocol_upgrade, ProtocolVersionId, H256};
+
+    use super::*;
+    use crate::{ConnectionPool, CoreDal};
+
+    #[tokio::test]
+    async fn getting_protocol_versions() {
+        let pool = ConnectionPool::<Core>::test_pool().await;
+        let mut conn = pool.connection().await.unwrap();
+        let base_system_contracts_hashes = BaseSystemContractsHashes {
+            bootloader: H256::repeat_byte(1),
+            default_aa: H256::repeat_byte(2),
+        };
+        let version_ids = [ProtocolVersionId::latest(), ProtocolVersionId::next()];
+        for (i, &id) in version_ids.iter().enumerate() {
+            conn.protocol_versions_dal()
+                .save_protocol_version_with_tx(&protocol_upgrade::ProtocolVersion {
+                    id,
+                    timestamp: i as u64 * 1_000,
+                    base_system_contracts_hashes,
+                    ..protocol_upgrade::ProtocolVersion::default()
+                })
+                .await
+                .unwrap();
+        }
+        for number in [1, 2] {
+            let header = L1BatchHeader::new(
+                L1BatchNumber(number),
+                number.into(),
+                base_system_contracts_hashes,
+                version_ids[0],
+            );
+            conn.blocks_dal()
+                .insert_mock_l1_batch(&header)
+                .await
+                .unwrap();
+        }
+
+        let versions = conn
+            .protocol_versions_web3_dal()
+            .get_protocol_versions()
+            .await
+            .unwrap();
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[0].version.version_id, version_ids[0] as u16);
+        assert_eq!(versions[0].first_l1_batch_number, Some(L1BatchNumber(1)));
+        assert_eq!(
+            versions[0].version.base_system_contracts,
+            base_system_contracts_hashes
+        );
+        assert_eq!(versions[1].version.version_id, version_ids[1] as u16);
+        assert_eq!(versions[1].version.timestamp, 1_000);
+        assert_eq!(versions[1].first_l1_batch_number, None);
+    }
 }
@@ -0,0 +1,74 @@
+//! Management of Postgres notifications used by the API server pub-sub.
+
+use zksync_db_connection::{connection::Connection, error::DalResult, instrument::InstrumentExt};
+
+use crate::Core;
+
+/// Channel notified after new miniblocks are inserted.
+pub const NEW_MINIBLOCKS_CHANNEL: &str = "new_miniblocks";
+/// Channel notified after new transactions are inserted.
+pub const NEW_TRANSACTIONS_CHANNEL: &str = "new_transactions";
+/// Channel notified after new L1 batches are inserted, or L1 transactions for batches are confirmed.
+pub const L1_BATCH_UPDATES_CHANNEL: &str = "l1_batch_updates";
+
+/// Tables with triggers sending notifications, together with the trigger names.
+const NOTIFYING_TRIGGERS: [(&str, &str); 4] = [
+    ("miniblocks", "notify_new_miniblocks"),
+    ("transactions", "notify_new_transactions"),
+    ("l1_batches", "notify_sealed_l1_batches"),
+    ("eth_txs_history", "notify_confirmed_eth_txs"),
+];
+
+/// Inspects triggers sending pub-sub notifications. Triggers are enabled by migrations; operators may disable them
+/// manually (e.g., using `ALTER TABLE miniblocks DISABLE TRIGGER notify_new_miniblocks`), but they are never toggled
+/// by servers. Notifications are only sent by the primary database, so they should be listened to on the primary
+/// as well.
+#[derive(Debug)]
+pub struct PubSubNotificationsDal<'a, 'c> {
+    pub(crate) storage: &'a mut Connection<'c, Core>,
+}
+
+impl PubSubNotificationsDal<'_, '_> {
+    /// Checks whether all notifying triggers are enabled.
+    pub async fn are_notifications_enabled(&mut self) -> DalResult<bool> {
+        let trigger_names: Vec<_> = NOTIFYING_TRIGGERS.iter().map(|&(_, name)| name).collect();
+        let enabled_count = sqlx::query!(
+            r#"
+            SELECT
+                COUNT(*) AS "count!"
+            FROM
+                pg_trigger
+            WHERE
+                tgname::TEXT = ANY ($1)
+                AND tgenabled <> 'D'
+            "#,
+            &trigger_names as &[&str]
+        )
+        .instrument("are_notifications_enabled")
+        .fetch_one(self.storage)
+        .await?
+        .count;
+
+        Ok(enabled_count == NOTIFYING_TRIGGERS.len() as i64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConnectionPool, CoreDal};
+
+    #[tokio::test]
+    async fn notifications_are_enabled_by_migrations() {
+        let pool = ConnectionPool::<Core>::test_pool().await;
+        let mut conn = pool.connection().await.unwrap();
+        let mut dal = conn.pubsub_notifications_dal();
+        assert!(dal.are_notifications_enabled().await.unwrap());
+
+        sqlx::query("ALTER TABLE l1_batches DISABLE TRIGGER notify_sealed_l1_batches")
+            .execute(dal.storage.conn())
+            .await
+            .unwrap();
+        assert!(!dal.are_notifications_enabled().await.unwrap());
+    }
+}
@@ -0,0 +1,169 @@
+//! Storage of compressed raw miniblocks used for bulk synchronization.
+
+use zksync_db_connection::{
+    connection::Connection,
+    error::DalResult,
+    instrument::{InstrumentExt, Instrumented},
+};
+use zksync_types::MiniblockNumber;
+
+use crate::Core;
+
+/// Stores [raw blocks](zksync_types::api::en::RawBlock) persisted in the background after miniblocks are sealed.
+/// Raw blocks are removed automatically when the corresponding miniblocks are reverted.
+#[derive(Debug)]
+pub struct RawBlocksDal<'a, 'c> {
+    pub(crate) storage: &'a mut Connection<'c, Core>,
+}
+
+impl RawBlocksDal<'_, '_> {
+    /// Persists an [encoded](zksync_types::api::en::RawBlock::encode()) raw block. The miniblock must be inserted
+    /// beforehand.
+    pub async fn insert_encoded_raw_block(
+        &mut self,
+        miniblock_number: MiniblockNumber,
+        data: &[u8],
+    ) -> DalResult<()> {
+        let instrumentation = Instrumented::new("insert_encoded_raw_block")
+            .with_arg("miniblock_number", &miniblock_number)
+            .with_arg("data.len", &data.len());
+        let query = sqlx::query!(
+            r#"
+            INSERT INTO
+                raw_miniblocks (number, data, created_at, updated_at)
+            VALUES
+                ($1, $2, NOW(), NOW())
+            ON CONFLICT (number) DO
+            UPDATE
+            SET
+                data = excluded.data,
+                updated_at = NOW()
+            "#,
+            i64::from(miniblock_number.0),
+            data
+        );
+        instrumentation.with(query).execute(self.storage).await?;
+        Ok(())
+    }
+
+    /// Returns the [encoded](zksync_types::api::en::RawBlock::encode()) raw block for the specified miniblock,
+    /// or `None` if the miniblock doesn't exist or its raw block isn't persisted (e.g., if the miniblock was sealed
+    /// before raw blocks started being persisted).
+    pub async fn get_encoded_raw_block(
+        &mut self,
+        miniblock_number: MiniblockNumber,
+    ) -> DalResult<Option<Vec<u8>>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                data
+            FROM
+                raw_miniblocks
+            WHERE
+                number = $1
+            "#,
+            i64::from(miniblock_number.0)
+        )
+        .instrument("get_encoded_raw_block")
+        .with_arg("miniblock_number", &miniblock_number)
+        .report_latency()
+        .fetch_optional(self.storage)
+        .await?;
+
+        Ok(row.map(|row| row.data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_types::{
+        api::en::{RawBlock, RawTransactionResult, SyncBlock},
+        Address, L1BatchNumber, ProtocolVersion, ProtocolVersionId, Transaction,
+    };
+
+    use super::*;
+    use crate::{
+        tests::{create_miniblock_header, mock_l2_transaction},
+        ConnectionPool, CoreDal,
+    };
+
+    fn create_raw_block(number: u32) -> RawBlock {
+        let header = create_miniblock_header(number);
+        let tx: Transaction = mock_l2_transaction().into();
+        RawBlock {
+            block: SyncBlock {
+                number: header.number,
+                l1_batch_number: L1BatchNumber(1),
+                last_in_batch: false,
+                timestamp: header.timestamp,
+                l1_gas_price: 100,
+                l2_fair_gas_price: 100,
+                fair_pubdata_price: None,
+                base_system_contracts_hashes: header.base_system_contracts_hashes,
+                operator_address: Address::default(),
+                transactions: Some(vec![tx.clone()]),
+                virtual_blocks: Some(1),
+                hash: Some(header.hash),
+                protocol_version: ProtocolVersionId::default(),
+            },
+            results: vec![RawTransactionResult {
+                hash: tx.hash(),
+                success: true,
+                refunded_gas: 10,
+                revert_reason: None,
+            }],
+            events: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn storing_raw_blocks() {
+        let pool = ConnectionPool::<Core>::test_pool().await;
+        let mut conn = pool.connection().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(&ProtocolVersion::default())
+            .await
+            .unwrap();
+        conn.blocks_dal()
+            .insert_miniblock(&create_miniblock_header(1))
+            .await
+            .unwrap();
+
+        let raw_block = create_raw_block(1);
+        conn.raw_blocks_dal()
+            .insert_encoded_raw_block(MiniblockNumber(1), &raw_block.encode())
+            .await
+            .unwrap();
+        let encoded = conn
+            .raw_blocks_dal()
+            .get_encoded_raw_block(MiniblockNumber(1))
+            .await
+            .unwrap()
+            .expect("no raw block");
+        let decoded = RawBlock::decode(&encoded).unwrap();
+        assert_eq!(decoded.block.number, MiniblockNumber(1));
+        assert_eq!(decoded.block.hash, raw_block.block.hash);
+        let decoded_txs = decoded.block.transactions.unwrap();
+        assert_eq!(decoded_txs.len(), 1);
+        assert_eq!(decoded_txs[0].hash(), raw_block.results[0].hash);
+        assert_eq!(decoded.results, raw_block.results);
+
+        let missing_block = conn
+            .raw_blocks_dal()
+            .get_encoded_raw_block(MiniblockNumber(2))
+            .await
+            .unwrap();
+        assert!(missing_block.is_none());
+
+        conn.blocks_dal()
+            .delete_miniblocks(MiniblockNumber(0))
+            .await
+            .unwrap();
+        let reverted_block = conn
+            .raw_blocks_dal()
+            .get_encoded_raw_block(MiniblockNumber(1))
+            .await
+            .unwrap();
+        assert!(reverted_block.is_none());
+    }
+}
@@ -1,3 +1,4 @@
+use futures::{stream::BoxStream, StreamExt, TryStreamExt};
 use zksync_db_connection::{connection::Connection, error::DalResult, instrument::InstrumentExt};
 use zksync_types::{
     snapshots::SnapshotStorageLog, AccountTreeId, Address, L1BatchNumber, MiniblockNumber,
@@ -49,10 +50,23 @@ impl SnapshotsCreatorDal<'_, '_> {
         l1_batch_number: L1BatchNumber,
         hashed_keys_range: std::ops::RangeInclusive<H256>,
     ) -> DalResult<Vec<SnapshotStorageLog>> {
+        self.stream_storage_logs_chunk(miniblock_number, l1_batch_number, hashed_keys_range)
+            .try_collect()
+            .await
+    }
+
+    /// Streaming version of [`Self::get_storage_logs_chunk()`]. Logs are fetched from Postgres lazily,
+    /// so that they don't need to be held in memory all at once.
+    pub fn stream_storage_logs_chunk(
+        &mut self,
+        miniblock_number: MiniblockNumber,
+        l1_batch_number: L1BatchNumber,
+        hashed_keys_range: std::ops::RangeInclusive<H256>,
+    ) -> BoxStream<'_, DalResult<SnapshotStorageLog>> {
         // We need to filter the returned logs by `l1_batch_number` in order to not return "phantom writes", i.e.,
         // logs that have deduplicated writes (e.g., a write to a non-zero value and back to zero in the same L1 batch)
         // which are actually written to in future L1 batches.
-        let storage_logs = sqlx::query!(
+        sqlx::query!(
             r#"
             SELECT
                 storage_logs.key AS "key!",
@@ -95,10 +109,8 @@ impl SnapshotsCreatorDal<'_, '_> {
         .with_arg("max_hashed_key", &hashed_keys_range.end())
         .report_latency()
         .expect_slow_query()
-        .fetch_all(self.storage)
-        .await?
-        .iter()
-        .map(|row| SnapshotStorageLog {
+        .fetch(self.storage)
+        .map_ok(|row| SnapshotStorageLog {
             key: StorageKey::new(
                 AccountTreeId::new(Address::from_slice(&row.address)),
                 H256::from_slice(&row.key),
@@ -107,8 +119,7 @@ impl SnapshotsCreatorDal<'_, '_> {
             l1_batch_number_of_initial_write: L1BatchNumber(row.l1_batch_number as u32),
             enumeration_index: row.index as u64,
         })
-        .collect();
-        Ok(storage_logs)
+        .boxed()
     }
 
     /// Returns all factory dependencies up to and including the specified `miniblock_number`.
@@ -1,10 +1,13 @@
 use std::{collections::HashMap, ops, time::Instant};
 
+use futures::{stream::BoxStream, StreamExt, TryStreamExt};
 use sqlx::types::chrono::Utc;
 use zksync_db_connection::{
+    binary_copy::BinaryCopyBuffer,
     connection::Connection,
+    connection_pool::ConnectionPool,
     error::DalResult,
-    instrument::{CopyStatement, InstrumentExt},
+    instrument::{CopyStatement, InstrumentExt, Instrumented},
     write_str, writeln_str,
 };
 use zksync_types::{
@@ -13,7 +16,7 @@ use zksync_types::{
 };
 
 pub use crate::models::storage_log::{DbStorageLog, StorageRecoveryLogEntry};
-use crate::{Core, CoreDal};
+use crate::{partitions_dal::PartitionedTable, Core, CoreDal};
 
 #[derive(Debug)]
 pub struct StorageLogsDal<'a, 'c> {
@@ -37,24 +40,59 @@ impl StorageLogsDal<'_, '_> {
         logs: &[(H256, Vec<StorageLog>)],
         mut operation_number: u32,
     ) -> DalResult<()> {
+        self.storage
+            .partitions_dal()
+            .ensure_partition(PartitionedTable::StorageLogs, block_number)
+            .await?;
+
         let logs_len = logs.len();
-        let copy = CopyStatement::new(
+        let use_binary_copy = ConnectionPool::<Core>::global_config().use_binary_copy();
+        let statement = if use_binary_copy {
             "COPY storage_logs(
                 hashed_key, address, key, value, operation_number, tx_hash, miniblock_number,
                 created_at, updated_at
             )
-            FROM STDIN WITH (DELIMITER '|')",
-        )
-        .instrument("insert_storage_logs")
-        .with_arg("block_number", &block_number)
-        .with_arg("logs.len", &logs_len)
-        .start(self.storage)
-        .await?;
+            FROM STDIN WITH (FORMAT BINARY)"
+        } else {
+            "COPY storage_logs(
+                hashed_key, address, key, value, operation_number, tx_hash, miniblock_number,
+                created_at, updated_at
+            )
+            FROM STDIN WITH (DELIMITER '|')"
+        };
+        let copy = CopyStatement::new(statement)
+            .instrument("insert_storage_logs")
+            .with_arg("block_number", &block_number)
+            .with_arg("logs.len", &logs_len)
+            .with_arg("use_binary_copy", &use_binary_copy)
+            .start(self.storage)
+            .await?;
 
-        let mut buffer = String::new();
-        let now = Utc::now().naive_utc().to_string();
-        for (tx_hash, logs) in logs {
-            for log in logs {
+        let now = Utc::now().naive_utc();
+        let logs = logs
+            .iter()
+            .flat_map(|(tx_hash, logs)| logs.iter().map(move |log| (tx_hash, log)));
+        let data = if use_binary_copy {
+            let mut buffer = BinaryCopyBuffer::new();
+            for (tx_hash, log) in logs {
+                buffer
+                    .row(9)
+                    .bytes(log.key.hashed_key().as_bytes())
+                    .bytes(log.key.address().as_bytes())
+                    .bytes(log.key.key().as_bytes())
+                    .bytes(log.value.as_bytes())
+                    .i32(operation_number as i32)
+                    .bytes(tx_hash.as_bytes())
+                    .i64(block_number.0.into())
+                    .timestamp(now)
+                    .timestamp(now);
+                operation_number += 1;
+            }
+            buffer.finish()
+        } else {
+            let mut buffer = String::new();
+            let now = now.to_string();
+            for (tx_hash, log) in logs {
                 write_str!(
                     &mut buffer,
                     r"\\x{hashed_key:x}|\\x{address:x}|\\x{key:x}|\\x{value:x}|",
@@ -67,11 +105,11 @@ impl StorageLogsDal<'_, '_> {
                     &mut buffer,
                     r"{operation_number}|\\x{tx_hash:x}|{block_number}|{now}|{now}"
                 );
-
                 operation_number += 1;
             }
-        }
-        copy.send(buffer.as_bytes()).await
+            buffer.into_bytes()
+        };
+        copy.send(&data).await
     }
 
     pub async fn insert_storage_logs_from_snapshot(
@@ -79,39 +117,80 @@ impl StorageLogsDal<'_, '_> {
         miniblock_number: MiniblockNumber,
         snapshot_storage_logs: &[SnapshotStorageLog],
     ) -> DalResult<()> {
+        self.storage
+            .partitions_dal()
+            .ensure_partition(PartitionedTable::StorageLogs, miniblock_number)
+            .await?;
+
         let storage_logs_len = snapshot_storage_logs.len();
-        let copy = CopyStatement::new(
+        let use_binary_copy = ConnectionPool::<Core>::global_config().use_binary_copy();
+        let statement = if use_binary_copy {
             "COPY storage_logs(
                 hashed_key, address, key, value, operation_number, tx_hash, miniblock_number,
                 created_at, updated_at
             )
-            FROM STDIN WITH (DELIMITER '|')",
-        )
-        .instrument("insert_storage_logs_from_snapshot")
-        .with_arg("miniblock_number", &miniblock_number)
-        .with_arg("storage_logs.len", &storage_logs_len)
-        .start(self.storage)
-        .await?;
-
-        let mut buffer = String::new();
-        let now = Utc::now().naive_utc().to_string();
-        for log in snapshot_storage_logs.iter() {
-            write_str!(
-                &mut buffer,
-                r"\\x{hashed_key:x}|\\x{address:x}|\\x{key:x}|\\x{value:x}|",
-                hashed_key = log.key.hashed_key(),
-                address = log.key.address(),
-                key = log.key.key(),
-                value = log.value
-            );
-            writeln_str!(
-                &mut buffer,
-                r"{}|\\x{:x}|{miniblock_number}|{now}|{now}",
-                log.enumeration_index,
-                H256::zero()
-            );
-        }
-        copy.send(buffer.as_bytes()).await
+            FROM STDIN WITH (FORMAT BINARY)"
+        } else {
+            "COPY storage_logs(
+                hashed_key, address, key, value, operation_number, tx_hash, miniblock_number,
+                created_at, updated_at
+            )
+            FROM STDIN WITH (DELIMITER '|')"
+        };
+        let instrumentation = Instrumented::new("insert_storage_logs_from_snapshot")
+            .with_arg("miniblock_number", &miniblock_number)
+            .with_arg("storage_logs.len", &storage_logs_len)
+            .with_arg("use_binary_copy", &use_binary_copy);
+
+        let now = Utc::now().naive_utc();
+        let data = if use_binary_copy {
+            let mut buffer = BinaryCopyBuffer::new();
+            for (i, log) in snapshot_storage_logs.iter().enumerate() {
+                let enumeration_index = i32::try_from(log.enumeration_index).map_err(|err| {
+                    instrumentation.arg_error(
+                        &format!("snapshot_storage_logs[{i}].enumeration_index"),
+                        err,
+                    )
+                })?;
+                buffer
+                    .row(9)
+                    .bytes(log.key.hashed_key().as_bytes())
+                    .bytes(log.key.address().as_bytes())
+                    .bytes(log.key.key().as_bytes())
+                    .bytes(log.value.as_bytes())
+                    .i32(enumeration_index)
+                    .bytes(H256::zero().as_bytes())
+                    .i64(miniblock_number.0.into())
+                    .timestamp(now)
+                    .timestamp(now);
+            }
+            buffer.finish()
+        } else {
+            let mut buffer = String::new();
+            let now = now.to_string();
+            for log in snapshot_storage_logs {
+                write_str!(
+                    &mut buffer,
+                    r"\\x{hashed_key:x}|\\x{address:x}|\\x{key:x}|\\x{value:x}|",
+                    hashed_key = log.key.hashed_key(),
+                    address = log.key.address(),
+                    key = log.key.key(),
+                    value = log.value
+                );
+                writeln_str!(
+                    &mut buffer,
+                    r"{}|\\x{:x}|{miniblock_number}|{now}|{now}",
+                    log.enumeration_index,
+                    H256::zero()
+                );
+            }
+            buffer.into_bytes()
+        };
+        let copy = instrumentation
+            .with(CopyStatement::new(statement))
+            .start(self.storage)
+            .await?;
+        copy.send(&data).await
     }
 
     pub async fn append_storage_logs(
@@ -259,6 +338,137 @@ impl StorageLogsDal<'_, '_> {
             .collect())
     }
 
+    /// Returns final values of storage keys modified in each miniblock of the specified range. Each returned tuple
+    /// consists of the miniblock number, the hashed storage key and its value after the miniblock. Tuples are ordered
+    /// by the miniblock number.
+    pub async fn modified_values_in_miniblocks(
+        &mut self,
+        miniblock_numbers: ops::RangeInclusive<MiniblockNumber>,
+    ) -> DalResult<Vec<(MiniblockNumber, H256, H256)>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT DISTINCT
+                ON (miniblock_number, hashed_key) miniblock_number,
+                hashed_key,
+                value
+            FROM
+                storage_logs
+            WHERE
+                miniblock_number BETWEEN $1 AND $2
+            ORDER BY
+                miniblock_number,
+                hashed_key,
+                operation_number DESC
+            "#,
+            i64::from(miniblock_numbers.start().0),
+            i64::from(miniblock_numbers.end().0)
+        )
+        .instrument("modified_values_in_miniblocks")
+        .with_arg("miniblock_numbers", &miniblock_numbers)
+        .fetch_all(self.storage)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                (
+                    MiniblockNumber(row.miniblock_number as u32),
+                    H256::from_slice(&row.hashed_key),
+                    H256::from_slice(&row.value),
+                )
+            })
+            .collect())
+    }
+
+    /// Returns distinct hashed storage keys together with the corresponding contract addresses that were
+    /// touched in the specified miniblock range. Most recently touched keys are returned first.
+    pub async fn recently_touched_keys(
+        &mut self,
+        miniblock_numbers: ops::RangeInclusive<MiniblockNumber>,
+        limit: usize,
+    ) -> DalResult<Vec<(H256, Address)>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                hashed_key,
+                address
+            FROM
+                storage_logs
+            WHERE
+                miniblock_number BETWEEN $1 AND $2
+            GROUP BY
+                hashed_key,
+                address
+            ORDER BY
+                MAX(miniblock_number) DESC
+            LIMIT
+                $3
+            "#,
+            i64::from(miniblock_numbers.start().0),
+            i64::from(miniblock_numbers.end().0),
+            limit as i64
+        )
+        .instrument("recently_touched_keys")
+        .with_arg("miniblock_numbers", &miniblock_numbers)
+        .with_arg("limit", &limit)
+        .fetch_all(self.storage)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                (
+                    H256::from_slice(&row.hashed_key),
+                    Address::from_slice(&row.address),
+                )
+            })
+            .collect())
+    }
+
+    /// Returns distinct storage keys (i.e., slots, not hashed keys) of the specified account written to
+    /// at or before the specified miniblock, in the ascending order starting from `start_key` (inclusive).
+    /// At most `limit` keys are returned. Keys which were zeroed out are returned as well.
+    pub async fn get_account_storage_keys(
+        &mut self,
+        address: Address,
+        at_miniblock: MiniblockNumber,
+        start_key: H256,
+        limit: usize,
+    ) -> DalResult<Vec<H256>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT DISTINCT
+                key
+            FROM
+                storage_logs
+            WHERE
+                address = $1
+                AND key >= $2
+                AND miniblock_number <= $3
+            ORDER BY
+                key
+            LIMIT
+                $4
+            "#,
+            address.as_bytes(),
+            start_key.as_bytes(),
+            i64::from(at_miniblock.0),
+            limit as i64
+        )
+        .instrument("get_account_storage_keys")
+        .with_arg("address", &address)
+        .with_arg("at_miniblock", &at_miniblock)
+        .with_arg("start_key", &start_key)
+        .with_arg("limit", &limit)
+        .fetch_all(self.storage)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| H256::from_slice(&row.key))
+            .collect())
+    }
+
     /// Removes all storage logs with a miniblock number strictly greater than the specified `block_number`.
     pub async fn rollback_storage_logs(&mut self, block_number: MiniblockNumber) -> DalResult<()> {
         sqlx::query!(
@@ -368,7 +578,18 @@ impl StorageLogsDal<'_, '_> {
         &mut self,
         l1_batch_number: L1BatchNumber,
     ) -> DalResult<HashMap<StorageKey, H256>> {
-        let rows = sqlx::query!(
+        self.stream_touched_slots_for_l1_batch(l1_batch_number)
+            .try_collect()
+            .await
+    }
+
+    /// Streaming version of [`Self::get_touched_slots_for_l1_batch()`]. Slots are returned in the order
+    /// of storage logs, so a slot may be returned multiple times; the last returned value is the latest one.
+    pub fn stream_touched_slots_for_l1_batch(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+    ) -> BoxStream<'_, DalResult<(StorageKey, H256)>> {
+        sqlx::query!(
             r#"
             SELECT
                 address,
@@ -400,17 +621,15 @@ impl StorageLogsDal<'_, '_> {
         )
         .instrument("get_touched_slots_for_l1_batch")
         .with_arg("l1_batch_number", &l1_batch_number)
-        .fetch_all(self.storage)
-        .await?;
-
-        let touched_slots = rows.into_iter().map(|row| {
+        .fetch(self.storage)
+        .map_ok(|row| {
             let key = StorageKey::new(
                 AccountTreeId::new(Address::from_slice(&row.address)),
                 H256::from_slice(&row.key),
             );
             (key, H256::from_slice(&row.value))
-        });
-        Ok(touched_slots.collect())
+        })
+        .boxed()
     }
 
     /// Returns (hashed) storage keys and the corresponding values that need to be applied to a storage
@@ -863,6 +1082,44 @@ mod tests {
         test_rollback(&mut conn, first_key, second_key).await;
     }
 
+    #[tokio::test]
+    async fn inserting_storage_logs_with_binary_copy() {
+        // Enabling binary `COPY` globally doesn't influence other tests since both insertion methods
+        // must produce the same results.
+        ConnectionPool::<Core>::global_config().set_use_binary_copy(true);
+        let pool = ConnectionPool::<Core>::test_pool().await;
+        let mut conn = pool.connection().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(&ProtocolVersion::default())
+            .await
+            .unwrap();
+
+        let account = AccountTreeId::new(Address::repeat_byte(1));
+        let logs: Vec<_> = (0..10)
+            .map(|i| {
+                let key = StorageKey::new(account, H256::from_low_u64_be(i));
+                StorageLog::new_write_log(key, H256::from_low_u64_be(i + 1))
+            })
+            .collect();
+        insert_miniblock(&mut conn, 1, logs.clone()).await;
+
+        let touched_slots = conn
+            .storage_logs_dal()
+            .get_touched_slots_for_l1_batch(L1BatchNumber(1))
+            .await
+            .unwrap();
+        assert_eq!(touched_slots.len(), logs.len());
+        for log in &logs {
+            assert_eq!(touched_slots[&log.key], log.value);
+        }
+        let value = conn
+            .storage_web3_dal()
+            .get_value(&logs[3].key)
+            .await
+            .unwrap();
+        assert_eq!(value, logs[3].value);
+    }
+
     async fn test_rollback(
         conn: &mut Connection<'_, Core>,
         key: StorageKey,
@@ -1155,6 +1412,60 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn getting_account_storage_keys() {
+        let pool = ConnectionPool::<Core>::test_pool().await;
+        let mut conn = pool.connection().await.unwrap();
+        prepare_tree_entries(&mut conn, 10).await;
+        let account = AccountTreeId::new(Address::repeat_byte(1));
+        let other_account = AccountTreeId::new(Address::repeat_byte(2));
+        let logs = vec![
+            StorageLog::new_write_log(
+                StorageKey::new(account, H256::repeat_byte(0xff)),
+                H256::zero(),
+            ),
+            StorageLog::new_write_log(
+                StorageKey::new(other_account, H256::repeat_byte(3)),
+                H256::repeat_byte(1),
+            ),
+        ];
+        insert_miniblock(&mut conn, 2, logs).await;
+
+        let keys = conn
+            .storage_logs_dal()
+            .get_account_storage_keys(*account.address(), MiniblockNumber(1), H256::zero(), 100)
+            .await
+            .unwrap();
+        let expected_keys: Vec<_> = (0..10).map(H256::repeat_byte).collect();
+        assert_eq!(keys, expected_keys);
+
+        let keys = conn
+            .storage_logs_dal()
+            .get_account_storage_keys(
+                *account.address(),
+                MiniblockNumber(2),
+                H256::repeat_byte(8),
+                100,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            keys,
+            [
+                H256::repeat_byte(8),
+                H256::repeat_byte(9),
+                H256::repeat_byte(0xff)
+            ]
+        );
+
+        let keys = conn
+            .storage_logs_dal()
+            .get_account_storage_keys(*account.address(), MiniblockNumber(2), H256::zero(), 3)
+            .await
+            .unwrap();
+        assert_eq!(keys, expected_keys[..3]);
+    }
+
     #[tokio::test]
     async fn filtering_deployed_contracts() {
         let contract_address = Address::repeat_byte(1);
@@ -2,7 +2,9 @@ use std::collections::HashSet;
 
 use sqlx::types::chrono::Utc;
 use zksync_db_connection::{
+    binary_copy::BinaryCopyBuffer,
     connection::Connection,
+    connection_pool::ConnectionPool,
     error::DalResult,
     instrument::{CopyStatement, InstrumentExt},
 };
@@ -51,6 +53,65 @@ impl StorageLogsDedupDal<'_, '_> {
         copy.send(&bytes).await
     }
 
+    /// Marks protective reads for the specified L1 batch as persisted. Must be called in the same DB transaction
+    /// as [`Self::insert_protective_reads()`].
+    pub async fn mark_protective_reads_as_persisted(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+    ) -> DalResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO
+                protective_reads_l1_batches (l1_batch_number, created_at, updated_at)
+            VALUES
+                ($1, NOW(), NOW())
+            ON CONFLICT (l1_batch_number) DO NOTHING
+            "#,
+            i64::from(l1_batch_number.0)
+        )
+        .instrument("mark_protective_reads_as_persisted")
+        .with_arg("l1_batch_number", &l1_batch_number)
+        .execute(self.storage)
+        .await?;
+        Ok(())
+    }
+
+    /// Returns the earliest L1 batch with number `>= from_l1_batch` for which protective reads are not persisted yet.
+    pub async fn get_first_l1_batch_without_protective_reads(
+        &mut self,
+        from_l1_batch: L1BatchNumber,
+    ) -> DalResult<Option<L1BatchNumber>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                number
+            FROM
+                l1_batches
+            WHERE
+                number >= $1
+                AND NOT EXISTS (
+                    SELECT
+                        1
+                    FROM
+                        protective_reads_l1_batches
+                    WHERE
+                        protective_reads_l1_batches.l1_batch_number = l1_batches.number
+                )
+            ORDER BY
+                number
+            LIMIT
+                1
+            "#,
+            i64::from(from_l1_batch.0)
+        )
+        .instrument("get_first_l1_batch_without_protective_reads")
+        .with_arg("from_l1_batch", &from_l1_batch)
+        .fetch_optional(self.storage)
+        .await?;
+
+        Ok(row.map(|row| L1BatchNumber(row.number as u32)))
+    }
+
     /// Insert initial writes and assigns indices to them.
     /// Assumes indices are already assigned for all saved initial_writes, so must be called only after the migration.
     pub async fn insert_initial_writes_from_snapshot(
@@ -98,6 +159,32 @@ impl StorageLogsDedupDal<'_, '_> {
             .map(|x| x as i64)
             .collect();
 
+        if ConnectionPool::<Core>::global_config().use_binary_copy() {
+            let hashed_keys_len = hashed_keys.len();
+            let copy = CopyStatement::new(
+                "COPY initial_writes (hashed_key, index, l1_batch_number, created_at, updated_at) \
+                 FROM STDIN WITH (FORMAT BINARY)",
+            )
+            .instrument("insert_initial_writes#copy")
+            .with_arg("l1_batch_number", &l1_batch_number)
+            .with_arg("hashed_keys.len", &hashed_keys_len)
+            .start(self.storage)
+            .await?;
+
+            let now = Utc::now().naive_utc();
+            let mut buffer = BinaryCopyBuffer::new();
+            for (hashed_key, &index) in hashed_keys.iter().zip(&indices) {
+                buffer
+                    .row(5)
+                    .bytes(hashed_key)
+                    .i64(index)
+                    .i64(l1_batch_number.0.into())
+                    .timestamp(now)
+                    .timestamp(now);
+            }
+            return copy.send(&buffer.finish()).await;
+        }
+
         sqlx::query!(
             r#"
             INSERT INTO
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, ops};
 
 use zksync_db_connection::{connection::Connection, error::DalResult, instrument::InstrumentExt};
 use zksync_types::{
@@ -94,6 +94,19 @@ impl StorageWeb3Dal<'_, '_> {
         key: &StorageKey,
         block_number: MiniblockNumber,
     ) -> DalResult<H256> {
+        let value = self
+            .get_historical_value_opt_unchecked(key, block_number)
+            .await?;
+        Ok(value.unwrap_or_default())
+    }
+
+    /// Same as [`Self::get_historical_value_unchecked()`], but distinguishes between keys that were never written to
+    /// up to (and including) the specified block (`None` is returned for them), and keys with a zero value.
+    pub async fn get_historical_value_opt_unchecked(
+        &mut self,
+        key: &StorageKey,
+        block_number: MiniblockNumber,
+    ) -> DalResult<Option<H256>> {
         let hashed_key = key.hashed_key();
 
         sqlx::query!(
@@ -114,17 +127,13 @@ impl StorageWeb3Dal<'_, '_> {
             hashed_key.as_bytes(),
             i64::from(block_number.0)
         )
-        .instrument("get_historical_value_unchecked")
+        .instrument("get_historical_value_opt_unchecked")
         .report_latency()
         .with_arg("key", &hashed_key)
         .with_arg("block_number", &block_number)
         .fetch_optional(self.storage)
         .await
-        .map(|option_row| {
-            option_row
-                .map(|row| H256::from_slice(&row.value))
-                .unwrap_or_else(H256::zero)
-        })
+        .map(|option_row| option_row.map(|row| H256::from_slice(&row.value)))
     }
 
     /// Provides information about the L1 batch that the specified miniblock is a part of.
@@ -263,6 +272,80 @@ impl StorageWeb3Dal<'_, '_> {
 
         Ok(row.map(|row| (row.bytecode, MiniblockNumber(row.miniblock_number as u32))))
     }
+
+    /// Batched version of [`Self::get_factory_dep()`]. Returns bytecodes together with miniblocks they were
+    /// inserted at for the factory deps with the specified `hashes`; missing factory deps are omitted.
+    pub async fn get_factory_deps_with_miniblocks(
+        &mut self,
+        hashes: &[H256],
+    ) -> DalResult<HashMap<H256, (Vec<u8>, MiniblockNumber)>> {
+        let hashes_as_bytes: Vec<_> = hashes.iter().map(H256::as_bytes).collect();
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                bytecode_hash,
+                bytecode,
+                miniblock_number
+            FROM
+                factory_deps
+            WHERE
+                bytecode_hash = ANY ($1)
+            "#,
+            &hashes_as_bytes as &[&[u8]],
+        )
+        .instrument("get_factory_deps_with_miniblocks")
+        .with_arg("hashes.len", &hashes.len())
+        .fetch_all(self.storage)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let hash = H256::from_slice(&row.bytecode_hash);
+                let miniblock_number = MiniblockNumber(row.miniblock_number as u32);
+                (hash, (row.bytecode, miniblock_number))
+            })
+            .collect())
+    }
+
+    /// Returns factory deps inserted in the specified miniblock range. Each returned tuple consists of the miniblock
+    /// number, the bytecode hash and the bytecode. Tuples are ordered by the miniblock number.
+    pub async fn get_factory_deps_in_miniblocks(
+        &mut self,
+        miniblock_numbers: ops::RangeInclusive<MiniblockNumber>,
+    ) -> DalResult<Vec<(MiniblockNumber, H256, Vec<u8>)>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                miniblock_number,
+                bytecode_hash,
+                bytecode
+            FROM
+                factory_deps
+            WHERE
+                miniblock_number BETWEEN $1 AND $2
+            ORDER BY
+                miniblock_number
+            "#,
+            i64::from(miniblock_numbers.start().0),
+            i64::from(miniblock_numbers.end().0)
+        )
+        .instrument("get_factory_deps_in_miniblocks")
+        .with_arg("miniblock_numbers", &miniblock_numbers)
+        .fetch_all(self.storage)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                (
+                    MiniblockNumber(row.miniblock_number as u32),
+                    H256::from_slice(&row.bytecode_hash),
+                    row.bytecode,
+                )
+            })
+            .collect())
+    }
 }
 
 #[cfg(test)]
@@ -1,5 +1,6 @@
 use std::time::Duration;
 
+use sqlx::types::chrono::NaiveDateTime;
 use zksync_contracts::BaseSystemContractsHashes;
 use zksync_db_connection::connection_pool::ConnectionPool;
 use zksync_types::{
@@ -203,7 +204,15 @@ async fn workflow_with_submit_tx_diff_hashes() {
         .unwrap();
 
     assert_eq!(result, L2TxSubmissionResult::Added);
+    let (pending_hash, pending_fee) = transactions_dal
+        .get_pending_tx_fee(initiator_address, nonce)
+        .await
+        .unwrap()
+        .expect("no pending tx");
+    assert_eq!(pending_hash, tx.hash());
+    assert_eq!(pending_fee, tx.common_data.fee);
 
+    let replaced_hash = tx.hash();
     let mut tx = mock_l2_transaction();
     tx.common_data.nonce = nonce;
     tx.common_data.initiator_address = initiator_address;
@@ -213,6 +222,31 @@ async fn workflow_with_submit_tx_diff_hashes() {
         .unwrap();
 
     assert_eq!(result, L2TxSubmissionResult::Replaced);
+    // Replacement must not change the number of pending transactions.
+    let pending_count = transactions_dal
+        .get_pending_txs_count(initiator_address)
+        .await
+        .unwrap();
+    assert_eq!(pending_count, 1);
+    let pending_count = transactions_dal
+        .get_pending_txs_count(Address::repeat_byte(0xff))
+        .await
+        .unwrap();
+    assert_eq!(pending_count, 0);
+
+    let mut transactions_web3_dal = TransactionsWeb3Dal {
+        storage: transactions_dal.storage,
+    };
+    let replacements = transactions_web3_dal
+        .get_tx_replacements_after(NaiveDateTime::default())
+        .await
+        .unwrap();
+    assert_eq!(replacements.len(), 1);
+    let replacement = &replacements[0].1;
+    assert_eq!(replacement.replaced_hash, replaced_hash);
+    assert_eq!(replacement.hash, tx.hash());
+    assert_eq!(replacement.from, initiator_address);
+    assert_eq!(replacement.nonce, nonce.0.into());
 }
 
 #[tokio::test]
@@ -229,10 +263,11 @@ async fn remove_stuck_txs() {
     let mut transactions_dal = TransactionsDal { storage };
 
     // Stuck tx
-    let mut tx = mock_l2_transaction();
-    tx.received_timestamp_ms = unix_timestamp_ms() - Duration::new(1000, 0).as_millis() as u64;
+    let mut stuck_tx = mock_l2_transaction();
+    stuck_tx.received_timestamp_ms =
+        unix_timestamp_ms() - Duration::new(1000, 0).as_millis() as u64;
     transactions_dal
-        .insert_transaction_l2(&tx, mock_tx_execution_metrics())
+        .insert_transaction_l2(&stuck_tx, mock_tx_execution_metrics())
         .await
         .unwrap();
     // Tx in mempool
@@ -262,7 +297,7 @@ async fn remove_stuck_txs() {
     // Get all txs
     transactions_dal.reset_mempool().await.unwrap();
     let txs = transactions_dal
-        .sync_mempool(&[], &[], 0, 0, 1000)
+        .sync_mempool(&[], &[], &[], 0, 0, 1000)
         .await
         .unwrap();
     assert_eq!(txs.len(), 4);
@@ -286,7 +321,7 @@ async fn remove_stuck_txs() {
     // Get all txs
     transactions_dal.reset_mempool().await.unwrap();
     let txs = transactions_dal
-        .sync_mempool(&[], &[], 0, 0, 1000)
+        .sync_mempool(&[], &[], &[], 0, 0, 1000)
         .await
         .unwrap();
     assert_eq!(txs.len(), 3);
@@ -296,10 +331,11 @@ async fn remove_stuck_txs() {
         .remove_stuck_txs(Duration::from_secs(500))
         .await
         .unwrap();
-    assert_eq!(removed_txs, 1);
-    transactions_dal.reset_mempool().await.unwrap();
+    assert_eq!(removed_txs, [stuck_tx.hash()]);
+    let reset_tx_count = transactions_dal.reset_mempool().await.unwrap();
+    assert_eq!(reset_tx_count, 2);
     let txs = transactions_dal
-        .sync_mempool(&[], &[], 0, 0, 1000)
+        .sync_mempool(&[], &[], &[], 0, 0, 1000)
         .await
         .unwrap();
     assert_eq!(txs.len(), 2);
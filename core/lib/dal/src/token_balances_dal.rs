@@ -0,0 +1,464 @@
+//! Index of token holders used to quickly look up non-zero token balances of an account.
+
+use std::{collections::HashSet, ops};
+
+use zksync_db_connection::{
+    connection::Connection,
+    error::DalResult,
+    instrument::{InstrumentExt, Instrumented},
+};
+use zksync_system_constants::ERC20_TRANSFER_TOPIC;
+use zksync_types::{Address, MiniblockNumber, VmEvent};
+use zksync_utils::h256_to_account_address;
+
+use crate::Core;
+
+/// Maintains the index of (holder, token) pairs extracted from ERC-20 `Transfer` events. The index is updated
+/// when sealing miniblocks and is cleaned up automatically when miniblocks are reverted.
+///
+/// The index doesn't store balances themselves; they should be read from the storage for the returned tokens.
+/// Since a token can only be held after a transfer (incl. minting, which is a transfer from the zero address),
+/// the index covers all tokens following the ERC-20 standard, except for the base token (ETH), which is minted
+/// without emitting `Transfer` events.
+#[derive(Debug)]
+pub struct TokenBalancesDal<'a, 'c> {
+    pub(crate) storage: &'a mut Connection<'c, Core>,
+}
+
+impl TokenBalancesDal<'_, '_> {
+    /// Indexes token holders from `Transfer` events emitted in the specified miniblock.
+    pub async fn insert_token_holders(
+        &mut self,
+        miniblock_number: MiniblockNumber,
+        events: &[VmEvent],
+    ) -> DalResult<()> {
+        let holders: HashSet<_> = events
+            .iter()
+            .filter(|event| {
+                // ERC-721 `Transfer` events have the same signature, but 4 topics (the last one is the token ID).
+                event.indexed_topics.len() == 3 && event.indexed_topics[0] == ERC20_TRANSFER_TOPIC
+            })
+            .flat_map(|event| {
+                let from = h256_to_account_address(&event.indexed_topics[1]);
+                let to = h256_to_account_address(&event.indexed_topics[2]);
+                [(from, event.address), (to, event.address)]
+            })
+            .filter(|(holder, _)| *holder != Address::zero())
+            .collect();
+        if holders.is_empty() {
+            return Ok(());
+        }
+
+        let (holder_addresses, token_addresses): (Vec<_>, Vec<_>) = holders
+            .iter()
+            .map(|(holder, token)| (holder.as_bytes(), token.as_bytes()))
+            .unzip();
+        let instrumentation = Instrumented::new("insert_token_holders")
+            .with_arg("miniblock_number", &miniblock_number)
+            .with_arg("holders.len", &holders.len());
+        let query = sqlx::query!(
+            r#"
+            INSERT INTO
+                token_balance_holders (
+                    holder_address,
+                    token_address,
+                    miniblock_number,
+                    created_at,
+                    updated_at
+                )
+            SELECT
+                u.holder_address,
+                u.token_address,
+                $1,
+                NOW(),
+                NOW()
+            FROM
+                UNNEST($2::bytea[], $3::bytea[]) AS u (holder_address, token_address)
+            ON CONFLICT (holder_address, token_address) DO NOTHING
+            "#,
+            i64::from(miniblock_number.0),
+            &holder_addresses as &[&[u8]],
+            &token_addresses as &[&[u8]],
+        );
+
+        instrumentation.with(query).execute(self.storage).await?;
+        Ok(())
+    }
+
+    /// Returns the number of the miniblock containing the `(max_transfers + 1)`th ERC-20 `Transfer` event
+    /// in the specified miniblock range, or `None` if the range contains fewer events. Used to split the backfill
+    /// of the index into batches.
+    pub async fn get_transfers_batch_bound(
+        &mut self,
+        miniblock_range: ops::RangeInclusive<MiniblockNumber>,
+        max_transfers: usize,
+    ) -> DalResult<Option<MiniblockNumber>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                miniblock_number
+            FROM
+                events
+            WHERE
+                miniblock_number BETWEEN $1 AND $2
+                AND topic1 = $3
+            ORDER BY
+                miniblock_number,
+                event_index_in_block
+            OFFSET
+                $4
+            LIMIT
+                1
+            "#,
+            i64::from(miniblock_range.start().0),
+            i64::from(miniblock_range.end().0),
+            ERC20_TRANSFER_TOPIC.as_bytes(),
+            max_transfers as i64
+        )
+        .instrument("get_transfers_batch_bound")
+        .with_arg("miniblock_range", &miniblock_range)
+        .with_arg("max_transfers", &max_transfers)
+        .report_latency()
+        .fetch_optional(self.storage)
+        .await?;
+
+        Ok(row.map(|row| MiniblockNumber(row.miniblock_number as u32)))
+    }
+
+    /// Indexes token holders from `Transfer` events persisted for the specified miniblock range. Returns the number
+    /// of indexed (holder, token) pairs.
+    ///
+    /// Unlike [`Self::insert_token_holders()`], this method may run concurrently with indexing newer miniblocks,
+    /// so it overwrites the miniblock number of already indexed pairs if it's greater than the backfilled one.
+    pub async fn backfill_token_holders(
+        &mut self,
+        miniblock_range: ops::RangeInclusive<MiniblockNumber>,
+    ) -> DalResult<u64> {
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO
+                token_balance_holders (
+                    holder_address,
+                    token_address,
+                    miniblock_number,
+                    created_at,
+                    updated_at
+                )
+            SELECT
+                holder_address,
+                token_address,
+                MIN(miniblock_number),
+                NOW(),
+                NOW()
+            FROM
+                (
+                    SELECT
+                        SUBSTRING(topic2 FROM 13) AS holder_address,
+                        address AS token_address,
+                        miniblock_number
+                    FROM
+                        events
+                    WHERE
+                        miniblock_number BETWEEN $1 AND $2
+                        AND topic1 = $3
+                        AND topic4 = ''
+                    UNION ALL
+                    SELECT
+                        SUBSTRING(topic3 FROM 13) AS holder_address,
+                        address AS token_address,
+                        miniblock_number
+                    FROM
+                        events
+                    WHERE
+                        miniblock_number BETWEEN $1 AND $2
+                        AND topic1 = $3
+                        AND topic4 = ''
+                ) AS transfers
+            WHERE
+                holder_address <> $4
+            GROUP BY
+                holder_address,
+                token_address
+            ON CONFLICT (holder_address, token_address) DO
+            UPDATE
+            SET
+                miniblock_number = excluded.miniblock_number,
+                updated_at = NOW()
+            WHERE
+                token_balance_holders.miniblock_number > excluded.miniblock_number
+            "#,
+            i64::from(miniblock_range.start().0),
+            i64::from(miniblock_range.end().0),
+            ERC20_TRANSFER_TOPIC.as_bytes(),
+            Address::zero().as_bytes()
+        )
+        .instrument("backfill_token_holders")
+        .with_arg("miniblock_range", &miniblock_range)
+        .report_latency()
+        .execute(self.storage)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Returns L2 addresses of known tokens (i.e., ones present in the `tokens` table) that were ever held
+    /// by the specified address.
+    pub async fn get_held_tokens(&mut self, holder: Address) -> DalResult<Vec<Address>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                token_balance_holders.token_address
+            FROM
+                token_balance_holders
+                INNER JOIN tokens ON tokens.l2_address = token_balance_holders.token_address
+            WHERE
+                token_balance_holders.holder_address = $1
+            "#,
+            holder.as_bytes()
+        )
+        .instrument("get_held_tokens")
+        .with_arg("holder", &holder)
+        .report_latency()
+        .fetch_all(self.storage)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| Address::from_slice(&row.token_address))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_types::{
+        tokens::{TokenInfo, TokenMetadata},
+        tx::IncludedTxLocation,
+        L1BatchNumber, ProtocolVersion, H256,
+    };
+    use zksync_utils::address_to_h256;
+
+    use super::*;
+    use crate::{tests::create_miniblock_header, ConnectionPool, Core, CoreDal};
+
+    fn transfer_event(token: Address, from: Address, to: Address) -> VmEvent {
+        VmEvent {
+            location: (L1BatchNumber(1), 0),
+            address: token,
+            indexed_topics: vec![
+                ERC20_TRANSFER_TOPIC,
+                address_to_h256(&from),
+                address_to_h256(&to),
+            ],
+            value: H256::from_low_u64_be(1).0.to_vec(),
+        }
+    }
+
+    #[tokio::test]
+    async fn indexing_token_holders() {
+        let pool = ConnectionPool::<Core>::test_pool().await;
+        let mut conn = pool.connection().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(&ProtocolVersion::default())
+            .await
+            .unwrap();
+
+        let holder = Address::repeat_byte(0x11);
+        let other_holder = Address::repeat_byte(0x22);
+        let tokens: Vec<_> = (1..=3).map(Address::repeat_byte).collect();
+        let token_infos: Vec<_> = tokens
+            .iter()
+            .map(|&l2_address| TokenInfo {
+                l1_address: l2_address,
+                l2_address,
+                metadata: TokenMetadata::default(l2_address),
+            })
+            .collect();
+        conn.tokens_dal().add_tokens(&token_infos).await.unwrap();
+        let unknown_token = Address::repeat_byte(0xff);
+
+        conn.blocks_dal()
+            .insert_miniblock(&create_miniblock_header(1))
+            .await
+            .unwrap();
+        let events = [
+            // Minting
+            transfer_event(tokens[0], Address::zero(), holder),
+            transfer_event(tokens[1], other_holder, holder),
+            transfer_event(unknown_token, other_holder, holder),
+        ];
+        conn.token_balances_dal()
+            .insert_token_holders(MiniblockNumber(1), &events)
+            .await
+            .unwrap();
+
+        conn.blocks_dal()
+            .insert_miniblock(&create_miniblock_header(2))
+            .await
+            .unwrap();
+        let events = [
+            transfer_event(tokens[0], holder, other_holder),
+            transfer_event(tokens[2], holder, other_holder),
+        ];
+        conn.token_balances_dal()
+            .insert_token_holders(MiniblockNumber(2), &events)
+            .await
+            .unwrap();
+
+        let mut held_tokens = conn
+            .token_balances_dal()
+            .get_held_tokens(holder)
+            .await
+            .unwrap();
+        held_tokens.sort_unstable();
+        assert_eq!(held_tokens, tokens);
+        let mut other_held_tokens = conn
+            .token_balances_dal()
+            .get_held_tokens(other_holder)
+            .await
+            .unwrap();
+        other_held_tokens.sort_unstable();
+        assert_eq!(other_held_tokens, tokens);
+        let zero_held_tokens = conn
+            .token_balances_dal()
+            .get_held_tokens(Address::zero())
+            .await
+            .unwrap();
+        assert!(zero_held_tokens.is_empty());
+
+        // Holders must be removed together with the miniblock in which they were first indexed.
+        conn.blocks_dal()
+            .delete_miniblocks(MiniblockNumber(1))
+            .await
+            .unwrap();
+        let held_tokens = conn
+            .token_balances_dal()
+            .get_held_tokens(holder)
+            .await
+            .unwrap();
+        assert_eq!(held_tokens.len(), 2);
+        assert!(!held_tokens.contains(&tokens[2]));
+        let other_held_tokens = conn
+            .token_balances_dal()
+            .get_held_tokens(other_holder)
+            .await
+            .unwrap();
+        assert_eq!(other_held_tokens, [tokens[1]]);
+    }
+
+    #[tokio::test]
+    async fn backfilling_token_holders() {
+        let pool = ConnectionPool::<Core>::test_pool().await;
+        let mut conn = pool.connection().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(&ProtocolVersion::default())
+            .await
+            .unwrap();
+
+        let holder = Address::repeat_byte(0x11);
+        let other_holder = Address::repeat_byte(0x22);
+        let tokens: Vec<_> = (1..=2).map(Address::repeat_byte).collect();
+        let token_infos: Vec<_> = tokens
+            .iter()
+            .map(|&l2_address| TokenInfo {
+                l1_address: l2_address,
+                l2_address,
+                metadata: TokenMetadata::default(l2_address),
+            })
+            .collect();
+        conn.tokens_dal().add_tokens(&token_infos).await.unwrap();
+
+        let mut nft_transfer = transfer_event(tokens[1], other_holder, holder);
+        nft_transfer.indexed_topics.push(H256::from_low_u64_be(1));
+        let events_by_miniblock = [
+            vec![transfer_event(tokens[0], Address::zero(), holder)],
+            vec![nft_transfer],
+            vec![
+                transfer_event(tokens[0], holder, other_holder),
+                transfer_event(tokens[1], other_holder, holder),
+            ],
+        ];
+        let tx_location = IncludedTxLocation {
+            tx_hash: H256::zero(),
+            tx_index_in_miniblock: 0,
+            tx_initiator_address: Address::zero(),
+        };
+        for (i, events) in events_by_miniblock.iter().enumerate() {
+            let miniblock_number = MiniblockNumber(i as u32 + 1);
+            conn.blocks_dal()
+                .insert_miniblock(&create_miniblock_header(miniblock_number.0))
+                .await
+                .unwrap();
+            conn.events_dal()
+                .save_events(miniblock_number, &[(tx_location, events.iter().collect())])
+                .await
+                .unwrap();
+        }
+        // Emulate the last miniblock being indexed by the state keeper before the backfill.
+        conn.token_balances_dal()
+            .insert_token_holders(MiniblockNumber(3), &events_by_miniblock[2])
+            .await
+            .unwrap();
+
+        let all_miniblocks = MiniblockNumber(1)..=MiniblockNumber(3);
+        let bound = conn
+            .token_balances_dal()
+            .get_transfers_batch_bound(all_miniblocks.clone(), 1)
+            .await
+            .unwrap();
+        // The NFT transfer is counted as well since the bound is approximate.
+        assert_eq!(bound, Some(MiniblockNumber(2)));
+        let bound = conn
+            .token_balances_dal()
+            .get_transfers_batch_bound(all_miniblocks.clone(), 4)
+            .await
+            .unwrap();
+        assert_eq!(bound, None);
+
+        conn.token_balances_dal()
+            .backfill_token_holders(MiniblockNumber(1)..=MiniblockNumber(2))
+            .await
+            .unwrap();
+        let held_tokens = conn
+            .token_balances_dal()
+            .get_held_tokens(holder)
+            .await
+            .unwrap();
+        assert_eq!(held_tokens.len(), 2);
+        let other_held_tokens = conn
+            .token_balances_dal()
+            .get_held_tokens(other_holder)
+            .await
+            .unwrap();
+        assert_eq!(other_held_tokens.len(), 2);
+
+        // Backfilling is idempotent.
+        conn.token_balances_dal()
+            .backfill_token_holders(all_miniblocks)
+            .await
+            .unwrap();
+
+        // The holder of `tokens[0]` must be attributed to the first miniblock, so it survives the revert.
+        // Holders from the NFT transfer must not be indexed at all.
+        conn.events_dal()
+            .rollback_events(MiniblockNumber(2))
+            .await
+            .unwrap();
+        conn.blocks_dal()
+            .delete_miniblocks(MiniblockNumber(2))
+            .await
+            .unwrap();
+        let held_tokens = conn
+            .token_balances_dal()
+            .get_held_tokens(holder)
+            .await
+            .unwrap();
+        assert_eq!(held_tokens, [tokens[0]]);
+        let other_held_tokens = conn
+            .token_balances_dal()
+            .get_held_tokens(other_holder)
+            .await
+            .unwrap();
+        assert!(other_held_tokens.is_empty());
+    }
+}
@@ -5,7 +5,7 @@ use zksync_db_connection::{
     instrument::{CopyStatement, InstrumentExt},
     write_str, writeln_str,
 };
-use zksync_types::{tokens::TokenInfo, Address, MiniblockNumber};
+use zksync_types::{api::TokenBridge, tokens::TokenInfo, Address, MiniblockNumber};
 
 use crate::{Core, CoreDal};
 
@@ -65,6 +65,42 @@ impl TokensDal<'_, '_> {
         Ok(())
     }
 
+    /// Inserts or updates a custom bridge for a token. If the L2 token address is not specified,
+    /// it will be taken from the `tokens` table.
+    pub async fn upsert_custom_token_bridge(&mut self, bridge: &TokenBridge) -> DalResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO
+                custom_token_bridges (
+                    l1_token_address,
+                    l2_token_address,
+                    l1_bridge_address,
+                    l2_bridge_address,
+                    created_at,
+                    updated_at
+                )
+            VALUES
+                ($1, $2, $3, $4, NOW(), NOW())
+            ON CONFLICT (l1_token_address) DO
+            UPDATE
+            SET
+                l2_token_address = $2,
+                l1_bridge_address = $3,
+                l2_bridge_address = $4,
+                updated_at = NOW()
+            "#,
+            bridge.l1_token_address.as_bytes(),
+            bridge.l2_token_address.as_ref().map(Address::as_bytes),
+            bridge.l1_bridge.as_bytes(),
+            bridge.l2_bridge.as_bytes()
+        )
+        .instrument("upsert_custom_token_bridge")
+        .with_arg("bridge", bridge)
+        .execute(self.storage)
+        .await?;
+        Ok(())
+    }
+
     pub async fn get_all_l2_token_addresses(&mut self) -> DalResult<Vec<Address>> {
         let rows = sqlx::query!(
             r#"
@@ -199,6 +235,67 @@ mod tests {
         assert!(well_known_tokens.contains(&tokens[1]));
     }
 
+    #[tokio::test]
+    async fn custom_token_bridges() {
+        let pool = ConnectionPool::<Core>::test_pool().await;
+        let mut storage = pool.connection().await.unwrap();
+        let token = test_token_info();
+        storage
+            .tokens_dal()
+            .add_tokens(slice::from_ref(&token))
+            .await
+            .unwrap();
+
+        let mut bridge = TokenBridge {
+            l1_token_address: token.l1_address,
+            l2_token_address: None,
+            l1_bridge: Address::repeat_byte(0xb1),
+            l2_bridge: Address::repeat_byte(0xb2),
+        };
+        storage
+            .tokens_dal()
+            .upsert_custom_token_bridge(&bridge)
+            .await
+            .unwrap();
+        let unknown_token_bridge = TokenBridge {
+            l1_token_address: Address::repeat_byte(0xff),
+            l2_token_address: None,
+            l1_bridge: Address::repeat_byte(0xb3),
+            l2_bridge: Address::repeat_byte(0xb4),
+        };
+        storage
+            .tokens_dal()
+            .upsert_custom_token_bridge(&unknown_token_bridge)
+            .await
+            .unwrap();
+
+        let bridges = storage
+            .tokens_web3_dal()
+            .get_custom_token_bridges()
+            .await
+            .unwrap();
+        // The L2 token address must be taken from the `tokens` table.
+        let expected_bridge = TokenBridge {
+            l2_token_address: Some(token.l2_address),
+            ..bridge.clone()
+        };
+        assert_eq!(bridges, [expected_bridge, unknown_token_bridge.clone()]);
+
+        bridge.l2_token_address = Some(Address::repeat_byte(0x22));
+        bridge.l2_bridge = Address::repeat_byte(0xb5);
+        storage
+            .tokens_dal()
+            .upsert_custom_token_bridge(&bridge)
+            .await
+            .unwrap();
+        let bridges = storage
+            .tokens_web3_dal()
+            .get_custom_token_bridges()
+            .await
+            .unwrap();
+        assert_eq!(bridges, [bridge, unknown_token_bridge]);
+    }
+
     #[tokio::test]
     async fn rolling_back_tokens() {
         let pool = ConnectionPool::<Core>::test_pool().await;
@@ -1,5 +1,6 @@
 use zksync_db_connection::{connection::Connection, error::DalResult, instrument::InstrumentExt};
 use zksync_types::{
+    api::TokenBridge,
     tokens::{TokenInfo, TokenMetadata},
     Address, MiniblockNumber,
 };
@@ -102,4 +103,35 @@ impl TokensWeb3Dal<'_, '_> {
         all_tokens.retain(|token| filtered_addresses.contains_key(&token.l2_address));
         Ok(all_tokens)
     }
+
+    /// Returns custom bridges for tokens ordered by the L1 token address.
+    pub async fn get_custom_token_bridges(&mut self) -> DalResult<Vec<TokenBridge>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                custom_token_bridges.l1_token_address,
+                COALESCE(custom_token_bridges.l2_token_address, tokens.l2_address) AS "l2_token_address?",
+                custom_token_bridges.l1_bridge_address,
+                custom_token_bridges.l2_bridge_address
+            FROM
+                custom_token_bridges
+                LEFT JOIN tokens ON tokens.l1_address = custom_token_bridges.l1_token_address
+            ORDER BY
+                custom_token_bridges.l1_token_address
+            "#
+        )
+        .instrument("get_custom_token_bridges")
+        .fetch_all(self.storage)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| TokenBridge {
+                l1_token_address: Address::from_slice(&row.l1_token_address),
+                l2_token_address: row.l2_token_address.as_deref().map(Address::from_slice),
+                l1_bridge: Address::from_slice(&row.l1_bridge_address),
+                l2_bridge: Address::from_slice(&row.l2_bridge_address),
+            })
+            .collect())
+    }
 }
@@ -12,19 +12,21 @@ use zksync_db_connection::{
 };
 use zksync_types::{
     block::MiniblockExecutionData,
-    fee::TransactionExecutionMetrics,
+    fee::{Fee, TransactionExecutionMetrics},
     l1::L1Tx,
     l2::L2Tx,
     protocol_upgrade::ProtocolUpgradeTx,
-    tx::{tx_execution_info::TxExecutionStatus, TransactionExecutionResult},
+    tx::{tx_execution_info::TxExecutionStatus, TransactionExecutionResult, TxAccessHints},
     vm_trace::Call,
-    Address, ExecuteTransactionCommon, L1BatchNumber, L1BlockNumber, MiniblockNumber, PriorityOpId,
-    ProtocolVersionId, Transaction, H256, PROTOCOL_UPGRADE_TX_TYPE, U256,
+    AccountTreeId, Address, ExecuteTransactionCommon, L1BatchNumber, L1BlockNumber,
+    MiniblockNumber, Nonce, PriorityOpId, ProtocolVersionId, StorageKey, Transaction, H256,
+    PROTOCOL_UPGRADE_TX_TYPE, U256,
 };
-use zksync_utils::u256_to_big_decimal;
+use zksync_utils::{bigdecimal_to_u256, u256_to_big_decimal};
 
 use crate::{
     models::storage_transaction::{CallTrace, StorageTransaction},
+    partitions_dal::PartitionedTable,
     Core, CoreDal,
 };
 
@@ -36,6 +38,9 @@ pub enum L2TxSubmissionResult {
     Duplicate,
     Proxied,
     InsertionInProgress,
+    /// Transaction has a nonce too far ahead of the account nonce and is queued by the API server
+    /// until it can be added to the mempool.
+    Queued,
 }
 
 impl fmt::Display for L2TxSubmissionResult {
@@ -47,6 +52,7 @@ impl fmt::Display for L2TxSubmissionResult {
             Self::Duplicate => "duplicate",
             Self::Proxied => "proxied",
             Self::InsertionInProgress => "insertion_in_progress",
+            Self::Queued => "queued",
         })
     }
 }
@@ -320,10 +326,11 @@ impl TransactionsDal<'_, '_> {
         // 2) transaction is replaced
         // 3) WHERE clause conditions for DO UPDATE block were not met, so the transaction can't be replaced
         // the subquery in RETURNING clause looks into pre-UPDATE state of the table. So if the subquery will return NULL
-        // transaction is fresh and was added to db(the second condition of RETURNING clause checks it).
+        // transaction is fresh and was added to db.
         // Otherwise, if the subquery won't return NULL it means that there is already tx with such nonce and `initiator_address` in DB
-        // and we can replace it WHERE clause conditions are met.
+        // and we can replace it WHERE clause conditions are met; the subquery returns the hash of the replaced transaction.
         // It is worth mentioning that if WHERE clause conditions are not met, None will be returned.
+        let mut transaction = self.storage.start_transaction().await?;
         let query_result = sqlx::query!(
             r#"
             INSERT INTO
@@ -406,7 +413,7 @@ impl TransactionsDal<'_, '_> {
                     WHERE
                         transactions.initiator_address = $2
                         AND transactions.nonce = $3
-                ) IS NOT NULL AS "is_replaced!"
+                ) AS "replaced_hash?"
             "#,
             tx_hash.as_bytes(),
             initiator_address.as_bytes(),
@@ -430,14 +437,27 @@ impl TransactionsDal<'_, '_> {
         )
         .instrument("insert_transaction_l2")
         .with_arg("tx_hash", &tx_hash)
-        .fetch_optional(self.storage)
+        .fetch_optional(&mut transaction)
         .await
-        .map(|option_record| option_record.map(|record| record.is_replaced));
+        .map(|option_record| option_record.map(|record| record.replaced_hash));
 
         let l2_tx_insertion_result = match query_result {
             Ok(option_query_result) => match option_query_result {
-                Some(true) => L2TxSubmissionResult::Replaced,
-                Some(false) => L2TxSubmissionResult::Added,
+                Some(Some(replaced_hash)) => {
+                    let replaced_hash = H256::from_slice(&replaced_hash);
+                    transaction
+                        .transactions_dal()
+                        .insert_replacement(
+                            replaced_hash,
+                            tx_hash,
+                            initiator_address,
+                            nonce,
+                            received_at,
+                        )
+                        .await?;
+                    L2TxSubmissionResult::Replaced
+                }
+                Some(None) => L2TxSubmissionResult::Added,
                 None => L2TxSubmissionResult::AlreadyExecuted,
             },
             Err(err) => {
@@ -469,10 +489,110 @@ impl TransactionsDal<'_, '_> {
             nonce,
             l2_tx_insertion_result
         );
-
+        transaction.commit().await?;
         Ok(l2_tx_insertion_result)
     }
 
+    async fn insert_replacement(
+        &mut self,
+        replaced_hash: H256,
+        tx_hash: H256,
+        initiator_address: Address,
+        nonce: i64,
+        replaced_at: NaiveDateTime,
+    ) -> DalResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO
+                transaction_replacements (replaced_tx_hash, tx_hash, initiator_address, nonce, created_at)
+            VALUES
+                ($1, $2, $3, $4, $5)
+            "#,
+            replaced_hash.as_bytes(),
+            tx_hash.as_bytes(),
+            initiator_address.as_bytes(),
+            nonce,
+            replaced_at
+        )
+        .instrument("insert_replacement")
+        .with_arg("replaced_hash", &replaced_hash)
+        .with_arg("tx_hash", &tx_hash)
+        .execute(self.storage)
+        .await?;
+        Ok(())
+    }
+
+    /// Returns fee parameters of a pending (i.e., not yet included into a miniblock) L2 transaction
+    /// with the specified initiator and nonce, together with its hash.
+    pub async fn get_pending_tx_fee(
+        &mut self,
+        initiator_address: Address,
+        nonce: Nonce,
+    ) -> DalResult<Option<(H256, Fee)>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                hash,
+                gas_limit,
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+                gas_per_pubdata_limit
+            FROM
+                transactions
+            WHERE
+                initiator_address = $1
+                AND nonce = $2
+                AND is_priority = FALSE
+                AND miniblock_number IS NULL
+            "#,
+            initiator_address.as_bytes(),
+            i64::from(nonce.0)
+        )
+        .instrument("get_pending_tx_fee")
+        .with_arg("initiator_address", &initiator_address)
+        .with_arg("nonce", &nonce)
+        .fetch_optional(self.storage)
+        .await?;
+
+        Ok(row.map(|row| {
+            let fee = Fee {
+                gas_limit: bigdecimal_to_u256(row.gas_limit.unwrap_or_default()),
+                max_fee_per_gas: bigdecimal_to_u256(row.max_fee_per_gas.unwrap_or_default()),
+                max_priority_fee_per_gas: bigdecimal_to_u256(
+                    row.max_priority_fee_per_gas.unwrap_or_default(),
+                ),
+                gas_per_pubdata_limit: bigdecimal_to_u256(
+                    row.gas_per_pubdata_limit.unwrap_or_default(),
+                ),
+            };
+            (H256::from_slice(&row.hash), fee)
+        }))
+    }
+
+    /// Returns the number of pending (i.e., neither included into a miniblock nor rejected) L2 transactions
+    /// from the specified initiator.
+    pub async fn get_pending_txs_count(&mut self, initiator_address: Address) -> DalResult<usize> {
+        let count = sqlx::query_scalar!(
+            r#"
+            SELECT
+                COUNT(*) AS "count!"
+            FROM
+                transactions
+            WHERE
+                initiator_address = $1
+                AND is_priority = FALSE
+                AND miniblock_number IS NULL
+                AND error IS NULL
+            "#,
+            initiator_address.as_bytes()
+        )
+        .instrument("get_pending_txs_count")
+        .with_arg("initiator_address", &initiator_address)
+        .fetch_one(self.storage)
+        .await?;
+        Ok(count as usize)
+    }
+
     pub async fn mark_txs_as_executed_in_l1_batch(
         &mut self,
         l1_batch_number: L1BatchNumber,
@@ -545,18 +665,24 @@ impl TransactionsDal<'_, '_> {
             .await?;
 
         if !bytea_call_traces.is_empty() {
+            transaction
+                .partitions_dal()
+                .ensure_partition(PartitionedTable::CallTraces, miniblock_number)
+                .await?;
             sqlx::query!(
                 r#"
                 INSERT INTO
-                    call_traces (tx_hash, call_trace)
+                    call_traces (tx_hash, miniblock_number, call_trace)
                 SELECT
                     u.tx_hash,
+                    $3,
                     u.call_trace
                 FROM
                     UNNEST($1::bytea[], $2::bytea[]) AS u (tx_hash, call_trace)
                 "#,
                 &call_traces_tx_hashes as &[&[u8]],
-                &bytea_call_traces
+                &bytea_call_traces,
+                i64::from(miniblock_number.0)
             )
             .instrument("insert_call_tracer")
             .report_latency()
@@ -621,6 +747,7 @@ impl TransactionsDal<'_, '_> {
         let mut l2_max_priority_fees_per_gas = Vec::with_capacity(l2_txs_len);
         let mut l2_gas_per_pubdata_limit = Vec::with_capacity(l2_txs_len);
         let mut l2_refunded_gas = Vec::with_capacity(l2_txs_len);
+        let mut l2_operator_suggested_refunds = Vec::with_capacity(l2_txs_len);
 
         for (index_in_block, tx_res) in transactions.iter().enumerate() {
             let transaction = &tx_res.transaction;
@@ -644,6 +771,13 @@ impl TransactionsDal<'_, '_> {
                 instrumentation
                     .arg_error(&format!("transactions[{index_in_block}].refunded_gas"), err)
             })?;
+            let operator_suggested_refund = i64::try_from(tx_res.operator_suggested_refund)
+                .map_err(|err| {
+                    instrumentation.arg_error(
+                        &format!("transactions[{index_in_block}].operator_suggested_refund"),
+                        err,
+                    )
+                })?;
 
             l2_values.push(u256_to_big_decimal(transaction.execute.value));
             l2_contract_addresses.push(transaction.execute.contract_address.as_bytes());
@@ -672,6 +806,7 @@ impl TransactionsDal<'_, '_> {
             l2_gas_per_pubdata_limit
                 .push(u256_to_big_decimal(common_data.fee.gas_per_pubdata_limit));
             l2_refunded_gas.push(refunded_gas);
+            l2_operator_suggested_refunds.push(operator_suggested_refund);
         }
 
         // Due to the current tx replacement model, it's possible that tx has been replaced,
@@ -697,6 +832,7 @@ impl TransactionsDal<'_, '_> {
                 effective_gas_price = data_table.effective_gas_price,
                 execution_info = data_table.new_execution_info,
                 refunded_gas = data_table.refunded_gas,
+                operator_suggested_refund = data_table.operator_suggested_refund,
                 value = data_table.value,
                 contract_address = data_table.contract_address,
                 paymaster = data_table.paymaster,
@@ -729,7 +865,8 @@ impl TransactionsDal<'_, '_> {
                                 UNNEST($17::NUMERIC[]) AS value,
                                 UNNEST($18::bytea[]) AS contract_address,
                                 UNNEST($19::bytea[]) AS paymaster,
-                                UNNEST($20::bytea[]) AS paymaster_input
+                                UNNEST($20::bytea[]) AS paymaster_input,
+                                UNNEST($22::BIGINT[]) AS operator_suggested_refund
                         ) AS data_table_temp
                         JOIN transactions ON transactions.initiator_address = data_table_temp.initiator_address
                         AND transactions.nonce = data_table_temp.nonce
@@ -761,6 +898,7 @@ impl TransactionsDal<'_, '_> {
             &l2_paymaster as &[&[u8]],
             &l2_paymaster_input as &[&[u8]],
             miniblock_number.0 as i32,
+            &l2_operator_suggested_refunds,
         );
 
         instrumentation.with(query).execute(self.storage).await?;
@@ -794,6 +932,7 @@ impl TransactionsDal<'_, '_> {
         let mut l1_errors = Vec::with_capacity(l1_txs_len);
         let mut l1_execution_infos = Vec::with_capacity(l1_txs_len);
         let mut l1_refunded_gas = Vec::with_capacity(l1_txs_len);
+        let mut l1_operator_suggested_refunds = Vec::with_capacity(l1_txs_len);
         let mut l1_effective_gas_prices = Vec::with_capacity(l1_txs_len);
 
         for (index_in_block, tx_res) in transactions.iter().enumerate() {
@@ -812,12 +951,20 @@ impl TransactionsDal<'_, '_> {
                 instrumentation
                     .arg_error(&format!("transactions[{index_in_block}].refunded_gas"), err)
             })?;
+            let operator_suggested_refund = i64::try_from(tx_res.operator_suggested_refund)
+                .map_err(|err| {
+                    instrumentation.arg_error(
+                        &format!("transactions[{index_in_block}].operator_suggested_refund"),
+                        err,
+                    )
+                })?;
 
             l1_hashes.push(tx_res.hash.as_bytes());
             l1_indices_in_block.push(index_in_block as i32);
             l1_errors.push(Self::map_transaction_error(tx_res));
             l1_execution_infos.push(l1_execution_info);
             l1_refunded_gas.push(refunded_gas);
+            l1_operator_suggested_refunds.push(operator_suggested_refund);
             l1_effective_gas_prices.push(u256_to_big_decimal(common_data.max_fee_per_gas));
         }
 
@@ -831,6 +978,7 @@ impl TransactionsDal<'_, '_> {
                 in_mempool = FALSE,
                 execution_info = execution_info || data_table.new_execution_info,
                 refunded_gas = data_table.refunded_gas,
+                operator_suggested_refund = data_table.operator_suggested_refund,
                 effective_gas_price = data_table.effective_gas_price,
                 updated_at = NOW()
             FROM
@@ -841,7 +989,8 @@ impl TransactionsDal<'_, '_> {
                         UNNEST($4::VARCHAR[]) AS error,
                         UNNEST($5::jsonb[]) AS new_execution_info,
                         UNNEST($6::BIGINT[]) AS refunded_gas,
-                        UNNEST($7::NUMERIC[]) AS effective_gas_price
+                        UNNEST($7::NUMERIC[]) AS effective_gas_price,
+                        UNNEST($8::BIGINT[]) AS operator_suggested_refund
                 ) AS data_table
             WHERE
                 transactions.hash = data_table.hash
@@ -853,6 +1002,7 @@ impl TransactionsDal<'_, '_> {
             &l1_execution_infos,
             &l1_refunded_gas,
             &l1_effective_gas_prices,
+            &l1_operator_suggested_refunds,
         );
 
         instrumentation.with(query).execute(self.storage).await?;
@@ -887,6 +1037,7 @@ impl TransactionsDal<'_, '_> {
         let mut upgrade_errors = Vec::new();
         let mut upgrade_execution_infos = Vec::new();
         let mut upgrade_refunded_gas = Vec::new();
+        let mut upgrade_operator_suggested_refunds = Vec::new();
         let mut upgrade_effective_gas_prices = Vec::new();
 
         for (index_in_block, tx_res) in transactions.iter().enumerate() {
@@ -906,12 +1057,20 @@ impl TransactionsDal<'_, '_> {
                 instrumentation
                     .arg_error(&format!("transactions[{index_in_block}].refunded_gas"), err)
             })?;
+            let operator_suggested_refund = i64::try_from(tx_res.operator_suggested_refund)
+                .map_err(|err| {
+                    instrumentation.arg_error(
+                        &format!("transactions[{index_in_block}].operator_suggested_refund"),
+                        err,
+                    )
+                })?;
 
             upgrade_hashes.push(tx_res.hash.as_bytes());
             upgrade_indices_in_block.push(index_in_block as i32);
             upgrade_errors.push(Self::map_transaction_error(tx_res));
             upgrade_execution_infos.push(execution_info);
             upgrade_refunded_gas.push(refunded_gas);
+            upgrade_operator_suggested_refunds.push(operator_suggested_refund);
             upgrade_effective_gas_prices.push(u256_to_big_decimal(common_data.max_fee_per_gas));
         }
 
@@ -925,6 +1084,7 @@ impl TransactionsDal<'_, '_> {
                 in_mempool = FALSE,
                 execution_info = execution_info || data_table.new_execution_info,
                 refunded_gas = data_table.refunded_gas,
+                operator_suggested_refund = data_table.operator_suggested_refund,
                 effective_gas_price = data_table.effective_gas_price,
                 updated_at = NOW()
             FROM
@@ -935,7 +1095,8 @@ impl TransactionsDal<'_, '_> {
                         UNNEST($4::VARCHAR[]) AS error,
                         UNNEST($5::jsonb[]) AS new_execution_info,
                         UNNEST($6::BIGINT[]) AS refunded_gas,
-                        UNNEST($7::NUMERIC[]) AS effective_gas_price
+                        UNNEST($7::NUMERIC[]) AS effective_gas_price,
+                        UNNEST($8::BIGINT[]) AS operator_suggested_refund
                 ) AS data_table
             WHERE
                 transactions.hash = data_table.hash
@@ -947,6 +1108,7 @@ impl TransactionsDal<'_, '_> {
             &upgrade_execution_infos,
             &upgrade_refunded_gas,
             &upgrade_effective_gas_prices,
+            &upgrade_operator_suggested_refunds,
         );
 
         instrumentation.with(query).execute(self.storage).await?;
@@ -980,6 +1142,27 @@ impl TransactionsDal<'_, '_> {
         Ok(())
     }
 
+    /// Marks the specified transactions as loaded into the mempool, so that they are not fetched
+    /// by the mempool fetcher.
+    pub async fn mark_txs_as_in_mempool(&mut self, tx_hashes: &[H256]) -> DalResult<()> {
+        let tx_hashes: Vec<_> = tx_hashes.iter().map(H256::as_bytes).collect();
+        sqlx::query!(
+            r#"
+            UPDATE transactions
+            SET
+                in_mempool = TRUE
+            WHERE
+                hash = ANY ($1)
+            "#,
+            &tx_hashes as &[&[u8]]
+        )
+        .instrument("mark_txs_as_in_mempool")
+        .with_arg("tx_hashes.len", &tx_hashes.len())
+        .execute(self.storage)
+        .await?;
+        Ok(())
+    }
+
     pub async fn reset_transactions_state(
         &mut self,
         miniblock_number: MiniblockNumber,
@@ -1010,8 +1193,10 @@ impl TransactionsDal<'_, '_> {
             r#"
             DELETE FROM call_traces
             WHERE
-                tx_hash = ANY ($1)
+                miniblock_number > $1
+                AND tx_hash = ANY ($2)
             "#,
+            i64::from(miniblock_number.0),
             &tx_hashes as &[&[u8]]
         )
         .instrument("reset_transactions_state")
@@ -1022,7 +1207,9 @@ impl TransactionsDal<'_, '_> {
         Ok(())
     }
 
-    pub async fn remove_stuck_txs(&mut self, stuck_tx_timeout: Duration) -> DalResult<usize> {
+    /// Removes L2 transactions not included into a miniblock within `stuck_tx_timeout` after they were received.
+    /// Returns hashes of the removed transactions.
+    pub async fn remove_stuck_txs(&mut self, stuck_tx_timeout: Duration) -> DalResult<Vec<H256>> {
         let stuck_tx_timeout = pg_interval_from_duration(stuck_tx_timeout);
         let rows = sqlx::query!(
             r#"
@@ -1042,15 +1229,21 @@ impl TransactionsDal<'_, '_> {
         .fetch_all(self.storage)
         .await?;
 
-        Ok(rows.len())
+        Ok(rows
+            .into_iter()
+            .map(|row| H256::from_slice(&row.hash))
+            .collect())
     }
 
     /// Fetches new updates for mempool. Returns new transactions and current nonces for related accounts;
     /// the latter are only used to bootstrap mempool for given account.
+    ///
+    /// L2 transactions initiated by `excluded_accounts` are not loaded.
     pub async fn sync_mempool(
         &mut self,
         stashed_accounts: &[Address],
         purged_accounts: &[Address],
+        excluded_accounts: &[Address],
         gas_per_pubdata: u32,
         fee_per_gas: u64,
         limit: usize,
@@ -1089,6 +1282,7 @@ impl TransactionsDal<'_, '_> {
         .execute(self.storage)
         .await?;
 
+        let excluded_addresses: Vec<_> = excluded_accounts.iter().map(Address::as_bytes).collect();
         // Note, that transactions are updated in order of their hashes to avoid deadlocks with other UPDATE queries.
         let transactions = sqlx::query_as!(
             StorageTransaction,
@@ -1115,6 +1309,7 @@ impl TransactionsDal<'_, '_> {
                                     OR (
                                         max_fee_per_gas >= $2
                                         AND gas_per_pubdata_limit >= $3
+                                        AND initiator_address <> ALL ($5)
                                     )
                                 )
                                 AND tx_format != $4
@@ -1136,9 +1331,11 @@ impl TransactionsDal<'_, '_> {
             limit as i32,
             BigDecimal::from(fee_per_gas),
             BigDecimal::from(gas_per_pubdata),
-            i32::from(PROTOCOL_UPGRADE_TX_TYPE)
+            i32::from(PROTOCOL_UPGRADE_TX_TYPE),
+            &excluded_addresses as &[&[u8]]
         )
         .instrument("sync_mempool")
+        .with_arg("excluded_addresses.len", &excluded_addresses.len())
         .with_arg("fee_per_gas", &fee_per_gas)
         .with_arg("gas_per_pubdata", &gas_per_pubdata)
         .with_arg("limit", &limit)
@@ -1149,8 +1346,10 @@ impl TransactionsDal<'_, '_> {
         Ok(transactions)
     }
 
-    pub async fn reset_mempool(&mut self) -> sqlx::Result<()> {
-        sqlx::query!(
+    /// Returns all transactions loaded into the mempool back to Postgres, so that they are loaded again
+    /// by [`Self::sync_mempool()`]. Returns the number of returned transactions.
+    pub async fn reset_mempool(&mut self) -> DalResult<usize> {
+        let result = sqlx::query!(
             r#"
             UPDATE transactions
             SET
@@ -1159,11 +1358,118 @@ impl TransactionsDal<'_, '_> {
                 in_mempool = TRUE
             "#
         )
-        .execute(self.storage.conn())
+        .instrument("reset_mempool")
+        .execute(self.storage)
+        .await?;
+        Ok(result.rows_affected() as usize)
+    }
+
+    /// Persists access hints for a pending L2 transaction, overwriting existing hints for the same transaction.
+    pub async fn insert_access_hints(
+        &mut self,
+        tx_hash: H256,
+        hints: &TxAccessHints,
+    ) -> DalResult<()> {
+        let (addresses, keys): (Vec<_>, Vec<_>) = hints
+            .storage_slots
+            .iter()
+            .map(|slot| (slot.address().as_bytes(), slot.key().as_bytes()))
+            .unzip();
+        sqlx::query!(
+            r#"
+            INSERT INTO
+                transaction_access_hints (tx_hash, addresses, keys, created_at)
+            VALUES
+                ($1, $2, $3, NOW())
+            ON CONFLICT (tx_hash) DO
+            UPDATE
+            SET
+                addresses = $2,
+                keys = $3,
+                created_at = NOW()
+            "#,
+            tx_hash.as_bytes(),
+            &addresses as &[&[u8]],
+            &keys as &[&[u8]]
+        )
+        .instrument("insert_access_hints")
+        .with_arg("tx_hash", &tx_hash)
+        .with_arg("hints.len", &addresses.len())
+        .execute(self.storage)
         .await?;
         Ok(())
     }
 
+    /// Removes and returns access hints for the specified transactions. Transactions without hints are omitted
+    /// from the returned map.
+    pub async fn take_access_hints(
+        &mut self,
+        tx_hashes: &[H256],
+    ) -> DalResult<HashMap<H256, TxAccessHints>> {
+        let hash_bytes: Vec<_> = tx_hashes.iter().map(H256::as_bytes).collect();
+        let rows = sqlx::query!(
+            r#"
+            DELETE FROM transaction_access_hints
+            WHERE
+                tx_hash = ANY ($1)
+            RETURNING
+                tx_hash,
+                addresses,
+                keys
+            "#,
+            &hash_bytes as &[&[u8]]
+        )
+        .instrument("take_access_hints")
+        .with_arg("tx_hashes.len", &tx_hashes.len())
+        .fetch_all(self.storage)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let storage_slots = row
+                    .addresses
+                    .iter()
+                    .zip(&row.keys)
+                    .map(|(address, key)| {
+                        StorageKey::new(
+                            AccountTreeId::new(Address::from_slice(address)),
+                            H256::from_slice(key),
+                        )
+                    })
+                    .collect();
+                (
+                    H256::from_slice(&row.tx_hash),
+                    TxAccessHints { storage_slots },
+                )
+            })
+            .collect())
+    }
+
+    /// Removes access hints for transactions that are no longer pending (e.g., were replaced or removed
+    /// before being loaded into the mempool). Returns the number of removed hints.
+    pub async fn prune_access_hints(&mut self) -> DalResult<u64> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM transaction_access_hints
+            WHERE
+                NOT EXISTS (
+                    SELECT
+                        1
+                    FROM
+                        transactions
+                    WHERE
+                        transactions.hash = transaction_access_hints.tx_hash
+                        AND transactions.miniblock_number IS NULL
+                )
+            "#
+        )
+        .instrument("prune_access_hints")
+        .execute(self.storage)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
     pub async fn get_last_processed_l1_block(&mut self) -> Option<L1BlockNumber> {
         {
             sqlx::query!(
@@ -1444,9 +1750,10 @@ impl TransactionsDal<'_, '_> {
     }
 
     pub async fn get_call_trace(&mut self, tx_hash: H256) -> DalResult<Option<Call>> {
-        let protocol_version: ProtocolVersionId = sqlx::query!(
+        let row = sqlx::query!(
             r#"
             SELECT
+                transactions.miniblock_number,
                 protocol_version
             FROM
                 transactions
@@ -1459,9 +1766,14 @@ impl TransactionsDal<'_, '_> {
         .instrument("get_call_trace")
         .with_arg("tx_hash", &tx_hash)
         .fetch_optional(self.storage)
-        .await?
-        .and_then(|row| row.protocol_version.map(|v| (v as u16).try_into().unwrap()))
-        .unwrap_or_else(ProtocolVersionId::last_potentially_undefined);
+        .await?;
+        // Call traces are only stored for executed transactions.
+        let Some(miniblock_number) = row.as_ref().and_then(|row| row.miniblock_number) else {
+            return Ok(None);
+        };
+        let protocol_version = row
+            .and_then(|row| row.protocol_version.map(|v| (v as u16).try_into().unwrap()))
+            .unwrap_or_else(ProtocolVersionId::last_potentially_undefined);
 
         Ok(sqlx::query_as!(
             CallTrace,
@@ -1472,8 +1784,10 @@ impl TransactionsDal<'_, '_> {
                 call_traces
             WHERE
                 tx_hash = $1
+                AND miniblock_number = $2
             "#,
-            tx_hash.as_bytes()
+            tx_hash.as_bytes(),
+            miniblock_number
         )
         .instrument("get_call_trace")
         .with_arg("tx_hash", &tx_hash)
@@ -1482,6 +1796,139 @@ impl TransactionsDal<'_, '_> {
         .map(|call_trace| call_trace.into_call(protocol_version)))
     }
 
+    /// Returns the last L1 batch processed by the call traces backfiller.
+    pub async fn get_call_traces_backfiller_last_processed_l1_batch(
+        &mut self,
+    ) -> DalResult<L1BatchNumber> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                last_processed_l1_batch AS "last_processed_l1_batch!"
+            FROM
+                call_traces_backfiller_info
+            "#
+        )
+        .instrument("get_call_traces_backfiller_last_processed_l1_batch")
+        .report_latency()
+        .fetch_one(self.storage)
+        .await?;
+        Ok(L1BatchNumber(row.last_processed_l1_batch as u32))
+    }
+
+    pub async fn set_call_traces_backfiller_last_processed_l1_batch(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+    ) -> DalResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE call_traces_backfiller_info
+            SET
+                last_processed_l1_batch = $1,
+                updated_at = NOW()
+            "#,
+            i64::from(l1_batch_number.0),
+        )
+        .instrument("set_call_traces_backfiller_last_processed_l1_batch")
+        .report_latency()
+        .with_arg("l1_batch_number", &l1_batch_number)
+        .execute(self.storage)
+        .await?;
+        Ok(())
+    }
+
+    /// Returns the earliest sealed L1 batch starting from `from_l1_batch` that contains transactions
+    /// without call traces. L1 batches with call traces offloaded to the object store are skipped.
+    pub async fn get_first_l1_batch_with_missing_call_traces(
+        &mut self,
+        from_l1_batch: L1BatchNumber,
+    ) -> DalResult<Option<L1BatchNumber>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                l1_batch_number AS "l1_batch_number!"
+            FROM
+                transactions
+            WHERE
+                l1_batch_number >= $1
+                AND NOT EXISTS (
+                    SELECT
+                        1
+                    FROM
+                        call_traces
+                    WHERE
+                        call_traces.tx_hash = transactions.hash
+                        AND call_traces.miniblock_number = transactions.miniblock_number
+                )
+                AND NOT EXISTS (
+                    SELECT
+                        1
+                    FROM
+                        offloaded_call_traces_l1_batches
+                    WHERE
+                        offloaded_call_traces_l1_batches.l1_batch_number = transactions.l1_batch_number
+                )
+            ORDER BY
+                l1_batch_number
+            LIMIT
+                1
+            "#,
+            i64::from(from_l1_batch.0)
+        )
+        .instrument("get_first_l1_batch_with_missing_call_traces")
+        .with_arg("from_l1_batch", &from_l1_batch)
+        .fetch_optional(self.storage)
+        .await?;
+
+        Ok(row.map(|row| L1BatchNumber(row.l1_batch_number as u32)))
+    }
+
+    /// Inserts call traces for already executed transactions (e.g., obtained by re-executing an L1 batch).
+    /// Call traces are serialized according to `protocol_version`, which must be the protocol version
+    /// of the L1 batch containing the transactions. Existing call traces are not overwritten.
+    pub async fn insert_call_traces(
+        &mut self,
+        protocol_version: ProtocolVersionId,
+        call_traces: &[(H256, Call)],
+    ) -> DalResult<()> {
+        if call_traces.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx_hashes = Vec::with_capacity(call_traces.len());
+        let mut bytea_call_traces = Vec::with_capacity(call_traces.len());
+        for (tx_hash, call_trace) in call_traces {
+            tx_hashes.push(*tx_hash);
+            bytea_call_traces
+                .push(CallTrace::from_call(call_trace.clone(), protocol_version).call_trace);
+        }
+        let tx_hashes: Vec<_> = tx_hashes.iter().map(H256::as_bytes).collect();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO
+                call_traces (tx_hash, miniblock_number, call_trace)
+            SELECT
+                u.tx_hash,
+                transactions.miniblock_number,
+                u.call_trace
+            FROM
+                UNNEST($1::bytea[], $2::bytea[]) AS u (tx_hash, call_trace)
+                INNER JOIN transactions ON transactions.hash = u.tx_hash
+            WHERE
+                transactions.miniblock_number IS NOT NULL
+            ON CONFLICT (tx_hash, miniblock_number) DO NOTHING
+            "#,
+            &tx_hashes as &[&[u8]],
+            &bytea_call_traces
+        )
+        .instrument("insert_call_traces")
+        .with_arg("call_traces.len", &tx_hashes.len())
+        .report_latency()
+        .execute(self.storage)
+        .await?;
+        Ok(())
+    }
+
     pub(crate) async fn get_tx_by_hash(&mut self, hash: H256) -> Option<Transaction> {
         sqlx::query_as!(
             StorageTransaction,
@@ -1504,7 +1951,8 @@ impl TransactionsDal<'_, '_> {
 
 #[cfg(test)]
 mod tests {
-    use zksync_types::ProtocolVersion;
+    use zksync_contracts::BaseSystemContractsHashes;
+    use zksync_types::{block::L1BatchHeader, ProtocolVersion};
 
     use super::*;
     use crate::{
@@ -1552,4 +2000,157 @@ mod tests {
             .expect("no call trace");
         assert_eq!(call_trace, expected_call_trace);
     }
+
+    #[tokio::test]
+    async fn backfilling_call_traces() {
+        let connection_pool = ConnectionPool::<Core>::test_pool().await;
+        let mut conn = connection_pool.connection().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(&ProtocolVersion::default())
+            .await
+            .unwrap();
+        conn.blocks_dal()
+            .insert_miniblock(&create_miniblock_header(1))
+            .await
+            .unwrap();
+        let l1_batch_header = L1BatchHeader::new(
+            L1BatchNumber(1),
+            100,
+            BaseSystemContractsHashes::default(),
+            ProtocolVersionId::latest(),
+        );
+        conn.blocks_dal()
+            .insert_mock_l1_batch(&l1_batch_header)
+            .await
+            .unwrap();
+
+        let tx = mock_l2_transaction();
+        let tx_hash = tx.hash();
+        conn.transactions_dal()
+            .insert_transaction_l2(&tx, TransactionExecutionMetrics::default())
+            .await
+            .unwrap();
+        let tx_results = [mock_execution_result(tx)];
+        conn.transactions_dal()
+            .mark_txs_as_executed_in_miniblock(MiniblockNumber(1), &tx_results, 1.into())
+            .await
+            .unwrap();
+        conn.transactions_dal()
+            .mark_txs_as_executed_in_l1_batch(L1BatchNumber(1), &tx_results)
+            .await
+            .unwrap();
+
+        let l1_batch_number = conn
+            .transactions_dal()
+            .get_first_l1_batch_with_missing_call_traces(L1BatchNumber(0))
+            .await
+            .unwrap();
+        assert_eq!(l1_batch_number, Some(L1BatchNumber(1)));
+        let l1_batch_number = conn
+            .transactions_dal()
+            .get_first_l1_batch_with_missing_call_traces(L1BatchNumber(2))
+            .await
+            .unwrap();
+        assert_eq!(l1_batch_number, None);
+
+        let call_trace = Call {
+            from: Address::from_low_u64_be(1),
+            to: Address::from_low_u64_be(2),
+            value: 100.into(),
+            ..Call::default()
+        };
+        conn.transactions_dal()
+            .insert_call_traces(
+                ProtocolVersionId::latest(),
+                &[(tx_hash, call_trace.clone())],
+            )
+            .await
+            .unwrap();
+        let l1_batch_number = conn
+            .transactions_dal()
+            .get_first_l1_batch_with_missing_call_traces(L1BatchNumber(0))
+            .await
+            .unwrap();
+        assert_eq!(l1_batch_number, None);
+
+        // Existing call traces must not be overwritten.
+        let other_call_trace = Call {
+            value: 200.into(),
+            ..call_trace.clone()
+        };
+        conn.transactions_dal()
+            .insert_call_traces(ProtocolVersionId::latest(), &[(tx_hash, other_call_trace)])
+            .await
+            .unwrap();
+        let persisted_call_trace = conn
+            .transactions_dal()
+            .get_call_trace(tx_hash)
+            .await
+            .unwrap()
+            .expect("no call trace");
+        assert_eq!(persisted_call_trace, call_trace);
+
+        let last_processed_l1_batch = conn
+            .transactions_dal()
+            .get_call_traces_backfiller_last_processed_l1_batch()
+            .await
+            .unwrap();
+        assert_eq!(last_processed_l1_batch, L1BatchNumber(0));
+        conn.transactions_dal()
+            .set_call_traces_backfiller_last_processed_l1_batch(L1BatchNumber(1))
+            .await
+            .unwrap();
+        let last_processed_l1_batch = conn
+            .transactions_dal()
+            .get_call_traces_backfiller_last_processed_l1_batch()
+            .await
+            .unwrap();
+        assert_eq!(last_processed_l1_batch, L1BatchNumber(1));
+    }
+
+    #[tokio::test]
+    async fn taking_and_pruning_access_hints() {
+        let connection_pool = ConnectionPool::<Core>::test_pool().await;
+        let mut conn = connection_pool.connection().await.unwrap();
+        let tx = mock_l2_transaction();
+        let tx_hash = tx.hash();
+        conn.transactions_dal()
+            .insert_transaction_l2(&tx, TransactionExecutionMetrics::default())
+            .await
+            .unwrap();
+
+        let hints = TxAccessHints {
+            storage_slots: vec![
+                StorageKey::new(AccountTreeId::new(Address::repeat_byte(1)), H256::zero()),
+                StorageKey::new(
+                    AccountTreeId::new(Address::repeat_byte(2)),
+                    H256::repeat_byte(3),
+                ),
+            ],
+        };
+        let orphaned_tx_hash = H256::repeat_byte(0xff);
+        for hash in [tx_hash, orphaned_tx_hash] {
+            conn.transactions_dal()
+                .insert_access_hints(hash, &hints)
+                .await
+                .unwrap();
+        }
+
+        // Hints for the orphaned transaction are pruned since it's not in the `transactions` table.
+        let pruned_count = conn.transactions_dal().prune_access_hints().await.unwrap();
+        assert_eq!(pruned_count, 1);
+
+        let taken_hints = conn
+            .transactions_dal()
+            .take_access_hints(&[tx_hash, orphaned_tx_hash])
+            .await
+            .unwrap();
+        assert_eq!(taken_hints, HashMap::from([(tx_hash, hints)]));
+        let taken_hints = conn
+            .transactions_dal()
+            .take_access_hints(&[tx_hash])
+            .await
+            .unwrap();
+        assert!(taken_hints.is_empty());
+    }
 }
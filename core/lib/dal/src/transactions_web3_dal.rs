@@ -1,3 +1,6 @@
+use std::ops;
+
+use futures::{stream::BoxStream, StreamExt, TryStreamExt};
 use sqlx::types::chrono::NaiveDateTime;
 use zksync_db_connection::{
     connection::Connection, error::DalResult, instrument::InstrumentExt, interpolate_query,
@@ -248,11 +251,17 @@ impl TransactionsWeb3Dal<'_, '_> {
                     transactions.error,
                     transactions.effective_gas_price,
                     transactions.refunded_gas,
+                    transactions.operator_suggested_refund,
                     commit_tx.tx_hash AS "eth_commit_tx_hash?",
                     prove_tx.tx_hash AS "eth_prove_tx_hash?",
-                    execute_tx.tx_hash AS "eth_execute_tx_hash?"
+                    execute_tx.tx_hash AS "eth_execute_tx_hash?",
+                    profiles.computational_gas_used AS "profile_computational_gas_used?",
+                    profiles.pubdata_published AS "profile_pubdata_published?",
+                    profiles.circuit_statistic AS "profile_circuit_statistic?",
+                    profiles.vm_execution_time_us AS "profile_vm_execution_time_us?"
                 FROM
                     transactions
+                    LEFT JOIN transaction_execution_profiles AS profiles ON profiles.tx_hash = transactions.hash
                     LEFT JOIN miniblocks ON miniblocks.number = transactions.miniblock_number
                     LEFT JOIN l1_batches ON l1_batches.number = miniblocks.l1_batch_number
                     LEFT JOIN eth_txs_history AS commit_tx ON (
@@ -320,6 +329,49 @@ impl TransactionsWeb3Dal<'_, '_> {
         Ok(hashes)
     }
 
+    /// Returns replacements of pending transactions that have happened after `from_timestamp`, together with
+    /// the time of each replacement.
+    pub async fn get_tx_replacements_after(
+        &mut self,
+        from_timestamp: NaiveDateTime,
+    ) -> DalResult<Vec<(NaiveDateTime, api::TransactionReplacement)>> {
+        let records = sqlx::query!(
+            r#"
+            SELECT
+                replaced_tx_hash,
+                tx_hash,
+                initiator_address,
+                nonce,
+                created_at
+            FROM
+                transaction_replacements
+            WHERE
+                created_at > $1
+            ORDER BY
+                created_at ASC
+            "#,
+            from_timestamp
+        )
+        .instrument("get_tx_replacements_after")
+        .with_arg("from_timestamp", &from_timestamp)
+        .fetch_all(self.storage)
+        .await?;
+
+        let replacements = records
+            .into_iter()
+            .map(|record| {
+                let replacement = api::TransactionReplacement {
+                    replaced_hash: H256::from_slice(&record.replaced_tx_hash),
+                    hash: H256::from_slice(&record.tx_hash),
+                    from: Address::from_slice(&record.initiator_address),
+                    nonce: record.nonce.into(),
+                };
+                (record.created_at, replacement)
+            })
+            .collect();
+        Ok(replacements)
+    }
+
     /// `committed_next_nonce` should equal the nonce for `initiator_address` in the storage.
     pub async fn next_nonce_by_initiator_account(
         &mut self,
@@ -400,6 +452,67 @@ impl TransactionsWeb3Dal<'_, '_> {
 
         Ok(rows.into_iter().map(Into::into).collect())
     }
+
+    /// Streams the server transactions (not API ones) from the specified range of miniblocks, ordered
+    /// by miniblock number and then by index in the miniblock. Unlike [`Self::get_raw_miniblock_transactions()`],
+    /// transactions are fetched from Postgres lazily, so that large ranges don't need to be held in memory.
+    pub fn stream_raw_transactions(
+        &mut self,
+        miniblocks: ops::RangeInclusive<MiniblockNumber>,
+    ) -> BoxStream<'_, DalResult<Transaction>> {
+        sqlx::query_as!(
+            StorageTransaction,
+            r#"
+            SELECT
+                *
+            FROM
+                transactions
+            WHERE
+                miniblock_number BETWEEN $1 AND $2
+            ORDER BY
+                miniblock_number,
+                index_in_block
+            "#,
+            i64::from(miniblocks.start().0),
+            i64::from(miniblocks.end().0)
+        )
+        .instrument("stream_raw_transactions")
+        .with_arg("miniblocks", &miniblocks)
+        .report_latency()
+        .fetch(self.storage)
+        .map_ok(Transaction::from)
+        .boxed()
+    }
+
+    /// Returns hashes of transactions in a certain miniblock in the order of their execution.
+    /// Returns an empty list if the miniblock doesn't exist.
+    pub async fn get_miniblock_tx_hashes(
+        &mut self,
+        miniblock: MiniblockNumber,
+    ) -> DalResult<Vec<H256>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                hash
+            FROM
+                transactions
+            WHERE
+                miniblock_number = $1
+            ORDER BY
+                index_in_block
+            "#,
+            i64::from(miniblock.0)
+        )
+        .instrument("get_miniblock_tx_hashes")
+        .with_arg("miniblock", &miniblock)
+        .fetch_all(self.storage)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| H256::from_slice(&row.hash))
+            .collect())
+    }
 }
 
 #[cfg(test)]
@@ -499,6 +612,52 @@ mod tests {
         assert!(web3_tx.unwrap().is_none());
     }
 
+    #[tokio::test]
+    async fn getting_transaction_details_with_refunds() {
+        let connection_pool = ConnectionPool::<Core>::test_pool().await;
+        let mut conn = connection_pool.connection().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(&ProtocolVersion::default())
+            .await
+            .unwrap();
+        let tx = mock_l2_transaction();
+        let tx_hash = tx.hash();
+        conn.transactions_dal()
+            .insert_transaction_l2(&tx, TransactionExecutionMetrics::default())
+            .await
+            .unwrap();
+
+        let details = conn
+            .transactions_web3_dal()
+            .get_transaction_details(tx_hash)
+            .await
+            .unwrap()
+            .expect("no transaction details");
+        assert_eq!(details.refunded_gas, None);
+        assert_eq!(details.operator_suggested_refund, None);
+
+        conn.blocks_dal()
+            .insert_miniblock(&create_miniblock_header(1))
+            .await
+            .unwrap();
+        let mut tx_result = mock_execution_result(tx);
+        tx_result.refunded_gas = 100;
+        tx_result.operator_suggested_refund = 120;
+        conn.transactions_dal()
+            .mark_txs_as_executed_in_miniblock(MiniblockNumber(1), &[tx_result], U256::from(1))
+            .await
+            .unwrap();
+
+        let details = conn
+            .transactions_web3_dal()
+            .get_transaction_details(tx_hash)
+            .await
+            .unwrap()
+            .expect("no transaction details");
+        assert_eq!(details.refunded_gas, Some(100.into()));
+        assert_eq!(details.operator_suggested_refund, Some(120.into()));
+    }
+
     #[tokio::test]
     async fn getting_receipts() {
         let connection_pool = ConnectionPool::<Core>::test_pool().await;
@@ -554,6 +713,22 @@ mod tests {
             .unwrap();
         assert_eq!(raw_txs.len(), 1);
         assert_eq!(raw_txs[0].hash(), tx_hash);
+
+        let streamed_txs: Vec<_> = conn
+            .transactions_web3_dal()
+            .stream_raw_transactions(MiniblockNumber(0)..=MiniblockNumber(1))
+            .try_collect()
+            .await
+            .unwrap();
+        let streamed_tx_hashes: Vec<_> = streamed_txs.iter().map(Transaction::hash).collect();
+        assert_eq!(streamed_tx_hashes, [tx_hash]);
+
+        let tx_hashes = conn
+            .transactions_web3_dal()
+            .get_miniblock_tx_hashes(MiniblockNumber(1))
+            .await
+            .unwrap();
+        assert_eq!(tx_hashes, [tx_hash]);
     }
 
     #[tokio::test]
@@ -0,0 +1,199 @@
+use zksync_db_connection::{connection::Connection, error::DalResult, instrument::Instrumented};
+use zksync_types::{tx::TransactionExecutionResult, MiniblockNumber};
+
+use crate::Core;
+
+/// Per-transaction execution profiles (resources consumed by transactions when executed by the state keeper).
+/// Profiles are returned as a part of transaction details in the API.
+#[derive(Debug)]
+pub struct TxExecutionProfilesDal<'a, 'c> {
+    pub(crate) storage: &'a mut Connection<'c, Core>,
+}
+
+impl TxExecutionProfilesDal<'_, '_> {
+    /// Records execution profiles for all transactions in the provided miniblock.
+    pub async fn insert_profiles(
+        &mut self,
+        miniblock_number: MiniblockNumber,
+        transactions: &[TransactionExecutionResult],
+    ) -> DalResult<()> {
+        if transactions.is_empty() {
+            return Ok(());
+        }
+
+        let instrumentation = Instrumented::new("insert_profiles")
+            .with_arg("miniblock_number", &miniblock_number)
+            .with_arg("transactions.len", &transactions.len());
+
+        let mut hashes = Vec::with_capacity(transactions.len());
+        let mut computational_gas_used = Vec::with_capacity(transactions.len());
+        let mut pubdata_published = Vec::with_capacity(transactions.len());
+        let mut circuit_statistics = Vec::with_capacity(transactions.len());
+        let mut vm_execution_times = Vec::with_capacity(transactions.len());
+        for (i, tx_res) in transactions.iter().enumerate() {
+            let metrics = &tx_res.execution_info;
+            let circuit_statistic =
+                serde_json::to_value(metrics.circuit_statistic).map_err(|err| {
+                    instrumentation.arg_error(
+                        &format!("transactions[{i}].execution_info.circuit_statistic"),
+                        err,
+                    )
+                })?;
+            let vm_execution_time_us = i64::try_from(metrics.vm_execution_time.as_micros())
+                .map_err(|err| {
+                    instrumentation.arg_error(
+                        &format!("transactions[{i}].execution_info.vm_execution_time"),
+                        err,
+                    )
+                })?;
+
+            hashes.push(tx_res.hash.as_bytes());
+            computational_gas_used.push(i64::from(metrics.computational_gas_used));
+            pubdata_published.push(i64::from(metrics.pubdata_published));
+            circuit_statistics.push(circuit_statistic);
+            vm_execution_times.push(vm_execution_time_us);
+        }
+
+        let query = sqlx::query!(
+            r#"
+            INSERT INTO
+                transaction_execution_profiles (
+                    tx_hash,
+                    miniblock_number,
+                    computational_gas_used,
+                    pubdata_published,
+                    circuit_statistic,
+                    vm_execution_time_us,
+                    created_at,
+                    updated_at
+                )
+            SELECT
+                u.tx_hash,
+                $1,
+                u.computational_gas_used,
+                u.pubdata_published,
+                u.circuit_statistic,
+                u.vm_execution_time_us,
+                NOW(),
+                NOW()
+            FROM
+                UNNEST($2::bytea[], $3::BIGINT[], $4::BIGINT[], $5::jsonb[], $6::BIGINT[]) AS u (
+                    tx_hash,
+                    computational_gas_used,
+                    pubdata_published,
+                    circuit_statistic,
+                    vm_execution_time_us
+                )
+            ON CONFLICT (tx_hash) DO
+            UPDATE
+            SET
+                miniblock_number = excluded.miniblock_number,
+                computational_gas_used = excluded.computational_gas_used,
+                pubdata_published = excluded.pubdata_published,
+                circuit_statistic = excluded.circuit_statistic,
+                vm_execution_time_us = excluded.vm_execution_time_us,
+                updated_at = NOW()
+            "#,
+            i64::from(miniblock_number.0),
+            &hashes as &[&[u8]],
+            &computational_gas_used,
+            &pubdata_published,
+            &circuit_statistics,
+            &vm_execution_times,
+        );
+
+        instrumentation.with(query).execute(self.storage).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use zksync_types::{
+        api::TransactionExecutionProfile, circuit::CircuitStatistic, tx::ExecutionMetrics,
+        ProtocolVersion,
+    };
+
+    use super::*;
+    use crate::{
+        tests::{create_miniblock_header, mock_execution_result, mock_l2_transaction},
+        ConnectionPool, Core, CoreDal,
+    };
+
+    #[tokio::test]
+    async fn recording_execution_profiles() {
+        let pool = ConnectionPool::<Core>::test_pool().await;
+        let mut conn = pool.connection().await.unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(&ProtocolVersion::default())
+            .await
+            .unwrap();
+        conn.blocks_dal()
+            .insert_miniblock(&create_miniblock_header(1))
+            .await
+            .unwrap();
+
+        let tx = mock_l2_transaction();
+        let tx_hash = tx.hash();
+        conn.transactions_dal()
+            .insert_transaction_l2(&tx, Default::default())
+            .await
+            .unwrap();
+        let mut tx_result = mock_execution_result(tx);
+        tx_result.execution_info = ExecutionMetrics {
+            computational_gas_used: 1_000,
+            pubdata_published: 200,
+            circuit_statistic: CircuitStatistic {
+                main_vm: 0.5,
+                keccak256: 0.25,
+                ..CircuitStatistic::default()
+            },
+            vm_execution_time: Duration::from_micros(1_234),
+            ..ExecutionMetrics::default()
+        };
+        let tx_results = [tx_result];
+        conn.transactions_dal()
+            .mark_txs_as_executed_in_miniblock(MiniblockNumber(1), &tx_results, 1.into())
+            .await
+            .unwrap();
+        conn.tx_execution_profiles_dal()
+            .insert_profiles(MiniblockNumber(1), &tx_results)
+            .await
+            .unwrap();
+
+        let details = conn
+            .transactions_web3_dal()
+            .get_transaction_details(tx_hash)
+            .await
+            .unwrap()
+            .expect("no transaction details");
+        assert_eq!(
+            details.execution_profile,
+            Some(TransactionExecutionProfile {
+                computational_gas_used: 1_000,
+                pubdata_published: 200,
+                circuits_used: tx_results[0].execution_info.circuit_statistic,
+                vm_execution_time_us: 1_234,
+            })
+        );
+
+        // Profiles must be removed together with the miniblock.
+        conn.transactions_dal()
+            .reset_transactions_state(MiniblockNumber(0))
+            .await
+            .unwrap();
+        conn.blocks_dal()
+            .delete_miniblocks(MiniblockNumber(0))
+            .await
+            .unwrap();
+        let details = conn
+            .transactions_web3_dal()
+            .get_transaction_details(tx_hash)
+            .await
+            .unwrap()
+            .expect("no transaction details");
+        assert_eq!(details.execution_profile, None);
+    }
+}
@@ -0,0 +1,256 @@
+//! Cancellation of DAL queries exceeding the configured timeout or dropped by the caller.
+//!
+//! Query timeouts are configured globally, with optional overrides for components identified by the requester tag
+//! of connections; see [`GlobalConnectionPoolConfig`](crate::connection_pool::GlobalConnectionPoolConfig).
+//! For connections with a timeout, a query is cancelled on the server using `pg_cancel_backend()` if it executes
+//! for longer than the timeout. Cancellation is performed via a dedicated short-lived connection, so it works
+//! even if the pool is exhausted.
+//!
+//! If the future executing a query is dropped before completion, the query continues running on the server,
+//! and the connection cannot be reused until the query completes. Such a connection is not returned to the pool;
+//! instead, the query is cancelled and the connection is closed once the connection is dropped. Cancelling the query
+//! right away would be unsafe: the cancellation signal could reach a subsequent unrelated query executed
+//! on the same connection.
+
+use std::{
+    fmt,
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use sqlx::{
+    postgres::{PgConnectOptions, PgPool},
+    Connection as _, PgConnection,
+};
+use tokio::sync::OnceCell;
+
+use crate::metrics::REQUEST_METRICS;
+
+/// Cancels queries executed on a certain pooled connection.
+pub(crate) struct QueryCanceller {
+    timeout: Duration,
+    connect_options: Arc<PgConnectOptions>,
+    /// Server process ID for the connection. Fetched lazily before the first query executed
+    /// on the connection, so that acquiring a connection doesn't require a roundtrip to the database.
+    backend_pid: OnceCell<i32>,
+    /// Set if a query executed on the connection was dropped before completion.
+    has_dropped_query: AtomicBool,
+}
+
+impl fmt::Debug for QueryCanceller {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Connect options are omitted since they may contain sensitive information (e.g., the DB password).
+        formatter
+            .debug_struct("QueryCanceller")
+            .field("timeout", &self.timeout)
+            .field("backend_pid", &self.backend_pid.get())
+            .field("has_dropped_query", &self.has_dropped_query)
+            .finish_non_exhaustive()
+    }
+}
+
+impl QueryCanceller {
+    /// Creates a canceller for a connection acquired from `pool`. The canceller must be [prepared](Self::prepare())
+    /// before it can cancel queries.
+    pub(crate) fn new(pool: &PgPool, timeout: Duration) -> Self {
+        Self {
+            timeout,
+            connect_options: pool.connect_options(),
+            backend_pid: OnceCell::new(),
+            has_dropped_query: AtomicBool::new(false),
+        }
+    }
+
+    /// Gets the server process ID for the `connection` this canceller was created for, unless it's already known.
+    /// This requires a roundtrip to the database, but only for the first call.
+    pub(crate) async fn prepare(&self, connection: &mut PgConnection) -> sqlx::Result<()> {
+        self.backend_pid
+            .get_or_try_init(|| sqlx::query_scalar("SELECT pg_backend_pid()").fetch_one(connection))
+            .await?;
+        Ok(())
+    }
+
+    pub(crate) fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Executes a query, cancelling it if it exceeds the timeout. If the returned future is dropped before completion,
+    /// the connection is marked as [unusable](Self::has_dropped_query()). Returns the query output and a flag
+    /// whether the query has timed out.
+    /// If the canceller wasn't successfully [prepared](Self::prepare()), the query is executed without cancellation.
+    ///
+    /// Unlike wrapping the query in `tokio::time::timeout()`, the query future is always awaited to completion
+    /// after a timeout; it completes with a cancellation error from Postgres. This ensures that the connection
+    /// remains in a consistent state and can be reused.
+    pub(crate) async fn run<R>(
+        &self,
+        query_future: impl Future<Output = sqlx::Result<R>>,
+    ) -> (sqlx::Result<R>, bool) {
+        let Some(&backend_pid) = self.backend_pid.get() else {
+            return (query_future.await, false);
+        };
+        let mut guard = DropGuard(Some((self, backend_pid)));
+        tokio::pin!(query_future);
+
+        let (output, timed_out) = match tokio::time::timeout(self.timeout, &mut query_future).await
+        {
+            Ok(output) => (output, false),
+            Err(_) => {
+                guard.0 = None;
+                // Await cancellation together with the query, so that the cancellation signal cannot reach
+                // a subsequent query on the same connection.
+                let (output, ()) = tokio::join!(query_future, self.cancel(backend_pid));
+                (output, true)
+            }
+        };
+        guard.0 = None;
+        (output, timed_out)
+    }
+
+    /// Checks whether a query executed on the connection was dropped before completion. Such a connection
+    /// must not be reused; it should be [closed](Self::close_after_dropped_query()) instead.
+    pub(crate) fn has_dropped_query(&self) -> bool {
+        self.has_dropped_query.load(Ordering::Relaxed)
+    }
+
+    /// Cancels the dropped query and closes the `connection` this canceller was created for in the background.
+    /// Since the connection is owned by the background task, the cancellation signal cannot reach any other query.
+    pub(crate) fn close_after_dropped_query(&self, connection: PgConnection) {
+        // `has_dropped_query` is only set if `backend_pid` is known.
+        let Some(&backend_pid) = self.backend_pid.get() else {
+            return;
+        };
+        // If there's no Tokio runtime, we're most probably shutting down, so dropping the connection is enough.
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            let cancel = self.cancel(backend_pid);
+            handle.spawn(async move {
+                cancel.await;
+                if let Err(err) = connection.close().await {
+                    tracing::debug!(
+                        "Failed closing connection for DB process {backend_pid}: {err}"
+                    );
+                }
+            });
+        }
+    }
+
+    fn cancel(&self, backend_pid: i32) -> impl Future<Output = ()> + Send + 'static {
+        let connect_options = self.connect_options.clone();
+        async move {
+            REQUEST_METRICS.cancellations.inc();
+            let result = async {
+                let mut connection = PgConnection::connect_with(&connect_options).await?;
+                sqlx::query("SELECT pg_cancel_backend($1)")
+                    .bind(backend_pid)
+                    .execute(&mut connection)
+                    .await?;
+                connection.close().await
+            };
+            if let Err(err) = result.await {
+                tracing::warn!(
+                    "Failed cancelling query executed by DB process {backend_pid}: {err}"
+                );
+            }
+        }
+    }
+}
+
+/// Guard marking the connection as having a dropped query on drop, unless disarmed.
+#[derive(Debug)]
+struct DropGuard<'a>(Option<(&'a QueryCanceller, i32)>);
+
+impl Drop for DropGuard<'_> {
+    fn drop(&mut self) {
+        let Some((canceller, backend_pid)) = self.0.take() else {
+            return;
+        };
+        tracing::info!(
+            "Query executed by DB process {backend_pid} was dropped before completion; \
+             the connection will be closed once it's dropped"
+        );
+        canceller.has_dropped_query.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+    use crate::{connection::Connection, connection_pool::ConnectionPool, utils::InternalMarker};
+
+    async fn backend_exists(pool: &ConnectionPool<InternalMarker>, backend_pid: i32) -> bool {
+        let mut storage = pool.connection().await.unwrap();
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM pg_stat_activity WHERE pid = $1)")
+            .bind(backend_pid)
+            .fetch_one(storage.conn())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn cancelling_query_on_timeout() {
+        let pool = ConnectionPool::<InternalMarker>::constrained_test_pool(2).await;
+        let mut storage = pool.connection().await.unwrap();
+        let canceller = QueryCanceller::new(&pool.inner, Duration::from_millis(100));
+        canceller.prepare(storage.conn()).await.unwrap();
+
+        let started_at = Instant::now();
+        let (output, timed_out) = canceller
+            .run(sqlx::query("SELECT pg_sleep(10)").execute(storage.conn()))
+            .await;
+        assert!(timed_out);
+        let err = output.unwrap_err();
+        let sqlx::Error::Database(db_err) = &err else {
+            panic!("unexpected error: {err:?}");
+        };
+        assert_eq!(db_err.code().as_deref(), Some("57014"));
+        assert!(started_at.elapsed() < Duration::from_secs(5));
+
+        // The connection should remain usable.
+        let (output, timed_out) = canceller
+            .run(sqlx::query("SELECT 1").execute(storage.conn()))
+            .await;
+        assert!(!timed_out);
+        output.unwrap();
+    }
+
+    #[tokio::test]
+    async fn closing_connection_with_dropped_query() {
+        let pool = ConnectionPool::<InternalMarker>::constrained_test_pool(3).await;
+        let connection = pool.inner.acquire().await.unwrap();
+        let canceller = QueryCanceller::new(&pool.inner, Duration::from_secs(60));
+        let mut storage = Connection::<InternalMarker>::from_pool(
+            connection,
+            None,
+            Some(canceller),
+            pool.inner.connect_options(),
+            None,
+        );
+        let (conn, _, canceller) = storage.parts_for_query().await;
+        let canceller = canceller.unwrap();
+        let backend_pid = *canceller.backend_pid.get().unwrap();
+
+        let query = canceller.run(sqlx::query("SELECT pg_sleep(60)").execute(conn));
+        tokio::time::timeout(Duration::from_millis(200), query)
+            .await
+            .unwrap_err();
+        assert!(canceller.has_dropped_query());
+        assert!(backend_exists(&pool, backend_pid).await);
+        drop(storage);
+
+        // The connection must be closed rather than returned to the pool.
+        let started_at = Instant::now();
+        while backend_exists(&pool, backend_pid).await {
+            assert!(
+                started_at.elapsed() < Duration::from_secs(10),
+                "connection with a dropped query was not closed"
+            );
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    }
+}
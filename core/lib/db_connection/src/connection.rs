@@ -4,19 +4,24 @@ use std::{
     panic::Location,
     sync::{
         atomic::{AtomicUsize, Ordering},
-        Mutex,
+        Arc, Mutex,
     },
-    time::{Instant, SystemTime},
+    time::{Duration, Instant, SystemTime},
 };
 
+use futures::future::BoxFuture;
+use rand::Rng;
 use sqlx::{
-    pool::PoolConnection, types::chrono, Connection as _, PgConnection, Postgres, Transaction,
+    pool::PoolConnection, postgres::PgConnectOptions, types::chrono, Connection as _, PgConnection,
+    Postgres, Transaction,
 };
 
 use crate::{
+    cancellation::QueryCanceller,
     connection_pool::ConnectionPool,
     error::{DalConnectionError, DalResult},
-    metrics::CONNECTION_METRICS,
+    instrument::InstrumentExt,
+    metrics::{CONNECTION_METRICS, REQUEST_METRICS},
     utils::InternalMarker,
 };
 
@@ -98,8 +103,13 @@ impl TracedConnections {
 }
 
 struct PooledConnection<'a> {
-    connection: PoolConnection<Postgres>,
+    /// Always set; taken out only on drop if the connection must not be returned to the pool.
+    connection: Option<PoolConnection<Postgres>>,
     tags: Option<ConnectionTags>,
+    /// Set if queries on this connection have a timeout.
+    canceller: Option<QueryCanceller>,
+    /// Options to connect to the same database, e.g. to capture slow queries via a dedicated connection.
+    connect_options: Arc<PgConnectOptions>,
     created_at: Instant,
     traced: Option<(&'a TracedConnections, usize)>,
 }
@@ -109,6 +119,7 @@ impl fmt::Debug for PooledConnection<'_> {
         formatter
             .debug_struct("PooledConnection")
             .field("tags", &self.tags)
+            .field("canceller", &self.canceller)
             .field("created_at", &self.created_at)
             .finish_non_exhaustive()
     }
@@ -134,18 +145,48 @@ impl Drop for PooledConnection<'_> {
         if let Some((connections, id)) = self.traced {
             connections.mark_as_dropped(id);
         }
+
+        if let Some(canceller) = &self.canceller {
+            if canceller.has_dropped_query() {
+                if let Some(connection) = self.connection.take() {
+                    // Detaching the connection allows the pool to open a replacement.
+                    canceller.close_after_dropped_query(connection.detach());
+                }
+            }
+        }
     }
 }
 
-#[derive(Debug)]
 enum ConnectionInner<'a> {
     Pooled(PooledConnection<'a>),
     Transaction {
         transaction: Transaction<'a, Postgres>,
         tags: Option<&'a ConnectionTags>,
+        canceller: Option<&'a QueryCanceller>,
+        connect_options: Arc<PgConnectOptions>,
     },
 }
 
+impl fmt::Debug for ConnectionInner<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pooled(pooled) => formatter.debug_tuple("Pooled").field(pooled).finish(),
+            // Connect options are omitted since they may contain sensitive information (e.g., the DB password).
+            Self::Transaction {
+                transaction,
+                tags,
+                canceller,
+                ..
+            } => formatter
+                .debug_struct("Transaction")
+                .field("transaction", transaction)
+                .field("tags", tags)
+                .field("canceller", canceller)
+                .finish_non_exhaustive(),
+        }
+    }
+}
+
 /// Marker trait for restricting using all possible types as a storage marker.
 pub trait DbMarker {}
 
@@ -165,12 +206,16 @@ impl<'a, DB: DbMarker> Connection<'a, DB> {
     pub(crate) fn from_pool(
         connection: PoolConnection<Postgres>,
         tags: Option<ConnectionTags>,
+        canceller: Option<QueryCanceller>,
+        connect_options: Arc<PgConnectOptions>,
         traced_connections: Option<&'a TracedConnections>,
     ) -> Self {
         let created_at = Instant::now();
         let inner = ConnectionInner::Pooled(PooledConnection {
-            connection,
+            connection: Some(connection),
             tags,
+            canceller,
+            connect_options,
             created_at,
             traced: traced_connections.map(|connections| {
                 let id = connections.acquire(tags, created_at);
@@ -184,13 +229,16 @@ impl<'a, DB: DbMarker> Connection<'a, DB> {
     }
 
     pub async fn start_transaction(&mut self) -> DalResult<Connection<'_, DB>> {
-        let (conn, tags) = self.conn_and_tags();
+        let connect_options = self.connect_options().clone();
+        let (conn, tags, canceller) = self.parts();
         let inner = ConnectionInner::Transaction {
             transaction: conn
                 .begin()
                 .await
                 .map_err(|err| DalConnectionError::start_transaction(err, tags.cloned()))?,
             tags,
+            canceller,
+            connect_options,
         };
         Ok(Connection {
             inner,
@@ -198,6 +246,30 @@ impl<'a, DB: DbMarker> Connection<'a, DB> {
         })
     }
 
+    /// Starts a read-only `REPEATABLE READ` transaction. All queries executed via the returned connection observe
+    /// the same consistent snapshot of the database, taken when the first query is executed. This should be used
+    /// to combine data from several queries that could otherwise be affected by concurrent changes (e.g., a miniblock
+    /// header and its transactions sealed in between the queries).
+    ///
+    /// Since the transaction is read-only, it doesn't need to be committed; it is rolled back when dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called on a connection within a transaction, since the isolation level can only be set
+    /// for top-level transactions.
+    pub async fn read_snapshot(&mut self) -> DalResult<Connection<'_, DB>> {
+        assert!(
+            !self.in_transaction(),
+            "Connection::read_snapshot cannot be invoked within a transaction"
+        );
+        let mut transaction = self.start_transaction().await?;
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+            .instrument("read_snapshot")
+            .execute(&mut transaction)
+            .await?;
+        Ok(transaction)
+    }
+
     /// Checks if the `Connection` is currently within database transaction.
     pub fn in_transaction(&self) -> bool {
         matches!(self.inner, ConnectionInner::Transaction { .. })
@@ -207,6 +279,7 @@ impl<'a, DB: DbMarker> Connection<'a, DB> {
         if let ConnectionInner::Transaction {
             transaction: postgres,
             tags,
+            ..
         } = self.inner
         {
             postgres
@@ -223,16 +296,120 @@ impl<'a, DB: DbMarker> Connection<'a, DB> {
     }
 
     pub fn conn_and_tags(&mut self) -> (&mut PgConnection, Option<&ConnectionTags>) {
+        let (conn, tags, _) = self.parts();
+        (conn, tags)
+    }
+
+    pub(crate) fn parts(
+        &mut self,
+    ) -> (
+        &mut PgConnection,
+        Option<&ConnectionTags>,
+        Option<&QueryCanceller>,
+    ) {
         match &mut self.inner {
-            ConnectionInner::Pooled(pooled) => (&mut pooled.connection, pooled.tags.as_ref()),
-            ConnectionInner::Transaction { transaction, tags } => (transaction, *tags),
+            ConnectionInner::Pooled(pooled) => (
+                pooled
+                    .connection
+                    .as_mut()
+                    .expect("connection is only taken out on drop"),
+                pooled.tags.as_ref(),
+                pooled.canceller.as_ref(),
+            ),
+            ConnectionInner::Transaction {
+                transaction,
+                tags,
+                canceller,
+                ..
+            } => (transaction, *tags, *canceller),
+        }
+    }
+
+    /// Same as [`Self::parts()`], but also prepares the query canceller (if any) for the connection, which requires
+    /// a roundtrip to the database for the first query on the connection.
+    pub(crate) async fn parts_for_query(
+        &mut self,
+    ) -> (
+        &mut PgConnection,
+        Option<&ConnectionTags>,
+        Option<&QueryCanceller>,
+    ) {
+        let (conn, tags, canceller) = self.parts();
+        if let Some(canceller) = canceller {
+            if let Err(err) = canceller.prepare(conn).await {
+                let tags_display = ConnectionTags::display(tags);
+                tracing::warn!(
+                    "Failed getting DB process ID for connection [{tags_display}]; its queries won't be cancelled: {err}"
+                );
+            }
+        }
+        (conn, tags, canceller)
+    }
+
+    pub(crate) fn connect_options(&self) -> &Arc<PgConnectOptions> {
+        match &self.inner {
+            ConnectionInner::Pooled(pooled) => &pooled.connect_options,
+            ConnectionInner::Transaction {
+                connect_options, ..
+            } => connect_options,
+        }
+    }
+
+    /// Runs `action` in a new transaction and commits it. If the transaction fails with a serialization
+    /// or deadlock error (see [`DalError::is_retriable()`]), it is rolled back and retried with a back-off
+    /// up to the configured number of times (see [`GlobalConnectionPoolConfig::set_query_max_retries()`]).
+    /// Thus, `action` must not have side effects outside the database.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called on a connection within a transaction, since a failed nested transaction cannot be retried.
+    ///
+    /// [`DalError::is_retriable()`]: crate::error::DalError::is_retriable()
+    /// [`GlobalConnectionPoolConfig::set_query_max_retries()`]: crate::connection_pool::GlobalConnectionPoolConfig::set_query_max_retries()
+    pub async fn transaction_with_retries<T, F>(&mut self, mut action: F) -> DalResult<T>
+    where
+        F: for<'t> FnMut(&'t mut Connection<'_, DB>) -> BoxFuture<'t, DalResult<T>>,
+    {
+        const AVG_BACKOFF_INTERVAL: Duration = Duration::from_millis(50);
+
+        assert!(
+            !self.in_transaction(),
+            "Connection::transaction_with_retries cannot be invoked within a transaction"
+        );
+        let max_retries = ConnectionPool::<InternalMarker>::global_config().query_max_retries();
+        let mut retry = 0;
+        loop {
+            let mut transaction = self.start_transaction().await?;
+            let result = match action(&mut transaction).await {
+                Ok(output) => transaction.commit().await.map(|()| output),
+                Err(err) => {
+                    drop(transaction); // rolls back the transaction
+                    Err(err)
+                }
+            };
+            let err = match result {
+                Err(err) if err.is_retriable() && retry < max_retries => err,
+                _ => return result,
+            };
+
+            retry += 1;
+            REQUEST_METRICS.transaction_retries.inc();
+            // Slightly randomize back-off interval so that conflicting transactions don't retry in lockstep.
+            let jitter = rand::thread_rng().gen_range(0.8..1.2);
+            let backoff_interval = (AVG_BACKOFF_INTERVAL * retry).mul_f32(jitter);
+            let tags_display = ConnectionTags::display(self.conn_and_tags().1);
+            tracing::info!(
+                "DB transaction [{tags_display}] failed with a retriable error, retrying ({retry}/{max_retries}) \
+                 after {backoff_interval:?}: {err}"
+            );
+            tokio::time::sleep(backoff_interval).await;
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{connection_pool::ConnectionPool, utils::InternalMarker};
+    use super::*;
 
     #[tokio::test]
     async fn processor_tags_propagate_to_transactions() {
@@ -266,4 +443,95 @@ mod tests {
             assert!(traced.is_empty());
         }
     }
+
+    #[tokio::test]
+    async fn retrying_transactions() {
+        let pool = ConnectionPool::<InternalMarker>::constrained_test_pool(1).await;
+        ConnectionPool::<InternalMarker>::global_config().set_query_max_retries(2);
+        let mut connection = pool.connection_tagged("test").await.unwrap();
+
+        let mut attempts = 0;
+        let output = connection
+            .transaction_with_retries(|transaction| {
+                attempts += 1;
+                let attempt = attempts;
+                Box::pin(async move {
+                    if attempt == 1 {
+                        sqlx::query(
+                            "DO $$ BEGIN RAISE EXCEPTION 'test' USING ERRCODE = 'serialization_failure'; END $$",
+                        )
+                        .instrument("fail")
+                        .execute(transaction)
+                        .await?;
+                    }
+                    Ok(attempt)
+                })
+            })
+            .await
+            .unwrap();
+        assert_eq!(output, 2);
+
+        let mut attempts = 0;
+        let err = connection
+            .transaction_with_retries(|transaction| {
+                attempts += 1;
+                Box::pin(async move {
+                    sqlx::query(
+                        "DO $$ BEGIN RAISE EXCEPTION 'test' USING ERRCODE = 'deadlock_detected'; END $$",
+                    )
+                    .instrument("fail")
+                    .execute(transaction)
+                    .await?;
+                    Ok(())
+                })
+            })
+            .await
+            .unwrap_err();
+        assert!(err.is_retriable(), "{err}");
+        assert_eq!(attempts, 3);
+    }
+
+    #[tokio::test]
+    async fn reading_consistent_snapshot() {
+        let pool = ConnectionPool::<InternalMarker>::constrained_test_pool(2).await;
+        let mut connection = pool.connection().await.unwrap();
+        sqlx::query("CREATE TABLE snapshot_test (value INT NOT NULL)")
+            .execute(connection.conn())
+            .await
+            .unwrap();
+
+        let mut snapshot_connection = pool.connection().await.unwrap();
+        let mut snapshot = snapshot_connection.read_snapshot().await.unwrap();
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM snapshot_test")
+            .fetch_one(snapshot.conn())
+            .await
+            .unwrap();
+        assert_eq!(count, 0);
+
+        sqlx::query("INSERT INTO snapshot_test VALUES (1)")
+            .execute(connection.conn())
+            .await
+            .unwrap();
+        // The insertion must not be visible in the snapshot.
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM snapshot_test")
+            .fetch_one(snapshot.conn())
+            .await
+            .unwrap();
+        assert_eq!(count, 0);
+
+        // The snapshot is read-only.
+        let err = sqlx::query("INSERT INTO snapshot_test VALUES (2)")
+            .instrument("insert")
+            .execute(&mut snapshot)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("read-only transaction"), "{err}");
+
+        drop(snapshot);
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM snapshot_test")
+            .fetch_one(snapshot_connection.conn())
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+    }
 }
@@ -1,11 +1,12 @@
 use std::{
+    collections::BTreeMap,
     env, fmt,
     future::Future,
     marker::PhantomData,
     panic::Location,
     sync::{
-        atomic::{AtomicU64, Ordering},
-        Arc,
+        atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
+        Arc, RwLock,
     },
     time::Duration,
 };
@@ -18,18 +19,25 @@ use sqlx::{
 };
 
 use crate::{
+    cancellation::QueryCanceller,
     connection::{Connection, ConnectionTags, DbMarker, TracedConnections},
     error::{DalConnectionError, DalResult},
-    metrics::CONNECTION_METRICS,
+    metrics::{CONNECTION_METRICS, POOL_METRICS, REPLICA_METRICS},
+    notifications::NotificationListener,
+    replicas::{ReadPreference, ReplicaLagMonitor, ReplicaPin, ReplicaRouting},
 };
 
 /// Builder for [`ConnectionPool`]s.
 #[derive(Clone)]
 pub struct ConnectionPoolBuilder<DB: DbMarker> {
     database_url: String,
+    name: Option<&'static str>,
     max_size: u32,
     acquire_timeout: Duration,
     statement_timeout: Option<Duration>,
+    replica_urls: Vec<String>,
+    max_replica_lag: Duration,
+    default_read_preference: ReadPreference,
     _marker: PhantomData<DB>,
 }
 
@@ -38,14 +46,25 @@ impl<DB: DbMarker> fmt::Debug for ConnectionPoolBuilder<DB> {
         // Database URL is potentially sensitive, thus we omit it.
         formatter
             .debug_struct("ConnectionPoolBuilder")
+            .field("name", &self.name)
             .field("max_size", &self.max_size)
             .field("acquire_timeout", &self.acquire_timeout)
             .field("statement_timeout", &self.statement_timeout)
+            .field("replica_count", &self.replica_urls.len())
+            .field("max_replica_lag", &self.max_replica_lag)
+            .field("default_read_preference", &self.default_read_preference)
             .finish()
     }
 }
 
 impl<DB: DbMarker> ConnectionPoolBuilder<DB> {
+    /// Sets the name of the pool. Named pools report per-pool metrics (size, acquisition latency and starvation),
+    /// so the name should have low cardinality (e.g., a name of the node subsystem the pool is dedicated to).
+    pub fn set_name(&mut self, name: &'static str) -> &mut Self {
+        self.name = Some(name);
+        self
+    }
+
     /// Overrides the maximum number of connections that can be allocated by the pool.
     pub fn set_max_size(&mut self, max_size: u32) -> &mut Self {
         self.max_size = max_size;
@@ -70,34 +89,92 @@ impl<DB: DbMarker> ConnectionPoolBuilder<DB> {
         self
     }
 
+    /// Configures read-only replicas for the pool. Connections will be acquired from a replica lagging behind
+    /// the main database by no more than `max_lag`, distributing connections among such replicas. If there are no
+    /// such replicas, or acquiring a connection to a replica fails, connections will be acquired from the database
+    /// the builder was created for. Each replica gets a separate pool with the same parameters as the main one.
+    ///
+    /// Replication lag is tracked by [`ReplicaLagMonitor`], which should be run for the built pool;
+    /// see [`ConnectionPool::replica_lag_monitor()`]. Routing only applies to connections acquired with
+    /// the [`ReadPreference::ReadPreferred`] preference; see [`Self::set_default_read_preference()`].
+    pub fn set_replicas(&mut self, replica_urls: Vec<String>, max_lag: Duration) -> &mut Self {
+        self.replica_urls = replica_urls;
+        self.max_replica_lag = max_lag;
+        self
+    }
+
+    /// Sets the read preference for connections acquired using [`ConnectionPool::connection()`]
+    /// and [`ConnectionPool::connection_tagged()`]. The preference can be overridden for specific connections
+    /// using [`ConnectionPool::connection_with_preference()`]. If not specified, connections are acquired
+    /// from the main database ([`ReadPreference::PrimaryOnly`]), so routing to replicas must be opted into
+    /// explicitly.
+    pub fn set_default_read_preference(&mut self, preference: ReadPreference) -> &mut Self {
+        self.default_read_preference = preference;
+        self
+    }
+
     /// Returns the maximum number of connections that can be allocated by the pool.
     pub fn max_size(&self) -> u32 {
         self.max_size
     }
 
-    /// Builds a connection pool from this builder.
-    pub async fn build(&self) -> anyhow::Result<ConnectionPool<DB>> {
-        let options = PgPoolOptions::new()
+    fn pool_options(&self) -> PgPoolOptions {
+        PgPoolOptions::new()
             .max_connections(self.max_size)
-            .acquire_timeout(self.acquire_timeout);
-        let mut connect_options: PgConnectOptions = self
-            .database_url
+            .acquire_timeout(self.acquire_timeout)
+    }
+
+    fn connect_options(&self, database_url: &str) -> anyhow::Result<PgConnectOptions> {
+        let mut connect_options: PgConnectOptions = database_url
             .parse()
             .context("Failed parsing database URL")?;
         if let Some(timeout) = self.statement_timeout {
             let timeout_string = format!("{}s", timeout.as_secs());
             connect_options = connect_options.options([("statement_timeout", timeout_string)]);
         }
-        let pool = options
+        Ok(connect_options)
+    }
+
+    /// Builds a connection pool from this builder.
+    pub async fn build(&self) -> anyhow::Result<ConnectionPool<DB>> {
+        let connect_options = self.connect_options(&self.database_url)?;
+        let pool = self
+            .pool_options()
             .connect_with(connect_options)
             .await
             .context("Failed connecting to database")?;
+
+        let replica_routing = if self.replica_urls.is_empty() {
+            None
+        } else {
+            // Replica pools connect lazily, so that unavailable replicas don't prevent the pool from being built.
+            let replica_pools = self
+                .replica_urls
+                .iter()
+                .enumerate()
+                .map(|(i, replica_url)| {
+                    let connect_options = self
+                        .connect_options(replica_url)
+                        .with_context(|| format!("invalid URL for replica #{i}"))?;
+                    Ok(self.pool_options().connect_lazy_with(connect_options))
+                })
+                .collect::<anyhow::Result<_>>()?;
+            let routing = ReplicaRouting::new(replica_pools, self.max_replica_lag);
+            Some(Arc::new(routing))
+        };
+
+        if let Some(name) = self.name {
+            POOL_METRICS.max_size[&name].set(self.max_size.into());
+        }
         tracing::info!("Created DB pool with parameters {self:?}");
         Ok(ConnectionPool {
             database_url: self.database_url.clone(),
+            name: self.name,
             inner: pool,
             max_size: self.max_size,
             traced_connections: None,
+            replica_routing,
+            default_read_preference: self.default_read_preference,
             _marker: Default::default(),
         })
     }
@@ -106,9 +183,13 @@ impl<DB: DbMarker> ConnectionPoolBuilder<DB> {
     pub async fn build_singleton(&self) -> anyhow::Result<ConnectionPool<DB>> {
         let singleton_builder = Self {
             database_url: self.database_url.clone(),
+            name: self.name,
             max_size: 1,
             acquire_timeout: self.acquire_timeout,
             statement_timeout: self.statement_timeout,
+            replica_urls: self.replica_urls.clone(),
+            max_replica_lag: self.max_replica_lag,
+            default_read_preference: self.default_read_preference,
             _marker: self._marker,
         };
         singleton_builder.build().await
@@ -159,7 +240,9 @@ impl TestTemplate {
     /// so that the db can be used as a template.
     pub async fn freeze<DB: DbMarker>(pool: ConnectionPool<DB>) -> anyhow::Result<Self> {
         use sqlx::Executor as _;
-        let mut conn = pool.acquire_connection_retried(None).await?;
+        let (mut conn, _) = pool
+            .acquire_connection_retried(None, ReadPreference::PrimaryOnly, &ReplicaPin::default())
+            .await?;
         conn.execute(
             "UPDATE pg_database SET datallowconn = false WHERE datname = current_database()",
         )
@@ -206,6 +289,14 @@ pub struct GlobalConnectionPoolConfig {
     // We consider millisecond precision to be enough for config purposes.
     long_connection_threshold_ms: AtomicU64,
     slow_query_threshold_ms: AtomicU64,
+    /// Bit representation of an `f64` sample rate.
+    slow_query_explain_sample_rate: AtomicU64,
+    /// Default query timeout; 0 means no timeout.
+    query_timeout_ms: AtomicU64,
+    /// Per-component overrides for `query_timeout_ms` keyed by the requester tag.
+    component_query_timeouts_ms: RwLock<BTreeMap<String, u64>>,
+    query_max_retries: AtomicU32,
+    use_binary_copy: AtomicBool,
 }
 
 impl GlobalConnectionPoolConfig {
@@ -213,6 +304,11 @@ impl GlobalConnectionPoolConfig {
         Self {
             long_connection_threshold_ms: AtomicU64::new(5_000), // 5 seconds
             slow_query_threshold_ms: AtomicU64::new(100),        // 0.1 seconds
+            slow_query_explain_sample_rate: AtomicU64::new(0),   // 0.0, i.e. disabled
+            query_timeout_ms: AtomicU64::new(0),                 // disabled
+            component_query_timeouts_ms: RwLock::new(BTreeMap::new()),
+            query_max_retries: AtomicU32::new(0),
+            use_binary_copy: AtomicBool::new(false),
         }
     }
 
@@ -224,6 +320,33 @@ impl GlobalConnectionPoolConfig {
         Duration::from_millis(self.slow_query_threshold_ms.load(Ordering::Relaxed))
     }
 
+    pub(crate) fn slow_query_explain_sample_rate(&self) -> f64 {
+        f64::from_bits(self.slow_query_explain_sample_rate.load(Ordering::Relaxed))
+    }
+
+    /// Returns the query timeout for connections tagged with the specified requester.
+    pub(crate) fn query_timeout(&self, requester: Option<&str>) -> Option<Duration> {
+        let component_timeout_ms = requester.and_then(|requester| {
+            let timeouts = self
+                .component_query_timeouts_ms
+                .read()
+                .expect("component query timeouts are poisoned");
+            timeouts.get(requester).copied()
+        });
+        let timeout_ms =
+            component_timeout_ms.unwrap_or_else(|| self.query_timeout_ms.load(Ordering::Relaxed));
+        (timeout_ms > 0).then(|| Duration::from_millis(timeout_ms))
+    }
+
+    pub(crate) fn query_max_retries(&self) -> u32 {
+        self.query_max_retries.load(Ordering::Relaxed)
+    }
+
+    /// Checks whether bulk inserts should use binary `COPY` statements; see [`Self::set_use_binary_copy()`].
+    pub fn use_binary_copy(&self) -> bool {
+        self.use_binary_copy.load(Ordering::Relaxed)
+    }
+
     /// Sets the threshold for the DB connection lifetime to denote a connection as long-living and log its details.
     pub fn set_long_connection_threshold(&self, threshold: Duration) -> anyhow::Result<&Self> {
         let millis = u64::try_from(threshold.as_millis())
@@ -243,14 +366,82 @@ impl GlobalConnectionPoolConfig {
         tracing::info!("Set slow query threshold to {threshold:?}");
         Ok(self)
     }
+
+    /// Sets the fraction of slow queries (as per [`Self::set_slow_query_threshold()`]) that are captured together
+    /// with their query plans in [`SlowQueryLog`](crate::slow_queries::SlowQueryLog). The rate must be
+    /// in the `0.0..=1.0` range; 0 disables capturing.
+    pub fn set_slow_query_explain_sample_rate(&self, rate: f64) -> anyhow::Result<&Self> {
+        anyhow::ensure!(
+            (0.0..=1.0).contains(&rate),
+            "slow_query_explain_sample_rate must be in 0.0..=1.0 range"
+        );
+        self.slow_query_explain_sample_rate
+            .store(rate.to_bits(), Ordering::Relaxed);
+        tracing::info!("Set slow query explain sample rate to {rate}");
+        Ok(self)
+    }
+
+    /// Sets the default timeout for DB queries. A query executing for longer than the timeout is cancelled
+    /// and fails with a Postgres error. Queries on connections with a timeout are also cancelled if the caller
+    /// drops the query future before completion. Zero timeout means no timeout, which is the default.
+    ///
+    /// Setting a timeout requires an additional roundtrip to the database before the first instrumented query
+    /// on each acquired connection.
+    pub fn set_query_timeout(&self, timeout: Duration) -> anyhow::Result<&Self> {
+        let millis =
+            u64::try_from(timeout.as_millis()).context("query_timeout is unreasonably large")?;
+        self.query_timeout_ms.store(millis, Ordering::Relaxed);
+        tracing::info!("Set query timeout to {timeout:?}");
+        Ok(self)
+    }
+
+    /// Overrides the query timeout (see [`Self::set_query_timeout()`]) for connections tagged with
+    /// the specified requester (i.e., acquired using [`ConnectionPool::connection_tagged()`] or
+    /// [`ConnectionPool::connection_with_preference()`]). Zero timeout disables timeouts for the component.
+    pub fn set_component_query_timeout(
+        &self,
+        component: &str,
+        timeout: Duration,
+    ) -> anyhow::Result<&Self> {
+        let millis = u64::try_from(timeout.as_millis())
+            .with_context(|| format!("query timeout for `{component}` is unreasonably large"))?;
+        self.component_query_timeouts_ms
+            .write()
+            .expect("component query timeouts are poisoned")
+            .insert(component.to_owned(), millis);
+        tracing::info!("Set query timeout for `{component}` to {timeout:?}");
+        Ok(self)
+    }
+
+    /// Sets the maximum number of retries for transactions failing with a serialization or deadlock error.
+    /// Only applies to transactions executed via [`Connection::transaction_with_retries()`]. The default value is 0,
+    /// i.e., transactions are not retried.
+    pub fn set_query_max_retries(&self, max_retries: u32) -> &Self {
+        self.query_max_retries.store(max_retries, Ordering::Relaxed);
+        tracing::info!("Set max retries for DB transactions to {max_retries}");
+        self
+    }
+
+    /// Sets whether bulk inserts in the hottest write paths should use binary `COPY` statements
+    /// (see [`BinaryCopyBuffer`](crate::binary_copy::BinaryCopyBuffer)) instead of text `COPY` or multi-row
+    /// `INSERT`s. Disabled by default.
+    pub fn set_use_binary_copy(&self, enabled: bool) -> &Self {
+        self.use_binary_copy.store(enabled, Ordering::Relaxed);
+        tracing::info!("Set using binary COPY for bulk inserts to {enabled}");
+        self
+    }
 }
 
 #[derive(Clone)]
 pub struct ConnectionPool<DB: DbMarker> {
     pub(crate) inner: PgPool,
     database_url: String,
+    name: Option<&'static str>,
     max_size: u32,
     pub(crate) traced_connections: Option<Arc<TracedConnections>>,
+    /// Routing of connections among read-only replicas; only set if the pool has replicas.
+    replica_routing: Option<Arc<ReplicaRouting>>,
+    default_read_preference: ReadPreference,
     _marker: PhantomData<DB>,
 }
 
@@ -260,7 +451,16 @@ impl<DB: DbMarker> fmt::Debug for ConnectionPool<DB> {
         // sensitive information (e.g. database password).
         formatter
             .debug_struct("ConnectionPool")
+            .field("name", &self.name)
             .field("max_size", &self.max_size)
+            .field(
+                "replica_count",
+                &self
+                    .replica_routing
+                    .as_ref()
+                    .map_or(0, |routing| routing.replica_count()),
+            )
+            .field("default_read_preference", &self.default_read_preference)
             .finish_non_exhaustive()
     }
 }
@@ -306,9 +506,13 @@ impl<DB: DbMarker> ConnectionPool<DB> {
     pub fn builder(database_url: &str, max_pool_size: u32) -> ConnectionPoolBuilder<DB> {
         ConnectionPoolBuilder {
             database_url: database_url.to_string(),
+            name: None,
             max_size: max_pool_size,
             acquire_timeout: Duration::from_secs(30), // Default value used by `sqlx`
             statement_timeout: None,
+            replica_urls: Vec::new(),
+            max_replica_lag: Duration::ZERO,
+            default_read_preference: ReadPreference::default(),
             _marker: Default::default(),
         }
     }
@@ -326,6 +530,21 @@ impl<DB: DbMarker> ConnectionPool<DB> {
         self.max_size
     }
 
+    /// Returns a monitor tracking replication lag of replicas for this pool, or `None` if the pool has no replicas.
+    pub fn replica_lag_monitor(&self, check_interval: Duration) -> Option<ReplicaLagMonitor> {
+        let routing = self.replica_routing.clone()?;
+        Some(ReplicaLagMonitor::new(routing, check_interval))
+    }
+
+    /// Creates a listener for Postgres notifications sent to the specified `channels`. The listener
+    /// uses a dedicated connection, which does not count towards the pool size.
+    pub async fn notification_listener(
+        &self,
+        channels: &[&str],
+    ) -> anyhow::Result<NotificationListener> {
+        NotificationListener::new(&self.database_url, channels).await
+    }
+
     /// Creates a `Connection` entity over a recoverable connection.
     /// Upon a database outage connection will block the thread until
     /// it will be able to recover the connection (or, if connection cannot
@@ -335,7 +554,8 @@ impl<DB: DbMarker> ConnectionPool<DB> {
     /// This method is intended to be used in crucial contexts, where the
     /// database access is must-have (e.g. block committer).
     pub async fn connection(&self) -> DalResult<Connection<'_, DB>> {
-        self.connection_inner(None).await
+        self.connection_inner(None, self.default_read_preference, &ReplicaPin::default())
+            .await
     }
 
     /// A version of `connection` that would also expose the duration of the connection
@@ -355,24 +575,84 @@ impl<DB: DbMarker> ConnectionPool<DB> {
                 requester,
                 location,
             };
-            self.connection_inner(Some(tags)).await
+            self.connection_inner(
+                Some(tags),
+                self.default_read_preference,
+                &ReplicaPin::default(),
+            )
+            .await
+        }
+    }
+
+    /// Same as [`Self::connection_tagged()`], but with the specified read preference overriding the default one
+    /// for the pool. For example, [`ReadPreference::ReadPreferred`] can be used to offload read-only queries
+    /// tolerating replication lag to replicas.
+    #[track_caller]
+    pub fn connection_with_preference(
+        &self,
+        requester: &'static str,
+        preference: ReadPreference,
+    ) -> impl Future<Output = DalResult<Connection<'_, DB>>> + '_ {
+        let location = Location::caller();
+        async move {
+            let tags = ConnectionTags {
+                requester,
+                location,
+            };
+            self.connection_inner(Some(tags), preference, &ReplicaPin::default())
+                .await
+        }
+    }
+
+    /// Same as [`Self::connection_with_preference()`] with [`ReadPreference::ReadPreferred`], but routes the connection
+    /// to the database selected by `pin`. All connections acquired with the same pin (e.g., for a single API request)
+    /// are routed to the same database, so they observe a consistent state.
+    #[track_caller]
+    pub fn connection_with_pin(
+        &self,
+        requester: &'static str,
+        pin: ReplicaPin,
+    ) -> impl Future<Output = DalResult<Connection<'_, DB>>> + '_ {
+        let location = Location::caller();
+        async move {
+            let tags = ConnectionTags {
+                requester,
+                location,
+            };
+            self.connection_inner(Some(tags), ReadPreference::ReadPreferred, &pin)
+                .await
         }
     }
 
     async fn connection_inner(
         &self,
         tags: Option<ConnectionTags>,
+        preference: ReadPreference,
+        pin: &ReplicaPin,
     ) -> DalResult<Connection<'_, DB>> {
+        if let Some(name) = self.name {
+            self.report_pool_usage(name);
+        }
         let acquire_latency = CONNECTION_METRICS.acquire.start();
-        let conn = self.acquire_connection_retried(tags.as_ref()).await?;
+        let (conn, pool) = self
+            .acquire_connection_retried(tags.as_ref(), preference, pin)
+            .await?;
         let elapsed = acquire_latency.observe();
         if let Some(tags) = &tags {
             CONNECTION_METRICS.acquire_tagged[&tags.requester].observe(elapsed);
         }
+        if let Some(name) = self.name {
+            POOL_METRICS.acquire[&name].observe(elapsed);
+        }
+
+        let query_timeout = Self::global_config().query_timeout(tags.map(|tags| tags.requester));
+        let canceller = query_timeout.map(|timeout| QueryCanceller::new(pool, timeout));
 
         Ok(Connection::<DB>::from_pool(
             conn,
             tags,
+            canceller,
+            pool.connect_options(),
             self.traced_connections.as_deref(),
         ))
     }
@@ -380,10 +660,23 @@ impl<DB: DbMarker> ConnectionPool<DB> {
     async fn acquire_connection_retried(
         &self,
         tags: Option<&ConnectionTags>,
-    ) -> DalResult<PoolConnection<Postgres>> {
+        preference: ReadPreference,
+        pin: &ReplicaPin,
+    ) -> DalResult<(PoolConnection<Postgres>, &PgPool)> {
         const DB_CONNECTION_RETRIES: usize = 3;
         const AVG_BACKOFF_INTERVAL: Duration = Duration::from_secs(1);
 
+        if let Some(routing) = &self.replica_routing {
+            match preference {
+                ReadPreference::ReadPreferred => {
+                    if let Some(acquired) = routing.try_acquire(tags, pin).await {
+                        return Ok(acquired);
+                    }
+                }
+                ReadPreference::PrimaryOnly => REPLICA_METRICS.primary_only.inc(),
+            }
+        }
+
         for _ in 0..DB_CONNECTION_RETRIES {
             CONNECTION_METRICS
                 .pool_size
@@ -392,7 +685,7 @@ impl<DB: DbMarker> ConnectionPool<DB> {
 
             let connection = self.inner.acquire().await;
             let connection_err = match connection {
-                Ok(connection) => return Ok(connection),
+                Ok(connection) => return Ok((connection, &self.inner)),
                 Err(err) => err,
             };
 
@@ -408,10 +701,20 @@ impl<DB: DbMarker> ConnectionPool<DB> {
         }
 
         // Attempting to get the pooled connection for the last time
-        self.inner.acquire().await.map_err(|err| {
+        let connection = self.inner.acquire().await.map_err(|err| {
             Self::report_connection_error(&err);
-            DalConnectionError::acquire_connection(err, tags.cloned()).into()
-        })
+            DalConnectionError::acquire_connection(err, tags.cloned())
+        })?;
+        Ok((connection, &self.inner))
+    }
+
+    fn report_pool_usage(&self, name: &'static str) {
+        let idle_count = self.inner.num_idle();
+        let in_use_count = (self.inner.size() as usize).saturating_sub(idle_count);
+        POOL_METRICS.in_use[&name].set(in_use_count);
+        if idle_count == 0 && in_use_count >= self.max_size as usize {
+            POOL_METRICS.starved_acquires[&name].inc();
+        }
     }
 
     fn report_connection_error(err: &sqlx::Error) {
@@ -426,6 +729,184 @@ mod tests {
     use super::*;
     use crate::utils::InternalMarker;
 
+    async fn current_database(pool: &ConnectionPool<InternalMarker>) -> String {
+        let mut storage = pool.connection().await.unwrap();
+        sqlx::query_scalar("SELECT current_database()")
+            .fetch_one(storage.conn())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn routing_connections_to_replicas() {
+        let main_builder = TestTemplate::empty()
+            .unwrap()
+            .create_db::<InternalMarker>(1)
+            .await
+            .unwrap();
+        let replica_builder = TestTemplate::empty()
+            .unwrap()
+            .create_db::<InternalMarker>(1)
+            .await
+            .unwrap();
+        let main_pool = main_builder.build().await.unwrap();
+        let replica_pool = replica_builder.build().await.unwrap();
+        let main_db = current_database(&main_pool).await;
+        let replica_db = current_database(&replica_pool).await;
+        assert_ne!(main_db, replica_db);
+
+        let pool = ConnectionPool::<InternalMarker>::singleton(&main_builder.database_url)
+            .set_replicas(
+                vec![replica_builder.database_url.clone()],
+                Duration::from_secs(1),
+            )
+            .set_default_read_preference(ReadPreference::ReadPreferred)
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(current_database(&pool).await, replica_db);
+
+        // The test replica is not a real replica, so it should be treated as having no lag.
+        let routing = pool.replica_routing.clone().unwrap();
+        routing.check_lags().await;
+        assert_eq!(current_database(&pool).await, replica_db);
+    }
+
+    #[tokio::test]
+    async fn acquiring_connections_with_read_preference() {
+        let main_builder = TestTemplate::empty()
+            .unwrap()
+            .create_db::<InternalMarker>(1)
+            .await
+            .unwrap();
+        let replica_builder = TestTemplate::empty()
+            .unwrap()
+            .create_db::<InternalMarker>(1)
+            .await
+            .unwrap();
+        let main_db = current_database(&main_builder.build().await.unwrap()).await;
+        let replica_db = current_database(&replica_builder.build().await.unwrap()).await;
+
+        let pool = ConnectionPool::<InternalMarker>::singleton(&main_builder.database_url)
+            .set_replicas(
+                vec![replica_builder.database_url.clone()],
+                Duration::from_secs(1),
+            )
+            .build()
+            .await
+            .unwrap();
+        // Connections are acquired from the main database by default.
+        assert_eq!(current_database(&pool).await, main_db);
+
+        let mut storage = pool
+            .connection_with_preference("test", ReadPreference::ReadPreferred)
+            .await
+            .unwrap();
+        let db: String = sqlx::query_scalar("SELECT current_database()")
+            .fetch_one(storage.conn())
+            .await
+            .unwrap();
+        assert_eq!(db, replica_db);
+        drop(storage);
+
+        let mut storage = pool
+            .connection_with_preference("test", ReadPreference::PrimaryOnly)
+            .await
+            .unwrap();
+        let db: String = sqlx::query_scalar("SELECT current_database()")
+            .fetch_one(storage.conn())
+            .await
+            .unwrap();
+        assert_eq!(db, main_db);
+    }
+
+    #[tokio::test]
+    async fn pinning_replica_for_connections() {
+        let main_builder = TestTemplate::empty()
+            .unwrap()
+            .create_db::<InternalMarker>(1)
+            .await
+            .unwrap();
+        let mut replica_urls = vec![];
+        let mut replica_dbs = vec![];
+        for _ in 0..2 {
+            let replica_builder = TestTemplate::empty()
+                .unwrap()
+                .create_db::<InternalMarker>(1)
+                .await
+                .unwrap();
+            replica_dbs.push(current_database(&replica_builder.build().await.unwrap()).await);
+            replica_urls.push(replica_builder.database_url);
+        }
+
+        let pool = ConnectionPool::<InternalMarker>::singleton(&main_builder.database_url)
+            .set_replicas(replica_urls, Duration::from_secs(1))
+            .build()
+            .await
+            .unwrap();
+        // Without a pin, connections are distributed among replicas.
+        let mut dbs = vec![];
+        for _ in 0..2 {
+            let mut storage = pool
+                .connection_with_preference("test", ReadPreference::ReadPreferred)
+                .await
+                .unwrap();
+            let db: String = sqlx::query_scalar("SELECT current_database()")
+                .fetch_one(storage.conn())
+                .await
+                .unwrap();
+            dbs.push(db);
+        }
+        dbs.sort_unstable();
+        replica_dbs.sort_unstable();
+        assert_eq!(dbs, replica_dbs);
+
+        let pin = ReplicaPin::default();
+        let mut pinned_dbs = vec![];
+        for _ in 0..3 {
+            let mut storage = pool.connection_with_pin("test", pin.clone()).await.unwrap();
+            let db: String = sqlx::query_scalar("SELECT current_database()")
+                .fetch_one(storage.conn())
+                .await
+                .unwrap();
+            pinned_dbs.push(db);
+        }
+        assert!(replica_dbs.contains(&pinned_dbs[0]), "{pinned_dbs:?}");
+        assert!(
+            pinned_dbs.iter().all(|db| *db == pinned_dbs[0]),
+            "{pinned_dbs:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn falling_back_to_main_database_if_replica_is_unavailable() {
+        let main_builder = TestTemplate::empty()
+            .unwrap()
+            .create_db::<InternalMarker>(1)
+            .await
+            .unwrap();
+        let main_pool = main_builder.build().await.unwrap();
+        let main_db = current_database(&main_pool).await;
+        drop(main_pool);
+
+        let mut replica_url = url::Url::parse(&main_builder.database_url).unwrap();
+        replica_url.set_path("/missing_replica_database");
+        let pool = ConnectionPool::<InternalMarker>::singleton(&main_builder.database_url)
+            .set_replicas(vec![replica_url.to_string()], Duration::from_secs(1))
+            .set_default_read_preference(ReadPreference::ReadPreferred)
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(current_database(&pool).await, main_db);
+        // The replica should be excluded from routing after the failure.
+        assert_eq!(current_database(&pool).await, main_db);
+        let routing = pool.replica_routing.clone().unwrap();
+        assert!(routing
+            .try_acquire(None, &ReplicaPin::default())
+            .await
+            .is_none());
+    }
+
     #[tokio::test]
     async fn setting_statement_timeout() {
         let db_url = TestTemplate::empty()
@@ -452,4 +933,31 @@ mod tests {
             sqlx::Error::Database(db_err) if db_err.message().contains("statement timeout")
         );
     }
+
+    #[tokio::test]
+    async fn reporting_pool_starvation() {
+        const POOL_NAME: &str = "test_starvation";
+
+        let pool = TestTemplate::empty()
+            .unwrap()
+            .create_db::<InternalMarker>(1)
+            .await
+            .unwrap()
+            .set_name(POOL_NAME)
+            .build()
+            .await
+            .unwrap();
+        let storage = pool.connection().await.unwrap();
+        let starved_acquires = &POOL_METRICS.starved_acquires[&POOL_NAME];
+        let initial_starved_acquires = starved_acquires.get();
+
+        tokio::time::timeout(Duration::from_millis(100), pool.connection())
+            .await
+            .unwrap_err();
+        assert_eq!(starved_acquires.get(), initial_starved_acquires + 1);
+        assert_eq!(POOL_METRICS.in_use[&POOL_NAME].get(), 1);
+
+        drop(storage);
+        pool.connection().await.unwrap();
+    }
 }
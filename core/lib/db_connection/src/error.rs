@@ -22,6 +22,24 @@ impl DalError {
         }
     }
 
+    /// Checks whether this error is caused by a serialization failure or a deadlock. Transactions failing
+    /// with such errors can be safely retried, e.g. using [`Connection::transaction_with_retries()`].
+    ///
+    /// [`Connection::transaction_with_retries()`]: crate::connection::Connection::transaction_with_retries()
+    pub fn is_retriable(&self) -> bool {
+        const SERIALIZATION_FAILURE: &str = "40001";
+        const DEADLOCK_DETECTED: &str = "40P01";
+
+        if let sqlx::Error::Database(err) = self.inner() {
+            matches!(
+                err.code().as_deref(),
+                Some(SERIALIZATION_FAILURE | DEADLOCK_DETECTED)
+            )
+        } else {
+            false
+        }
+    }
+
     /// Wraps this error into an `anyhow` wrapper.
     pub fn generalize(self) -> anyhow::Error {
         anyhow::Error::from(self).context("Postgres error")
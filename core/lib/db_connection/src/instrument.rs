@@ -5,6 +5,11 @@
 //! - Report query latency as a metric
 //! - Report slow and failing queries as metrics
 //! - Log slow and failing queries together with their arguments, which makes it easier to debug.
+//! - Wrap queries in `DEBUG`-level `dal_query` tracing spans, so that DB time can be attributed to the calling code.
+//! - Capture a sample of slow queries together with their query plans in a
+//!   [`SlowQueryLog`](crate::slow_queries::SlowQueryLog).
+//! - Cancel queries exceeding the query timeout configured for the connection, and close connections with queries
+//!   dropped before completion.
 //!
 //! The entry point for instrumentation is the [`InstrumentExt`] trait. After it is imported into the scope,
 //! its `instrument()` method can be placed on the output of `query*` functions or macros. You can then call
@@ -13,18 +18,26 @@
 
 use std::{fmt, future::Future, panic::Location};
 
+use futures::{
+    stream::{self, BoxStream},
+    StreamExt,
+};
+use rand::Rng;
 use sqlx::{
     postgres::{PgCopyIn, PgQueryResult, PgRow},
     query::{Map, Query, QueryAs},
-    FromRow, IntoArguments, PgConnection, Postgres,
+    Execute, FromRow, IntoArguments, PgConnection, Postgres,
 };
 use tokio::time::Instant;
+use tracing::Instrument;
 
 use crate::{
+    cancellation::QueryCanceller,
     connection::{Connection, ConnectionTags, DbMarker},
     connection_pool::ConnectionPool,
     error::{DalError, DalRequestError, DalResult},
     metrics::REQUEST_METRICS,
+    slow_queries::SampledSlowQuery,
     utils::InternalMarker,
 };
 
@@ -190,8 +203,9 @@ impl<'a> InstrumentedData<'a> {
     async fn fetch<R>(
         self,
         connection_tags: Option<&ConnectionTags>,
+        canceller: Option<&QueryCanceller>,
         query_future: impl Future<Output = Result<R, sqlx::Error>>,
-    ) -> DalResult<R> {
+    ) -> FetchedQuery<R> {
         let Self {
             name,
             location,
@@ -200,14 +214,21 @@ impl<'a> InstrumentedData<'a> {
             slow_query_reporting_enabled,
         } = self;
         let started_at = Instant::now();
+        let query_future = query_future.instrument(tracing::debug_span!("dal_query", query = name));
+        let query_future = async {
+            match canceller {
+                Some(canceller) => canceller.run(query_future).await,
+                None => (query_future.await, false),
+            }
+        };
         tokio::pin!(query_future);
 
-        let slow_query_threshold =
-            ConnectionPool::<InternalMarker>::global_config().slow_query_threshold();
+        let global_config = ConnectionPool::<InternalMarker>::global_config();
+        let slow_query_threshold = global_config.slow_query_threshold();
         let mut is_slow = false;
         let output =
             tokio::time::timeout_at(started_at + slow_query_threshold, &mut query_future).await;
-        let output = match output {
+        let (output, timed_out) = match output {
             Ok(output) => output,
             Err(_) => {
                 let connection_tags = ConnectionTags::display(connection_tags);
@@ -230,6 +251,19 @@ impl<'a> InstrumentedData<'a> {
         }
 
         let connection_tags_display = ConnectionTags::display(connection_tags);
+        if timed_out {
+            // `canceller` is always set if the query has timed out
+            let timeout = canceller.map(QueryCanceller::timeout).unwrap_or_default();
+            tracing::warn!(
+                "Query {name}{args} called at {file}:{line} [{connection_tags_display}] has exceeded timeout {timeout:?} \
+                 and was cancelled",
+                file = location.file(),
+                line = location.line()
+            );
+            REQUEST_METRICS.request_timeout[&name].inc();
+        }
+
+        let mut sampled_slow_query = None;
         if let Err(err) = &output {
             tracing::warn!(
                 "Query {name}{args} called at {file}:{line} [{connection_tags_display}] has resulted in error: {err}",
@@ -243,14 +277,135 @@ impl<'a> InstrumentedData<'a> {
                 file = location.file(),
                 line = location.line()
             );
+            let sample_rate = global_config.slow_query_explain_sample_rate();
+            if sample_rate > 0.0 && rand::thread_rng().gen_bool(sample_rate) {
+                sampled_slow_query = Some(SampledSlowQuery {
+                    name,
+                    location,
+                    args: args.to_owned(),
+                    elapsed,
+                });
+            }
         }
 
-        output.map_err(|err| {
+        let output = output.map_err(|err| {
             DalRequestError::new(err, name, location)
                 .with_args(args.to_owned())
                 .with_connection_tags(connection_tags.cloned())
                 .into()
-        })
+        });
+        FetchedQuery {
+            output,
+            sampled_slow_query,
+        }
+    }
+}
+
+/// Output of a query executed via [`InstrumentedData::fetch()`].
+#[must_use = "Query output should be obtained using `finish()`"]
+struct FetchedQuery<R> {
+    output: DalResult<R>,
+    sampled_slow_query: Option<SampledSlowQuery>,
+}
+
+impl<R> FetchedQuery<R> {
+    /// Captures the query in the background if it was slow and sampled for capturing, and returns the query output.
+    fn finish<DB: DbMarker>(self, storage: &Connection<'_, DB>, sql: &str) -> DalResult<R> {
+        if let Some(slow_query) = self.sampled_slow_query {
+            slow_query.capture(storage.connect_options().clone(), sql);
+        }
+        self.output
+    }
+}
+
+/// Instrumentation data for a query fetched as a stream. Unlike [`InstrumentedData`], owns all its data,
+/// so that it can be moved into the stream.
+#[derive(Debug)]
+struct StreamedQueryData {
+    name: &'static str,
+    location: &'static Location<'static>,
+    args: Vec<(&'static str, String)>,
+    connection_tags: Option<ConnectionTags>,
+    report_latency: bool,
+    slow_query_reporting_enabled: bool,
+    started_at: Instant,
+    has_failed: bool,
+}
+
+impl StreamedQueryData {
+    fn new(data: InstrumentedData<'_>, connection_tags: Option<&ConnectionTags>) -> Self {
+        Self {
+            name: data.name,
+            location: data.location,
+            args: data.args.to_owned(),
+            connection_tags: connection_tags.copied(),
+            report_latency: data.report_latency,
+            slow_query_reporting_enabled: data.slow_query_reporting_enabled,
+            started_at: Instant::now(),
+            has_failed: false,
+        }
+    }
+
+    fn args(&self) -> impl fmt::Display + '_ {
+        struct Args<'a>(&'a [(&'static str, String)]);
+
+        impl fmt::Display for Args<'_> {
+            fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                if self.0.is_empty() {
+                    return Ok(());
+                }
+                formatter.write_str("(")?;
+                for (i, (name, value)) in self.0.iter().enumerate() {
+                    write!(formatter, "{name}={value}")?;
+                    if i + 1 < self.0.len() {
+                        formatter.write_str(", ")?;
+                    }
+                }
+                formatter.write_str(")")
+            }
+        }
+
+        Args(&self.args)
+    }
+
+    fn handle_error(&mut self, err: sqlx::Error) -> DalError {
+        self.has_failed = true;
+        let connection_tags = ConnectionTags::display(self.connection_tags.as_ref());
+        tracing::warn!(
+            "Streamed query {name}{args} called at {file}:{line} [{connection_tags}] has resulted in error: {err}",
+            name = self.name,
+            args = self.args(),
+            file = self.location.file(),
+            line = self.location.line()
+        );
+        REQUEST_METRICS.request_error[&self.name].inc();
+        DalRequestError::new(err, self.name, self.location)
+            .with_args(self.args.clone())
+            .with_connection_tags(self.connection_tags)
+            .into()
+    }
+
+    fn handle_completion(&self) {
+        if self.has_failed {
+            return;
+        }
+        let elapsed = self.started_at.elapsed();
+        if self.report_latency {
+            REQUEST_METRICS.request[&self.name].observe(elapsed);
+        }
+        let slow_query_threshold =
+            ConnectionPool::<InternalMarker>::global_config().slow_query_threshold();
+        if self.slow_query_reporting_enabled && elapsed > slow_query_threshold {
+            let connection_tags = ConnectionTags::display(self.connection_tags.as_ref());
+            tracing::info!(
+                "Slow streamed query {name}{args} called at {file}:{line} [{connection_tags}] has finished after {elapsed:?}",
+                name = self.name,
+                args = self.args(),
+                file = self.location.file(),
+                line = self.location.line()
+            );
+            REQUEST_METRICS.request_slow[&self.name].inc();
+        }
     }
 }
 
@@ -335,8 +490,13 @@ where
         self,
         storage: &mut Connection<'_, DB>,
     ) -> DalResult<PgQueryResult> {
-        let (conn, tags) = storage.conn_and_tags();
-        self.data.fetch(tags, self.query.execute(conn)).await
+        let sql = self.query.sql();
+        let (conn, tags, canceller) = storage.parts_for_query().await;
+        let fetched = self
+            .data
+            .fetch(tags, canceller, self.query.execute(conn))
+            .await;
+        fetched.finish(storage, sql)
     }
 
     /// Fetches an optional row using this query.
@@ -344,8 +504,13 @@ where
         self,
         storage: &mut Connection<'_, DB>,
     ) -> DalResult<Option<PgRow>> {
-        let (conn, tags) = storage.conn_and_tags();
-        self.data.fetch(tags, self.query.fetch_optional(conn)).await
+        let sql = self.query.sql();
+        let (conn, tags, canceller) = storage.parts_for_query().await;
+        let fetched = self
+            .data
+            .fetch(tags, canceller, self.query.fetch_optional(conn))
+            .await;
+        fetched.finish(storage, sql)
     }
 }
 
@@ -359,8 +524,13 @@ where
         self,
         storage: &mut Connection<'_, DB>,
     ) -> DalResult<Vec<O>> {
-        let (conn, tags) = storage.conn_and_tags();
-        self.data.fetch(tags, self.query.fetch_all(conn)).await
+        let sql = self.query.sql();
+        let (conn, tags, canceller) = storage.parts_for_query().await;
+        let fetched = self
+            .data
+            .fetch(tags, canceller, self.query.fetch_all(conn))
+            .await;
+        fetched.finish(storage, sql)
     }
 }
 
@@ -375,14 +545,24 @@ where
         self,
         storage: &mut Connection<'_, DB>,
     ) -> DalResult<Option<O>> {
-        let (conn, tags) = storage.conn_and_tags();
-        self.data.fetch(tags, self.query.fetch_optional(conn)).await
+        let sql = self.query.sql();
+        let (conn, tags, canceller) = storage.parts_for_query().await;
+        let fetched = self
+            .data
+            .fetch(tags, canceller, self.query.fetch_optional(conn))
+            .await;
+        fetched.finish(storage, sql)
     }
 
     /// Fetches a single row using this query.
     pub async fn fetch_one<DB: DbMarker>(self, storage: &mut Connection<'_, DB>) -> DalResult<O> {
-        let (conn, tags) = storage.conn_and_tags();
-        self.data.fetch(tags, self.query.fetch_one(conn)).await
+        let sql = self.query.sql();
+        let (conn, tags, canceller) = storage.parts_for_query().await;
+        let fetched = self
+            .data
+            .fetch(tags, canceller, self.query.fetch_one(conn))
+            .await;
+        fetched.finish(storage, sql)
     }
 
     /// Fetches all rows using this query and collects them into a `Vec`.
@@ -390,8 +570,47 @@ where
         self,
         storage: &mut Connection<'_, DB>,
     ) -> DalResult<Vec<O>> {
+        let sql = self.query.sql();
+        let (conn, tags, canceller) = storage.parts_for_query().await;
+        let fetched = self
+            .data
+            .fetch(tags, canceller, self.query.fetch_all(conn))
+            .await;
+        fetched.finish(storage, sql)
+    }
+
+    /// Fetches rows using this query as a stream. Unlike [`Self::fetch_all()`], rows are not collected in memory;
+    /// they are fetched from the database lazily as the stream is polled.
+    ///
+    /// Instrumentation is applied to the stream as a whole: latency is measured until the stream is exhausted,
+    /// and slow queries are reported once they complete (rather than while they are executing). If the stream
+    /// is dropped before it is exhausted, neither latency nor slowness is reported.
+    pub fn fetch<'c, DB: DbMarker>(
+        self,
+        storage: &'c mut Connection<'_, DB>,
+    ) -> BoxStream<'c, DalResult<O>>
+    where
+        'q: 'c,
+        F: 'c,
+        O: 'c,
+    {
         let (conn, tags) = storage.conn_and_tags();
-        self.data.fetch(tags, self.query.fetch_all(conn)).await
+        let data = StreamedQueryData::new(self.data, tags);
+        let rows = self.query.fetch(conn);
+        stream::unfold((rows, data), |(mut rows, mut data)| async move {
+            match rows.next().await {
+                None => {
+                    data.handle_completion();
+                    None
+                }
+                Some(Ok(row)) => Some((Ok(row), (rows, data))),
+                Some(Err(err)) => {
+                    let err = data.handle_error(err);
+                    Some((Err(err), (rows, data)))
+                }
+            }
+        })
+        .boxed()
     }
 }
 
@@ -420,10 +639,15 @@ impl<'a> Instrumented<'a, CopyStatement> {
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
+    use futures::TryStreamExt;
     use zksync_basic_types::{MiniblockNumber, H256};
 
     use super::*;
-    use crate::{connection_pool::ConnectionPool, utils::InternalMarker};
+    use crate::{
+        connection_pool::ConnectionPool, slow_queries::SlowQueryLog, utils::InternalMarker,
+    };
 
     #[tokio::test]
     async fn instrumenting_erroneous_query() {
@@ -456,4 +680,98 @@ mod tests {
             .await
             .unwrap();
     }
+
+    #[tokio::test]
+    async fn capturing_slow_query() {
+        let pool = ConnectionPool::<InternalMarker>::test_pool().await;
+        ConnectionPool::<InternalMarker>::global_config()
+            .set_slow_query_explain_sample_rate(1.0)
+            .unwrap();
+
+        let mut conn = pool.connection().await.unwrap();
+        sqlx::query("SELECT pg_sleep(0.5)")
+            .map(drop)
+            .instrument("captured_slow")
+            .with_arg("miniblock", &MiniblockNumber(1))
+            .fetch_optional(&mut conn)
+            .await
+            .unwrap();
+
+        // The query is captured in a background task.
+        let query = tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                let queries = SlowQueryLog::global().recent();
+                if let Some(query) = queries
+                    .into_iter()
+                    .find(|query| query.name == "captured_slow")
+                {
+                    break query;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("slow query was not captured");
+        assert_eq!(query.sql, "SELECT pg_sleep(0.5)");
+        assert_eq!(query.args, [("miniblock", "MiniblockNumber(1)".to_owned())]);
+        assert!(query.elapsed >= Duration::from_millis(500), "{query:?}");
+        let query_plan = query.query_plan.as_ref().unwrap();
+        assert!(query_plan.contains("Result"), "{query_plan}");
+    }
+
+    #[tokio::test]
+    async fn cancelling_query_exceeding_component_timeout() {
+        let pool = ConnectionPool::<InternalMarker>::test_pool().await;
+        ConnectionPool::<InternalMarker>::global_config()
+            .set_component_query_timeout("timeout_test", Duration::from_millis(100))
+            .unwrap();
+
+        let mut conn = pool.connection_tagged("timeout_test").await.unwrap();
+        let err = sqlx::query("SELECT pg_sleep(10)")
+            .instrument("timed_out")
+            .execute(&mut conn)
+            .await
+            .unwrap_err();
+        let sqlx::Error::Database(db_err) = err.inner() else {
+            panic!("unexpected error: {err:?}");
+        };
+        assert_eq!(db_err.code().as_deref(), Some("57014"));
+        // The connection should remain usable after cancellation.
+        sqlx::query("SELECT 1")
+            .instrument("after_timeout")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        // Connections for other components should not have a timeout.
+        let mut conn = pool.connection_tagged("test").await.unwrap();
+        sqlx::query("SELECT pg_sleep(0.3)")
+            .instrument("not_timed_out")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn instrumenting_streamed_query() {
+        let pool = ConnectionPool::<InternalMarker>::test_pool().await;
+        let mut conn = pool.connection().await.unwrap();
+        let numbers: Vec<i32> = sqlx::query("SELECT generate_series(1, 5) AS number")
+            .map(|row: PgRow| sqlx::Row::get(&row, "number"))
+            .instrument("streamed")
+            .report_latency()
+            .fetch(&mut conn)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(numbers, [1, 2, 3, 4, 5]);
+
+        let mut stream = sqlx::query("WHAT")
+            .map(drop)
+            .instrument("erroneous_streamed")
+            .with_arg("miniblock", &MiniblockNumber(1))
+            .fetch(&mut conn);
+        let err = stream.next().await.unwrap().unwrap_err();
+        assert!(err.to_string().contains("erroneous_streamed"), "{err}");
+    }
 }
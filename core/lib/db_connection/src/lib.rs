@@ -1,11 +1,16 @@
 //! Common utils for data access layer (DAL) implementations.
 
+pub mod binary_copy;
+mod cancellation;
 pub mod connection;
 pub mod connection_pool;
 pub mod error;
 pub mod healthcheck;
 pub mod instrument;
 pub mod metrics;
+pub mod notifications;
+pub mod replicas;
+pub mod slow_queries;
 #[macro_use]
 pub mod macro_utils;
 pub mod utils;
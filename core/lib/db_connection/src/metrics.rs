@@ -1,7 +1,7 @@
 use std::{thread, time::Duration};
 
 use vise::{
-    Buckets, Counter, EncodeLabelSet, EncodeLabelValue, Family, Histogram, LabeledFamily,
+    Buckets, Counter, EncodeLabelSet, EncodeLabelValue, Family, Gauge, Histogram, LabeledFamily,
     LatencyObserver, Metrics, Unit,
 };
 
@@ -18,6 +18,13 @@ pub(crate) struct RequestMetrics {
     /// Counter of errored DB requests.
     #[metrics(labels = ["method"])]
     pub request_error: LabeledFamily<&'static str, Counter>,
+    /// Counter of DB requests cancelled because of exceeding the query timeout.
+    #[metrics(labels = ["method"])]
+    pub request_timeout: LabeledFamily<&'static str, Counter>,
+    /// Number of query cancellations (both because of timeouts and because of queries dropped by the caller).
+    pub cancellations: Counter,
+    /// Number of retried DB transactions that have failed with a serialization or deadlock error.
+    pub transaction_retries: Counter,
 }
 
 #[vise::register]
@@ -94,3 +101,58 @@ pub(crate) struct ConnectionMetrics {
 
 #[vise::register]
 pub(crate) static CONNECTION_METRICS: vise::Global<ConnectionMetrics> = vise::Global::new();
+
+/// Metrics for named connection pools, e.g. pools dedicated to a certain node subsystem.
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "sql_pool")]
+pub(crate) struct PoolMetrics {
+    /// Maximum size of the pool.
+    #[metrics(labels = ["pool"])]
+    pub max_size: LabeledFamily<&'static str, Gauge<u64>>,
+    /// Number of connections in use as of the last connection acquisition.
+    #[metrics(labels = ["pool"])]
+    pub in_use: LabeledFamily<&'static str, Gauge<usize>>,
+    /// Latency of acquiring a connection from the pool.
+    #[metrics(buckets = Buckets::LATENCIES, labels = ["pool"])]
+    pub acquire: LabeledFamily<&'static str, Histogram<Duration>>,
+    /// Number of connection acquisitions started when the pool was exhausted (i.e., all connections
+    /// were in use), so that the requester had to wait for a connection to be released.
+    #[metrics(labels = ["pool"])]
+    pub starved_acquires: LabeledFamily<&'static str, Counter>,
+}
+
+#[vise::register]
+pub(crate) static POOL_METRICS: vise::Global<PoolMetrics> = vise::Global::new();
+
+/// Reason of acquiring a connection from the main database for a pool with read-only replicas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "reason", rename_all = "snake_case")]
+pub(crate) enum ReplicaFallbackReason {
+    /// All replicas lag too much or are unavailable.
+    NoEligibleReplicas,
+    /// Acquiring a connection to the selected replica has failed.
+    AcquireError,
+}
+
+/// Metrics for connection pools with read-only replicas.
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "sql_replica")]
+pub(crate) struct ReplicaMetrics {
+    /// Number of connections acquired from replicas.
+    pub acquired: Counter,
+    /// Number of connections acquired from the main database instead of a replica.
+    pub fallbacks: Family<ReplicaFallbackReason, Counter>,
+    /// Number of connections acquired from the main database because of the [`ReadPreference::PrimaryOnly`]
+    /// preference.
+    ///
+    /// [`ReadPreference::PrimaryOnly`]: crate::replicas::ReadPreference::PrimaryOnly
+    pub primary_only: Counter,
+    /// Replication lag of replicas measured during periodic checks.
+    #[metrics(buckets = Buckets::LATENCIES, unit = Unit::Seconds)]
+    pub lag: Histogram<Duration>,
+    /// Number of replicas eligible for routing as of the last lag check.
+    pub eligible_replicas: Gauge<usize>,
+}
+
+#[vise::register]
+pub(crate) static REPLICA_METRICS: vise::Global<ReplicaMetrics> = vise::Global::new();
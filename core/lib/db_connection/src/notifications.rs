@@ -0,0 +1,52 @@
+//! Support of Postgres asynchronous notifications (`LISTEN` / `NOTIFY`).
+
+use std::fmt;
+
+use anyhow::Context as _;
+use sqlx::postgres::PgListener;
+
+/// Listener of Postgres notifications sent to one or more channels.
+///
+/// The listener uses a dedicated DB connection that is not a part of the [`ConnectionPool`](crate::connection_pool::ConnectionPool)
+/// it was created from. If this connection is lost, the listener transparently re-establishes it; notifications
+/// sent in the meantime are lost. Thus, notifications should only be used as a hint, and the code relying on them
+/// should have a fallback (e.g., infrequent polling).
+pub struct NotificationListener {
+    inner: PgListener,
+    channels: Vec<String>,
+}
+
+impl fmt::Debug for NotificationListener {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("NotificationListener")
+            .field("channels", &self.channels)
+            .finish_non_exhaustive()
+    }
+}
+
+impl NotificationListener {
+    pub(crate) async fn new(database_url: &str, channels: &[&str]) -> anyhow::Result<Self> {
+        let mut inner = PgListener::connect(database_url)
+            .await
+            .context("failed connecting notification listener to database")?;
+        inner
+            .listen_all(channels.iter().copied())
+            .await
+            .with_context(|| format!("failed listening to channels {channels:?}"))?;
+        Ok(Self {
+            inner,
+            channels: channels.iter().map(|&channel| channel.to_owned()).collect(),
+        })
+    }
+
+    /// Waits for the next notification and returns the channel it was sent to.
+    pub async fn recv(&mut self) -> anyhow::Result<String> {
+        let notification = self
+            .inner
+            .recv()
+            .await
+            .context("failed receiving notification")?;
+        Ok(notification.channel().to_owned())
+    }
+}
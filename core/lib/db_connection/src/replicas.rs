@@ -0,0 +1,249 @@
+//! Routing of connections among read-only Postgres replicas.
+
+use std::{
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use sqlx::{
+    pool::PoolConnection,
+    postgres::{PgPool, Postgres},
+};
+use tokio::sync::watch;
+
+use crate::{
+    connection::ConnectionTags,
+    metrics::{ReplicaFallbackReason, CONNECTION_METRICS, REPLICA_METRICS},
+};
+
+/// Preference of the database to acquire a connection to, for connection pools with read-only replicas.
+/// For pools without replicas, all connections are acquired to the main database regardless of the preference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReadPreference {
+    /// Acquire a connection to an eligible replica (i.e., one not lagging behind the main database too much),
+    /// falling back to the main database if there are no such replicas. Such connections must only be used
+    /// for read-only queries that tolerate replication lag.
+    ReadPreferred,
+    /// Always acquire a connection to the main database. Should be used for connections performing writes,
+    /// or reads that must observe the latest state of the main database. This is the default preference,
+    /// so that configuring replicas doesn't change consistency guarantees for existing callers.
+    #[default]
+    PrimaryOnly,
+}
+
+/// Database selected for [`ReadPreference::ReadPreferred`] connections acquired with the same pin. Used to route
+/// all connections for a logical operation (e.g., handling an API request) to the same database. Otherwise, each
+/// connection is routed separately, so that an operation could read inconsistent data from replicas with different
+/// replication lag and from the main database.
+///
+/// The database is selected when the first connection is acquired with the pin. If acquiring a connection
+/// to the selected replica fails, the pin is switched to the main database, since it is never behind the replica.
+#[derive(Debug, Clone)]
+pub struct ReplicaPin(Arc<AtomicUsize>);
+
+impl Default for ReplicaPin {
+    fn default() -> Self {
+        Self(Arc::new(AtomicUsize::new(Self::UNPINNED)))
+    }
+}
+
+impl ReplicaPin {
+    const UNPINNED: usize = usize::MAX;
+    const MAIN_DATABASE: usize = usize::MAX - 1;
+
+    /// Pins the specified database unless the pin is already set. Returns the pinned value.
+    fn pin(&self, value: usize) -> usize {
+        match self
+            .0
+            .compare_exchange(Self::UNPINNED, value, Ordering::Relaxed, Ordering::Relaxed)
+        {
+            Ok(_) => value,
+            Err(pinned) => pinned,
+        }
+    }
+
+    fn pin_main_database(&self) {
+        self.0.store(Self::MAIN_DATABASE, Ordering::Relaxed);
+    }
+}
+
+/// Replication lag value signaling that the replica is unavailable.
+const UNAVAILABLE_LAG_MS: u64 = u64::MAX;
+
+/// Returns the replication lag of the database, or `NULL` if the database is not a replica. If the replica
+/// has replayed all received WAL, its lag is considered to be zero (otherwise, the lag would grow indefinitely
+/// if there are no writes to the main database).
+const REPLICATION_LAG_QUERY: &str = "\
+    SELECT CASE \
+        WHEN pg_last_wal_receive_lsn() = pg_last_wal_replay_lsn() THEN 0 \
+        ELSE EXTRACT(EPOCH FROM NOW() - pg_last_xact_replay_timestamp()) \
+    END::DOUBLE PRECISION";
+
+#[derive(Debug)]
+struct Replica {
+    /// 0-based index of the replica used in logs.
+    index: usize,
+    pool: PgPool,
+    /// Replication lag in milliseconds as of the last check.
+    lag_ms: AtomicU64,
+}
+
+impl Replica {
+    fn is_eligible(&self, max_lag_ms: u64) -> bool {
+        let lag_ms = self.lag_ms.load(Ordering::Relaxed);
+        lag_ms != UNAVAILABLE_LAG_MS && lag_ms <= max_lag_ms
+    }
+
+    async fn check_lag(&self) {
+        let lag = sqlx::query_scalar::<_, Option<f64>>(REPLICATION_LAG_QUERY)
+            .fetch_one(&self.pool)
+            .await;
+        let lag_ms = match lag {
+            Ok(lag) => {
+                // `None` means that the database is not a replica; we treat it as having no lag.
+                let lag = Duration::from_secs_f64(lag.unwrap_or(0.0).max(0.0));
+                REPLICA_METRICS.lag.observe(lag);
+                u64::try_from(lag.as_millis()).unwrap_or(UNAVAILABLE_LAG_MS - 1)
+            }
+            Err(err) => {
+                tracing::warn!(
+                    "Failed checking replication lag for replica #{}: {err}",
+                    self.index
+                );
+                UNAVAILABLE_LAG_MS
+            }
+        };
+        self.lag_ms.store(lag_ms, Ordering::Relaxed);
+    }
+}
+
+/// Routing of connections among read-only replicas with replication lag awareness.
+#[derive(Debug)]
+pub(crate) struct ReplicaRouting {
+    replicas: Vec<Replica>,
+    max_lag_ms: u64,
+    /// Used to distribute connections among eligible replicas in the round-robin fashion.
+    next_replica: AtomicUsize,
+}
+
+impl ReplicaRouting {
+    pub(crate) fn new(replica_pools: Vec<PgPool>, max_lag: Duration) -> Self {
+        let replicas = replica_pools
+            .into_iter()
+            .enumerate()
+            .map(|(index, pool)| Replica {
+                index,
+                pool,
+                // Replicas are considered up-to-date until the first lag check.
+                lag_ms: AtomicU64::new(0),
+            })
+            .collect();
+        Self {
+            replicas,
+            max_lag_ms: u64::try_from(max_lag.as_millis()).unwrap_or(u64::MAX - 1),
+            next_replica: AtomicUsize::new(0),
+        }
+    }
+
+    pub(crate) fn replica_count(&self) -> usize {
+        self.replicas.len()
+    }
+
+    fn select_replica(&self) -> Option<&Replica> {
+        let replica_count = self.replicas.len();
+        let start = self.next_replica.fetch_add(1, Ordering::Relaxed);
+        (0..replica_count)
+            .map(|i| &self.replicas[(start + i) % replica_count])
+            .find(|replica| replica.is_eligible(self.max_lag_ms))
+    }
+
+    /// Tries to acquire a connection to the replica selected by `pin`. If the pin is not set yet, selects one
+    /// of eligible replicas. Returns `None` if the pin is set to the main database, there are no eligible replicas,
+    /// or acquiring a connection has failed; in this case, the caller should fall back to the main database.
+    /// Returns the acquired connection together with the replica pool it was acquired from.
+    pub(crate) async fn try_acquire(
+        &self,
+        tags: Option<&ConnectionTags>,
+        pin: &ReplicaPin,
+    ) -> Option<(PoolConnection<Postgres>, &PgPool)> {
+        let mut pinned = pin.0.load(Ordering::Relaxed);
+        if pinned == ReplicaPin::UNPINNED {
+            pinned = match self.select_replica() {
+                Some(replica) => pin.pin(replica.index),
+                None => {
+                    REPLICA_METRICS.fallbacks[&ReplicaFallbackReason::NoEligibleReplicas].inc();
+                    pin.pin(ReplicaPin::MAIN_DATABASE)
+                }
+            };
+        }
+        // The pinned replica is used even if it has become ineligible, so that reads stay consistent.
+        // If the pin is set to the main database, `get()` returns `None`.
+        let replica = self.replicas.get(pinned)?;
+
+        match replica.pool.acquire().await {
+            Ok(connection) => {
+                REPLICA_METRICS.acquired.inc();
+                Some((connection, &replica.pool))
+            }
+            Err(err) => {
+                CONNECTION_METRICS.pool_acquire_error[&(&err).into()].inc();
+                REPLICA_METRICS.fallbacks[&ReplicaFallbackReason::AcquireError].inc();
+                let tags_display = ConnectionTags::display(tags);
+                tracing::warn!(
+                    "Failed to get connection to replica #{} ({tags_display}), falling back to main DB: {err}",
+                    replica.index
+                );
+                // Exclude the replica from routing until the next successful lag check.
+                replica.lag_ms.store(UNAVAILABLE_LAG_MS, Ordering::Relaxed);
+                pin.pin_main_database();
+                None
+            }
+        }
+    }
+
+    pub(crate) async fn check_lags(&self) {
+        for replica in &self.replicas {
+            replica.check_lag().await;
+        }
+        let eligible_count = self
+            .replicas
+            .iter()
+            .filter(|replica| replica.is_eligible(self.max_lag_ms))
+            .count();
+        REPLICA_METRICS.eligible_replicas.set(eligible_count);
+    }
+}
+
+/// Task periodically checking replication lag for a connection pool with replicas. Replicas lagging too much
+/// or unavailable are excluded from connection routing until the next check.
+#[derive(Debug)]
+#[must_use = "Monitor should be `run()`"]
+pub struct ReplicaLagMonitor {
+    routing: Arc<ReplicaRouting>,
+    check_interval: Duration,
+}
+
+impl ReplicaLagMonitor {
+    pub(crate) fn new(routing: Arc<ReplicaRouting>, check_interval: Duration) -> Self {
+        Self {
+            routing,
+            check_interval,
+        }
+    }
+
+    /// Runs this monitor until a stop signal is received.
+    pub async fn run(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        while !*stop_receiver.borrow_and_update() {
+            self.routing.check_lags().await;
+            // Error here corresponds to a timeout w/o `stop_receiver` changed; we're OK with this.
+            tokio::time::timeout(self.check_interval, stop_receiver.changed())
+                .await
+                .ok();
+        }
+        tracing::info!("Stop signal received, replica lag monitor is shutting down");
+        Ok(())
+    }
+}
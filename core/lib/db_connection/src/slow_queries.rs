@@ -0,0 +1,155 @@
+//! Capturing slow queries together with their query plans.
+
+use std::{
+    collections::VecDeque,
+    panic::Location,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use sqlx::{postgres::PgConnectOptions, Connection as _, PgConnection};
+
+/// Slow query captured by the DAL instrumentation.
+#[derive(Debug, Clone)]
+pub struct SlowQuery {
+    /// Name of the query assigned during instrumentation.
+    pub name: &'static str,
+    /// Location of the code invoking the query.
+    pub location: &'static Location<'static>,
+    /// SQL text of the query.
+    pub sql: String,
+    /// Query arguments provided via `Instrumented::with_arg()`, formatted using `Debug`.
+    pub args: Vec<(&'static str, String)>,
+    /// Query latency.
+    pub elapsed: Duration,
+    /// Time when the query has finished.
+    pub finished_at: SystemTime,
+    /// Query plan returned by `EXPLAIN`. `None` if the plan could not be obtained.
+    pub query_plan: Option<String>,
+}
+
+/// Slow query sampled for capturing, which lacks SQL text and the query plan.
+#[derive(Debug)]
+pub(crate) struct SampledSlowQuery {
+    pub name: &'static str,
+    pub location: &'static Location<'static>,
+    pub args: Vec<(&'static str, String)>,
+    pub elapsed: Duration,
+}
+
+impl SampledSlowQuery {
+    /// Spawns a background task obtaining the query plan for this query and storing the query
+    /// in the global [`SlowQueryLog`].
+    ///
+    /// The plan is obtained using plain `EXPLAIN` (i.e., without `ANALYZE`) via a dedicated short-lived connection,
+    /// so capturing neither re-executes the query nor delays the caller or holds its connection. Bound query arguments
+    /// are consumed when executing a query, so for queries with bind parameters, a generic plan is obtained
+    /// using `EXPLAIN (GENERIC_PLAN)` (requires Postgres 16+).
+    pub fn capture(self, connect_options: Arc<PgConnectOptions>, sql: &str) {
+        // If there's no Tokio runtime, we're most probably shutting down, so capturing is irrelevant.
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let sql = sql.trim().to_owned();
+        handle.spawn(async move {
+            let query_plan = match explain(&connect_options, &sql).await {
+                Ok(plan) => {
+                    tracing::info!(
+                        "Captured query plan for slow query {name} called at {file}:{line} ({elapsed:?}):\n{plan}",
+                        name = self.name,
+                        file = self.location.file(),
+                        line = self.location.line(),
+                        elapsed = self.elapsed
+                    );
+                    Some(plan)
+                }
+                Err(err) => {
+                    tracing::info!(
+                        "Failed obtaining query plan for slow query {name}: {err}",
+                        name = self.name
+                    );
+                    None
+                }
+            };
+
+            SlowQueryLog::global().push(SlowQuery {
+                name: self.name,
+                location: self.location,
+                sql,
+                args: self.args,
+                elapsed: self.elapsed,
+                finished_at: SystemTime::now(),
+                query_plan,
+            });
+        });
+    }
+}
+
+async fn explain(connect_options: &PgConnectOptions, sql: &str) -> sqlx::Result<String> {
+    let explain_sql = if has_bind_parameters(sql) {
+        format!("EXPLAIN (GENERIC_PLAN) {sql}")
+    } else {
+        format!("EXPLAIN {sql}")
+    };
+    let mut connection = PgConnection::connect_with(connect_options).await?;
+    let lines: Vec<String> = sqlx::query_scalar(&explain_sql)
+        .fetch_all(&mut connection)
+        .await?;
+    connection.close().await?;
+    Ok(lines.join("\n"))
+}
+
+fn has_bind_parameters(sql: &str) -> bool {
+    sql.as_bytes()
+        .windows(2)
+        .any(|window| window[0] == b'$' && window[1].is_ascii_digit())
+}
+
+/// Bounded log of the most recent slow queries captured by the DAL instrumentation. Capturing is enabled
+/// by setting a non-zero sample rate via [`GlobalConnectionPoolConfig::set_slow_query_explain_sample_rate()`].
+///
+/// [`GlobalConnectionPoolConfig::set_slow_query_explain_sample_rate()`]: crate::connection_pool::GlobalConnectionPoolConfig::set_slow_query_explain_sample_rate
+#[derive(Debug)]
+pub struct SlowQueryLog {
+    queries: Mutex<VecDeque<SlowQuery>>,
+}
+
+impl SlowQueryLog {
+    const CAPACITY: usize = 100;
+
+    /// Returns the global log shared by all connection pools.
+    pub fn global() -> &'static Self {
+        static LOG: SlowQueryLog = SlowQueryLog {
+            queries: Mutex::new(VecDeque::new()),
+        };
+        &LOG
+    }
+
+    fn push(&self, query: SlowQuery) {
+        let mut queries = self.queries.lock().expect("slow query log is poisoned");
+        if queries.len() == Self::CAPACITY {
+            queries.pop_front();
+        }
+        queries.push_back(query);
+    }
+
+    /// Returns captured slow queries, from the most recent to the oldest one.
+    pub fn recent(&self) -> Vec<SlowQuery> {
+        let queries = self.queries.lock().expect("slow query log is poisoned");
+        queries.iter().rev().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checking_bind_parameters() {
+        assert!(has_bind_parameters(
+            "SELECT * FROM miniblocks WHERE number = $1"
+        ));
+        assert!(!has_bind_parameters("SELECT * FROM miniblocks"));
+        assert!(!has_bind_parameters("SELECT '$'"));
+    }
+}
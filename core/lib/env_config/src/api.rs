@@ -65,6 +65,7 @@ mod tests {
                 filters_limit: Some(10000),
                 subscriptions_limit: Some(10000),
                 pubsub_polling_interval: Some(200),
+                pubsub_db_notifications: true,
                 max_nonce_ahead: 5,
                 request_timeout: Some(10),
                 account_pks: Some(vec![
@@ -74,15 +75,34 @@ mod tests {
                 estimate_gas_scale_factor: 1.0f64,
                 gas_price_scale_factor: 1.2,
                 estimate_gas_acceptable_overestimation: 1000,
+                estimate_gas_optimize_search: true,
                 l1_to_l2_transactions_compatibility_mode: true,
                 max_tx_size: 1000000,
                 vm_execution_cache_misses_limit: None,
+                vm_execution_timeout_ms: Some(5000),
+                eth_call_cache_size: Some(1000),
+                estimate_gas_cache_size: Some(500),
+                estimate_gas_cache_ttl_ms: Some(3000),
                 vm_concurrency_limit: Some(512),
+                vm_permit_timeout_ms: Some(1000),
+                vm_saturation_threshold: Some(0.8),
                 factory_deps_cache_size_mb: Some(128),
                 initial_writes_cache_size_mb: Some(32),
                 latest_values_cache_size_mb: Some(256),
+                latest_values_cache_path: Some("/db/values_cache".to_owned()),
+                latest_values_cache_persistent_max_entries: Some(1_000_000),
+                caches_warm_up_miniblocks: Some(100),
+                historical_state_path: Some("/db/historical_state".to_owned()),
+                historical_state_retained_miniblocks: Some(10_000),
                 fee_history_limit: Some(100),
+                fee_model_simulation_limit: Some(50),
                 max_batch_request_size: Some(200),
+                max_batch_request_cost: Some(1000),
+                max_queued_txs_per_account: Some(8),
+                tx_replacement_fee_bump_percent: Some(15),
+                read_only_main_node_url: Some("http://127.0.0.1:3050/".to_owned()),
+                max_tx_submissions_per_minute_per_sender: Some(NonZeroU32::new(60).unwrap()),
+                max_pending_txs_per_sender: Some(64),
                 max_response_body_size_mb: Some(10),
                 websocket_requests_per_minute_limit: Some(NonZeroU32::new(10).unwrap()),
                 tree_api_url: None,
@@ -92,6 +112,17 @@ mod tests {
                     addr("0x0000000000000000000000000000000000000001"),
                     addr("0x0000000000000000000000000000000000000002"),
                 ],
+                aa_trusted_token_slots: vec![
+                    hash("0x0000000000000000000000000000000000000000000000000000000000000003"),
+                    hash("0x0000000000000000000000000000000000000000000000000000000000000004"),
+                ],
+                aa_trusted_addresses: vec![addr("0x0000000000000000000000000000000000000003")],
+                admin_namespace_enabled: true,
+                unstable_namespace_enabled: true,
+                sse_port: Some(3060),
+                http_auth_token: Some("http_secret".to_owned()),
+                ws_auth_token: None,
+                ipc_path: Some("/tmp/zksync.ipc".to_owned()),
             },
             prometheus: PrometheusConfig {
                 listener_port: 3312,
@@ -103,7 +134,10 @@ mod tests {
                 slow_time_limit_ms: Some(250),
                 hard_time_limit_ms: Some(2_000),
             },
-            merkle_tree: MerkleTreeApiConfig { port: 8082 },
+            merkle_tree: MerkleTreeApiConfig {
+                port: 8082,
+                max_proof_keys: 1_000,
+            },
         }
     }
 
@@ -120,23 +154,50 @@ mod tests {
             API_WEB3_JSON_RPC_FILTERS_LIMIT=10000
             API_WEB3_JSON_RPC_SUBSCRIPTIONS_LIMIT=10000
             API_WEB3_JSON_RPC_PUBSUB_POLLING_INTERVAL=200
+            API_WEB3_JSON_RPC_PUBSUB_DB_NOTIFICATIONS=true
             API_WEB3_JSON_RPC_MAX_NONCE_AHEAD=5
             API_WEB3_JSON_RPC_GAS_PRICE_SCALE_FACTOR=1.2
             API_WEB3_JSON_RPC_REQUEST_TIMEOUT=10
             API_WEB3_JSON_RPC_ACCOUNT_PKS="0x0000000000000000000000000000000000000000000000000000000000000001,0x0000000000000000000000000000000000000000000000000000000000000002"
             API_WEB3_JSON_RPC_WHITELISTED_TOKENS_FOR_AA="0x0000000000000000000000000000000000000001,0x0000000000000000000000000000000000000002"
+            API_WEB3_JSON_RPC_AA_TRUSTED_TOKEN_SLOTS="0x0000000000000000000000000000000000000000000000000000000000000003,0x0000000000000000000000000000000000000000000000000000000000000004"
+            API_WEB3_JSON_RPC_AA_TRUSTED_ADDRESSES="0x0000000000000000000000000000000000000003"
+            API_WEB3_JSON_RPC_ADMIN_NAMESPACE_ENABLED=true
+            API_WEB3_JSON_RPC_UNSTABLE_NAMESPACE_ENABLED=true
             API_WEB3_JSON_RPC_ESTIMATE_GAS_SCALE_FACTOR=1.0
             API_WEB3_JSON_RPC_ESTIMATE_GAS_ACCEPTABLE_OVERESTIMATION=1000
+            API_WEB3_JSON_RPC_ESTIMATE_GAS_OPTIMIZE_SEARCH=true
             API_WEB3_JSON_RPC_L1_TO_L2_TRANSACTIONS_COMPATIBILITY_MODE=true
             API_WEB3_JSON_RPC_MAX_TX_SIZE=1000000
             API_WEB3_JSON_RPC_VM_CONCURRENCY_LIMIT=512
             API_WEB3_JSON_RPC_FACTORY_DEPS_CACHE_SIZE_MB=128
             API_WEB3_JSON_RPC_INITIAL_WRITES_CACHE_SIZE_MB=32
             API_WEB3_JSON_RPC_LATEST_VALUES_CACHE_SIZE_MB=256
+            API_WEB3_JSON_RPC_LATEST_VALUES_CACHE_PATH=/db/values_cache
+            API_WEB3_JSON_RPC_LATEST_VALUES_CACHE_PERSISTENT_MAX_ENTRIES=1000000
+            API_WEB3_JSON_RPC_CACHES_WARM_UP_MINIBLOCKS=100
+            API_WEB3_JSON_RPC_HISTORICAL_STATE_PATH=/db/historical_state
+            API_WEB3_JSON_RPC_HISTORICAL_STATE_RETAINED_MINIBLOCKS=10000
             API_WEB3_JSON_RPC_FEE_HISTORY_LIMIT=100
+            API_WEB3_JSON_RPC_FEE_MODEL_SIMULATION_LIMIT=50
             API_WEB3_JSON_RPC_MAX_BATCH_REQUEST_SIZE=200
+            API_WEB3_JSON_RPC_MAX_BATCH_REQUEST_COST=1000
+            API_WEB3_JSON_RPC_MAX_QUEUED_TXS_PER_ACCOUNT=8
+            API_WEB3_JSON_RPC_TX_REPLACEMENT_FEE_BUMP_PERCENT=15
+            API_WEB3_JSON_RPC_READ_ONLY_MAIN_NODE_URL=http://127.0.0.1:3050/
+            API_WEB3_JSON_RPC_MAX_TX_SUBMISSIONS_PER_MINUTE_PER_SENDER=60
+            API_WEB3_JSON_RPC_MAX_PENDING_TXS_PER_SENDER=64
+            API_WEB3_JSON_RPC_SSE_PORT=3060
+            API_WEB3_JSON_RPC_HTTP_AUTH_TOKEN=http_secret
+            API_WEB3_JSON_RPC_IPC_PATH=/tmp/zksync.ipc
             API_WEB3_JSON_RPC_WEBSOCKET_REQUESTS_PER_MINUTE_LIMIT=10
             API_WEB3_JSON_RPC_MEMPOOL_CACHE_SIZE=10000
+            API_WEB3_JSON_RPC_ETH_CALL_CACHE_SIZE=1000
+            API_WEB3_JSON_RPC_ESTIMATE_GAS_CACHE_SIZE=500
+            API_WEB3_JSON_RPC_ESTIMATE_GAS_CACHE_TTL_MS=3000
+            API_WEB3_JSON_RPC_VM_EXECUTION_TIMEOUT_MS=5000
+            API_WEB3_JSON_RPC_VM_PERMIT_TIMEOUT_MS=1000
+            API_WEB3_JSON_RPC_VM_SATURATION_THRESHOLD=0.8
             API_WEB3_JSON_RPC_MEMPOOL_CACHE_UPDATE_INTERVAL=50
             API_CONTRACT_VERIFICATION_PORT="3070"
             API_CONTRACT_VERIFICATION_URL="http://127.0.0.1:3070"
@@ -148,6 +209,7 @@ mod tests {
             API_HEALTHCHECK_SLOW_TIME_LIMIT_MS=250
             API_HEALTHCHECK_HARD_TIME_LIMIT_MS=2000
             API_MERKLE_TREE_PORT=8082
+            API_MERKLE_TREE_MAX_PROOF_KEYS=1000
         "#;
         lock.set_env(config);
 
@@ -0,0 +1,34 @@
+use zksync_config::configs::BatchExporterConfig;
+
+use crate::{envy_load, FromEnv};
+
+impl FromEnv for BatchExporterConfig {
+    fn from_env() -> anyhow::Result<Self> {
+        envy_load("batch_exporter", "BATCH_EXPORTER_")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::EnvMutex;
+
+    static MUTEX: EnvMutex = EnvMutex::new();
+
+    #[test]
+    fn from_env() {
+        let config = r#"
+            BATCH_EXPORTER_POLL_INTERVAL_MS="10000"
+        "#;
+        let mut lock = MUTEX.lock();
+        lock.set_env(config);
+        let actual = BatchExporterConfig::from_env().unwrap();
+        assert_eq!(
+            actual,
+            BatchExporterConfig {
+                poll_interval_ms: 10_000,
+                object_store: None,
+            }
+        );
+    }
+}
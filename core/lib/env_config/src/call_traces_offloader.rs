@@ -0,0 +1,36 @@
+use zksync_config::configs::CallTracesOffloaderConfig;
+
+use crate::{envy_load, FromEnv};
+
+impl FromEnv for CallTracesOffloaderConfig {
+    fn from_env() -> anyhow::Result<Self> {
+        envy_load("call_traces_offloader", "CALL_TRACES_OFFLOADER_")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::EnvMutex;
+
+    static MUTEX: EnvMutex = EnvMutex::new();
+
+    #[test]
+    fn from_env() {
+        let config = r#"
+            CALL_TRACES_OFFLOADER_OFFLOAD_AFTER_SEC="604800"
+            CALL_TRACES_OFFLOADER_POLL_INTERVAL_MS="30000"
+        "#;
+        let mut lock = MUTEX.lock();
+        lock.set_env(config);
+        let actual = CallTracesOffloaderConfig::from_env().unwrap();
+        assert_eq!(
+            actual,
+            CallTracesOffloaderConfig {
+                offload_after_sec: 604_800,
+                poll_interval_ms: 30_000,
+                object_store: None,
+            }
+        );
+    }
+}
@@ -37,7 +37,9 @@ impl FromEnv for MempoolConfig {
 #[cfg(test)]
 mod tests {
     use zksync_basic_types::L2ChainId;
-    use zksync_config::configs::chain::{FeeModelVersion, L1BatchCommitDataGeneratorMode};
+    use zksync_config::configs::chain::{
+        FeeAccountRotation, FeeModelVersion, L1BatchCommitDataGeneratorMode,
+    };
 
     use super::*;
     use crate::test_utils::{addr, hash, EnvMutex};
@@ -77,6 +79,15 @@ mod tests {
             block_commit_deadline_ms: 2500,
             miniblock_commit_deadline_ms: 1000,
             miniblock_seal_queue_capacity: 10,
+            miniblock_max_tx_count: Some(100),
+            miniblock_min_commit_deadline_ms: Some(200),
+            max_gas_per_miniblock: Some(40_000_000),
+            max_priority_ops_per_miniblock: Some(10),
+            max_priority_ops_per_batch: Some(100),
+            max_consecutive_priority_ops: Some(5),
+            max_gas_per_tx: Some(50_000_000),
+            max_pubdata_per_tx: Some(20_000),
+            max_factory_deps_size_per_tx: Some(100_000),
             max_single_tx_gas: 1_000_000,
             max_allowed_l2_tx_gas_limit: 2_000_000_000,
             close_block_at_eth_params_percentage: 0.2,
@@ -85,6 +96,11 @@ mod tests {
             reject_tx_at_eth_params_percentage: 0.8,
             reject_tx_at_geometry_percentage: 0.3,
             fee_account_addr: Some(addr("de03a0B5963f75f1C8485B355fF6D30f3093BDE7")),
+            additional_fee_account_addrs: vec![
+                addr("0x0000000000000000000000000000000000000001"),
+                addr("0x0000000000000000000000000000000000000002"),
+            ],
+            fee_account_rotation: Some(FeeAccountRotation::RoundRobin),
             reject_tx_at_gas_percentage: 0.5,
             minimal_l2_gas_price: 100000000,
             compute_overhead_part: 0.0,
@@ -98,6 +114,12 @@ mod tests {
             virtual_blocks_interval: 1,
             virtual_blocks_per_miniblock: 1,
             enum_index_migration_chunk_size: Some(2_000),
+            protective_reads_persistence_enabled: Some(false),
+            seal_miniblock_on_shutdown: Some(true),
+            ntp_server: Some("pool.ntp.org:123".to_owned()),
+            max_clock_skew_ms: Some(2_000),
+            shadow_vm_execution: Some(true),
+            batch_executor_process_isolation: Some(true),
             bootloader_hash: Some(hash(
                 "0x010007ede999d096c84553fb514d3d6ca76fbf39789dda76bfeda9f3ae06236e",
             )),
@@ -114,6 +136,8 @@ mod tests {
             r#"
             CHAIN_STATE_KEEPER_TRANSACTION_SLOTS="50"
             CHAIN_STATE_KEEPER_FEE_ACCOUNT_ADDR="0xde03a0B5963f75f1C8485B355fF6D30f3093BDE7"
+            CHAIN_STATE_KEEPER_ADDITIONAL_FEE_ACCOUNT_ADDRS="0x0000000000000000000000000000000000000001,0x0000000000000000000000000000000000000002"
+            CHAIN_STATE_KEEPER_FEE_ACCOUNT_ROTATION="RoundRobin"
             CHAIN_STATE_KEEPER_MAX_SINGLE_TX_GAS="1000000"
             CHAIN_STATE_KEEPER_MAX_ALLOWED_L2_TX_GAS_LIMIT="2000000000"
             CHAIN_STATE_KEEPER_CLOSE_BLOCK_AT_GEOMETRY_PERCENTAGE="0.5"
@@ -125,6 +149,15 @@ mod tests {
             CHAIN_STATE_KEEPER_BLOCK_COMMIT_DEADLINE_MS="2500"
             CHAIN_STATE_KEEPER_MINIBLOCK_COMMIT_DEADLINE_MS="1000"
             CHAIN_STATE_KEEPER_MINIBLOCK_SEAL_QUEUE_CAPACITY="10"
+            CHAIN_STATE_KEEPER_MINIBLOCK_MAX_TX_COUNT="100"
+            CHAIN_STATE_KEEPER_MAX_GAS_PER_MINIBLOCK="40000000"
+            CHAIN_STATE_KEEPER_MAX_PRIORITY_OPS_PER_MINIBLOCK="10"
+            CHAIN_STATE_KEEPER_MAX_PRIORITY_OPS_PER_BATCH="100"
+            CHAIN_STATE_KEEPER_MAX_CONSECUTIVE_PRIORITY_OPS="5"
+            CHAIN_STATE_KEEPER_MAX_GAS_PER_TX="50000000"
+            CHAIN_STATE_KEEPER_MAX_PUBDATA_PER_TX="20000"
+            CHAIN_STATE_KEEPER_MAX_FACTORY_DEPS_SIZE_PER_TX="100000"
+            CHAIN_STATE_KEEPER_MINIBLOCK_MIN_COMMIT_DEADLINE_MS="200"
             CHAIN_STATE_KEEPER_MINIMAL_L2_GAS_PRICE="100000000"
             CHAIN_STATE_KEEPER_COMPUTE_OVERHEAD_PART="0.0"
             CHAIN_STATE_KEEPER_PUBDATA_OVERHEAD_PART="1.0"
@@ -135,6 +168,12 @@ mod tests {
             CHAIN_STATE_KEEPER_VALIDATION_COMPUTATIONAL_GAS_LIMIT="10000000"
             CHAIN_STATE_KEEPER_SAVE_CALL_TRACES="false"
             CHAIN_STATE_KEEPER_ENUM_INDEX_MIGRATION_CHUNK_SIZE="2000"
+            CHAIN_STATE_KEEPER_PROTECTIVE_READS_PERSISTENCE_ENABLED="false"
+            CHAIN_STATE_KEEPER_SEAL_MINIBLOCK_ON_SHUTDOWN="true"
+            CHAIN_STATE_KEEPER_NTP_SERVER="pool.ntp.org:123"
+            CHAIN_STATE_KEEPER_MAX_CLOCK_SKEW_MS="2000"
+            CHAIN_STATE_KEEPER_SHADOW_VM_EXECUTION="true"
+            CHAIN_STATE_KEEPER_BATCH_EXECUTOR_PROCESS_ISOLATION="true"
             CHAIN_STATE_KEEPER_VIRTUAL_BLOCKS_PER_MINIBLOCK="1"
             CHAIN_STATE_KEEPER_VIRTUAL_BLOCKS_INTERVAL="1"
             CHAIN_STATE_KEEPER_BOOTLOADER_HASH=0x010007ede999d096c84553fb514d3d6ca76fbf39789dda76bfeda9f3ae06236e
@@ -172,6 +211,14 @@ mod tests {
             stuck_tx_timeout: 10,
             remove_stuck_txs: true,
             delay_interval: 100,
+            tx_deny_list: Some("/etc/zksync/deny-list.txt".to_owned()),
+            tx_deny_list_reload_interval_sec: Some(30),
+            tx_denied_calldata_prefixes: vec!["0xa9059cbb".to_owned(), "0x095ea7b3".to_owned()],
+            tx_deployment_only_windows: vec!["1700000000-1700003600".to_owned()],
+            max_l2_transactions: Some(2_000_000),
+            score_fee_weight: Some(10),
+            score_age_weight: Some(1),
+            score_rejection_penalty: Some(60),
         }
     }
 
@@ -185,6 +232,14 @@ mod tests {
             CHAIN_MEMPOOL_REMOVE_STUCK_TXS="true"
             CHAIN_MEMPOOL_DELAY_INTERVAL="100"
             CHAIN_MEMPOOL_CAPACITY="1000000"
+            CHAIN_MEMPOOL_TX_DENY_LIST="/etc/zksync/deny-list.txt"
+            CHAIN_MEMPOOL_TX_DENY_LIST_RELOAD_INTERVAL_SEC="30"
+            CHAIN_MEMPOOL_TX_DENIED_CALLDATA_PREFIXES="0xa9059cbb,0x095ea7b3"
+            CHAIN_MEMPOOL_TX_DEPLOYMENT_ONLY_WINDOWS="1700000000-1700003600"
+            CHAIN_MEMPOOL_MAX_L2_TRANSACTIONS="2000000"
+            CHAIN_MEMPOOL_SCORE_FEE_WEIGHT="10"
+            CHAIN_MEMPOOL_SCORE_AGE_WEIGHT="1"
+            CHAIN_MEMPOOL_SCORE_REJECTION_PENALTY="60"
         "#;
         lock.set_env(config);
 
@@ -0,0 +1,41 @@
+use zksync_config::configs::ChangeStreamConfig;
+
+use crate::{envy_load, FromEnv};
+
+impl FromEnv for ChangeStreamConfig {
+    fn from_env() -> anyhow::Result<Self> {
+        envy_load("change_stream", "CHANGE_STREAM_")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::EnvMutex;
+
+    static MUTEX: EnvMutex = EnvMutex::new();
+
+    #[test]
+    fn from_env() {
+        let config = r#"
+            CHANGE_STREAM_NATS_URL="nats://127.0.0.1:4222"
+            CHANGE_STREAM_NATS_CREDENTIALS_PATH="/etc/nats/zksync.creds"
+            CHANGE_STREAM_NATS_TLS_REQUIRED="true"
+            CHANGE_STREAM_QUEUE_CAPACITY="100"
+        "#;
+        let mut lock = MUTEX.lock();
+        lock.set_env(config);
+        let actual = ChangeStreamConfig::from_env().unwrap();
+        assert_eq!(
+            actual,
+            ChangeStreamConfig {
+                nats_url: "nats://127.0.0.1:4222".to_owned(),
+                nats_credentials_path: Some("/etc/nats/zksync.creds".to_owned()),
+                nats_tls_required: true,
+                nats_root_certificates_path: None,
+                subject_prefix: "zksync".to_owned(),
+                queue_capacity: 100,
+            }
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use anyhow::Context as _;
 use zksync_config::{
-    configs::eth_sender::SenderConfig, ETHConfig, ETHWatchConfig, GasAdjusterConfig,
+    configs::eth_sender::{RemoteSignerConfig, SenderConfig},
+    ETHConfig, ETHWatchConfig, GasAdjusterConfig,
 };
 
 use crate::{envy_load, FromEnv};
@@ -12,6 +13,7 @@ impl FromEnv for ETHConfig {
             gas_adjuster: GasAdjusterConfig::from_env().ok(),
             watcher: ETHWatchConfig::from_env().ok(),
             web3_url: std::env::var("ETH_CLIENT_WEB3_URL").context("ETH_CLIENT_WEB3_URL")?,
+            remote_signer: RemoteSignerConfig::from_env().ok(),
         })
     }
 }
@@ -22,6 +24,12 @@ impl FromEnv for SenderConfig {
     }
 }
 
+impl FromEnv for RemoteSignerConfig {
+    fn from_env() -> anyhow::Result<Self> {
+        envy_load("eth_sender.remote_signer", "ETH_SENDER_REMOTE_SIGNER_")
+    }
+}
+
 impl FromEnv for GasAdjusterConfig {
     fn from_env() -> anyhow::Result<Self> {
         envy_load("eth_sender.gas_adjuster", "ETH_SENDER_GAS_ADJUSTER_")
@@ -35,7 +43,7 @@ mod tests {
     };
 
     use super::*;
-    use crate::test_utils::{hash, EnvMutex};
+    use crate::test_utils::{addr, hash, EnvMutex};
 
     static MUTEX: EnvMutex = EnvMutex::new();
 
@@ -61,6 +69,8 @@ mod tests {
                 max_acceptable_priority_fee_in_gwei: 100_000_000_000,
                 proof_loading_mode: ProofLoadingMode::OldProofFromDb,
                 pubdata_sending_mode: PubdataSendingMode::Calldata,
+                max_commit_blob_base_fee: Some(50_000_000_000),
+                commit_blob_base_fee_deadline: 1_800,
             }),
             gas_adjuster: Some(GasAdjusterConfig {
                 default_priority_fee_per_gas: 20000000000,
@@ -79,8 +89,22 @@ mod tests {
             watcher: Some(ETHWatchConfig {
                 confirmations_for_eth_event: Some(0),
                 eth_node_poll_interval: 300,
+                governance_alerts_webhook_url: None,
+                governance_alerts_lead_time_sec: None,
             }),
             web3_url: "http://127.0.0.1:8545".to_string(),
+            remote_signer: Some(RemoteSignerConfig {
+                urls: vec![
+                    "https://signer-1.example.com/".to_string(),
+                    "https://signer-2.example.com/".to_string(),
+                ],
+                operator_address: addr("de03a0b5963f75f1c8485b355ff6d30f3093bde7"),
+                blob_operator_address: None,
+                request_signing_key: Some(hash(
+                    "c43ef8a4b3a5dc5e0a2f6b8f0d4a1e2d3c4b5a69788796a5b4c3d2e1f0a9b8c7",
+                )),
+                request_timeout_ms: 5_000,
+            }),
         }
     }
 
@@ -120,6 +144,11 @@ mod tests {
             ETH_SENDER_SENDER_MAX_ACCEPTABLE_PRIORITY_FEE_IN_GWEI="100000000000"
             ETH_SENDER_SENDER_PROOF_LOADING_MODE="OldProofFromDb"
             ETH_SENDER_SENDER_PUBDATA_SENDING_MODE="Calldata"
+            ETH_SENDER_SENDER_MAX_COMMIT_BLOB_BASE_FEE="50000000000"
+            ETH_SENDER_SENDER_COMMIT_BLOB_BASE_FEE_DEADLINE="1800"
+            ETH_SENDER_REMOTE_SIGNER_URLS="https://signer-1.example.com/,https://signer-2.example.com/"
+            ETH_SENDER_REMOTE_SIGNER_OPERATOR_ADDRESS="0xde03a0b5963f75f1c8485b355ff6d30f3093bde7"
+            ETH_SENDER_REMOTE_SIGNER_REQUEST_SIGNING_KEY="0xc43ef8a4b3a5dc5e0a2f6b8f0d4a1e2d3c4b5a69788796a5b4c3d2e1f0a9b8c7"
             ETH_CLIENT_WEB3_URL="http://127.0.0.1:8545"
 
         "#;
@@ -127,6 +156,10 @@ mod tests {
 
         let actual = ETHConfig::from_env().unwrap();
         assert_eq!(actual, expected_config());
+        // The remote signer request signing key must not be leaked via `Debug`.
+        let debug_output = format!("{actual:?}");
+        assert!(!debug_output.contains("c43ef8a4"), "{debug_output}");
+        assert!(debug_output.contains("[REDACTED]"), "{debug_output}");
         assert_eq!(
             actual.sender.unwrap().private_key().unwrap(),
             hash("27593fea79697e947890ecbecce7901b0008345e5d7259710d0dd5e500d040be")
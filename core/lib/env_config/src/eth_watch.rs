@@ -19,6 +19,8 @@ mod tests {
         ETHWatchConfig {
             confirmations_for_eth_event: Some(0),
             eth_node_poll_interval: 300,
+            governance_alerts_webhook_url: Some("http://127.0.0.1:8080/alerts".to_owned()),
+            governance_alerts_lead_time_sec: Some(3600),
         }
     }
 
@@ -28,6 +30,8 @@ mod tests {
         let config = r#"
             ETH_WATCH_CONFIRMATIONS_FOR_ETH_EVENT="0"
             ETH_WATCH_ETH_NODE_POLL_INTERVAL="300"
+            ETH_WATCH_GOVERNANCE_ALERTS_WEBHOOK_URL="http://127.0.0.1:8080/alerts"
+            ETH_WATCH_GOVERNANCE_ALERTS_LEAD_TIME_SEC="3600"
         "#;
         lock.set_env(config);
 
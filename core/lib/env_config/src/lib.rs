@@ -2,6 +2,9 @@ use anyhow::Context as _;
 use serde::de::DeserializeOwned;
 
 mod api;
+mod batch_exporter;
+mod call_traces_offloader;
+mod change_stream;
 mod chain;
 mod contract_verifier;
 mod contracts;
@@ -6,7 +6,7 @@ use vise::{
 
 pub use self::{
     query::QueryClient,
-    signing::{PKSigningClient, SigningClient},
+    signing::{PKSigningClient, RemoteSigningClient, SigningClient},
 };
 
 mod query;
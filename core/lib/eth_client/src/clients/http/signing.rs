@@ -3,7 +3,9 @@ use std::{fmt, sync::Arc};
 use async_trait::async_trait;
 use zksync_config::{configs::ContractsConfig, ETHConfig};
 use zksync_contracts::zksync_contract;
-use zksync_eth_signer::{raw_ethereum_tx::TransactionParameters, EthereumSigner, PrivateKeySigner};
+use zksync_eth_signer::{
+    raw_ethereum_tx::TransactionParameters, EthereumSigner, PrivateKeySigner, RemoteSigner,
+};
 use zksync_types::{
     web3::{
         self,
@@ -90,6 +92,32 @@ impl PKSigningClient {
     }
 }
 
+/// HTTP-based Ethereum client, delegating transaction signing to a remote signer service.
+pub type RemoteSigningClient = SigningClient<RemoteSigner>;
+
+impl RemoteSigningClient {
+    pub fn from_remote_signer(
+        signer: RemoteSigner,
+        diamond_proxy_addr: Address,
+        default_priority_fee_per_gas: u64,
+        l1_chain_id: L1ChainId,
+        web3_url: &str,
+    ) -> Self {
+        let transport = Http::new(web3_url).expect("Failed to create transport");
+        let operator_address = signer.address();
+        tracing::info!("Operator address (signed remotely): {operator_address:?}");
+        SigningClient::new(
+            transport,
+            zksync_contract(),
+            operator_address,
+            signer,
+            diamond_proxy_addr,
+            default_priority_fee_per_gas.into(),
+            l1_chain_id,
+        )
+    }
+}
+
 /// Gas limit value to be used in transaction if for some reason
 /// gas limit was not set for it.
 ///
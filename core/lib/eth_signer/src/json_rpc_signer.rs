@@ -226,7 +226,7 @@ impl JsonRpcSigner {
     }
 }
 
-mod messages {
+pub(crate) mod messages {
     use hex::encode;
     use serde::{Deserialize, Serialize};
     use zksync_types::{
@@ -244,7 +244,7 @@ mod messages {
     }
 
     impl JsonRpcRequest {
-        fn create(method: impl ToString, params: Vec<serde_json::Value>) -> Self {
+        pub(crate) fn create(method: impl ToString, params: Vec<serde_json::Value>) -> Self {
             Self {
                 id: "1".to_owned(),
                 jsonrpc: "2.0".to_owned(),
@@ -2,6 +2,7 @@ use async_trait::async_trait;
 use error::SignerError;
 pub use json_rpc_signer::JsonRpcSigner;
 pub use pk_signer::PrivateKeySigner;
+pub use remote_signer::RemoteSigner;
 use zksync_types::{Address, EIP712TypedStructure, Eip712Domain, PackedEthSignature};
 
 pub use crate::raw_ethereum_tx::TransactionParameters;
@@ -10,6 +11,7 @@ pub mod error;
 pub mod json_rpc_signer;
 pub mod pk_signer;
 pub mod raw_ethereum_tx;
+pub mod remote_signer;
 
 #[async_trait]
 pub trait EthereumSigner: 'static + Send + Sync + Clone {
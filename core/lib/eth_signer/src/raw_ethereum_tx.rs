@@ -73,7 +73,35 @@ pub struct Transaction {
     pub blob_versioned_hashes: Option<Vec<H256>>,
 }
 
+impl From<TransactionParameters> for Transaction {
+    fn from(raw_tx: TransactionParameters) -> Self {
+        Self {
+            to: raw_tx.to,
+            nonce: raw_tx.nonce,
+            gas: raw_tx.gas,
+            // According to the code in web3 <https://docs.rs/web3/latest/src/web3/api/accounts.rs.html#86>
+            // We should use `max_fee_per_gas` as `gas_price` if we use EIP1559
+            gas_price: raw_tx.max_fee_per_gas,
+            value: raw_tx.value,
+            data: raw_tx.data,
+            transaction_type: raw_tx.transaction_type,
+            access_list: raw_tx.access_list.unwrap_or_default(),
+            max_priority_fee_per_gas: raw_tx.max_priority_fee_per_gas,
+            max_fee_per_blob_gas: raw_tx.max_fee_per_blob_gas,
+            blob_versioned_hashes: raw_tx.blob_versioned_hashes,
+        }
+    }
+}
+
 impl Transaction {
+    /// Checks whether this is a legacy transaction, i.e., one with a replay-protected `v` signature value (EIP-155).
+    pub(crate) fn is_legacy(&self) -> bool {
+        matches!(
+            self.transaction_type.map(|t| t.as_u64()),
+            Some(LEGACY_TX_ID) | None
+        )
+    }
+
     fn rlp_append_legacy(&self, stream: &mut RlpStream) {
         stream.append(&self.nonce);
         stream.append(&self.gas_price);
@@ -212,7 +240,7 @@ impl Transaction {
         }
     }
 
-    fn encode(&self, chain_id: u64, signature: Option<&Signature>) -> Vec<u8> {
+    pub(crate) fn encode(&self, chain_id: u64, signature: Option<&Signature>) -> Vec<u8> {
         match self.transaction_type.map(|t| t.as_u64()) {
             Some(LEGACY_TX_ID) | None => {
                 let stream = self.encode_legacy(chain_id, signature);
@@ -245,10 +273,7 @@ impl Transaction {
 
     /// Sign and return a raw signed transaction.
     pub fn sign(self, sign: impl signing::Key, chain_id: u64) -> SignedTransaction {
-        let adjust_v_value = matches!(
-            self.transaction_type.map(|t| t.as_u64()),
-            Some(LEGACY_TX_ID) | None
-        );
+        let adjust_v_value = self.is_legacy();
 
         let encoded = self.encode(chain_id, None);
 
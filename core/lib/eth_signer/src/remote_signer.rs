@@ -0,0 +1,609 @@
+//! Signer delegating signing to a remote service, such as web3signer or a KMS / HSM proxy.
+//!
+//! The signer communicates with the service via JSON-RPC (`eth_signTransaction` and `eth_signTypedData_v3` methods).
+//! If a request signing key is configured, each request is authenticated with the following headers:
+//!
+//! - `X-Request-Timestamp`: UNIX timestamp of the request in seconds.
+//! - `X-Request-Signature`: hex-encoded 65-byte Ethereum signature of `keccak256(timestamp || body)`, where
+//!   `timestamp` is the decimal value of the timestamp header and `body` is the raw request body.
+//!
+//! The service can recover the address of the request signing key from the signature and check it against
+//! an allowlist. Note that the request signing key only authenticates requests; the keys used to sign transactions
+//! never leave the remote service.
+//!
+//! Responses of the service are not trusted: signed transactions are checked to match the requested parameters
+//! and to be signed by the signer account, and typed data signatures are checked to be produced by the signer account.
+
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use jsonrpc_core::types::response::Output;
+use rlp::Rlp;
+use serde_json::Value;
+use zksync_types::{
+    web3::signing::{self, Signature},
+    Address, EIP712TypedStructure, Eip712Domain, PackedEthSignature, H256, U256, U64,
+};
+
+use crate::{
+    error::{RpcSignerError, SignerError},
+    json_rpc_signer::{is_signature_from_address, messages::JsonRpcRequest},
+    raw_ethereum_tx::{Transaction, TransactionParameters},
+    EthereumSigner,
+};
+
+/// Name of the header with the request timestamp.
+pub const TIMESTAMP_HEADER: &str = "X-Request-Timestamp";
+/// Name of the header with the request signature.
+pub const SIGNATURE_HEADER: &str = "X-Request-Signature";
+
+/// Returns the bytes signed to authenticate a request to the remote signer.
+pub fn request_signed_bytes(timestamp: u64, body: &[u8]) -> H256 {
+    let mut message = timestamp.to_string().into_bytes();
+    message.extend_from_slice(body);
+    PackedEthSignature::message_to_signed_bytes(&message)
+}
+
+/// Signer delegating signing to a remote service. The signer can be configured with multiple service URLs;
+/// if a URL is unreachable or responds with a non-OK HTTP status, the next URL is tried. The URL that
+/// has successfully served the last request is tried first for subsequent requests.
+///
+/// The signer is cheaply cloneable; all clones share the HTTP client and the failover state.
+#[derive(Clone)]
+pub struct RemoteSigner {
+    inner: Arc<RemoteSignerInner>,
+}
+
+struct RemoteSignerInner {
+    urls: Vec<reqwest::Url>,
+    client: reqwest::Client,
+    address: Address,
+    request_signing_key: Option<H256>,
+    active_url_index: AtomicUsize,
+}
+
+impl fmt::Debug for RemoteSigner {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // We do not want to have the request signing key in the debug representation.
+        formatter
+            .debug_struct("RemoteSigner")
+            .field("urls", &self.inner.urls)
+            .field("address", &self.inner.address)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RemoteSigner {
+    /// Creates a signer for the specified account. URLs must use HTTPS, unless they point to a loopback host.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no URLs are provided, or a URL is invalid or uses plain HTTP for a non-loopback host.
+    pub fn new(
+        urls: &[String],
+        address: Address,
+        request_signing_key: Option<H256>,
+        request_timeout: Duration,
+    ) -> Result<Self, SignerError> {
+        if urls.is_empty() {
+            return Err(SignerError::CustomError(
+                "no URLs provided for the remote signer".to_owned(),
+            ));
+        }
+        let urls = urls
+            .iter()
+            .map(|url| Self::parse_url(url))
+            .collect::<Result<_, _>>()?;
+        let client = reqwest::Client::builder()
+            .timeout(request_timeout)
+            .build()
+            .map_err(|err| SignerError::CustomError(err.to_string()))?;
+
+        Ok(Self {
+            inner: Arc::new(RemoteSignerInner {
+                urls,
+                client,
+                address,
+                request_signing_key,
+                active_url_index: AtomicUsize::new(0),
+            }),
+        })
+    }
+
+    fn parse_url(url: &str) -> Result<reqwest::Url, SignerError> {
+        let url: reqwest::Url = url
+            .parse()
+            .map_err(|err| SignerError::CustomError(format!("invalid signer URL: {err}")))?;
+        let is_loopback = matches!(url.host_str(), Some("localhost" | "127.0.0.1" | "[::1]"));
+        if url.scheme() != "https" && !is_loopback {
+            return Err(SignerError::CustomError(format!(
+                "signer URL `{url}` must use HTTPS"
+            )));
+        }
+        Ok(url)
+    }
+
+    /// Returns the address of the account managed by this signer.
+    pub fn address(&self) -> Address {
+        self.inner.address
+    }
+
+    fn transaction_json(&self, raw_tx: TransactionParameters) -> Value {
+        let mut tx = serde_json::json!({
+            "from": self.inner.address,
+            "gas": raw_tx.gas,
+            "maxFeePerGas": raw_tx.max_fee_per_gas,
+            "maxPriorityFeePerGas": raw_tx.max_priority_fee_per_gas,
+            "value": raw_tx.value,
+            "data": format!("0x{}", hex::encode(&raw_tx.data)),
+            "nonce": raw_tx.nonce,
+            "chainId": U64::from(raw_tx.chain_id),
+        });
+        if let Some(to) = raw_tx.to {
+            tx["to"] = serde_json::json!(to);
+        }
+        if let Some(transaction_type) = raw_tx.transaction_type {
+            tx["type"] = serde_json::json!(transaction_type);
+        }
+        if let Some(access_list) = raw_tx.access_list {
+            tx["accessList"] = serde_json::json!(access_list);
+        }
+        if let Some(max_fee_per_blob_gas) = raw_tx.max_fee_per_blob_gas {
+            tx["maxFeePerBlobGas"] = serde_json::json!(max_fee_per_blob_gas);
+        }
+        if let Some(blob_versioned_hashes) = raw_tx.blob_versioned_hashes {
+            tx["blobVersionedHashes"] = serde_json::json!(blob_versioned_hashes);
+        }
+        tx
+    }
+
+    /// Checks that `signed_tx` returned by the remote service is the requested transaction signed by the signer account.
+    fn verify_signed_transaction(
+        &self,
+        requested_tx: TransactionParameters,
+        signed_tx: &[u8],
+    ) -> Result<(), SignerError> {
+        let chain_id = requested_tx.chain_id;
+        let tx = Transaction::from(requested_tx);
+        let signature = extract_signature(signed_tx)?;
+
+        // Re-encoding the requested transaction with the returned signature checks all transaction fields
+        // (nonce, recipient, calldata, fees, chain ID etc.) against the request.
+        if tx.encode(chain_id, Some(&signature)) != signed_tx {
+            return Err(SignerError::SigningFailed(
+                "RemoteSigner returned a transaction differing from the requested one".to_owned(),
+            ));
+        }
+
+        let recovery_id = if tx.is_legacy() {
+            // EIP-155 replay-protected signature
+            chain_id
+                .checked_mul(2)
+                .and_then(|offset| signature.v.checked_sub(offset + 35))
+        } else {
+            Some(signature.v)
+        };
+        let recovery_id = recovery_id.filter(|&id| id <= 1).ok_or_else(|| {
+            SignerError::RecoverAddress(format!("invalid signature `v` value: {}", signature.v))
+        })?;
+        let message_hash = signing::keccak256(&tx.encode(chain_id, None));
+        let mut rs = [0_u8; 64];
+        rs[..32].copy_from_slice(signature.r.as_bytes());
+        rs[32..].copy_from_slice(signature.s.as_bytes());
+        let signer = signing::recover(&message_hash, &rs, recovery_id as i32)
+            .map_err(|err| SignerError::RecoverAddress(err.to_string()))?;
+        if signer != self.address() {
+            return Err(SignerError::SigningFailed(format!(
+                "transaction returned by RemoteSigner is signed by {signer:?} instead of {:?}",
+                self.address()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Performs a POST query to the remote signer, failing over to other URLs on network errors.
+    /// RPC errors returned by the signer are not retried.
+    async fn post(&self, message: &JsonRpcRequest) -> Result<Value, RpcSignerError> {
+        let body = serde_json::to_vec(message)
+            .map_err(|err| RpcSignerError::MalformedResponse(err.to_string()))?;
+        let url_count = self.inner.urls.len();
+        let start_index = self.inner.active_url_index.load(Ordering::Relaxed);
+
+        let mut errors = Vec::with_capacity(url_count);
+        for i in 0..url_count {
+            let index = (start_index + i) % url_count;
+            let url = &self.inner.urls[index];
+            match self.post_to(url, body.clone()).await {
+                Ok(reply) => {
+                    self.inner.active_url_index.store(index, Ordering::Relaxed);
+                    return match reply {
+                        Output::Success(success) => Ok(success.result),
+                        Output::Failure(failure) => Err(RpcSignerError::RpcError(failure)),
+                    };
+                }
+                Err(err) => errors.push(format!("{url}: {err}")),
+            }
+        }
+        Err(RpcSignerError::NetworkError(format!(
+            "all signer URLs failed: {}",
+            errors.join("; ")
+        )))
+    }
+
+    async fn post_to(&self, url: &reqwest::Url, body: Vec<u8>) -> Result<Output, RpcSignerError> {
+        let mut request = self
+            .inner
+            .client
+            .post(url.clone())
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+        if let Some(key) = &self.inner.request_signing_key {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("system time is before UNIX epoch")
+                .as_secs();
+            let signed_bytes = request_signed_bytes(timestamp, &body);
+            let signature = PackedEthSignature::sign_raw(key, &signed_bytes)
+                .map_err(|err| RpcSignerError::NetworkError(err.to_string()))?;
+            request = request.header(TIMESTAMP_HEADER, timestamp).header(
+                SIGNATURE_HEADER,
+                format!("0x{}", hex::encode(signature.serialize_packed())),
+            );
+        }
+
+        let res = request
+            .body(body)
+            .send()
+            .await
+            .map_err(|err| RpcSignerError::NetworkError(err.to_string()))?;
+        if res.status() != reqwest::StatusCode::OK {
+            let error = format!(
+                "Post query responded with a non-OK response: {}",
+                res.status()
+            );
+            return Err(RpcSignerError::NetworkError(error));
+        }
+        res.json()
+            .await
+            .map_err(|err| RpcSignerError::MalformedResponse(err.to_string()))
+    }
+}
+
+/// Extracts the signature from an RLP-encoded signed transaction (either legacy or typed, per EIP-2718).
+fn extract_signature(signed_tx: &[u8]) -> Result<Signature, SignerError> {
+    let payload = match signed_tx.first() {
+        // Legacy transactions are encoded as an RLP list.
+        Some(&first_byte) if first_byte >= 0xc0 => signed_tx,
+        // Typed transactions are prefixed with the transaction type.
+        Some(_) => &signed_tx[1..],
+        None => {
+            return Err(SignerError::DecodeRawTxFailed(
+                "empty signed transaction".to_owned(),
+            ))
+        }
+    };
+    let decode_err = |err: rlp::DecoderError| SignerError::DecodeRawTxFailed(err.to_string());
+
+    let rlp = Rlp::new(payload);
+    let item_count = rlp.item_count().map_err(decode_err)?;
+    if item_count < 3 {
+        return Err(SignerError::DecodeRawTxFailed(format!(
+            "signed transaction has too few fields ({item_count})"
+        )));
+    }
+    let v: u64 = rlp.val_at(item_count - 3).map_err(decode_err)?;
+    let r: U256 = rlp.val_at(item_count - 2).map_err(decode_err)?;
+    let s: U256 = rlp.val_at(item_count - 1).map_err(decode_err)?;
+    let to_h256 = |value: U256| {
+        let mut bytes = [0_u8; 32];
+        value.to_big_endian(&mut bytes);
+        H256(bytes)
+    };
+    Ok(Signature {
+        v,
+        r: to_h256(r),
+        s: to_h256(s),
+    })
+}
+
+#[async_trait::async_trait]
+impl EthereumSigner for RemoteSigner {
+    /// Signs typed struct by EIP-712 signature standard. The signature returned by the remote signer
+    /// is checked to be produced by the signer account.
+    async fn sign_typed_data<S: EIP712TypedStructure + Sync>(
+        &self,
+        eip712_domain: &Eip712Domain,
+        typed_struct: &S,
+    ) -> Result<PackedEthSignature, SignerError> {
+        let message = JsonRpcRequest::sign_typed_data(self.address(), eip712_domain, typed_struct);
+        let ret = self
+            .post(&message)
+            .await
+            .map_err(|err| SignerError::SigningFailed(err.to_string()))?;
+        let signature: PackedEthSignature = serde_json::from_value(ret)
+            .map_err(|err| SignerError::SigningFailed(err.to_string()))?;
+
+        let signed_bytes =
+            PackedEthSignature::typed_data_to_signed_bytes(eip712_domain, typed_struct);
+        if is_signature_from_address(&signature, &signed_bytes, self.address())? {
+            Ok(signature)
+        } else {
+            Err(SignerError::SigningFailed(
+                "Invalid signature from RemoteSigner".to_string(),
+            ))
+        }
+    }
+
+    /// Signs and returns the RLP-encoded transaction. The transaction returned by the remote signer
+    /// is checked to match `raw_tx` and to be signed by the signer account.
+    async fn sign_transaction(
+        &self,
+        raw_tx: TransactionParameters,
+    ) -> Result<Vec<u8>, SignerError> {
+        let tx = self.transaction_json(raw_tx.clone());
+        let message = JsonRpcRequest::create("eth_signTransaction", vec![tx]);
+        let ret = self
+            .post(&message)
+            .await
+            .map_err(|err| SignerError::SigningFailed(err.to_string()))?;
+
+        // web3signer returns the raw transaction as a string, while geth-like signers return an object
+        // with the `raw` field.
+        let raw_tx = match &ret {
+            Value::String(raw_tx) => Some(raw_tx.as_str()),
+            Value::Object(object) => object.get("raw").and_then(Value::as_str),
+            _ => None,
+        };
+        let raw_tx = raw_tx.ok_or_else(|| {
+            SignerError::DecodeRawTxFailed(format!("unexpected signer response: {ret}"))
+        })?;
+        let signed_tx = raw_tx.strip_prefix("0x").unwrap_or(raw_tx);
+        let signed_tx = hex::decode(signed_tx)
+            .map_err(|err| SignerError::DecodeRawTxFailed(err.to_string()))?;
+        self.verify_signed_transaction(raw_tx, &signed_tx)?;
+        Ok(signed_tx)
+    }
+
+    async fn get_address(&self) -> Result<Address, SignerError> {
+        Ok(self.address())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::IntoFuture, net::SocketAddr};
+
+    use axum::{
+        body::Bytes,
+        extract::State,
+        http::{HeaderMap, StatusCode},
+        routing::post,
+        Json, Router,
+    };
+    use futures::future::{AbortHandle, Abortable};
+    use jsonrpc_core::{Id, Success, Version};
+    use serde_json::json;
+
+    use super::*;
+    use crate::PrivateKeySigner;
+
+    #[derive(Debug)]
+    struct ServerState {
+        signer_address: Address,
+        request_signer: Address,
+        /// Key used by the server to sign transactions.
+        signing_key: H256,
+        /// Transaction signed by the server regardless of the request.
+        signed_tx: TransactionParameters,
+    }
+
+    impl ServerState {
+        fn new(signing_key: H256, request_signing_key: H256) -> Self {
+            Self {
+                signer_address: PackedEthSignature::address_from_private_key(&signing_key).unwrap(),
+                request_signer: PackedEthSignature::address_from_private_key(&request_signing_key)
+                    .unwrap(),
+                signing_key,
+                signed_tx: blob_tx(),
+            }
+        }
+    }
+
+    async fn handle(
+        State(state): State<Arc<ServerState>>,
+        headers: HeaderMap,
+        body: Bytes,
+    ) -> Result<Json<Value>, StatusCode> {
+        let timestamp: u64 = headers
+            .get(TIMESTAMP_HEADER)
+            .and_then(|value| value.to_str().ok()?.parse().ok())
+            .ok_or(StatusCode::UNAUTHORIZED)?;
+        let signature = headers
+            .get(SIGNATURE_HEADER)
+            .and_then(|value| hex::decode(&value.to_str().ok()?[2..]).ok())
+            .and_then(|bytes| PackedEthSignature::deserialize_packed(&bytes).ok())
+            .ok_or(StatusCode::UNAUTHORIZED)?;
+        let signed_bytes = request_signed_bytes(timestamp, &body);
+        if signature.signature_recover_signer(&signed_bytes).ok() != Some(state.request_signer) {
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+
+        let request: JsonRpcRequest =
+            serde_json::from_slice(&body).map_err(|_| StatusCode::BAD_REQUEST)?;
+        assert_eq!(request.method, "eth_signTransaction");
+        let tx = &request.params[0];
+        assert_eq!(tx["from"], json!(state.signer_address));
+        assert_eq!(tx["type"], json!("0x3"));
+        assert_eq!(tx["blobVersionedHashes"], json!([H256::repeat_byte(1)]));
+
+        let signed_tx = PrivateKeySigner::new(state.signing_key)
+            .sign_transaction(state.signed_tx.clone())
+            .await
+            .unwrap();
+        let output = Output::Success(Success {
+            jsonrpc: Some(Version::V2),
+            result: json!(format!("0x{}", hex::encode(signed_tx))),
+            id: Id::Num(1),
+        });
+        Ok(Json(json!(output)))
+    }
+
+    async fn run_server(state: ServerState) -> (String, AbortHandle) {
+        let app = Router::new()
+            .route("/", post(handle))
+            .with_state(Arc::new(state));
+        let server = axum::Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0)))
+            .serve(app.into_make_service());
+        let address = format!("http://{}/", server.local_addr());
+
+        let (abort_handle, abort_registration) = AbortHandle::new_pair();
+        tokio::spawn(Abortable::new(server.into_future(), abort_registration));
+        (address, abort_handle)
+    }
+
+    fn blob_tx() -> TransactionParameters {
+        TransactionParameters {
+            nonce: 5.into(),
+            to: Some(Address::repeat_byte(0x11)),
+            gas: 100_000.into(),
+            data: vec![1, 2, 3],
+            chain_id: 9,
+            max_fee_per_gas: 2_000.into(),
+            max_priority_fee_per_gas: 100.into(),
+            transaction_type: Some(3.into()),
+            max_fee_per_blob_gas: Some(1_000.into()),
+            blob_versioned_hashes: Some(vec![H256::repeat_byte(1)]),
+            ..TransactionParameters::default()
+        }
+    }
+
+    #[test]
+    fn insecure_urls_are_rejected() {
+        let timeout = Duration::from_secs(1);
+        RemoteSigner::new(&[], Address::zero(), None, timeout).unwrap_err();
+        let urls = ["http://signer.example.com/".to_owned()];
+        RemoteSigner::new(&urls, Address::zero(), None, timeout).unwrap_err();
+
+        let urls = [
+            "https://signer.example.com/".to_owned(),
+            "http://127.0.0.1:8080/".to_owned(),
+        ];
+        RemoteSigner::new(&urls, Address::zero(), None, timeout).unwrap();
+    }
+
+    #[tokio::test]
+    async fn signing_transactions_with_failover() {
+        let request_signing_key = H256::repeat_byte(0x17);
+        let signing_key = H256::repeat_byte(0x23);
+        let state = ServerState::new(signing_key, request_signing_key);
+        let signer_address = state.signer_address;
+        let (address, abort_handle) = run_server(state).await;
+
+        // The first URL is unreachable, so the signer should fail over to the second one.
+        let urls = ["http://127.0.0.1:1/".to_owned(), address];
+        let signer = RemoteSigner::new(
+            &urls,
+            signer_address,
+            Some(request_signing_key),
+            Duration::from_secs(5),
+        )
+        .unwrap();
+        let raw_tx = signer.sign_transaction(blob_tx()).await.unwrap();
+        let expected_raw_tx = PrivateKeySigner::new(signing_key)
+            .sign_transaction(blob_tx())
+            .await
+            .unwrap();
+        assert_eq!(raw_tx, expected_raw_tx);
+        assert_eq!(signer.inner.active_url_index.load(Ordering::Relaxed), 1);
+
+        // Requests with an invalid signature should be rejected by the server.
+        let signer = RemoteSigner::new(
+            &urls[1..],
+            signer_address,
+            Some(H256::repeat_byte(0x42)),
+            Duration::from_secs(5),
+        )
+        .unwrap();
+        let err = signer.sign_transaction(blob_tx()).await.unwrap_err();
+        assert!(err.to_string().contains("401"), "{err}");
+
+        abort_handle.abort();
+    }
+
+    #[tokio::test]
+    async fn signed_transactions_are_verified() {
+        let request_signing_key = H256::repeat_byte(0x17);
+        let signing_key = H256::repeat_byte(0x23);
+
+        // The server signs a transaction with another nonce.
+        let mut state = ServerState::new(signing_key, request_signing_key);
+        let signer_address = state.signer_address;
+        state.signed_tx.nonce += U256::one();
+        let (address, abort_handle) = run_server(state).await;
+        let signer = RemoteSigner::new(
+            &[address],
+            signer_address,
+            Some(request_signing_key),
+            Duration::from_secs(5),
+        )
+        .unwrap();
+        let err = signer.sign_transaction(blob_tx()).await.unwrap_err();
+        let SignerError::SigningFailed(message) = &err else {
+            panic!("unexpected error: {err}");
+        };
+        assert!(
+            message.contains("differing from the requested"),
+            "{message}"
+        );
+        abort_handle.abort();
+
+        // The server signs the transaction with a key not matching the signer account.
+        let mut state = ServerState::new(H256::repeat_byte(0x42), request_signing_key);
+        state.signer_address = signer_address;
+        let (address, abort_handle) = run_server(state).await;
+        let signer = RemoteSigner::new(
+            &[address],
+            signer_address,
+            Some(request_signing_key),
+            Duration::from_secs(5),
+        )
+        .unwrap();
+        let err = signer.sign_transaction(blob_tx()).await.unwrap_err();
+        let SignerError::SigningFailed(message) = &err else {
+            panic!("unexpected error: {err}");
+        };
+        assert!(message.contains("instead of"), "{message}");
+        abort_handle.abort();
+    }
+
+    #[tokio::test]
+    async fn verifying_legacy_transaction() {
+        let signing_key = H256::repeat_byte(0x23);
+        let address = PackedEthSignature::address_from_private_key(&signing_key).unwrap();
+        let urls = ["http://127.0.0.1:1/".to_owned()];
+        let signer = RemoteSigner::new(&urls, address, None, Duration::from_secs(1)).unwrap();
+        let tx = TransactionParameters {
+            transaction_type: None,
+            max_fee_per_blob_gas: None,
+            blob_versioned_hashes: None,
+            ..blob_tx()
+        };
+        let signed_tx = PrivateKeySigner::new(signing_key)
+            .sign_transaction(tx.clone())
+            .await
+            .unwrap();
+        signer
+            .verify_signed_transaction(tx.clone(), &signed_tx)
+            .unwrap();
+
+        let tx_for_other_chain = TransactionParameters { chain_id: 10, ..tx };
+        signer
+            .verify_signed_transaction(tx_for_other_chain, &signed_tx)
+            .unwrap_err();
+    }
+}
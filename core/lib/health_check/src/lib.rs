@@ -30,6 +30,9 @@ pub enum HealthStatus {
     Ready,
     /// Component is affected by some non-fatal issue. The component is still considered healthy.
     Affected,
+    /// Component is operational, but cannot currently keep up with the load (e.g., it's saturated with requests).
+    /// The component is considered unhealthy, so that load balancers can route traffic elsewhere until it recovers.
+    Degraded,
     /// Component has received a termination request and is in the process of shutting down.
     /// Components that shut down instantly may skip this status and proceed directly to [`Self::ShutDown`].
     ShuttingDown,
@@ -49,10 +52,11 @@ impl HealthStatus {
         match self {
             Self::Ready => 0,
             Self::Affected => 1,
-            Self::ShuttingDown => 2,
-            Self::ShutDown => 3,
-            Self::NotReady => 4,
-            Self::Panicked => 5,
+            Self::Degraded => 2,
+            Self::ShuttingDown => 3,
+            Self::ShutDown => 4,
+            Self::NotReady => 5,
+            Self::Panicked => 6,
         }
     }
 }
@@ -79,6 +83,11 @@ impl Health {
     pub fn status(&self) -> HealthStatus {
         self.status
     }
+
+    /// Returns component-specific health details, if any.
+    pub fn details(&self) -> Option<&serde_json::Value> {
+        self.details.as_ref()
+    }
 }
 
 impl From<HealthStatus> for Health {
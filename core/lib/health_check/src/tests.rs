@@ -120,6 +120,14 @@ async fn aggregating_health_checks() {
         HealthStatus::Affected
     );
 
+    second_updater.update(HealthStatus::Degraded.into());
+
+    let app_health = checks.check_health().await;
+    assert!(!app_health.is_healthy());
+    assert_matches!(app_health.inner.status(), HealthStatus::Degraded);
+    assert_matches!(app_health.components["first"].status, HealthStatus::Ready);
+
+    second_updater.update(HealthStatus::Affected.into());
     drop(first_updater);
 
     let app_health = checks.check_health().await;
@@ -4,17 +4,24 @@ use zksync_types::{
     l1::L1Tx, l2::L2Tx, Address, ExecuteTransactionCommon, Nonce, PriorityOpId, Transaction,
 };
 
-use crate::types::{AccountTransactions, L2TxFilter, MempoolScore};
+use crate::types::{AccountTransactions, L2TxFilter, MempoolScore, MempoolScoringPolicy};
 
 #[derive(Debug)]
 pub struct MempoolInfo {
     pub stashed_accounts: Vec<Address>,
     pub purged_accounts: Vec<Address>,
+    /// Subset of stashed accounts evicted because of the `max_l2_transactions` limit.
+    pub evicted_accounts: Vec<Address>,
+    /// Whether L2 transactions can be inserted into the mempool without triggering eviction.
+    pub has_l2_room: bool,
 }
 
 #[derive(Debug)]
 pub struct MempoolStats {
     pub l1_transaction_count: usize,
+    /// Timestamp (in milliseconds since UNIX epoch) at which the next priority operation to be executed
+    /// was received. `None` if there is no such operation in the mempool.
+    pub next_l1_transaction_received_timestamp_ms: Option<u64>,
     pub l2_transaction_count: u64,
     pub l2_priority_queue_size: usize,
 }
@@ -30,9 +37,18 @@ pub struct MempoolStore {
     /// Next priority operation
     next_priority_id: PriorityOpId,
     stashed_accounts: Vec<Address>,
+    /// Accounts without executable transactions purged during eviction since the last
+    /// [`Self::get_mempool_info()`] call.
+    purged_accounts: Vec<Address>,
+    /// Accounts evicted because of the `max_l2_transactions` limit since the last [`Self::get_mempool_info()`] call.
+    evicted_accounts: Vec<Address>,
     /// Number of L2 transactions in the mempool.
     size: u64,
+    /// Soft limit on the number of L2 transactions; if reached, accounts without executable transactions are purged.
     capacity: u64,
+    /// Hard limit on the number of L2 transactions; if exceeded, accounts with the lowest score are evicted.
+    max_l2_transactions: Option<u64>,
+    scoring_policy: MempoolScoringPolicy,
 }
 
 impl MempoolStore {
@@ -43,20 +59,41 @@ impl MempoolStore {
             l2_priority_queue: BTreeSet::new(),
             next_priority_id,
             stashed_accounts: vec![],
+            purged_accounts: vec![],
+            evicted_accounts: vec![],
             size: 0,
             capacity,
+            max_l2_transactions: None,
+            scoring_policy: MempoolScoringPolicy::default(),
         }
     }
 
+    /// Sets the policy used to prioritize L2 transactions.
+    pub fn with_scoring_policy(mut self, policy: MempoolScoringPolicy) -> Self {
+        self.scoring_policy = policy;
+        self
+    }
+
+    /// Sets the hard limit on the number of L2 transactions in the mempool. Once the limit is exceeded,
+    /// the mempool purges accounts without executable transactions, and then evicts accounts
+    /// with the lowest-scored executable transactions. Evicted accounts are reported as stashed
+    /// in [`MempoolInfo`], so that their transactions are retained in the storage and can be loaded later.
+    pub fn with_max_l2_transactions(mut self, max_l2_transactions: u64) -> Self {
+        self.max_l2_transactions = Some(max_l2_transactions);
+        self
+    }
+
     /// Inserts batch of new transactions to mempool
     /// `initial_nonces` provides current committed nonce information to mempool
     /// variable is used only if account is not present in mempool yet and we have to bootstrap it
     /// in other cases mempool relies on state keeper and its internal state to keep that info up to date
+    ///
+    /// Returns the number of L2 transactions evicted from the mempool as a result of the insertion.
     pub fn insert(
         &mut self,
         transactions: Vec<Transaction>,
         initial_nonces: HashMap<Address, Nonce>,
-    ) {
+    ) -> u64 {
         for transaction in transactions {
             let Transaction {
                 common_data,
@@ -93,6 +130,7 @@ impl MempoolStore {
                 }
             }
         }
+        self.evict()
     }
 
     fn insert_l2_transaction(
@@ -102,13 +140,14 @@ impl MempoolStore {
     ) {
         let account = transaction.initiator_account();
 
+        let policy = &self.scoring_policy;
         let metadata = match self.l2_transactions_per_account.entry(account) {
-            hash_map::Entry::Occupied(mut txs) => txs.get_mut().insert(transaction),
+            hash_map::Entry::Occupied(mut txs) => txs.get_mut().insert(transaction, policy),
             hash_map::Entry::Vacant(entry) => {
                 let account_nonce = initial_nonces.get(&account).cloned().unwrap_or(Nonce(0));
                 entry
                     .insert(AccountTransactions::new(account_nonce))
-                    .insert(transaction)
+                    .insert(transaction, policy)
             }
         };
         if let Some(score) = metadata.previous_score {
@@ -134,11 +173,19 @@ impl MempoolStore {
 
     /// Returns next transaction for execution from mempool
     pub fn next_transaction(&mut self, filter: &L2TxFilter) -> Option<Transaction> {
-        if let Some(transaction) = self.l1_transactions.remove(&self.next_priority_id) {
-            self.next_priority_id += 1;
-            return Some(transaction.into());
-        }
+        self.next_l1_transaction()
+            .or_else(|| self.next_l2_transaction(filter))
+    }
+
+    /// Returns the next priority operation for execution from mempool, skipping L2 transactions.
+    pub fn next_l1_transaction(&mut self) -> Option<Transaction> {
+        let transaction = self.l1_transactions.remove(&self.next_priority_id)?;
+        self.next_priority_id += 1;
+        Some(transaction.into())
+    }
 
+    /// Returns the next L2 transaction for execution from mempool, skipping priority operations.
+    pub fn next_l2_transaction(&mut self, filter: &L2TxFilter) -> Option<Transaction> {
         let mut removed = 0;
         // We want to fetch the next transaction that would match the fee requirements.
         let tx_pointer = self
@@ -167,7 +214,7 @@ impl MempoolStore {
             .l2_transactions_per_account
             .get_mut(&tx_pointer.account)
             .expect("mempool: dangling pointer in priority queue")
-            .next();
+            .next(&self.scoring_policy);
 
         if let Some(score) = score {
             self.l2_priority_queue.insert(score);
@@ -189,11 +236,12 @@ impl MempoolStore {
                 // reset next priority id
                 self.next_priority_id = self.next_priority_id.min(data.serial_id);
             }
-            ExecuteTransactionCommon::L2(_) => {
+            ExecuteTransactionCommon::L2(data) => {
+                // The account may have been evicted from the mempool while its transaction was executed.
                 if let Some(score) = self
                     .l2_transactions_per_account
-                    .get_mut(&tx.initiator_account())
-                    .expect("account is not available in mempool")
+                    .entry(tx.initiator_account())
+                    .or_insert_with(|| AccountTransactions::new(data.nonce))
                     .reset(tx)
                 {
                     self.l2_priority_queue.remove(&score);
@@ -205,16 +253,39 @@ impl MempoolStore {
         }
     }
 
+    /// Handles a transaction rejected by the state keeper. Works similarly to [`Self::rollback()`], but additionally
+    /// lowers the reputation of the transaction initiator, which is accounted for in the scores
+    /// of its subsequent transactions.
+    pub fn reject(&mut self, tx: &Transaction) {
+        self.rollback(tx);
+        if let Some(account_txs) = self
+            .l2_transactions_per_account
+            .get_mut(&tx.initiator_account())
+        {
+            account_txs.penalize();
+        }
+    }
+
     pub fn get_mempool_info(&mut self) -> MempoolInfo {
+        let mut purged_accounts = std::mem::take(&mut self.purged_accounts);
+        purged_accounts.extend(self.gc());
         MempoolInfo {
             stashed_accounts: std::mem::take(&mut self.stashed_accounts),
-            purged_accounts: self.gc(),
+            purged_accounts,
+            evicted_accounts: std::mem::take(&mut self.evicted_accounts),
+            has_l2_room: self
+                .max_l2_transactions
+                .map_or(true, |max_l2_transactions| self.size < max_l2_transactions),
         }
     }
 
     pub fn stats(&self) -> MempoolStats {
         MempoolStats {
             l1_transaction_count: self.l1_transactions.len(),
+            next_l1_transaction_received_timestamp_ms: self
+                .l1_transactions
+                .get(&self.next_priority_id)
+                .map(|tx| tx.received_timestamp_ms),
             l2_transaction_count: self.size,
             l2_priority_queue_size: self.l2_priority_queue.len(),
         }
@@ -222,22 +293,63 @@ impl MempoolStore {
 
     fn gc(&mut self) -> Vec<Address> {
         if self.size >= self.capacity {
-            let index: HashSet<_> = self
-                .l2_priority_queue
-                .iter()
-                .map(|pointer| pointer.account)
-                .collect();
-            let transactions = std::mem::take(&mut self.l2_transactions_per_account);
-            let (kept, drained) = transactions
-                .into_iter()
-                .partition(|(address, _)| index.contains(address));
-            self.l2_transactions_per_account = kept;
-            self.size = self
-                .l2_transactions_per_account
-                .iter()
-                .fold(0, |agg, (_, tnxs)| agg + tnxs.len() as u64);
-            return drained.into_keys().collect();
+            return self.purge_non_executable_accounts();
         }
         vec![]
     }
+
+    /// Enforces `max_l2_transactions` limit. Returns the number of evicted transactions.
+    fn evict(&mut self) -> u64 {
+        let Some(max_l2_transactions) = self.max_l2_transactions else {
+            return 0;
+        };
+        if self.size <= max_l2_transactions {
+            return 0;
+        }
+
+        let initial_size = self.size;
+        // Consistently with `gc()`, accounts without executable transactions are purged.
+        let purged_accounts = self.purge_non_executable_accounts();
+        self.purged_accounts.extend(purged_accounts);
+        while self.size > max_l2_transactions {
+            let Some(pointer) = self.l2_priority_queue.pop_first() else {
+                break;
+            };
+            let removed = self
+                .l2_transactions_per_account
+                .remove(&pointer.account)
+                .expect("mempool: dangling pointer in priority queue")
+                .len();
+            self.size -= removed as u64;
+            // Evicted transactions are executable, so they must not be removed from the storage.
+            self.stashed_accounts.push(pointer.account);
+            self.evicted_accounts.push(pointer.account);
+        }
+
+        let evicted = initial_size - self.size;
+        tracing::debug!(
+            "Evicted {evicted} L2 transactions from mempool; {} transactions left",
+            self.size
+        );
+        evicted
+    }
+
+    /// Removes all accounts without transactions in the priority queue. Returns addresses of the removed accounts.
+    fn purge_non_executable_accounts(&mut self) -> Vec<Address> {
+        let index: HashSet<_> = self
+            .l2_priority_queue
+            .iter()
+            .map(|pointer| pointer.account)
+            .collect();
+        let transactions = std::mem::take(&mut self.l2_transactions_per_account);
+        let (kept, drained) = transactions
+            .into_iter()
+            .partition(|(address, _)| index.contains(address));
+        self.l2_transactions_per_account = kept;
+        self.size = self
+            .l2_transactions_per_account
+            .iter()
+            .fold(0, |agg, (_, tnxs)| agg + tnxs.len() as u64);
+        drained.into_keys().collect()
+    }
 }
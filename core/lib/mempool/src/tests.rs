@@ -12,7 +12,10 @@ use zksync_types::{
     H256, U256,
 };
 
-use crate::{mempool_store::MempoolStore, types::L2TxFilter};
+use crate::{
+    mempool_store::MempoolStore,
+    types::{L2TxFilter, MempoolScoringPolicy},
+};
 
 #[test]
 fn basic_flow() {
@@ -117,6 +120,36 @@ fn prioritize_l1_txns() {
         .is_l1())
 }
 
+#[test]
+fn skipping_l1_txns() {
+    let mut mempool = MempoolStore::new(PriorityOpId(0), 100);
+    let account = Address::random();
+    let transactions = vec![
+        gen_l2_tx(account, Nonce(0)),
+        gen_l1_tx(PriorityOpId(0)),
+        gen_l1_tx(PriorityOpId(1)),
+    ];
+    mempool.insert(transactions, HashMap::new());
+
+    let tx = mempool.next_l2_transaction(&L2TxFilter::default()).unwrap();
+    assert!(!tx.is_l1());
+    assert!(mempool
+        .next_l2_transaction(&L2TxFilter::default())
+        .is_none());
+    assert_eq!(mempool.stats().l1_transaction_count, 2);
+
+    for idx in 0..2 {
+        let data = mempool.next_l1_transaction().unwrap().common_data;
+        match data {
+            ExecuteTransactionCommon::L1(data) => {
+                assert_eq!(data.serial_id, PriorityOpId(idx));
+            }
+            _ => unreachable!("expected L1 transaction"),
+        }
+    }
+    assert!(mempool.next_l1_transaction().is_none());
+}
+
 #[test]
 fn l1_txns_priority_id() {
     let mut mempool = MempoolStore::new(PriorityOpId(0), 100);
@@ -365,6 +398,168 @@ fn mempool_capacity() {
     );
 }
 
+#[test]
+fn scoring_by_fee() {
+    let policy = MempoolScoringPolicy {
+        fee_weight: 1,
+        ..MempoolScoringPolicy::default()
+    };
+    let mut mempool = MempoolStore::new(PriorityOpId(0), 100).with_scoring_policy(policy);
+    let account0 = Address::random();
+    let account1 = Address::random();
+    let account2 = Address::random();
+    let transactions = vec![
+        gen_l2_tx_with_fee(account0, Nonce(0), RECEIVED_AT_MS, 10),
+        gen_l2_tx_with_fee(account1, Nonce(0), RECEIVED_AT_MS + 1_000, 100),
+        // Fee advantage is offset by the age disadvantage.
+        gen_l2_tx_with_fee(account2, Nonce(0), RECEIVED_AT_MS + 20_000, 20),
+    ];
+    mempool.insert(transactions, HashMap::new());
+
+    assert_eq!(
+        view(mempool.next_transaction(&L2TxFilter::default())),
+        (account1, 0)
+    );
+    assert_eq!(
+        view(mempool.next_transaction(&L2TxFilter::default())),
+        (account0, 0)
+    );
+    assert_eq!(
+        view(mempool.next_transaction(&L2TxFilter::default())),
+        (account2, 0)
+    );
+}
+
+#[test]
+fn rejected_senders_are_deprioritized() {
+    let policy = MempoolScoringPolicy {
+        rejection_penalty: 100,
+        ..MempoolScoringPolicy::default()
+    };
+    let mut mempool = MempoolStore::new(PriorityOpId(0), 100).with_scoring_policy(policy);
+    let account0 = Address::random();
+    let account1 = Address::random();
+    let transactions = vec![
+        gen_l2_tx_with_timestamp(account0, Nonce(0), RECEIVED_AT_MS),
+        gen_l2_tx_with_timestamp(account0, Nonce(1), RECEIVED_AT_MS),
+        gen_l2_tx_with_timestamp(account1, Nonce(0), RECEIVED_AT_MS + 1_000),
+    ];
+    mempool.insert(transactions, HashMap::new());
+    assert_eq!(
+        view(mempool.next_transaction(&L2TxFilter::default())),
+        (account0, 0)
+    );
+    mempool.reject(&gen_l2_tx_with_timestamp(
+        account0,
+        Nonce(0),
+        RECEIVED_AT_MS,
+    ));
+
+    // Replacement transaction is scored with the rejection penalty.
+    mempool.insert(
+        vec![gen_l2_tx_with_timestamp(account0, Nonce(0), RECEIVED_AT_MS)],
+        HashMap::new(),
+    );
+    assert_eq!(
+        view(mempool.next_transaction(&L2TxFilter::default())),
+        (account1, 0)
+    );
+    assert_eq!(
+        view(mempool.next_transaction(&L2TxFilter::default())),
+        (account0, 0)
+    );
+    assert_eq!(
+        view(mempool.next_transaction(&L2TxFilter::default())),
+        (account0, 1)
+    );
+}
+
+#[test]
+fn evicting_lowest_scored_accounts() {
+    let policy = MempoolScoringPolicy {
+        fee_weight: 1,
+        ..MempoolScoringPolicy::default()
+    };
+    let mut mempool = MempoolStore::new(PriorityOpId(0), 100)
+        .with_scoring_policy(policy)
+        .with_max_l2_transactions(3);
+    let account0 = Address::random();
+    let account1 = Address::random();
+    let account2 = Address::random();
+    let account3 = Address::random();
+    let transactions = vec![
+        gen_l2_tx_with_fee(account0, Nonce(0), RECEIVED_AT_MS, 10),
+        gen_l2_tx_with_fee(account0, Nonce(1), RECEIVED_AT_MS, 10),
+        gen_l2_tx_with_fee(account1, Nonce(0), RECEIVED_AT_MS + 1_000, 1),
+        // Non-executable transaction; should be purged first.
+        gen_l2_tx_with_fee(account2, Nonce(5), RECEIVED_AT_MS, 1_000),
+    ];
+    let evicted = mempool.insert(transactions, HashMap::new());
+    assert_eq!(evicted, 1);
+    assert_eq!(mempool.stats().l2_transaction_count, 3);
+
+    let transactions = vec![gen_l2_tx_with_fee(
+        account3,
+        Nonce(0),
+        RECEIVED_AT_MS + 2_000,
+        1_000,
+    )];
+    let evicted = mempool.insert(transactions, HashMap::new());
+    assert_eq!(evicted, 1);
+    assert_eq!(mempool.stats().l2_transaction_count, 3);
+    let mempool_info = mempool.get_mempool_info();
+    // The executable transaction of `account1` must be retained in the storage.
+    assert_eq!(mempool_info.stashed_accounts, [account1]);
+    assert_eq!(mempool_info.purged_accounts, [account2]);
+    assert_eq!(mempool_info.evicted_accounts, [account1]);
+    assert!(!mempool_info.has_l2_room);
+
+    assert_eq!(
+        view(mempool.next_transaction(&L2TxFilter::default())),
+        (account3, 0)
+    );
+    assert!(mempool.get_mempool_info().has_l2_room);
+    assert_eq!(
+        view(mempool.next_transaction(&L2TxFilter::default())),
+        (account0, 0)
+    );
+    assert_eq!(
+        view(mempool.next_transaction(&L2TxFilter::default())),
+        (account0, 1)
+    );
+    assert_eq!(mempool.next_transaction(&L2TxFilter::default()), None);
+}
+
+#[test]
+fn rolling_back_tx_of_purged_account() {
+    let mut mempool = MempoolStore::new(PriorityOpId(0), 2);
+    let account0 = Address::random();
+    let account1 = Address::random();
+    mempool.insert(
+        vec![gen_l2_tx_with_timestamp(account0, Nonce(3), RECEIVED_AT_MS)],
+        HashMap::from([(account0, Nonce(3))]),
+    );
+    let tx = mempool.next_transaction(&L2TxFilter::default()).unwrap();
+    mempool.insert(
+        vec![
+            gen_l2_tx_with_timestamp(account1, Nonce(0), RECEIVED_AT_MS + 1_000),
+            gen_l2_tx_with_timestamp(account1, Nonce(1), RECEIVED_AT_MS + 1_000),
+        ],
+        HashMap::new(),
+    );
+    // `account0` has no transactions left, so it's purged.
+    assert_eq!(mempool.get_mempool_info().purged_accounts, [account0]);
+
+    mempool.rollback(&tx);
+    mempool.insert(vec![tx], HashMap::new());
+    assert_eq!(
+        view(mempool.next_transaction(&L2TxFilter::default())),
+        (account0, 3)
+    );
+}
+
+const RECEIVED_AT_MS: u64 = 1_700_000_000_000;
+
 fn gen_l2_tx(address: Address, nonce: Nonce) -> Transaction {
     gen_l2_tx_with_timestamp(address, nonce, unix_timestamp_ms())
 }
@@ -384,6 +579,20 @@ fn gen_l2_tx_with_timestamp(address: Address, nonce: Nonce, received_at_ms: u64)
     txn.into()
 }
 
+fn gen_l2_tx_with_fee(
+    address: Address,
+    nonce: Nonce,
+    received_at_ms: u64,
+    max_fee_per_gas_mwei: u64,
+) -> Transaction {
+    let mut txn = gen_l2_tx_with_timestamp(address, nonce, received_at_ms);
+    let ExecuteTransactionCommon::L2(data) = &mut txn.common_data else {
+        unreachable!("expected L2 transaction");
+    };
+    data.fee.max_fee_per_gas = U256::from(max_fee_per_gas_mwei) * 1_000_000;
+    txn
+}
+
 fn gen_l1_tx(priority_id: PriorityOpId) -> Transaction {
     let execute = Execute {
         contract_address: Address::repeat_byte(0x11),
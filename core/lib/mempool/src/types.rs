@@ -12,6 +12,11 @@ pub(crate) struct AccountTransactions {
     /// account nonce in mempool
     /// equals to committed nonce in db + number of transactions sent to state keeper
     nonce: Nonce,
+    /// Score of the transaction with the current account nonce (i.e., the one present in the priority queue).
+    /// The score is cached since it depends on the account reputation, which can change over time.
+    queued_score: Option<MempoolScore>,
+    /// Number of transactions of this account rejected by the state keeper.
+    rejected_count: u32,
 }
 
 impl AccountTransactions {
@@ -19,75 +24,127 @@ impl AccountTransactions {
         Self {
             transactions: HashMap::new(),
             nonce,
+            queued_score: None,
+            rejected_count: 0,
         }
     }
 
     /// Inserts new transaction for given account. Returns insertion metadata
-    pub fn insert(&mut self, transaction: L2Tx) -> InsertionMetadata {
+    pub fn insert(
+        &mut self,
+        transaction: L2Tx,
+        policy: &MempoolScoringPolicy,
+    ) -> InsertionMetadata {
         let mut metadata = InsertionMetadata::default();
         let nonce = transaction.common_data.nonce;
         // skip insertion if transaction is old
         if nonce < self.nonce {
             return metadata;
         }
-        let new_score = Self::score_for_transaction(&transaction);
-        let previous_score = self
-            .transactions
-            .insert(nonce, transaction)
-            .map(|tx| Self::score_for_transaction(&tx));
-        metadata.is_new = previous_score.is_none();
+        let new_score = policy.score(&transaction, self.rejected_count);
+        metadata.is_new = self.transactions.insert(nonce, transaction).is_none();
         if nonce == self.nonce {
+            metadata.previous_score = self.queued_score.replace(new_score.clone());
             metadata.new_score = Some(new_score);
-            metadata.previous_score = previous_score;
         }
         metadata
     }
 
     /// Returns next transaction to be included in block and optional score of its successor
     /// Panics if no such transaction exists
-    pub fn next(&mut self) -> (L2Tx, Option<MempoolScore>) {
+    pub fn next(&mut self, policy: &MempoolScoringPolicy) -> (L2Tx, Option<MempoolScore>) {
         let transaction = self
             .transactions
             .remove(&self.nonce)
             .expect("missing transaction in mempool");
         self.nonce += 1;
-        let score = self
+        self.queued_score = self
             .transactions
             .get(&self.nonce)
-            .map(Self::score_for_transaction);
-        (transaction, score)
+            .map(|tx| policy.score(tx, self.rejected_count));
+        (transaction, self.queued_score.clone())
     }
 
-    /// Handles transaction rejection. Returns optional score of its successor
+    /// Handles transaction rollback. Returns optional score of its successor
     pub fn reset(&mut self, transaction: &Transaction) -> Option<MempoolScore> {
         // current nonce for the group needs to be reset
         let tx_nonce = transaction
             .nonce()
             .expect("nonce is not set for L2 transaction");
         self.nonce = self.nonce.min(tx_nonce);
-        self.transactions
-            .get(&(tx_nonce + 1))
-            .map(Self::score_for_transaction)
+        self.queued_score.take()
+    }
+
+    /// Lowers account reputation after one of its transactions was rejected.
+    pub fn penalize(&mut self) {
+        self.rejected_count = self.rejected_count.saturating_add(1);
     }
 
     pub fn len(&self) -> usize {
         self.transactions.len()
     }
+}
+
+/// Policy used to score L2 transactions in mempool. The score of a transaction is a linear combination of
+/// its max fee per gas, its age, and the reputation of its initiator (i.e., the number of rejected transactions
+/// of the initiator that are still tracked by the mempool).
+///
+/// The default policy only accounts for transaction age, i.e., transactions are ordered by their receipt time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MempoolScoringPolicy {
+    /// Score points per 1 Mwei (10^6 wei) of the max fee per gas.
+    pub fee_weight: u64,
+    /// Score points per second of the transaction age.
+    pub age_weight: u64,
+    /// Score points deducted for each rejected transaction of the initiator.
+    pub rejection_penalty: u64,
+}
+
+impl Default for MempoolScoringPolicy {
+    fn default() -> Self {
+        Self {
+            fee_weight: 0,
+            age_weight: 1,
+            rejection_penalty: 0,
+        }
+    }
+}
+
+impl MempoolScoringPolicy {
+    const WEI_PER_MWEI: u64 = 1_000_000;
+
+    pub(crate) fn score(&self, transaction: &L2Tx, rejected_count: u32) -> MempoolScore {
+        let fee_data = &transaction.common_data.fee;
+        let fee_mwei = fee_data.max_fee_per_gas / Self::WEI_PER_MWEI;
+        let fee_mwei = if fee_mwei > U256::from(u64::MAX) {
+            u64::MAX
+        } else {
+            fee_mwei.as_u64()
+        };
+        // The age of a transaction grows uniformly for all transactions, so it's sufficient to account
+        // for the receipt timestamp; this keeps scores static.
+        let received_at_secs = transaction.received_timestamp_ms / 1_000;
+        let priority = i128::from(self.fee_weight) * i128::from(fee_mwei)
+            - i128::from(self.age_weight) * i128::from(received_at_secs)
+            - i128::from(self.rejection_penalty) * i128::from(rejected_count);
 
-    fn score_for_transaction(transaction: &L2Tx) -> MempoolScore {
         MempoolScore {
             account: transaction.initiator_account(),
+            priority,
             received_at_ms: transaction.received_timestamp_ms,
-            fee_data: transaction.common_data.fee.clone(),
+            fee_data: fee_data.clone(),
         }
     }
 }
 
-/// Mempool score of transaction. Used to prioritize L2 transactions in mempool
-/// Currently trivial ordering is used based on received at timestamp
+/// Mempool score of transaction. Used to prioritize L2 transactions in mempool.
+/// Transactions are ordered by priority computed according to [`MempoolScoringPolicy`]; ties are broken
+/// by the receipt timestamp (older transactions first).
 #[derive(Eq, PartialEq, Clone, Debug, Hash)]
 pub struct MempoolScore {
     pub account: Address,
+    /// Priority of the transaction; greater values are executed first.
+    pub priority: i128,
     pub received_at_ms: u64,
     // Not used for actual scoring, but state keeper would request
     // transactions that have acceptable fee values (so transactions
@@ -105,6 +162,10 @@ impl MempoolScore {
 
 impl Ord for MempoolScore {
     fn cmp(&self, other: &MempoolScore) -> Ordering {
+        match self.priority.cmp(&other.priority) {
+            Ordering::Equal => {}
+            ordering => return ordering,
+        }
         match self.received_at_ms.cmp(&other.received_at_ms).reverse() {
             Ordering::Equal => {}
             ordering => return ordering,
@@ -159,6 +220,7 @@ mod tests {
 
         let score = MempoolScore {
             account: Address::random(),
+            priority: 0,                        // Not important
             received_at_ms: Default::default(), // Not important
             fee_data: Fee {
                 gas_limit: Default::default(), // Not important
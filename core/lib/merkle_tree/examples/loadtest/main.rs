@@ -68,6 +68,9 @@ struct Cli {
     /// Enables tree pruning.
     #[arg(long = "prune", conflicts_with = "in_memory")]
     prune: bool,
+    /// Collects a per-level load report for the specified number of RocksDB patches and logs it.
+    #[arg(long = "profile-load", conflicts_with = "in_memory")]
+    profile_load: Option<usize>,
 }
 
 impl Cli {
@@ -105,6 +108,9 @@ impl Cli {
             if let Some(chunk_size) = self.chunk_size {
                 rocksdb.set_multi_get_chunk_size(chunk_size);
             }
+            if let Some(patch_count) = self.profile_load {
+                rocksdb.profile_load(patch_count);
+            }
 
             if self.prune {
                 let (mut pruner, pruner_handle) = MerkleTreePruner::new(rocksdb.clone(), 0);
@@ -1,6 +1,12 @@
 //! Consistency verification for the Merkle tree.
 
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::{
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
 
 use rayon::prelude::*;
 
@@ -106,6 +112,87 @@ impl<DB: Database, H: HashTree> MerkleTree<DB, H> {
         Ok(())
     }
 
+    /// Verifies the consistency of a single subtree as stored in the database. The subtree is the one
+    /// containing `key` and rooted at the tree level `depth` (measured in nibbles). Compared to
+    /// [`Self::verify_consistency()`], this method is cheap enough to be called periodically
+    /// on a large tree.
+    ///
+    /// In addition to hash consistency, all leaves in the subtree are checked to have unique non-zero indices
+    /// not exceeding the leaf count of the tree. Since only a part of the tree is traversed, it is not checked
+    /// that leaf indices are sequential.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error (the first encountered one if there are multiple).
+    pub fn verify_subtree_consistency(
+        &self,
+        version: u64,
+        key: Key,
+        depth: usize,
+    ) -> Result<(), ConsistencyError> {
+        let manifest = self.db.try_manifest()?;
+        let manifest = manifest.ok_or(ConsistencyError::MissingVersion(version))?;
+        if version >= manifest.version_count {
+            return Err(ConsistencyError::MissingVersion(version));
+        }
+
+        let root = self
+            .db
+            .try_root(version)?
+            .ok_or(ConsistencyError::MissingRoot(version))?;
+        let (leaf_count, mut node) = match root {
+            Root::Empty => return Ok(()),
+            Root::Filled { leaf_count, node } => (leaf_count.get(), node),
+        };
+        let leaf_data = LeafConsistencyData::sparse(leaf_count);
+
+        // Descend to the subtree root along `key`. Internal nodes on the path are not validated themselves;
+        // we only check that the subtree hash matches the reference in its parent.
+        let mut node_key = Nibbles::EMPTY.with_version(version);
+        for nibble_idx in 0..depth {
+            let Node::Internal(internal_node) = &node else {
+                break; // The tree consists of a single leaf
+            };
+            let nibble = Nibbles::nibble(&key, nibble_idx);
+            let Some(child_ref) = internal_node.child_ref(nibble).copied() else {
+                return Ok(()); // The subtree is empty
+            };
+            let child_key = node_key
+                .nibbles
+                .push(nibble)
+                .ok_or(ConsistencyError::TerminalInternalNode { key: node_key })?;
+            let child_key = child_key.with_version(child_ref.version);
+            let child = self
+                .db
+                .try_tree_node(&child_key, child_ref.is_leaf)?
+                .ok_or(ConsistencyError::MissingNode {
+                    key: child_key,
+                    is_leaf: child_ref.is_leaf,
+                })?;
+
+            if nibble_idx + 1 < depth && !child_ref.is_leaf {
+                node = child;
+                node_key = child_key;
+                continue;
+            }
+            let child_hash = self.validate_node(&child, child_key, Some(&leaf_data))?;
+            return if child_hash == child_ref.hash {
+                Ok(())
+            } else {
+                Err(ConsistencyError::HashMismatch {
+                    key: node_key,
+                    nibble,
+                    expected: child_ref.hash,
+                    actual: child_hash,
+                })
+            };
+        }
+
+        // Either `depth == 0`, or the tree consists of a single leaf.
+        self.validate_node(&node, node_key, Some(&leaf_data))?;
+        Ok(())
+    }
+
     fn validate_node(
         &self,
         node: &Node,
@@ -187,7 +274,7 @@ impl<DB: Database, H: HashTree> MerkleTree<DB, H> {
 struct LeafConsistencyData {
     expected_leaf_count: u64,
     actual_leaf_count: AtomicU64,
-    leaf_indices_set: AtomicBitSet,
+    leaf_indices_set: LeafIndicesSet,
 }
 
 #[allow(clippy::cast_possible_truncation)] // expected leaf count is quite small
@@ -196,7 +283,18 @@ impl LeafConsistencyData {
         Self {
             expected_leaf_count,
             actual_leaf_count: AtomicU64::new(0),
-            leaf_indices_set: AtomicBitSet::new(expected_leaf_count as usize),
+            leaf_indices_set: LeafIndicesSet::Dense(AtomicBitSet::new(
+                expected_leaf_count as usize,
+            )),
+        }
+    }
+
+    /// Creates leaf data for a subtree, which is expected to contain a small fraction of all tree leaves.
+    fn sparse(expected_leaf_count: u64) -> Self {
+        Self {
+            expected_leaf_count,
+            actual_leaf_count: AtomicU64::new(0),
+            leaf_indices_set: LeafIndicesSet::Sparse(Mutex::default()),
         }
     }
 
@@ -214,8 +312,7 @@ impl LeafConsistencyData {
             });
         }
 
-        let index = (leaf.leaf_index - 1) as usize;
-        if self.leaf_indices_set.set(index) {
+        if self.leaf_indices_set.insert(leaf.leaf_index) {
             return Err(ConsistencyError::DuplicateLeafIndex {
                 index: leaf.leaf_index,
                 full_key: leaf.full_key,
@@ -238,6 +335,29 @@ impl LeafConsistencyData {
     }
 }
 
+/// Set of leaf indices encountered during tree traversal.
+#[derive(Debug)]
+enum LeafIndicesSet {
+    /// Used when traversing the entire tree.
+    Dense(AtomicBitSet),
+    /// Used when traversing a subtree.
+    Sparse(Mutex<HashSet<u64>>),
+}
+
+impl LeafIndicesSet {
+    /// Returns `true` if the index was already present in the set.
+    #[allow(clippy::cast_possible_truncation)] // expected leaf count is quite small
+    fn insert(&self, leaf_index: u64) -> bool {
+        match self {
+            Self::Dense(bits) => bits.set((leaf_index - 1) as usize),
+            Self::Sparse(indices) => !indices
+                .lock()
+                .expect("leaf indices set is poisoned")
+                .insert(leaf_index),
+        }
+    }
+}
+
 /// Primitive atomic bit set implementation that only supports setting bits.
 #[derive(Debug)]
 struct AtomicBitSet {
@@ -516,4 +636,59 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn basic_subtree_consistency_checks() {
+        let tree = MerkleTree::new(prepare_database());
+        for depth in [0, 1, 2, 5, 10, 16, 64] {
+            for key in [FIRST_KEY, SECOND_KEY, U256::zero()] {
+                tree.verify_subtree_consistency(0, key, depth).unwrap();
+            }
+        }
+
+        let err = tree
+            .verify_subtree_consistency(1, FIRST_KEY, 1)
+            .unwrap_err();
+        assert_matches!(err, ConsistencyError::MissingVersion(1));
+    }
+
+    #[test]
+    fn subtree_hash_mismatch_error() {
+        let mut db = prepare_database();
+        for (_, node) in db.nodes_mut() {
+            if let Node::Leaf(leaf) = node {
+                leaf.value_hash = ValueHash::zero();
+            }
+        }
+        let tree = MerkleTree::new(db);
+
+        let err = tree
+            .verify_subtree_consistency(0, FIRST_KEY, 1)
+            .unwrap_err();
+        assert_matches!(
+            err,
+            ConsistencyError::HashMismatch { key, nibble: 0xd, .. } if key == NodeKey::empty(0)
+        );
+        // The subtree doesn't contain the corrupted leaves.
+        tree.verify_subtree_consistency(0, U256::zero(), 1).unwrap();
+    }
+
+    #[test]
+    fn subtree_duplicate_leaf_index_error() {
+        let mut db = prepare_database();
+        for (_, node) in db.nodes_mut() {
+            if let Node::Leaf(leaf) = node {
+                leaf.leaf_index = 1;
+            }
+        }
+        let tree = MerkleTree::new(db);
+
+        let err = tree
+            .verify_subtree_consistency(0, FIRST_KEY, 1)
+            .unwrap_err();
+        assert_matches!(err, ConsistencyError::DuplicateLeafIndex { index: 1, .. });
+        // Each of the leaf-level subtrees contains a single leaf, so the duplicate index cannot be detected.
+        tree.verify_subtree_consistency(0, FIRST_KEY, 16).unwrap();
+        tree.verify_subtree_consistency(0, SECOND_KEY, 16).unwrap();
+    }
 }
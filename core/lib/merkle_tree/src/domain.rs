@@ -1,7 +1,8 @@
 //! Tying the Merkle tree implementation to the problem domain.
 
+use std::{ops, sync::Arc};
+
 use rayon::{ThreadPool, ThreadPoolBuilder};
-use zksync_crypto::hasher::blake2::Blake2Hasher;
 use zksync_prover_interface::inputs::{PrepareBasicCircuitsJob, StorageLogMetadata};
 use zksync_types::{
     writes::{InitialStorageWrite, RepeatedStorageWrite},
@@ -9,12 +10,14 @@ use zksync_types::{
 };
 
 use crate::{
+    pruning::VersionGuards,
     storage::{PatchSet, Patched, RocksDBWrapper},
     types::{
         Key, Root, TreeEntry, TreeEntryWithProof, TreeInstruction, TreeLogEntry, ValueHash,
         TREE_DEPTH,
     },
-    BlockOutput, HashTree, MerkleTree, NoVersionError,
+    BlockOutput, ConsistencyError, Database, HashTree, MerkleTree, MerkleTreePruner,
+    MerkleTreePrunerHandle, NoVersionError, TreeHasherKind,
 };
 
 /// Metadata for the current tree state.
@@ -47,9 +50,10 @@ enum TreeMode {
 /// or discarded via [`Self::reset()`].
 #[derive(Debug)]
 pub struct ZkSyncTree {
-    tree: MerkleTree<Patched<RocksDBWrapper>>,
+    tree: MerkleTree<Patched<RocksDBWrapper>, TreeHasherKind>,
     thread_pool: Option<ThreadPool>,
     mode: TreeMode,
+    version_guards: Arc<VersionGuards>,
 }
 
 impl ZkSyncTree {
@@ -97,18 +101,64 @@ impl ZkSyncTree {
     }
 
     fn new_with_mode(db: RocksDBWrapper, mode: TreeMode) -> Self {
+        let hasher = Self::stored_hasher(&db).unwrap_or_default();
         Self {
-            tree: MerkleTree::new(Patched::new(db)),
+            tree: MerkleTree::with_hasher(Patched::new(db), hasher),
             thread_pool: None,
             mode,
+            version_guards: Arc::default(),
         }
     }
 
+    /// Returns the hasher recorded in the tree tags, or `None` if the tree doesn't have tags yet.
+    fn stored_hasher(db: &RocksDBWrapper) -> Option<TreeHasherKind> {
+        let tags = db.manifest()?.tags?;
+        let hasher = TreeHasherKind::from_name(&tags.hasher)
+            .unwrap_or_else(|| panic!("Unsupported tree hasher `{}`", tags.hasher));
+        Some(hasher)
+    }
+
+    /// Returns the hasher used by this tree.
+    pub fn hasher(&self) -> TreeHasherKind {
+        self.tree.hasher
+    }
+
+    /// Sets the hasher for this tree. The hasher can only be changed while the tree has no versions;
+    /// afterwards, it is fixed (it is recorded in the tree tags once the tree is saved).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `hasher` differs from the current hasher and the tree has versions.
+    pub fn set_hasher(&mut self, hasher: TreeHasherKind) {
+        if self.tree.hasher == hasher {
+            return;
+        }
+        assert!(
+            self.tree.latest_version().is_none(),
+            "Cannot change hasher from `{}` to `{}` for a non-empty tree",
+            self.tree.hasher.name(),
+            hasher.name()
+        );
+        self.tree.hasher = hasher;
+    }
+
     /// Returns a readonly handle to the tree. The handle **does not** see uncommitted changes to the tree,
     /// only ones flushed to RocksDB.
     pub fn reader(&self) -> ZkSyncTreeReader {
         let db = self.tree.db.inner().clone();
-        ZkSyncTreeReader(MerkleTree::new(db))
+        let tree = MerkleTree::with_hasher(db, self.tree.hasher);
+        ZkSyncTreeReader(tree, self.version_guards.clone())
+    }
+
+    /// Creates a pruner for this tree. The pruner only retains the latest tree version unless limited
+    /// via [`MerkleTreePrunerHandle::set_retained_version_limit()`]. As with the [reader](Self::reader()),
+    /// the pruner only sees changes flushed to RocksDB. The pruner retains tree versions pinned by readers
+    /// for the duration of a read.
+    pub fn pruner(&self) -> (MerkleTreePruner<RocksDBWrapper>, MerkleTreePrunerHandle) {
+        let db = self.tree.db.inner().clone();
+        let (mut pruner, handle) = MerkleTreePruner::new(db, 0);
+        pruner.set_version_guards(self.version_guards.clone());
+        (pruner, handle)
     }
 
     /// Sets the chunk size for multi-get operations. The requested keys will be split
@@ -214,7 +264,7 @@ impl ZkSyncTree {
         for (log, instruction) in output.logs.iter().zip(instructions) {
             let empty_levels_end = TREE_DEPTH - log.merkle_path.len();
             let empty_subtree_hashes =
-                (0..empty_levels_end).map(|i| Blake2Hasher.empty_subtree_hash(i));
+                (0..empty_levels_end).map(|i| self.tree.hasher.empty_subtree_hash(i));
             let merkle_paths = log.merkle_path.iter().copied();
             let merkle_paths = empty_subtree_hashes
                 .chain(merkle_paths)
@@ -401,12 +451,16 @@ impl ZkSyncTree {
 
 /// Readonly handle to a [`ZkSyncTree`].
 #[derive(Debug)]
-pub struct ZkSyncTreeReader(MerkleTree<RocksDBWrapper>);
+pub struct ZkSyncTreeReader(
+    MerkleTree<RocksDBWrapper, TreeHasherKind>,
+    Arc<VersionGuards>,
+);
 
 // While cloning `MerkleTree` is logically unsound, cloning a reader is reasonable since it is readonly.
 impl Clone for ZkSyncTreeReader {
     fn clone(&self) -> Self {
-        Self(MerkleTree::new(self.0.db.clone()))
+        let tree = MerkleTree::with_hasher(self.0.db.clone(), self.0.hasher);
+        Self(tree, self.1.clone())
     }
 }
 
@@ -416,6 +470,12 @@ impl ZkSyncTreeReader {
         self.0.latest_root_hash()
     }
 
+    /// Returns the root hash of this tree after applying the specified L1 batch, or `None`
+    /// if the batch is not applied to the tree yet.
+    pub fn root_hash_for_l1_batch(&self, l1_batch_number: L1BatchNumber) -> Option<ValueHash> {
+        self.0.root_hash(l1_batch_number.0.into())
+    }
+
     /// Returns the next L1 batch number that should be processed by the tree.
     #[allow(clippy::missing_panics_doc)]
     pub fn next_l1_batch_number(&self) -> L1BatchNumber {
@@ -430,6 +490,44 @@ impl ZkSyncTreeReader {
         self.0.latest_root().leaf_count()
     }
 
+    /// Returns the number of leaves in the tree after applying the specified L1 batch, or `None`
+    /// if the corresponding tree version is missing.
+    pub fn leaf_count_for_l1_batch(&self, l1_batch_number: L1BatchNumber) -> Option<u64> {
+        let root = self.0.root(l1_batch_number.0.into())?;
+        Some(root.leaf_count())
+    }
+
+    /// Verifies consistency of the subtree containing `key` and rooted at `depth` nibbles
+    /// for the tree version corresponding to `l1_batch_number`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an inconsistency is detected.
+    pub fn verify_subtree_consistency(
+        &self,
+        l1_batch_number: L1BatchNumber,
+        key: Key,
+        depth: usize,
+    ) -> Result<(), ConsistencyError> {
+        let version = u64::from(l1_batch_number.0);
+        self.0.verify_subtree_consistency(version, key, depth)
+    }
+
+    /// Reads entries with the specified keys from the tree. The entries are returned in the same order
+    /// as requested; entries for missing keys are empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tree `version` is missing.
+    pub fn entries(
+        &self,
+        l1_batch_number: L1BatchNumber,
+        keys: &[Key],
+    ) -> Result<Vec<TreeEntry>, NoVersionError> {
+        let version = u64::from(l1_batch_number.0);
+        self.0.entries(version, keys)
+    }
+
     /// Reads entries together with Merkle proofs with the specified keys from the tree. The entries are returned
     /// in the same order as requested.
     ///
@@ -444,4 +542,68 @@ impl ZkSyncTreeReader {
         let version = u64::from(l1_batch_number.0);
         self.0.entries_with_proofs(version, keys)
     }
+
+    /// Same as [`Self::entries_with_proofs()`], but also returns the root hash of the tree after applying
+    /// the specified L1 batch, which the returned proofs are relative to. The tree version is pinned
+    /// for the duration of the read, so that it cannot be pruned between reading proofs and the root hash.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tree `version` is missing, or is being pruned.
+    pub fn entries_with_proofs_and_root_hash(
+        &self,
+        l1_batch_number: L1BatchNumber,
+        keys: &[Key],
+    ) -> Result<(ValueHash, Vec<TreeEntryWithProof>), NoVersionError> {
+        let version = u64::from(l1_batch_number.0);
+        let _guard = self
+            .1
+            .pin(version)
+            .ok_or_else(|| self.no_version_error(version))?;
+        let root_hash = self
+            .0
+            .root_hash(version)
+            .ok_or_else(|| self.no_version_error(version))?;
+        // The version cannot be pruned while it's pinned, so proofs are consistent with `root_hash`.
+        let entries = self.0.entries_with_proofs(version, keys)?;
+        Ok((root_hash, entries))
+    }
+
+    fn no_version_error(&self, missing_version: u64) -> NoVersionError {
+        NoVersionError {
+            missing_version,
+            version_count: self.0.latest_version().map_or(0, |version| version + 1),
+        }
+    }
+
+    /// Computes the root hash of this tree after applying the specified L1 batch using the specified hasher.
+    /// This is expensive since the entire tree version is re-hashed; see [`MerkleTree::root_hash_with_hasher()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tree version corresponding to `l1_batch_number` is missing.
+    pub fn root_hash_with_hasher(
+        &self,
+        l1_batch_number: L1BatchNumber,
+        hasher: TreeHasherKind,
+    ) -> Result<ValueHash, NoVersionError> {
+        let version = u64::from(l1_batch_number.0);
+        self.0.root_hash_with_hasher(version, hasher.hasher())
+    }
+
+    /// Reads existing entries with keys in the specified range from the tree, in the ascending key order.
+    /// At most `limit` entries are returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tree `version` is missing.
+    pub fn entries_in_range(
+        &self,
+        l1_batch_number: L1BatchNumber,
+        key_range: ops::RangeInclusive<Key>,
+        limit: usize,
+    ) -> Result<Vec<TreeEntry>, NoVersionError> {
+        let version = u64::from(l1_batch_number.0);
+        self.0.entries_in_range(version, key_range, limit)
+    }
 }
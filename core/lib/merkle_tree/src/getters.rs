@@ -1,10 +1,14 @@
 //! Getters for the Merkle tree.
 
+use std::ops;
+
+use rayon::prelude::*;
+
 use crate::{
     hasher::HasherWithStats,
     recovery::MerkleTreeRecovery,
     storage::{LoadAncestorsResult, SortedKeys, WorkingPatchSet},
-    types::{Nibbles, Node, ProfiledTreeOperation, TreeEntry, TreeEntryWithProof},
+    types::{ChildRef, Nibbles, Node, ProfiledTreeOperation, Root, TreeEntry, TreeEntryWithProof},
     Database, HashTree, Key, MerkleTree, NoVersionError, PruneDatabase, ValueHash,
 };
 
@@ -59,21 +63,171 @@ impl<DB: Database, H: HashTree> MerkleTree<DB, H> {
             },
         )
     }
+
+    /// Reads existing entries with keys in the specified `key_range` from the tree. The entries are returned
+    /// in the ascending key order; at most `limit` entries are returned (i.e., the entries with the least keys
+    /// in the range).
+    ///
+    /// Together with proofs for the range bounds obtained via [`Self::entries_with_proofs()`], the returned entries
+    /// allow verifying the range contents using [`TreeRangeDigest`](crate::TreeRangeDigest).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tree `version` is missing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tree is inconsistent, i.e., a node referenced by its parent is missing from the database.
+    pub fn entries_in_range(
+        &self,
+        version: u64,
+        key_range: ops::RangeInclusive<Key>,
+        limit: usize,
+    ) -> Result<Vec<TreeEntry>, NoVersionError> {
+        let _profiling_guard = self
+            .db
+            .start_profiling(ProfiledTreeOperation::GetEntriesInRange);
+        let root = load_root(&self.db, version)?;
+        let mut entries = vec![];
+        if let Root::Filled { node, .. } = root {
+            collect_entries_in_range(
+                &self.db,
+                &node,
+                Nibbles::EMPTY,
+                &key_range,
+                limit,
+                &mut entries,
+            );
+        }
+        Ok(entries)
+    }
+
+    /// Computes the root hash of the specified tree `version` using the provided `hasher` rather than
+    /// the hasher the tree was built with. This allows computing tree commitments for two hash functions
+    /// (dual hashing), e.g., during a migration window to a new hasher.
+    ///
+    /// Unlike [`Self::root_hash()`], this method traverses and re-hashes the entire tree version, so it is
+    /// computationally and I/O-expensive. Top-level subtrees are processed in parallel using `rayon`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tree `version` is missing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tree is inconsistent, i.e., a node referenced by its parent is missing from the database.
+    pub fn root_hash_with_hasher(
+        &self,
+        version: u64,
+        hasher: &dyn HashTree,
+    ) -> Result<ValueHash, NoVersionError> {
+        let root = load_root(&self.db, version)?;
+        let Root::Filled { node, .. } = root else {
+            return Ok(hasher.empty_tree_hash());
+        };
+        Ok(rehash_node(&self.db, hasher, &node, Nibbles::EMPTY))
+    }
 }
 
-fn load_and_transform_entries<T>(
+/// Maximum nibble count of internal nodes for which children are re-hashed in parallel.
+const MAX_PARALLEL_REHASH_NIBBLE_COUNT: usize = 1;
+
+/// Recursively computes the hash of `node` located at `nibbles` using `hasher`.
+fn rehash_node(
     db: &impl Database,
-    version: u64,
-    leaf_keys: &[Key],
-    mut transform: impl FnMut(&mut WorkingPatchSet, &Key, &Nibbles) -> T,
-) -> Result<Vec<T>, NoVersionError> {
-    let root = db.root(version).ok_or_else(|| {
+    hasher: &dyn HashTree,
+    node: &Node,
+    nibbles: Nibbles,
+) -> ValueHash {
+    let level = nibbles.nibble_count() * 4;
+    let Node::Internal(internal_node) = node else {
+        return node.hash(&mut HasherWithStats::new(hasher), level);
+    };
+
+    let rehash_child = |(nibble, child_ref): (u8, &ChildRef)| {
+        let child_nibbles = nibbles.push(nibble).unwrap();
+        // ^ `unwrap()` is safe: internal nodes cannot be at the terminal tree level in a consistent tree
+        let child_key = child_nibbles.with_version(child_ref.version);
+        let child = db
+            .tree_node(&child_key, child_ref.is_leaf)
+            .unwrap_or_else(|| panic!("node at {child_key} is missing from the database"));
+        (nibble, rehash_node(db, hasher, &child, child_nibbles))
+    };
+    let children: Vec<_> = internal_node.children().collect();
+    let child_hashes: Vec<_> = if nibbles.nibble_count() <= MAX_PARALLEL_REHASH_NIBBLE_COUNT {
+        children.into_par_iter().map(rehash_child).collect()
+    } else {
+        children.into_iter().map(rehash_child).collect()
+    };
+
+    let mut rehashed_node = internal_node.clone();
+    for (nibble, child_hash) in child_hashes {
+        rehashed_node.child_ref_mut(nibble).unwrap().hash = child_hash;
+        // ^ `unwrap()` is safe: the child exists by construction
+    }
+    rehashed_node.hash(&mut HasherWithStats::new(hasher), level)
+}
+
+fn load_root(db: &impl Database, version: u64) -> Result<Root, NoVersionError> {
+    db.root(version).ok_or_else(|| {
         let manifest = db.manifest().unwrap_or_default();
         NoVersionError {
             missing_version: version,
             version_count: manifest.version_count,
         }
-    })?;
+    })
+}
+
+/// Performs a depth-first traversal of the tree in the ascending key order, skipping subtrees
+/// that do not intersect with the `key_range`.
+fn collect_entries_in_range(
+    db: &impl Database,
+    node: &Node,
+    nibbles: Nibbles,
+    key_range: &ops::RangeInclusive<Key>,
+    limit: usize,
+    entries: &mut Vec<TreeEntry>,
+) {
+    match node {
+        Node::Leaf(leaf) => {
+            if key_range.contains(&leaf.full_key) && entries.len() < limit {
+                entries.push((*leaf).into());
+            }
+        }
+        Node::Internal(node) => {
+            let child_nibble_count = nibbles.nibble_count() + 1;
+            let start_prefix = Nibbles::new(key_range.start(), child_nibble_count);
+            let end_prefix = Nibbles::new(key_range.end(), child_nibble_count);
+
+            for (nibble, child_ref) in node.children() {
+                if entries.len() >= limit {
+                    return;
+                }
+                let child_nibbles = nibbles.push(nibble).unwrap();
+                // ^ `unwrap()` is safe: internal nodes cannot be at the terminal tree level in a consistent tree
+                if child_nibbles < start_prefix {
+                    continue;
+                } else if child_nibbles > end_prefix {
+                    return;
+                }
+
+                let child_key = child_nibbles.with_version(child_ref.version);
+                let child = db
+                    .tree_node(&child_key, child_ref.is_leaf)
+                    .unwrap_or_else(|| panic!("node at {child_key} is missing from the database"));
+                collect_entries_in_range(db, &child, child_nibbles, key_range, limit, entries);
+            }
+        }
+    }
+}
+
+fn load_and_transform_entries<T>(
+    db: &impl Database,
+    version: u64,
+    leaf_keys: &[Key],
+    mut transform: impl FnMut(&mut WorkingPatchSet, &Key, &Nibbles) -> T,
+) -> Result<Vec<T>, NoVersionError> {
+    let root = load_root(db, version)?;
     let sorted_keys = SortedKeys::new(leaf_keys.iter().copied());
     let mut patch_set = WorkingPatchSet::new(version, root);
     let LoadAncestorsResult {
@@ -116,7 +270,7 @@ impl<DB: PruneDatabase, H: HashTree> MerkleTreeRecovery<DB, H> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::PatchSet;
+    use crate::{PatchSet, TreeRangeDigest};
 
     #[test]
     fn entries_in_empty_tree() {
@@ -153,4 +307,41 @@ mod tests {
         assert!(entries[1].base.is_empty());
         entries[1].verify(&tree.hasher, output.root_hash);
     }
+
+    #[test]
+    fn entries_in_range() {
+        let mut tree = MerkleTree::new(PatchSet::default());
+        let entries: Vec<_> = (1_u64..=100)
+            .map(|i| TreeEntry::new(Key::from(i * 1_000_003), i, ValueHash::from_low_u64_be(i)))
+            .collect();
+        let output = tree.extend(entries.clone());
+
+        let all_entries = tree.entries_in_range(0, Key::zero()..=Key::MAX, usize::MAX);
+        assert_eq!(all_entries.unwrap(), entries);
+
+        let range = Key::from(10_000_000)..=Key::from(50_000_000);
+        let range_entries = tree.entries_in_range(0, range.clone(), usize::MAX).unwrap();
+        let expected_entries: Vec<_> = entries
+            .iter()
+            .filter(|entry| range.contains(&entry.key))
+            .copied()
+            .collect();
+        assert_eq!(range_entries, expected_entries);
+
+        let limited_entries = tree.entries_in_range(0, range.clone(), 5).unwrap();
+        assert_eq!(limited_entries, expected_entries[..5]);
+
+        // Check that the range can be verified using proofs for its bounds.
+        let bounds = tree
+            .entries_with_proofs(0, &[*range.start(), *range.end()])
+            .unwrap();
+        let mut digest = TreeRangeDigest::new(&tree.hasher, *range.start(), &bounds[0]);
+        for entry in range_entries {
+            digest.update(entry);
+        }
+        assert_eq!(digest.finalize(&bounds[1]), output.root_hash);
+
+        let err = tree.entries_in_range(1, range, 5).unwrap_err();
+        assert_eq!(err.missing_version, 1);
+    }
 }
@@ -4,6 +4,7 @@ use std::{fmt, iter};
 
 use once_cell::sync::Lazy;
 use zksync_crypto::hasher::{blake2::Blake2Hasher, Hasher};
+use zksync_types::ProtocolVersionId;
 
 pub(crate) use self::nodes::{InternalNodeCache, MerklePath};
 pub use self::proofs::TreeRangeDigest;
@@ -135,6 +136,72 @@ impl HashTree for Blake2Hasher {
     }
 }
 
+/// Hash functions supported for zkSync trees. The hash function is fixed for a tree instance (it's recorded
+/// in the tree tags and checked when the tree is loaded), but can differ among protocol versions; see
+/// [`Self::for_protocol_version()`].
+///
+/// This type implements [`HashTree`] by delegating to the selected hasher, so it can be used as the hasher
+/// type param for [`MerkleTree`](crate::MerkleTree) if the hasher is only known at runtime.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum TreeHasherKind {
+    /// Blake2s-256 hasher ([`Blake2Hasher`]).
+    #[default]
+    Blake2s256,
+}
+
+impl TreeHasherKind {
+    /// All supported hash functions.
+    pub const ALL: &'static [Self] = &[Self::Blake2s256];
+
+    /// Protocol versions starting from which the corresponding hash function is used, in the ascending
+    /// protocol version order. A new hasher is introduced by appending an entry to this list.
+    const ACTIVATIONS: &'static [(ProtocolVersionId, Self)] =
+        &[(ProtocolVersionId::Version0, Self::Blake2s256)];
+
+    /// Returns the hash function used for trees in the specified protocol version.
+    pub fn for_protocol_version(protocol_version: ProtocolVersionId) -> Self {
+        Self::ACTIVATIONS
+            .iter()
+            .rev()
+            .find_map(|&(activated_at, kind)| (activated_at <= protocol_version).then_some(kind))
+            .unwrap_or_default()
+    }
+
+    /// Looks up a hash function by its [name](HashTree::name()) recorded in the tree tags.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|kind| kind.hasher().name() == name)
+    }
+
+    /// Returns the hasher implementation.
+    pub fn hasher(self) -> &'static dyn HashTree {
+        match self {
+            Self::Blake2s256 => &Blake2Hasher,
+        }
+    }
+}
+
+impl HashTree for TreeHasherKind {
+    fn name(&self) -> &'static str {
+        self.hasher().name()
+    }
+
+    fn hash_leaf(&self, value_hash: &ValueHash, leaf_index: u64) -> ValueHash {
+        self.hasher().hash_leaf(value_hash, leaf_index)
+    }
+
+    fn hash_branch(&self, lhs: &ValueHash, rhs: &ValueHash) -> ValueHash {
+        self.hasher().hash_branch(lhs, rhs)
+    }
+
+    fn empty_subtree_hash(&self, depth: usize) -> ValueHash {
+        self.hasher().empty_subtree_hash(depth)
+    }
+}
+
 fn compute_empty_tree_hashes() -> Vec<ValueHash> {
     let empty_leaf_hash = Blake2Hasher.hash_bytes(&[0_u8; 40]);
     iter::successors(Some(empty_leaf_hash), |hash| {
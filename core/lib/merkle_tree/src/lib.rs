@@ -18,6 +18,10 @@
 //!
 //! - [`Blake2Hasher`] is the main implementation based on Blake2s-256
 //! - `()` provides a no-op implementation useful for benchmarking.
+//! - [`TreeHasherKind`] allows selecting one of supported hashers at runtime (e.g., based on the protocol version).
+//!
+//! The tree can be re-hashed using another hasher via [`MerkleTree::root_hash_with_hasher()`], which allows
+//! computing commitments for two hashers during a migration window.
 //!
 //! # Tree hashing specification
 //!
@@ -49,8 +53,9 @@
 use zksync_crypto::hasher::blake2::Blake2Hasher;
 
 pub use crate::{
+    consistency::ConsistencyError,
     errors::NoVersionError,
-    hasher::{HashTree, TreeRangeDigest},
+    hasher::{HashTree, TreeHasherKind, TreeRangeDigest},
     pruning::{MerkleTreePruner, MerkleTreePrunerHandle},
     storage::{
         Database, MerkleTreeColumnFamily, PatchSet, Patched, PruneDatabase, PrunePatchSet,
@@ -3,11 +3,12 @@
 use std::{
     fmt, ops,
     sync::atomic::{AtomicU64, Ordering},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use vise::{
-    Buckets, EncodeLabelSet, EncodeLabelValue, Family, Gauge, Global, Histogram, Metrics, Unit,
+    Buckets, Counter, EncodeLabelSet, EncodeLabelValue, Family, Gauge, Global, Histogram, Metrics,
+    Unit,
 };
 
 use crate::types::Nibbles;
@@ -242,6 +243,9 @@ struct ApplyPatchMetrics {
     /// Total byte size of nodes included into a RocksDB patch per block, grouped by the key nibble count.
     #[metrics(buckets = BYTE_SIZE_BUCKETS)]
     node_bytes: Family<NibbleCount, Histogram<u64>>,
+    /// Number of stale node keys included into a RocksDB patch per block, grouped by the key nibble count.
+    #[metrics(buckets = NODE_COUNT_BUCKETS)]
+    stale_keys_by_nibble_count: Family<NibbleCount, Histogram<u64>>,
     /// Number of hashes in child references copied from previous tree versions. Allows to estimate
     /// the level of redundancy of the tree.
     #[metrics(buckets = NODE_COUNT_BUCKETS)]
@@ -257,6 +261,8 @@ struct PatchNodeStats {
     count: u64,
     /// Total serialized size of nodes (excluding key sizes).
     bytes: u64,
+    /// Number of stale keys.
+    stale_keys: u64,
 }
 
 #[must_use = "patch metrics should be `report()`ed"]
@@ -282,7 +288,12 @@ impl ApplyPatchStats {
         stats.bytes += node_bytes.len() as u64;
     }
 
-    pub fn report(self) {
+    pub fn update_stale_key(&mut self, key_nibbles: &Nibbles) {
+        let idx = key_nibbles.nibble_count().min(MAX_TRACKED_NIBBLE_COUNT);
+        self.node_stats_by_nibble_count[idx].stale_keys += 1;
+    }
+
+    pub fn report(self, mut load_profile: Option<&mut LoadProfile>) {
         let metrics = &APPLY_PATCH_METRICS;
         let total_node_count = self
             .node_stats_by_nibble_count
@@ -295,13 +306,152 @@ impl ApplyPatchStats {
         for (nibble_count, stats) in node_bytes {
             let label = NibbleCount::new(nibble_count);
             metrics.nodes_by_nibble_count[&label].observe(stats.count);
-            metrics.nodes_by_nibble_count[&label].observe(stats.bytes);
+            metrics.node_bytes[&label].observe(stats.bytes);
+            metrics.stale_keys_by_nibble_count[&label].observe(stats.stale_keys);
+
+            if let Some(profile) = load_profile.as_deref_mut() {
+                let level = &mut profile.levels[nibble_count];
+                level.node_writes += stats.count;
+                level.written_bytes += stats.bytes;
+                level.stale_keys += stats.stale_keys;
+            }
         }
 
         metrics.copied_hashes.observe(self.copied_hashes);
     }
 }
 
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "merkle_tree_load")]
+struct LoadMetrics {
+    /// Number of nodes read from RocksDB, grouped by the key nibble count.
+    node_reads: Family<NibbleCount, Counter>,
+    /// Number of RocksDB block cache hits when reading nodes, grouped by the key nibble count.
+    /// Only collected while load profiling is active.
+    block_cache_hits: Family<NibbleCount, Counter>,
+    /// Number of RocksDB block reads (including ones served from the block cache) when reading nodes,
+    /// grouped by the key nibble count. Only collected while load profiling is active.
+    block_reads: Family<NibbleCount, Counter>,
+}
+
+#[vise::register]
+static LOAD_METRICS: Global<LoadMetrics> = Global::new();
+
+/// Statistics for a single node read operation from RocksDB. All nodes in an operation are assumed
+/// to be on the same tree level (which is how the tree loads nodes).
+#[must_use = "node read stats should be `report()`ed"]
+#[derive(Debug, Default)]
+pub(crate) struct NodeReadStats {
+    pub nibble_count: usize,
+    pub node_count: u64,
+    pub block_cache_hits: AtomicU64,
+    pub block_reads: AtomicU64,
+    /// Whether block cache stats should be collected. This requires enabling RocksDB perf stats,
+    /// so it's only done while load profiling is active.
+    pub collect_block_stats: bool,
+}
+
+impl NodeReadStats {
+    pub fn report(self, load_profile: Option<&mut LoadProfile>) {
+        let label = NibbleCount::new(self.nibble_count);
+        let block_cache_hits = self.block_cache_hits.into_inner();
+        let block_reads = self.block_reads.into_inner();
+        LOAD_METRICS.node_reads[&label].inc_by(self.node_count);
+        LOAD_METRICS.block_cache_hits[&label].inc_by(block_cache_hits);
+        LOAD_METRICS.block_reads[&label].inc_by(block_reads);
+
+        if let Some(profile) = load_profile {
+            let level = &mut profile.levels[self.nibble_count.min(MAX_TRACKED_NIBBLE_COUNT)];
+            level.node_reads += self.node_count;
+            level.block_cache_hits += block_cache_hits;
+            level.block_reads += block_reads;
+        }
+    }
+}
+
+/// Load statistics for a single tree level accumulated in a [`LoadProfile`].
+#[derive(Debug, Default, Clone, Copy)]
+struct LevelLoadStats {
+    node_reads: u64,
+    block_cache_hits: u64,
+    block_reads: u64,
+    node_writes: u64,
+    written_bytes: u64,
+    stale_keys: u64,
+}
+
+/// One-shot load profile accumulated for a certain number of applied patches.
+#[derive(Debug)]
+pub(crate) struct LoadProfile {
+    started_at: Instant,
+    patch_count: usize,
+    remaining_patches: usize,
+    levels: [LevelLoadStats; MAX_TRACKED_NIBBLE_COUNT + 1],
+}
+
+impl LoadProfile {
+    pub fn new(patch_count: usize) -> Self {
+        Self {
+            started_at: Instant::now(),
+            patch_count,
+            remaining_patches: patch_count,
+            levels: [LevelLoadStats::default(); MAX_TRACKED_NIBBLE_COUNT + 1],
+        }
+    }
+
+    /// Records that a patch was applied. Returns `true` if the profile is complete.
+    pub fn record_patch(&mut self) -> bool {
+        self.remaining_patches = self.remaining_patches.saturating_sub(1);
+        self.remaining_patches == 0
+    }
+
+    #[cfg(test)]
+    pub fn total_node_reads(&self) -> u64 {
+        self.levels.iter().map(|level| level.node_reads).sum()
+    }
+
+    #[cfg(test)]
+    pub fn total_node_writes(&self) -> u64 {
+        self.levels.iter().map(|level| level.node_writes).sum()
+    }
+}
+
+impl fmt::Display for LoadProfile {
+    #[allow(clippy::cast_precision_loss)] // Acceptable for reporting
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let applied_patches = self.patch_count - self.remaining_patches;
+        writeln!(
+            formatter,
+            "Merkle tree load report for {applied_patches} patch(es) collected in {:?}:",
+            self.started_at.elapsed()
+        )?;
+        writeln!(
+            formatter,
+            "{:>8} {:>12} {:>12} {:>12} {:>14} {:>12}",
+            "nibbles", "reads", "cache hits", "writes", "written bytes", "stale keys"
+        )?;
+        for (nibble_count, level) in self.levels.iter().enumerate() {
+            let cache_hits = if level.block_reads > 0 {
+                let hit_rate = level.block_cache_hits as f64 / level.block_reads as f64;
+                format!("{:.1}%", hit_rate * 100.0)
+            } else {
+                "-".to_owned()
+            };
+            writeln!(
+                formatter,
+                "{:>8} {:>12} {:>12} {:>12} {:>14} {:>12}",
+                NibbleCount::new(nibble_count).to_string(),
+                level.node_reads,
+                cache_hits,
+                level.node_writes,
+                level.written_bytes,
+                level.stale_keys
+            )?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
 #[metrics(label = "bound", rename_all = "snake_case")]
 enum Bound {
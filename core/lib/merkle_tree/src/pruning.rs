@@ -1,6 +1,14 @@
 //! Tree pruning logic.
 
-use std::{fmt, sync::mpsc, time::Duration};
+use std::{
+    collections::{btree_map, BTreeMap},
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc, Arc, Mutex, MutexGuard,
+    },
+    time::Duration,
+};
 
 use crate::{
     metrics::{PruningStats, PRUNING_TIMINGS},
@@ -14,9 +22,20 @@ use crate::{
 #[derive(Debug)]
 pub struct MerkleTreePrunerHandle {
     aborted_sender: mpsc::Sender<()>,
+    retained_version_limit: Arc<AtomicU64>,
 }
 
 impl MerkleTreePrunerHandle {
+    /// Limits the versions that can be pruned: the pruner will retain `version` and all newer versions
+    /// in addition to versions retained per the `past_versions_to_keep` policy. This allows to implement
+    /// custom retention policies (e.g., based on the tree version age).
+    ///
+    /// By default, there is no limit. Decreasing the limit doesn't restore already pruned versions.
+    pub fn set_retained_version_limit(&self, version: u64) {
+        self.retained_version_limit
+            .store(version, Ordering::Relaxed);
+    }
+
     /// Aborts the pruner that this handle is attached to. If the pruner has already terminated
     /// (e.g., due to a panic), this is a no-op.
     pub fn abort(self) {
@@ -24,6 +43,70 @@ impl MerkleTreePrunerHandle {
     }
 }
 
+/// Tree versions pinned by readers performing multi-step reads (e.g., reading a root hash together with proofs),
+/// shared with the [`MerkleTreePruner`]. A pinned version is retained by the pruner until the pin is released.
+#[derive(Debug, Default)]
+pub(crate) struct VersionGuards(Mutex<VersionGuardsInner>);
+
+#[derive(Debug, Default)]
+struct VersionGuardsInner {
+    /// Number of active guards for each pinned version.
+    pinned_versions: BTreeMap<u64, usize>,
+    /// Versions older than this one may be pruned (or are being pruned), so they cannot be pinned.
+    min_retained_version: u64,
+}
+
+impl VersionGuards {
+    fn lock(&self) -> MutexGuard<'_, VersionGuardsInner> {
+        self.0.lock().expect("version guards are poisoned")
+    }
+
+    /// Pins the specified `version`. Returns `None` if the version may be pruned concurrently.
+    /// Note that pinning doesn't check whether the version exists in the tree.
+    pub(crate) fn pin(self: &Arc<Self>, version: u64) -> Option<VersionGuard> {
+        let mut inner = self.lock();
+        if version < inner.min_retained_version {
+            return None;
+        }
+        *inner.pinned_versions.entry(version).or_default() += 1;
+        Some(VersionGuard {
+            guards: self.clone(),
+            version,
+        })
+    }
+
+    /// Limits `target_retained_version` so that all pinned versions are retained, and prevents pinning
+    /// versions older than the returned version.
+    fn restrict_retained_version(&self, target_retained_version: u64) -> u64 {
+        let mut inner = self.lock();
+        let min_pinned_version = inner.pinned_versions.keys().next().copied();
+        let target_retained_version =
+            target_retained_version.min(min_pinned_version.unwrap_or(u64::MAX));
+        inner.min_retained_version = inner.min_retained_version.max(target_retained_version);
+        target_retained_version
+    }
+}
+
+/// Guard for a tree version pinned via [`VersionGuards::pin()`]. The version is unpinned when the guard is dropped.
+#[must_use = "Version is unpinned once the guard is dropped"]
+#[derive(Debug)]
+pub(crate) struct VersionGuard {
+    guards: Arc<VersionGuards>,
+    version: u64,
+}
+
+impl Drop for VersionGuard {
+    fn drop(&mut self) {
+        let mut inner = self.guards.lock();
+        if let btree_map::Entry::Occupied(mut entry) = inner.pinned_versions.entry(self.version) {
+            *entry.get_mut() -= 1;
+            if *entry.get() == 0 {
+                entry.remove();
+            }
+        }
+    }
+}
+
 /// Component responsible for Merkle tree pruning, i.e. removing nodes not referenced by new versions
 /// of the tree. A pruner should be instantiated using a [`Clone`] of the tree database, possibly
 /// configured and then [`run()`](Self::run()) on its own thread. [`MerkleTreePrunerHandle`] provides
@@ -40,6 +123,8 @@ impl MerkleTreePrunerHandle {
 pub struct MerkleTreePruner<DB> {
     db: DB,
     past_versions_to_keep: u64,
+    retained_version_limit: Arc<AtomicU64>,
+    version_guards: Arc<VersionGuards>,
     target_pruned_key_count: usize,
     poll_interval: Duration,
     aborted_receiver: mpsc::Receiver<()>,
@@ -50,6 +135,7 @@ impl<DB> fmt::Debug for MerkleTreePruner<DB> {
         formatter
             .debug_struct("MerkleTreePruner")
             .field("past_versions_to_keep", &self.past_versions_to_keep)
+            .field("retained_version_limit", &self.retained_version_limit)
             .field("target_pruned_key_count", &self.target_pruned_key_count)
             .field("poll_interval", &self.poll_interval)
             .finish_non_exhaustive()
@@ -66,10 +152,16 @@ impl<DB: PruneDatabase> MerkleTreePruner<DB> {
     /// is dropped.*
     pub fn new(db: DB, past_versions_to_keep: u64) -> (Self, MerkleTreePrunerHandle) {
         let (aborted_sender, aborted_receiver) = mpsc::channel();
-        let handle = MerkleTreePrunerHandle { aborted_sender };
+        let retained_version_limit = Arc::new(AtomicU64::new(u64::MAX));
+        let handle = MerkleTreePrunerHandle {
+            aborted_sender,
+            retained_version_limit: retained_version_limit.clone(),
+        };
         let this = Self {
             db,
             past_versions_to_keep,
+            retained_version_limit,
+            version_guards: Arc::default(),
             target_pruned_key_count: 500_000,
             poll_interval: Duration::from_secs(60),
             aborted_receiver,
@@ -77,6 +169,11 @@ impl<DB: PruneDatabase> MerkleTreePruner<DB> {
         (this, handle)
     }
 
+    /// Makes this pruner retain versions pinned via the specified `version_guards`.
+    pub(crate) fn set_version_guards(&mut self, version_guards: Arc<VersionGuards>) {
+        self.version_guards = version_guards;
+    }
+
     /// Sets the target number of stale keys pruned on a single iteration. This limits the size of
     /// a produced RocksDB `WriteBatch` and the RAM consumption of the pruner. At the same time,
     /// larger values can lead to more efficient RocksDB compaction.
@@ -97,7 +194,13 @@ impl<DB: PruneDatabase> MerkleTreePruner<DB> {
     fn target_retained_version(&self) -> Option<u64> {
         let manifest = self.db.manifest()?;
         let latest_version = manifest.version_count.checked_sub(1)?;
-        latest_version.checked_sub(self.past_versions_to_keep)
+        let target_version = latest_version.checked_sub(self.past_versions_to_keep)?;
+        let retained_version_limit = self.retained_version_limit.load(Ordering::Relaxed);
+        let target_version = target_version.min(retained_version_limit);
+        Some(
+            self.version_guards
+                .restrict_retained_version(target_version),
+        )
     }
 
     #[doc(hidden)] // Used in integration tests; logically private
@@ -233,6 +336,61 @@ mod tests {
         }
     }
 
+    #[test]
+    fn pruner_with_retained_version_limit() {
+        let mut db = create_db();
+        let (mut pruner, handle) = MerkleTreePruner::new(&mut db, 0);
+        handle.set_retained_version_limit(2);
+
+        let stats = pruner.run_once().unwrap();
+        assert_eq!(stats.deleted_stale_key_versions, 1..3);
+        assert_eq!(stats.target_retained_version, 2);
+        assert!(!stats.has_more_work());
+        assert!(pruner.run_once().is_none());
+
+        handle.set_retained_version_limit(10);
+        let stats = pruner.run_once().unwrap();
+        assert_eq!(stats.deleted_stale_key_versions, 3..5);
+        assert_eq!(stats.target_retained_version, 4);
+
+        for version in 0..4 {
+            assert!(db.root_mut(version).is_none());
+        }
+        assert!(db.root_mut(4).is_some());
+    }
+
+    #[test]
+    fn pruner_with_pinned_version() {
+        let mut db = create_db();
+        let (mut pruner, _handle) = MerkleTreePruner::new(&mut db, 0);
+        let version_guards = Arc::<VersionGuards>::default();
+        pruner.set_version_guards(version_guards.clone());
+
+        let guard = version_guards.pin(2).unwrap();
+        let stats = pruner.run_once().unwrap();
+        assert_eq!(stats.deleted_stale_key_versions, 1..3);
+        assert_eq!(stats.target_retained_version, 2);
+        assert!(pruner.run_once().is_none());
+        // Versions that may be pruned cannot be pinned.
+        assert!(version_guards.pin(1).is_none());
+        let other_guard = version_guards.pin(3).unwrap();
+
+        drop(guard);
+        let stats = pruner.run_once().unwrap();
+        assert_eq!(stats.deleted_stale_key_versions, 3..4);
+        assert_eq!(stats.target_retained_version, 3);
+        assert!(version_guards.pin(2).is_none());
+
+        drop(other_guard);
+        let stats = pruner.run_once().unwrap();
+        assert_eq!(stats.deleted_stale_key_versions, 4..5);
+        assert_eq!(stats.target_retained_version, 4);
+        for version in 0..4 {
+            assert!(db.root_mut(version).is_none());
+        }
+        assert!(db.root_mut(4).is_some());
+    }
+
     #[test]
     fn pruner_is_aborted_immediately_when_requested() {
         let (mut pruner, pruner_handle) = MerkleTreePruner::new(PatchSet::default(), 0);
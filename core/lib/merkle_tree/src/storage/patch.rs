@@ -2,7 +2,7 @@
 
 use std::{
     collections::{hash_map::Entry, HashMap},
-    iter,
+    iter, mem,
     time::Instant,
 };
 
@@ -171,6 +171,15 @@ impl WorkingNode {
     }
 }
 
+/// Changes in a subtree rooted at a child of the root node produced by [`WorkingPatchSet::finalize_subtree()`].
+#[derive(Debug, Default)]
+struct FinalizedSubtree {
+    /// Hash of the subtree root, or `None` if hashing is skipped.
+    root_hash: Option<ValueHash>,
+    patched_nodes: Vec<(NodeKey, Node)>,
+    stale_keys: Vec<NodeKey>,
+}
+
 /// Result of ancestors loading.
 #[derive(Debug)]
 pub(crate) struct LoadAncestorsResult {
@@ -345,6 +354,10 @@ impl WorkingPatchSet {
     }
 
     /// Computes hashes and serializes this change set.
+    ///
+    /// Subtrees rooted at the children of the root node are hashed in parallel, and so are nodes
+    /// on the same level within each subtree. Both kinds of tasks are scheduled on the current `rayon` pool,
+    /// so work stealing balances the load if changes are distributed unevenly among subtrees.
     pub(super) fn finalize(
         self,
         manifest: Manifest,
@@ -353,15 +366,15 @@ impl WorkingPatchSet {
         hasher: &dyn HashTree,
     ) -> (ValueHash, PatchSet, HashingStats) {
         let mut stats = HashingStats::default();
+        let started_at = Instant::now();
         let (root_hash, patch) = self.finalize_inner(
             manifest,
             leaf_count,
             operation,
             |nibble_count, level_changes| {
-                let started_at = Instant::now();
                 let tree_level = nibble_count * 4;
                 // `into_par_iter()` below uses `rayon` to parallelize hash computations.
-                let output = level_changes
+                level_changes
                     .into_par_iter()
                     .map_init(
                         || hasher.with_stats(&stats),
@@ -370,11 +383,10 @@ impl WorkingPatchSet {
                             (nibbles, Some(node.inner.hash(hasher, tree_level)), node)
                         },
                     )
-                    .collect::<Vec<_>>();
-                stats.hashing_duration += started_at.elapsed();
-                output
+                    .collect::<Vec<_>>()
             },
         );
+        stats.hashing_duration += started_at.elapsed();
         let root_hash = root_hash.unwrap_or_else(|| hasher.empty_tree_hash());
         (root_hash, patch, stats)
     }
@@ -384,7 +396,7 @@ impl WorkingPatchSet {
         manifest: Manifest,
         leaf_count: u64,
         operation: Operation,
-        mut map_level_changes: impl FnMut(usize, HashMap<NibblesBytes, WorkingNode>) -> I,
+        map_level_changes: impl Fn(usize, HashMap<NibblesBytes, WorkingNode>) -> I + Sync,
     ) -> (Option<ValueHash>, PatchSet)
     where
         I: IntoIterator<Item = (Nibbles, Option<ValueHash>, WorkingNode)>,
@@ -395,70 +407,154 @@ impl WorkingPatchSet {
             // The tree is empty and there is no root present.
             return (None, PatchSet::for_empty_root(manifest, self.root_version));
         }
+
+        let mut root_level = mem::take(&mut changes_by_nibble_count[0]);
+        let root_node = root_level
+            .get_mut(Nibbles::EMPTY.bytes())
+            .expect("root node must be present in a non-empty patch set");
+        let subtrees = Self::split_into_subtrees(changes_by_nibble_count);
+        let subtrees: Vec<_> = subtrees
+            .into_iter()
+            .enumerate()
+            .filter(|(_, levels)| !levels.is_empty())
+            .map(|(subtree_idx, levels)| {
+                let nibble = u8::try_from(subtree_idx).unwrap();
+                let Node::Internal(root_node) = &root_node.inner else {
+                    unreachable!("Root node with changed descendants must be an internal node");
+                };
+                let subtree_root_version = root_node.child_ref(nibble).unwrap().version;
+                // ^ `unwrap()` is safe by construction: the root node must reference all changed subtrees.
+                (nibble, subtree_root_version, levels)
+            })
+            .collect();
+
+        // `into_par_iter()` below uses `rayon` to process subtrees in parallel.
+        let finalized_subtrees: Vec<_> = subtrees
+            .into_par_iter()
+            .map(|(nibble, subtree_root_version, levels)| {
+                let subtree = Self::finalize_subtree(
+                    levels,
+                    subtree_root_version,
+                    operation,
+                    &map_level_changes,
+                );
+                (nibble, subtree)
+            })
+            .collect();
+
         let mut patched_nodes = HashMap::with_capacity(len);
         let mut stale_keys = vec![];
+        for (nibble, subtree) in finalized_subtrees {
+            if let Some(subtree_hash) = subtree.root_hash {
+                let Node::Internal(root_node) = &mut root_node.inner else {
+                    unreachable!("Root node with changed descendants must be an internal node");
+                };
+                root_node.child_ref_mut(nibble).unwrap().hash = subtree_hash;
+            }
+            patched_nodes.extend(subtree.patched_nodes);
+            stale_keys.extend(subtree.stale_keys);
+        }
+
+        let (nibbles, root_hash, root_node) =
+            map_level_changes(0, root_level).into_iter().next().unwrap();
+        if matches!(operation, Operation::Insert) {
+            // The root node is always replaced for inserts and is never replaced for updated.
+            if let Some(prev_version) = root_node.prev_version {
+                stale_keys.push(nibbles.with_version(prev_version));
+            }
+        }
+
+        let root = Root::new(leaf_count, root_node.inner);
+        let patch = PatchSet::new(
+            manifest,
+            self.root_version,
+            root,
+            patched_nodes,
+            stale_keys,
+            operation,
+        );
+        (root_hash, patch)
+    }
+
+    /// Splits non-root changes by the first nibble of their keys. Levels in the returned subtrees
+    /// are indexed by `nibble_count - 1`; subtrees without changes have no levels.
+    fn split_into_subtrees(
+        changes_by_nibble_count: Vec<HashMap<NibblesBytes, WorkingNode>>,
+    ) -> [Vec<HashMap<NibblesBytes, WorkingNode>>; SUBTREE_COUNT] {
+        let mut subtrees = [(); SUBTREE_COUNT].map(|()| vec![]);
+        let levels = changes_by_nibble_count.into_iter().enumerate().skip(1);
+        for (nibble_count, level) in levels {
+            for (nibbles, node) in level {
+                let first_nibble = nibbles[0] >> 4;
+                let subtree = &mut subtrees[first_nibble as usize];
+                if subtree.len() < nibble_count {
+                    subtree.resize_with(nibble_count, HashMap::new);
+                }
+                subtree[nibble_count - 1].insert(nibbles, node);
+            }
+        }
+        subtrees
+    }
+
+    /// Computes hashes for changed nodes in a subtree rooted at a child of the root node.
+    fn finalize_subtree<I>(
+        mut levels: Vec<HashMap<NibblesBytes, WorkingNode>>,
+        subtree_root_version: u64,
+        operation: Operation,
+        map_level_changes: &impl Fn(usize, HashMap<NibblesBytes, WorkingNode>) -> I,
+    ) -> FinalizedSubtree
+    where
+        I: IntoIterator<Item = (Nibbles, Option<ValueHash>, WorkingNode)>,
+    {
+        let mut output = FinalizedSubtree::default();
 
         // Compute hashes for the changed nodes with decreasing nibble count (i.e., topologically
         // sorted) and store the computed hash in the parent nodes.
-        while let Some(level_changes) = changes_by_nibble_count.pop() {
-            let nibble_count = changes_by_nibble_count.len();
+        while let Some(level_changes) = levels.pop() {
+            let nibble_count = levels.len() + 1;
             let hashed_nodes = map_level_changes(nibble_count, level_changes);
 
             for (nibbles, node_hash, node) in hashed_nodes {
-                let node_version =
-                    if let Some(upper_level_changes) = changes_by_nibble_count.last_mut() {
-                        let (parent_nibbles, last_nibble) = nibbles.split_last().unwrap();
-                        let parent = upper_level_changes.get_mut(parent_nibbles.bytes()).unwrap();
-                        let Node::Internal(parent) = &mut parent.inner else {
-                            unreachable!("Node parent must be an internal node");
-                        };
-                        // ^ `unwrap()`s are safe by construction: the parent of any changed node
-                        // is an `InternalNode` that must be in the change set as well.
-                        let self_ref = parent.child_ref_mut(last_nibble).unwrap();
-                        // ^ `unwrap()` is safe by construction: the parent node must reference
-                        // the currently considered child.
-                        if let Some(node_hash) = node_hash {
-                            self_ref.hash = node_hash;
-                        }
-                        self_ref.version
-                    } else {
-                        // We're at the root node level.
-                        if matches!(operation, Operation::Insert) {
-                            // The root node is always replaced for inserts and is never replaced for updated.
-                            if let Some(prev_version) = node.prev_version {
-                                stale_keys.push(nibbles.with_version(prev_version));
-                            }
-                        }
-
-                        let root = Root::new(leaf_count, node.inner);
-                        let patch = PatchSet::new(
-                            manifest,
-                            self.root_version,
-                            root,
-                            patched_nodes,
-                            stale_keys,
-                            operation,
-                        );
-                        return (node_hash, patch);
+                let node_version = if let Some(upper_level_changes) = levels.last_mut() {
+                    let (parent_nibbles, last_nibble) = nibbles.split_last().unwrap();
+                    let parent = upper_level_changes.get_mut(parent_nibbles.bytes()).unwrap();
+                    let Node::Internal(parent) = &mut parent.inner else {
+                        unreachable!("Node parent must be an internal node");
                     };
+                    // ^ `unwrap()`s are safe by construction: the parent of any changed node
+                    // is an `InternalNode` that must be in the change set as well.
+                    let self_ref = parent.child_ref_mut(last_nibble).unwrap();
+                    // ^ `unwrap()` is safe by construction: the parent node must reference
+                    // the currently considered child.
+                    if let Some(node_hash) = node_hash {
+                        self_ref.hash = node_hash;
+                    }
+                    self_ref.version
+                } else {
+                    // We're at the subtree root level; its hash is propagated to the root node by the caller.
+                    output.root_hash = node_hash;
+                    subtree_root_version
+                };
 
                 let was_replaced = node
                     .prev_version
                     .map_or(true, |prev_version| prev_version < node_version);
                 if was_replaced {
                     if let Some(prev_version) = node.prev_version {
-                        stale_keys.push(nibbles.with_version(prev_version));
+                        output.stale_keys.push(nibbles.with_version(prev_version));
                     }
                 }
                 if was_replaced || matches!(operation, Operation::Update) {
                     // All nodes in the patch set are updated for the update operation, regardless
                     // of the version change. For insert operations, we only should update nodes
                     // with the changed version.
-                    patched_nodes.insert(nibbles.with_version(node_version), node.inner);
+                    output
+                        .patched_nodes
+                        .push((nibbles.with_version(node_version), node.inner));
                 }
             }
         }
-        unreachable!("We should have returned when the root node was encountered above");
+        output
     }
 
     pub fn take_root(&mut self) -> Option<Node> {
@@ -1,19 +1,27 @@
 //! RocksDB implementation of [`Database`].
 
-use std::{any::Any, cell::RefCell, path::Path, sync::Arc};
+use std::{
+    any::Any,
+    cell::RefCell,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+};
 
 use rayon::prelude::*;
 use thread_local::ThreadLocal;
 use zksync_storage::{
     db::{NamedColumnFamily, ProfileGuard, ProfiledOperation},
     rocksdb,
-    rocksdb::DBPinnableSlice,
+    rocksdb::{perf, DBPinnableSlice},
     RocksDB,
 };
 
 use crate::{
     errors::{DeserializeError, ErrorContext},
-    metrics::ApplyPatchStats,
+    metrics::{ApplyPatchStats, LoadProfile, NodeReadStats},
     storage::{
         database::{PruneDatabase, PrunePatchSet},
         Database, NodeKeys, PatchSet,
@@ -32,21 +40,26 @@ pub enum MerkleTreeColumnFamily {
     Tree,
     /// Column family containing stale node keys that are eventually removed by the pruning logic.
     StaleKeys,
+    /// Column family containing leaf nodes in the `NodeKey` -> `LeafNode` form. Only used
+    /// if the database was created with separate leaves (see [`RocksDBWrapper::enable_separate_leaves()`]);
+    /// otherwise, leaves are stored in [`Self::Tree`] together with internal nodes.
+    Leaves,
 }
 
 impl NamedColumnFamily for MerkleTreeColumnFamily {
     const DB_NAME: &'static str = "merkle_tree";
-    const ALL: &'static [Self] = &[Self::Tree, Self::StaleKeys];
+    const ALL: &'static [Self] = &[Self::Tree, Self::StaleKeys, Self::Leaves];
 
     fn name(&self) -> &'static str {
         match self {
             Self::Tree => "default",
             Self::StaleKeys => "stale_keys",
+            Self::Leaves => "leaves",
         }
     }
 
     fn requires_tuning(&self) -> bool {
-        matches!(self, Self::Tree)
+        matches!(self, Self::Tree | Self::Leaves)
     }
 }
 
@@ -70,7 +83,11 @@ pub struct RocksDBWrapper {
     // We want to scope profiled operations both by the thread and by DB instance, hence the use of `ThreadLocal`
     // struct (as opposed to `thread_local!` vars).
     profiled_operation: Arc<ThreadLocal<LocalProfiledOperation>>,
+    load_profile: Arc<Mutex<Option<LoadProfile>>>,
+    // Mirrors whether `load_profile` is set, so that hot paths don't need to lock the mutex.
+    is_profiling_load: Arc<AtomicBool>,
     multi_get_chunk_size: usize,
+    separate_leaves: bool,
 }
 
 impl RocksDBWrapper {
@@ -78,6 +95,9 @@ impl RocksDBWrapper {
     // This key must not overlap with keys for nodes; easy to see that it's true,
     // since the minimum node key is [0, 0, 0, 0, 0, 0, 0, 0].
     const MANIFEST_KEY: &'static [u8] = &[0];
+    /// Key marking that leaf nodes are stored in the [`MerkleTreeColumnFamily::Leaves`] CF.
+    /// Like [`Self::MANIFEST_KEY`], it doesn't overlap with node keys.
+    const SEPARATE_LEAVES_KEY: &'static [u8] = &[1];
 
     /// Creates a new wrapper, initializing RocksDB at the specified directory.
     ///
@@ -105,13 +125,81 @@ impl RocksDBWrapper {
         self.multi_get_chunk_size = chunk_size;
     }
 
+    /// Checks whether leaf nodes are stored in a separate column family ([`MerkleTreeColumnFamily::Leaves`]).
+    pub fn has_separate_leaves(&self) -> bool {
+        self.separate_leaves
+    }
+
+    /// Switches the wrapper to store leaf nodes in a separate column family. Since leaves constitute
+    /// the bulk of tree data and are never read together with internal nodes, this makes internal nodes
+    /// more compact on disk and in the block cache, and allows compacting both kinds of nodes independently.
+    ///
+    /// The node layout is persisted in the database on the first write. It can only be changed
+    /// for an empty database; if the database is not empty, this method has no effect.
+    /// Returns whether leaf nodes are stored separately after the call.
+    pub fn enable_separate_leaves(&mut self) -> bool {
+        if !self.separate_leaves && self.raw_node(Self::MANIFEST_KEY).is_none() {
+            self.separate_leaves = true;
+        }
+        self.separate_leaves
+    }
+
+    /// Enables one-shot load profiling for the next `patch_count` patches applied to the database
+    /// (i.e., the next `patch_count` tree versions unless patches are batched). While profiling is active,
+    /// per-level node reads, block cache hit rates, node writes and stale keys are accumulated; once
+    /// the specified number of patches is applied, a load report is logged with the `INFO` level,
+    /// and profiling is switched off. Calling this method while profiling is active restarts profiling.
+    ///
+    /// Per-level node read metrics are reported regardless of this profiling mode. Block cache stats
+    /// require enabling RocksDB perf stats, which is relatively expensive, so they are only collected
+    /// (and reported as metrics) while profiling is active.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `patch_count` is zero.
+    pub fn profile_load(&self, patch_count: usize) {
+        assert!(
+            patch_count > 0,
+            "number of profiled patches must be positive"
+        );
+        let mut load_profile = self.lock_load_profile();
+        *load_profile = Some(LoadProfile::new(patch_count));
+        self.is_profiling_load.store(true, Ordering::Relaxed);
+    }
+
+    fn is_profiling_load(&self) -> bool {
+        self.is_profiling_load.load(Ordering::Relaxed)
+    }
+
+    fn lock_load_profile(&self) -> MutexGuard<'_, Option<LoadProfile>> {
+        self.load_profile
+            .lock()
+            .expect("Merkle tree load profile is poisoned")
+    }
+
     fn raw_node(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.raw_node_from_cf(MerkleTreeColumnFamily::Tree, key)
+    }
+
+    fn raw_node_from_cf(&self, cf: MerkleTreeColumnFamily, key: &[u8]) -> Option<Vec<u8>> {
         self.db
-            .get_cf(MerkleTreeColumnFamily::Tree, key)
+            .get_cf(cf, key)
             .expect("Failed reading from RocksDB")
     }
 
-    fn raw_nodes(&self, keys: &NodeKeys) -> Vec<Option<DBPinnableSlice<'_>>> {
+    fn node_cf(&self, is_leaf: bool) -> MerkleTreeColumnFamily {
+        if is_leaf && self.separate_leaves {
+            MerkleTreeColumnFamily::Leaves
+        } else {
+            MerkleTreeColumnFamily::Tree
+        }
+    }
+
+    fn raw_nodes(
+        &self,
+        keys: &NodeKeys,
+        stats: &NodeReadStats,
+    ) -> Vec<Option<DBPinnableSlice<'_>>> {
         // Propagate the currently profiled operation to rayon threads used in the parallel iterator below.
         let profiled_operation = self
             .profiled_operation
@@ -125,16 +213,88 @@ impl RocksDBWrapper {
                 let _guard = profiled_operation
                     .as_ref()
                     .and_then(ProfiledOperation::start_profiling);
-                let keys = chunk.iter().map(|(key, _)| key.to_db_key());
-                let results = self.db.multi_get_cf(MerkleTreeColumnFamily::Tree, keys);
-                results
-                    .into_iter()
-                    .map(|result| result.expect("Failed reading from RocksDB"))
+                Self::with_block_stats(stats, || self.multi_get_nodes(chunk))
             })
             .flatten_iter()
             .collect()
     }
 
+    /// Executes `action` on the current thread, recording block cache hits and block reads to `stats`.
+    /// Block stats are only recorded if `stats.collect_block_stats` is set.
+    fn with_block_stats<T>(stats: &NodeReadStats, action: impl FnOnce() -> T) -> T {
+        if !stats.collect_block_stats {
+            return action();
+        }
+
+        // We don't reset the perf context, since it may be used by the outer profiled operation;
+        // instead, we compute the differences in the relevant metrics.
+        perf::set_perf_stats(perf::PerfStatsLevel::EnableCount);
+        let context = perf::PerfContext::default();
+        let cache_hits_before = context.metric(perf::PerfMetric::BlockCacheHitCount);
+        let reads_before = context.metric(perf::PerfMetric::BlockReadCount);
+        let output = action();
+        let cache_hits = context.metric(perf::PerfMetric::BlockCacheHitCount);
+        let reads = context.metric(perf::PerfMetric::BlockReadCount);
+        stats.block_cache_hits.fetch_add(
+            cache_hits.saturating_sub(cache_hits_before),
+            Ordering::Relaxed,
+        );
+        // Block reads reported by RocksDB do not include cache hits.
+        let reads =
+            reads.saturating_sub(reads_before) + cache_hits.saturating_sub(cache_hits_before);
+        stats.block_reads.fetch_add(reads, Ordering::Relaxed);
+        output
+    }
+
+    fn report_node_reads(&self, stats: NodeReadStats) {
+        if stats.collect_block_stats {
+            let mut load_profile = self.lock_load_profile();
+            stats.report(load_profile.as_mut());
+        } else {
+            stats.report(None);
+        }
+    }
+
+    fn multi_get_nodes(&self, keys: &NodeKeys) -> Vec<Option<DBPinnableSlice<'_>>> {
+        if !self.separate_leaves {
+            let keys = keys.iter().map(|(key, _)| key.to_db_key());
+            let results = self.db.multi_get_cf(MerkleTreeColumnFamily::Tree, keys);
+            return results
+                .into_iter()
+                .map(|result| result.expect("Failed reading from RocksDB"))
+                .collect();
+        }
+
+        let leaf_keys = keys
+            .iter()
+            .filter(|(_, is_leaf)| *is_leaf)
+            .map(|(key, _)| key.to_db_key());
+        let mut leaves = self
+            .db
+            .multi_get_cf(MerkleTreeColumnFamily::Leaves, leaf_keys)
+            .into_iter();
+        let internal_keys = keys
+            .iter()
+            .filter(|(_, is_leaf)| !*is_leaf)
+            .map(|(key, _)| key.to_db_key());
+        let mut internal_nodes = self
+            .db
+            .multi_get_cf(MerkleTreeColumnFamily::Tree, internal_keys)
+            .into_iter();
+
+        // Merge results back in the order of the requested keys.
+        let results = keys.iter().map(|(_, is_leaf)| {
+            let result = if *is_leaf {
+                leaves.next()
+            } else {
+                internal_nodes.next()
+            };
+            // `unwrap()` is safe: the number of results for each CF matches the number of requested keys
+            result.unwrap().expect("Failed reading from RocksDB")
+        });
+        results.collect()
+    }
+
     fn deserialize_node(
         raw_node: &[u8],
         key: &NodeKey,
@@ -164,10 +324,17 @@ impl RocksDBWrapper {
 
 impl From<RocksDB<MerkleTreeColumnFamily>> for RocksDBWrapper {
     fn from(db: RocksDB<MerkleTreeColumnFamily>) -> Self {
+        let separate_leaves = db
+            .get_cf(MerkleTreeColumnFamily::Tree, Self::SEPARATE_LEAVES_KEY)
+            .expect("Failed reading from RocksDB")
+            .is_some();
         Self {
             db,
             profiled_operation: Arc::new(ThreadLocal::new()),
+            load_profile: Arc::new(Mutex::new(None)),
+            is_profiling_load: Arc::new(AtomicBool::new(false)),
             multi_get_chunk_size: usize::MAX,
+            separate_leaves,
         }
     }
 }
@@ -196,14 +363,35 @@ impl Database for RocksDBWrapper {
         key: &NodeKey,
         is_leaf: bool,
     ) -> Result<Option<Node>, DeserializeError> {
-        let Some(raw_node) = self.raw_node(&key.to_db_key()) else {
+        let stats = NodeReadStats {
+            nibble_count: key.nibbles.nibble_count(),
+            node_count: 1,
+            collect_block_stats: self.is_profiling_load(),
+            ..NodeReadStats::default()
+        };
+        let raw_node = Self::with_block_stats(&stats, || {
+            self.raw_node_from_cf(self.node_cf(is_leaf), &key.to_db_key())
+        });
+        self.report_node_reads(stats);
+
+        let Some(raw_node) = raw_node else {
             return Ok(None);
         };
         Self::deserialize_node(&raw_node, key, is_leaf).map(Some)
     }
 
     fn tree_nodes(&self, keys: &NodeKeys) -> Vec<Option<Node>> {
-        let raw_nodes = self.raw_nodes(keys).into_iter().zip(keys);
+        let stats = NodeReadStats {
+            nibble_count: keys
+                .first()
+                .map_or(0, |(key, _)| key.nibbles.nibble_count()),
+            node_count: keys.len() as u64,
+            collect_block_stats: self.is_profiling_load(),
+            ..NodeReadStats::default()
+        };
+        let raw_nodes = self.raw_nodes(keys, &stats);
+        self.report_node_reads(stats);
+        let raw_nodes = raw_nodes.into_iter().zip(keys);
 
         let nodes = raw_nodes.map(|(maybe_node, (key, is_leaf))| {
             maybe_node
@@ -248,6 +436,9 @@ impl Database for RocksDBWrapper {
 
         patch.manifest.serialize(&mut node_bytes);
         write_batch.put_cf(tree_cf, Self::MANIFEST_KEY, &node_bytes);
+        if self.separate_leaves {
+            write_batch.put_cf(tree_cf, Self::SEPARATE_LEAVES_KEY, &[]);
+        }
 
         for (version, sub_patch) in patch.patches_by_version {
             let is_update = patch.updated_version == Some(version);
@@ -257,7 +448,11 @@ impl Database for RocksDBWrapper {
                 // potential garbage left after reverting the tree to a previous version.
                 let next_root_key = NodeKey::empty(version + 1);
                 let keys_to_delete = &*root_key.to_db_key()..&*next_root_key.to_db_key();
-                write_batch.delete_range_cf(tree_cf, keys_to_delete);
+                write_batch.delete_range_cf(tree_cf, keys_to_delete.clone());
+                if self.separate_leaves {
+                    let leaves_cf = MerkleTreeColumnFamily::Leaves;
+                    write_batch.delete_range_cf(leaves_cf, keys_to_delete);
+                }
             }
 
             if let Some(root) = sub_patch.root {
@@ -270,7 +465,8 @@ impl Database for RocksDBWrapper {
                 node_bytes.clear();
                 node.serialize(&mut node_bytes);
                 metrics.update_node_bytes(&node_key.nibbles, &node_bytes);
-                write_batch.put_cf(tree_cf, &node_key.to_db_key(), &node_bytes);
+                let cf = self.node_cf(matches!(node, Node::Leaf(_)));
+                write_batch.put_cf(cf, &node_key.to_db_key(), &node_bytes);
             }
         }
 
@@ -283,13 +479,29 @@ impl Database for RocksDBWrapper {
                     .map(move |key| StaleNodeKey::new(key, version))
             });
         for replaced_key in all_stale_keys {
+            metrics.update_stale_key(&replaced_key.key.nibbles);
             write_batch.put_cf(stale_keys_cf, &replaced_key.to_db_key(), &[]);
         }
 
         self.db
             .write(write_batch)
             .expect("Failed writing a batch to RocksDB");
-        metrics.report();
+
+        if !self.is_profiling_load() {
+            metrics.report(None);
+            return;
+        }
+        let mut load_profile = self.lock_load_profile();
+        metrics.report(load_profile.as_mut());
+        if load_profile
+            .as_mut()
+            .map_or(false, LoadProfile::record_patch)
+        {
+            let profile = load_profile.take().unwrap();
+            // ^ `unwrap()` is safe: we've checked that the profile is present
+            self.is_profiling_load.store(false, Ordering::Relaxed);
+            tracing::info!("{profile}");
+        }
     }
 }
 
@@ -319,8 +531,14 @@ impl PruneDatabase for RocksDBWrapper {
         let mut write_batch = self.db.new_write_batch();
 
         let tree_cf = MerkleTreeColumnFamily::Tree;
+        let leaves_cf = MerkleTreeColumnFamily::Leaves;
         for pruned_key in patch.pruned_node_keys {
-            write_batch.delete_cf(tree_cf, &pruned_key.to_db_key());
+            let pruned_key = pruned_key.to_db_key();
+            write_batch.delete_cf(tree_cf, &pruned_key);
+            // Stale keys don't record whether they correspond to a leaf, so we remove the key from both CFs.
+            if self.separate_leaves {
+                write_batch.delete_cf(leaves_cf, &pruned_key);
+            }
         }
 
         let stale_keys_cf = MerkleTreeColumnFamily::StaleKeys;
@@ -345,8 +563,20 @@ mod tests {
 
     #[test]
     fn garbage_is_removed_on_db_reverts() {
+        test_garbage_removal_on_db_reverts(false);
+    }
+
+    #[test]
+    fn garbage_is_removed_on_db_reverts_with_separate_leaves() {
+        test_garbage_removal_on_db_reverts(true);
+    }
+
+    fn test_garbage_removal_on_db_reverts(separate_leaves: bool) {
         let dir = TempDir::new().expect("failed creating temporary dir for RocksDB");
         let mut db = RocksDBWrapper::new(dir.path()).unwrap();
+        if separate_leaves {
+            assert!(db.enable_separate_leaves());
+        }
 
         // Insert some data to the database.
         let mut expected_keys = HashSet::new();
@@ -388,10 +618,10 @@ mod tests {
     }
 
     fn assert_contains_exactly_keys(db: &RocksDBWrapper, expected_keys: &HashSet<NodeKey>) {
-        let cf = MerkleTreeColumnFamily::Tree;
-        let actual_keys: HashSet<_> = db
-            .db
-            .prefix_iterator_cf(cf, &[0; 7])
+        let cfs = [MerkleTreeColumnFamily::Tree, MerkleTreeColumnFamily::Leaves];
+        let actual_keys: HashSet<_> = cfs
+            .into_iter()
+            .flat_map(|cf| db.db.prefix_iterator_cf(cf, &[0; 7]))
             .map(|(key, _)| key)
             .collect();
 
@@ -401,4 +631,87 @@ mod tests {
             .collect();
         assert_eq!(actual_keys, expected_raw_keys);
     }
+
+    #[test]
+    fn separate_leaves_layout() {
+        let dir = TempDir::new().expect("failed creating temporary dir for RocksDB");
+        let mut db = RocksDBWrapper::new(dir.path()).unwrap();
+        assert!(!db.has_separate_leaves());
+        assert!(db.enable_separate_leaves());
+
+        let root = Root::new(2, Node::Internal(InternalNode::default()));
+        let nodes = generate_nodes(0, &[1, 2]);
+        let node_keys: Vec<_> = nodes.keys().map(|key| (*key, true)).collect();
+        let patch = create_patch(0, root, nodes.clone());
+        db.apply_patch(patch);
+
+        let leaves_cf = MerkleTreeColumnFamily::Leaves;
+        let leaf_count = db.db.prefix_iterator_cf(leaves_cf, &[0; 8]).count();
+        assert_eq!(leaf_count, 2);
+        let tree_cf = MerkleTreeColumnFamily::Tree;
+        let tree_node_count = db.db.prefix_iterator_cf(tree_cf, &[0; 8]).count();
+        assert_eq!(tree_node_count, 1); // the root node
+
+        let loaded_nodes = db.tree_nodes(&node_keys);
+        for ((key, _), node) in node_keys.iter().zip(loaded_nodes) {
+            assert_eq!(node.as_ref(), nodes.get(key));
+            let node = db.try_tree_node(key, true).unwrap();
+            assert_eq!(node.as_ref(), nodes.get(key));
+        }
+        assert!(db.root(0).is_some());
+
+        // Check that the layout is persisted.
+        drop(db);
+        let db = RocksDBWrapper::new(dir.path()).unwrap();
+        assert!(db.has_separate_leaves());
+        let loaded_nodes = db.tree_nodes(&node_keys);
+        assert!(loaded_nodes.iter().all(Option::is_some));
+
+        // The layout cannot be changed for a non-empty DB.
+        let dir = TempDir::new().expect("failed creating temporary dir for RocksDB");
+        let mut db = RocksDBWrapper::new(dir.path()).unwrap();
+        let patch = create_patch(0, Root::Empty, HashMap::new());
+        db.apply_patch(patch);
+        assert!(!db.enable_separate_leaves());
+        assert!(!db.has_separate_leaves());
+    }
+
+    #[test]
+    fn one_shot_load_profiling() {
+        let dir = TempDir::new().expect("failed creating temporary dir for RocksDB");
+        let mut db = RocksDBWrapper::new(dir.path()).unwrap();
+        db.profile_load(2);
+
+        let root = Root::new(2, Node::Internal(InternalNode::default()));
+        let nodes = generate_nodes(0, &[1, 2]);
+        let node_keys: Vec<_> = nodes.keys().map(|key| (*key, true)).collect();
+        db.apply_patch(create_patch(0, root, nodes));
+        let loaded_nodes = db.tree_nodes(&node_keys);
+        assert!(loaded_nodes.iter().all(Option::is_some));
+
+        {
+            let load_profile = db.lock_load_profile();
+            let load_profile = load_profile
+                .as_ref()
+                .expect("profiling finished prematurely");
+            assert_eq!(load_profile.total_node_writes(), 3); // 2 leaves + root
+            assert_eq!(load_profile.total_node_reads(), 2);
+            let report = load_profile.to_string();
+            assert!(report.contains("load report for 1 patch(es)"), "{report}");
+        }
+
+        let root = Root::new(3, Node::Internal(InternalNode::default()));
+        db.apply_patch(create_patch(1, root, generate_nodes(1, &[3])));
+        // Profiling should be switched off after the 2nd patch.
+        assert!(db.lock_load_profile().is_none());
+        assert!(!db.is_profiling_load());
+
+        let stats = NodeReadStats {
+            node_count: 1,
+            collect_block_stats: db.is_profiling_load(),
+            ..NodeReadStats::default()
+        };
+        RocksDBWrapper::with_block_stats(&stats, || db.tree_nodes(&node_keys));
+        assert_eq!(stats.block_reads.into_inner(), 0);
+    }
 }
@@ -571,6 +571,8 @@ pub enum ProfiledTreeOperation {
     GetEntries,
     /// Getting entries from the tree with Merkle proofs.
     GetEntriesWithProofs,
+    /// Getting entries in a key range from the tree.
+    GetEntriesInRange,
 }
 
 impl ProfiledTreeOperation {
@@ -579,6 +581,7 @@ impl ProfiledTreeOperation {
             Self::LoadAncestors => "load_ancestors",
             Self::GetEntries => "get_entries",
             Self::GetEntriesWithProofs => "get_entries_with_proofs",
+            Self::GetEntriesInRange => "get_entries_in_range",
         }
     }
 }
@@ -6,7 +6,9 @@ use serde::{Deserialize, Serialize};
 use serde_with::{hex::Hex, serde_as};
 use tempfile::TempDir;
 use zksync_crypto::hasher::blake2::Blake2Hasher;
-use zksync_merkle_tree::{domain::ZkSyncTree, HashTree, TreeEntry, TreeInstruction};
+use zksync_merkle_tree::{
+    domain::ZkSyncTree, HashTree, TreeEntry, TreeHasherKind, TreeInstruction,
+};
 use zksync_prover_interface::inputs::StorageLogMetadata;
 use zksync_storage::RocksDB;
 use zksync_system_constants::ACCOUNT_CODE_STORAGE_ADDRESS;
@@ -108,6 +110,83 @@ fn basic_workflow_multiblock() {
     let tree = ZkSyncTree::new_lightweight(db.into());
     assert_eq!(tree.root_hash(), expected_root_hash);
     assert_eq!(tree.next_l1_batch_number(), L1BatchNumber(12));
+
+    let reader = tree.reader();
+    assert_eq!(
+        reader.root_hash_for_l1_batch(L1BatchNumber(11)),
+        Some(expected_root_hash)
+    );
+    let first_root_hash = reader.root_hash_for_l1_batch(L1BatchNumber(0)).unwrap();
+    assert_ne!(first_root_hash, expected_root_hash);
+    assert_eq!(reader.root_hash_for_l1_batch(L1BatchNumber(12)), None);
+}
+
+#[test]
+fn reading_proofs_together_with_root_hash() {
+    let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+    let logs = gen_storage_logs();
+    let db = RocksDB::new(temp_dir.as_ref()).unwrap();
+    let mut tree = ZkSyncTree::new_lightweight(db.into());
+    for block in logs.chunks(9) {
+        tree.process_l1_batch(block);
+    }
+    tree.save();
+
+    let reader = tree.reader();
+    let keys: Vec<_> = logs
+        .iter()
+        .map(|instr| instr.key().hashed_key_u256())
+        .collect();
+    for l1_batch_number in [0, 5, 11].map(L1BatchNumber) {
+        let (root_hash, entries) = reader
+            .entries_with_proofs_and_root_hash(l1_batch_number, &keys)
+            .unwrap();
+        assert_eq!(
+            reader.root_hash_for_l1_batch(l1_batch_number),
+            Some(root_hash)
+        );
+        for entry in &entries {
+            entry.verify(&Blake2Hasher, root_hash);
+        }
+    }
+    assert!(reader
+        .entries_with_proofs_and_root_hash(L1BatchNumber(12), &keys)
+        .is_err());
+
+    let (mut pruner, _handle) = tree.pruner();
+    pruner.run_once().unwrap();
+    let err = reader
+        .entries_with_proofs_and_root_hash(L1BatchNumber(0), &keys)
+        .unwrap_err();
+    assert_eq!(err.missing_version, 0);
+    let (root_hash, _) = reader
+        .entries_with_proofs_and_root_hash(L1BatchNumber(11), &keys)
+        .unwrap();
+    assert_eq!(root_hash, tree.root_hash());
+}
+
+#[test]
+fn tree_hasher_is_fixed_after_first_l1_batch() {
+    let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+    let logs = gen_storage_logs();
+    {
+        let db = RocksDB::new(temp_dir.as_ref()).unwrap();
+        let mut tree = ZkSyncTree::new_lightweight(db.into());
+        assert_eq!(tree.hasher(), TreeHasherKind::default());
+        tree.set_hasher(TreeHasherKind::Blake2s256);
+        tree.process_l1_batch(&logs);
+        tree.save();
+    }
+
+    let db = RocksDB::new(temp_dir.as_ref()).unwrap();
+    let mut tree = ZkSyncTree::new_lightweight(db.into());
+    assert_eq!(tree.hasher(), TreeHasherKind::Blake2s256);
+    // Setting the same hasher is a no-op.
+    tree.set_hasher(TreeHasherKind::Blake2s256);
+    assert_eq!(
+        tree.reader().root_hash_for_l1_batch(L1BatchNumber(0)),
+        Some(tree.root_hash())
+    );
 }
 
 #[test]
@@ -6,10 +6,10 @@ use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
 use test_casing::test_casing;
 use zksync_crypto::hasher::blake2::Blake2Hasher;
 use zksync_merkle_tree::{
-    Database, HashTree, MerkleTree, PatchSet, Patched, TreeEntry, TreeInstruction, TreeLogEntry,
-    TreeRangeDigest,
+    Database, HashTree, MerkleTree, PatchSet, Patched, TreeEntry, TreeHasherKind, TreeInstruction,
+    TreeLogEntry, TreeRangeDigest,
 };
-use zksync_types::{AccountTreeId, Address, StorageKey, H256, U256};
+use zksync_types::{AccountTreeId, Address, ProtocolVersionId, StorageKey, H256, U256};
 
 use crate::common::{
     compute_tree_hash, convert_to_writes, generate_key_value_pairs, ENTRIES_AND_HASH,
@@ -41,6 +41,82 @@ fn root_hash_is_computed_correctly_on_empty_tree(kv_count: u64) {
     assert_eq!(output.root_hash, expected_hash);
 }
 
+#[test_casing(3, [1, 2, 4])]
+fn root_hash_is_computed_correctly_with_dedicated_thread_pool(thread_count: usize) {
+    let thread_pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(thread_count)
+        .build()
+        .unwrap();
+    let mut tree = MerkleTree::new(PatchSet::default());
+    let mut kvs = generate_key_value_pairs(0..1_000);
+    let expected_hash = compute_tree_hash(kvs.iter().copied());
+    let output = thread_pool.install(|| tree.extend(kvs.clone()));
+    assert_eq!(output.root_hash, expected_hash);
+
+    // Update a sparse subset of keys so that not all subtrees are changed.
+    let mut updated_kvs = vec![];
+    for kv in kvs.iter_mut().step_by(97) {
+        *kv = kv.with_value(H256::repeat_byte(0xff));
+        updated_kvs.push(*kv);
+    }
+    let expected_hash = compute_tree_hash(kvs.iter().copied());
+    let output = thread_pool.install(|| tree.extend(updated_kvs));
+    assert_eq!(output.root_hash, expected_hash);
+    tree.verify_consistency(1, true).unwrap();
+}
+
+#[test_casing(8, KV_COUNTS)]
+fn root_hash_is_computed_correctly_with_another_hasher(kv_count: u64) {
+    // Build a tree using the no-op hasher, and compute its root hash using Blake2.
+    let mut tree = MerkleTree::with_hasher(PatchSet::default(), ());
+    let mut kvs = generate_key_value_pairs(0..kv_count);
+    tree.extend(kvs.clone());
+    let expected_hash = compute_tree_hash(kvs.iter().copied());
+    assert_eq!(
+        tree.root_hash_with_hasher(0, &Blake2Hasher).unwrap(),
+        expected_hash
+    );
+
+    for kv in kvs.iter_mut().step_by(3) {
+        *kv = kv.with_value(H256::repeat_byte(0xff));
+    }
+    tree.extend(kvs.iter().copied().step_by(3).collect());
+    let expected_hash = compute_tree_hash(kvs.iter().copied());
+    assert_eq!(
+        tree.root_hash_with_hasher(1, &Blake2Hasher).unwrap(),
+        expected_hash
+    );
+
+    let hasher = TreeHasherKind::for_protocol_version(ProtocolVersionId::latest());
+    assert_eq!(
+        tree.root_hash_with_hasher(1, &hasher).unwrap(),
+        expected_hash
+    );
+    assert_eq!(tree.root_hash_with_hasher(1, &()).unwrap(), H256::zero());
+    let err = tree.root_hash_with_hasher(2, &Blake2Hasher).unwrap_err();
+    assert_eq!(err.missing_version, 2);
+}
+
+#[test]
+fn hasher_kind_basics() {
+    assert_eq!(TreeHasherKind::default(), TreeHasherKind::Blake2s256);
+    assert_eq!(
+        TreeHasherKind::from_name("blake2s256"),
+        Some(TreeHasherKind::Blake2s256)
+    );
+    assert_eq!(TreeHasherKind::from_name("sha256"), None);
+    for version in [ProtocolVersionId::Version0, ProtocolVersionId::latest()] {
+        assert_eq!(
+            TreeHasherKind::for_protocol_version(version),
+            TreeHasherKind::Blake2s256
+        );
+    }
+    assert_eq!(
+        TreeHasherKind::Blake2s256.empty_tree_hash(),
+        Blake2Hasher.empty_tree_hash()
+    );
+}
+
 #[test_casing(8, KV_COUNTS)]
 fn output_proofs_are_computed_correctly_on_empty_tree(kv_count: u64) {
     const RNG_SEED: u64 = 123;
@@ -1,11 +1,13 @@
 use std::fmt::{Display, Formatter};
 
+use serde::{Deserialize, Serialize};
+
 use super::VmRevertReason;
 
 /// Structure for non-contract errors from the Virtual Machine (EVM).
 
 /// Differentiates VM-specific issues from contract-related errors.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Halt {
     // Can only be returned in `VerifyAndExecute`
     ValidationFailed(VmRevertReason),
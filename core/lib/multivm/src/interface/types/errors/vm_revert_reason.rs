@@ -1,5 +1,7 @@
 use std::fmt::{Debug, Display};
 
+use serde::{Deserialize, Serialize};
+
 use zksync_types::U256;
 
 #[derive(Debug, thiserror::Error)]
@@ -13,7 +15,7 @@ pub enum VmRevertReasonParsingError {
 }
 
 /// Rich Revert Reasons `https://github.com/0xProject/ZEIPs/issues/32`
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum VmRevertReason {
     General {
         msg: String,
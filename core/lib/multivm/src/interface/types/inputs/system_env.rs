@@ -1,15 +1,18 @@
-use std::fmt::Debug;
+use std::{fmt::Debug, sync::Arc};
 
+use serde::{Deserialize, Serialize};
 use zksync_contracts::BaseSystemContracts;
 use zksync_types::{L2ChainId, ProtocolVersionId};
 
 /// Params related to the execution process, not batch it self
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct SystemEnv {
     // Always false for VM
     pub zk_porter_available: bool,
     pub version: ProtocolVersionId,
-    pub base_system_smart_contracts: BaseSystemContracts,
+    /// Base system contracts. Shared via `Arc` since the contracts are large and environments are cloned
+    /// (e.g., by the API sandbox for each call).
+    pub base_system_smart_contracts: Arc<BaseSystemContracts>,
     pub bootloader_gas_limit: u32,
     pub execution_mode: TxExecutionMode,
     pub default_validation_computational_gas_limit: u32,
@@ -44,7 +47,7 @@ impl Debug for SystemEnv {
 /// With `VerifyExecute` mode, transaction will be executed normally.
 /// With `EstimateFee`, the bootloader will be used that has the same behavior
 /// as the full `VerifyExecute` block, but errors in the account validation will be ignored.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TxExecutionMode {
     VerifyExecute,
     EstimateFee,
@@ -1,7 +1,11 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
 use zksync_system_constants::PUBLISH_BYTECODE_OVERHEAD;
 use zksync_types::{
+    commitment::SerializeCommitment,
     event::{extract_long_l2_to_l1_messages, extract_published_bytecodes},
-    l2_to_l1_log::{SystemL2ToL1Log, UserL2ToL1Log},
+    l2_to_l1_log::{L2ToL1Log, SystemL2ToL1Log, UserL2ToL1Log},
     tx::ExecutionMetrics,
     StorageLogQuery, Transaction, VmEvent,
 };
@@ -9,15 +13,18 @@ use zksync_utils::bytecode::bytecode_len_in_bytes;
 
 use crate::interface::{Halt, VmExecutionStatistics, VmRevertReason};
 
+/// Size of the length prefix of a long L2-to-L1 message or a published bytecode in the L1 batch pubdata.
+const PUBDATA_LENGTH_PREFIX_SIZE: usize = 4;
+
 /// Refunds produced for the user.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Refunds {
     pub gas_refunded: u64,
     pub operator_suggested_refund: u64,
 }
 
 /// Events/storage logs/l2->l1 logs created within transaction execution.
-#[derive(Debug, Clone, Default, PartialEq)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct VmExecutionLogs {
     pub storage_logs: Vec<StorageLogQuery>,
     pub events: Vec<VmEvent>,
@@ -36,7 +43,7 @@ impl VmExecutionLogs {
 }
 
 /// Result and logs of the VM execution.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VmExecutionResultAndLogs {
     pub result: ExecutionResult,
     pub logs: VmExecutionLogs,
@@ -44,7 +51,7 @@ pub struct VmExecutionResultAndLogs {
     pub refunds: Refunds,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ExecutionResult {
     /// Returned successfully
     Success { output: Vec<u8> },
@@ -76,18 +83,34 @@ impl VmExecutionResultAndLogs {
         // - message length in bytes, rounded up to a multiple of 32
         // - 32 bytes of encoded offset
         // - 32 bytes of encoded length
-        let l2_l1_long_messages = extract_long_l2_to_l1_messages(&self.logs.events)
+        let long_messages = extract_long_l2_to_l1_messages(&self.logs.events);
+        let l2_l1_long_messages = long_messages
             .iter()
             .map(|event| (event.len() + 31) / 32 * 32 + 64)
             .sum();
 
-        let published_bytecode_bytes = extract_published_bytecodes(&self.logs.events)
+        let published_bytecode_lengths: Vec<_> = extract_published_bytecodes(&self.logs.events)
+            .into_iter()
+            .map(bytecode_len_in_bytes)
+            .collect();
+        let published_bytecode_bytes = published_bytecode_lengths
             .iter()
-            .map(|bytecodehash| {
-                bytecode_len_in_bytes(*bytecodehash) + PUBLISH_BYTECODE_OVERHEAD as usize
-            })
+            .map(|len| len + PUBLISH_BYTECODE_OVERHEAD as usize)
             .sum();
 
+        // In the L1 batch pubdata, messages and bytecodes are only prefixed with their length,
+        // and system logs are not published at all.
+        let pubdata_without_state_diffs = self.logs.user_l2_to_l1_logs.len()
+            * L2ToL1Log::SERIALIZED_SIZE
+            + long_messages
+                .iter()
+                .map(|message| message.len() + PUBDATA_LENGTH_PREFIX_SIZE)
+                .sum::<usize>()
+            + published_bytecode_lengths
+                .iter()
+                .map(|len| len + PUBDATA_LENGTH_PREFIX_SIZE)
+                .sum::<usize>();
+
         ExecutionMetrics {
             gas_used: self.statistics.gas_used as usize,
             published_bytecode_bytes,
@@ -101,7 +124,9 @@ impl VmExecutionResultAndLogs {
             cycles_used: self.statistics.cycles_used,
             computational_gas_used: self.statistics.computational_gas_used,
             pubdata_published: self.statistics.pubdata_published,
+            pubdata_without_state_diffs,
             circuit_statistic: self.statistics.circuit_statistic,
+            vm_execution_time: Duration::ZERO,
         }
     }
 }
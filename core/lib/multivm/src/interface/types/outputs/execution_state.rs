@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use zksync_types::{
     l2_to_l1_log::{SystemL2ToL1Log, UserL2ToL1Log},
     zk_evm_types::LogQuery,
@@ -5,7 +6,7 @@ use zksync_types::{
 };
 
 /// State of the VM since the start of the batch execution.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CurrentExecutionState {
     /// Events produced by the VM.
     pub events: Vec<VmEvent>,
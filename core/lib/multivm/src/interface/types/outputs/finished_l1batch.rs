@@ -1,7 +1,9 @@
+use serde::{Deserialize, Serialize};
+
 use super::{BootloaderMemory, CurrentExecutionState, VmExecutionResultAndLogs};
 
 /// State of the VM after the batch execution.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FinishedL1Batch {
     /// Result of the execution of the block tip part of the batch.
     pub block_tip_execution_result: VmExecutionResultAndLogs,
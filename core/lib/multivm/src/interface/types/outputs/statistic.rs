@@ -1,7 +1,8 @@
+use serde::{Deserialize, Serialize};
 use zksync_types::circuit::CircuitStatistic;
 
 /// Statistics of the tx execution.
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct VmExecutionStatistics {
     /// Number of contracts used by the VM during the tx execution.
     pub contracts_used: usize,
@@ -0,0 +1,42 @@
+use std::{collections::HashMap, sync::Arc};
+
+use once_cell::sync::OnceCell;
+use zksync_state::{StoragePtr, WriteStorage};
+use zksync_types::{StorageKey, StorageValue};
+
+use crate::glue::tracers::IntoOldVmTracer;
+
+pub mod vm_1_4_1;
+pub mod vm_1_4_2;
+pub mod vm_boojum_integration;
+pub mod vm_latest;
+pub mod vm_refunds_enhancement;
+pub mod vm_virtual_blocks;
+
+/// Tracer recording storage slots read from the underlying storage during the VM execution, together with
+/// the values read. Slots that were written before being read are not recorded since the execution result
+/// doesn't depend on their initial values.
+///
+/// The tracer doesn't hook into individual VM cycles; it just takes a snapshot of the storage reads
+/// once the execution is finished, so it's cheap to use.
+///
+/// Not supported for VM versions preceding the VM with virtual blocks; for them, the tracer is a no-op.
+#[derive(Debug, Clone)]
+pub struct AccessedStorageTracer {
+    result: Arc<OnceCell<HashMap<StorageKey, StorageValue>>>,
+}
+
+impl AccessedStorageTracer {
+    pub fn new(result: Arc<OnceCell<HashMap<StorageKey, StorageValue>>>) -> Self {
+        Self { result }
+    }
+
+    fn store_result<S: WriteStorage>(&self, storage: &StoragePtr<S>) {
+        // If the VM is run multiple times (e.g., when retrying execution without bytecode compression),
+        // storage reads accumulate in the storage view, so it's fine to keep the first snapshot.
+        self.result
+            .get_or_init(|| storage.borrow().read_storage_keys().clone());
+    }
+}
+
+impl IntoOldVmTracer for AccessedStorageTracer {}
@@ -0,0 +1,20 @@
+use zksync_state::WriteStorage;
+
+use crate::{
+    interface::{tracer::VmExecutionStopReason, traits::tracers::dyn_tracers::vm_1_4_1::DynTracer},
+    tracers::accessed_storage::AccessedStorageTracer,
+    vm_1_4_1::{BootloaderState, HistoryMode, SimpleMemory, VmTracer, ZkSyncVmState},
+};
+
+impl<S, H: HistoryMode> DynTracer<S, SimpleMemory<H>> for AccessedStorageTracer {}
+
+impl<S: WriteStorage, H: HistoryMode> VmTracer<S, H> for AccessedStorageTracer {
+    fn after_vm_execution(
+        &mut self,
+        state: &mut ZkSyncVmState<S, H>,
+        _bootloader_state: &BootloaderState,
+        _stop_reason: VmExecutionStopReason,
+    ) {
+        self.store_result(&state.storage.storage.get_ptr());
+    }
+}
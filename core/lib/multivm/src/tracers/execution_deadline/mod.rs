@@ -0,0 +1,53 @@
+use std::time::Instant;
+
+use crate::glue::tracers::IntoOldVmTracer;
+
+pub mod vm_1_4_1;
+pub mod vm_1_4_2;
+pub mod vm_boojum_integration;
+pub mod vm_latest;
+pub mod vm_refunds_enhancement;
+pub mod vm_virtual_blocks;
+
+/// Number of VM cycles between consecutive deadline checks. Checking the current time is relatively expensive
+/// compared to executing a single cycle, so it's not performed on each cycle.
+const CYCLES_BETWEEN_CHECKS: u32 = 1_024;
+
+/// Tracer stopping the VM execution once the specified wall-clock deadline is reached.
+///
+/// Not supported for VM versions preceding the VM with virtual blocks; for them, the tracer is a no-op.
+#[derive(Debug, Clone)]
+pub struct ExecutionDeadline {
+    deadline: Instant,
+    cycles_since_check: u32,
+    is_reached: bool,
+}
+
+impl ExecutionDeadline {
+    pub fn new(deadline: Instant) -> Self {
+        Self {
+            deadline,
+            cycles_since_check: 0,
+            is_reached: false,
+        }
+    }
+
+    /// Checks whether the deadline is reached. The current time is only queried once per [`CYCLES_BETWEEN_CHECKS`] calls.
+    fn check(&mut self) -> bool {
+        if self.is_reached {
+            return true;
+        }
+        self.cycles_since_check += 1;
+        if self.cycles_since_check >= CYCLES_BETWEEN_CHECKS {
+            self.cycles_since_check = 0;
+            self.is_reached = Instant::now() >= self.deadline;
+        }
+        self.is_reached
+    }
+
+    fn halt_reason() -> String {
+        "Execution deadline exceeded".to_owned()
+    }
+}
+
+impl IntoOldVmTracer for ExecutionDeadline {}
@@ -1,4 +1,4 @@
-use std::marker::PhantomData;
+use std::{marker::PhantomData, sync::Arc};
 
 use zksync_contracts::BaseSystemContracts;
 use zksync_state::{InMemoryStorage, StoragePtr, StorageView, WriteStorage};
@@ -140,7 +140,7 @@ impl<H: HistoryMode> VmTesterBuilder<H> {
             system_env: SystemEnv {
                 zk_porter_available: false,
                 version: ProtocolVersionId::latest(),
-                base_system_smart_contracts: BaseSystemContracts::playground(),
+                base_system_smart_contracts: Arc::new(BaseSystemContracts::playground()),
                 bootloader_gas_limit: BLOCK_GAS_LIMIT,
                 execution_mode: TxExecutionMode::VerifyExecute,
                 default_validation_computational_gas_limit: BLOCK_GAS_LIMIT,
@@ -172,7 +172,7 @@ impl<H: HistoryMode> VmTesterBuilder<H> {
         mut self,
         base_system_smart_contracts: BaseSystemContracts,
     ) -> Self {
-        self.system_env.base_system_smart_contracts = base_system_smart_contracts;
+        self.system_env.base_system_smart_contracts = Arc::new(base_system_smart_contracts);
         self
     }
 
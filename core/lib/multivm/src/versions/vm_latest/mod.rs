@@ -11,7 +11,7 @@ pub use self::{
         dispatcher::TracerDispatcher,
         traits::{ToTracerPointer, TracerPointer, VmTracer},
     },
-    types::internals::ZkSyncVmState,
+    types::internals::{PreparedBootloaderMemory, ZkSyncVmState},
     utils::transaction_encoding::TransactionVmExt,
     vm::Vm,
 };
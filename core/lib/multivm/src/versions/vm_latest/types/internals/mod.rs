@@ -2,7 +2,7 @@ pub(crate) use pubdata::PubdataInput;
 pub(crate) use snapshot::VmSnapshot;
 pub(crate) use transaction_data::TransactionData;
 pub(crate) use vm_state::new_vm_state;
-pub use vm_state::ZkSyncVmState;
+pub use vm_state::{PreparedBootloaderMemory, ZkSyncVmState};
 mod pubdata;
 mod snapshot;
 mod transaction_data;
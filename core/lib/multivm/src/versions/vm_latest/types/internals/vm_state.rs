@@ -12,7 +12,7 @@ use zk_evm_1_5_0::{
 };
 use zksync_state::{StoragePtr, WriteStorage};
 use zksync_system_constants::BOOTLOADER_ADDRESS;
-use zksync_types::{block::MiniblockHasher, Address, MiniblockNumber};
+use zksync_types::{block::MiniblockHasher, Address, MiniblockNumber, H256};
 use zksync_utils::h256_to_u256;
 
 use crate::{
@@ -57,11 +57,38 @@ fn formal_calldata_abi() -> PrimitiveValue {
     }
 }
 
+/// VM memory with the bootloader code page already populated.
+///
+/// Populating the code page is a significant part of the VM initialization cost, while it only depends
+/// on the bootloader. Thus, the prepared memory can be created once and reused for all VMs
+/// with the same base system contracts.
+#[derive(Debug, Clone)]
+pub struct PreparedBootloaderMemory<H: HistoryMode> {
+    bootloader_hash: H256,
+    memory: SimpleMemory<H>,
+}
+
+impl<H: HistoryMode> PreparedBootloaderMemory<H> {
+    pub fn new(system_env: &SystemEnv) -> Self {
+        let bootloader = &system_env.base_system_smart_contracts.bootloader;
+        let mut memory = SimpleMemory::default();
+        memory.populate(
+            vec![(BOOTLOADER_CODE_PAGE, bootloader.code.clone())],
+            Timestamp(0),
+        );
+        Self {
+            bootloader_hash: bootloader.hash,
+            memory,
+        }
+    }
+}
+
 /// Initialize the vm state and all necessary oracles
 pub(crate) fn new_vm_state<S: WriteStorage, H: HistoryMode>(
     storage: StoragePtr<S>,
     system_env: &SystemEnv,
     l1_batch_env: &L1BatchEnv,
+    prepared_memory: Option<&PreparedBootloaderMemory<H>>,
 ) -> (ZkSyncVmState<S, H>, BootloaderState) {
     let last_l2_block = if let Some(last_l2_block) = load_last_l2_block(storage.clone()) {
         last_l2_block
@@ -80,7 +107,15 @@ pub(crate) fn new_vm_state<S: WriteStorage, H: HistoryMode>(
     assert_next_block(&last_l2_block, &l1_batch_env.first_l2_block);
     let first_l2_block = l1_batch_env.first_l2_block;
     let storage_oracle: StorageOracle<S, H> = StorageOracle::new(storage.clone());
-    let mut memory = SimpleMemory::default();
+    let mut memory = if let Some(prepared_memory) = prepared_memory {
+        assert_eq!(
+            prepared_memory.bootloader_hash, system_env.base_system_smart_contracts.bootloader.hash,
+            "Prepared memory was created for another bootloader"
+        );
+        prepared_memory.memory.clone()
+    } else {
+        PreparedBootloaderMemory::new(system_env).memory
+    };
     let event_sink = InMemoryEventSink::default();
     let precompiles_processor = PrecompilesProcessorWithHistory::<H>::default();
     let mut decommittment_processor: DecommitterOracle<false, S, H> =
@@ -98,18 +133,6 @@ pub(crate) fn new_vm_state<S: WriteStorage, H: HistoryMode>(
         Timestamp(0),
     );
 
-    memory.populate(
-        vec![(
-            BOOTLOADER_CODE_PAGE,
-            system_env
-                .base_system_smart_contracts
-                .bootloader
-                .code
-                .clone(),
-        )],
-        Timestamp(0),
-    );
-
     let bootloader_initial_memory = bootloader_initial_memory(l1_batch_env);
     memory.populate_page(
         BOOTLOADER_HEAP_PAGE as usize,
@@ -18,7 +18,7 @@ use crate::{
         bootloader_state::BootloaderState,
         old_vm::{events::merge_events, history_recorder::HistoryEnabled},
         tracers::dispatcher::TracerDispatcher,
-        types::internals::{new_vm_state, VmSnapshot, ZkSyncVmState},
+        types::internals::{new_vm_state, PreparedBootloaderMemory, VmSnapshot, ZkSyncVmState},
     },
     HistoryMode,
 };
@@ -38,11 +38,25 @@ pub struct Vm<S: WriteStorage, H: HistoryMode> {
     _phantom: std::marker::PhantomData<H>,
 }
 
-impl<S: WriteStorage, H: HistoryMode> VmInterface<S, H> for Vm<S, H> {
-    type TracerDispatcher = TracerDispatcher<S, H::Vm1_5_0>;
+impl<S: WriteStorage, H: HistoryMode> Vm<S, H> {
+    /// Creates a VM reusing the memory prepared for the bootloader in `system_env`.
+    pub fn with_prepared_memory(
+        batch_env: L1BatchEnv,
+        system_env: SystemEnv,
+        storage: StoragePtr<S>,
+        prepared_memory: &PreparedBootloaderMemory<H::Vm1_5_0>,
+    ) -> Self {
+        Self::new_inner(batch_env, system_env, storage, Some(prepared_memory))
+    }
 
-    fn new(batch_env: L1BatchEnv, system_env: SystemEnv, storage: StoragePtr<S>) -> Self {
-        let (state, bootloader_state) = new_vm_state(storage.clone(), &system_env, &batch_env);
+    fn new_inner(
+        batch_env: L1BatchEnv,
+        system_env: SystemEnv,
+        storage: StoragePtr<S>,
+        prepared_memory: Option<&PreparedBootloaderMemory<H::Vm1_5_0>>,
+    ) -> Self {
+        let (state, bootloader_state) =
+            new_vm_state(storage.clone(), &system_env, &batch_env, prepared_memory);
         Self {
             bootloader_state,
             state,
@@ -53,6 +67,14 @@ impl<S: WriteStorage, H: HistoryMode> VmInterface<S, H> for Vm<S, H> {
             _phantom: Default::default(),
         }
     }
+}
+
+impl<S: WriteStorage, H: HistoryMode> VmInterface<S, H> for Vm<S, H> {
+    type TracerDispatcher = TracerDispatcher<S, H::Vm1_5_0>;
+
+    fn new(batch_env: L1BatchEnv, system_env: SystemEnv, storage: StoragePtr<S>) -> Self {
+        Self::new_inner(batch_env, system_env, storage, None)
+    }
 
     /// Push tx into memory for the future execution
     fn push_transaction(&mut self, tx: Transaction) {
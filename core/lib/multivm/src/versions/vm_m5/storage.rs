@@ -30,7 +30,7 @@ impl<T: WriteStorage> Storage for T {
     }
 
     fn load_factory_dep(&mut self, hash: H256) -> Option<Vec<u8>> {
-        ReadStorage::load_factory_dep(self, hash)
+        ReadStorage::load_factory_dep(self, hash).map(|bytecode| bytecode.to_vec())
     }
 
     fn number_of_updated_storage_slots(&self) -> usize {
@@ -52,7 +52,9 @@ impl<S: Storage, H: HistoryMode> Vm<S, H> {
             batch_env.clone().glue_into(),
             block_properties,
             system_env.execution_mode.glue_into(),
-            &system_env.base_system_smart_contracts.clone().glue_into(),
+            &(*system_env.base_system_smart_contracts)
+                .clone()
+                .glue_into(),
             system_env.bootloader_gas_limit,
         );
         Self {
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use zksync_contracts::BaseSystemContracts;
 use zksync_state::{InMemoryStorage, StoragePtr, StorageView, WriteStorage};
 
@@ -140,7 +142,7 @@ impl<H: HistoryMode> VmTesterBuilder<H> {
             system_env: SystemEnv {
                 zk_porter_available: false,
                 version: ProtocolVersionId::latest(),
-                base_system_smart_contracts: BaseSystemContracts::playground(),
+                base_system_smart_contracts: Arc::new(BaseSystemContracts::playground()),
                 gas_limit: BLOCK_GAS_LIMIT,
                 execution_mode: TxExecutionMode::VerifyExecute,
                 default_validation_computational_gas_limit: BLOCK_GAS_LIMIT,
@@ -171,7 +173,7 @@ impl<H: HistoryMode> VmTesterBuilder<H> {
         mut self,
         base_system_smart_contracts: BaseSystemContracts,
     ) -> Self {
-        self.system_env.base_system_smart_contracts = base_system_smart_contracts;
+        self.system_env.base_system_smart_contracts = Arc::new(base_system_smart_contracts);
         self
     }
 
@@ -24,6 +24,9 @@ pub enum Bucket {
     SchedulerWitnessJobsFri,
     ProofsFri,
     StorageSnapshot,
+    BatchExports,
+    CallTraces,
+    MerkleTreeSnapshots,
 }
 
 impl Bucket {
@@ -40,6 +43,9 @@ impl Bucket {
             Self::SchedulerWitnessJobsFri => "scheduler_witness_jobs_fri",
             Self::ProofsFri => "proofs_fri",
             Self::StorageSnapshot => "storage_logs_snapshots",
+            Self::BatchExports => "batch_exports",
+            Self::CallTraces => "call_traces",
+            Self::MerkleTreeSnapshots => "merkle_tree_snapshots",
         }
     }
 }
@@ -58,6 +58,7 @@ impl ProtoRepr for proto::Web3JsonRpc {
             filters_limit: self.filters_limit,
             subscriptions_limit: self.subscriptions_limit,
             pubsub_polling_interval: self.pubsub_polling_interval,
+            pubsub_db_notifications: self.pubsub_db_notifications.unwrap_or(false),
             max_nonce_ahead: *required(&self.max_nonce_ahead).context("max_nonce_ahead")?,
             gas_price_scale_factor: *required(&self.gas_price_scale_factor)
                 .context("gas_price_scale_factor")?,
@@ -69,6 +70,7 @@ impl ProtoRepr for proto::Web3JsonRpc {
                 &self.estimate_gas_acceptable_overestimation,
             )
             .context("acceptable_overestimation")?,
+            estimate_gas_optimize_search: self.estimate_gas_optimize_search.unwrap_or(false),
             l1_to_l2_transactions_compatibility_mode: *required(
                 &self.l1_to_l2_transactions_compatibility_mode,
             )
@@ -81,11 +83,25 @@ impl ProtoRepr for proto::Web3JsonRpc {
                 .map(|x| x.try_into())
                 .transpose()
                 .context("vm_execution_cache_misses_limit")?,
+            vm_execution_timeout_ms: self.vm_execution_timeout_ms,
+            eth_call_cache_size: self
+                .eth_call_cache_size
+                .map(|x| x.try_into())
+                .transpose()
+                .context("eth_call_cache_size")?,
+            estimate_gas_cache_size: self
+                .estimate_gas_cache_size
+                .map(|x| x.try_into())
+                .transpose()
+                .context("estimate_gas_cache_size")?,
+            estimate_gas_cache_ttl_ms: self.estimate_gas_cache_ttl_ms,
             vm_concurrency_limit: self
                 .vm_concurrency_limit
                 .map(|x| x.try_into())
                 .transpose()
                 .context("vm_concurrency_limit")?,
+            vm_permit_timeout_ms: self.vm_permit_timeout_ms,
+            vm_saturation_threshold: self.vm_saturation_threshold,
             factory_deps_cache_size_mb: self
                 .factory_deps_cache_size_mb
                 .map(|x| x.try_into())
@@ -101,12 +117,29 @@ impl ProtoRepr for proto::Web3JsonRpc {
                 .map(|x| x.try_into())
                 .transpose()
                 .context("latests_values_cache_size_mb")?,
+            latest_values_cache_path: self.latest_values_cache_path.clone(),
+            latest_values_cache_persistent_max_entries: self
+                .latest_values_cache_persistent_max_entries,
+            caches_warm_up_miniblocks: self.caches_warm_up_miniblocks,
+            historical_state_path: self.historical_state_path.clone(),
+            historical_state_retained_miniblocks: self.historical_state_retained_miniblocks,
             fee_history_limit: self.fee_history_limit,
+            fee_model_simulation_limit: self.fee_model_simulation_limit,
             max_batch_request_size: self
                 .max_batch_request_size
                 .map(|x| x.try_into())
                 .transpose()
                 .context("max_batch_requres_size")?,
+            max_batch_request_cost: self.max_batch_request_cost,
+            max_queued_txs_per_account: self.max_queued_txs_per_account,
+            tx_replacement_fee_bump_percent: self.tx_replacement_fee_bump_percent,
+            read_only_main_node_url: self.read_only_main_node_url.clone(),
+            max_tx_submissions_per_minute_per_sender: self
+                .max_tx_submissions_per_minute_per_sender
+                .map(|x| x.try_into())
+                .transpose()
+                .context("max_tx_submissions_per_minute_per_sender")?,
+            max_pending_txs_per_sender: self.max_pending_txs_per_sender,
             max_response_body_size_mb: self
                 .max_response_body_size_mb
                 .map(|x| x.try_into())
@@ -131,6 +164,30 @@ impl ProtoRepr for proto::Web3JsonRpc {
                 .map(|(i, k)| parse_h160(k).context(i))
                 .collect::<Result<Vec<_>, _>>()
                 .context("account_pks")?,
+            aa_trusted_token_slots: self
+                .aa_trusted_token_slots
+                .iter()
+                .enumerate()
+                .map(|(i, k)| parse_h256(k).context(i))
+                .collect::<Result<Vec<_>, _>>()
+                .context("aa_trusted_token_slots")?,
+            aa_trusted_addresses: self
+                .aa_trusted_addresses
+                .iter()
+                .enumerate()
+                .map(|(i, k)| parse_h160(k).context(i))
+                .collect::<Result<Vec<_>, _>>()
+                .context("aa_trusted_addresses")?,
+            admin_namespace_enabled: self.admin_namespace_enabled.unwrap_or(false),
+            unstable_namespace_enabled: self.unstable_namespace_enabled.unwrap_or(false),
+            sse_port: self
+                .sse_port
+                .map(u16::try_from)
+                .transpose()
+                .context("sse_port")?,
+            http_auth_token: self.http_auth_token.clone(),
+            ws_auth_token: self.ws_auth_token.clone(),
+            ipc_path: self.ipc_path.clone(),
         })
     }
     fn build(this: &Self::Type) -> Self {
@@ -146,6 +203,7 @@ impl ProtoRepr for proto::Web3JsonRpc {
             filters_limit: this.filters_limit,
             subscriptions_limit: this.subscriptions_limit,
             pubsub_polling_interval: this.pubsub_polling_interval,
+            pubsub_db_notifications: Some(this.pubsub_db_notifications),
             max_nonce_ahead: Some(this.max_nonce_ahead),
             gas_price_scale_factor: Some(this.gas_price_scale_factor),
             request_timeout: this.request_timeout,
@@ -158,6 +216,7 @@ impl ProtoRepr for proto::Web3JsonRpc {
             estimate_gas_acceptable_overestimation: Some(
                 this.estimate_gas_acceptable_overestimation,
             ),
+            estimate_gas_optimize_search: Some(this.estimate_gas_optimize_search),
             l1_to_l2_transactions_compatibility_mode: Some(
                 this.l1_to_l2_transactions_compatibility_mode,
             ),
@@ -165,7 +224,13 @@ impl ProtoRepr for proto::Web3JsonRpc {
             vm_execution_cache_misses_limit: this
                 .vm_execution_cache_misses_limit
                 .map(|x| x.try_into().unwrap()),
+            vm_execution_timeout_ms: this.vm_execution_timeout_ms,
+            eth_call_cache_size: this.eth_call_cache_size.map(|x| x.try_into().unwrap()),
+            estimate_gas_cache_size: this.estimate_gas_cache_size.map(|x| x.try_into().unwrap()),
+            estimate_gas_cache_ttl_ms: this.estimate_gas_cache_ttl_ms,
             vm_concurrency_limit: this.vm_concurrency_limit.map(|x| x.try_into().unwrap()),
+            vm_permit_timeout_ms: this.vm_permit_timeout_ms,
+            vm_saturation_threshold: this.vm_saturation_threshold,
             factory_deps_cache_size_mb: this
                 .factory_deps_cache_size_mb
                 .map(|x| x.try_into().unwrap()),
@@ -175,8 +240,23 @@ impl ProtoRepr for proto::Web3JsonRpc {
             latest_values_cache_size_mb: this
                 .latest_values_cache_size_mb
                 .map(|x| x.try_into().unwrap()),
+            latest_values_cache_path: this.latest_values_cache_path.clone(),
+            latest_values_cache_persistent_max_entries: this
+                .latest_values_cache_persistent_max_entries,
+            caches_warm_up_miniblocks: this.caches_warm_up_miniblocks,
+            historical_state_path: this.historical_state_path.clone(),
+            historical_state_retained_miniblocks: this.historical_state_retained_miniblocks,
             fee_history_limit: this.fee_history_limit,
+            fee_model_simulation_limit: this.fee_model_simulation_limit,
             max_batch_request_size: this.max_batch_request_size.map(|x| x.try_into().unwrap()),
+            max_batch_request_cost: this.max_batch_request_cost,
+            max_queued_txs_per_account: this.max_queued_txs_per_account,
+            tx_replacement_fee_bump_percent: this.tx_replacement_fee_bump_percent,
+            read_only_main_node_url: this.read_only_main_node_url.clone(),
+            max_tx_submissions_per_minute_per_sender: this
+                .max_tx_submissions_per_minute_per_sender
+                .map(|x| x.into()),
+            max_pending_txs_per_sender: this.max_pending_txs_per_sender,
             max_response_body_size_mb: this
                 .max_response_body_size_mb
                 .map(|x| x.try_into().unwrap()),
@@ -189,6 +269,22 @@ impl ProtoRepr for proto::Web3JsonRpc {
                 .iter()
                 .map(|k| format!("{:?}", k))
                 .collect(),
+            aa_trusted_token_slots: this
+                .aa_trusted_token_slots
+                .iter()
+                .map(|k| format!("{:?}", k))
+                .collect(),
+            aa_trusted_addresses: this
+                .aa_trusted_addresses
+                .iter()
+                .map(|k| format!("{:?}", k))
+                .collect(),
+            admin_namespace_enabled: Some(this.admin_namespace_enabled),
+            unstable_namespace_enabled: Some(this.unstable_namespace_enabled),
+            sse_port: this.sse_port.map(Into::into),
+            http_auth_token: this.http_auth_token.clone(),
+            ws_auth_token: this.ws_auth_token.clone(),
+            ipc_path: this.ipc_path.clone(),
         }
     }
 }
@@ -222,11 +318,18 @@ impl ProtoRepr for proto::MerkleTreeApi {
             port: required(&self.port)
                 .and_then(|p| Ok((*p).try_into()?))
                 .context("port")?,
+            max_proof_keys: self
+                .max_proof_keys
+                .map(|x| x.try_into())
+                .transpose()
+                .context("max_proof_keys")?
+                .unwrap_or_else(Self::Type::default_max_proof_keys),
         })
     }
     fn build(this: &Self::Type) -> Self {
         Self {
             port: Some(this.port.into()),
+            max_proof_keys: Some(this.max_proof_keys.try_into().unwrap()),
         }
     }
 }
@@ -0,0 +1,23 @@
+use anyhow::Context as _;
+use zksync_config::configs;
+use zksync_protobuf::{repr::ProtoRepr, required};
+
+use crate::{proto::batch_exporter as proto, read_optional_repr};
+
+impl ProtoRepr for proto::BatchExporter {
+    type Type = configs::BatchExporterConfig;
+
+    fn read(&self) -> anyhow::Result<Self::Type> {
+        Ok(Self::Type {
+            poll_interval_ms: *required(&self.poll_interval_ms).context("poll_interval_ms")?,
+            object_store: read_optional_repr(&self.object_store).context("object_store")?,
+        })
+    }
+
+    fn build(this: &Self::Type) -> Self {
+        Self {
+            poll_interval_ms: Some(this.poll_interval_ms),
+            object_store: this.object_store.as_ref().map(ProtoRepr::build),
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use anyhow::Context as _;
+use zksync_config::configs;
+use zksync_protobuf::{repr::ProtoRepr, required};
+
+use crate::{proto::call_traces_offloader as proto, read_optional_repr};
+
+impl ProtoRepr for proto::CallTracesOffloader {
+    type Type = configs::CallTracesOffloaderConfig;
+
+    fn read(&self) -> anyhow::Result<Self::Type> {
+        Ok(Self::Type {
+            offload_after_sec: *required(&self.offload_after_sec).context("offload_after_sec")?,
+            poll_interval_ms: *required(&self.poll_interval_ms).context("poll_interval_ms")?,
+            object_store: read_optional_repr(&self.object_store).context("object_store")?,
+        })
+    }
+
+    fn build(this: &Self::Type) -> Self {
+        Self {
+            offload_after_sec: Some(this.offload_after_sec),
+            poll_interval_ms: Some(this.poll_interval_ms),
+            object_store: this.object_store.as_ref().map(ProtoRepr::build),
+        }
+    }
+}
@@ -2,7 +2,7 @@ use anyhow::Context as _;
 use zksync_config::configs;
 use zksync_protobuf::{repr::ProtoRepr, required};
 
-use crate::proto::chain as proto;
+use crate::{parse_h160, proto::chain as proto};
 
 impl proto::FeeModelVersion {
     fn new(n: &configs::chain::FeeModelVersion) -> Self {
@@ -22,6 +22,26 @@ impl proto::FeeModelVersion {
     }
 }
 
+impl proto::FeeAccountRotation {
+    fn new(n: &configs::chain::FeeAccountRotation) -> Self {
+        use configs::chain::FeeAccountRotation as From;
+        match n {
+            From::Disabled => Self::Disabled,
+            From::RoundRobin => Self::RoundRobin,
+            From::PerBatch => Self::PerBatch,
+        }
+    }
+
+    fn parse(&self) -> configs::chain::FeeAccountRotation {
+        use configs::chain::FeeAccountRotation as To;
+        match self {
+            Self::Disabled => To::Disabled,
+            Self::RoundRobin => To::RoundRobin,
+            Self::PerBatch => To::PerBatch,
+        }
+    }
+}
+
 impl ProtoRepr for proto::StateKeeper {
     type Type = configs::chain::StateKeeperConfig;
     fn read(&self) -> anyhow::Result<Self::Type> {
@@ -37,6 +57,31 @@ impl ProtoRepr for proto::StateKeeper {
             miniblock_seal_queue_capacity: required(&self.miniblock_seal_queue_capacity)
                 .and_then(|x| Ok((*x).try_into()?))
                 .context("miniblock_seal_queue_capacity")?,
+            miniblock_max_tx_count: self
+                .miniblock_max_tx_count
+                .map(|x| x.try_into())
+                .transpose()
+                .context("miniblock_max_tx_count")?,
+            miniblock_min_commit_deadline_ms: self.miniblock_min_commit_deadline_ms,
+            max_gas_per_miniblock: self.max_gas_per_miniblock,
+            max_priority_ops_per_miniblock: self
+                .max_priority_ops_per_miniblock
+                .map(|x| x.try_into())
+                .transpose()
+                .context("max_priority_ops_per_miniblock")?,
+            max_priority_ops_per_batch: self
+                .max_priority_ops_per_batch
+                .map(|x| x.try_into())
+                .transpose()
+                .context("max_priority_ops_per_batch")?,
+            max_consecutive_priority_ops: self
+                .max_consecutive_priority_ops
+                .map(|x| x.try_into())
+                .transpose()
+                .context("max_consecutive_priority_ops")?,
+            max_gas_per_tx: self.max_gas_per_tx,
+            max_pubdata_per_tx: self.max_pubdata_per_tx,
+            max_factory_deps_size_per_tx: self.max_factory_deps_size_per_tx,
             max_single_tx_gas: *required(&self.max_single_tx_gas).context("max_single_tx_gas")?,
             max_allowed_l2_tx_gas_limit: *required(&self.max_allowed_l2_tx_gas_limit)
                 .context("max_allowed_l2_tx_gas_limit")?,
@@ -81,6 +126,24 @@ impl ProtoRepr for proto::StateKeeper {
                 .map(|x| x.try_into())
                 .transpose()
                 .context("enum_index_migration_chunk_size")?,
+            protective_reads_persistence_enabled: self.protective_reads_persistence_enabled,
+            seal_miniblock_on_shutdown: self.seal_miniblock_on_shutdown,
+            ntp_server: self.ntp_server.clone(),
+            max_clock_skew_ms: self.max_clock_skew_ms,
+            shadow_vm_execution: self.shadow_vm_execution,
+            batch_executor_process_isolation: self.batch_executor_process_isolation,
+            additional_fee_account_addrs: self
+                .additional_fee_account_addrs
+                .iter()
+                .enumerate()
+                .map(|(i, k)| parse_h160(k).context(i))
+                .collect::<Result<Vec<_>, _>>()
+                .context("additional_fee_account_addrs")?,
+            fee_account_rotation: self
+                .fee_account_rotation
+                .map(|x| Ok::<_, anyhow::Error>(proto::FeeAccountRotation::try_from(x)?.parse()))
+                .transpose()
+                .context("fee_account_rotation")?,
             max_circuits_per_batch: required(&self.max_circuits_per_batch)
                 .and_then(|x| Ok((*x).try_into()?))
                 .context("max_circuits_per_batch")?,
@@ -102,6 +165,21 @@ impl ProtoRepr for proto::StateKeeper {
             miniblock_seal_queue_capacity: Some(
                 this.miniblock_seal_queue_capacity.try_into().unwrap(),
             ),
+            miniblock_max_tx_count: this.miniblock_max_tx_count.map(|x| x.try_into().unwrap()),
+            miniblock_min_commit_deadline_ms: this.miniblock_min_commit_deadline_ms,
+            max_gas_per_miniblock: this.max_gas_per_miniblock,
+            max_priority_ops_per_miniblock: this
+                .max_priority_ops_per_miniblock
+                .map(|x| x.try_into().unwrap()),
+            max_priority_ops_per_batch: this
+                .max_priority_ops_per_batch
+                .map(|x| x.try_into().unwrap()),
+            max_consecutive_priority_ops: this
+                .max_consecutive_priority_ops
+                .map(|x| x.try_into().unwrap()),
+            max_gas_per_tx: this.max_gas_per_tx,
+            max_pubdata_per_tx: this.max_pubdata_per_tx,
+            max_factory_deps_size_per_tx: this.max_factory_deps_size_per_tx,
             max_single_tx_gas: Some(this.max_single_tx_gas),
             max_allowed_l2_tx_gas_limit: Some(this.max_allowed_l2_tx_gas_limit),
             reject_tx_at_geometry_percentage: Some(this.reject_tx_at_geometry_percentage),
@@ -125,6 +203,20 @@ impl ProtoRepr for proto::StateKeeper {
                 .enum_index_migration_chunk_size
                 .as_ref()
                 .map(|x| (*x).try_into().unwrap()),
+            protective_reads_persistence_enabled: this.protective_reads_persistence_enabled,
+            seal_miniblock_on_shutdown: this.seal_miniblock_on_shutdown,
+            ntp_server: this.ntp_server.clone(),
+            max_clock_skew_ms: this.max_clock_skew_ms,
+            shadow_vm_execution: this.shadow_vm_execution,
+            batch_executor_process_isolation: this.batch_executor_process_isolation,
+            additional_fee_account_addrs: this
+                .additional_fee_account_addrs
+                .iter()
+                .map(|k| format!("{:?}", k))
+                .collect(),
+            fee_account_rotation: this
+                .fee_account_rotation
+                .map(|x| proto::FeeAccountRotation::new(&x).into()),
             max_circuits_per_batch: Some(this.max_circuits_per_batch.try_into().unwrap()),
         }
     }
@@ -157,6 +249,14 @@ impl ProtoRepr for proto::Mempool {
             stuck_tx_timeout: *required(&self.stuck_tx_timeout).context("stuck_tx_timeout")?,
             remove_stuck_txs: *required(&self.remove_stuck_txs).context("remove_stuck_txs")?,
             delay_interval: *required(&self.delay_interval).context("delay_interval")?,
+            tx_deny_list: self.tx_deny_list.clone(),
+            tx_deny_list_reload_interval_sec: self.tx_deny_list_reload_interval_sec,
+            tx_denied_calldata_prefixes: self.tx_denied_calldata_prefixes.clone(),
+            tx_deployment_only_windows: self.tx_deployment_only_windows.clone(),
+            max_l2_transactions: self.max_l2_transactions,
+            score_fee_weight: self.score_fee_weight,
+            score_age_weight: self.score_age_weight,
+            score_rejection_penalty: self.score_rejection_penalty,
         })
     }
 
@@ -168,6 +268,14 @@ impl ProtoRepr for proto::Mempool {
             stuck_tx_timeout: Some(this.stuck_tx_timeout),
             remove_stuck_txs: Some(this.remove_stuck_txs),
             delay_interval: Some(this.delay_interval),
+            tx_deny_list: this.tx_deny_list.clone(),
+            tx_deny_list_reload_interval_sec: this.tx_deny_list_reload_interval_sec,
+            tx_denied_calldata_prefixes: this.tx_denied_calldata_prefixes.clone(),
+            tx_deployment_only_windows: this.tx_deployment_only_windows.clone(),
+            max_l2_transactions: this.max_l2_transactions,
+            score_fee_weight: this.score_fee_weight,
+            score_age_weight: this.score_age_weight,
+            score_rejection_penalty: this.score_rejection_penalty,
         }
     }
 }
@@ -0,0 +1,35 @@
+use anyhow::Context as _;
+use zksync_config::configs;
+use zksync_protobuf::{repr::ProtoRepr, required};
+
+use crate::proto::change_stream as proto;
+
+impl ProtoRepr for proto::ChangeStream {
+    type Type = configs::ChangeStreamConfig;
+
+    fn read(&self) -> anyhow::Result<Self::Type> {
+        Ok(Self::Type {
+            nats_url: required(&self.nats_url).context("nats_url")?.clone(),
+            nats_credentials_path: self.nats_credentials_path.clone(),
+            nats_tls_required: self.nats_tls_required.unwrap_or(false),
+            nats_root_certificates_path: self.nats_root_certificates_path.clone(),
+            subject_prefix: required(&self.subject_prefix)
+                .context("subject_prefix")?
+                .clone(),
+            queue_capacity: (*required(&self.queue_capacity).context("queue_capacity")?)
+                .try_into()
+                .context("queue_capacity")?,
+        })
+    }
+
+    fn build(this: &Self::Type) -> Self {
+        Self {
+            nats_url: Some(this.nats_url.clone()),
+            nats_credentials_path: this.nats_credentials_path.clone(),
+            nats_tls_required: Some(this.nats_tls_required),
+            nats_root_certificates_path: this.nats_root_certificates_path.clone(),
+            subject_prefix: Some(this.subject_prefix.clone()),
+            queue_capacity: Some(this.queue_capacity as u64),
+        }
+    }
+}
@@ -13,6 +13,7 @@ impl ProtoRepr for proto::Contracts {
         let bridges = required(&self.bridges).context("bridges")?;
         let erc20 = required(&bridges.erc20).context("erc20")?;
         let weth_bridge = required(&bridges.weth).context("weth_bridge")?;
+        let shared_bridge = bridges.shared.as_ref();
         Ok(Self::Type {
             governance_addr: required(&l1.governance_addr)
                 .and_then(|x| parse_h160(x))
@@ -47,6 +48,16 @@ impl ProtoRepr for proto::Contracts {
                 .map(|x| parse_h160(x))
                 .transpose()
                 .context("l2_weth_bridge_addr")?,
+            l1_shared_bridge_proxy_addr: shared_bridge
+                .and_then(|bridge| bridge.l1_address.as_ref())
+                .map(|x| parse_h160(x))
+                .transpose()
+                .context("l1_shared_bridge_proxy_addr")?,
+            l2_shared_bridge_addr: shared_bridge
+                .and_then(|bridge| bridge.l2_address.as_ref())
+                .map(|x| parse_h160(x))
+                .transpose()
+                .context("l2_shared_bridge_addr")?,
             l2_testnet_paymaster_addr: l2
                 .testnet_paymaster_addr
                 .as_ref()
@@ -81,6 +92,10 @@ impl ProtoRepr for proto::Contracts {
                     l1_address: this.l1_weth_bridge_proxy_addr.map(|a| format!("{:?}", a)),
                     l2_address: this.l2_weth_bridge_addr.map(|a| format!("{:?}", a)),
                 }),
+                shared: Some(proto::Bridge {
+                    l1_address: this.l1_shared_bridge_proxy_addr.map(|a| format!("{:?}", a)),
+                    l2_address: this.l2_shared_bridge_addr.map(|a| format!("{:?}", a)),
+                }),
             }),
         }
     }
@@ -25,10 +25,46 @@ impl proto::MerkleTreeMode {
     }
 }
 
+impl proto::MerkleTreeRocksdbPreset {
+    fn new(x: &configs::database::MerkleTreeRocksdbPreset) -> Self {
+        use configs::database::MerkleTreeRocksdbPreset as From;
+        match x {
+            From::Archive => Self::Archive,
+            From::Pruned => Self::Pruned,
+        }
+    }
+
+    fn parse(&self) -> configs::database::MerkleTreeRocksdbPreset {
+        use configs::database::MerkleTreeRocksdbPreset as To;
+        match self {
+            Self::Archive => To::Archive,
+            Self::Pruned => To::Pruned,
+        }
+    }
+}
+
+impl proto::MerkleTreeCompactionStyle {
+    fn new(x: &configs::database::MerkleTreeCompactionStyle) -> Self {
+        use configs::database::MerkleTreeCompactionStyle as From;
+        match x {
+            From::Level => Self::Level,
+            From::Universal => Self::Universal,
+        }
+    }
+
+    fn parse(&self) -> configs::database::MerkleTreeCompactionStyle {
+        use configs::database::MerkleTreeCompactionStyle as To;
+        match self {
+            Self::Level => To::Level,
+            Self::Universal => To::Universal,
+        }
+    }
+}
+
 impl ProtoRepr for proto::MerkleTree {
     type Type = configs::database::MerkleTreeConfig;
     fn read(&self) -> anyhow::Result<Self::Type> {
-        Ok(Self::Type {
+        let config = Self::Type {
             path: required(&self.path).context("path")?.clone(),
             mode: required(&self.mode)
                 .and_then(|x| Ok(proto::MerkleTreeMode::try_from(*x)?))
@@ -48,7 +84,31 @@ impl ProtoRepr for proto::MerkleTree {
             max_l1_batches_per_iter: required(&self.max_l1_batches_per_iter)
                 .and_then(|x| Ok((*x).try_into()?))
                 .context("max_l1_batches_per_iter")?,
-        })
+            hashing_thread_count: self
+                .hashing_thread_count
+                .map(|x| x.try_into())
+                .transpose()
+                .context("hashing_thread_count")?,
+            pruning_retention_sec: self.pruning_retention_sec,
+            consistency_check_interval_sec: self.consistency_check_interval_sec,
+            rocksdb_preset: self
+                .rocksdb_preset
+                .map(|x| anyhow::Ok(proto::MerkleTreeRocksdbPreset::try_from(x)?.parse()))
+                .transpose()
+                .context("rocksdb_preset")?,
+            compaction_style: self
+                .compaction_style
+                .map(|x| anyhow::Ok(proto::MerkleTreeCompactionStyle::try_from(x)?.parse()))
+                .transpose()
+                .context("compaction_style")?,
+            separate_leaves_column_family: self.separate_leaves_column_family,
+            snapshot_export_interval_sec: self.snapshot_export_interval_sec,
+            lag_l1_batches: self.lag_l1_batches,
+            throttle_latency_threshold_ms: self.throttle_latency_threshold_ms,
+            in_memory: self.in_memory.unwrap_or(false),
+        };
+        config.validate()?;
+        Ok(config)
     }
 
     fn build(this: &Self::Type) -> Self {
@@ -60,6 +120,22 @@ impl ProtoRepr for proto::MerkleTree {
             memtable_capacity_mb: Some(this.memtable_capacity_mb.try_into().unwrap()),
             stalled_writes_timeout_sec: Some(this.stalled_writes_timeout_sec),
             max_l1_batches_per_iter: Some(this.max_l1_batches_per_iter.try_into().unwrap()),
+            hashing_thread_count: this.hashing_thread_count.map(|x| x.try_into().unwrap()),
+            pruning_retention_sec: this.pruning_retention_sec,
+            consistency_check_interval_sec: this.consistency_check_interval_sec,
+            rocksdb_preset: this
+                .rocksdb_preset
+                .as_ref()
+                .map(|x| proto::MerkleTreeRocksdbPreset::new(x).into()),
+            compaction_style: this
+                .compaction_style
+                .as_ref()
+                .map(|x| proto::MerkleTreeCompactionStyle::new(x).into()),
+            separate_leaves_column_family: this.separate_leaves_column_family,
+            snapshot_export_interval_sec: this.snapshot_export_interval_sec,
+            lag_l1_batches: this.lag_l1_batches,
+            throttle_latency_threshold_ms: this.throttle_latency_threshold_ms,
+            in_memory: Some(this.in_memory),
         }
     }
 }
@@ -83,6 +159,14 @@ impl ProtoRepr for proto::Db {
     }
 }
 
+impl proto::ComponentQueryTimeout {
+    fn read(&self) -> anyhow::Result<(String, u64)> {
+        let component = required(&self.component).context("component")?.clone();
+        let timeout_ms = *required(&self.timeout_ms).context("timeout_ms")?;
+        Ok((component, timeout_ms))
+    }
+}
+
 impl ProtoRepr for proto::Postgres {
     type Type = configs::database::PostgresConfig;
 
@@ -103,12 +187,29 @@ impl ProtoRepr for proto::Postgres {
             master_url: self.server_url.clone(),
             replica_url,
             prover_url: self.prover_url.clone(),
+            read_replica_urls: self.read_replica_urls.clone(),
+            max_replica_lag_ms: self.max_replica_lag_ms,
             max_connections: self.max_connections,
             max_connections_master: self.max_connections_master,
+            max_connections_api: self.max_connections_api,
+            max_connections_state_keeper: self.max_connections_state_keeper,
+            max_connections_metadata_calculator: self.max_connections_metadata_calculator,
+            max_connections_housekeeping: self.max_connections_housekeeping,
             acquire_timeout_sec: self.acquire_timeout_sec,
             statement_timeout_sec: self.statement_timeout_sec,
             long_connection_threshold_ms: self.long_connection_threshold_ms,
             slow_query_threshold_ms: self.slow_query_threshold_ms,
+            slow_query_explain_sample_rate: self.slow_query_explain_sample_rate,
+            query_timeout_ms: self.query_timeout_ms,
+            component_query_timeouts_ms: self
+                .component_query_timeouts
+                .iter()
+                .enumerate()
+                .map(|(i, timeout)| timeout.read().context(i))
+                .collect::<anyhow::Result<_>>()
+                .context("component_query_timeouts")?,
+            query_max_retries: self.query_max_retries,
+            use_binary_copy: self.use_binary_copy,
             test_server_url,
             test_prover_url,
         })
@@ -119,12 +220,30 @@ impl ProtoRepr for proto::Postgres {
             server_url: this.master_url.clone(),
             server_replica_url: this.replica_url.clone(),
             prover_url: this.prover_url.clone(),
+            read_replica_urls: this.read_replica_urls.clone(),
+            max_replica_lag_ms: this.max_replica_lag_ms,
             max_connections: this.max_connections,
             max_connections_master: this.max_connections_master,
+            max_connections_api: this.max_connections_api,
+            max_connections_state_keeper: this.max_connections_state_keeper,
+            max_connections_metadata_calculator: this.max_connections_metadata_calculator,
+            max_connections_housekeeping: this.max_connections_housekeeping,
             acquire_timeout_sec: this.acquire_timeout_sec,
             statement_timeout_sec: this.statement_timeout_sec,
             long_connection_threshold_ms: this.long_connection_threshold_ms,
             slow_query_threshold_ms: this.slow_query_threshold_ms,
+            slow_query_explain_sample_rate: this.slow_query_explain_sample_rate,
+            query_timeout_ms: this.query_timeout_ms,
+            component_query_timeouts: this
+                .component_query_timeouts_ms
+                .iter()
+                .map(|(component, &timeout_ms)| proto::ComponentQueryTimeout {
+                    component: Some(component.clone()),
+                    timeout_ms: Some(timeout_ms),
+                })
+                .collect(),
+            query_max_retries: this.query_max_retries,
+            use_binary_copy: this.use_binary_copy,
             test: Some(proto::TestDatabase {
                 server_url: this.test_server_url.clone(),
                 prover_url: this.test_prover_url.clone(),
@@ -2,7 +2,7 @@ use anyhow::Context as _;
 use zksync_config::configs::{self};
 use zksync_protobuf::{required, ProtoRepr};
 
-use crate::{proto::eth as proto, read_optional_repr};
+use crate::{parse_h160, parse_h256, proto::eth as proto, read_optional_repr};
 
 impl proto::ProofSendingMode {
     fn new(x: &configs::eth_sender::ProofSendingMode) -> Self {
@@ -68,6 +68,7 @@ impl ProtoRepr for proto::Eth {
             gas_adjuster: read_optional_repr(&self.gas_adjuster).context("gas_adjuster")?,
             watcher: read_optional_repr(&self.watcher).context("watcher")?,
             web3_url: required(&self.web3_url).context("web3_url")?.clone(),
+            remote_signer: read_optional_repr(&self.remote_signer).context("remote_signer")?,
         })
     }
 
@@ -77,6 +78,7 @@ impl ProtoRepr for proto::Eth {
             gas_adjuster: this.gas_adjuster.as_ref().map(ProtoRepr::build),
             watcher: this.watcher.as_ref().map(ProtoRepr::build),
             web3_url: Some(this.web3_url.clone()),
+            remote_signer: this.remote_signer.as_ref().map(ProtoRepr::build),
         }
     }
 }
@@ -132,6 +134,10 @@ impl ProtoRepr for proto::Sender {
                 .and_then(|x| Ok(proto::ProofLoadingMode::try_from(*x)?))
                 .context("proof_loading_mode")?
                 .parse(),
+            max_commit_blob_base_fee: self.max_commit_blob_base_fee,
+            commit_blob_base_fee_deadline: self
+                .commit_blob_base_fee_deadline
+                .unwrap_or_else(Self::Type::default_commit_blob_base_fee_deadline),
         })
     }
 
@@ -163,6 +169,8 @@ impl ProtoRepr for proto::Sender {
                 proto::PubdataSendingMode::new(&this.pubdata_sending_mode).into(),
             ),
             proof_loading_mode: Some(proto::ProofLoadingMode::new(&this.proof_loading_mode).into()),
+            max_commit_blob_base_fee: this.max_commit_blob_base_fee,
+            commit_blob_base_fee_deadline: Some(this.commit_blob_base_fee_deadline),
         }
     }
 }
@@ -228,6 +236,8 @@ impl ProtoRepr for proto::EthWatch {
             confirmations_for_eth_event: self.confirmations_for_eth_event,
             eth_node_poll_interval: *required(&self.eth_node_poll_interval)
                 .context("eth_node_poll_interval")?,
+            governance_alerts_webhook_url: self.governance_alerts_webhook_url.clone(),
+            governance_alerts_lead_time_sec: self.governance_alerts_lead_time_sec,
         })
     }
 
@@ -235,6 +245,44 @@ impl ProtoRepr for proto::EthWatch {
         Self {
             confirmations_for_eth_event: this.confirmations_for_eth_event,
             eth_node_poll_interval: Some(this.eth_node_poll_interval),
+            governance_alerts_webhook_url: this.governance_alerts_webhook_url.clone(),
+            governance_alerts_lead_time_sec: this.governance_alerts_lead_time_sec,
+        }
+    }
+}
+
+impl ProtoRepr for proto::RemoteSigner {
+    type Type = configs::eth_sender::RemoteSignerConfig;
+    fn read(&self) -> anyhow::Result<Self::Type> {
+        Ok(Self::Type {
+            urls: self.urls.clone(),
+            operator_address: required(&self.operator_address)
+                .and_then(|x| parse_h160(x))
+                .context("operator_address")?,
+            blob_operator_address: self
+                .blob_operator_address
+                .as_ref()
+                .map(|x| parse_h160(x))
+                .transpose()
+                .context("blob_operator_address")?,
+            request_signing_key: self
+                .request_signing_key
+                .as_ref()
+                .map(|x| parse_h256(x))
+                .transpose()
+                .context("request_signing_key")?,
+            request_timeout_ms: *required(&self.request_timeout_ms)
+                .context("request_timeout_ms")?,
+        })
+    }
+
+    fn build(this: &Self::Type) -> Self {
+        Self {
+            urls: this.urls.clone(),
+            operator_address: Some(format!("{:?}", this.operator_address)),
+            blob_operator_address: this.blob_operator_address.map(|x| format!("{:?}", x)),
+            request_signing_key: this.request_signing_key.map(|x| format!("{:?}", x)),
+            request_timeout_ms: Some(this.request_timeout_ms),
         }
     }
 }
@@ -37,6 +37,10 @@ impl ProtoRepr for proto::GeneralConfig {
             snapshot_creator: read_optional_repr(&self.snapshot_creator)
                 .context("snapshot_creator")?,
             observability: read_optional_repr(&self.observability).context("observability")?,
+            batch_exporter: read_optional_repr(&self.batch_exporter).context("batch_exporter")?,
+            call_traces_offloader: read_optional_repr(&self.call_traces_offloader)
+                .context("call_traces_offloader")?,
+            change_stream: read_optional_repr(&self.change_stream).context("change_stream")?,
         })
     }
 
@@ -68,6 +72,9 @@ impl ProtoRepr for proto::GeneralConfig {
             eth: this.eth.as_ref().map(ProtoRepr::build),
             snapshot_creator: this.snapshot_creator.as_ref().map(ProtoRepr::build),
             observability: this.observability.as_ref().map(ProtoRepr::build),
+            batch_exporter: this.batch_exporter.as_ref().map(ProtoRepr::build),
+            call_traces_offloader: this.call_traces_offloader.as_ref().map(ProtoRepr::build),
+            change_stream: this.change_stream.as_ref().map(ProtoRepr::build),
         }
     }
 }
@@ -5,6 +5,9 @@
 //! * protobuf json format
 
 mod api;
+mod batch_exporter;
+mod call_traces_offloader;
+mod change_stream;
 mod chain;
 mod circuit_breaker;
 mod contract_verifier;
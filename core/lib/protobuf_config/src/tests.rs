@@ -37,6 +37,9 @@ fn test_encoding() {
     test_encode_all_formats::<ReprConv<proto::prover::ProofDataHandler>>(rng);
     test_encode_all_formats::<ReprConv<proto::snapshot_creator::SnapshotsCreator>>(rng);
     test_encode_all_formats::<ReprConv<proto::observability::Observability>>(rng);
+    test_encode_all_formats::<ReprConv<proto::batch_exporter::BatchExporter>>(rng);
+    test_encode_all_formats::<ReprConv<proto::call_traces_offloader::CallTracesOffloader>>(rng);
+    test_encode_all_formats::<ReprConv<proto::change_stream::ChangeStream>>(rng);
 }
 
 pub fn decode_yaml_repr<T: ProtoRepr>(
@@ -0,0 +1,101 @@
+//! Process-wide cache for contract bytecodes.
+
+use std::{mem, sync::Arc};
+
+use zksync_types::{MiniblockNumber, H256};
+
+use crate::cache::{lru_cache::LruCache, stats::CacheStats, CacheValue};
+
+/// Information about the miniblock a [`CachedBytecode`] was inserted at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum InsertedAt {
+    /// Exact miniblock; known if the bytecode was loaded from Postgres.
+    Exact(MiniblockNumber),
+    /// Upper bound on the miniblock; e.g., the last miniblock processed by the state keeper RocksDB cache.
+    NoLaterThan(MiniblockNumber),
+    /// No information about the miniblock.
+    Unknown,
+}
+
+impl InsertedAt {
+    pub fn for_upper_bound(miniblock: Option<MiniblockNumber>) -> Self {
+        miniblock.map_or(Self::Unknown, Self::NoLaterThan)
+    }
+
+    /// Checks whether the bytecode is visible at the specified miniblock. Returns `None` if this cannot be determined
+    /// from the available information.
+    pub fn is_visible_at(self, miniblock: MiniblockNumber) -> Option<bool> {
+        match self {
+            Self::Exact(inserted_at) => Some(inserted_at <= miniblock),
+            Self::NoLaterThan(inserted_at) if inserted_at <= miniblock => Some(true),
+            Self::NoLaterThan(_) | Self::Unknown => None,
+        }
+    }
+}
+
+/// Bytecode stored in [`BytecodeCache`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CachedBytecode {
+    pub bytecode: Arc<[u8]>,
+    pub inserted_at: InsertedAt,
+}
+
+impl CacheValue<H256> for CachedBytecode {
+    fn cache_weight(&self) -> u32 {
+        (self.bytecode.len() + mem::size_of::<InsertedAt>())
+            .try_into()
+            .expect("Cached bytes are too large")
+    }
+}
+
+/// Cache for contract bytecodes (aka factory deps) keyed by the bytecode hash that can be shared among
+/// all components of a process executing transactions (e.g., the API sandbox, state keeper and witness input producer),
+/// so that hot contract bytecodes are stored in memory only once.
+///
+/// The cache is cheaply cloneable; all clones refer to the same underlying cache. Bytecodes are reference-counted,
+/// so a bytecode evicted from the cache is freed only after all components using it drop their references.
+#[derive(Debug, Clone)]
+pub struct BytecodeCache(LruCache<H256, CachedBytecode>);
+
+impl BytecodeCache {
+    pub(crate) const NAME: &'static str = "factory_deps_cache";
+
+    /// Creates a cache with the specified capacity measured in bytes. If the capacity is zero,
+    /// the cache is disabled.
+    pub fn new(capacity: u64) -> Self {
+        Self(LruCache::new(Self::NAME, capacity))
+    }
+
+    pub(crate) fn get(&self, hash: &H256) -> Option<CachedBytecode> {
+        self.0.get(hash)
+    }
+
+    pub(crate) fn insert(&self, hash: H256, bytecode: Arc<[u8]>, inserted_at: InsertedAt) {
+        let value = CachedBytecode {
+            bytecode,
+            inserted_at,
+        };
+        self.0.insert(hash, value);
+    }
+
+    /// Returns statistics for this cache, or `None` if the cache is disabled.
+    #[must_use]
+    pub fn stats(&self) -> Option<CacheStats> {
+        self.0.stats()
+    }
+
+    /// Removes all bytecodes from this cache.
+    pub fn clear(&self) {
+        self.0.clear();
+    }
+
+    /// Changes the capacity of this cache (in bytes) retaining as many bytecodes as possible. Returns the previous
+    /// capacity. All clones of the cache are affected.
+    pub fn resize(&self, capacity: u64) -> u64 {
+        self.0.resize(capacity)
+    }
+
+    pub(crate) fn retain(&self, mut predicate: impl FnMut(&CachedBytecode) -> bool) {
+        self.0.retain(|_, bytecode| predicate(bytecode));
+    }
+}
@@ -0,0 +1,177 @@
+//! Invalidation of caches after a block revert or reorg.
+
+use std::{
+    fmt,
+    sync::{Arc, RwLock},
+};
+
+use anyhow::Context as _;
+use zksync_dal::{Connection, Core, CoreDal};
+use zksync_types::{L1BatchNumber, MiniblockNumber};
+
+use crate::cache::bytecode_cache::BytecodeCache;
+
+/// Information about reverted blocks passed to [`InvalidateCache`] implementations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RevertedBlocks {
+    /// Last L1 batch retained after the revert.
+    pub last_l1_batch_to_keep: L1BatchNumber,
+    /// Last miniblock retained after the revert. This is the last miniblock in [`Self::last_l1_batch_to_keep`].
+    pub last_miniblock_to_keep: MiniblockNumber,
+}
+
+impl RevertedBlocks {
+    /// Creates information about reverted blocks given the last L1 batch retained after the revert.
+    ///
+    /// # Errors
+    ///
+    /// Propagates Postgres errors. Returns an error if the L1 batch is not present in Postgres.
+    pub async fn new(
+        connection: &mut Connection<'_, Core>,
+        last_l1_batch_to_keep: L1BatchNumber,
+    ) -> anyhow::Result<Self> {
+        let (_, last_miniblock_to_keep) = connection
+            .blocks_dal()
+            .get_miniblock_range_of_l1_batch(last_l1_batch_to_keep)
+            .await?
+            .with_context(|| format!("L1 batch #{last_l1_batch_to_keep} is missing in Postgres"))?;
+        Ok(Self {
+            last_l1_batch_to_keep,
+            last_miniblock_to_keep,
+        })
+    }
+}
+
+/// Cache that can purge entries affected by reverted blocks.
+pub trait InvalidateCache: fmt::Debug + Send + Sync {
+    /// Removes entries that could have been derived from the reverted blocks (i.e., blocks after
+    /// the ones specified in `reverted`).
+    fn invalidate(&self, reverted: RevertedBlocks);
+}
+
+impl InvalidateCache for BytecodeCache {
+    fn invalidate(&self, reverted: RevertedBlocks) {
+        // Bytecodes without the exact insertion miniblock could have been inserted in reverted miniblocks as well,
+        // so we remove them conservatively unless they are known to be inserted before the revert.
+        self.retain(|bytecode| {
+            bytecode
+                .inserted_at
+                .is_visible_at(reverted.last_miniblock_to_keep)
+                .unwrap_or(false)
+        });
+    }
+}
+
+/// Channel for invalidating caches after a block revert or reorg. Caches are [registered](Self::register())
+/// by components owning them; the block reverter then [invalidates](Self::invalidate()) all registered caches
+/// at once after rolling back Postgres.
+///
+/// Reverts are remembered by the invalidator and applied to caches registered after them. This allows reverting blocks
+/// on node startup, before the node components (and thus caches, some of which persist across restarts) are created.
+///
+/// The invalidator is cheaply cloneable; all clones refer to the same set of caches.
+#[derive(Debug, Clone, Default)]
+pub struct CacheInvalidator {
+    inner: Arc<RwLock<CacheInvalidatorInner>>,
+}
+
+#[derive(Debug, Default)]
+struct CacheInvalidatorInner {
+    caches: Vec<Arc<dyn InvalidateCache>>,
+    /// Revert retaining the least number of blocks among all reverts performed so far.
+    earliest_revert: Option<RevertedBlocks>,
+}
+
+impl CacheInvalidator {
+    /// Registers a cache to be invalidated. If blocks were reverted before the cache is registered,
+    /// the cache is invalidated immediately.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned.
+    pub fn register(&self, cache: Arc<dyn InvalidateCache>) {
+        tracing::debug!("Registering cache for invalidation: {cache:?}");
+        let mut inner = self.inner.write().expect("cache invalidator is poisoned");
+        if let Some(reverted) = inner.earliest_revert {
+            tracing::info!(
+                "Invalidating cache {cache:?} after revert to L1 batch #{} (miniblock #{})",
+                reverted.last_l1_batch_to_keep,
+                reverted.last_miniblock_to_keep
+            );
+            cache.invalidate(reverted);
+        }
+        inner.caches.push(cache);
+    }
+
+    /// Invalidates all registered caches. Must be called after the blocks are removed from Postgres, so that
+    /// the caches are not repopulated with data from the reverted blocks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the internal lock is poisoned.
+    pub fn invalidate(&self, reverted: RevertedBlocks) {
+        let mut inner = self.inner.write().expect("cache invalidator is poisoned");
+        tracing::info!(
+            "Invalidating {} caches after revert to L1 batch #{} (miniblock #{})",
+            inner.caches.len(),
+            reverted.last_l1_batch_to_keep,
+            reverted.last_miniblock_to_keep
+        );
+        for cache in &inner.caches {
+            cache.invalidate(reverted);
+        }
+
+        let earliest_revert = inner.earliest_revert.get_or_insert(reverted);
+        if reverted.last_miniblock_to_keep < earliest_revert.last_miniblock_to_keep {
+            *earliest_revert = reverted;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    struct MockCache(Mutex<Vec<RevertedBlocks>>);
+
+    impl InvalidateCache for MockCache {
+        fn invalidate(&self, reverted: RevertedBlocks) {
+            self.0.lock().unwrap().push(reverted);
+        }
+    }
+
+    fn reverted_blocks(number: u32) -> RevertedBlocks {
+        RevertedBlocks {
+            last_l1_batch_to_keep: L1BatchNumber(number),
+            last_miniblock_to_keep: MiniblockNumber(number * 10),
+        }
+    }
+
+    #[test]
+    fn reverts_are_applied_to_caches_registered_later() {
+        let invalidator = CacheInvalidator::default();
+        let early_cache = Arc::new(MockCache::default());
+        invalidator.register(early_cache.clone());
+
+        invalidator.invalidate(reverted_blocks(5));
+        invalidator.invalidate(reverted_blocks(3));
+        invalidator.invalidate(reverted_blocks(4));
+        assert_eq!(
+            *early_cache.0.lock().unwrap(),
+            [reverted_blocks(5), reverted_blocks(3), reverted_blocks(4)]
+        );
+
+        let late_cache = Arc::new(MockCache::default());
+        invalidator.register(late_cache.clone());
+        assert_eq!(*late_cache.0.lock().unwrap(), [reverted_blocks(3)]);
+
+        invalidator.invalidate(reverted_blocks(2));
+        assert_eq!(
+            *late_cache.0.lock().unwrap(),
+            [reverted_blocks(3), reverted_blocks(2)]
+        );
+    }
+}
@@ -1,14 +1,48 @@
-use std::hash::Hash;
+use std::{
+    hash::Hash,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock, RwLockReadGuard,
+    },
+    thread,
+};
 
 use crate::cache::{
     metrics::{Method, RequestOutcome, METRICS},
+    stats::{CacheCounters, CacheStats},
     CacheValue, MokaBase,
 };
+
+/// Counters for [`LruCache`]. Evictions are not observed directly; instead, they are estimated based
+/// on the number of inserted and explicitly removed entries.
+#[derive(Debug, Default)]
+struct LruCacheCounters {
+    base: CacheCounters,
+    insertions: AtomicU64,
+    removals: AtomicU64,
+}
+
 /// Cache implementation that uses LRU eviction policy.
+///
+/// The cache is cheaply cloneable; all clones refer to the same underlying cache (including after
+/// [resizing](Self::resize())).
 #[derive(Debug, Clone)]
 pub struct LruCache<K: Eq + Hash, V> {
     name: &'static str,
+    inner: Arc<RwLock<LruCacheInner<K, V>>>,
+    counters: Arc<LruCacheCounters>,
+}
+
+#[derive(Debug)]
+struct LruCacheInner<K, V> {
+    capacity: u64,
     cache: Option<MokaBase<K, V>>,
+    /// Cache replaced by the latest [resize](LruCache::resize()) while its entries are migrated to `cache`
+    /// in the background. Lookups missing `cache` fall back to this cache, and all mutations invalidate
+    /// the affected entries in it.
+    prev_cache: Option<MokaBase<K, V>>,
+    /// Incremented on each resize; used to stop outdated migrations.
+    generation: u64,
 }
 
 impl<K, V> LruCache<K, V>
@@ -22,27 +56,45 @@ where
     ///
     /// Panics if an invalid cache capacity is provided.
     pub fn new(name: &'static str, capacity: u64) -> Self {
-        let cache = if capacity == 0 {
-            None
-        } else {
-            Some(
-                MokaBase::<K, V>::builder()
-                    .weigher(|_, value| value.cache_weight())
-                    .max_capacity(capacity)
-                    .build(),
-            )
+        let inner = LruCacheInner {
+            capacity,
+            cache: Self::build_base(capacity),
+            prev_cache: None,
+            generation: 0,
         };
+        Self {
+            name,
+            inner: Arc::new(RwLock::new(inner)),
+            counters: Arc::default(),
+        }
+    }
 
-        Self { name, cache }
+    fn build_base(capacity: u64) -> Option<MokaBase<K, V>> {
+        (capacity > 0).then(|| {
+            MokaBase::<K, V>::builder()
+                .weigher(|_, value| value.cache_weight())
+                .max_capacity(capacity)
+                .build()
+        })
+    }
+
+    fn read_inner(&self) -> RwLockReadGuard<'_, LruCacheInner<K, V>> {
+        self.inner.read().expect("LRU cache is poisoned")
     }
 
     /// Gets an entry and pulls it to the front if it exists.
     pub fn get(&self, key: &K) -> Option<V> {
         let latency = METRICS.latency[&(self.name, Method::Get)].start();
-        let entry = self.cache.as_ref()?.get(key);
+        let inner = self.read_inner();
+        let cache = inner.cache.as_ref()?;
         // ^ We intentionally don't report metrics if there's no real cache.
+        let entry = cache
+            .get(key)
+            .or_else(|| inner.prev_cache.as_ref()?.get(key));
+        drop(inner);
 
         latency.observe();
+        self.counters.base.observe_lookup(entry.is_some());
         let request_outcome = if entry.is_some() {
             RequestOutcome::Hit
         } else {
@@ -56,41 +108,243 @@ where
     /// Pushes an entry and performs LRU cache operations.
     pub fn insert(&self, key: K, value: V) {
         let latency = METRICS.latency[&(self.name, Method::Insert)].start();
-        let Some(cache) = self.cache.as_ref() else {
+        let inner = self.read_inner();
+        let Some(cache) = inner.cache.as_ref() else {
             return;
         };
         // ^ We intentionally don't report metrics if there's no real cache.
+        if !cache.contains_key(&key) {
+            self.counters.insertions.fetch_add(1, Ordering::Relaxed);
+        }
+        // Must be performed before the insertion so that a concurrent migration doesn't overwrite the new value.
+        if let Some(prev_cache) = &inner.prev_cache {
+            prev_cache.invalidate(&key);
+        }
         cache.insert(key, value);
+        drop(inner);
 
         latency.observe();
         self.report_size();
     }
 
     pub(crate) fn report_size(&self) {
-        if let Some(cache) = &self.cache {
-            METRICS.len[&self.name].set(cache.entry_count());
-            METRICS.used_memory[&self.name].set(cache.weighted_size());
-        }
+        let inner = self.read_inner();
+        let (len, used_memory) = inner
+            .cache
+            .as_ref()
+            .map_or((0, 0), |cache| (cache.entry_count(), cache.weighted_size()));
+        METRICS.len[&self.name].set(len);
+        METRICS.used_memory[&self.name].set(used_memory);
     }
 
     /// Removes the specified key from this cache.
     pub fn remove(&self, key: &K) {
-        if let Some(cache) = &self.cache {
-            cache.invalidate(key);
+        let inner = self.read_inner();
+        if let Some(prev_cache) = &inner.prev_cache {
+            prev_cache.invalidate(key);
+        }
+        if let Some(cache) = &inner.cache {
+            if cache.contains_key(key) {
+                cache.invalidate(key);
+                self.counters.removals.fetch_add(1, Ordering::Relaxed);
+            }
         }
     }
 
     /// Removes all entries from this cache.
     pub fn clear(&self) {
-        if let Some(cache) = &self.cache {
+        let inner = self.read_inner();
+        if let Some(prev_cache) = &inner.prev_cache {
+            prev_cache.invalidate_all();
+        }
+        if let Some(cache) = &inner.cache {
+            self.counters
+                .removals
+                .fetch_add(cache.entry_count(), Ordering::Relaxed);
             cache.invalidate_all();
+            drop(inner);
             self.report_size();
         }
     }
 
+    /// Removes all entries not satisfying the specified predicate from this cache.
+    pub fn retain(&self, mut predicate: impl FnMut(&K, &V) -> bool)
+    where
+        K: Clone,
+    {
+        let inner = self.read_inner();
+        let Some(cache) = &inner.cache else {
+            return;
+        };
+        if let Some(prev_cache) = &inner.prev_cache {
+            let stale_keys: Vec<_> = prev_cache
+                .iter()
+                .filter(|entry| !predicate(entry.key(), entry.value()))
+                .map(|entry| entry.key().clone())
+                .collect();
+            for key in &stale_keys {
+                prev_cache.invalidate(key);
+            }
+        }
+        let stale_keys: Vec<_> = cache
+            .iter()
+            .filter(|entry| !predicate(entry.key(), entry.value()))
+            .map(|entry| entry.key().clone())
+            .collect();
+        for key in &stale_keys {
+            cache.invalidate(key);
+        }
+        drop(inner);
+        self.counters
+            .removals
+            .fetch_add(stale_keys.len() as u64, Ordering::Relaxed);
+        self.report_size();
+    }
+
+    /// Returns the current capacity of this cache in bytes. Zero capacity means that the cache is disabled.
+    #[must_use]
+    pub fn capacity(&self) -> u64 {
+        self.read_inner().capacity
+    }
+
+    /// Changes the capacity of this cache, returning the previous capacity. Setting the capacity to zero disables
+    /// the cache, and vice versa.
+    ///
+    /// The resized cache is swapped in immediately, and entries are migrated to it on a background thread;
+    /// until the migration is complete, lookups fall back to the replaced cache. If the capacity is decreased,
+    /// the cache retains migrated entries fitting into the new capacity according to its eviction policy.
+    /// If the cache is resized again before the migration is complete, the remaining entries are discarded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cache is poisoned.
+    pub fn resize(&self, new_capacity: u64) -> u64
+    where
+        K: Clone,
+    {
+        let mut inner = self.inner.write().expect("LRU cache is poisoned");
+        let prev_capacity = inner.capacity;
+        if prev_capacity == new_capacity {
+            return prev_capacity;
+        }
+
+        let new_cache = Self::build_base(new_capacity);
+        let prev_cache = inner.cache.take();
+        if let (Some(prev_cache), None) = (&prev_cache, &new_cache) {
+            self.counters
+                .removals
+                .fetch_add(prev_cache.entry_count(), Ordering::Relaxed);
+        }
+        inner.generation += 1;
+        let generation = inner.generation;
+        inner.capacity = new_capacity;
+        inner.cache = new_cache.clone();
+        inner.prev_cache = new_cache.as_ref().and(prev_cache.clone());
+        drop(inner);
+
+        tracing::info!(
+            "Resized cache `{}` from {prev_capacity}B to {new_capacity}B",
+            self.name
+        );
+        self.report_size();
+
+        if let (Some(prev_cache), Some(new_cache)) = (prev_cache, new_cache) {
+            let this = self.clone();
+            thread::Builder::new()
+                .name(format!("{}-migration", self.name))
+                .spawn(move || this.migrate_entries(&prev_cache, &new_cache, generation))
+                .expect("failed spawning cache migration thread");
+        }
+        prev_capacity
+    }
+
+    /// Moves entries from `prev_cache` to `new_cache` after a resize.
+    fn migrate_entries(
+        &self,
+        prev_cache: &MokaBase<K, V>,
+        new_cache: &MokaBase<K, V>,
+        generation: u64,
+    ) where
+        K: Clone,
+    {
+        const CHUNK_SIZE: usize = 1_000;
+
+        // Keys are collected beforehand so that no cache locks are held while entries are migrated.
+        let keys: Vec<_> = prev_cache.iter().map(|entry| entry.key().clone()).collect();
+        let mut migrated_count = 0;
+        for chunk in keys.chunks(CHUNK_SIZE) {
+            // Holding a read lock ensures that the cache isn't resized while the chunk is migrated.
+            let inner = self.read_inner();
+            if inner.generation != generation {
+                tracing::info!(
+                    "Cache `{}` was resized during migration; {} / {} entries were migrated",
+                    self.name,
+                    migrated_count,
+                    keys.len()
+                );
+                return;
+            }
+            for key in chunk {
+                if new_cache.contains_key(key) {
+                    continue; // the entry was inserted after the resize
+                }
+                let Some(value) = prev_cache.get(key) else {
+                    continue; // the entry was invalidated after the resize
+                };
+                new_cache.insert(key.clone(), value);
+                // Insertions and removals invalidate the entry in `prev_cache` before modifying `new_cache`,
+                // so if the entry is still present, the migrated value is up to date.
+                if prev_cache.contains_key(key) {
+                    migrated_count += 1;
+                } else {
+                    new_cache.invalidate(key);
+                }
+            }
+        }
+
+        let mut inner = self.inner.write().expect("LRU cache is poisoned");
+        if inner.generation == generation {
+            inner.prev_cache = None;
+        }
+        drop(inner);
+        tracing::info!(
+            "Migrated {migrated_count} / {} entries in cache `{}` after resizing",
+            keys.len(),
+            self.name
+        );
+        self.report_size();
+    }
+
+    /// Returns statistics for this cache, or `None` if the cache is disabled. The number of evictions
+    /// is estimated and may be slightly off since the cache processes some operations asynchronously.
+    #[must_use]
+    pub fn stats(&self) -> Option<CacheStats> {
+        let inner = self.read_inner();
+        let cache = inner.cache.as_ref()?;
+        let entries = cache.entry_count();
+        let mut stats = self
+            .counters
+            .base
+            .stats(self.name, entries, Some(cache.weighted_size()));
+        let insertions = self.counters.insertions.load(Ordering::Relaxed);
+        let removals = self.counters.removals.load(Ordering::Relaxed);
+        stats.evictions = insertions.saturating_sub(removals).saturating_sub(entries);
+        Some(stats)
+    }
+
+    #[cfg(test)]
+    fn wait_for_migration(&self) {
+        while self.read_inner().prev_cache.is_some() {
+            thread::sleep(std::time::Duration::from_millis(1));
+        }
+    }
+
     #[cfg(test)]
     pub(crate) fn estimated_len(&self) -> u64 {
-        self.cache.as_ref().map_or(0, MokaBase::entry_count)
+        self.read_inner()
+            .cache
+            .as_ref()
+            .map_or(0, MokaBase::entry_count)
     }
 }
 
@@ -122,4 +376,80 @@ mod tests {
         // The item is evicted after the first access.
         assert_eq!(not_quite_zero_cache.get(&H256::zero()), None);
     }
+
+    #[test]
+    fn cache_stats() {
+        let zero_cache = LruCache::<H256, Vec<u8>>::new("test", 0);
+        assert_eq!(zero_cache.stats(), None);
+
+        let cache = LruCache::<H256, Vec<u8>>::new("test", 1_024);
+        cache.insert(H256::zero(), vec![1, 2, 3]);
+        assert_eq!(cache.get(&H256::zero()), Some(vec![1, 2, 3]));
+        assert_eq!(cache.get(&H256::repeat_byte(1)), None);
+        cache.remove(&H256::zero());
+        assert_eq!(cache.get(&H256::zero()), None);
+
+        let stats = cache.stats().unwrap();
+        assert_eq!(stats.name, "test");
+        assert_eq!((stats.hits, stats.misses), (1, 2));
+        assert_eq!(stats.evictions, 0);
+    }
+
+    #[test]
+    fn resizing_cache() {
+        let cache = LruCache::<H256, Vec<u8>>::new("test", 1_024);
+        let cache_clone = cache.clone();
+        for i in 0_u8..10 {
+            cache.insert(H256::repeat_byte(i), vec![i; 32]);
+        }
+
+        assert_eq!(cache.resize(4_096), 1_024);
+        assert_eq!(cache_clone.capacity(), 4_096);
+        for i in 0_u8..10 {
+            let value = cache_clone.get(&H256::repeat_byte(i));
+            assert_eq!(value, Some(vec![i; 32]));
+        }
+        cache.wait_for_migration();
+        for i in 0_u8..10 {
+            let value = cache_clone.get(&H256::repeat_byte(i));
+            assert_eq!(value, Some(vec![i; 32]));
+        }
+
+        assert_eq!(cache.resize(0), 4_096);
+        assert_eq!(cache_clone.get(&H256::zero()), None);
+        assert_eq!(cache_clone.stats(), None);
+
+        assert_eq!(cache.resize(1_024), 0);
+        assert_eq!(cache_clone.get(&H256::zero()), None);
+        cache_clone.insert(H256::zero(), vec![1, 2, 3]);
+        assert_eq!(cache.get(&H256::zero()), Some(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn modifying_cache_during_migration() {
+        let cache = LruCache::<H256, Vec<u8>>::new("test", 1 << 20);
+        for i in 0_u8..=255 {
+            cache.insert(H256::repeat_byte(i), vec![i; 32]);
+        }
+
+        cache.resize(1 << 21);
+        cache.remove(&H256::repeat_byte(1));
+        cache.insert(H256::repeat_byte(2), vec![0; 16]);
+        cache.retain(|key, _| *key != H256::repeat_byte(3));
+        cache.wait_for_migration();
+
+        assert_eq!(cache.get(&H256::repeat_byte(1)), None);
+        assert_eq!(cache.get(&H256::repeat_byte(2)), Some(vec![0; 16]));
+        assert_eq!(cache.get(&H256::repeat_byte(3)), None);
+        for i in 4_u8..=255 {
+            assert_eq!(cache.get(&H256::repeat_byte(i)), Some(vec![i; 32]));
+        }
+
+        // Resizing the cache again during migration must not resurrect stale entries.
+        cache.resize(1 << 20);
+        cache.remove(&H256::repeat_byte(4));
+        cache.resize(1 << 22);
+        cache.wait_for_migration();
+        assert_eq!(cache.get(&H256::repeat_byte(4)), None);
+    }
 }
@@ -1,8 +1,11 @@
 //! Generic cache abstraction used by storage implementations.
 
+pub mod bytecode_cache;
+pub mod invalidation;
 pub mod lru_cache;
 mod metrics;
 pub mod sequential_cache;
+pub mod stats;
 
 type MokaBase<K, V> = mini_moka::sync::Cache<K, V>;
 
@@ -0,0 +1,54 @@
+//! Runtime statistics for caches.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Statistics for a single cache collected since the process start.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Cache name.
+    pub name: &'static str,
+    /// Number of entries in the cache.
+    pub entries: u64,
+    /// Memory used by the cache entries in bytes. `None` if the cache doesn't track memory usage.
+    pub used_memory: Option<u64>,
+    /// Number of lookups that returned an entry.
+    pub hits: u64,
+    /// Number of lookups that didn't return an entry.
+    pub misses: u64,
+    /// Number of entries removed by the cache eviction policy (i.e., not removed explicitly).
+    pub evictions: u64,
+}
+
+/// Counters for cache operations that can be converted to [`CacheStats`].
+#[derive(Debug, Default)]
+pub struct CacheCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl CacheCounters {
+    /// Records a cache lookup.
+    pub fn observe_lookup(&self, is_hit: bool) {
+        let counter = if is_hit { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records evictions of the specified number of entries.
+    pub fn observe_evictions(&self, count: u64) {
+        self.evictions.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Creates stats based on these counters and the current cache state.
+    #[must_use]
+    pub fn stats(&self, name: &'static str, entries: u64, used_memory: Option<u64>) -> CacheStats {
+        CacheStats {
+            name,
+            entries,
+            used_memory,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+        }
+    }
+}
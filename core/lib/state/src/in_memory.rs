@@ -1,4 +1,7 @@
-use std::collections::{hash_map::Entry, BTreeMap, HashMap};
+use std::{
+    collections::{hash_map::Entry, BTreeMap, HashMap},
+    sync::Arc,
+};
 
 use zksync_types::{
     block::DeployedContract, get_code_key, get_known_code_key, get_system_context_init_logs,
@@ -116,8 +119,10 @@ impl ReadStorage for &InMemoryStorage {
         !self.state.contains_key(&key.hashed_key())
     }
 
-    fn load_factory_dep(&mut self, hash: H256) -> Option<Vec<u8>> {
-        self.factory_deps.get(&hash).cloned()
+    fn load_factory_dep(&mut self, hash: H256) -> Option<Arc<[u8]>> {
+        self.factory_deps
+            .get(&hash)
+            .map(|bytecode| bytecode.as_slice().into())
     }
 
     fn get_enumeration_index(&mut self, key: &StorageKey) -> Option<u64> {
@@ -134,7 +139,7 @@ impl ReadStorage for InMemoryStorage {
         (&*self).is_write_initial(key)
     }
 
-    fn load_factory_dep(&mut self, hash: H256) -> Option<Vec<u8>> {
+    fn load_factory_dep(&mut self, hash: H256) -> Option<Arc<[u8]>> {
         (&*self).load_factory_dep(hash)
     }
 
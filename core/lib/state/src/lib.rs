@@ -9,7 +9,7 @@
     clippy::doc_markdown // frequent false positive: RocksDB
 )]
 
-use std::{cell::RefCell, collections::HashMap, fmt, rc::Rc};
+use std::{cell::RefCell, collections::HashMap, fmt, rc::Rc, sync::Arc};
 
 use zksync_types::{
     get_known_code_key,
@@ -28,12 +28,20 @@ mod test_utils;
 mod witness;
 
 pub use self::{
-    cache::sequential_cache::SequentialCache,
+    cache::{
+        bytecode_cache::BytecodeCache,
+        invalidation::{CacheInvalidator, InvalidateCache, RevertedBlocks},
+        sequential_cache::SequentialCache,
+        stats::{CacheCounters, CacheStats},
+    },
     in_memory::InMemoryStorage,
-    postgres::{PostgresStorage, PostgresStorageCaches, PostgresStorageCachesTask},
+    postgres::{
+        HistoricalState, HistoricalStateUpdater, PostgresStorage, PostgresStorageCaches,
+        PostgresStorageCachesTask,
+    },
     rocksdb::{RocksdbStorage, RocksdbStorageBuilder, StateKeeperColumnFamily},
     shadow_storage::ShadowStorage,
-    storage_view::{StorageView, StorageViewMetrics},
+    storage_view::{StorageView, StorageViewBuffers, StorageViewMetrics},
     witness::WitnessStorage,
 };
 
@@ -48,8 +56,9 @@ pub trait ReadStorage: fmt::Debug {
     /// in the storage but was not committed is still an initial write).
     fn is_write_initial(&mut self, key: &StorageKey) -> bool;
 
-    /// Load the factory dependency code by its hash.
-    fn load_factory_dep(&mut self, hash: H256) -> Option<Vec<u8>>;
+    /// Load the factory dependency code by its hash. The bytecode is reference-counted, so that it can be shared
+    /// with bytecode caches without copying.
+    fn load_factory_dep(&mut self, hash: H256) -> Option<Arc<[u8]>>;
 
     /// Returns whether a bytecode hash is "known" to the system.
     fn is_bytecode_known(&mut self, bytecode_hash: &H256) -> bool {
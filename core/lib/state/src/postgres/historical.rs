@@ -0,0 +1,924 @@
+//! Local multi-version VM state backed by RocksDB, which allows serving reads for past miniblocks
+//! without querying Postgres.
+//!
+//! ## Storage layout
+//!
+//! | Column      | Key                                                  | Value                                                     | Description                                     |
+//! | ----------- | ---------------------------------------------------- | --------------------------------------------------------- | ----------------------------------------------- |
+//! | Meta        | 'first_miniblock'                                    | miniblock number (u32, LE) ++ completeness flag (u8)      | First miniblock with recorded changes           |
+//! | Meta        | 'latest_miniblock'                                   | miniblock number (u32, LE) ++ miniblock hash              | Latest processed miniblock                      |
+//! | Values      | hashed `StorageKey` ++ inverted miniblock (u32, BE)  | 32 bytes value                                            | Value of the key after the miniblock            |
+//! | FactoryDeps | bytecode hash                                        | miniblock number (u32, LE) ++ bytecode                    | Bytecode and the miniblock it was inserted at   |
+//! | Changes     | miniblock number (u32, BE)                           | miniblock hash ++ keys count (u32, LE) ++ keys ++ hashes  | Keys and bytecodes changed in the miniblock     |
+//!
+//! Miniblock numbers in the values column are inverted (i.e., stored as `u32::MAX - number`), so that iterating
+//! from `hashed_key ++ inverted(number)` in the lexical order yields the latest value of the key as of `number`.
+
+use std::{
+    collections::BTreeMap,
+    ops,
+    path::Path,
+    sync::{Arc, Mutex, MutexGuard, RwLock},
+    time::Duration,
+};
+
+use anyhow::Context as _;
+use tokio::sync::watch;
+use zksync_dal::{partitions_dal::PartitionedTable, Connection, ConnectionPool, Core, CoreDal};
+use zksync_storage::{
+    db::{NamedColumnFamily, WriteBatch},
+    RocksDB,
+};
+use zksync_types::{MiniblockNumber, StorageValue, H256};
+
+use super::metrics::HISTORICAL_STATE_METRICS;
+use crate::cache::invalidation::{InvalidateCache, RevertedBlocks};
+
+const FIRST_MINIBLOCK_KEY: &[u8] = b"first_miniblock";
+const LATEST_MINIBLOCK_KEY: &[u8] = b"latest_miniblock";
+
+/// Bounds of the range covering all keys in the data column families (all keys are at most 36 bytes long).
+static MIN_KEY: [u8; 0] = [];
+static KEY_UPPER_BOUND: [u8; 37] = [0xff; 37];
+
+fn all_keys_range() -> ops::Range<&'static [u8]> {
+    &MIN_KEY[..]..&KEY_UPPER_BOUND[..]
+}
+
+/// RocksDB column families used by [`HistoricalState`].
+#[derive(Debug, Clone, Copy)]
+enum HistoricalStateColumnFamily {
+    /// Metadata (e.g., the range of processed miniblocks).
+    Meta,
+    /// Storage values for each miniblock they were modified in.
+    Values,
+    /// Factory deps together with the miniblocks they were inserted at.
+    FactoryDeps,
+    /// Changes in each processed miniblock. Used to roll back the state.
+    Changes,
+}
+
+impl NamedColumnFamily for HistoricalStateColumnFamily {
+    const DB_NAME: &'static str = "historical_state";
+    const ALL: &'static [Self] = &[Self::Meta, Self::Values, Self::FactoryDeps, Self::Changes];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Meta => "meta",
+            Self::Values => "values",
+            Self::FactoryDeps => "factory_deps",
+            Self::Changes => "changes",
+        }
+    }
+}
+
+fn values_key(hashed_key: &H256, miniblock: MiniblockNumber) -> [u8; 36] {
+    let mut buffer = [0_u8; 36];
+    buffer[..32].copy_from_slice(hashed_key.as_bytes());
+    buffer[32..].copy_from_slice(&(u32::MAX - miniblock.0).to_be_bytes());
+    buffer
+}
+
+/// Changes in a single miniblock.
+#[derive(Debug, Default)]
+struct MiniblockChanges {
+    hash: H256,
+    values: Vec<(H256, StorageValue)>,
+    factory_deps: Vec<(H256, Vec<u8>)>,
+}
+
+impl MiniblockChanges {
+    fn serialize_changelog(&self) -> Vec<u8> {
+        let keys_count = u32::try_from(self.values.len()).expect("too many keys in miniblock");
+        let hashes = self.values.iter().map(|(key, _)| key);
+        let hashes = hashes.chain(self.factory_deps.iter().map(|(hash, _)| hash));
+
+        let mut buffer =
+            Vec::with_capacity(36 + 32 * (self.values.len() + self.factory_deps.len()));
+        buffer.extend_from_slice(self.hash.as_bytes());
+        buffer.extend_from_slice(&keys_count.to_le_bytes());
+        for hash in hashes {
+            buffer.extend_from_slice(hash.as_bytes());
+        }
+        buffer
+    }
+}
+
+/// Parsed entry from the changes column family.
+#[derive(Debug)]
+struct Changelog {
+    hash: H256,
+    hashed_keys: Vec<H256>,
+    bytecode_hashes: Vec<H256>,
+}
+
+impl Changelog {
+    fn deserialize(bytes: &[u8]) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            bytes.len() >= 36 && (bytes.len() - 36) % 32 == 0,
+            "unexpected changelog length: {}",
+            bytes.len()
+        );
+        let hash = H256::from_slice(&bytes[..32]);
+        let keys_count = u32::from_le_bytes(bytes[32..36].try_into().unwrap()) as usize;
+        let mut hashes: Vec<_> = bytes[36..].chunks(32).map(H256::from_slice).collect();
+        anyhow::ensure!(
+            keys_count <= hashes.len(),
+            "invalid keys count in changelog: {keys_count}, while it has {} hashes",
+            hashes.len()
+        );
+        let bytecode_hashes = hashes.split_off(keys_count);
+        Ok(Self {
+            hash,
+            hashed_keys: hashes,
+            bytecode_hashes,
+        })
+    }
+}
+
+/// Range of miniblocks covered by [`HistoricalState`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Coverage {
+    first_miniblock: MiniblockNumber,
+    /// Whether the state contains all storage values as of `first_miniblock`. This is the case if the state
+    /// was built from the genesis, but not if it was built on top of a snapshot recovery.
+    is_complete: bool,
+    latest_miniblock: MiniblockNumber,
+    latest_miniblock_hash: H256,
+}
+
+impl Coverage {
+    fn contains(&self, miniblock: MiniblockNumber) -> bool {
+        (self.first_miniblock..=self.latest_miniblock).contains(&miniblock)
+    }
+}
+
+/// Local multi-version VM state backed by RocksDB. Unlike the state keeper cache (`RocksdbStorage`), the state
+/// retains all past versions of storage values, so it can serve reads for any miniblock starting from the one
+/// it was initialized at.
+///
+/// The state is updated from Postgres by [`HistoricalStateUpdater`]; it lags behind Postgres by up to the updater
+/// poll interval. Hence, [`PostgresStorage`](crate::PostgresStorage) falls back to Postgres for miniblocks
+/// not covered by the state (i.e., very old and very new ones).
+///
+/// If the node was recovered from a snapshot, the state is built starting from the miniblock after the snapshot;
+/// reads of storage keys not modified since the snapshot fall back to Postgres as well.
+///
+/// Old miniblocks are pruned from the state by the updater, so that the state doesn't outlive storage logs
+/// pruned from Postgres and (optionally) retains only a configured number of latest miniblocks.
+#[derive(Debug, Clone)]
+pub struct HistoricalState {
+    db: RocksDB<HistoricalStateColumnFamily>,
+    coverage: Arc<RwLock<Option<Coverage>>>,
+    /// Serializes all writes to the state. Writes happen both from the updater and from block revert hooks,
+    /// so without the lock, the updater could apply changes loaded before a revert on top of the rolled back state.
+    write_lock: Arc<Mutex<()>>,
+}
+
+impl HistoricalState {
+    /// Opens the historical state at the specified path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if RocksDB cannot be opened, or the stored metadata is corrupted.
+    pub fn new(db_path: &Path) -> anyhow::Result<Self> {
+        let db = RocksDB::new(db_path).with_context(|| {
+            format!(
+                "failed initializing RocksDB for historical state at `{}`",
+                db_path.display()
+            )
+        })?;
+        let mut this = Self {
+            db,
+            coverage: Arc::default(),
+            write_lock: Arc::default(),
+        };
+        let coverage = this.load_coverage()?;
+        if let Some(coverage) = &coverage {
+            HISTORICAL_STATE_METRICS
+                .first_miniblock
+                .set(coverage.first_miniblock.0.into());
+            HISTORICAL_STATE_METRICS
+                .latest_miniblock
+                .set(coverage.latest_miniblock.0.into());
+        }
+        this.coverage = Arc::new(RwLock::new(coverage));
+        Ok(this)
+    }
+
+    fn load_coverage(&self) -> anyhow::Result<Option<Coverage>> {
+        let first_miniblock = self
+            .db
+            .get_cf(HistoricalStateColumnFamily::Meta, FIRST_MINIBLOCK_KEY)
+            .context("failed reading first miniblock from RocksDB")?;
+        let latest_miniblock = self
+            .db
+            .get_cf(HistoricalStateColumnFamily::Meta, LATEST_MINIBLOCK_KEY)
+            .context("failed reading latest miniblock from RocksDB")?;
+        let (Some(first_miniblock), Some(latest_miniblock)) = (first_miniblock, latest_miniblock)
+        else {
+            return Ok(None);
+        };
+
+        anyhow::ensure!(
+            first_miniblock.len() == 5,
+            "unexpected first miniblock length: {}",
+            first_miniblock.len()
+        );
+        anyhow::ensure!(
+            latest_miniblock.len() == 36,
+            "unexpected latest miniblock length: {}",
+            latest_miniblock.len()
+        );
+        Ok(Some(Coverage {
+            first_miniblock: MiniblockNumber(u32::from_le_bytes(
+                first_miniblock[..4].try_into().unwrap(),
+            )),
+            is_complete: first_miniblock[4] != 0,
+            latest_miniblock: MiniblockNumber(u32::from_le_bytes(
+                latest_miniblock[..4].try_into().unwrap(),
+            )),
+            latest_miniblock_hash: H256::from_slice(&latest_miniblock[4..]),
+        }))
+    }
+
+    fn lock_writes(&self) -> MutexGuard<'_, ()> {
+        self.write_lock
+            .lock()
+            .expect("historical state write lock is poisoned")
+    }
+
+    fn coverage(&self) -> Option<Coverage> {
+        *self
+            .coverage
+            .read()
+            .expect("historical state coverage is poisoned")
+    }
+
+    /// Returns the latest miniblock processed by the state, or `None` if the state is empty.
+    pub fn latest_miniblock(&self) -> Option<MiniblockNumber> {
+        Some(self.coverage()?.latest_miniblock)
+    }
+
+    /// Reads the value of a storage key as of the specified miniblock. Returns `None` if the value cannot be determined
+    /// using the state alone (the miniblock isn't covered by the state, or the state is incomplete); in this case,
+    /// the value should be loaded from Postgres.
+    ///
+    /// # Panics
+    ///
+    /// Panics on RocksDB errors.
+    pub(super) fn read_value(
+        &self,
+        hashed_key: &H256,
+        miniblock: MiniblockNumber,
+    ) -> Option<StorageValue> {
+        let coverage = self
+            .coverage()
+            .filter(|coverage| coverage.contains(miniblock))?;
+        let seek_key = values_key(hashed_key, miniblock);
+        let mut iter = self
+            .db
+            .from_iterator_cf(HistoricalStateColumnFamily::Values, &seek_key);
+        let value = match iter.next() {
+            Some((key, value)) if key[..32] == *hashed_key.as_bytes() => {
+                assert_eq!(value.len(), 32, "unexpected value length: {}", value.len());
+                Some(H256::from_slice(&value))
+            }
+            // The key wasn't modified since the first miniblock.
+            _ => coverage.is_complete.then_some(StorageValue::zero()),
+        };
+
+        if value.is_some() {
+            HISTORICAL_STATE_METRICS.values_hits.inc();
+        }
+        value
+    }
+
+    /// Loads a factory dep as of the specified miniblock. Returns `None` if the factory dep cannot be determined
+    /// using the state alone; `Some(None)` if the factory dep is known to be missing.
+    ///
+    /// # Panics
+    ///
+    /// Panics on RocksDB errors.
+    pub(super) fn load_factory_dep(
+        &self,
+        hash: H256,
+        miniblock: MiniblockNumber,
+    ) -> Option<Option<Arc<[u8]>>> {
+        let coverage = self
+            .coverage()
+            .filter(|coverage| coverage.contains(miniblock))?;
+        let raw_value = self
+            .db
+            .get_cf(HistoricalStateColumnFamily::FactoryDeps, hash.as_bytes())
+            .expect("failed reading factory dep from RocksDB");
+        let Some(raw_value) = raw_value else {
+            // The factory dep wasn't inserted since the first miniblock.
+            return coverage.is_complete.then_some(None);
+        };
+
+        assert!(
+            raw_value.len() >= 4,
+            "unexpected factory dep length: {}",
+            raw_value.len()
+        );
+        let inserted_at = u32::from_le_bytes(raw_value[..4].try_into().unwrap());
+        Some((inserted_at <= miniblock.0).then(|| raw_value[4..].into()))
+    }
+
+    /// Reads the changelog for the specified miniblock.
+    fn changelog(&self, miniblock: MiniblockNumber) -> anyhow::Result<Option<Changelog>> {
+        let raw_value = self
+            .db
+            .get_cf(
+                HistoricalStateColumnFamily::Changes,
+                &miniblock.0.to_be_bytes(),
+            )
+            .context("failed reading changelog from RocksDB")?;
+        raw_value.as_deref().map(Changelog::deserialize).transpose()
+    }
+
+    /// Atomically writes changes in the specified miniblocks to the state. `first_miniblock` must be specified
+    /// iff the state is empty.
+    ///
+    /// Returns `false` and doesn't write anything if the changes don't directly follow the state; this happens
+    /// if the state was rolled back or cleared after the changes were loaded.
+    fn apply_changes(
+        &self,
+        first_miniblock: Option<(MiniblockNumber, bool)>,
+        changes: &BTreeMap<MiniblockNumber, MiniblockChanges>,
+    ) -> anyhow::Result<bool> {
+        let (&earliest_miniblock, _) = changes.first_key_value().context("no changes to apply")?;
+        let (&latest_miniblock, latest_changes) =
+            changes.last_key_value().context("no changes to apply")?;
+
+        let _guard = self.lock_writes();
+        let expected_miniblock = match (self.coverage(), first_miniblock) {
+            (Some(coverage), None) => coverage.latest_miniblock + 1,
+            (None, Some((first_miniblock, _))) => first_miniblock,
+            _ => return Ok(false),
+        };
+        if earliest_miniblock != expected_miniblock {
+            return Ok(false);
+        }
+
+        let mut batch = self.db.new_write_batch();
+        for (&miniblock, miniblock_changes) in changes {
+            for (hashed_key, value) in &miniblock_changes.values {
+                batch.put_cf(
+                    HistoricalStateColumnFamily::Values,
+                    &values_key(hashed_key, miniblock),
+                    value.as_bytes(),
+                );
+            }
+            for (hash, bytecode) in &miniblock_changes.factory_deps {
+                let mut raw_value = Vec::with_capacity(4 + bytecode.len());
+                raw_value.extend_from_slice(&miniblock.0.to_le_bytes());
+                raw_value.extend_from_slice(bytecode);
+                batch.put_cf(
+                    HistoricalStateColumnFamily::FactoryDeps,
+                    hash.as_bytes(),
+                    &raw_value,
+                );
+            }
+            batch.put_cf(
+                HistoricalStateColumnFamily::Changes,
+                &miniblock.0.to_be_bytes(),
+                &miniblock_changes.serialize_changelog(),
+            );
+        }
+        if let Some((first_miniblock, is_complete)) = first_miniblock {
+            Self::put_first_miniblock(&mut batch, first_miniblock, is_complete);
+        }
+        Self::put_latest_miniblock(&mut batch, latest_miniblock, latest_changes.hash);
+        self.db
+            .write(batch)
+            .context("failed writing historical state changes to RocksDB")?;
+
+        let mut coverage = self
+            .coverage
+            .write()
+            .expect("historical state coverage is poisoned");
+        let (first_miniblock, is_complete) = first_miniblock
+            .or_else(|| {
+                let coverage = coverage.as_ref()?;
+                Some((coverage.first_miniblock, coverage.is_complete))
+            })
+            .context("first miniblock is not specified for an empty historical state")?;
+        *coverage = Some(Coverage {
+            first_miniblock,
+            is_complete,
+            latest_miniblock,
+            latest_miniblock_hash: latest_changes.hash,
+        });
+        HISTORICAL_STATE_METRICS
+            .first_miniblock
+            .set(first_miniblock.0.into());
+        HISTORICAL_STATE_METRICS
+            .latest_miniblock
+            .set(latest_miniblock.0.into());
+        Ok(true)
+    }
+
+    /// Rolls back the state so that it doesn't contain miniblocks after `last_miniblock_to_keep`. If the state
+    /// doesn't cover `last_miniblock_to_keep`, it is cleared.
+    fn revert(&self, last_miniblock_to_keep: MiniblockNumber) -> anyhow::Result<()> {
+        let _guard = self.lock_writes();
+        let Some(coverage) = self.coverage() else {
+            return Ok(());
+        };
+        if coverage.latest_miniblock <= last_miniblock_to_keep {
+            return Ok(());
+        }
+        tracing::info!(
+            "Rolling back historical state covering miniblocks {}..={} to miniblock #{last_miniblock_to_keep}",
+            coverage.first_miniblock,
+            coverage.latest_miniblock
+        );
+        if coverage.first_miniblock <= last_miniblock_to_keep {
+            self.roll_back(coverage, last_miniblock_to_keep)
+        } else {
+            self.clear_unlocked()
+        }
+    }
+
+    /// Rolls back the state to the specified miniblock. The miniblock must be covered by the state.
+    /// Must be called with the write lock held.
+    fn roll_back(
+        &self,
+        coverage: Coverage,
+        last_miniblock_to_keep: MiniblockNumber,
+    ) -> anyhow::Result<()> {
+        let last_changelog = self
+            .changelog(last_miniblock_to_keep)?
+            .with_context(|| format!("no changelog for miniblock #{last_miniblock_to_keep}"))?;
+
+        // Shrink the coverage first, so that the rolled back data is not accessed.
+        *self
+            .coverage
+            .write()
+            .expect("historical state coverage is poisoned") = Some(Coverage {
+            latest_miniblock: last_miniblock_to_keep,
+            latest_miniblock_hash: last_changelog.hash,
+            ..coverage
+        });
+
+        let mut batch = self.db.new_write_batch();
+        let start_key = (last_miniblock_to_keep.0 + 1).to_be_bytes();
+        let changelogs = self
+            .db
+            .from_iterator_cf(HistoricalStateColumnFamily::Changes, &start_key);
+        for (raw_key, raw_value) in changelogs {
+            let miniblock = MiniblockNumber(u32::from_be_bytes(
+                raw_key[..].try_into().context("invalid changelog key")?,
+            ));
+            let changelog = Changelog::deserialize(&raw_value)
+                .with_context(|| format!("invalid changelog for miniblock #{miniblock}"))?;
+            for hashed_key in &changelog.hashed_keys {
+                batch.delete_cf(
+                    HistoricalStateColumnFamily::Values,
+                    &values_key(hashed_key, miniblock),
+                );
+            }
+            for hash in &changelog.bytecode_hashes {
+                batch.delete_cf(HistoricalStateColumnFamily::FactoryDeps, hash.as_bytes());
+            }
+            batch.delete_cf(HistoricalStateColumnFamily::Changes, &raw_key);
+        }
+        Self::put_latest_miniblock(&mut batch, last_miniblock_to_keep, last_changelog.hash);
+        self.db
+            .write(batch)
+            .context("failed rolling back historical state in RocksDB")?;
+
+        HISTORICAL_STATE_METRICS
+            .latest_miniblock
+            .set(last_miniblock_to_keep.0.into());
+        Ok(())
+    }
+
+    /// Removes all data from the state.
+    fn clear(&self) -> anyhow::Result<()> {
+        let _guard = self.lock_writes();
+        self.clear_unlocked()
+    }
+
+    fn clear_unlocked(&self) -> anyhow::Result<()> {
+        *self
+            .coverage
+            .write()
+            .expect("historical state coverage is poisoned") = None;
+
+        let mut batch = self.db.new_write_batch();
+        for &cf in HistoricalStateColumnFamily::ALL {
+            batch.delete_range_cf(cf, all_keys_range());
+        }
+        self.db
+            .write(batch)
+            .context("failed clearing historical state in RocksDB")
+    }
+
+    /// Prunes miniblocks before `first_miniblock_to_keep` from the state, processing at most `max_miniblocks`
+    /// miniblocks at a time. The latest miniblock is never pruned. For each key, the latest version as of the new
+    /// first miniblock is retained, so reads for the remaining miniblocks are unaffected.
+    fn prune(
+        &self,
+        first_miniblock_to_keep: MiniblockNumber,
+        max_miniblocks: u32,
+    ) -> anyhow::Result<()> {
+        let _guard = self.lock_writes();
+        let Some(coverage) = self.coverage() else {
+            return Ok(());
+        };
+        let new_first_miniblock =
+            first_miniblock_to_keep
+                .min(coverage.latest_miniblock)
+                .min(MiniblockNumber(
+                    coverage.first_miniblock.0.saturating_add(max_miniblocks),
+                ));
+        if new_first_miniblock <= coverage.first_miniblock {
+            return Ok(());
+        }
+
+        // Shrink the coverage first, so that the pruned data is not accessed.
+        *self
+            .coverage
+            .write()
+            .expect("historical state coverage is poisoned") = Some(Coverage {
+            first_miniblock: new_first_miniblock,
+            ..coverage
+        });
+
+        let mut batch = self.db.new_write_batch();
+        let start_key = coverage.first_miniblock.0.to_be_bytes();
+        let changelogs = self
+            .db
+            .from_iterator_cf(HistoricalStateColumnFamily::Changes, &start_key);
+        for (raw_key, raw_value) in changelogs {
+            let miniblock = MiniblockNumber(u32::from_be_bytes(
+                raw_key[..].try_into().context("invalid changelog key")?,
+            ));
+            if miniblock >= new_first_miniblock {
+                break;
+            }
+            let changelog = Changelog::deserialize(&raw_value)
+                .with_context(|| format!("invalid changelog for miniblock #{miniblock}"))?;
+            for hashed_key in &changelog.hashed_keys {
+                let key = values_key(hashed_key, miniblock);
+                let mut versions = self.db.from_iterator_cf(
+                    HistoricalStateColumnFamily::Values,
+                    &values_key(hashed_key, new_first_miniblock),
+                );
+                let is_latest_version = versions
+                    .next()
+                    .map_or(false, |(latest_key, _)| latest_key[..] == key[..]);
+                if !is_latest_version {
+                    batch.delete_cf(HistoricalStateColumnFamily::Values, &key);
+                }
+            }
+            batch.delete_cf(HistoricalStateColumnFamily::Changes, &raw_key);
+        }
+        Self::put_first_miniblock(&mut batch, new_first_miniblock, coverage.is_complete);
+        self.db
+            .write(batch)
+            .context("failed pruning historical state in RocksDB")?;
+
+        HISTORICAL_STATE_METRICS
+            .first_miniblock
+            .set(new_first_miniblock.0.into());
+        Ok(())
+    }
+
+    fn put_first_miniblock(
+        batch: &mut WriteBatch<'_, HistoricalStateColumnFamily>,
+        miniblock: MiniblockNumber,
+        is_complete: bool,
+    ) {
+        let mut raw_value = [0_u8; 5];
+        raw_value[..4].copy_from_slice(&miniblock.0.to_le_bytes());
+        raw_value[4] = u8::from(is_complete);
+        batch.put_cf(
+            HistoricalStateColumnFamily::Meta,
+            FIRST_MINIBLOCK_KEY,
+            &raw_value,
+        );
+    }
+
+    fn put_latest_miniblock(
+        batch: &mut WriteBatch<'_, HistoricalStateColumnFamily>,
+        miniblock: MiniblockNumber,
+        miniblock_hash: H256,
+    ) {
+        let mut raw_value = [0_u8; 36];
+        raw_value[..4].copy_from_slice(&miniblock.0.to_le_bytes());
+        raw_value[4..].copy_from_slice(miniblock_hash.as_bytes());
+        batch.put_cf(
+            HistoricalStateColumnFamily::Meta,
+            LATEST_MINIBLOCK_KEY,
+            &raw_value,
+        );
+    }
+}
+
+impl InvalidateCache for HistoricalState {
+    /// Rolls back the state after a block revert so that data from reverted miniblocks is not served. Errors are logged;
+    /// the state will be brought in sync with Postgres by [`HistoricalStateUpdater`] in any case.
+    fn invalidate(&self, reverted: RevertedBlocks) {
+        if let Err(err) = self.revert(reverted.last_miniblock_to_keep) {
+            tracing::warn!("Failed rolling back historical state: {err:#}");
+        }
+    }
+}
+
+/// An asynchronous task that updates [`HistoricalState`] from Postgres. Besides following new miniblocks,
+/// the task detects miniblock reverts and rolls back the state accordingly, and prunes miniblocks
+/// that are no longer retained.
+#[derive(Debug)]
+pub struct HistoricalStateUpdater {
+    state: HistoricalState,
+    connection_pool: ConnectionPool<Core>,
+    poll_interval: Duration,
+    miniblocks_chunk_size: u32,
+    retained_miniblocks: Option<u32>,
+}
+
+impl HistoricalStateUpdater {
+    const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+    /// Default maximum number of miniblocks processed in a single step.
+    const DEFAULT_MINIBLOCKS_CHUNK_SIZE: u32 = 100;
+
+    /// Creates an updater for the specified state.
+    pub fn new(state: HistoricalState, connection_pool: ConnectionPool<Core>) -> Self {
+        Self {
+            state,
+            connection_pool,
+            poll_interval: Self::DEFAULT_POLL_INTERVAL,
+            miniblocks_chunk_size: Self::DEFAULT_MINIBLOCKS_CHUNK_SIZE,
+            retained_miniblocks: None,
+        }
+    }
+
+    /// Sets the number of latest miniblocks retained by the state. If not set, the state retains all miniblocks
+    /// for which storage logs are present in Postgres.
+    #[must_use]
+    pub fn with_retained_miniblocks(mut self, retained_miniblocks: Option<u32>) -> Self {
+        self.retained_miniblocks = retained_miniblocks;
+        self
+    }
+
+    #[cfg(test)]
+    #[must_use]
+    pub(super) fn with_miniblocks_chunk_size(mut self, chunk_size: u32) -> Self {
+        self.miniblocks_chunk_size = chunk_size;
+        self
+    }
+
+    /// Runs the updater until a stop signal is received.
+    ///
+    /// # Errors
+    ///
+    /// Propagates Postgres and RocksDB errors.
+    pub async fn run(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        while !*stop_receiver.borrow_and_update() {
+            let mut connection = self
+                .connection_pool
+                .connection_tagged("historical_state_updater")
+                .await?;
+            let has_more_miniblocks = self.step(&mut connection).await?;
+            drop(connection);
+
+            if !has_more_miniblocks
+                && tokio::time::timeout(self.poll_interval, stop_receiver.changed())
+                    .await
+                    .is_ok()
+            {
+                break;
+            }
+        }
+        tracing::info!("Stop signal received, historical state updater is shutting down");
+        Ok(())
+    }
+
+    /// Performs a single update step. Returns `true` if there are more miniblocks to process or prune.
+    pub(super) async fn step(&self, connection: &mut Connection<'_, Core>) -> anyhow::Result<bool> {
+        let has_more_miniblocks = self.update(connection).await?;
+        let has_more_pruning = self.prune(connection).await?;
+        Ok(has_more_miniblocks || has_more_pruning)
+    }
+
+    /// Applies the next chunk of miniblocks to the state. Returns `true` if there are more miniblocks to process.
+    async fn update(&self, connection: &mut Connection<'_, Core>) -> anyhow::Result<bool> {
+        let Some(sealed_miniblock) = connection
+            .blocks_dal()
+            .get_sealed_miniblock_number()
+            .await?
+        else {
+            return Ok(false);
+        };
+
+        if let Some(coverage) = self.state.coverage() {
+            self.ensure_no_reverts(connection, coverage).await?;
+        }
+        let (next_miniblock, first_miniblock) = match self.state.coverage() {
+            Some(coverage) => (coverage.latest_miniblock + 1, None),
+            None => Self::initial_miniblock(connection).await?,
+        };
+        if next_miniblock > sealed_miniblock {
+            return Ok(false);
+        }
+
+        let last_miniblock =
+            (next_miniblock + (self.miniblocks_chunk_size - 1)).min(sealed_miniblock);
+        let changes = Self::load_changes(connection, next_miniblock..=last_miniblock).await?;
+        let state = self.state.clone();
+        let applied =
+            tokio::task::spawn_blocking(move || state.apply_changes(first_miniblock, &changes))
+                .await
+                .context("panicked applying historical state changes")??;
+        if !applied {
+            tracing::debug!(
+                "Historical state was modified concurrently (e.g., rolled back after a block revert); \
+                 discarding changes for miniblocks #{next_miniblock}..=#{last_miniblock}"
+            );
+            return Ok(true);
+        }
+        if let Some((first_miniblock, is_complete)) = first_miniblock {
+            tracing::info!(
+                "Initialized historical state starting from miniblock #{first_miniblock} (complete: {is_complete})"
+            );
+        }
+        tracing::debug!(
+            "Updated historical state with miniblocks #{next_miniblock}..=#{last_miniblock}"
+        );
+        Ok(last_miniblock < sealed_miniblock)
+    }
+
+    /// Prunes miniblocks which storage logs were pruned from Postgres, or which exceed the configured retention.
+    /// Returns `true` if there are more miniblocks to prune.
+    async fn prune(&self, connection: &mut Connection<'_, Core>) -> anyhow::Result<bool> {
+        let Some(coverage) = self.state.coverage() else {
+            return Ok(false);
+        };
+        let partitions = connection
+            .partitions_dal()
+            .get_partitions(PartitionedTable::StorageLogs)
+            .await?;
+        let first_postgres_miniblock = partitions
+            .first()
+            .map_or(MiniblockNumber(0), |partition| partition.miniblocks.start);
+        let first_retained_miniblock =
+            self.retained_miniblocks
+                .map_or(MiniblockNumber(0), |count| {
+                    MiniblockNumber(
+                        coverage
+                            .latest_miniblock
+                            .0
+                            .saturating_sub(count.saturating_sub(1)),
+                    )
+                });
+        let first_miniblock_to_keep = first_postgres_miniblock.max(first_retained_miniblock);
+        if first_miniblock_to_keep <= coverage.first_miniblock {
+            return Ok(false);
+        }
+
+        let state = self.state.clone();
+        let chunk_size = self.miniblocks_chunk_size;
+        tokio::task::spawn_blocking(move || state.prune(first_miniblock_to_keep, chunk_size))
+            .await
+            .context("panicked pruning historical state")??;
+        let Some(new_coverage) = self.state.coverage() else {
+            return Ok(false);
+        };
+        tracing::debug!(
+            "Pruned historical state to start from miniblock #{}",
+            new_coverage.first_miniblock
+        );
+        let max_first_miniblock = first_miniblock_to_keep.min(new_coverage.latest_miniblock);
+        Ok(new_coverage.first_miniblock < max_first_miniblock)
+    }
+
+    /// Returns the first miniblock to be processed by an empty state and the corresponding metadata.
+    async fn initial_miniblock(
+        connection: &mut Connection<'_, Core>,
+    ) -> anyhow::Result<(MiniblockNumber, Option<(MiniblockNumber, bool)>)> {
+        let snapshot_recovery = connection
+            .snapshot_recovery_dal()
+            .get_applied_snapshot_status()
+            .await?;
+        let (first_miniblock, is_complete) = match snapshot_recovery {
+            Some(recovery) => (recovery.miniblock_number + 1, false),
+            None => (MiniblockNumber(0), true),
+        };
+        Ok((first_miniblock, Some((first_miniblock, is_complete))))
+    }
+
+    /// Checks whether the latest processed miniblock was reverted, and rolls back the state if it was.
+    async fn ensure_no_reverts(
+        &self,
+        connection: &mut Connection<'_, Core>,
+        coverage: Coverage,
+    ) -> anyhow::Result<()> {
+        let postgres_hash = connection
+            .blocks_web3_dal()
+            .get_miniblock_hash(coverage.latest_miniblock)
+            .await?;
+        if postgres_hash == Some(coverage.latest_miniblock_hash) {
+            return Ok(());
+        }
+        tracing::info!(
+            "Latest miniblock #{} in historical state has diverged from Postgres; looking for the last common miniblock",
+            coverage.latest_miniblock
+        );
+
+        // Binary search for the first diverged miniblock. Miniblock hashes are compared using changelogs,
+        // since the state doesn't store hashes of all miniblocks in an easily accessible form.
+        let (mut left, mut right) = (coverage.first_miniblock, coverage.latest_miniblock);
+        while left < right {
+            let middle = left + (right.0 - left.0) / 2;
+            let changelog = self
+                .state
+                .changelog(middle)?
+                .with_context(|| format!("no changelog for miniblock #{middle}"))?;
+            let postgres_hash = connection
+                .blocks_web3_dal()
+                .get_miniblock_hash(middle)
+                .await?;
+            if postgres_hash == Some(changelog.hash) {
+                left = middle + 1;
+            } else {
+                right = middle;
+            }
+        }
+
+        let state = self.state.clone();
+        if left > coverage.first_miniblock {
+            let last_common_miniblock = left - 1;
+            tracing::info!("Rolling back historical state to miniblock #{last_common_miniblock}");
+            tokio::task::spawn_blocking(move || state.revert(last_common_miniblock))
+                .await
+                .context("panicked rolling back historical state")??;
+        } else {
+            tracing::warn!(
+                "Historical state has no miniblocks in common with Postgres; clearing it"
+            );
+            tokio::task::spawn_blocking(move || state.clear())
+                .await
+                .context("panicked clearing historical state")??;
+        }
+        Ok(())
+    }
+
+    async fn load_changes(
+        connection: &mut Connection<'_, Core>,
+        miniblocks: ops::RangeInclusive<MiniblockNumber>,
+    ) -> anyhow::Result<BTreeMap<MiniblockNumber, MiniblockChanges>> {
+        let latency = HISTORICAL_STATE_METRICS.load_changes_latency.start();
+        let miniblocks_count = (miniblocks.end().0 - miniblocks.start().0 + 1) as usize;
+        let (hashes, _) = connection
+            .blocks_web3_dal()
+            .get_block_hashes_since(*miniblocks.start(), miniblocks_count)
+            .await?;
+        anyhow::ensure!(
+            hashes.len() == miniblocks_count,
+            "unexpected number of miniblock hashes for miniblocks {miniblocks:?}: {}",
+            hashes.len()
+        );
+
+        let mut changes: BTreeMap<_, _> = (miniblocks.start().0..=miniblocks.end().0)
+            .zip(hashes)
+            .map(|(number, hash)| {
+                let miniblock_changes = MiniblockChanges {
+                    hash,
+                    ..MiniblockChanges::default()
+                };
+                (MiniblockNumber(number), miniblock_changes)
+            })
+            .collect();
+
+        let values = connection
+            .storage_logs_dal()
+            .modified_values_in_miniblocks(miniblocks.clone())
+            .await?;
+        let values_count = values.len();
+        for (miniblock, hashed_key, value) in values {
+            let miniblock_changes = changes
+                .get_mut(&miniblock)
+                .with_context(|| format!("unexpected miniblock #{miniblock} in storage logs"))?;
+            miniblock_changes.values.push((hashed_key, value));
+        }
+
+        let factory_deps = connection
+            .storage_web3_dal()
+            .get_factory_deps_in_miniblocks(miniblocks.clone())
+            .await?;
+        for (miniblock, hash, bytecode) in factory_deps {
+            let miniblock_changes = changes
+                .get_mut(&miniblock)
+                .with_context(|| format!("unexpected miniblock #{miniblock} in factory deps"))?;
+            miniblock_changes.factory_deps.push((hash, bytecode));
+        }
+
+        HISTORICAL_STATE_METRICS.loaded_values.observe(values_count);
+        latency.observe();
+        Ok(changes)
+    }
+}
@@ -8,6 +8,7 @@ use vise::{Buckets, Counter, EncodeLabelSet, EncodeLabelValue, Family, Gauge, Hi
 #[metrics(label = "stage", rename_all = "snake_case")]
 pub(super) enum ValuesUpdateStage {
     LoadKeys,
+    UpdatePersistentTier,
     RemoveStaleKeys,
 }
 
@@ -24,6 +25,15 @@ pub(super) struct ValuesCacheMetrics {
     /// Number of keys modified during a specific values cache update.
     #[metrics(buckets = &[10.0, 20.0, 50.0, 100.0, 200.0, 500.0, 1_000.0])]
     pub values_update_modified_keys: Histogram<usize>,
+    /// Number of values loaded from the persistent tier of the values cache on misses of the in-memory tier.
+    pub persistent_values_hits: Counter,
+    /// Number of times the persistent tier of the values cache was emptied because it exceeded its maximum size.
+    pub persistent_values_emptied: Counter,
+    /// Number of values not written to the persistent tier of the values cache because too many writes were pending.
+    pub dropped_persistent_writes: Counter,
+    /// Latency of warming up VM caches on startup.
+    #[metrics(buckets = Buckets::LATENCIES)]
+    pub warm_up_latency: Histogram<Duration>,
     /// Current miniblock for the values cache.
     pub values_valid_for_miniblock: Gauge<u64>,
     /// Number of times the negative initial writes cache was successfully used. This is distinct
@@ -40,6 +50,7 @@ pub(super) enum Method {
     ReadValue,
     IsWriteInitial,
     LoadFactoryDep,
+    PrefetchValues,
 }
 
 #[derive(Debug, Metrics)]
@@ -48,7 +59,31 @@ pub(super) struct PostgresStorageMetrics {
     /// Latency of storage reading methods for Postgres-backed storage.
     #[metrics(buckets = Buckets::LATENCIES)]
     pub storage: Family<Method, Histogram<Duration>>,
+    /// Number of storage values loaded from Postgres per a prefetch query.
+    #[metrics(buckets = Buckets::exponential(1.0..=4_096.0, 4.0))]
+    pub prefetched_values: Histogram<usize>,
 }
 
 #[vise::register]
 pub(super) static STORAGE_METRICS: vise::Global<PostgresStorageMetrics> = vise::Global::new();
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "server_state_historical")]
+pub(super) struct HistoricalStateMetrics {
+    /// First miniblock retained by the historical state.
+    pub first_miniblock: Gauge<u64>,
+    /// Latest miniblock processed by the historical state.
+    pub latest_miniblock: Gauge<u64>,
+    /// Number of storage values read from the historical state (i.e., without querying Postgres).
+    pub values_hits: Counter,
+    /// Latency of loading changes for a chunk of miniblocks from Postgres.
+    #[metrics(buckets = Buckets::LATENCIES)]
+    pub load_changes_latency: Histogram<Duration>,
+    /// Number of storage values loaded from Postgres for a chunk of miniblocks.
+    #[metrics(buckets = Buckets::exponential(1.0..=65_536.0, 4.0))]
+    pub loaded_values: Histogram<usize>,
+}
+
+#[vise::register]
+pub(super) static HISTORICAL_STATE_METRICS: vise::Global<HistoricalStateMetrics> =
+    vise::Global::new();
@@ -1,6 +1,8 @@
 use std::{
+    collections::{HashMap, HashSet},
     mem,
-    sync::{Arc, RwLock},
+    path::Path,
+    sync::{Arc, Mutex, RwLock},
 };
 
 use anyhow::Context as _;
@@ -12,34 +14,33 @@ use tokio::{
     },
 };
 use zksync_dal::{Connection, ConnectionPool, Core, CoreDal};
-use zksync_types::{L1BatchNumber, MiniblockNumber, StorageKey, StorageValue, H256};
+use zksync_types::{get_code_key, L1BatchNumber, MiniblockNumber, StorageKey, StorageValue, H256};
 
-use self::metrics::{Method, ValuesUpdateStage, CACHE_METRICS, STORAGE_METRICS};
+pub use self::historical::{HistoricalState, HistoricalStateUpdater};
+use self::{
+    metrics::{Method, ValuesUpdateStage, CACHE_METRICS, STORAGE_METRICS},
+    persistent::PersistentValuesCache,
+};
 use crate::{
-    cache::{lru_cache::LruCache, CacheValue},
+    cache::{
+        bytecode_cache::{BytecodeCache, InsertedAt},
+        invalidation::{InvalidateCache, RevertedBlocks},
+        lru_cache::LruCache,
+        stats::CacheStats,
+        CacheValue,
+    },
     ReadStorage,
 };
 
+mod historical;
 mod metrics;
+mod persistent;
 #[cfg(test)]
 mod tests;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct TimestampedFactoryDep {
-    bytecode: Vec<u8>,
-    inserted_at: MiniblockNumber,
-}
-
-/// Type alias for smart contract source code cache.
-type FactoryDepsCache = LruCache<H256, TimestampedFactoryDep>;
-
-impl CacheValue<H256> for TimestampedFactoryDep {
-    fn cache_weight(&self) -> u32 {
-        (self.bytecode.len() + mem::size_of::<MiniblockNumber>())
-            .try_into()
-            .expect("Cached bytes are too large")
-    }
-}
+/// Maximum number of values queued for writing to the persistent tier of [`ValuesCache`] between cache updates.
+/// Values inserted into the cache after this number is reached are only cached in memory.
+const MAX_PENDING_PERSISTENT_WRITES: usize = 100_000;
 
 /// Type alias for initial writes caches.
 type InitialWritesCache = LruCache<StorageKey, L1BatchNumber>;
@@ -62,12 +63,29 @@ impl CacheValue<StorageKey> for L1BatchNumber {
 /// Timestamp is assigned to equal the latest miniblock when a value is fetched from the storage.
 /// A value may be valid for earlier miniblocks, but fetching the actual modification "timestamp"
 /// would make the relevant Postgres query more complex.
+///
+/// The exception are absent keys (i.e., keys never written to): since they are known to be absent for
+/// all earlier miniblocks as well, they are cached with the genesis timestamp. Such negative entries
+/// are invalidated on writes in the same way as other values.
 #[derive(Debug, Clone, Copy)]
 struct TimestampedStorageValue {
     value: StorageValue,
     loaded_at: MiniblockNumber,
 }
 
+impl TimestampedStorageValue {
+    /// Creates a value loaded at the specified miniblock. `None` corresponds to an absent key.
+    fn new(value: Option<StorageValue>, loaded_at: MiniblockNumber) -> Self {
+        match value {
+            Some(value) => Self { value, loaded_at },
+            None => Self {
+                value: StorageValue::zero(),
+                loaded_at: MiniblockNumber(0),
+            },
+        }
+    }
+}
+
 impl CacheValue<H256> for TimestampedStorageValue {
     #[allow(clippy::cast_possible_truncation)] // doesn't happen in practice
     fn cache_weight(&self) -> u32 {
@@ -85,6 +103,64 @@ struct ValuesCacheInner {
     /// be taken into account).
     valid_for: MiniblockNumber,
     values: LruCache<H256, TimestampedStorageValue>,
+    /// Optional persistent tier consulted on misses of the in-memory tier. Has the same `valid_for` miniblock
+    /// as the in-memory tier.
+    persistent: Option<PersistentValuesCache>,
+    /// Values to be written to the persistent tier on the next cache update. All values are valid for `valid_for`.
+    pending_persistent_writes: Mutex<HashMap<H256, TimestampedStorageValue>>,
+    /// Incremented each time the cache is rolled back after a revert. Used to check whether a value read
+    /// from the persistent tier (which is done without holding the lock) can be promoted to the in-memory tier.
+    revert_generation: u64,
+}
+
+impl ValuesCacheInner {
+    fn queue_persistent_write(&self, hashed_key: H256, value: TimestampedStorageValue) {
+        let mut pending = self
+            .pending_persistent_writes
+            .lock()
+            .expect("pending persistent writes are poisoned");
+        if pending.len() < MAX_PENDING_PERSISTENT_WRITES || pending.contains_key(&hashed_key) {
+            pending.insert(hashed_key, value);
+        } else {
+            CACHE_METRICS.dropped_persistent_writes.inc();
+        }
+    }
+
+    fn take_pending_persistent_writes(&self) -> HashMap<H256, TimestampedStorageValue> {
+        mem::take(
+            &mut *self
+                .pending_persistent_writes
+                .lock()
+                .expect("pending persistent writes are poisoned"),
+        )
+    }
+
+    /// Clears the persistent tier (if any) after the cache was rolled back concurrently with its update.
+    /// The update could have written values that are invalid after the rollback.
+    fn clear_persistent_after_concurrent_invalidation(&self) -> anyhow::Result<()> {
+        if let Some(persistent) = &self.persistent {
+            persistent.clear()?;
+        }
+        Ok(())
+    }
+}
+
+fn get_persistent_value(
+    persistent: &PersistentValuesCache,
+    hashed_key: &H256,
+) -> Option<TimestampedStorageValue> {
+    match persistent.get(hashed_key) {
+        Ok(value) => {
+            if value.is_some() {
+                CACHE_METRICS.persistent_values_hits.inc();
+            }
+            value
+        }
+        Err(err) => {
+            tracing::warn!("Failed reading from persistent storage values cache: {err:#}");
+            None
+        }
+    }
 }
 
 /// Cache for the VM storage. Only caches values for a single VM storage snapshot, which logically
@@ -102,20 +178,30 @@ struct ValuesCacheInner {
 /// doesn't grab the lock until *after* the Postgres data has been loaded. (This works because we
 /// know statically that there is a single thread updating the cache; hence, we have no contention
 /// over updating the cache.) To summarize, `RwLock` should see barely any contention.
+///
+/// The persistent tier (if any) is never read or written on the VM execution path while holding the lock.
+/// Reads check that the cache wasn't moved before promoting a value to the in-memory tier, and writes are queued
+/// and flushed to RocksDB by the cache updater together with removing modified keys.
 #[derive(Debug, Clone)]
 struct ValuesCache(Arc<RwLock<ValuesCacheInner>>);
 
 impl ValuesCache {
+    const NAME: &'static str = "values_cache";
+
     fn new(capacity: u64) -> Self {
         let inner = ValuesCacheInner {
             valid_for: MiniblockNumber(0),
-            values: LruCache::new("values_cache", capacity),
+            values: LruCache::new(Self::NAME, capacity),
+            persistent: None,
+            pending_persistent_writes: Mutex::default(),
+            revert_generation: 0,
         };
         Self(Arc::new(RwLock::new(inner)))
     }
 
     /// *NB.* The returned value should be considered immediately stale; at best, it can be
-    /// the lower boundary on the current `valid_for` value.
+    /// the lower boundary on the current `valid_for` value (unless the cache is [invalidated](Self::invalidate())
+    /// after a revert).
     fn valid_for(&self) -> MiniblockNumber {
         self.0.read().expect("values cache is poisoned").valid_for
     }
@@ -123,6 +209,7 @@ impl ValuesCache {
     /// Gets the cached value for `key` provided that the cache currently holds values
     /// for `miniblock_number`.
     fn get(&self, miniblock_number: MiniblockNumber, key: &StorageKey) -> Option<StorageValue> {
+        let hashed_key = key.hashed_key();
         let lock = self.0.read().expect("values cache is poisoned");
         if lock.valid_for < miniblock_number {
             // The request is from the future; we cannot say which values in the cache remain valid,
@@ -130,7 +217,25 @@ impl ValuesCache {
             return None;
         }
 
-        let timestamped_value = lock.values.get(&key.hashed_key())?;
+        let in_memory_value = lock.values.get(&hashed_key);
+        let timestamped_value = if let Some(value) = in_memory_value {
+            value
+        } else {
+            let persistent = lock.persistent.clone()?;
+            let (valid_for, revert_generation) = (lock.valid_for, lock.revert_generation);
+            drop(lock);
+
+            // Values in the persistent tier are valid for the miniblock the cache was valid for when the lock was held,
+            // even if the tier was updated in the meantime: the updater only removes modified values from the tier
+            // and adds values that are valid both before and after the update.
+            let value = get_persistent_value(&persistent, &hashed_key)?;
+            let lock = self.0.read().expect("values cache is poisoned");
+            // Promote the value to the in-memory tier, unless the cache was moved while reading the value.
+            if lock.valid_for == valid_for && lock.revert_generation == revert_generation {
+                lock.values.insert(hashed_key, value);
+            }
+            value
+        };
         if timestamped_value.loaded_at <= miniblock_number {
             Some(timestamped_value.value)
         } else {
@@ -140,20 +245,126 @@ impl ValuesCache {
 
     /// Caches `value` for `key`, but only if the cache currently holds values for `miniblock_number`.
     fn insert(&self, miniblock_number: MiniblockNumber, key: StorageKey, value: StorageValue) {
+        let value = TimestampedStorageValue::new(Some(value), miniblock_number);
+        self.insert_timestamped(miniblock_number, key, value);
+    }
+
+    /// Caches the fact that `key` was never written to up to and including `miniblock_number`, but only
+    /// if the cache currently holds values for `miniblock_number`.
+    fn insert_absent(&self, miniblock_number: MiniblockNumber, key: StorageKey) {
+        let value = TimestampedStorageValue::new(None, miniblock_number);
+        self.insert_timestamped(miniblock_number, key, value);
+    }
+
+    fn insert_timestamped(
+        &self,
+        miniblock_number: MiniblockNumber,
+        key: StorageKey,
+        value: TimestampedStorageValue,
+    ) {
         let lock = self.0.read().expect("values cache is poisoned");
         if lock.valid_for == miniblock_number {
-            lock.values.insert(
-                key.hashed_key(),
-                TimestampedStorageValue {
-                    value,
-                    loaded_at: miniblock_number,
-                },
-            );
+            let hashed_key = key.hashed_key();
+            lock.values.insert(hashed_key, value);
+            if lock.persistent.is_some() {
+                lock.queue_persistent_write(hashed_key, value);
+            }
         } else {
             CACHE_METRICS.stale_values.inc();
         }
     }
 
+    /// Warms up the cache with `values` loaded at `miniblock_number` (values are keyed by hashed storage keys;
+    /// `None` values correspond to absent keys).
+    /// If the cache is behind `miniblock_number` and has no persistent tier, it's emptied and moved to `miniblock_number`
+    /// beforehand; otherwise, values are only inserted if the cache is valid for `miniblock_number`.
+    /// Returns the number of inserted values.
+    fn warm_up(
+        &self,
+        miniblock_number: MiniblockNumber,
+        values: impl Iterator<Item = (H256, Option<StorageValue>)>,
+    ) -> anyhow::Result<usize> {
+        let mut lock = self
+            .0
+            .write()
+            .map_err(|_| anyhow::anyhow!("values cache is poisoned"))?;
+        if lock.valid_for != miniblock_number {
+            // Moving the persistent tier is left to the cache updater, which knows how to do it efficiently.
+            if lock.persistent.is_some() || lock.valid_for > miniblock_number {
+                return Ok(0);
+            }
+            lock.valid_for = miniblock_number;
+            lock.values.clear();
+            CACHE_METRICS
+                .values_valid_for_miniblock
+                .set(u64::from(miniblock_number.0));
+        }
+
+        let mut inserted_count = 0;
+        let mut pending_persistent_writes = lock
+            .pending_persistent_writes
+            .lock()
+            .map_err(|_| anyhow::anyhow!("pending persistent writes are poisoned"))?;
+        for (hashed_key, value) in values {
+            let value = TimestampedStorageValue::new(value, miniblock_number);
+            lock.values.insert(hashed_key, value);
+            if lock.persistent.is_some() {
+                // Warm-up values are not subject to `MAX_PENDING_PERSISTENT_WRITES`; otherwise, most of them
+                // wouldn't be persisted.
+                pending_persistent_writes.insert(hashed_key, value);
+            }
+            inserted_count += 1;
+        }
+        drop(pending_persistent_writes);
+        lock.values.report_size();
+        Ok(inserted_count)
+    }
+
+    /// Attaches the persistent tier to this cache. If the persistent tier is valid for a miniblock
+    /// that is still present in Postgres, the cache is moved to this miniblock; otherwise, the persistent tier is cleared.
+    async fn attach_persistent_tier(
+        &self,
+        persistent: PersistentValuesCache,
+        connection: &mut Connection<'_, Core>,
+    ) -> anyhow::Result<()> {
+        let mut restored_miniblock = None;
+        if let Some((miniblock_number, miniblock_hash)) = persistent.valid_for()? {
+            let header = connection
+                .blocks_dal()
+                .get_miniblock_header(miniblock_number)
+                .await?;
+            if header.map_or(false, |header| header.hash == miniblock_hash) {
+                restored_miniblock = Some(miniblock_number);
+            } else {
+                tracing::info!(
+                    "Miniblock #{miniblock_number} the persistent storage values cache is valid for \
+                     is missing or has a different hash in Postgres (e.g., it was reverted); clearing the cache"
+                );
+            }
+        }
+        if restored_miniblock.is_none() {
+            persistent.clear()?;
+        }
+
+        let mut lock = self
+            .0
+            .write()
+            .map_err(|_| anyhow::anyhow!("values cache is poisoned"))?;
+        if let Some(miniblock_number) = restored_miniblock {
+            tracing::info!(
+                "Restored persistent storage values cache valid for miniblock #{miniblock_number}"
+            );
+            // Values in the in-memory tier are not necessarily valid for the restored miniblock.
+            lock.valid_for = miniblock_number;
+            lock.values.clear();
+            CACHE_METRICS
+                .values_valid_for_miniblock
+                .set(u64::from(miniblock_number.0));
+        }
+        lock.persistent = Some(persistent);
+        Ok(())
+    }
+
     async fn update(
         &self,
         from_miniblock: MiniblockNumber,
@@ -161,22 +372,56 @@ impl ValuesCache {
         connection: &mut Connection<'_, Core>,
     ) -> anyhow::Result<()> {
         const MAX_MINIBLOCKS_LAG: u32 = 5;
+        /// With the persistent tier, the cache can be far behind after a restart. Since resetting the cache
+        /// would defeat the purpose of persisting it, we allow much larger lag in this case.
+        const MAX_MINIBLOCKS_LAG_WITH_PERSISTENT_TIER: u32 = 1_000;
 
         tracing::debug!(
             "Updating storage values cache from miniblock {from_miniblock} to {to_miniblock}"
         );
 
-        if to_miniblock.0 - from_miniblock.0 > MAX_MINIBLOCKS_LAG {
+        let persistent = self
+            .0
+            .read()
+            .map_err(|_| anyhow::anyhow!("values cache is poisoned"))?
+            .persistent
+            .clone();
+        let (max_lag, persistent_update) = if let Some(persistent) = persistent {
+            let header = connection
+                .blocks_dal()
+                .get_miniblock_header(to_miniblock)
+                .await?
+                .with_context(|| format!("miniblock #{to_miniblock} is missing in Postgres"))?;
+            (
+                MAX_MINIBLOCKS_LAG_WITH_PERSISTENT_TIER,
+                Some((persistent, header.hash)),
+            )
+        } else {
+            (MAX_MINIBLOCKS_LAG, None)
+        };
+
+        if to_miniblock.0 - from_miniblock.0 > max_lag {
             // We can spend too much time loading data from Postgres, so we opt for an easier "update" route:
             // evict *everything* from cache and call it a day. This should not happen too often in practice.
             tracing::info!(
                 "Storage values cache is too far behind (current miniblock is {from_miniblock}; \
                  requested update to {to_miniblock}); resetting the cache"
             );
+            // The persistent tier is updated first (without holding the lock), so that a RocksDB error
+            // leaves the cache intact. Pending writes are discarded together with other values.
+            if let Some((persistent, to_miniblock_hash)) = &persistent_update {
+                self.take_pending_persistent_writes()?;
+                persistent.reset(to_miniblock, *to_miniblock_hash)?;
+            }
+
             let mut lock = self
                 .0
                 .write()
                 .map_err(|_| anyhow::anyhow!("values cache is poisoned"))?;
+            if lock.valid_for < from_miniblock {
+                Self::log_concurrent_invalidation(lock.valid_for, from_miniblock);
+                return lock.clear_persistent_after_concurrent_invalidation();
+            }
             anyhow::ensure!(
                 lock.valid_for == from_miniblock,
                 "sanity check failed: values cache was expected to be valid for miniblock #{from_miniblock}, but it's actually \
@@ -185,6 +430,7 @@ impl ValuesCache {
             );
             lock.valid_for = to_miniblock;
             lock.values.clear();
+            lock.take_pending_persistent_writes();
 
             CACHE_METRICS.values_emptied.inc();
         } else {
@@ -205,6 +451,37 @@ impl ValuesCache {
                 modified_keys_len = modified_keys.len()
             );
 
+            // The persistent tier is updated first (without holding the lock), so that a RocksDB error
+            // leaves the cache intact.
+            let modified_keys_set: HashSet<_> = if persistent_update.is_some() {
+                modified_keys.iter().copied().collect()
+            } else {
+                HashSet::new()
+            };
+            if let Some((persistent, to_miniblock_hash)) = &persistent_update {
+                let update_latency =
+                    CACHE_METRICS.values_update[&ValuesUpdateStage::UpdatePersistentTier].start();
+                let mut new_values = self.take_pending_persistent_writes()?;
+                new_values.retain(|hashed_key, _| !modified_keys_set.contains(hashed_key));
+
+                if persistent.is_full() {
+                    tracing::info!(
+                        "Persistent tier of storage values cache exceeds its maximum number of entries; emptying it"
+                    );
+                    persistent.reset(to_miniblock, *to_miniblock_hash)?;
+                    CACHE_METRICS.persistent_values_emptied.inc();
+                } else {
+                    let new_values: Vec<_> = new_values.into_iter().collect();
+                    persistent.update(
+                        &modified_keys,
+                        &new_values,
+                        to_miniblock,
+                        *to_miniblock_hash,
+                    )?;
+                }
+                update_latency.observe();
+            }
+
             let update_latency =
                 CACHE_METRICS.values_update[&ValuesUpdateStage::RemoveStaleKeys].start();
             let mut lock = self
@@ -213,7 +490,11 @@ impl ValuesCache {
                 .map_err(|_| anyhow::anyhow!("values cache is poisoned"))?;
             // The code below holding onto the write `lock` is the only code that can theoretically poison the `RwLock`
             // (other than emptying the cache above). Thus, it's kept as simple and tight as possible.
-            // E.g., we load data from Postgres beforehand.
+            // E.g., we load data from Postgres and update the persistent tier beforehand.
+            if lock.valid_for < from_miniblock {
+                Self::log_concurrent_invalidation(lock.valid_for, from_miniblock);
+                return lock.clear_persistent_after_concurrent_invalidation();
+            }
             anyhow::ensure!(
                 lock.valid_for == from_miniblock,
                 "sanity check failed: values cache was expected to be valid for miniblock #{from_miniblock}, but it's actually \
@@ -224,6 +505,11 @@ impl ValuesCache {
             for modified_key in &modified_keys {
                 lock.values.remove(modified_key);
             }
+            // Writes queued while the persistent tier was updated remain valid unless they correspond to modified keys.
+            lock.pending_persistent_writes
+                .get_mut()
+                .map_err(|_| anyhow::anyhow!("pending persistent writes are poisoned"))?
+                .retain(|hashed_key, _| !modified_keys_set.contains(hashed_key));
             lock.values.report_size();
             drop(lock);
             update_latency.observe();
@@ -234,6 +520,80 @@ impl ValuesCache {
             .set(u64::from(to_miniblock.0));
         Ok(())
     }
+
+    fn take_pending_persistent_writes(
+        &self,
+    ) -> anyhow::Result<HashMap<H256, TimestampedStorageValue>> {
+        let lock = self
+            .0
+            .read()
+            .map_err(|_| anyhow::anyhow!("values cache is poisoned"))?;
+        Ok(lock.take_pending_persistent_writes())
+    }
+
+    fn stats(&self) -> Option<CacheStats> {
+        self.0
+            .read()
+            .expect("values cache is poisoned")
+            .values
+            .stats()
+    }
+
+    fn resize(&self, capacity: u64) -> u64 {
+        self.0
+            .read()
+            .expect("values cache is poisoned")
+            .values
+            .resize(capacity)
+    }
+
+    /// Removes all values from the cache (including its persistent tier) without changing the `valid_for` miniblock.
+    fn clear(&self) {
+        let lock = self.0.read().expect("values cache is poisoned");
+        lock.values.clear();
+        lock.take_pending_persistent_writes();
+        if let Some(persistent) = &lock.persistent {
+            if let Err(err) = persistent.clear() {
+                tracing::warn!("Failed clearing persistent tier of storage values cache: {err:#}");
+            }
+        }
+    }
+
+    fn log_concurrent_invalidation(valid_for: MiniblockNumber, from_miniblock: MiniblockNumber) {
+        tracing::info!(
+            "Storage values cache was invalidated to miniblock #{valid_for} while updating it from miniblock \
+             #{from_miniblock}; skipping the update"
+        );
+    }
+
+    /// Removes values that could have been loaded from miniblocks after `last_miniblock_to_keep` and rolls back
+    /// the `valid_for` miniblock. The persistent tier (if any) is cleared since it doesn't record
+    /// when values were loaded.
+    fn invalidate(&self, last_miniblock_to_keep: MiniblockNumber) {
+        let mut lock = self.0.write().expect("values cache is poisoned");
+        if lock.valid_for <= last_miniblock_to_keep {
+            return;
+        }
+        tracing::info!(
+            "Invalidating storage values cache valid for miniblock #{} to miniblock #{last_miniblock_to_keep}",
+            lock.valid_for
+        );
+        lock.valid_for = last_miniblock_to_keep;
+        lock.revert_generation += 1;
+        lock.values
+            .retain(|_, value| value.loaded_at <= last_miniblock_to_keep);
+        lock.take_pending_persistent_writes();
+        if let Some(persistent) = &lock.persistent {
+            if let Err(err) = persistent.clear() {
+                tracing::warn!("Failed clearing persistent tier of storage values cache: {err:#}");
+            }
+        }
+        drop(lock);
+
+        CACHE_METRICS
+            .values_valid_for_miniblock
+            .set(u64::from(last_miniblock_to_keep.0));
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -246,13 +606,21 @@ struct ValuesCacheAndUpdater {
 ///
 /// Currently, this struct includes the following caches:
 ///
-/// - Cache for smart contract bytecodes (never invalidated, since it is content-addressable)
+/// - Cache for smart contract bytecodes (content-addressable, so only bytecodes inserted in reverted miniblocks
+///   are removed on invalidation)
 /// - Cache for L1 batch numbers of initial writes for storage keys (never invalidated, except after
 ///   reverting L1 batch execution)
-/// - Cache of the VM storage snapshot corresponding to the latest sealed miniblock
+/// - Cache of the VM storage snapshot corresponding to the latest sealed miniblock. This cache can optionally
+///   have a persistent tier backed by RocksDB (see [`PostgresStorageCachesTask::with_persistent_values_cache()`]).
+///
+/// Additionally, caches may include a [`HistoricalState`], which allows reading storage values and factory deps
+/// for past miniblocks without querying Postgres.
+///
+/// After a block revert, caches must be invalidated using the [`InvalidateCache`] implementation
+/// (e.g., by registering them in a [`CacheInvalidator`](crate::CacheInvalidator)).
 #[derive(Debug, Clone)]
 pub struct PostgresStorageCaches {
-    factory_deps: FactoryDepsCache,
+    factory_deps: BytecodeCache,
     initial_writes: InitialWritesCache,
     // Besides L1 batch numbers for initial writes, we also cache information that a certain key
     // was not written to before the certain L1 batch (i.e., this lower boundary is the cached value).
@@ -263,12 +631,15 @@ pub struct PostgresStorageCaches {
     // it wasn't written to at the point that interests us.
     negative_initial_writes: InitialWritesCache,
     values: Option<ValuesCacheAndUpdater>,
+    historical_state: Option<HistoricalState>,
 }
 
 impl PostgresStorageCaches {
+    const INITIAL_WRITES_NAME: &'static str = "initial_writes_cache";
     const NEG_INITIAL_WRITES_NAME: &'static str = "negative_initial_writes_cache";
 
-    /// Creates caches with the specified capacities measured in bytes.
+    /// Creates caches with the specified capacities measured in bytes. Capacities can be changed later
+    /// using [`Self::resize()`].
     pub fn new(factory_deps_capacity: u64, initial_writes_capacity: u64) -> Self {
         tracing::debug!(
             "Initialized VM execution cache with {factory_deps_capacity}B capacity for factory deps, \
@@ -276,9 +647,9 @@ impl PostgresStorageCaches {
         );
 
         Self {
-            factory_deps: FactoryDepsCache::new("factory_deps_cache", factory_deps_capacity),
+            factory_deps: BytecodeCache::new(factory_deps_capacity),
             initial_writes: InitialWritesCache::new(
-                "initial_writes_cache",
+                Self::INITIAL_WRITES_NAME,
                 initial_writes_capacity / 2,
             ),
             negative_initial_writes: InitialWritesCache::new(
@@ -286,9 +657,89 @@ impl PostgresStorageCaches {
                 initial_writes_capacity / 2,
             ),
             values: None,
+            historical_state: None,
         }
     }
 
+    /// Replaces the factory deps cache with the specified bytecode cache, which can be shared with other components.
+    #[must_use]
+    pub fn with_bytecode_cache(mut self, bytecode_cache: BytecodeCache) -> Self {
+        self.factory_deps = bytecode_cache;
+        self
+    }
+
+    /// Sets the local historical state consulted on cache misses before querying Postgres. The state must be updated
+    /// separately using [`HistoricalStateUpdater`].
+    #[must_use]
+    pub fn with_historical_state(mut self, historical_state: HistoricalState) -> Self {
+        self.historical_state = Some(historical_state);
+        self
+    }
+
+    /// Reads the value of the storage key as of the specified miniblock from the historical state. Returns `None`
+    /// if the historical state is not configured, or cannot provide the value (in which case the value should be read
+    /// from Postgres).
+    pub fn read_historical_value(
+        &self,
+        key: &StorageKey,
+        miniblock_number: MiniblockNumber,
+    ) -> Option<StorageValue> {
+        self.historical_state
+            .as_ref()?
+            .read_value(&key.hashed_key(), miniblock_number)
+    }
+
+    /// Returns statistics for all enabled caches.
+    #[must_use]
+    pub fn stats(&self) -> Vec<CacheStats> {
+        let values_stats = self.values.as_ref().and_then(|values| values.cache.stats());
+        let all_stats = [
+            self.factory_deps.stats(),
+            self.initial_writes.stats(),
+            self.negative_initial_writes.stats(),
+            values_stats,
+        ];
+        all_stats.into_iter().flatten().collect()
+    }
+
+    /// Removes all entries from the cache with the specified name (as returned in [`Self::stats()`]).
+    /// Flushing the VM storage values cache clears its persistent tier as well. Returns `false` if there is
+    /// no enabled cache with the specified name.
+    #[must_use]
+    pub fn flush(&self, cache_name: &str) -> bool {
+        if !self.stats().iter().any(|stats| stats.name == cache_name) {
+            return false;
+        }
+        tracing::info!("Flushing VM cache `{cache_name}`");
+        match cache_name {
+            BytecodeCache::NAME => self.factory_deps.clear(),
+            Self::INITIAL_WRITES_NAME => self.initial_writes.clear(),
+            Self::NEG_INITIAL_WRITES_NAME => self.negative_initial_writes.clear(),
+            ValuesCache::NAME => {
+                if let Some(values) = &self.values {
+                    values.cache.clear();
+                }
+            }
+            _ => unreachable!("unknown cache name: {cache_name}"),
+        }
+        true
+    }
+
+    /// Changes the capacity (in bytes) of the cache with the specified name, retaining as many entries as possible.
+    /// Unlike [`Self::flush()`], disabled caches (i.e., ones with zero capacity) can be resized as well, except for
+    /// the VM storage values cache, which must be [configured](Self::configure_storage_values_cache()) beforehand.
+    /// Returns the previous capacity, or `None` if there is no cache with the specified name.
+    pub fn resize(&self, cache_name: &str, capacity: u64) -> Option<u64> {
+        let prev_capacity = match cache_name {
+            BytecodeCache::NAME => self.factory_deps.resize(capacity),
+            Self::INITIAL_WRITES_NAME => self.initial_writes.resize(capacity),
+            Self::NEG_INITIAL_WRITES_NAME => self.negative_initial_writes.resize(capacity),
+            ValuesCache::NAME => self.values.as_ref()?.cache.resize(capacity),
+            _ => return None,
+        };
+        Some(prev_capacity)
+    }
+
     /// Configures the VM storage values cache. The returned closure is the background task that will update
     /// the cache according to [`Self::schedule_values_update()`] calls. It should be spawned on a separate thread
     /// or a blocking Tokio task.
@@ -322,10 +773,106 @@ impl PostgresStorageCaches {
         PostgresStorageCachesTask {
             connection_pool,
             values_cache,
+            persistent_values_cache: None,
             command_receiver,
         }
     }
 
+    /// Warms up the caches using storage keys touched in the latest `miniblock_count` sealed miniblocks (judging by
+    /// storage logs), so that VM executions right after a restart don't need to load all data from Postgres.
+    ///
+    /// - The VM storage values cache (if configured) is filled with the current values of the touched keys, unless the cache
+    ///   is ahead of the latest sealed miniblock. If the cache has a persistent tier, restoring this tier may discard
+    ///   warmed-up values.
+    /// - The factory deps cache is filled with bytecodes of contracts the touched keys belong to.
+    ///
+    /// This method should be called before running [`PostgresStorageCachesTask`] returned by
+    /// [`Self::configure_storage_values_cache()`].
+    ///
+    /// # Errors
+    ///
+    /// Propagates Postgres and RocksDB errors.
+    pub async fn warm_up(
+        &self,
+        connection: &mut Connection<'_, Core>,
+        miniblock_count: u32,
+    ) -> anyhow::Result<()> {
+        /// Maximum number of touched storage keys to load.
+        const MAX_KEYS: usize = 100_000;
+        /// Number of storage keys to load values for in a single Postgres query.
+        const KEYS_CHUNK_SIZE: usize = 10_000;
+
+        if miniblock_count == 0 {
+            return Ok(());
+        }
+        let Some(latest_miniblock) = connection
+            .blocks_dal()
+            .get_sealed_miniblock_number()
+            .await?
+        else {
+            return Ok(()); // No miniblocks to take keys from
+        };
+        let latency = CACHE_METRICS.warm_up_latency.start();
+        let first_miniblock =
+            MiniblockNumber(latest_miniblock.0.saturating_sub(miniblock_count - 1));
+        let touched_keys = connection
+            .storage_logs_dal()
+            .recently_touched_keys(first_miniblock..=latest_miniblock, MAX_KEYS)
+            .await?;
+
+        // Besides touched keys, load bytecode hashes of the touched contracts, which are required to warm up
+        // the factory deps cache.
+        let addresses: HashSet<_> = touched_keys.iter().map(|(_, address)| *address).collect();
+        let code_keys: HashSet<_> = addresses
+            .iter()
+            .map(|address| get_code_key(address).hashed_key())
+            .collect();
+        let mut hashed_keys: Vec<_> = touched_keys.iter().map(|(key, _)| *key).collect();
+        hashed_keys.extend(code_keys.iter().copied());
+        hashed_keys.sort_unstable();
+        hashed_keys.dedup();
+
+        let mut values = HashMap::with_capacity(hashed_keys.len());
+        for chunk in hashed_keys.chunks(KEYS_CHUNK_SIZE) {
+            let chunk_values = connection
+                .storage_logs_dal()
+                .get_storage_values(chunk, latest_miniblock)
+                .await?;
+            values.extend(chunk_values);
+        }
+
+        let bytecode_hashes: Vec<_> = code_keys
+            .iter()
+            .filter_map(|key| values.get(key).copied().flatten())
+            .filter(|hash| !hash.is_zero())
+            .collect();
+        let factory_deps = connection
+            .storage_web3_dal()
+            .get_factory_deps_with_miniblocks(&bytecode_hashes)
+            .await?;
+        let factory_deps_count = factory_deps.len();
+        for (hash, (bytecode, inserted_at)) in factory_deps {
+            self.factory_deps
+                .insert(hash, bytecode.into(), InsertedAt::Exact(inserted_at));
+        }
+
+        let values_count = if let Some(values_cache) = &self.values {
+            values_cache
+                .cache
+                .warm_up(latest_miniblock, values.into_iter())?
+        } else {
+            0
+        };
+        let elapsed = latency.observe();
+        tracing::info!(
+            "Warmed up VM caches using {touched_keys_len} storage keys touched in miniblocks \
+             {first_miniblock}..={latest_miniblock}: cached {values_count} storage values and \
+             {factory_deps_count} factory deps; took {elapsed:?}",
+            touched_keys_len = touched_keys.len()
+        );
+        Ok(())
+    }
+
     /// Schedules an update of the VM storage values cache to the specified miniblock. If the values cache is not configured,
     /// this is a no-op.
     ///
@@ -346,29 +893,86 @@ impl PostgresStorageCaches {
     }
 }
 
+impl InvalidateCache for PostgresStorageCaches {
+    fn invalidate(&self, reverted: RevertedBlocks) {
+        self.factory_deps.invalidate(reverted);
+        self.initial_writes
+            .retain(|_, &l1_batch| l1_batch <= reverted.last_l1_batch_to_keep);
+        // Negative entries are lower bounds for the L1 batch of an initial write; after the revert,
+        // a write may occur in the first L1 batch after the retained ones.
+        let first_new_l1_batch = reverted.last_l1_batch_to_keep + 1;
+        self.negative_initial_writes
+            .retain(|_, &l1_batch| l1_batch <= first_new_l1_batch);
+        if let Some(values) = &self.values {
+            values.cache.invalidate(reverted.last_miniblock_to_keep);
+        }
+        if let Some(historical_state) = &self.historical_state {
+            historical_state.invalidate(reverted);
+        }
+    }
+}
+
 /// An asynchronous task that updates the VM storage values cache.
 #[derive(Debug)]
 pub struct PostgresStorageCachesTask {
     connection_pool: ConnectionPool<Core>,
     values_cache: ValuesCache,
+    persistent_values_cache: Option<PersistentValuesCache>,
     command_receiver: UnboundedReceiver<MiniblockNumber>,
 }
 
 impl PostgresStorageCachesTask {
+    /// Adds a persistent tier backed by RocksDB at the specified path to the VM storage values cache.
+    /// The persistent tier is consulted on misses of the in-memory tier, and retains cached values across
+    /// process restarts, so that the cache doesn't need to be warmed up from scratch after each restart.
+    /// The persistent tier is emptied once it contains more than `max_entries` values.
+    ///
+    /// The persistent tier is only used after the task is [run](Self::run()) and has checked that
+    /// the persisted values are still valid (e.g., were not affected by a revert).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if RocksDB cannot be opened.
+    pub fn with_persistent_values_cache(
+        mut self,
+        db_path: &Path,
+        max_entries: u64,
+    ) -> anyhow::Result<Self> {
+        tracing::debug!(
+            "Initializing persistent tier of VM storage values cache at `{}` with max {max_entries} entries",
+            db_path.display()
+        );
+        self.persistent_values_cache = Some(PersistentValuesCache::new(db_path, max_entries)?);
+        Ok(self)
+    }
+
     /// Runs the task.
     ///
     /// ## Errors
     ///
     /// - Propagates Postgres errors.
     /// - Propagates errors from the cache update task.
+    /// - Propagates RocksDB errors when updating the persistent tier of the values cache.
     pub async fn run(mut self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
-        let mut current_miniblock = self.values_cache.valid_for();
+        if let Some(persistent) = self.persistent_values_cache.take() {
+            let mut connection = self
+                .connection_pool
+                .connection_tagged("values_cache_updater")
+                .await?;
+            self.values_cache
+                .attach_persistent_tier(persistent, &mut connection)
+                .await?;
+        }
+
         loop {
             tokio::select! {
                 _ = stop_receiver.changed() => {
                     break;
                 }
                 Some(to_miniblock) = self.command_receiver.recv() => {
+                    // The cache is only updated by this task, but it can be rolled back by invalidation after a revert,
+                    // so we re-read the current miniblock on each update.
+                    let current_miniblock = self.values_cache.valid_for();
                     if to_miniblock <= current_miniblock {
                         continue;
                     }
@@ -379,7 +983,6 @@ impl PostgresStorageCachesTask {
                     self.values_cache
                         .update(current_miniblock, to_miniblock, &mut connection)
                         .await?;
-                    current_miniblock = to_miniblock;
                 }
                 else => {
                     // The command sender has been dropped, which means that we must receive the stop signal soon.
@@ -402,6 +1005,9 @@ pub struct PostgresStorage<'a> {
     pending_l1_batch_number: L1BatchNumber,
     consider_new_l1_batch: bool,
     caches: Option<PostgresStorageCaches>,
+    bytecode_cache: Option<BytecodeCache>,
+    /// Values loaded by [`Self::prefetch_values()`], keyed by hashed storage keys.
+    prefetched_values: HashMap<H256, StorageValue>,
 }
 
 impl<'a> PostgresStorage<'a> {
@@ -453,6 +1059,8 @@ impl<'a> PostgresStorage<'a> {
             pending_l1_batch_number: resolved.pending_l1_batch,
             consider_new_l1_batch,
             caches: None,
+            bytecode_cache: None,
+            prefetched_values: HashMap::new(),
         })
     }
 
@@ -460,11 +1068,76 @@ impl<'a> PostgresStorage<'a> {
     #[must_use]
     pub fn with_caches(self, caches: PostgresStorageCaches) -> Self {
         Self {
+            bytecode_cache: Some(caches.factory_deps.clone()),
             caches: Some(caches),
             ..self
         }
     }
 
+    /// Sets the bytecode cache to use with the storage. This is useful if the storage is used without
+    /// [other caches](Self::with_caches()).
+    #[must_use]
+    pub fn with_bytecode_cache(self, bytecode_cache: BytecodeCache) -> Self {
+        Self {
+            bytecode_cache: Some(bytecode_cache),
+            ..self
+        }
+    }
+
+    /// Loads values for the specified `keys` from Postgres using a single query, so that subsequent
+    /// [`ReadStorage::read_value()`] calls for these keys don't need a round trip to Postgres. Keys that are present
+    /// in the values cache are skipped. This is useful if the keys accessed by the VM are known (e.g., from an access list
+    /// or a previous execution of the same transaction).
+    ///
+    /// # Errors
+    ///
+    /// Propagates Postgres errors.
+    pub async fn prefetch_values(&mut self, keys: &[StorageKey]) -> anyhow::Result<()> {
+        let latency = STORAGE_METRICS.storage[&Method::PrefetchValues].start();
+        let values_cache = self.values_cache();
+        let keys_to_load: HashMap<_, _> = keys
+            .iter()
+            .filter(|key| {
+                values_cache.map_or(true, |cache| {
+                    cache.get(self.miniblock_number, key).is_none()
+                })
+            })
+            .map(|key| (key.hashed_key(), *key))
+            .filter(|(hashed_key, _)| !self.prefetched_values.contains_key(hashed_key))
+            .collect();
+        if keys_to_load.is_empty() {
+            return Ok(());
+        }
+
+        let hashed_keys: Vec<_> = keys_to_load.keys().copied().collect();
+        let values = self
+            .connection
+            .storage_logs_dal()
+            .get_storage_values(&hashed_keys, self.miniblock_number)
+            .await
+            .with_context(|| {
+                format!(
+                    "failed prefetching {} storage values for miniblock #{}",
+                    hashed_keys.len(),
+                    self.miniblock_number
+                )
+            })?;
+
+        for (hashed_key, value) in values {
+            if let (Some(cache), Some(key)) = (self.values_cache(), keys_to_load.get(&hashed_key)) {
+                match value {
+                    Some(value) => cache.insert(self.miniblock_number, *key, value),
+                    None => cache.insert_absent(self.miniblock_number, *key),
+                }
+            }
+            self.prefetched_values
+                .insert(hashed_key, value.unwrap_or_default());
+        }
+        STORAGE_METRICS.prefetched_values.observe(hashed_keys.len());
+        latency.observe();
+        Ok(())
+    }
+
     /// This method is expected to be called for each write that was found in the database, and it decides
     /// whether the change is initial or not. Even if a change is present in the DB, in some cases we would not consider it.
     /// For example, in API we always represent the state at the beginning of an L1 batch, so we discard all the writes
@@ -480,24 +1153,40 @@ impl<'a> PostgresStorage<'a> {
     fn values_cache(&self) -> Option<&ValuesCache> {
         Some(&self.caches.as_ref()?.values.as_ref()?.cache)
     }
+
+    fn historical_state(&self) -> Option<&HistoricalState> {
+        self.caches.as_ref()?.historical_state.as_ref()
+    }
 }
 
 impl ReadStorage for PostgresStorage<'_> {
     fn read_value(&mut self, &key: &StorageKey) -> StorageValue {
         let latency = STORAGE_METRICS.storage[&Method::ReadValue].start();
         let values_cache = self.values_cache();
-        let cached_value = values_cache.and_then(|cache| cache.get(self.miniblock_number, &key));
+        let cached_value = self
+            .prefetched_values
+            .get(&key.hashed_key())
+            .copied()
+            .or_else(|| values_cache.and_then(|cache| cache.get(self.miniblock_number, &key)))
+            .or_else(|| {
+                self.historical_state()?
+                    .read_value(&key.hashed_key(), self.miniblock_number)
+            });
 
         let value = cached_value.unwrap_or_else(|| {
             let mut dal = self.connection.storage_web3_dal();
             let value = self
                 .rt_handle
-                .block_on(dal.get_historical_value_unchecked(&key, self.miniblock_number))
+                .block_on(dal.get_historical_value_opt_unchecked(&key, self.miniblock_number))
                 .expect("Failed executing `read_value`");
             if let Some(cache) = self.values_cache() {
-                cache.insert(self.miniblock_number, key, value);
+                match value {
+                    Some(value) => cache.insert(self.miniblock_number, key, value),
+                    // Absent keys are extremely common (e.g., fresh accounts), so we cache them as well.
+                    None => cache.insert_absent(self.miniblock_number, key),
+                }
             }
-            value
+            value.unwrap_or_default()
         });
 
         latency.observe();
@@ -553,41 +1242,49 @@ impl ReadStorage for PostgresStorage<'_> {
         !contains_key
     }
 
-    fn load_factory_dep(&mut self, hash: H256) -> Option<Vec<u8>> {
+    fn load_factory_dep(&mut self, hash: H256) -> Option<Arc<[u8]>> {
         let latency = STORAGE_METRICS.storage[&Method::LoadFactoryDep].start();
 
         let cached_value = self
-            .caches
+            .bytecode_cache
             .as_ref()
-            .and_then(|caches| caches.factory_deps.get(&hash));
-
-        let value = cached_value.or_else(|| {
-            let mut dal = self.connection.storage_web3_dal();
-            let value = self
-                .rt_handle
-                .block_on(dal.get_factory_dep(hash))
-                .expect("Failed executing `load_factory_dep`")
-                .map(|(bytecode, inserted_at)| TimestampedFactoryDep {
-                    bytecode,
-                    inserted_at,
-                });
-
-            if let Some(caches) = &self.caches {
-                // If we receive None, we won't cache it.
-                if let Some(value) = value.clone() {
-                    caches.factory_deps.insert(hash, value);
-                }
-            };
-
-            value
-        });
+            .and_then(|cache| cache.get(&hash));
+        if let Some(cached_value) = &cached_value {
+            // Bytecodes cached without the exact insertion miniblock (e.g., by the state keeper) are only used
+            // if they are known to be inserted before the miniblock of this storage.
+            if let Some(is_visible) = cached_value
+                .inserted_at
+                .is_visible_at(self.miniblock_number)
+            {
+                latency.observe();
+                return is_visible.then(|| cached_value.bytecode.clone());
+            }
+        } else {
+            let historical_value = self
+                .historical_state()
+                .and_then(|state| state.load_factory_dep(hash, self.miniblock_number));
+            if let Some(bytecode) = historical_value {
+                latency.observe();
+                return bytecode;
+            }
+        }
 
+        let mut dal = self.connection.storage_web3_dal();
+        let value = self
+            .rt_handle
+            .block_on(dal.get_factory_dep(hash))
+            .expect("Failed executing `load_factory_dep`");
         latency.observe();
-        Some(
-            value
-                .filter(|dep| dep.inserted_at <= self.miniblock_number)?
-                .bytecode,
-        )
+
+        // If we receive None, we won't cache it.
+        let (bytecode, inserted_at) = value?;
+        // Bytecodes are content-addressed, so the cached bytecode (if any) can be reused instead of
+        // the loaded one; this way, all components share a single copy of the bytecode.
+        let bytecode = cached_value.map_or_else(|| bytecode.into(), |cached| cached.bytecode);
+        if let Some(cache) = &self.bytecode_cache {
+            cache.insert(hash, bytecode.clone(), InsertedAt::Exact(inserted_at));
+        }
+        (inserted_at <= self.miniblock_number).then_some(bytecode)
     }
 
     fn get_enumeration_index(&mut self, key: &StorageKey) -> Option<u64> {
@@ -0,0 +1,195 @@
+//! Persistent (RocksDB-backed) tier for the VM storage values cache.
+//!
+//! ## Storage layout
+//!
+//! | Column | Key                 | Value                                        | Description                               |
+//! | ------ | ------------------- | -------------------------------------------- | ----------------------------------------- |
+//! | Meta   | 'valid_for'         | miniblock number (u32, LE) ++ miniblock hash | Miniblock the cached values are valid for |
+//! | Values | hashed `StorageKey` | 32 bytes value ++ miniblock number (u32, LE) | Value and the miniblock it was loaded at  |
+
+use std::{ops, path::Path};
+
+use anyhow::Context as _;
+use zksync_storage::{
+    db::{NamedColumnFamily, WriteBatch},
+    RocksDB,
+};
+use zksync_types::{MiniblockNumber, H256};
+
+use super::TimestampedStorageValue;
+
+const VALID_FOR_KEY: &[u8] = b"valid_for";
+
+/// Bounds of the range covering all keys in the values column family (all keys are 32 bytes long).
+static MIN_VALUES_KEY: [u8; 32] = [0; 32];
+static VALUES_KEY_UPPER_BOUND: [u8; 33] = [0xff; 33];
+
+fn all_values_range() -> ops::Range<&'static [u8]> {
+    &MIN_VALUES_KEY[..]..&VALUES_KEY_UPPER_BOUND[..]
+}
+
+/// RocksDB column families used by [`PersistentValuesCache`].
+#[derive(Debug, Clone, Copy)]
+pub(super) enum ValuesCacheColumnFamily {
+    /// Metadata (e.g., the miniblock the cache is valid for).
+    Meta,
+    /// Cached storage values.
+    Values,
+}
+
+impl NamedColumnFamily for ValuesCacheColumnFamily {
+    const DB_NAME: &'static str = "storage_values_cache";
+    const ALL: &'static [Self] = &[Self::Meta, Self::Values];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Meta => "meta",
+            Self::Values => "values",
+        }
+    }
+}
+
+fn serialize_value(value: &TimestampedStorageValue) -> [u8; 36] {
+    let mut buffer = [0_u8; 36];
+    buffer[..32].copy_from_slice(value.value.as_bytes());
+    buffer[32..].copy_from_slice(&value.loaded_at.0.to_le_bytes());
+    buffer
+}
+
+fn deserialize_value(bytes: &[u8]) -> anyhow::Result<TimestampedStorageValue> {
+    anyhow::ensure!(
+        bytes.len() == 36,
+        "unexpected cached value length: {}",
+        bytes.len()
+    );
+    let loaded_at = bytes[32..].try_into().unwrap();
+    Ok(TimestampedStorageValue {
+        value: H256::from_slice(&bytes[..32]),
+        loaded_at: MiniblockNumber(u32::from_le_bytes(loaded_at)),
+    })
+}
+
+/// Persistent tier for the VM storage values cache. Unlike the in-memory tier, it survives process restarts
+/// and isn't subject to LRU eviction, so that the values cache doesn't need to be warmed up from scratch.
+/// Instead, the tier is bounded by the number of entries; once the bound is exceeded, the tier is emptied
+/// on the next update.
+///
+/// The persistent tier has the same validity semantics as the in-memory tier: all cached values are valid
+/// for the miniblock returned by [`Self::valid_for()`]. The miniblock is persisted together with its hash,
+/// so that the cache can be discarded on startup if the miniblock was reverted in the meantime.
+/// Values are written to the tier in batches when the cache is updated (see [`Self::update()`]).
+#[derive(Debug, Clone)]
+pub(super) struct PersistentValuesCache {
+    db: RocksDB<ValuesCacheColumnFamily>,
+    max_entries: u64,
+}
+
+impl PersistentValuesCache {
+    pub fn new(db_path: &Path, max_entries: u64) -> anyhow::Result<Self> {
+        let db = RocksDB::new(db_path).with_context(|| {
+            format!(
+                "failed initializing RocksDB for storage values cache at `{}`",
+                db_path.display()
+            )
+        })?;
+        Ok(Self { db, max_entries })
+    }
+
+    /// Checks whether the number of cached values exceeds the configured bound. The number of values
+    /// is estimated by RocksDB, so the check is approximate.
+    pub fn is_full(&self) -> bool {
+        self.db
+            .estimated_number_of_entries(ValuesCacheColumnFamily::Values)
+            > self.max_entries
+    }
+
+    /// Returns the miniblock number and hash the cache is valid for, or `None` if the cache
+    /// was never updated.
+    pub fn valid_for(&self) -> anyhow::Result<Option<(MiniblockNumber, H256)>> {
+        let raw_value = self
+            .db
+            .get_cf(ValuesCacheColumnFamily::Meta, VALID_FOR_KEY)
+            .context("failed reading `valid_for` from RocksDB")?;
+        let Some(raw_value) = raw_value else {
+            return Ok(None);
+        };
+        anyhow::ensure!(
+            raw_value.len() == 36,
+            "unexpected `valid_for` length: {}",
+            raw_value.len()
+        );
+        let number = u32::from_le_bytes(raw_value[..4].try_into().unwrap());
+        let hash = H256::from_slice(&raw_value[4..]);
+        Ok(Some((MiniblockNumber(number), hash)))
+    }
+
+    pub fn get(&self, hashed_key: &H256) -> anyhow::Result<Option<TimestampedStorageValue>> {
+        let raw_value = self
+            .db
+            .get_cf(ValuesCacheColumnFamily::Values, hashed_key.as_bytes())
+            .context("failed reading value from RocksDB")?;
+        raw_value.as_deref().map(deserialize_value).transpose()
+    }
+
+    /// Atomically removes the specified keys from the cache, inserts `new_values` and marks the cache as valid
+    /// for the specified miniblock. `new_values` must be valid for this miniblock (i.e., must not be modified
+    /// by any of the miniblocks the cache is moved through).
+    pub fn update(
+        &self,
+        modified_keys: &[H256],
+        new_values: &[(H256, TimestampedStorageValue)],
+        to_miniblock: MiniblockNumber,
+        to_miniblock_hash: H256,
+    ) -> anyhow::Result<()> {
+        let mut batch = self.db.new_write_batch();
+        for key in modified_keys {
+            batch.delete_cf(ValuesCacheColumnFamily::Values, key.as_bytes());
+        }
+        for (hashed_key, value) in new_values {
+            batch.put_cf(
+                ValuesCacheColumnFamily::Values,
+                hashed_key.as_bytes(),
+                &serialize_value(value),
+            );
+        }
+        Self::put_valid_for(&mut batch, to_miniblock, to_miniblock_hash);
+        self.db
+            .write(batch)
+            .context("failed updating storage values cache in RocksDB")
+    }
+
+    /// Atomically removes all values from the cache and marks it as valid for the specified miniblock.
+    pub fn reset(
+        &self,
+        to_miniblock: MiniblockNumber,
+        to_miniblock_hash: H256,
+    ) -> anyhow::Result<()> {
+        let mut batch = self.db.new_write_batch();
+        batch.delete_range_cf(ValuesCacheColumnFamily::Values, all_values_range());
+        Self::put_valid_for(&mut batch, to_miniblock, to_miniblock_hash);
+        self.db
+            .write(batch)
+            .context("failed resetting storage values cache in RocksDB")
+    }
+
+    /// Removes all data from the cache, including the miniblock it is valid for.
+    pub fn clear(&self) -> anyhow::Result<()> {
+        let mut batch = self.db.new_write_batch();
+        batch.delete_range_cf(ValuesCacheColumnFamily::Values, all_values_range());
+        batch.delete_cf(ValuesCacheColumnFamily::Meta, VALID_FOR_KEY);
+        self.db
+            .write(batch)
+            .context("failed clearing storage values cache in RocksDB")
+    }
+
+    fn put_valid_for(
+        batch: &mut WriteBatch<'_, ValuesCacheColumnFamily>,
+        miniblock: MiniblockNumber,
+        miniblock_hash: H256,
+    ) {
+        let mut raw_value = [0_u8; 36];
+        raw_value[..4].copy_from_slice(&miniblock.0.to_le_bytes());
+        raw_value[4..].copy_from_slice(miniblock_hash.as_bytes());
+        batch.put_cf(ValuesCacheColumnFamily::Meta, VALID_FOR_KEY, &raw_value);
+    }
+}
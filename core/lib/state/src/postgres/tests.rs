@@ -7,11 +7,18 @@ use rand::{
     seq::{IteratorRandom, SliceRandom},
     Rng, SeedableRng,
 };
+use tempfile::TempDir;
 use zksync_dal::ConnectionPool;
 use zksync_types::StorageLog;
 
 use super::*;
-use crate::test_utils::{create_l1_batch, create_miniblock, gen_storage_logs, prepare_postgres};
+use crate::{
+    cache::bytecode_cache::{CachedBytecode, InsertedAt},
+    test_utils::{
+        create_l1_batch, create_miniblock, gen_storage_logs, prepare_postgres,
+        prepare_postgres_for_snapshot_recovery,
+    },
+};
 
 fn test_postgres_storage_basics(
     pool: &ConnectionPool<Core>,
@@ -254,22 +261,22 @@ fn test_factory_deps_cache(pool: &ConnectionPool<Core>, rt_handle: Handle) {
 
     // Fill the cache
     let dep = storage.load_factory_dep(zero_addr);
-    assert_eq!(dep, Some(vec![1, 2, 3]));
+    assert_eq!(dep, Some(vec![1, 2, 3].into()));
     assert_eq!(
         caches.factory_deps.get(&zero_addr),
-        Some(TimestampedFactoryDep {
-            bytecode: vec![1, 2, 3],
-            inserted_at: MiniblockNumber(0)
+        Some(CachedBytecode {
+            bytecode: vec![1, 2, 3].into(),
+            inserted_at: InsertedAt::Exact(MiniblockNumber(0))
         })
     );
 
     let dep = storage.load_factory_dep(H256::from_low_u64_be(1));
-    assert_eq!(dep, Some(vec![1, 2, 3, 4]));
+    assert_eq!(dep, Some(vec![1, 2, 3, 4].into()));
     assert_eq!(
         caches.factory_deps.get(&H256::from_low_u64_be(1)),
-        Some(TimestampedFactoryDep {
-            bytecode: vec![1, 2, 3, 4],
-            inserted_at: MiniblockNumber(1)
+        Some(CachedBytecode {
+            bytecode: vec![1, 2, 3, 4].into(),
+            inserted_at: InsertedAt::Exact(MiniblockNumber(1))
         })
     );
 
@@ -284,7 +291,7 @@ fn test_factory_deps_cache(pool: &ConnectionPool<Core>, rt_handle: Handle) {
 
     // First bytecode was published at miniblock 0, so it should be visible.
     let dep = storage.load_factory_dep(zero_addr);
-    assert_eq!(dep, Some(vec![1, 2, 3]));
+    assert_eq!(dep, Some(vec![1, 2, 3].into()));
 
     // Second bytecode was published at miniblock 1, so it shouldn't be visible.
     let dep = storage.load_factory_dep(H256::from_low_u64_be(1));
@@ -406,6 +413,113 @@ async fn using_initial_writes_cache() {
         .unwrap();
 }
 
+#[tokio::test]
+async fn prefetching_values() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut connection = pool.connection().await.unwrap();
+    prepare_postgres(&mut connection).await;
+    let logs = gen_storage_logs(0..20);
+    let non_existing_key = gen_storage_logs(100..101)[0].key;
+    let new_logs = vec![StorageLog::new_write_log(logs[0].key, H256::repeat_byte(1))];
+    create_miniblock(&mut connection, MiniblockNumber(1), new_logs).await;
+
+    let mut storage =
+        PostgresStorage::new_async(Handle::current(), connection, MiniblockNumber(0), false)
+            .await
+            .unwrap();
+    let keys: Vec<_> = logs[..5]
+        .iter()
+        .map(|log| log.key)
+        .chain([non_existing_key])
+        .collect();
+    storage.prefetch_values(&keys).await.unwrap();
+    assert_eq!(storage.prefetched_values.len(), keys.len());
+
+    // Reads of prefetched values must not access Postgres (which would panic since we're in an async context).
+    for log in &logs[..5] {
+        assert_eq!(storage.read_value(&log.key), log.value);
+    }
+    assert_eq!(storage.read_value(&non_existing_key), H256::zero());
+
+    // Check that cached values are not prefetched.
+    let mut caches = PostgresStorageCaches::new(1_024, 1_024);
+    let _ = caches.configure_storage_values_cache(1_024 * 1_024, pool.clone());
+    let values_cache = caches.values.as_ref().unwrap().cache.clone();
+    values_cache.insert(MiniblockNumber(0), logs[0].key, logs[0].value);
+
+    let connection = pool.connection().await.unwrap();
+    let mut storage =
+        PostgresStorage::new_async(Handle::current(), connection, MiniblockNumber(0), false)
+            .await
+            .unwrap()
+            .with_caches(caches);
+    storage.prefetch_values(&keys).await.unwrap();
+    assert_eq!(storage.prefetched_values.len(), keys.len() - 1);
+    assert!(!storage
+        .prefetched_values
+        .contains_key(&logs[0].key.hashed_key()));
+    // Prefetched values must be inserted into the values cache.
+    values_cache
+        .assertions(MiniblockNumber(0))
+        .assert_entries(&[(logs[1].key, Some(logs[1].value))]);
+}
+
+#[tokio::test]
+async fn warming_up_caches() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut connection = pool.connection().await.unwrap();
+    prepare_postgres(&mut connection).await;
+    let logs = gen_storage_logs(20..25);
+    let address = *logs[0].key.address();
+    let bytecode_hash = H256::repeat_byte(0x23);
+    let code_log = StorageLog::new_write_log(get_code_key(&address), bytecode_hash);
+    let new_logs = logs.iter().copied().chain([code_log]).collect();
+    create_miniblock(&mut connection, MiniblockNumber(1), new_logs).await;
+    let contracts = HashMap::from([(bytecode_hash, vec![1, 2, 3, 4])]);
+    connection
+        .factory_deps_dal()
+        .insert_factory_deps(MiniblockNumber(1), &contracts)
+        .await
+        .unwrap();
+
+    let mut caches = PostgresStorageCaches::new(1_024 * 1_024, 1_024);
+    let _ = caches.configure_storage_values_cache(1_024 * 1_024, pool.clone());
+    caches.warm_up(&mut connection, 1).await.unwrap();
+
+    let values_cache = &caches.values.as_ref().unwrap().cache;
+    assert_eq!(values_cache.valid_for(), MiniblockNumber(1));
+    let expected_entries: Vec<_> = logs
+        .iter()
+        .chain([&code_log])
+        .map(|log| (log.key, Some(log.value)))
+        .collect();
+    values_cache
+        .assertions(MiniblockNumber(1))
+        .assert_entries(&expected_entries);
+    // Values loaded at miniblock #1 must not be used for earlier miniblocks.
+    values_cache
+        .assertions(MiniblockNumber(0))
+        .assert_entries(&[(logs[0].key, None)]);
+
+    let factory_dep = caches.factory_deps.get(&bytecode_hash).unwrap();
+    assert_eq!(factory_dep.bytecode[..], [1, 2, 3, 4]);
+    assert_eq!(
+        factory_dep.inserted_at,
+        InsertedAt::Exact(MiniblockNumber(1))
+    );
+
+    // A cache that is ahead of the latest sealed miniblock must not be warmed up.
+    let mut caches = PostgresStorageCaches::new(1_024 * 1_024, 1_024);
+    let _ = caches.configure_storage_values_cache(1_024 * 1_024, pool.clone());
+    let values_cache = caches.values.as_ref().unwrap().cache.clone();
+    values_cache.0.write().unwrap().valid_for = MiniblockNumber(2);
+    caches.warm_up(&mut connection, 1).await.unwrap();
+    assert_eq!(values_cache.valid_for(), MiniblockNumber(2));
+    values_cache
+        .assertions(MiniblockNumber(2))
+        .assert_entries(&[(logs[0].key, None)]);
+}
+
 #[derive(Debug)]
 struct ValueCacheAssertions<'a> {
     cache: &'a ValuesCache,
@@ -571,6 +685,581 @@ async fn using_values_cache() {
         .unwrap();
 }
 
+fn test_caching_absent_values(pool: &ConnectionPool<Core>, rt_handle: Handle) {
+    let mut caches = PostgresStorageCaches::new(1_024, 1_024);
+    let task = caches.configure_storage_values_cache(1_024 * 1_024, pool.clone());
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let update_task_handle = tokio::task::spawn(task.run(stop_receiver));
+    let values_cache = caches.values.as_ref().unwrap().cache.clone();
+
+    let mut connection = rt_handle.block_on(pool.connection()).unwrap();
+    rt_handle.block_on(prepare_postgres(&mut connection));
+    let existing_key = gen_storage_logs(0..20)[1].key;
+    let absent_key = gen_storage_logs(100..120)[0].key;
+    // Zero out the existing key, so that it has the same value as the absent key.
+    let logs = vec![StorageLog::new_write_log(existing_key, H256::zero())];
+    rt_handle.block_on(create_miniblock(&mut connection, MiniblockNumber(1), logs));
+    caches.schedule_values_update(MiniblockNumber(1));
+    rt_handle.block_on(wait_for_cache_update(&values_cache, MiniblockNumber(1)));
+
+    let mut storage = PostgresStorage::new(rt_handle, connection, MiniblockNumber(1), true)
+        .with_caches(caches.clone());
+    assert_eq!(storage.read_value(&existing_key), H256::zero());
+    assert_eq!(storage.read_value(&absent_key), H256::zero());
+
+    values_cache
+        .assertions(MiniblockNumber(1))
+        .assert_entries(&[
+            (existing_key, Some(H256::zero())),
+            (absent_key, Some(H256::zero())),
+        ]);
+    // The absent key is known to be absent for earlier miniblocks as well, unlike the zeroed key.
+    values_cache
+        .assertions(MiniblockNumber(0))
+        .assert_entries(&[(existing_key, None), (absent_key, Some(H256::zero()))]);
+
+    // Writing to the absent key must invalidate the cached entry.
+    let logs = vec![StorageLog::new_write_log(absent_key, H256::repeat_byte(1))];
+    storage.rt_handle.block_on(create_miniblock(
+        &mut storage.connection,
+        MiniblockNumber(2),
+        logs,
+    ));
+    caches.schedule_values_update(MiniblockNumber(2));
+    storage
+        .rt_handle
+        .block_on(wait_for_cache_update(&values_cache, MiniblockNumber(2)));
+    for miniblock_number in [0, 1, 2] {
+        values_cache
+            .assertions(MiniblockNumber(miniblock_number))
+            .assert_entries(&[(absent_key, None)]);
+    }
+
+    let mut storage = PostgresStorage::new(
+        storage.rt_handle,
+        storage.connection,
+        MiniblockNumber(2),
+        true,
+    )
+    .with_caches(caches);
+    assert_eq!(storage.read_value(&absent_key), H256::repeat_byte(1));
+    values_cache
+        .assertions(MiniblockNumber(2))
+        .assert_entries(&[(absent_key, Some(H256::repeat_byte(1)))]);
+
+    stop_sender.send_replace(true);
+    storage
+        .rt_handle
+        .block_on(update_task_handle)
+        .expect("update task panicked")
+        .unwrap();
+}
+
+#[tokio::test]
+async fn caching_absent_values() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let handle = Handle::current();
+    tokio::task::spawn_blocking(move || test_caching_absent_values(&pool, handle))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn using_persistent_values_cache() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let temp_dir = TempDir::new().expect("failed creating temporary dir for RocksDB");
+    let mut connection = pool.connection().await.unwrap();
+    prepare_postgres(&mut connection).await;
+
+    let logs = gen_storage_logs(0..20);
+    let (modified_key, unmodified_key) = (logs[0].key, logs[1].key);
+    let values_cache = ValuesCache::new(1_024 * 1_024);
+    let persistent = PersistentValuesCache::new(temp_dir.path(), u64::MAX).unwrap();
+    values_cache
+        .attach_persistent_tier(persistent, &mut connection)
+        .await
+        .unwrap();
+    assert_eq!(values_cache.valid_for(), MiniblockNumber(0));
+    values_cache.insert(MiniblockNumber(0), modified_key, logs[0].value);
+    values_cache.insert(MiniblockNumber(0), unmodified_key, logs[1].value);
+
+    let new_logs = vec![StorageLog::new_write_log(
+        modified_key,
+        H256::repeat_byte(1),
+    )];
+    create_miniblock(&mut connection, MiniblockNumber(1), new_logs).await;
+    values_cache
+        .update(MiniblockNumber(0), MiniblockNumber(1), &mut connection)
+        .await
+        .unwrap();
+    drop(values_cache);
+
+    // Emulate a process restart; the persisted values should be restored.
+    let values_cache = ValuesCache::new(1_024 * 1_024);
+    let persistent = PersistentValuesCache::new(temp_dir.path(), u64::MAX).unwrap();
+    values_cache
+        .attach_persistent_tier(persistent.clone(), &mut connection)
+        .await
+        .unwrap();
+    assert_eq!(values_cache.valid_for(), MiniblockNumber(1));
+    values_cache
+        .assertions(MiniblockNumber(1))
+        .assert_entries(&[(modified_key, None), (unmodified_key, Some(logs[1].value))]);
+    // The restored value should be promoted to the in-memory tier.
+    let in_memory_value = values_cache
+        .0
+        .read()
+        .unwrap()
+        .values
+        .get(&unmodified_key.hashed_key());
+    assert_eq!(in_memory_value.unwrap().value, logs[1].value);
+    drop(values_cache);
+
+    // Emulate miniblock #1 being reverted and replaced with another miniblock.
+    persistent
+        .update(&[], &[], MiniblockNumber(1), H256::repeat_byte(0xff))
+        .unwrap();
+    let values_cache = ValuesCache::new(1_024 * 1_024);
+    values_cache
+        .attach_persistent_tier(persistent.clone(), &mut connection)
+        .await
+        .unwrap();
+    assert_eq!(values_cache.valid_for(), MiniblockNumber(0));
+    values_cache
+        .assertions(MiniblockNumber(0))
+        .assert_entries(&[(unmodified_key, None)]);
+    assert_eq!(persistent.valid_for().unwrap(), None);
+}
+
+#[tokio::test]
+async fn persistent_values_cache_write_behind_and_bound() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let temp_dir = TempDir::new().expect("failed creating temporary dir for RocksDB");
+    let mut connection = pool.connection().await.unwrap();
+    prepare_postgres(&mut connection).await;
+
+    let logs = gen_storage_logs(0..20);
+    let values_cache = ValuesCache::new(1_024 * 1_024);
+    let persistent = PersistentValuesCache::new(temp_dir.path(), 5).unwrap();
+    values_cache
+        .attach_persistent_tier(persistent.clone(), &mut connection)
+        .await
+        .unwrap();
+    for log in &logs[..10] {
+        values_cache.insert(MiniblockNumber(0), log.key, log.value);
+    }
+    // Values should only be written to the persistent tier on the cache update.
+    let first_hashed_key = logs[0].key.hashed_key();
+    assert!(persistent.get(&first_hashed_key).unwrap().is_none());
+
+    create_miniblock(&mut connection, MiniblockNumber(1), vec![]).await;
+    values_cache
+        .update(MiniblockNumber(0), MiniblockNumber(1), &mut connection)
+        .await
+        .unwrap();
+    let persisted_value = persistent.get(&first_hashed_key).unwrap().unwrap();
+    assert_eq!(persisted_value.value, logs[0].value);
+    assert!(persistent.is_full());
+
+    for log in &logs[10..] {
+        values_cache.insert(MiniblockNumber(1), log.key, log.value);
+    }
+    create_miniblock(&mut connection, MiniblockNumber(2), vec![]).await;
+    values_cache
+        .update(MiniblockNumber(1), MiniblockNumber(2), &mut connection)
+        .await
+        .unwrap();
+
+    // The persistent tier has exceeded its bound, so it should be emptied.
+    for log in &logs {
+        assert!(persistent.get(&log.key.hashed_key()).unwrap().is_none());
+    }
+    let (valid_for, _) = persistent.valid_for().unwrap().unwrap();
+    assert_eq!(valid_for, MiniblockNumber(2));
+    // The in-memory tier should be unaffected.
+    values_cache
+        .assertions(MiniblockNumber(2))
+        .assert_entries(&[(logs[0].key, Some(logs[0].value))]);
+}
+
+#[tokio::test]
+async fn invalidating_caches_after_revert() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut connection = pool.connection().await.unwrap();
+    prepare_postgres(&mut connection).await;
+
+    let mut caches = PostgresStorageCaches::new(1_024, 1_024);
+    let _task = caches.configure_storage_values_cache(1_024 * 1_024, pool.clone());
+    let values_cache = caches.values.as_ref().unwrap().cache.clone();
+
+    let logs = gen_storage_logs(0..20);
+    let (old_key, new_key) = (logs[0].key, logs[1].key);
+    values_cache.insert(MiniblockNumber(0), old_key, logs[0].value);
+    create_miniblock(&mut connection, MiniblockNumber(1), vec![]).await;
+    values_cache
+        .update(MiniblockNumber(0), MiniblockNumber(1), &mut connection)
+        .await
+        .unwrap();
+    values_cache.insert(MiniblockNumber(1), new_key, logs[1].value);
+
+    caches.initial_writes.insert(old_key, L1BatchNumber(0));
+    caches.initial_writes.insert(new_key, L1BatchNumber(1));
+    caches
+        .negative_initial_writes
+        .insert(old_key, L1BatchNumber(1));
+    caches
+        .negative_initial_writes
+        .insert(new_key, L1BatchNumber(2));
+    let bytecode_hashes: Vec<_> = (1_u8..=3).map(H256::repeat_byte).collect();
+    caches
+        .factory_deps
+        .insert(bytecode_hashes[0], &[0; 32], Some(MiniblockNumber(0)));
+    caches
+        .factory_deps
+        .insert(bytecode_hashes[1], &[1; 32], Some(MiniblockNumber(1)));
+    caches
+        .factory_deps
+        .insert(bytecode_hashes[2], &[2; 32], None);
+
+    caches.invalidate(RevertedBlocks {
+        last_l1_batch_to_keep: L1BatchNumber(0),
+        last_miniblock_to_keep: MiniblockNumber(0),
+    });
+
+    assert_eq!(values_cache.valid_for(), MiniblockNumber(0));
+    values_cache
+        .assertions(MiniblockNumber(0))
+        .assert_entries(&[(old_key, Some(logs[0].value))]);
+    let new_value = values_cache
+        .0
+        .read()
+        .unwrap()
+        .values
+        .get(&new_key.hashed_key());
+    assert!(new_value.is_none(), "{new_value:?}");
+
+    assert_eq!(caches.initial_writes.get(&old_key), Some(L1BatchNumber(0)));
+    assert_eq!(caches.initial_writes.get(&new_key), None);
+    assert_eq!(
+        caches.negative_initial_writes.get(&old_key),
+        Some(L1BatchNumber(1))
+    );
+    assert_eq!(caches.negative_initial_writes.get(&new_key), None);
+    assert!(caches.factory_deps.get(&bytecode_hashes[0]).is_some());
+    assert!(caches.factory_deps.get(&bytecode_hashes[1]).is_none());
+    assert!(caches.factory_deps.get(&bytecode_hashes[2]).is_none());
+}
+
+#[test]
+fn flushing_caches() {
+    let caches = PostgresStorageCaches::new(1_024, 1_024);
+    let key = gen_storage_logs(0..1)[0].key;
+    caches.initial_writes.insert(key, L1BatchNumber(1));
+    assert_eq!(caches.initial_writes.get(&key), Some(L1BatchNumber(1)));
+
+    let stats = caches.stats();
+    let cache_names: Vec<_> = stats.iter().map(|stats| stats.name).collect();
+    assert_eq!(
+        cache_names,
+        [
+            "factory_deps_cache",
+            "initial_writes_cache",
+            "negative_initial_writes_cache"
+        ]
+    );
+    let initial_writes_stats = &stats[1];
+    assert_eq!(
+        (initial_writes_stats.hits, initial_writes_stats.misses),
+        (1, 0)
+    );
+
+    assert!(caches.flush("initial_writes_cache"));
+    assert_eq!(caches.initial_writes.get(&key), None);
+    // The values cache is not configured.
+    assert!(!caches.flush("values_cache"));
+    assert!(!caches.flush("unknown_cache"));
+}
+
+#[tokio::test]
+async fn resizing_caches() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut caches = PostgresStorageCaches::new(1_024, 0);
+    let key = gen_storage_logs(0..1)[0].key;
+    assert_eq!(caches.resize("values_cache", 1_024), None);
+    assert_eq!(caches.resize("unknown_cache", 1_024), None);
+
+    // Enable the initial writes cache that was disabled on creation.
+    assert_eq!(caches.resize("initial_writes_cache", 1_024), Some(0));
+    caches.initial_writes.insert(key, L1BatchNumber(1));
+    assert_eq!(caches.initial_writes.get(&key), Some(L1BatchNumber(1)));
+
+    let _task = caches.configure_storage_values_cache(1_024, pool);
+    let values_cache = caches.values.as_ref().unwrap().cache.clone();
+    values_cache.insert(MiniblockNumber(0), key, H256::repeat_byte(1));
+    assert_eq!(caches.resize("values_cache", 4_096), Some(1_024));
+    values_cache
+        .assertions(MiniblockNumber(0))
+        .assert_entries(&[(key, Some(H256::repeat_byte(1)))]);
+
+    assert_eq!(caches.resize("factory_deps_cache", 0), Some(1_024));
+    let cache_names: Vec<_> = caches.stats().iter().map(|stats| stats.name).collect();
+    assert_eq!(cache_names, ["initial_writes_cache", "values_cache"]);
+}
+
+async fn update_historical_state(
+    updater: &HistoricalStateUpdater,
+    connection: &mut Connection<'_, Core>,
+) {
+    while updater.step(connection).await.unwrap() {
+        // Keep processing miniblocks.
+    }
+}
+
+#[tokio::test]
+async fn using_historical_state() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let temp_dir = TempDir::new().expect("failed creating temporary dir for RocksDB");
+    let mut connection = pool.connection().await.unwrap();
+    prepare_postgres(&mut connection).await;
+
+    let logs = gen_storage_logs(0..20);
+    let (modified_key, unmodified_key) = (logs[0].key, logs[1].key);
+    let missing_key = gen_storage_logs(20..25)[0].key;
+    for number in 1_u8..=3 {
+        let new_logs = vec![StorageLog::new_write_log(
+            modified_key,
+            H256::repeat_byte(number),
+        )];
+        create_miniblock(&mut connection, MiniblockNumber(number.into()), new_logs).await;
+    }
+    let factory_deps = HashMap::from([(H256::repeat_byte(0xfe), vec![1, 2, 3])]);
+    connection
+        .factory_deps_dal()
+        .insert_factory_deps(MiniblockNumber(2), &factory_deps)
+        .await
+        .unwrap();
+
+    let state = HistoricalState::new(temp_dir.path()).unwrap();
+    let updater =
+        HistoricalStateUpdater::new(state.clone(), pool.clone()).with_miniblocks_chunk_size(2);
+    assert_eq!(state.latest_miniblock(), None);
+    assert_eq!(
+        state.read_value(&modified_key.hashed_key(), MiniblockNumber(0)),
+        None
+    );
+    update_historical_state(&updater, &mut connection).await;
+    assert_eq!(state.latest_miniblock(), Some(MiniblockNumber(3)));
+
+    let read = |key: &StorageKey, number: u32| {
+        state.read_value(&key.hashed_key(), MiniblockNumber(number))
+    };
+    assert_eq!(read(&modified_key, 0), Some(logs[0].value));
+    for number in 1_u8..=3 {
+        assert_eq!(
+            read(&modified_key, number.into()),
+            Some(H256::repeat_byte(number))
+        );
+        assert_eq!(read(&unmodified_key, number.into()), Some(logs[1].value));
+        assert_eq!(read(&missing_key, number.into()), Some(H256::zero()));
+    }
+    assert_eq!(read(&modified_key, 4), None);
+
+    let factory_dep_hash = H256::repeat_byte(0xfe);
+    assert_eq!(
+        state.load_factory_dep(factory_dep_hash, MiniblockNumber(1)),
+        Some(None)
+    );
+    assert_eq!(
+        state.load_factory_dep(factory_dep_hash, MiniblockNumber(2)),
+        Some(Some(vec![1, 2, 3].into()))
+    );
+    assert_eq!(
+        state.load_factory_dep(H256::zero(), MiniblockNumber(2)),
+        Some(None)
+    );
+
+    // Check that the state is used by `PostgresStorage`.
+    let caches = PostgresStorageCaches::new(1_024, 1_024).with_historical_state(state.clone());
+    let rt_handle = Handle::current();
+    let storage_connection = pool.connection().await.unwrap();
+    tokio::task::spawn_blocking(move || {
+        let mut storage =
+            PostgresStorage::new(rt_handle, storage_connection, MiniblockNumber(2), false)
+                .with_caches(caches);
+        assert_eq!(storage.read_value(&modified_key), H256::repeat_byte(2));
+        assert_eq!(storage.read_value(&missing_key), H256::zero());
+        assert_eq!(
+            storage.load_factory_dep(factory_dep_hash),
+            Some(vec![1, 2, 3].into())
+        );
+    })
+    .await
+    .unwrap();
+
+    // Emulate a process restart.
+    drop(updater);
+    drop(state);
+    let state = HistoricalState::new(temp_dir.path()).unwrap();
+    assert_eq!(state.latest_miniblock(), Some(MiniblockNumber(3)));
+    assert_eq!(
+        state.read_value(&modified_key.hashed_key(), MiniblockNumber(3)),
+        Some(H256::repeat_byte(3))
+    );
+
+    // Emulate reverting miniblocks #2 and #3.
+    connection
+        .storage_logs_dal()
+        .rollback_storage_logs(MiniblockNumber(1))
+        .await
+        .unwrap();
+    connection
+        .blocks_dal()
+        .delete_miniblocks(MiniblockNumber(1))
+        .await
+        .unwrap();
+    connection
+        .factory_deps_dal()
+        .rollback_factory_deps(MiniblockNumber(1))
+        .await
+        .unwrap();
+    let updater = HistoricalStateUpdater::new(state.clone(), pool.clone());
+    update_historical_state(&updater, &mut connection).await;
+    assert_eq!(state.latest_miniblock(), Some(MiniblockNumber(1)));
+    assert_eq!(
+        state.read_value(&modified_key.hashed_key(), MiniblockNumber(1)),
+        Some(H256::repeat_byte(1))
+    );
+    assert_eq!(
+        state.read_value(&modified_key.hashed_key(), MiniblockNumber(2)),
+        None
+    );
+
+    let new_logs = vec![StorageLog::new_write_log(
+        modified_key,
+        H256::repeat_byte(0xff),
+    )];
+    create_miniblock(&mut connection, MiniblockNumber(2), new_logs).await;
+    update_historical_state(&updater, &mut connection).await;
+    assert_eq!(state.latest_miniblock(), Some(MiniblockNumber(2)));
+    assert_eq!(
+        state.read_value(&modified_key.hashed_key(), MiniblockNumber(2)),
+        Some(H256::repeat_byte(0xff))
+    );
+    assert_eq!(
+        state.load_factory_dep(factory_dep_hash, MiniblockNumber(2)),
+        Some(None)
+    );
+}
+
+#[tokio::test]
+async fn using_historical_state_after_snapshot_recovery() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let temp_dir = TempDir::new().expect("failed creating temporary dir for RocksDB");
+    let mut connection = pool.connection().await.unwrap();
+    let (snapshot_recovery, snapshot_logs) =
+        prepare_postgres_for_snapshot_recovery(&mut connection).await;
+    let (modified_key, unmodified_key) = (snapshot_logs[0].key, snapshot_logs[1].key);
+    let next_miniblock = snapshot_recovery.miniblock_number + 1;
+    let new_logs = vec![StorageLog::new_write_log(
+        modified_key,
+        H256::repeat_byte(1),
+    )];
+    create_miniblock(&mut connection, next_miniblock, new_logs).await;
+
+    let state = HistoricalState::new(temp_dir.path()).unwrap();
+    let updater = HistoricalStateUpdater::new(state.clone(), pool.clone());
+    update_historical_state(&updater, &mut connection).await;
+    assert_eq!(state.latest_miniblock(), Some(next_miniblock));
+
+    assert_eq!(
+        state.read_value(&modified_key.hashed_key(), next_miniblock),
+        Some(H256::repeat_byte(1))
+    );
+    // The snapshot miniblock isn't covered by the state.
+    assert_eq!(
+        state.read_value(
+            &modified_key.hashed_key(),
+            snapshot_recovery.miniblock_number
+        ),
+        None
+    );
+    // The state is incomplete, so values of the unmodified keys should be read from Postgres.
+    assert_eq!(
+        state.read_value(&unmodified_key.hashed_key(), next_miniblock),
+        None
+    );
+}
+
+#[tokio::test]
+async fn pruning_and_reverting_historical_state() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let temp_dir = TempDir::new().expect("failed creating temporary dir for RocksDB");
+    let mut connection = pool.connection().await.unwrap();
+    prepare_postgres(&mut connection).await;
+
+    let logs = gen_storage_logs(0..20);
+    let (modified_key, unmodified_key) = (logs[0].key, logs[1].key);
+    for number in 1_u8..=5 {
+        let new_logs = vec![StorageLog::new_write_log(
+            modified_key,
+            H256::repeat_byte(number),
+        )];
+        create_miniblock(&mut connection, MiniblockNumber(number.into()), new_logs).await;
+    }
+
+    let state = HistoricalState::new(temp_dir.path()).unwrap();
+    let updater = HistoricalStateUpdater::new(state.clone(), pool.clone())
+        .with_miniblocks_chunk_size(2)
+        .with_retained_miniblocks(Some(2));
+    update_historical_state(&updater, &mut connection).await;
+    assert_eq!(state.latest_miniblock(), Some(MiniblockNumber(5)));
+
+    let read = |key: &StorageKey, number: u32| {
+        state.read_value(&key.hashed_key(), MiniblockNumber(number))
+    };
+    for number in 0..=3 {
+        assert_eq!(read(&modified_key, number), None);
+    }
+    for number in 4_u8..=5 {
+        assert_eq!(
+            read(&modified_key, number.into()),
+            Some(H256::repeat_byte(number))
+        );
+        // The latest version of the key as of the first retained miniblock must survive pruning.
+        assert_eq!(read(&unmodified_key, number.into()), Some(logs[1].value));
+    }
+
+    // Pruning must persist across restarts.
+    drop(updater);
+    drop(state);
+    let state = HistoricalState::new(temp_dir.path()).unwrap();
+    assert_eq!(
+        state.read_value(&modified_key.hashed_key(), MiniblockNumber(3)),
+        None
+    );
+    assert_eq!(
+        state.read_value(&modified_key.hashed_key(), MiniblockNumber(4)),
+        Some(H256::repeat_byte(4))
+    );
+
+    // Reverts must be applied immediately, without waiting for the updater.
+    state.invalidate(RevertedBlocks {
+        last_l1_batch_to_keep: L1BatchNumber(0),
+        last_miniblock_to_keep: MiniblockNumber(4),
+    });
+    assert_eq!(state.latest_miniblock(), Some(MiniblockNumber(4)));
+    assert_eq!(
+        state.read_value(&modified_key.hashed_key(), MiniblockNumber(5)),
+        None
+    );
+    // Reverting past the first retained miniblock clears the state.
+    state.invalidate(RevertedBlocks {
+        last_l1_batch_to_keep: L1BatchNumber(0),
+        last_miniblock_to_keep: MiniblockNumber(3),
+    });
+    assert_eq!(state.latest_miniblock(), None);
+}
+
 /// (Sort of) fuzzes [`ValuesCache`] by comparing outputs of [`PostgresStorage`] with and without caching
 /// on randomly generated `read_value()` queries.
 fn mini_fuzz_values_cache_inner(
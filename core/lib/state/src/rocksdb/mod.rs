@@ -24,6 +24,7 @@ use std::{
     convert::TryInto,
     mem,
     path::{Path, PathBuf},
+    sync::Arc,
     time::Instant,
 };
 
@@ -32,13 +33,16 @@ use itertools::{Either, Itertools};
 use tokio::sync::watch;
 use zksync_dal::{Connection, Core, CoreDal, DalError};
 use zksync_storage::{db::NamedColumnFamily, RocksDB};
-use zksync_types::{L1BatchNumber, StorageKey, StorageValue, H256, U256};
+use zksync_types::{L1BatchNumber, MiniblockNumber, StorageKey, StorageValue, H256, U256};
 use zksync_utils::{h256_to_u256, u256_to_h256};
 
 use self::metrics::METRICS;
 #[cfg(test)]
 use self::tests::RocksdbStorageEventListener;
-use crate::{InMemoryStorage, ReadStorage};
+use crate::{
+    cache::bytecode_cache::{BytecodeCache, InsertedAt},
+    InMemoryStorage, ReadStorage,
+};
 
 mod metrics;
 mod recovery;
@@ -132,6 +136,10 @@ pub struct RocksdbStorage {
     db: RocksDB<StateKeeperColumnFamily>,
     pending_patch: InMemoryStorage,
     enum_index_migration_chunk_size: usize,
+    bytecode_cache: Option<BytecodeCache>,
+    /// Last miniblock of the last L1 batch processed by this storage. All bytecodes in the storage were inserted
+    /// no later than this miniblock; this information is recorded in the bytecode cache.
+    last_processed_miniblock: Option<MiniblockNumber>,
     /// Test-only listeners to events produced by the storage.
     #[cfg(test)]
     listener: RocksdbStorageEventListener,
@@ -149,6 +157,8 @@ impl RocksdbStorageBuilder {
             db: value,
             pending_patch: InMemoryStorage::default(),
             enum_index_migration_chunk_size: 100,
+            bytecode_cache: None,
+            last_processed_miniblock: None,
             #[cfg(test)]
             listener: RocksdbStorageEventListener::default(),
         })
@@ -159,6 +169,12 @@ impl RocksdbStorageBuilder {
         self.0.enum_index_migration_chunk_size = chunk_size;
     }
 
+    /// Sets the bytecode cache consulted before reading factory deps from RocksDB. The cache can be shared
+    /// with other components.
+    pub fn set_bytecode_cache(&mut self, bytecode_cache: BytecodeCache) {
+        self.0.bytecode_cache = Some(bytecode_cache);
+    }
+
     /// Returns the last processed l1 batch number + 1.
     ///
     /// # Panics
@@ -235,6 +251,8 @@ impl RocksdbStorage {
                 db: RocksDB::new(&path).context("failed initializing state keeper RocksDB")?,
                 pending_patch: InMemoryStorage::default(),
                 enum_index_migration_chunk_size: 100,
+                bytecode_cache: None,
+                last_processed_miniblock: None,
                 #[cfg(test)]
                 listener: RocksdbStorageEventListener::default(),
             })
@@ -305,6 +323,15 @@ impl RocksdbStorage {
             (self.listener.on_l1_batch_synced)(current_l1_batch_number - 1);
         }
 
+        if self.bytecode_cache.is_some() {
+            self.last_processed_miniblock = storage
+                .blocks_dal()
+                .get_miniblock_range_of_l1_batch(latest_l1_batch_number)
+                .await
+                .map_err(DalError::generalize)?
+                .map(|(_, last_miniblock)| last_miniblock);
+        }
+
         latency.observe();
         METRICS.lag.set(0);
         let estimated_size = self.estimated_map_size();
@@ -662,11 +689,28 @@ impl ReadStorage for RocksdbStorage {
         self.read_value_inner(key).is_none()
     }
 
-    fn load_factory_dep(&mut self, hash: H256) -> Option<Vec<u8>> {
+    fn load_factory_dep(&mut self, hash: H256) -> Option<Arc<[u8]>> {
+        // Unlike Postgres storage, we don't need to check when the cached bytecode was inserted:
+        // the VM only loads bytecodes referenced by the current state.
+        if let Some(cached) = self
+            .bytecode_cache
+            .as_ref()
+            .and_then(|cache| cache.get(&hash))
+        {
+            return Some(cached.bytecode);
+        }
+
         let cf = StateKeeperColumnFamily::FactoryDeps;
-        self.db
+        let bytecode = self
+            .db
             .get_cf(cf, hash.as_bytes())
-            .expect("failed to read RocksDB state value")
+            .expect("failed to read RocksDB state value")?;
+        let bytecode: Arc<[u8]> = bytecode.into();
+        if let Some(cache) = &self.bytecode_cache {
+            let inserted_at = InsertedAt::for_upper_bound(self.last_processed_miniblock);
+            cache.insert(hash, bytecode.clone(), inserted_at);
+        }
+        Some(bytecode)
     }
 
     fn get_enumeration_index(&mut self, key: &StorageKey) -> Option<u64> {
@@ -5,13 +5,17 @@ use std::fmt;
 use assert_matches::assert_matches;
 use tempfile::TempDir;
 use test_casing::test_casing;
+use tokio::runtime::Handle;
 use zksync_dal::{ConnectionPool, Core};
 use zksync_types::{MiniblockNumber, StorageLog};
 
 use super::*;
-use crate::test_utils::{
-    create_l1_batch, create_miniblock, gen_storage_logs, prepare_postgres,
-    prepare_postgres_for_snapshot_recovery,
+use crate::{
+    test_utils::{
+        create_l1_batch, create_miniblock, gen_storage_logs, prepare_postgres,
+        prepare_postgres_for_snapshot_recovery,
+    },
+    PostgresStorage,
 };
 
 pub(super) struct RocksdbStorageEventListener {
@@ -213,7 +217,7 @@ async fn rocksdb_storage_revert() {
 
         for i in 0..5 {
             assert_eq!(
-                storage.load_factory_dep(H256::repeat_byte(i)).unwrap(),
+                storage.load_factory_dep(H256::repeat_byte(i)).unwrap()[..],
                 [i; 64]
             );
         }
@@ -231,7 +235,7 @@ async fn rocksdb_storage_revert() {
 
         for i in 0..3 {
             assert_eq!(
-                storage.load_factory_dep(H256::repeat_byte(i)).unwrap(),
+                storage.load_factory_dep(H256::repeat_byte(i)).unwrap()[..],
                 [i; 64]
             );
         }
@@ -241,6 +245,79 @@ async fn rocksdb_storage_revert() {
     }
 }
 
+#[tokio::test]
+async fn bytecode_cache_is_shared_with_postgres_storage() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut conn = pool.connection().await.unwrap();
+    prepare_postgres(&mut conn).await;
+    let storage_logs = gen_storage_logs(20..40);
+    create_miniblock(&mut conn, MiniblockNumber(1), storage_logs[..10].to_vec()).await;
+    insert_factory_deps(&mut conn, MiniblockNumber(1), 0..1).await;
+    create_l1_batch(&mut conn, L1BatchNumber(1), &storage_logs[..10]).await;
+    create_miniblock(&mut conn, MiniblockNumber(2), storage_logs[10..].to_vec()).await;
+    insert_factory_deps(&mut conn, MiniblockNumber(2), 1..2).await;
+    create_l1_batch(&mut conn, L1BatchNumber(2), &storage_logs[10..]).await;
+
+    let bytecode_cache = BytecodeCache::new(1 << 20);
+    let dir = TempDir::new().expect("cannot create temporary dir for state keeper");
+    let (_stop_sender, stop_receiver) = watch::channel(false);
+    let mut builder = RocksdbStorage::builder(dir.path()).await.unwrap();
+    builder.set_bytecode_cache(bytecode_cache.clone());
+    let mut storage = builder
+        .synchronize(&mut conn, &stop_receiver)
+        .await
+        .unwrap()
+        .expect("Storage synchronization unexpectedly stopped");
+    drop(conn);
+
+    // The state keeper records an upper bound on the insertion miniblock for bytecodes loaded from RocksDB.
+    let hashes = [H256::repeat_byte(0), H256::repeat_byte(1)];
+    let bytecodes = hashes.map(|hash| storage.load_factory_dep(hash).unwrap());
+    for (hash, bytecode) in hashes.iter().zip(&bytecodes) {
+        let cached = bytecode_cache.get(hash).unwrap();
+        assert!(Arc::ptr_eq(&cached.bytecode, bytecode));
+        assert_eq!(
+            cached.inserted_at,
+            InsertedAt::NoLaterThan(MiniblockNumber(2))
+        );
+    }
+
+    let rt_handle = Handle::current();
+    let (api_bytecodes, bytecode_cache) = tokio::task::spawn_blocking(move || {
+        // The latest state can use bytecodes cached by the state keeper as is.
+        let connection = rt_handle.block_on(pool.connection()).unwrap();
+        let mut api_storage =
+            PostgresStorage::new(rt_handle.clone(), connection, MiniblockNumber(2), false)
+                .with_bytecode_cache(bytecode_cache.clone());
+        let api_bytecodes = hashes.map(|hash| api_storage.load_factory_dep(hash).unwrap());
+        drop(api_storage);
+
+        // An older state needs the exact insertion miniblocks, which are loaded from Postgres.
+        let connection = rt_handle.block_on(pool.connection()).unwrap();
+        let mut api_storage =
+            PostgresStorage::new(rt_handle, connection, MiniblockNumber(1), false)
+                .with_bytecode_cache(bytecode_cache.clone());
+        let old_bytecode = api_storage.load_factory_dep(hashes[0]).unwrap();
+        assert!(Arc::ptr_eq(&old_bytecode, &api_bytecodes[0]));
+        assert!(api_storage.load_factory_dep(hashes[1]).is_none());
+        (api_bytecodes, bytecode_cache)
+    })
+    .await
+    .unwrap();
+
+    // Bytecodes are stored once and retain the exact miniblocks after they are loaded from Postgres.
+    for (i, hash) in hashes.iter().enumerate() {
+        assert!(Arc::ptr_eq(&api_bytecodes[i], &bytecodes[i]));
+        let cached = bytecode_cache.get(hash).unwrap();
+        assert!(Arc::ptr_eq(&cached.bytecode, &bytecodes[i]));
+        let expected_miniblock = MiniblockNumber(i as u32 + 1);
+        assert_eq!(cached.inserted_at, InsertedAt::Exact(expected_miniblock));
+
+        let state_keeper_bytecode = storage.load_factory_dep(*hash).unwrap();
+        assert!(Arc::ptr_eq(&state_keeper_bytecode, &bytecodes[i]));
+    }
+}
+
 #[tokio::test]
 async fn rocksdb_enum_index_migration() {
     let pool = ConnectionPool::<Core>::test_pool().await;
@@ -381,7 +458,10 @@ async fn recovering_factory_deps_from_snapshot() {
     let mut storage = sync_test_storage(&dir, &mut conn).await;
 
     for (bytecode_hash, bytecode) in &all_factory_deps {
-        assert_eq!(storage.load_factory_dep(*bytecode_hash).unwrap(), *bytecode);
+        assert_eq!(
+            storage.load_factory_dep(*bytecode_hash).unwrap()[..],
+            bytecode[..]
+        );
     }
 }
 
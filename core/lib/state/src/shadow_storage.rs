@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use vise::{Counter, Metrics};
 use zksync_types::{L1BatchNumber, StorageKey, StorageValue, H256};
 
@@ -77,7 +79,7 @@ impl ReadStorage for ShadowStorage<'_> {
         source_value
     }
 
-    fn load_factory_dep(&mut self, hash: H256) -> Option<Vec<u8>> {
+    fn load_factory_dep(&mut self, hash: H256) -> Option<Arc<[u8]>> {
         let source_value = self.source_storage.load_factory_dep(hash);
         let expected_value = self.to_check_storage.load_factory_dep(hash);
         if source_value != expected_value {
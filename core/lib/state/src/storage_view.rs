@@ -3,6 +3,7 @@ use std::{
     collections::HashMap,
     fmt, mem,
     rc::Rc,
+    sync::Arc,
     time::{Duration, Instant},
 };
 
@@ -52,6 +53,27 @@ pub struct StorageView<S> {
     metrics: StorageViewMetrics,
 }
 
+/// Heap allocations backing a [`StorageView`] that can be reused by another view, so that executing
+/// many short-lived transactions (e.g., in the API sandbox) doesn't reallocate the view caches each time.
+///
+/// Buffers are obtained via [`StorageView::into_buffers()`] and are always empty; only the allocated capacity
+/// is retained.
+#[derive(Debug, Default)]
+pub struct StorageViewBuffers {
+    modified_storage_keys: HashMap<StorageKey, StorageValue>,
+    read_storage_keys: HashMap<StorageKey, StorageValue>,
+    initial_writes_cache: HashMap<StorageKey, bool>,
+}
+
+impl StorageViewBuffers {
+    /// Returns the total number of entries these buffers can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.modified_storage_keys.capacity()
+            + self.read_storage_keys.capacity()
+            + self.initial_writes_cache.capacity()
+    }
+}
+
 impl<S> StorageView<S> {
     /// Returns the block's start state using StorageView's in-memory cache for the run
     pub fn witness_block_state(&self) -> WitnessBlockState {
@@ -65,6 +87,20 @@ impl<S> StorageView<S> {
     pub fn modified_storage_keys(&self) -> &HashMap<StorageKey, StorageValue> {
         &self.modified_storage_keys
     }
+
+    /// Drops the underlying storage and returns cleared buffers of this view, which can be used
+    /// to [create](Self::with_buffers()) another view.
+    pub fn into_buffers(self) -> StorageViewBuffers {
+        let mut buffers = StorageViewBuffers {
+            modified_storage_keys: self.modified_storage_keys,
+            read_storage_keys: self.read_storage_keys,
+            initial_writes_cache: self.initial_writes_cache,
+        };
+        buffers.modified_storage_keys.clear();
+        buffers.read_storage_keys.clear();
+        buffers.initial_writes_cache.clear();
+        buffers
+    }
 }
 
 impl<S> ReadStorage for Box<S>
@@ -79,7 +115,7 @@ where
         (**self).is_write_initial(key)
     }
 
-    fn load_factory_dep(&mut self, hash: H256) -> Option<Vec<u8>> {
+    fn load_factory_dep(&mut self, hash: H256) -> Option<Arc<[u8]>> {
         (**self).load_factory_dep(hash)
     }
 
@@ -95,11 +131,16 @@ where
 impl<S: ReadStorage + fmt::Debug> StorageView<S> {
     /// Creates a new storage view based on the underlying storage.
     pub fn new(storage_handle: S) -> Self {
+        Self::with_buffers(storage_handle, StorageViewBuffers::default())
+    }
+
+    /// Creates a new storage view based on the underlying storage, reusing allocations from a previously used view.
+    pub fn with_buffers(storage_handle: S, buffers: StorageViewBuffers) -> Self {
         Self {
             storage_handle,
-            modified_storage_keys: HashMap::new(),
-            read_storage_keys: HashMap::new(),
-            initial_writes_cache: HashMap::new(),
+            modified_storage_keys: buffers.modified_storage_keys,
+            read_storage_keys: buffers.read_storage_keys,
+            initial_writes_cache: buffers.initial_writes_cache,
             metrics: StorageViewMetrics::default(),
         }
     }
@@ -170,7 +211,7 @@ impl<S: ReadStorage + fmt::Debug> ReadStorage for StorageView<S> {
         }
     }
 
-    fn load_factory_dep(&mut self, hash: H256) -> Option<Vec<u8>> {
+    fn load_factory_dep(&mut self, hash: H256) -> Option<Arc<[u8]>> {
         self.storage_handle.load_factory_dep(hash)
     }
 
@@ -260,4 +301,24 @@ mod test {
         assert_eq!(metrics.get_value_storage_invocations, 3);
         assert_eq!(metrics.set_value_storage_invocations, 2);
     }
+
+    #[test]
+    fn reusing_buffers() {
+        let account = AccountTreeId::new(Address::from([0xfe; 20]));
+        let key = StorageKey::new(account, H256::from_low_u64_be(61));
+        let value = H256::from_low_u64_be(73);
+
+        let raw_storage = InMemoryStorage::default();
+        let mut storage_view = StorageView::new(&raw_storage);
+        storage_view.set_value(key, value);
+        assert!(storage_view.is_write_initial(&key));
+
+        let buffers = storage_view.into_buffers();
+        assert!(buffers.capacity() > 0);
+
+        let mut storage_view = StorageView::with_buffers(&raw_storage, buffers);
+        assert!(storage_view.modified_storage_keys().is_empty());
+        assert_eq!(storage_view.read_value(&key), H256::zero());
+        assert_eq!(storage_view.metrics().storage_invocations_missed, 1);
+    }
 }
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use vise::{Counter, Metrics};
 use zksync_types::{witness_block_state::WitnessBlockState, StorageKey, StorageValue, H256};
 
@@ -45,7 +47,7 @@ impl ReadStorage for WitnessStorage<'_> {
         *self.block_state.is_write_initial.get(key).unwrap_or(&false)
     }
 
-    fn load_factory_dep(&mut self, _hash: H256) -> Option<Vec<u8>> {
+    fn load_factory_dep(&mut self, _hash: H256) -> Option<Arc<[u8]>> {
         None
     }
 
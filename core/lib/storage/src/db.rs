@@ -17,7 +17,8 @@ use std::{
 
 use rocksdb::{
     perf, properties, BlockBasedOptions, Cache, ColumnFamily, ColumnFamilyDescriptor,
-    DBPinnableSlice, Direction, IteratorMode, Options, PrefixRange, ReadOptions, WriteOptions, DB,
+    DBCompactionStyle, DBPinnableSlice, Direction, Env, IteratorMode, Options, PrefixRange,
+    ReadOptions, WriteOptions, DB,
 };
 use thread_local::ThreadLocal;
 
@@ -278,6 +279,21 @@ impl StalledWritesRetries {
     }
 }
 
+/// Compaction style used for large column families (as defined in [`NamedColumnFamily::requires_tuning()`]).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CompactionStyle {
+    /// Level-style compaction (the RocksDB default). Provides low space amplification at the cost
+    /// of higher write amplification.
+    #[default]
+    Level,
+    /// Universal compaction. Reduces write amplification (and thus the likelihood of write stalls
+    /// on heavy write loads) at the cost of higher space amplification.
+    ///
+    /// **Important.** Switching an existing DB from universal to level compaction requires a full
+    /// manual compaction; the reverse switch is safe.
+    Universal,
+}
+
 /// [`RocksDB`] options.
 #[derive(Debug, Clone, Copy)]
 pub struct RocksDBOptions {
@@ -298,6 +314,12 @@ pub struct RocksDBOptions {
     pub stalled_writes_retries: StalledWritesRetries,
     /// Number of open files that can be used by the DB. Default is None, for no limit.
     pub max_open_files: Option<NonZeroU32>,
+    /// Compaction style for large CFs. Other CFs always use the default (level) compaction.
+    pub large_cf_compaction_style: CompactionStyle,
+    /// If set, the database is kept entirely in RAM (using the RocksDB in-memory environment) instead of
+    /// the filesystem; the database path is only used as a name. All data is lost when the database is dropped.
+    /// Useful for tests and ephemeral nodes.
+    pub in_memory: bool,
 }
 
 impl Default for RocksDBOptions {
@@ -308,6 +330,8 @@ impl Default for RocksDBOptions {
             large_memtable_capacity: None,
             stalled_writes_retries: StalledWritesRetries::new(Duration::from_secs(10)),
             max_open_files: None,
+            large_cf_compaction_style: CompactionStyle::Level,
+            in_memory: false,
         }
     }
 }
@@ -330,21 +354,28 @@ impl<CF: NamedColumnFamily> RocksDB<CF> {
 
     pub fn with_options(path: &Path, options: RocksDBOptions) -> Result<Self, rocksdb::Error> {
         let caches = RocksDBCaches::new(options.block_cache_capacity);
-        let mut db_options = Self::rocksdb_options(None, None);
+        let mut db_options = Self::rocksdb_options(None, CompactionStyle::Level, None);
         let max_open_files = if let Some(non_zero) = options.max_open_files {
             i32::try_from(non_zero.get()).unwrap_or(i32::MAX)
         } else {
             -1
         };
         db_options.set_max_open_files(max_open_files);
-        let existing_cfs = DB::list_cf(&db_options, path).unwrap_or_else(|err| {
-            tracing::warn!(
-                "Failed getting column families for RocksDB `{}` at `{}`, assuming CFs are empty; {err}",
-                CF::DB_NAME,
-                path.display()
-            );
-            vec![]
-        });
+        if options.in_memory {
+            db_options.set_env(&Env::mem_env()?);
+        }
+        let existing_cfs = if options.in_memory {
+            vec![] // A newly created in-memory DB is always empty
+        } else {
+            DB::list_cf(&db_options, path).unwrap_or_else(|err| {
+                tracing::warn!(
+                    "Failed getting column families for RocksDB `{}` at `{}`, assuming CFs are empty; {err}",
+                    CF::DB_NAME,
+                    path.display()
+                );
+                vec![]
+            })
+        };
 
         let cfs_and_options: HashMap<_, _> = CF::ALL
             .iter()
@@ -387,7 +418,16 @@ impl<CF: NamedColumnFamily> RocksDB<CF> {
             }
 
             let memtable_capacity = options.large_memtable_capacity.filter(|_| requires_tuning);
-            let cf_options = Self::rocksdb_options(memtable_capacity, Some(block_based_options));
+            let compaction_style = if requires_tuning {
+                options.large_cf_compaction_style
+            } else {
+                CompactionStyle::Level
+            };
+            let cf_options = Self::rocksdb_options(
+                memtable_capacity,
+                compaction_style,
+                Some(block_based_options),
+            );
             ColumnFamilyDescriptor::new(cf_name, cf_options)
         });
 
@@ -426,6 +466,7 @@ impl<CF: NamedColumnFamily> RocksDB<CF> {
 
     fn rocksdb_options(
         memtable_capacity: Option<usize>,
+        compaction_style: CompactionStyle,
         block_based_options: Option<BlockBasedOptions>,
     ) -> Options {
         let mut options = Options::default();
@@ -434,8 +475,17 @@ impl<CF: NamedColumnFamily> RocksDB<CF> {
 
         let num_cpus = num_cpus::get() as i32;
         options.increase_parallelism(num_cpus);
-        if let Some(memtable_capacity) = memtable_capacity {
-            options.optimize_level_style_compaction(memtable_capacity);
+        match (compaction_style, memtable_capacity) {
+            (CompactionStyle::Level, Some(memtable_capacity)) => {
+                options.optimize_level_style_compaction(memtable_capacity);
+            }
+            (CompactionStyle::Level, None) => { /* use default options */ }
+            (CompactionStyle::Universal, Some(memtable_capacity)) => {
+                options.optimize_universal_style_compaction(memtable_capacity);
+            }
+            (CompactionStyle::Universal, None) => {
+                options.set_compaction_style(DBCompactionStyle::Universal);
+            }
         }
         // Settings below are taken as per PingCAP recommendations:
         // https://www.pingcap.com/blog/how-to-troubleshoot-rocksdb-write-stalls-in-tikv/
@@ -952,6 +1002,29 @@ mod tests {
         assert_eq!(value.unwrap(), b"value");
     }
 
+    #[test]
+    fn in_memory_db() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("db");
+        let options = RocksDBOptions {
+            in_memory: true,
+            ..RocksDBOptions::default()
+        };
+        let db = RocksDB::<NewColumnFamilies>::with_options(&path, options).unwrap();
+        let mut batch = db.new_write_batch();
+        batch.put_cf(NewColumnFamilies::Other, b"test", b"value");
+        db.write(batch).unwrap();
+        let value = db.get_cf(NewColumnFamilies::Other, b"test").unwrap();
+        assert_eq!(value.unwrap(), b"value");
+        assert!(!path.exists());
+        drop(db);
+
+        // Data is not persisted across DB instances.
+        let db = RocksDB::<NewColumnFamilies>::with_options(&path, options).unwrap();
+        let value = db.get_cf(NewColumnFamilies::Other, b"test").unwrap();
+        assert!(value.is_none());
+    }
+
     #[test]
     fn write_batch_can_be_restored_from_bytes() {
         let temp_dir = TempDir::new().unwrap();
@@ -1,10 +1,14 @@
 //! API types related to the External Node specific methods.
 
+use std::io::Read;
+
+use anyhow::Context as _;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
 use serde::{Deserialize, Serialize};
 use zksync_basic_types::{Address, L1BatchNumber, MiniblockNumber, H256};
 use zksync_contracts::BaseSystemContractsHashes;
 
-use crate::ProtocolVersionId;
+use crate::{ProtocolVersionId, VmEvent};
 
 /// Representation of the L2 block, as needed for the EN synchronization.
 /// This structure has several fields that describe *L1 batch* rather than
@@ -47,3 +51,52 @@ pub struct SyncBlock {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConsensusGenesis(pub serde_json::Value);
+
+/// Execution result of a transaction included into a [`RawBlock`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawTransactionResult {
+    /// Transaction hash.
+    pub hash: H256,
+    /// Whether the transaction was executed successfully.
+    pub success: bool,
+    /// Amount of gas refunded to the transaction initiator.
+    pub refunded_gas: u64,
+    /// Revert reason for failed transactions.
+    pub revert_reason: Option<String>,
+}
+
+/// Self-contained representation of an L2 block together with its transactions and their execution results,
+/// served by the `zks_getRawBlock` method. Raw blocks are persisted when L2 blocks are sealed, so that they can be served
+/// without reconstructing them from normalized tables; this makes them suitable for bulk synchronization.
+///
+/// On the wire, raw blocks are transferred [encoded](Self::encode()) as gzip-compressed JSON.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawBlock {
+    /// L2 block data. Transactions are always present.
+    pub block: SyncBlock,
+    /// Execution results for all transactions in the block, in the execution order.
+    pub results: Vec<RawTransactionResult>,
+    /// Events emitted in the block, in the order of their emission.
+    pub events: Vec<VmEvent>,
+}
+
+impl RawBlock {
+    /// Encodes this block into a compact binary representation.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        serde_json::to_writer(&mut encoder, self).expect("failed serializing raw block");
+        // Writing to an in-memory buffer cannot fail.
+        encoder.finish().unwrap()
+    }
+
+    /// Decodes a block previously [encoded](Self::encode()).
+    pub fn decode(bytes: &[u8]) -> anyhow::Result<Self> {
+        let mut json = Vec::new();
+        GzDecoder::new(bytes)
+            .read_to_end(&mut json)
+            .context("failed decompressing raw block")?;
+        serde_json::from_slice(&json).context("failed deserializing raw block")
+    }
+}
@@ -11,10 +11,11 @@ pub use crate::transaction_request::{
     Eip712Meta, SerializationTransactionError, TransactionRequest,
 };
 use crate::{
+    circuit::CircuitStatistic,
     protocol_version::L1VerifierConfig,
     vm_trace::{Call, CallType},
     web3::types::{AccessList, Index, H2048},
-    Address, MiniblockNumber, ProtocolVersionId,
+    Address, L1BlockNumber, MiniblockNumber, PriorityOpId, ProtocolVersionId,
 };
 
 pub mod en;
@@ -126,6 +127,10 @@ pub struct BlockNumberObject {
 #[serde(rename_all = "camelCase")]
 pub struct BlockHashObject {
     pub block_hash: H256,
+    /// Whether the block must be canonical. Since the node only stores canonical blocks, this flag doesn't influence
+    /// block resolution; hashes of blocks reverted during a chain reorganization are always reported as non-canonical.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub require_canonical: bool,
 }
 
 /// Helper enum for EIP-1898.
@@ -189,14 +194,32 @@ pub struct L2ToL1LogProof {
     pub root: H256,
 }
 
-/// A struct with the two default bridge contracts.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Bridge contracts of the chain: the default bridges and custom bridges for specific tokens.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BridgeAddresses {
+    /// Default shared bridge; only present for chains connected to the bridgehub.
+    /// If present, the ERC20 default bridge is the legacy one.
+    pub l1_shared_default_bridge: Option<Address>,
+    pub l2_shared_default_bridge: Option<Address>,
     pub l1_erc20_default_bridge: Address,
     pub l2_erc20_default_bridge: Address,
     pub l1_weth_bridge: Option<Address>,
     pub l2_weth_bridge: Option<Address>,
+    /// Bridges used for specific tokens instead of the default bridges, sorted by the L1 token address.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom_bridges: Vec<TokenBridge>,
+}
+
+/// Custom bridge for a specific token.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenBridge {
+    pub l1_token_address: Address,
+    /// L2 counterpart of the token. May be unknown if the token wasn't bridged yet.
+    pub l2_token_address: Option<Address>,
+    pub l1_bridge: Address,
+    pub l2_bridge: Address,
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
@@ -575,6 +598,31 @@ pub struct TransactionDetails {
     pub eth_commit_tx_hash: Option<H256>,
     pub eth_prove_tx_hash: Option<H256>,
     pub eth_execute_tx_hash: Option<H256>,
+    /// Gas refunded to the transaction initiator after execution. `None` for pending transactions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refunded_gas: Option<U256>,
+    /// Refund (in gas) computed by the operator when executing the transaction. Differs from `refunded_gas` if
+    /// the bootloader has chosen a refund different from the operator suggestion. `None` for pending transactions
+    /// and for transactions executed before the suggested refund was persisted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operator_suggested_refund: Option<U256>,
+    /// Resources consumed by the transaction during execution. `None` for pending transactions
+    /// and for transactions executed before execution profiles were recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub execution_profile: Option<TransactionExecutionProfile>,
+}
+
+/// Resources consumed by a transaction when it was executed by the state keeper.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionExecutionProfile {
+    pub computational_gas_used: u32,
+    /// Pubdata published by the transaction, in bytes.
+    pub pubdata_published: u32,
+    /// Number of circuits of each type used by the transaction (can be fractional).
+    pub circuits_used: CircuitStatistic,
+    /// Wall-clock time spent executing the transaction in the VM, in microseconds.
+    pub vm_execution_time_us: u64,
 }
 
 #[derive(Debug, Clone)]
@@ -653,6 +701,17 @@ pub struct ProtocolVersion {
     pub l2_system_upgrade_tx_hash: Option<H256>,
 }
 
+/// Protocol version together with the information on its activation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProtocolVersionInfo {
+    #[serde(flatten)]
+    pub version: ProtocolVersion,
+    /// Number of the first L1 batch produced with this protocol version. `None` if the version
+    /// is not activated yet.
+    pub first_l1_batch_number: Option<L1BatchNumber>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub enum SupportedTracers {
@@ -719,6 +778,219 @@ pub struct L1BatchDetails {
     pub base: BlockDetailsBase,
 }
 
+/// Kind of event recorded in the priority operations audit log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PriorityOpAuditEvent {
+    /// Priority operation was observed on L1 and persisted by the node.
+    Received,
+    /// Priority operation was executed in a miniblock.
+    Executed,
+    /// Miniblock containing the priority operation was rolled back.
+    RolledBack,
+}
+
+impl PriorityOpAuditEvent {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Received => "received",
+            Self::Executed => "executed",
+            Self::RolledBack => "rolled_back",
+        }
+    }
+}
+
+impl std::str::FromStr for PriorityOpAuditEvent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "received" => Ok(Self::Received),
+            "executed" => Ok(Self::Executed),
+            "rolled_back" => Ok(Self::RolledBack),
+            _ => Err(format!("unknown priority op audit event: {s}")),
+        }
+    }
+}
+
+/// Single entry in the append-only audit log of a priority operation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriorityOpAuditRecord {
+    /// Serial ID of the priority operation in the L1 priority queue.
+    pub priority_op_id: PriorityOpId,
+    /// Hash of the L2 transaction corresponding to the priority operation.
+    pub tx_hash: H256,
+    pub event: PriorityOpAuditEvent,
+    /// Hash of the L1 transaction that has created the operation. Only set for `received` events.
+    pub l1_tx_hash: Option<H256>,
+    /// Number of the L1 block the operation was included in. Only set for `received` events.
+    pub l1_block_number: Option<L1BlockNumber>,
+    /// Miniblock the operation was executed in (for `executed` events) or reverted from (for `rolledBack` events).
+    pub miniblock_number: Option<MiniblockNumber>,
+    /// Index of the transaction in the miniblock. Only set for `executed` events.
+    pub index_in_block: Option<u32>,
+    /// Whether execution has succeeded. Only set for `executed` events.
+    pub success: Option<bool>,
+    /// Amount of gas refunded to the refund recipient. Only set for `executed` events.
+    pub refunded_gas: Option<u64>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Kind of governance operation scheduled on L1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum GovernanceOperationKind {
+    /// Operation with calls published on L1 when the operation is scheduled.
+    Transparent,
+    /// Operation with only its ID published on L1; calls are revealed only when the operation is executed.
+    Shadow,
+}
+
+impl GovernanceOperationKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Transparent => "transparent",
+            Self::Shadow => "shadow",
+        }
+    }
+}
+
+impl std::str::FromStr for GovernanceOperationKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "transparent" => Ok(Self::Transparent),
+            "shadow" => Ok(Self::Shadow),
+            _ => Err(format!("unknown governance operation kind: {s}")),
+        }
+    }
+}
+
+/// Call performed by a governance operation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GovernanceCall {
+    pub target: Address,
+    pub value: U256,
+    pub data: Bytes,
+}
+
+/// Governance operation scheduled on L1 that is neither executed nor cancelled yet.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingGovernanceOperation {
+    /// Operation ID assigned by the governance contract.
+    pub id: H256,
+    pub kind: GovernanceOperationKind,
+    /// Hash of the L1 transaction that has scheduled the operation.
+    pub l1_tx_hash: H256,
+    /// Number of the L1 block the operation was scheduled in.
+    pub l1_block_number: L1BlockNumber,
+    /// Timestamp of the L1 block the operation was scheduled in.
+    pub scheduled_at: u64,
+    /// Earliest timestamp at which the operation can be executed.
+    pub ready_at: u64,
+    /// Calls performed by the operation. Always empty for shadow operations.
+    pub calls: Vec<GovernanceCall>,
+}
+
+/// Replacement of a pending L2 transaction by a transaction with the same initiator and nonce.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionReplacement {
+    /// Hash of the replaced transaction.
+    pub replaced_hash: H256,
+    /// Hash of the replacing transaction.
+    pub hash: H256,
+    pub from: Address,
+    pub nonce: U256,
+}
+
+/// Pubdata committed for an L1 batch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct L1BatchPubdata {
+    pub pubdata: Bytes,
+    /// Pubdata split into EIP-4844 blobs in the same way as it is done when publishing pubdata via blobs.
+    /// Empty if the protocol version of the batch doesn't support blobs.
+    pub blobs: Vec<Bytes>,
+}
+
+/// Stage of the L1 batch lifecycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum L1BatchStage {
+    Sealed,
+    Committed,
+    Proven,
+    Executed,
+}
+
+impl L1BatchStage {
+    pub const ALL: [Self; 4] = [Self::Sealed, Self::Committed, Self::Proven, Self::Executed];
+}
+
+/// L1 batch reaching a certain stage of its lifecycle.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct L1BatchStageUpdate {
+    pub l1_batch_number: L1BatchNumber,
+    pub stage: L1BatchStage,
+    /// Hash of the confirmed L1 transaction that has moved the batch to this stage. Always `None`
+    /// for sealed batches.
+    pub l1_tx_hash: Option<H256>,
+}
+
+/// L1 pubdata pricing assumed when simulating the fee model.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "camelCase")]
+pub enum SimulatedPubdataPricing {
+    /// Pubdata is published via calldata, i.e. its L1 price is derived from the L1 gas price of each batch.
+    Calldata,
+    /// Pubdata is published at a fixed L1 price per byte (e.g., the expected blob price).
+    #[serde(rename_all = "camelCase")]
+    Fixed { l1_pubdata_price: u64 },
+}
+
+/// Hypothetical fee model parameters to simulate. Mirrors the `V2` fee model config.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeModelSimulationParams {
+    pub minimal_l2_gas_price: u64,
+    pub compute_overhead_part: f64,
+    pub pubdata_overhead_part: f64,
+    pub batch_overhead_l1_gas: u64,
+    pub max_gas_per_batch: u64,
+    pub max_pubdata_per_batch: u64,
+    pub pubdata_pricing: SimulatedPubdataPricing,
+}
+
+/// Fee-related values for a single L1 batch, either actual or simulated.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchFeesSummary {
+    pub fair_l2_gas_price: u64,
+    pub fair_pubdata_price: u64,
+    pub base_fee: u64,
+    pub gas_per_pubdata: u64,
+    /// Total fees paid by the batch transactions. For simulated values, this assumes that
+    /// the amount of gas spent by the transactions would not change.
+    pub total_fees: U256,
+}
+
+/// Result of the fee model simulation for a single L1 batch.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeModelSimulationBatch {
+    pub l1_batch_number: L1BatchNumber,
+    pub l1_gas_price: u64,
+    pub gas_used: U256,
+    pub actual: BatchFeesSummary,
+    pub simulated: BatchFeesSummary,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct StorageProof {
@@ -734,3 +1006,170 @@ pub struct Proof {
     pub address: Address,
     pub storage_proof: Vec<StorageProof>,
 }
+
+/// Storage slot identified by the account address and the slot key.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageSlot {
+    pub address: Address,
+    pub key: H256,
+}
+
+/// Merkle proof for a single storage slot. If the slot was never written to, the proof is an exclusion proof:
+/// `value` is zero, and `index` is 0.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageSlotProof {
+    pub address: Address,
+    #[serde(flatten)]
+    pub proof: StorageProof,
+}
+
+/// Result of `zks_getStorageProofs`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageProofs {
+    pub l1_batch_number: L1BatchNumber,
+    /// Root hash of the Merkle tree after the L1 batch. All proofs are relative to this hash.
+    pub root_hash: H256,
+    /// Proofs in the same order as the requested storage slots.
+    pub proofs: Vec<StorageSlotProof>,
+}
+
+/// Options for `zks_call`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallOptions {
+    /// If set, the response will contain storage slots and accounts accessed during the call.
+    #[serde(default)]
+    pub return_accessed_storage: bool,
+}
+
+/// Storage slot read during a call, together with its value at the start of the call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessedStorageSlot {
+    pub address: Address,
+    pub key: H256,
+    pub value: H256,
+}
+
+/// Storage accessed during a call. A call on the same block with the same params will produce
+/// the same output as long as the values of all accessed slots are unchanged.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessedStorage {
+    /// Accounts whose code was read during the call (e.g., called contracts), sorted by address.
+    pub accounts: Vec<Address>,
+    /// Storage slots read during the call, sorted by address and key.
+    pub storage_slots: Vec<AccessedStorageSlot>,
+}
+
+/// Result of `zks_call`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallResult {
+    pub output: Bytes,
+    /// Storage accessed during the call. Only returned if requested via [`CallOptions`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accessed_storage: Option<AccessedStorage>,
+}
+
+/// Runtime-configurable rules used when validating account abstraction transactions, in addition to
+/// the standard rules (e.g., access to the well-known ERC-20 slots of bridged and whitelisted tokens).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AaValidationRules {
+    /// Storage slots (as indices in the contract storage) that are trusted for each bridged or whitelisted token.
+    #[serde(default)]
+    pub trusted_token_slots: Vec<H256>,
+    /// Addresses of contracts that can be called during validation with unrestricted access to their storage.
+    #[serde(default)]
+    pub trusted_addresses: Vec<Address>,
+}
+
+/// Information about an active WebSocket connection to the API server.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WsConnectionInfo {
+    /// Server-local ID of the connection. Can be used to close the connection.
+    pub id: u64,
+    /// Connection age in seconds.
+    pub age_secs: u64,
+    /// Number of active subscriptions on the connection.
+    pub subscription_count: usize,
+    /// Total number of requests received over the connection.
+    pub request_count: u64,
+    /// Total number of subscription notifications sent over the connection.
+    pub notification_count: u64,
+    /// Average number of requests per minute over the connection lifetime.
+    pub requests_per_minute: f64,
+    /// Average number of subscription notifications per minute over the connection lifetime.
+    pub notifications_per_minute: f64,
+    /// Whether the connection was closed by the node operator. Closed connections remain listed
+    /// until the client disconnects.
+    pub is_closed: bool,
+}
+
+/// Usage of a seal criterion by the L1 batch currently open in the state keeper.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SealCriterionUsage {
+    /// Criterion name, as used in state keeper metrics.
+    pub criterion: String,
+    /// Ratio of the resource consumed by the batch to the threshold at which the criterion seals the batch.
+    /// The batch is sealed once the ratio reaches (or, for some criteria, exceeds) 1.
+    pub usage: f64,
+}
+
+/// Seal status of the L1 batch currently open in the state keeper.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchSealStatus {
+    pub l1_batch_number: L1BatchNumber,
+    /// Number of transactions executed in the batch so far.
+    pub tx_count: usize,
+    /// Usage of seal criteria by the batch, in the order the criteria are evaluated by the state keeper.
+    pub criteria: Vec<SealCriterionUsage>,
+}
+
+/// Slow DB query captured by the DAL instrumentation together with its query plan.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlowQueryInfo {
+    /// Name of the query assigned in the DAL.
+    pub name: String,
+    /// Location of the code invoking the query in the `file:line` format.
+    pub location: String,
+    /// SQL text of the query.
+    pub sql: String,
+    /// Query arguments logged by the DAL, as `(name, value)` tuples.
+    pub args: Vec<(String, String)>,
+    /// Query latency in milliseconds.
+    pub elapsed_ms: u64,
+    /// Time when the query has finished.
+    pub finished_at: DateTime<Utc>,
+    /// Query plan returned by `EXPLAIN`, or `null` if the plan could not be obtained.
+    pub query_plan: Option<String>,
+}
+
+/// Runtime statistics for a cache used by the API server, collected since the node start.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheInfo {
+    /// Cache name. Can be used to flush the cache.
+    pub name: String,
+    /// Number of entries in the cache.
+    pub entries: u64,
+    /// Memory used by the cache entries in bytes, or `null` if the cache doesn't track memory usage.
+    pub used_memory_bytes: Option<u64>,
+    /// Number of lookups that returned an entry.
+    pub hits: u64,
+    /// Number of lookups that didn't return an entry.
+    pub misses: u64,
+    /// Ratio of hits to all lookups, or `null` if there were no lookups.
+    pub hit_ratio: Option<f64>,
+    /// Number of entries removed by the cache eviction policy (e.g., LRU or expiration). For some caches,
+    /// this number is estimated.
+    pub evictions: u64,
+}
@@ -145,7 +145,7 @@ impl L1BatchHeader {
     }
 }
 
-#[derive(Clone, Copy, Eq, PartialEq, Default)]
+#[derive(Clone, Copy, Eq, PartialEq, Default, Serialize, Deserialize)]
 pub struct BlockGasCount {
     pub commit: u32,
     pub prove: u32,
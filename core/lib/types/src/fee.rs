@@ -79,6 +79,39 @@ impl Fee {
     }
 }
 
+/// Breakdown of the gas used by a transaction during fee estimation, based on the sandbox execution metrics.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct GasBreakdown {
+    /// Gas spent on the transaction execution, excluding gas spent on publishing pubdata.
+    pub computation_gas: U256,
+    /// Gas spent on publishing pubdata.
+    pub pubdata_gas: U256,
+    /// Gas per pubdata byte used during the estimation.
+    pub gas_per_pubdata: U256,
+}
+
+/// Gas refunds observed during the sandbox execution of a transaction with the estimated gas limit.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct GasRefunds {
+    /// Refund computed by the operator (i.e., the server) based on the actual gas and pubdata usage.
+    pub operator_suggested_refund: U256,
+    /// Refund actually applied by the bootloader.
+    pub refunded_gas: U256,
+}
+
+/// Fee estimate for a transaction.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FeeEstimate {
+    #[serde(flatten)]
+    pub fee: Fee,
+    /// Breakdown of the gas used by the transaction. May be missing if returned by an older server.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub breakdown: Option<GasBreakdown>,
+    /// Refunds for the estimated transaction. May be missing if returned by an older server.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refunds: Option<GasRefunds>,
+}
+
 /// Returns how many slots would ABI-encoding of the transaction with such parameters take
 pub fn encoding_len(
     data_len: u64,
@@ -101,3 +134,58 @@ pub fn encoding_len(
 
     BASE_LEN + dynamic_len as usize
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializing_fee_estimate() {
+        let estimate = FeeEstimate {
+            fee: Fee {
+                gas_limit: 100_000.into(),
+                max_fee_per_gas: 10.into(),
+                max_priority_fee_per_gas: 0.into(),
+                gas_per_pubdata_limit: 100.into(),
+            },
+            breakdown: Some(GasBreakdown {
+                computation_gas: 80_000.into(),
+                pubdata_gas: 10_000.into(),
+                gas_per_pubdata: 100.into(),
+            }),
+            refunds: Some(GasRefunds {
+                operator_suggested_refund: 5_000.into(),
+                refunded_gas: 4_000.into(),
+            }),
+        };
+        let json = serde_json::to_value(&estimate).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "gas_limit": "0x186a0",
+                "max_fee_per_gas": "0xa",
+                "max_priority_fee_per_gas": "0x0",
+                "gas_per_pubdata_limit": "0x64",
+                "breakdown": {
+                    "computation_gas": "0x13880",
+                    "pubdata_gas": "0x2710",
+                    "gas_per_pubdata": "0x64",
+                },
+                "refunds": {
+                    "operator_suggested_refund": "0x1388",
+                    "refunded_gas": "0xfa0",
+                },
+            })
+        );
+
+        // Responses of older servers must be parsed as well.
+        let mut json = json;
+        let json_object = json.as_object_mut().unwrap();
+        json_object.remove("breakdown");
+        json_object.remove("refunds");
+        let parsed: FeeEstimate = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.fee, estimate.fee);
+        assert_eq!(parsed.breakdown, None);
+        assert_eq!(parsed.refunds, None);
+    }
+}
@@ -0,0 +1,17 @@
+use crate::StorageKey;
+
+/// Access-list-like hints optionally attached to an L2 transaction on submission.
+///
+/// Hints list storage slots that the transaction is expected to access. They are not signed and are never verified,
+/// so they must only be used as a scheduling heuristic (e.g., to decide which transactions can be executed
+/// in parallel), and never affect execution results.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TxAccessHints {
+    pub storage_slots: Vec<StorageKey>,
+}
+
+impl TxAccessHints {
+    pub fn is_empty(&self) -> bool {
+        self.storage_slots.is_empty()
+    }
+}
@@ -10,9 +10,12 @@ use zksync_basic_types::{Address, H256};
 use zksync_utils::bytecode::CompressedBytecodeInfo;
 
 use self::tx_execution_info::TxExecutionStatus;
-pub use self::{execute::Execute, tx_execution_info::ExecutionMetrics};
+pub use self::{
+    access_hints::TxAccessHints, execute::Execute, tx_execution_info::ExecutionMetrics,
+};
 use crate::{vm_trace::Call, Transaction};
 
+pub mod access_hints;
 pub mod execute;
 pub mod tx_execution_info;
 pub use zksync_crypto_primitives as primitives;
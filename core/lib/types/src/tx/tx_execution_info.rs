@@ -1,4 +1,7 @@
-use std::ops::{Add, AddAssign};
+use std::{
+    ops::{Add, AddAssign},
+    time::Duration,
+};
 
 use crate::{
     circuit::CircuitStatistic,
@@ -56,7 +59,7 @@ impl DeduplicatedWritesMetrics {
     }
 }
 
-#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct ExecutionMetrics {
     pub gas_used: usize,
     pub published_bytecode_bytes: usize,
@@ -70,12 +73,19 @@ pub struct ExecutionMetrics {
     pub cycles_used: u32,
     pub computational_gas_used: u32,
     pub pubdata_published: u32,
+    /// Predicted size of the L1 batch pubdata produced by the execution, excluding state diffs
+    /// (i.e., user L2-to-L1 logs, long L2-to-L1 messages and published bytecodes in their pubdata encoding).
+    pub pubdata_without_state_diffs: usize,
     pub circuit_statistic: CircuitStatistic,
+    /// Wall-clock time spent executing the transaction in the VM. Only measured by the state keeper;
+    /// zero in all other contexts. Not included into the serialized execution info persisted for transactions.
+    #[serde(skip)]
+    pub vm_execution_time: Duration,
 }
 
 impl ExecutionMetrics {
     pub fn from_tx_metrics(tx_metrics: &TransactionExecutionMetrics) -> Self {
-        Self {
+        let mut metrics = Self {
             published_bytecode_bytes: tx_metrics.published_bytecode_bytes,
             l2_l1_long_messages: tx_metrics.l2_l1_long_messages,
             l2_to_l1_logs: tx_metrics.l2_l1_logs,
@@ -88,8 +98,13 @@ impl ExecutionMetrics {
             cycles_used: tx_metrics.cycles_used,
             computational_gas_used: tx_metrics.computational_gas_used,
             pubdata_published: tx_metrics.pubdata_published,
+            pubdata_without_state_diffs: 0,
             circuit_statistic: tx_metrics.circuit_statistic,
-        }
+            vm_execution_time: Duration::ZERO,
+        };
+        // Transaction metrics don't allow to restore the exact pubdata encoding, so we use a conservative estimate.
+        metrics.pubdata_without_state_diffs = metrics.size();
+        metrics
     }
 
     pub fn size(&self) -> usize {
@@ -122,7 +137,10 @@ impl Add for ExecutionMetrics {
             cycles_used: self.cycles_used + other.cycles_used,
             computational_gas_used: self.computational_gas_used + other.computational_gas_used,
             pubdata_published: self.pubdata_published + other.pubdata_published,
+            pubdata_without_state_diffs: self.pubdata_without_state_diffs
+                + other.pubdata_without_state_diffs,
             circuit_statistic: self.circuit_statistic + other.circuit_statistic,
+            vm_execution_time: self.vm_execution_time + other.vm_execution_time,
         }
     }
 }
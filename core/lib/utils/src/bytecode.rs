@@ -1,6 +1,7 @@
 use std::{collections::HashMap, convert::TryInto};
 
 use itertools::Itertools;
+use serde::{Deserialize, Serialize};
 use zksync_basic_types::{
     ethabi::{encode, Token},
     H256,
@@ -97,7 +98,7 @@ pub fn compress_bytecode(code: &[u8]) -> Result<Vec<u8>, FailedToCompressBytecod
     Ok(compressed)
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CompressedBytecodeInfo {
     pub original: Vec<u8>,
     pub compressed: Vec<u8>,
@@ -64,9 +64,10 @@ pub fn be_chunks_to_h256_words(chunks: Vec<[u8; 32]>) -> Vec<H256> {
     chunks.into_iter().map(|el| H256::from_slice(&el)).collect()
 }
 
-pub fn bytes_to_be_words(vec: Vec<u8>) -> Vec<U256> {
-    ensure_chunkable(&vec);
-    vec.chunks(32).map(U256::from_big_endian).collect()
+pub fn bytes_to_be_words(bytes: impl AsRef<[u8]>) -> Vec<U256> {
+    let bytes = bytes.as_ref();
+    ensure_chunkable(bytes);
+    bytes.chunks(32).map(U256::from_big_endian).collect()
 }
 
 pub fn be_words_to_bytes(words: &[U256]) -> Vec<u8> {
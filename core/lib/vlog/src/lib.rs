@@ -1,7 +1,9 @@
 //! This module contains the observability subsystem.
 //! It is responsible for providing a centralized interface for consistent observability configuration.
 
-use std::{backtrace::Backtrace, borrow::Cow, panic::PanicInfo, str::FromStr};
+use std::{
+    backtrace::Backtrace, borrow::Cow, collections::HashMap, panic::PanicInfo, str::FromStr,
+};
 
 // Temporary re-export of `sentry::capture_message` aiming to simplify the transition from `vlog` to using
 // crates directly.
@@ -17,7 +19,7 @@ use opentelemetry_otlp::WithExportConfig;
 use opentelemetry_semantic_conventions::resource::SERVICE_NAME;
 pub use sentry::{capture_message, Level as AlertLevel};
 use sentry::{types::Dsn, ClientInitGuard};
-use tracing_opentelemetry::OpenTelemetryLayer;
+use tracing_opentelemetry::{OpenTelemetryLayer, OpenTelemetrySpanExt};
 use tracing_subscriber::{
     filter::Filtered,
     fmt,
@@ -223,7 +225,8 @@ impl ObservabilityBuilder {
             )
             .with_trace_config(
                 trace::config()
-                    .with_sampler(Sampler::AlwaysOn)
+                    // Respect sampling decisions of remote callers propagated via trace context.
+                    .with_sampler(Sampler::ParentBased(Box::new(Sampler::AlwaysOn)))
                     .with_id_generator(RandomIdGenerator::default())
                     .with_resource(Resource::new(resource)),
             )
@@ -318,6 +321,25 @@ impl ObservabilityBuilder {
     }
 }
 
+/// Sets the parent of the provided span to the remote trace context propagated via W3C Trace Context headers
+/// (`traceparent` and `tracestate`). Header names must be lowercase. This is a no-op if OpenTelemetry export
+/// is not configured, or if the headers don't contain a valid trace context.
+pub fn set_remote_parent<'a>(
+    span: &tracing::Span,
+    headers: impl IntoIterator<Item = (&'a str, &'a str)>,
+) {
+    let headers: HashMap<String, String> = headers
+        .into_iter()
+        .map(|(name, value)| (name.to_owned(), value.to_owned()))
+        .collect();
+    if headers.is_empty() {
+        return;
+    }
+    let context =
+        opentelemetry::global::get_text_map_propagator(|propagator| propagator.extract(&headers));
+    span.set_parent(context);
+}
+
 fn json_panic_handler(panic_info: &PanicInfo) {
     let backtrace = Backtrace::force_capture();
     let timestamp = chrono::Utc::now();
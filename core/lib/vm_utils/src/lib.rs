@@ -8,7 +8,7 @@ use multivm::{
 };
 use tokio::runtime::Handle;
 use zksync_dal::{Connection, Core};
-use zksync_state::{PostgresStorage, StoragePtr, StorageView, WriteStorage};
+use zksync_state::{BytecodeCache, PostgresStorage, StoragePtr, StorageView, WriteStorage};
 use zksync_types::{L1BatchNumber, L2ChainId, Transaction};
 
 use crate::storage::L1BatchParamsProvider;
@@ -23,6 +23,7 @@ pub fn create_vm(
     l1_batch_number: L1BatchNumber,
     mut connection: Connection<'_, Core>,
     l2_chain_id: L2ChainId,
+    bytecode_cache: Option<BytecodeCache>,
 ) -> anyhow::Result<VmAndStorage> {
     let l1_batch_params_provider = rt_handle
         .block_on(L1BatchParamsProvider::new(&mut connection))
@@ -50,12 +51,15 @@ pub fn create_vm(
         .context("expected miniblock to be executed and sealed")?;
 
     let storage_miniblock_number = first_miniblock_in_batch.number() - 1;
-    let pg_storage = PostgresStorage::new(
+    let mut pg_storage = PostgresStorage::new(
         rt_handle.clone(),
         connection,
         storage_miniblock_number,
         true,
     );
+    if let Some(bytecode_cache) = bytecode_cache {
+        pg_storage = pg_storage.with_bytecode_cache(bytecode_cache);
+    }
     let storage_view = StorageView::new(pg_storage).to_rc_ptr();
     let vm = VmInstance::new(l1_batch_env, system_env, storage_view.clone());
 
@@ -1,4 +1,7 @@
-use std::time::{Duration, Instant};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use anyhow::Context;
 use multivm::{
@@ -58,7 +61,7 @@ pub fn l1_batch_params(
         SystemEnv {
             zk_porter_available: ZKPORTER_IS_AVAILABLE,
             version: protocol_version,
-            base_system_smart_contracts: base_system_contracts,
+            base_system_smart_contracts: Arc::new(base_system_contracts),
             bootloader_gas_limit: BATCH_COMPUTATIONAL_GAS_LIMIT,
             execution_mode: TxExecutionMode::VerifyExecute,
             default_validation_computational_gas_limit: validation_computational_gas_limit,
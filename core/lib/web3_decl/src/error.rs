@@ -14,7 +14,7 @@ use std::{
 use jsonrpsee::core::ClientError;
 use pin_project_lite::pin_project;
 use thiserror::Error;
-use zksync_types::{api::SerializationTransactionError, L1BatchNumber, MiniblockNumber};
+use zksync_types::{api::SerializationTransactionError, L1BatchNumber, MiniblockNumber, H256};
 
 /// Server-side representation of the RPC error.
 #[derive(Debug, Error)]
@@ -25,6 +25,10 @@ pub enum Web3Error {
     PrunedBlock(MiniblockNumber),
     #[error("L1 batch with such an ID is pruned; the first retained L1 batch is {0}")]
     PrunedL1Batch(L1BatchNumber),
+    #[error(
+        "Block with hash {0:?} is not canonical; it was reverted during a chain reorganization"
+    )]
+    NonCanonicalBlock(H256),
     #[error("{}", _0.as_ref())]
     ProxyError(#[from] EnrichedClientError),
     #[error("{0}")]
@@ -41,6 +45,8 @@ pub enum Web3Error {
     InvalidFilterBlockHash,
     #[error("Not implemented")]
     NotImplemented,
+    #[error("Invalid params: {0}")]
+    InvalidParams(String),
 
     #[error("Tree API is not available")]
     TreeApiUnavailable,
@@ -0,0 +1,91 @@
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use zksync_types::{
+    api::{AaValidationRules, BatchSealStatus, CacheInfo, SlowQueryInfo, WsConnectionInfo},
+    Address, L1BatchNumber,
+};
+
+/// RPCs in this namespace allow node operators to change the node configuration at runtime. Changes are not persisted;
+/// they are lost after the node restart. The namespace must not be exposed publicly.
+#[cfg_attr(
+    all(feature = "client", feature = "server"),
+    rpc(server, client, namespace = "admin")
+)]
+#[cfg_attr(
+    all(feature = "client", not(feature = "server")),
+    rpc(client, namespace = "admin")
+)]
+#[cfg_attr(
+    all(not(feature = "client"), feature = "server"),
+    rpc(server, namespace = "admin")
+)]
+pub trait AdminNamespace {
+    /// Returns additional rules used during account abstraction validation.
+    #[method(name = "getAaValidationRules")]
+    async fn get_aa_validation_rules(&self) -> RpcResult<AaValidationRules>;
+
+    /// Replaces additional rules used during account abstraction validation.
+    #[method(name = "setAaValidationRules")]
+    async fn set_aa_validation_rules(&self, rules: AaValidationRules) -> RpcResult<()>;
+
+    /// Returns tokens white-listed for use by paymasters (in addition to natively bridged tokens).
+    #[method(name = "getWhitelistedTokensForAA")]
+    async fn get_whitelisted_tokens_for_aa(&self) -> RpcResult<Vec<Address>>;
+
+    /// Adds tokens to the white-list for use by paymasters. Returns the updated white-list.
+    /// On external nodes, the white-list is periodically synced from the main node, so changes are short-lived.
+    #[method(name = "addWhitelistedTokensForAA")]
+    async fn add_whitelisted_tokens_for_aa(&self, tokens: Vec<Address>) -> RpcResult<Vec<Address>>;
+
+    /// Removes tokens from the white-list for use by paymasters. Returns the updated white-list.
+    #[method(name = "removeWhitelistedTokensForAA")]
+    async fn remove_whitelisted_tokens_for_aa(
+        &self,
+        tokens: Vec<Address>,
+    ) -> RpcResult<Vec<Address>>;
+
+    /// Lists active WebSocket connections to the node.
+    #[method(name = "getWsConnections")]
+    async fn get_ws_connections(&self) -> RpcResult<Vec<WsConnectionInfo>>;
+
+    /// Closes a WebSocket connection with the specified ID: all further requests over the connection are rejected,
+    /// and all its subscriptions are terminated. Returns `false` if the connection is not found.
+    #[method(name = "closeWsConnection")]
+    async fn close_ws_connection(&self, id: u64) -> RpcResult<bool>;
+
+    /// Reports how close the L1 batch currently open in the state keeper is to being sealed according to each
+    /// seal criterion (e.g., gas, pubdata, circuits, or time). Returns `null` if the state keeper doesn't run
+    /// in the same process as the API server, or hasn't opened a batch yet.
+    #[method(name = "getBatchSealStatus")]
+    async fn get_batch_seal_status(&self) -> RpcResult<Option<BatchSealStatus>>;
+
+    /// Lists slow DB queries captured by the node, from the most recent to the oldest one. Queries are only captured
+    /// if a non-zero sample rate is configured for them (`slow_query_explain_sample_rate` in the Postgres config);
+    /// only a bounded number of the most recent queries is retained.
+    #[method(name = "getSlowQueries")]
+    async fn get_slow_queries(&self) -> RpcResult<Vec<SlowQueryInfo>>;
+
+    /// Rolls back the Merkle tree so that the specified L1 batch is the last one processed by it. Unlike other methods
+    /// in this namespace, the change is persisted. The rollback is refused if it would make the tree inconsistent
+    /// with Postgres (e.g., if Postgres contains tree data for later L1 batches, or doesn't contain data for
+    /// the specified batch). Fails if the tree doesn't run in the same process as the API server.
+    #[method(name = "rollbackTree")]
+    async fn rollback_tree(&self, l1_batch_number: L1BatchNumber) -> RpcResult<()>;
+
+    /// Returns statistics (size, hit ratio and evictions) for all enabled caches used by the API server,
+    /// such as VM storage caches and caches for `eth_call` / `eth_estimateGas` results.
+    #[method(name = "getCacheStats")]
+    async fn get_cache_stats(&self) -> RpcResult<Vec<CacheInfo>>;
+
+    /// Removes all entries from the caches with the specified names (as returned by `admin_getCacheStats`),
+    /// or from all caches if names are not specified. Returns the names of flushed caches. Fails without flushing
+    /// any caches if any of the names is unknown.
+    #[method(name = "flushCaches")]
+    async fn flush_caches(&self, names: Option<Vec<String>>) -> RpcResult<Vec<String>>;
+
+    /// Changes the capacity (in bytes) of the VM storage cache with the specified name, such as `values_cache`,
+    /// `initial_writes_cache`, `negative_initial_writes_cache` or `factory_deps_cache`. When shrinking a cache,
+    /// as many entries as fit into the new capacity are retained. Zero capacity disables the cache. Returns
+    /// the previous capacity.
+    #[method(name = "setCacheCapacity")]
+    async fn set_cache_capacity(&self, name: String, capacity: u64) -> RpcResult<u64>;
+}
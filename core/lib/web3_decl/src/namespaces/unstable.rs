@@ -0,0 +1,49 @@
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use zksync_types::{
+    api::{FeeModelSimulationBatch, FeeModelSimulationParams, PriorityOpAuditRecord},
+    web3::types::AccessList,
+    Bytes, PriorityOpId, H256,
+};
+
+/// RPCs in this namespace are meant for node operators and may change without notice.
+#[cfg_attr(
+    all(feature = "client", feature = "server"),
+    rpc(server, client, namespace = "unstable")
+)]
+#[cfg_attr(
+    all(feature = "client", not(feature = "server")),
+    rpc(client, namespace = "unstable")
+)]
+#[cfg_attr(
+    all(not(feature = "client"), feature = "server"),
+    rpc(server, namespace = "unstable")
+)]
+pub trait UnstableNamespace {
+    /// Returns the audit log for the specified priority operation, oldest entries first.
+    #[method(name = "getPriorityOpHistory")]
+    async fn get_priority_op_history(
+        &self,
+        priority_op_id: PriorityOpId,
+    ) -> RpcResult<Vec<PriorityOpAuditRecord>>;
+
+    /// Submits a raw transaction together with access-list-like hints listing storage slots the transaction
+    /// is expected to access. Hints are not signed or verified; they are only used by the state keeper to evaluate
+    /// scheduling transactions for parallel execution, and never affect transaction execution. At most 1,024 slots
+    /// are retained. Hints are ignored by external nodes.
+    #[method(name = "sendRawTransactionWithHints")]
+    async fn send_raw_transaction_with_hints(
+        &self,
+        tx_bytes: Bytes,
+        access_list: AccessList,
+    ) -> RpcResult<H256>;
+
+    /// Recomputes batch fee inputs and user fees for up to `batch_count` latest L1 batches
+    /// as if they were sealed with the provided fee model params. Batches are returned newest first.
+    /// `batch_count` is capped by the server configuration.
+    #[method(name = "simulateFeeModel")]
+    async fn simulate_fee_model(
+        &self,
+        params: FeeModelSimulationParams,
+        batch_count: u64,
+    ) -> RpcResult<Vec<FeeModelSimulationBatch>>;
+}
@@ -1,18 +1,22 @@
 use std::collections::HashMap;
 
-use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use jsonrpsee::{
+    core::{RpcResult, SubscriptionResult},
+    proc_macros::rpc,
+};
 use zksync_types::{
     api::{
-        BlockDetails, BridgeAddresses, L1BatchDetails, L2ToL1LogProof, Proof, ProtocolVersion,
-        TransactionDetails,
+        BlockDetails, BlockIdVariant, BridgeAddresses, CallOptions, CallResult, L1BatchDetails,
+        L1BatchPubdata, L2ToL1LogProof, PendingGovernanceOperation, Proof, ProtocolVersion,
+        ProtocolVersionInfo, StorageProofs, StorageSlot, TransactionDetails,
     },
-    fee::Fee,
+    fee::FeeEstimate,
     fee_model::FeeParams,
     transaction_request::CallRequest,
     Address, L1BatchNumber, MiniblockNumber, H256, U256, U64,
 };
 
-use crate::types::Token;
+use crate::types::{Bytes, PubSubResult, Token};
 
 #[cfg_attr(
     all(feature = "client", feature = "server"),
@@ -28,7 +32,7 @@ use crate::types::Token;
 )]
 pub trait ZksNamespace {
     #[method(name = "estimateFee")]
-    async fn estimate_fee(&self, req: CallRequest) -> RpcResult<Fee>;
+    async fn estimate_fee(&self, req: CallRequest) -> RpcResult<FeeEstimate>;
 
     #[method(name = "estimateGasL1ToL2")]
     async fn estimate_gas_l1_to_l2(&self, req: CallRequest) -> RpcResult<U256>;
@@ -92,10 +96,23 @@ pub trait ZksNamespace {
         block_number: MiniblockNumber,
     ) -> RpcResult<Vec<zksync_types::Transaction>>;
 
+    /// Returns the specified L2 block with its transactions and their execution results, encoded as
+    /// [`RawBlock`](zksync_types::api::en::RawBlock). Returns `None` if the block doesn't exist or its raw block
+    /// isn't persisted. Raw blocks are persisted in the background, so they may be missing for the latest blocks
+    /// or for blocks sealed before raw blocks started being persisted.
+    #[method(name = "getRawBlock")]
+    async fn get_raw_block(&self, block_number: MiniblockNumber) -> RpcResult<Option<Bytes>>;
+
     #[method(name = "getL1BatchDetails")]
     async fn get_l1_batch_details(&self, batch: L1BatchNumber)
         -> RpcResult<Option<L1BatchDetails>>;
 
+    /// Returns pubdata committed for the specified L1 batch. Returns `None` if the batch is not sealed,
+    /// its commitment is not computed yet, or the batch predates the pubdata format used since the Boojum upgrade.
+    #[method(name = "getL1BatchPubdata")]
+    async fn get_l1_batch_pubdata(&self, batch: L1BatchNumber)
+        -> RpcResult<Option<L1BatchPubdata>>;
+
     #[method(name = "getBytecodeByHash")]
     async fn get_bytecode_by_hash(&self, hash: H256) -> RpcResult<Option<Vec<u8>>>;
 
@@ -111,6 +128,11 @@ pub trait ZksNamespace {
         version_id: Option<u16>,
     ) -> RpcResult<Option<ProtocolVersion>>;
 
+    /// Returns all known protocol versions ordered by their ID, together with the first L1 batch
+    /// produced with each version.
+    #[method(name = "getProtocolVersions")]
+    async fn get_protocol_versions(&self) -> RpcResult<Vec<ProtocolVersionInfo>>;
+
     #[method(name = "getProof")]
     async fn get_proof(
         &self,
@@ -118,4 +140,38 @@ pub trait ZksNamespace {
         keys: Vec<H256>,
         l1_batch_number: L1BatchNumber,
     ) -> RpcResult<Option<Proof>>;
+
+    /// Returns Merkle proofs for arbitrary storage slots at the specified L1 batch, which can be verified
+    /// against the returned tree root hash. Unlike `zks_getProof`, slots may belong to different accounts,
+    /// and proofs can be obtained for any L1 batch retained by the Merkle tree. Returns `None` if the L1 batch
+    /// is not yet processed by the tree.
+    #[method(name = "getStorageProofs")]
+    async fn get_storage_proofs(
+        &self,
+        slots: Vec<StorageSlot>,
+        l1_batch_number: L1BatchNumber,
+    ) -> RpcResult<Option<StorageProofs>>;
+
+    /// Same as `eth_call`, but allows to request additional data about the call via `options`
+    /// (e.g., storage slots and accounts accessed during the call).
+    #[method(name = "call")]
+    async fn call_with_options(
+        &self,
+        req: CallRequest,
+        block: Option<BlockIdVariant>,
+        options: Option<CallOptions>,
+    ) -> RpcResult<CallResult>;
+
+    /// Returns governance operations scheduled on L1 that are neither executed nor cancelled yet,
+    /// ordered by the time they become ready for execution.
+    #[method(name = "getPendingGovernanceOps")]
+    async fn get_pending_governance_ops(&self) -> RpcResult<Vec<PendingGovernanceOperation>>;
+}
+
+#[rpc(server, namespace = "zks")]
+pub trait ZksPubSub {
+    /// Subscribes to L1 batch lifecycle updates. Supported subscription types are `l1BatchSealed`,
+    /// `l1BatchCommitted`, `l1BatchProven` and `l1BatchExecuted`.
+    #[subscription(name = "subscribe" => "subscription", unsubscribe = "unsubscribe", item = PubSubResult)]
+    async fn subscribe(&self, sub_type: String) -> SubscriptionResult;
 }
@@ -14,7 +14,10 @@ use core::{
 use rlp::Rlp;
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 pub use zksync_types::{
-    api::{Block, BlockNumber, Log, TransactionReceipt, TransactionRequest},
+    api::{
+        Block, BlockNumber, L1BatchStageUpdate, Log, TransactionReceipt, TransactionReplacement,
+        TransactionRequest,
+    },
     vm_trace::{ContractSourceDebugInfo, VmDebugTrace, VmExecutionStep},
     web3::{
         ethabi,
@@ -345,6 +348,8 @@ pub enum PubSubResult {
     Header(BlockHeader),
     Log(Log),
     TxHash(H256),
+    TxReplacement(TransactionReplacement),
+    L1BatchStageUpdate(L1BatchStageUpdate),
     Syncing(bool),
 }
 
@@ -404,6 +409,10 @@ mod tests {
                 r#"{"blockHash": "0x0000000000000000000000000000000000000000000000000000000000000000"}"#,
                 BlockId::Hash(H256::default()),
             ),
+            (
+                r#"{"blockHash": "0x0000000000000000000000000000000000000000000000000000000000000000", "requireCanonical": true}"#,
+                BlockId::Hash(H256::default()),
+            ),
             (
                 r#"{"blockNumber": "0x10"}"#,
                 BlockId::Number(BlockNumber::Number(16.into())),
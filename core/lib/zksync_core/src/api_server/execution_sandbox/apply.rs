@@ -6,13 +6,16 @@
 //!
 //! This module is intended to be blocking.
 
-use std::time::{Duration, Instant};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use anyhow::Context as _;
 use multivm::{
     interface::{L1BatchEnv, L2BlockEnv, SystemEnv, VmInterface},
     utils::adjust_pubdata_price_for_tx,
-    vm_latest::{constants::BATCH_COMPUTATIONAL_GAS_LIMIT, HistoryDisabled},
+    vm_latest::{self, HistoryDisabled, PreparedBootloaderMemory},
     VmInstance,
 };
 use tokio::runtime::Handle;
@@ -20,7 +23,7 @@ use zksync_dal::{Connection, ConnectionPool, Core, CoreDal};
 use zksync_state::{PostgresStorage, ReadStorage, StoragePtr, StorageView, WriteStorage};
 use zksync_system_constants::{
     SYSTEM_CONTEXT_ADDRESS, SYSTEM_CONTEXT_CURRENT_L2_BLOCK_INFO_POSITION,
-    SYSTEM_CONTEXT_CURRENT_TX_ROLLING_HASH_POSITION, ZKPORTER_IS_AVAILABLE,
+    SYSTEM_CONTEXT_CURRENT_TX_ROLLING_HASH_POSITION,
 };
 use zksync_types::{
     api,
@@ -34,6 +37,7 @@ use zksync_types::{
 use zksync_utils::{h256_to_u256, time::seconds_since_epoch, u256_to_h256};
 
 use super::{
+    vm_env_pool::PooledVmEnv,
     vm_metrics::{self, SandboxStage, SANDBOX_METRICS},
     BlockArgs, TxExecutionArgs, TxSharedArgs, VmPermit,
 };
@@ -43,6 +47,7 @@ type BoxedVm<'a> = Box<VmInstance<StorageView<PostgresStorage<'a>>, HistoryDisab
 #[derive(Debug)]
 struct Sandbox<'a> {
     system_env: SystemEnv,
+    bootloader_memory: Option<Arc<PreparedBootloaderMemory<HistoryDisabled>>>,
     l1_batch_env: L1BatchEnv,
     execution_args: &'a TxExecutionArgs,
     l2_block_info_to_reset: Option<StoredL2BlockInfo>,
@@ -80,7 +85,7 @@ impl<'a> Sandbox<'a> {
         )
         .await?;
 
-        let storage = PostgresStorage::new_async(
+        let mut storage = PostgresStorage::new_async(
             Handle::current(),
             connection,
             resolved_block_info.state_l2_block_number,
@@ -89,9 +94,17 @@ impl<'a> Sandbox<'a> {
         .await
         .context("cannot create `PostgresStorage`")?
         .with_caches(shared_args.caches.clone());
+        if !execution_args.prefetched_keys.is_empty() {
+            let prefetch_latency = SANDBOX_METRICS.sandbox[&SandboxStage::PrefetchStorage].start();
+            storage
+                .prefetch_values(&execution_args.prefetched_keys)
+                .await
+                .context("failed prefetching storage values")?;
+            prefetch_latency.observe();
+        }
 
-        let storage_view = StorageView::new(storage);
-        let (system_env, l1_batch_env) = Self::prepare_env(
+        let storage_view = StorageView::with_buffers(storage, shared_args.storage_view_pool.take());
+        let (env, l1_batch_env) = Self::prepare_env(
             shared_args,
             execution_args,
             &resolved_block_info,
@@ -99,7 +112,8 @@ impl<'a> Sandbox<'a> {
         );
 
         Ok(Self {
-            system_env,
+            system_env: env.system_env,
+            bootloader_memory: env.bootloader_memory,
             l1_batch_env,
             storage_view,
             execution_args,
@@ -218,11 +232,11 @@ impl<'a> Sandbox<'a> {
         execution_args: &TxExecutionArgs,
         resolved_block_info: &ResolvedBlockInfo,
         next_l2_block_info: L2BlockEnv,
-    ) -> (SystemEnv, L1BatchEnv) {
+    ) -> (PooledVmEnv, L1BatchEnv) {
         let TxSharedArgs {
             operator_account,
             fee_input,
-            base_system_contracts,
+            vm_env_pool,
             validation_computational_gas_limit,
             chain_id,
             ..
@@ -232,26 +246,22 @@ impl<'a> Sandbox<'a> {
         let fee_input = resolved_block_info
             .historical_fee_input
             .unwrap_or(fee_input);
-        let system_env = SystemEnv {
-            zk_porter_available: ZKPORTER_IS_AVAILABLE,
-            version: resolved_block_info.protocol_version,
-            base_system_smart_contracts: base_system_contracts
-                .get_by_protocol_version(resolved_block_info.protocol_version),
-            bootloader_gas_limit: BATCH_COMPUTATIONAL_GAS_LIMIT,
-            execution_mode: execution_args.execution_mode,
-            default_validation_computational_gas_limit: validation_computational_gas_limit,
+        let env = vm_env_pool.env(
+            resolved_block_info.protocol_version,
+            execution_args.execution_mode,
+            validation_computational_gas_limit,
             chain_id,
-        };
+        );
         let l1_batch_env = L1BatchEnv {
             previous_batch_hash: None,
             number: resolved_block_info.vm_l1_batch_number,
             timestamp: resolved_block_info.l1_batch_timestamp,
             fee_input,
-            fee_account: *operator_account.address(),
+            fee_account: operator_account.for_l1_batch(resolved_block_info.vm_l1_batch_number),
             enforced_base_fee: execution_args.enforced_base_fee,
             first_l2_block: next_l2_block_info,
         };
-        (system_env, l1_batch_env)
+        (env, l1_batch_env)
     }
 
     /// This method is blocking.
@@ -272,12 +282,22 @@ impl<'a> Sandbox<'a> {
         };
 
         let storage_view = self.storage_view.to_rc_ptr();
-        let vm = Box::new(VmInstance::new_with_specific_version(
-            self.l1_batch_env,
-            self.system_env,
-            storage_view.clone(),
-            protocol_version.into_api_vm_version(),
-        ));
+        let vm = if let Some(bootloader_memory) = &self.bootloader_memory {
+            VmInstance::Vm1_5_0(vm_latest::Vm::with_prepared_memory(
+                self.l1_batch_env,
+                self.system_env,
+                storage_view.clone(),
+                bootloader_memory,
+            ))
+        } else {
+            VmInstance::new_with_specific_version(
+                self.l1_batch_env,
+                self.system_env,
+                storage_view.clone(),
+                protocol_version.into_api_vm_version(),
+            )
+        };
+        let vm = Box::new(vm);
 
         (vm, storage_view)
     }
@@ -313,6 +333,7 @@ pub(super) fn apply_vm_in_sandbox<T>(
         tracing::debug!("Obtained connection (took {connection_acquire_time:?})");
     }
 
+    let storage_view_pool = shared_args.storage_view_pool.clone();
     let sandbox = rt_handle.block_on(Sandbox::new(
         connection,
         shared_args,
@@ -340,6 +361,8 @@ pub(super) fn apply_vm_in_sandbox<T>(
         vm_execution_took,
         storage_view.as_ref().borrow_mut().metrics(),
     );
+    drop(vm);
+    storage_view_pool.recycle(storage_view);
     Ok(result)
 }
 
@@ -410,14 +433,14 @@ struct ResolvedBlockInfo {
 }
 
 impl BlockArgs {
-    fn is_pending_miniblock(&self) -> bool {
+    pub(super) fn is_pending_miniblock(&self) -> bool {
         matches!(
             self.block_id,
             api::BlockId::Number(api::BlockNumber::Pending)
         )
     }
 
-    fn is_estimate_like(&self) -> bool {
+    pub(super) fn is_estimate_like(&self) -> bool {
         matches!(
             self.block_id,
             api::BlockId::Number(api::BlockNumber::Pending)
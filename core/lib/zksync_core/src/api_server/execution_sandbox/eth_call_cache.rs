@@ -0,0 +1,166 @@
+//! LRU cache for `eth_call` results on historical blocks.
+
+use std::{
+    num::NonZeroUsize,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Context as _;
+use lru::LruCache;
+use multivm::interface::{ExecutionResult, VmExecutionResultAndLogs};
+use zksync_dal::{ConnectionPool, Core, CoreDal};
+use zksync_state::{CacheCounters, CacheStats, InvalidateCache, RevertedBlocks};
+use zksync_types::{l2::L2Tx, Address, MiniblockNumber, Nonce, H256, U256};
+
+use super::{
+    vm_metrics::{CacheLookup, SANDBOX_METRICS},
+    BlockArgs,
+};
+
+/// Key of the [`EthCallCache`]. Besides the call params, contains the hash of the miniblock the call is executed on,
+/// so that entries become unreachable if the miniblock is reverted (e.g., during a reorg on the external node).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(super) struct EthCallCacheKey {
+    block_number: MiniblockNumber,
+    block_hash: H256,
+    initiator: Address,
+    contract_address: Address,
+    calldata: Vec<u8>,
+    value: U256,
+    nonce: Nonce,
+    max_fee_per_gas: U256,
+    gas_per_pubdata_limit: U256,
+    paymaster: Address,
+    paymaster_input: Vec<u8>,
+}
+
+impl EthCallCacheKey {
+    /// Returns `None` if the call is not cacheable, i.e., it is executed on top of a block
+    /// that can still change, or it deploys contracts.
+    pub async fn new(
+        connection_pool: &ConnectionPool<Core>,
+        tx: &L2Tx,
+        block_args: &BlockArgs,
+    ) -> anyhow::Result<Option<Self>> {
+        let has_factory_deps = tx
+            .execute
+            .factory_deps
+            .as_ref()
+            .is_some_and(|deps| !deps.is_empty());
+        if block_args.is_estimate_like() || has_factory_deps {
+            return Ok(None);
+        }
+
+        let block_number = block_args.resolved_block_number();
+        let mut connection = connection_pool
+            .connection_tagged("api")
+            .await
+            .context("failed acquiring DB connection")?;
+        let block_hash = connection
+            .blocks_web3_dal()
+            .get_miniblock_hash(block_number)
+            .await
+            .with_context(|| format!("failed getting hash for miniblock #{block_number}"))?;
+        let Some(block_hash) = block_hash else {
+            return Ok(None);
+        };
+
+        let common_data = &tx.common_data;
+        Ok(Some(Self {
+            block_number,
+            block_hash,
+            initiator: common_data.initiator_address,
+            contract_address: tx.execute.contract_address,
+            calldata: tx.execute.calldata.clone(),
+            value: tx.execute.value,
+            nonce: common_data.nonce,
+            max_fee_per_gas: common_data.fee.max_fee_per_gas,
+            gas_per_pubdata_limit: common_data.fee.gas_per_pubdata_limit,
+            paymaster: common_data.paymaster_params.paymaster,
+            paymaster_input: common_data.paymaster_params.paymaster_input.clone(),
+        }))
+    }
+}
+
+/// LRU cache for results of `eth_call`s executed on historical blocks. Such calls are idempotent,
+/// so their results can be reused for identical requests.
+///
+/// The cache is cheaply cloneable; all clones refer to the same underlying cache.
+#[derive(Debug, Clone)]
+pub(crate) struct EthCallCache {
+    entries: Arc<Mutex<LruCache<EthCallCacheKey, VmExecutionResultAndLogs>>>,
+    counters: Arc<CacheCounters>,
+}
+
+impl EthCallCache {
+    pub(crate) const NAME: &'static str = "eth_call_cache";
+
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(LruCache::new(capacity))),
+            counters: Arc::default(),
+        }
+    }
+
+    pub(super) fn get(&self, key: &EthCallCacheKey) -> Option<VmExecutionResultAndLogs> {
+        let result = self
+            .entries
+            .lock()
+            .expect("`eth_call` cache is poisoned")
+            .get(key)
+            .cloned();
+        self.counters.observe_lookup(result.is_some());
+        let lookup = if result.is_some() {
+            CacheLookup::Hit
+        } else {
+            CacheLookup::Miss
+        };
+        SANDBOX_METRICS.eth_call_cache_lookups[&lookup].inc();
+        result
+    }
+
+    pub(super) fn insert(&self, key: EthCallCacheKey, result: &VmExecutionResultAndLogs) {
+        // Halts may be caused by execution limits that do not depend on the call itself
+        // (e.g., the number of storage cache misses), so they are not cached.
+        if matches!(result.result, ExecutionResult::Halt { .. }) {
+            return;
+        }
+        let evicted = self
+            .entries
+            .lock()
+            .expect("`eth_call` cache is poisoned")
+            .push(key.clone(), result.clone());
+        // `push()` returns either the replaced entry for the same key, or the evicted LRU entry.
+        if evicted.is_some_and(|(evicted_key, _)| evicted_key != key) {
+            self.counters.observe_evictions(1);
+        }
+    }
+
+    pub(crate) fn stats(&self) -> CacheStats {
+        let entries = self.entries.lock().expect("`eth_call` cache is poisoned");
+        self.counters.stats(Self::NAME, entries.len() as u64, None)
+    }
+
+    pub(crate) fn clear(&self) {
+        self.entries
+            .lock()
+            .expect("`eth_call` cache is poisoned")
+            .clear();
+    }
+}
+
+/// Entries for reverted miniblocks are unreachable since the miniblock hash is a part of the key, but they still
+/// occupy the cache until evicted; invalidation removes them right away.
+impl InvalidateCache for EthCallCache {
+    fn invalidate(&self, reverted: RevertedBlocks) {
+        let mut cache = self.entries.lock().expect("`eth_call` cache is poisoned");
+        let stale_keys: Vec<_> = cache
+            .iter()
+            .filter(|(key, _)| key.block_number > reverted.last_miniblock_to_keep)
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &stale_keys {
+            cache.pop(key);
+        }
+    }
+}
@@ -1,22 +1,31 @@
 //! Implementation of "executing" methods, e.g. `eth_call`.
 
+use std::{
+    num::NonZeroUsize,
+    time::{Duration, Instant},
+};
+
 use anyhow::Context as _;
 use multivm::{
     interface::{TxExecutionMode, VmExecutionResultAndLogs, VmInterface},
-    tracers::StorageInvocations,
+    tracers::{ExecutionDeadline, StorageInvocations},
     vm_latest::constants::ETH_CALL_GAS_LIMIT,
     MultiVMTracer,
 };
 use tracing::{span, Level};
 use zksync_dal::{ConnectionPool, Core};
 use zksync_types::{
-    fee::TransactionExecutionMetrics, l2::L2Tx, ExecuteTransactionCommon, Nonce,
-    PackedEthSignature, Transaction, U256,
+    fee::TransactionExecutionMetrics, l2::L2Tx, web3::types::AccessList, AccountTreeId,
+    ExecuteTransactionCommon, Nonce, PackedEthSignature, StorageKey, Transaction, U256,
 };
 
 #[cfg(test)]
 use super::testonly::MockTransactionExecutor;
-use super::{apply, vm_metrics, ApiTracer, BlockArgs, TxSharedArgs, VmPermit};
+use super::{
+    apply,
+    eth_call_cache::{EthCallCache, EthCallCacheKey},
+    vm_metrics, ApiTracer, BlockArgs, TxSharedArgs, VmPermit,
+};
 
 #[derive(Debug)]
 pub(crate) struct TxExecutionArgs {
@@ -25,6 +34,11 @@ pub(crate) struct TxExecutionArgs {
     pub added_balance: U256,
     pub enforced_base_fee: Option<u64>,
     pub missed_storage_invocation_limit: usize,
+    /// Max wall-clock duration of the VM execution. If exceeded, the execution is aborted.
+    pub execution_timeout: Option<Duration>,
+    /// Storage keys that are expected to be read during execution. Values for these keys are loaded
+    /// from Postgres in a single query before the execution starts.
+    pub prefetched_keys: Vec<StorageKey>,
 }
 
 impl TxExecutionArgs {
@@ -35,12 +49,15 @@ impl TxExecutionArgs {
             added_balance: U256::zero(),
             enforced_base_fee: Some(tx.common_data.fee.max_fee_per_gas.as_u64()),
             missed_storage_invocation_limit: usize::MAX,
+            execution_timeout: None,
+            prefetched_keys: vec![],
         }
     }
 
     fn for_eth_call(
         enforced_base_fee: u64,
         vm_execution_cache_misses_limit: Option<usize>,
+        execution_timeout: Option<Duration>,
     ) -> Self {
         let missed_storage_invocation_limit = vm_execution_cache_misses_limit.unwrap_or(usize::MAX);
         Self {
@@ -49,6 +66,8 @@ impl TxExecutionArgs {
             added_balance: U256::zero(),
             enforced_base_fee: Some(enforced_base_fee),
             missed_storage_invocation_limit,
+            execution_timeout,
+            prefetched_keys: vec![],
         }
     }
 
@@ -72,8 +91,38 @@ impl TxExecutionArgs {
             enforced_nonce: tx.nonce(),
             added_balance,
             enforced_base_fee: Some(base_fee),
+            execution_timeout: None,
+            prefetched_keys: vec![],
         }
     }
+
+    /// Sets storage keys to prefetch before the execution. At most [`MAX_PREFETCHED_KEYS`] keys are retained.
+    pub fn with_prefetched_keys(mut self, mut keys: Vec<StorageKey>) -> Self {
+        keys.truncate(MAX_PREFETCHED_KEYS);
+        self.prefetched_keys = keys;
+        self
+    }
+}
+
+/// Maximum number of storage keys prefetched before executing a transaction. Limits the Postgres load
+/// for access lists supplied by users.
+pub(crate) const MAX_PREFETCHED_KEYS: usize = 1_024;
+
+/// Converts an access list of a call request into storage keys to prefetch.
+pub(crate) fn access_list_keys(access_list: Option<&AccessList>) -> Vec<StorageKey> {
+    let Some(access_list) = access_list else {
+        return vec![];
+    };
+    access_list
+        .iter()
+        .flat_map(|item| {
+            let account = AccountTreeId::new(item.address);
+            item.storage_keys
+                .iter()
+                .map(move |&slot| StorageKey::new(account, slot))
+        })
+        .take(MAX_PREFETCHED_KEYS)
+        .collect()
 }
 
 #[derive(Debug, Clone)]
@@ -89,12 +138,33 @@ pub(crate) struct TransactionExecutionOutput {
 /// Executor of transactions.
 #[derive(Debug)]
 pub(crate) enum TransactionExecutor {
-    Real,
+    Real {
+        /// Optional cache for `eth_call` results on historical blocks.
+        eth_call_cache: Option<EthCallCache>,
+    },
     #[cfg(test)]
     Mock(MockTransactionExecutor),
 }
 
 impl TransactionExecutor {
+    /// Creates a real executor. If `eth_call_cache_size` is set and non-zero, `eth_call` results
+    /// will be cached.
+    pub fn real(eth_call_cache_size: Option<usize>) -> Self {
+        Self::Real {
+            eth_call_cache: eth_call_cache_size
+                .and_then(NonZeroUsize::new)
+                .map(EthCallCache::new),
+        }
+    }
+
+    pub(crate) fn eth_call_cache(&self) -> Option<&EthCallCache> {
+        match self {
+            Self::Real { eth_call_cache } => eth_call_cache.as_ref(),
+            #[cfg(test)]
+            Self::Mock(_) => None,
+        }
+    }
+
     /// This method assumes that (block with number `resolved_block_number` is present in DB)
     /// or (`block_id` is `pending` and block with number `resolved_block_number - 1` is present in DB)
     #[allow(clippy::too_many_arguments)]
@@ -124,8 +194,10 @@ impl TransactionExecutor {
             .as_ref()
             .map_or(0, |deps| deps.len() as u16);
 
+        // Spans are not propagated to blocking tasks automatically.
+        let parent_span = tracing::Span::current();
         let (published_bytecodes, execution_result) = tokio::task::spawn_blocking(move || {
-            let span = span!(Level::DEBUG, "execute_in_sandbox").entered();
+            let span = span!(parent: &parent_span, Level::DEBUG, "execute_in_sandbox").entered();
             let result = apply::apply_vm_in_sandbox(
                 vm_permit,
                 shared_args,
@@ -137,10 +209,16 @@ impl TransactionExecutor {
                 |vm, tx| {
                     let storage_invocation_tracer =
                         StorageInvocations::new(execution_args.missed_storage_invocation_limit);
+                    // The deadline is counted from the start of the VM execution, so that time spent waiting
+                    // for the VM permit or preparing the VM is not taken into account.
+                    let deadline_tracer = execution_args
+                        .execution_timeout
+                        .map(|timeout| ExecutionDeadline::new(Instant::now() + timeout));
                     let custom_tracers: Vec<_> = custom_tracers
                         .into_iter()
                         .map(|tracer| tracer.into_boxed())
                         .chain(vec![storage_invocation_tracer.into_tracer_pointer()])
+                        .chain(deadline_tracer.map(|tracer| tracer.into_tracer_pointer()))
                         .collect();
                     vm.inspect_transaction_with_bytecode_compression(
                         custom_tracers.into(),
@@ -173,11 +251,17 @@ impl TransactionExecutor {
         mut tx: L2Tx,
         block_args: BlockArgs,
         vm_execution_cache_misses_limit: Option<usize>,
+        vm_execution_timeout: Option<Duration>,
+        prefetched_keys: Vec<StorageKey>,
         custom_tracers: Vec<ApiTracer>,
     ) -> anyhow::Result<VmExecutionResultAndLogs> {
         let enforced_base_fee = tx.common_data.fee.max_fee_per_gas.as_u64();
-        let execution_args =
-            TxExecutionArgs::for_eth_call(enforced_base_fee, vm_execution_cache_misses_limit);
+        let execution_args = TxExecutionArgs::for_eth_call(
+            enforced_base_fee,
+            vm_execution_cache_misses_limit,
+            vm_execution_timeout,
+        )
+        .with_prefetched_keys(prefetched_keys);
 
         if tx.common_data.signature.is_empty() {
             tx.common_data.signature = PackedEthSignature::default().serialize_packed().into();
@@ -187,6 +271,19 @@ impl TransactionExecutor {
         // limiting the amount of gas the call can use.
         // We can't use `BLOCK_ERGS_LIMIT` here since the VM itself has some overhead.
         tx.common_data.fee.gas_limit = ETH_CALL_GAS_LIMIT.into();
+
+        // Calls with custom tracers are not cached since tracers may have side effects.
+        let cache = self.eth_call_cache().filter(|_| custom_tracers.is_empty());
+        let cache_key = if let Some(cache) = cache {
+            let cache_key = EthCallCacheKey::new(&connection_pool, &tx, &block_args).await?;
+            if let Some(result) = cache_key.as_ref().and_then(|key| cache.get(key)) {
+                return Ok(result);
+            }
+            cache_key
+        } else {
+            None
+        };
+
         let output = self
             .execute_tx_in_sandbox(
                 vm_permit,
@@ -199,6 +296,9 @@ impl TransactionExecutor {
                 custom_tracers,
             )
             .await?;
+        if let (Some(cache), Some(cache_key)) = (cache, cache_key) {
+            cache.insert(cache_key, &output.vm);
+        }
         Ok(output.vm)
     }
 }
@@ -0,0 +1,217 @@
+//! Health check reporting saturation of the VM sandbox.
+
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use serde::Serialize;
+use zksync_health_check::{async_trait, CheckHealth, Health, HealthStatus};
+
+/// Number of latest permit waits used to estimate wait time percentiles.
+const WAIT_WINDOW_SIZE: usize = 256;
+
+/// Statistics on VM permit acquisition shared between [`VmConcurrencyLimiter`](super::VmConcurrencyLimiter)
+/// and its health check.
+#[derive(Debug, Default)]
+pub(super) struct SaturationStats {
+    waiting: AtomicUsize,
+    rejected: AtomicU64,
+    recent_waits: Mutex<VecDeque<Duration>>,
+}
+
+impl SaturationStats {
+    /// Marks the start of waiting for a permit. The returned guard must be dropped when waiting ends.
+    pub fn start_waiting(&self) -> WaitGuard<'_> {
+        self.waiting.fetch_add(1, Ordering::Relaxed);
+        WaitGuard(self)
+    }
+
+    pub fn record_wait(&self, wait: Duration) {
+        let mut recent_waits = self.recent_waits.lock().unwrap();
+        if recent_waits.len() == WAIT_WINDOW_SIZE {
+            recent_waits.pop_front();
+        }
+        recent_waits.push_back(wait);
+    }
+
+    /// Records a request that has timed out waiting for a permit.
+    pub fn record_timeout(&self) {
+        self.rejected.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn wait_p95(&self) -> Duration {
+        let mut waits: Vec<_> = self.recent_waits.lock().unwrap().iter().copied().collect();
+        if waits.is_empty() {
+            return Duration::ZERO;
+        }
+        waits.sort_unstable();
+        let idx = (waits.len() * 95 + 99) / 100 - 1;
+        waits[idx]
+    }
+}
+
+/// Guard returned by [`SaturationStats::start_waiting()`].
+#[must_use = "Waiting ends when the guard is dropped"]
+#[derive(Debug)]
+pub(super) struct WaitGuard<'a>(&'a SaturationStats);
+
+impl Drop for WaitGuard<'_> {
+    fn drop(&mut self) {
+        self.0.waiting.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct SandboxHealthDetails {
+    max_concurrency: usize,
+    available_permits: usize,
+    waiting_requests: usize,
+    wait_p95_ms: u64,
+    rejected_requests: u64,
+}
+
+/// Health check for the VM sandbox of an API server. Reports available VM permits, the 95th percentile of
+/// the time spent waiting for a permit, and the number of requests rejected because they timed out waiting for a permit.
+/// The sandbox is reported as [degraded](HealthStatus::Degraded) if the share of VM permits in use reaches
+/// the saturation threshold, so that load balancers can drain overloaded replicas.
+#[derive(Debug)]
+pub struct VmConcurrencyHealthCheck {
+    name: &'static str,
+    limiter: Arc<tokio::sync::Semaphore>,
+    max_concurrency: usize,
+    saturation_threshold: f64,
+    stats: Arc<SaturationStats>,
+}
+
+impl VmConcurrencyHealthCheck {
+    pub(super) fn new(
+        name: &'static str,
+        limiter: Arc<tokio::sync::Semaphore>,
+        max_concurrency: usize,
+        saturation_threshold: f64,
+        stats: Arc<SaturationStats>,
+    ) -> Self {
+        Self {
+            name,
+            limiter,
+            max_concurrency,
+            saturation_threshold,
+            stats,
+        }
+    }
+
+    fn details(&self) -> SandboxHealthDetails {
+        SandboxHealthDetails {
+            max_concurrency: self.max_concurrency,
+            available_permits: self.limiter.available_permits(),
+            waiting_requests: self.stats.waiting.load(Ordering::Relaxed),
+            wait_p95_ms: self.stats.wait_p95().as_millis() as u64,
+            rejected_requests: self.stats.rejected.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[async_trait]
+impl CheckHealth for VmConcurrencyHealthCheck {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    async fn check_health(&self) -> Health {
+        let details = self.details();
+        let used_permits = details
+            .max_concurrency
+            .saturating_sub(details.available_permits);
+        let saturation = used_permits as f64 / details.max_concurrency.max(1) as f64;
+        let status = if saturation >= self.saturation_threshold {
+            HealthStatus::Degraded
+        } else {
+            HealthStatus::Ready
+        };
+        Health::from(status).with_details(details)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use super::*;
+    use crate::api_server::execution_sandbox::{VmConcurrencyLimiter, VmPermitError};
+
+    #[test]
+    fn computing_wait_p95() {
+        let stats = SaturationStats::default();
+        assert_eq!(stats.wait_p95(), Duration::ZERO);
+        for i in 1..=100 {
+            stats.record_wait(Duration::from_millis(i));
+        }
+        assert_eq!(stats.wait_p95(), Duration::from_millis(95));
+
+        // Old waits are evicted from the window.
+        for _ in 0..WAIT_WINDOW_SIZE {
+            stats.record_wait(Duration::from_millis(1));
+        }
+        assert_eq!(stats.wait_p95(), Duration::from_millis(1));
+    }
+
+    #[tokio::test]
+    async fn reporting_sandbox_saturation() {
+        let (limiter, barrier) = VmConcurrencyLimiter::new(2);
+        let limiter = limiter.with_permit_timeout(Some(Duration::from_millis(10)));
+        let health_check = limiter.health_check("api_sandbox");
+
+        let health = health_check.check_health().await;
+        assert_matches!(health.status(), HealthStatus::Ready);
+        let details = health.details().unwrap();
+        assert_eq!(details["max_concurrency"], 2);
+        assert_eq!(details["available_permits"], 2);
+
+        let first_permit = limiter.acquire().await.unwrap();
+        let health = health_check.check_health().await;
+        assert_matches!(health.status(), HealthStatus::Ready);
+        let second_permit = limiter.acquire().await.unwrap();
+        let health = health_check.check_health().await;
+        assert_matches!(health.status(), HealthStatus::Degraded);
+        assert!(!health.status().is_healthy());
+        assert_eq!(health.details().unwrap()["available_permits"], 0);
+
+        // Requests waiting for a permit must time out and be counted as rejected.
+        let err = limiter.acquire().await.unwrap_err();
+        assert_matches!(err, VmPermitError::Timeout(_));
+        let health = health_check.check_health().await;
+        assert_eq!(health.details().unwrap()["rejected_requests"], 1);
+        assert_eq!(health.details().unwrap()["waiting_requests"], 0);
+
+        drop((first_permit, second_permit));
+        let health = health_check.check_health().await;
+        assert_matches!(health.status(), HealthStatus::Ready);
+
+        // Shutdown is not counted as a rejection.
+        barrier.close();
+        let err = limiter.acquire().await.unwrap_err();
+        assert_matches!(err, VmPermitError::Closed);
+        let health = health_check.check_health().await;
+        assert_eq!(health.details().unwrap()["rejected_requests"], 1);
+    }
+
+    #[tokio::test]
+    async fn configuring_saturation_threshold() {
+        let (limiter, _barrier) = VmConcurrencyLimiter::new(4);
+        let limiter = limiter.with_saturation_threshold(0.5);
+        let health_check = limiter.health_check("api_sandbox");
+
+        let first_permit = limiter.acquire().await.unwrap();
+        let health = health_check.check_health().await;
+        assert_matches!(health.status(), HealthStatus::Ready);
+        let second_permit = limiter.acquire().await.unwrap();
+        let health = health_check.check_health().await;
+        assert_matches!(health.status(), HealthStatus::Degraded);
+        drop((first_permit, second_permit));
+    }
+}
@@ -5,30 +5,37 @@ use tokio::runtime::Handle;
 use zksync_dal::{Connection, Core, CoreDal};
 use zksync_state::PostgresStorageCaches;
 use zksync_types::{
-    api, fee_model::BatchFeeInput, AccountTreeId, Address, L1BatchNumber, L2ChainId,
-    MiniblockNumber,
+    api, fee_model::BatchFeeInput, Address, L1BatchNumber, L2ChainId, MiniblockNumber, H256,
 };
 
-use self::vm_metrics::SandboxStage;
+pub use self::health::VmConcurrencyHealthCheck;
 pub(super) use self::{
     error::SandboxExecutionError,
-    execute::{TransactionExecutor, TxExecutionArgs},
+    eth_call_cache::EthCallCache,
+    execute::{access_list_keys, TransactionExecutor, TxExecutionArgs},
+    storage_view_pool::StorageViewPool,
     tracers::ApiTracer,
     validate::ValidationError,
-    vm_metrics::{SubmitTxStage, SANDBOX_METRICS},
+    vm_env_pool::VmEnvPool,
+    vm_metrics::{CacheLookup, FutureTxEvent, SubmitTxPrecheck, SubmitTxStage, SANDBOX_METRICS},
 };
-use super::tx_sender::MultiVMBaseSystemContracts;
+use self::{health::SaturationStats, vm_metrics::SandboxStage};
+use crate::state_keeper::FeeAccountSelector;
 
 // Note: keep the modules private, and instead re-export functions that make public interface.
 mod apply;
 mod error;
+mod eth_call_cache;
 mod execute;
+mod health;
+mod storage_view_pool;
 #[cfg(test)]
 pub(super) mod testonly;
 #[cfg(test)]
 mod tests;
 mod tracers;
 mod validate;
+mod vm_env_pool;
 mod vm_metrics;
 
 /// Permit to invoke VM code.
@@ -48,6 +55,17 @@ impl VmPermit {
     }
 }
 
+/// Error acquiring a [`VmPermit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum VmPermitError {
+    /// The VM concurrency limiter is closed because the server is shutting down.
+    #[error("VM concurrency limiter is closed")]
+    Closed,
+    /// A permit couldn't be obtained in time because the server is overloaded.
+    #[error("timed out waiting for a VM permit after {0:?}")]
+    Timeout(Duration),
+}
+
 /// Barrier-like synchronization primitive allowing to close a [`VmConcurrencyLimiter`] it's attached to
 /// so that it doesn't issue new permits, and to wait for all permits to drop.
 #[derive(Debug, Clone)]
@@ -100,10 +118,16 @@ impl VmConcurrencyBarrier {
 pub struct VmConcurrencyLimiter {
     /// Semaphore that limits the number of concurrent VM executions.
     limiter: Arc<tokio::sync::Semaphore>,
+    max_concurrency: usize,
+    permit_timeout: Option<Duration>,
+    saturation_threshold: f64,
+    stats: Arc<SaturationStats>,
     rt_handle: Handle,
 }
 
 impl VmConcurrencyLimiter {
+    const DEFAULT_SATURATION_THRESHOLD: f64 = 0.9;
+
     /// Creates a limiter together with a barrier allowing to control its shutdown.
     pub fn new(max_concurrency: usize) -> (Self, VmConcurrencyBarrier) {
         tracing::info!(
@@ -113,6 +137,10 @@ impl VmConcurrencyLimiter {
 
         let this = Self {
             limiter: Arc::clone(&limiter),
+            max_concurrency,
+            permit_timeout: None,
+            saturation_threshold: Self::DEFAULT_SATURATION_THRESHOLD,
+            stats: Arc::default(),
             rt_handle: Handle::current(),
         };
         let barrier = VmConcurrencyBarrier {
@@ -122,17 +150,45 @@ impl VmConcurrencyLimiter {
         (this, barrier)
     }
 
+    /// Sets the timeout for waiting for a permit. If not set, [`Self::acquire()`] waits for a permit indefinitely.
+    pub fn with_permit_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.permit_timeout = timeout;
+        self
+    }
+
+    /// Sets the share of used permits starting from which the [health check](Self::health_check()) reports
+    /// the limiter as degraded. The default value is 0.9.
+    pub fn with_saturation_threshold(mut self, threshold: f64) -> Self {
+        self.saturation_threshold = threshold;
+        self
+    }
+
     /// Waits until there is a free slot in the concurrency limiter.
     /// Returns a permit that should be dropped when the VM execution is finished.
-    pub async fn acquire(&self) -> Option<VmPermit> {
+    pub async fn acquire(&self) -> Result<VmPermit, VmPermitError> {
         let available_permits = self.limiter.available_permits();
         SANDBOX_METRICS
             .sandbox_execution_permits
             .observe(available_permits);
 
         let latency = SANDBOX_METRICS.sandbox[&SandboxStage::VmConcurrencyLimiterAcquire].start();
-        let permit = Arc::clone(&self.limiter).acquire_owned().await.ok()?;
+        let wait_guard = self.stats.start_waiting();
+        let permit_future = Arc::clone(&self.limiter).acquire_owned();
+        let permit = if let Some(timeout) = self.permit_timeout {
+            let Ok(permit) = tokio::time::timeout(timeout, permit_future).await else {
+                drop(wait_guard);
+                self.stats.record_timeout();
+                tracing::debug!("Timed out waiting for a VM permit after {timeout:?}");
+                return Err(VmPermitError::Timeout(timeout));
+            };
+            permit
+        } else {
+            permit_future.await
+        };
+        drop(wait_guard);
+        let permit = permit.map_err(|_| VmPermitError::Closed)?;
         let elapsed = latency.observe();
+        self.stats.record_wait(elapsed);
         // We don't want to emit too many logs.
         if elapsed > Duration::from_millis(10) {
             tracing::debug!(
@@ -140,11 +196,22 @@ impl VmConcurrencyLimiter {
             );
         }
 
-        Some(VmPermit {
+        Ok(VmPermit {
             rt_handle: self.rt_handle.clone(),
             _permit: Arc::new(permit),
         })
     }
+
+    /// Returns a health check reporting saturation of this limiter under the specified component name.
+    pub fn health_check(&self, name: &'static str) -> VmConcurrencyHealthCheck {
+        VmConcurrencyHealthCheck::new(
+            name,
+            Arc::clone(&self.limiter),
+            self.max_concurrency,
+            self.saturation_threshold,
+            Arc::clone(&self.stats),
+        )
+    }
 }
 
 async fn get_pending_state(
@@ -163,26 +230,31 @@ async fn get_pending_state(
 /// Arguments for VM execution not specific to a particular transaction.
 #[derive(Debug, Clone)]
 pub(crate) struct TxSharedArgs {
-    pub operator_account: AccountTreeId,
+    /// Selects the operator account receiving fees based on the L1 batch in which a transaction is executed.
+    pub operator_account: FeeAccountSelector,
     pub fee_input: BatchFeeInput,
-    pub base_system_contracts: MultiVMBaseSystemContracts,
+    pub vm_env_pool: Arc<VmEnvPool>,
+    pub storage_view_pool: Arc<StorageViewPool>,
     pub caches: PostgresStorageCaches,
     pub validation_computational_gas_limit: u32,
     pub chain_id: L2ChainId,
     pub whitelisted_tokens_for_aa: Vec<Address>,
+    pub aa_validation_rules: api::AaValidationRules,
 }
 
 impl TxSharedArgs {
     #[cfg(test)]
-    pub fn mock(base_system_contracts: MultiVMBaseSystemContracts) -> Self {
+    pub fn mock(base_system_contracts: super::tx_sender::MultiVMBaseSystemContracts) -> Self {
         Self {
-            operator_account: AccountTreeId::default(),
+            operator_account: FeeAccountSelector::default(),
             fee_input: BatchFeeInput::l1_pegged(55, 555),
-            base_system_contracts,
+            vm_env_pool: Arc::new(VmEnvPool::new(base_system_contracts)),
+            storage_view_pool: Arc::default(),
             caches: PostgresStorageCaches::new(1, 1),
             validation_computational_gas_limit: u32::MAX,
             chain_id: L2ChainId::default(),
             whitelisted_tokens_for_aa: Vec::new(),
+            aa_validation_rules: api::AaValidationRules::default(),
         }
     }
 }
@@ -234,6 +306,8 @@ impl BlockStartInfo {
 pub(crate) enum BlockArgsError {
     #[error("Block is pruned; first retained block is {0}")]
     Pruned(MiniblockNumber),
+    #[error("Block with hash {0:?} was reverted during a chain reorganization")]
+    NonCanonical(H256),
     #[error("Block is missing, but can appear in the future")]
     Missing,
     #[error("Database error")]
@@ -281,6 +355,18 @@ impl BlockArgs {
             .await
             .with_context(|| format!("failed resolving block ID {block_id:?}"))?;
         let Some(resolved_block_number) = resolved_block_number else {
+            if let api::BlockId::Hash(hash) = block_id {
+                let is_reverted = connection
+                    .blocks_web3_dal()
+                    .is_reverted_miniblock_hash(hash)
+                    .await
+                    .with_context(|| {
+                        format!("failed checking whether block {hash:?} is reverted")
+                    })?;
+                if is_reverted {
+                    return Err(BlockArgsError::NonCanonical(hash));
+                }
+            }
             return Err(BlockArgsError::Missing);
         };
 
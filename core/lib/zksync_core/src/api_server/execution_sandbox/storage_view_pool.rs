@@ -0,0 +1,79 @@
+//! Pool of reusable storage view allocations used by the sandbox.
+
+use std::{rc::Rc, sync::Mutex};
+
+use zksync_state::{StoragePtr, StorageView, StorageViewBuffers};
+
+use super::vm_metrics::{CacheLookup, SANDBOX_METRICS};
+
+/// Pool of [`StorageViewBuffers`] reused among sandbox invocations. Buffers are taken from the pool when
+/// a storage view is created for a sandboxed VM, and are returned to the pool once the VM is dropped.
+///
+/// The number of pooled buffers is implicitly bounded by the maximum number of concurrently executing VMs.
+/// Buffers that have grown too large (e.g., after executing a transaction touching lots of storage slots) are not
+/// returned to the pool, so that a single outlier execution doesn't pin a large amount of memory.
+#[derive(Debug)]
+pub(crate) struct StorageViewPool {
+    buffers: Mutex<Vec<StorageViewBuffers>>,
+    max_retained_capacity: usize,
+}
+
+impl Default for StorageViewPool {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_MAX_RETAINED_CAPACITY)
+    }
+}
+
+impl StorageViewPool {
+    /// Default maximum number of entries in buffers retained by the pool.
+    const DEFAULT_MAX_RETAINED_CAPACITY: usize = 16_384;
+
+    pub fn new(max_retained_capacity: usize) -> Self {
+        Self {
+            buffers: Mutex::default(),
+            max_retained_capacity,
+        }
+    }
+
+    /// Takes buffers from the pool, or creates new empty buffers if the pool is empty.
+    pub fn take(&self) -> StorageViewBuffers {
+        let mut pooled = self.buffers.lock().expect("storage view pool is poisoned");
+        let buffers = pooled.pop();
+        SANDBOX_METRICS.storage_view_pool_size.set(pooled.len());
+        drop(pooled);
+
+        if let Some(buffers) = buffers {
+            SANDBOX_METRICS.storage_view_pool_lookups[&CacheLookup::Hit].inc();
+            buffers
+        } else {
+            SANDBOX_METRICS.storage_view_pool_lookups[&CacheLookup::Miss].inc();
+            StorageViewBuffers::default()
+        }
+    }
+
+    /// Returns buffers of the provided storage view to the pool. The view is not recycled if it is still shared
+    /// (i.e., the VM using it is not dropped yet).
+    pub fn recycle<S>(&self, storage_view: StoragePtr<StorageView<S>>) {
+        let Ok(storage_view) = Rc::try_unwrap(storage_view) else {
+            tracing::debug!("Storage view is still shared; not recycling its buffers");
+            return;
+        };
+        let buffers = storage_view.into_inner().into_buffers();
+        if buffers.capacity() > self.max_retained_capacity {
+            SANDBOX_METRICS.storage_view_pool_discarded_buffers.inc();
+            return;
+        }
+
+        let mut pooled = self.buffers.lock().expect("storage view pool is poisoned");
+        pooled.push(buffers);
+        SANDBOX_METRICS.storage_view_pool_size.set(pooled.len());
+    }
+
+    #[cfg(test)]
+    pub(super) fn len(&self) -> usize {
+        self.buffers
+            .lock()
+            .expect("storage view pool is poisoned")
+            .len()
+    }
+}
@@ -1,11 +1,23 @@
 //! Tests for the VM execution sandbox.
 
+use std::{collections::HashSet, num::NonZeroUsize};
+
 use assert_matches::assert_matches;
+use multivm::interface::{ExecutionResult, Halt, TxExecutionMode, VmExecutionResultAndLogs};
+use once_cell::sync::OnceCell;
 use zksync_dal::ConnectionPool;
+use zksync_state::{InMemoryStorage, InvalidateCache, RevertedBlocks, StorageView};
+use zksync_types::{AccountTreeId, ProtocolVersionId, StorageKey, Transaction, H256};
 
 use super::*;
 use crate::{
-    api_server::{execution_sandbox::apply::apply_vm_in_sandbox, tx_sender::ApiContracts},
+    api_server::{
+        execution_sandbox::{
+            apply::apply_vm_in_sandbox,
+            eth_call_cache::{EthCallCache, EthCallCacheKey},
+        },
+        tx_sender::ApiContracts,
+    },
     genesis::{insert_genesis_batch, GenesisParams},
     utils::testonly::{create_l2_transaction, create_miniblock, prepare_recovery_snapshot},
 };
@@ -65,6 +77,44 @@ async fn creating_block_args() {
     assert_matches!(err, BlockArgsError::Missing);
 }
 
+#[tokio::test]
+async fn creating_block_args_for_reverted_block() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let miniblock = create_miniblock(1);
+    storage
+        .blocks_dal()
+        .insert_miniblock(&miniblock)
+        .await
+        .unwrap();
+    let start_info = BlockStartInfo::new(&mut storage).await.unwrap();
+
+    let block_id = api::BlockId::Hash(miniblock.hash);
+    let block_args = BlockArgs::new(&mut storage, block_id, start_info)
+        .await
+        .unwrap();
+    assert_eq!(block_args.resolved_block_number, MiniblockNumber(1));
+
+    storage
+        .blocks_dal()
+        .delete_miniblocks(MiniblockNumber(0))
+        .await
+        .unwrap();
+    let err = BlockArgs::new(&mut storage, block_id, start_info)
+        .await
+        .unwrap_err();
+    assert_matches!(err, BlockArgsError::NonCanonical(hash) if hash == miniblock.hash);
+
+    let unknown_block = api::BlockId::Hash(H256::repeat_byte(0xff));
+    let err = BlockArgs::new(&mut storage, unknown_block, start_info)
+        .await
+        .unwrap_err();
+    assert_matches!(err, BlockArgsError::Missing);
+}
+
 #[tokio::test]
 async fn creating_block_args_after_snapshot_recovery() {
     let pool = ConnectionPool::<Core>::test_pool().await;
@@ -197,3 +247,389 @@ async fn test_instantiating_vm(pool: ConnectionPool<Core>, block_args: BlockArgs
     .expect("VM instantiation panicked")
     .expect("VM instantiation errored");
 }
+
+#[test]
+fn vm_env_pool_reuses_environments() {
+    let pool = VmEnvPool::new(ApiContracts::load_from_disk().eth_call);
+    let chain_id = L2ChainId::from(270);
+    let env = pool.env(
+        ProtocolVersionId::latest(),
+        TxExecutionMode::EthCall,
+        123,
+        chain_id,
+    );
+    assert_eq!(env.system_env.version, ProtocolVersionId::latest());
+    assert_eq!(env.system_env.execution_mode, TxExecutionMode::EthCall);
+    assert_eq!(
+        env.system_env.default_validation_computational_gas_limit,
+        123
+    );
+    assert_eq!(env.system_env.chain_id, chain_id);
+    assert_eq!(pool.len(), 1);
+
+    let other_env = pool.env(
+        ProtocolVersionId::latest(),
+        TxExecutionMode::EstimateFee,
+        456,
+        L2ChainId::default(),
+    );
+    assert_eq!(
+        other_env.system_env.execution_mode,
+        TxExecutionMode::EstimateFee
+    );
+    assert_eq!(
+        other_env
+            .system_env
+            .default_validation_computational_gas_limit,
+        456
+    );
+    assert_eq!(other_env.system_env.chain_id, L2ChainId::default());
+    assert_eq!(pool.len(), 1);
+    // Base system contracts must be shared rather than copied.
+    assert!(Arc::ptr_eq(
+        &env.system_env.base_system_smart_contracts,
+        &other_env.system_env.base_system_smart_contracts
+    ));
+    // ...as well as the prepared bootloader memory.
+    assert!(Arc::ptr_eq(
+        env.bootloader_memory.as_ref().unwrap(),
+        other_env.bootloader_memory.as_ref().unwrap()
+    ));
+
+    let old_env = pool.env(
+        ProtocolVersionId::Version20,
+        TxExecutionMode::VerifyExecute,
+        123,
+        chain_id,
+    );
+    assert_eq!(pool.len(), 2);
+    // Memory is only prepared for the latest VM.
+    assert!(old_env.bootloader_memory.is_none());
+}
+
+#[tokio::test]
+async fn storage_view_pool_reuses_buffers() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let block_args = BlockArgs::pending(&mut storage).await.unwrap();
+    drop(storage);
+
+    let shared_args = TxSharedArgs::mock(ApiContracts::load_from_disk().estimate_gas);
+    let storage_view_pool = shared_args.storage_view_pool.clone();
+    let (vm_concurrency_limiter, _) = VmConcurrencyLimiter::new(1);
+    for _ in 0..2 {
+        let vm_permit = vm_concurrency_limiter.acquire().await.unwrap();
+        let shared_args = shared_args.clone();
+        let pool = pool.clone();
+        let transaction: Transaction = create_l2_transaction(10, 100).into();
+        tokio::task::spawn_blocking(move || {
+            apply_vm_in_sandbox(
+                vm_permit,
+                shared_args,
+                true,
+                &TxExecutionArgs::for_gas_estimate(None, &transaction, 123),
+                &pool,
+                transaction.clone(),
+                block_args,
+                |_, _| (),
+            )
+        })
+        .await
+        .expect("VM instantiation panicked")
+        .expect("VM instantiation errored");
+
+        // Buffers should be returned to the pool after each execution, and taken from it on the next one.
+        assert_eq!(storage_view_pool.len(), 1);
+    }
+
+    let buffers = storage_view_pool.take();
+    assert!(buffers.capacity() > 0);
+    assert_eq!(storage_view_pool.len(), 0);
+
+    // Buffers exceeding the retained capacity must be discarded.
+    let small_pool = StorageViewPool::new(0);
+    let storage_view = StorageView::with_buffers(InMemoryStorage::default(), buffers);
+    small_pool.recycle(storage_view.to_rc_ptr());
+    assert_eq!(small_pool.len(), 0);
+}
+
+fn mock_execution_result(result: ExecutionResult) -> VmExecutionResultAndLogs {
+    VmExecutionResultAndLogs {
+        result,
+        logs: Default::default(),
+        statistics: Default::default(),
+        refunds: Default::default(),
+    }
+}
+
+#[tokio::test]
+async fn caching_eth_call_results() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    storage
+        .blocks_dal()
+        .insert_miniblock(&create_miniblock(1))
+        .await
+        .unwrap();
+
+    let tx = create_l2_transaction(10, 100);
+    let pending_block_args = BlockArgs::pending(&mut storage).await.unwrap();
+    let key = EthCallCacheKey::new(&pool, &tx, &pending_block_args)
+        .await
+        .unwrap();
+    assert!(key.is_none(), "{key:?}");
+
+    let start_info = BlockStartInfo::new(&mut storage).await.unwrap();
+    let block_args = BlockArgs::new(&mut storage, api::BlockId::Number(1.into()), start_info)
+        .await
+        .unwrap();
+    let key = EthCallCacheKey::new(&pool, &tx, &block_args)
+        .await
+        .unwrap()
+        .expect("call on a historical block is not cacheable");
+
+    let cache = EthCallCache::new(NonZeroUsize::new(10).unwrap());
+    assert!(cache.get(&key).is_none());
+    let halted = mock_execution_result(ExecutionResult::Halt {
+        reason: Halt::TracerCustom("limit exceeded".to_owned()),
+    });
+    cache.insert(key.clone(), &halted);
+    assert!(cache.get(&key).is_none());
+
+    let success = mock_execution_result(ExecutionResult::Success {
+        output: vec![1, 2, 3],
+    });
+    cache.insert(key.clone(), &success);
+    let cached = cache.get(&key).unwrap();
+    assert_eq!(cached.result, success.result);
+
+    // Reverting the miniblock and replacing it with another one must invalidate the cache entry.
+    storage
+        .blocks_dal()
+        .delete_miniblocks(MiniblockNumber(0))
+        .await
+        .unwrap();
+    let mut new_miniblock = create_miniblock(1);
+    new_miniblock.hash = H256::repeat_byte(0xff);
+    storage
+        .blocks_dal()
+        .insert_miniblock(&new_miniblock)
+        .await
+        .unwrap();
+    let new_key = EthCallCacheKey::new(&pool, &tx, &block_args)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_ne!(new_key, key);
+    assert!(cache.get(&new_key).is_none());
+
+    cache.insert(new_key.clone(), &success);
+    assert!(cache.get(&new_key).is_some());
+    cache.invalidate(RevertedBlocks {
+        last_l1_batch_to_keep: L1BatchNumber(0),
+        last_miniblock_to_keep: MiniblockNumber(0),
+    });
+    assert!(cache.get(&new_key).is_none());
+}
+
+#[tokio::test]
+async fn aborting_execution_after_deadline() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let block_args = BlockArgs::pending(&mut storage).await.unwrap();
+    drop(storage);
+
+    let (vm_concurrency_limiter, _) = VmConcurrencyLimiter::new(1);
+    let vm_permit = vm_concurrency_limiter.acquire().await.unwrap();
+    let tx = create_l2_transaction(10, 100);
+    let execution_args = TxExecutionArgs {
+        execution_timeout: Some(Duration::ZERO),
+        ..TxExecutionArgs::for_validation(&tx)
+    };
+    let output = TransactionExecutor::real(None)
+        .execute_tx_in_sandbox(
+            vm_permit,
+            TxSharedArgs::mock(ApiContracts::load_from_disk().eth_call),
+            false,
+            execution_args,
+            pool.clone(),
+            tx.into(),
+            block_args,
+            vec![],
+        )
+        .await
+        .unwrap();
+    assert_matches!(
+        output.vm.result,
+        ExecutionResult::Halt { reason: Halt::TracerCustom(msg) } if msg.contains("deadline")
+    );
+
+    // The VM permit must be released after the execution is aborted.
+    assert!(vm_concurrency_limiter.acquire().await.is_ok());
+}
+
+#[tokio::test]
+async fn recording_accessed_storage() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let block_args = BlockArgs::pending(&mut storage).await.unwrap();
+
+    let (vm_concurrency_limiter, _) = VmConcurrencyLimiter::new(1);
+    let vm_permit = vm_concurrency_limiter.acquire().await.unwrap();
+    let accessed_storage = Arc::new(OnceCell::new());
+    let tracers = vec![ApiTracer::AccessedStorage(accessed_storage.clone())];
+    TransactionExecutor::real(None)
+        .execute_tx_eth_call(
+            vm_permit,
+            TxSharedArgs::mock(ApiContracts::load_from_disk().eth_call),
+            pool.clone(),
+            create_l2_transaction(10, 100),
+            block_args,
+            None,
+            None,
+            vec![],
+            tracers,
+        )
+        .await
+        .unwrap();
+
+    let accessed_storage = accessed_storage
+        .get()
+        .expect("tracer didn't record storage");
+    assert!(!accessed_storage.is_empty());
+    // All recorded values must be read from the storage state at the start of the call.
+    for (key, value) in accessed_storage {
+        let stored_value = storage.storage_web3_dal().get_value(key).await.unwrap();
+        assert_eq!(stored_value, *value, "{key:?}");
+    }
+}
+
+#[tokio::test]
+async fn prefetching_storage_keeps_execution_results() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let block_args = BlockArgs::pending(&mut storage).await.unwrap();
+    drop(storage);
+
+    let (vm_concurrency_limiter, _) = VmConcurrencyLimiter::new(1);
+    let tx = create_l2_transaction(10, 100);
+    let mut outputs = vec![];
+    let mut prefetched_keys = vec![];
+    for _ in 0..2 {
+        let vm_permit = vm_concurrency_limiter.acquire().await.unwrap();
+        let accessed_storage = Arc::new(OnceCell::new());
+        let tracers = vec![ApiTracer::AccessedStorage(accessed_storage.clone())];
+        let output = TransactionExecutor::real(None)
+            .execute_tx_eth_call(
+                vm_permit,
+                TxSharedArgs::mock(ApiContracts::load_from_disk().eth_call),
+                pool.clone(),
+                tx.clone(),
+                block_args,
+                None,
+                None,
+                prefetched_keys,
+                tracers,
+            )
+            .await
+            .unwrap();
+        let accessed_storage = Arc::try_unwrap(accessed_storage)
+            .unwrap()
+            .into_inner()
+            .expect("tracer didn't record storage");
+        // Prefetch the storage accessed during the first call for the second one.
+        prefetched_keys = accessed_storage.keys().copied().collect();
+        outputs.push((output.result, accessed_storage));
+    }
+
+    assert_eq!(outputs[0].0, outputs[1].0);
+    assert_eq!(outputs[0].1, outputs[1].1);
+}
+
+#[test]
+fn converting_access_list_to_prefetched_keys() {
+    use zksync_types::web3::types::AccessListItem;
+
+    assert!(access_list_keys(None).is_empty());
+
+    let address = Address::repeat_byte(1);
+    let access_list = vec![
+        AccessListItem {
+            address,
+            storage_keys: vec![H256::zero(), H256::repeat_byte(1)],
+        },
+        AccessListItem {
+            address: Address::repeat_byte(2),
+            storage_keys: vec![],
+        },
+    ];
+    let keys = access_list_keys(Some(&access_list));
+    assert_eq!(
+        keys,
+        [
+            StorageKey::new(AccountTreeId::new(address), H256::zero()),
+            StorageKey::new(AccountTreeId::new(address), H256::repeat_byte(1)),
+        ]
+    );
+
+    let large_access_list = vec![AccessListItem {
+        address,
+        storage_keys: vec![H256::zero(); 2 * execute::MAX_PREFETCHED_KEYS],
+    }];
+    let keys = access_list_keys(Some(&large_access_list));
+    assert_eq!(keys.len(), execute::MAX_PREFETCHED_KEYS);
+}
+
+#[tokio::test]
+async fn validation_params_with_custom_rules() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    let token = Address::repeat_byte(0x10);
+    let trusted_contract = Address::repeat_byte(0x20);
+    let custom_slot = H256::from_low_u64_be(0xff);
+    let rules = api::AaValidationRules {
+        trusted_token_slots: vec![custom_slot],
+        trusted_addresses: vec![trusted_contract],
+    };
+    let tx = create_l2_transaction(10, 100);
+    let params = validate::get_validation_params(&mut storage, &tx, u32::MAX, &[token], &rules)
+        .await
+        .unwrap();
+
+    assert!(params.trusted_slots.contains(&(token, 0xff.into())));
+    for &slot in zksync_types::TRUSTED_TOKEN_SLOTS.iter() {
+        assert!(params.trusted_slots.contains(&(token, slot)));
+    }
+    assert_eq!(params.trusted_addresses, HashSet::from([trusted_contract]));
+
+    let params = validate::get_validation_params(
+        &mut storage,
+        &tx,
+        u32::MAX,
+        &[token],
+        &api::AaValidationRules::default(),
+    )
+    .await
+    .unwrap();
+    assert!(!params.trusted_slots.contains(&(token, 0xff.into())));
+    assert!(params.trusted_addresses.is_empty());
+}
@@ -1,14 +1,19 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
-use multivm::{tracers::CallTracer, vm_latest::HistoryMode, MultiVMTracer, MultiVmTracerPointer};
+use multivm::{
+    tracers::{AccessedStorageTracer, CallTracer},
+    vm_latest::HistoryMode,
+    MultiVMTracer, MultiVmTracerPointer,
+};
 use once_cell::sync::OnceCell;
 use zksync_state::WriteStorage;
-use zksync_types::vm_trace::Call;
+use zksync_types::{vm_trace::Call, StorageKey, StorageValue};
 
 /// Custom tracers supported by our API
 #[derive(Debug)]
 pub(crate) enum ApiTracer {
     CallTracer(Arc<OnceCell<Vec<Call>>>),
+    AccessedStorage(Arc<OnceCell<HashMap<StorageKey, StorageValue>>>),
 }
 
 impl ApiTracer {
@@ -20,6 +25,9 @@ impl ApiTracer {
     ) -> MultiVmTracerPointer<S, H> {
         match self {
             ApiTracer::CallTracer(tracer) => CallTracer::new(tracer.clone()).into_tracer_pointer(),
+            ApiTracer::AccessedStorage(result) => {
+                AccessedStorageTracer::new(result).into_tracer_pointer()
+            }
         }
     }
 }
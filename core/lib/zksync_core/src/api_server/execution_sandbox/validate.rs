@@ -11,7 +11,11 @@ use multivm::{
     MultiVMTracer,
 };
 use zksync_dal::{Connection, ConnectionPool, Core, CoreDal};
-use zksync_types::{l2::L2Tx, Address, Transaction, TRUSTED_ADDRESS_SLOTS, TRUSTED_TOKEN_SLOTS};
+use zksync_types::{
+    api::AaValidationRules, l2::L2Tx, Address, Transaction, TRUSTED_ADDRESS_SLOTS,
+    TRUSTED_TOKEN_SLOTS,
+};
+use zksync_utils::h256_to_u256;
 
 use super::{
     apply,
@@ -55,6 +59,7 @@ impl TransactionExecutor {
             &tx,
             computational_gas_limit,
             &shared_args.whitelisted_tokens_for_aa,
+            &shared_args.aa_validation_rules,
         )
         .await
         .context("failed getting validation params")?;
@@ -63,8 +68,10 @@ impl TransactionExecutor {
         let execution_args = TxExecutionArgs::for_validation(&tx);
         let tx: Transaction = tx.into();
 
+        // Spans are not propagated to blocking tasks automatically.
+        let parent_span = tracing::Span::current();
         let validation_result = tokio::task::spawn_blocking(move || {
-            let span = tracing::debug_span!("validate_in_sandbox").entered();
+            let span = tracing::debug_span!(parent: &parent_span, "validate_in_sandbox").entered();
             let result = apply::apply_vm_in_sandbox(
                 vm_permit,
                 shared_args,
@@ -120,11 +127,14 @@ impl TransactionExecutor {
 /// Some slots can be marked as "trusted". That is needed for slots which can not be
 /// trusted to change between validation and execution in general case, but
 /// sometimes we can safely rely on them to not change often.
-async fn get_validation_params(
+///
+/// Besides the standard token slots, trusted slots and addresses can be provided in `rules`.
+pub(super) async fn get_validation_params(
     connection: &mut Connection<'_, Core>,
     tx: &L2Tx,
     computational_gas_limit: u32,
     whitelisted_tokens_for_aa: &[Address],
+    rules: &AaValidationRules,
 ) -> anyhow::Result<ValidationTracerParams> {
     let method_latency = EXECUTION_METRICS.get_validation_params.start();
     let user_address = tx.common_data.initiator_address;
@@ -140,13 +150,22 @@ async fn get_validation_params(
     EXECUTION_METRICS.tokens_amount.set(all_tokens.len());
 
     let span = tracing::debug_span!("compute_trusted_slots_for_validation").entered();
+    let custom_token_slots: Vec<_> = rules
+        .trusted_token_slots
+        .iter()
+        .map(|&slot| h256_to_u256(slot))
+        .collect();
     let trusted_slots: HashSet<_> = all_tokens
         .iter()
-        .flat_map(|&token| TRUSTED_TOKEN_SLOTS.iter().map(move |&slot| (*token, slot)))
+        .flat_map(|&token| {
+            TRUSTED_TOKEN_SLOTS
+                .iter()
+                .chain(&custom_token_slots)
+                .map(move |&slot| (*token, slot))
+        })
         .collect();
 
-    // We currently don't support any specific trusted addresses.
-    let trusted_addresses = HashSet::new();
+    let trusted_addresses: HashSet<_> = rules.trusted_addresses.iter().copied().collect();
 
     // The slots the value of which will be added as allowed address on the fly.
     // Required for working with transparent proxies.
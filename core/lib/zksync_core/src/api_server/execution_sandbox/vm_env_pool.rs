@@ -0,0 +1,117 @@
+//! Pool of pre-initialized VM system environments used by the sandbox.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use multivm::{
+    interface::{SystemEnv, TxExecutionMode},
+    vm_latest::{
+        constants::BATCH_COMPUTATIONAL_GAS_LIMIT, HistoryDisabled, PreparedBootloaderMemory,
+    },
+    VmVersion,
+};
+use zksync_system_constants::ZKPORTER_IS_AVAILABLE;
+use zksync_types::{L2ChainId, ProtocolVersionId};
+
+use super::vm_metrics::{CacheLookup, SANDBOX_METRICS};
+use crate::api_server::tx_sender::MultiVMBaseSystemContracts;
+
+/// VM environment returned by [`VmEnvPool`].
+#[derive(Debug, Clone)]
+pub(crate) struct PooledVmEnv {
+    pub system_env: SystemEnv,
+    /// Initial bootloader memory with the bootloader code already loaded. Only present for protocol versions
+    /// executed by the latest VM; older VMs are always initialized from scratch.
+    pub bootloader_memory: Option<Arc<PreparedBootloaderMemory<HistoryDisabled>>>,
+}
+
+/// Pool of VM environments keyed by the protocol version. Environments are initialized lazily on the first request
+/// for a particular protocol version (i.e., the base system contracts for the version are selected and decoded,
+/// and the initial bootloader memory is prepared once), and are cloned on subsequent requests. Cloning is cheap
+/// since base system contracts and prepared memory are shared via an `Arc`.
+///
+/// The pool is supposed to be shared among all sandbox invocations using the same set of base system contracts
+/// (e.g., via wrapping it in an `Arc`), so that per-call overhead of environment preparation is minimized.
+#[derive(Debug)]
+pub(crate) struct VmEnvPool {
+    base_system_contracts: MultiVMBaseSystemContracts,
+    environments: RwLock<HashMap<ProtocolVersionId, PooledVmEnv>>,
+}
+
+impl VmEnvPool {
+    pub fn new(base_system_contracts: MultiVMBaseSystemContracts) -> Self {
+        Self {
+            base_system_contracts,
+            environments: RwLock::default(),
+        }
+    }
+
+    /// Returns a VM environment for the specified protocol version and execution params.
+    pub fn env(
+        &self,
+        protocol_version: ProtocolVersionId,
+        execution_mode: TxExecutionMode,
+        validation_computational_gas_limit: u32,
+        chain_id: L2ChainId,
+    ) -> PooledVmEnv {
+        let cached_env = self
+            .environments
+            .read()
+            .expect("VM environment pool is poisoned")
+            .get(&protocol_version)
+            .cloned();
+        let mut env = if let Some(env) = cached_env {
+            SANDBOX_METRICS.vm_env_pool_lookups[&CacheLookup::Hit].inc();
+            env
+        } else {
+            SANDBOX_METRICS.vm_env_pool_lookups[&CacheLookup::Miss].inc();
+            let env = self.create_env(protocol_version);
+            self.environments
+                .write()
+                .expect("VM environment pool is poisoned")
+                .entry(protocol_version)
+                .or_insert(env)
+                .clone()
+        };
+
+        env.system_env.execution_mode = execution_mode;
+        env.system_env.default_validation_computational_gas_limit =
+            validation_computational_gas_limit;
+        env.system_env.chain_id = chain_id;
+        env
+    }
+
+    fn create_env(&self, protocol_version: ProtocolVersionId) -> PooledVmEnv {
+        let system_env = SystemEnv {
+            zk_porter_available: ZKPORTER_IS_AVAILABLE,
+            version: protocol_version,
+            base_system_smart_contracts: Arc::new(
+                self.base_system_contracts
+                    .clone()
+                    .get_by_protocol_version(protocol_version),
+            ),
+            bootloader_gas_limit: BATCH_COMPUTATIONAL_GAS_LIMIT,
+            execution_mode: TxExecutionMode::VerifyExecute,
+            default_validation_computational_gas_limit: BATCH_COMPUTATIONAL_GAS_LIMIT,
+            chain_id: L2ChainId::default(),
+        };
+        // Prepared memory only depends on the bootloader, so it's not affected by the execution params
+        // overridden in `Self::env()`.
+        let bootloader_memory = (protocol_version.into_api_vm_version() == VmVersion::Vm1_5_0)
+            .then(|| Arc::new(PreparedBootloaderMemory::new(&system_env)));
+        PooledVmEnv {
+            system_env,
+            bootloader_memory,
+        }
+    }
+
+    #[cfg(test)]
+    pub(super) fn len(&self) -> usize {
+        self.environments
+            .read()
+            .expect("VM environment pool is poisoned")
+            .len()
+    }
+}
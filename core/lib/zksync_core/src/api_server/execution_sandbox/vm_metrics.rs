@@ -1,7 +1,7 @@
 use std::time::Duration;
 
 use multivm::interface::{VmExecutionResultAndLogs, VmMemoryMetrics};
-use vise::{Buckets, EncodeLabelSet, EncodeLabelValue, Family, Gauge, Histogram, Metrics};
+use vise::{Buckets, Counter, EncodeLabelSet, EncodeLabelValue, Family, Gauge, Histogram, Metrics};
 use zksync_shared_metrics::InteractionType;
 use zksync_state::StorageViewMetrics;
 use zksync_types::{
@@ -75,6 +75,7 @@ static STORAGE_METRICS: vise::Global<RuntimeContextStorageMetrics> = vise::Globa
 pub(super) enum SandboxStage {
     VmConcurrencyLimiterAcquire,
     Initialization,
+    PrefetchStorage,
     ValidateInSandbox,
     Validation,
     Execution,
@@ -95,6 +96,52 @@ pub(in crate::api_server) enum SubmitTxStage {
     DbInsert,
 }
 
+/// Cheap pre-check performed for a submitted transaction before it's dry-run in the VM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "check", rename_all = "snake_case")]
+pub(in crate::api_server) enum SubmitTxPrecheck {
+    /// Transaction encoding, signature recovery and chain ID checks.
+    Signature,
+    /// Gas limit checks (including the intrinsic gas check).
+    GasLimit,
+    /// Fee per gas checks.
+    Fee,
+    /// Number of factory dependencies.
+    FactoryDeps,
+    /// Account nonce check.
+    Nonce,
+    /// Account balance check.
+    Balance,
+    /// Sequencer policy check performed by the transaction filter.
+    Filter,
+    /// Per-sender submission rate and pending transactions limits.
+    SenderLimits,
+}
+
+/// Event related to a transaction in the queue of transactions with future nonces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "event", rename_all = "snake_case")]
+pub(in crate::api_server) enum FutureTxEvent {
+    /// Transaction was put into the queue.
+    Queued,
+    /// Transaction was released from the queue and accepted by the mempool.
+    Released,
+    /// Transaction was released from the queue, but was rejected on submission.
+    Rejected,
+    /// Transaction was dropped because its nonce has become stale.
+    Stale,
+    /// Transaction was dropped because it has spent too much time in the queue.
+    Expired,
+}
+
+/// Result of a lookup in one of the sandbox caches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "result", rename_all = "snake_case")]
+pub(crate) enum CacheLookup {
+    Hit,
+    Miss,
+}
+
 #[derive(Debug, Metrics)]
 #[metrics(prefix = "api_web3")]
 pub(in crate::api_server) struct SandboxMetrics {
@@ -104,8 +151,26 @@ pub(in crate::api_server) struct SandboxMetrics {
     pub(super) sandbox_execution_permits: Histogram<usize>,
     #[metrics(buckets = Buckets::LATENCIES)]
     pub submit_tx: Family<SubmitTxStage, Histogram<Duration>>,
+    /// Number of submitted transactions rejected by cheap pre-checks, i.e., without acquiring a VM permit.
+    pub submit_tx_precheck_rejections: Family<SubmitTxPrecheck, Counter>,
+    /// Number of transactions in the queue of transactions with future nonces.
+    pub future_txs_queue_size: Gauge<usize>,
+    /// Events related to transactions with future nonces.
+    pub future_txs: Family<FutureTxEvent, Counter>,
     #[metrics(buckets = Buckets::linear(0.0..=30.0, 3.0))]
     pub estimate_gas_binary_search_iterations: Histogram<usize>,
+    /// Number of system environment lookups in VM environment pools.
+    pub(super) vm_env_pool_lookups: Family<CacheLookup, Counter>,
+    /// Number of buffer lookups in the storage view pool. Hits correspond to reused buffers.
+    pub(super) storage_view_pool_lookups: Family<CacheLookup, Counter>,
+    /// Current number of buffers in the storage view pool.
+    pub(super) storage_view_pool_size: Gauge<usize>,
+    /// Number of storage view buffers not returned to the pool because they have grown too large.
+    pub(super) storage_view_pool_discarded_buffers: Counter,
+    /// Number of lookups in the `eth_call` result cache.
+    pub(super) eth_call_cache_lookups: Family<CacheLookup, Counter>,
+    /// Number of lookups in the `eth_estimateGas` result cache.
+    pub estimate_gas_cache_lookups: Family<CacheLookup, Counter>,
 }
 
 #[vise::register]
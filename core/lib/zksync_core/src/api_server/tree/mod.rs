@@ -8,13 +8,15 @@ use axum::{
     extract::State,
     http::{header, StatusCode},
     response::{IntoResponse, Response},
-    routing, Json, Router,
+    routing, Extension, Json, Router,
 };
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use tokio::sync::watch;
+use zksync_dal::{ConnectionPool, Core, CoreDal};
 use zksync_health_check::{CheckHealth, Health, HealthStatus};
 use zksync_merkle_tree::NoVersionError;
-use zksync_types::{L1BatchNumber, H256, U256};
+use zksync_types::{AccountTreeId, Address, L1BatchNumber, StorageKey, H256, U256};
+use zksync_utils::{h256_to_u256, u256_to_h256};
 
 use self::metrics::{MerkleTreeApiMethod, API_METRICS};
 use crate::metadata_calculator::{AsyncTreeReader, LazyAsyncTreeReader, MerkleTreeInfo};
@@ -23,15 +25,48 @@ mod metrics;
 #[cfg(test)]
 mod tests;
 
+/// Maximum number of entries returned in a single range proof.
+const MAX_RANGE_PROOF_ENTRIES: usize = 10_000;
+/// Maximum number of storage keys returned in a single page of account entries.
+const MAX_ACCOUNT_ENTRIES_PAGE_SIZE: usize = 10_000;
+
+/// Limit on the total number of keys in a single proofs request, passed to handlers as an extension.
+#[derive(Debug, Clone, Copy)]
+struct MaxProofKeys(usize);
+
+/// Request for proofs for a set of keys at a certain tree version.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TreeProofsRequest {
+    pub l1_batch_number: L1BatchNumber,
+    pub hashed_keys: Vec<U256>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TreeRangeProofRequest {
+    l1_batch_number: L1BatchNumber,
+    start_key: U256,
+    end_key: U256,
+    limit: usize,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
-struct TreeProofsRequest {
+struct TreeAccountEntriesRequest {
     l1_batch_number: L1BatchNumber,
-    hashed_keys: Vec<U256>,
+    address: Address,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    start_key: Option<H256>,
+    limit: usize,
 }
 
+/// Entries with Merkle proofs for a set of keys at a certain tree version.
 #[derive(Debug, Serialize, Deserialize)]
-struct TreeProofsResponse {
-    entries: Vec<TreeEntryWithProof>,
+pub struct TreeProofs {
+    /// Root hash of the tree at the requested version; all proofs are relative to it. `None` if the proofs
+    /// were returned by an outdated tree API server.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root_hash: Option<H256>,
+    /// Entries in the same order as the requested keys.
+    pub entries: Vec<TreeEntryWithProof>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -43,6 +78,66 @@ pub struct TreeEntryWithProof {
     pub merkle_path: Vec<H256>,
 }
 
+/// Merkle range proof: all existing entries with keys in a contiguous range at a certain tree version,
+/// together with proofs for the range bounds. The proof can be verified by computing the tree root hash
+/// using a `TreeRangeDigest` from the Merkle tree crate.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TreeRangeProof {
+    /// Root hash of the tree at the requested version; all proofs are relative to it.
+    pub root_hash: H256,
+    /// Start key of the range (inclusive).
+    pub start_key: U256,
+    /// Entry with a proof for the start key. The entry is empty if the key is missing from the tree.
+    pub start: TreeEntryWithProof,
+    /// End key of the range (inclusive). Can be less than the requested end key if the range was truncated
+    /// because of the entry limit; in this case, the next range should start after this key.
+    pub end_key: U256,
+    /// Entry with a proof for the end key. The entry is empty if the key is missing from the tree.
+    pub end: TreeEntryWithProof,
+    /// Existing entries with keys strictly between the start and end keys, in the ascending key order.
+    pub entries: Vec<TreeRangeEntry>,
+}
+
+/// Page of existing storage entries of a certain account at a certain tree version.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TreeAccountEntriesPage {
+    /// Entries in the ascending storage key order. Storage slots zeroed out at the requested version are skipped,
+    /// so a page may contain fewer entries than requested even if it's not the last one.
+    pub entries: Vec<TreeAccountEntry>,
+    /// Storage key to start the next page from, or `None` if there are no more entries for the account.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_key: Option<H256>,
+}
+
+/// Entry in a [`TreeAccountEntriesPage`].
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TreeAccountEntry {
+    /// Storage key (i.e., slot) in the account storage.
+    pub key: H256,
+    /// Hashed key of the entry in the tree.
+    pub hashed_key: U256,
+    pub value: H256,
+    pub index: u64,
+}
+
+/// Entry in a [`TreeRangeProof`].
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TreeRangeEntry {
+    pub key: U256,
+    pub value: H256,
+    pub index: u64,
+}
+
+impl From<zksync_merkle_tree::TreeEntry> for TreeRangeEntry {
+    fn from(entry: zksync_merkle_tree::TreeEntry) -> Self {
+        Self {
+            key: entry.key,
+            value: entry.value,
+            index: entry.leaf_index,
+        }
+    }
+}
+
 impl TreeEntryWithProof {
     fn is_zero(&value: &u64) -> bool {
         value == 0
@@ -65,6 +160,9 @@ impl TreeEntryWithProof {
 #[derive(Debug)]
 enum TreeApiServerError {
     NoTreeVersion(NoVersionError),
+    InvalidKeyRange { start_key: U256, end_key: U256 },
+    TooManyKeys { key_count: usize, limit: usize },
+    Internal(anyhow::Error),
 }
 
 // Contains the same fields as `NoVersionError` and is serializable.
@@ -117,6 +215,38 @@ impl IntoResponse for TreeApiServerError {
                 };
                 (StatusCode::NOT_FOUND, headers, Json(body)).into_response()
             }
+            Self::InvalidKeyRange { start_key, end_key } => {
+                let body = Problem {
+                    r#type: "/errors#invalid-key-range",
+                    title: "Invalid key range",
+                    detail: format!(
+                        "start key {start_key:#x} is greater than end key {end_key:#x}"
+                    ),
+                    data: (),
+                };
+                (StatusCode::BAD_REQUEST, headers, Json(body)).into_response()
+            }
+            Self::TooManyKeys { key_count, limit } => {
+                let body = Problem {
+                    r#type: "/errors#too-many-keys",
+                    title: "Too many keys",
+                    detail: format!(
+                        "request contains {key_count} keys, while at most {limit} are allowed"
+                    ),
+                    data: (),
+                };
+                (StatusCode::BAD_REQUEST, headers, Json(body)).into_response()
+            }
+            Self::Internal(err) => {
+                tracing::warn!("Internal error serving Merkle tree API request: {err:#}");
+                let body = Problem {
+                    r#type: "/errors#internal",
+                    title: "Internal error",
+                    detail: format!("{err:#}"),
+                    data: (),
+                };
+                (StatusCode::INTERNAL_SERVER_ERROR, headers, Json(body)).into_response()
+            }
         }
     }
 }
@@ -141,11 +271,30 @@ pub trait TreeApiClient: 'static + Send + Sync + fmt::Debug {
     async fn get_info(&self) -> Result<MerkleTreeInfo, TreeApiError>;
 
     /// Obtains proofs for the specified `hashed_keys` at the specified tree version (= L1 batch number).
+    /// Proofs for keys missing from the tree are exclusion proofs (i.e., the returned entry has zero value and index).
     async fn get_proofs(
         &self,
         l1_batch_number: L1BatchNumber,
         hashed_keys: Vec<U256>,
-    ) -> Result<Vec<TreeEntryWithProof>, TreeApiError>;
+    ) -> Result<TreeProofs, TreeApiError>;
+
+    /// Obtains proofs for several sets of keys, each at its own tree version, in a single request.
+    /// Returned proofs are in the same order as the requests.
+    async fn get_proofs_batch(
+        &self,
+        requests: Vec<TreeProofsRequest>,
+    ) -> Result<Vec<TreeProofs>, TreeApiError>;
+
+    /// Obtains a range proof for existing entries with keys in `start_key..=end_key` at the specified tree version.
+    /// At most `limit` entries are returned (the server may further limit this number); if the range contains
+    /// more entries, it is truncated, which is reflected in [`TreeRangeProof::end_key`].
+    async fn get_range_proof(
+        &self,
+        l1_batch_number: L1BatchNumber,
+        start_key: U256,
+        end_key: U256,
+        limit: usize,
+    ) -> Result<TreeRangeProof, TreeApiError>;
 }
 
 /// In-memory client implementation.
@@ -163,7 +312,7 @@ impl TreeApiClient for LazyAsyncTreeReader {
         &self,
         l1_batch_number: L1BatchNumber,
         hashed_keys: Vec<U256>,
-    ) -> Result<Vec<TreeEntryWithProof>, TreeApiError> {
+    ) -> Result<TreeProofs, TreeApiError> {
         if let Some(reader) = self.read() {
             reader
                 .get_proofs_inner(l1_batch_number, hashed_keys)
@@ -173,6 +322,42 @@ impl TreeApiClient for LazyAsyncTreeReader {
             Err(TreeApiError::NotReady)
         }
     }
+
+    async fn get_proofs_batch(
+        &self,
+        requests: Vec<TreeProofsRequest>,
+    ) -> Result<Vec<TreeProofs>, TreeApiError> {
+        if let Some(reader) = self.read() {
+            reader
+                .get_proofs_batch_inner(requests)
+                .await
+                .map_err(TreeApiError::NoVersion)
+        } else {
+            Err(TreeApiError::NotReady)
+        }
+    }
+
+    async fn get_range_proof(
+        &self,
+        l1_batch_number: L1BatchNumber,
+        start_key: U256,
+        end_key: U256,
+        limit: usize,
+    ) -> Result<TreeRangeProof, TreeApiError> {
+        if start_key > end_key {
+            let err =
+                anyhow::anyhow!("start key {start_key:#x} is greater than end key {end_key:#x}");
+            return Err(TreeApiError::Internal(err));
+        }
+        if let Some(reader) = self.read() {
+            reader
+                .get_range_proof_inner(l1_batch_number, start_key, end_key, limit)
+                .await
+                .map_err(TreeApiError::NoVersion)
+        } else {
+            Err(TreeApiError::NotReady)
+        }
+    }
 }
 
 /// [`TreeApiClient`] implementation requesting data from a Merkle tree API server.
@@ -181,6 +366,9 @@ pub struct TreeApiHttpClient {
     inner: reqwest::Client,
     info_url: String,
     proofs_url: String,
+    proofs_batch_url: String,
+    range_proof_url: String,
+    account_entries_url: String,
 }
 
 impl TreeApiHttpClient {
@@ -189,8 +377,73 @@ impl TreeApiHttpClient {
             inner: reqwest::Client::new(),
             info_url: url_base.to_owned(),
             proofs_url: format!("{url_base}/proofs"),
+            proofs_batch_url: format!("{url_base}/proofs/batch"),
+            range_proof_url: format!("{url_base}/proofs/range"),
+            account_entries_url: format!("{url_base}/entries/account"),
         }
     }
+
+    /// Obtains existing storage entries of the specified account at the specified tree version, in the ascending
+    /// storage key order. Entries are paginated: at most `limit` storage keys are considered (the server
+    /// may further limit this number) starting from `start_key` (inclusive), if it is specified. The next page
+    /// can be obtained by passing [`TreeAccountEntriesPage::next_key`] as `start_key`.
+    ///
+    /// Since hashed tree keys cannot be scoped to an account, the server enumerates account storage keys using
+    /// Postgres. Thus, entries may be incomplete for L1 batches pruned from Postgres.
+    pub async fn get_account_entries(
+        &self,
+        l1_batch_number: L1BatchNumber,
+        address: Address,
+        start_key: Option<H256>,
+        limit: usize,
+    ) -> Result<TreeAccountEntriesPage, TreeApiError> {
+        let response = self
+            .inner
+            .post(&self.account_entries_url)
+            .json(&TreeAccountEntriesRequest {
+                l1_batch_number,
+                address,
+                start_key,
+                limit,
+            })
+            .send()
+            .await
+            .with_context(|| {
+                format!(
+                    "failed requesting entries for account {address:?} for L1 batch #{l1_batch_number}"
+                )
+            })?;
+        let description = format!("account entries for L1 batch #{l1_batch_number}");
+        Self::parse_proofs_response(response, &description).await
+    }
+
+    /// Parses a response from a proof-related endpoint, converting `NoVersionError` problems
+    /// returned by the server.
+    async fn parse_proofs_response<T: DeserializeOwned>(
+        response: reqwest::Response,
+        description: &str,
+    ) -> Result<T, TreeApiError> {
+        let is_problem = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .map_or(false, |header| *header == PROBLEM_CONTENT_TYPE);
+        if response.status() == StatusCode::NOT_FOUND && is_problem {
+            // Try to parse `NoVersionError` from the response body.
+            let problem_data: NoVersionErrorData = response
+                .json()
+                .await
+                .context("failed parsing error response")?;
+            return Err(TreeApiError::NoVersion(problem_data.into()));
+        }
+
+        let response = response
+            .error_for_status()
+            .with_context(|| format!("requesting {description} returned non-OK response"))?;
+        Ok(response
+            .json()
+            .await
+            .with_context(|| format!("failed deserializing {description}"))?)
+    }
 }
 
 #[async_trait]
@@ -232,7 +485,7 @@ impl TreeApiClient for TreeApiHttpClient {
         &self,
         l1_batch_number: L1BatchNumber,
         hashed_keys: Vec<U256>,
-    ) -> Result<Vec<TreeEntryWithProof>, TreeApiError> {
+    ) -> Result<TreeProofs, TreeApiError> {
         let response = self
             .inner
             .post(&self.proofs_url)
@@ -243,27 +496,47 @@ impl TreeApiClient for TreeApiHttpClient {
             .send()
             .await
             .with_context(|| format!("failed requesting proofs for L1 batch #{l1_batch_number}"))?;
+        let description = format!("proofs for L1 batch #{l1_batch_number}");
+        Self::parse_proofs_response(response, &description).await
+    }
 
-        let is_problem = response
-            .headers()
-            .get(header::CONTENT_TYPE)
-            .map_or(false, |header| *header == PROBLEM_CONTENT_TYPE);
-        if response.status() == StatusCode::NOT_FOUND && is_problem {
-            // Try to parse `NoVersionError` from the response body.
-            let problem_data: NoVersionErrorData = response
-                .json()
-                .await
-                .context("failed parsing error response")?;
-            return Err(TreeApiError::NoVersion(problem_data.into()));
-        }
+    async fn get_proofs_batch(
+        &self,
+        requests: Vec<TreeProofsRequest>,
+    ) -> Result<Vec<TreeProofs>, TreeApiError> {
+        let response = self
+            .inner
+            .post(&self.proofs_batch_url)
+            .json(&requests)
+            .send()
+            .await
+            .context("failed requesting batched proofs")?;
+        Self::parse_proofs_response(response, "batched proofs").await
+    }
 
-        let response = response.error_for_status().with_context(|| {
-            format!("requesting proofs for L1 batch #{l1_batch_number} returned non-OK response")
-        })?;
-        let response: TreeProofsResponse = response.json().await.with_context(|| {
-            format!("failed deserializing proofs for L1 batch #{l1_batch_number}")
-        })?;
-        Ok(response.entries)
+    async fn get_range_proof(
+        &self,
+        l1_batch_number: L1BatchNumber,
+        start_key: U256,
+        end_key: U256,
+        limit: usize,
+    ) -> Result<TreeRangeProof, TreeApiError> {
+        let response = self
+            .inner
+            .post(&self.range_proof_url)
+            .json(&TreeRangeProofRequest {
+                l1_batch_number,
+                start_key,
+                end_key,
+                limit,
+            })
+            .send()
+            .await
+            .with_context(|| {
+                format!("failed requesting range proof for L1 batch #{l1_batch_number}")
+            })?;
+        let description = format!("range proof for L1 batch #{l1_batch_number}");
+        Self::parse_proofs_response(response, &description).await
     }
 }
 
@@ -279,24 +552,131 @@ impl AsyncTreeReader {
         &self,
         l1_batch_number: L1BatchNumber,
         hashed_keys: Vec<U256>,
-    ) -> Result<Vec<TreeEntryWithProof>, NoVersionError> {
-        let proofs = self
+    ) -> Result<TreeProofs, NoVersionError> {
+        let (root_hash, proofs) = self
             .clone()
             .entries_with_proofs(l1_batch_number, hashed_keys)
             .await?;
-        Ok(proofs.into_iter().map(TreeEntryWithProof::new).collect())
+        Ok(TreeProofs {
+            root_hash: Some(root_hash),
+            entries: proofs.into_iter().map(TreeEntryWithProof::new).collect(),
+        })
+    }
+
+    async fn get_proofs_batch_inner(
+        &self,
+        requests: Vec<TreeProofsRequest>,
+    ) -> Result<Vec<TreeProofs>, NoVersionError> {
+        let mut responses = Vec::with_capacity(requests.len());
+        for request in requests {
+            let response = self
+                .get_proofs_inner(request.l1_batch_number, request.hashed_keys)
+                .await?;
+            responses.push(response);
+        }
+        Ok(responses)
+    }
+
+    /// Assumes that `start_key <= end_key`.
+    async fn get_range_proof_inner(
+        &self,
+        l1_batch_number: L1BatchNumber,
+        start_key: U256,
+        mut end_key: U256,
+        limit: usize,
+    ) -> Result<TreeRangeProof, NoVersionError> {
+        // A zero limit would lead to an incorrect proof since the range wouldn't be truncated.
+        let limit = limit.clamp(1, MAX_RANGE_PROOF_ENTRIES);
+        let mut entries = self
+            .clone()
+            .entries_in_range(l1_batch_number, start_key..=end_key, limit)
+            .await?;
+        if entries.len() == limit {
+            // The range may be truncated; shrink it to end at the last returned entry.
+            if let Some(last_entry) = entries.last() {
+                end_key = last_entry.key;
+            }
+        }
+        // Entries for the range bounds are returned with proofs.
+        entries.retain(|entry| entry.key != start_key && entry.key != end_key);
+
+        let (root_hash, bounds) = self
+            .clone()
+            .entries_with_proofs(l1_batch_number, vec![start_key, end_key])
+            .await?;
+        let [start, end]: [_; 2] = bounds.try_into().unwrap();
+        // ^ `unwrap()` is safe: the tree returns an entry for each requested key
+        Ok(TreeRangeProof {
+            root_hash,
+            start_key,
+            start: TreeEntryWithProof::new(start),
+            end_key,
+            end: TreeEntryWithProof::new(end),
+            entries: entries.into_iter().map(TreeRangeEntry::from).collect(),
+        })
     }
 
     async fn get_proofs_handler(
         State(this): State<Self>,
+        Extension(MaxProofKeys(limit)): Extension<MaxProofKeys>,
         Json(request): Json<TreeProofsRequest>,
-    ) -> Result<Json<TreeProofsResponse>, TreeApiServerError> {
+    ) -> Result<Json<TreeProofs>, TreeApiServerError> {
+        let key_count = request.hashed_keys.len();
+        if key_count > limit {
+            return Err(TreeApiServerError::TooManyKeys { key_count, limit });
+        }
+
         let latency = API_METRICS.latency[&MerkleTreeApiMethod::GetProofs].start();
-        let entries = this
+        let response = this
             .get_proofs_inner(request.l1_batch_number, request.hashed_keys)
             .await
             .map_err(TreeApiServerError::NoTreeVersion)?;
-        let response = TreeProofsResponse { entries };
+        latency.observe();
+        Ok(Json(response))
+    }
+
+    async fn get_proofs_batch_handler(
+        State(this): State<Self>,
+        Extension(MaxProofKeys(limit)): Extension<MaxProofKeys>,
+        Json(requests): Json<Vec<TreeProofsRequest>>,
+    ) -> Result<Json<Vec<TreeProofs>>, TreeApiServerError> {
+        // Each request is counted as at least one key, so that a batch of empty requests is limited as well.
+        let key_count: usize = requests
+            .iter()
+            .map(|request| request.hashed_keys.len().max(1))
+            .sum();
+        if key_count > limit {
+            return Err(TreeApiServerError::TooManyKeys { key_count, limit });
+        }
+
+        let latency = API_METRICS.latency[&MerkleTreeApiMethod::GetProofsBatch].start();
+        let response = this
+            .get_proofs_batch_inner(requests)
+            .await
+            .map_err(TreeApiServerError::NoTreeVersion)?;
+        latency.observe();
+        Ok(Json(response))
+    }
+
+    async fn get_range_proof_handler(
+        State(this): State<Self>,
+        Json(request): Json<TreeRangeProofRequest>,
+    ) -> Result<Json<TreeRangeProof>, TreeApiServerError> {
+        let TreeRangeProofRequest {
+            l1_batch_number,
+            start_key,
+            end_key,
+            limit,
+        } = request;
+        if start_key > end_key {
+            return Err(TreeApiServerError::InvalidKeyRange { start_key, end_key });
+        }
+
+        let latency = API_METRICS.latency[&MerkleTreeApiMethod::GetRangeProof].start();
+        let response = this
+            .get_range_proof_inner(l1_batch_number, start_key, end_key, limit)
+            .await
+            .map_err(TreeApiServerError::NoTreeVersion)?;
         latency.observe();
         Ok(Json(response))
     }
@@ -304,14 +684,37 @@ impl AsyncTreeReader {
     fn create_api_server(
         self,
         bind_address: &SocketAddr,
+        pool: ConnectionPool<Core>,
+        max_proof_keys: usize,
         mut stop_receiver: watch::Receiver<bool>,
     ) -> anyhow::Result<MerkleTreeServer> {
         tracing::debug!("Starting Merkle tree API server on {bind_address}");
 
+        let account_entries_state = AccountEntriesState {
+            tree_reader: self.clone(),
+            pool,
+        };
+        let account_entries_routes = Router::new()
+            .route(
+                "/entries/account",
+                routing::post(AccountEntriesState::get_account_entries_handler),
+            )
+            .with_state(account_entries_state);
+
         let app = Router::new()
             .route("/", routing::get(Self::info_handler))
             .route("/proofs", routing::post(Self::get_proofs_handler))
-            .with_state(self);
+            .route(
+                "/proofs/batch",
+                routing::post(Self::get_proofs_batch_handler),
+            )
+            .route(
+                "/proofs/range",
+                routing::post(Self::get_range_proof_handler),
+            )
+            .with_state(self)
+            .layer(Extension(MaxProofKeys(max_proof_keys)))
+            .merge(account_entries_routes);
 
         let server = axum::Server::try_bind(bind_address)
             .with_context(|| format!("Failed binding Merkle tree API server to {bind_address}"))?
@@ -339,18 +742,124 @@ impl AsyncTreeReader {
         })
     }
 
-    /// Runs the HTTP API server.
+    /// Runs the HTTP API server. `pool` is used to enumerate storage keys of accounts; since the API is read-only,
+    /// it may point to a replica. `max_proof_keys` limits the total number of keys in a single proofs request.
     pub async fn run_api_server(
         self,
         bind_address: SocketAddr,
+        pool: ConnectionPool<Core>,
+        max_proof_keys: usize,
         stop_receiver: watch::Receiver<bool>,
     ) -> anyhow::Result<()> {
-        self.create_api_server(&bind_address, stop_receiver)?
+        self.create_api_server(&bind_address, pool, max_proof_keys, stop_receiver)?
             .run()
             .await
     }
 }
 
+/// State for the account entries endpoint. Unlike other endpoints, it needs Postgres access: hashed tree keys
+/// cannot be scoped to an account, so storage keys of an account are enumerated using `storage_logs`.
+#[derive(Debug, Clone)]
+struct AccountEntriesState {
+    tree_reader: AsyncTreeReader,
+    pool: ConnectionPool<Core>,
+}
+
+impl AccountEntriesState {
+    async fn get_account_entries(
+        &self,
+        l1_batch_number: L1BatchNumber,
+        address: Address,
+        start_key: Option<H256>,
+        limit: usize,
+    ) -> Result<TreeAccountEntriesPage, TreeApiServerError> {
+        let limit = limit.clamp(1, MAX_ACCOUNT_ENTRIES_PAGE_SIZE);
+        let mut storage = self
+            .pool
+            .connection_tagged("merkle_tree_api")
+            .await
+            .map_err(|err| TreeApiServerError::Internal(err.generalize()))?;
+        let miniblock_range = storage
+            .blocks_dal()
+            .get_miniblock_range_of_l1_batch(l1_batch_number)
+            .await
+            .map_err(|err| TreeApiServerError::Internal(err.generalize()))?;
+        let Some((_, last_miniblock)) = miniblock_range else {
+            // Report a missing tree version if it's missing; otherwise, the L1 batch is pruned from Postgres.
+            self.tree_reader
+                .clone()
+                .entries(l1_batch_number, vec![])
+                .await
+                .map_err(TreeApiServerError::NoTreeVersion)?;
+            let err = anyhow::anyhow!("L1 batch #{l1_batch_number} is missing from Postgres");
+            return Err(TreeApiServerError::Internal(err));
+        };
+        let keys = storage
+            .storage_logs_dal()
+            .get_account_storage_keys(
+                address,
+                last_miniblock,
+                start_key.unwrap_or_default(),
+                limit,
+            )
+            .await
+            .map_err(|err| TreeApiServerError::Internal(err.generalize()))?;
+        drop(storage);
+
+        let account = AccountTreeId::new(address);
+        let hashed_keys = keys
+            .iter()
+            .map(|&key| StorageKey::new(account, key).hashed_key_u256())
+            .collect();
+        let entries = self
+            .tree_reader
+            .clone()
+            .entries(l1_batch_number, hashed_keys)
+            .await
+            .map_err(TreeApiServerError::NoTreeVersion)?;
+
+        let next_key = if keys.len() == limit {
+            // There may be more keys after the last returned one.
+            keys.last()
+                .and_then(|&key| h256_to_u256(key).checked_add(U256::one()))
+                .map(u256_to_h256)
+        } else {
+            None
+        };
+        let entries = keys.into_iter().zip(entries).filter_map(|(key, entry)| {
+            (!entry.is_empty()).then_some(TreeAccountEntry {
+                key,
+                hashed_key: entry.key,
+                value: entry.value,
+                index: entry.leaf_index,
+            })
+        });
+        Ok(TreeAccountEntriesPage {
+            entries: entries.collect(),
+            next_key,
+        })
+    }
+
+    async fn get_account_entries_handler(
+        State(this): State<Self>,
+        Json(request): Json<TreeAccountEntriesRequest>,
+    ) -> Result<Json<TreeAccountEntriesPage>, TreeApiServerError> {
+        let TreeAccountEntriesRequest {
+            l1_batch_number,
+            address,
+            start_key,
+            limit,
+        } = request;
+
+        let latency = API_METRICS.latency[&MerkleTreeApiMethod::GetAccountEntries].start();
+        let response = this
+            .get_account_entries(l1_batch_number, address, start_key, limit)
+            .await?;
+        latency.observe();
+        Ok(Json(response))
+    }
+}
+
 /// `axum`-powered REST server for Merkle tree API.
 #[must_use = "Server must be `run()`"]
 struct MerkleTreeServer {
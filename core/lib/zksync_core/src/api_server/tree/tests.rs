@@ -4,13 +4,16 @@ use std::net::Ipv4Addr;
 
 use assert_matches::assert_matches;
 use tempfile::TempDir;
-use zksync_dal::{ConnectionPool, Core};
+use zksync_dal::{ConnectionPool, Core, CoreDal};
+use zksync_utils::u32_to_h256;
 
 use super::*;
 use crate::metadata_calculator::tests::{
     gen_storage_logs, reset_db_state, run_calculator, setup_calculator,
 };
 
+const MAX_PROOF_KEYS: usize = 100;
+
 #[tokio::test]
 async fn merkle_tree_api() {
     let pool = ConnectionPool::<Core>::test_pool().await;
@@ -20,13 +23,13 @@ async fn merkle_tree_api() {
 
     reset_db_state(&pool, 5).await;
     let tree_reader = calculator.tree_reader();
-    let calculator_task = tokio::spawn(run_calculator(calculator, pool));
+    let calculator_task = tokio::spawn(run_calculator(calculator, pool.clone()));
 
     let (stop_sender, stop_receiver) = watch::channel(false);
     let api_server = tree_reader
         .wait()
         .await
-        .create_api_server(&api_addr, stop_receiver.clone())
+        .create_api_server(&api_addr, pool, MAX_PROOF_KEYS, stop_receiver.clone())
         .unwrap();
     let local_addr = *api_server.local_addr();
     let api_server_task = tokio::spawn(api_server.run());
@@ -51,8 +54,10 @@ async fn merkle_tree_api() {
         .get_proofs(L1BatchNumber(5), hashed_keys)
         .await
         .unwrap();
-    assert_eq!(proofs.len(), 20);
-    for (i, proof) in proofs.into_iter().enumerate() {
+    assert_eq!(proofs.entries.len(), 20);
+    // L1 batch #5 is the latest one in the tree.
+    assert_eq!(proofs.root_hash, Some(tree_info.root_hash));
+    for (i, proof) in proofs.entries.into_iter().enumerate() {
         let should_be_present = i < 10;
         assert_eq!(proof.index == 0, !should_be_present);
         assert!(!proof.merkle_path.is_empty());
@@ -73,6 +78,267 @@ async fn merkle_tree_api() {
     api_server_task.await.unwrap().unwrap();
 }
 
+#[tokio::test]
+async fn merkle_tree_api_batched_and_range_proofs() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+    let (calculator, _) = setup_calculator(temp_dir.path(), &pool).await;
+    let api_addr = (Ipv4Addr::LOCALHOST, 0).into();
+
+    reset_db_state(&pool, 5).await;
+    let tree_reader = calculator.tree_reader();
+    run_calculator(calculator, pool.clone()).await;
+
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let api_server = tree_reader
+        .wait()
+        .await
+        .create_api_server(&api_addr, pool, MAX_PROOF_KEYS, stop_receiver)
+        .unwrap();
+    let local_addr = *api_server.local_addr();
+    let api_server_task = tokio::spawn(api_server.run());
+    let api_client = TreeApiHttpClient::new(&format!("http://{local_addr}"));
+    let tree_info = api_client.get_info().await.unwrap();
+
+    // Batched proofs must be equivalent to proofs obtained one request at a time.
+    let hashed_keys: Vec<_> = gen_storage_logs(20..30, 1)[0]
+        .iter()
+        .map(|log| log.key.hashed_key_u256())
+        .collect();
+    let requests: Vec<_> = [3, 5]
+        .into_iter()
+        .map(|number| TreeProofsRequest {
+            l1_batch_number: L1BatchNumber(number),
+            hashed_keys: hashed_keys.clone(),
+        })
+        .collect();
+    let batched_proofs = api_client.get_proofs_batch(requests.clone()).await.unwrap();
+    assert_eq!(batched_proofs.len(), 2);
+    for (request, batched_proofs) in requests.into_iter().zip(batched_proofs) {
+        let proofs = api_client
+            .get_proofs(request.l1_batch_number, request.hashed_keys)
+            .await
+            .unwrap();
+        assert_eq!(batched_proofs.root_hash, proofs.root_hash);
+        assert_eq!(batched_proofs.entries.len(), proofs.entries.len());
+        for (batched_entry, entry) in batched_proofs.entries.iter().zip(&proofs.entries) {
+            assert_eq!(batched_entry.value, entry.value);
+            assert_eq!(batched_entry.index, entry.index);
+            assert_eq!(batched_entry.merkle_path, entry.merkle_path);
+        }
+    }
+
+    let err = api_client
+        .get_proofs_batch(vec![TreeProofsRequest {
+            l1_batch_number: L1BatchNumber(10),
+            hashed_keys: vec![],
+        }])
+        .await
+        .unwrap_err();
+    assert_matches!(err, TreeApiError::NoVersion(err) if err.missing_version == 10);
+
+    // A range proof for the entire key space must contain all tree entries.
+    let range_proof = api_client
+        .get_range_proof(L1BatchNumber(5), U256::zero(), U256::MAX, usize::MAX)
+        .await
+        .unwrap();
+    assert_eq!(range_proof.root_hash, tree_info.root_hash);
+    assert_eq!(range_proof.end_key, U256::MAX);
+    let bound_count = [&range_proof.start, &range_proof.end]
+        .into_iter()
+        .filter(|entry| entry.index != 0)
+        .count();
+    assert_eq!(
+        (range_proof.entries.len() + bound_count) as u64,
+        tree_info.leaf_count
+    );
+    let keys: Vec<_> = range_proof.entries.iter().map(|entry| entry.key).collect();
+    assert!(
+        keys.windows(2).all(|window| window[0] < window[1]),
+        "{keys:?}"
+    );
+
+    // Check a truncated range.
+    let truncated_proof = api_client
+        .get_range_proof(L1BatchNumber(5), U256::zero(), U256::MAX, 5)
+        .await
+        .unwrap();
+    assert_eq!(truncated_proof.end_key, keys[4]);
+    assert_ne!(truncated_proof.end.index, 0);
+    assert_eq!(truncated_proof.end.value, range_proof.entries[4].value);
+    let truncated_keys: Vec<_> = truncated_proof
+        .entries
+        .iter()
+        .map(|entry| entry.key)
+        .collect();
+    assert_eq!(truncated_keys, keys[..4]);
+
+    let err = api_client
+        .get_range_proof(L1BatchNumber(5), U256::MAX, U256::zero(), 5)
+        .await
+        .unwrap_err();
+    assert_matches!(err, TreeApiError::Internal(_));
+
+    stop_sender.send_replace(true);
+    api_server_task.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn merkle_tree_api_rejecting_bad_requests() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+    let (calculator, _) = setup_calculator(temp_dir.path(), &pool).await;
+    let api_addr = (Ipv4Addr::LOCALHOST, 0).into();
+
+    reset_db_state(&pool, 5).await;
+    let tree_reader = calculator.tree_reader();
+    run_calculator(calculator, pool.clone()).await;
+
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let api_server = tree_reader
+        .wait()
+        .await
+        .create_api_server(&api_addr, pool, MAX_PROOF_KEYS, stop_receiver)
+        .unwrap();
+    let local_addr = *api_server.local_addr();
+    let api_server_task = tokio::spawn(api_server.run());
+    let api_client = TreeApiHttpClient::new(&format!("http://{local_addr}"));
+    let http_client = reqwest::Client::new();
+
+    let check_problem = |response: reqwest::Response, expected_type: &'static str| async move {
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            PROBLEM_CONTENT_TYPE
+        );
+        let problem: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(problem["type"], expected_type, "{problem:?}");
+    };
+
+    // The HTTP client converts these problems to internal errors, so server responses are checked directly.
+    let response = http_client
+        .post(format!("http://{local_addr}/proofs/range"))
+        .json(&TreeRangeProofRequest {
+            l1_batch_number: L1BatchNumber(5),
+            start_key: U256::MAX,
+            end_key: U256::zero(),
+            limit: 5,
+        })
+        .send()
+        .await
+        .unwrap();
+    check_problem(response, "/errors#invalid-key-range").await;
+
+    let hashed_keys: Vec<_> = (0..=MAX_PROOF_KEYS).map(U256::from).collect();
+    let request = TreeProofsRequest {
+        l1_batch_number: L1BatchNumber(5),
+        hashed_keys,
+    };
+    let response = http_client
+        .post(format!("http://{local_addr}/proofs"))
+        .json(&request)
+        .send()
+        .await
+        .unwrap();
+    check_problem(response, "/errors#too-many-keys").await;
+    let err = api_client
+        .get_proofs(request.l1_batch_number, request.hashed_keys)
+        .await
+        .unwrap_err();
+    assert_matches!(err, TreeApiError::Internal(_));
+
+    // Empty requests in a batch must count towards the limit as well.
+    let requests = vec![
+        TreeProofsRequest {
+            l1_batch_number: L1BatchNumber(5),
+            hashed_keys: vec![],
+        };
+        MAX_PROOF_KEYS + 1
+    ];
+    let response = http_client
+        .post(format!("http://{local_addr}/proofs/batch"))
+        .json(&requests)
+        .send()
+        .await
+        .unwrap();
+    check_problem(response, "/errors#too-many-keys").await;
+
+    // Requests at the limit must be served.
+    let proofs = api_client
+        .get_proofs_batch(requests[..MAX_PROOF_KEYS].to_vec())
+        .await
+        .unwrap();
+    assert_eq!(proofs.len(), MAX_PROOF_KEYS);
+
+    stop_sender.send_replace(true);
+    api_server_task.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn merkle_tree_api_account_entries() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+    let (calculator, _) = setup_calculator(temp_dir.path(), &pool).await;
+    let api_addr = (Ipv4Addr::LOCALHOST, 0).into();
+
+    reset_db_state(&pool, 5).await;
+    let tree_reader = calculator.tree_reader();
+    run_calculator(calculator, pool.clone()).await;
+
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let api_server = tree_reader
+        .wait()
+        .await
+        .create_api_server(&api_addr, pool, MAX_PROOF_KEYS, stop_receiver)
+        .unwrap();
+    let local_addr = *api_server.local_addr();
+    let api_server_task = tokio::spawn(api_server.run());
+    let api_client = TreeApiHttpClient::new(&format!("http://{local_addr}"));
+    // Logs are sorted by the account and split evenly among L1 batches, so all logs for this account
+    // are in L1 batch #3.
+    let account = *gen_storage_logs(0..100, 1)[0][40].key.account();
+
+    let mut all_entries = vec![];
+    let mut start_key = None;
+    loop {
+        let page = api_client
+            .get_account_entries(L1BatchNumber(5), *account.address(), start_key, 7)
+            .await
+            .unwrap();
+        assert!(page.entries.len() <= 7);
+        all_entries.extend(page.entries);
+        start_key = page.next_key;
+        if start_key.is_none() {
+            break;
+        }
+    }
+    for entry in &all_entries {
+        let storage_key = StorageKey::new(account, entry.key);
+        assert_eq!(entry.hashed_key, storage_key.hashed_key_u256());
+        assert_ne!(entry.index, 0);
+    }
+    let keys: Vec<_> = all_entries.iter().map(|entry| entry.key).collect();
+    let expected_keys: Vec<_> = (0..20).map(u32_to_h256).collect();
+    assert_eq!(keys, expected_keys);
+
+    // Entries must be scoped to the tree version.
+    let page = api_client
+        .get_account_entries(L1BatchNumber(2), *account.address(), None, usize::MAX)
+        .await
+        .unwrap();
+    assert_eq!(page.next_key, None);
+    assert!(page.entries.is_empty(), "{page:?}");
+
+    let err = api_client
+        .get_account_entries(L1BatchNumber(10), *account.address(), None, 5)
+        .await
+        .unwrap_err();
+    assert_matches!(err, TreeApiError::NoVersion(err) if err.missing_version == 10);
+
+    stop_sender.send_replace(true);
+    api_server_task.await.unwrap().unwrap();
+}
+
 #[tokio::test]
 async fn local_merkle_tree_client() {
     let pool = ConnectionPool::<Core>::test_pool().await;
@@ -86,12 +352,32 @@ async fn local_merkle_tree_client() {
     assert_matches!(err, TreeApiError::NotReady);
 
     // Wait until the calculator processes initial L1 batches.
-    run_calculator(calculator, pool).await;
+    run_calculator(calculator, pool.clone()).await;
 
     let tree_info = tree_reader.get_info().await.unwrap();
     assert!(tree_info.leaf_count > 20);
     assert_eq!(tree_info.next_l1_batch_number, L1BatchNumber(6));
 
+    // Proofs for past L1 batches must be relative to the root hash of the tree after the batch.
+    let hashed_keys = vec![U256::from_big_endian(&[1; 32])];
+    let proofs = tree_reader
+        .get_proofs(L1BatchNumber(3), hashed_keys)
+        .await
+        .unwrap();
+    let expected_root_hash = pool
+        .connection()
+        .await
+        .unwrap()
+        .blocks_dal()
+        .get_l1_batch_state_root(L1BatchNumber(3))
+        .await
+        .unwrap();
+    assert!(expected_root_hash.is_some());
+    assert_eq!(proofs.root_hash, expected_root_hash);
+    assert_ne!(proofs.root_hash, Some(tree_info.root_hash));
+    assert_eq!(proofs.entries.len(), 1);
+    assert_eq!(proofs.entries[0].index, 0);
+
     let err = tree_reader
         .get_proofs(L1BatchNumber(10), vec![])
         .await
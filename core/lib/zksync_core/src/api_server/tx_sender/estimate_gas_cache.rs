@@ -0,0 +1,154 @@
+//! Short-lived cache for `eth_estimateGas` results.
+
+use std::{
+    num::NonZeroUsize,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use lru::LruCache;
+use zksync_state::{CacheCounters, CacheStats};
+use zksync_types::{
+    fee::FeeEstimate, Address, ExecuteTransactionCommon, MiniblockNumber, Transaction, U256,
+};
+
+use crate::api_server::execution_sandbox::{CacheLookup, SANDBOX_METRICS};
+
+/// Key of the [`EstimateGasCache`]. Besides the transaction params, contains the number of the pending miniblock
+/// the estimation is performed on and the fee params, so that entries become unreachable once a new miniblock
+/// is sealed or fees change.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(super) struct EstimateGasCacheKey {
+    block_number: MiniblockNumber,
+    is_l1: bool,
+    initiator: Address,
+    contract_address: Address,
+    calldata: Vec<u8>,
+    value: U256,
+    paymaster: Address,
+    paymaster_input: Vec<u8>,
+    base_fee: u64,
+    gas_per_pubdata_byte: u64,
+    /// Bit representation of the fee scale factor (`f64` doesn't implement `Hash`).
+    estimated_fee_scale_factor: u64,
+    acceptable_overestimation: u64,
+}
+
+impl EstimateGasCacheKey {
+    /// Returns `None` if the estimation result is not cacheable (e.g., the transaction deploys contracts).
+    pub fn new(
+        tx: &Transaction,
+        block_number: MiniblockNumber,
+        base_fee: u64,
+        gas_per_pubdata_byte: u64,
+        estimated_fee_scale_factor: f64,
+        acceptable_overestimation: u64,
+    ) -> Option<Self> {
+        let has_factory_deps = tx
+            .execute
+            .factory_deps
+            .as_ref()
+            .is_some_and(|deps| !deps.is_empty());
+        if has_factory_deps {
+            return None;
+        }
+
+        let (paymaster, paymaster_input) = match &tx.common_data {
+            ExecuteTransactionCommon::L2(common_data) => (
+                common_data.paymaster_params.paymaster,
+                common_data.paymaster_params.paymaster_input.clone(),
+            ),
+            _ => (Address::zero(), vec![]),
+        };
+        Some(Self {
+            block_number,
+            is_l1: tx.is_l1(),
+            initiator: tx.initiator_account(),
+            contract_address: tx.execute.contract_address,
+            calldata: tx.execute.calldata.clone(),
+            value: tx.execute.value,
+            paymaster,
+            paymaster_input,
+            base_fee,
+            gas_per_pubdata_byte,
+            estimated_fee_scale_factor: estimated_fee_scale_factor.to_bits(),
+            acceptable_overestimation,
+        })
+    }
+}
+
+/// LRU cache for `eth_estimateGas` results with time-based expiration. Wallets tend to repeatedly estimate
+/// the same transaction while the user confirms it, so a short TTL is enough to absorb most of such requests.
+#[derive(Debug)]
+pub(super) struct EstimateGasCache {
+    entries: Mutex<LruCache<EstimateGasCacheKey, (Instant, FeeEstimate)>>,
+    ttl: Duration,
+    counters: CacheCounters,
+}
+
+impl EstimateGasCache {
+    pub(super) const NAME: &'static str = "estimate_gas_cache";
+
+    pub fn new(capacity: NonZeroUsize, ttl: Duration) -> Self {
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+            ttl,
+            counters: CacheCounters::default(),
+        }
+    }
+
+    pub fn get(&self, key: &EstimateGasCacheKey) -> Option<FeeEstimate> {
+        let mut entries = self
+            .entries
+            .lock()
+            .expect("`eth_estimateGas` cache is poisoned");
+        let result = match entries.get(key) {
+            Some((inserted_at, estimate)) if inserted_at.elapsed() < self.ttl => {
+                Some(estimate.clone())
+            }
+            Some(_) => {
+                entries.pop(key);
+                self.counters.observe_evictions(1);
+                None
+            }
+            None => None,
+        };
+        drop(entries);
+        self.counters.observe_lookup(result.is_some());
+
+        let lookup = if result.is_some() {
+            CacheLookup::Hit
+        } else {
+            CacheLookup::Miss
+        };
+        SANDBOX_METRICS.estimate_gas_cache_lookups[&lookup].inc();
+        result
+    }
+
+    pub fn insert(&self, key: EstimateGasCacheKey, estimate: FeeEstimate) {
+        let evicted = self
+            .entries
+            .lock()
+            .expect("`eth_estimateGas` cache is poisoned")
+            .push(key.clone(), (Instant::now(), estimate));
+        // `push()` returns either the replaced entry for the same key, or the evicted LRU entry.
+        if evicted.is_some_and(|(evicted_key, _)| evicted_key != key) {
+            self.counters.observe_evictions(1);
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let entries = self
+            .entries
+            .lock()
+            .expect("`eth_estimateGas` cache is poisoned");
+        self.counters.stats(Self::NAME, entries.len() as u64, None)
+    }
+
+    pub fn clear(&self) {
+        self.entries
+            .lock()
+            .expect("`eth_estimateGas` cache is poisoned")
+            .clear();
+    }
+}
@@ -0,0 +1,183 @@
+//! Queue for transactions with nonces too far ahead of the expected account nonce.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use zksync_types::{l2::L2Tx, Address};
+
+use crate::api_server::execution_sandbox::{FutureTxEvent, SANDBOX_METRICS};
+
+/// Maximum total number of queued transactions across all accounts.
+const MAX_QUEUED_TXS: usize = 10_000;
+/// Time after which a queued transaction is dropped if it still cannot be released.
+const QUEUED_TX_TTL: Duration = Duration::from_secs(600);
+
+#[derive(Debug)]
+struct QueuedTx {
+    tx: L2Tx,
+    queued_at: Instant,
+}
+
+#[derive(Debug, Default)]
+struct FutureTxQueueInner {
+    /// Queued transactions for each account, keyed by the nonce.
+    accounts: HashMap<Address, BTreeMap<u32, QueuedTx>>,
+    len: usize,
+}
+
+impl FutureTxQueueInner {
+    fn update_metrics(&self) {
+        SANDBOX_METRICS.future_txs_queue_size.set(self.len);
+    }
+}
+
+/// Bounded per-account queue for transactions with nonces exceeding the range accepted by the mempool
+/// (i.e., the expected account nonce plus `max_nonce_ahead`). Queued transactions are released to the mempool
+/// once the nonce gap closes.
+#[derive(Debug)]
+pub(super) struct FutureTxQueue {
+    max_txs_per_account: usize,
+    inner: Mutex<FutureTxQueueInner>,
+}
+
+impl FutureTxQueue {
+    pub fn new(max_txs_per_account: usize) -> Self {
+        Self {
+            max_txs_per_account,
+            inner: Mutex::default(),
+        }
+    }
+
+    /// Tries to queue a transaction given the maximum nonce currently accepted by the mempool for its initiator.
+    /// A queued transaction with the same nonce is replaced. Returns `false` if the transaction cannot be queued,
+    /// either because its nonce is too far ahead, or because the queue is full.
+    pub fn push(&self, tx: L2Tx, max_nonce: u32) -> bool {
+        let nonce = tx.nonce().0;
+        let max_queued_nonce =
+            max_nonce.saturating_add(u32::try_from(self.max_txs_per_account).unwrap_or(u32::MAX));
+        if nonce <= max_nonce || nonce > max_queued_nonce {
+            return false;
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        let has_capacity = inner.len < MAX_QUEUED_TXS;
+        let account_txs = inner.accounts.entry(tx.initiator_account()).or_default();
+        let queued_tx = QueuedTx {
+            tx,
+            queued_at: Instant::now(),
+        };
+        if let Some(existing_tx) = account_txs.get_mut(&nonce) {
+            *existing_tx = queued_tx;
+        } else if has_capacity && account_txs.len() < self.max_txs_per_account {
+            account_txs.insert(nonce, queued_tx);
+            inner.len += 1;
+        } else {
+            if account_txs.is_empty() {
+                inner.accounts.remove(&queued_tx.tx.initiator_account());
+            }
+            return false;
+        }
+        inner.update_metrics();
+        SANDBOX_METRICS.future_txs[&FutureTxEvent::Queued].inc();
+        true
+    }
+
+    /// Returns all accounts with queued transactions.
+    pub fn accounts(&self) -> Vec<Address> {
+        let inner = self.inner.lock().unwrap();
+        inner.accounts.keys().copied().collect()
+    }
+
+    /// Removes transactions for the specified account that can be submitted to the mempool, i.e., ones with nonces
+    /// in `expected_nonce..=max_nonce`, and returns them ordered by nonce. Transactions with nonces below
+    /// `expected_nonce` and expired transactions are dropped.
+    pub fn take_ready(&self, account: Address, expected_nonce: u32, max_nonce: u32) -> Vec<L2Tx> {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(account_txs) = inner.accounts.get_mut(&account) else {
+            return vec![];
+        };
+
+        let prev_len = account_txs.len();
+        let not_ready_txs = account_txs.split_off(&max_nonce.saturating_add(1));
+        let ready_txs = std::mem::replace(account_txs, not_ready_txs);
+        let (stale_txs, ready_txs): (Vec<_>, Vec<_>) = ready_txs
+            .into_iter()
+            .partition(|(nonce, _)| *nonce < expected_nonce);
+        let stale_count = stale_txs.len();
+        let expired_count = {
+            let len_before = account_txs.len();
+            account_txs.retain(|_, queued_tx| queued_tx.queued_at.elapsed() < QUEUED_TX_TTL);
+            len_before - account_txs.len()
+        };
+        let is_empty = account_txs.is_empty();
+        let new_len = account_txs.len();
+
+        if is_empty {
+            inner.accounts.remove(&account);
+        }
+        inner.len -= prev_len - new_len;
+        inner.update_metrics();
+        drop(inner);
+
+        SANDBOX_METRICS.future_txs[&FutureTxEvent::Stale].inc_by(stale_count as u64);
+        SANDBOX_METRICS.future_txs[&FutureTxEvent::Expired].inc_by(expired_count as u64);
+        ready_txs
+            .into_iter()
+            .map(|(_, queued_tx)| queued_tx.tx)
+            .collect()
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.inner.lock().unwrap().len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_types::Nonce;
+
+    use super::*;
+    use crate::utils::testonly::create_l2_transaction;
+
+    fn mock_tx(initiator: Address, nonce: u32) -> L2Tx {
+        let mut tx = create_l2_transaction(10, 100);
+        tx.common_data.initiator_address = initiator;
+        tx.common_data.nonce = Nonce(nonce);
+        tx
+    }
+
+    #[test]
+    fn queueing_future_txs() {
+        let queue = FutureTxQueue::new(2);
+        let account = Address::repeat_byte(1);
+        assert!(!queue.push(mock_tx(account, 10), 10));
+        assert!(queue.push(mock_tx(account, 11), 10));
+        assert!(queue.push(mock_tx(account, 12), 10));
+        assert!(!queue.push(mock_tx(account, 13), 10));
+        // Transactions with the same nonce should be replaced.
+        assert!(queue.push(mock_tx(account, 12), 10));
+        assert_eq!(queue.len(), 2);
+
+        let other_account = Address::repeat_byte(2);
+        assert!(queue.push(mock_tx(other_account, 6), 5));
+        assert_eq!(queue.len(), 3);
+        let mut accounts = queue.accounts();
+        accounts.sort_unstable();
+        assert_eq!(accounts, [account, other_account]);
+
+        assert!(queue.take_ready(account, 0, 10).is_empty());
+        let ready_txs = queue.take_ready(account, 1, 11);
+        let ready_nonces: Vec<_> = ready_txs.iter().map(|tx| tx.nonce().0).collect();
+        assert_eq!(ready_nonces, [11]);
+        assert_eq!(queue.len(), 2);
+
+        // A transaction with the nonce below the expected one should be dropped.
+        assert!(queue.take_ready(account, 13, 20).is_empty());
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.accounts(), [other_account]);
+    }
+}
@@ -1,9 +1,13 @@
 use std::collections::hash_map::{Entry, HashMap};
 
 use tokio::sync::Mutex;
-use zksync_dal::{transactions_dal::L2TxSubmissionResult, ConnectionPool, Core, CoreDal};
+use zksync_dal::{
+    transactions_dal::L2TxSubmissionResult, ConnectionPool, Core, CoreDal, DalResult,
+};
 use zksync_shared_metrics::{TxStage, APP_METRICS};
-use zksync_types::{fee::TransactionExecutionMetrics, l2::L2Tx, Address, Nonce, H256};
+use zksync_types::{
+    fee::TransactionExecutionMetrics, l2::L2Tx, tx::TxAccessHints, Address, Nonce, H256,
+};
 
 use super::{tx_sink::TxSink, SubmitTxError};
 use crate::api_server::web3::metrics::API_METRICS;
@@ -22,6 +26,41 @@ impl MasterPoolSink {
             inflight_requests: Mutex::new(HashMap::new()),
         }
     }
+
+    /// Inserts a transaction together with its access hints. Hints are persisted before the transaction
+    /// in the same DB transaction, so that they are visible once the transaction is loaded into the mempool.
+    async fn insert_transaction(
+        &self,
+        tx: &L2Tx,
+        execution_metrics: TransactionExecutionMetrics,
+        access_hints: Option<&TxAccessHints>,
+    ) -> DalResult<L2TxSubmissionResult> {
+        let mut connection = self.master_pool.connection_tagged("api").await?;
+        let Some(access_hints) = access_hints.filter(|hints| !hints.is_empty()) else {
+            return connection
+                .transactions_dal()
+                .insert_transaction_l2(tx, execution_metrics)
+                .await;
+        };
+
+        let mut transaction = connection.start_transaction().await?;
+        transaction
+            .transactions_dal()
+            .insert_access_hints(tx.hash(), access_hints)
+            .await?;
+        let submission_res_handle = transaction
+            .transactions_dal()
+            .insert_transaction_l2(tx, execution_metrics)
+            .await?;
+        // Hints are only persisted if the transaction is actually added to the mempool.
+        if matches!(
+            submission_res_handle,
+            L2TxSubmissionResult::Added | L2TxSubmissionResult::Replaced
+        ) {
+            transaction.commit().await?;
+        }
+        Ok(submission_res_handle)
+    }
 }
 
 #[async_trait::async_trait]
@@ -30,6 +69,7 @@ impl TxSink for MasterPoolSink {
         &self,
         tx: &L2Tx,
         execution_metrics: TransactionExecutionMetrics,
+        access_hints: Option<&TxAccessHints>,
     ) -> Result<L2TxSubmissionResult, SubmitTxError> {
         let address_and_nonce = (tx.initiator_account(), tx.nonce());
 
@@ -51,18 +91,14 @@ impl TxSink for MasterPoolSink {
         };
         drop(lock);
 
-        let result = match self.master_pool.connection_tagged("api").await {
-            Ok(mut connection) => connection
-                .transactions_dal()
-                .insert_transaction_l2(tx, execution_metrics)
-                .await
-                .map(|submission_res_handle| {
-                    APP_METRICS.processed_txs[&TxStage::Mempool(submission_res_handle)].inc();
-                    submission_res_handle
-                })
-                .map_err(|err| err.generalize().into()),
-            Err(err) => Err(err.generalize().into()),
-        };
+        let result = self
+            .insert_transaction(tx, execution_metrics, access_hints)
+            .await
+            .map(|submission_res_handle| {
+                APP_METRICS.processed_txs[&TxStage::Mempool(submission_res_handle)].inc();
+                submission_res_handle
+            })
+            .map_err(|err| err.generalize().into());
 
         self.inflight_requests
             .lock()
@@ -1,6 +1,11 @@
 //! Helper module to submit transactions into the zkSync Network.
 
-use std::{cmp, sync::Arc, time::Instant};
+use std::{
+    cmp,
+    num::{NonZeroU32, NonZeroUsize},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use anyhow::Context as _;
 use multivm::{
@@ -11,48 +16,69 @@ use multivm::{
     },
     vm_latest::constants::BATCH_COMPUTATIONAL_GAS_LIMIT,
 };
-use tokio::sync::RwLock;
+use once_cell::sync::OnceCell;
+use tokio::sync::{watch, RwLock};
 use zksync_config::configs::{api::Web3JsonRpcConfig, chain::StateKeeperConfig};
 use zksync_contracts::BaseSystemContracts;
 use zksync_dal::{
     transactions_dal::L2TxSubmissionResult, Connection, ConnectionPool, Core, CoreDal,
 };
-use zksync_state::PostgresStorageCaches;
+use zksync_state::{CacheInvalidator, CacheStats, PostgresStorageCaches};
 use zksync_types::{
-    fee::{Fee, TransactionExecutionMetrics},
+    api::AaValidationRules,
+    fee::{Fee, FeeEstimate, GasBreakdown, GasRefunds, TransactionExecutionMetrics},
     fee_model::BatchFeeInput,
     get_code_key, get_intrinsic_constants,
     l1::is_l1_tx_type,
     l2::{error::TxCheckError::TxDuplication, L2Tx},
+    tx::TxAccessHints,
     utils::storage_key_for_eth_balance,
-    AccountTreeId, Address, ExecuteTransactionCommon, L2ChainId, MiniblockNumber, Nonce,
-    PackedEthSignature, ProtocolVersionId, Transaction, VmVersion, H160, H256, MAX_L2_TX_GAS_LIMIT,
+    Address, ExecuteTransactionCommon, L2ChainId, MiniblockNumber, Nonce, PackedEthSignature,
+    ProtocolVersionId, StorageKey, Transaction, VmVersion, H160, H256, MAX_L2_TX_GAS_LIMIT,
     MAX_NEW_FACTORY_DEPS, U256,
 };
 use zksync_utils::h256_to_u256;
 
 pub(super) use self::result::SubmitTxError;
-use self::tx_sink::TxSink;
+use self::{
+    estimate_gas_cache::{EstimateGasCache, EstimateGasCacheKey},
+    future_txs::FutureTxQueue,
+    sender_limits::SenderRateLimiter,
+    tx_sink::TxSink,
+};
 use crate::{
     api_server::{
         execution_sandbox::{
-            BlockArgs, BlockStartInfo, SubmitTxStage, TransactionExecutor, TxExecutionArgs,
-            TxSharedArgs, VmConcurrencyLimiter, VmPermit, SANDBOX_METRICS,
+            ApiTracer, BlockArgs, BlockStartInfo, EthCallCache, FutureTxEvent, StorageViewPool,
+            SubmitTxPrecheck, SubmitTxStage, TransactionExecutor, TxExecutionArgs, TxSharedArgs,
+            VmConcurrencyLimiter, VmEnvPool, VmPermit, SANDBOX_METRICS,
         },
         tx_sender::result::ApiCallResult,
     },
     fee_model::BatchFeeModelInputProvider,
-    state_keeper::seal_criteria::{ConditionalSealer, NoopSealer, SealData},
+    state_keeper::{
+        seal_criteria::{ConditionalSealer, NoopSealer, SealData},
+        FeeAccountSelector,
+    },
+    tx_filter::{
+        AllowAllFilter, FilterRejection, TransactionFilter, TxFilterStage, TX_FILTER_METRICS,
+    },
     utils::pending_protocol_version,
 };
 
+mod estimate_gas_cache;
+mod future_txs;
 pub mod master_pool_sink;
 pub mod proxy;
 mod result;
+mod sender_limits;
 #[cfg(test)]
 pub(crate) mod tests;
 pub mod tx_sink;
 
+/// Multiplier applied to the gas used by a transaction in the initial gas estimation run to get an optimistic gas limit.
+const OPTIMISTIC_GAS_LIMIT_SCALE: f64 = 1.2;
+
 #[derive(Debug, Clone)]
 pub struct MultiVMBaseSystemContracts {
     /// Contracts to be used for pre-virtual-blocks protocol versions.
@@ -162,6 +188,12 @@ pub struct TxSenderBuilder {
     sealer: Option<Arc<dyn ConditionalSealer>>,
     /// Cache for tokens that are white-listed for AA.
     whitelisted_tokens_for_aa_cache: Option<Arc<RwLock<Vec<Address>>>>,
+    /// Additional rules used during AA validation.
+    aa_validation_rules: Option<Arc<RwLock<AaValidationRules>>>,
+    /// Filter applied to submitted transactions.
+    tx_filter: Option<Arc<dyn TransactionFilter>>,
+    /// Invalidator to register caches used by `TxSender` in.
+    cache_invalidator: Option<CacheInvalidator>,
 }
 
 impl TxSenderBuilder {
@@ -176,6 +208,9 @@ impl TxSenderBuilder {
             tx_sink,
             sealer: None,
             whitelisted_tokens_for_aa_cache: None,
+            aa_validation_rules: None,
+            tx_filter: None,
+            cache_invalidator: None,
         }
     }
 
@@ -189,6 +224,27 @@ impl TxSenderBuilder {
         self
     }
 
+    /// Sets AA validation rules shared with other components (e.g., with `TxSender`s for other API servers),
+    /// so that the rules changed at runtime apply to all of them.
+    pub fn with_aa_validation_rules(mut self, rules: Arc<RwLock<AaValidationRules>>) -> Self {
+        self.aa_validation_rules = Some(rules);
+        self
+    }
+
+    /// Sets the filter applied to submitted transactions. It should be shared with the state keeper, so that
+    /// the same policy applies to transactions that are already in the mempool.
+    pub fn with_tx_filter(mut self, tx_filter: Arc<dyn TransactionFilter>) -> Self {
+        self.tx_filter = Some(tx_filter);
+        self
+    }
+
+    /// Registers storage caches and the `eth_call` cache in the specified invalidator, so that they are purged
+    /// after a block revert or reorg.
+    pub fn with_cache_invalidator(mut self, cache_invalidator: CacheInvalidator) -> Self {
+        self.cache_invalidator = Some(cache_invalidator);
+        self
+    }
+
     pub async fn build(
         self,
         batch_fee_input_provider: Arc<dyn BatchFeeModelInputProvider>,
@@ -202,18 +258,48 @@ impl TxSenderBuilder {
             self.whitelisted_tokens_for_aa_cache.unwrap_or_else(|| {
                 Arc::new(RwLock::new(self.config.whitelisted_tokens_for_aa.clone()))
             });
+        let aa_validation_rules = self
+            .aa_validation_rules
+            .unwrap_or_else(|| Arc::new(RwLock::new(self.config.aa_validation_rules.clone())));
+        let tx_filter = self.tx_filter.unwrap_or_else(|| Arc::new(AllowAllFilter));
+
+        let executor = TransactionExecutor::real(self.config.eth_call_cache_size);
+        if let Some(cache_invalidator) = &self.cache_invalidator {
+            cache_invalidator.register(Arc::new(storage_caches.clone()));
+            if let Some(eth_call_cache) = executor.eth_call_cache() {
+                cache_invalidator.register(Arc::new(eth_call_cache.clone()));
+            }
+        }
+        let estimate_gas_cache = self
+            .config
+            .estimate_gas_cache_size
+            .and_then(NonZeroUsize::new)
+            .map(|capacity| EstimateGasCache::new(capacity, self.config.estimate_gas_cache_ttl));
+        let future_txs = (self.config.max_queued_txs_per_account > 0)
+            .then(|| FutureTxQueue::new(self.config.max_queued_txs_per_account));
+        let sender_rate_limiter = self
+            .config
+            .max_tx_submissions_per_minute_per_sender
+            .map(SenderRateLimiter::new);
 
         TxSender(Arc::new(TxSenderInner {
             sender_config: self.config,
             tx_sink: self.tx_sink,
             replica_connection_pool: self.replica_connection_pool,
             batch_fee_input_provider,
-            api_contracts,
+            estimate_gas_vm_env_pool: Arc::new(VmEnvPool::new(api_contracts.estimate_gas)),
+            eth_call_vm_env_pool: Arc::new(VmEnvPool::new(api_contracts.eth_call)),
+            storage_view_pool: Arc::default(),
             vm_concurrency_limiter,
             storage_caches,
             whitelisted_tokens_for_aa_cache,
+            aa_validation_rules,
             sealer,
-            executor: TransactionExecutor::Real,
+            tx_filter,
+            executor,
+            estimate_gas_cache,
+            future_txs,
+            sender_rate_limiter,
         }))
     }
 }
@@ -224,16 +310,31 @@ impl TxSenderBuilder {
 /// The intention is to only keep the actually used information here.
 #[derive(Debug, Clone)]
 pub struct TxSenderConfig {
-    pub fee_account_addr: Address,
+    pub fee_accounts: FeeAccountSelector,
     pub gas_price_scale_factor: f64,
     pub max_nonce_ahead: u32,
+    /// Maximum number of queued transactions per account with nonces exceeding `max_nonce_ahead`.
+    /// If set to 0, such transactions are rejected.
+    pub max_queued_txs_per_account: usize,
+    /// Minimum fee bump (in percent) required to replace a pending transaction with the same initiator and nonce.
+    pub tx_replacement_fee_bump_percent: u32,
+    /// Maximum number of transactions per minute that can be submitted from a single sender.
+    pub max_tx_submissions_per_minute_per_sender: Option<NonZeroU32>,
+    /// Maximum number of pending transactions from a single sender.
+    pub max_pending_txs_per_sender: Option<u32>,
     pub max_allowed_l2_tx_gas_limit: u64,
     pub vm_execution_cache_misses_limit: Option<usize>,
+    pub vm_execution_timeout: Option<Duration>,
+    pub estimate_gas_optimize_search: bool,
+    pub eth_call_cache_size: Option<usize>,
+    pub estimate_gas_cache_size: Option<usize>,
+    pub estimate_gas_cache_ttl: Duration,
     pub validation_computational_gas_limit: u32,
     pub l1_to_l2_transactions_compatibility_mode: bool,
     pub chain_id: L2ChainId,
     pub max_pubdata_per_batch: u64,
     pub whitelisted_tokens_for_aa: Vec<Address>,
+    pub aa_validation_rules: AaValidationRules,
 }
 
 impl TxSenderConfig {
@@ -244,11 +345,21 @@ impl TxSenderConfig {
         chain_id: L2ChainId,
     ) -> Self {
         Self {
-            fee_account_addr,
+            fee_accounts: FeeAccountSelector::new(fee_account_addr, state_keeper_config),
             gas_price_scale_factor: web3_json_config.gas_price_scale_factor,
             max_nonce_ahead: web3_json_config.max_nonce_ahead,
+            max_queued_txs_per_account: web3_json_config.max_queued_txs_per_account(),
+            tx_replacement_fee_bump_percent: web3_json_config.tx_replacement_fee_bump_percent(),
+            max_tx_submissions_per_minute_per_sender: web3_json_config
+                .max_tx_submissions_per_minute_per_sender,
+            max_pending_txs_per_sender: web3_json_config.max_pending_txs_per_sender,
             max_allowed_l2_tx_gas_limit: state_keeper_config.max_allowed_l2_tx_gas_limit,
             vm_execution_cache_misses_limit: web3_json_config.vm_execution_cache_misses_limit,
+            vm_execution_timeout: web3_json_config.vm_execution_timeout(),
+            estimate_gas_optimize_search: web3_json_config.estimate_gas_optimize_search,
+            eth_call_cache_size: web3_json_config.eth_call_cache_size,
+            estimate_gas_cache_size: web3_json_config.estimate_gas_cache_size,
+            estimate_gas_cache_ttl: web3_json_config.estimate_gas_cache_ttl(),
             validation_computational_gas_limit: state_keeper_config
                 .validation_computational_gas_limit,
             l1_to_l2_transactions_compatibility_mode: web3_json_config
@@ -256,6 +367,10 @@ impl TxSenderConfig {
             chain_id,
             max_pubdata_per_batch: state_keeper_config.max_pubdata_per_batch,
             whitelisted_tokens_for_aa: web3_json_config.whitelisted_tokens_for_aa.clone(),
+            aa_validation_rules: AaValidationRules {
+                trusted_token_slots: web3_json_config.aa_trusted_token_slots.clone(),
+                trusted_addresses: web3_json_config.aa_trusted_addresses.clone(),
+            },
         }
     }
 }
@@ -267,16 +382,31 @@ pub struct TxSenderInner {
     pub replica_connection_pool: ConnectionPool<Core>,
     // Used to keep track of gas prices for the fee ticker.
     pub batch_fee_input_provider: Arc<dyn BatchFeeModelInputProvider>,
-    pub(super) api_contracts: ApiContracts,
+    /// Pre-initialized VM environments used when estimating gas.
+    pub(super) estimate_gas_vm_env_pool: Arc<VmEnvPool>,
+    /// Pre-initialized VM environments used when performing `eth_call` requests.
+    pub(super) eth_call_vm_env_pool: Arc<VmEnvPool>,
+    /// Reusable storage view allocations shared by all sandbox executions.
+    pub(super) storage_view_pool: Arc<StorageViewPool>,
     /// Used to limit the amount of VMs that can be executed simultaneously.
     pub(super) vm_concurrency_limiter: Arc<VmConcurrencyLimiter>,
     // Caches used in VM execution.
     storage_caches: PostgresStorageCaches,
     // Cache for white-listed tokens.
     pub(super) whitelisted_tokens_for_aa_cache: Arc<RwLock<Vec<Address>>>,
+    // Additional AA validation rules; can be changed at runtime.
+    aa_validation_rules: Arc<RwLock<AaValidationRules>>,
     /// Batch sealer used to check whether transaction can be executed by the sequencer.
     sealer: Arc<dyn ConditionalSealer>,
+    /// Filter applied to submitted transactions.
+    tx_filter: Arc<dyn TransactionFilter>,
     pub(super) executor: TransactionExecutor,
+    /// Cache for `eth_estimateGas` results.
+    estimate_gas_cache: Option<EstimateGasCache>,
+    /// Queue for transactions with nonces too far ahead of the expected account nonce.
+    future_txs: Option<FutureTxQueue>,
+    /// Rate limiter for transaction submissions from a single sender.
+    sender_rate_limiter: Option<SenderRateLimiter>,
 }
 
 #[derive(Clone)]
@@ -297,10 +427,57 @@ impl TxSender {
         self.0.storage_caches.clone()
     }
 
+    /// Returns statistics for all enabled caches used by this sender.
+    pub(crate) fn cache_stats(&self) -> Vec<CacheStats> {
+        let mut stats = self.0.storage_caches.stats();
+        stats.extend(self.0.executor.eth_call_cache().map(EthCallCache::stats));
+        stats.extend(
+            self.0
+                .estimate_gas_cache
+                .as_ref()
+                .map(EstimateGasCache::stats),
+        );
+        stats
+    }
+
+    /// Removes all entries from the cache with the specified name (as returned in [`Self::cache_stats()`]).
+    /// Returns `false` if there is no enabled cache with the specified name.
+    pub(crate) fn flush_cache(&self, cache_name: &str) -> bool {
+        match cache_name {
+            EthCallCache::NAME => {
+                let cache = self.0.executor.eth_call_cache();
+                cache.map(EthCallCache::clear).is_some()
+            }
+            EstimateGasCache::NAME => {
+                let cache = self.0.estimate_gas_cache.as_ref();
+                cache.map(EstimateGasCache::clear).is_some()
+            }
+            _ => self.0.storage_caches.flush(cache_name),
+        }
+    }
+
     pub(crate) async fn read_whitelisted_tokens_for_aa_cache(&self) -> Vec<Address> {
         self.0.whitelisted_tokens_for_aa_cache.read().await.clone()
     }
 
+    /// Updates white-listed tokens for AA using the provided closure. Returns the updated list of tokens.
+    pub(crate) async fn update_whitelisted_tokens_for_aa(
+        &self,
+        update: impl FnOnce(&mut Vec<Address>),
+    ) -> Vec<Address> {
+        let mut tokens = self.0.whitelisted_tokens_for_aa_cache.write().await;
+        update(&mut tokens);
+        tokens.clone()
+    }
+
+    pub(crate) async fn read_aa_validation_rules(&self) -> AaValidationRules {
+        self.0.aa_validation_rules.read().await.clone()
+    }
+
+    pub(crate) async fn set_aa_validation_rules(&self, rules: AaValidationRules) {
+        *self.0.aa_validation_rules.write().await = rules;
+    }
+
     async fn acquire_replica_connection(&self) -> anyhow::Result<Connection<'_, Core>> {
         self.0
             .replica_connection_pool
@@ -309,18 +486,55 @@ impl TxSender {
             .context("failed acquiring connection to replica DB")
     }
 
-    #[tracing::instrument(skip(self, tx))]
     pub async fn submit_tx(&self, tx: L2Tx) -> Result<L2TxSubmissionResult, SubmitTxError> {
+        self.submit_tx_with_access_hints(tx, None).await
+    }
+
+    /// Submits a transaction together with optional access hints. Hints are not validated; they are propagated
+    /// to the mempool as is and are only used by the state keeper to evaluate scheduling transactions
+    /// for parallel execution. Hints are dropped for transactions queued because of a future nonce.
+    #[tracing::instrument(skip(self, tx, access_hints))]
+    pub async fn submit_tx_with_access_hints(
+        &self,
+        tx: L2Tx,
+        access_hints: Option<TxAccessHints>,
+    ) -> Result<L2TxSubmissionResult, SubmitTxError> {
         let stage_latency = SANDBOX_METRICS.submit_tx[&SubmitTxStage::Validate].start();
         let mut connection = self.acquire_replica_connection().await?;
         let protocol_verison = pending_protocol_version(&mut connection).await?;
-        self.validate_tx(&tx, protocol_verison).await?;
+        // Cheap checks are performed before acquiring a VM permit, so that invalid transactions
+        // (e.g., ones with a stale nonce) cannot starve the VM concurrency limiter.
+        if let Err(err) = self
+            .validate_tx(&mut connection, &tx, protocol_verison)
+            .await
+        {
+            if let (SubmitTxError::NonceIsTooHigh(_, max_nonce, _), Some(future_txs)) =
+                (&err, &self.0.future_txs)
+            {
+                // The nonce check precedes the balance check in `validate_tx()`, so the balance must be validated
+                // before queueing the transaction.
+                if let Err(err) = self.validate_enough_balance(&mut connection, &tx).await {
+                    SANDBOX_METRICS.submit_tx_precheck_rejections[&SubmitTxPrecheck::Balance].inc();
+                    return Err(err);
+                }
+                let tx_hash = tx.hash();
+                if future_txs.push(tx, *max_nonce) {
+                    tracing::debug!("Queued tx {tx_hash:?} with a future nonce");
+                    return Ok(L2TxSubmissionResult::Queued);
+                }
+                SANDBOX_METRICS.submit_tx_precheck_rejections[&SubmitTxPrecheck::Nonce].inc();
+                return Err(err);
+            }
+            if let Some(check) = Self::failed_precheck(&err) {
+                SANDBOX_METRICS.submit_tx_precheck_rejections[&check].inc();
+            }
+            return Err(err);
+        }
         stage_latency.observe();
 
         let stage_latency = SANDBOX_METRICS.submit_tx[&SubmitTxStage::DryRun].start();
         let shared_args = self.shared_args().await;
-        let vm_permit = self.0.vm_concurrency_limiter.acquire().await;
-        let vm_permit = vm_permit.ok_or(SubmitTxError::ServerShuttingDown)?;
+        let vm_permit = self.0.vm_concurrency_limiter.acquire().await?;
         let block_args = BlockArgs::pending(&mut connection).await?;
         drop(connection);
 
@@ -375,7 +589,7 @@ impl TxSender {
         let submission_res_handle = self
             .0
             .tx_sink
-            .submit_tx(&tx, execution_output.metrics)
+            .submit_tx(&tx, execution_output.metrics, access_hints.as_ref())
             .await?;
 
         match submission_res_handle {
@@ -410,11 +624,76 @@ impl TxSender {
         }
     }
 
+    /// Checks whether this sender queues transactions with future nonces. If it does, [`Self::run_future_txs_releaser()`]
+    /// should be run in the background.
+    pub(crate) fn queues_future_txs(&self) -> bool {
+        self.0.future_txs.is_some()
+    }
+
+    /// Periodically releases queued transactions with future nonces to the mempool once their nonces are accepted,
+    /// until a stop signal is received.
+    pub(crate) async fn run_future_txs_releaser(
+        self,
+        poll_interval: Duration,
+        mut stop_receiver: watch::Receiver<bool>,
+    ) -> anyhow::Result<()> {
+        while !*stop_receiver.borrow_and_update() {
+            if let Err(err) = self.release_future_txs().await {
+                tracing::warn!("Failed releasing queued transactions with future nonces: {err:#}");
+            }
+            // Error here corresponds to a timeout w/o `stop_receiver` changed; we're OK with this.
+            tokio::time::timeout(poll_interval, stop_receiver.changed())
+                .await
+                .ok();
+        }
+        tracing::info!("Stop signal received, future transactions releaser is shutting down");
+        Ok(())
+    }
+
+    async fn release_future_txs(&self) -> anyhow::Result<()> {
+        let Some(future_txs) = &self.0.future_txs else {
+            return Ok(());
+        };
+
+        let accounts = future_txs.accounts();
+        if accounts.is_empty() {
+            return Ok(());
+        }
+        let mut storage = self.acquire_replica_connection().await?;
+        let expected_nonces = storage
+            .storage_web3_dal()
+            .get_nonces_for_addresses(&accounts)
+            .await
+            .context("failed getting expected nonces for accounts with queued transactions")?;
+        drop(storage);
+
+        for account in accounts {
+            // Nonces are not persisted for accounts that have never sent a transaction.
+            let expected_nonce = expected_nonces.get(&account).map_or(0, |nonce| nonce.0);
+            let max_nonce = expected_nonce + self.0.sender_config.max_nonce_ahead;
+            for tx in future_txs.take_ready(account, expected_nonce, max_nonce) {
+                let tx_hash = tx.hash();
+                match self.submit_tx(tx).await {
+                    Ok(result) => {
+                        tracing::debug!("Released queued tx {tx_hash:?}: {result}");
+                        SANDBOX_METRICS.future_txs[&FutureTxEvent::Released].inc();
+                    }
+                    Err(err) => {
+                        tracing::info!("Queued tx {tx_hash:?} was rejected on release: {err}");
+                        SANDBOX_METRICS.future_txs[&FutureTxEvent::Rejected].inc();
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
     async fn shared_args(&self) -> TxSharedArgs {
         TxSharedArgs {
-            operator_account: AccountTreeId::new(self.0.sender_config.fee_account_addr),
+            operator_account: self.0.sender_config.fee_accounts.clone(),
             fee_input: self.0.batch_fee_input_provider.get_batch_fee_input().await,
-            base_system_contracts: self.0.api_contracts.eth_call.clone(),
+            vm_env_pool: self.0.eth_call_vm_env_pool.clone(),
+            storage_view_pool: self.0.storage_view_pool.clone(),
             caches: self.storage_caches(),
             validation_computational_gas_limit: self
                 .0
@@ -422,14 +701,55 @@ impl TxSender {
                 .validation_computational_gas_limit,
             chain_id: self.0.sender_config.chain_id,
             whitelisted_tokens_for_aa: self.read_whitelisted_tokens_for_aa_cache().await,
+            aa_validation_rules: self.read_aa_validation_rules().await,
         }
     }
 
+    /// Returns the pre-check in [`Self::validate_tx()`] that has produced the specified error.
+    fn failed_precheck(err: &SubmitTxError) -> Option<SubmitTxPrecheck> {
+        Some(match err {
+            SubmitTxError::GasLimitIsTooBig | SubmitTxError::IntrinsicGas => {
+                SubmitTxPrecheck::GasLimit
+            }
+            SubmitTxError::MaxFeePerGasTooLow
+            | SubmitTxError::MaxPriorityFeeGreaterThanMaxFee
+            | SubmitTxError::ReplacementUnderpriced(..) => SubmitTxPrecheck::Fee,
+            SubmitTxError::TooManyFactoryDependencies(..) => SubmitTxPrecheck::FactoryDeps,
+            SubmitTxError::NonceIsTooLow(..) | SubmitTxError::NonceIsTooHigh(..) => {
+                SubmitTxPrecheck::Nonce
+            }
+            SubmitTxError::NotEnoughBalanceForFeeValue(..) => SubmitTxPrecheck::Balance,
+            SubmitTxError::DeniedByFilter(_) => SubmitTxPrecheck::Filter,
+            SubmitTxError::SenderRateLimited(_) | SubmitTxError::TooManyPendingTxs(..) => {
+                SubmitTxPrecheck::SenderLimits
+            }
+            _ => return None,
+        })
+    }
+
     async fn validate_tx(
         &self,
+        connection: &mut Connection<'_, Core>,
         tx: &L2Tx,
         protocol_version: ProtocolVersionId,
     ) -> Result<(), SubmitTxError> {
+        match self.0.tx_filter.check(&tx.clone().into()) {
+            Ok(()) => { /* the transaction is admitted */ }
+            Err(FilterRejection::Denied(reason)) => {
+                tracing::info!("Submitted tx {:?} is denied by filter: {reason}", tx.hash());
+                TX_FILTER_METRICS.rejected[&TxFilterStage::Api].inc();
+                return Err(SubmitTxError::DeniedByFilter(reason));
+            }
+            Err(FilterRejection::RetryLater(reason)) => {
+                // The transaction is kept in the mempool until the state keeper admits it.
+                tracing::debug!(
+                    "Submitted tx {:?} is deferred by filter: {reason}",
+                    tx.hash()
+                );
+                TX_FILTER_METRICS.deferred[&TxFilterStage::Api].inc();
+            }
+        }
+
         // This check is intended to ensure that the gas-related values will be safe to convert to u64 in the future computations.
         let max_gas = U256::from(u64::MAX);
         if tx.common_data.fee.gas_limit > max_gas
@@ -488,18 +808,36 @@ impl TxSender {
             return Err(SubmitTxError::IntrinsicGas);
         }
 
+        let initiator_account = tx.initiator_account();
+        if let Some(rate_limiter) = &self.0.sender_rate_limiter {
+            if !rate_limiter.check(initiator_account) {
+                tracing::info!(
+                    "Submitted tx {:?} is rejected because sender {initiator_account:?} is rate-limited",
+                    tx.hash()
+                );
+                return Err(SubmitTxError::SenderRateLimited(initiator_account));
+            }
+        }
+
         // We still double-check the nonce manually
         // to make sure that only the correct nonce is submitted and the transaction's hashes never repeat
-        self.validate_account_nonce(tx).await?;
+        self.validate_account_nonce(connection, tx).await?;
+        let is_replacement = self.validate_replacement_fee(connection, tx).await?;
+        if !is_replacement {
+            self.validate_pending_txs_count(connection, tx).await?;
+        }
         // Even though without enough balance the tx will not pass anyway
         // we check the user for enough balance explicitly here for better DevEx.
-        self.validate_enough_balance(tx).await?;
+        self.validate_enough_balance(connection, tx).await?;
         Ok(())
     }
 
-    async fn validate_account_nonce(&self, tx: &L2Tx) -> Result<(), SubmitTxError> {
-        let Nonce(expected_nonce) = self
-            .get_expected_nonce(tx.initiator_account())
+    async fn validate_account_nonce(
+        &self,
+        connection: &mut Connection<'_, Core>,
+        tx: &L2Tx,
+    ) -> Result<(), SubmitTxError> {
+        let Nonce(expected_nonce) = Self::load_expected_nonce(connection, tx.initiator_account())
             .await
             .with_context(|| {
                 format!(
@@ -528,14 +866,90 @@ impl TxSender {
         }
     }
 
+    /// Checks that a transaction replacing a pending transaction with the same initiator and nonce
+    /// bumps both the max fee per gas and the max priority fee per gas by at least the configured percentage.
+    /// Returns whether the transaction replaces a pending transaction.
+    async fn validate_replacement_fee(
+        &self,
+        connection: &mut Connection<'_, Core>,
+        tx: &L2Tx,
+    ) -> Result<bool, SubmitTxError> {
+        let pending_tx = connection
+            .transactions_dal()
+            .get_pending_tx_fee(tx.initiator_account(), tx.nonce())
+            .await
+            .context("failed getting pending transaction fee")?;
+        let Some((pending_tx_hash, pending_fee)) = pending_tx else {
+            return Ok(false);
+        };
+        if pending_tx_hash == tx.hash() {
+            // Duplicate transactions are handled separately when inserting the transaction.
+            return Ok(false);
+        }
+
+        let bump_percent = self.0.sender_config.tx_replacement_fee_bump_percent;
+        let bumped = |fee: U256| fee.saturating_mul((100 + bump_percent).into()) / 100;
+        let required_max_fee = bumped(pending_fee.max_fee_per_gas);
+        let required_priority_fee = bumped(pending_fee.max_priority_fee_per_gas);
+        let fee = &tx.common_data.fee;
+        if fee.max_fee_per_gas < required_max_fee
+            || fee.max_priority_fee_per_gas < required_priority_fee
+        {
+            tracing::info!(
+                "Submitted tx {:?} replacing {pending_tx_hash:?} is underpriced: {fee:?}, previous fee: {pending_fee:?}",
+                tx.hash()
+            );
+            return Err(SubmitTxError::ReplacementUnderpriced(
+                required_max_fee,
+                required_priority_fee,
+            ));
+        }
+        Ok(true)
+    }
+
+    /// Checks that the transaction initiator doesn't exceed the configured number of pending transactions.
+    async fn validate_pending_txs_count(
+        &self,
+        connection: &mut Connection<'_, Core>,
+        tx: &L2Tx,
+    ) -> Result<(), SubmitTxError> {
+        let Some(max_pending_txs) = self.0.sender_config.max_pending_txs_per_sender else {
+            return Ok(());
+        };
+        let initiator_account = tx.initiator_account();
+        let pending_txs_count = connection
+            .transactions_dal()
+            .get_pending_txs_count(initiator_account)
+            .await
+            .context("failed getting pending transactions count")?;
+        if pending_txs_count >= max_pending_txs as usize {
+            tracing::info!(
+                "Submitted tx {:?} is rejected because sender {initiator_account:?} has {pending_txs_count} pending txs",
+                tx.hash()
+            );
+            return Err(SubmitTxError::TooManyPendingTxs(
+                initiator_account,
+                max_pending_txs,
+            ));
+        }
+        Ok(())
+    }
+
     async fn get_expected_nonce(&self, initiator_account: Address) -> anyhow::Result<Nonce> {
         let mut storage = self.acquire_replica_connection().await?;
+        Self::load_expected_nonce(&mut storage, initiator_account).await
+    }
+
+    async fn load_expected_nonce(
+        storage: &mut Connection<'_, Core>,
+        initiator_account: Address,
+    ) -> anyhow::Result<Nonce> {
         let latest_block_number = storage.blocks_dal().get_sealed_miniblock_number().await?;
         let latest_block_number = match latest_block_number {
             Some(number) => number,
             None => {
                 // We don't have miniblocks in the storage yet. Use the snapshot miniblock number instead.
-                let start = BlockStartInfo::new(&mut storage).await?;
+                let start = BlockStartInfo::new(storage).await?;
                 MiniblockNumber(start.first_miniblock.saturating_sub(1))
             }
         };
@@ -552,14 +966,18 @@ impl TxSender {
         Ok(Nonce(nonce))
     }
 
-    async fn validate_enough_balance(&self, tx: &L2Tx) -> Result<(), SubmitTxError> {
+    async fn validate_enough_balance(
+        &self,
+        connection: &mut Connection<'_, Core>,
+        tx: &L2Tx,
+    ) -> Result<(), SubmitTxError> {
         let paymaster = tx.common_data.paymaster_params.paymaster;
         // The paymaster is expected to pay for the tx; whatever balance the user has, we don't care.
         if paymaster != Address::default() {
             return Ok(());
         }
 
-        let balance = self.get_balance(&tx.common_data.initiator_address).await?;
+        let balance = Self::load_balance(connection, &tx.common_data.initiator_address).await?;
         // Estimate the minimum fee price user will agree to.
         let gas_price = tx.common_data.fee.max_fee_per_gas;
         let max_fee = tx.common_data.fee.gas_limit * gas_price;
@@ -577,10 +995,16 @@ impl TxSender {
     }
 
     async fn get_balance(&self, initiator_address: &H160) -> anyhow::Result<U256> {
+        let mut storage = self.acquire_replica_connection().await?;
+        Self::load_balance(&mut storage, initiator_address).await
+    }
+
+    async fn load_balance(
+        storage: &mut Connection<'_, Core>,
+        initiator_address: &H160,
+    ) -> anyhow::Result<U256> {
         let eth_balance_key = storage_key_for_eth_balance(initiator_address);
-        let balance = self
-            .acquire_replica_connection()
-            .await?
+        let balance = storage
             .storage_web3_dal()
             .get_value(&eth_balance_key)
             .await?;
@@ -588,7 +1012,10 @@ impl TxSender {
     }
 
     /// Given the gas_limit to be used for the body of the transaction,
-    /// returns the result for executing the transaction with such gas_limit
+    /// returns the result for executing the transaction with such gas_limit.
+    ///
+    /// `accessed_keys` are storage keys accessed by the transaction in the previous steps. If set, the keys
+    /// are prefetched before the execution; otherwise, the keys accessed during this step are recorded into it.
     #[allow(clippy::too_many_arguments)]
     async fn estimate_gas_step(
         &self,
@@ -600,6 +1027,7 @@ impl TxSender {
         block_args: BlockArgs,
         base_fee: u64,
         vm_version: VmVersion,
+        accessed_keys: &mut Option<Vec<StorageKey>>,
     ) -> anyhow::Result<(VmExecutionResultAndLogs, TransactionExecutionMetrics)> {
         let gas_limit_with_overhead = tx_gas_limit
             + derive_overhead(
@@ -634,8 +1062,19 @@ impl TxSender {
 
         let shared_args = self.shared_args_for_gas_estimate(fee_model_params).await;
         let vm_execution_cache_misses_limit = self.0.sender_config.vm_execution_cache_misses_limit;
-        let execution_args =
+        let mut execution_args =
             TxExecutionArgs::for_gas_estimate(vm_execution_cache_misses_limit, &tx, base_fee);
+        let accessed_storage = if let Some(keys) = accessed_keys {
+            execution_args = execution_args.with_prefetched_keys(keys.clone());
+            None
+        } else {
+            Some(Arc::new(OnceCell::new()))
+        };
+        let custom_tracers = accessed_storage
+            .iter()
+            .map(|cell| ApiTracer::AccessedStorage(cell.clone()))
+            .collect();
+
         let execution_output = self
             .0
             .executor
@@ -647,33 +1086,76 @@ impl TxSender {
                 self.0.replica_connection_pool.clone(),
                 tx.clone(),
                 block_args,
-                vec![],
+                custom_tracers,
             )
             .await?;
+
+        if let Some(cell) = accessed_storage {
+            // The cell may be empty if the transaction was executed on a VM version not supporting the tracer.
+            let keys = cell.get().map(|reads| reads.keys().copied().collect());
+            *accessed_keys = Some(keys.unwrap_or_default());
+        }
         Ok((execution_output.vm, execution_output.metrics))
     }
 
+    /// Checks whether the transaction is a plain transfer of base tokens to an account without deployed code.
+    async fn is_simple_transfer(&self, tx: &Transaction) -> anyhow::Result<bool> {
+        let has_factory_deps = tx
+            .execute
+            .factory_deps
+            .as_ref()
+            .is_some_and(|deps| !deps.is_empty());
+        if tx.is_l1() || !tx.execute.calldata.is_empty() || has_factory_deps {
+            return Ok(false);
+        }
+
+        let recipient = tx.execute.contract_address;
+        let code_hash = self
+            .acquire_replica_connection()
+            .await?
+            .storage_web3_dal()
+            .get_value(&get_code_key(&recipient))
+            .await
+            .with_context(|| format!("failed getting code hash for account {recipient:?}"))?;
+        Ok(code_hash == H256::zero())
+    }
+
     async fn shared_args_for_gas_estimate(&self, fee_input: BatchFeeInput) -> TxSharedArgs {
         let config = &self.0.sender_config;
 
         TxSharedArgs {
-            operator_account: AccountTreeId::new(config.fee_account_addr),
+            operator_account: config.fee_accounts.clone(),
             fee_input,
             // We want to bypass the computation gas limit check for gas estimation
             validation_computational_gas_limit: BATCH_COMPUTATIONAL_GAS_LIMIT,
-            base_system_contracts: self.0.api_contracts.estimate_gas.clone(),
+            vm_env_pool: self.0.estimate_gas_vm_env_pool.clone(),
+            storage_view_pool: self.0.storage_view_pool.clone(),
             caches: self.storage_caches(),
             chain_id: config.chain_id,
             whitelisted_tokens_for_aa: self.read_whitelisted_tokens_for_aa_cache().await,
+            aa_validation_rules: self.read_aa_validation_rules().await,
         }
     }
 
     pub async fn get_txs_fee_in_wei(
         &self,
-        mut tx: Transaction,
+        tx: Transaction,
         estimated_fee_scale_factor: f64,
         acceptable_overestimation: u64,
     ) -> Result<Fee, SubmitTxError> {
+        let estimate = self
+            .get_txs_fee_estimate(tx, estimated_fee_scale_factor, acceptable_overestimation)
+            .await?;
+        Ok(estimate.fee)
+    }
+
+    /// Same as [`Self::get_txs_fee_in_wei()`], but also returns a breakdown of the gas used by the transaction.
+    pub async fn get_txs_fee_estimate(
+        &self,
+        mut tx: Transaction,
+        estimated_fee_scale_factor: f64,
+        acceptable_overestimation: u64,
+    ) -> Result<FeeEstimate, SubmitTxError> {
         let estimation_started_at = Instant::now();
 
         let mut connection = self.acquire_replica_connection().await?;
@@ -706,6 +1188,22 @@ impl TxSender {
 
         let (base_fee, gas_per_pubdata_byte) =
             derive_base_fee_and_gas_per_pubdata(fee_input, protocol_version.into());
+        let cache_key = self.0.estimate_gas_cache.as_ref().and_then(|_| {
+            EstimateGasCacheKey::new(
+                &tx,
+                block_args.resolved_block_number(),
+                base_fee,
+                gas_per_pubdata_byte,
+                estimated_fee_scale_factor,
+                acceptable_overestimation,
+            )
+        });
+        if let (Some(cache), Some(key)) = (&self.0.estimate_gas_cache, &cache_key) {
+            if let Some(estimate) = cache.get(key) {
+                return Ok(estimate);
+            }
+        }
+
         match &mut tx.common_data {
             ExecuteTransactionCommon::L2(common_data) => {
                 common_data.fee.max_fee_per_gas = base_fee.into();
@@ -756,8 +1254,11 @@ impl TxSender {
         }
 
         // Acquire the vm token for the whole duration of the binary search.
-        let vm_permit = self.0.vm_concurrency_limiter.acquire().await;
-        let vm_permit = vm_permit.ok_or(SubmitTxError::ServerShuttingDown)?;
+        let vm_permit = self.0.vm_concurrency_limiter.acquire().await?;
+
+        // Storage keys accessed by the transaction are recorded during the first estimation step
+        // and are prefetched in the following steps.
+        let mut accessed_keys = None;
 
         // When the pubdata cost grows very high, the total gas limit required may become very high as well. If
         // we do binary search over any possible gas limit naively, we may end up with a very high number of iterations,
@@ -765,10 +1266,10 @@ impl TxSender {
         //
         // To optimize for this case, we first calculate the amount of gas needed to cover for the pubdata. After that, we
         // need to do a smaller binary search that is focused on computational gas limit only.
-        let additional_gas_for_pubdata = if tx.is_l1() {
+        let (additional_gas_for_pubdata, initial_gas_used) = if tx.is_l1() {
             // For L1 transactions the pubdata priced in such a way that the maximal computational
             // gas limit should be enough to cover for the pubdata as well, so no additional gas is provided there.
-            0u64
+            (0u64, None)
         } else {
             // For L2 transactions, we estimate the amount of gas needed to cover for the pubdata by creating a transaction with infinite gas limit.
             // And getting how much pubdata it used.
@@ -785,12 +1286,17 @@ impl TxSender {
                     block_args,
                     base_fee,
                     protocol_version.into(),
+                    &mut accessed_keys,
                 )
                 .await
                 .context("estimate_gas step failed")?;
 
             // It is assumed that there is no overflow here
-            (result.statistics.pubdata_published as u64) * gas_per_pubdata_byte
+            let gas_for_pubdata =
+                (result.statistics.pubdata_published as u64) * gas_per_pubdata_byte;
+            let initial_gas_used =
+                (!result.result.is_failed()).then_some(result.statistics.gas_used);
+            (gas_for_pubdata, initial_gas_used)
         };
 
         // We are using binary search to find the minimal values of gas_limit under which
@@ -809,6 +1315,45 @@ impl TxSender {
         );
 
         let mut number_of_iterations = 0usize;
+        let search_seed =
+            initial_gas_used.filter(|_| self.0.sender_config.estimate_gas_optimize_search);
+        if let Some(initial_gas_used) = search_seed {
+            let is_simple_transfer = self.is_simple_transfer(&tx).await?;
+            let (optimized_lower_bound, optimistic_gas_limits) = optimized_search_bounds(
+                initial_gas_used,
+                additional_gas_for_pubdata,
+                is_simple_transfer,
+            );
+            lower_bound = optimized_lower_bound;
+            for gas_limit in optimistic_gas_limits {
+                let (result, _) = self
+                    .estimate_gas_step(
+                        vm_permit.clone(),
+                        tx.clone(),
+                        additional_gas_for_pubdata + gas_limit,
+                        gas_per_pubdata_byte as u32,
+                        fee_input,
+                        block_args,
+                        base_fee,
+                        protocol_version.into(),
+                        &mut accessed_keys,
+                    )
+                    .await
+                    .context("estimate_gas step failed")?;
+                number_of_iterations += 1;
+
+                if result.result.is_failed() {
+                    lower_bound = gas_limit + 1;
+                } else {
+                    upper_bound = gas_limit;
+                    break;
+                }
+            }
+            tracing::trace!(
+                "fee estimation tx {tx_id:?}: optimized search bounds: lower_bound: {lower_bound}, upper_bound: {upper_bound}"
+            );
+        }
+
         while lower_bound + acceptable_overestimation < upper_bound {
             let mid = (lower_bound + upper_bound) / 2;
             // There is no way to distinct between errors due to out of gas
@@ -826,6 +1371,7 @@ impl TxSender {
                     block_args,
                     base_fee,
                     protocol_version.into(),
+                    &mut accessed_keys,
                 )
                 .await
                 .context("estimate_gas step failed")?;
@@ -866,10 +1412,15 @@ impl TxSender {
                 block_args,
                 base_fee,
                 protocol_version.into(),
+                &mut accessed_keys,
             )
             .await
             .context("final estimate_gas step failed")?;
 
+        let refunds = GasRefunds {
+            operator_suggested_refund: result.refunds.operator_suggested_refund.into(),
+            refunded_gas: result.refunds.gas_refunded.into(),
+        };
         result.into_api_call_result()?;
         self.ensure_tx_executable(&tx, &tx_metrics, false)?;
 
@@ -917,23 +1468,42 @@ impl TxSender {
                 }
             };
 
-        Ok(Fee {
-            max_fee_per_gas: base_fee.into(),
-            max_priority_fee_per_gas: 0u32.into(),
-            gas_limit: full_gas_limit.into(),
-            gas_per_pubdata_limit: gas_per_pubdata_byte.into(),
-        })
+        // It is assumed that there is no overflow here
+        let pubdata_gas = u64::from(tx_metrics.pubdata_published) * gas_per_pubdata_byte;
+        let breakdown = GasBreakdown {
+            computation_gas: (tx_metrics.gas_used as u64)
+                .saturating_sub(pubdata_gas)
+                .into(),
+            pubdata_gas: pubdata_gas.into(),
+            gas_per_pubdata: gas_per_pubdata_byte.into(),
+        };
+        let estimate = FeeEstimate {
+            fee: Fee {
+                max_fee_per_gas: base_fee.into(),
+                max_priority_fee_per_gas: 0u32.into(),
+                gas_limit: full_gas_limit.into(),
+                gas_per_pubdata_limit: gas_per_pubdata_byte.into(),
+            },
+            breakdown: Some(breakdown),
+            refunds: Some(refunds),
+        };
+        if let (Some(cache), Some(key)) = (&self.0.estimate_gas_cache, cache_key) {
+            cache.insert(key, estimate.clone());
+        }
+        Ok(estimate)
     }
 
     pub(super) async fn eth_call(
         &self,
         block_args: BlockArgs,
         tx: L2Tx,
+        prefetched_keys: Vec<StorageKey>,
+        custom_tracers: Vec<ApiTracer>,
     ) -> Result<Vec<u8>, SubmitTxError> {
-        let vm_permit = self.0.vm_concurrency_limiter.acquire().await;
-        let vm_permit = vm_permit.ok_or(SubmitTxError::ServerShuttingDown)?;
+        let vm_permit = self.0.vm_concurrency_limiter.acquire().await?;
 
         let vm_execution_cache_misses_limit = self.0.sender_config.vm_execution_cache_misses_limit;
+        let vm_execution_timeout = self.0.sender_config.vm_execution_timeout;
         self.0
             .executor
             .execute_tx_eth_call(
@@ -943,7 +1513,9 @@ impl TxSender {
                 tx,
                 block_args,
                 vm_execution_cache_misses_limit,
-                vec![],
+                vm_execution_timeout,
+                prefetched_keys,
+                custom_tracers,
             )
             .await?
             .into_api_call_result()
@@ -994,18 +1566,46 @@ impl TxSender {
             .sealer
             .find_unexecutable_reason(&seal_data, protocol_version)
         {
-            let message = format!(
-                "Tx is Unexecutable because of {reason}; inputs for decision: {seal_data:?}"
-            );
             if log_message {
-                tracing::info!("{tx_hash:#?} {message}");
+                tracing::info!(
+                    "{tx_hash:#?} Tx is Unexecutable because of {reason}; inputs for decision: {seal_data:?}"
+                );
             }
-            return Err(SubmitTxError::Unexecutable(message));
+            return Err(SubmitTxError::Unexecutable(format!(
+                "Tx is Unexecutable because of {reason}"
+            )));
         }
         Ok(())
     }
 }
 
+/// Returns the lower bound for the gas estimation binary search and optimistic gas limits to be checked before the search
+/// (in the order they should be checked). Bounds are derived from the gas used by the transaction in the initial run
+/// with the maximum gas limit, and do not include gas charged for pubdata.
+///
+/// Using the initial gas usage as the lower bound is a heuristic; a transaction may theoretically require less gas
+/// if executed with a lower gas limit, in which case the estimate will be slightly higher than necessary.
+fn optimized_search_bounds(
+    initial_gas_used: u64,
+    additional_gas_for_pubdata: u64,
+    is_simple_transfer: bool,
+) -> (u64, Vec<u64>) {
+    let lower_bound = initial_gas_used
+        .saturating_sub(additional_gas_for_pubdata)
+        .min(MAX_L2_TX_GAS_LIMIT);
+    let optimistic_gas_limit =
+        ((lower_bound as f64 * OPTIMISTIC_GAS_LIMIT_SCALE) as u64).min(MAX_L2_TX_GAS_LIMIT);
+
+    let mut gas_limits = Vec::with_capacity(2);
+    // Simple transfers don't depend on the gas limit, so they are expected to succeed with the initial gas usage,
+    // which allows skipping the binary search altogether.
+    if is_simple_transfer {
+        gas_limits.push(lower_bound);
+    }
+    gas_limits.push(optimistic_gas_limit);
+    (lower_bound, gas_limits)
+}
+
 /// During switch to the 1.4.1 protocol version, there will be a moment of discrepancy, when while
 /// the L2 has already upgraded to 1.4.1 (and thus suggests smaller overhead), the L1 is still on the previous version.
 ///
@@ -12,6 +12,7 @@ use zksync_types::{
     api::{BlockId, Transaction, TransactionDetails, TransactionId},
     fee::TransactionExecutionMetrics,
     l2::L2Tx,
+    tx::TxAccessHints,
     Address, Nonce, H256,
 };
 use zksync_web3_decl::{
@@ -218,7 +219,9 @@ impl TxSink for TxProxy {
         &self,
         tx: &L2Tx,
         _execution_metrics: TransactionExecutionMetrics,
+        _access_hints: Option<&TxAccessHints>,
     ) -> Result<L2TxSubmissionResult, SubmitTxError> {
+        // Access hints are not proxied: they are only accepted by the main node via the `unstable` namespace.
         // We're running an external node: we have to proxy the transaction to the main node.
         // But before we do that, save the tx to cache in case someone will request it
         // Before it reaches the main node.
@@ -1,9 +1,9 @@
 use multivm::interface::{ExecutionResult, VmExecutionResultAndLogs};
 use thiserror::Error;
-use zksync_types::{l2::error::TxCheckError, U256};
+use zksync_types::{l2::error::TxCheckError, Address, U256};
 use zksync_web3_decl::error::EnrichedClientError;
 
-use crate::api_server::execution_sandbox::{SandboxExecutionError, ValidationError};
+use crate::api_server::execution_sandbox::{SandboxExecutionError, ValidationError, VmPermitError};
 
 /// Errors that con occur submitting a transaction or estimating gas for its execution.
 #[derive(Debug, Error)]
@@ -28,6 +28,9 @@ pub enum SubmitTxError {
     RateLimitExceeded,
     #[error("server shutting down")]
     ServerShuttingDown,
+    /// Returned if a VM permit cannot be obtained in time.
+    #[error("server is overloaded, try again later")]
+    ServerOverloaded,
     #[error("failed to include transaction in the system. reason: {0}")]
     BootloaderFailure(String),
     #[error("failed to validate the transaction. reason: {0}")]
@@ -44,6 +47,11 @@ pub enum SubmitTxError {
     MaxFeePerGasTooLow,
     #[error("max priority fee per gas higher than max fee per gas")]
     MaxPriorityFeeGreaterThanMaxFee,
+    /// Returned if a transaction replacing a pending transaction doesn't bump its fees enough.
+    #[error(
+        "replacement transaction underpriced. required max fee per gas: {0}, max priority fee per gas: {1}"
+    )]
+    ReplacementUnderpriced(U256, U256),
     #[error(
         "virtual machine entered unexpected state. please contact developers and provide transaction details \
         that caused this error. Error description: {0}"
@@ -72,6 +80,14 @@ pub enum SubmitTxError {
     ProxyError(#[from] EnrichedClientError),
     #[error("not enough gas to publish compressed bytecodes")]
     FailedToPublishCompressedBytecodes,
+    #[error("transaction denied by the sequencer policy: {0}")]
+    DeniedByFilter(String),
+    /// Returned if the sender exceeds the per-sender transaction submission rate.
+    #[error("too many transactions submitted by {0:?}, try again later")]
+    SenderRateLimited(Address),
+    /// Returned if the sender has too many pending transactions.
+    #[error("too many pending transactions from {0:?}. max allowed: {1}")]
+    TooManyPendingTxs(Address, u32),
     /// Catch-all internal error (e.g., database error) that should not be exposed to the caller.
     #[error("internal error")]
     Internal(#[from] anyhow::Error),
@@ -90,6 +106,7 @@ impl SubmitTxError {
             Self::Unexecutable(_) => "unexecutable",
             Self::RateLimitExceeded => "rate-limit-exceeded",
             Self::ServerShuttingDown => "shutting-down",
+            Self::ServerOverloaded => "server-overloaded",
             Self::BootloaderFailure(_) => "bootloader-failure",
             Self::ValidationFailed(_) => "validation-failed",
             Self::FailedToChargeFee(_) => "failed-too-charge-fee",
@@ -98,6 +115,7 @@ impl SubmitTxError {
             Self::FromIsNotAnAccount => "from-is-not-an-account",
             Self::MaxFeePerGasTooLow => "max-fee-per-gas-too-low",
             Self::MaxPriorityFeeGreaterThanMaxFee => "max-priority-fee-greater-than-max-fee",
+            Self::ReplacementUnderpriced(_, _) => "replacement-underpriced",
             Self::UnexpectedVMBehavior(_) => "unexpected-vm-behavior",
             Self::UnrealisticPubdataPriceLimit => "unrealistic-pubdata-price-limit",
             Self::TooManyFactoryDependencies(_, _) => "too-many-factory-dependencies",
@@ -107,6 +125,9 @@ impl SubmitTxError {
             Self::IntrinsicGas => "intrinsic-gas",
             Self::ProxyError(_) => "proxy-error",
             Self::FailedToPublishCompressedBytecodes => "failed-to-publish-compressed-bytecodes",
+            Self::DeniedByFilter(_) => "denied-by-filter",
+            Self::SenderRateLimited(_) => "sender-rate-limited",
+            Self::TooManyPendingTxs(_, _) => "too-many-pending-txs",
             Self::Internal(_) => "internal",
         }
     }
@@ -149,6 +170,15 @@ impl From<SandboxExecutionError> for SubmitTxError {
     }
 }
 
+impl From<VmPermitError> for SubmitTxError {
+    fn from(err: VmPermitError) -> Self {
+        match err {
+            VmPermitError::Closed => Self::ServerShuttingDown,
+            VmPermitError::Timeout(_) => Self::ServerOverloaded,
+        }
+    }
+}
+
 impl From<ValidationError> for SubmitTxError {
     fn from(err: ValidationError) -> Self {
         match err {
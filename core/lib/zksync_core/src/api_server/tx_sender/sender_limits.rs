@@ -0,0 +1,67 @@
+//! Per-sender limits on transaction submission.
+
+use std::{
+    fmt,
+    num::NonZeroU32,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use governor::{clock::DefaultClock, state::keyed::DefaultKeyedStateStore, Quota, RateLimiter};
+use zksync_types::Address;
+
+/// Interval between purging the limiter state for senders that haven't submitted transactions recently.
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Rate limiter for transaction submissions keyed by the sender address.
+pub(super) struct SenderRateLimiter {
+    inner: RateLimiter<Address, DefaultKeyedStateStore<Address>, DefaultClock>,
+    last_cleanup: Mutex<Instant>,
+}
+
+impl fmt::Debug for SenderRateLimiter {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("SenderRateLimiter")
+            .finish_non_exhaustive()
+    }
+}
+
+impl SenderRateLimiter {
+    pub fn new(max_txs_per_minute: NonZeroU32) -> Self {
+        Self {
+            inner: RateLimiter::keyed(Quota::per_minute(max_txs_per_minute)),
+            last_cleanup: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Checks whether a transaction from the specified sender can be submitted. If it can, the submission
+    /// is accounted for.
+    pub fn check(&self, sender: Address) -> bool {
+        let mut last_cleanup = self.last_cleanup.lock().unwrap();
+        if last_cleanup.elapsed() >= CLEANUP_INTERVAL {
+            *last_cleanup = Instant::now();
+            drop(last_cleanup);
+            self.inner.retain_recent();
+        } else {
+            drop(last_cleanup);
+        }
+        self.inner.check_key(&sender).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_limiting_senders() {
+        let limiter = SenderRateLimiter::new(NonZeroU32::new(2).unwrap());
+        let sender = Address::repeat_byte(1);
+        assert!(limiter.check(sender));
+        assert!(limiter.check(sender));
+        assert!(!limiter.check(sender));
+        // Limits are per sender.
+        assert!(limiter.check(Address::repeat_byte(2)));
+    }
+}
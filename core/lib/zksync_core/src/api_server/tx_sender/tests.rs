@@ -1,13 +1,18 @@
 //! Tests for the transaction sender.
 
+use assert_matches::assert_matches;
 use zksync_config::configs::wallets::Wallets;
 use zksync_types::{get_nonce_key, L1BatchNumber, StorageLog};
+use zksync_utils::u256_to_h256;
 
-use super::*;
+use super::{master_pool_sink::MasterPoolSink, *};
 use crate::{
     api_server::execution_sandbox::{testonly::MockTransactionExecutor, VmConcurrencyBarrier},
     genesis::{insert_genesis_batch, GenesisParams},
-    utils::testonly::{create_miniblock, prepare_recovery_snapshot, MockBatchFeeParamsProvider},
+    utils::testonly::{
+        create_l2_transaction, create_miniblock, prepare_recovery_snapshot,
+        MockBatchFeeParamsProvider,
+    },
 };
 
 pub(crate) async fn create_test_tx_sender(
@@ -16,6 +21,15 @@ pub(crate) async fn create_test_tx_sender(
     tx_executor: TransactionExecutor,
 ) -> (TxSender, VmConcurrencyBarrier) {
     let web3_config = Web3JsonRpcConfig::for_tests();
+    create_test_tx_sender_with_config(pool, l2_chain_id, tx_executor, web3_config).await
+}
+
+async fn create_test_tx_sender_with_config(
+    pool: ConnectionPool<Core>,
+    l2_chain_id: L2ChainId,
+    tx_executor: TransactionExecutor,
+    web3_config: Web3JsonRpcConfig,
+) -> (TxSender, VmConcurrencyBarrier) {
     let state_keeper_config = StateKeeperConfig::for_tests();
     let wallets = Wallets::for_tests();
     let tx_sender_config = TxSenderConfig::new(
@@ -32,9 +46,11 @@ pub(crate) async fn create_test_tx_sender(
         &web3_config,
         &state_keeper_config,
         pool.clone(),
-        pool,
+        Arc::new(MasterPoolSink::new(pool)),
         batch_fee_model_input_provider,
         storage_caches,
+        crate::AaValidationState::new(&tx_sender_config),
+        Arc::new(AllowAllFilter),
     )
     .await;
 
@@ -146,3 +162,244 @@ async fn getting_nonce_for_account_after_snapshot_recovery() {
     let nonce = tx_sender.get_expected_nonce(missing_address).await.unwrap();
     assert_eq!(nonce, Nonce(0));
 }
+
+#[tokio::test]
+async fn submitting_invalid_tx_does_not_require_vm_permit() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    drop(storage);
+
+    let l2_chain_id = L2ChainId::default();
+    let tx_executor = MockTransactionExecutor::default().into();
+    let (tx_sender, vm_barrier) = create_test_tx_sender(pool, l2_chain_id, tx_executor).await;
+    // If any of the submitted transactions tried to acquire a VM permit, it would fail with `ServerShuttingDown`.
+    vm_barrier.close();
+
+    let mut tx = create_l2_transaction(1_000_000_000, 800);
+    tx.common_data.fee.gas_limit = 1_000_000.into();
+    let err = tx_sender.submit_tx(tx.clone()).await.unwrap_err();
+    assert_matches!(err, SubmitTxError::NotEnoughBalanceForFeeValue(balance, ..) if balance.is_zero());
+
+    tx.common_data.nonce = Nonce(1_000_000);
+    let err = tx_sender.submit_tx(tx.clone()).await.unwrap_err();
+    assert_matches!(err, SubmitTxError::NonceIsTooHigh(0, _, 1_000_000));
+
+    tx.common_data.fee.gas_limit = 100.into();
+    let err = tx_sender.submit_tx(tx).await.unwrap_err();
+    assert_matches!(err, SubmitTxError::IntrinsicGas);
+}
+
+#[tokio::test]
+async fn replacing_pending_tx_requires_fee_bump() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let mut pending_tx = create_l2_transaction(1_000_000_000, 800);
+    pending_tx.common_data.fee.gas_limit = 1_000_000.into();
+    storage
+        .transactions_dal()
+        .insert_transaction_l2(&pending_tx, Default::default())
+        .await
+        .unwrap();
+    drop(storage);
+
+    let l2_chain_id = L2ChainId::default();
+    let tx_executor = MockTransactionExecutor::default().into();
+    let (tx_sender, vm_barrier) = create_test_tx_sender(pool, l2_chain_id, tx_executor).await;
+    vm_barrier.close();
+    let bump_percent = tx_sender.0.sender_config.tx_replacement_fee_bump_percent;
+
+    let mut tx = create_l2_transaction(1_000_000_000, 800);
+    tx.common_data.fee.gas_limit = 1_000_000.into();
+    tx.common_data.initiator_address = pending_tx.initiator_account();
+    let err = tx_sender.submit_tx(tx.clone()).await.unwrap_err();
+    let expected_max_fee = U256::from(1_000_000_000_u64 * u64::from(100 + bump_percent) / 100);
+    assert_matches!(
+        err,
+        SubmitTxError::ReplacementUnderpriced(max_fee, priority_fee)
+            if max_fee == expected_max_fee && priority_fee.is_zero()
+    );
+
+    // With a sufficient fee bump, the transaction passes the replacement check and fails on the balance check.
+    tx.common_data.fee.max_fee_per_gas = expected_max_fee;
+    let err = tx_sender.submit_tx(tx).await.unwrap_err();
+    assert_matches!(err, SubmitTxError::NotEnoughBalanceForFeeValue(..));
+}
+
+#[tokio::test]
+async fn sender_limits_are_enforced() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let mut pending_tx = create_l2_transaction(1_000_000_000, 800);
+    pending_tx.common_data.fee.gas_limit = 1_000_000.into();
+    storage
+        .transactions_dal()
+        .insert_transaction_l2(&pending_tx, Default::default())
+        .await
+        .unwrap();
+    drop(storage);
+
+    let l2_chain_id = L2ChainId::default();
+    let tx_executor = MockTransactionExecutor::default().into();
+    let (mut tx_sender, vm_barrier) = create_test_tx_sender(pool, l2_chain_id, tx_executor).await;
+    vm_barrier.close();
+    let inner = Arc::get_mut(&mut tx_sender.0).unwrap();
+    inner.sender_config.max_pending_txs_per_sender = Some(1);
+    inner.sender_rate_limiter = Some(SenderRateLimiter::new(NonZeroU32::new(3).unwrap()));
+    let initiator = pending_tx.initiator_account();
+
+    let mut tx = create_l2_transaction(1_000_000_000, 800);
+    tx.common_data.fee.gas_limit = 1_000_000.into();
+    tx.common_data.initiator_address = initiator;
+    tx.common_data.nonce = Nonce(1);
+    let err = tx_sender.submit_tx(tx.clone()).await.unwrap_err();
+    assert_matches!(err, SubmitTxError::TooManyPendingTxs(address, 1) if address == initiator);
+
+    // Replacing the pending transaction is not subject to the pending transactions limit. The transaction
+    // passes sender checks and fails on the balance check.
+    tx.common_data.nonce = Nonce(0);
+    tx.common_data.fee.max_fee_per_gas = 2_000_000_000.into();
+    let err = tx_sender.submit_tx(tx.clone()).await.unwrap_err();
+    assert_matches!(err, SubmitTxError::NotEnoughBalanceForFeeValue(..));
+
+    let err = tx_sender.submit_tx(tx.clone()).await.unwrap_err();
+    assert_matches!(err, SubmitTxError::NotEnoughBalanceForFeeValue(..));
+    let err = tx_sender.submit_tx(tx).await.unwrap_err();
+    assert_matches!(err, SubmitTxError::SenderRateLimited(address) if address == initiator);
+}
+
+#[tokio::test]
+async fn queueing_txs_with_future_nonces() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+
+    let l2_chain_id = L2ChainId::default();
+    let tx_executor = MockTransactionExecutor::default().into();
+    let web3_config = Web3JsonRpcConfig {
+        max_queued_txs_per_account: Some(16),
+        ..Web3JsonRpcConfig::for_tests()
+    };
+    let (tx_sender, vm_barrier) =
+        create_test_tx_sender_with_config(pool.clone(), l2_chain_id, tx_executor, web3_config)
+            .await;
+    // Queueing transactions must not require a VM permit.
+    vm_barrier.close();
+    let max_nonce_ahead = tx_sender.0.sender_config.max_nonce_ahead;
+    let max_queued_txs = tx_sender.0.sender_config.max_queued_txs_per_account as u32;
+
+    let mut tx = create_l2_transaction(1_000_000_000, 800);
+    tx.common_data.fee.gas_limit = 1_000_000.into();
+    tx.common_data.nonce = Nonce(max_nonce_ahead + 1);
+    let initiator = tx.initiator_account();
+    // Transactions must not be queued if the initiator cannot pay for them.
+    let err = tx_sender.submit_tx(tx.clone()).await.unwrap_err();
+    assert_matches!(err, SubmitTxError::NotEnoughBalanceForFeeValue(..));
+    let future_txs = tx_sender.0.future_txs.as_ref().unwrap();
+    assert!(future_txs.accounts().is_empty());
+
+    let balance = U256::one() << 64;
+    let balance_log = StorageLog::new_write_log(
+        storage_key_for_eth_balance(&initiator),
+        u256_to_h256(balance),
+    );
+    storage
+        .storage_logs_dal()
+        .append_storage_logs(MiniblockNumber(0), &[(H256::default(), vec![balance_log])])
+        .await
+        .unwrap();
+    let result = tx_sender.submit_tx(tx.clone()).await.unwrap();
+    assert_matches!(result, L2TxSubmissionResult::Queued);
+    assert_eq!(future_txs.accounts(), [initiator]);
+
+    // Transactions with nonces too far ahead should still be rejected.
+    tx.common_data.nonce = Nonce(max_nonce_ahead + max_queued_txs + 1);
+    let err = tx_sender.submit_tx(tx).await.unwrap_err();
+    assert_matches!(err, SubmitTxError::NonceIsTooHigh(0, _, _));
+
+    // The queued transaction must not be released while its nonce is not accepted.
+    tx_sender.release_future_txs().await.unwrap();
+    assert_eq!(future_txs.accounts(), [initiator]);
+
+    let nonce_log = StorageLog::new_write_log(get_nonce_key(&initiator), H256::from_low_u64_be(1));
+    storage
+        .storage_logs_dal()
+        .append_storage_logs(MiniblockNumber(0), &[(H256::default(), vec![nonce_log])])
+        .await
+        .unwrap();
+    // The released transaction is rejected because of the closed VM barrier, but it must be removed from the queue.
+    tx_sender.release_future_txs().await.unwrap();
+    assert!(future_txs.accounts().is_empty());
+}
+
+#[test]
+fn optimized_gas_estimation_search_bounds() {
+    let (lower_bound, gas_limits) = optimized_search_bounds(150_000, 50_000, false);
+    assert_eq!(lower_bound, 100_000);
+    assert_eq!(gas_limits, [120_000]);
+
+    let (lower_bound, gas_limits) = optimized_search_bounds(150_000, 50_000, true);
+    assert_eq!(lower_bound, 100_000);
+    assert_eq!(gas_limits, [100_000, 120_000]);
+
+    // Bounds must not exceed the maximum gas limit.
+    let (lower_bound, gas_limits) = optimized_search_bounds(u64::MAX, 0, false);
+    assert_eq!(lower_bound, MAX_L2_TX_GAS_LIMIT);
+    assert_eq!(gas_limits, [MAX_L2_TX_GAS_LIMIT]);
+}
+
+#[test]
+fn estimate_gas_cache_basics() {
+    let tx: Transaction = create_l2_transaction(10, 100).into();
+    let key = EstimateGasCacheKey::new(&tx, MiniblockNumber(1), 10, 100, 1.3, 1_000).unwrap();
+    let estimate = FeeEstimate {
+        fee: Fee {
+            gas_limit: 100_000.into(),
+            max_fee_per_gas: 10.into(),
+            max_priority_fee_per_gas: 0.into(),
+            gas_per_pubdata_limit: 100.into(),
+        },
+        breakdown: Some(GasBreakdown {
+            computation_gas: 80_000.into(),
+            pubdata_gas: 10_000.into(),
+            gas_per_pubdata: 100.into(),
+        }),
+        refunds: None,
+    };
+
+    let cache = EstimateGasCache::new(NonZeroUsize::new(16).unwrap(), Duration::from_secs(60));
+    assert_eq!(cache.get(&key), None);
+    cache.insert(key.clone(), estimate.clone());
+    assert_eq!(cache.get(&key), Some(estimate.clone()));
+
+    // Keys for the next miniblock or for changed fees must not hit the cache.
+    let next_block_key =
+        EstimateGasCacheKey::new(&tx, MiniblockNumber(2), 10, 100, 1.3, 1_000).unwrap();
+    assert_eq!(cache.get(&next_block_key), None);
+    let other_fee_key =
+        EstimateGasCacheKey::new(&tx, MiniblockNumber(1), 20, 100, 1.3, 1_000).unwrap();
+    assert_eq!(cache.get(&other_fee_key), None);
+
+    // Expired entries must not be returned.
+    let cache = EstimateGasCache::new(NonZeroUsize::new(16).unwrap(), Duration::ZERO);
+    cache.insert(key.clone(), estimate);
+    assert_eq!(cache.get(&key), None);
+}
+
+#[test]
+fn estimate_gas_cache_key_for_deployment() {
+    let mut tx: Transaction = create_l2_transaction(10, 100).into();
+    tx.execute.factory_deps = Some(vec![vec![0; 32]]);
+    let key = EstimateGasCacheKey::new(&tx, MiniblockNumber(1), 10, 100, 1.3, 1_000);
+    assert_eq!(key, None);
+}
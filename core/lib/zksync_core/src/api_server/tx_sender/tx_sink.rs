@@ -3,6 +3,7 @@ use zksync_types::{
     api::{Transaction, TransactionDetails, TransactionId},
     fee::TransactionExecutionMetrics,
     l2::L2Tx,
+    tx::TxAccessHints,
     Address, Nonce, H256,
 };
 use zksync_web3_decl::error::Web3Error;
@@ -23,11 +24,13 @@ use super::SubmitTxError;
 /// and may be implemented as no-ops.
 #[async_trait::async_trait]
 pub trait TxSink: std::fmt::Debug + Send + Sync + 'static {
-    /// Ensures that transaction is propagated to the mempool.
+    /// Ensures that transaction is propagated to the mempool. Access hints for the transaction (if any)
+    /// should be propagated together with the transaction if the sink supports them; otherwise, they may be ignored.
     async fn submit_tx(
         &self,
         tx: &L2Tx,
         execution_metrics: TransactionExecutionMetrics,
+        access_hints: Option<&TxAccessHints>,
     ) -> Result<L2TxSubmissionResult, SubmitTxError>;
 
     /// Attempts to look up the pending nonce for the account in the sink-specific storage.
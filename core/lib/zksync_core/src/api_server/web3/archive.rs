@@ -0,0 +1,81 @@
+//! Fallback for historical queries targeting pruned blocks.
+
+use std::fmt;
+
+use async_trait::async_trait;
+use zksync_types::{api, transaction_request::CallRequest, Address, Bytes, H256, U256};
+use zksync_web3_decl::{
+    client::L2Client,
+    error::{ClientRpcContext, Web3Error},
+    namespaces::EthNamespaceClient,
+};
+
+/// Archive backend able to serve historical queries for blocks pruned from the node storage.
+/// Used by the API server as a fallback if a query targets a pruned block.
+#[async_trait]
+pub trait ArchiveClient: fmt::Debug + Send + Sync + 'static {
+    /// Executes `eth_call` for the specified block.
+    async fn call(&self, request: CallRequest, block_id: api::BlockId) -> Result<Bytes, Web3Error>;
+
+    /// Returns the value of the storage slot at the specified block.
+    async fn get_storage_at(
+        &self,
+        address: Address,
+        idx: U256,
+        block_id: api::BlockId,
+    ) -> Result<H256, Web3Error>;
+}
+
+/// [`ArchiveClient`] proxying requests to a remote archive node (i.e., a node retaining full state history).
+#[derive(Debug)]
+pub struct ArchiveNodeClient {
+    client: L2Client,
+}
+
+impl ArchiveNodeClient {
+    pub fn new(client: L2Client) -> Self {
+        Self {
+            client: client.for_component("archive_node"),
+        }
+    }
+
+    fn block_id_variant(block_id: api::BlockId) -> api::BlockIdVariant {
+        match block_id {
+            api::BlockId::Number(number) => api::BlockIdVariant::BlockNumber(number),
+            api::BlockId::Hash(block_hash) => {
+                api::BlockIdVariant::BlockHashObject(api::BlockHashObject {
+                    block_hash,
+                    require_canonical: false,
+                })
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl ArchiveClient for ArchiveNodeClient {
+    async fn call(&self, request: CallRequest, block_id: api::BlockId) -> Result<Bytes, Web3Error> {
+        Ok(self
+            .client
+            .call(request, Some(Self::block_id_variant(block_id)))
+            .rpc_context("call")
+            .with_arg("block_id", &block_id)
+            .await?)
+    }
+
+    async fn get_storage_at(
+        &self,
+        address: Address,
+        idx: U256,
+        block_id: api::BlockId,
+    ) -> Result<H256, Web3Error> {
+        Ok(self
+            .client
+            .get_storage_at(address, idx, Some(Self::block_id_variant(block_id)))
+            .rpc_context("get_storage_at")
+            .with_arg("address", &address)
+            .with_arg("idx", &idx)
+            .with_arg("block_id", &block_id)
+            .await?)
+    }
+}
@@ -0,0 +1,252 @@
+//! HTTP-level middleware requiring authentication for calls to privileged namespaces.
+
+use std::{
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use axum::{
+    body::Body,
+    http::{header, HeaderMap, Request, Response, StatusCode},
+};
+use futures::future::BoxFuture;
+use serde::Deserialize;
+use vise::{Counter, Metrics};
+use zksync_web3_decl::jsonrpsee::types::ErrorObject;
+
+use super::batch_cost::{error_response, read_body, BatchItem};
+
+/// Prefixes of methods in privileged namespaces (`debug`, `en`, `admin` and `unstable`).
+const PRIVILEGED_METHOD_PREFIXES: [&str; 4] = ["debug_", "en_", "admin_", "unstable_"];
+/// JSON-RPC error code returned for unauthenticated calls to privileged methods.
+const UNAUTHORIZED_CODE: i32 = -32_001;
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "api_jsonrpc_backend_auth")]
+struct AuthMetrics {
+    /// Number of requests rejected because they lack valid authentication.
+    rejected: Counter,
+}
+
+#[vise::register]
+static METRICS: vise::Global<AuthMetrics> = vise::Global::new();
+
+pub(crate) fn is_privileged_method(method: &str) -> bool {
+    PRIVILEGED_METHOD_PREFIXES
+        .iter()
+        .any(|prefix| method.starts_with(prefix))
+}
+
+/// Body of a JSON-RPC request; only method names are parsed.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum RequestBody {
+    Batch(Vec<BatchItem>),
+    Single(BatchItem),
+}
+
+/// Checks whether the request body contains calls to privileged methods. Bodies that are not valid JSON-RPC requests
+/// are considered non-privileged; they will be rejected by `jsonrpsee` without executing any calls.
+fn calls_privileged_methods(body: &[u8]) -> bool {
+    let Ok(body) = serde_json::from_slice::<RequestBody>(body) else {
+        return false;
+    };
+    let items = match body {
+        RequestBody::Batch(items) => items,
+        RequestBody::Single(item) => vec![item],
+    };
+    items.iter().any(|item| match item {
+        BatchItem::Call { method } => is_privileged_method(method),
+        BatchItem::Other(_) => false,
+    })
+}
+
+/// Compares byte strings in constant time, so that the token cannot be guessed based on response timing.
+fn constant_time_eq(lhs: &[u8], rhs: &[u8]) -> bool {
+    lhs.len() == rhs.len() && lhs.iter().zip(rhs).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn is_websocket_upgrade(headers: &HeaderMap) -> bool {
+    headers.get(header::UPGRADE).map_or(false, |value| {
+        value.as_bytes().eq_ignore_ascii_case(b"websocket")
+    })
+}
+
+/// HTTP middleware requiring clients to provide a secret token as an `Authorization: Bearer <token>` header
+/// in order to call methods in privileged namespaces (`debug`, `en`, `admin` and `unstable`).
+///
+/// For HTTP requests, the token is only required if the request (possibly, a batch one) calls a privileged method.
+/// WebSocket connections are authenticated once on upgrade, since individual requests are not visible
+/// on the HTTP level; if the layer is installed on a WebSocket server, unauthenticated connections are rejected.
+#[derive(Debug, Clone)]
+pub(crate) struct AuthLayer {
+    token: Arc<str>,
+}
+
+impl AuthLayer {
+    pub fn new(token: &str) -> Self {
+        Self {
+            token: token.into(),
+        }
+    }
+}
+
+impl<S> tower::Layer<S> for AuthLayer {
+    type Service = AuthService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        AuthService {
+            inner,
+            token: self.token.clone(),
+        }
+    }
+}
+
+/// Service produced by [`AuthLayer`].
+#[derive(Debug, Clone)]
+pub(crate) struct AuthService<S> {
+    inner: S,
+    token: Arc<str>,
+}
+
+impl<S> AuthService<S> {
+    fn is_authorized(&self, headers: &HeaderMap) -> bool {
+        let Some(value) = headers.get(header::AUTHORIZATION) else {
+            return false;
+        };
+        let Some(token) = value.as_bytes().strip_prefix(b"Bearer ") else {
+            return false;
+        };
+        constant_time_eq(token, self.token.as_bytes())
+    }
+}
+
+fn unauthorized_response() -> Response<Body> {
+    METRICS.rejected.inc();
+    let error = ErrorObject::owned(
+        UNAUTHORIZED_CODE,
+        "Calling methods in privileged namespaces requires authentication",
+        None::<()>,
+    );
+    error_response(StatusCode::UNAUTHORIZED, error)
+}
+
+impl<S> tower::Service<Request<Body>> for AuthService<S>
+where
+    S: tower::Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        let is_authorized = self.is_authorized(request.headers());
+        // Use the service instance that was polled for readiness, and leave a fresh clone in its place.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        if is_authorized {
+            return Box::pin(inner.call(request));
+        }
+        if is_websocket_upgrade(request.headers()) {
+            return Box::pin(async { Ok(unauthorized_response()) });
+        }
+
+        Box::pin(async move {
+            let (parts, body) = request.into_parts();
+            let body = match read_body(body).await {
+                Ok(body) => body,
+                Err(response) => return Ok(response),
+            };
+            if calls_privileged_methods(&body) {
+                return Ok(unauthorized_response());
+            }
+            inner
+                .call(Request::from_parts(parts, Body::from(body)))
+                .await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use tower::{Layer, ServiceExt};
+
+    use super::*;
+
+    #[test]
+    fn detecting_privileged_calls() {
+        assert!(!calls_privileged_methods(
+            br#"{"jsonrpc":"2.0","id":1,"method":"eth_chainId"}"#
+        ));
+        assert!(calls_privileged_methods(
+            br#"{"jsonrpc":"2.0","id":1,"method":"debug_traceCall"}"#
+        ));
+        assert!(calls_privileged_methods(
+            br#"{"jsonrpc":"2.0","id":1,"method":"unstable_simulateFeeModel"}"#
+        ));
+        assert!(!calls_privileged_methods(b"[not JSON"));
+
+        let batch = br#"
+            [
+                {"jsonrpc":"2.0","id":1,"method":"eth_chainId"},
+                {"jsonrpc":"2.0","id":2,"method":"en_syncL2Block","params":[]},
+                42
+            ]
+        "#;
+        assert!(calls_privileged_methods(batch));
+        let batch = br#"[{"jsonrpc":"2.0","id":1,"method":"zks_L1ChainId"}, 42]"#;
+        assert!(!calls_privileged_methods(batch));
+    }
+
+    #[tokio::test]
+    async fn authenticating_privileged_calls() {
+        let inner = tower::service_fn(|request: Request<Body>| async move {
+            let body = read_body(request.into_body()).await.unwrap();
+            Ok::<_, Infallible>(Response::new(Body::from(body)))
+        });
+        let service = AuthLayer::new("secret").layer(inner);
+
+        let public_call = r#"{"jsonrpc":"2.0","id":1,"method":"eth_chainId"}"#;
+        let request = Request::post("/").body(Body::from(public_call)).unwrap();
+        let response = service.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let privileged_call = r#"{"jsonrpc":"2.0","id":1,"method":"admin_listWsConnections"}"#;
+        for auth_header in [None, Some("Bearer wrong"), Some("secret")] {
+            let mut request = Request::post("/");
+            if let Some(auth_header) = auth_header {
+                request = request.header(header::AUTHORIZATION, auth_header);
+            }
+            let request = request.body(Body::from(privileged_call)).unwrap();
+            let response = service.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+            let response_body = read_body(response.into_body()).await.unwrap();
+            let response: serde_json::Value = serde_json::from_slice(&response_body).unwrap();
+            assert_eq!(response["error"]["code"], UNAUTHORIZED_CODE);
+        }
+
+        let request = Request::post("/")
+            .header(header::AUTHORIZATION, "Bearer secret")
+            .body(Body::from(privileged_call))
+            .unwrap();
+        let response = service.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response_body = read_body(response.into_body()).await.unwrap();
+        assert_eq!(response_body, privileged_call.as_bytes());
+
+        let request = Request::get("/")
+            .header(header::UPGRADE, "websocket")
+            .body(Body::empty())
+            .unwrap();
+        let response = service.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,414 @@
+//! Middleware limiting the total cost of batch JSON-RPC requests.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+
+use axum::{
+    body::{Body, Bytes, HttpBody},
+    http::{header, Request, Response, StatusCode},
+};
+use futures::future::BoxFuture;
+use pin_project_lite::pin_project;
+use serde::{de::IgnoredAny, Deserialize, Serialize};
+use vise::{Buckets, Counter, Histogram, Metrics};
+use zksync_web3_decl::jsonrpsee::{
+    server::middleware::rpc::{layer::ResponseFuture, RpcServiceT},
+    types::{
+        error::{OVERSIZED_REQUEST_CODE, PARSE_ERROR_CODE, TOO_BIG_BATCH_REQUEST_CODE},
+        ErrorObject, Request as RpcRequest,
+    },
+    MethodResponse,
+};
+
+/// Maximum size of a request body buffered by the middleware. Coincides with the default request size limit in `jsonrpsee`.
+const MAX_REQUEST_BODY_SIZE: usize = 10 * 1_024 * 1_024;
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "api_jsonrpc_backend_batch")]
+struct BatchCostMetrics {
+    /// Total cost of batch requests.
+    #[metrics(buckets = Buckets::exponential(1.0..=16_384.0, 2.0))]
+    cost: Histogram<u32>,
+    /// Number of batch requests rejected because their cost exceeds the limit.
+    rejected_by_cost: Counter,
+    /// Number of WebSocket calls rejected because the total cost of calls in flight exceeds the limit.
+    ws_rejected_calls_by_cost: Counter,
+}
+
+#[vise::register]
+static METRICS: vise::Global<BatchCostMetrics> = vise::Global::new();
+
+/// Returns the relative cost of a call to the specified JSON-RPC method. Costs roughly reflect resources necessary
+/// to process the call; e.g., tracing a transaction is much more expensive than returning the chain ID.
+fn method_cost(method_name: &str) -> u32 {
+    if method_name.starts_with("debug_trace") {
+        return 50;
+    }
+    match method_name {
+        "eth_call" | "eth_estimateGas" | "zks_estimateFee" | "zks_estimateGasL1ToL2" => 10,
+        "eth_getLogs" | "eth_getFilterLogs" | "eth_feeHistory" | "zks_getProof" => 10,
+        _ => 1,
+    }
+}
+
+/// Item of a batch JSON-RPC request. Only the method name is parsed; items without a method are still accounted for,
+/// so that they cannot be used to bypass the limit.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub(super) enum BatchItem {
+    Call { method: String },
+    Other(IgnoredAny),
+}
+
+/// Computes the total cost of a batch request. Returns `None` if the body is not a batch request (or is not valid JSON,
+/// in which case the request will be rejected by `jsonrpsee` without executing any calls).
+fn batch_cost(body: &[u8]) -> Option<u32> {
+    let first_char = body.iter().find(|&&byte| !byte.is_ascii_whitespace())?;
+    if *first_char != b'[' {
+        return None;
+    }
+    let items: Vec<BatchItem> = serde_json::from_slice(body).ok()?;
+    let cost = items.iter().map(|item| match item {
+        BatchItem::Call { method } => method_cost(method),
+        BatchItem::Other(_) => 1,
+    });
+    Some(cost.fold(0, u32::saturating_add))
+}
+
+#[derive(Debug, Serialize)]
+struct BatchCostErrorData {
+    cost: u32,
+    limit: u32,
+}
+
+pub(super) fn error_response(status: StatusCode, error: ErrorObject<'_>) -> Response<Body> {
+    let body = serde_json::json!({
+        "jsonrpc": "2.0",
+        "error": error,
+        "id": null,
+    });
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/json; charset=utf-8")
+        .body(Body::from(body.to_string()))
+        .expect("failed building error response")
+}
+
+pub(super) async fn read_body(mut body: Body) -> Result<Bytes, Response<Body>> {
+    let mut buffer = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|err| {
+            let message = format!("Failed reading request body: {err}");
+            let error = ErrorObject::owned(PARSE_ERROR_CODE, message, None::<()>);
+            error_response(StatusCode::BAD_REQUEST, error)
+        })?;
+        if buffer.len() + chunk.len() > MAX_REQUEST_BODY_SIZE {
+            let error = ErrorObject::owned(
+                OVERSIZED_REQUEST_CODE,
+                "Request is too big",
+                Some(MAX_REQUEST_BODY_SIZE),
+            );
+            return Err(error_response(StatusCode::PAYLOAD_TOO_LARGE, error));
+        }
+        buffer.extend_from_slice(&chunk);
+    }
+    Ok(buffer.into())
+}
+
+/// HTTP middleware limiting the total cost of calls in batch JSON-RPC requests, with calls weighted using [`method_cost()`].
+/// Batches exceeding the limit are rejected as a whole before any calls are executed.
+///
+/// The limit is applied on the HTTP level because `jsonrpsee` RPC middleware processes batch calls one by one.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct BatchCostLayer {
+    limit: u32,
+}
+
+impl BatchCostLayer {
+    pub fn new(limit: u32) -> Self {
+        Self { limit }
+    }
+}
+
+impl<S> tower::Layer<S> for BatchCostLayer {
+    type Service = BatchCostService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        BatchCostService {
+            inner,
+            limit: self.limit,
+        }
+    }
+}
+
+/// Service produced by [`BatchCostLayer`].
+#[derive(Debug, Clone)]
+pub(crate) struct BatchCostService<S> {
+    inner: S,
+    limit: u32,
+}
+
+impl<S> tower::Service<Request<Body>> for BatchCostService<S>
+where
+    S: tower::Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        // Use the service instance that was polled for readiness, and leave a fresh clone in its place.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let limit = self.limit;
+
+        Box::pin(async move {
+            let (parts, body) = request.into_parts();
+            let body = match read_body(body).await {
+                Ok(body) => body,
+                Err(response) => return Ok(response),
+            };
+
+            if let Some(cost) = batch_cost(&body) {
+                METRICS.cost.observe(cost);
+                if cost > limit {
+                    tracing::debug!("Rejected batch request with cost {cost} (limit: {limit})");
+                    METRICS.rejected_by_cost.inc();
+                    let error = ErrorObject::owned(
+                        TOO_BIG_BATCH_REQUEST_CODE,
+                        "Batch request cost exceeds the limit",
+                        Some(BatchCostErrorData { cost, limit }),
+                    );
+                    return Ok(error_response(StatusCode::OK, error));
+                }
+            }
+            inner
+                .call(Request::from_parts(parts, Body::from(body)))
+                .await
+        })
+    }
+}
+
+/// RPC middleware limiting the total cost of batch requests over WebSocket, with calls weighted using [`method_cost()`].
+///
+/// Unlike HTTP, batches over WebSocket are not visible to middleware as a whole, so the limit is applied to the total cost
+/// of calls in flight over the connection. `jsonrpsee` dispatches all calls in a batch before awaiting any of them,
+/// so if a batch exceeds the limit, calls beyond the limit are rejected without being executed. The first call
+/// in flight is never rejected, so that expensive single calls are not affected by the limit (same as for HTTP).
+///
+/// `jsonrpsee` will allocate the instance of this struct once per session.
+#[derive(Debug)]
+pub(crate) struct BatchCostMiddleware<S> {
+    inner: S,
+    limit: u32,
+    in_flight_cost: Arc<AtomicU32>,
+}
+
+impl<S> BatchCostMiddleware<S> {
+    pub(crate) fn new(inner: S, limit: u32) -> Self {
+        Self {
+            inner,
+            limit,
+            in_flight_cost: Arc::default(),
+        }
+    }
+}
+
+impl<'a, S> RpcServiceT<'a> for BatchCostMiddleware<S>
+where
+    S: Send + Sync + RpcServiceT<'a>,
+{
+    type Future = ResponseFuture<WithInFlightCost<S::Future>>;
+
+    fn call(&self, request: RpcRequest<'a>) -> Self::Future {
+        let cost = method_cost(request.method_name());
+        let prev_cost = self.in_flight_cost.fetch_add(cost, Ordering::SeqCst);
+        let total_cost = prev_cost.saturating_add(cost);
+        let guard = InFlightCostGuard {
+            cost,
+            in_flight_cost: self.in_flight_cost.clone(),
+        };
+
+        if prev_cost > 0 && total_cost > self.limit {
+            drop(guard);
+            let limit = self.limit;
+            tracing::debug!("Rejected WS call with total cost {total_cost} (limit: {limit})");
+            METRICS.ws_rejected_calls_by_cost.inc();
+            let error = ErrorObject::owned(
+                TOO_BIG_BATCH_REQUEST_CODE,
+                "Batch request cost exceeds the limit",
+                Some(BatchCostErrorData {
+                    cost: total_cost,
+                    limit,
+                }),
+            );
+            return ResponseFuture::ready(MethodResponse::error(request.id, error));
+        }
+        ResponseFuture::future(WithInFlightCost {
+            _guard: guard,
+            inner: self.inner.call(request),
+        })
+    }
+}
+
+/// Subtracts the call cost from the in-flight cost on drop.
+#[derive(Debug)]
+struct InFlightCostGuard {
+    cost: u32,
+    in_flight_cost: Arc<AtomicU32>,
+}
+
+impl Drop for InFlightCostGuard {
+    fn drop(&mut self) {
+        self.in_flight_cost.fetch_sub(self.cost, Ordering::SeqCst);
+    }
+}
+
+pin_project! {
+    #[derive(Debug)]
+    pub(crate) struct WithInFlightCost<F> {
+        _guard: InFlightCostGuard,
+        #[pin]
+        inner: F,
+    }
+}
+
+impl<F: Future<Output = MethodResponse>> Future for WithInFlightCost<F> {
+    type Output = MethodResponse;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.project().inner.poll(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use futures::FutureExt;
+    use tower::{Layer, ServiceExt};
+    use zksync_web3_decl::jsonrpsee::types::Id;
+
+    use super::*;
+
+    #[test]
+    fn computing_batch_cost() {
+        assert_eq!(
+            batch_cost(br#"{"jsonrpc":"2.0","id":1,"method":"eth_chainId"}"#),
+            None
+        );
+        assert_eq!(batch_cost(b"[not JSON"), None);
+        assert_eq!(batch_cost(b"[]"), Some(0));
+
+        let batch = br#"
+            [
+                {"jsonrpc":"2.0","id":1,"method":"eth_chainId"},
+                {"jsonrpc":"2.0","id":2,"method":"eth_call","params":[]},
+                {"jsonrpc":"2.0","id":3,"method":"debug_traceTransaction","params":[]},
+                {"jsonrpc":"2.0","id":4,"method":"debug_traceBlockByNumber.callFlatTracer"},
+                {"jsonrpc":"2.0","id":5},
+                42
+            ]
+        "#;
+        assert_eq!(batch_cost(batch), Some(1 + 10 + 50 + 50 + 1 + 1));
+    }
+
+    #[tokio::test]
+    async fn rejecting_expensive_batches() {
+        let inner = tower::service_fn(|request: Request<Body>| async move {
+            let body = read_body(request.into_body()).await.unwrap();
+            Ok::<_, Infallible>(Response::new(Body::from(body)))
+        });
+        let service = BatchCostLayer::new(100).layer(inner);
+
+        let call = r#"{"jsonrpc":"2.0","id":1,"method":"debug_traceCall"}"#;
+        for body in [call.to_owned(), format!("[{call},{call}]")] {
+            let request = Request::post("/").body(Body::from(body.clone())).unwrap();
+            let response = service.clone().oneshot(request).await.unwrap();
+            let response_body = read_body(response.into_body()).await.unwrap();
+            assert_eq!(response_body, body.as_bytes());
+        }
+
+        let expensive_batch = format!("[{call},{call},{call}]");
+        let request = Request::post("/")
+            .body(Body::from(expensive_batch))
+            .unwrap();
+        let response = service.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response_body = read_body(response.into_body()).await.unwrap();
+        let response: serde_json::Value = serde_json::from_slice(&response_body).unwrap();
+        assert_eq!(response["id"], serde_json::Value::Null);
+        assert_eq!(response["error"]["code"], TOO_BIG_BATCH_REQUEST_CODE);
+        assert_eq!(
+            response["error"]["data"],
+            serde_json::json!({ "cost": 150, "limit": 100 })
+        );
+    }
+
+    /// RPC service responding to all calls with an error with the code `-1`.
+    #[derive(Debug)]
+    struct MockRpcService;
+
+    impl<'a> RpcServiceT<'a> for MockRpcService {
+        type Future = BoxFuture<'a, MethodResponse>;
+
+        fn call(&self, request: RpcRequest<'a>) -> Self::Future {
+            let error = ErrorObject::owned(-1, "mock", None::<()>);
+            async move { MethodResponse::error(request.id, error) }.boxed()
+        }
+    }
+
+    fn error_code(response: &MethodResponse) -> i64 {
+        let response: serde_json::Value = serde_json::from_str(&response.result).unwrap();
+        response["error"]["code"].as_i64().unwrap()
+    }
+
+    #[tokio::test]
+    async fn rejecting_expensive_ws_calls_in_flight() {
+        let service = BatchCostMiddleware::new(MockRpcService, 100);
+        let call = |id| {
+            service.call(RpcRequest::new(
+                "debug_traceCall".into(),
+                None,
+                Id::Number(id),
+            ))
+        };
+
+        // Emulate a batch: all calls are dispatched before any of them is awaited.
+        let first_call = call(1);
+        let second_call = call(2);
+        let response = call(3).await;
+        assert_eq!(error_code(&response), i64::from(TOO_BIG_BATCH_REQUEST_CODE));
+        let response: serde_json::Value = serde_json::from_str(&response.result).unwrap();
+        assert_eq!(
+            response["error"]["data"],
+            serde_json::json!({ "cost": 150, "limit": 100 })
+        );
+
+        assert_eq!(error_code(&first_call.await), -1);
+        assert_eq!(error_code(&second_call.await), -1);
+        // The cost of completed calls is released.
+        assert_eq!(service.in_flight_cost.load(Ordering::SeqCst), 0);
+        let calls: Vec<_> = (0..2).map(call).collect();
+        for response in futures::future::join_all(calls).await {
+            assert_eq!(error_code(&response), -1);
+        }
+
+        // A single call is never rejected, even if its cost exceeds the limit.
+        let service = BatchCostMiddleware::new(MockRpcService, 10);
+        let request = RpcRequest::new("debug_traceCall".into(), None, Id::Number(1));
+        assert_eq!(error_code(&service.call(request).await), -1);
+    }
+}
@@ -3,6 +3,7 @@
 use std::{cell::RefCell, mem, sync::Arc, time::Instant};
 
 use thread_local::ThreadLocal;
+use zksync_dal::ReplicaPin;
 use zksync_types::api;
 use zksync_web3_decl::{
     error::Web3Error,
@@ -24,6 +25,8 @@ pub(crate) struct MethodMetadata {
     pub block_diff: Option<u32>,
     /// Did this call return an app-level error?
     pub has_app_error: bool,
+    /// Replica pin shared by all read-preferred DB connections acquired by the call.
+    pub replica_pin: ReplicaPin,
 }
 
 impl MethodMetadata {
@@ -34,6 +37,7 @@ impl MethodMetadata {
             block_id: None,
             block_diff: None,
             has_app_error: false,
+            replica_pin: ReplicaPin::default(),
         }
     }
 }
@@ -88,6 +92,17 @@ impl MethodTracer {
         }
     }
 
+    /// Returns the replica pin for the current JSON-RPC method call, so that all read-preferred DB connections
+    /// acquired by the call are routed to the same database. Outside method handlers, returns a new pin.
+    pub fn replica_pin(&self) -> ReplicaPin {
+        let cell = self.inner.get_or_default();
+        let pin = cell
+            .borrow()
+            .as_ref()
+            .map(|metadata| metadata.replica_pin.clone());
+        pin.unwrap_or_default()
+    }
+
     pub(super) fn new_call(self: &Arc<Self>, name: &'static str) -> MethodCall {
         MethodCall {
             tracer: self.clone(),
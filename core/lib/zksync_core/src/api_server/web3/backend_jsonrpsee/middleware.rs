@@ -27,7 +27,10 @@ use zksync_web3_decl::jsonrpsee::{
 };
 
 use super::metadata::{MethodCall, MethodTracer};
-use crate::api_server::web3::metrics::API_METRICS;
+use crate::api_server::web3::{
+    metrics::{API_METRICS, WS_METRICS},
+    ws_connections::WsConnection,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
 #[metrics(label = "transport", rename_all = "snake_case")]
@@ -104,11 +107,102 @@ where
     }
 }
 
+/// WebSocket middleware tracking the connection in [`WsConnections`](crate::api_server::web3::WsConnections).
+/// Rejects requests over connections closed by the node operator and links created subscriptions to the connection.
+///
+/// The connection is registered once per TCP connection by the server (rather than by this middleware), so that
+/// the server can terminate the connection once it's closed by the node operator.
+#[derive(Debug)]
+pub(crate) struct WsConnectionMiddleware<S> {
+    inner: S,
+    connection: Arc<WsConnection>,
+}
+
+impl<S> WsConnectionMiddleware<S> {
+    pub(crate) fn new(inner: S, connection: Arc<WsConnection>) -> Self {
+        Self { inner, connection }
+    }
+}
+
+impl<'a, S> RpcServiceT<'a> for WsConnectionMiddleware<S>
+where
+    S: Send + Sync + RpcServiceT<'a>,
+{
+    type Future = ResponseFuture<WithSubscriptionTracking<S::Future>>;
+
+    fn call(&self, request: Request<'a>) -> Self::Future {
+        if !self.connection.record_request() {
+            WS_METRICS.rejected_requests.inc();
+            let rp = MethodResponse::error(
+                request.id,
+                ErrorObject::borrowed(
+                    ErrorCode::ServerError(reqwest::StatusCode::FORBIDDEN.as_u16().into()).code(),
+                    "Connection closed by the server",
+                    None,
+                ),
+            );
+            return ResponseFuture::ready(rp);
+        }
+
+        let is_subscription = request.method_name() == SUBSCRIBE_METHOD_NAME;
+        ResponseFuture::future(WithSubscriptionTracking {
+            connection: is_subscription.then(|| self.connection.clone()),
+            inner: self.inner.call(request),
+        })
+    }
+}
+
+const SUBSCRIBE_METHOD_NAME: &str = "eth_subscribe";
+
+pin_project! {
+    #[derive(Debug)]
+    pub(crate) struct WithSubscriptionTracking<F> {
+        // Only set for subscription requests.
+        connection: Option<Arc<WsConnection>>,
+        #[pin]
+        inner: F,
+    }
+}
+
+impl<F> WithSubscriptionTracking<F> {
+    fn subscription_id(response: &MethodResponse) -> Option<String> {
+        if !response.is_success() {
+            return None;
+        }
+        let response: serde_json::Value = serde_json::from_str(&response.result).ok()?;
+        match response.get("result")? {
+            serde_json::Value::String(id) => Some(id.clone()),
+            serde_json::Value::Number(id) => Some(id.to_string()),
+            _ => None,
+        }
+    }
+}
+
+impl<F: Future<Output = MethodResponse>> Future for WithSubscriptionTracking<F> {
+    type Output = MethodResponse;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let projection = self.project();
+        match projection.inner.poll(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(response) => {
+                if let Some(connection) = projection.connection.take() {
+                    if let Some(subscription_id) = Self::subscription_id(&response) {
+                        connection.add_subscription(subscription_id);
+                    }
+                }
+                Poll::Ready(response)
+            }
+        }
+    }
+}
+
 /// RPC-level middleware that adds [`MethodCall`] metadata to method logic. Method handlers can then access this metadata
 /// using [`MethodTracer`], which is a part of `RpcState`. When the handler completes or is dropped, the results are reported
 /// as metrics.
 ///
 /// As an example, a method handler can set the requested block ID, which would then be used in relevant metric labels.
+/// Additionally, each call is wrapped in an `rpc_call` tracing span.
 #[derive(Debug)]
 pub(crate) struct MetadataMiddleware<S> {
     inner: S,
@@ -145,9 +239,12 @@ where
             .copied()
             .unwrap_or("");
 
+        // The span is a parent for spans created by the method handler, e.g. for DB queries and VM execution.
+        let span = tracing::info_span!("rpc_call", method = method_name);
         WithMethodCall {
             call: self.method_tracer.new_call(method_name),
-            inner: self.inner.call(request),
+            inner: span.in_scope(|| self.inner.call(request)),
+            span,
         }
     }
 }
@@ -156,6 +253,7 @@ pin_project! {
     #[derive(Debug)]
     pub(crate) struct WithMethodCall<F> {
         call: MethodCall,
+        span: tracing::Span,
         #[pin]
         inner: F,
     }
@@ -166,6 +264,7 @@ impl<F: Future<Output = MethodResponse>> Future for WithMethodCall<F> {
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let projection = self.project();
+        let _entered = projection.span.enter();
         let guard = projection.call.set_as_current();
         match projection.inner.poll(cx) {
             Poll::Pending => Poll::Pending,
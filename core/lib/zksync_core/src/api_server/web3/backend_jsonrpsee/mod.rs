@@ -8,16 +8,25 @@ use zksync_web3_decl::{
 };
 
 pub(crate) use self::{
+    auth::{is_privileged_method, AuthLayer},
+    batch_cost::{BatchCostLayer, BatchCostMiddleware},
     metadata::{MethodMetadata, MethodTracer},
-    middleware::{LimitMiddleware, MetadataMiddleware, ShutdownMiddleware, TrafficTracker},
+    middleware::{
+        LimitMiddleware, MetadataMiddleware, ShutdownMiddleware, TrafficTracker,
+        WsConnectionMiddleware,
+    },
+    trace_context::TraceContextLayer,
 };
 use crate::api_server::tx_sender::SubmitTxError;
 
+mod auth;
+mod batch_cost;
 mod metadata;
 mod middleware;
 pub mod namespaces;
 #[cfg(test)]
 pub(crate) mod testonly;
+mod trace_context;
 
 impl MethodTracer {
     pub(crate) fn map_err(&self, err: Web3Error) -> ErrorObjectOwned {
@@ -34,9 +43,11 @@ impl MethodTracer {
             Web3Error::NoBlock
             | Web3Error::PrunedBlock(_)
             | Web3Error::PrunedL1Batch(_)
+            | Web3Error::NonCanonicalBlock(_)
             | Web3Error::TooManyTopics
             | Web3Error::FilterNotFound
             | Web3Error::InvalidFilterBlockHash
+            | Web3Error::InvalidParams(_)
             | Web3Error::LogsLimitExceeded(_, _, _) => ErrorCode::InvalidParams.code(),
             Web3Error::SubmitTransactionError(_, _)
             | Web3Error::SerializationError(_)
@@ -0,0 +1,72 @@
+use async_trait::async_trait;
+use zksync_types::{
+    api::{AaValidationRules, BatchSealStatus, CacheInfo, SlowQueryInfo, WsConnectionInfo},
+    Address, L1BatchNumber,
+};
+use zksync_web3_decl::{jsonrpsee::core::RpcResult, namespaces::AdminNamespaceServer};
+
+use crate::api_server::web3::namespaces::AdminNamespace;
+
+#[async_trait]
+impl AdminNamespaceServer for AdminNamespace {
+    async fn get_aa_validation_rules(&self) -> RpcResult<AaValidationRules> {
+        Ok(self.get_aa_validation_rules_impl().await)
+    }
+
+    async fn set_aa_validation_rules(&self, rules: AaValidationRules) -> RpcResult<()> {
+        self.set_aa_validation_rules_impl(rules)
+            .await
+            .map_err(|err| self.current_method().map_err(err))
+    }
+
+    async fn get_whitelisted_tokens_for_aa(&self) -> RpcResult<Vec<Address>> {
+        Ok(self.get_whitelisted_tokens_for_aa_impl().await)
+    }
+
+    async fn add_whitelisted_tokens_for_aa(&self, tokens: Vec<Address>) -> RpcResult<Vec<Address>> {
+        Ok(self.add_whitelisted_tokens_for_aa_impl(tokens).await)
+    }
+
+    async fn remove_whitelisted_tokens_for_aa(
+        &self,
+        tokens: Vec<Address>,
+    ) -> RpcResult<Vec<Address>> {
+        Ok(self.remove_whitelisted_tokens_for_aa_impl(tokens).await)
+    }
+
+    async fn get_ws_connections(&self) -> RpcResult<Vec<WsConnectionInfo>> {
+        Ok(self.get_ws_connections_impl())
+    }
+
+    async fn close_ws_connection(&self, id: u64) -> RpcResult<bool> {
+        Ok(self.close_ws_connection_impl(id))
+    }
+
+    async fn get_batch_seal_status(&self) -> RpcResult<Option<BatchSealStatus>> {
+        Ok(self.get_batch_seal_status_impl())
+    }
+
+    async fn get_slow_queries(&self) -> RpcResult<Vec<SlowQueryInfo>> {
+        Ok(self.get_slow_queries_impl())
+    }
+
+    async fn rollback_tree(&self, l1_batch_number: L1BatchNumber) -> RpcResult<()> {
+        self.rollback_tree_impl(l1_batch_number)
+            .await
+            .map_err(|err| self.current_method().map_err(err))
+    }
+
+    async fn get_cache_stats(&self) -> RpcResult<Vec<CacheInfo>> {
+        Ok(self.get_cache_stats_impl())
+    }
+
+    async fn flush_caches(&self, names: Option<Vec<String>>) -> RpcResult<Vec<String>> {
+        self.flush_caches_impl(names)
+            .map_err(|err| self.current_method().map_err(err))
+    }
+
+    async fn set_cache_capacity(&self, name: String, capacity: u64) -> RpcResult<u64> {
+        self.set_cache_capacity_impl(&name, capacity)
+            .map_err(|err| self.current_method().map_err(err))
+    }
+}
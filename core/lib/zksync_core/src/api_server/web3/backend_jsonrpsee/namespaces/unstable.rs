@@ -0,0 +1,41 @@
+use async_trait::async_trait;
+use zksync_types::{
+    api::{FeeModelSimulationBatch, FeeModelSimulationParams, PriorityOpAuditRecord},
+    web3::types::AccessList,
+    Bytes, PriorityOpId, H256,
+};
+use zksync_web3_decl::{jsonrpsee::core::RpcResult, namespaces::UnstableNamespaceServer};
+
+use crate::api_server::web3::namespaces::UnstableNamespace;
+
+#[async_trait]
+impl UnstableNamespaceServer for UnstableNamespace {
+    async fn get_priority_op_history(
+        &self,
+        priority_op_id: PriorityOpId,
+    ) -> RpcResult<Vec<PriorityOpAuditRecord>> {
+        self.get_priority_op_history_impl(priority_op_id)
+            .await
+            .map_err(|err| self.current_method().map_err(err))
+    }
+
+    async fn send_raw_transaction_with_hints(
+        &self,
+        tx_bytes: Bytes,
+        access_list: AccessList,
+    ) -> RpcResult<H256> {
+        self.send_raw_transaction_with_hints_impl(tx_bytes, access_list)
+            .await
+            .map_err(|err| self.current_method().map_err(err))
+    }
+
+    async fn simulate_fee_model(
+        &self,
+        params: FeeModelSimulationParams,
+        batch_count: u64,
+    ) -> RpcResult<Vec<FeeModelSimulationBatch>> {
+        self.simulate_fee_model_impl(params, batch_count)
+            .await
+            .map_err(|err| self.current_method().map_err(err))
+    }
+}
@@ -2,10 +2,11 @@ use std::collections::HashMap;
 
 use zksync_types::{
     api::{
-        BlockDetails, BridgeAddresses, L1BatchDetails, L2ToL1LogProof, Proof, ProtocolVersion,
-        TransactionDetails,
+        BlockDetails, BlockIdVariant, BridgeAddresses, CallOptions, CallResult, L1BatchDetails,
+        L1BatchPubdata, L2ToL1LogProof, PendingGovernanceOperation, Proof, ProtocolVersion,
+        ProtocolVersionInfo, StorageProofs, StorageSlot, TransactionDetails,
     },
-    fee::Fee,
+    fee::FeeEstimate,
     fee_model::FeeParams,
     transaction_request::CallRequest,
     Address, L1BatchNumber, MiniblockNumber, H256, U256, U64,
@@ -13,14 +14,14 @@ use zksync_types::{
 use zksync_web3_decl::{
     jsonrpsee::core::{async_trait, RpcResult},
     namespaces::zks::ZksNamespaceServer,
-    types::Token,
+    types::{Bytes, Token},
 };
 
 use crate::api_server::web3::ZksNamespace;
 
 #[async_trait]
 impl ZksNamespaceServer for ZksNamespace {
-    async fn estimate_fee(&self, req: CallRequest) -> RpcResult<Fee> {
+    async fn estimate_fee(&self, req: CallRequest) -> RpcResult<FeeEstimate> {
         self.estimate_fee_impl(req)
             .await
             .map_err(|err| self.current_method().map_err(err))
@@ -45,7 +46,9 @@ impl ZksNamespaceServer for ZksNamespace {
     }
 
     async fn get_bridge_contracts(&self) -> RpcResult<BridgeAddresses> {
-        Ok(self.get_bridge_contracts_impl())
+        self.get_bridge_contracts_impl()
+            .await
+            .map_err(|err| self.current_method().map_err(err))
     }
 
     async fn l1_chain_id(&self) -> RpcResult<U64> {
@@ -125,6 +128,12 @@ impl ZksNamespaceServer for ZksNamespace {
             .map_err(|err| self.current_method().map_err(err))
     }
 
+    async fn get_raw_block(&self, block_number: MiniblockNumber) -> RpcResult<Option<Bytes>> {
+        self.get_raw_block_impl(block_number)
+            .await
+            .map_err(|err| self.current_method().map_err(err))
+    }
+
     async fn get_l1_batch_details(
         &self,
         batch_number: L1BatchNumber,
@@ -134,6 +143,15 @@ impl ZksNamespaceServer for ZksNamespace {
             .map_err(|err| self.current_method().map_err(err))
     }
 
+    async fn get_l1_batch_pubdata(
+        &self,
+        batch: L1BatchNumber,
+    ) -> RpcResult<Option<L1BatchPubdata>> {
+        self.get_l1_batch_pubdata_impl(batch)
+            .await
+            .map_err(|err| self.current_method().map_err(err))
+    }
+
     async fn get_bytecode_by_hash(&self, hash: H256) -> RpcResult<Option<Vec<u8>>> {
         self.get_bytecode_by_hash_impl(hash)
             .await
@@ -157,6 +175,12 @@ impl ZksNamespaceServer for ZksNamespace {
             .map_err(|err| self.current_method().map_err(err))
     }
 
+    async fn get_protocol_versions(&self) -> RpcResult<Vec<ProtocolVersionInfo>> {
+        self.get_protocol_versions_impl()
+            .await
+            .map_err(|err| self.current_method().map_err(err))
+    }
+
     async fn get_proof(
         &self,
         address: Address,
@@ -167,4 +191,31 @@ impl ZksNamespaceServer for ZksNamespace {
             .await
             .map_err(|err| self.current_method().map_err(err))
     }
+
+    async fn get_storage_proofs(
+        &self,
+        slots: Vec<StorageSlot>,
+        l1_batch_number: L1BatchNumber,
+    ) -> RpcResult<Option<StorageProofs>> {
+        self.get_storage_proofs_impl(slots, l1_batch_number)
+            .await
+            .map_err(|err| self.current_method().map_err(err))
+    }
+
+    async fn call_with_options(
+        &self,
+        req: CallRequest,
+        block: Option<BlockIdVariant>,
+        options: Option<CallOptions>,
+    ) -> RpcResult<CallResult> {
+        self.call_impl(req, block.map(Into::into), options.unwrap_or_default())
+            .await
+            .map_err(|err| self.current_method().map_err(err))
+    }
+
+    async fn get_pending_governance_ops(&self) -> RpcResult<Vec<PendingGovernanceOperation>> {
+        self.get_pending_governance_ops_impl()
+            .await
+            .map_err(|err| self.current_method().map_err(err))
+    }
 }
@@ -0,0 +1,57 @@
+//! HTTP-level middleware propagating distributed tracing context.
+
+use std::task::{Context, Poll};
+
+use axum::http::Request;
+use tracing::{instrument::Instrumented, Instrument};
+
+/// Headers defined by the [W3C Trace Context](https://www.w3.org/TR/trace-context/) spec.
+const TRACE_CONTEXT_HEADERS: [&str; 2] = ["traceparent", "tracestate"];
+
+/// HTTP middleware creating a span for each request. If the request has W3C Trace Context headers, the span
+/// is attached to the propagated trace, so that the caller can correlate its request with RPC handling, DB queries
+/// and VM execution on the server.
+///
+/// Only used for the HTTP server; WebSocket requests are processed outside the connection upgrade request,
+/// so they cannot inherit its trace context.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TraceContextLayer;
+
+impl<S> tower::Layer<S> for TraceContextLayer {
+    type Service = TraceContextService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TraceContextService { inner }
+    }
+}
+
+/// Service produced by [`TraceContextLayer`].
+#[derive(Debug, Clone)]
+pub(crate) struct TraceContextService<S> {
+    inner: S,
+}
+
+impl<S, B> tower::Service<Request<B>> for TraceContextService<S>
+where
+    S: tower::Service<Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Instrumented<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<B>) -> Self::Future {
+        let span = tracing::info_span!("http_request", path = request.uri().path());
+        let headers = TRACE_CONTEXT_HEADERS.into_iter().filter_map(|name| {
+            let value = request.headers().get(name)?.to_str().ok()?;
+            Some((name, value))
+        });
+        vlog::set_remote_parent(&span, headers);
+
+        let inner_future = span.in_scope(|| self.inner.call(request));
+        inner_future.instrument(span)
+    }
+}
@@ -0,0 +1,378 @@
+//! Unix domain socket (IPC) transport for the JSON-RPC server. Intended for co-located services (indexers, sidecars)
+//! that want to avoid TCP overhead; compatible with tooling expecting a Geth-style `--ipcpath` endpoint.
+//!
+//! Requests are read as a stream of JSON values (not necessarily separated by whitespace), and responses
+//! are written as newline-delimited JSON in the order the requests were received. Subscription notifications
+//! are interleaved with responses. Calls are dispatched directly to the RPC module, i.e., bypassing the middleware
+//! of the HTTP / WS servers; thus, IPC calls are not authenticated, are not subject to rate limiting and are
+//! not reflected in method metrics. To not expose privileged namespaces (`debug`, `en`, `admin` and `unstable`)
+//! without authentication, they are never served via IPC.
+
+use std::{
+    io,
+    os::unix::fs::FileTypeExt,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context as _;
+use serde_json::value::RawValue;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{unix::OwnedWriteHalf, UnixListener, UnixStream},
+    sync::{mpsc, watch},
+    task::JoinSet,
+};
+use zksync_web3_decl::jsonrpsee::{
+    types::{
+        error::{INVALID_REQUEST_CODE, OVERSIZED_REQUEST_CODE, PARSE_ERROR_CODE},
+        ErrorObject,
+    },
+    RpcModule,
+};
+
+use super::backend_jsonrpsee::is_privileged_method;
+
+/// Maximum size of a single request (including batch requests). Connections sending larger requests are closed.
+/// Matches the default request size limit for the HTTP / WS servers.
+const MAX_REQUEST_SIZE: usize = 10 * 1_024 * 1_024;
+/// Number of subscription notifications buffered for a single subscription before it's terminated.
+const SUBSCRIPTION_BUFFER_SIZE: usize = 1_024;
+/// Number of responses buffered for a single connection before request processing is paused.
+const RESPONSES_BUFFER_SIZE: usize = 128;
+
+fn error_response(code: i32, message: &str) -> String {
+    let error = ErrorObject::owned(code, message, None::<()>);
+    let response = serde_json::json!({
+        "jsonrpc": "2.0",
+        "error": error,
+        "id": null,
+    });
+    response.to_string()
+}
+
+/// Extracts complete JSON values from the start of the buffer, leaving an incomplete trailing value (if any)
+/// in the buffer.
+fn take_requests(buffer: &mut Vec<u8>) -> Result<Vec<Box<RawValue>>, serde_json::Error> {
+    let mut values = serde_json::Deserializer::from_slice(buffer).into_iter::<Box<RawValue>>();
+    let mut requests = vec![];
+    let mut consumed_len = 0;
+    loop {
+        match values.next() {
+            Some(Ok(request)) => {
+                requests.push(request);
+                consumed_len = values.byte_offset();
+            }
+            Some(Err(err)) if err.is_eof() => break,
+            Some(Err(err)) => return Err(err),
+            None => {
+                consumed_len = values.byte_offset();
+                break;
+            }
+        }
+    }
+    buffer.drain(..consumed_len);
+    Ok(requests)
+}
+
+/// JSON-RPC server listening on a Unix domain socket.
+#[derive(Debug)]
+pub(super) struct IpcServer {
+    path: PathBuf,
+    listener: UnixListener,
+    rpc: RpcModule<()>,
+}
+
+impl IpcServer {
+    /// Binds the server to the specified socket path. A stale socket file left by a previous server instance
+    /// is removed; other kinds of files at the path are never overwritten. Methods in privileged namespaces
+    /// are removed from the provided RPC module.
+    pub fn bind(path: PathBuf, mut rpc: RpcModule<()>) -> anyhow::Result<Self> {
+        let privileged_methods: Vec<_> = rpc
+            .method_names()
+            .filter(|name| is_privileged_method(name))
+            .collect();
+        for method_name in privileged_methods {
+            rpc.remove_method(method_name);
+        }
+
+        Self::remove_stale_socket(&path)?;
+        let listener = UnixListener::bind(&path)
+            .with_context(|| format!("Failed binding IPC server to {path:?}"))?;
+        tracing::info!("Started IPC server at {path:?}");
+        Ok(Self {
+            path,
+            listener,
+            rpc,
+        })
+    }
+
+    fn remove_stale_socket(path: &Path) -> anyhow::Result<()> {
+        let metadata = match std::fs::symlink_metadata(path) {
+            Ok(metadata) => metadata,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(err) => {
+                return Err(err).with_context(|| format!("Failed getting metadata for {path:?}"))
+            }
+        };
+        anyhow::ensure!(
+            metadata.file_type().is_socket(),
+            "Cannot bind IPC server to {path:?}: path exists and is not a socket"
+        );
+        tracing::info!("Removing stale IPC socket at {path:?}");
+        std::fs::remove_file(path).with_context(|| format!("Failed removing {path:?}"))
+    }
+
+    pub async fn run(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        let mut connections = JoinSet::new();
+        loop {
+            tokio::select! {
+                accept_result = self.listener.accept() => {
+                    let (stream, _) = accept_result.context("Failed accepting IPC connection")?;
+                    tracing::debug!("Accepted IPC connection");
+                    connections.spawn(serve_connection(stream, self.rpc.clone(), stop_receiver.clone()));
+                }
+                Some(_) = connections.join_next() => {
+                    // Finished connections are reaped here, so that the set doesn't grow indefinitely.
+                }
+                _ = stop_receiver.changed() => break,
+            }
+        }
+
+        tracing::info!("Stop signal received, IPC server is shutting down");
+        drop(self.listener);
+        if let Err(err) = std::fs::remove_file(&self.path) {
+            tracing::warn!("Failed removing IPC socket {:?}: {err}", self.path);
+        }
+        // Connections are closed on stop signal themselves.
+        while connections.join_next().await.is_some() {}
+        tracing::info!("IPC server shut down");
+        Ok(())
+    }
+}
+
+async fn serve_connection(
+    stream: UnixStream,
+    rpc: RpcModule<()>,
+    mut stop_receiver: watch::Receiver<bool>,
+) {
+    let (mut reader, writer) = stream.into_split();
+    let (responses_sender, responses_receiver) = mpsc::channel(RESPONSES_BUFFER_SIZE);
+    let writer_task = tokio::spawn(write_responses(writer, responses_receiver));
+    let mut subscriptions = JoinSet::new();
+    let mut buffer = Vec::new();
+
+    'connection: loop {
+        tokio::select! {
+            read_result = reader.read_buf(&mut buffer) => {
+                match read_result {
+                    Ok(0) => break,
+                    Ok(_) => { /* Continue processing */ }
+                    Err(err) => {
+                        tracing::debug!("Failed reading from IPC connection: {err}");
+                        break;
+                    }
+                }
+            }
+            Some(_) = subscriptions.join_next() => continue,
+            _ = stop_receiver.changed() => break,
+        }
+
+        let requests = match take_requests(&mut buffer) {
+            Ok(requests) => requests,
+            Err(err) => {
+                // We cannot recover the request boundaries after a syntax error, so the connection is closed.
+                let message = format!("Parse error: {err}");
+                responses_sender
+                    .send(error_response(PARSE_ERROR_CODE, &message))
+                    .await
+                    .ok();
+                break;
+            }
+        };
+        for request in requests {
+            let (response, notification_receivers) = handle_request(&rpc, &request).await;
+            if responses_sender.send(response).await.is_err() {
+                break 'connection; // The writer has failed
+            }
+            // Notifications are forwarded only after the response is sent, so that clients receive
+            // the subscription ID before any notifications.
+            for receiver in notification_receivers {
+                subscriptions.spawn(forward_notifications(receiver, responses_sender.clone()));
+            }
+        }
+
+        if buffer.len() > MAX_REQUEST_SIZE {
+            let response = error_response(OVERSIZED_REQUEST_CODE, "Request is too big");
+            responses_sender.send(response).await.ok();
+            break;
+        }
+    }
+
+    // Dropping subscription receivers terminates the corresponding subscriptions.
+    subscriptions.shutdown().await;
+    drop(responses_sender);
+    match writer_task.await {
+        Ok(Ok(())) => tracing::debug!("Closed IPC connection"),
+        Ok(Err(err)) => tracing::debug!("Failed writing to IPC connection: {err}"),
+        Err(err) => tracing::warn!("IPC connection writer panicked: {err}"),
+    }
+}
+
+async fn write_responses(
+    mut writer: OwnedWriteHalf,
+    mut responses_receiver: mpsc::Receiver<String>,
+) -> io::Result<()> {
+    while let Some(response) = responses_receiver.recv().await {
+        writer.write_all(response.as_bytes()).await?;
+        writer.write_all(b"\n").await?;
+    }
+    writer.shutdown().await
+}
+
+/// Handles a single or batch request. Returns the response and receivers for notifications produced by the calls.
+/// For calls other than subscriptions, notification channels are closed immediately.
+async fn handle_request(
+    rpc: &RpcModule<()>,
+    request: &RawValue,
+) -> (String, Vec<mpsc::Receiver<String>>) {
+    if !request.get().starts_with('[') {
+        let (response, receiver) = handle_call(rpc, request).await;
+        return (response, receiver.into_iter().collect());
+    }
+
+    let calls = match serde_json::from_str::<Vec<&RawValue>>(request.get()) {
+        Ok(calls) if !calls.is_empty() => calls,
+        _ => {
+            let response = error_response(INVALID_REQUEST_CODE, "Invalid batch request");
+            return (response, vec![]);
+        }
+    };
+    let mut responses = Vec::with_capacity(calls.len());
+    let mut receivers = vec![];
+    for call in calls {
+        let (response, receiver) = handle_call(rpc, call).await;
+        responses.push(response);
+        receivers.extend(receiver);
+    }
+    (format!("[{}]", responses.join(",")), receivers)
+}
+
+async fn handle_call(
+    rpc: &RpcModule<()>,
+    call: &RawValue,
+) -> (String, Option<mpsc::Receiver<String>>) {
+    match rpc
+        .raw_json_request(call.get(), SUBSCRIPTION_BUFFER_SIZE)
+        .await
+    {
+        Ok((response, receiver)) => (response.result, Some(receiver)),
+        Err(err) => {
+            let message = format!("Invalid request: {err}");
+            (error_response(INVALID_REQUEST_CODE, &message), None)
+        }
+    }
+}
+
+async fn forward_notifications(
+    mut receiver: mpsc::Receiver<String>,
+    responses_sender: mpsc::Sender<String>,
+) {
+    while let Some(notification) = receiver.recv().await {
+        if responses_sender.send(notification).await.is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    use super::*;
+
+    #[test]
+    fn taking_requests_from_buffer() {
+        let mut buffer = br#"{"id":1}[{"id":2}, {"id":3}]  {"id":"#.to_vec();
+        let requests = take_requests(&mut buffer).unwrap();
+        let requests: Vec<_> = requests.iter().map(|req| req.get()).collect();
+        assert_eq!(requests, [r#"{"id":1}"#, r#"[{"id":2}, {"id":3}]"#]);
+        assert_eq!(buffer, br#"  {"id":"#);
+
+        buffer.extend_from_slice(b"4}\n");
+        let requests = take_requests(&mut buffer).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].get(), r#"{"id":4}"#);
+        assert!(buffer.is_empty());
+
+        buffer.extend_from_slice(b"{]");
+        take_requests(&mut buffer).unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn serving_requests_via_ipc() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("zksync.ipc");
+        let mut rpc = RpcModule::new(());
+        rpc.register_method("test_add", |params, _| {
+            let (x, y) = params.parse::<(u64, u64)>()?;
+            Ok::<_, ErrorObject<'static>>(x + y)
+        })
+        .unwrap();
+        rpc.register_method("admin_test", |_, _| Ok::<_, ErrorObject<'static>>(()))
+            .unwrap();
+
+        let (stop_sender, stop_receiver) = watch::channel(false);
+        let server = IpcServer::bind(path.clone(), rpc).unwrap();
+        let server_task = tokio::spawn(server.run(stop_receiver));
+
+        let stream = UnixStream::connect(&path).await.unwrap();
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        // Send two requests split across writes.
+        writer
+            .write_all(br#"{"jsonrpc":"2.0","id":1,"method":"test_add","params":[1,2]}{"jsonrpc":"#)
+            .await
+            .unwrap();
+        let line = lines.next_line().await.unwrap().unwrap();
+        let response: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"], 3);
+
+        writer
+            .write_all(br#""2.0","id":2,"method":"test_add","params":[3,4]}"#)
+            .await
+            .unwrap();
+        let line = lines.next_line().await.unwrap().unwrap();
+        let response: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(response["id"], 2);
+        assert_eq!(response["result"], 7);
+
+        let batch = br#"[
+            {"jsonrpc":"2.0","id":3,"method":"test_add","params":[5,6]},
+            {"jsonrpc":"2.0","id":4,"method":"test_unknown","params":[]}
+        ]"#;
+        writer.write_all(batch).await.unwrap();
+        let line = lines.next_line().await.unwrap().unwrap();
+        let response: serde_json::Value = serde_json::from_str(&line).unwrap();
+        let response = response.as_array().unwrap();
+        assert_eq!(response.len(), 2);
+        assert_eq!(response[0]["result"], 11);
+        assert!(response[1]["error"].is_object(), "{response:?}");
+
+        // Privileged methods are not served via IPC.
+        writer
+            .write_all(br#"{"jsonrpc":"2.0","id":5,"method":"admin_test","params":[]}"#)
+            .await
+            .unwrap();
+        let line = lines.next_line().await.unwrap().unwrap();
+        let response: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(response["id"], 5);
+        assert_eq!(response["error"]["code"], -32_601, "{response:?}"); // method not found
+
+        stop_sender.send_replace(true);
+        server_task.await.unwrap().unwrap();
+        assert!(!path.exists());
+        // The connection should be closed by the server.
+        assert!(lines.next_line().await.unwrap().is_none());
+    }
+}
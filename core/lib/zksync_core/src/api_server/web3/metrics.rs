@@ -93,6 +93,7 @@ impl From<&MethodMetadata> for MethodLabels {
 enum Web3ErrorKind {
     NoBlock,
     Pruned,
+    NonCanonicalBlock,
     SubmitTransaction,
     TransactionSerialization,
     Proxy,
@@ -100,6 +101,7 @@ enum Web3ErrorKind {
     FilterNotFound,
     LogsLimitExceeded,
     InvalidFilterBlockHash,
+    InvalidParams,
     TreeApiUnavailable,
     Internal,
 }
@@ -109,6 +111,7 @@ impl Web3ErrorKind {
         match err {
             Web3Error::NoBlock => Self::NoBlock,
             Web3Error::PrunedBlock(_) | Web3Error::PrunedL1Batch(_) => Self::Pruned,
+            Web3Error::NonCanonicalBlock(_) => Self::NonCanonicalBlock,
             Web3Error::SubmitTransactionError(..) => Self::SubmitTransaction,
             Web3Error::ProxyError(_) => Self::Proxy,
             Web3Error::SerializationError(_) => Self::TransactionSerialization,
@@ -116,6 +119,7 @@ impl Web3ErrorKind {
             Web3Error::FilterNotFound => Self::FilterNotFound,
             Web3Error::LogsLimitExceeded(..) => Self::LogsLimitExceeded,
             Web3Error::InvalidFilterBlockHash => Self::InvalidFilterBlockHash,
+            Web3Error::InvalidParams(_) => Self::InvalidParams,
             Web3Error::TreeApiUnavailable => Self::TreeApiUnavailable,
             Web3Error::InternalError(_) | Web3Error::NotImplemented => Self::Internal,
         }
@@ -174,6 +178,9 @@ pub(in crate::api_server) struct ApiMetrics {
     /// Number of transaction submission errors for a specific submission error reason.
     #[metrics(labels = ["reason"])]
     pub submit_tx_error: LabeledFamily<&'static str, Counter>,
+    /// Number of calls targeting pruned blocks that were served by the archive backend.
+    #[metrics(labels = ["method"])]
+    pub web3_archive_fallbacks: LabeledFamily<&'static str, Counter>,
 
     #[metrics(buckets = Buckets::exponential(1.0..=128.0, 2.0))]
     pub web3_in_flight_requests: Family<ApiTransportLabel, Histogram<usize>>,
@@ -261,7 +268,9 @@ pub(in crate::api_server) static API_METRICS: vise::Global<ApiMetrics> = vise::G
 pub(super) enum SubscriptionType {
     Blocks,
     Txs,
+    TxReplacements,
     Logs,
+    L1Batches,
 }
 
 #[derive(Debug, Metrics)]
@@ -293,6 +302,49 @@ pub(super) struct PubSubMetrics {
 #[vise::register]
 pub(super) static PUB_SUB_METRICS: vise::Global<PubSubMetrics> = vise::Global::new();
 
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "api_web3_ws")]
+pub(super) struct WsMetrics {
+    /// Total number of opened WebSocket connections.
+    pub opened_connections: Counter,
+    /// Number of WebSocket connections closed by the node operator.
+    pub force_closed_connections: Counter,
+    /// Number of requests rejected because they were sent over a connection closed by the node operator.
+    pub rejected_requests: Counter,
+    /// Lifetime of a WebSocket connection.
+    #[metrics(buckets = Buckets::LATENCIES, unit = Unit::Seconds)]
+    pub connection_lifetime: Histogram<Duration>,
+    /// Total number of requests sent over a single WebSocket connection during its lifetime.
+    #[metrics(buckets = Buckets::exponential(1.0..=1_048_576.0, 4.0))]
+    pub requests_per_connection: Histogram<u64>,
+    /// Number of subscriptions created over all WebSocket connections.
+    pub created_subscriptions: Family<SubscriptionType, Counter>,
+    /// Number of subscriptions terminated because their connection was closed by the node operator.
+    pub force_closed_subscriptions: Family<SubscriptionType, Counter>,
+}
+
+#[vise::register]
+pub(super) static WS_METRICS: vise::Global<WsMetrics> = vise::Global::new();
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "api_web3_sse")]
+pub(super) struct SseMetrics {
+    /// Total number of opened SSE connections split by the stream type.
+    pub opened_connections: Family<SubscriptionType, Counter>,
+    /// Number of SSE connections rejected because the connections limit was reached.
+    pub rejected_connections: Family<SubscriptionType, Counter>,
+    /// Number of currently open SSE connections split by the stream type.
+    pub active_connections: Family<SubscriptionType, Gauge>,
+    /// Number of SSE connections terminated because the client lagged behind notifiers.
+    pub lagged_connections: Family<SubscriptionType, Counter>,
+    /// Lifetime of an SSE connection.
+    #[metrics(buckets = Buckets::LATENCIES, unit = Unit::Seconds)]
+    pub connection_lifetime: Family<SubscriptionType, Histogram<Duration>>,
+}
+
+#[vise::register]
+pub(super) static SSE_METRICS: vise::Global<SseMetrics> = vise::Global::new();
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
 #[metrics(label = "type", rename_all = "snake_case")]
 pub(super) enum FilterType {
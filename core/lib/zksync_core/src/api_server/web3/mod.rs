@@ -1,8 +1,12 @@
-use std::{collections::HashSet, net::SocketAddr, num::NonZeroU32, sync::Arc, time::Duration};
+use std::{
+    collections::HashSet, convert::Infallible, net::SocketAddr, num::NonZeroU32, path::PathBuf,
+    sync::Arc, time::Duration,
+};
 
 use anyhow::Context as _;
 use chrono::NaiveDateTime;
 use futures::future;
+use hyper::{server::conn::AddrStream, service::make_service_fn};
 use serde::Deserialize;
 use tokio::{
     sync::{mpsc, oneshot, watch, Mutex},
@@ -11,50 +15,66 @@ use tokio::{
 use tower_http::{cors::CorsLayer, metrics::InFlightRequestsLayer};
 use zksync_dal::{ConnectionPool, Core};
 use zksync_health_check::{HealthStatus, HealthUpdater, ReactiveHealthCheck};
+use zksync_object_store::ObjectStore;
 use zksync_types::MiniblockNumber;
 use zksync_web3_decl::{
     jsonrpsee::{
-        server::{BatchRequestConfig, RpcServiceBuilder, ServerBuilder},
+        server::{stop_channel, BatchRequestConfig, RpcServiceBuilder, ServerBuilder},
         RpcModule,
     },
     namespaces::{
-        DebugNamespaceServer, EnNamespaceServer, EthNamespaceServer, EthPubSubServer,
-        NetNamespaceServer, SnapshotsNamespaceServer, Web3NamespaceServer, ZksNamespaceServer,
+        AdminNamespaceServer, DebugNamespaceServer, EnNamespaceServer, EthNamespaceServer,
+        EthPubSubServer, NetNamespaceServer, SnapshotsNamespaceServer, UnstableNamespaceServer,
+        Web3NamespaceServer, ZksNamespaceServer, ZksPubSubServer,
     },
     types::Filter,
 };
 
 use self::{
+    archive::ArchiveClient,
     backend_jsonrpsee::{
-        LimitMiddleware, MetadataMiddleware, MethodTracer, ShutdownMiddleware, TrafficTracker,
+        AuthLayer, BatchCostLayer, BatchCostMiddleware, LimitMiddleware, MetadataMiddleware,
+        MethodTracer, ShutdownMiddleware, TraceContextLayer, TrafficTracker,
+        WsConnectionMiddleware,
     },
+    ipc::IpcServer,
     mempool_cache::MempoolCache,
     metrics::API_METRICS,
     namespaces::{
-        DebugNamespace, EnNamespace, EthNamespace, NetNamespace, SnapshotsNamespace, Web3Namespace,
-        ZksNamespace,
+        AdminNamespace, DebugNamespace, EnNamespace, EthNamespace, NetNamespace,
+        SnapshotsNamespace, UnstableNamespace, Web3Namespace, ZksNamespace,
     },
     pubsub::{EthSubscribe, EthSubscriptionIdProvider, PubSubEvent},
+    sse::SseServer,
     state::{Filters, InternalApiConfig, RpcState, SealedMiniblockNumber},
+    ws_connections::WsConnection,
 };
+pub use self::{pubsub::PubSubNotificationSource, ws_connections::WsConnections};
 use crate::{
     api_server::{
         execution_sandbox::{BlockStartInfo, VmConcurrencyBarrier},
         tree::TreeApiClient,
         tx_sender::TxSender,
     },
+    call_traces_offloader::CallTracesReader,
+    metadata_calculator::TreeRollbackHandle,
+    state_keeper::BatchSealMonitor,
     sync_layer::SyncState,
     utils::wait_for_l1_batch,
 };
 
+pub mod archive;
 pub mod backend_jsonrpsee;
+mod ipc;
 mod mempool_cache;
 pub(super) mod metrics;
 pub mod namespaces;
 mod pubsub;
+mod sse;
 pub mod state;
 #[cfg(test)]
 pub(crate) mod tests;
+mod ws_connections;
 
 /// Timeout for graceful shutdown logic within API servers.
 const GRACEFUL_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
@@ -66,6 +86,8 @@ const NO_REQUESTS_WAIT_TIMEOUT: Duration = Duration::from_secs(30);
 /// Time interval with no requests sent to the API server to declare that traffic to the server is ceased,
 /// and start gracefully shutting down the server.
 const SHUTDOWN_INTERVAL_WITHOUT_REQUESTS: Duration = Duration::from_millis(500);
+/// Maximum number of connections to the server if the subscriptions limit is not set.
+const DEFAULT_MAX_CONNECTIONS: usize = 5_000;
 
 /// Represents all kinds of `Filter`.
 #[derive(Debug, Clone)]
@@ -95,6 +117,8 @@ pub enum Namespace {
     En,
     Pubsub,
     Snapshots,
+    Unstable,
+    Admin,
 }
 
 impl Namespace {
@@ -115,6 +139,8 @@ pub struct ApiServerHandles {
     pub health_check: ReactiveHealthCheck,
     #[allow(unused)] // only used in tests
     pub(crate) local_addr: future::TryMaybeDone<oneshot::Receiver<SocketAddr>>,
+    #[allow(unused)] // only used in tests
+    pub(crate) sse_local_addr: Option<SocketAddr>,
 }
 
 /// Optional part of the API server parameters.
@@ -125,10 +151,20 @@ struct OptionalApiParams {
     filters_limit: Option<usize>,
     subscriptions_limit: Option<usize>,
     batch_request_size_limit: Option<usize>,
+    batch_request_cost_limit: Option<u32>,
     response_body_size_limit: Option<usize>,
     websocket_requests_per_minute_limit: Option<NonZeroU32>,
     tree_api: Option<Arc<dyn TreeApiClient>>,
+    archive_client: Option<Arc<dyn ArchiveClient>>,
+    call_traces_store: Option<Arc<dyn ObjectStore>>,
+    ws_connections: WsConnections,
+    batch_seal_monitor: BatchSealMonitor,
+    tree_rollback_handle: TreeRollbackHandle,
+    pub_sub_notification_source: PubSubNotificationSource,
     pub_sub_events_sender: Option<mpsc::UnboundedSender<PubSubEvent>>,
+    sse_addr: Option<SocketAddr>,
+    auth_token: Option<String>,
+    ipc_path: Option<PathBuf>,
 }
 
 /// Structure capable of spawning a configured Web3 API server along with all the required
@@ -224,6 +260,14 @@ impl ApiBuilder {
         self
     }
 
+    /// Sets the limit for the total cost of calls in a single batch request. Calls are weighted by the relative cost
+    /// of the called method (e.g., a debug trace is much more expensive than getting the chain ID). For WS servers,
+    /// the limit applies to the total cost of calls in flight over a single connection.
+    pub fn with_batch_request_cost_limit(mut self, batch_request_cost_limit: u32) -> Self {
+        self.optional.batch_request_cost_limit = Some(batch_request_cost_limit);
+        self
+    }
+
     pub fn with_response_body_size_limit(mut self, response_body_size_limit: usize) -> Self {
         self.optional.response_body_size_limit = Some(response_body_size_limit);
         self
@@ -248,6 +292,40 @@ impl ApiBuilder {
         self
     }
 
+    /// Sets the source of wake-ups for the pub-sub notifiers. By default, notifiers poll the database
+    /// with the interval set via [`Self::with_polling_interval()`].
+    pub fn with_pub_sub_notification_source(mut self, source: PubSubNotificationSource) -> Self {
+        self.optional.pub_sub_notification_source = source;
+        self
+    }
+
+    /// Enables the server-sent events (SSE) endpoint streaming new block headers and logs on the specified port.
+    /// The endpoint shares notifiers with `eth_subscribe`, so it is only started for the WebSocket server
+    /// with the `pubsub` namespace enabled. The number of open SSE connections is capped
+    /// by the [subscriptions limit](Self::with_subscriptions_limit()).
+    pub fn with_sse_port(mut self, port: u16) -> Self {
+        self.optional.sse_addr = Some(([0, 0, 0, 0], port).into());
+        self
+    }
+
+    /// Requires clients to provide the specified secret token (as an `Authorization: Bearer <token>` header)
+    /// in order to call methods in privileged namespaces (`debug`, `en`, `admin` and `unstable`). For the WebSocket
+    /// server, the token is checked once when a connection is established, and only if privileged namespaces
+    /// are enabled.
+    pub fn with_auth_token(mut self, token: String) -> Self {
+        self.optional.auth_token = Some(token);
+        self
+    }
+
+    /// Additionally serves the JSON-RPC API on a Unix domain socket at the specified path (akin to Geth's `--ipcpath`).
+    /// The IPC transport serves the same namespaces as the configured server, except for privileged ones (`debug`, `en`,
+    /// `admin` and `unstable`). It doesn't require authentication and isn't subject to rate limits, so the socket access
+    /// should be restricted using file system permissions.
+    pub fn with_ipc_path(mut self, path: PathBuf) -> Self {
+        self.optional.ipc_path = Some(path);
+        self
+    }
+
     pub fn enable_api_namespaces(mut self, namespaces: Vec<Namespace>) -> Self {
         self.namespaces = Some(namespaces);
         self
@@ -259,6 +337,43 @@ impl ApiBuilder {
         self
     }
 
+    /// Configures an archive backend used to serve historical queries for pruned blocks.
+    pub fn with_archive_client(mut self, archive_client: Arc<dyn ArchiveClient>) -> Self {
+        tracing::info!("Using archive client for pruned blocks: {archive_client:?}");
+        self.optional.archive_client = Some(archive_client);
+        self
+    }
+
+    /// Configures an object store with call traces offloaded from Postgres. Required to serve traces for old blocks
+    /// via the `debug` namespace if call traces are offloaded.
+    pub fn with_call_traces_store(mut self, object_store: Arc<dyn ObjectStore>) -> Self {
+        tracing::info!("Using object store for offloaded call traces: {object_store:?}");
+        self.optional.call_traces_store = Some(object_store);
+        self
+    }
+
+    /// Sets the registry of WebSocket connections. The registry can be shared with other servers; e.g., the HTTP server
+    /// with the `admin` namespace can use it to list and close connections to the WS server.
+    /// By default, each server uses a separate registry.
+    pub fn with_ws_connections(mut self, ws_connections: WsConnections) -> Self {
+        self.optional.ws_connections = ws_connections;
+        self
+    }
+
+    /// Sets the monitor of the L1 batch open in the state keeper, which is exposed via the `admin` namespace.
+    /// Should be shared with the state keeper running in the same process; by default, no batch status is reported.
+    pub fn with_batch_seal_monitor(mut self, monitor: BatchSealMonitor) -> Self {
+        self.optional.batch_seal_monitor = monitor;
+        self
+    }
+
+    /// Sets the handle for rolling back the Merkle tree, which is exposed via the `admin` namespace.
+    /// Should be shared with the Merkle tree running in the same process; by default, rollback requests are rejected.
+    pub fn with_tree_rollback_handle(mut self, handle: TreeRollbackHandle) -> Self {
+        self.optional.tree_rollback_handle = handle;
+        self
+    }
+
     #[cfg(test)]
     fn with_pub_sub_events(mut self, sender: mpsc::UnboundedSender<PubSubEvent>) -> Self {
         self.optional.pub_sub_events_sender = Some(sender);
@@ -336,6 +451,11 @@ impl ApiServer {
             mempool_cache,
             last_sealed_miniblock,
             tree_api: self.optional.tree_api,
+            archive_client: self.optional.archive_client,
+            call_traces: CallTracesReader::new(self.optional.call_traces_store),
+            ws_connections: self.optional.ws_connections,
+            batch_seal_monitor: self.optional.batch_seal_monitor,
+            tree_rollback_handle: self.optional.tree_rollback_handle,
         })
     }
 
@@ -354,7 +474,11 @@ impl ApiServer {
         // Collect all the methods into a single RPC module.
         let mut rpc = RpcModule::new(());
         if let Some(pub_sub) = pub_sub {
-            rpc.merge(pub_sub.into_rpc())
+            if namespaces.contains(&Namespace::Zks) {
+                rpc.merge(ZksPubSubServer::into_rpc(pub_sub.clone()))
+                    .expect("Can't merge zks pubsub namespace");
+            }
+            rpc.merge(EthPubSubServer::into_rpc(pub_sub))
                 .expect("Can't merge eth pubsub namespace");
         }
 
@@ -383,9 +507,17 @@ impl ApiServer {
                 .expect("Can't merge debug namespace");
         }
         if namespaces.contains(&Namespace::Snapshots) {
-            rpc.merge(SnapshotsNamespace::new(rpc_state).into_rpc())
+            rpc.merge(SnapshotsNamespace::new(rpc_state.clone()).into_rpc())
                 .expect("Can't merge snapshots namespace");
         }
+        if namespaces.contains(&Namespace::Unstable) {
+            rpc.merge(UnstableNamespace::new(rpc_state.clone()).into_rpc())
+                .expect("Can't merge unstable namespace");
+        }
+        if namespaces.contains(&Namespace::Admin) {
+            rpc.merge(AdminNamespace::new(rpc_state).into_rpc())
+                .expect("Can't merge admin namespace");
+        }
         Ok(rpc)
     }
 
@@ -448,6 +580,8 @@ impl ApiServer {
         // processes enough requests, information about the latest sealed miniblock will be updated
         // by reporting block difference metrics, so the actual update lag would be much smaller than this value.
         const SEALED_MINIBLOCK_UPDATE_INTERVAL: Duration = Duration::from_millis(25);
+        // Interval between checks whether queued transactions with future nonces can be released to the mempool.
+        const FUTURE_TXS_RELEASE_INTERVAL: Duration = Duration::from_secs(1);
 
         let transport = self.transport;
 
@@ -468,10 +602,18 @@ impl ApiServer {
 
         tasks.push(tokio::spawn(mempool_cache_update_task));
 
+        if self.tx_sender.queues_future_txs() {
+            let releaser_task = self
+                .tx_sender
+                .clone()
+                .run_future_txs_releaser(FUTURE_TXS_RELEASE_INTERVAL, stop_receiver.clone());
+            tasks.push(tokio::spawn(releaser_task));
+        }
+
         let pub_sub = if matches!(transport, ApiTransport::WebSocket(_))
             && self.namespaces.contains(&Namespace::Pubsub)
         {
-            let mut pub_sub = EthSubscribe::new();
+            let mut pub_sub = EthSubscribe::new(self.optional.ws_connections.clone());
             if let Some(sender) = &self.optional.pub_sub_events_sender {
                 pub_sub.set_events_sender(sender.clone());
             }
@@ -479,6 +621,7 @@ impl ApiServer {
             tasks.extend(pub_sub.spawn_notifiers(
                 self.pool.clone(),
                 self.polling_interval,
+                self.optional.pub_sub_notification_source.clone(),
                 stop_receiver.clone(),
             ));
             Some(pub_sub)
@@ -486,6 +629,30 @@ impl ApiServer {
             None
         };
 
+        let mut sse_local_addr = None;
+        if let Some(sse_addr) = &self.optional.sse_addr {
+            if let Some(pub_sub) = &pub_sub {
+                // SSE connections are limited in the same way as WS connections.
+                let connections_limit = self
+                    .optional
+                    .subscriptions_limit
+                    .unwrap_or(DEFAULT_MAX_CONNECTIONS);
+                let sse_server = SseServer::new(
+                    pub_sub.clone(),
+                    sse_addr,
+                    connections_limit,
+                    stop_receiver.clone(),
+                )?;
+                sse_local_addr = Some(sse_server.local_addr);
+                tasks.push(tokio::spawn(sse_server.server_future));
+            } else {
+                tracing::warn!(
+                    "SSE endpoint is only supported by the WebSocket server with the `pubsub` namespace enabled; \
+                     it will not be started"
+                );
+            }
+        }
+
         // TODO (QIT-26): We still expose `health_check` in `ApiServerHandles` for the old code. After we switch to the
         // framework it'll no longer be needed.
         let health_check = self.health_updater.subscribe();
@@ -503,6 +670,7 @@ impl ApiServer {
             health_check,
             tasks,
             local_addr: future::try_maybe_done(local_addr),
+            sse_local_addr,
         })
     }
 
@@ -544,15 +712,37 @@ impl ApiServer {
             .map_or(BatchRequestConfig::Unlimited, |limit| {
                 BatchRequestConfig::Limit(limit as u32)
             });
+        let batch_cost = is_http
+            .then_some(self.optional.batch_request_cost_limit)
+            .flatten()
+            .map(BatchCostLayer::new);
+        // Batches over WS are not visible to HTTP middleware, so their cost is limited by the RPC middleware.
+        let ws_batch_cost_limit = (!is_http)
+            .then_some(self.optional.batch_request_cost_limit)
+            .flatten();
+        let has_privileged_namespaces = self.namespaces.iter().any(|namespace| {
+            matches!(
+                namespace,
+                Namespace::Debug | Namespace::En | Namespace::Admin | Namespace::Unstable
+            )
+        });
+        let auth = self
+            .optional
+            .auth_token
+            .as_deref()
+            .filter(|_| is_http || has_privileged_namespaces)
+            .map(AuthLayer::new);
         let response_body_size_limit = self
             .optional
             .response_body_size_limit
             .map_or(u32::MAX, |limit| limit as u32);
         let websocket_requests_per_minute_limit = self.optional.websocket_requests_per_minute_limit;
         let subscriptions_limit = self.optional.subscriptions_limit;
+        let ws_connections = self.optional.ws_connections.clone();
         let vm_barrier = self.optional.vm_barrier.clone();
         let health_updater = self.health_updater.clone();
         let method_tracer = self.method_tracer.clone();
+        let ipc_path = self.optional.ipc_path.clone();
 
         let rpc = self
             .build_rpc_module(pub_sub, last_sealed_miniblock, mempool_cache)
@@ -570,7 +760,13 @@ impl ApiServer {
                 .allow_methods([reqwest::Method::POST])
                 // Allow requests from any origin
                 .allow_origin(tower_http::cors::Any)
-                .allow_headers([reqwest::header::CONTENT_TYPE])
+                // Allow trace context headers so that browser clients can propagate traces
+                .allow_headers([
+                    reqwest::header::CONTENT_TYPE,
+                    reqwest::header::AUTHORIZATION,
+                    reqwest::header::HeaderName::from_static("traceparent"),
+                    reqwest::header::HeaderName::from_static("tracestate"),
+                ])
         });
         // Setup metrics for the number of in-flight requests.
         let (in_flight_requests, counter) = InFlightRequestsLayer::pair();
@@ -583,58 +779,115 @@ impl ApiServer {
         // Assemble server middleware.
         let middleware = tower::ServiceBuilder::new()
             .layer(in_flight_requests)
-            .option_layer(cors);
+            .option_layer(is_http.then_some(TraceContextLayer))
+            .option_layer(cors)
+            .option_layer(auth)
+            .option_layer(batch_cost);
 
         // Settings shared by HTTP and WS servers.
         let max_connections = !is_http
             .then_some(subscriptions_limit)
             .flatten()
-            .unwrap_or(5_000);
+            .unwrap_or(DEFAULT_MAX_CONNECTIONS);
 
         let traffic_tracker = TrafficTracker::default();
         let traffic_tracker_for_middleware = traffic_tracker.clone();
-        let rpc_middleware = RpcServiceBuilder::new()
-            .layer_fn(move |svc| {
-                ShutdownMiddleware::new(svc, traffic_tracker_for_middleware.clone())
-            })
-            .layer_fn(move |svc| {
-                MetadataMiddleware::new(svc, registered_method_names.clone(), method_tracer.clone())
-            })
-            .option_layer((!is_http).then(|| {
-                tower::layer::layer_fn(move |svc| {
-                    LimitMiddleware::new(svc, websocket_requests_per_minute_limit)
+        // WS connections are tracked by the RPC middleware; see `WsConnectionMiddleware` for details.
+        let rpc_middleware = move |ws_connection: Option<Arc<WsConnection>>| {
+            let traffic_tracker = traffic_tracker_for_middleware.clone();
+            let registered_method_names = registered_method_names.clone();
+            let method_tracer = method_tracer.clone();
+            RpcServiceBuilder::new()
+                .layer_fn(move |svc| ShutdownMiddleware::new(svc, traffic_tracker.clone()))
+                .layer_fn(move |svc| {
+                    MetadataMiddleware::new(
+                        svc,
+                        registered_method_names.clone(),
+                        method_tracer.clone(),
+                    )
                 })
-            }));
+                .option_layer(ws_connection.map(|connection| {
+                    tower::layer::layer_fn(move |svc| {
+                        WsConnectionMiddleware::new(svc, connection.clone())
+                    })
+                }))
+                .option_layer((!is_http).then(|| {
+                    tower::layer::layer_fn(move |svc| {
+                        LimitMiddleware::new(svc, websocket_requests_per_minute_limit)
+                    })
+                }))
+                .option_layer(ws_batch_cost_limit.map(|limit| {
+                    tower::layer::layer_fn(move |svc| BatchCostMiddleware::new(svc, limit))
+                }))
+        };
 
         let server_builder = ServerBuilder::default()
             .max_connections(max_connections as u32)
             .set_http_middleware(middleware)
             .max_response_body_size(response_body_size_limit)
-            .set_batch_request_config(batch_request_config)
-            .set_rpc_middleware(rpc_middleware);
+            .set_batch_request_config(batch_request_config);
 
-        let (local_addr, server_handle) = if is_http {
+        let ipc_server = ipc_path
+            .map(|path| IpcServer::bind(path, rpc.clone()))
+            .transpose()?;
+        let (local_addr, server_handle, ws_server_task) = if is_http {
             // HTTP-specific settings
             let server = server_builder
+                .set_rpc_middleware(rpc_middleware(None))
                 .http_only()
                 .build(addr)
                 .await
                 .context("Failed building HTTP JSON-RPC server")?;
-            (server.local_addr(), server.start(rpc))
+            (server.local_addr(), server.start(rpc), None)
         } else {
-            // WS-specific settings
-            let server = server_builder
+            // WS-specific settings. The server is assembled from `jsonrpsee` services manually, so that each connection
+            // gets its own stop handle. This allows terminating connections closed via `WsConnections::close()`.
+            let service_builder = server_builder
                 .set_id_provider(EthSubscriptionIdProvider)
-                .build(addr)
-                .await
-                .context("Failed building WS JSON-RPC server")?;
-            (server.local_addr(), server.start(rpc))
+                .to_service_builder();
+            let (stop_handle, server_handle) = stop_channel();
+            let server_stop_handle = stop_handle.clone();
+            let make_service = make_service_fn(move |_: &AddrStream| {
+                let connection = Arc::new(ws_connections.register());
+                let mut close_receiver = connection.close_receiver();
+                let (connection_stop_handle, connection_handle) = stop_channel();
+                let server_stop_handle = stop_handle.clone();
+                tokio::spawn(async move {
+                    let is_closed =
+                        async { close_receiver.wait_for(|&closed| closed).await.is_ok() };
+                    tokio::select! {
+                        () = server_stop_handle.shutdown() => {}
+                        is_closed = is_closed => {
+                            if !is_closed {
+                                return; // The connection was dropped.
+                            }
+                        }
+                    }
+                    connection_handle.stop().ok();
+                });
+
+                let service = service_builder
+                    .clone()
+                    .set_rpc_middleware(rpc_middleware(Some(connection)))
+                    .build(rpc.clone(), connection_stop_handle);
+                future::ready(Ok::<_, Infallible>(service))
+            });
+
+            let server = hyper::Server::try_bind(&addr)
+                .context("Failed building WS JSON-RPC server")?
+                .serve(make_service);
+            let local_addr = server.local_addr();
+            let server = server.with_graceful_shutdown(server_stop_handle.shutdown());
+            let server_task = tokio::spawn(server);
+            (Ok(local_addr), server_handle, Some(server_task))
         };
         let local_addr = local_addr.with_context(|| {
             format!("Failed getting local address for {transport_str} JSON-RPC server")
         })?;
         tracing::info!("Initialized {transport_str} API on {local_addr:?}");
         local_addr_sender.send(local_addr).ok();
+        let ipc_server_task =
+            ipc_server.map(|server| tokio::spawn(server.run(stop_receiver.clone())));
         health_updater.update(HealthStatus::Ready.into());
 
         // We want to be able to immediately stop the server task if the server stops on its own for whatever reason.
@@ -686,8 +939,20 @@ impl ApiServer {
         });
 
         server_handle.stopped().await;
+        if let Some(ws_server_task) = ws_server_task {
+            ws_server_task
+                .await
+                .context("WS server panicked")?
+                .context("WS server failed")?;
+        }
         drop(health_updater);
         tracing::info!("{transport_str} JSON-RPC server stopped");
+        if let Some(ipc_server_task) = ipc_server_task {
+            ipc_server_task
+                .await
+                .context("IPC server panicked")?
+                .context("IPC server failed")?;
+        }
         if let Some(vm_barrier) = vm_barrier {
             Self::wait_for_vm(vm_barrier, transport_str).await;
         }
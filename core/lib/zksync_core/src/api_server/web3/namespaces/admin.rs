@@ -0,0 +1,188 @@
+use zksync_db_connection::slow_queries::SlowQueryLog;
+use zksync_types::{
+    api::{AaValidationRules, BatchSealStatus, CacheInfo, SlowQueryInfo, WsConnectionInfo},
+    Address, L1BatchNumber,
+};
+use zksync_web3_decl::error::Web3Error;
+
+use crate::{
+    api_server::web3::{backend_jsonrpsee::MethodTracer, state::RpcState},
+    metadata_calculator::TreeRollbackError,
+};
+
+/// Admin namespace allowing node operators to change AA validation settings at runtime,
+/// to manage WebSocket connections, to inspect the L1 batch open in the state keeper and slow DB queries,
+/// to inspect, flush and resize caches, and to roll back the Merkle tree.
+#[derive(Debug, Clone)]
+pub(crate) struct AdminNamespace {
+    state: RpcState,
+}
+
+impl AdminNamespace {
+    pub fn new(state: RpcState) -> Self {
+        Self { state }
+    }
+
+    pub(crate) fn current_method(&self) -> &MethodTracer {
+        &self.state.current_method
+    }
+
+    pub async fn get_aa_validation_rules_impl(&self) -> AaValidationRules {
+        self.state.tx_sender.read_aa_validation_rules().await
+    }
+
+    pub async fn set_aa_validation_rules_impl(
+        &self,
+        mut rules: AaValidationRules,
+    ) -> Result<(), Web3Error> {
+        if rules.trusted_addresses.contains(&Address::zero()) {
+            return Err(Web3Error::InvalidParams(
+                "zero address cannot be trusted during validation".to_owned(),
+            ));
+        }
+        rules.trusted_token_slots.sort_unstable();
+        rules.trusted_token_slots.dedup();
+        rules.trusted_addresses.sort_unstable();
+        rules.trusted_addresses.dedup();
+
+        tracing::info!("Updating AA validation rules: {rules:?}");
+        self.state.tx_sender.set_aa_validation_rules(rules).await;
+        Ok(())
+    }
+
+    pub async fn get_whitelisted_tokens_for_aa_impl(&self) -> Vec<Address> {
+        self.state
+            .tx_sender
+            .read_whitelisted_tokens_for_aa_cache()
+            .await
+    }
+
+    pub async fn add_whitelisted_tokens_for_aa_impl(&self, tokens: Vec<Address>) -> Vec<Address> {
+        tracing::info!("Adding tokens to the AA white-list: {tokens:?}");
+        self.state
+            .tx_sender
+            .update_whitelisted_tokens_for_aa(|whitelist| {
+                for token in tokens {
+                    if !whitelist.contains(&token) {
+                        whitelist.push(token);
+                    }
+                }
+            })
+            .await
+    }
+
+    pub async fn remove_whitelisted_tokens_for_aa_impl(
+        &self,
+        tokens: Vec<Address>,
+    ) -> Vec<Address> {
+        tracing::info!("Removing tokens from the AA white-list: {tokens:?}");
+        self.state
+            .tx_sender
+            .update_whitelisted_tokens_for_aa(|whitelist| {
+                whitelist.retain(|token| !tokens.contains(token));
+            })
+            .await
+    }
+
+    pub fn get_ws_connections_impl(&self) -> Vec<WsConnectionInfo> {
+        self.state.ws_connections.list()
+    }
+
+    pub fn close_ws_connection_impl(&self, id: u64) -> bool {
+        tracing::info!("Closing WS connection #{id}");
+        self.state.ws_connections.close(id)
+    }
+
+    pub fn get_batch_seal_status_impl(&self) -> Option<BatchSealStatus> {
+        self.state.batch_seal_monitor.status()
+    }
+
+    pub async fn rollback_tree_impl(
+        &self,
+        l1_batch_number: L1BatchNumber,
+    ) -> Result<(), Web3Error> {
+        tracing::info!("Requesting Merkle tree rollback to L1 batch #{l1_batch_number}");
+        self.state
+            .tree_rollback_handle
+            .rollback(l1_batch_number)
+            .await
+            .map_err(|err| match err {
+                TreeRollbackError::Refused(_) => Web3Error::InvalidParams(err.to_string()),
+                TreeRollbackError::NotRunning => Web3Error::TreeApiUnavailable,
+                TreeRollbackError::Internal(err) => Web3Error::InternalError(err),
+            })
+    }
+
+    pub fn get_slow_queries_impl(&self) -> Vec<SlowQueryInfo> {
+        let queries = SlowQueryLog::global().recent();
+        queries
+            .into_iter()
+            .map(|query| SlowQueryInfo {
+                name: query.name.to_owned(),
+                location: format!("{}:{}", query.location.file(), query.location.line()),
+                sql: query.sql,
+                args: query
+                    .args
+                    .into_iter()
+                    .map(|(name, value)| (name.to_owned(), value))
+                    .collect(),
+                elapsed_ms: u64::try_from(query.elapsed.as_millis()).unwrap_or(u64::MAX),
+                finished_at: query.finished_at.into(),
+                query_plan: query.query_plan,
+            })
+            .collect()
+    }
+
+    pub fn get_cache_stats_impl(&self) -> Vec<CacheInfo> {
+        let stats = self.state.tx_sender.cache_stats();
+        stats
+            .into_iter()
+            .map(|stats| {
+                let lookups = stats.hits + stats.misses;
+                CacheInfo {
+                    name: stats.name.to_owned(),
+                    entries: stats.entries,
+                    used_memory_bytes: stats.used_memory,
+                    hits: stats.hits,
+                    misses: stats.misses,
+                    hit_ratio: (lookups > 0).then(|| stats.hits as f64 / lookups as f64),
+                    evictions: stats.evictions,
+                }
+            })
+            .collect()
+    }
+
+    pub fn flush_caches_impl(&self, names: Option<Vec<String>>) -> Result<Vec<String>, Web3Error> {
+        let tx_sender = &self.state.tx_sender;
+        let all_names: Vec<_> = tx_sender
+            .cache_stats()
+            .into_iter()
+            .map(|stats| stats.name.to_owned())
+            .collect();
+        let names = names.unwrap_or_else(|| all_names.clone());
+        if let Some(unknown_name) = names.iter().find(|&name| !all_names.contains(name)) {
+            return Err(Web3Error::InvalidParams(format!(
+                "unknown cache `{unknown_name}`; known caches are {all_names:?}"
+            )));
+        }
+
+        tracing::info!("Flushing caches: {names:?}");
+        for name in &names {
+            tx_sender.flush_cache(name);
+        }
+        Ok(names)
+    }
+
+    pub fn set_cache_capacity_impl(&self, name: &str, capacity: u64) -> Result<u64, Web3Error> {
+        tracing::info!("Setting capacity of cache `{name}` to {capacity}B");
+        self.state
+            .tx_sender
+            .storage_caches()
+            .resize(name, capacity)
+            .ok_or_else(|| {
+                Web3Error::InvalidParams(format!(
+                    "cache `{name}` does not exist or cannot be resized"
+                ))
+            })
+    }
+}
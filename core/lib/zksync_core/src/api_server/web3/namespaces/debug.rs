@@ -1,9 +1,7 @@
 use std::sync::Arc;
 
-use anyhow::Context as _;
 use multivm::{interface::ExecutionResult, vm_latest::constants::BATCH_COMPUTATIONAL_GAS_LIMIT};
 use once_cell::sync::OnceCell;
-use zksync_dal::{CoreDal, DalError};
 use zksync_system_constants::MAX_ENCODED_TX_SIZE;
 use zksync_types::{
     api::{BlockId, BlockNumber, DebugCall, ResultDebugCall, TracerConfig},
@@ -12,26 +10,27 @@ use zksync_types::{
     l2::L2Tx,
     transaction_request::CallRequest,
     vm_trace::Call,
-    AccountTreeId, H256,
+    H256,
 };
 use zksync_web3_decl::error::Web3Error;
 
-use crate::api_server::{
-    execution_sandbox::{ApiTracer, TxSharedArgs},
-    tx_sender::{ApiContracts, TxSenderConfig},
-    web3::{backend_jsonrpsee::MethodTracer, state::RpcState},
+use crate::{
+    api_server::{
+        execution_sandbox::{access_list_keys, ApiTracer, TxSharedArgs},
+        tx_sender::{SubmitTxError, TxSenderConfig},
+        web3::{backend_jsonrpsee::MethodTracer, state::RpcState},
+    },
+    state_keeper::FeeAccountSelector,
 };
 
 #[derive(Debug, Clone)]
 pub(crate) struct DebugNamespace {
     batch_fee_input: BatchFeeInput,
     state: RpcState,
-    api_contracts: ApiContracts,
 }
 
 impl DebugNamespace {
     pub async fn new(state: RpcState) -> Self {
-        let api_contracts = ApiContracts::load_from_disk();
         Self {
             // For now, the same scaling is used for both the L1 gas price and the pubdata price
             batch_fee_input: state
@@ -44,7 +43,6 @@ impl DebugNamespace {
                 )
                 .await,
             state,
-            api_contracts,
         }
     }
 
@@ -67,16 +65,17 @@ impl DebugNamespace {
         let only_top_call = options
             .map(|options| options.tracer_config.only_top_call)
             .unwrap_or(false);
-        let mut connection = self.state.acquire_connection().await?;
+        // Traces are only returned for blocks resolved using the same connection, so replication lag is tolerated.
+        let mut connection = self.state.acquire_read_preferred_connection().await?;
         let block_number = self.state.resolve_block(&mut connection, block_id).await?;
         self.current_method()
             .set_block_diff(self.state.last_sealed_miniblock.diff(block_number));
 
-        let call_traces = connection
-            .blocks_web3_dal()
-            .get_traces_for_miniblock(block_number)
-            .await
-            .map_err(DalError::generalize)?;
+        let call_traces = self
+            .state
+            .call_traces
+            .get_traces_for_miniblock(&mut connection, block_number)
+            .await?;
         let call_trace = call_traces
             .into_iter()
             .map(|call_trace| {
@@ -111,11 +110,11 @@ impl DebugNamespace {
             .map(|options| options.tracer_config.only_top_call)
             .unwrap_or(false);
         let mut connection = self.state.acquire_connection().await?;
-        let call_trace = connection
-            .transactions_dal()
-            .get_call_trace(tx_hash)
-            .await
-            .map_err(DalError::generalize)?;
+        let call_trace = self
+            .state
+            .call_traces
+            .get_call_trace(&mut connection, tx_hash)
+            .await?;
         Ok(call_trace.map(|call_trace| {
             let mut result: DebugCall = call_trace.into();
             if only_top_call {
@@ -151,6 +150,7 @@ impl DebugNamespace {
                 .last_sealed_miniblock
                 .diff_with_block_args(&block_args),
         );
+        let prefetched_keys = access_list_keys(request.access_list.as_ref());
         let tx = L2Tx::from_request(request.into(), MAX_ENCODED_TX_SIZE)?;
 
         let shared_args = self.shared_args().await;
@@ -159,8 +159,8 @@ impl DebugNamespace {
             .tx_sender
             .vm_concurrency_limiter()
             .acquire()
-            .await;
-        let vm_permit = vm_permit.context("cannot acquire VM permit")?;
+            .await
+            .map_err(SubmitTxError::from)?;
 
         // We don't need properly trace if we only need top call
         let call_tracer_result = Arc::new(OnceCell::default());
@@ -179,6 +179,8 @@ impl DebugNamespace {
                 tx.clone(),
                 block_args,
                 self.sender_config().vm_execution_cache_misses_limit,
+                self.sender_config().vm_execution_timeout,
+                prefetched_keys,
                 custom_tracers,
             )
             .await?;
@@ -214,9 +216,10 @@ impl DebugNamespace {
     async fn shared_args(&self) -> TxSharedArgs {
         let sender_config = self.sender_config();
         TxSharedArgs {
-            operator_account: AccountTreeId::default(),
+            operator_account: FeeAccountSelector::default(),
             fee_input: self.batch_fee_input,
-            base_system_contracts: self.api_contracts.eth_call.clone(),
+            vm_env_pool: self.state.tx_sender.0.eth_call_vm_env_pool.clone(),
+            storage_view_pool: self.state.tx_sender.0.storage_view_pool.clone(),
             caches: self.state.tx_sender.storage_caches().clone(),
             validation_computational_gas_limit: BATCH_COMPUTATIONAL_GAS_LIMIT,
             chain_id: sender_config.chain_id,
@@ -225,6 +228,7 @@ impl DebugNamespace {
                 .tx_sender
                 .read_whitelisted_tokens_for_aa_cache()
                 .await,
+            aa_validation_rules: self.state.tx_sender.read_aa_validation_rules().await,
         }
     }
 }
@@ -21,8 +21,9 @@ use zksync_web3_decl::{
     types::{Address, Block, Filter, FilterChanges, Log, U64},
 };
 
-use crate::api_server::web3::{
-    backend_jsonrpsee::MethodTracer, metrics::API_METRICS, state::RpcState, TypedFilter,
+use crate::api_server::{
+    execution_sandbox::access_list_keys,
+    web3::{backend_jsonrpsee::MethodTracer, metrics::API_METRICS, state::RpcState, TypedFilter},
 };
 
 pub const EVENT_TOPIC_NUMBER_LIMIT: usize = 4;
@@ -67,7 +68,15 @@ impl EthNamespace {
         let block_args = self
             .state
             .resolve_block_args(&mut connection, block_id)
-            .await?;
+            .await;
+        let block_args = match (block_args, &self.state.archive_client) {
+            (Err(Web3Error::PrunedBlock(_)), Some(archive_client)) => {
+                drop(connection);
+                API_METRICS.web3_archive_fallbacks[&"eth_call"].inc();
+                return archive_client.call(request, block_id).await;
+            }
+            (block_args, _) => block_args?,
+        };
         self.current_method().set_block_diff(
             self.state
                 .last_sealed_miniblock
@@ -75,8 +84,13 @@ impl EthNamespace {
         );
         drop(connection);
 
+        let prefetched_keys = access_list_keys(request.access_list.as_ref());
         let tx = L2Tx::from_request(request.into(), self.state.api_config.max_tx_size)?;
-        let call_result = self.state.tx_sender.eth_call(block_args, tx).await?;
+        let call_result = self
+            .state
+            .tx_sender
+            .eth_call(block_args, tx, prefetched_keys, vec![])
+            .await?;
         Ok(call_result.into())
     }
 
@@ -218,7 +232,13 @@ impl EthNamespace {
         self.current_method().set_block_id(block_id);
         self.state.start_info.ensure_not_pruned(block_id)?;
 
-        let mut storage = self.state.acquire_connection().await?;
+        let mut connection = self.state.acquire_connection().await?;
+        // The block header and its transactions are loaded using separate queries, which must observe
+        // the same DB state.
+        let mut storage = connection
+            .read_snapshot()
+            .await
+            .map_err(DalError::generalize)?;
         let Some(block_number) = self
             .state
             .resolve_block_unchecked(&mut storage, block_id)
@@ -308,7 +328,13 @@ impl EthNamespace {
         self.current_method().set_block_id(block_id);
         self.state.start_info.ensure_not_pruned(block_id)?;
 
-        let mut storage = self.state.acquire_connection().await?;
+        let mut connection = self.state.acquire_connection().await?;
+        // The block header and its transactions are loaded using separate queries, which must observe
+        // the same DB state.
+        let mut storage = connection
+            .read_snapshot()
+            .await
+            .map_err(DalError::generalize)?;
         let Some(block_number) = self
             .state
             .resolve_block_unchecked(&mut storage, block_id)
@@ -373,8 +399,20 @@ impl EthNamespace {
 
         let storage_key = StorageKey::new(AccountTreeId::new(address), u256_to_h256(idx));
         let mut connection = self.state.acquire_connection().await?;
-        let block_number = self.state.resolve_block(&mut connection, block_id).await?;
+        let block_number = self.state.resolve_block(&mut connection, block_id).await;
+        let block_number = match (block_number, &self.state.archive_client) {
+            (Err(Web3Error::PrunedBlock(_)), Some(archive_client)) => {
+                drop(connection);
+                API_METRICS.web3_archive_fallbacks[&"eth_getStorageAt"].inc();
+                return archive_client.get_storage_at(address, idx, block_id).await;
+            }
+            (block_number, _) => block_number?,
+        };
         self.set_block_diff(block_number);
+        let storage_caches = self.state.tx_sender.storage_caches();
+        if let Some(value) = storage_caches.read_historical_value(&storage_key, block_number) {
+            return Ok(value);
+        }
         let value = connection
             .storage_web3_dal()
             .get_historical_value_unchecked(&storage_key, block_number)
@@ -639,7 +677,8 @@ impl EthNamespace {
             .min(self.state.api_config.fee_history_limit)
             .max(1);
 
-        let mut connection = self.state.acquire_connection().await?;
+        // Fee history is resolved relative to the newest block available in the DB, so it tolerates replication lag.
+        let mut connection = self.state.acquire_read_preferred_connection().await?;
         let newest_miniblock = self
             .state
             .resolve_block(&mut connection, BlockId::Number(newest_block))
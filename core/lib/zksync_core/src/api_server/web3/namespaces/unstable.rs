@@ -0,0 +1,184 @@
+use multivm::utils::derive_base_fee_and_gas_per_pubdata;
+use zksync_dal::{CoreDal, DalError};
+use zksync_system_constants::L1_GAS_PER_PUBDATA_BYTE;
+use zksync_types::{
+    api::{
+        BatchFeesSummary, FeeModelSimulationBatch, FeeModelSimulationParams, PriorityOpAuditRecord,
+        SimulatedPubdataPricing,
+    },
+    fee_model::{BatchFeeInput, FeeModelConfigV2, FeeParamsV2, L1BatchFeeSummary},
+    tx::TxAccessHints,
+    web3::types::AccessList,
+    Bytes, PriorityOpId, ProtocolVersionId, H256, U256,
+};
+use zksync_web3_decl::error::Web3Error;
+
+use crate::{
+    api_server::{
+        execution_sandbox::access_list_keys,
+        web3::{backend_jsonrpsee::MethodTracer, metrics::API_METRICS, state::RpcState},
+    },
+    fee_model::checked_compute_batch_fee_model_input_v2,
+};
+
+#[derive(Debug, Clone)]
+pub(crate) struct UnstableNamespace {
+    state: RpcState,
+}
+
+impl UnstableNamespace {
+    pub fn new(state: RpcState) -> Self {
+        Self { state }
+    }
+
+    pub(crate) fn current_method(&self) -> &MethodTracer {
+        &self.state.current_method
+    }
+
+    pub async fn get_priority_op_history_impl(
+        &self,
+        priority_op_id: PriorityOpId,
+    ) -> Result<Vec<PriorityOpAuditRecord>, Web3Error> {
+        let mut storage = self.state.acquire_connection().await?;
+        Ok(storage
+            .priority_ops_audit_dal()
+            .get_priority_op_history(priority_op_id)
+            .await
+            .map_err(DalError::generalize)?)
+    }
+
+    pub async fn send_raw_transaction_with_hints_impl(
+        &self,
+        tx_bytes: Bytes,
+        access_list: AccessList,
+    ) -> Result<H256, Web3Error> {
+        let (mut tx, hash) = self.state.parse_transaction_bytes(&tx_bytes.0)?;
+        tx.set_input(tx_bytes.0, hash);
+        let access_hints = TxAccessHints {
+            storage_slots: access_list_keys(Some(&access_list)),
+        };
+
+        let submit_result = self
+            .state
+            .tx_sender
+            .submit_tx_with_access_hints(tx, Some(access_hints))
+            .await;
+        submit_result.map(|_| hash).map_err(|err| {
+            tracing::debug!("Send raw transaction with hints error: {err}");
+            API_METRICS.submit_tx_error[&err.prom_error_code()].inc();
+            err.into()
+        })
+    }
+
+    pub async fn simulate_fee_model_impl(
+        &self,
+        params: FeeModelSimulationParams,
+        batch_count: u64,
+    ) -> Result<Vec<FeeModelSimulationBatch>, Web3Error> {
+        if params.max_gas_per_batch == 0 || params.max_pubdata_per_batch == 0 {
+            return Err(Web3Error::InvalidParams(
+                "`maxGasPerBatch` and `maxPubdataPerBatch` must be positive".to_owned(),
+            ));
+        }
+        let overhead_parts = [params.compute_overhead_part, params.pubdata_overhead_part];
+        if overhead_parts
+            .iter()
+            .any(|part| !part.is_finite() || *part < 0.0)
+        {
+            return Err(Web3Error::InvalidParams(
+                "`computeOverheadPart` and `pubdataOverheadPart` must be finite and non-negative"
+                    .to_owned(),
+            ));
+        }
+        // `clamp()` is not used since it panics if the configured limit is 0.
+        let batch_count = batch_count
+            .min(self.state.api_config.fee_model_simulation_limit)
+            .max(1);
+
+        let mut storage = self.state.acquire_connection().await?;
+        let summaries = storage
+            .blocks_web3_dal()
+            .get_l1_batch_fee_summaries(batch_count)
+            .await
+            .map_err(DalError::generalize)?;
+        drop(storage);
+
+        summaries
+            .into_iter()
+            .map(|summary| {
+                let l1_batch_number = summary.number;
+                simulate_batch_fees(&params, summary).ok_or_else(|| {
+                    Web3Error::InvalidParams(format!(
+                        "fee model params lead to an overflow when simulating fees for L1 batch #{l1_batch_number}"
+                    ))
+                })
+            })
+            .collect()
+    }
+}
+
+/// Returns `None` if simulated values overflow, which can happen for unreasonably large params.
+fn simulate_batch_fees(
+    params: &FeeModelSimulationParams,
+    summary: L1BatchFeeSummary,
+) -> Option<FeeModelSimulationBatch> {
+    let l1_gas_price = summary.fee_input.l1_gas_price();
+    let l1_pubdata_price = match params.pubdata_pricing {
+        SimulatedPubdataPricing::Calldata => {
+            l1_gas_price.checked_mul(u64::from(L1_GAS_PER_PUBDATA_BYTE))?
+        }
+        SimulatedPubdataPricing::Fixed { l1_pubdata_price } => l1_pubdata_price,
+    };
+    let fee_params = FeeParamsV2 {
+        config: FeeModelConfigV2 {
+            minimal_l2_gas_price: params.minimal_l2_gas_price,
+            compute_overhead_part: params.compute_overhead_part,
+            pubdata_overhead_part: params.pubdata_overhead_part,
+            batch_overhead_l1_gas: params.batch_overhead_l1_gas,
+            max_gas_per_batch: params.max_gas_per_batch,
+            max_pubdata_per_batch: params.max_pubdata_per_batch,
+        },
+        l1_gas_price,
+        l1_pubdata_price,
+    };
+    let simulated_input = checked_compute_batch_fee_model_input_v2(fee_params, 1.0, 1.0)?;
+    // Batches sealed before 1.4.1 use the pegged pubdata price, so the simulated input is converted accordingly.
+    let simulated_input = BatchFeeInput::for_protocol_version(
+        summary.protocol_version,
+        simulated_input.fair_l2_gas_price,
+        Some(simulated_input.fair_pubdata_price),
+        simulated_input.l1_gas_price,
+    );
+
+    let actual = fees_summary(summary.protocol_version, summary.fee_input, |_| {
+        Some(summary.fees_paid)
+    })?;
+    // Gas spent by transactions is assumed to stay the same, which is an approximation since
+    // pubdata is charged in L2 gas.
+    let simulated = fees_summary(summary.protocol_version, simulated_input, |base_fee| {
+        summary.gas_used.checked_mul(U256::from(base_fee))
+    })?;
+    Some(FeeModelSimulationBatch {
+        l1_batch_number: summary.number,
+        l1_gas_price,
+        gas_used: summary.gas_used,
+        actual,
+        simulated,
+    })
+}
+
+fn fees_summary(
+    protocol_version: ProtocolVersionId,
+    fee_input: BatchFeeInput,
+    total_fees: impl FnOnce(u64) -> Option<U256>,
+) -> Option<BatchFeesSummary> {
+    let (base_fee, gas_per_pubdata) =
+        derive_base_fee_and_gas_per_pubdata(fee_input, protocol_version.into());
+    Some(BatchFeesSummary {
+        fair_l2_gas_price: fee_input.fair_l2_gas_price(),
+        fair_pubdata_price: fee_input.fair_pubdata_price(),
+        base_fee,
+        gas_per_pubdata,
+        total_fees: total_fees(base_fee)?,
+    })
+}
@@ -1,15 +1,22 @@
-use std::{collections::HashMap, convert::TryInto};
+use std::{collections::HashMap, convert::TryInto, sync::Arc};
 
 use anyhow::Context as _;
-use zksync_dal::{Connection, Core, CoreDal, DalError};
+use futures::TryStreamExt;
+use once_cell::sync::OnceCell;
+use zksync_dal::{
+    online_migrations_dal::OnlineMigrationStatus, Connection, Core, CoreDal, DalError,
+};
+use zksync_l1_contract_interface::i_executor::commit::kzg::ZK_SYNC_BYTES_PER_BLOB;
 use zksync_mini_merkle_tree::MiniMerkleTree;
 use zksync_system_constants::DEFAULT_L2_TX_GAS_PER_PUBDATA_BYTE;
 use zksync_types::{
     api::{
-        BlockDetails, BridgeAddresses, GetLogsFilter, L1BatchDetails, L2ToL1LogProof, Proof,
-        ProtocolVersion, StorageProof, TransactionDetails,
+        AccessedStorage, AccessedStorageSlot, BlockDetails, BlockId, BlockNumber, BridgeAddresses,
+        CallOptions, CallResult, GetLogsFilter, L1BatchDetails, L1BatchPubdata, L2ToL1LogProof,
+        PendingGovernanceOperation, Proof, ProtocolVersion, ProtocolVersionInfo, StorageProof,
+        StorageProofs, StorageSlot, StorageSlotProof, TransactionDetails,
     },
-    fee::Fee,
+    fee::FeeEstimate,
     fee_model::FeeParams,
     l1::L1Tx,
     l2::L2Tx,
@@ -17,18 +24,23 @@ use zksync_types::{
     tokens::ETHEREUM_ADDRESS,
     transaction_request::CallRequest,
     utils::storage_key_for_standard_token_balance,
-    AccountTreeId, L1BatchNumber, MiniblockNumber, ProtocolVersionId, StorageKey, Transaction,
-    L1_MESSENGER_ADDRESS, L2_ETH_TOKEN_ADDRESS, REQUIRED_L1_TO_L2_GAS_PER_PUBDATA_BYTE, U256, U64,
+    AccountTreeId, L1BatchNumber, MiniblockNumber, ProtocolVersionId, StorageKey, StorageValue,
+    Transaction, ACCOUNT_CODE_STORAGE_ADDRESS, L1_MESSENGER_ADDRESS, L2_ETH_TOKEN_ADDRESS,
+    REQUIRED_L1_TO_L2_GAS_PER_PUBDATA_BYTE, U256, U64,
 };
-use zksync_utils::{address_to_h256, h256_to_u256};
+use zksync_utils::{address_to_h256, h256_to_account_address, h256_to_u256};
 use zksync_web3_decl::{
     error::Web3Error,
-    types::{Address, Token, H256},
+    types::{Address, Bytes, Token, H256},
 };
 
-use crate::api_server::{
-    tree::TreeApiError,
-    web3::{backend_jsonrpsee::MethodTracer, RpcState},
+use crate::{
+    api_server::{
+        execution_sandbox::{access_list_keys, ApiTracer},
+        tree::TreeApiError,
+        web3::{backend_jsonrpsee::MethodTracer, RpcState},
+    },
+    online_migrations::TokenBalanceHoldersMigration,
 };
 
 #[derive(Debug)]
@@ -46,7 +58,7 @@ impl ZksNamespace {
     }
 
     #[tracing::instrument(skip(self, request))]
-    pub async fn estimate_fee_impl(&self, request: CallRequest) -> Result<Fee, Web3Error> {
+    pub async fn estimate_fee_impl(&self, request: CallRequest) -> Result<FeeEstimate, Web3Error> {
         let mut request_with_gas_per_pubdata_overridden = request;
         self.state
             .set_nonce_for_call_request(&mut request_with_gas_per_pubdata_overridden)
@@ -86,11 +98,11 @@ impl ZksNamespace {
             .try_into()
             .map_err(Web3Error::SerializationError)?;
 
-        let fee = self.estimate_fee(tx.into()).await?;
-        Ok(fee.gas_limit)
+        let estimate = self.estimate_fee(tx.into()).await?;
+        Ok(estimate.fee.gas_limit)
     }
 
-    async fn estimate_fee(&self, tx: Transaction) -> Result<Fee, Web3Error> {
+    async fn estimate_fee(&self, tx: Transaction) -> Result<FeeEstimate, Web3Error> {
         let scale_factor = self.state.api_config.estimate_gas_scale_factor;
         let acceptable_overestimation =
             self.state.api_config.estimate_gas_acceptable_overestimation;
@@ -98,7 +110,7 @@ impl ZksNamespace {
         Ok(self
             .state
             .tx_sender
-            .get_txs_fee_in_wei(tx, scale_factor, acceptable_overestimation as u64)
+            .get_txs_fee_estimate(tx, scale_factor, acceptable_overestimation as u64)
             .await?)
     }
 
@@ -118,8 +130,29 @@ impl ZksNamespace {
     }
 
     #[tracing::instrument(skip(self))]
-    pub fn get_bridge_contracts_impl(&self) -> BridgeAddresses {
-        self.state.api_config.bridge_addresses.clone()
+    pub async fn get_bridge_contracts_impl(&self) -> Result<BridgeAddresses, Web3Error> {
+        let mut bridge_addresses = self.state.api_config.bridge_addresses.clone();
+        let mut storage = self.state.acquire_connection().await?;
+        let registered_bridges = storage
+            .tokens_web3_dal()
+            .get_custom_token_bridges()
+            .await
+            .map_err(DalError::generalize)?;
+        drop(storage);
+
+        if !registered_bridges.is_empty() {
+            // Bridges registered in Postgres take precedence over ones from the config (e.g., ones fetched
+            // by the external node from the main node on startup).
+            let custom_bridges = &mut bridge_addresses.custom_bridges;
+            custom_bridges.retain(|bridge| {
+                !registered_bridges
+                    .iter()
+                    .any(|registered| registered.l1_token_address == bridge.l1_token_address)
+            });
+            custom_bridges.extend(registered_bridges);
+            custom_bridges.sort_unstable_by_key(|bridge| bridge.l1_token_address);
+        }
+        Ok(bridge_addresses)
     }
 
     #[tracing::instrument(skip(self))]
@@ -161,11 +194,34 @@ impl ZksNamespace {
         address: Address,
     ) -> Result<HashMap<Address, U256>, Web3Error> {
         let mut storage = self.state.acquire_connection().await?;
-        let tokens = storage
-            .tokens_dal()
-            .get_all_l2_token_addresses()
-            .await
-            .map_err(DalError::generalize)?;
+        // The token holders index is populated from events, so it's incomplete if the node has started
+        // from a snapshot, or if the index is not backfilled yet. In these cases, we have to check all known tokens.
+        let is_index_complete = if self.state.start_info.first_miniblock == MiniblockNumber(0) {
+            let backfill_status = storage
+                .online_migrations_dal()
+                .get_migration_status(TokenBalanceHoldersMigration::NAME)
+                .await
+                .map_err(DalError::generalize)?;
+            backfill_status.map_or(false, OnlineMigrationStatus::reads_new_layout)
+        } else {
+            false
+        };
+        let tokens = if is_index_complete {
+            let mut tokens = storage
+                .token_balances_dal()
+                .get_held_tokens(address)
+                .await
+                .map_err(DalError::generalize)?;
+            // The base token isn't covered by the index since it's minted without emitting `Transfer` events.
+            tokens.push(ETHEREUM_ADDRESS);
+            tokens
+        } else {
+            storage
+                .tokens_dal()
+                .get_all_l2_token_addresses()
+                .await
+                .map_err(DalError::generalize)?
+        };
         let hashed_balance_keys = tokens.iter().map(|&token_address| {
             let token_account = AccountTreeId::new(if token_address == ETHEREUM_ADDRESS {
                 L2_ETH_TOKEN_ADDRESS
@@ -387,13 +443,31 @@ impl ZksNamespace {
     ) -> Result<Vec<Transaction>, Web3Error> {
         self.state.start_info.ensure_not_pruned(block_number)?;
         let mut storage = self.state.acquire_connection().await?;
+        // Transactions are streamed so that raw DB rows for large blocks aren't held in memory
+        // in addition to the converted transactions.
         Ok(storage
             .transactions_web3_dal()
-            .get_raw_miniblock_transactions(block_number)
+            .stream_raw_transactions(block_number..=block_number)
+            .try_collect()
             .await
             .map_err(DalError::generalize)?)
     }
 
+    #[tracing::instrument(skip(self))]
+    pub async fn get_raw_block_impl(
+        &self,
+        block_number: MiniblockNumber,
+    ) -> Result<Option<Bytes>, Web3Error> {
+        self.state.start_info.ensure_not_pruned(block_number)?;
+        let mut storage = self.state.acquire_connection().await?;
+        let raw_block = storage
+            .raw_blocks_dal()
+            .get_encoded_raw_block(block_number)
+            .await
+            .map_err(DalError::generalize)?;
+        Ok(raw_block.map(Bytes))
+    }
+
     #[tracing::instrument(skip(self))]
     pub async fn get_transaction_details_impl(
         &self,
@@ -427,6 +501,50 @@ impl ZksNamespace {
             .map_err(DalError::generalize)?)
     }
 
+    #[tracing::instrument(skip(self))]
+    pub async fn get_l1_batch_pubdata_impl(
+        &self,
+        batch_number: L1BatchNumber,
+    ) -> Result<Option<L1BatchPubdata>, Web3Error> {
+        self.state.start_info.ensure_not_pruned(batch_number)?;
+        let mut storage = self.state.acquire_connection().await?;
+        let Some(l1_batch) = storage
+            .blocks_dal()
+            .get_l1_batch_metadata(batch_number)
+            .await
+            .map_err(DalError::generalize)?
+        else {
+            return Ok(None);
+        };
+        drop(storage);
+
+        let protocol_version = l1_batch
+            .header
+            .protocol_version
+            .unwrap_or_else(ProtocolVersionId::last_potentially_undefined);
+        if protocol_version.is_pre_boojum() {
+            return Ok(None);
+        }
+        // Mirrors pubdata construction in the commit transaction.
+        let pubdata = l1_batch
+            .header
+            .pubdata_input
+            .clone()
+            .unwrap_or_else(|| l1_batch.construct_pubdata());
+        let blobs = if protocol_version.is_pre_1_4_2() {
+            vec![]
+        } else {
+            pubdata
+                .chunks(ZK_SYNC_BYTES_PER_BLOB)
+                .map(|blob| blob.to_vec().into())
+                .collect()
+        };
+        Ok(Some(L1BatchPubdata {
+            pubdata: pubdata.into(),
+            blobs,
+        }))
+    }
+
     #[tracing::instrument(skip(self))]
     pub async fn get_bytecode_by_hash_impl(
         &self,
@@ -485,6 +603,16 @@ impl ZksNamespace {
         Ok(protocol_version)
     }
 
+    #[tracing::instrument(skip(self))]
+    pub async fn get_protocol_versions_impl(&self) -> Result<Vec<ProtocolVersionInfo>, Web3Error> {
+        let mut storage = self.state.acquire_connection().await?;
+        Ok(storage
+            .protocol_versions_web3_dal()
+            .get_protocol_versions()
+            .await
+            .map_err(DalError::generalize)?)
+    }
+
     #[tracing::instrument(skip_all)]
     pub async fn get_proofs_impl(
         &self,
@@ -519,6 +647,7 @@ impl ZksNamespace {
         };
 
         let storage_proof = proofs
+            .entries
             .into_iter()
             .zip(keys)
             .map(|(proof, key)| StorageProof {
@@ -534,4 +663,155 @@ impl ZksNamespace {
             storage_proof,
         }))
     }
+
+    #[tracing::instrument(skip(self, slots))]
+    pub async fn get_storage_proofs_impl(
+        &self,
+        slots: Vec<StorageSlot>,
+        l1_batch_number: L1BatchNumber,
+    ) -> Result<Option<StorageProofs>, Web3Error> {
+        let limit = self.state.api_config.req_entities_limit;
+        if slots.len() > limit {
+            return Err(Web3Error::InvalidParams(format!(
+                "too many storage slots requested: {}; the limit is {limit}",
+                slots.len()
+            )));
+        }
+
+        // Unlike `zks_getProof`, we don't check whether the L1 batch is pruned in Postgres; proofs are served
+        // for all L1 batches retained by the tree.
+        let hashed_keys = slots
+            .iter()
+            .map(|slot| {
+                StorageKey::new(AccountTreeId::new(slot.address), slot.key).hashed_key_u256()
+            })
+            .collect();
+        let tree_api = self
+            .state
+            .tree_api
+            .as_deref()
+            .ok_or(Web3Error::TreeApiUnavailable)?;
+        let proofs = match tree_api.get_proofs(l1_batch_number, hashed_keys).await {
+            Ok(proofs) => proofs,
+            Err(TreeApiError::NotReady) => return Err(Web3Error::TreeApiUnavailable),
+            Err(TreeApiError::NoVersion(err)) => {
+                return if err.missing_version >= err.version_count {
+                    Ok(None)
+                } else {
+                    Err(Web3Error::InvalidParams(format!(
+                        "L1 batch #{l1_batch_number} is pruned from Merkle tree"
+                    )))
+                };
+            }
+            Err(TreeApiError::Internal(err)) => return Err(Web3Error::InternalError(err)),
+        };
+        let root_hash = proofs.root_hash.ok_or_else(|| {
+            let err = anyhow::anyhow!("Merkle tree API server doesn't return root hashes");
+            Web3Error::InternalError(err)
+        })?;
+
+        let proofs = proofs
+            .entries
+            .into_iter()
+            .zip(slots)
+            .map(|(entry, slot)| StorageSlotProof {
+                address: slot.address,
+                proof: StorageProof {
+                    key: slot.key,
+                    proof: entry.merkle_path,
+                    value: entry.value,
+                    index: entry.index,
+                },
+            })
+            .collect();
+        Ok(Some(StorageProofs {
+            l1_batch_number,
+            root_hash,
+            proofs,
+        }))
+    }
+
+    #[tracing::instrument(skip(self, request, block_id))]
+    pub async fn call_impl(
+        &self,
+        request: CallRequest,
+        block_id: Option<BlockId>,
+        options: CallOptions,
+    ) -> Result<CallResult, Web3Error> {
+        let block_id = block_id.unwrap_or(BlockId::Number(BlockNumber::Pending));
+        self.current_method().set_block_id(block_id);
+
+        let mut connection = self.state.acquire_connection().await?;
+        let block_args = self
+            .state
+            .resolve_block_args(&mut connection, block_id)
+            .await?;
+        self.current_method().set_block_diff(
+            self.state
+                .last_sealed_miniblock
+                .diff_with_block_args(&block_args),
+        );
+        drop(connection);
+
+        let prefetched_keys = access_list_keys(request.access_list.as_ref());
+        let tx = L2Tx::from_request(request.into(), self.state.api_config.max_tx_size)?;
+        let accessed_storage_cell = options
+            .return_accessed_storage
+            .then(|| Arc::new(OnceCell::new()));
+        let custom_tracers = accessed_storage_cell
+            .iter()
+            .map(|cell| ApiTracer::AccessedStorage(cell.clone()))
+            .collect();
+        let output = self
+            .state
+            .tx_sender
+            .eth_call(block_args, tx, prefetched_keys, custom_tracers)
+            .await?;
+
+        let accessed_storage = accessed_storage_cell.map(|cell| {
+            // The cell may be empty if the call was executed on a VM version not supporting the tracer.
+            cell.get().map(map_accessed_storage).unwrap_or_default()
+        });
+        Ok(CallResult {
+            output: output.into(),
+            accessed_storage,
+        })
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn get_pending_governance_ops_impl(
+        &self,
+    ) -> Result<Vec<PendingGovernanceOperation>, Web3Error> {
+        let mut storage = self.state.acquire_connection().await?;
+        Ok(storage
+            .governance_operations_dal()
+            .get_pending_operations()
+            .await
+            .map_err(DalError::generalize)?)
+    }
+}
+
+/// Converts storage reads recorded by the sandbox into the API format. Accounts are derived from reads
+/// of the account code storage, which the VM performs for each called contract.
+fn map_accessed_storage(reads: &HashMap<StorageKey, StorageValue>) -> AccessedStorage {
+    let mut storage_slots: Vec<_> = reads
+        .iter()
+        .map(|(key, value)| AccessedStorageSlot {
+            address: *key.address(),
+            key: *key.key(),
+            value: *value,
+        })
+        .collect();
+    storage_slots.sort_unstable_by_key(|slot| (slot.address, slot.key));
+
+    let mut accounts: Vec<_> = reads
+        .keys()
+        .filter(|key| *key.address() == ACCOUNT_CODE_STORAGE_ADDRESS)
+        .map(|key| h256_to_account_address(key.key()))
+        .collect();
+    accounts.sort_unstable();
+    AccessedStorage {
+        accounts,
+        storage_slots,
+    }
 }
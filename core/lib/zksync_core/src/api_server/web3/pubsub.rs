@@ -6,10 +6,16 @@ use futures::FutureExt;
 use tokio::{
     sync::{broadcast, mpsc, watch},
     task::JoinHandle,
-    time::{interval, Duration},
+    time::{interval, Duration, Interval, MissedTickBehavior},
 };
-use zksync_dal::{ConnectionPool, Core, CoreDal};
-use zksync_types::{MiniblockNumber, H128, H256};
+use zksync_dal::{
+    pubsub_notifications_dal::{
+        L1_BATCH_UPDATES_CHANNEL, NEW_MINIBLOCKS_CHANNEL, NEW_TRANSACTIONS_CHANNEL,
+    },
+    ConnectionPool, Core, CoreDal,
+};
+use zksync_db_connection::notifications::NotificationListener;
+use zksync_types::{api::L1BatchStage, L1BatchNumber, MiniblockNumber, H128, H256};
 use zksync_web3_decl::{
     jsonrpsee::{
         core::{server::SubscriptionMessage, SubscriptionResult},
@@ -17,18 +23,59 @@ use zksync_web3_decl::{
         types::{error::ErrorCode, ErrorObject, SubscriptionId},
         PendingSubscriptionSink, SendTimeoutError, SubscriptionSink,
     },
-    namespaces::EthPubSubServer,
-    types::{BlockHeader, Log, PubSubFilter, PubSubResult},
+    namespaces::{EthPubSubServer, ZksPubSubServer},
+    types::{BlockHeader, Log, PubSubFilter, PubSubResult, TransactionReplacement},
 };
 
 use super::{
-    metrics::{SubscriptionType, PUB_SUB_METRICS},
+    metrics::{SubscriptionType, PUB_SUB_METRICS, WS_METRICS},
     namespaces::eth::EVENT_TOPIC_NUMBER_LIMIT,
+    ws_connections::WsConnections,
 };
 use crate::api_server::execution_sandbox::BlockStartInfo;
 
 const BROADCAST_CHANNEL_CAPACITY: usize = 1024;
 const SUBSCRIPTION_SINK_SEND_TIMEOUT: Duration = Duration::from_secs(1);
+/// Multiplier for the polling interval to get the fallback polling interval used with DB notifications.
+const DB_NOTIFICATIONS_FALLBACK_MULTIPLIER: u32 = 50;
+
+/// Source of wake-ups for the pub-sub notifiers.
+#[derive(Debug, Clone, Default)]
+pub enum PubSubNotificationSource {
+    /// Notifiers poll the database with a fixed interval.
+    #[default]
+    Polling,
+    /// Notifiers are woken up by Postgres notifications (`LISTEN` / `NOTIFY`) sent when new miniblocks, transactions
+    /// or L1 batches are inserted, or L1 transactions for batches are confirmed. The polling interval is used to rate-limit DB queries. Since notifications
+    /// may be lost, the database is additionally polled with a much larger interval.
+    ///
+    /// Notifications are only sent by the primary database, so the provided pool must connect to the primary
+    /// (unlike the pool used to query data, which may connect to a replica). Triggers sending notifications
+    /// must be enabled (they are enabled by migrations, but may be disabled by operators); see
+    /// [`PubSubNotificationsDal`](zksync_dal::pubsub_notifications_dal::PubSubNotificationsDal).
+    DbNotifications(ConnectionPool<Core>),
+}
+
+impl PubSubNotificationSource {
+    /// Creates a source based on Postgres notifications, falling back to polling if triggers sending notifications
+    /// are disabled in the database. The trigger state is only inspected; it's never changed.
+    pub async fn db_notifications(pool: ConnectionPool<Core>) -> anyhow::Result<Self> {
+        let notifications_enabled = pool
+            .connection_tagged("api")
+            .await?
+            .pubsub_notifications_dal()
+            .are_notifications_enabled()
+            .await?;
+        Ok(if notifications_enabled {
+            Self::DbNotifications(pool)
+        } else {
+            tracing::warn!(
+                "Triggers sending pub-sub notifications are disabled in the database; falling back to polling"
+            );
+            Self::Polling
+        })
+    }
+}
 
 #[derive(Debug, Clone, Copy)]
 pub struct EthSubscriptionIdProvider;
@@ -40,12 +87,32 @@ impl IdProvider for EthSubscriptionIdProvider {
     }
 }
 
+/// Filter applied to items sent to a single subscriber.
+#[derive(Debug)]
+enum SubscriptionFilter {
+    Logs(PubSubFilter),
+    L1BatchStage(L1BatchStage),
+}
+
+impl SubscriptionFilter {
+    fn matches(&self, item: &PubSubResult) -> bool {
+        match (self, item) {
+            (Self::Logs(filter), PubSubResult::Log(log)) => filter.matches(log),
+            (Self::L1BatchStage(stage), PubSubResult::L1BatchStageUpdate(update)) => {
+                update.stage == *stage
+            }
+            _ => true,
+        }
+    }
+}
+
 /// Events emitted by the subscription logic. Only used in WebSocket server tests so far.
 #[derive(Debug)]
 pub(super) enum PubSubEvent {
     Subscribed(SubscriptionType),
     NotifyIterationFinished(SubscriptionType),
     MiniblockAdvanced(SubscriptionType, MiniblockNumber),
+    L1BatchStageAdvanced(L1BatchStage, L1BatchNumber),
 }
 
 /// Manager of notifications for a certain type of subscriptions.
@@ -54,10 +121,72 @@ struct PubSubNotifier {
     sender: broadcast::Sender<Vec<PubSubResult>>,
     connection_pool: ConnectionPool<Core>,
     polling_interval: Duration,
+    notification_source: PubSubNotificationSource,
     events_sender: Option<mpsc::UnboundedSender<PubSubEvent>>,
 }
 
+/// Trigger for a single notifier iteration.
+#[derive(Debug)]
+enum NotifierTrigger {
+    Polling(Interval),
+    DbNotifications {
+        listener: NotificationListener,
+        rate_limiter: Interval,
+        fallback_interval: Duration,
+        is_first_iteration: bool,
+    },
+}
+
+impl NotifierTrigger {
+    /// Waits until the next notifier iteration should be performed, or the stop signal is received.
+    async fn wait(&mut self, stop_receiver: &mut watch::Receiver<bool>) -> anyhow::Result<()> {
+        match self {
+            Self::Polling(timer) => {
+                timer.tick().await;
+            }
+            Self::DbNotifications {
+                listener,
+                rate_limiter,
+                fallback_interval,
+                is_first_iteration,
+            } => {
+                // The first iteration is performed immediately in order to initialize the notifier state.
+                if !std::mem::take(is_first_iteration) {
+                    tokio::select! {
+                        res = listener.recv() => {
+                            res?;
+                        }
+                        () = tokio::time::sleep(*fallback_interval) => {}
+                        _ = stop_receiver.changed() => return Ok(()),
+                    }
+                }
+                rate_limiter.tick().await;
+            }
+        }
+        Ok(())
+    }
+}
+
 impl PubSubNotifier {
+    async fn trigger(&self, channel: &str) -> anyhow::Result<NotifierTrigger> {
+        Ok(match &self.notification_source {
+            PubSubNotificationSource::Polling => {
+                NotifierTrigger::Polling(interval(self.polling_interval))
+            }
+            PubSubNotificationSource::DbNotifications(primary_pool) => {
+                let listener = primary_pool.notification_listener(&[channel]).await?;
+                let mut rate_limiter = interval(self.polling_interval);
+                rate_limiter.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                NotifierTrigger::DbNotifications {
+                    listener,
+                    rate_limiter,
+                    fallback_interval: self.polling_interval * DB_NOTIFICATIONS_FALLBACK_MULTIPLIER,
+                    is_first_iteration: true,
+                }
+            }
+        })
+    }
+
     async fn get_starting_miniblock_number(&self) -> anyhow::Result<MiniblockNumber> {
         let mut storage = self
             .connection_pool
@@ -83,15 +212,15 @@ impl PubSubNotifier {
 }
 
 impl PubSubNotifier {
-    async fn notify_blocks(self, stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+    async fn notify_blocks(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
         let mut last_block_number = self.get_starting_miniblock_number().await?;
-        let mut timer = interval(self.polling_interval);
+        let mut trigger = self.trigger(NEW_MINIBLOCKS_CHANNEL).await?;
         loop {
             if *stop_receiver.borrow() {
                 tracing::info!("Stop signal received, pubsub_block_notifier is shutting down");
                 break;
             }
-            timer.tick().await;
+            trigger.wait(&mut stop_receiver).await?;
 
             let db_latency = PUB_SUB_METRICS.db_poll_latency[&SubscriptionType::Blocks].start();
             let new_blocks = self.new_blocks(last_block_number).await?;
@@ -133,15 +262,15 @@ impl PubSubNotifier {
             .map_err(Into::into)
     }
 
-    async fn notify_txs(self, stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+    async fn notify_txs(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
         let mut last_time = chrono::Utc::now().naive_utc();
-        let mut timer = interval(self.polling_interval);
+        let mut trigger = self.trigger(NEW_TRANSACTIONS_CHANNEL).await?;
         loop {
             if *stop_receiver.borrow() {
                 tracing::info!("Stop signal received, pubsub_tx_notifier is shutting down");
                 break;
             }
-            timer.tick().await;
+            trigger.wait(&mut stop_receiver).await?;
 
             let db_latency = PUB_SUB_METRICS.db_poll_latency[&SubscriptionType::Txs].start();
             let new_txs = self.new_txs(last_time).await?;
@@ -174,16 +303,152 @@ impl PubSubNotifier {
             .map_err(Into::into)
     }
 
-    async fn notify_logs(self, stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
-        let mut last_block_number = self.get_starting_miniblock_number().await?;
+    async fn notify_tx_replacements(
+        self,
+        mut stop_receiver: watch::Receiver<bool>,
+    ) -> anyhow::Result<()> {
+        let mut last_time = chrono::Utc::now().naive_utc();
+        let mut trigger = self.trigger(NEW_TRANSACTIONS_CHANNEL).await?;
+        loop {
+            if *stop_receiver.borrow() {
+                tracing::info!(
+                    "Stop signal received, pubsub_tx_replacements_notifier is shutting down"
+                );
+                break;
+            }
+            trigger.wait(&mut stop_receiver).await?;
+
+            let db_latency =
+                PUB_SUB_METRICS.db_poll_latency[&SubscriptionType::TxReplacements].start();
+            let new_replacements = self.new_tx_replacements(last_time).await?;
+            db_latency.observe();
+
+            if let Some((new_last_time, _)) = new_replacements.last() {
+                last_time = *new_last_time;
+                let new_replacements = new_replacements
+                    .into_iter()
+                    .map(|(_, replacement)| PubSubResult::TxReplacement(replacement))
+                    .collect();
+                self.send_pub_sub_results(new_replacements, SubscriptionType::TxReplacements);
+            }
+            self.emit_event(PubSubEvent::NotifyIterationFinished(
+                SubscriptionType::TxReplacements,
+            ));
+        }
+        Ok(())
+    }
+
+    async fn new_tx_replacements(
+        &self,
+        last_time: NaiveDateTime,
+    ) -> anyhow::Result<Vec<(NaiveDateTime, TransactionReplacement)>> {
+        self.connection_pool
+            .connection_tagged("api")
+            .await
+            .context("connection_tagged")?
+            .transactions_web3_dal()
+            .get_tx_replacements_after(last_time)
+            .await
+            .map_err(Into::into)
+    }
+
+    async fn notify_l1_batch_updates(
+        self,
+        mut stop_receiver: watch::Receiver<bool>,
+    ) -> anyhow::Result<()> {
+        let mut last_l1_batch_numbers = self.get_starting_l1_batch_numbers().await?;
+        let mut trigger = self.trigger(L1_BATCH_UPDATES_CHANNEL).await?;
+        loop {
+            if *stop_receiver.borrow() {
+                tracing::info!(
+                    "Stop signal received, pubsub_l1_batch_updates_notifier is shutting down"
+                );
+                break;
+            }
+            trigger.wait(&mut stop_receiver).await?;
+
+            let db_latency = PUB_SUB_METRICS.db_poll_latency[&SubscriptionType::L1Batches].start();
+            let mut storage = self
+                .connection_pool
+                .connection_tagged("api")
+                .await
+                .context("connection_tagged")?;
+            let mut new_updates = vec![];
+            for (stage, last_l1_batch_number) in &mut last_l1_batch_numbers {
+                let updates = storage
+                    .blocks_web3_dal()
+                    .get_l1_batch_stage_updates(*stage, *last_l1_batch_number)
+                    .await?;
+                if let Some(last_update) = updates.last() {
+                    *last_l1_batch_number = last_update.l1_batch_number;
+                    self.emit_event(PubSubEvent::L1BatchStageAdvanced(
+                        *stage,
+                        *last_l1_batch_number,
+                    ));
+                }
+                new_updates.extend(updates);
+            }
+            drop(storage);
+            db_latency.observe();
+
+            if !new_updates.is_empty() {
+                let new_updates = new_updates
+                    .into_iter()
+                    .map(PubSubResult::L1BatchStageUpdate)
+                    .collect();
+                self.send_pub_sub_results(new_updates, SubscriptionType::L1Batches);
+            }
+            self.emit_event(PubSubEvent::NotifyIterationFinished(
+                SubscriptionType::L1Batches,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the last L1 batch that has reached each lifecycle stage.
+    async fn get_starting_l1_batch_numbers(
+        &self,
+    ) -> anyhow::Result<Vec<(L1BatchStage, L1BatchNumber)>> {
+        let mut storage = self
+            .connection_pool
+            .connection_tagged("api")
+            .await
+            .context("connection_tagged")?;
+        let mut blocks_dal = storage.blocks_dal();
+        let mut numbers = Vec::with_capacity(L1BatchStage::ALL.len());
+        for stage in L1BatchStage::ALL {
+            let number = match stage {
+                L1BatchStage::Sealed => blocks_dal.get_sealed_l1_batch_number().await?,
+                L1BatchStage::Committed => {
+                    blocks_dal
+                        .get_number_of_last_l1_batch_committed_on_eth()
+                        .await?
+                }
+                L1BatchStage::Proven => {
+                    blocks_dal
+                        .get_number_of_last_l1_batch_proven_on_eth()
+                        .await?
+                }
+                L1BatchStage::Executed => {
+                    blocks_dal
+                        .get_number_of_last_l1_batch_executed_on_eth()
+                        .await?
+                }
+            };
+            numbers.push((stage, number.unwrap_or_default()));
+        }
+        Ok(numbers)
+    }
 
-        let mut timer = interval(self.polling_interval);
+    async fn notify_logs(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        let mut last_block_number = self.get_starting_miniblock_number().await?;
+        let mut trigger = self.trigger(NEW_MINIBLOCKS_CHANNEL).await?;
         loop {
             if *stop_receiver.borrow() {
                 tracing::info!("Stop signal received, pubsub_logs_notifier is shutting down");
                 break;
             }
-            timer.tick().await;
+            trigger.wait(&mut stop_receiver).await?;
 
             let db_latency = PUB_SUB_METRICS.db_poll_latency[&SubscriptionType::Logs].start();
             let new_logs = self.new_logs(last_block_number).await?;
@@ -216,23 +481,32 @@ impl PubSubNotifier {
 }
 
 /// Subscription support for Web3 APIs.
+#[derive(Clone)]
 pub(super) struct EthSubscribe {
     blocks: broadcast::Sender<Vec<PubSubResult>>,
     transactions: broadcast::Sender<Vec<PubSubResult>>,
+    tx_replacements: broadcast::Sender<Vec<PubSubResult>>,
     logs: broadcast::Sender<Vec<PubSubResult>>,
+    l1_batches: broadcast::Sender<Vec<PubSubResult>>,
+    connections: WsConnections,
     events_sender: Option<mpsc::UnboundedSender<PubSubEvent>>,
 }
 
 impl EthSubscribe {
-    pub fn new() -> Self {
+    pub fn new(connections: WsConnections) -> Self {
         let (blocks, _) = broadcast::channel(BROADCAST_CHANNEL_CAPACITY);
         let (transactions, _) = broadcast::channel(BROADCAST_CHANNEL_CAPACITY);
+        let (tx_replacements, _) = broadcast::channel(BROADCAST_CHANNEL_CAPACITY);
         let (logs, _) = broadcast::channel(BROADCAST_CHANNEL_CAPACITY);
+        let (l1_batches, _) = broadcast::channel(BROADCAST_CHANNEL_CAPACITY);
 
         Self {
             blocks,
             transactions,
+            tx_replacements,
             logs,
+            l1_batches,
+            connections,
             events_sender: None,
         }
     }
@@ -241,6 +515,16 @@ impl EthSubscribe {
         self.events_sender = Some(sender);
     }
 
+    /// Subscribes to new block headers. Used by transports other than WebSocket.
+    pub fn subscribe_to_blocks(&self) -> broadcast::Receiver<Vec<PubSubResult>> {
+        self.blocks.subscribe()
+    }
+
+    /// Subscribes to new logs. Used by transports other than WebSocket.
+    pub fn subscribe_to_logs(&self) -> broadcast::Receiver<Vec<PubSubResult>> {
+        self.logs.subscribe()
+    }
+
     async fn reject(sink: PendingSubscriptionSink) {
         sink.reject(ErrorObject::borrowed(
             ErrorCode::InvalidParams.code(),
@@ -250,14 +534,25 @@ impl EthSubscribe {
         .await;
     }
 
+    fn subscription_id_to_string(id: SubscriptionId<'_>) -> String {
+        match id {
+            SubscriptionId::Num(id) => id.to_string(),
+            SubscriptionId::Str(id) => id.into_owned(),
+        }
+    }
+
     async fn run_subscriber(
         sink: SubscriptionSink,
         subscription_type: SubscriptionType,
         mut receiver: broadcast::Receiver<Vec<PubSubResult>>,
-        filter: Option<PubSubFilter>,
+        filter: Option<SubscriptionFilter>,
+        connections: WsConnections,
     ) {
         let _guard = PUB_SUB_METRICS.active_subscribers[&subscription_type].inc_guard(1);
         let lifetime_latency = PUB_SUB_METRICS.subscriber_lifetime[&subscription_type].start();
+        WS_METRICS.created_subscriptions[&subscription_type].inc();
+        let subscription_id = Self::subscription_id_to_string(sink.subscription_id());
+        let mut connection_closures = connections.subscribe_to_closures();
         let closed = sink.closed().fuse();
         tokio::pin!(closed);
 
@@ -286,8 +581,19 @@ impl EthSubscribe {
                         filter.as_ref()
                     )
                     .await;
-                    if handle_result.is_err() {
-                        PUB_SUB_METRICS.subscriber_send_timeouts[&subscription_type].inc();
+                    match handle_result {
+                        Ok(sent_count) => {
+                            connections.record_notifications(&subscription_id, sent_count);
+                        }
+                        Err(_) => {
+                            PUB_SUB_METRICS.subscriber_send_timeouts[&subscription_type].inc();
+                            break;
+                        }
+                    }
+                }
+                _ = connection_closures.changed() => {
+                    if connections.is_subscription_closed(&subscription_id) {
+                        WS_METRICS.force_closed_subscriptions[&subscription_type].inc();
                         break;
                     }
                 }
@@ -296,6 +602,7 @@ impl EthSubscribe {
                 }
             }
         }
+        connections.remove_subscription(&subscription_id);
         lifetime_latency.observe();
     }
 
@@ -303,15 +610,14 @@ impl EthSubscribe {
         sink: &SubscriptionSink,
         subscription_type: SubscriptionType,
         new_items: Vec<PubSubResult>,
-        filter: Option<&PubSubFilter>,
-    ) -> Result<(), SendTimeoutError> {
+        filter: Option<&SubscriptionFilter>,
+    ) -> Result<u64, SendTimeoutError> {
         let notify_latency = PUB_SUB_METRICS.notify_subscribers_latency[&subscription_type].start();
+        let mut sent_count = 0;
         for item in new_items {
-            if let PubSubResult::Log(log) = &item {
-                if let Some(filter) = &filter {
-                    if !filter.matches(log) {
-                        continue;
-                    }
+            if let Some(filter) = filter {
+                if !filter.matches(&item) {
+                    continue;
                 }
             }
 
@@ -323,10 +629,11 @@ impl EthSubscribe {
             .await?;
 
             PUB_SUB_METRICS.notify[&subscription_type].inc();
+            sent_count += 1;
         }
 
         notify_latency.observe();
-        Ok(())
+        Ok(sent_count)
     }
 
     #[tracing::instrument(skip(self, pending_sink))]
@@ -347,6 +654,7 @@ impl EthSubscribe {
                     SubscriptionType::Blocks,
                     blocks_rx,
                     None,
+                    self.connections.clone(),
                 ));
 
                 Some(SubscriptionType::Blocks)
@@ -361,9 +669,24 @@ impl EthSubscribe {
                     SubscriptionType::Txs,
                     transactions_rx,
                     None,
+                    self.connections.clone(),
                 ));
                 Some(SubscriptionType::Txs)
             }
+            "transactionReplacements" => {
+                let Ok(sink) = pending_sink.accept().await else {
+                    return;
+                };
+                let tx_replacements_rx = self.tx_replacements.subscribe();
+                tokio::spawn(Self::run_subscriber(
+                    sink,
+                    SubscriptionType::TxReplacements,
+                    tx_replacements_rx,
+                    None,
+                    self.connections.clone(),
+                ));
+                Some(SubscriptionType::TxReplacements)
+            }
             "logs" => {
                 let filter = params.unwrap_or_default();
                 let topic_count = filter.topics.as_ref().map_or(0, Vec::len);
@@ -380,7 +703,8 @@ impl EthSubscribe {
                         sink,
                         SubscriptionType::Logs,
                         logs_rx,
-                        Some(filter),
+                        Some(SubscriptionFilter::Logs(filter)),
+                        self.connections.clone(),
                     ));
                     Some(SubscriptionType::Logs)
                 }
@@ -412,19 +736,51 @@ impl EthSubscribe {
         }
     }
 
+    #[tracing::instrument(skip(self, pending_sink))]
+    pub async fn zks_sub(&self, pending_sink: PendingSubscriptionSink, sub_type: String) {
+        let stage = match sub_type.as_str() {
+            "l1BatchSealed" => L1BatchStage::Sealed,
+            "l1BatchCommitted" => L1BatchStage::Committed,
+            "l1BatchProven" => L1BatchStage::Proven,
+            "l1BatchExecuted" => L1BatchStage::Executed,
+            _ => {
+                Self::reject(pending_sink).await;
+                return;
+            }
+        };
+        let Ok(sink) = pending_sink.accept().await else {
+            return;
+        };
+        let l1_batches_rx = self.l1_batches.subscribe();
+        tokio::spawn(Self::run_subscriber(
+            sink,
+            SubscriptionType::L1Batches,
+            l1_batches_rx,
+            Some(SubscriptionFilter::L1BatchStage(stage)),
+            self.connections.clone(),
+        ));
+        if let Some(sender) = &self.events_sender {
+            sender
+                .send(PubSubEvent::Subscribed(SubscriptionType::L1Batches))
+                .ok();
+        }
+    }
+
     /// Spawns notifier tasks. This should be called once per instance.
     pub fn spawn_notifiers(
         &self,
         connection_pool: ConnectionPool<Core>,
         polling_interval: Duration,
+        notification_source: PubSubNotificationSource,
         stop_receiver: watch::Receiver<bool>,
     ) -> Vec<JoinHandle<anyhow::Result<()>>> {
-        let mut notifier_tasks = Vec::with_capacity(3);
+        let mut notifier_tasks = Vec::with_capacity(5);
 
         let notifier = PubSubNotifier {
             sender: self.blocks.clone(),
             connection_pool: connection_pool.clone(),
             polling_interval,
+            notification_source: notification_source.clone(),
             events_sender: self.events_sender.clone(),
         };
         let notifier_task = tokio::spawn(notifier.notify_blocks(stop_receiver.clone()));
@@ -434,15 +790,37 @@ impl EthSubscribe {
             sender: self.transactions.clone(),
             connection_pool: connection_pool.clone(),
             polling_interval,
+            notification_source: notification_source.clone(),
             events_sender: self.events_sender.clone(),
         };
         let notifier_task = tokio::spawn(notifier.notify_txs(stop_receiver.clone()));
         notifier_tasks.push(notifier_task);
 
+        let notifier = PubSubNotifier {
+            sender: self.tx_replacements.clone(),
+            connection_pool: connection_pool.clone(),
+            polling_interval,
+            notification_source: notification_source.clone(),
+            events_sender: self.events_sender.clone(),
+        };
+        let notifier_task = tokio::spawn(notifier.notify_tx_replacements(stop_receiver.clone()));
+        notifier_tasks.push(notifier_task);
+
+        let notifier = PubSubNotifier {
+            sender: self.l1_batches.clone(),
+            connection_pool: connection_pool.clone(),
+            polling_interval,
+            notification_source: notification_source.clone(),
+            events_sender: self.events_sender.clone(),
+        };
+        let notifier_task = tokio::spawn(notifier.notify_l1_batch_updates(stop_receiver.clone()));
+        notifier_tasks.push(notifier_task);
+
         let notifier = PubSubNotifier {
             sender: self.logs.clone(),
             connection_pool,
             polling_interval,
+            notification_source,
             events_sender: self.events_sender.clone(),
         };
         let notifier_task = tokio::spawn(notifier.notify_logs(stop_receiver));
@@ -464,3 +842,15 @@ impl EthPubSubServer for EthSubscribe {
         Ok(())
     }
 }
+
+#[async_trait::async_trait]
+impl ZksPubSubServer for EthSubscribe {
+    async fn subscribe(
+        &self,
+        pending: PendingSubscriptionSink,
+        sub_type: String,
+    ) -> SubscriptionResult {
+        self.zks_sub(pending, sub_type).await;
+        Ok(())
+    }
+}
@@ -0,0 +1,260 @@
+//! Server-sent events (SSE) transport for new block headers and logs. Intended for clients that cannot hold
+//! WebSocket connections. Events are sourced from the same notifiers as `eth_subscribe`.
+
+use std::{
+    convert::Infallible, future::Future, net::SocketAddr, pin::Pin, sync::Arc, time::Instant,
+};
+
+use anyhow::Context as _;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing, Router,
+};
+use futures::{stream, Stream, StreamExt};
+use serde::Deserialize;
+use tokio::sync::{broadcast, watch, OwnedSemaphorePermit, Semaphore};
+use zksync_web3_decl::types::{PubSubFilter, PubSubResult};
+
+use super::{
+    metrics::{SubscriptionType, PUB_SUB_METRICS, SSE_METRICS},
+    namespaces::eth::EVENT_TOPIC_NUMBER_LIMIT,
+    pubsub::EthSubscribe,
+};
+
+/// Query params for the logs stream.
+#[derive(Debug, Deserialize)]
+struct LogsQuery {
+    /// JSON-encoded filter with the same format as for `eth_subscribe("logs")`.
+    filter: Option<String>,
+}
+
+/// Shared state of the SSE server.
+#[derive(Clone)]
+struct SseState {
+    pub_sub: EthSubscribe,
+    /// Limits the number of concurrently open event streams.
+    connections: Arc<Semaphore>,
+}
+
+impl SseState {
+    /// Returns `None` if the connections limit is reached.
+    fn acquire_connection(
+        &self,
+        subscription_type: SubscriptionType,
+    ) -> Option<SseConnectionGuard> {
+        let Ok(permit) = self.connections.clone().try_acquire_owned() else {
+            SSE_METRICS.rejected_connections[&subscription_type].inc();
+            return None;
+        };
+        SSE_METRICS.opened_connections[&subscription_type].inc();
+        SSE_METRICS.active_connections[&subscription_type].inc_by(1);
+        Some(SseConnectionGuard {
+            _permit: permit,
+            subscription_type,
+            started_at: Instant::now(),
+        })
+    }
+}
+
+/// Guard held by an SSE event stream; releases the connection slot and reports metrics on drop.
+#[derive(Debug)]
+struct SseConnectionGuard {
+    _permit: OwnedSemaphorePermit,
+    subscription_type: SubscriptionType,
+    started_at: Instant,
+}
+
+impl Drop for SseConnectionGuard {
+    fn drop(&mut self) {
+        SSE_METRICS.active_connections[&self.subscription_type].dec_by(1);
+        SSE_METRICS.connection_lifetime[&self.subscription_type].observe(self.started_at.elapsed());
+    }
+}
+
+/// SSE server bound to a local address.
+pub(super) struct SseServer {
+    pub local_addr: SocketAddr,
+    pub server_future: Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send>>,
+}
+
+impl SseServer {
+    /// Binds the server to the specified address. At most `connections_limit` event streams can be open at the same
+    /// time; further requests are rejected with the 503 Service Unavailable status.
+    pub fn new(
+        pub_sub: EthSubscribe,
+        bind_address: &SocketAddr,
+        connections_limit: usize,
+        mut stop_receiver: watch::Receiver<bool>,
+    ) -> anyhow::Result<Self> {
+        let state = SseState {
+            pub_sub,
+            connections: Arc::new(Semaphore::new(connections_limit)),
+        };
+        let app = Router::new()
+            .route("/blocks", routing::get(blocks_handler))
+            .route("/logs", routing::get(logs_handler))
+            .with_state(state);
+
+        let server = axum::Server::try_bind(bind_address)
+            .with_context(|| format!("Failed binding SSE server to {bind_address}"))?
+            .serve(app.into_make_service());
+        let local_addr = server.local_addr();
+        tracing::info!("Started SSE server at {local_addr}");
+        let server_future = async move {
+            server
+                .with_graceful_shutdown(async move {
+                    if stop_receiver.changed().await.is_err() {
+                        tracing::warn!(
+                            "Stop signal sender for SSE server was dropped without sending a signal"
+                        );
+                    }
+                    tracing::info!("Stop signal received, SSE server is shutting down");
+                })
+                .await
+                .context("SSE server failed")?;
+            tracing::info!("SSE server shut down");
+            Ok(())
+        };
+
+        Ok(Self {
+            local_addr,
+            server_future: Box::pin(server_future),
+        })
+    }
+}
+
+fn too_many_connections_response() -> Response {
+    let message = "Too many open SSE connections";
+    (StatusCode::SERVICE_UNAVAILABLE, message).into_response()
+}
+
+async fn blocks_handler(State(state): State<SseState>) -> Response {
+    let Some(guard) = state.acquire_connection(SubscriptionType::Blocks) else {
+        return too_many_connections_response();
+    };
+    let receiver = state.pub_sub.subscribe_to_blocks();
+    Sse::new(events_stream(receiver, guard, None))
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+async fn logs_handler(State(state): State<SseState>, Query(query): Query<LogsQuery>) -> Response {
+    let filter = match query
+        .filter
+        .as_deref()
+        .map(serde_json::from_str::<PubSubFilter>)
+    {
+        None => PubSubFilter::default(),
+        Some(Ok(filter)) => filter,
+        Some(Err(err)) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                format!("Invalid logs filter: {err}"),
+            )
+                .into_response();
+        }
+    };
+    let topic_count = filter.topics.as_ref().map_or(0, Vec::len);
+    if topic_count > EVENT_TOPIC_NUMBER_LIMIT {
+        return (
+            StatusCode::BAD_REQUEST,
+            format!("Logs filter cannot have more than {EVENT_TOPIC_NUMBER_LIMIT} topics"),
+        )
+            .into_response();
+    }
+
+    let Some(guard) = state.acquire_connection(SubscriptionType::Logs) else {
+        return too_many_connections_response();
+    };
+    let receiver = state.pub_sub.subscribe_to_logs();
+    Sse::new(events_stream(receiver, guard, Some(filter)))
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+/// Converts broadcast notifications into SSE events. Similar to WebSocket subscriptions, the stream is terminated
+/// if the client lags behind notifiers. The connection guard is dropped together with the stream, i.e., once
+/// the client disconnects or the stream is terminated.
+fn events_stream(
+    receiver: broadcast::Receiver<Vec<PubSubResult>>,
+    guard: SseConnectionGuard,
+    filter: Option<PubSubFilter>,
+) -> impl Stream<Item = Result<Event, Infallible>> {
+    let subscription_type = guard.subscription_type;
+    let items = stream::unfold((receiver, guard), move |(mut receiver, guard)| async move {
+        match receiver.recv().await {
+            Ok(items) => Some((items, (receiver, guard))),
+            Err(broadcast::error::RecvError::Closed) => None,
+            Err(broadcast::error::RecvError::Lagged(message_count)) => {
+                PUB_SUB_METRICS.skipped_broadcast_messages[&subscription_type]
+                    .observe(message_count);
+                SSE_METRICS.lagged_connections[&subscription_type].inc();
+                None
+            }
+        }
+    });
+
+    let filter_items = move |items: Vec<PubSubResult>| {
+        let items = items.into_iter().filter(|item| match (item, &filter) {
+            (PubSubResult::Log(log), Some(filter)) => filter.matches(log),
+            _ => true,
+        });
+        let events: Vec<_> = items
+            .map(|item| {
+                PUB_SUB_METRICS.notify[&subscription_type].inc();
+                Ok(Event::default()
+                    .json_data(&item)
+                    .expect("PubSubResult always serializable to json;qed"))
+            })
+            .collect();
+        stream::iter(events)
+    };
+    items.flat_map(filter_items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api_server::web3::WsConnections;
+
+    #[test]
+    fn connection_slots_are_released_on_drop() {
+        let state = SseState {
+            pub_sub: EthSubscribe::new(WsConnections::default()),
+            connections: Arc::new(Semaphore::new(1)),
+        };
+        let guard = state.acquire_connection(SubscriptionType::Blocks).unwrap();
+        assert!(state.acquire_connection(SubscriptionType::Logs).is_none());
+
+        drop(guard);
+        state.acquire_connection(SubscriptionType::Logs).unwrap();
+    }
+
+    #[tokio::test]
+    async fn limiting_sse_connections() {
+        let pub_sub = EthSubscribe::new(WsConnections::default());
+        let (_stop_sender, stop_receiver) = watch::channel(false);
+        let bind_address = SocketAddr::from(([127, 0, 0, 1], 0));
+        let server = SseServer::new(pub_sub, &bind_address, 1, stop_receiver).unwrap();
+        let base_url = format!("http://{}", server.local_addr);
+        let server_task = tokio::spawn(server.server_future);
+
+        let client = reqwest::Client::new();
+        let blocks_response = client
+            .get(format!("{base_url}/blocks"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(blocks_response.status(), StatusCode::OK);
+        let logs_response = client.get(format!("{base_url}/logs")).send().await.unwrap();
+        assert_eq!(logs_response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        // The open blocks stream would prevent graceful shutdown, so the server is aborted.
+        server_task.abort();
+    }
+}
@@ -24,17 +24,23 @@ use zksync_types::{
 use zksync_web3_decl::{error::Web3Error, types::Filter};
 
 use super::{
+    archive::ArchiveClient,
     backend_jsonrpsee::MethodTracer,
     mempool_cache::MempoolCache,
     metrics::{FilterType, FILTER_METRICS},
-    TypedFilter,
+    TypedFilter, WsConnections,
 };
 use crate::{
     api_server::{
-        execution_sandbox::{BlockArgs, BlockArgsError, BlockStartInfo},
+        execution_sandbox::{
+            BlockArgs, BlockArgsError, BlockStartInfo, SubmitTxPrecheck, SANDBOX_METRICS,
+        },
         tree::TreeApiClient,
         tx_sender::{tx_sink::TxSink, TxSender},
     },
+    call_traces_offloader::CallTracesReader,
+    metadata_calculator::TreeRollbackHandle,
+    state_keeper::BatchSealMonitor,
     sync_layer::SyncState,
 };
 
@@ -97,6 +103,7 @@ pub struct InternalApiConfig {
     pub l2_testnet_paymaster_addr: Option<Address>,
     pub req_entities_limit: usize,
     pub fee_history_limit: u64,
+    pub fee_model_simulation_limit: u64,
     pub filters_disabled: bool,
     pub mempool_cache_update_interval: Duration,
     pub mempool_cache_size: usize,
@@ -118,10 +125,14 @@ impl InternalApiConfig {
             estimate_gas_acceptable_overestimation: web3_config
                 .estimate_gas_acceptable_overestimation,
             bridge_addresses: api::BridgeAddresses {
+                l1_shared_default_bridge: contracts_config.l1_shared_bridge_proxy_addr,
+                l2_shared_default_bridge: contracts_config.l2_shared_bridge_addr,
                 l1_erc20_default_bridge: contracts_config.l1_erc20_bridge_proxy_addr,
                 l2_erc20_default_bridge: contracts_config.l2_erc20_bridge_addr,
                 l1_weth_bridge: contracts_config.l1_weth_bridge_proxy_addr,
                 l2_weth_bridge: contracts_config.l2_weth_bridge_addr,
+                // Custom bridges are loaded from Postgres on each request.
+                custom_bridges: vec![],
             },
             bridgehub_proxy_addr: genesis_config
                 .shared_bridge
@@ -139,6 +150,7 @@ impl InternalApiConfig {
             l2_testnet_paymaster_addr: contracts_config.l2_testnet_paymaster_addr,
             req_entities_limit: web3_config.req_entities_limit(),
             fee_history_limit: web3_config.fee_history_limit(),
+            fee_model_simulation_limit: web3_config.fee_model_simulation_limit(),
             filters_disabled: web3_config.filters_disabled,
             mempool_cache_update_interval: web3_config.mempool_cache_update_interval(),
             mempool_cache_size: web3_config.mempool_cache_size(),
@@ -230,6 +242,16 @@ pub(crate) struct RpcState {
     pub(super) installed_filters: Option<Arc<Mutex<Filters>>>,
     pub(super) connection_pool: ConnectionPool<Core>,
     pub(super) tree_api: Option<Arc<dyn TreeApiClient>>,
+    /// Backend serving historical queries for pruned blocks, if configured.
+    pub(super) archive_client: Option<Arc<dyn ArchiveClient>>,
+    /// Reader for call traces, which may be partially offloaded to an object store.
+    pub(super) call_traces: CallTracesReader,
+    /// Registry of WebSocket connections exposed via the `admin` namespace.
+    pub(super) ws_connections: WsConnections,
+    /// Monitor of the L1 batch open in the state keeper exposed via the `admin` namespace.
+    pub(super) batch_seal_monitor: BatchSealMonitor,
+    /// Handle for rolling back the Merkle tree via the `admin` namespace.
+    pub(super) tree_rollback_handle: TreeRollbackHandle,
     pub(super) tx_sender: TxSender,
     pub(super) sync_state: Option<SyncState>,
     pub(super) api_config: InternalApiConfig,
@@ -243,12 +265,15 @@ pub(crate) struct RpcState {
 impl RpcState {
     pub fn parse_transaction_bytes(&self, bytes: &[u8]) -> Result<(L2Tx, H256), Web3Error> {
         let chain_id = self.api_config.l2_chain_id;
-        let (tx_request, hash) = api::TransactionRequest::from_bytes(bytes, chain_id)?;
-
-        Ok((
-            L2Tx::from_request(tx_request, self.api_config.max_tx_size)?,
-            hash,
-        ))
+        let parse_result =
+            api::TransactionRequest::from_bytes(bytes, chain_id).and_then(|(tx_request, hash)| {
+                let tx = L2Tx::from_request(tx_request, self.api_config.max_tx_size)?;
+                Ok((tx, hash))
+            });
+        if parse_result.is_err() {
+            SANDBOX_METRICS.submit_tx_precheck_rejections[&SubmitTxPrecheck::Signature].inc();
+        }
+        parse_result.map_err(Into::into)
     }
 
     pub fn u64_to_block_number(n: U64) -> MiniblockNumber {
@@ -275,6 +300,18 @@ impl RpcState {
             .map_err(|err| err.generalize().into())
     }
 
+    /// Same as [`Self::acquire_connection()`], but routes the connection to a read-only replica if the pool
+    /// has replicas configured. Must only be used for read-only queries tolerating replication lag.
+    /// All such connections acquired while handling a single JSON-RPC call are routed to the same database.
+    #[track_caller]
+    pub(crate) fn acquire_read_preferred_connection(
+        &self,
+    ) -> impl Future<Output = Result<Connection<'_, Core>, Web3Error>> + '_ {
+        self.connection_pool
+            .connection_with_pin("api", self.current_method.replica_pin())
+            .map_err(|err| err.generalize().into())
+    }
+
     /// Resolves the specified block ID to a block number, which is guaranteed to be present in the node storage.
     pub(crate) async fn resolve_block(
         &self,
@@ -282,12 +319,26 @@ impl RpcState {
         block: api::BlockId,
     ) -> Result<MiniblockNumber, Web3Error> {
         self.start_info.ensure_not_pruned(block)?;
-        connection
+        let block_number = connection
             .blocks_web3_dal()
             .resolve_block_id(block)
             .await
-            .context("resolve_block_id")?
-            .ok_or(Web3Error::NoBlock)
+            .context("resolve_block_id")?;
+        if let Some(block_number) = block_number {
+            return Ok(block_number);
+        }
+
+        if let api::BlockId::Hash(hash) = block {
+            let is_reverted = connection
+                .blocks_web3_dal()
+                .is_reverted_miniblock_hash(hash)
+                .await
+                .map_err(DalError::generalize)?;
+            if is_reverted {
+                return Err(Web3Error::NonCanonicalBlock(hash));
+            }
+        }
+        Err(Web3Error::NoBlock)
     }
 
     /// Resolves the specified block ID to a block number, which is **not** guaranteed to be present in the node storage.
@@ -325,6 +376,7 @@ impl RpcState {
             .await
             .map_err(|err| match err {
                 BlockArgsError::Pruned(number) => Web3Error::PrunedBlock(number),
+                BlockArgsError::NonCanonical(hash) => Web3Error::NonCanonicalBlock(hash),
                 BlockArgsError::Missing => Web3Error::NoBlock,
                 BlockArgsError::Database(err) => Web3Error::InternalError(err),
             })
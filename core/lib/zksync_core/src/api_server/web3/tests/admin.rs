@@ -0,0 +1,195 @@
+//! Tests for the `admin` Web3 namespace.
+
+use zksync_types::api::AaValidationRules;
+use zksync_web3_decl::namespaces::AdminNamespaceClient;
+
+use super::*;
+
+#[derive(Debug)]
+struct AaValidationRulesTest;
+
+#[async_trait]
+impl HttpTest for AaValidationRulesTest {
+    async fn test(&self, client: &HttpClient, _pool: &ConnectionPool<Core>) -> anyhow::Result<()> {
+        let rules = client.get_aa_validation_rules().await?;
+        assert_eq!(rules, AaValidationRules::default());
+
+        let new_rules = AaValidationRules {
+            trusted_token_slots: vec![H256::from_low_u64_be(2), H256::from_low_u64_be(1)],
+            trusted_addresses: vec![Address::repeat_byte(0x20)],
+        };
+        client.set_aa_validation_rules(new_rules).await?;
+        let rules = client.get_aa_validation_rules().await?;
+        assert_eq!(
+            rules.trusted_token_slots,
+            [H256::from_low_u64_be(1), H256::from_low_u64_be(2)]
+        );
+        assert_eq!(rules.trusted_addresses, [Address::repeat_byte(0x20)]);
+
+        let invalid_rules = AaValidationRules {
+            trusted_token_slots: vec![],
+            trusted_addresses: vec![Address::zero()],
+        };
+        let err = client
+            .set_aa_validation_rules(invalid_rules)
+            .await
+            .unwrap_err();
+        if let ClientError::Call(error) = err {
+            assert_eq!(error.code(), ErrorCode::InvalidParams.code());
+        } else {
+            panic!("Unexpected error: {err:?}");
+        }
+        // Rules must not be changed after an invalid update.
+        assert_eq!(client.get_aa_validation_rules().await?, rules);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn managing_aa_validation_rules() {
+    test_http_server(AaValidationRulesTest).await;
+}
+
+#[derive(Debug)]
+struct WhitelistedTokensTest;
+
+#[async_trait]
+impl HttpTest for WhitelistedTokensTest {
+    async fn test(&self, client: &HttpClient, _pool: &ConnectionPool<Core>) -> anyhow::Result<()> {
+        let tokens = client.get_whitelisted_tokens_for_aa().await?;
+        assert!(tokens.is_empty(), "{tokens:?}");
+
+        let token = Address::repeat_byte(1);
+        let other_token = Address::repeat_byte(2);
+        let tokens = client
+            .add_whitelisted_tokens_for_aa(vec![token, other_token, token])
+            .await?;
+        assert_eq!(tokens, [token, other_token]);
+        let tokens = client.add_whitelisted_tokens_for_aa(vec![token]).await?;
+        assert_eq!(tokens, [token, other_token]);
+
+        let tokens = client.remove_whitelisted_tokens_for_aa(vec![token]).await?;
+        assert_eq!(tokens, [other_token]);
+        let tokens = client.get_whitelisted_tokens_for_aa().await?;
+        assert_eq!(tokens, [other_token]);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn managing_whitelisted_tokens_for_aa() {
+    test_http_server(WhitelistedTokensTest).await;
+}
+
+#[derive(Debug)]
+struct BatchSealStatusTest;
+
+#[async_trait]
+impl HttpTest for BatchSealStatusTest {
+    async fn test(&self, client: &HttpClient, _pool: &ConnectionPool<Core>) -> anyhow::Result<()> {
+        // The state keeper doesn't run alongside the test server, so the status is not available.
+        let status = client.get_batch_seal_status().await?;
+        assert_eq!(status, None);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn getting_batch_seal_status_without_state_keeper() {
+    test_http_server(BatchSealStatusTest).await;
+}
+
+#[derive(Debug)]
+struct TreeRollbackTest;
+
+#[async_trait]
+impl HttpTest for TreeRollbackTest {
+    async fn test(&self, client: &HttpClient, _pool: &ConnectionPool<Core>) -> anyhow::Result<()> {
+        // The Merkle tree doesn't run alongside the test server, so rollbacks must be rejected.
+        let err = client.rollback_tree(L1BatchNumber(0)).await.unwrap_err();
+        if let ClientError::Call(error) = err {
+            assert_eq!(error.code(), 6, "{error:?}");
+        } else {
+            panic!("Unexpected error: {err:?}");
+        }
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn rolling_back_tree_without_tree() {
+    test_http_server(TreeRollbackTest).await;
+}
+
+#[derive(Debug)]
+struct SlowQueriesTest;
+
+#[async_trait]
+impl HttpTest for SlowQueriesTest {
+    async fn test(&self, client: &HttpClient, _pool: &ConnectionPool<Core>) -> anyhow::Result<()> {
+        // Slow query capturing is disabled by default.
+        let queries = client.get_slow_queries().await?;
+        assert_eq!(queries, []);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn getting_slow_queries() {
+    test_http_server(SlowQueriesTest).await;
+}
+
+#[derive(Debug)]
+struct CachesTest;
+
+#[async_trait]
+impl HttpTest for CachesTest {
+    async fn test(&self, client: &HttpClient, _pool: &ConnectionPool<Core>) -> anyhow::Result<()> {
+        // Only the factory deps cache is enabled for the test server.
+        let stats = client.get_cache_stats().await?;
+        let cache_names: Vec<_> = stats.iter().map(|info| info.name.as_str()).collect();
+        assert_eq!(cache_names, ["factory_deps_cache"]);
+
+        let flushed = client.flush_caches(None).await?;
+        assert_eq!(flushed, ["factory_deps_cache"]);
+        let flushed = client
+            .flush_caches(Some(vec!["factory_deps_cache".to_owned()]))
+            .await?;
+        assert_eq!(flushed, ["factory_deps_cache"]);
+
+        let err = client
+            .flush_caches(Some(vec!["values_cache".to_owned()]))
+            .await
+            .unwrap_err();
+        if let ClientError::Call(error) = err {
+            assert_eq!(error.code(), ErrorCode::InvalidParams.code());
+            assert!(error.message().contains("values_cache"), "{error:?}");
+        } else {
+            panic!("Unexpected error: {err:?}");
+        }
+
+        let prev_capacity = client
+            .set_cache_capacity("initial_writes_cache".to_owned(), 1_024)
+            .await?;
+        assert_eq!(prev_capacity, 0);
+        let stats = client.get_cache_stats().await?;
+        let cache_names: Vec<_> = stats.iter().map(|info| info.name.as_str()).collect();
+        assert_eq!(cache_names, ["factory_deps_cache", "initial_writes_cache"]);
+
+        let err = client
+            .set_cache_capacity("eth_call_cache".to_owned(), 1_024)
+            .await
+            .unwrap_err();
+        if let ClientError::Call(error) = err {
+            assert_eq!(error.code(), ErrorCode::InvalidParams.code());
+        } else {
+            panic!("Unexpected error: {err:?}");
+        }
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn managing_caches() {
+    test_http_server(CachesTest).await;
+}
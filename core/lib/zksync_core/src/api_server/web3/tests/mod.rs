@@ -9,6 +9,7 @@ use assert_matches::assert_matches;
 use async_trait::async_trait;
 use jsonrpsee::core::{client::ClientT, params::BatchRequestBuilder, ClientError};
 use multivm::zk_evm_latest::ethereum_types::U256;
+use tempfile::TempDir;
 use tokio::sync::watch;
 use zksync_config::{
     configs::{
@@ -18,11 +19,16 @@ use zksync_config::{
     },
     GenesisConfig,
 };
-use zksync_dal::{transactions_dal::L2TxSubmissionResult, Connection, ConnectionPool, CoreDal};
+use zksync_dal::{
+    online_migrations_dal::OnlineMigrationStatus, transactions_dal::L2TxSubmissionResult,
+    Connection, ConnectionPool, CoreDal,
+};
 use zksync_health_check::CheckHealth;
+use zksync_l1_contract_interface::i_executor::commit::kzg::ZK_SYNC_BYTES_PER_BLOB;
+use zksync_system_constants::ERC20_TRANSFER_TOPIC;
 use zksync_types::{
     api,
-    block::MiniblockHeader,
+    block::{L1BatchHeader, MiniblockHeader},
     fee::TransactionExecutionMetrics,
     get_nonce_key,
     l2::L2Tx,
@@ -35,7 +41,7 @@ use zksync_types::{
     utils::{storage_key_for_eth_balance, storage_key_for_standard_token_balance},
     AccountTreeId, Address, L1BatchNumber, Nonce, StorageKey, StorageLog, VmEvent, H256, U64,
 };
-use zksync_utils::u256_to_h256;
+use zksync_utils::{address_to_h256, u256_to_h256};
 use zksync_web3_decl::{
     jsonrpsee::{http_client::HttpClient, types::error::ErrorCode},
     namespaces::{EnNamespaceClient, EthNamespaceClient, ZksNamespaceClient},
@@ -48,15 +54,22 @@ use crate::{
         tx_sender::tests::create_test_tx_sender,
     },
     genesis::{insert_genesis_batch, mock_genesis_config, GenesisParams},
+    metadata_calculator::{
+        tests::{gen_storage_logs, mock_config, reset_db_state, run_calculator},
+        LazyAsyncTreeReader, MetadataCalculator,
+    },
+    online_migrations::TokenBalanceHoldersMigration,
     utils::testonly::{
         create_l1_batch, create_l1_batch_metadata, create_l2_transaction, create_miniblock,
         l1_batch_metadata_to_commitment_artifacts, prepare_recovery_snapshot,
     },
 };
 
+mod admin;
 mod debug;
 mod filters;
 mod snapshots;
+mod unstable;
 mod vm;
 mod ws;
 
@@ -119,8 +132,11 @@ pub(crate) async fn spawn_http_server(
         api_config,
         pool,
         None,
+        None,
         tx_executor,
         method_tracer,
+        None,
+        None,
         stop_receiver,
     )
     .await
@@ -132,14 +148,18 @@ async fn spawn_ws_server(
     pool: ConnectionPool<Core>,
     stop_receiver: watch::Receiver<bool>,
     websocket_requests_per_minute_limit: Option<NonZeroU32>,
+    sse_port: Option<u16>,
 ) -> (ApiServerHandles, mpsc::UnboundedReceiver<PubSubEvent>) {
     spawn_server(
         ApiTransportLabel::Ws,
         api_config,
         pool,
         websocket_requests_per_minute_limit,
+        sse_port,
         MockTransactionExecutor::default(),
         Arc::default(),
+        None,
+        None,
         stop_receiver,
     )
     .await
@@ -150,8 +170,11 @@ async fn spawn_server(
     api_config: InternalApiConfig,
     pool: ConnectionPool<Core>,
     websocket_requests_per_minute_limit: Option<NonZeroU32>,
+    sse_port: Option<u16>,
     tx_executor: MockTransactionExecutor,
     method_tracer: Arc<MethodTracer>,
+    archive_client: Option<Arc<dyn ArchiveClient>>,
+    tree_api: Option<Arc<dyn TreeApiClient>>,
     stop_receiver: watch::Receiver<bool>,
 ) -> (ApiServerHandles, mpsc::UnboundedReceiver<PubSubEvent>) {
     let (tx_sender, vm_barrier) =
@@ -159,7 +182,12 @@ async fn spawn_server(
     let (pub_sub_events_sender, pub_sub_events_receiver) = mpsc::unbounded_channel();
 
     let mut namespaces = Namespace::DEFAULT.to_vec();
-    namespaces.extend([Namespace::Debug, Namespace::Snapshots]);
+    namespaces.extend([
+        Namespace::Debug,
+        Namespace::Snapshots,
+        Namespace::Unstable,
+        Namespace::Admin,
+    ]);
 
     let server_builder = match transport {
         ApiTransportLabel::Http => ApiBuilder::jsonrpsee_backend(api_config, pool).http(0),
@@ -171,9 +199,20 @@ async fn spawn_server(
                 builder = builder
                     .with_websocket_requests_per_minute_limit(websocket_requests_per_minute_limit);
             }
+            if let Some(sse_port) = sse_port {
+                builder = builder.with_sse_port(sse_port);
+            }
             builder
         }
     };
+    let server_builder = match archive_client {
+        Some(archive_client) => server_builder.with_archive_client(archive_client),
+        None => server_builder,
+    };
+    let server_builder = match tree_api {
+        Some(tree_api) => server_builder.with_tree_api(tree_api),
+        None => server_builder,
+    };
     let server_handles = server_builder
         .with_polling_interval(POLL_INTERVAL)
         .with_tx_sender(tx_sender)
@@ -204,6 +243,14 @@ trait HttpTest: Send + Sync {
         Arc::default()
     }
 
+    fn archive_client(&self) -> Option<Arc<dyn ArchiveClient>> {
+        None
+    }
+
+    fn tree_api(&self) -> Option<Arc<dyn TreeApiClient>> {
+        None
+    }
+
     async fn test(&self, client: &HttpClient, pool: &ConnectionPool<Core>) -> anyhow::Result<()>;
 
     /// Overrides the `filters_disabled` configuration parameter for HTTP server startup
@@ -287,11 +334,16 @@ async fn test_http_server(test: impl HttpTest) {
     let genesis = GenesisConfig::for_tests();
     let mut api_config = InternalApiConfig::new(&web3_config, &contracts_config, &genesis);
     api_config.filters_disabled = test.filters_disabled();
-    let mut server_handles = spawn_http_server(
+    let (mut server_handles, _) = spawn_server(
+        ApiTransportLabel::Http,
         api_config,
         pool.clone(),
+        None,
+        None,
         test.transaction_executor(),
         test.method_tracer(),
+        test.archive_client(),
+        test.tree_api(),
         stop_receiver,
     )
     .await;
@@ -374,8 +426,15 @@ async fn seal_l1_batch(
     storage: &mut Connection<'_, Core>,
     number: L1BatchNumber,
 ) -> anyhow::Result<()> {
-    let header = create_l1_batch(number.0);
-    storage.blocks_dal().insert_mock_l1_batch(&header).await?;
+    seal_l1_batch_with_header(storage, &create_l1_batch(number.0)).await
+}
+
+async fn seal_l1_batch_with_header(
+    storage: &mut Connection<'_, Core>,
+    header: &L1BatchHeader,
+) -> anyhow::Result<()> {
+    let number = header.number;
+    storage.blocks_dal().insert_mock_l1_batch(header).await?;
     storage
         .blocks_dal()
         .mark_miniblocks_as_executed_in_l1_batch(number)
@@ -513,6 +572,11 @@ impl HttpTest for BlockMethodsWithSnapshotRecovery {
                 .await
                 .unwrap_err();
             assert_pruned_block_error(&error, expected_block_number);
+            let error = client
+                .get_raw_block(MiniblockNumber(number))
+                .await
+                .unwrap_err();
+            assert_pruned_block_error(&error, expected_block_number);
 
             let error = client
                 .get_block_transaction_count_by_number(number.into())
@@ -945,6 +1009,48 @@ impl HttpTest for AllAccountBalancesTest {
                 &[(H256::zero(), vec![token_balance_log])],
             )
             .await?;
+        // While the token holders index is not backfilled, all known tokens are checked.
+        let balances = client.get_all_account_balances(Self::ADDRESS).await?;
+        assert_eq!(
+            balances,
+            HashMap::from([
+                (Address::zero(), eth_balance),
+                (custom_token.l2_address, token_balance),
+            ])
+        );
+
+        let name = TokenBalanceHoldersMigration::NAME;
+        let mut dal = storage.online_migrations_dal();
+        dal.register_migration(name).await?;
+        for (from, to) in [
+            (
+                OnlineMigrationStatus::Backfilling,
+                OnlineMigrationStatus::BackfillCompleted,
+            ),
+            (
+                OnlineMigrationStatus::BackfillCompleted,
+                OnlineMigrationStatus::CutOver,
+            ),
+        ] {
+            assert!(dal.transition_migration(name, from, to).await?);
+        }
+        // Once the index is backfilled, the token must be indexed to be returned.
+        let balances = client.get_all_account_balances(Self::ADDRESS).await?;
+        assert_eq!(balances, HashMap::from([(Address::zero(), eth_balance)]));
+        let transfer_event = VmEvent {
+            location: (L1BatchNumber(1), 0),
+            address: custom_token.l2_address,
+            indexed_topics: vec![
+                ERC20_TRANSFER_TOPIC,
+                H256::zero(),
+                address_to_h256(&Self::ADDRESS),
+            ],
+            value: u256_to_h256(token_balance).0.to_vec(),
+        };
+        storage
+            .token_balances_dal()
+            .insert_token_holders(MiniblockNumber(2), &[transfer_event])
+            .await?;
 
         let balances = client.get_all_account_balances(Self::ADDRESS).await?;
         assert_eq!(
@@ -1099,3 +1205,174 @@ impl HttpTest for GenesisConfigTest {
 async fn tracing_genesis_config() {
     test_http_server(GenesisConfigTest).await;
 }
+
+#[derive(Debug)]
+struct BridgeContractsTest;
+
+#[async_trait]
+impl HttpTest for BridgeContractsTest {
+    async fn test(&self, client: &HttpClient, pool: &ConnectionPool<Core>) -> anyhow::Result<()> {
+        let contracts_config = ContractsConfig::for_tests();
+        let bridges = client.get_bridge_contracts().await?;
+        assert_eq!(
+            bridges.l1_shared_default_bridge,
+            contracts_config.l1_shared_bridge_proxy_addr
+        );
+        assert_eq!(
+            bridges.l1_erc20_default_bridge,
+            contracts_config.l1_erc20_bridge_proxy_addr
+        );
+        assert_eq!(bridges.custom_bridges, []);
+
+        let custom_bridge = api::TokenBridge {
+            l1_token_address: Address::repeat_byte(0xfe),
+            l2_token_address: Some(Address::repeat_byte(0xef)),
+            l1_bridge: Address::repeat_byte(0xb1),
+            l2_bridge: Address::repeat_byte(0xb2),
+        };
+        let mut storage = pool.connection().await?;
+        storage
+            .tokens_dal()
+            .upsert_custom_token_bridge(&custom_bridge)
+            .await?;
+
+        let bridges = client.get_bridge_contracts().await?;
+        assert_eq!(bridges.custom_bridges, [custom_bridge]);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn getting_bridge_contracts() {
+    test_http_server(BridgeContractsTest).await;
+}
+
+#[derive(Debug)]
+struct L1BatchPubdataTest;
+
+#[async_trait]
+impl HttpTest for L1BatchPubdataTest {
+    async fn test(&self, client: &HttpClient, pool: &ConnectionPool<Core>) -> anyhow::Result<()> {
+        let pubdata = client.get_l1_batch_pubdata(L1BatchNumber(1)).await?;
+        assert_eq!(pubdata, None);
+
+        let mut storage = pool.connection().await?;
+        store_miniblock(&mut storage, MiniblockNumber(1), &[]).await?;
+        let mut header = create_l1_batch(1);
+        let expected_pubdata: Vec<_> = (0..ZK_SYNC_BYTES_PER_BLOB + 10).map(|i| i as u8).collect();
+        header.pubdata_input = Some(expected_pubdata.clone());
+        seal_l1_batch_with_header(&mut storage, &header).await?;
+
+        let pubdata = client
+            .get_l1_batch_pubdata(L1BatchNumber(1))
+            .await?
+            .context("no pubdata for L1 batch #1")?;
+        assert_eq!(pubdata.pubdata.0, expected_pubdata);
+        let blobs: Vec<_> = pubdata.blobs.into_iter().map(|blob| blob.0).collect();
+        assert_eq!(
+            blobs,
+            [
+                expected_pubdata[..ZK_SYNC_BYTES_PER_BLOB].to_vec(),
+                expected_pubdata[ZK_SYNC_BYTES_PER_BLOB..].to_vec(),
+            ]
+        );
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn getting_l1_batch_pubdata() {
+    test_http_server(L1BatchPubdataTest).await;
+}
+
+#[derive(Debug)]
+struct StorageProofsTest {
+    calculator: Mutex<Option<MetadataCalculator>>,
+    tree_reader: LazyAsyncTreeReader,
+    _temp_dir: TempDir,
+}
+
+impl StorageProofsTest {
+    const L1_BATCH_COUNT: usize = 5;
+
+    async fn new() -> Self {
+        let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+        let calculator = MetadataCalculator::new(mock_config(temp_dir.path()), None)
+            .await
+            .unwrap();
+        Self {
+            tree_reader: calculator.tree_reader(),
+            calculator: Mutex::new(Some(calculator)),
+            _temp_dir: temp_dir,
+        }
+    }
+}
+
+#[async_trait]
+impl HttpTest for StorageProofsTest {
+    fn tree_api(&self) -> Option<Arc<dyn TreeApiClient>> {
+        Some(Arc::new(self.tree_reader.clone()))
+    }
+
+    async fn test(&self, client: &HttpClient, pool: &ConnectionPool<Core>) -> anyhow::Result<()> {
+        reset_db_state(pool, Self::L1_BATCH_COUNT).await;
+        let calculator = self.calculator.lock().await.take().unwrap();
+        run_calculator(calculator, pool.clone()).await;
+
+        let logs = gen_storage_logs(0..100, Self::L1_BATCH_COUNT);
+        let mut slots: Vec<_> = logs[0]
+            .iter()
+            .map(|log| api::StorageSlot {
+                address: *log.key.address(),
+                key: *log.key.key(),
+            })
+            .collect();
+        let written_slot_count = slots.len();
+        // Add a slot that was never written to.
+        slots.push(api::StorageSlot {
+            address: Address::repeat_byte(0x23),
+            key: H256::zero(),
+        });
+
+        let mut storage = pool.connection().await?;
+        for l1_batch_number in [1, 5].map(L1BatchNumber) {
+            let proofs = client
+                .get_storage_proofs(slots.clone(), l1_batch_number)
+                .await?
+                .context("no storage proofs")?;
+            assert_eq!(proofs.l1_batch_number, l1_batch_number);
+            let state_root = storage
+                .blocks_dal()
+                .get_l1_batch_state_root(l1_batch_number)
+                .await?
+                .context("no state root")?;
+            assert_eq!(proofs.root_hash, state_root);
+
+            assert_eq!(proofs.proofs.len(), slots.len());
+            for (i, (proof, slot)) in proofs.proofs.iter().zip(&slots).enumerate() {
+                assert_eq!(proof.address, slot.address);
+                assert_eq!(proof.proof.key, slot.key);
+                assert!(!proof.proof.proof.is_empty());
+                if i < written_slot_count {
+                    assert_eq!(proof.proof.value, logs[0][i].value);
+                    assert_ne!(proof.proof.index, 0);
+                } else {
+                    assert_eq!(proof.proof.value, H256::zero());
+                    assert_eq!(proof.proof.index, 0);
+                }
+            }
+        }
+
+        // The L1 batch is not processed by the tree yet.
+        let proofs = client
+            .get_storage_proofs(slots, L1BatchNumber(Self::L1_BATCH_COUNT as u32 + 1))
+            .await?;
+        assert!(proofs.is_none(), "{proofs:?}");
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn getting_storage_proofs() {
+    test_http_server(StorageProofsTest::new().await).await;
+}
@@ -0,0 +1,156 @@
+//! Tests for the `unstable` Web3 namespace.
+
+use zksync_types::{
+    api::{FeeModelSimulationParams, PriorityOpAuditEvent, SimulatedPubdataPricing},
+    l1::{L1Tx, OpProcessingType, PriorityQueueType},
+    Execute, L1BlockNumber, L1TxCommonData, PriorityOpId,
+};
+use zksync_web3_decl::namespaces::UnstableNamespaceClient;
+
+use super::*;
+
+fn create_l1_transaction(serial_id: u64) -> L1Tx {
+    L1Tx {
+        execute: Execute {
+            contract_address: Address::repeat_byte(0x11),
+            calldata: vec![1, 2, 3],
+            factory_deps: None,
+            value: U256::zero(),
+        },
+        common_data: L1TxCommonData {
+            serial_id: PriorityOpId(serial_id),
+            sender: Address::repeat_byte(1),
+            deadline_block: 0,
+            eth_hash: H256::repeat_byte(2),
+            eth_block: 10,
+            gas_limit: Default::default(),
+            max_fee_per_gas: Default::default(),
+            gas_per_pubdata_limit: 1_u32.into(),
+            full_fee: Default::default(),
+            layer_2_tip_fee: U256::zero(),
+            refund_recipient: Address::zero(),
+            to_mint: Default::default(),
+            priority_queue_type: PriorityQueueType::Deque,
+            op_processing_type: OpProcessingType::Common,
+            canonical_tx_hash: H256::from_low_u64_be(serial_id),
+        },
+        received_timestamp_ms: 0,
+    }
+}
+
+#[derive(Debug)]
+struct PriorityOpHistoryTest;
+
+#[async_trait]
+impl HttpTest for PriorityOpHistoryTest {
+    async fn test(&self, client: &HttpClient, pool: &ConnectionPool<Core>) -> anyhow::Result<()> {
+        let history = client.get_priority_op_history(PriorityOpId(0)).await?;
+        assert!(history.is_empty(), "{history:?}");
+
+        let tx = create_l1_transaction(0);
+        let mut storage = pool.connection().await?;
+        storage
+            .transactions_dal()
+            .insert_transaction_l1(&tx, L1BlockNumber(10))
+            .await?;
+        storage
+            .priority_ops_audit_dal()
+            .insert_received_event(&tx)
+            .await?;
+
+        let history = client.get_priority_op_history(PriorityOpId(0)).await?;
+        assert_eq!(history.len(), 1, "{history:?}");
+        assert_eq!(history[0].event, PriorityOpAuditEvent::Received);
+        assert_eq!(history[0].tx_hash, tx.hash());
+        assert_eq!(history[0].l1_tx_hash, Some(H256::repeat_byte(2)));
+        assert_eq!(history[0].l1_block_number, Some(L1BlockNumber(10)));
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn getting_priority_op_history() {
+    test_http_server(PriorityOpHistoryTest).await;
+}
+
+#[derive(Debug)]
+struct FeeModelSimulationTest;
+
+#[async_trait]
+impl HttpTest for FeeModelSimulationTest {
+    async fn test(&self, client: &HttpClient, pool: &ConnectionPool<Core>) -> anyhow::Result<()> {
+        let mut storage = pool.connection().await?;
+        let tx_result = execute_l2_transaction(create_l2_transaction(1, 2));
+        store_miniblock(&mut storage, MiniblockNumber(1), &[tx_result]).await?;
+        seal_l1_batch(&mut storage, L1BatchNumber(1)).await?;
+        drop(storage);
+
+        let mut params = FeeModelSimulationParams {
+            minimal_l2_gas_price: 100_000_000,
+            compute_overhead_part: 0.0,
+            pubdata_overhead_part: 1.0,
+            batch_overhead_l1_gas: 800_000,
+            max_gas_per_batch: 200_000_000,
+            max_pubdata_per_batch: 100_000,
+            pubdata_pricing: SimulatedPubdataPricing::Calldata,
+        };
+        let batches = client.simulate_fee_model(params, 10).await?;
+        let batch_numbers: Vec<_> = batches.iter().map(|batch| batch.l1_batch_number).collect();
+        assert_eq!(batch_numbers, [L1BatchNumber(1), L1BatchNumber(0)]);
+
+        let batch = &batches[0];
+        assert_eq!(
+            batch.simulated.fair_l2_gas_price,
+            params.minimal_l2_gas_price
+        );
+        assert_eq!(
+            batch.simulated.total_fees,
+            batch.gas_used * U256::from(batch.simulated.base_fee)
+        );
+
+        let batches = client.simulate_fee_model(params, 1).await?;
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].l1_batch_number, L1BatchNumber(1));
+
+        let invalid_params = FeeModelSimulationParams {
+            max_pubdata_per_batch: 0,
+            ..params
+        };
+        let err = client
+            .simulate_fee_model(invalid_params, 1)
+            .await
+            .unwrap_err();
+        assert_matches!(err, ClientError::Call(err) if err.code() == ErrorCode::InvalidParams.code());
+
+        let invalid_params = FeeModelSimulationParams {
+            compute_overhead_part: -1.0,
+            ..params
+        };
+        let err = client
+            .simulate_fee_model(invalid_params, 1)
+            .await
+            .unwrap_err();
+        assert_matches!(err, ClientError::Call(err) if err.code() == ErrorCode::InvalidParams.code());
+
+        // Overhead per unit of gas doesn't fit into `u64`.
+        params.compute_overhead_part = 1.0;
+        params.batch_overhead_l1_gas = u64::MAX;
+        params.max_gas_per_batch = 1;
+        let err = client.simulate_fee_model(params, 1).await.unwrap_err();
+        assert_matches!(err, ClientError::Call(err) if err.code() == ErrorCode::InvalidParams.code());
+
+        params.batch_overhead_l1_gas = 800_000;
+        params.max_gas_per_batch = 200_000_000;
+        params.pubdata_pricing = SimulatedPubdataPricing::Fixed {
+            l1_pubdata_price: u64::MAX,
+        };
+        let err = client.simulate_fee_model(params, 1).await.unwrap_err();
+        assert_matches!(err, ClientError::Call(err) if err.code() == ErrorCode::InvalidParams.code());
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn simulating_fee_model() {
+    test_http_server(FeeModelSimulationTest).await;
+}
@@ -4,10 +4,14 @@ use std::sync::atomic::{AtomicU32, Ordering};
 
 use multivm::interface::{ExecutionResult, VmRevertReason};
 use zksync_types::{
-    get_intrinsic_constants, transaction_request::CallRequest, L2ChainId, PackedEthSignature, U256,
+    get_intrinsic_constants, transaction_request::CallRequest, tx::TxAccessHints,
+    web3::types::AccessListItem, L2ChainId, PackedEthSignature, U256,
 };
 use zksync_utils::u256_to_h256;
-use zksync_web3_decl::namespaces::DebugNamespaceClient;
+use zksync_web3_decl::{
+    error::Web3Error,
+    namespaces::{DebugNamespaceClient, UnstableNamespaceClient},
+};
 
 use super::*;
 
@@ -144,9 +148,134 @@ async fn call_method_after_snapshot_recovery() {
     test_http_server(CallTestAfterSnapshotRecovery).await;
 }
 
+/// Archive client returning the requested block number encoded as the call output / storage value.
+#[derive(Debug)]
+struct MockArchiveClient;
+
+impl MockArchiveClient {
+    fn block_number(block_id: api::BlockId) -> u64 {
+        match block_id {
+            api::BlockId::Number(api::BlockNumber::Number(number)) => number.as_u64(),
+            api::BlockId::Number(api::BlockNumber::Earliest) => 0,
+            _ => panic!("Unexpected block ID for archive: {block_id:?}"),
+        }
+    }
+}
+
+#[async_trait]
+impl ArchiveClient for MockArchiveClient {
+    async fn call(
+        &self,
+        request: CallRequest,
+        block_id: api::BlockId,
+    ) -> Result<zksync_types::Bytes, Web3Error> {
+        assert_eq!(request.data.unwrap().0, b"pruned");
+        Ok(Self::block_number(block_id).to_be_bytes().to_vec().into())
+    }
+
+    async fn get_storage_at(
+        &self,
+        _address: Address,
+        _idx: U256,
+        block_id: api::BlockId,
+    ) -> Result<H256, Web3Error> {
+        Ok(H256::from_low_u64_be(Self::block_number(block_id)))
+    }
+}
+
+#[derive(Debug)]
+struct CallTestWithArchiveFallback;
+
+#[async_trait]
+impl HttpTest for CallTestWithArchiveFallback {
+    fn storage_initialization(&self) -> StorageInitialization {
+        StorageInitialization::empty_recovery()
+    }
+
+    fn transaction_executor(&self) -> MockTransactionExecutor {
+        let first_local_miniblock = StorageInitialization::SNAPSHOT_RECOVERY_BLOCK + 1;
+        CallTest::create_executor(first_local_miniblock)
+    }
+
+    fn archive_client(&self) -> Option<Arc<dyn ArchiveClient>> {
+        Some(Arc::new(MockArchiveClient))
+    }
+
+    async fn test(&self, client: &HttpClient, _pool: &ConnectionPool<Core>) -> anyhow::Result<()> {
+        let pruned_block_numbers = [0, 1, StorageInitialization::SNAPSHOT_RECOVERY_BLOCK.0];
+        for number in pruned_block_numbers {
+            let block_id = api::BlockIdVariant::BlockNumber(number.into());
+            let call_result = client
+                .call(CallTest::call_request(b"pruned"), Some(block_id))
+                .await?;
+            assert_eq!(call_result.0, u64::from(number).to_be_bytes());
+
+            let value = client
+                .get_storage_at(Address::repeat_byte(1), 0.into(), Some(block_id))
+                .await?;
+            assert_eq!(value, H256::from_low_u64_be(number.into()));
+        }
+
+        // Non-pruned blocks must be served locally.
+        let call_result = client
+            .call(CallTest::call_request(b"pending"), None)
+            .await?;
+        assert_eq!(call_result.0, b"output");
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn call_method_with_archive_fallback() {
+    test_http_server(CallTestWithArchiveFallback).await;
+}
+
+#[derive(Debug)]
+struct CallWithOptionsTest;
+
+#[async_trait]
+impl HttpTest for CallWithOptionsTest {
+    fn transaction_executor(&self) -> MockTransactionExecutor {
+        CallTest::create_executor(MiniblockNumber(0))
+    }
+
+    async fn test(&self, client: &HttpClient, _pool: &ConnectionPool<Core>) -> anyhow::Result<()> {
+        let call_result = client
+            .call_with_options(CallTest::call_request(b"pending"), None, None)
+            .await?;
+        assert_eq!(call_result.output.0, b"output");
+        assert_eq!(call_result.accessed_storage, None);
+
+        let latest_block = api::BlockIdVariant::BlockNumber(api::BlockNumber::Latest);
+        let options = api::CallOptions {
+            return_accessed_storage: true,
+        };
+        let call_result = client
+            .call_with_options(
+                CallTest::call_request(b"first"),
+                Some(latest_block),
+                Some(options),
+            )
+            .await?;
+        assert_eq!(call_result.output.0, b"output");
+        // The mock executor doesn't run tracers, so no storage is recorded.
+        assert_eq!(
+            call_result.accessed_storage,
+            Some(api::AccessedStorage::default())
+        );
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn call_with_options_method_basics() {
+    test_http_server(CallWithOptionsTest).await;
+}
+
 #[derive(Debug)]
 struct SendRawTransactionTest {
     snapshot_recovery: bool,
+    with_access_hints: bool,
 }
 
 impl SendRawTransactionTest {
@@ -232,8 +361,34 @@ impl HttpTest for SendRawTransactionTest {
         }
 
         let (tx_bytes, tx_hash) = Self::transaction_bytes_and_hash();
-        let send_result = client.send_raw_transaction(tx_bytes.into()).await?;
+        if !self.with_access_hints {
+            let send_result = client.send_raw_transaction(tx_bytes.into()).await?;
+            assert_eq!(send_result, tx_hash);
+            return Ok(());
+        }
+
+        let access_list = vec![AccessListItem {
+            address: Address::repeat_byte(0x10),
+            storage_keys: vec![H256::zero(), H256::repeat_byte(1)],
+        }];
+        let send_result = client
+            .send_raw_transaction_with_hints(tx_bytes.into(), access_list)
+            .await?;
         assert_eq!(send_result, tx_hash);
+
+        let mut storage = pool.connection().await?;
+        let access_hints = storage
+            .transactions_dal()
+            .take_access_hints(&[tx_hash])
+            .await?;
+        let account = AccountTreeId::new(Address::repeat_byte(0x10));
+        let expected_hints = TxAccessHints {
+            storage_slots: vec![
+                StorageKey::new(account, H256::zero()),
+                StorageKey::new(account, H256::repeat_byte(1)),
+            ],
+        };
+        assert_eq!(access_hints, HashMap::from([(tx_hash, expected_hints)]));
         Ok(())
     }
 }
@@ -242,6 +397,7 @@ impl HttpTest for SendRawTransactionTest {
 async fn send_raw_transaction_basics() {
     test_http_server(SendRawTransactionTest {
         snapshot_recovery: false,
+        with_access_hints: false,
     })
     .await;
 }
@@ -250,6 +406,16 @@ async fn send_raw_transaction_basics() {
 async fn send_raw_transaction_after_snapshot_recovery() {
     test_http_server(SendRawTransactionTest {
         snapshot_recovery: true,
+        with_access_hints: false,
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn send_raw_transaction_with_access_hints() {
+    test_http_server(SendRawTransactionTest {
+        snapshot_recovery: false,
+        with_access_hints: true,
     })
     .await;
 }
@@ -8,19 +8,21 @@ use reqwest::StatusCode;
 use tokio::sync::watch;
 use zksync_config::configs::chain::NetworkConfig;
 use zksync_dal::ConnectionPool;
-use zksync_types::{api, Address, L1BatchNumber, H256, U64};
+use zksync_types::{
+    aggregated_operations::AggregatedActionType, api, Address, L1BatchNumber, H256, U64,
+};
 use zksync_web3_decl::{
     jsonrpsee::{
         core::client::{Subscription, SubscriptionClientT},
         rpc_params,
         ws_client::{WsClient, WsClientBuilder},
     },
-    namespaces::{EthNamespaceClient, ZksNamespaceClient},
+    namespaces::{AdminNamespaceClient, EthNamespaceClient, ZksNamespaceClient},
     types::{BlockHeader, PubSubFilter},
 };
 
 use super::*;
-use crate::api_server::web3::metrics::SubscriptionType;
+use crate::api_server::web3::{metrics::SubscriptionType, PubSubNotificationSource, WsConnections};
 
 #[allow(clippy::needless_pass_by_ref_mut)] // false positive
 async fn wait_for_subscription(
@@ -94,6 +96,30 @@ async fn wait_for_notifier_miniblock(
     wait_future.await.expect("Timed out waiting for notifier");
 }
 
+#[allow(clippy::needless_pass_by_ref_mut)] // false positive
+async fn wait_for_notifier_l1_batch(
+    events: &mut mpsc::UnboundedReceiver<PubSubEvent>,
+    stage: api::L1BatchStage,
+    expected: L1BatchNumber,
+) {
+    let wait_future = tokio::time::timeout(TEST_TIMEOUT, async {
+        loop {
+            let event = events
+                .recv()
+                .await
+                .expect("Events emitter unexpectedly dropped");
+            if let PubSubEvent::L1BatchStageAdvanced(event_stage, number) = event {
+                if event_stage == stage && number >= expected {
+                    break;
+                }
+            } else {
+                tracing::trace!(?event, "Skipping event");
+            }
+        }
+    });
+    wait_future.await.expect("Timed out waiting for notifier");
+}
+
 #[tokio::test]
 async fn notifiers_start_after_snapshot_recovery() {
     let pool = ConnectionPool::<Core>::test_pool().await;
@@ -108,10 +134,14 @@ async fn notifiers_start_after_snapshot_recovery() {
 
     let (stop_sender, stop_receiver) = watch::channel(false);
     let (events_sender, mut events_receiver) = mpsc::unbounded_channel();
-    let mut subscribe_logic = EthSubscribe::new();
+    let mut subscribe_logic = EthSubscribe::new(WsConnections::default());
     subscribe_logic.set_events_sender(events_sender);
-    let notifier_handles =
-        subscribe_logic.spawn_notifiers(pool.clone(), POLL_INTERVAL, stop_receiver);
+    let notifier_handles = subscribe_logic.spawn_notifiers(
+        pool.clone(),
+        POLL_INTERVAL,
+        PubSubNotificationSource::Polling,
+        stop_receiver,
+    );
     assert!(!notifier_handles.is_empty());
 
     // Wait a little doing nothing and check that notifier tasks are still active (i.e., have not panicked).
@@ -142,6 +172,86 @@ async fn notifiers_start_after_snapshot_recovery() {
     }
 }
 
+#[tokio::test]
+async fn notifiers_react_to_db_notifications() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    StorageInitialization::Genesis
+        .prepare_storage(&NetworkConfig::for_tests(), &mut storage)
+        .await
+        .unwrap();
+
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let (events_sender, mut events_receiver) = mpsc::unbounded_channel();
+    let mut subscribe_logic = EthSubscribe::new(WsConnections::default());
+    subscribe_logic.set_events_sender(events_sender);
+    // The fallback polling interval is large enough for the notifiers to only react to DB notifications during the test.
+    let polling_interval = Duration::from_secs(1);
+    let notifier_handles = subscribe_logic.spawn_notifiers(
+        pool.clone(),
+        polling_interval,
+        PubSubNotificationSource::DbNotifications(pool.clone()),
+        stop_receiver,
+    );
+    wait_for_notifiers(
+        &mut events_receiver,
+        &[
+            SubscriptionType::Blocks,
+            SubscriptionType::Txs,
+            SubscriptionType::Logs,
+            SubscriptionType::L1Batches,
+        ],
+    )
+    .await;
+
+    let tx_result = execute_l2_transaction(create_l2_transaction(1, 2));
+    store_miniblock(&mut storage, MiniblockNumber(1), &[tx_result])
+        .await
+        .unwrap();
+    let wait_future = wait_for_notifier_miniblock(
+        &mut events_receiver,
+        SubscriptionType::Blocks,
+        MiniblockNumber(1),
+    );
+    tokio::time::timeout(polling_interval * 10, wait_future)
+        .await
+        .expect("Notifier did not react to DB notification");
+
+    seal_l1_batch(&mut storage, L1BatchNumber(1)).await.unwrap();
+    let wait_future = wait_for_notifier_l1_batch(
+        &mut events_receiver,
+        api::L1BatchStage::Sealed,
+        L1BatchNumber(1),
+    );
+    tokio::time::timeout(polling_interval * 10, wait_future)
+        .await
+        .expect("Notifier did not react to sealed L1 batch notification");
+
+    storage
+        .eth_sender_dal()
+        .insert_bogus_confirmed_eth_tx(
+            L1BatchNumber(1),
+            AggregatedActionType::Commit,
+            H256::repeat_byte(1),
+            chrono::Utc::now(),
+        )
+        .await
+        .unwrap();
+    let wait_future = wait_for_notifier_l1_batch(
+        &mut events_receiver,
+        api::L1BatchStage::Committed,
+        L1BatchNumber(1),
+    );
+    tokio::time::timeout(polling_interval * 10, wait_future)
+        .await
+        .expect("Notifier did not react to confirmed L1 tx notification");
+
+    stop_sender.send_replace(true);
+    for handle in notifier_handles {
+        handle.await.unwrap().expect("Notifier task failed");
+    }
+}
+
 #[async_trait]
 trait WsTest: Send + Sync {
     /// Prepares the storage before the server is started. The default implementation performs genesis.
@@ -181,6 +291,7 @@ async fn test_ws_server(test: impl WsTest) {
         pool.clone(),
         stop_receiver,
         test.websocket_requests_per_minute_limit(),
+        None,
     )
     .await;
 
@@ -317,6 +428,137 @@ async fn basic_subscriptions_after_snapshot_recovery() {
     .await;
 }
 
+#[derive(Debug)]
+struct TxReplacementSubscriptionTest;
+
+#[async_trait]
+impl WsTest for TxReplacementSubscriptionTest {
+    async fn test(
+        &self,
+        client: &WsClient,
+        pool: &ConnectionPool<Core>,
+        mut pub_sub_events: mpsc::UnboundedReceiver<PubSubEvent>,
+    ) -> anyhow::Result<()> {
+        wait_for_notifiers(&mut pub_sub_events, &[SubscriptionType::TxReplacements]).await;
+
+        let params = rpc_params!["transactionReplacements"];
+        let mut replacements_subscription = client
+            .subscribe::<api::TransactionReplacement, _>("eth_subscribe", params, "eth_unsubscribe")
+            .await?;
+        wait_for_subscription(&mut pub_sub_events, SubscriptionType::TxReplacements).await;
+
+        let mut storage = pool.connection().await?;
+        let tx = create_l2_transaction(1, 2);
+        let mut replacing_tx = create_l2_transaction(2, 2);
+        replacing_tx.common_data.initiator_address = tx.initiator_account();
+        for tx in [&tx, &replacing_tx] {
+            storage
+                .transactions_dal()
+                .insert_transaction_l2(tx, TransactionExecutionMetrics::default())
+                .await?;
+        }
+        drop(storage);
+
+        let replacement = tokio::time::timeout(TEST_TIMEOUT, replacements_subscription.next())
+            .await
+            .context("Timed out waiting for tx replacement")?
+            .context("Tx replacements subscription terminated")??;
+        assert_eq!(
+            replacement,
+            api::TransactionReplacement {
+                replaced_hash: tx.hash(),
+                hash: replacing_tx.hash(),
+                from: tx.initiator_account(),
+                nonce: 0.into(),
+            }
+        );
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn tx_replacement_subscription() {
+    test_ws_server(TxReplacementSubscriptionTest).await;
+}
+
+#[derive(Debug)]
+struct L1BatchSubscriptionsTest;
+
+#[async_trait]
+impl WsTest for L1BatchSubscriptionsTest {
+    async fn test(
+        &self,
+        client: &WsClient,
+        pool: &ConnectionPool<Core>,
+        mut pub_sub_events: mpsc::UnboundedReceiver<PubSubEvent>,
+    ) -> anyhow::Result<()> {
+        wait_for_notifiers(&mut pub_sub_events, &[SubscriptionType::L1Batches]).await;
+
+        let mut sealed_subscription = client
+            .subscribe::<api::L1BatchStageUpdate, _>(
+                "zks_subscribe",
+                rpc_params!["l1BatchSealed"],
+                "zks_unsubscribe",
+            )
+            .await?;
+        wait_for_subscription(&mut pub_sub_events, SubscriptionType::L1Batches).await;
+        let mut committed_subscription = client
+            .subscribe::<api::L1BatchStageUpdate, _>(
+                "zks_subscribe",
+                rpc_params!["l1BatchCommitted"],
+                "zks_unsubscribe",
+            )
+            .await?;
+        wait_for_subscription(&mut pub_sub_events, SubscriptionType::L1Batches).await;
+
+        let mut storage = pool.connection().await?;
+        seal_l1_batch(&mut storage, L1BatchNumber(1)).await?;
+        let update = tokio::time::timeout(TEST_TIMEOUT, sealed_subscription.next())
+            .await
+            .context("Timed out waiting for sealed L1 batch")?
+            .context("Sealed L1 batches subscription terminated")??;
+        assert_eq!(
+            update,
+            api::L1BatchStageUpdate {
+                l1_batch_number: L1BatchNumber(1),
+                stage: api::L1BatchStage::Sealed,
+                l1_tx_hash: None,
+            }
+        );
+
+        let commit_tx_hash = H256::repeat_byte(1);
+        storage
+            .eth_sender_dal()
+            .insert_bogus_confirmed_eth_tx(
+                L1BatchNumber(1),
+                AggregatedActionType::Commit,
+                commit_tx_hash,
+                chrono::Utc::now(),
+            )
+            .await?;
+        drop(storage);
+
+        let update = tokio::time::timeout(TEST_TIMEOUT, committed_subscription.next())
+            .await
+            .context("Timed out waiting for committed L1 batch")?
+            .context("Committed L1 batches subscription terminated")??;
+        assert_eq!(
+            update,
+            api::L1BatchStageUpdate {
+                l1_batch_number: L1BatchNumber(1),
+                stage: api::L1BatchStage::Committed,
+                l1_tx_hash: Some(commit_tx_hash),
+            }
+        );
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn l1_batch_subscriptions() {
+    test_ws_server(L1BatchSubscriptionsTest).await;
+}
+
 #[derive(Debug)]
 struct LogSubscriptionsTest {
     snapshot_recovery: bool,
@@ -558,6 +800,113 @@ async fn log_subscriptions_with_many_new_blocks_at_once() {
     test_ws_server(LogSubscriptionsWithManyBlocksTest).await;
 }
 
+/// Reads the specified number of events from an SSE response, skipping keep-alive comments.
+async fn read_sse_events<T: serde::de::DeserializeOwned>(
+    response: &mut reqwest::Response,
+    buffer: &mut String,
+    expected_count: usize,
+) -> anyhow::Result<Vec<T>> {
+    let mut events = Vec::with_capacity(expected_count);
+    while events.len() < expected_count {
+        if let Some(pos) = buffer.find("\n\n") {
+            let event: String = buffer.drain(..pos + 2).collect();
+            for line in event.lines() {
+                if let Some(data) = line.strip_prefix("data:") {
+                    events.push(serde_json::from_str(data.trim())?);
+                }
+            }
+            continue;
+        }
+
+        let chunk = tokio::time::timeout(TEST_TIMEOUT, response.chunk())
+            .await
+            .context("Timed out waiting for SSE event")??
+            .context("SSE stream terminated")?;
+        buffer.push_str(std::str::from_utf8(&chunk)?);
+    }
+    Ok(events)
+}
+
+#[tokio::test]
+async fn sse_endpoint() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let contracts_config = ContractsConfig::for_tests();
+    let web3_config = Web3JsonRpcConfig::for_tests();
+    let genesis_config = GenesisConfig::for_tests();
+    let api_config = InternalApiConfig::new(&web3_config, &contracts_config, &genesis_config);
+    let mut storage = pool.connection().await.unwrap();
+    StorageInitialization::Genesis
+        .prepare_storage(&NetworkConfig::for_tests(), &mut storage)
+        .await
+        .unwrap();
+    drop(storage);
+
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let (mut server_handles, mut pub_sub_events) =
+        spawn_ws_server(api_config, pool.clone(), stop_receiver, None, Some(0)).await;
+    server_handles.wait_until_ready().await;
+    let sse_port = server_handles
+        .sse_local_addr
+        .expect("SSE server is not started")
+        .port();
+    wait_for_notifiers(
+        &mut pub_sub_events,
+        &[SubscriptionType::Blocks, SubscriptionType::Logs],
+    )
+    .await;
+
+    let base_url = format!("http://127.0.0.1:{sse_port}");
+    let client = reqwest::Client::new();
+    let too_many_topics = PubSubFilter {
+        address: None,
+        topics: Some(vec![None; 5]),
+    };
+    let response = client
+        .get(format!("{base_url}/logs"))
+        .query(&[("filter", serde_json::to_string(&too_many_topics).unwrap())])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let mut blocks_response = client
+        .get(format!("{base_url}/blocks"))
+        .send()
+        .await
+        .unwrap()
+        .error_for_status()
+        .unwrap();
+    let topic_filter = PubSubFilter {
+        address: None,
+        topics: Some(vec![Some(H256::repeat_byte(42).into())]),
+    };
+    let mut logs_response = client
+        .get(format!("{base_url}/logs"))
+        .query(&[("filter", serde_json::to_string(&topic_filter).unwrap())])
+        .send()
+        .await
+        .unwrap()
+        .error_for_status()
+        .unwrap();
+
+    let mut storage = pool.connection().await.unwrap();
+    let (_, events) = store_events(&mut storage, 1, 0).await.unwrap();
+    drop(storage);
+    let events: Vec<_> = events.iter().collect();
+
+    let headers: Vec<BlockHeader> = read_sse_events(&mut blocks_response, &mut String::new(), 1)
+        .await
+        .unwrap();
+    assert_eq!(headers[0].number, Some(1.into()));
+    let logs: Vec<api::Log> = read_sse_events(&mut logs_response, &mut String::new(), 2)
+        .await
+        .unwrap();
+    assert_logs_match(&logs, &[events[1], events[3]]);
+
+    stop_sender.send_replace(true);
+    server_handles.shutdown().await;
+}
+
 #[derive(Debug)]
 struct LogSubscriptionsWithDelayTest;
 
@@ -708,3 +1057,56 @@ impl WsTest for BatchGetsRateLimitedTest {
 async fn batch_rate_limiting() {
     test_ws_server(BatchGetsRateLimitedTest).await;
 }
+
+#[derive(Debug)]
+struct WsConnectionsManagementTest;
+
+#[async_trait]
+impl WsTest for WsConnectionsManagementTest {
+    async fn test(
+        &self,
+        client: &WsClient,
+        _pool: &ConnectionPool<Core>,
+        mut pub_sub_events: mpsc::UnboundedReceiver<PubSubEvent>,
+    ) -> anyhow::Result<()> {
+        let params = rpc_params!["newHeads"];
+        let _blocks_subscription = client
+            .subscribe::<BlockHeader, _>("eth_subscribe", params, "eth_unsubscribe")
+            .await?;
+        wait_for_subscription(&mut pub_sub_events, SubscriptionType::Blocks).await;
+
+        // The subscription is linked to the connection asynchronously, so we may need to wait for it.
+        let connection = tokio::time::timeout(TEST_TIMEOUT, async {
+            loop {
+                let connections = client.get_ws_connections().await?;
+                assert_eq!(connections.len(), 1, "{connections:?}");
+                if connections[0].subscription_count == 1 {
+                    return anyhow::Ok(connections[0].clone());
+                }
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        })
+        .await
+        .context("Timed out waiting for subscription to be linked to connection")??;
+        // `eth_subscribe` + at least one `admin_getWsConnections` call
+        assert!(connection.request_count >= 2, "{connection:?}");
+        assert!(!connection.is_closed);
+
+        assert!(!client.close_ws_connection(connection.id + 1).await?);
+        assert!(client.close_ws_connection(connection.id).await?);
+
+        // The server should terminate the connection.
+        tokio::time::timeout(TEST_TIMEOUT, client.on_disconnect())
+            .await
+            .context("Timed out waiting for the connection to be terminated")?;
+        assert!(!client.is_connected());
+        let expected_err = client.chain_id().await.unwrap_err();
+        assert_matches!(expected_err, ClientError::RestartNeeded(_));
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn managing_ws_connections() {
+    test_ws_server(WsConnectionsManagementTest).await;
+}
@@ -0,0 +1,286 @@
+//! Tracking of WebSocket connections served by the API server.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use tokio::sync::watch;
+use zksync_types::api;
+
+use super::metrics::WS_METRICS;
+
+#[derive(Debug)]
+struct ConnectionEntry {
+    opened_at: Instant,
+    request_count: u64,
+    notification_count: u64,
+    subscriptions: HashSet<String>,
+    is_closed: bool,
+    /// Signals the task serving the connection to terminate it. Dropped together with the entry.
+    close_sender: watch::Sender<bool>,
+}
+
+impl ConnectionEntry {
+    fn new() -> Self {
+        Self {
+            opened_at: Instant::now(),
+            request_count: 0,
+            notification_count: 0,
+            subscriptions: HashSet::new(),
+            is_closed: false,
+            close_sender: watch::channel(false).0,
+        }
+    }
+
+    fn info(&self, id: u64, now: Instant) -> api::WsConnectionInfo {
+        let age = now.saturating_duration_since(self.opened_at);
+        // Use at least 1 second as the age so that rates for fresh connections are not inflated.
+        let age_in_minutes = age.as_secs_f64().max(1.0) / 60.0;
+        api::WsConnectionInfo {
+            id,
+            age_secs: age.as_secs(),
+            subscription_count: self.subscriptions.len(),
+            request_count: self.request_count,
+            notification_count: self.notification_count,
+            requests_per_minute: self.request_count as f64 / age_in_minutes,
+            notifications_per_minute: self.notification_count as f64 / age_in_minutes,
+            is_closed: self.is_closed,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct WsConnectionsInner {
+    next_id: u64,
+    connections: HashMap<u64, ConnectionEntry>,
+    /// Mapping from subscription IDs to IDs of connections they belong to.
+    subscriptions: HashMap<String, u64>,
+}
+
+impl WsConnectionsInner {
+    fn connection_for_subscription(
+        &mut self,
+        subscription_id: &str,
+    ) -> Option<&mut ConnectionEntry> {
+        let connection_id = self.subscriptions.get(subscription_id)?;
+        self.connections.get_mut(connection_id)
+    }
+}
+
+/// Registry of active WebSocket connections. Allows to list connections and to close abusive ones.
+///
+/// The registry is cheaply cloneable; all clones refer to the same registry. It can be shared among multiple API servers
+/// (e.g., the WS server populating the registry and the HTTP server exposing the `admin` namespace).
+#[derive(Debug, Clone)]
+pub struct WsConnections {
+    inner: Arc<Mutex<WsConnectionsInner>>,
+    /// Notifies subscriber tasks that one or more connections were closed.
+    closed_sender: Arc<watch::Sender<()>>,
+}
+
+impl Default for WsConnections {
+    fn default() -> Self {
+        Self {
+            inner: Arc::default(),
+            closed_sender: Arc::new(watch::channel(()).0),
+        }
+    }
+}
+
+impl WsConnections {
+    /// Registers a new connection. The connection is unregistered when the returned handle is dropped.
+    pub(super) fn register(&self) -> WsConnection {
+        let mut inner = self.inner.lock().unwrap();
+        let id = inner.next_id;
+        inner.next_id += 1;
+        inner.connections.insert(id, ConnectionEntry::new());
+        drop(inner);
+
+        WS_METRICS.opened_connections.inc();
+        WsConnection {
+            id,
+            connections: self.clone(),
+        }
+    }
+
+    /// Lists all active connections ordered by their IDs.
+    pub(super) fn list(&self) -> Vec<api::WsConnectionInfo> {
+        let inner = self.inner.lock().unwrap();
+        let now = Instant::now();
+        let mut infos: Vec<_> = inner
+            .connections
+            .iter()
+            .map(|(&id, entry)| entry.info(id, now))
+            .collect();
+        infos.sort_unstable_by_key(|info| info.id);
+        infos
+    }
+
+    /// Closes the specified connection: the underlying socket will be closed by the server, all its subscriptions
+    /// will be terminated, and requests received over it in the meantime will be rejected. Returns `false`
+    /// if the connection is not found.
+    pub(super) fn close(&self, id: u64) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(entry) = inner.connections.get_mut(&id) else {
+            return false;
+        };
+        if !entry.is_closed {
+            entry.is_closed = true;
+            entry.close_sender.send_replace(true);
+            drop(inner);
+            WS_METRICS.force_closed_connections.inc();
+            self.closed_sender.send_replace(());
+        }
+        true
+    }
+
+    /// Subscribes to notifications about closed connections.
+    pub(super) fn subscribe_to_closures(&self) -> watch::Receiver<()> {
+        self.closed_sender.subscribe()
+    }
+
+    /// Checks whether the connection owning the specified subscription was closed.
+    pub(super) fn is_subscription_closed(&self, subscription_id: &str) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        inner
+            .connection_for_subscription(subscription_id)
+            .map_or(false, |entry| entry.is_closed)
+    }
+
+    pub(super) fn record_notifications(&self, subscription_id: &str, count: u64) {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(entry) = inner.connection_for_subscription(subscription_id) {
+            entry.notification_count += count;
+        }
+    }
+
+    /// Removes a subscription once it's terminated (e.g., because the client has unsubscribed).
+    pub(super) fn remove_subscription(&self, subscription_id: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(connection_id) = inner.subscriptions.remove(subscription_id) {
+            if let Some(entry) = inner.connections.get_mut(&connection_id) {
+                entry.subscriptions.remove(subscription_id);
+            }
+        }
+    }
+}
+
+/// Handle for a single connection in [`WsConnections`]. Unregisters the connection on drop.
+#[derive(Debug)]
+pub(crate) struct WsConnection {
+    id: u64,
+    connections: WsConnections,
+}
+
+impl WsConnection {
+    /// Returns a receiver that is set to `true` once the connection is closed via [`WsConnections::close()`].
+    /// The sender is dropped once this connection is dropped.
+    pub fn close_receiver(&self) -> watch::Receiver<bool> {
+        let inner = self.connections.inner.lock().unwrap();
+        inner
+            .connections
+            .get(&self.id)
+            .expect("connection entry removed while the connection is alive")
+            .close_sender
+            .subscribe()
+    }
+
+    /// Records a request over this connection. Returns `false` if the connection is closed.
+    pub fn record_request(&self) -> bool {
+        let mut inner = self.connections.inner.lock().unwrap();
+        let entry = inner
+            .connections
+            .get_mut(&self.id)
+            .expect("connection entry removed while the connection is alive");
+        entry.request_count += 1;
+        !entry.is_closed
+    }
+
+    pub fn add_subscription(&self, subscription_id: String) {
+        let mut inner = self.connections.inner.lock().unwrap();
+        let entry = inner
+            .connections
+            .get_mut(&self.id)
+            .expect("connection entry removed while the connection is alive");
+        entry.subscriptions.insert(subscription_id.clone());
+        inner.subscriptions.insert(subscription_id, self.id);
+    }
+}
+
+impl Drop for WsConnection {
+    fn drop(&mut self) {
+        let mut inner = self.connections.inner.lock().unwrap();
+        let Some(entry) = inner.connections.remove(&self.id) else {
+            return;
+        };
+        for subscription_id in &entry.subscriptions {
+            inner.subscriptions.remove(subscription_id);
+        }
+        drop(inner);
+
+        WS_METRICS
+            .connection_lifetime
+            .observe(entry.opened_at.elapsed());
+        WS_METRICS
+            .requests_per_connection
+            .observe(entry.request_count);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registering_and_closing_connections() {
+        let connections = WsConnections::default();
+        let connection = connections.register();
+        let other_connection = connections.register();
+        assert!(connection.record_request());
+        assert!(connection.record_request());
+        connection.add_subscription("0x01".to_owned());
+        other_connection.add_subscription("0x02".to_owned());
+
+        let infos = connections.list();
+        assert_eq!(infos.len(), 2);
+        assert_eq!(infos[0].id, connection.id);
+        assert_eq!(infos[0].request_count, 2);
+        assert_eq!(infos[0].subscription_count, 1);
+        assert!(!infos[0].is_closed);
+        assert_eq!(infos[1].id, other_connection.id);
+        assert_eq!(infos[1].request_count, 0);
+
+        connections.record_notifications("0x01", 3);
+        assert_eq!(connections.list()[0].notification_count, 3);
+
+        let mut closures = connections.subscribe_to_closures();
+        let mut close_receiver = connection.close_receiver();
+        let other_close_receiver = other_connection.close_receiver();
+        assert!(connections.close(connection.id));
+        assert!(closures.has_changed().unwrap());
+        assert!(*close_receiver.borrow_and_update());
+        assert!(!*other_close_receiver.borrow());
+        closures.borrow_and_update();
+        assert!(connections.is_subscription_closed("0x01"));
+        assert!(!connections.is_subscription_closed("0x02"));
+        assert!(!connection.record_request());
+        assert!(other_connection.record_request());
+        // Closing the connection repeatedly should be a no-op.
+        assert!(connections.close(connection.id));
+        assert!(!closures.has_changed().unwrap());
+
+        connections.remove_subscription("0x02");
+        assert_eq!(connections.list()[1].subscription_count, 0);
+
+        let connection_id = connection.id;
+        drop(connection);
+        assert!(close_receiver.has_changed().is_err());
+        let infos = connections.list();
+        assert_eq!(infos.len(), 1);
+        assert_eq!(infos[0].id, other_connection.id);
+        assert!(!connections.close(connection_id));
+        assert!(!connections.is_subscription_closed("0x01"));
+    }
+}
@@ -10,6 +10,7 @@ use zksync_dal::{
 };
 use zksync_object_store::{ObjectStore, ObjectStoreFactory};
 use zksync_queued_job_processor::JobProcessor;
+use zksync_state::BytecodeCache;
 use zksync_types::{witness_block_state::WitnessBlockState, L1BatchNumber, L2ChainId};
 
 use self::metrics::METRICS;
@@ -25,6 +26,7 @@ pub struct BasicWitnessInputProducer {
     connection_pool: ConnectionPool<Core>,
     l2_chain_id: L2ChainId,
     object_store: Arc<dyn ObjectStore>,
+    bytecode_cache: Option<BytecodeCache>,
 }
 
 impl BasicWitnessInputProducer {
@@ -37,15 +39,24 @@ impl BasicWitnessInputProducer {
             connection_pool,
             object_store: store_factory.create_store().await,
             l2_chain_id,
+            bytecode_cache: None,
         })
     }
 
+    /// Sets the bytecode cache used when re-executing L1 batches. The cache can be shared with other components.
+    #[must_use]
+    pub fn with_bytecode_cache(mut self, bytecode_cache: BytecodeCache) -> Self {
+        self.bytecode_cache = Some(bytecode_cache);
+        self
+    }
+
     fn process_job_impl(
         rt_handle: Handle,
         l1_batch_number: L1BatchNumber,
         started_at: Instant,
         connection_pool: ConnectionPool<Core>,
         l2_chain_id: L2ChainId,
+        bytecode_cache: Option<BytecodeCache>,
     ) -> anyhow::Result<WitnessBlockState> {
         let mut connection = rt_handle
             .block_on(connection_pool.connection())
@@ -57,9 +68,14 @@ impl BasicWitnessInputProducer {
                 .get_miniblocks_to_execute_for_l1_batch(l1_batch_number),
         )?;
 
-        let (mut vm, storage_view) =
-            create_vm(rt_handle.clone(), l1_batch_number, connection, l2_chain_id)
-                .context("failed to create vm for BasicWitnessInputProducer")?;
+        let (mut vm, storage_view) = create_vm(
+            rt_handle.clone(),
+            l1_batch_number,
+            connection,
+            l2_chain_id,
+            bytecode_cache,
+        )
+        .context("failed to create vm for BasicWitnessInputProducer")?;
 
         tracing::info!("Started execution of l1_batch: {l1_batch_number:?}");
 
@@ -147,6 +163,7 @@ impl JobProcessor for BasicWitnessInputProducer {
     ) -> JoinHandle<anyhow::Result<Self::JobArtifacts>> {
         let l2_chain_id = self.l2_chain_id;
         let connection_pool = self.connection_pool.clone();
+        let bytecode_cache = self.bytecode_cache.clone();
         tokio::task::spawn_blocking(move || {
             let rt_handle = Handle::current();
             Self::process_job_impl(
@@ -155,6 +172,7 @@ impl JobProcessor for BasicWitnessInputProducer {
                 started_at,
                 connection_pool.clone(),
                 l2_chain_id,
+                bytecode_cache,
             )
         })
     }
@@ -0,0 +1,27 @@
+//! Metrics for the batch exporter.
+
+use std::time::Duration;
+
+use vise::{Buckets, Counter, Family, Gauge, Histogram, Metrics, Unit};
+
+use super::schema::ExportedTable;
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "server_batch_exporter")]
+pub(super) struct BatchExporterMetrics {
+    /// Latency of exporting a single L1 batch.
+    #[metrics(buckets = Buckets::LATENCIES, unit = Unit::Seconds)]
+    pub export_latency: Histogram<Duration>,
+    /// Number of exported rows per table.
+    pub exported_rows: Family<ExportedTable, Counter>,
+    /// Total size of exported Parquet files per table.
+    #[metrics(unit = Unit::Bytes)]
+    pub exported_bytes: Family<ExportedTable, Counter>,
+    /// Number of L1 batches re-exported because they were exported with an outdated schema.
+    pub reexported_batches: Counter,
+    /// Number of the last exported L1 batch.
+    pub last_exported_l1_batch: Gauge<u64>,
+}
+
+#[vise::register]
+pub(super) static METRICS: vise::Global<BatchExporterMetrics> = vise::Global::new();
@@ -0,0 +1,217 @@
+//! Exporter of sealed L1 batches (transactions, events and storage logs) as Parquet files to an object store,
+//! so that chain history can be analyzed with external tools (Spark, DuckDB etc.) without querying the main DB.
+
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Context as _;
+use tokio::sync::watch;
+use zksync_dal::{Connection, ConnectionPool, Core, CoreDal};
+use zksync_health_check::{Health, HealthStatus, HealthUpdater, ReactiveHealthCheck};
+use zksync_object_store::{Bucket, ObjectStore, ObjectStoreError};
+use zksync_types::L1BatchNumber;
+
+use self::{
+    metrics::METRICS,
+    schema::{BatchData, ExportedTable, SCHEMA_VERSION},
+};
+
+mod metrics;
+mod schema;
+#[cfg(test)]
+mod tests;
+
+/// L1 batch to be exported.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ExportTask {
+    l1_batch_number: L1BatchNumber,
+    /// Schema version the batch was previously exported with, if any.
+    outdated_schema_version: Option<u32>,
+}
+
+/// Component exporting sealed L1 batches as Parquet files, one file per table per batch.
+///
+/// Batches are exported sequentially, starting from the earliest batch in the DB. Once all sealed batches are exported,
+/// the exporter re-exports batches exported with an outdated schema version (if any); see the [`schema`] module docs
+/// for details.
+#[derive(Debug)]
+pub struct BatchExporter {
+    pool: ConnectionPool<Core>,
+    object_store: Arc<dyn ObjectStore>,
+    poll_interval: Duration,
+    health_updater: HealthUpdater,
+}
+
+impl BatchExporter {
+    pub fn new(
+        pool: ConnectionPool<Core>,
+        object_store: Arc<dyn ObjectStore>,
+        poll_interval: Duration,
+    ) -> Self {
+        Self {
+            pool,
+            object_store,
+            poll_interval,
+            health_updater: ReactiveHealthCheck::new("batch_exporter").1,
+        }
+    }
+
+    pub fn health_check(&self) -> ReactiveHealthCheck {
+        self.health_updater.subscribe()
+    }
+
+    async fn next_task(storage: &mut Connection<'_, Core>) -> anyhow::Result<Option<ExportTask>> {
+        let Some(sealed_l1_batch) = storage.blocks_dal().get_sealed_l1_batch_number().await? else {
+            return Ok(None); // The DB is empty
+        };
+        let last_exported_l1_batch = storage
+            .batch_exports_dal()
+            .get_last_exported_l1_batch()
+            .await?;
+        let next_l1_batch = if let Some(last_exported) = last_exported_l1_batch {
+            last_exported + 1
+        } else {
+            storage
+                .blocks_dal()
+                .get_earliest_l1_batch_number()
+                .await?
+                .context("no L1 batches in the DB despite a sealed batch present")?
+        };
+        if next_l1_batch <= sealed_l1_batch {
+            return Ok(Some(ExportTask {
+                l1_batch_number: next_l1_batch,
+                outdated_schema_version: None,
+            }));
+        }
+
+        let outdated = storage
+            .batch_exports_dal()
+            .get_l1_batch_with_outdated_schema(SCHEMA_VERSION)
+            .await?;
+        Ok(
+            outdated.map(|(l1_batch_number, schema_version)| ExportTask {
+                l1_batch_number,
+                outdated_schema_version: Some(schema_version),
+            }),
+        )
+    }
+
+    async fn load_batch_data(
+        storage: &mut Connection<'_, Core>,
+        l1_batch_number: L1BatchNumber,
+    ) -> anyhow::Result<BatchData> {
+        let transactions = storage
+            .batch_exports_dal()
+            .get_transactions_for_export(l1_batch_number)
+            .await?;
+        let events = storage
+            .batch_exports_dal()
+            .get_events_for_export(l1_batch_number)
+            .await?
+            .with_context(|| format!("L1 batch #{l1_batch_number} has no miniblocks"))?;
+        let storage_logs = storage
+            .batch_exports_dal()
+            .get_storage_logs_for_export(l1_batch_number)
+            .await?
+            .with_context(|| format!("L1 batch #{l1_batch_number} has no miniblocks"))?;
+        Ok(BatchData {
+            l1_batch_number,
+            transactions,
+            events,
+            storage_logs,
+        })
+    }
+
+    /// Exports the next L1 batch if there is one. Returns the number of the exported batch.
+    async fn export_next_l1_batch(&self) -> anyhow::Result<Option<L1BatchNumber>> {
+        let mut storage = self.pool.connection_tagged("batch_exporter").await?;
+        let Some(task) = Self::next_task(&mut storage).await? else {
+            return Ok(None);
+        };
+        let l1_batch_number = task.l1_batch_number;
+        tracing::info!("Exporting L1 batch #{l1_batch_number}: {task:?}");
+
+        let latency = METRICS.export_latency.start();
+        let batch_data = Self::load_batch_data(&mut storage, l1_batch_number).await?;
+        drop(storage);
+
+        let encoded_tables = tokio::task::spawn_blocking(move || {
+            ExportedTable::ALL
+                .into_iter()
+                .map(|table| {
+                    let encoded = batch_data.to_parquet(table).with_context(|| {
+                        format!("failed encoding `{table:?}` for L1 batch #{l1_batch_number}")
+                    })?;
+                    Ok((table, batch_data.row_count(table), encoded))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()
+        })
+        .await
+        .context("panicked encoding Parquet files")??;
+
+        for (table, row_count, encoded) in encoded_tables {
+            let key = table.object_key(SCHEMA_VERSION, l1_batch_number);
+            let byte_count = encoded.len();
+            self.object_store
+                .put_raw(Bucket::BatchExports, &key, encoded)
+                .await
+                .with_context(|| format!("failed uploading `{key}`"))?;
+            tracing::debug!("Uploaded `{key}` with {row_count} rows ({byte_count} bytes)");
+            METRICS.exported_rows[&table].inc_by(row_count as u64);
+            METRICS.exported_bytes[&table].inc_by(byte_count as u64);
+        }
+
+        if let Some(outdated_version) = task.outdated_schema_version {
+            // Outdated files are removed only after the new ones are uploaded, so that the batch data is always available.
+            for table in ExportedTable::ALL {
+                let key = table.object_key(outdated_version, l1_batch_number);
+                match self
+                    .object_store
+                    .remove_raw(Bucket::BatchExports, &key)
+                    .await
+                {
+                    Ok(()) | Err(ObjectStoreError::KeyNotFound(_)) => {}
+                    Err(err) => {
+                        return Err(anyhow::Error::from(err)
+                            .context(format!("failed removing outdated `{key}`")));
+                    }
+                }
+            }
+            METRICS.reexported_batches.inc();
+        }
+
+        self.pool
+            .connection_tagged("batch_exporter")
+            .await?
+            .batch_exports_dal()
+            .mark_l1_batch_exported(l1_batch_number, SCHEMA_VERSION)
+            .await?;
+        let latency = latency.observe();
+        tracing::info!("Exported L1 batch #{l1_batch_number} in {latency:?}");
+        if task.outdated_schema_version.is_none() {
+            METRICS.last_exported_l1_batch.set(l1_batch_number.0.into());
+        }
+
+        let health_details = serde_json::json!({
+            "last_exported_l1_batch": l1_batch_number,
+            "schema_version": SCHEMA_VERSION,
+        });
+        self.health_updater
+            .update(Health::from(HealthStatus::Ready).with_details(health_details));
+        Ok(Some(l1_batch_number))
+    }
+
+    pub async fn run(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        self.health_updater.update(HealthStatus::Ready.into());
+        while !*stop_receiver.borrow_and_update() {
+            if self.export_next_l1_batch().await?.is_some() {
+                continue;
+            }
+            // Error here corresponds to a timeout w/o `stop_receiver` changed; we're OK with this.
+            tokio::time::timeout(self.poll_interval, stop_receiver.changed())
+                .await
+                .ok();
+        }
+        tracing::info!("Stop signal received, batch exporter is shutting down");
+        Ok(())
+    }
+}
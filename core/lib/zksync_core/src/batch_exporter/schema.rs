@@ -0,0 +1,258 @@
+//! Schemas of exported tables and their encoding as Parquet files.
+//!
+//! # Schema evolution
+//!
+//! All tables share a single [`SCHEMA_VERSION`]. The version is a part of the object key of each exported file
+//! (as a Hive-style `schema_version=_` partition) and is also recorded in the file metadata under
+//! [`SCHEMA_VERSION_METADATA_KEY`]. When the schema of any table changes, the version must be bumped;
+//! the exporter then gradually re-exports batches exported with older versions and removes outdated files,
+//! so that eventually all batches are available with the latest schema. To keep the data readable by engines
+//! merging schemas across partitions (e.g., Spark with `mergeSchema` or DuckDB with `union_by_name`),
+//! columns should only be added (as nullable), never renamed or retyped.
+
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use arrow_array::{
+    ArrayRef, BinaryArray, BooleanArray, Int32Array, RecordBatch, StringArray, UInt32Array,
+    UInt64Array,
+};
+use arrow_schema::{DataType, Field, Schema};
+use parquet::{
+    arrow::ArrowWriter, basic::Compression, file::properties::WriterProperties, format::KeyValue,
+};
+use vise::{EncodeLabelSet, EncodeLabelValue};
+use zksync_dal::batch_exports_dal::{ExportedEvent, ExportedStorageLog, ExportedTransaction};
+use zksync_types::{L1BatchNumber, H256};
+
+/// Current version of the schema of exported tables.
+pub(super) const SCHEMA_VERSION: u32 = 1;
+/// Key in the Parquet file metadata holding the schema version.
+pub(super) const SCHEMA_VERSION_METADATA_KEY: &str = "zksync.schema_version";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "table", rename_all = "snake_case")]
+pub(super) enum ExportedTable {
+    Transactions,
+    Events,
+    StorageLogs,
+}
+
+impl ExportedTable {
+    pub const ALL: [Self; 3] = [Self::Transactions, Self::Events, Self::StorageLogs];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Transactions => "transactions",
+            Self::Events => "events",
+            Self::StorageLogs => "storage_logs",
+        }
+    }
+
+    /// Returns the object key for the data of the specified L1 batch exported with the specified schema version.
+    pub fn object_key(self, schema_version: u32, l1_batch_number: L1BatchNumber) -> String {
+        format!(
+            "{table}/schema_version={schema_version}/l1_batch_number={l1_batch_number}/data.parquet",
+            table = self.as_str(),
+            l1_batch_number = l1_batch_number.0
+        )
+    }
+
+    fn schema(self) -> Schema {
+        let fields = match self {
+            Self::Transactions => vec![
+                Field::new("l1_batch_number", DataType::UInt32, false),
+                Field::new("miniblock_number", DataType::UInt32, false),
+                Field::new("index_in_block", DataType::UInt32, false),
+                Field::new("hash", DataType::Binary, false),
+                Field::new("is_priority", DataType::Boolean, false),
+                Field::new("initiator_address", DataType::Binary, false),
+                Field::new("contract_address", DataType::Binary, true),
+                Field::new("nonce", DataType::UInt32, true),
+                // 256-bit values are stored as decimal strings since they don't fit into Parquet decimals.
+                Field::new("value", DataType::Utf8, false),
+                Field::new("gas_limit", DataType::Utf8, true),
+                Field::new("refunded_gas", DataType::UInt64, false),
+                Field::new("error", DataType::Utf8, true),
+                Field::new("tx_format", DataType::Int32, true),
+            ],
+            Self::Events => vec![
+                Field::new("l1_batch_number", DataType::UInt32, false),
+                Field::new("miniblock_number", DataType::UInt32, false),
+                Field::new("tx_hash", DataType::Binary, false),
+                Field::new("tx_index_in_block", DataType::UInt32, false),
+                Field::new("event_index_in_block", DataType::UInt32, false),
+                Field::new("event_index_in_tx", DataType::UInt32, false),
+                Field::new("address", DataType::Binary, false),
+                Field::new("topic1", DataType::Binary, true),
+                Field::new("topic2", DataType::Binary, true),
+                Field::new("topic3", DataType::Binary, true),
+                Field::new("topic4", DataType::Binary, true),
+                Field::new("value", DataType::Binary, false),
+            ],
+            Self::StorageLogs => vec![
+                Field::new("l1_batch_number", DataType::UInt32, false),
+                Field::new("miniblock_number", DataType::UInt32, false),
+                Field::new("operation_number", DataType::UInt32, false),
+                Field::new("hashed_key", DataType::Binary, false),
+                Field::new("address", DataType::Binary, false),
+                Field::new("key", DataType::Binary, false),
+                Field::new("value", DataType::Binary, false),
+            ],
+        };
+        Schema::new(fields)
+    }
+}
+
+/// Data of a single L1 batch to be exported.
+#[derive(Debug)]
+pub(super) struct BatchData {
+    pub l1_batch_number: L1BatchNumber,
+    pub transactions: Vec<ExportedTransaction>,
+    pub events: Vec<ExportedEvent>,
+    pub storage_logs: Vec<ExportedStorageLog>,
+}
+
+impl BatchData {
+    pub fn row_count(&self, table: ExportedTable) -> usize {
+        match table {
+            ExportedTable::Transactions => self.transactions.len(),
+            ExportedTable::Events => self.events.len(),
+            ExportedTable::StorageLogs => self.storage_logs.len(),
+        }
+    }
+
+    fn l1_batch_number_column(&self, len: usize) -> ArrayRef {
+        Arc::new(UInt32Array::from(vec![self.l1_batch_number.0; len]))
+    }
+
+    fn record_batch(&self, table: ExportedTable) -> anyhow::Result<RecordBatch> {
+        let columns: Vec<ArrayRef> = match table {
+            ExportedTable::Transactions => {
+                let txs = &self.transactions;
+                vec![
+                    self.l1_batch_number_column(txs.len()),
+                    Arc::new(UInt32Array::from_iter_values(
+                        txs.iter().map(|tx| tx.miniblock_number.0),
+                    )),
+                    Arc::new(UInt32Array::from_iter_values(
+                        txs.iter().map(|tx| tx.index_in_block),
+                    )),
+                    Arc::new(BinaryArray::from_iter_values(
+                        txs.iter().map(|tx| tx.hash.as_bytes()),
+                    )),
+                    Arc::new(BooleanArray::from(
+                        txs.iter().map(|tx| tx.is_priority).collect::<Vec<_>>(),
+                    )),
+                    Arc::new(BinaryArray::from_iter_values(
+                        txs.iter().map(|tx| tx.initiator_address.as_bytes()),
+                    )),
+                    Arc::new(BinaryArray::from_iter(txs.iter().map(|tx| {
+                        tx.contract_address.as_ref().map(|addr| addr.as_bytes())
+                    }))),
+                    Arc::new(UInt32Array::from_iter(txs.iter().map(|tx| tx.nonce))),
+                    Arc::new(StringArray::from_iter_values(
+                        txs.iter().map(|tx| tx.value.to_string()),
+                    )),
+                    Arc::new(StringArray::from_iter(
+                        txs.iter()
+                            .map(|tx| tx.gas_limit.map(|limit| limit.to_string())),
+                    )),
+                    Arc::new(UInt64Array::from_iter_values(
+                        txs.iter().map(|tx| tx.refunded_gas),
+                    )),
+                    Arc::new(StringArray::from_iter(
+                        txs.iter().map(|tx| tx.error.as_deref()),
+                    )),
+                    Arc::new(Int32Array::from_iter(txs.iter().map(|tx| tx.tx_format))),
+                ]
+            }
+            ExportedTable::Events => {
+                let events = &self.events;
+                let topic_column = |idx: usize| -> ArrayRef {
+                    Arc::new(BinaryArray::from_iter(
+                        events
+                            .iter()
+                            .map(|event| event.topics.get(idx).map(H256::as_bytes)),
+                    ))
+                };
+                vec![
+                    self.l1_batch_number_column(events.len()),
+                    Arc::new(UInt32Array::from_iter_values(
+                        events.iter().map(|event| event.miniblock_number.0),
+                    )),
+                    Arc::new(BinaryArray::from_iter_values(
+                        events.iter().map(|event| event.tx_hash.as_bytes()),
+                    )),
+                    Arc::new(UInt32Array::from_iter_values(
+                        events.iter().map(|event| event.tx_index_in_block),
+                    )),
+                    Arc::new(UInt32Array::from_iter_values(
+                        events.iter().map(|event| event.event_index_in_block),
+                    )),
+                    Arc::new(UInt32Array::from_iter_values(
+                        events.iter().map(|event| event.event_index_in_tx),
+                    )),
+                    Arc::new(BinaryArray::from_iter_values(
+                        events.iter().map(|event| event.address.as_bytes()),
+                    )),
+                    topic_column(0),
+                    topic_column(1),
+                    topic_column(2),
+                    topic_column(3),
+                    Arc::new(BinaryArray::from_iter_values(
+                        events.iter().map(|event| event.value.as_slice()),
+                    )),
+                ]
+            }
+            ExportedTable::StorageLogs => {
+                let logs = &self.storage_logs;
+                vec![
+                    self.l1_batch_number_column(logs.len()),
+                    Arc::new(UInt32Array::from_iter_values(
+                        logs.iter().map(|log| log.miniblock_number.0),
+                    )),
+                    Arc::new(UInt32Array::from_iter_values(
+                        logs.iter().map(|log| log.operation_number),
+                    )),
+                    Arc::new(BinaryArray::from_iter_values(
+                        logs.iter().map(|log| log.hashed_key.as_bytes()),
+                    )),
+                    Arc::new(BinaryArray::from_iter_values(
+                        logs.iter().map(|log| log.address.as_bytes()),
+                    )),
+                    Arc::new(BinaryArray::from_iter_values(
+                        logs.iter().map(|log| log.key.as_bytes()),
+                    )),
+                    Arc::new(BinaryArray::from_iter_values(
+                        logs.iter().map(|log| log.value.as_bytes()),
+                    )),
+                ]
+            }
+        };
+        RecordBatch::try_new(Arc::new(table.schema()), columns)
+            .with_context(|| format!("failed creating record batch for `{}`", table.as_str()))
+    }
+
+    /// Encodes data for the specified table as a Parquet file.
+    pub fn to_parquet(&self, table: ExportedTable) -> anyhow::Result<Vec<u8>> {
+        let record_batch = self.record_batch(table)?;
+        let metadata = KeyValue::new(
+            SCHEMA_VERSION_METADATA_KEY.to_owned(),
+            SCHEMA_VERSION.to_string(),
+        );
+        let props = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .set_key_value_metadata(Some(vec![metadata]))
+            .build();
+
+        let mut buffer = vec![];
+        let mut writer = ArrowWriter::try_new(&mut buffer, record_batch.schema(), Some(props))
+            .context("failed creating Parquet writer")?;
+        writer
+            .write(&record_batch)
+            .context("failed writing record batch")?;
+        writer.close().context("failed finalizing Parquet file")?;
+        Ok(buffer)
+    }
+}
@@ -0,0 +1,163 @@
+//! Tests for the batch exporter.
+
+use std::io::{Seek, Write};
+
+use assert_matches::assert_matches;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use zksync_object_store::ObjectStoreFactory;
+
+use super::{schema::SCHEMA_VERSION_METADATA_KEY, *};
+use crate::{
+    genesis::{insert_genesis_batch, GenesisParams},
+    utils::testonly::{create_l1_batch, create_miniblock},
+};
+
+async fn create_exporter(pool: &ConnectionPool<Core>) -> BatchExporter {
+    let object_store = ObjectStoreFactory::mock().create_store().await;
+    BatchExporter::new(pool.clone(), object_store, Duration::from_millis(10))
+}
+
+/// Returns the number of rows and the schema version from the file metadata.
+fn read_parquet(bytes: &[u8]) -> (usize, Option<String>) {
+    let mut file = tempfile::tempfile().unwrap();
+    file.write_all(bytes).unwrap();
+    file.rewind().unwrap();
+
+    let builder = ParquetRecordBatchReaderBuilder::try_new(file).unwrap();
+    let schema_version = builder
+        .metadata()
+        .file_metadata()
+        .key_value_metadata()
+        .and_then(|metadata| {
+            metadata
+                .iter()
+                .find(|kv| kv.key == SCHEMA_VERSION_METADATA_KEY)
+                .and_then(|kv| kv.value.clone())
+        });
+    let row_count = builder
+        .build()
+        .unwrap()
+        .map(|batch| batch.unwrap().num_rows())
+        .sum();
+    (row_count, schema_version)
+}
+
+#[tokio::test]
+async fn exporting_l1_batches() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let exporter = create_exporter(&pool).await;
+
+    let exported = exporter.export_next_l1_batch().await.unwrap();
+    assert_eq!(exported, Some(L1BatchNumber(0)));
+    assert_eq!(exporter.export_next_l1_batch().await.unwrap(), None);
+
+    let expected_storage_logs = storage
+        .batch_exports_dal()
+        .get_storage_logs_for_export(L1BatchNumber(0))
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!expected_storage_logs.is_empty());
+    let key = ExportedTable::StorageLogs.object_key(SCHEMA_VERSION, L1BatchNumber(0));
+    let file = exporter
+        .object_store
+        .get_raw(Bucket::BatchExports, &key)
+        .await
+        .unwrap();
+    let (row_count, schema_version) = read_parquet(&file);
+    assert_eq!(row_count, expected_storage_logs.len());
+    assert_eq!(schema_version, Some(SCHEMA_VERSION.to_string()));
+
+    storage
+        .blocks_dal()
+        .insert_miniblock(&create_miniblock(1))
+        .await
+        .unwrap();
+    storage
+        .blocks_dal()
+        .insert_mock_l1_batch(&create_l1_batch(1))
+        .await
+        .unwrap();
+    storage
+        .blocks_dal()
+        .mark_miniblocks_as_executed_in_l1_batch(L1BatchNumber(1))
+        .await
+        .unwrap();
+
+    let exported = exporter.export_next_l1_batch().await.unwrap();
+    assert_eq!(exported, Some(L1BatchNumber(1)));
+    for table in ExportedTable::ALL {
+        let key = table.object_key(SCHEMA_VERSION, L1BatchNumber(1));
+        let file = exporter
+            .object_store
+            .get_raw(Bucket::BatchExports, &key)
+            .await
+            .unwrap();
+        let (row_count, _) = read_parquet(&file);
+        assert_eq!(row_count, 0, "{table:?}");
+    }
+    assert_eq!(
+        storage
+            .batch_exports_dal()
+            .get_last_exported_l1_batch()
+            .await
+            .unwrap(),
+        Some(L1BatchNumber(1))
+    );
+}
+
+#[tokio::test]
+async fn reexporting_batch_with_outdated_schema() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let outdated_version = SCHEMA_VERSION - 1;
+    storage
+        .batch_exports_dal()
+        .mark_l1_batch_exported(L1BatchNumber(0), outdated_version)
+        .await
+        .unwrap();
+
+    let exporter = create_exporter(&pool).await;
+    for table in ExportedTable::ALL {
+        let key = table.object_key(outdated_version, L1BatchNumber(0));
+        exporter
+            .object_store
+            .put_raw(Bucket::BatchExports, &key, vec![0; 32])
+            .await
+            .unwrap();
+    }
+
+    let exported = exporter.export_next_l1_batch().await.unwrap();
+    assert_eq!(exported, Some(L1BatchNumber(0)));
+    for table in ExportedTable::ALL {
+        let outdated_key = table.object_key(outdated_version, L1BatchNumber(0));
+        let err = exporter
+            .object_store
+            .get_raw(Bucket::BatchExports, &outdated_key)
+            .await
+            .unwrap_err();
+        assert_matches!(err, ObjectStoreError::KeyNotFound(_));
+
+        let key = table.object_key(SCHEMA_VERSION, L1BatchNumber(0));
+        exporter
+            .object_store
+            .get_raw(Bucket::BatchExports, &key)
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(exporter.export_next_l1_batch().await.unwrap(), None);
+    let outdated = storage
+        .batch_exports_dal()
+        .get_l1_batch_with_outdated_schema(SCHEMA_VERSION)
+        .await
+        .unwrap();
+    assert_eq!(outdated, None);
+}
@@ -1,5 +1,6 @@
 use std::{path::Path, time::Duration};
 
+use anyhow::Context as _;
 use bitflags::bitflags;
 use serde::Serialize;
 use tokio::time::sleep;
@@ -8,7 +9,7 @@ use zksync_contracts::zksync_contract;
 use zksync_dal::{ConnectionPool, Core, CoreDal};
 use zksync_eth_signer::{EthereumSigner, PrivateKeySigner, TransactionParameters};
 use zksync_merkle_tree::domain::ZkSyncTree;
-use zksync_state::RocksdbStorage;
+use zksync_state::{CacheInvalidator, RevertedBlocks, RocksdbStorage};
 use zksync_storage::RocksDB;
 use zksync_types::{
     aggregated_operations::AggregatedActionType,
@@ -22,6 +23,8 @@ use zksync_types::{
     L1BatchNumber, PackedEthSignature, H160, H256, U256,
 };
 
+use crate::metadata_calculator::{check_tree_rollback, TreeRollbackError};
+
 bitflags! {
     pub struct BlockReverterFlags: u32 {
         const POSTGRES = 0b_0001;
@@ -108,6 +111,7 @@ pub struct BlockReverter {
     eth_config: Option<BlockReverterEthConfig>,
     connection_pool: ConnectionPool<Core>,
     executed_batches_revert_mode: L1ExecutedBatchesRevert,
+    cache_invalidator: Option<CacheInvalidator>,
 }
 
 impl BlockReverter {
@@ -126,9 +130,16 @@ impl BlockReverter {
             eth_config,
             connection_pool,
             executed_batches_revert_mode,
+            cache_invalidator: None,
         }
     }
 
+    /// Sets the invalidator for in-memory caches, which will be invoked after rolling back Postgres data.
+    pub fn with_cache_invalidator(mut self, cache_invalidator: CacheInvalidator) -> Self {
+        self.cache_invalidator = Some(cache_invalidator);
+        self
+    }
+
     /// Rolls back DBs (Postgres + RocksDB) to a previous state.
     pub async fn rollback_db(
         &self,
@@ -222,6 +233,41 @@ impl BlockReverter {
         tree.save();
     }
 
+    /// Rolls back only the Merkle tree so that `last_l1_batch_to_keep` is its last L1 batch; Postgres is left intact.
+    /// Unlike [`Self::rollback_db()`], the rollback is guarded: it is refused if Postgres is ahead of or behind
+    /// the target L1 batch, or if the tree root hash for the target batch doesn't match the one in Postgres.
+    ///
+    /// The tree must not be running when this method is called; to roll back a running tree,
+    /// use the `admin_rollbackTree` RPC method.
+    pub async fn rollback_tree(
+        &self,
+        last_l1_batch_to_keep: L1BatchNumber,
+    ) -> Result<(), TreeRollbackError> {
+        let merkle_tree_path = Path::new(&self.merkle_tree_path);
+        if !merkle_tree_path.exists() {
+            return Err(TreeRollbackError::Refused(format!(
+                "Merkle tree not found at {merkle_tree_path:?}"
+            )));
+        }
+        let db = RocksDB::new(merkle_tree_path)
+            .context("failed initializing RocksDB for Merkle tree; is the tree running?")?;
+        let mut tree = ZkSyncTree::new_lightweight(db.into());
+
+        let mut storage = self
+            .connection_pool
+            .connection()
+            .await
+            .context("failed getting Postgres connection")?;
+        check_tree_rollback(&mut storage, &tree.reader(), last_l1_batch_to_keep).await?;
+        drop(storage);
+
+        tracing::info!("Rolling back Merkle tree to L1 batch #{last_l1_batch_to_keep}...");
+        tree.revert_logs(last_l1_batch_to_keep);
+        tracing::info!("saving tree changes to disk...");
+        tree.save();
+        Ok(())
+    }
+
     /// Reverts blocks in the state keeper cache.
     async fn rollback_state_keeper_cache(&self, last_l1_batch_to_keep: L1BatchNumber) {
         tracing::info!("opening DB with state keeper cache...");
@@ -255,6 +301,12 @@ impl BlockReverter {
             .unwrap()
             .expect("L1 batch should contain at least one miniblock");
 
+        tracing::info!("recording rolled back priority operations...");
+        transaction
+            .priority_ops_audit_dal()
+            .insert_rollback_events(last_miniblock_to_keep)
+            .await
+            .expect("failed recording rolled back priority operations");
         tracing::info!("rolling back transactions state...");
         transaction
             .transactions_dal()
@@ -326,6 +378,14 @@ impl BlockReverter {
             transaction.consensus_dal().fork().await.unwrap();
         }
         transaction.commit().await.unwrap();
+
+        if let Some(cache_invalidator) = &self.cache_invalidator {
+            tracing::info!("invalidating caches...");
+            cache_invalidator.invalidate(RevertedBlocks {
+                last_l1_batch_to_keep,
+                last_miniblock_to_keep,
+            });
+        }
     }
 
     /// Sends revert transaction to L1.
@@ -0,0 +1,23 @@
+//! Metrics for the call traces backfiller.
+
+use std::time::Duration;
+
+use vise::{Buckets, Counter, Gauge, Histogram, Metrics, Unit};
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "server_call_traces_backfiller")]
+pub(super) struct CallTracesBackfillerMetrics {
+    /// Latency of re-executing an L1 batch.
+    #[metrics(buckets = Buckets::LATENCIES, unit = Unit::Seconds)]
+    pub execution_latency: Histogram<Duration>,
+    /// Latency of persisting call traces for an L1 batch.
+    #[metrics(buckets = Buckets::LATENCIES, unit = Unit::Seconds)]
+    pub persistence_latency: Histogram<Duration>,
+    /// Total number of backfilled call traces.
+    pub call_traces: Counter,
+    /// Number of the last L1 batch with backfilled call traces.
+    pub last_processed_l1_batch: Gauge<u64>,
+}
+
+#[vise::register]
+pub(super) static METRICS: vise::Global<CallTracesBackfillerMetrics> = vise::Global::new();
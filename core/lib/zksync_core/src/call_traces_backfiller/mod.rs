@@ -0,0 +1,257 @@
+//! Background backfilling of call traces for sealed L1 batches.
+//!
+//! Call traces are required to serve `debug_traceTransaction` and similar methods. They are not persisted
+//! if the state keeper is configured with `save_call_traces = false`, and can be removed from Postgres
+//! to save space. [`CallTracesBackfiller`] regenerates missing call traces by re-executing sealed L1 batches
+//! in the background.
+//!
+//! Re-execution requires transactions and the storage state before the re-executed batch to be present in Postgres.
+//! Thus, call traces cannot be backfilled for L1 batches up to and including the snapshot L1 batch on nodes recovered
+//! from a snapshot; the backfiller starts from the first L1 batch after the snapshot on such nodes.
+//!
+//! The backfiller persists the last processed L1 batch in Postgres, so that it doesn't re-execute batches after a restart.
+
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Context as _;
+use multivm::interface::{ExecutionResult, VmExecutionResultAndLogs};
+use tokio::sync::watch;
+use zksync_dal::{ConnectionPool, Core, CoreDal};
+use zksync_health_check::{Health, HealthStatus, HealthUpdater, ReactiveHealthCheck};
+use zksync_types::{vm_trace::Call, L1BatchNumber, L2ChainId, Transaction};
+
+use self::metrics::METRICS;
+use crate::state_keeper::{replay::BatchReplayer, TxExecutionResult};
+
+mod metrics;
+
+/// Builds the top-level call trace for a transaction. Mirrors `TransactionExecutionResult::call_trace()`
+/// used when persisting call traces in the state keeper.
+fn top_level_call(
+    tx: &Transaction,
+    tx_result: &VmExecutionResultAndLogs,
+    call_traces: Vec<Call>,
+) -> Option<Call> {
+    if call_traces.is_empty() {
+        return None;
+    }
+    let revert_reason = match &tx_result.result {
+        ExecutionResult::Success { .. } => None,
+        ExecutionResult::Revert { output } => Some(output.to_string()),
+        ExecutionResult::Halt { reason } => Some(reason.to_string()),
+    };
+    let gas_limit = tx.gas_limit().as_u64();
+    Some(Call::new_high_level(
+        gas_limit,
+        gas_limit - tx_result.refunds.gas_refunded,
+        tx.execute.value,
+        tx.execute.calldata.clone(),
+        vec![],
+        revert_reason,
+        call_traces,
+    ))
+}
+
+/// Component regenerating call traces for sealed L1 batches that have transactions without call traces.
+///
+/// Batches are processed sequentially in the ascending order. A batch is processed by re-executing it
+/// using the same batch executor as the state keeper; existing call traces are left intact.
+#[derive(Debug)]
+pub struct CallTracesBackfiller {
+    pool: ConnectionPool<Core>,
+    replayer: BatchReplayer,
+    health_updater: HealthUpdater,
+}
+
+impl CallTracesBackfiller {
+    const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+    /// Creates a new backfiller. `optional_bytecode_compression` must correspond to the setting of the state keeper
+    /// that has executed the batches (it's disabled on the main node and enabled on external nodes).
+    pub fn new(
+        pool: ConnectionPool<Core>,
+        l2_chain_id: L2ChainId,
+        optional_bytecode_compression: bool,
+    ) -> Self {
+        let replayer = BatchReplayer::new(pool.clone(), l2_chain_id, optional_bytecode_compression)
+            .with_call_traces();
+        Self {
+            replayer,
+            pool,
+            health_updater: ReactiveHealthCheck::new("call_traces_backfiller").1,
+        }
+    }
+
+    pub fn health_check(&self) -> ReactiveHealthCheck {
+        self.health_updater.subscribe()
+    }
+
+    /// Backfills call traces for the earliest L1 batch starting from `next_l1_batch` that lacks them.
+    /// Returns the number of the processed batch.
+    async fn process_next_l1_batch(
+        &self,
+        next_l1_batch: L1BatchNumber,
+        stop_receiver: &watch::Receiver<bool>,
+    ) -> anyhow::Result<Option<L1BatchNumber>> {
+        let mut storage = self
+            .pool
+            .connection_tagged("call_traces_backfiller")
+            .await?;
+        let Some(l1_batch_number) = storage
+            .transactions_dal()
+            .get_first_l1_batch_with_missing_call_traces(next_l1_batch)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let protocol_version = storage
+            .blocks_dal()
+            .get_batch_protocol_version_id(l1_batch_number)
+            .await?
+            .with_context(|| format!("no protocol version for L1 batch #{l1_batch_number}"))?;
+        drop(storage);
+        tracing::info!("Backfilling call traces for L1 batch #{l1_batch_number}");
+
+        let latency = METRICS.execution_latency.start();
+        let executed_batch = match self
+            .replayer
+            .reexecute(l1_batch_number, stop_receiver)
+            .await
+        {
+            Ok(batch) => batch,
+            // Re-execution was interrupted by the stop signal, which is handled by the caller.
+            Err(_) if *stop_receiver.borrow() => return Ok(None),
+            Err(err) => {
+                return Err(err.context(format!("failed re-executing L1 batch #{l1_batch_number}")))
+            }
+        };
+        let latency = latency.observe();
+        tracing::debug!("Re-executed L1 batch #{l1_batch_number} in {latency:?}");
+
+        let mut call_traces = vec![];
+        for (tx, tx_result) in executed_batch.miniblocks.into_iter().flatten() {
+            let TxExecutionResult::Success {
+                tx_result,
+                call_tracer_result,
+                ..
+            } = tx_result
+            else {
+                tracing::warn!(
+                    "Transaction {:?} from L1 batch #{l1_batch_number} was not executed successfully \
+                     on re-execution: {tx_result:?}",
+                    tx.hash()
+                );
+                continue;
+            };
+            if let Some(call) = top_level_call(&tx, &tx_result, call_tracer_result) {
+                call_traces.push((tx.hash(), call));
+            }
+        }
+
+        let call_trace_count = call_traces.len();
+        let latency = METRICS.persistence_latency.start();
+        let mut storage = self
+            .pool
+            .connection_tagged("call_traces_backfiller")
+            .await?;
+        let call_traces: Arc<[_]> = call_traces.into();
+        storage
+            .transaction_with_retries(|transaction| {
+                let call_traces = call_traces.clone();
+                Box::pin(async move {
+                    transaction
+                        .transactions_dal()
+                        .insert_call_traces(protocol_version, &call_traces)
+                        .await?;
+                    transaction
+                        .transactions_dal()
+                        .set_call_traces_backfiller_last_processed_l1_batch(l1_batch_number)
+                        .await
+                })
+            })
+            .await?;
+        latency.observe();
+
+        tracing::info!("Backfilled {call_trace_count} call traces for L1 batch #{l1_batch_number}");
+        METRICS.call_traces.inc_by(call_trace_count as u64);
+        METRICS
+            .last_processed_l1_batch
+            .set(l1_batch_number.0.into());
+        self.update_health(l1_batch_number);
+        Ok(Some(l1_batch_number))
+    }
+
+    fn update_health(&self, last_processed_l1_batch: L1BatchNumber) {
+        let health_details = serde_json::json!({
+            "last_processed_l1_batch": last_processed_l1_batch,
+        });
+        self.health_updater
+            .update(Health::from(HealthStatus::Ready).with_details(health_details));
+    }
+
+    /// Returns the last L1 batch that is either processed by the backfiller or cannot be processed
+    /// because of snapshot recovery.
+    async fn load_last_processed_l1_batch(&self) -> anyhow::Result<L1BatchNumber> {
+        let mut storage = self
+            .pool
+            .connection_tagged("call_traces_backfiller")
+            .await?;
+        let last_processed_l1_batch = storage
+            .transactions_dal()
+            .get_call_traces_backfiller_last_processed_l1_batch()
+            .await?;
+        let snapshot_recovery = storage
+            .snapshot_recovery_dal()
+            .get_applied_snapshot_status()
+            .await?;
+        let Some(snapshot_recovery) = snapshot_recovery else {
+            return Ok(last_processed_l1_batch);
+        };
+
+        let snapshot_l1_batch = snapshot_recovery.l1_batch_number;
+        if last_processed_l1_batch >= snapshot_l1_batch {
+            return Ok(last_processed_l1_batch);
+        }
+        // Transactions and storage state for L1 batches up to and including the snapshot one are not present
+        // in Postgres, so these batches cannot be re-executed.
+        tracing::warn!(
+            "Node is recovered from a snapshot at L1 batch #{snapshot_l1_batch}; call traces \
+             cannot be backfilled for this and earlier L1 batches"
+        );
+        storage
+            .transactions_dal()
+            .set_call_traces_backfiller_last_processed_l1_batch(snapshot_l1_batch)
+            .await?;
+        Ok(snapshot_l1_batch)
+    }
+
+    pub async fn run(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        let last_processed_l1_batch = self.load_last_processed_l1_batch().await?;
+        tracing::info!(
+            "Starting call traces backfiller; last processed L1 batch: #{last_processed_l1_batch}"
+        );
+        METRICS
+            .last_processed_l1_batch
+            .set(last_processed_l1_batch.0.into());
+        self.update_health(last_processed_l1_batch);
+
+        // All L1 batches before `next_l1_batch` are processed. Batches are never reprocessed, so that
+        // transactions not producing call traces don't lead to an infinite loop.
+        let mut next_l1_batch = last_processed_l1_batch + 1;
+        while !*stop_receiver.borrow_and_update() {
+            if let Some(processed_l1_batch) = self
+                .process_next_l1_batch(next_l1_batch, &stop_receiver)
+                .await?
+            {
+                next_l1_batch = processed_l1_batch + 1;
+                continue;
+            }
+            // Error here corresponds to a timeout w/o `stop_receiver` changed; we're OK with this.
+            tokio::time::timeout(Self::POLL_INTERVAL, stop_receiver.changed())
+                .await
+                .ok();
+        }
+        tracing::info!("Stop signal received, call traces backfiller is shutting down");
+        Ok(())
+    }
+}
@@ -0,0 +1,22 @@
+//! Metrics for the call traces offloader.
+
+use std::time::Duration;
+
+use vise::{Buckets, Counter, Gauge, Histogram, Metrics, Unit};
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "server_call_traces_offloader")]
+pub(super) struct CallTracesOffloaderMetrics {
+    /// Latency of offloading call traces for a single L1 batch.
+    #[metrics(buckets = Buckets::LATENCIES, unit = Unit::Seconds)]
+    pub offload_latency: Histogram<Duration>,
+    /// Number of offloaded call traces.
+    pub offloaded_traces: Counter,
+    /// Number of the last L1 batch with offloaded call traces.
+    pub last_offloaded_l1_batch: Gauge<u64>,
+    /// Number of call trace requests served from the object store.
+    pub offloaded_reads: Counter,
+}
+
+#[vise::register]
+pub(super) static METRICS: vise::Global<CallTracesOffloaderMetrics> = vise::Global::new();
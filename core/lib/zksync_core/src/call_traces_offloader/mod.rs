@@ -0,0 +1,244 @@
+//! Offloading of call traces for old L1 batches from Postgres to an object store.
+//!
+//! Call traces are one of the largest tables in the DB, while traces for old blocks are rarely requested.
+//! [`CallTracesOffloader`] moves call traces for L1 batches older than the configured age to the object store
+//! (one object per batch), and [`CallTracesReader`] provides access to call traces regardless of where they are stored.
+
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+use zksync_dal::{Connection, ConnectionPool, Core, CoreDal};
+use zksync_health_check::{Health, HealthStatus, HealthUpdater, ReactiveHealthCheck};
+use zksync_object_store::{
+    serialize_using_bincode, Bucket, ObjectStore, ObjectStoreError, StoredObject,
+};
+use zksync_types::{vm_trace::Call, L1BatchNumber, MiniblockNumber, H256};
+use zksync_utils::time::seconds_since_epoch;
+
+use self::metrics::METRICS;
+
+mod metrics;
+#[cfg(test)]
+mod tests;
+
+/// Call traces for all transactions in an L1 batch, as persisted in the object store.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct OffloadedCallTraces {
+    /// Traces in the execution order, accompanied by the miniblock number and the transaction hash.
+    traces: Vec<(MiniblockNumber, H256, Call)>,
+}
+
+impl StoredObject for OffloadedCallTraces {
+    const BUCKET: Bucket = Bucket::CallTraces;
+    type Key<'a> = L1BatchNumber;
+
+    fn encode_key(key: Self::Key<'_>) -> String {
+        format!("call_traces_l1_batch_{key}.bin")
+    }
+
+    serialize_using_bincode!();
+}
+
+/// Component offloading call traces for old L1 batches to the object store.
+///
+/// Batches are offloaded sequentially, starting from the earliest batch in the DB. A batch is offloaded once it has been
+/// sealed for at least the configured age. Call traces are uploaded to the object store first; only after that,
+/// the batch is marked as offloaded and its traces are removed from Postgres (atomically), so that traces
+/// are always available from at least one location.
+#[derive(Debug)]
+pub struct CallTracesOffloader {
+    pool: ConnectionPool<Core>,
+    object_store: Arc<dyn ObjectStore>,
+    offload_after: Duration,
+    poll_interval: Duration,
+    health_updater: HealthUpdater,
+}
+
+impl CallTracesOffloader {
+    pub fn new(
+        pool: ConnectionPool<Core>,
+        object_store: Arc<dyn ObjectStore>,
+        offload_after: Duration,
+        poll_interval: Duration,
+    ) -> Self {
+        Self {
+            pool,
+            object_store,
+            offload_after,
+            poll_interval,
+            health_updater: ReactiveHealthCheck::new("call_traces_offloader").1,
+        }
+    }
+
+    pub fn health_check(&self) -> ReactiveHealthCheck {
+        self.health_updater.subscribe()
+    }
+
+    /// Offloads call traces for the next L1 batch if there is one. Returns the number of the offloaded batch.
+    async fn offload_next_l1_batch(&self) -> anyhow::Result<Option<L1BatchNumber>> {
+        let mut storage = self.pool.connection_tagged("call_traces_offloader").await?;
+        let last_offloaded_l1_batch = storage
+            .call_traces_dal()
+            .get_last_offloaded_l1_batch()
+            .await?;
+        let max_timestamp = seconds_since_epoch().saturating_sub(self.offload_after.as_secs());
+        let Some(l1_batch_number) = storage
+            .call_traces_dal()
+            .get_l1_batch_to_offload(last_offloaded_l1_batch, max_timestamp)
+            .await?
+        else {
+            return Ok(None);
+        };
+        tracing::info!("Offloading call traces for L1 batch #{l1_batch_number}");
+
+        let latency = METRICS.offload_latency.start();
+        let traces = storage
+            .call_traces_dal()
+            .get_call_traces_for_l1_batch(l1_batch_number)
+            .await?;
+        drop(storage);
+
+        let trace_count = traces.len();
+        let key = self
+            .object_store
+            .put(l1_batch_number, &OffloadedCallTraces { traces })
+            .await
+            .with_context(|| {
+                format!("failed uploading call traces for L1 batch #{l1_batch_number}")
+            })?;
+        tracing::debug!("Uploaded `{key}` with {trace_count} call traces");
+
+        self.pool
+            .connection_tagged("call_traces_offloader")
+            .await?
+            .call_traces_dal()
+            .mark_l1_batch_offloaded(l1_batch_number)
+            .await?;
+        let latency = latency.observe();
+        tracing::info!(
+            "Offloaded {trace_count} call traces for L1 batch #{l1_batch_number} in {latency:?}"
+        );
+        METRICS.offloaded_traces.inc_by(trace_count as u64);
+        METRICS
+            .last_offloaded_l1_batch
+            .set(l1_batch_number.0.into());
+
+        let health_details = serde_json::json!({
+            "last_offloaded_l1_batch": l1_batch_number,
+        });
+        self.health_updater
+            .update(Health::from(HealthStatus::Ready).with_details(health_details));
+        Ok(Some(l1_batch_number))
+    }
+
+    pub async fn run(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        self.health_updater.update(HealthStatus::Ready.into());
+        while !*stop_receiver.borrow_and_update() {
+            if self.offload_next_l1_batch().await?.is_some() {
+                continue;
+            }
+            // Error here corresponds to a timeout w/o `stop_receiver` changed; we're OK with this.
+            tokio::time::timeout(self.poll_interval, stop_receiver.changed())
+                .await
+                .ok();
+        }
+        tracing::info!("Stop signal received, call traces offloader is shutting down");
+        Ok(())
+    }
+}
+
+/// Provides access to call traces stored either in Postgres or in the object store.
+///
+/// Postgres is always queried first; the object store is only queried if traces for the relevant L1 batch
+/// are marked as offloaded. Since marking a batch as offloaded and removing its traces from Postgres is atomic,
+/// this order guarantees that traces being offloaded concurrently are not missed.
+#[derive(Debug, Clone, Default)]
+pub struct CallTracesReader {
+    object_store: Option<Arc<dyn ObjectStore>>,
+}
+
+impl CallTracesReader {
+    /// Creates a reader. If `object_store` is not specified, only traces persisted in Postgres are available.
+    pub fn new(object_store: Option<Arc<dyn ObjectStore>>) -> Self {
+        Self { object_store }
+    }
+
+    /// Returns the call trace for the specified transaction.
+    pub async fn get_call_trace(
+        &self,
+        storage: &mut Connection<'_, Core>,
+        tx_hash: H256,
+    ) -> anyhow::Result<Option<Call>> {
+        if let Some(call_trace) = storage.transactions_dal().get_call_trace(tx_hash).await? {
+            return Ok(Some(call_trace));
+        }
+        let Some(l1_batch_number) = storage
+            .call_traces_dal()
+            .get_offloaded_l1_batch_for_tx(tx_hash)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let Some(offloaded) = self.load(l1_batch_number).await? else {
+            return Ok(None);
+        };
+        Ok(offloaded
+            .traces
+            .into_iter()
+            .find_map(|(_, hash, call)| (hash == tx_hash).then_some(call)))
+    }
+
+    /// Returns call traces for all transactions in the specified miniblock in the order of their execution.
+    pub async fn get_traces_for_miniblock(
+        &self,
+        storage: &mut Connection<'_, Core>,
+        miniblock_number: MiniblockNumber,
+    ) -> anyhow::Result<Vec<Call>> {
+        let traces = storage
+            .blocks_web3_dal()
+            .get_traces_for_miniblock(miniblock_number)
+            .await?;
+        if !traces.is_empty() {
+            return Ok(traces);
+        }
+        let Some(l1_batch_number) = storage
+            .call_traces_dal()
+            .get_offloaded_l1_batch_for_miniblock(miniblock_number)
+            .await?
+        else {
+            return Ok(traces);
+        };
+        let Some(offloaded) = self.load(l1_batch_number).await? else {
+            return Ok(vec![]);
+        };
+        Ok(offloaded
+            .traces
+            .into_iter()
+            .filter_map(|(number, _, call)| (number == miniblock_number).then_some(call))
+            .collect())
+    }
+
+    async fn load(
+        &self,
+        l1_batch_number: L1BatchNumber,
+    ) -> anyhow::Result<Option<OffloadedCallTraces>> {
+        let Some(object_store) = &self.object_store else {
+            tracing::debug!(
+                "Call traces for L1 batch #{l1_batch_number} are offloaded, but no object store is configured"
+            );
+            return Ok(None);
+        };
+        match object_store.get(l1_batch_number).await {
+            Ok(offloaded) => {
+                METRICS.offloaded_reads.inc();
+                Ok(Some(offloaded))
+            }
+            Err(ObjectStoreError::KeyNotFound(_)) => Ok(None),
+            Err(err) => Err(anyhow::Error::from(err).context(format!(
+                "failed loading offloaded call traces for L1 batch #{l1_batch_number}"
+            ))),
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! Tests for the call traces offloader.
+
+use zksync_object_store::ObjectStoreFactory;
+use zksync_types::{fee::TransactionExecutionMetrics, Address};
+
+use super::*;
+use crate::{
+    genesis::{insert_genesis_batch, GenesisParams},
+    utils::testonly::{
+        create_l1_batch, create_l2_transaction, create_miniblock, execute_l2_transaction,
+    },
+};
+
+fn mock_call(gas: u64) -> Call {
+    Call {
+        from: Address::repeat_byte(1),
+        gas,
+        ..Call::default()
+    }
+}
+
+/// Inserts miniblock #1 and L1 batch #1 with a single transaction having a call trace.
+async fn insert_l1_batch_with_call_trace(storage: &mut Connection<'_, Core>) -> (H256, Call) {
+    let tx = create_l2_transaction(10, 100);
+    storage
+        .transactions_dal()
+        .insert_transaction_l2(&tx, TransactionExecutionMetrics::default())
+        .await
+        .unwrap();
+    let mut tx_result = execute_l2_transaction(tx);
+    let call = mock_call(100);
+    tx_result.call_traces.push(call.clone());
+    let tx_hash = tx_result.hash;
+    let tx_results = [tx_result];
+
+    storage
+        .blocks_dal()
+        .insert_miniblock(&create_miniblock(1))
+        .await
+        .unwrap();
+    storage
+        .transactions_dal()
+        .mark_txs_as_executed_in_miniblock(MiniblockNumber(1), &tx_results, 1.into())
+        .await
+        .unwrap();
+    storage
+        .blocks_dal()
+        .insert_mock_l1_batch(&create_l1_batch(1))
+        .await
+        .unwrap();
+    storage
+        .blocks_dal()
+        .mark_miniblocks_as_executed_in_l1_batch(L1BatchNumber(1))
+        .await
+        .unwrap();
+    storage
+        .transactions_dal()
+        .mark_txs_as_executed_in_l1_batch(L1BatchNumber(1), &tx_results)
+        .await
+        .unwrap();
+    (tx_hash, call)
+}
+
+#[tokio::test]
+async fn offloading_call_traces() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let (tx_hash, call) = insert_l1_batch_with_call_trace(&mut storage).await;
+
+    let object_store = ObjectStoreFactory::mock().create_store().await;
+    let offloader = CallTracesOffloader::new(
+        pool.clone(),
+        object_store.clone(),
+        Duration::ZERO,
+        Duration::from_millis(10),
+    );
+    let reader = CallTracesReader::new(Some(object_store.clone()));
+    let trace = reader.get_call_trace(&mut storage, tx_hash).await.unwrap();
+    assert_eq!(trace, Some(call.clone()));
+
+    let offloaded = offloader.offload_next_l1_batch().await.unwrap();
+    assert_eq!(offloaded, Some(L1BatchNumber(0)));
+    let offloaded = offloader.offload_next_l1_batch().await.unwrap();
+    assert_eq!(offloaded, Some(L1BatchNumber(1)));
+    assert_eq!(offloader.offload_next_l1_batch().await.unwrap(), None);
+
+    let db_trace = storage
+        .transactions_dal()
+        .get_call_trace(tx_hash)
+        .await
+        .unwrap();
+    assert_eq!(db_trace, None);
+    let stored: OffloadedCallTraces = object_store.get(L1BatchNumber(1)).await.unwrap();
+    assert_eq!(stored.traces, [(MiniblockNumber(1), tx_hash, call.clone())]);
+
+    let trace = reader.get_call_trace(&mut storage, tx_hash).await.unwrap();
+    assert_eq!(trace, Some(call.clone()));
+    let traces = reader
+        .get_traces_for_miniblock(&mut storage, MiniblockNumber(1))
+        .await
+        .unwrap();
+    assert_eq!(traces, [call]);
+
+    // Without an object store, offloaded traces are unavailable.
+    let reader = CallTracesReader::default();
+    let trace = reader.get_call_trace(&mut storage, tx_hash).await.unwrap();
+    assert_eq!(trace, None);
+}
+
+#[tokio::test]
+async fn recent_l1_batches_are_not_offloaded() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    let (tx_hash, call) = insert_l1_batch_with_call_trace(&mut storage).await;
+
+    let object_store = ObjectStoreFactory::mock().create_store().await;
+    // Mock L1 batches have timestamps close to the Unix epoch, so the offloading age must be quite large.
+    let offload_after = Duration::from_secs(seconds_since_epoch() + 3_600);
+    let offloader = CallTracesOffloader::new(
+        pool.clone(),
+        object_store,
+        offload_after,
+        Duration::from_millis(10),
+    );
+    assert_eq!(offloader.offload_next_l1_batch().await.unwrap(), None);
+
+    let db_trace = storage
+        .transactions_dal()
+        .get_call_trace(tx_hash)
+        .await
+        .unwrap();
+    assert_eq!(db_trace, Some(call));
+}
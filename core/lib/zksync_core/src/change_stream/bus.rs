@@ -0,0 +1,89 @@
+//! Message bus abstraction and its NATS implementation.
+
+use std::fmt;
+
+use anyhow::Context as _;
+use async_nats::{header::NATS_MESSAGE_ID, jetstream, ConnectOptions, HeaderMap};
+use async_trait::async_trait;
+use zksync_config::configs::ChangeStreamConfig;
+
+/// External message bus (e.g., NATS or Kafka) that change stream messages are published to.
+#[async_trait]
+pub trait MessageBus: 'static + Send + Sync + fmt::Debug {
+    /// Publishes a message to the specified subject (aka topic). `key` uniquely identifies the message;
+    /// it can be used by the bus to deduplicate messages published multiple times (e.g., after a retry)
+    /// or to partition messages.
+    ///
+    /// The message should be considered published once this method returns successfully.
+    async fn publish(&self, subject: &str, key: &str, payload: &[u8]) -> anyhow::Result<()>;
+}
+
+/// [`MessageBus`] implementation publishing messages to a NATS JetStream stream.
+///
+/// A message is considered published once it's acknowledged by JetStream, which guarantees at-least-once delivery.
+/// Message keys are sent in the `Nats-Msg-Id` header, so that JetStream deduplicates messages published
+/// multiple times. The connection is re-established by the client in the background after a failure.
+pub struct NatsMessageBus {
+    jetstream: jetstream::Context,
+}
+
+impl fmt::Debug for NatsMessageBus {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("NatsMessageBus")
+            .finish_non_exhaustive()
+    }
+}
+
+impl NatsMessageBus {
+    /// Creates a bus using the NATS server URL, credentials and TLS options from the provided config.
+    /// If the server is unavailable, the connection is retried in the background, so that the node can start.
+    pub async fn new(config: &ChangeStreamConfig) -> anyhow::Result<Self> {
+        let mut options = if let Some(path) = &config.nats_credentials_path {
+            ConnectOptions::with_credentials_file(path)
+                .await
+                .with_context(|| format!("failed loading NATS credentials from `{path}`"))?
+        } else {
+            ConnectOptions::new()
+        };
+        options = options
+            .name("zksync_change_stream")
+            .require_tls(config.nats_tls_required)
+            .retry_on_initial_connect();
+        if let Some(path) = &config.nats_root_certificates_path {
+            options = options.add_root_certificates(path.into());
+        }
+
+        let client = options
+            .connect(config.nats_url.as_str())
+            .await
+            .with_context(|| format!("failed connecting to NATS at {}", config.nats_url))?;
+        Ok(Self {
+            jetstream: jetstream::new(client),
+        })
+    }
+}
+
+#[async_trait]
+impl MessageBus for NatsMessageBus {
+    async fn publish(&self, subject: &str, key: &str, payload: &[u8]) -> anyhow::Result<()> {
+        let mut headers = HeaderMap::new();
+        headers.insert(NATS_MESSAGE_ID, key);
+        let ack = self
+            .jetstream
+            .publish_with_headers(subject.to_owned(), headers, payload.to_vec().into())
+            .await
+            .with_context(|| format!("failed publishing message to NATS subject `{subject}`"))?
+            .await
+            .with_context(|| {
+                format!("message to NATS subject `{subject}` was not acknowledged by JetStream")
+            })?;
+        if ack.duplicate {
+            tracing::debug!(
+                "Message `{key}` was already published to JetStream stream `{}`",
+                ack.stream
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,33 @@
+//! Metrics for the change stream.
+
+use std::time::Duration;
+
+use vise::{
+    Buckets, Counter, EncodeLabelSet, EncodeLabelValue, Family, Gauge, Histogram, Metrics, Unit,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "kind", rename_all = "snake_case")]
+pub(super) enum MessageKind {
+    Miniblock,
+    L1Batch,
+}
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "server_change_stream")]
+pub(super) struct ChangeStreamMetrics {
+    /// Number of messages published to the message bus.
+    pub published_messages: Family<MessageKind, Counter>,
+    /// Latency of publishing a single message, including retries.
+    #[metrics(buckets = Buckets::LATENCIES, unit = Unit::Seconds)]
+    pub publish_latency: Histogram<Duration>,
+    /// Number of failed attempts to publish a message.
+    pub publish_errors: Counter,
+    /// Number of messages queued for publishing.
+    pub queue_len: Gauge<usize>,
+    /// Number of the last miniblock published to the message bus.
+    pub last_published_miniblock: Gauge<u64>,
+}
+
+#[vise::register]
+pub(super) static METRICS: vise::Global<ChangeStreamMetrics> = vise::Global::new();
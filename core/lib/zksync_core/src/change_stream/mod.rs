@@ -0,0 +1,327 @@
+//! Change stream publishing sealed miniblocks and L1 batches to an external message bus.
+//!
+//! Indexers and other downstream consumers can subscribe to the stream instead of polling the JSON-RPC API.
+//! [`ChangeStreamHandler`] is plugged into the state keeper as an output handler; it converts sealed miniblocks
+//! (including transaction receipts and events) and L1 batches into messages and queues them. Queued messages
+//! are published by [`ChangeStreamPublisherTask`] in the order they were produced, so that publishing
+//! doesn't block sealing unless the queue is full.
+//!
+//! Messages are published at least once; a message key (e.g., `miniblock_{number}_{hash}`) allows the bus
+//! to deduplicate retried messages. Messages for miniblocks not persisted before a node crash may be published;
+//! after a restart, the state keeper may produce a miniblock with the same number but different contents.
+//! Thus, consumers should treat the latest message for a certain miniblock number as authoritative.
+
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Context as _;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, watch};
+use zksync_types::{
+    tx::tx_execution_info::TxExecutionStatus, Address, Bytes, L1BatchNumber, MiniblockNumber, H256,
+    U256,
+};
+
+pub use self::bus::{MessageBus, NatsMessageBus};
+use self::metrics::{MessageKind, METRICS};
+use crate::state_keeper::{updates::UpdatesManager, StateKeeperOutputHandler};
+
+mod bus;
+mod metrics;
+#[cfg(test)]
+mod tests;
+
+/// Receipt of a transaction included into a [miniblock](MiniblockMessage).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceiptMessage {
+    pub transaction_hash: H256,
+    /// Index of the transaction in the miniblock.
+    pub transaction_index: u32,
+    pub from: Address,
+    pub to: Address,
+    pub is_l1_originated: bool,
+    pub success: bool,
+    pub revert_reason: Option<String>,
+    pub gas_limit: U256,
+    pub gas_refunded: u64,
+}
+
+/// Event emitted in a [miniblock](MiniblockMessage).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventMessage {
+    /// Index of the emitting transaction in the miniblock. `None` for events emitted by the bootloader
+    /// after all transactions in the L1 batch are executed (such events belong to the last miniblock of the batch).
+    pub transaction_index: Option<u32>,
+    /// Index of the event in the miniblock.
+    pub log_index: u32,
+    pub address: Address,
+    pub topics: Vec<H256>,
+    pub data: Bytes,
+}
+
+/// Message published for each sealed miniblock.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MiniblockMessage {
+    pub number: MiniblockNumber,
+    pub l1_batch_number: L1BatchNumber,
+    pub hash: H256,
+    pub parent_hash: H256,
+    pub timestamp: u64,
+    pub protocol_version: u16,
+    pub receipts: Vec<ReceiptMessage>,
+    pub events: Vec<EventMessage>,
+}
+
+impl MiniblockMessage {
+    fn new(updates_manager: &UpdatesManager) -> Self {
+        let miniblock = &updates_manager.miniblock;
+        // Transactions in the current miniblock are not yet added to the L1 batch updates.
+        let first_tx_index = updates_manager.l1_batch.executed_transactions.len() as u32;
+
+        let receipts = miniblock
+            .executed_transactions
+            .iter()
+            .enumerate()
+            .map(|(index, tx_result)| ReceiptMessage {
+                transaction_hash: tx_result.hash,
+                transaction_index: index as u32,
+                from: tx_result.transaction.initiator_account(),
+                to: tx_result.transaction.recipient_account(),
+                is_l1_originated: tx_result.transaction.is_l1(),
+                success: matches!(tx_result.execution_status, TxExecutionStatus::Success),
+                revert_reason: tx_result.revert_reason.clone(),
+                gas_limit: tx_result.transaction.gas_limit(),
+                gas_refunded: tx_result.refunded_gas,
+            })
+            .collect();
+
+        let tx_count = miniblock.executed_transactions.len() as u32;
+        let events = miniblock
+            .events
+            .iter()
+            .enumerate()
+            .map(|(log_index, event)| {
+                let tx_index = event.location.1.wrapping_sub(first_tx_index);
+                EventMessage {
+                    transaction_index: (tx_index < tx_count).then_some(tx_index),
+                    log_index: log_index as u32,
+                    address: event.address,
+                    topics: event.indexed_topics.clone(),
+                    data: Bytes(event.value.clone()),
+                }
+            })
+            .collect();
+
+        Self {
+            number: miniblock.number,
+            l1_batch_number: updates_manager.l1_batch.number,
+            hash: miniblock.get_miniblock_hash(),
+            parent_hash: miniblock.prev_block_hash,
+            timestamp: miniblock.timestamp,
+            protocol_version: miniblock.protocol_version as u16,
+            receipts,
+            events,
+        }
+    }
+}
+
+/// Message published for each sealed L1 batch. Published after the message for the last miniblock in the batch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct L1BatchMessage {
+    pub number: L1BatchNumber,
+    pub timestamp: u64,
+    pub last_miniblock_number: MiniblockNumber,
+    pub transaction_count: u32,
+}
+
+#[derive(Debug)]
+enum ChangeStreamMessage {
+    Miniblock(MiniblockMessage),
+    L1Batch(L1BatchMessage),
+}
+
+impl ChangeStreamMessage {
+    fn kind(&self) -> MessageKind {
+        match self {
+            Self::Miniblock(_) => MessageKind::Miniblock,
+            Self::L1Batch(_) => MessageKind::L1Batch,
+        }
+    }
+
+    fn subject(&self, prefix: &str) -> String {
+        match self {
+            Self::Miniblock(_) => format!("{prefix}.miniblocks"),
+            Self::L1Batch(_) => format!("{prefix}.l1_batches"),
+        }
+    }
+
+    fn key(&self) -> String {
+        match self {
+            Self::Miniblock(message) => {
+                format!("miniblock_{}_{:?}", message.number, message.hash)
+            }
+            Self::L1Batch(message) => format!("l1_batch_{}", message.number),
+        }
+    }
+
+    fn payload(&self) -> Vec<u8> {
+        let result = match self {
+            Self::Miniblock(message) => serde_json::to_vec(message),
+            Self::L1Batch(message) => serde_json::to_vec(message),
+        };
+        result.expect("failed serializing change stream message")
+    }
+}
+
+/// State keeper output handler queueing sealed miniblocks and L1 batches for publishing.
+#[derive(Debug)]
+pub struct ChangeStreamHandler {
+    sender: mpsc::Sender<ChangeStreamMessage>,
+}
+
+impl ChangeStreamHandler {
+    /// Creates a handler together with the task publishing queued messages to `bus`. The task should be run
+    /// on a separate Tokio task. At most `queue_capacity` messages are queued; if the queue is full, the handler
+    /// waits until queued messages are published (i.e., there is back pressure).
+    pub fn new(
+        bus: Arc<dyn MessageBus>,
+        subject_prefix: String,
+        queue_capacity: usize,
+    ) -> (Self, ChangeStreamPublisherTask) {
+        let (sender, receiver) = mpsc::channel(queue_capacity.max(1));
+        let task = ChangeStreamPublisherTask {
+            bus,
+            subject_prefix,
+            sender: sender.downgrade(),
+            receiver,
+            retry_interval: ChangeStreamPublisherTask::DEFAULT_RETRY_INTERVAL,
+        };
+        (Self { sender }, task)
+    }
+
+    async fn enqueue(&self, message: ChangeStreamMessage) -> anyhow::Result<()> {
+        self.sender
+            .send(message)
+            .await
+            .ok()
+            .context("change stream publisher unexpectedly shut down")?;
+        report_queue_len(&self.sender);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl StateKeeperOutputHandler for ChangeStreamHandler {
+    async fn handle_miniblock(&mut self, updates_manager: &UpdatesManager) -> anyhow::Result<()> {
+        let message = MiniblockMessage::new(updates_manager);
+        self.enqueue(ChangeStreamMessage::Miniblock(message)).await
+    }
+
+    async fn handle_l1_batch(&mut self, updates_manager: &UpdatesManager) -> anyhow::Result<()> {
+        // The last (fictive) miniblock in the batch is sealed together with the batch.
+        let miniblock_message = MiniblockMessage::new(updates_manager);
+        let batch_message = L1BatchMessage {
+            number: updates_manager.l1_batch.number,
+            timestamp: updates_manager.batch_timestamp(),
+            last_miniblock_number: miniblock_message.number,
+            transaction_count: updates_manager.l1_batch.executed_transactions.len() as u32,
+        };
+        self.enqueue(ChangeStreamMessage::Miniblock(miniblock_message))
+            .await?;
+        self.enqueue(ChangeStreamMessage::L1Batch(batch_message))
+            .await
+    }
+}
+
+fn report_queue_len(sender: &mpsc::Sender<ChangeStreamMessage>) {
+    METRICS
+        .queue_len
+        .set(sender.max_capacity() - sender.capacity());
+}
+
+/// Task publishing messages queued by [`ChangeStreamHandler`] to the message bus.
+#[derive(Debug)]
+pub struct ChangeStreamPublisherTask {
+    bus: Arc<dyn MessageBus>,
+    subject_prefix: String,
+    // Weak sender handle to get queue length stats.
+    sender: mpsc::WeakSender<ChangeStreamMessage>,
+    receiver: mpsc::Receiver<ChangeStreamMessage>,
+    retry_interval: Duration,
+}
+
+impl ChangeStreamPublisherTask {
+    const DEFAULT_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+    #[cfg(test)]
+    fn with_retry_interval(mut self, retry_interval: Duration) -> Self {
+        self.retry_interval = retry_interval;
+        self
+    }
+
+    /// Publishes messages sequentially; a message is retried until it's published or the task is stopped.
+    pub async fn run(mut self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        tracing::info!(
+            "Starting change stream publisher with subject prefix `{}`",
+            self.subject_prefix
+        );
+        loop {
+            let message = tokio::select! {
+                message = self.receiver.recv() => message,
+                _ = stop_receiver.changed() => break,
+            };
+            let Some(message) = message else {
+                tracing::info!("Change stream handler is dropped; stopping publisher");
+                return Ok(());
+            };
+            if let Some(sender) = self.sender.upgrade() {
+                report_queue_len(&sender);
+            }
+            if !self.publish(&message, &mut stop_receiver).await {
+                break;
+            }
+        }
+
+        tracing::info!("Stop signal received, change stream publisher is shutting down");
+        Ok(())
+    }
+
+    /// Returns `false` if the task was stopped before the message is published.
+    async fn publish(
+        &self,
+        message: &ChangeStreamMessage,
+        stop_receiver: &mut watch::Receiver<bool>,
+    ) -> bool {
+        let subject = message.subject(&self.subject_prefix);
+        let key = message.key();
+        let payload = message.payload();
+
+        let latency = METRICS.publish_latency.start();
+        while let Err(err) = self.bus.publish(&subject, &key, &payload).await {
+            METRICS.publish_errors.inc();
+            tracing::warn!(
+                "Failed publishing message `{key}` to `{subject}`, retrying in {:?}: {err:#}",
+                self.retry_interval
+            );
+            if tokio::time::timeout(self.retry_interval, stop_receiver.changed())
+                .await
+                .is_ok()
+            {
+                return false;
+            }
+        }
+        latency.observe();
+        METRICS.published_messages[&message.kind()].inc();
+        if let ChangeStreamMessage::Miniblock(message) = message {
+            METRICS
+                .last_published_miniblock
+                .set(message.number.0.into());
+        }
+        tracing::debug!("Published message `{key}` to `{subject}`");
+        true
+    }
+}
@@ -0,0 +1,218 @@
+//! Tests for the change stream.
+
+use std::sync::Mutex;
+
+use zksync_config::configs::ChangeStreamConfig;
+use zksync_types::{block::BlockGasCount, tx::ExecutionMetrics, VmEvent};
+
+use super::*;
+use crate::state_keeper::{
+    io::MiniblockParams,
+    tests::{create_execution_result, create_transaction, create_updates_manager},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+struct PublishedMessage {
+    subject: String,
+    key: String,
+    payload: Vec<u8>,
+}
+
+/// Message bus recording published messages and failing the specified number of first publishing attempts.
+#[derive(Debug, Default)]
+struct MockMessageBus {
+    messages: Mutex<Vec<PublishedMessage>>,
+    remaining_failures: Mutex<usize>,
+}
+
+impl MockMessageBus {
+    fn failing(failures: usize) -> Self {
+        Self {
+            remaining_failures: Mutex::new(failures),
+            ..Self::default()
+        }
+    }
+
+    fn messages(&self) -> Vec<PublishedMessage> {
+        self.messages.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl MessageBus for MockMessageBus {
+    async fn publish(&self, subject: &str, key: &str, payload: &[u8]) -> anyhow::Result<()> {
+        let mut remaining_failures = self.remaining_failures.lock().unwrap();
+        if *remaining_failures > 0 {
+            *remaining_failures -= 1;
+            anyhow::bail!("bus is unavailable");
+        }
+        self.messages.lock().unwrap().push(PublishedMessage {
+            subject: subject.to_owned(),
+            key: key.to_owned(),
+            payload: payload.to_vec(),
+        });
+        Ok(())
+    }
+}
+
+fn create_event(l1_batch_number: L1BatchNumber, tx_index_in_l1_batch: u32) -> VmEvent {
+    VmEvent {
+        location: (l1_batch_number, tx_index_in_l1_batch),
+        address: Address::repeat_byte(0x11),
+        indexed_topics: vec![H256::repeat_byte(0x22)],
+        value: vec![tx_index_in_l1_batch as u8],
+    }
+}
+
+fn execute_tx(updates_manager: &mut UpdatesManager, tx_index_in_l1_batch: u32) {
+    let l1_batch_number = updates_manager.l1_batch.number;
+    let mut execution_result = create_execution_result(tx_index_in_l1_batch as u16, []);
+    execution_result
+        .logs
+        .events
+        .push(create_event(l1_batch_number, tx_index_in_l1_batch));
+    updates_manager.extend_from_executed_transaction(
+        create_transaction(10, 100),
+        execution_result,
+        vec![],
+        BlockGasCount::default(),
+        ExecutionMetrics::default(),
+        vec![],
+    );
+}
+
+#[test]
+fn creating_miniblock_message() {
+    let mut updates_manager = create_updates_manager();
+    execute_tx(&mut updates_manager, 0);
+    updates_manager.push_miniblock(MiniblockParams {
+        timestamp: 2,
+        virtual_blocks: 1,
+    });
+    execute_tx(&mut updates_manager, 1);
+    execute_tx(&mut updates_manager, 2);
+
+    let message = MiniblockMessage::new(&updates_manager);
+    assert_eq!(message.number, updates_manager.miniblock.number);
+    assert_eq!(message.l1_batch_number, updates_manager.l1_batch.number);
+    assert_eq!(message.hash, updates_manager.miniblock.get_miniblock_hash());
+    assert_eq!(message.timestamp, 2);
+
+    let tx_hashes: Vec<_> = updates_manager
+        .miniblock
+        .executed_transactions
+        .iter()
+        .map(|tx_result| tx_result.hash)
+        .collect();
+    let receipt_hashes: Vec<_> = message
+        .receipts
+        .iter()
+        .map(|receipt| receipt.transaction_hash)
+        .collect();
+    assert_eq!(receipt_hashes, tx_hashes);
+    assert!(message.receipts.iter().all(|receipt| receipt.success));
+
+    // Events must be attributed to transactions in the miniblock, rather than in the L1 batch.
+    assert_eq!(message.events.len(), 2);
+    for (i, event) in message.events.iter().enumerate() {
+        assert_eq!(event.transaction_index, Some(i as u32));
+        assert_eq!(event.log_index, i as u32);
+        assert_eq!(event.data.0, [i as u8 + 1]);
+    }
+}
+
+#[test]
+fn bootloader_events_are_not_attributed_to_transactions() {
+    let mut updates_manager = create_updates_manager();
+    execute_tx(&mut updates_manager, 0);
+    updates_manager.push_miniblock(MiniblockParams {
+        timestamp: 2,
+        virtual_blocks: 1,
+    });
+    // Emulate the fictive miniblock with an event emitted by the bootloader.
+    let l1_batch_number = updates_manager.l1_batch.number;
+    updates_manager
+        .miniblock
+        .events
+        .push(create_event(l1_batch_number, 1));
+
+    let message = MiniblockMessage::new(&updates_manager);
+    assert!(message.receipts.is_empty());
+    assert_eq!(message.events.len(), 1);
+    assert_eq!(message.events[0].transaction_index, None);
+}
+
+#[tokio::test]
+async fn publishing_messages_with_retries() {
+    let bus = Arc::new(MockMessageBus::failing(2));
+    let (mut handler, task) = ChangeStreamHandler::new(bus.clone(), "test".to_owned(), 10);
+    let task = task.with_retry_interval(Duration::from_millis(10));
+    let (_stop_sender, stop_receiver) = watch::channel(false);
+
+    let mut updates_manager = create_updates_manager();
+    execute_tx(&mut updates_manager, 0);
+    handler.handle_miniblock(&updates_manager).await.unwrap();
+    let miniblock_hash = updates_manager.miniblock.get_miniblock_hash();
+    let miniblock_number = updates_manager.miniblock.number;
+    updates_manager.push_miniblock(MiniblockParams {
+        timestamp: 2,
+        virtual_blocks: 1,
+    });
+    handler.handle_l1_batch(&updates_manager).await.unwrap();
+    drop(handler);
+
+    // The task should terminate once the handler is dropped and all messages are published.
+    task.run(stop_receiver).await.unwrap();
+
+    let messages = bus.messages();
+    let subjects: Vec<_> = messages.iter().map(|msg| msg.subject.as_str()).collect();
+    assert_eq!(
+        subjects,
+        ["test.miniblocks", "test.miniblocks", "test.l1_batches"]
+    );
+    assert_eq!(
+        messages[0].key,
+        format!("miniblock_{miniblock_number}_{miniblock_hash:?}")
+    );
+
+    let miniblock: MiniblockMessage = serde_json::from_slice(&messages[0].payload).unwrap();
+    assert_eq!(miniblock.number, miniblock_number);
+    assert_eq!(miniblock.receipts.len(), 1);
+    let fictive_miniblock: MiniblockMessage = serde_json::from_slice(&messages[1].payload).unwrap();
+    assert_eq!(fictive_miniblock.number, miniblock_number + 1);
+    assert!(fictive_miniblock.receipts.is_empty());
+    let l1_batch: L1BatchMessage = serde_json::from_slice(&messages[2].payload).unwrap();
+    assert_eq!(l1_batch.number, updates_manager.l1_batch.number);
+    assert_eq!(l1_batch.last_miniblock_number, miniblock_number + 1);
+    assert_eq!(l1_batch.transaction_count, 1);
+}
+
+#[tokio::test]
+async fn publisher_can_be_stopped_while_retrying() {
+    let bus = Arc::new(MockMessageBus::failing(usize::MAX));
+    let (mut handler, task) = ChangeStreamHandler::new(bus.clone(), "test".to_owned(), 10);
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let task = tokio::spawn(task.run(stop_receiver));
+
+    let updates_manager = create_updates_manager();
+    handler.handle_miniblock(&updates_manager).await.unwrap();
+    stop_sender.send_replace(true);
+    task.await.unwrap().unwrap();
+    assert!(bus.messages().is_empty());
+}
+
+/// Accepts a single connection emulating a NATS server, and returns all messages published via it.
+#[tokio::test]
+async fn creating_nats_bus_with_missing_credentials() {
+    let config = ChangeStreamConfig {
+        nats_url: "nats://127.0.0.1:4222".to_owned(),
+        nats_credentials_path: Some("/non/existing/zksync.creds".to_owned()),
+        nats_tls_required: false,
+        nats_root_certificates_path: None,
+        subject_prefix: "zksync".to_owned(),
+        queue_capacity: 16,
+    };
+    let err = NatsMessageBus::new(&config).await.unwrap_err();
+    let err = format!("{err:#}");
+    assert!(err.contains("failed loading NATS credentials"), "{err}");
+}
@@ -1,6 +1,7 @@
-use std::time::Duration;
+use std::{slice, time::Duration};
 
 use anyhow::Context;
+use futures::TryStreamExt;
 use itertools::Itertools;
 use metrics::{CommitmentStage, METRICS};
 use multivm::zk_evm_latest::ethereum_types::U256;
@@ -50,14 +51,19 @@ impl CommitmentGenerator {
             .connection_tagged("commitment_generator")
             .await?;
 
-        // Calculate events queue using VM events.
+        // Calculate events queue using VM events. Events are streamed, so that only the converted log queries
+        // are held in memory.
         let events_queue = {
-            let events = connection
-                .events_dal()
-                .get_vm_events_for_l1_batch(l1_batch_number)
+            let mut events_dal = connection.events_dal();
+            let mut events = events_dal
+                .stream_vm_events_for_l1_batch(l1_batch_number)
                 .await?
                 .with_context(|| format!("Events are missing for L1 batch #{l1_batch_number}"))?;
-            convert_vm_events_to_log_queries(&events)
+            let mut events_queue = vec![];
+            while let Some(event) = events.try_next().await? {
+                events_queue.extend(convert_vm_events_to_log_queries(slice::from_ref(&event)));
+            }
+            events_queue
         };
 
         let initial_bootloader_contents = connection
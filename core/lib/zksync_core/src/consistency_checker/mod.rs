@@ -434,7 +434,7 @@ impl ConsistencyChecker {
     }
 
     /// All returned errors are validation errors.
-    fn extract_commit_data(
+    pub(crate) fn extract_commit_data(
         commit_tx_input_data: &[u8],
         commit_function: &ethabi::Function,
         batch_number: L1BatchNumber,
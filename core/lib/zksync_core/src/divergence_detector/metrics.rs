@@ -0,0 +1,18 @@
+//! Metrics for the divergence detector.
+//!
+//! Re-execution latency and the last checked L1 batch are reported by the shared replay loop
+//! with the `divergence_detector` component label.
+
+use vise::{Counter, Metrics};
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "server_divergence_detector")]
+pub(super) struct DivergenceDetectorMetrics {
+    /// Number of checked L1 batches.
+    pub checked_l1_batches: Counter,
+    /// Number of L1 batches for which re-execution has diverged from the persisted outputs.
+    pub diverged_l1_batches: Counter,
+}
+
+#[vise::register]
+pub(super) static METRICS: vise::Global<DivergenceDetectorMetrics> = vise::Global::new();
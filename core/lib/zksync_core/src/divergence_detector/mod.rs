@@ -0,0 +1,116 @@
+//! Detection of divergences between live and repeated execution of L1 batches.
+//!
+//! [`DivergenceDetector`] periodically re-executes the most recently sealed L1 batch using the VM of the running
+//! server and compares storage writes and events with the ones persisted by the state keeper. A mismatch
+//! indicates non-deterministic execution or corrupted state (e.g., in the RocksDB cache used by the state keeper),
+//! and should be investigated before the batch is committed on L1.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::sync::watch;
+use zksync_dal::{Connection, ConnectionPool, Core, CoreDal};
+use zksync_health_check::{Health, HealthStatus, ReactiveHealthCheck};
+use zksync_types::{L1BatchNumber, L2ChainId};
+
+use self::metrics::METRICS;
+use crate::state_keeper::replay::{
+    BatchReplayer, ReexecutedL1Batch, ReplayLoop, ReplayedBatchProcessor,
+};
+
+mod metrics;
+#[cfg(test)]
+mod tests;
+
+/// Compares outputs of re-executed L1 batches with the persisted ones.
+#[derive(Debug, Default)]
+struct DivergenceChecker {
+    last_diverged_l1_batch: Option<L1BatchNumber>,
+}
+
+#[async_trait]
+impl ReplayedBatchProcessor for DivergenceChecker {
+    const NAME: &'static str = "divergence_detector";
+    const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+    async fn next_l1_batch(
+        &mut self,
+        storage: &mut Connection<'_, Core>,
+        last_processed_l1_batch: Option<L1BatchNumber>,
+    ) -> anyhow::Result<Option<L1BatchNumber>> {
+        let Some(l1_batch_number) = storage.blocks_dal().get_sealed_l1_batch_number().await? else {
+            return Ok(None);
+        };
+        // The genesis batch is not executed by the state keeper, so there's nothing to compare.
+        if l1_batch_number == L1BatchNumber(0) || last_processed_l1_batch >= Some(l1_batch_number) {
+            return Ok(None);
+        }
+        Ok(Some(l1_batch_number))
+    }
+
+    /// Once a divergence is detected, the returned health stays affected until restart.
+    async fn process_l1_batch(
+        &mut self,
+        storage: &mut Connection<'_, Core>,
+        l1_batch_number: L1BatchNumber,
+        executed_batch: ReexecutedL1Batch,
+    ) -> anyhow::Result<Health> {
+        let report = executed_batch
+            .compare_with_persisted(storage, l1_batch_number)
+            .await?;
+        METRICS.checked_l1_batches.inc();
+        if report.is_consistent() {
+            tracing::info!("L1 batch #{l1_batch_number} is consistent");
+        } else {
+            tracing::error!(
+                "Re-execution of L1 batch #{l1_batch_number} diverged from the persisted outputs:\n{report}"
+            );
+            METRICS.diverged_l1_batches.inc();
+            self.last_diverged_l1_batch = Some(l1_batch_number);
+        }
+
+        let status = if self.last_diverged_l1_batch.is_some() {
+            HealthStatus::Affected
+        } else {
+            HealthStatus::Ready
+        };
+        let health_details = serde_json::json!({
+            "last_checked_l1_batch": l1_batch_number,
+            "last_diverged_l1_batch": self.last_diverged_l1_batch,
+        });
+        Ok(Health::from(status).with_details(health_details))
+    }
+}
+
+/// Component re-executing recently sealed L1 batches and comparing the outputs with the persisted ones.
+///
+/// Only the latest sealed L1 batch is checked on each iteration, so if batches are sealed faster than they are
+/// re-executed, some batches are skipped. Divergences are reported via logs, metrics and the component health;
+/// they don't stop the component or the node.
+#[derive(Debug)]
+pub struct DivergenceDetector {
+    inner: ReplayLoop<DivergenceChecker>,
+}
+
+impl DivergenceDetector {
+    /// Creates a new detector. `optional_bytecode_compression` must correspond to the setting of the state keeper
+    /// that has executed the batches.
+    pub fn new(
+        pool: ConnectionPool<Core>,
+        l2_chain_id: L2ChainId,
+        optional_bytecode_compression: bool,
+    ) -> Self {
+        let replayer = BatchReplayer::new(pool.clone(), l2_chain_id, optional_bytecode_compression);
+        Self {
+            inner: ReplayLoop::new(pool, replayer, DivergenceChecker::default()),
+        }
+    }
+
+    pub fn health_check(&self) -> ReactiveHealthCheck {
+        self.inner.health_check()
+    }
+
+    pub async fn run(self, stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        self.inner.run(stop_receiver).await
+    }
+}
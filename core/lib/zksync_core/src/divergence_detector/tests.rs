@@ -0,0 +1,113 @@
+//! Tests for the divergence detector.
+
+use zksync_types::{AccountTreeId, Address, MiniblockNumber, StorageKey, StorageLog, H256};
+
+use super::*;
+use crate::{
+    genesis::{insert_genesis_batch, GenesisParams},
+    state_keeper::tests::default_vm_batch_result,
+    utils::testonly::{create_l1_batch, create_miniblock},
+};
+
+async fn insert_l1_batch(storage: &mut Connection<'_, Core>, number: u32) {
+    storage
+        .blocks_dal()
+        .insert_miniblock(&create_miniblock(number))
+        .await
+        .unwrap();
+    storage
+        .blocks_dal()
+        .insert_mock_l1_batch(&create_l1_batch(number))
+        .await
+        .unwrap();
+    storage
+        .blocks_dal()
+        .mark_miniblocks_as_executed_in_l1_batch(L1BatchNumber(number))
+        .await
+        .unwrap();
+}
+
+fn empty_reexecuted_batch() -> ReexecutedL1Batch {
+    ReexecutedL1Batch {
+        miniblocks: vec![],
+        finished_batch: default_vm_batch_result(),
+    }
+}
+
+#[tokio::test]
+async fn selecting_l1_batches_to_check() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    let mut checker = DivergenceChecker::default();
+    let next_l1_batch = checker.next_l1_batch(&mut storage, None).await.unwrap();
+    assert_eq!(next_l1_batch, None);
+
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    // The genesis batch is never checked.
+    let next_l1_batch = checker.next_l1_batch(&mut storage, None).await.unwrap();
+    assert_eq!(next_l1_batch, None);
+
+    insert_l1_batch(&mut storage, 1).await;
+    let next_l1_batch = checker.next_l1_batch(&mut storage, None).await.unwrap();
+    assert_eq!(next_l1_batch, Some(L1BatchNumber(1)));
+    let next_l1_batch = checker
+        .next_l1_batch(&mut storage, Some(L1BatchNumber(1)))
+        .await
+        .unwrap();
+    assert_eq!(next_l1_batch, None);
+
+    // Only the latest sealed batch is checked.
+    insert_l1_batch(&mut storage, 2).await;
+    insert_l1_batch(&mut storage, 3).await;
+    let next_l1_batch = checker
+        .next_l1_batch(&mut storage, Some(L1BatchNumber(1)))
+        .await
+        .unwrap();
+    assert_eq!(next_l1_batch, Some(L1BatchNumber(3)));
+}
+
+#[tokio::test]
+async fn detecting_divergences() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    insert_genesis_batch(&mut storage, &GenesisParams::mock())
+        .await
+        .unwrap();
+    insert_l1_batch(&mut storage, 1).await;
+
+    let mut checker = DivergenceChecker::default();
+    let health = checker
+        .process_l1_batch(&mut storage, L1BatchNumber(1), empty_reexecuted_batch())
+        .await
+        .unwrap();
+    assert_eq!(health.status(), HealthStatus::Ready);
+    assert_eq!(checker.last_diverged_l1_batch, None);
+
+    // Persist a storage write that is not produced by re-execution.
+    insert_l1_batch(&mut storage, 2).await;
+    let key = StorageKey::new(AccountTreeId::new(Address::repeat_byte(1)), H256::zero());
+    let log = StorageLog::new_write_log(key, H256::repeat_byte(0xff));
+    storage
+        .storage_logs_dal()
+        .insert_storage_logs(MiniblockNumber(2), &[(H256::zero(), vec![log])])
+        .await
+        .unwrap();
+
+    let health = checker
+        .process_l1_batch(&mut storage, L1BatchNumber(2), empty_reexecuted_batch())
+        .await
+        .unwrap();
+    assert_eq!(health.status(), HealthStatus::Affected);
+    assert_eq!(checker.last_diverged_l1_batch, Some(L1BatchNumber(2)));
+
+    // The detector stays affected even if subsequent batches are consistent.
+    insert_l1_batch(&mut storage, 3).await;
+    let health = checker
+        .process_l1_batch(&mut storage, L1BatchNumber(3), empty_reexecuted_batch())
+        .await
+        .unwrap();
+    assert_eq!(health.status(), HealthStatus::Affected);
+    assert_eq!(checker.last_diverged_l1_batch, Some(L1BatchNumber(2)));
+}
@@ -16,16 +16,19 @@ use super::{
     aggregated_operations::AggregatedOperation,
     l1_batch_commit_data_generator::L1BatchCommitDataGenerator,
     publish_criterion::{
-        DataSizeCriterion, GasCriterion, L1BatchPublishCriterion, NumberCriterion,
-        TimestampDeadlineCriterion,
+        BlobBaseFeeCommitLimit, DataSizeCriterion, GasCriterion, L1BatchPublishCriterion,
+        NumberCriterion, TimestampDeadlineCriterion,
     },
 };
+use crate::l1_gas_price::L1TxParamsProvider;
 
 #[derive(Debug)]
 pub struct Aggregator {
     commit_criteria: Vec<Box<dyn L1BatchPublishCriterion>>,
     proof_criteria: Vec<Box<dyn L1BatchPublishCriterion>>,
     execute_criteria: Vec<Box<dyn L1BatchPublishCriterion>>,
+    /// Postpones commit operations during blob base fee spikes. Only set if pubdata is sent via blobs.
+    commit_blob_base_fee_limit: Option<BlobBaseFeeCommitLimit>,
     config: SenderConfig,
     blob_store: Arc<dyn ObjectStore>,
     /// If we are operating in 4844 mode we need to wait for commit transaction
@@ -43,8 +46,17 @@ impl Aggregator {
         blob_store: Arc<dyn ObjectStore>,
         operate_4844_mode: bool,
         l1_batch_commit_data_generator: Arc<dyn L1BatchCommitDataGenerator>,
+        l1_tx_params: Arc<dyn L1TxParamsProvider>,
     ) -> Self {
         let pubdata_da = config.pubdata_sending_mode.into();
+        let commit_blob_base_fee_limit = config
+            .max_commit_blob_base_fee
+            .filter(|_| pubdata_da == PubdataDA::Blobs)
+            .map(|max_blob_base_fee| BlobBaseFeeCommitLimit {
+                max_blob_base_fee,
+                deadline_seconds: config.commit_blob_base_fee_deadline,
+                l1_tx_params,
+            });
 
         Self {
             commit_criteria: vec![
@@ -101,6 +113,7 @@ impl Aggregator {
                     max_allowed_lag: Some(config.timestamp_criteria_max_allowed_lag),
                 }),
             ],
+            commit_blob_base_fee_limit,
             config,
             blob_store,
             operate_4844_mode,
@@ -228,17 +241,22 @@ impl Aggregator {
                 }
             });
 
-        let batches = extract_ready_subrange(
+        let mut batches = extract_ready_subrange(
             storage,
             &mut self.commit_criteria,
             ready_for_commit_l1_batches,
             last_sealed_batch,
         )
-        .await;
+        .await?;
+        if let Some(limit) = &self.commit_blob_base_fee_limit {
+            batches = limit.apply(batches, unix_timestamp_ms() / 1_000)?;
+        }
 
-        batches.map(|batches| {
-            AggregatedOperation::Commit(last_committed_l1_batch, batches, self.pubdata_da)
-        })
+        Some(AggregatedOperation::Commit(
+            last_committed_l1_batch,
+            batches,
+            self.pubdata_da,
+        ))
     }
 
     async fn load_dummy_proof_operations(
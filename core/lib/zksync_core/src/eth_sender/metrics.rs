@@ -98,6 +98,8 @@ pub(super) struct EthSenderMetrics {
     pub l1_blocks_waited_in_mempool: Family<ActionTypeLabel, Histogram<u64>>,
     /// Number of L1 batches aggregated for publishing with a specific reason.
     pub block_aggregation_reason: Family<AggregationReasonLabels, Counter>,
+    /// Number of times a commit operation was postponed because of a high blob base fee.
+    pub commits_postponed_by_blob_base_fee: Counter,
 }
 
 impl EthSenderMetrics {
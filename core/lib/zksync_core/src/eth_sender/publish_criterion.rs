@@ -11,7 +11,7 @@ use zksync_types::{
 use super::metrics::METRICS;
 use crate::{
     eth_sender::l1_batch_commit_data_generator::L1BatchCommitDataGenerator,
-    gas_tracker::agg_l1_batch_base_cost,
+    gas_tracker::agg_l1_batch_base_cost, l1_gas_price::L1TxParamsProvider,
 };
 
 #[async_trait]
@@ -252,3 +252,59 @@ impl L1BatchPublishCriterion for DataSizeCriterion {
         None
     }
 }
+
+/// Postpones commit operations while the blob base fee is above the configured ceiling. Unlike publish criteria,
+/// this limit is applied after the L1 batch range is selected and can only shrink the range or drop it entirely.
+#[derive(Debug)]
+pub struct BlobBaseFeeCommitLimit {
+    /// Blob base fee (in wei) above which commit operations are postponed.
+    pub max_blob_base_fee: u64,
+    /// Maximum L1 batch age in seconds. L1 batches older than this are committed regardless of the blob base fee.
+    pub deadline_seconds: u64,
+    pub l1_tx_params: Arc<dyn L1TxParamsProvider>,
+}
+
+impl BlobBaseFeeCommitLimit {
+    /// Returns L1 batches that should be committed given the current blob base fee. If the fee is above the ceiling,
+    /// only L1 batches that have reached the deadline are retained, so that as few blobs as possible are published
+    /// during a fee spike.
+    pub fn apply(
+        &self,
+        l1_batches: Vec<L1BatchWithMetadata>,
+        now_seconds: u64,
+    ) -> Option<Vec<L1BatchWithMetadata>> {
+        let blob_base_fee = self.l1_tx_params.get_blob_base_fee();
+        if blob_base_fee <= self.max_blob_base_fee {
+            return Some(l1_batches);
+        }
+
+        let first_l1_batch_number = l1_batches.first()?.header.number;
+        let overdue_l1_batches: Vec<_> = l1_batches
+            .into_iter()
+            .take_while(|l1_batch| {
+                now_seconds.saturating_sub(l1_batch.header.timestamp) >= self.deadline_seconds
+            })
+            .collect();
+        if let Some(last_overdue_l1_batch) = overdue_l1_batches.last() {
+            tracing::warn!(
+                "Blob base fee {blob_base_fee} exceeds the limit {}, but L1 batches {:?} have reached \
+                 the {}s deadline; committing them",
+                self.max_blob_base_fee,
+                first_l1_batch_number.0..=last_overdue_l1_batch.header.number.0,
+                self.deadline_seconds
+            );
+            METRICS.block_aggregation_reason
+                [&(AggregatedActionType::Commit, "blob_base_fee_deadline").into()]
+                .inc();
+            Some(overdue_l1_batches)
+        } else {
+            tracing::info!(
+                "Postponing commit of L1 batches starting from #{first_l1_batch_number}: blob base fee {blob_base_fee} \
+                 exceeds the limit {}",
+                self.max_blob_base_fee
+            );
+            METRICS.commits_postponed_by_blob_base_fee.inc();
+            None
+        }
+    }
+}
@@ -21,16 +21,22 @@ use zksync_types::{
     Address, L1BatchNumber, L1BlockNumber, ProtocolVersion, ProtocolVersionId, H256,
 };
 
-use super::l1_batch_commit_data_generator::{
-    L1BatchCommitDataGenerator, RollupModeL1BatchCommitDataGenerator,
-    ValidiumModeL1BatchCommitDataGenerator,
+use super::{
+    l1_batch_commit_data_generator::{
+        L1BatchCommitDataGenerator, RollupModeL1BatchCommitDataGenerator,
+        ValidiumModeL1BatchCommitDataGenerator,
+    },
+    publish_criterion::BlobBaseFeeCommitLimit,
 };
 use crate::{
     eth_sender::{
         aggregated_operations::AggregatedOperation, eth_tx_manager::L1BlockNumbers, Aggregator,
         ETHSenderError, EthTxAggregator, EthTxManager,
     },
-    l1_gas_price::{GasAdjuster, PubdataPricing, RollupPubdataPricing, ValidiumPubdataPricing},
+    l1_gas_price::{
+        GasAdjuster, L1TxParamsProvider, PubdataPricing, RollupPubdataPricing,
+        ValidiumPubdataPricing,
+    },
     utils::testonly::{create_l1_batch, l1_batch_metadata_to_commitment_artifacts, DeploymentMode},
 };
 
@@ -148,6 +154,7 @@ impl EthSenderTester {
                 store_factory.create_store().await,
                 aggregator_operate_4844_mode,
                 l1_batch_commit_data_generator.clone(),
+                gas_adjuster.clone(),
             ),
             gateway.clone(),
             // zkSync contract address
@@ -979,6 +986,62 @@ async fn get_multicall_data(deployment_mode: DeploymentMode) {
     assert!(multicall_data.is_ok());
 }
 
+#[derive(Debug)]
+struct MockL1TxParams {
+    blob_base_fee: u64,
+}
+
+impl L1TxParamsProvider for MockL1TxParams {
+    fn get_base_fee(&self, _time_in_mempool: u32) -> u64 {
+        unreachable!()
+    }
+
+    fn get_blob_base_fee(&self) -> u64 {
+        self.blob_base_fee
+    }
+
+    fn get_priority_fee(&self) -> u64 {
+        unreachable!()
+    }
+
+    fn get_next_block_minimal_base_fee(&self) -> u64 {
+        unreachable!()
+    }
+}
+
+#[test]
+fn postponing_commits_by_blob_base_fee() {
+    let l1_batches: Vec<_> = (1..=5)
+        .map(|number| l1_batch_with_metadata(create_l1_batch(number)))
+        .collect();
+    let limit_with_fee = |blob_base_fee| BlobBaseFeeCommitLimit {
+        max_blob_base_fee: 1_000,
+        deadline_seconds: 100,
+        l1_tx_params: Arc::new(MockL1TxParams { blob_base_fee }),
+    };
+    let batch_numbers = |l1_batches: Option<Vec<L1BatchWithMetadata>>| {
+        l1_batches.map(|batches| {
+            batches
+                .iter()
+                .map(|batch| batch.header.number.0)
+                .collect::<Vec<_>>()
+        })
+    };
+
+    let limit = limit_with_fee(1_000);
+    let output = limit.apply(l1_batches.clone(), 50);
+    assert_eq!(batch_numbers(output), Some(vec![1, 2, 3, 4, 5]));
+
+    let limit = limit_with_fee(1_001);
+    // None of the L1 batches (timestamps 1..=5) have reached the deadline.
+    assert_eq!(batch_numbers(limit.apply(l1_batches.clone(), 100)), None);
+    // Only L1 batches that have reached the deadline are committed.
+    let output = limit.apply(l1_batches.clone(), 103);
+    assert_eq!(batch_numbers(output), Some(vec![1, 2, 3]));
+    let output = limit.apply(l1_batches, 1_000);
+    assert_eq!(batch_numbers(output), Some(vec![1, 2, 3, 4, 5]));
+}
+
 async fn insert_genesis_protocol_version(tester: &EthSenderTester) {
     tester
         .storage()
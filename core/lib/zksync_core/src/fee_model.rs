@@ -147,11 +147,26 @@ fn compute_batch_fee_model_input_v1(
 
 /// Calculates the batch fee input based on the main node parameters.
 /// This function uses the `V2` fee model, i.e. where the pubdata price does not include the proving costs.
-fn compute_batch_fee_model_input_v2(
+pub(crate) fn compute_batch_fee_model_input_v2(
     params: FeeParamsV2,
     l1_gas_price_scale_factor: f64,
     l1_pubdata_price_scale_factor: f64,
 ) -> PubdataIndependentBatchFeeModelInput {
+    checked_compute_batch_fee_model_input_v2(
+        params,
+        l1_gas_price_scale_factor,
+        l1_pubdata_price_scale_factor,
+    )
+    .expect("overflow computing batch fee input")
+}
+
+/// Same as [`compute_batch_fee_model_input_v2()`], but returns `None` on overflow instead of panicking.
+/// Useful if the params are not trusted (e.g., provided by the user).
+pub(crate) fn checked_compute_batch_fee_model_input_v2(
+    params: FeeParamsV2,
+    l1_gas_price_scale_factor: f64,
+    l1_pubdata_price_scale_factor: f64,
+) -> Option<PubdataIndependentBatchFeeModelInput> {
     let FeeParamsV2 {
         config,
         l1_gas_price,
@@ -179,37 +194,38 @@ fn compute_batch_fee_model_input_v2(
         // Firstly, we calculate which part of the overall overhead overhead each unit of L2 gas should cover.
         let l1_batch_overhead_per_gas =
             ceil_div_u256(l1_batch_overhead_wei, U256::from(max_gas_per_batch));
+        let l1_batch_overhead_per_gas = u64::try_from(l1_batch_overhead_per_gas).ok()?;
 
         // Then, we multiply by the `compute_overhead_part` to get the overhead for the computation for each gas.
         // Also, this means that if we almost never close batches because of compute, the `compute_overhead_part` should be zero and so
         // it is possible that the computation costs include for no overhead.
-        let gas_overhead_wei =
-            (l1_batch_overhead_per_gas.as_u64() as f64 * compute_overhead_part) as u64;
+        let gas_overhead_wei = (l1_batch_overhead_per_gas as f64 * compute_overhead_part) as u64;
 
         // We sum up the minimal L2 gas price (i.e. the raw prover/compute cost of a single L2 gas) and the overhead for batch being closed.
-        minimal_l2_gas_price + gas_overhead_wei
+        minimal_l2_gas_price.checked_add(gas_overhead_wei)?
     };
 
     let fair_pubdata_price = {
         // Firstly, we calculate which part of the overall overhead overhead each pubdata byte should cover.
         let l1_batch_overhead_per_pubdata =
             ceil_div_u256(l1_batch_overhead_wei, U256::from(max_pubdata_per_batch));
+        let l1_batch_overhead_per_pubdata = u64::try_from(l1_batch_overhead_per_pubdata).ok()?;
 
         // Then, we multiply by the `pubdata_overhead_part` to get the overhead for each pubdata byte.
         // Also, this means that if we almost never close batches because of pubdata, the `pubdata_overhead_part` should be zero and so
         // it is possible that the pubdata costs include no overhead.
         let pubdata_overhead_wei =
-            (l1_batch_overhead_per_pubdata.as_u64() as f64 * pubdata_overhead_part) as u64;
+            (l1_batch_overhead_per_pubdata as f64 * pubdata_overhead_part) as u64;
 
         // We sum up the raw L1 pubdata price (i.e. the expected price of publishing a single pubdata byte) and the overhead for batch being closed.
-        l1_pubdata_price + pubdata_overhead_wei
+        l1_pubdata_price.checked_add(pubdata_overhead_wei)?
     };
 
-    PubdataIndependentBatchFeeModelInput {
+    Some(PubdataIndependentBatchFeeModelInput {
         l1_gas_price,
         fair_l2_gas_price,
         fair_pubdata_price,
-    }
+    })
 }
 
 #[cfg(test)]
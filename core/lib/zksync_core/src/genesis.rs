@@ -438,6 +438,10 @@ async fn insert_system_contracts(
         .storage_logs_dedup_dal()
         .insert_protective_reads(L1BatchNumber(0), &protective_reads)
         .await?;
+    transaction
+        .storage_logs_dedup_dal()
+        .mark_protective_reads_as_persisted(L1BatchNumber(0))
+        .await?;
 
     let written_storage_keys: Vec<_> = deduplicated_writes
         .iter()
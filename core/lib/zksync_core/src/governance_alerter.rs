@@ -0,0 +1,263 @@
+//! Alerts about governance operations scheduled on L1, sent to an HTTP webhook.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::Context as _;
+use serde::Serialize;
+use tokio::sync::watch;
+use vise::{Counter, Metrics};
+use zksync_dal::{ConnectionPool, Core, CoreDal};
+use zksync_types::api::PendingGovernanceOperation;
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "governance_alerter")]
+struct GovernanceAlerterMetrics {
+    /// Number of alerts successfully delivered to the webhook.
+    alerts_sent: Counter,
+    /// Number of failed alert deliveries. Failed alerts are retried on the next iteration.
+    alert_errors: Counter,
+}
+
+#[vise::register]
+static METRICS: vise::Global<GovernanceAlerterMetrics> = vise::Global::new();
+
+/// Payload of a webhook request.
+#[derive(Debug, Serialize)]
+struct GovernanceAlert<'a> {
+    /// Alert kind: either `scheduled` or `upcoming`.
+    alert: String,
+    operation: &'a PendingGovernanceOperation,
+}
+
+/// Task notifying operators about governance operations tracked by the Ethereum watcher. For each operation, two alerts
+/// are sent: once the operation is scheduled, and once it is about to become ready for execution.
+#[derive(Debug)]
+pub struct GovernanceAlerter {
+    pool: ConnectionPool<Core>,
+    client: reqwest::Client,
+    webhook_url: reqwest::Url,
+    lead_time: Duration,
+    poll_interval: Duration,
+}
+
+impl GovernanceAlerter {
+    pub fn new(
+        pool: ConnectionPool<Core>,
+        webhook_url: &str,
+        lead_time: Duration,
+        poll_interval: Duration,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            pool,
+            client: reqwest::Client::new(),
+            webhook_url: webhook_url
+                .parse()
+                .context("invalid governance alerts webhook URL")?,
+            lead_time,
+            poll_interval,
+        })
+    }
+
+    async fn send_alerts(&self) -> anyhow::Result<()> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .context("invalid system time")?;
+        let upcoming_deadline = (now + self.lead_time).as_secs();
+
+        let mut storage = self.pool.connection_tagged("governance_alerter").await?;
+        let operations = storage
+            .governance_operations_dal()
+            .get_operations_to_alert(upcoming_deadline)
+            .await?;
+        drop(storage);
+
+        for (operation, alert) in operations {
+            let payload = GovernanceAlert {
+                alert: alert.to_string(),
+                operation: &operation,
+            };
+            let response = self
+                .client
+                .post(self.webhook_url.clone())
+                .json(&payload)
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status);
+            if let Err(err) = response {
+                tracing::warn!(
+                    "Failed sending `{alert}` alert for governance operation {:?}: {err}",
+                    operation.id
+                );
+                METRICS.alert_errors.inc();
+                continue;
+            }
+
+            tracing::info!(
+                "Sent `{alert}` alert for governance operation {:?}",
+                operation.id
+            );
+            METRICS.alerts_sent.inc();
+            let mut storage = self.pool.connection_tagged("governance_alerter").await?;
+            storage
+                .governance_operations_dal()
+                .set_alert(operation.id, alert)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Runs this alerter until a stop signal is received.
+    pub async fn run(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        while !*stop_receiver.borrow_and_update() {
+            if let Err(err) = self.send_alerts().await {
+                tracing::warn!("Failed sending governance alerts: {err:#}");
+            }
+            // Error here corresponds to a timeout w/o `stop_receiver` changed; we're OK with this.
+            tokio::time::timeout(self.poll_interval, stop_receiver.changed())
+                .await
+                .ok();
+        }
+        tracing::info!("Stop signal received, governance alerter is shutting down");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use axum::{extract::State, routing::post, Json, Router};
+    use tokio::sync::mpsc;
+    use zksync_dal::governance_operations_dal::GovernanceOperationAlert;
+    use zksync_types::{
+        api::{GovernanceCall, GovernanceOperationKind},
+        Address, L1BlockNumber, H256, U256,
+    };
+
+    use super::*;
+
+    fn mock_operation(ready_at: u64) -> PendingGovernanceOperation {
+        PendingGovernanceOperation {
+            id: H256::repeat_byte(1),
+            kind: GovernanceOperationKind::Transparent,
+            l1_tx_hash: H256::repeat_byte(2),
+            l1_block_number: L1BlockNumber(1),
+            scheduled_at: 0,
+            ready_at,
+            calls: vec![GovernanceCall {
+                target: Address::repeat_byte(3),
+                value: U256::zero(),
+                data: vec![1, 2, 3].into(),
+            }],
+        }
+    }
+
+    async fn spawn_webhook(
+        alerts_sender: mpsc::UnboundedSender<serde_json::Value>,
+    ) -> std::net::SocketAddr {
+        let router = Router::new()
+            .route(
+                "/alerts",
+                post(
+                    |State(sender): State<mpsc::UnboundedSender<serde_json::Value>>,
+                     Json(alert): Json<serde_json::Value>| async move {
+                        sender.send(alert).ok();
+                    },
+                ),
+            )
+            .with_state(alerts_sender);
+        let server =
+            axum::Server::bind(&(Ipv4Addr::LOCALHOST, 0).into()).serve(router.into_make_service());
+        let local_addr = server.local_addr();
+        tokio::spawn(server);
+        local_addr
+    }
+
+    #[tokio::test]
+    async fn sending_governance_alerts() {
+        let pool = ConnectionPool::<Core>::test_pool().await;
+        let operation = mock_operation(u64::MAX / 2);
+        pool.connection()
+            .await
+            .unwrap()
+            .governance_operations_dal()
+            .insert_scheduled_operation(&operation)
+            .await
+            .unwrap();
+
+        let (alerts_sender, mut alerts_receiver) = mpsc::unbounded_channel();
+        let webhook_addr = spawn_webhook(alerts_sender).await;
+        let alerter = GovernanceAlerter::new(
+            pool.clone(),
+            &format!("http://{webhook_addr}/alerts"),
+            Duration::from_secs(60),
+            Duration::from_millis(10),
+        )
+        .unwrap();
+
+        alerter.send_alerts().await.unwrap();
+        let alert = alerts_receiver.try_recv().unwrap();
+        assert_eq!(alert["alert"], "scheduled");
+        assert_eq!(
+            alert["operation"],
+            serde_json::to_value(&operation).unwrap()
+        );
+
+        // The operation is not upcoming yet, so no alerts should be sent.
+        alerter.send_alerts().await.unwrap();
+        assert!(alerts_receiver.try_recv().is_err());
+
+        let operation = PendingGovernanceOperation {
+            l1_block_number: L1BlockNumber(2),
+            ready_at: 0,
+            ..operation
+        };
+        let mut storage = pool.connection().await.unwrap();
+        let mut dal = storage.governance_operations_dal();
+        dal.mark_operation_cancelled(operation.id).await.unwrap();
+        dal.insert_scheduled_operation(&operation).await.unwrap();
+        dal.set_alert(operation.id, GovernanceOperationAlert::Scheduled)
+            .await
+            .unwrap();
+        drop(storage);
+
+        alerter.send_alerts().await.unwrap();
+        let alert = alerts_receiver.try_recv().unwrap();
+        assert_eq!(alert["alert"], "upcoming");
+        alerter.send_alerts().await.unwrap();
+        assert!(alerts_receiver.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn failed_alerts_are_retried() {
+        let pool = ConnectionPool::<Core>::test_pool().await;
+        pool.connection()
+            .await
+            .unwrap()
+            .governance_operations_dal()
+            .insert_scheduled_operation(&mock_operation(0))
+            .await
+            .unwrap();
+
+        // Use a port that nothing listens on.
+        let listener = std::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let unused_addr = listener.local_addr().unwrap();
+        drop(listener);
+        let alerter = GovernanceAlerter::new(
+            pool.clone(),
+            &format!("http://{unused_addr}/alerts"),
+            Duration::from_secs(60),
+            Duration::from_millis(10),
+        )
+        .unwrap();
+        alerter.send_alerts().await.unwrap();
+
+        let mut storage = pool.connection().await.unwrap();
+        let alerts = storage
+            .governance_operations_dal()
+            .get_operations_to_alert(0)
+            .await
+            .unwrap();
+        assert_eq!(alerts.len(), 1);
+    }
+}
@@ -2,6 +2,7 @@
 
 use std::{
     net::Ipv4Addr,
+    path::Path,
     str::FromStr,
     sync::Arc,
     time::{Duration, Instant},
@@ -14,7 +15,7 @@ use prometheus_exporter::PrometheusExporterConfig;
 use prover_dal::Prover;
 use temp_config_store::Secrets;
 use tokio::{
-    sync::{oneshot, watch},
+    sync::{oneshot, watch, RwLock},
     task::JoinHandle,
 };
 use zksync_circuit_breaker::{
@@ -32,24 +33,28 @@ use zksync_config::{
         database::{MerkleTreeConfig, MerkleTreeMode},
         wallets,
         wallets::Wallets,
-        ContractsConfig, GeneralConfig,
+        ChangeStreamConfig, ContractsConfig, GeneralConfig,
     },
-    ApiConfig, DBConfig, GenesisConfig, PostgresConfig,
+    ApiConfig, DBConfig, ETHConfig, GenesisConfig, PostgresConfig,
 };
 use zksync_contracts::governance_contract;
 use zksync_dal::{metrics::PostgresMetrics, ConnectionPool, Core, CoreDal};
 use zksync_db_connection::healthcheck::ConnectionPoolHealthCheck;
 use zksync_eth_client::{
-    clients::{PKSigningClient, QueryClient},
-    BoundEthInterface,
+    clients::{PKSigningClient, QueryClient, RemoteSigningClient},
+    BoundEthInterface, EthInterface,
 };
+use zksync_eth_signer::RemoteSigner;
 use zksync_eth_watch::start_eth_watch;
 use zksync_health_check::{AppHealthCheck, HealthStatus, ReactiveHealthCheck};
 use zksync_object_store::{ObjectStore, ObjectStoreFactory};
 use zksync_queued_job_processor::JobProcessor;
 use zksync_shared_metrics::{InitStage, APP_METRICS};
-use zksync_state::PostgresStorageCaches;
-use zksync_types::{fee_model::FeeModelConfig, L2ChainId};
+use zksync_state::{BytecodeCache, HistoricalState, HistoricalStateUpdater, PostgresStorageCaches};
+use zksync_types::{
+    api::AaValidationRules, fee_model::FeeModelConfig, Address, L1ChainId, L2ChainId,
+};
+use zksync_web3_decl::client::L2Client;
 
 use crate::{
     api_server::{
@@ -57,11 +62,19 @@ use crate::{
         execution_sandbox::{VmConcurrencyBarrier, VmConcurrencyLimiter},
         healthcheck::HealthCheckHandle,
         tree::TreeApiHttpClient,
-        tx_sender::{ApiContracts, TxSender, TxSenderBuilder, TxSenderConfig},
+        tx_sender::{
+            proxy::TxProxy, tx_sink::TxSink, ApiContracts, TxSender, TxSenderBuilder,
+            TxSenderConfig,
+        },
         web3::{self, state::InternalApiConfig, Namespace},
     },
     basic_witness_input_producer::BasicWitnessInputProducer,
+    batch_exporter::BatchExporter,
+    call_traces_backfiller::CallTracesBackfiller,
+    call_traces_offloader::CallTracesOffloader,
+    change_stream::{ChangeStreamHandler, NatsMessageBus},
     commitment_generator::CommitmentGenerator,
+    divergence_detector::DivergenceDetector,
     eth_sender::{
         l1_batch_commit_data_generator::{
             L1BatchCommitDataGenerator, RollupModeL1BatchCommitDataGenerator,
@@ -70,6 +83,7 @@ use crate::{
         Aggregator, EthTxAggregator, EthTxManager,
     },
     genesis::GenesisParams,
+    governance_alerter::GovernanceAlerter,
     house_keeper::{
         blocks_state_reporter::L1BatchMetricsReporter,
         fri_gpu_prover_archiver::FriGpuProverArchiver,
@@ -85,35 +99,53 @@ use crate::{
         waiting_to_queued_fri_witness_job_mover::WaitingToQueuedFriWitnessJobMover,
     },
     l1_gas_price::{
-        GasAdjusterSingleton, PubdataPricing, RollupPubdataPricing, ValidiumPubdataPricing,
+        GasAdjusterSingleton, MainNodeFeeParamsFetcher, PubdataPricing, RollupPubdataPricing,
+        ValidiumPubdataPricing,
+    },
+    metadata_calculator::{
+        MerkleTreeSnapshotExporter, MerkleTreeVerifier, MetadataCalculator,
+        MetadataCalculatorConfig, TreeRollbackHandle,
+    },
+    online_migrations::{
+        AddressTransactionsMigration, OnlineMigrationsRunner, TokenBalanceHoldersMigration,
     },
-    metadata_calculator::{MetadataCalculator, MetadataCalculatorConfig},
+    protective_reads_writer::ProtectiveReadsWriter,
     state_keeper::{
-        create_state_keeper, MempoolFetcher, MempoolGuard, OutputHandler, SequencerSealer,
-        StateKeeperPersistence,
+        create_state_keeper, BatchSealMonitor, MempoolFetcher, MempoolGuard, OutputHandler,
+        RawBlocksPersistence, SequencerSealer, StateKeeperPersistence,
     },
-    utils::ensure_l1_batch_commit_data_generation_mode,
+    tx_filter::{AllowAllFilter, FilterChain, TransactionFilter},
+    utils::{ensure_l1_batch_commit_data_generation_mode, latest_available_miniblock},
 };
 
 pub mod api_server;
 pub mod basic_witness_input_producer;
+pub mod batch_exporter;
 pub mod block_reverter;
+pub mod call_traces_backfiller;
+pub mod call_traces_offloader;
+pub mod change_stream;
 pub mod commitment_generator;
 pub mod consensus;
 pub mod consistency_checker;
+pub mod divergence_detector;
 pub mod eth_sender;
 pub mod fee_model;
 pub mod gas_tracker;
 pub mod genesis;
+pub mod governance_alerter;
 pub mod house_keeper;
 pub mod l1_gas_price;
 pub mod metadata_calculator;
+pub mod online_migrations;
 pub mod proof_data_handler;
+pub mod protective_reads_writer;
 pub mod proto;
 pub mod reorg_detector;
 pub mod state_keeper;
 pub mod sync_layer;
 pub mod temp_config_store;
+pub mod tx_filter;
 pub mod utils;
 
 /// Inserts the initial information about zkSync tokens into the database.
@@ -192,6 +224,18 @@ pub enum Component {
     Consensus,
     /// Component generating commitment for L1 batches.
     CommitmentGenerator,
+    /// Component exporting sealed L1 batches as Parquet files to the object store.
+    BatchExporter,
+    /// Component computing protective reads for sealed L1 batches in the background.
+    ProtectiveReadsWriter,
+    /// Component re-executing recent L1 batches and comparing outputs with the persisted ones.
+    DivergenceDetector,
+    /// Component backfilling call traces by re-executing sealed L1 batches.
+    CallTracesBackfiller,
+    /// Component offloading call traces for old L1 batches to the object store.
+    CallTracesOffloader,
+    /// Component executing online migrations of large tables.
+    OnlineMigrations,
 }
 
 #[derive(Debug)]
@@ -228,11 +272,20 @@ impl FromStr for Components {
             "proof_data_handler" => Ok(Components(vec![Component::ProofDataHandler])),
             "consensus" => Ok(Components(vec![Component::Consensus])),
             "commitment_generator" => Ok(Components(vec![Component::CommitmentGenerator])),
+            "batch_exporter" => Ok(Components(vec![Component::BatchExporter])),
+            "protective_reads_writer" => Ok(Components(vec![Component::ProtectiveReadsWriter])),
+            "divergence_detector" => Ok(Components(vec![Component::DivergenceDetector])),
+            "call_traces_backfiller" => Ok(Components(vec![Component::CallTracesBackfiller])),
+            "call_traces_offloader" => Ok(Components(vec![Component::CallTracesOffloader])),
+            "online_migrations" => Ok(Components(vec![Component::OnlineMigrations])),
             other => Err(format!("{} is not a valid component name", other)),
         }
     }
 }
 
+/// Capacity of the process-wide bytecode cache (in bytes) used if the API config is not provided.
+const DEFAULT_BYTECODE_CACHE_CAPACITY: u64 = 128 * 1_024 * 1_024;
+
 pub async fn initialize_components(
     configs: &GeneralConfig,
     wallets: &Wallets,
@@ -250,30 +303,75 @@ pub async fn initialize_components(
     let l2_chain_id = genesis_config.l2_chain_id;
     let db_config = configs.db_config.clone().context("db_config")?;
     let postgres_config = configs.postgres_config.clone().context("postgres_config")?;
+    let read_only_main_node_url = configs
+        .api_config
+        .as_ref()
+        .and_then(|config| config.web3_json_rpc.read_only_main_node_url());
+    if read_only_main_node_url.is_some() {
+        if let Some(component) = components
+            .iter()
+            .find(|component| !matches!(component, Component::HttpApi | Component::WsApi))
+        {
+            anyhow::bail!("{component:?} component cannot be run in the read-only API mode");
+        }
+    }
+
+    // Bytecode cache shared among all components executing transactions in this process.
+    let bytecode_cache_capacity = configs
+        .api_config
+        .as_ref()
+        .map_or(DEFAULT_BYTECODE_CACHE_CAPACITY, |config| {
+            config.web3_json_rpc.factory_deps_cache_size() as u64
+        });
+    let bytecode_cache = BytecodeCache::new(bytecode_cache_capacity);
 
     if let Some(threshold) = postgres_config.slow_query_threshold() {
         ConnectionPool::<Core>::global_config().set_slow_query_threshold(threshold)?;
     }
+    if let Some(rate) = postgres_config.slow_query_explain_sample_rate {
+        ConnectionPool::<Core>::global_config().set_slow_query_explain_sample_rate(rate)?;
+    }
     if let Some(threshold) = postgres_config.long_connection_threshold() {
         ConnectionPool::<Core>::global_config().set_long_connection_threshold(threshold)?;
     }
+    if let Some(timeout) = postgres_config.query_timeout() {
+        ConnectionPool::<Core>::global_config().set_query_timeout(timeout)?;
+    }
+    for (component, timeout) in postgres_config.component_query_timeouts() {
+        ConnectionPool::<Core>::global_config().set_component_query_timeout(component, timeout)?;
+    }
+    if let Some(max_retries) = postgres_config.query_max_retries {
+        ConnectionPool::<Core>::global_config().set_query_max_retries(max_retries);
+    }
+    if let Some(enabled) = postgres_config.use_binary_copy {
+        ConnectionPool::<Core>::global_config().set_use_binary_copy(enabled);
+    }
 
     let pool_size = postgres_config.max_connections()?;
     let pool_size_master = postgres_config
         .max_connections_master()
         .unwrap_or(pool_size);
 
-    let connection_pool =
-        ConnectionPool::<Core>::builder(postgres_config.master_url()?, pool_size_master)
-            .build()
-            .await
-            .context("failed to build connection_pool")?;
+    // In the read-only API mode, the node must not write to Postgres, so the master pool points to the replica.
+    let master_url = if read_only_main_node_url.is_some() {
+        postgres_config.replica_url()?
+    } else {
+        postgres_config.master_url()?
+    };
+    let connection_pool = ConnectionPool::<Core>::builder(master_url, pool_size_master)
+        .build()
+        .await
+        .context("failed to build connection_pool")?;
     // We're most interested in setting acquire / statement timeouts for the API server, which puts the most load
     // on Postgres.
     let replica_connection_pool =
         ConnectionPool::<Core>::builder(postgres_config.replica_url()?, pool_size)
             .set_acquire_timeout(postgres_config.acquire_timeout())
             .set_statement_timeout(postgres_config.statement_timeout())
+            .set_replicas(
+                postgres_config.read_replica_urls.clone(),
+                postgres_config.max_replica_lag(),
+            )
             .build()
             .await
             .context("failed to build replica_connection_pool")?;
@@ -295,12 +393,13 @@ pub async fn initialize_components(
         .clone()
         .context("circuit_breaker_config")?;
 
+    let circuit_breakers = Arc::new(
+        circuit_breakers_for_components(components, &postgres_config, &circuit_breaker_config)
+            .await
+            .context("circuit_breakers_for_components")?,
+    );
     let circuit_breaker_checker = CircuitBreakerChecker::new(
-        Arc::new(
-            circuit_breakers_for_components(components, &postgres_config, &circuit_breaker_config)
-                .await
-                .context("circuit_breakers_for_components")?,
-        ),
+        circuit_breakers.clone(),
         circuit_breaker_config.sync_interval(),
     );
     circuit_breaker_checker.check().await.unwrap_or_else(|err| {
@@ -347,6 +446,32 @@ pub async fn initialize_components(
         prometheus_task,
         tokio::spawn(circuit_breaker_checker.run(stop_receiver.clone())),
     ];
+    if let Some(lag_monitor) = replica_connection_pool.replica_lag_monitor(Duration::from_secs(1)) {
+        task_futures.push(tokio::spawn(lag_monitor.run(stop_receiver.clone())));
+    }
+
+    // The transaction filter is shared by the API servers and the state keeper, so that the same policy applies
+    // to submitted transactions and transactions already in the mempool.
+    let tx_filter = if components.contains(&Component::HttpApi)
+        || components.contains(&Component::WsApi)
+        || components.contains(&Component::StateKeeper)
+    {
+        build_tx_filter(
+            configs.mempool_config.as_ref(),
+            &mut task_futures,
+            stop_receiver.clone(),
+        )
+        .await
+        .context("build_tx_filter()")?
+    } else {
+        Arc::new(AllowAllFilter)
+    };
+    // Shared by the state keeper and the HTTP API server, so that the open L1 batch can be inspected
+    // via the `admin` namespace.
+    let batch_seal_monitor = BatchSealMonitor::default();
+    // Shared by the Merkle tree and the HTTP API server, so that the tree can be rolled back
+    // via the `admin` namespace.
+    let tree_rollback_handle = TreeRollbackHandle::default();
 
     if components.contains(&Component::WsApi)
         || components.contains(&Component::HttpApi)
@@ -370,6 +495,28 @@ pub async fn initialize_components(
         );
         let internal_api_config =
             InternalApiConfig::new(&api_config.web3_json_rpc, contracts_config, genesis_config);
+        // AA validation settings are shared among API servers, so that changes made via the `admin` namespace
+        // apply to all of them.
+        let aa_validation = AaValidationState::new(&tx_sender_config);
+        // Shared between HTTP and WS servers, so that WS connections can be managed via the HTTP `admin` namespace.
+        let ws_connections = web3::WsConnections::default();
+
+        // In the read-only mode, submitted transactions are proxied to the main node, and fee parameters
+        // are fetched from it.
+        let (tx_sink, main_node_fee_params_fetcher): (Arc<dyn TxSink>, _) =
+            if let Some(main_node_url) = read_only_main_node_url {
+                let (tx_proxy, fee_params_fetcher) = build_read_only_api_deps(
+                    main_node_url,
+                    &replica_connection_pool,
+                    &mut task_futures,
+                    stop_receiver.clone(),
+                )
+                .await
+                .context("build_read_only_api_deps()")?;
+                (Arc::new(tx_proxy), Some(fee_params_fetcher))
+            } else {
+                (Arc::new(MasterPoolSink::new(connection_pool.clone())), None)
+            };
 
         // Lazily initialize storage caches only when they are needed (e.g., skip their initialization
         // if we only run the explorer APIs). This is required because the cache update task will
@@ -381,23 +528,50 @@ pub async fn initialize_components(
             storage_caches = Some(
                 build_storage_caches(
                     &configs.api_config.clone().context("api")?.web3_json_rpc,
+                    &bytecode_cache,
                     &replica_connection_pool,
                     &mut task_futures,
                     stop_receiver.clone(),
                 )
+                .await
                 .context("build_storage_caches()")?,
             );
 
             let started_at = Instant::now();
             tracing::info!("Initializing HTTP API");
-            let bounded_gas_adjuster = gas_adjuster
-                .get_or_init()
-                .await
-                .context("gas_adjuster.get_or_init()")?;
-            let batch_fee_input_provider = Arc::new(MainNodeFeeInputProvider::new(
-                bounded_gas_adjuster,
-                FeeModelConfig::from_state_keeper_config(&state_keeper_config),
-            ));
+            let batch_fee_input_provider: Arc<dyn BatchFeeModelInputProvider> =
+                if let Some(fetcher) = &main_node_fee_params_fetcher {
+                    fetcher.clone()
+                } else {
+                    let bounded_gas_adjuster = gas_adjuster
+                        .get_or_init()
+                        .await
+                        .context("gas_adjuster.get_or_init()")?;
+                    Arc::new(MainNodeFeeInputProvider::new(
+                        bounded_gas_adjuster,
+                        FeeModelConfig::from_state_keeper_config(&state_keeper_config),
+                    ))
+                };
+            // If call traces are offloaded, the debug namespace needs access to the object store to serve them.
+            let call_traces_store = match &configs.call_traces_offloader {
+                Some(config) => {
+                    let object_store_config = match config.object_store.clone() {
+                        Some(object_store_config) => object_store_config,
+                        None => configs
+                            .prover_config
+                            .clone()
+                            .context("Prover")?
+                            .object_store
+                            .context("object_store_config")?,
+                    };
+                    Some(
+                        ObjectStoreFactory::new(object_store_config)
+                            .create_store()
+                            .await,
+                    )
+                }
+                None => None,
+            };
             run_http_api(
                 &mut task_futures,
                 &app_health,
@@ -406,12 +580,18 @@ pub async fn initialize_components(
                 &state_keeper_config,
                 &internal_api_config,
                 &api_config,
-                connection_pool.clone(),
+                tx_sink.clone(),
                 replica_connection_pool.clone(),
                 stop_receiver.clone(),
                 batch_fee_input_provider,
                 state_keeper_config.save_call_traces,
                 storage_caches.clone().unwrap(),
+                aa_validation.clone(),
+                tx_filter.clone(),
+                ws_connections.clone(),
+                batch_seal_monitor.clone(),
+                tree_rollback_handle.clone(),
+                call_traces_store,
             )
             .await
             .context("run_http_api")?;
@@ -429,23 +609,30 @@ pub async fn initialize_components(
                 Some(storage_caches) => storage_caches,
                 None => build_storage_caches(
                     &configs.api_config.clone().context("api")?.web3_json_rpc,
+                    &bytecode_cache,
                     &replica_connection_pool,
                     &mut task_futures,
                     stop_receiver.clone(),
                 )
+                .await
                 .context("build_storage_caches()")?,
             };
 
             let started_at = Instant::now();
             tracing::info!("initializing WS API");
-            let bounded_gas_adjuster = gas_adjuster
-                .get_or_init()
-                .await
-                .context("gas_adjuster.get_or_init()")?;
-            let batch_fee_input_provider = Arc::new(MainNodeFeeInputProvider::new(
-                bounded_gas_adjuster,
-                FeeModelConfig::from_state_keeper_config(&state_keeper_config),
-            ));
+            let batch_fee_input_provider: Arc<dyn BatchFeeModelInputProvider> =
+                if let Some(fetcher) = &main_node_fee_params_fetcher {
+                    fetcher.clone()
+                } else {
+                    let bounded_gas_adjuster = gas_adjuster
+                        .get_or_init()
+                        .await
+                        .context("gas_adjuster.get_or_init()")?;
+                    Arc::new(MainNodeFeeInputProvider::new(
+                        bounded_gas_adjuster,
+                        FeeModelConfig::from_state_keeper_config(&state_keeper_config),
+                    ))
+                };
             run_ws_api(
                 &mut task_futures,
                 &app_health,
@@ -455,10 +642,14 @@ pub async fn initialize_components(
                 &internal_api_config,
                 &api_config,
                 batch_fee_input_provider,
-                connection_pool.clone(),
+                tx_sink,
                 replica_connection_pool.clone(),
                 stop_receiver.clone(),
                 storage_caches,
+                aa_validation,
+                tx_filter.clone(),
+                ws_connections,
+                read_only_main_node_url.is_some(),
             )
             .await
             .context("run_ws_api")?;
@@ -526,6 +717,10 @@ pub async fn initialize_components(
             &db_config,
             &configs.mempool_config.clone().context("mempool_config")?,
             batch_fee_input_provider,
+            tx_filter,
+            batch_seal_monitor,
+            configs.change_stream.as_ref(),
+            bytecode_cache.clone(),
             stop_receiver.clone(),
         )
         .await
@@ -582,6 +777,19 @@ pub async fn initialize_components(
             .context("eth_config")?
             .watcher
             .context("watcher")?;
+        if let Some(webhook_url) = &eth_watch_config.governance_alerts_webhook_url {
+            let alerter_pool = ConnectionPool::<Core>::singleton(postgres_config.master_url()?)
+                .build()
+                .await
+                .context("failed to build governance_alerter_pool")?;
+            let alerter = GovernanceAlerter::new(
+                alerter_pool,
+                webhook_url,
+                eth_watch_config.governance_alerts_lead_time(),
+                eth_watch_config.poll_interval(),
+            )?;
+            task_futures.push(tokio::spawn(alerter.run(stop_receiver.clone())));
+        }
         task_futures.push(
             start_eth_watch(
                 eth_watch_config,
@@ -607,24 +815,12 @@ pub async fn initialize_components(
             .await
             .context("failed to build eth_sender_pool")?;
 
-        let eth_sender_wallets = wallets.eth_sender.clone().context("eth_sender")?;
-        let operator_private_key = eth_sender_wallets.operator.private_key();
-        let diamond_proxy_addr = contracts_config.diamond_proxy_addr;
-        let default_priority_fee_per_gas = eth
-            .gas_adjuster
-            .as_ref()
-            .context("gas_adjuster")?
-            .default_priority_fee_per_gas;
-        let l1_chain_id = genesis_config.l1_chain_id;
-        let web3_url = &eth.web3_url;
-
-        let eth_client = PKSigningClient::new_raw(
-            operator_private_key,
-            diamond_proxy_addr,
-            default_priority_fee_per_gas,
-            l1_chain_id,
-            web3_url,
-        );
+        let (eth_client, eth_client_blobs) = create_eth_sender_clients(
+            &eth,
+            wallets.eth_sender.as_ref(),
+            contracts_config.diamond_proxy_addr,
+            genesis_config.l1_chain_id,
+        )?;
 
         let l1_batch_commit_data_generator_mode =
             genesis_config.l1_batch_commit_data_generator_mode;
@@ -645,7 +841,7 @@ pub async fn initialize_components(
                 }
             };
 
-        let operator_blobs_address = eth_sender_wallets.blob_operator.map(|x| x.address());
+        let operator_blobs_address = eth_client_blobs.map(|client| client.sender_account());
 
         let sender_config = eth.sender.clone().context("eth_sender")?;
         let eth_tx_aggregator_actor = EthTxAggregator::new(
@@ -656,8 +852,12 @@ pub async fn initialize_components(
                 store_factory.create_store().await,
                 operator_blobs_address.is_some(),
                 l1_batch_commit_data_generator.clone(),
+                gas_adjuster
+                    .get_or_init()
+                    .await
+                    .context("gas_adjuster.get_or_init()")?,
             ),
-            Arc::new(eth_client),
+            eth_client,
             contracts_config.validator_timelock_addr,
             contracts_config.l1_multicall3_addr,
             main_zksync_contract_address,
@@ -682,37 +882,12 @@ pub async fn initialize_components(
             .await
             .context("failed to build eth_manager_pool")?;
         let eth_sender = configs.eth.clone().context("eth_sender_config")?;
-        let eth_sender_wallets = wallets.eth_sender.clone().context("eth_sender")?;
-        let operator_private_key = eth_sender_wallets.operator.private_key();
-        let diamond_proxy_addr = contracts_config.diamond_proxy_addr;
-        let default_priority_fee_per_gas = eth
-            .gas_adjuster
-            .as_ref()
-            .context("gas_adjuster")?
-            .default_priority_fee_per_gas;
-        let l1_chain_id = genesis_config.l1_chain_id;
-        let web3_url = &eth.web3_url;
-
-        let eth_client = PKSigningClient::new_raw(
-            operator_private_key,
-            diamond_proxy_addr,
-            default_priority_fee_per_gas,
-            l1_chain_id,
-            web3_url,
-        );
-
-        let eth_client_blobs = if let Some(blob_operator) = eth_sender_wallets.blob_operator {
-            let operator_blob_private_key = blob_operator.private_key();
-            Some(PKSigningClient::new_raw(
-                operator_blob_private_key,
-                diamond_proxy_addr,
-                default_priority_fee_per_gas,
-                l1_chain_id,
-                web3_url,
-            ))
-        } else {
-            None
-        };
+        let (eth_client, eth_client_blobs) = create_eth_sender_clients(
+            &eth,
+            wallets.eth_sender.as_ref(),
+            contracts_config.diamond_proxy_addr,
+            genesis_config.l1_chain_id,
+        )?;
 
         let eth_tx_manager_actor = EthTxManager::new(
             eth_manager_pool,
@@ -721,8 +896,8 @@ pub async fn initialize_components(
                 .get_or_init()
                 .await
                 .context("gas_adjuster.get_or_init()")?,
-            Arc::new(eth_client),
-            eth_client_blobs.map(|c| Arc::new(c) as Arc<dyn BoundEthInterface>),
+            eth_client,
+            eth_client_blobs,
         );
         task_futures.extend([tokio::spawn(
             eth_tx_manager_actor.run(stop_receiver.clone()),
@@ -738,6 +913,9 @@ pub async fn initialize_components(
         &app_health,
         components,
         &store_factory,
+        Arc::new(query_client.clone()),
+        &circuit_breakers,
+        &tree_rollback_handle,
         stop_receiver.clone(),
     )
     .await
@@ -754,6 +932,7 @@ pub async fn initialize_components(
             &singleton_connection_pool,
             &store_factory,
             l2_chain_id,
+            bytecode_cache.clone(),
             stop_receiver.clone(),
         )
         .await
@@ -791,6 +970,116 @@ pub async fn initialize_components(
         ));
     }
 
+    if components.contains(&Component::BatchExporter) {
+        let config = configs
+            .batch_exporter
+            .clone()
+            .context("batch_exporter_config")?;
+        let object_store = match config.object_store.clone() {
+            Some(object_store_config) => {
+                ObjectStoreFactory::new(object_store_config)
+                    .create_store()
+                    .await
+            }
+            None => store_factory.create_store().await,
+        };
+        let batch_exporter_pool = ConnectionPool::<Core>::singleton(postgres_config.master_url()?)
+            .build()
+            .await
+            .context("failed to build batch_exporter_pool")?;
+        let batch_exporter =
+            BatchExporter::new(batch_exporter_pool, object_store, config.poll_interval());
+        app_health.insert_component(batch_exporter.health_check());
+        task_futures.push(tokio::spawn(batch_exporter.run(stop_receiver.clone())));
+    }
+
+    if components.contains(&Component::CallTracesOffloader) {
+        let config = configs
+            .call_traces_offloader
+            .clone()
+            .context("call_traces_offloader_config")?;
+        let object_store = match config.object_store.clone() {
+            Some(object_store_config) => {
+                ObjectStoreFactory::new(object_store_config)
+                    .create_store()
+                    .await
+            }
+            None => store_factory.create_store().await,
+        };
+        let call_traces_offloader_pool =
+            ConnectionPool::<Core>::singleton(postgres_config.master_url()?)
+                .build()
+                .await
+                .context("failed to build call_traces_offloader_pool")?;
+        let call_traces_offloader = CallTracesOffloader::new(
+            call_traces_offloader_pool,
+            object_store,
+            config.offload_after(),
+            config.poll_interval(),
+        );
+        app_health.insert_component(call_traces_offloader.health_check());
+        task_futures.push(tokio::spawn(
+            call_traces_offloader.run(stop_receiver.clone()),
+        ));
+    }
+
+    if components.contains(&Component::ProtectiveReadsWriter) {
+        let protective_reads_writer_pool =
+            ConnectionPool::<Core>::singleton(postgres_config.master_url()?)
+                .build()
+                .await
+                .context("failed to build protective_reads_writer_pool")?;
+        let protective_reads_writer =
+            ProtectiveReadsWriter::new(protective_reads_writer_pool, l2_chain_id, false);
+        app_health.insert_component(protective_reads_writer.health_check());
+        task_futures.push(tokio::spawn(
+            protective_reads_writer.run(stop_receiver.clone()),
+        ));
+    }
+
+    if components.contains(&Component::CallTracesBackfiller) {
+        let call_traces_backfiller_pool =
+            ConnectionPool::<Core>::singleton(postgres_config.master_url()?)
+                .build()
+                .await
+                .context("failed to build call_traces_backfiller_pool")?;
+        let call_traces_backfiller =
+            CallTracesBackfiller::new(call_traces_backfiller_pool, l2_chain_id, false);
+        app_health.insert_component(call_traces_backfiller.health_check());
+        task_futures.push(tokio::spawn(
+            call_traces_backfiller.run(stop_receiver.clone()),
+        ));
+    }
+
+    if components.contains(&Component::OnlineMigrations) {
+        let online_migrations_pool =
+            ConnectionPool::<Core>::singleton(postgres_config.master_url()?)
+                .build()
+                .await
+                .context("failed to build online_migrations_pool")?;
+        // Online migrations should be added here via `OnlineMigrationsRunner::with_migration()`
+        // in the order of their execution.
+        let online_migrations_runner = OnlineMigrationsRunner::new(online_migrations_pool)
+            .with_migration(TokenBalanceHoldersMigration)
+            .with_migration(AddressTransactionsMigration);
+        app_health.insert_component(online_migrations_runner.health_check());
+        task_futures.push(tokio::spawn(
+            online_migrations_runner.run(stop_receiver.clone()),
+        ));
+    }
+
+    if components.contains(&Component::DivergenceDetector) {
+        let divergence_detector_pool =
+            ConnectionPool::<Core>::singleton(postgres_config.replica_url()?)
+                .build()
+                .await
+                .context("failed to build divergence_detector_pool")?;
+        let divergence_detector =
+            DivergenceDetector::new(divergence_detector_pool, l2_chain_id, false);
+        app_health.insert_component(divergence_detector.health_check());
+        task_futures.push(tokio::spawn(divergence_detector.run(stop_receiver.clone())));
+    }
+
     // Run healthcheck server for all components.
     let db_health_check = ConnectionPoolHealthCheck::new(replica_connection_pool);
     app_health.insert_custom_component(Arc::new(db_health_check));
@@ -804,6 +1093,70 @@ pub async fn initialize_components(
     Ok((task_futures, stop_sender, health_check_handle))
 }
 
+/// Creates signing L1 clients for the operator account and, optionally, for the blob operator account.
+/// If a remote signer is configured, transactions are signed by it; otherwise, operator private keys
+/// from the wallets config are used.
+pub fn create_eth_sender_clients(
+    eth: &ETHConfig,
+    eth_sender_wallets: Option<&wallets::EthSender>,
+    diamond_proxy_addr: Address,
+    l1_chain_id: L1ChainId,
+) -> anyhow::Result<(
+    Arc<dyn BoundEthInterface>,
+    Option<Arc<dyn BoundEthInterface>>,
+)> {
+    let default_priority_fee_per_gas = eth
+        .gas_adjuster
+        .as_ref()
+        .context("gas_adjuster")?
+        .default_priority_fee_per_gas;
+    let web3_url = &eth.web3_url;
+
+    if let Some(remote_signer) = &eth.remote_signer {
+        let create_client = |address| {
+            let signer = RemoteSigner::new(
+                &remote_signer.urls,
+                address,
+                remote_signer.request_signing_key,
+                remote_signer.request_timeout(),
+            )
+            .context("failed creating remote signer")?;
+            let client = RemoteSigningClient::from_remote_signer(
+                signer,
+                diamond_proxy_addr,
+                default_priority_fee_per_gas,
+                l1_chain_id,
+                web3_url,
+            );
+            anyhow::Ok(Arc::new(client) as Arc<dyn BoundEthInterface>)
+        };
+        let eth_client = create_client(remote_signer.operator_address)?;
+        let eth_client_blobs = remote_signer
+            .blob_operator_address
+            .map(create_client)
+            .transpose()?;
+        return Ok((eth_client, eth_client_blobs));
+    }
+
+    let eth_sender_wallets = eth_sender_wallets.context("eth_sender")?;
+    let create_client = |private_key| {
+        let client = PKSigningClient::new_raw(
+            private_key,
+            diamond_proxy_addr,
+            default_priority_fee_per_gas,
+            l1_chain_id,
+            web3_url,
+        );
+        Arc::new(client) as Arc<dyn BoundEthInterface>
+    };
+    let eth_client = create_client(eth_sender_wallets.operator.private_key());
+    let eth_client_blobs = eth_sender_wallets
+        .blob_operator
+        .as_ref()
+        .map(|blob_operator| create_client(blob_operator.private_key()));
+    Ok((eth_client, eth_client_blobs))
+}
+
 #[allow(clippy::too_many_arguments)]
 async fn add_state_keeper_to_task_futures(
     task_futures: &mut Vec<JoinHandle<anyhow::Result<()>>>,
@@ -815,6 +1168,10 @@ async fn add_state_keeper_to_task_futures(
     db_config: &DBConfig,
     mempool_config: &MempoolConfig,
     batch_fee_input_provider: Arc<dyn BatchFeeModelInputProvider>,
+    tx_filter: Arc<dyn TransactionFilter>,
+    batch_seal_monitor: BatchSealMonitor,
+    change_stream_config: Option<&ChangeStreamConfig>,
+    bytecode_cache: BytecodeCache,
     stop_receiver: watch::Receiver<bool>,
 ) -> anyhow::Result<()> {
     let pool_builder = ConnectionPool::<Core>::singleton(postgres_config.master_url()?);
@@ -827,7 +1184,7 @@ async fn add_state_keeper_to_task_futures(
             .connection()
             .await
             .context("Access storage to build mempool")?;
-        let mempool = MempoolGuard::from_storage(&mut storage, mempool_config.capacity).await;
+        let mempool = MempoolGuard::from_storage(&mut storage, mempool_config).await;
         mempool.register_metrics();
         mempool
     };
@@ -836,23 +1193,67 @@ async fn add_state_keeper_to_task_futures(
         .build()
         .await
         .context("failed to build miniblock_sealer_pool")?;
-    let (persistence, miniblock_sealer) = StateKeeperPersistence::new(
+    let shadow_vm_pool = if state_keeper_config.shadow_vm_execution() {
+        let pool = pool_builder
+            .build()
+            .await
+            .context("failed to build shadow_vm_pool")?;
+        Some(pool)
+    } else {
+        None
+    };
+
+    let (mut persistence, miniblock_sealer) = StateKeeperPersistence::new(
         miniblock_sealer_pool,
         contracts_config.l2_erc20_bridge_addr,
         state_keeper_config.miniblock_seal_queue_capacity,
     );
+    if !state_keeper_config.protective_reads_persistence_enabled() {
+        persistence = persistence.without_protective_reads();
+    }
     task_futures.push(tokio::spawn(miniblock_sealer.run()));
 
-    let (state_keeper, async_catchup_task) = create_state_keeper(
+    let raw_blocks_pool = pool_builder
+        .build()
+        .await
+        .context("failed to build raw_blocks_pool")?;
+    let (raw_blocks, raw_blocks_persister) = RawBlocksPersistence::new(
+        raw_blocks_pool,
+        state_keeper_config.miniblock_seal_queue_capacity,
+    );
+    task_futures.push(tokio::spawn(
+        raw_blocks_persister.run(stop_receiver.clone()),
+    ));
+
+    let mut output_handler =
+        OutputHandler::new(Box::new(persistence)).with_handler(Box::new(raw_blocks));
+    if let Some(change_stream_config) = change_stream_config {
+        let bus = NatsMessageBus::new(change_stream_config)
+            .await
+            .context("failed initializing change stream message bus")?;
+        let (change_stream, publisher) = ChangeStreamHandler::new(
+            Arc::new(bus),
+            change_stream_config.subject_prefix.clone(),
+            change_stream_config.queue_capacity,
+        );
+        output_handler = output_handler.with_handler(Box::new(change_stream));
+        task_futures.push(tokio::spawn(publisher.run(stop_receiver.clone())));
+    }
+
+    let (state_keeper, async_catchup_task, clock_skew_monitor) = create_state_keeper(
         state_keeper_config,
         state_keeper_wallets,
         db_config,
         l2chain_id,
         mempool_config,
         state_keeper_pool.clone(),
+        shadow_vm_pool,
         mempool.clone(),
         batch_fee_input_provider.clone(),
-        OutputHandler::new(Box::new(persistence)),
+        tx_filter,
+        output_handler,
+        batch_seal_monitor,
+        bytecode_cache,
         stop_receiver.clone(),
     )
     .await;
@@ -863,6 +1264,9 @@ async fn add_state_keeper_to_task_futures(
         stop_receiver_clone.changed().await?;
         result
     }));
+    if let Some(monitor) = clock_skew_monitor {
+        task_futures.push(tokio::spawn(monitor.run(stop_receiver.clone())));
+    }
     task_futures.push(tokio::spawn(
         state_keeper.run_fee_address_migration(state_keeper_pool),
     ));
@@ -883,12 +1287,16 @@ async fn add_state_keeper_to_task_futures(
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn add_trees_to_task_futures(
     configs: &GeneralConfig,
     task_futures: &mut Vec<JoinHandle<anyhow::Result<()>>>,
     app_health: &AppHealthCheck,
     components: &[Component],
     store_factory: &ObjectStoreFactory,
+    l1_client: Arc<dyn EthInterface>,
+    circuit_breakers: &CircuitBreakers,
+    tree_rollback_handle: &TreeRollbackHandle,
     stop_receiver: watch::Receiver<bool>,
 ) -> anyhow::Result<()> {
     if !components.contains(&Component::Tree) {
@@ -918,6 +1326,11 @@ async fn add_trees_to_task_futures(
         MerkleTreeMode::Lightweight => None,
         MerkleTreeMode::Full => Some(store_factory.create_store().await),
     };
+    let snapshot_export_store = if db_config.merkle_tree.snapshot_export_interval().is_some() {
+        Some(store_factory.create_store().await)
+    } else {
+        None
+    };
 
     run_tree(
         task_futures,
@@ -927,6 +1340,10 @@ async fn add_trees_to_task_futures(
         api_config,
         &operation_config,
         object_store,
+        snapshot_export_store,
+        l1_client,
+        circuit_breakers,
+        tree_rollback_handle,
         stop_receiver,
     )
     .await
@@ -942,6 +1359,10 @@ async fn run_tree(
     api_config: Option<&MerkleTreeApiConfig>,
     operation_manager: &OperationsManagerConfig,
     object_store: Option<Arc<dyn ObjectStore>>,
+    snapshot_export_store: Option<Arc<dyn ObjectStore>>,
+    l1_client: Arc<dyn EthInterface>,
+    circuit_breakers: &CircuitBreakers,
+    tree_rollback_handle: &TreeRollbackHandle,
     stop_receiver: watch::Receiver<bool>,
 ) -> anyhow::Result<()> {
     let started_at = Instant::now();
@@ -955,20 +1376,54 @@ async fn run_tree(
     let config = MetadataCalculatorConfig::for_main_node(merkle_tree_config, operation_manager);
     let metadata_calculator = MetadataCalculator::new(config, object_store)
         .await
-        .context("failed initializing metadata_calculator")?;
+        .context("failed initializing metadata_calculator")?
+        .with_rollback_handle(tree_rollback_handle);
     if let Some(api_config) = api_config {
         let address = (Ipv4Addr::UNSPECIFIED, api_config.port).into();
+        let max_proof_keys = api_config.max_proof_keys;
         let tree_reader = metadata_calculator.tree_reader();
+        let pool = ConnectionPool::<Core>::singleton(postgres_config.replica_url()?)
+            .build()
+            .await
+            .context("failed to build connection pool for Merkle tree API")?;
         let stop_receiver = stop_receiver.clone();
         task_futures.push(tokio::spawn(async move {
             tree_reader
                 .wait()
                 .await
-                .run_api_server(address, stop_receiver)
+                .run_api_server(address, pool, max_proof_keys, stop_receiver)
                 .await
         }));
     }
 
+    if let Some(interval) = merkle_tree_config.consistency_check_interval() {
+        let pool = ConnectionPool::<Core>::singleton(postgres_config.replica_url()?)
+            .build()
+            .await
+            .context("failed to build connection pool")?;
+        let verifier =
+            MerkleTreeVerifier::new(metadata_calculator.tree_reader(), pool, l1_client, interval);
+        circuit_breakers
+            .insert(Box::new(verifier.circuit_breaker()))
+            .await;
+        task_futures.push(tokio::spawn(verifier.run(stop_receiver.clone())));
+    }
+
+    if let Some(interval) = merkle_tree_config.snapshot_export_interval() {
+        let object_store = snapshot_export_store.context("no object store for tree snapshots")?;
+        let pool = ConnectionPool::<Core>::singleton(postgres_config.replica_url()?)
+            .build()
+            .await
+            .context("failed to build connection pool")?;
+        let exporter = MerkleTreeSnapshotExporter::new(
+            metadata_calculator.tree_reader(),
+            pool,
+            object_store,
+            interval,
+        );
+        task_futures.push(tokio::spawn(exporter.run(stop_receiver.clone())));
+    }
+
     let tree_health_check = metadata_calculator.tree_health_check();
     app_health.insert_component(tree_health_check);
     let pool = ConnectionPool::<Core>::singleton(postgres_config.master_url()?)
@@ -989,6 +1444,7 @@ async fn add_basic_witness_input_producer_to_task_futures(
     connection_pool: &ConnectionPool<Core>,
     store_factory: &ObjectStoreFactory,
     l2_chain_id: L2ChainId,
+    bytecode_cache: BytecodeCache,
     stop_receiver: watch::Receiver<bool>,
 ) -> anyhow::Result<()> {
     // Witness Generator won't be spawned with `ZKSYNC_LOCAL_SETUP` running.
@@ -999,7 +1455,9 @@ async fn add_basic_witness_input_producer_to_task_futures(
     let started_at = Instant::now();
     tracing::info!("initializing BasicWitnessInputProducer");
     let producer =
-        BasicWitnessInputProducer::new(connection_pool.clone(), store_factory, l2_chain_id).await?;
+        BasicWitnessInputProducer::new(connection_pool.clone(), store_factory, l2_chain_id)
+            .await?
+            .with_bytecode_cache(bytecode_cache);
     task_futures.push(tokio::spawn(producer.run(stop_receiver, None)));
     tracing::info!(
         "Initialized BasicWitnessInputProducer in {:?}",
@@ -1162,46 +1620,149 @@ async fn add_house_keeper_to_task_futures(
     Ok(())
 }
 
-fn build_storage_caches(
+async fn build_storage_caches(
     rpc_config: &Web3JsonRpcConfig,
+    bytecode_cache: &BytecodeCache,
     replica_connection_pool: &ConnectionPool<Core>,
     task_futures: &mut Vec<JoinHandle<anyhow::Result<()>>>,
     stop_receiver: watch::Receiver<bool>,
 ) -> anyhow::Result<PostgresStorageCaches> {
-    let factory_deps_capacity = rpc_config.factory_deps_cache_size() as u64;
     let initial_writes_capacity = rpc_config.initial_writes_cache_size() as u64;
     let values_capacity = rpc_config.latest_values_cache_size() as u64;
-    let mut storage_caches =
-        PostgresStorageCaches::new(factory_deps_capacity, initial_writes_capacity);
+    // The factory deps cache is replaced with the process-wide bytecode cache, so it's created empty.
+    let mut storage_caches = PostgresStorageCaches::new(0, initial_writes_capacity)
+        .with_bytecode_cache(bytecode_cache.clone());
 
-    if values_capacity > 0 {
-        let values_cache_task = storage_caches
+    let values_cache_task = if values_capacity > 0 {
+        let mut values_cache_task = storage_caches
             .configure_storage_values_cache(values_capacity, replica_connection_pool.clone());
+        if let Some(path) = &rpc_config.latest_values_cache_path {
+            values_cache_task = values_cache_task
+                .with_persistent_values_cache(
+                    Path::new(path),
+                    rpc_config.latest_values_cache_persistent_max_entries(),
+                )
+                .context("with_persistent_values_cache()")?;
+        }
+        Some(values_cache_task)
+    } else {
+        None
+    };
+
+    if let Some(path) = &rpc_config.historical_state_path {
+        let historical_state = HistoricalState::new(Path::new(path))
+            .context("failed initializing historical state")?;
+        let updater =
+            HistoricalStateUpdater::new(historical_state.clone(), replica_connection_pool.clone())
+                .with_retained_miniblocks(rpc_config.historical_state_retained_miniblocks);
+        task_futures.push(tokio::task::spawn(updater.run(stop_receiver.clone())));
+        storage_caches = storage_caches.with_historical_state(historical_state);
+    }
+
+    // Warm-up must finish before the values cache task is started, and before the API server accepts requests.
+    if let Some(miniblock_count) = rpc_config.caches_warm_up_miniblocks {
+        let mut connection = replica_connection_pool.connection_tagged("api").await?;
+        storage_caches
+            .warm_up(&mut connection, miniblock_count)
+            .await
+            .context("failed warming up VM caches")?;
+    }
+    if let Some(values_cache_task) = values_cache_task {
         task_futures.push(tokio::task::spawn(values_cache_task.run(stop_receiver)));
     }
     Ok(storage_caches)
 }
 
+/// AA validation settings shared among `TxSender`s of the API servers.
+#[derive(Debug, Clone)]
+struct AaValidationState {
+    whitelisted_tokens: Arc<RwLock<Vec<Address>>>,
+    rules: Arc<RwLock<AaValidationRules>>,
+}
+
+impl AaValidationState {
+    fn new(tx_sender_config: &TxSenderConfig) -> Self {
+        Self {
+            whitelisted_tokens: Arc::new(RwLock::new(
+                tx_sender_config.whitelisted_tokens_for_aa.clone(),
+            )),
+            rules: Arc::new(RwLock::new(tx_sender_config.aa_validation_rules.clone())),
+        }
+    }
+}
+
+/// Builds the transaction filter according to the mempool config. If a deny list is configured, spawns a task
+/// periodically reloading it.
+async fn build_tx_filter(
+    mempool_config: Option<&MempoolConfig>,
+    task_futures: &mut Vec<JoinHandle<anyhow::Result<()>>>,
+    stop_receiver: watch::Receiver<bool>,
+) -> anyhow::Result<Arc<dyn TransactionFilter>> {
+    let Some(mempool_config) = mempool_config else {
+        return Ok(Arc::new(AllowAllFilter));
+    };
+
+    let (filters, deny_list_updater) = FilterChain::from_config(mempool_config).await?;
+    if let Some(updater) = deny_list_updater {
+        task_futures.push(tokio::spawn(updater.run(stop_receiver)));
+    }
+    Ok(Arc::new(filters))
+}
+
+/// Builds the transaction proxy and the fee params fetcher for the read-only API mode, in which Postgres
+/// is populated by another node.
+async fn build_read_only_api_deps(
+    main_node_url: &str,
+    replica_pool: &ConnectionPool<Core>,
+    task_futures: &mut Vec<JoinHandle<anyhow::Result<()>>>,
+    stop_receiver: watch::Receiver<bool>,
+) -> anyhow::Result<(TxProxy, Arc<MainNodeFeeParamsFetcher>)> {
+    let mut storage = replica_pool.connection().await?;
+    let latest_miniblock = latest_available_miniblock(&mut storage).await?;
+    drop(storage);
+    anyhow::ensure!(
+        latest_miniblock.is_some(),
+        "Postgres is not initialized; genesis or snapshot recovery must be performed by the node writing to it"
+    );
+
+    tracing::info!("Running API in the read-only mode against main node {main_node_url}");
+    let main_node_client = L2Client::http(main_node_url)
+        .context("failed creating JSON-RPC client for main node")?
+        .build();
+    let tx_proxy = TxProxy::new(main_node_client.clone());
+    task_futures.push(tokio::spawn(
+        tx_proxy.run_account_nonce_sweeper(replica_pool.clone(), stop_receiver.clone()),
+    ));
+    let fee_params_fetcher = Arc::new(MainNodeFeeParamsFetcher::new(main_node_client));
+    task_futures.push(tokio::spawn(fee_params_fetcher.clone().run(stop_receiver)));
+    Ok((tx_proxy, fee_params_fetcher))
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn build_tx_sender(
     tx_sender_config: &TxSenderConfig,
     web3_json_config: &Web3JsonRpcConfig,
     state_keeper_config: &StateKeeperConfig,
     replica_pool: ConnectionPool<Core>,
-    master_pool: ConnectionPool<Core>,
+    tx_sink: Arc<dyn TxSink>,
     batch_fee_model_input_provider: Arc<dyn BatchFeeModelInputProvider>,
     storage_caches: PostgresStorageCaches,
+    aa_validation: AaValidationState,
+    tx_filter: Arc<dyn TransactionFilter>,
 ) -> (TxSender, VmConcurrencyBarrier) {
     let sequencer_sealer = SequencerSealer::new(state_keeper_config.clone());
-    let master_pool_sink = MasterPoolSink::new(master_pool);
-    let tx_sender_builder = TxSenderBuilder::new(
-        tx_sender_config.clone(),
-        replica_pool.clone(),
-        Arc::new(master_pool_sink),
-    )
-    .with_sealer(Arc::new(sequencer_sealer));
+    let tx_sender_builder =
+        TxSenderBuilder::new(tx_sender_config.clone(), replica_pool.clone(), tx_sink)
+            .with_sealer(Arc::new(sequencer_sealer))
+            .with_whitelisted_tokens_for_aa(aa_validation.whitelisted_tokens)
+            .with_aa_validation_rules(aa_validation.rules)
+            .with_tx_filter(tx_filter);
 
     let max_concurrency = web3_json_config.vm_concurrency_limit();
     let (vm_concurrency_limiter, vm_barrier) = VmConcurrencyLimiter::new(max_concurrency);
+    let vm_concurrency_limiter = vm_concurrency_limiter
+        .with_permit_timeout(web3_json_config.vm_permit_timeout())
+        .with_saturation_threshold(web3_json_config.vm_saturation_threshold());
 
     let batch_fee_input_provider =
         ApiFeeInputProvider::new(batch_fee_model_input_provider, replica_pool);
@@ -1226,29 +1787,47 @@ async fn run_http_api(
     state_keeper_config: &StateKeeperConfig,
     internal_api: &InternalApiConfig,
     api_config: &ApiConfig,
-    master_connection_pool: ConnectionPool<Core>,
+    tx_sink: Arc<dyn TxSink>,
     replica_connection_pool: ConnectionPool<Core>,
     stop_receiver: watch::Receiver<bool>,
     batch_fee_model_input_provider: Arc<dyn BatchFeeModelInputProvider>,
     with_debug_namespace: bool,
     storage_caches: PostgresStorageCaches,
+    aa_validation: AaValidationState,
+    tx_filter: Arc<dyn TransactionFilter>,
+    ws_connections: web3::WsConnections,
+    batch_seal_monitor: BatchSealMonitor,
+    tree_rollback_handle: TreeRollbackHandle,
+    call_traces_store: Option<Arc<dyn ObjectStore>>,
 ) -> anyhow::Result<()> {
     let (tx_sender, vm_barrier) = build_tx_sender(
         tx_sender_config,
         &api_config.web3_json_rpc,
         state_keeper_config,
         replica_connection_pool.clone(),
-        master_connection_pool,
+        tx_sink,
         batch_fee_model_input_provider,
         storage_caches,
+        aa_validation,
+        tx_filter,
     )
     .await;
+    let sandbox_health = tx_sender
+        .vm_concurrency_limiter()
+        .health_check("http_api_sandbox");
+    app_health.insert_custom_component(Arc::new(sandbox_health));
 
     let mut namespaces = Namespace::DEFAULT.to_vec();
     if with_debug_namespace {
         namespaces.push(Namespace::Debug)
     }
     namespaces.push(Namespace::Snapshots);
+    if api_config.web3_json_rpc.admin_namespace_enabled {
+        namespaces.push(Namespace::Admin);
+    }
+    if api_config.web3_json_rpc.unstable_namespace_enabled {
+        namespaces.push(Namespace::Unstable);
+    }
 
     let updaters_pool = ConnectionPool::<Core>::builder(postgres_config.replica_url()?, 2)
         .build()
@@ -1261,15 +1840,25 @@ async fn run_http_api(
             .with_updaters_pool(updaters_pool)
             .with_filter_limit(api_config.web3_json_rpc.filters_limit())
             .with_batch_request_size_limit(api_config.web3_json_rpc.max_batch_request_size())
+            .with_batch_request_cost_limit(api_config.web3_json_rpc.max_batch_request_cost())
             .with_response_body_size_limit(api_config.web3_json_rpc.max_response_body_size())
             .with_tx_sender(tx_sender)
             .with_vm_barrier(vm_barrier)
+            .with_ws_connections(ws_connections)
+            .with_batch_seal_monitor(batch_seal_monitor)
+            .with_tree_rollback_handle(tree_rollback_handle)
             .enable_api_namespaces(namespaces);
     if let Some(tree_api_url) = api_config.web3_json_rpc.tree_api_url() {
         let tree_api = Arc::new(TreeApiHttpClient::new(tree_api_url));
         api_builder = api_builder.with_tree_api(tree_api.clone());
         app_health.insert_custom_component(tree_api);
     }
+    if let Some(auth_token) = &api_config.web3_json_rpc.http_auth_token {
+        api_builder = api_builder.with_auth_token(auth_token.clone());
+    }
+    if let Some(call_traces_store) = call_traces_store {
+        api_builder = api_builder.with_call_traces_store(call_traces_store);
+    }
 
     let server_handles = api_builder
         .build()
@@ -1291,21 +1880,31 @@ async fn run_ws_api(
     internal_api: &InternalApiConfig,
     api_config: &ApiConfig,
     batch_fee_model_input_provider: Arc<dyn BatchFeeModelInputProvider>,
-    master_connection_pool: ConnectionPool<Core>,
+    tx_sink: Arc<dyn TxSink>,
     replica_connection_pool: ConnectionPool<Core>,
     stop_receiver: watch::Receiver<bool>,
     storage_caches: PostgresStorageCaches,
+    aa_validation: AaValidationState,
+    tx_filter: Arc<dyn TransactionFilter>,
+    ws_connections: web3::WsConnections,
+    is_read_only: bool,
 ) -> anyhow::Result<()> {
     let (tx_sender, vm_barrier) = build_tx_sender(
         tx_sender_config,
         &api_config.web3_json_rpc,
         state_keeper_config,
         replica_connection_pool.clone(),
-        master_connection_pool,
+        tx_sink,
         batch_fee_model_input_provider,
         storage_caches,
+        aa_validation,
+        tx_filter,
     )
     .await;
+    let sandbox_health = tx_sender
+        .vm_concurrency_limiter()
+        .health_check("ws_api_sandbox");
+    app_health.insert_custom_component(Arc::new(sandbox_health));
     let last_miniblock_pool = ConnectionPool::<Core>::singleton(postgres_config.replica_url()?)
         .build()
         .await
@@ -1313,6 +1912,25 @@ async fn run_ws_api(
 
     let mut namespaces = Namespace::DEFAULT.to_vec();
     namespaces.push(Namespace::Snapshots);
+    let pub_sub_notification_source = if is_read_only {
+        // Notifications are only delivered to listeners on the primary database, which must not be accessed
+        // in the read-only mode, so pub-sub falls back to polling the replica.
+        if api_config.web3_json_rpc.pubsub_db_notifications {
+            tracing::warn!(
+                "Pub-sub DB notifications are not supported in the read-only API mode; falling back to polling"
+            );
+        }
+        web3::PubSubNotificationSource::Polling
+    } else if api_config.web3_json_rpc.pubsub_db_notifications {
+        // Notifications are sent by triggers on the primary database, so they are listened to on the primary as well.
+        let master_pool = ConnectionPool::<Core>::singleton(postgres_config.master_url()?)
+            .build()
+            .await
+            .context("failed to build pub-sub notifications pool")?;
+        web3::PubSubNotificationSource::db_notifications(master_pool).await?
+    } else {
+        web3::PubSubNotificationSource::Polling
+    };
 
     let mut api_builder =
         web3::ApiBuilder::jsonrpsee_backend(internal_api.clone(), replica_connection_pool)
@@ -1321,6 +1939,7 @@ async fn run_ws_api(
             .with_filter_limit(api_config.web3_json_rpc.filters_limit())
             .with_subscriptions_limit(api_config.web3_json_rpc.subscriptions_limit())
             .with_batch_request_size_limit(api_config.web3_json_rpc.max_batch_request_size())
+            .with_batch_request_cost_limit(api_config.web3_json_rpc.max_batch_request_cost())
             .with_response_body_size_limit(api_config.web3_json_rpc.max_response_body_size())
             .with_websocket_requests_per_minute_limit(
                 api_config
@@ -1328,14 +1947,25 @@ async fn run_ws_api(
                     .websocket_requests_per_minute_limit(),
             )
             .with_polling_interval(api_config.web3_json_rpc.pubsub_interval())
+            .with_pub_sub_notification_source(pub_sub_notification_source)
             .with_tx_sender(tx_sender)
             .with_vm_barrier(vm_barrier)
+            .with_ws_connections(ws_connections)
             .enable_api_namespaces(namespaces);
     if let Some(tree_api_url) = api_config.web3_json_rpc.tree_api_url() {
         let tree_api = Arc::new(TreeApiHttpClient::new(tree_api_url));
         api_builder = api_builder.with_tree_api(tree_api.clone());
         app_health.insert_custom_component(tree_api);
     }
+    if let Some(sse_port) = api_config.web3_json_rpc.sse_port {
+        api_builder = api_builder.with_sse_port(sse_port);
+    }
+    if let Some(auth_token) = &api_config.web3_json_rpc.ws_auth_token {
+        api_builder = api_builder.with_auth_token(auth_token.clone());
+    }
+    if let Some(ipc_path) = &api_config.web3_json_rpc.ipc_path {
+        api_builder = api_builder.with_ipc_path(ipc_path.into());
+    }
 
     let server_handles = api_builder
         .build()
@@ -0,0 +1,415 @@
+//! Export of Merkle tree snapshots to an object store.
+//!
+//! A tree snapshot for an L1 batch consists of a [header](TreeSnapshotHeader) and [chunks](TreeSnapshotChunk)
+//! of tree entries. Chunks are defined in the same way as for recovery from a Postgres snapshot
+//! (i.e., using [`uniform_hashed_keys_chunk()`]), so a tree snapshot can be used as a drop-in replacement
+//! for the Postgres snapshot during tree recovery. Since exported entries already contain leaf indices
+//! and are sorted by key, recovery from an exported snapshot is significantly faster than from Postgres.
+
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+use zksync_dal::{ConnectionPool, Core, CoreDal};
+use zksync_merkle_tree::{Key, TreeEntry};
+use zksync_object_store::{
+    serialize_using_bincode, Bucket, ObjectStore, ObjectStoreError, StoredObject,
+};
+use zksync_types::{snapshots::uniform_hashed_keys_chunk, L1BatchNumber, H256};
+
+use super::helpers::{AsyncTreeReader, LazyAsyncTreeReader};
+
+/// Desired number of entries in a chunk of an exported snapshot. Matches the chunk size used for recovery
+/// from Postgres snapshots.
+pub(super) const DESIRED_CHUNK_SIZE: u64 = 200_000;
+
+/// Computes the number of chunks for a snapshot with the specified number of tree entries.
+pub(super) fn chunk_count(entry_count: u64) -> u64 {
+    entry_count.div_ceil(DESIRED_CHUNK_SIZE)
+}
+
+/// Tree entry as stored in an exported snapshot.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub(super) struct TreeSnapshotEntry {
+    key: H256,
+    value: H256,
+    leaf_index: u64,
+}
+
+impl From<&TreeEntry> for TreeSnapshotEntry {
+    fn from(entry: &TreeEntry) -> Self {
+        let mut key = H256::zero();
+        entry.key.to_big_endian(&mut key.0);
+        Self {
+            key,
+            value: entry.value,
+            leaf_index: entry.leaf_index,
+        }
+    }
+}
+
+impl From<TreeSnapshotEntry> for TreeEntry {
+    fn from(entry: TreeSnapshotEntry) -> Self {
+        Self {
+            key: Key::from_big_endian(entry.key.as_bytes()),
+            value: entry.value,
+            leaf_index: entry.leaf_index,
+        }
+    }
+}
+
+/// Header of a tree snapshot. Uploaded after all snapshot chunks, so that its presence in the object store
+/// signals that the snapshot is complete.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(super) struct TreeSnapshotHeader {
+    pub l1_batch_number: L1BatchNumber,
+    pub root_hash: H256,
+    pub leaf_count: u64,
+    /// First entry for each chunk, or `None` if the chunk is empty. Used to filter out recovered chunks.
+    pub chunk_starts: Vec<Option<TreeSnapshotEntry>>,
+}
+
+impl TreeSnapshotHeader {
+    pub fn chunk_count(&self) -> u64 {
+        self.chunk_starts.len() as u64
+    }
+
+    /// Loads the header for the specified L1 batch from the store. Returns `Ok(None)` if the header is missing.
+    pub async fn load(
+        store: &dyn ObjectStore,
+        l1_batch_number: L1BatchNumber,
+    ) -> anyhow::Result<Option<Self>> {
+        match store.get::<Self>(l1_batch_number).await {
+            Ok(header) => Ok(Some(header)),
+            Err(ObjectStoreError::KeyNotFound(_)) => Ok(None),
+            Err(err) => Err(anyhow::Error::from(err).context(format!(
+                "failed loading Merkle tree snapshot header for L1 batch #{l1_batch_number}"
+            ))),
+        }
+    }
+}
+
+impl StoredObject for TreeSnapshotHeader {
+    const BUCKET: Bucket = Bucket::MerkleTreeSnapshots;
+    type Key<'a> = L1BatchNumber;
+
+    fn encode_key(key: Self::Key<'_>) -> String {
+        format!("tree_snapshot_l1_batch_{key}_header.bin")
+    }
+
+    serialize_using_bincode!();
+}
+
+/// Key of a [`TreeSnapshotChunk`] in the object store.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct TreeSnapshotChunkKey {
+    pub l1_batch_number: L1BatchNumber,
+    pub chunk_id: u64,
+}
+
+/// Chunk of a tree snapshot containing entries sorted by key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct TreeSnapshotChunk {
+    pub entries: Vec<TreeSnapshotEntry>,
+}
+
+impl StoredObject for TreeSnapshotChunk {
+    const BUCKET: Bucket = Bucket::MerkleTreeSnapshots;
+    type Key<'a> = TreeSnapshotChunkKey;
+
+    fn encode_key(key: Self::Key<'_>) -> String {
+        format!(
+            "tree_snapshot_l1_batch_{}_chunk_{:0>4}.bin",
+            key.l1_batch_number, key.chunk_id
+        )
+    }
+
+    serialize_using_bincode!();
+}
+
+/// Exports a snapshot of the tree for the specified L1 batch. Returns `Ok(None)` if the corresponding
+/// tree version is missing (e.g., if it was pruned), or if a stop signal was received.
+pub(super) async fn export_tree_snapshot(
+    tree_reader: &AsyncTreeReader,
+    object_store: &dyn ObjectStore,
+    l1_batch_number: L1BatchNumber,
+    stop_receiver: &watch::Receiver<bool>,
+) -> anyhow::Result<Option<TreeSnapshotHeader>> {
+    let Some(leaf_count) = tree_reader
+        .clone()
+        .leaf_count_for_l1_batch(l1_batch_number)
+        .await
+    else {
+        return Ok(None);
+    };
+    let chunk_count = chunk_count(leaf_count);
+    tracing::info!(
+        "Exporting Merkle tree snapshot for L1 batch #{l1_batch_number} with {leaf_count} entries in {chunk_count} chunks"
+    );
+
+    let mut chunk_starts = Vec::with_capacity(chunk_count as usize);
+    let mut exported_leaf_count = 0;
+    for chunk_id in 0..chunk_count {
+        if *stop_receiver.borrow() {
+            return Ok(None);
+        }
+
+        let key_chunk = uniform_hashed_keys_chunk(chunk_id, chunk_count);
+        let key_range = Key::from_big_endian(key_chunk.start().as_bytes())
+            ..=Key::from_big_endian(key_chunk.end().as_bytes());
+        let entries = tree_reader
+            .clone()
+            .entries_in_range(l1_batch_number, key_range, usize::MAX)
+            .await;
+        let Ok(entries) = entries else {
+            // The tree version was pruned during the export.
+            return Ok(None);
+        };
+
+        let entries: Vec<_> = entries.iter().map(TreeSnapshotEntry::from).collect();
+        chunk_starts.push(entries.first().copied());
+        exported_leaf_count += entries.len() as u64;
+        let key = TreeSnapshotChunkKey {
+            l1_batch_number,
+            chunk_id,
+        };
+        object_store
+            .put(key, &TreeSnapshotChunk { entries })
+            .await
+            .with_context(|| format!("failed uploading tree snapshot chunk {key:?}"))?;
+        tracing::debug!(
+            "Exported chunk #{chunk_id} of Merkle tree snapshot for L1 batch #{l1_batch_number}"
+        );
+    }
+    anyhow::ensure!(
+        exported_leaf_count == leaf_count,
+        "Number of exported tree entries ({exported_leaf_count}) differs from the number of leaves in the tree \
+         ({leaf_count}) for L1 batch #{l1_batch_number}"
+    );
+
+    let root_hash = tree_reader
+        .clone()
+        .root_hash_for_l1_batch(l1_batch_number)
+        .await;
+    let Some(root_hash) = root_hash else {
+        return Ok(None);
+    };
+    let header = TreeSnapshotHeader {
+        l1_batch_number,
+        root_hash,
+        leaf_count,
+        chunk_starts,
+    };
+    object_store
+        .put(l1_batch_number, &header)
+        .await
+        .context("failed uploading tree snapshot header")?;
+    Ok(Some(header))
+}
+
+/// Background task exporting Merkle tree snapshots to an object store.
+///
+/// Snapshots are exported for L1 batches of Postgres snapshots created by the snapshot creator, so that nodes
+/// recovering from a Postgres snapshot can bootstrap their Merkle tree from the export instead of recovering it
+/// from Postgres data. The tree pruning retention period (if any) must be large enough for the exporter
+/// to read the snapshot tree version in full.
+#[derive(Debug)]
+pub struct MerkleTreeSnapshotExporter {
+    tree_reader: LazyAsyncTreeReader,
+    pool: ConnectionPool<Core>,
+    object_store: Arc<dyn ObjectStore>,
+    poll_interval: Duration,
+    /// Last L1 batch for which the export was checked.
+    last_checked_l1_batch: Option<L1BatchNumber>,
+}
+
+impl MerkleTreeSnapshotExporter {
+    pub fn new(
+        tree_reader: LazyAsyncTreeReader,
+        pool: ConnectionPool<Core>,
+        object_store: Arc<dyn ObjectStore>,
+        poll_interval: Duration,
+    ) -> Self {
+        Self {
+            tree_reader,
+            pool,
+            object_store,
+            poll_interval,
+            last_checked_l1_batch: None,
+        }
+    }
+
+    async fn run_once(
+        &mut self,
+        tree_reader: &AsyncTreeReader,
+        stop_receiver: &watch::Receiver<bool>,
+    ) -> anyhow::Result<()> {
+        let mut storage = self.pool.connection_tagged("metadata_calculator").await?;
+        let snapshot = storage
+            .snapshots_dal()
+            .get_newest_snapshot_metadata()
+            .await
+            .context("failed getting newest snapshot metadata")?;
+        drop(storage);
+        let Some(snapshot) = snapshot else {
+            return Ok(()); // No snapshots are created yet
+        };
+        let l1_batch_number = snapshot.l1_batch_number;
+        if self.last_checked_l1_batch == Some(l1_batch_number) {
+            return Ok(());
+        }
+        let next_tree_l1_batch = tree_reader.clone().info().await.next_l1_batch_number;
+        if next_tree_l1_batch <= l1_batch_number {
+            return Ok(()); // The tree hasn't processed the snapshot L1 batch yet
+        }
+
+        if TreeSnapshotHeader::load(&*self.object_store, l1_batch_number)
+            .await?
+            .is_some()
+        {
+            tracing::debug!(
+                "Merkle tree snapshot for L1 batch #{l1_batch_number} is already exported"
+            );
+        } else {
+            let header = export_tree_snapshot(
+                tree_reader,
+                &*self.object_store,
+                l1_batch_number,
+                stop_receiver,
+            )
+            .await?;
+            if header.is_some() {
+                tracing::info!("Exported Merkle tree snapshot for L1 batch #{l1_batch_number}");
+            } else if *stop_receiver.borrow() {
+                return Ok(()); // Will retry the export after restart
+            } else {
+                tracing::warn!(
+                    "Cannot export Merkle tree snapshot for L1 batch #{l1_batch_number}: tree version is missing"
+                );
+            }
+        }
+        self.last_checked_l1_batch = Some(l1_batch_number);
+        Ok(())
+    }
+
+    pub async fn run(mut self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        let tree_reader = tokio::select! {
+            reader = self.tree_reader.clone().wait() => reader,
+            _ = stop_receiver.changed() => {
+                tracing::info!("Stop signal received, Merkle tree snapshot exporter is shutting down");
+                return Ok(());
+            }
+        };
+        tracing::info!(
+            "Starting Merkle tree snapshot exporter with {:?} poll interval",
+            self.poll_interval
+        );
+
+        while !*stop_receiver.borrow_and_update() {
+            self.run_once(&tree_reader, &stop_receiver).await?;
+            // Error here corresponds to a timeout w/o `stop_receiver` changed; we're OK with this.
+            tokio::time::timeout(self.poll_interval, stop_receiver.changed())
+                .await
+                .ok();
+        }
+        tracing::info!("Stop signal received, Merkle tree snapshot exporter is shutting down");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+    use zksync_object_store::ObjectStoreFactory;
+    use zksync_types::snapshots::SnapshotVersion;
+
+    use super::*;
+    use crate::metadata_calculator::tests::{reset_db_state, run_calculator, setup_calculator};
+
+    #[tokio::test]
+    async fn exporting_tree_snapshot() {
+        let pool = ConnectionPool::<Core>::test_pool().await;
+        let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+        let (calculator, _) = setup_calculator(temp_dir.path(), &pool).await;
+        reset_db_state(&pool, 5).await;
+        let tree_reader = calculator.tree_reader();
+        run_calculator(calculator, pool.clone()).await;
+
+        let object_store = ObjectStoreFactory::mock().create_store().await;
+        let mut exporter = MerkleTreeSnapshotExporter::new(
+            tree_reader,
+            pool.clone(),
+            object_store.clone(),
+            Duration::ZERO,
+        );
+        let tree_reader = exporter.tree_reader.read().unwrap();
+        let (_stop_sender, stop_receiver) = watch::channel(false);
+        exporter
+            .run_once(&tree_reader, &stop_receiver)
+            .await
+            .unwrap();
+        assert_eq!(exporter.last_checked_l1_batch, None);
+
+        pool.connection()
+            .await
+            .unwrap()
+            .snapshots_dal()
+            .add_snapshot(
+                SnapshotVersion::Version0,
+                L1BatchNumber(3),
+                1,
+                "file:///factory_deps",
+            )
+            .await
+            .unwrap();
+        exporter
+            .run_once(&tree_reader, &stop_receiver)
+            .await
+            .unwrap();
+        assert_eq!(exporter.last_checked_l1_batch, Some(L1BatchNumber(3)));
+
+        let header = TreeSnapshotHeader::load(&*object_store, L1BatchNumber(3))
+            .await
+            .unwrap()
+            .expect("no tree snapshot header");
+        assert_eq!(header.l1_batch_number, L1BatchNumber(3));
+        let expected_root_hash = tree_reader
+            .clone()
+            .root_hash_for_l1_batch(L1BatchNumber(3))
+            .await;
+        assert_eq!(Some(header.root_hash), expected_root_hash);
+        let expected_leaf_count = tree_reader
+            .clone()
+            .leaf_count_for_l1_batch(L1BatchNumber(3))
+            .await;
+        assert_eq!(Some(header.leaf_count), expected_leaf_count);
+        assert_eq!(header.chunk_count(), chunk_count(header.leaf_count));
+
+        let mut exported_entries = vec![];
+        for chunk_id in 0..header.chunk_count() {
+            let key = TreeSnapshotChunkKey {
+                l1_batch_number: L1BatchNumber(3),
+                chunk_id,
+            };
+            let chunk: TreeSnapshotChunk = object_store.get(key).await.unwrap();
+            assert_eq!(
+                chunk.entries.first(),
+                header.chunk_starts[chunk_id as usize].as_ref()
+            );
+            exported_entries.extend(chunk.entries);
+        }
+        assert_eq!(exported_entries.len() as u64, header.leaf_count);
+        assert!(exported_entries
+            .windows(2)
+            .all(|window| window[0].key < window[1].key));
+
+        // The exporter should not re-export an existing snapshot.
+        exporter.last_checked_l1_batch = None;
+        exporter
+            .run_once(&tree_reader, &stop_receiver)
+            .await
+            .unwrap();
+        assert_eq!(exporter.last_checked_l1_batch, Some(L1BatchNumber(3)));
+    }
+}
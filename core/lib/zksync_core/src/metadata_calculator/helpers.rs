@@ -4,6 +4,7 @@ use std::{
     collections::{BTreeMap, HashSet},
     future,
     future::Future,
+    ops,
     path::Path,
     time::Duration,
 };
@@ -13,16 +14,17 @@ use serde::{Deserialize, Serialize};
 #[cfg(test)]
 use tokio::sync::mpsc;
 use tokio::sync::watch;
-use zksync_config::configs::database::MerkleTreeMode;
+use zksync_config::configs::database::{MerkleTreeCompactionStyle, MerkleTreeMode};
 use zksync_dal::{Connection, Core, CoreDal};
 use zksync_health_check::{Health, HealthStatus};
 use zksync_merkle_tree::{
     domain::{TreeMetadata, ZkSyncTree, ZkSyncTreeReader},
     recovery::MerkleTreeRecovery,
-    Database, Key, NoVersionError, RocksDBWrapper, TreeEntry, TreeEntryWithProof, TreeInstruction,
+    ConsistencyError, Database, Key, MerkleTreePruner, MerkleTreePrunerHandle, NoVersionError,
+    RocksDBWrapper, TreeEntry, TreeEntryWithProof, TreeHasherKind, TreeInstruction,
 };
-use zksync_storage::{RocksDB, RocksDBOptions, StalledWritesRetries};
-use zksync_types::{block::L1BatchHeader, L1BatchNumber, StorageKey, H256};
+use zksync_storage::{CompactionStyle, RocksDB, RocksDBOptions, StalledWritesRetries};
+use zksync_types::{block::L1BatchHeader, L1BatchNumber, ProtocolVersionId, StorageKey, H256};
 
 use super::{
     metrics::{LoadChangesStage, TreeUpdateStage, METRICS},
@@ -78,13 +80,17 @@ fn create_db_sync(config: &MetadataCalculatorConfig) -> anyhow::Result<RocksDBWr
         multi_get_chunk_size,
         memtable_capacity,
         stalled_writes_timeout,
+        compaction_style,
+        separate_leaves_column_family,
+        in_memory,
         ..
     } = config;
 
     tracing::info!(
-        "Initializing Merkle tree database at `{path}` (max open files: {max_open_files:?}) with {multi_get_chunk_size} multi-get chunk size, \
+        "Initializing Merkle tree database at `{path}` (in memory: {in_memory:?}, max open files: {max_open_files:?}) \
+         with {multi_get_chunk_size} multi-get chunk size, \
          {block_cache_capacity}B block cache (indices & filters included: {include_indices_and_filters_in_block_cache:?}), \
-         {memtable_capacity}B memtable capacity, \
+         {memtable_capacity}B memtable capacity, {compaction_style:?} compaction style, \
          {stalled_writes_timeout:?} stalled writes timeout",
         path = path.display()
     );
@@ -97,6 +103,11 @@ fn create_db_sync(config: &MetadataCalculatorConfig) -> anyhow::Result<RocksDBWr
             large_memtable_capacity: Some(memtable_capacity),
             stalled_writes_retries: StalledWritesRetries::new(stalled_writes_timeout),
             max_open_files,
+            large_cf_compaction_style: match compaction_style {
+                MerkleTreeCompactionStyle::Level => CompactionStyle::Level,
+                MerkleTreeCompactionStyle::Universal => CompactionStyle::Universal,
+            },
+            in_memory,
         },
     )?;
     if cfg!(test) {
@@ -106,6 +117,12 @@ fn create_db_sync(config: &MetadataCalculatorConfig) -> anyhow::Result<RocksDBWr
     }
     let mut db = RocksDBWrapper::from(db);
     db.set_multi_get_chunk_size(multi_get_chunk_size);
+    if separate_leaves_column_family && !db.enable_separate_leaves() {
+        tracing::info!(
+            "Merkle tree database is not empty and stores leaves together with internal nodes; \
+             separate leaves column family will not be used"
+        );
+    }
     Ok(db)
 }
 
@@ -150,6 +167,15 @@ impl AsyncTree {
         self.mode
     }
 
+    /// Processes L1 batches using a dedicated `rayon` thread pool with the specified number of threads.
+    pub fn use_dedicated_thread_pool(&mut self, thread_count: usize) {
+        self.as_mut().use_dedicated_thread_pool(thread_count);
+    }
+
+    pub fn pruner(&self) -> (MerkleTreePruner<RocksDBWrapper>, MerkleTreePrunerHandle) {
+        self.as_ref().pruner()
+    }
+
     pub fn reader(&self) -> AsyncTreeReader {
         AsyncTreeReader {
             inner: self.inner.as_ref().expect(Self::INCONSISTENT_MSG).reader(),
@@ -157,6 +183,12 @@ impl AsyncTree {
         }
     }
 
+    /// Returns a synchronous reader for the tree. Unlike [`Self::reader()`], the reader performs I/O
+    /// on the calling thread.
+    pub fn sync_reader(&self) -> ZkSyncTreeReader {
+        self.as_ref().reader()
+    }
+
     pub fn is_empty(&self) -> bool {
         self.as_ref().is_empty()
     }
@@ -182,6 +214,22 @@ impl AsyncTree {
             self.mode
         );
         let batch_number = batch.header.number;
+        let protocol_version = batch
+            .header
+            .protocol_version
+            .unwrap_or_else(ProtocolVersionId::last_potentially_undefined);
+        let hasher = TreeHasherKind::for_protocol_version(protocol_version);
+        let tree = self.as_mut();
+        if tree.next_l1_batch_number() == L1BatchNumber(0) {
+            tree.set_hasher(hasher);
+        } else {
+            anyhow::ensure!(
+                tree.hasher() == hasher,
+                "L1 batch #{batch_number} with protocol version {protocol_version:?} requires tree hasher {hasher:?}, \
+                 but the tree uses {:?}; the tree must be rebuilt using the new hasher",
+                tree.hasher()
+            );
+        }
 
         let mut tree = self.inner.take().context(Self::INCONSISTENT_MSG)?;
         let (tree, metadata) = tokio::task::spawn_blocking(move || {
@@ -235,19 +283,81 @@ impl AsyncTreeReader {
         .unwrap()
     }
 
+    /// Returns entries for the specified keys in the same order as requested.
+    pub async fn entries(
+        self,
+        l1_batch_number: L1BatchNumber,
+        keys: Vec<Key>,
+    ) -> Result<Vec<TreeEntry>, NoVersionError> {
+        tokio::task::spawn_blocking(move || self.inner.entries(l1_batch_number, &keys))
+            .await
+            .unwrap()
+    }
+
+    /// Returns entries with proofs for the specified keys, together with the tree root hash after
+    /// the specified L1 batch (the proofs are relative to it).
     pub async fn entries_with_proofs(
         self,
         l1_batch_number: L1BatchNumber,
         keys: Vec<Key>,
-    ) -> Result<Vec<TreeEntryWithProof>, NoVersionError> {
-        tokio::task::spawn_blocking(move || self.inner.entries_with_proofs(l1_batch_number, &keys))
+    ) -> Result<(H256, Vec<TreeEntryWithProof>), NoVersionError> {
+        tokio::task::spawn_blocking(move || {
+            self.inner
+                .entries_with_proofs_and_root_hash(l1_batch_number, &keys)
+        })
+        .await
+        .unwrap()
+    }
+
+    /// Returns existing entries with keys in the specified range (in the ascending key order).
+    /// At most `limit` entries are returned.
+    pub async fn entries_in_range(
+        self,
+        l1_batch_number: L1BatchNumber,
+        key_range: ops::RangeInclusive<Key>,
+        limit: usize,
+    ) -> Result<Vec<TreeEntry>, NoVersionError> {
+        tokio::task::spawn_blocking(move || {
+            self.inner
+                .entries_in_range(l1_batch_number, key_range, limit)
+        })
+        .await
+        .unwrap()
+    }
+
+    /// Returns the tree root hash after the specified L1 batch, or `None` if the corresponding tree version
+    /// is missing (e.g., if it was pruned).
+    pub async fn root_hash_for_l1_batch(self, l1_batch_number: L1BatchNumber) -> Option<H256> {
+        tokio::task::spawn_blocking(move || self.inner.root_hash_for_l1_batch(l1_batch_number))
+            .await
+            .unwrap()
+    }
+
+    /// Returns the number of tree leaves after the specified L1 batch, or `None` if the corresponding tree version
+    /// is missing.
+    pub async fn leaf_count_for_l1_batch(self, l1_batch_number: L1BatchNumber) -> Option<u64> {
+        tokio::task::spawn_blocking(move || self.inner.leaf_count_for_l1_batch(l1_batch_number))
             .await
             .unwrap()
     }
+
+    pub async fn verify_subtree_consistency(
+        self,
+        l1_batch_number: L1BatchNumber,
+        key: Key,
+        depth: usize,
+    ) -> Result<(), ConsistencyError> {
+        tokio::task::spawn_blocking(move || {
+            self.inner
+                .verify_subtree_consistency(l1_batch_number, key, depth)
+        })
+        .await
+        .unwrap()
+    }
 }
 
 /// Lazily initialized [`AsyncTreeReader`].
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LazyAsyncTreeReader(pub(super) watch::Receiver<Option<AsyncTreeReader>>);
 
 impl LazyAsyncTreeReader {
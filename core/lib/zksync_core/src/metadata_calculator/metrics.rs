@@ -110,6 +110,10 @@ pub(super) struct MetadataCalculatorMetrics {
     /// Number of changes loaded from Postgres in a specific loading stage.
     #[metrics(buckets = COUNTS_BUCKETS)]
     load_changes_count: Family<LoadChangesStage, Histogram<usize>>,
+    /// Oldest Merkle tree version retained by the tree pruner.
+    pub pruning_retained_version: Gauge<u64>,
+    /// Whether tree updates are currently throttled because of high Postgres latency (1) or not (0).
+    pub is_throttled: Gauge<u64>,
 }
 
 impl MetadataCalculatorMetrics {
@@ -175,6 +179,7 @@ impl MetadataCalculator {
 #[metrics(label = "stage", rename_all = "snake_case")]
 pub(super) enum RecoveryStage {
     LoadChunkStarts,
+    Checkpoint,
     Finalize,
 }
 
@@ -183,8 +188,7 @@ pub(super) enum RecoveryStage {
 pub(super) enum ChunkRecoveryStage {
     AcquireConnection,
     LoadEntries,
-    LockTree,
-    ExtendTree,
+    WaitForTree,
 }
 
 /// Metrics for Merkle tree recovery driven by the metadata calculator.
@@ -200,6 +204,9 @@ pub(super) struct MetadataCalculatorRecoveryMetrics {
     /// Latency of a chunk recovery stage.
     #[metrics(buckets = Buckets::LATENCIES, unit = Unit::Seconds)]
     pub chunk_latency: Family<ChunkRecoveryStage, Histogram<Duration>>,
+    /// Number of chunks persisted in a single recovery checkpoint.
+    #[metrics(buckets = Buckets::linear(1.0..=20.0, 1.0))]
+    pub checkpoint_chunk_count: Histogram<usize>,
 }
 
 #[vise::register]
@@ -8,28 +8,42 @@ use std::{
 };
 
 use anyhow::Context as _;
-use tokio::sync::watch;
+use tokio::sync::{mpsc, watch};
 use zksync_config::configs::{
     chain::OperationsManagerConfig,
-    database::{MerkleTreeConfig, MerkleTreeMode},
+    database::{MerkleTreeCompactionStyle, MerkleTreeConfig, MerkleTreeMode},
 };
 use zksync_dal::{ConnectionPool, Core};
 use zksync_health_check::{HealthUpdater, ReactiveHealthCheck};
 use zksync_object_store::ObjectStore;
 
-pub use self::helpers::LazyAsyncTreeReader;
-pub(crate) use self::helpers::{AsyncTreeReader, L1BatchWithLogs, MerkleTreeInfo};
+pub use self::{
+    export::MerkleTreeSnapshotExporter,
+    helpers::LazyAsyncTreeReader,
+    rollback::{TreeRollbackError, TreeRollbackHandle},
+    verifier::{MerkleTreeConsistencyBreaker, MerkleTreeVerifier},
+};
 use self::{
     helpers::{create_db, Delayer, GenericAsyncTree, MerkleTreeHealth},
+    pruning::MerkleTreePruningTask,
+    rollback::TreeRollbackRequest,
     updater::TreeUpdater,
 };
+pub(crate) use self::{
+    helpers::{AsyncTreeReader, L1BatchWithLogs, MerkleTreeInfo},
+    rollback::check_tree_rollback,
+};
 
+mod export;
 mod helpers;
 mod metrics;
+mod pruning;
 mod recovery;
+mod rollback;
 #[cfg(test)]
 pub(crate) mod tests;
 mod updater;
+mod verifier;
 
 /// Configuration of [`MetadataCalculator`].
 #[derive(Debug, Clone)]
@@ -59,6 +73,25 @@ pub struct MetadataCalculatorConfig {
     pub memtable_capacity: usize,
     /// Timeout to wait for the Merkle tree database to run compaction on stalled writes.
     pub stalled_writes_timeout: Duration,
+    /// Compaction style for large RocksDB column families (i.e., ones storing tree nodes).
+    pub compaction_style: MerkleTreeCompactionStyle,
+    /// Whether to store tree leaves in a separate RocksDB column family. Only has an effect when creating
+    /// a new database.
+    pub separate_leaves_column_family: bool,
+    /// Number of threads used to hash tree nodes. If not specified, the global `rayon` thread pool will be used.
+    pub hashing_thread_count: Option<usize>,
+    /// Retention period for old tree versions measured by the age of the corresponding L1 batches.
+    /// If not specified, old tree versions are not pruned.
+    pub pruning_retention: Option<Duration>,
+    /// Number of L1 batches the tree intentionally lags behind the last sealed L1 batch.
+    pub lag_l1_batches: u32,
+    /// Latency threshold for loading L1 batch data from Postgres. If the average latency exceeds the threshold,
+    /// the tree throttles itself by processing a single L1 batch per iteration and waiting for `delay_interval`
+    /// between iterations. If not specified, the tree is never throttled.
+    pub throttle_latency_threshold: Option<Duration>,
+    /// Whether to keep the tree database in RAM rather than on disk. If set, `db_path` is only used
+    /// as the database name.
+    pub in_memory: bool,
 }
 
 impl MetadataCalculatorConfig {
@@ -77,6 +110,13 @@ impl MetadataCalculatorConfig {
             include_indices_and_filters_in_block_cache: false,
             memtable_capacity: merkle_tree_config.memtable_capacity(),
             stalled_writes_timeout: merkle_tree_config.stalled_writes_timeout(),
+            compaction_style: merkle_tree_config.compaction_style(),
+            separate_leaves_column_family: merkle_tree_config.separate_leaves_column_family(),
+            hashing_thread_count: merkle_tree_config.hashing_thread_count,
+            pruning_retention: merkle_tree_config.pruning_retention(),
+            lag_l1_batches: merkle_tree_config.lag_l1_batches(),
+            throttle_latency_threshold: merkle_tree_config.throttle_latency_threshold(),
+            in_memory: merkle_tree_config.in_memory,
         }
     }
 }
@@ -86,6 +126,8 @@ pub struct MetadataCalculator {
     config: MetadataCalculatorConfig,
     tree_reader: watch::Sender<Option<AsyncTreeReader>>,
     object_store: Option<Arc<dyn ObjectStore>>,
+    tree_snapshot_store: Option<Arc<dyn ObjectStore>>,
+    rollback_requests: Option<mpsc::Receiver<TreeRollbackRequest>>,
     delayer: Delayer,
     health_updater: HealthUpdater,
     max_l1_batches_per_iter: usize,
@@ -111,6 +153,8 @@ impl MetadataCalculator {
         Ok(Self {
             tree_reader: watch::channel(None).0,
             object_store,
+            tree_snapshot_store: None,
+            rollback_requests: None,
             delayer: Delayer::new(config.delay_interval),
             health_updater,
             max_l1_batches_per_iter: config.max_l1_batches_per_iter,
@@ -118,6 +162,23 @@ impl MetadataCalculator {
         })
     }
 
+    /// Sets the object store containing tree snapshots exported by [`MerkleTreeSnapshotExporter`].
+    /// If the tree needs to be recovered and the store contains a snapshot for the recovered L1 batch,
+    /// the tree will be recovered from this snapshot rather than from Postgres.
+    #[must_use]
+    pub fn with_tree_snapshot_store(mut self, store: Arc<dyn ObjectStore>) -> Self {
+        self.tree_snapshot_store = Some(store);
+        self
+    }
+
+    /// Makes the calculator process rollback requests sent via the provided `handle`. Only a single calculator
+    /// can be attached to a handle; attaching another calculator will have no effect.
+    #[must_use]
+    pub fn with_rollback_handle(mut self, handle: &TreeRollbackHandle) -> Self {
+        self.rollback_requests = handle.take_receiver();
+        self
+    }
+
     /// Returns a health check for this calculator.
     pub fn tree_health_check(&self) -> ReactiveHealthCheck {
         self.health_updater.subscribe()
@@ -155,11 +216,22 @@ impl MetadataCalculator {
     ) -> anyhow::Result<()> {
         let tree = self.create_tree().await?;
         let tree = tree
-            .ensure_ready(&pool, &stop_receiver, &self.health_updater)
+            .ensure_ready(
+                &pool,
+                self.tree_snapshot_store.as_deref(),
+                &stop_receiver,
+                &self.health_updater,
+            )
             .await?;
-        let Some(tree) = tree else {
+        let Some(mut tree) = tree else {
             return Ok(()); // recovery was aborted because a stop signal was received
         };
+        if let Some(thread_count) = self.config.hashing_thread_count {
+            tracing::info!(
+                "Using dedicated thread pool with {thread_count} threads for Merkle tree hashing"
+            );
+            tree.use_dedicated_thread_pool(thread_count);
+        }
         let tree_reader = tree.reader();
         tracing::info!(
             "Merkle tree is initialized and ready to process L1 batches: {:?}",
@@ -167,9 +239,41 @@ impl MetadataCalculator {
         );
         self.tree_reader.send_replace(Some(tree_reader));
 
-        let updater = TreeUpdater::new(tree, self.max_l1_batches_per_iter, self.object_store);
+        let pruning_tasks = self.config.pruning_retention.map(|retention| {
+            let (pruner, pruner_handle) = tree.pruner();
+            let pruning_task = MerkleTreePruningTask::new(pruner_handle, pool.clone(), retention);
+            let pruner_task = tokio::task::spawn_blocking(|| pruner.run());
+            let pruning_task = tokio::spawn(pruning_task.run(stop_receiver.clone()));
+            (pruner_task, pruning_task)
+        });
+
+        let updater = TreeUpdater::new(
+            tree,
+            self.max_l1_batches_per_iter,
+            self.config.lag_l1_batches,
+            self.config.throttle_latency_threshold,
+            self.object_store,
+        );
         updater
-            .loop_updating_tree(self.delayer, &pool, stop_receiver, self.health_updater)
-            .await
+            .loop_updating_tree(
+                self.delayer,
+                &pool,
+                stop_receiver,
+                self.rollback_requests,
+                self.health_updater,
+            )
+            .await?;
+        if let Some((pruner_task, pruning_task)) = pruning_tasks {
+            // The pruning task aborts the pruner on exit (including on error), so the pruner thread
+            // is guaranteed to finish after the task.
+            let pruning_result = pruning_task
+                .await
+                .context("Merkle tree pruning task panicked")?;
+            pruner_task
+                .await
+                .context("Merkle tree pruner thread panicked")?;
+            pruning_result?;
+        }
+        Ok(())
     }
 }
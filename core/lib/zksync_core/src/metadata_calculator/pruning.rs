@@ -0,0 +1,122 @@
+//! Retention-based pruning of old Merkle tree versions.
+
+use std::time::Duration;
+
+use tokio::sync::watch;
+use zksync_dal::{ConnectionPool, Core, CoreDal};
+use zksync_merkle_tree::MerkleTreePrunerHandle;
+use zksync_utils::time::seconds_since_epoch;
+
+use super::metrics::METRICS;
+
+/// Task limiting tree versions retained by the Merkle tree pruner based on the age of the corresponding L1 batches.
+/// The pruner itself runs on a dedicated thread and is aborted once this task is stopped.
+#[derive(Debug)]
+pub(super) struct MerkleTreePruningTask {
+    handle: MerkleTreePrunerHandle,
+    pool: ConnectionPool<Core>,
+    retention: Duration,
+    poll_interval: Duration,
+}
+
+impl MerkleTreePruningTask {
+    const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+    pub fn new(
+        handle: MerkleTreePrunerHandle,
+        pool: ConnectionPool<Core>,
+        retention: Duration,
+    ) -> Self {
+        // Do not prune anything until the retained version is computed.
+        handle.set_retained_version_limit(0);
+        Self {
+            handle,
+            pool,
+            retention,
+            poll_interval: Self::DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    /// Updates the retained version limit for the pruner. Returns the new limit, or `None` if there are
+    /// no L1 batches old enough to be pruned.
+    async fn update_retained_version(&self) -> anyhow::Result<Option<u64>> {
+        let cutoff_timestamp = seconds_since_epoch().saturating_sub(self.retention.as_secs());
+
+        let mut storage = self.pool.connection_tagged("metadata_calculator").await?;
+        let last_pruned_l1_batch = storage
+            .blocks_dal()
+            .get_latest_l1_batch_before_timestamp(cutoff_timestamp)
+            .await?;
+        drop(storage);
+
+        let Some(last_pruned_l1_batch) = last_pruned_l1_batch else {
+            return Ok(None);
+        };
+        // Tree version `N` corresponds to the tree state after processing L1 batch #N.
+        let retained_version = u64::from(last_pruned_l1_batch.0) + 1;
+        self.handle.set_retained_version_limit(retained_version);
+        METRICS.pruning_retained_version.set(retained_version);
+        tracing::debug!(
+            "Set retained Merkle tree version to {retained_version} (L1 batches older than {cutoff_timestamp} are pruned)"
+        );
+        Ok(Some(retained_version))
+    }
+
+    pub async fn run(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        tracing::info!(
+            "Starting Merkle tree pruning task with {:?} retention",
+            self.retention
+        );
+        while !*stop_receiver.borrow_and_update() {
+            self.update_retained_version().await?;
+            // Error here corresponds to a timeout w/o `stop_receiver` changed; we're OK with this.
+            tokio::time::timeout(self.poll_interval, stop_receiver.changed())
+                .await
+                .ok();
+        }
+        tracing::info!("Stop signal received, Merkle tree pruning task is shutting down");
+        self.handle.abort();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+    use zksync_config::configs::database::MerkleTreeMode;
+
+    use super::*;
+    use crate::metadata_calculator::{
+        helpers::{create_db, AsyncTree},
+        tests::{mock_config, reset_db_state, run_calculator, setup_calculator},
+    };
+
+    #[tokio::test]
+    async fn pruning_tree_versions_by_l1_batch_age() {
+        let pool = ConnectionPool::<Core>::test_pool().await;
+        let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+        let (calculator, _) = setup_calculator(temp_dir.path(), &pool).await;
+        reset_db_state(&pool, 5).await;
+        run_calculator(calculator, pool.clone()).await;
+
+        let db = create_db(mock_config(&temp_dir.path().join("new")))
+            .await
+            .unwrap();
+        let tree = AsyncTree::new(db, MerkleTreeMode::Full);
+        let (mut pruner, pruner_handle) = tree.pruner();
+
+        // Mock L1 batches have timestamps close to UNIX epoch, so none of them are old enough to be pruned.
+        let retention = Duration::from_secs(seconds_since_epoch() + 3_600);
+        let mut task = MerkleTreePruningTask::new(pruner_handle, pool.clone(), retention);
+        assert_eq!(task.update_retained_version().await.unwrap(), None);
+        assert!(pruner.run_once().is_none());
+
+        task.retention = Duration::from_secs(3_600);
+        let retained_version = task.update_retained_version().await.unwrap();
+        assert_eq!(retained_version, Some(6));
+        let stats = pruner.run_once().unwrap();
+        // The latest tree version must be retained regardless of the retention period.
+        assert_eq!(stats.target_retained_version, 5);
+        assert_eq!(stats.deleted_stale_key_versions.end, 6);
+    }
+}
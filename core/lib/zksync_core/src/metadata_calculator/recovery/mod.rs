@@ -11,19 +11,32 @@
 //! - Tree is ready for normal operation (i.e., it's not empty and is not recovering).
 //!
 //! If recovery is necessary, it starts / resumes by loading the Postgres snapshot in chunks
-//! and feeding each chunk to the tree. Chunks are loaded concurrently since this is the most
-//! I/O-heavy operation; the concurrency is naturally limited by the number of connections to
-//! Postgres in the supplied connection pool, but we explicitly use a [`Semaphore`] to control it
-//! in order to not run into DB timeout errors. Before starting recovery in chunks, we filter out
-//! chunks that have already been recovered by checking if the first key in a chunk is present
-//! in the tree. (Note that for this to work, chunks **must** always be defined in the same way.)
+//! and feeding each chunk to the tree. Chunks are loaded by multiple concurrent workers since this
+//! is the most I/O-heavy operation; the concurrency is naturally limited by the number of connections
+//! to Postgres in the supplied connection pool, but we explicitly use a [`Semaphore`] to control it
+//! in order to not run into DB timeout errors. Loaded chunks are passed to a single tree writer,
+//! which persists all chunks available at the moment as a single *checkpoint*. Combining chunks
+//! amortizes RocksDB writes and allows hashing independent subtrees in parallel; at the same time,
+//! the number of entries in a checkpoint is limited to bound RAM usage.
+//!
+//! Each checkpoint is persisted atomically and consists of whole chunks. Thus, before starting recovery
+//! in chunks, we filter out chunks that have already been recovered by checking if the first key in a chunk
+//! is present in the tree. (Note that for this to work, chunks **must** always be defined in the same way.)
 //!
 //! The recovery logic is fault-tolerant and supports graceful shutdown. If recovery is interrupted,
-//! recovery of the remaining chunks will continue when Metadata calculator is restarted.
+//! recovery of the remaining chunks will continue from the last persisted checkpoint when Metadata calculator
+//! is restarted.
 //!
 //! Recovery performs basic sanity checks to ensure that the tree won't end up containing garbage data.
 //! E.g., it's checked that the tree always recovers from the same snapshot; that the tree root hash
 //! after recovery matches one in the Postgres snapshot etc.
+//!
+//! If a tree snapshot for the recovered L1 batch was exported to an object store (see [`MerkleTreeSnapshotExporter`]),
+//! chunks are loaded from the object store rather than from Postgres. Chunks in an exported snapshot are defined
+//! in the same way as for the Postgres snapshot; thus, the recovery source may change between restarts
+//! as long as both sources result in the same number of chunks.
+//!
+//! [`MerkleTreeSnapshotExporter`]: super::MerkleTreeSnapshotExporter
 
 use std::{
     fmt, ops,
@@ -33,16 +46,18 @@ use std::{
 use anyhow::Context as _;
 use async_trait::async_trait;
 use futures::future;
-use tokio::sync::{watch, Mutex, Semaphore};
-use zksync_dal::{Connection, ConnectionPool, Core, CoreDal};
+use tokio::sync::{mpsc, watch, Semaphore};
+use zksync_dal::{ConnectionPool, Core, CoreDal};
 use zksync_health_check::HealthUpdater;
-use zksync_merkle_tree::TreeEntry;
+use zksync_merkle_tree::{Key, TreeEntry};
+use zksync_object_store::ObjectStore;
 use zksync_types::{
     snapshots::{uniform_hashed_keys_chunk, SnapshotRecoveryStatus},
     MiniblockNumber, H256,
 };
 
 use super::{
+    export::{self, TreeSnapshotChunk, TreeSnapshotChunkKey, TreeSnapshotHeader},
     helpers::{AsyncTree, AsyncTreeRecovery, GenericAsyncTree, MerkleTreeHealth},
     metrics::{ChunkRecoveryStage, RecoveryStage, RECOVERY_METRICS},
 };
@@ -116,10 +131,6 @@ struct SnapshotParameters {
 }
 
 impl SnapshotParameters {
-    /// This is intentionally not configurable because chunks must be the same for the entire recovery
-    /// (i.e., not changed after a node restart).
-    const DESIRED_CHUNK_SIZE: u64 = 200_000;
-
     async fn new(
         pool: &ConnectionPool<Core>,
         recovery: &SnapshotRecoveryStatus,
@@ -141,25 +152,78 @@ impl SnapshotParameters {
         })
     }
 
+    /// Creates parameters for recovery from a Merkle tree snapshot, checking the snapshot header against
+    /// the snapshot recovery data in Postgres.
+    async fn from_tree_snapshot(
+        pool: &ConnectionPool<Core>,
+        recovery: &SnapshotRecoveryStatus,
+        header: &TreeSnapshotHeader,
+    ) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            header.l1_batch_number == recovery.l1_batch_number
+                && header.root_hash == recovery.l1_batch_root_hash,
+            "Merkle tree snapshot for L1 batch #{} with root hash {:?} doesn't match snapshot recovery \
+             status ({recovery:?})",
+            header.l1_batch_number,
+            header.root_hash
+        );
+        // Chunk boundaries must match the ones used for recovery from Postgres, which are determined
+        // by the number of storage logs recovered from the snapshot.
+        let params = Self::new(pool, recovery).await?;
+        anyhow::ensure!(
+            header.chunk_count() == params.chunk_count(),
+            "Unexpected chunk count in the Merkle tree snapshot header for L1 batch #{}: expected {} \
+             (based on storage logs in Postgres), got {}",
+            header.l1_batch_number,
+            params.chunk_count(),
+            header.chunk_count()
+        );
+        Ok(params)
+    }
+
+    /// The chunk size is intentionally not configurable because chunks must be the same for the entire recovery
+    /// (i.e., not changed after a node restart).
     fn chunk_count(&self) -> u64 {
-        self.log_count.div_ceil(Self::DESIRED_CHUNK_SIZE)
+        export::chunk_count(self.log_count)
     }
 }
 
+/// Source of tree entries for recovery.
+#[derive(Debug, Clone, Copy)]
+enum RecoverySource<'a> {
+    /// Postgres snapshot.
+    Postgres,
+    /// Tree snapshot exported to an object store.
+    TreeSnapshot {
+        store: &'a dyn ObjectStore,
+        header: &'a TreeSnapshotHeader,
+    },
+}
+
 /// Options for tree recovery.
 #[derive(Debug)]
 struct RecoveryOptions<'a> {
     chunk_count: u64,
     concurrency_limit: usize,
+    /// Maximum number of tree entries persisted in a single checkpoint. The limit is soft: a checkpoint
+    /// always contains at least one chunk, and chunks are never split among checkpoints.
+    checkpoint_entry_limit: usize,
     events: Box<dyn HandleRecoveryEvent + 'a>,
+    source: RecoverySource<'a>,
+}
+
+impl RecoveryOptions<'_> {
+    const DEFAULT_CHECKPOINT_ENTRY_LIMIT: usize = 1_000_000;
 }
 
 impl GenericAsyncTree {
     /// Ensures that the tree is ready for the normal operation, recovering it from a Postgres snapshot
-    /// if necessary.
+    /// if necessary. If `tree_snapshot_store` is specified and contains a tree snapshot for the recovered L1 batch,
+    /// the tree is recovered from this snapshot instead.
     pub async fn ensure_ready(
         self,
         pool: &ConnectionPool<Core>,
+        tree_snapshot_store: Option<&dyn ObjectStore>,
         stop_receiver: &watch::Receiver<bool>,
         health_updater: &HealthUpdater,
     ) -> anyhow::Result<Option<AsyncTree>> {
@@ -193,12 +257,35 @@ impl GenericAsyncTree {
             }
         };
 
-        let snapshot = SnapshotParameters::new(pool, &snapshot_recovery).await?;
+        let tree_snapshot_header = match tree_snapshot_store {
+            Some(store) => {
+                TreeSnapshotHeader::load(store, snapshot_recovery.l1_batch_number).await?
+            }
+            None => None,
+        };
+        let (snapshot, source) = match (tree_snapshot_store, &tree_snapshot_header) {
+            (Some(store), Some(header)) => {
+                tracing::info!(
+                    "Found Merkle tree snapshot for L1 batch #{} in the object store; recovering from it",
+                    header.l1_batch_number
+                );
+                let snapshot =
+                    SnapshotParameters::from_tree_snapshot(pool, &snapshot_recovery, header)
+                        .await?;
+                (snapshot, RecoverySource::TreeSnapshot { store, header })
+            }
+            _ => {
+                let snapshot = SnapshotParameters::new(pool, &snapshot_recovery).await?;
+                (snapshot, RecoverySource::Postgres)
+            }
+        };
         tracing::debug!("Obtained snapshot parameters: {snapshot:?}");
         let recovery_options = RecoveryOptions {
             chunk_count: snapshot.chunk_count(),
             concurrency_limit: pool.max_size() as usize,
+            checkpoint_entry_limit: RecoveryOptions::DEFAULT_CHECKPOINT_ENTRY_LIMIT,
             events: Box::new(RecoveryHealthUpdater::new(health_updater)),
+            source,
         };
         tree.recover(snapshot, recovery_options, pool, stop_receiver)
             .await
@@ -217,15 +304,18 @@ impl AsyncTreeRecovery {
         let chunks: Vec<_> = (0..chunk_count)
             .map(|chunk_id| uniform_hashed_keys_chunk(chunk_id, chunk_count))
             .collect();
+        let source = options.source;
+        let source_name = match source {
+            RecoverySource::Postgres => "Postgres snapshot",
+            RecoverySource::TreeSnapshot { .. } => "tree snapshot in object store",
+        };
         tracing::info!(
-            "Recovering Merkle tree from Postgres snapshot in {chunk_count} concurrent chunks"
+            "Recovering Merkle tree from {source_name} in {chunk_count} concurrent chunks"
         );
 
-        let mut storage = pool.connection().await?;
         let remaining_chunks = self
-            .filter_chunks(&mut storage, snapshot.miniblock, &chunks)
+            .filter_chunks(source, pool, snapshot.miniblock, &chunks)
             .await?;
-        drop(storage);
         options
             .events
             .recovery_started(chunk_count, chunk_count - remaining_chunks.len() as u64);
@@ -234,33 +324,65 @@ impl AsyncTreeRecovery {
             remaining_chunks.len()
         );
 
-        let tree = Mutex::new(self);
+        let (chunk_sender, chunk_receiver) = mpsc::channel(options.concurrency_limit);
         let semaphore = Semaphore::new(options.concurrency_limit);
-        let chunk_tasks = remaining_chunks.into_iter().map(|chunk| async {
-            let _permit = semaphore
-                .acquire()
-                .await
-                .context("semaphore is never closed")?;
-            options.events.chunk_started().await;
-            Self::recover_key_chunk(&tree, snapshot.miniblock, chunk, pool, stop_receiver).await?;
-            options.events.chunk_recovered().await;
-            anyhow::Ok(())
+        let events = &*options.events;
+        let load_tasks = remaining_chunks.into_iter().map(|chunk_id| {
+            let chunk = chunks[chunk_id as usize].clone();
+            let chunk_sender = chunk_sender.clone();
+            let semaphore = &semaphore;
+            async move {
+                let _permit = semaphore
+                    .acquire()
+                    .await
+                    .context("semaphore is never closed")?;
+                events.chunk_started().await;
+                let entries = match source {
+                    RecoverySource::Postgres => {
+                        Self::load_key_chunk(snapshot.miniblock, chunk, pool, stop_receiver).await?
+                    }
+                    RecoverySource::TreeSnapshot { store, header } => {
+                        let key = TreeSnapshotChunkKey {
+                            l1_batch_number: header.l1_batch_number,
+                            chunk_id,
+                        };
+                        Self::load_tree_snapshot_chunk(store, key, chunk, stop_receiver).await?
+                    }
+                };
+                let Some(entries) = entries else {
+                    return Ok(()); // A stop signal was received
+                };
+
+                let wait_latency =
+                    RECOVERY_METRICS.chunk_latency[&ChunkRecoveryStage::WaitForTree].start();
+                // Sending can only fail if the tree writer has stopped, in which case the chunk is not needed.
+                chunk_sender.send(entries).await.ok();
+                wait_latency.observe();
+                anyhow::Ok(())
+            }
         });
-        future::try_join_all(chunk_tasks).await?;
+        let load_chunks = future::try_join_all(load_tasks);
+        drop(chunk_sender); // Allows the tree writer to terminate once all chunks are loaded
+        let write_chunks = self.write_chunks(
+            chunk_receiver,
+            options.checkpoint_entry_limit,
+            events,
+            stop_receiver,
+        );
+        tokio::try_join!(load_chunks, write_chunks)?;
 
         if *stop_receiver.borrow() {
             return Ok(None);
         }
 
         let finalize_latency = RECOVERY_METRICS.latency[&RecoveryStage::Finalize].start();
-        let mut tree = tree.into_inner();
-        let actual_root_hash = tree.root_hash().await;
+        let actual_root_hash = self.root_hash().await;
         anyhow::ensure!(
             actual_root_hash == snapshot.expected_root_hash,
             "Root hash of recovered tree {actual_root_hash:?} differs from expected root hash {:?}",
             snapshot.expected_root_hash
         );
-        let tree = tree.finalize().await;
+        let tree = self.finalize().await;
         let finalize_latency = finalize_latency.observe();
         tracing::info!(
             "Finished tree recovery in {finalize_latency:?}; resuming normal tree operation"
@@ -268,19 +390,38 @@ impl AsyncTreeRecovery {
         Ok(Some(tree))
     }
 
-    /// Filters out `key_chunks` for which recovery was successfully performed.
+    /// Filters out `key_chunks` for which recovery was successfully performed. Returns IDs of the remaining chunks.
     async fn filter_chunks(
         &mut self,
-        storage: &mut Connection<'_, Core>,
+        source: RecoverySource<'_>,
+        pool: &ConnectionPool<Core>,
         snapshot_miniblock: MiniblockNumber,
         key_chunks: &[ops::RangeInclusive<H256>],
-    ) -> anyhow::Result<Vec<ops::RangeInclusive<H256>>> {
+    ) -> anyhow::Result<Vec<u64>> {
         let chunk_starts_latency =
             RECOVERY_METRICS.latency[&RecoveryStage::LoadChunkStarts].start();
-        let chunk_starts = storage
-            .storage_logs_dal()
-            .get_chunk_starts_for_miniblock(snapshot_miniblock, key_chunks)
-            .await?;
+        let chunk_starts: Vec<Option<TreeEntry>> = match source {
+            RecoverySource::Postgres => {
+                let mut storage = pool.connection().await?;
+                let chunk_starts = storage
+                    .storage_logs_dal()
+                    .get_chunk_starts_for_miniblock(snapshot_miniblock, key_chunks)
+                    .await?;
+                let chunk_starts = chunk_starts.into_iter().map(|start| {
+                    start.map(|entry| TreeEntry {
+                        key: entry.tree_key(),
+                        value: entry.value,
+                        leaf_index: entry.leaf_index,
+                    })
+                });
+                chunk_starts.collect()
+            }
+            RecoverySource::TreeSnapshot { header, .. } => header
+                .chunk_starts
+                .iter()
+                .map(|start| start.map(TreeEntry::from))
+                .collect(),
+        };
         let chunk_starts_latency = chunk_starts_latency.observe();
         tracing::debug!(
             "Loaded start entries for {} chunks in {chunk_starts_latency:?}",
@@ -293,40 +434,80 @@ impl AsyncTreeRecovery {
             .filter_map(|(i, &start)| Some((i, start?)));
         let start_keys = existing_starts
             .clone()
-            .map(|(_, start_entry)| start_entry.tree_key())
+            .map(|(_, start_entry)| start_entry.key)
             .collect();
         let tree_entries = self.entries(start_keys).await;
 
         let mut output = vec![];
-        for (tree_entry, (i, db_entry)) in tree_entries.into_iter().zip(existing_starts) {
+        for (tree_entry, (i, snapshot_entry)) in tree_entries.into_iter().zip(existing_starts) {
             if tree_entry.is_empty() {
-                output.push(key_chunks[i].clone());
+                output.push(i as u64);
                 continue;
             }
             anyhow::ensure!(
-                tree_entry.value == db_entry.value && tree_entry.leaf_index == db_entry.leaf_index,
-                "Mismatch between entry for key {:0>64x} in Postgres snapshot for miniblock #{snapshot_miniblock} \
-                 ({db_entry:?}) and tree ({tree_entry:?}); the recovery procedure may be corrupted",
-                db_entry.key
+                tree_entry.value == snapshot_entry.value
+                    && tree_entry.leaf_index == snapshot_entry.leaf_index,
+                "Mismatch between entry for key {:0>64x} in snapshot for miniblock #{snapshot_miniblock} \
+                 ({snapshot_entry:?}) and tree ({tree_entry:?}); the recovery procedure may be corrupted",
+                snapshot_entry.key
             );
         }
         Ok(output)
     }
 
-    async fn recover_key_chunk(
-        tree: &Mutex<AsyncTreeRecovery>,
+    /// Persists chunks received from `chunk_receiver` in the tree. All chunks available at the moment
+    /// are combined into a single checkpoint, subject to `checkpoint_entry_limit`.
+    async fn write_chunks(
+        &mut self,
+        mut chunk_receiver: mpsc::Receiver<Vec<TreeEntry>>,
+        checkpoint_entry_limit: usize,
+        events: &dyn HandleRecoveryEvent,
+        stop_receiver: &watch::Receiver<bool>,
+    ) -> anyhow::Result<()> {
+        while let Some(mut checkpoint_entries) = chunk_receiver.recv().await {
+            let mut chunk_count = 1;
+            while checkpoint_entries.len() < checkpoint_entry_limit {
+                let Ok(entries) = chunk_receiver.try_recv() else {
+                    break;
+                };
+                checkpoint_entries.extend(entries);
+                chunk_count += 1;
+            }
+
+            if *stop_receiver.borrow() {
+                return Ok(());
+            }
+
+            let entry_count = checkpoint_entries.len();
+            let checkpoint_latency = RECOVERY_METRICS.latency[&RecoveryStage::Checkpoint].start();
+            self.extend(checkpoint_entries).await;
+            let checkpoint_latency = checkpoint_latency.observe();
+            RECOVERY_METRICS.checkpoint_chunk_count.observe(chunk_count);
+            tracing::debug!(
+                "Persisted checkpoint with {chunk_count} chunks ({entry_count} entries) in {checkpoint_latency:?}"
+            );
+
+            for _ in 0..chunk_count {
+                events.chunk_recovered().await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Loads tree entries for the specified chunk. Returns `None` if a stop signal was received.
+    async fn load_key_chunk(
         snapshot_miniblock: MiniblockNumber,
         key_chunk: ops::RangeInclusive<H256>,
         pool: &ConnectionPool<Core>,
         stop_receiver: &watch::Receiver<bool>,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<Option<Vec<TreeEntry>>> {
         let acquire_connection_latency =
             RECOVERY_METRICS.chunk_latency[&ChunkRecoveryStage::AcquireConnection].start();
         let mut storage = pool.connection().await?;
         acquire_connection_latency.observe();
 
         if *stop_receiver.borrow() {
-            return Ok(());
+            return Ok(None);
         }
 
         let entries_latency =
@@ -343,7 +524,7 @@ impl AsyncTreeRecovery {
         );
 
         if *stop_receiver.borrow() {
-            return Ok(());
+            return Ok(None);
         }
 
         // Sanity check: all entry keys must be distinct. Otherwise, we may end up writing non-final values
@@ -367,23 +548,57 @@ impl AsyncTreeRecovery {
                 leaf_index: entry.leaf_index,
             })
             .collect();
-        let lock_tree_latency =
-            RECOVERY_METRICS.chunk_latency[&ChunkRecoveryStage::LockTree].start();
-        let mut tree = tree.lock().await;
-        lock_tree_latency.observe();
+        Ok(Some(all_entries))
+    }
 
+    /// Loads tree entries for the specified chunk of an exported tree snapshot. Returns `None` if a stop signal
+    /// was received.
+    async fn load_tree_snapshot_chunk(
+        store: &dyn ObjectStore,
+        key: TreeSnapshotChunkKey,
+        key_chunk: ops::RangeInclusive<H256>,
+        stop_receiver: &watch::Receiver<bool>,
+    ) -> anyhow::Result<Option<Vec<TreeEntry>>> {
         if *stop_receiver.borrow() {
-            return Ok(());
+            return Ok(None);
         }
 
-        let extend_tree_latency =
-            RECOVERY_METRICS.chunk_latency[&ChunkRecoveryStage::ExtendTree].start();
-        tree.extend(all_entries).await;
-        let extend_tree_latency = extend_tree_latency.observe();
+        let entries_latency =
+            RECOVERY_METRICS.chunk_latency[&ChunkRecoveryStage::LoadEntries].start();
+        let chunk: TreeSnapshotChunk = store
+            .get(key)
+            .await
+            .with_context(|| format!("failed loading Merkle tree snapshot chunk {key:?}"))?;
+        let entries_latency = entries_latency.observe();
         tracing::debug!(
-            "Extended Merkle tree with entries for chunk {key_chunk:?} in {extend_tree_latency:?}"
+            "Loaded {} entries for chunk {key_chunk:?} from object store in {entries_latency:?}",
+            chunk.entries.len()
         );
-        Ok(())
+
+        if *stop_receiver.borrow() {
+            return Ok(None);
+        }
+
+        let key_range = Key::from_big_endian(key_chunk.start().as_bytes())
+            ..=Key::from_big_endian(key_chunk.end().as_bytes());
+        let entries: Vec<_> = chunk.entries.into_iter().map(TreeEntry::from).collect();
+        // Sanity check: entries must be sorted by key, distinct, and belong to the chunk.
+        for window in entries.windows(2) {
+            let [prev_entry, next_entry] = window else {
+                unreachable!();
+            };
+            anyhow::ensure!(
+                prev_entry.key < next_entry.key,
+                "Merkle tree snapshot chunk {key:?} is corrupted: entries {prev_entry:?} and {next_entry:?} \
+                 are not sorted by key"
+            );
+        }
+        if let Some(entry) = entries.iter().find(|entry| !key_range.contains(&entry.key)) {
+            anyhow::bail!(
+                "Merkle tree snapshot chunk {key:?} is corrupted: entry {entry:?} is outside the chunk key range"
+            );
+        }
+        Ok(Some(entries))
     }
 }
 
@@ -13,6 +13,7 @@ use zksync_config::configs::{
 use zksync_dal::CoreDal;
 use zksync_health_check::{CheckHealth, HealthStatus, ReactiveHealthCheck};
 use zksync_merkle_tree::{domain::ZkSyncTree, TreeInstruction};
+use zksync_object_store::ObjectStoreFactory;
 use zksync_types::{L1BatchNumber, ProtocolVersionId, StorageLog};
 
 use super::*;
@@ -50,8 +51,12 @@ async fn create_tree_recovery(path: &Path, l1_batch: L1BatchNumber) -> AsyncTree
     AsyncTreeRecovery::new(db, l1_batch.0.into(), MerkleTreeMode::Full)
 }
 
+/// `(concurrency_limit, checkpoint_entry_limit)` pairs.
+const RECOVERY_CASES: [(usize, usize); 3] = [(1, 1), (1, usize::MAX), (4, usize::MAX)];
+
+#[test_casing(3, RECOVERY_CASES)]
 #[tokio::test]
-async fn basic_recovery_workflow() {
+async fn basic_recovery_workflow(concurrency_limit: usize, checkpoint_entry_limit: usize) {
     let pool = ConnectionPool::<Core>::test_pool().await;
     let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
     let snapshot_recovery = prepare_recovery_snapshot_with_genesis(&pool, &temp_dir).await;
@@ -70,8 +75,10 @@ async fn basic_recovery_workflow() {
         let (health_check, health_updater) = ReactiveHealthCheck::new("tree");
         let recovery_options = RecoveryOptions {
             chunk_count,
-            concurrency_limit: 1,
+            concurrency_limit,
+            checkpoint_entry_limit,
             events: Box::new(RecoveryHealthUpdater::new(&health_updater)),
+            source: RecoverySource::Postgres,
         };
         let tree = tree
             .recover(snapshot, recovery_options, &pool, &stop_receiver)
@@ -85,6 +92,64 @@ async fn basic_recovery_workflow() {
     }
 }
 
+#[tokio::test]
+async fn recovery_from_tree_snapshot() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+    let snapshot_recovery = prepare_recovery_snapshot_with_genesis(&pool, &temp_dir).await;
+
+    // Export the tree created by `prepare_recovery_snapshot_with_genesis()`.
+    let object_store = ObjectStoreFactory::mock().create_store().await;
+    let db_path = temp_dir.path().join("init").join("new");
+    let db = create_db(mock_config(&db_path)).await.unwrap();
+    let tree_reader = AsyncTree::new(db, MerkleTreeMode::Full).reader();
+    let (_stop_sender, stop_receiver) = watch::channel(false);
+    let header = export::export_tree_snapshot(
+        &tree_reader,
+        &*object_store,
+        L1BatchNumber(1),
+        &stop_receiver,
+    )
+    .await
+    .unwrap()
+    .expect("tree snapshot export unexpectedly aborted");
+    let snapshot = SnapshotParameters::from_tree_snapshot(&pool, &snapshot_recovery, &header)
+        .await
+        .unwrap();
+
+    let mut corrupted_header = header.clone();
+    corrupted_header.chunk_starts.push(None);
+    let err = SnapshotParameters::from_tree_snapshot(&pool, &snapshot_recovery, &corrupted_header)
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("Unexpected chunk count"),
+        "{err:#}"
+    );
+
+    let tree = create_tree_recovery(&temp_dir.path().join("recovery"), L1BatchNumber(1)).await;
+    let (health_check, health_updater) = ReactiveHealthCheck::new("tree");
+    let recovery_options = RecoveryOptions {
+        chunk_count: snapshot.chunk_count(),
+        concurrency_limit: 4,
+        checkpoint_entry_limit: usize::MAX,
+        events: Box::new(RecoveryHealthUpdater::new(&health_updater)),
+        source: RecoverySource::TreeSnapshot {
+            store: &*object_store,
+            header: &header,
+        },
+    };
+    let tree = tree
+        .recover(snapshot, recovery_options, &pool, &stop_receiver)
+        .await
+        .unwrap()
+        .expect("Tree recovery unexpectedly aborted");
+
+    assert_eq!(tree.root_hash(), snapshot_recovery.l1_batch_root_hash);
+    let health = health_check.check_health().await;
+    assert_matches!(health.status(), HealthStatus::Ready);
+}
+
 async fn prepare_recovery_snapshot_with_genesis(
     pool: &ConnectionPool<Core>,
     temp_dir: &TempDir,
@@ -175,7 +240,10 @@ async fn recovery_fault_tolerance(chunk_count: u64) {
     let recovery_options = RecoveryOptions {
         chunk_count,
         concurrency_limit: 1,
+        // Persist each chunk in a separate checkpoint so that the number of recovered chunks is deterministic.
+        checkpoint_entry_limit: 1,
         events: Box::new(TestEventListener::new(1, stop_sender)),
+        source: RecoverySource::Postgres,
     };
     let snapshot = SnapshotParameters::new(&pool, &snapshot_recovery)
         .await
@@ -193,7 +261,9 @@ async fn recovery_fault_tolerance(chunk_count: u64) {
     let recovery_options = RecoveryOptions {
         chunk_count,
         concurrency_limit: 1,
+        checkpoint_entry_limit: 1,
         events: Box::new(TestEventListener::new(2, stop_sender).expect_recovered_chunks(1)),
+        source: RecoverySource::Postgres,
     };
     assert!(tree
         .recover(snapshot, recovery_options, &pool, &stop_receiver)
@@ -208,7 +278,9 @@ async fn recovery_fault_tolerance(chunk_count: u64) {
     let recovery_options = RecoveryOptions {
         chunk_count,
         concurrency_limit: 1,
+        checkpoint_entry_limit: 1,
         events: Box::new(TestEventListener::new(u64::MAX, stop_sender).expect_recovered_chunks(3)),
+        source: RecoverySource::Postgres,
     };
     let tree = tree
         .recover(snapshot, recovery_options, &pool, &stop_receiver)
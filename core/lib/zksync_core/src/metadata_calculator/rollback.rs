@@ -0,0 +1,151 @@
+//! Guarded rollback of the Merkle tree to a specific L1 batch.
+
+use std::sync::{Arc, Mutex};
+
+use anyhow::Context as _;
+use tokio::sync::{mpsc, oneshot};
+use zksync_dal::{Connection, Core, CoreDal};
+use zksync_merkle_tree::domain::ZkSyncTreeReader;
+use zksync_types::L1BatchNumber;
+
+/// Errors that can occur when rolling back the Merkle tree.
+#[derive(Debug, thiserror::Error)]
+pub enum TreeRollbackError {
+    /// Rollback was refused because it would leave the tree and Postgres in an inconsistent state.
+    #[error("Merkle tree rollback refused: {0}")]
+    Refused(String),
+    /// Rollback cannot be performed because the Merkle tree doesn't run in this process, or has stopped.
+    #[error("Merkle tree is not running")]
+    NotRunning,
+    #[error(transparent)]
+    Internal(#[from] anyhow::Error),
+}
+
+/// Checks whether the Merkle tree can be safely rolled back so that `last_l1_batch_to_keep` is its last L1 batch.
+///
+/// Rollback is refused if:
+///
+/// - The tree has no L1 batches after `last_l1_batch_to_keep`.
+/// - Postgres has no state root hash for `last_l1_batch_to_keep` (i.e., Postgres is behind the target).
+/// - Postgres has state root hashes for L1 batches after `last_l1_batch_to_keep` (i.e., Postgres is ahead
+///   of the target and must be rolled back together with the tree using the block reverter).
+/// - The tree version for `last_l1_batch_to_keep` is missing (e.g., pruned), or its root hash differs
+///   from the one in Postgres.
+pub(crate) async fn check_tree_rollback(
+    storage: &mut Connection<'_, Core>,
+    tree: &ZkSyncTreeReader,
+    last_l1_batch_to_keep: L1BatchNumber,
+) -> Result<(), TreeRollbackError> {
+    let next_tree_l1_batch = tree.next_l1_batch_number();
+    if next_tree_l1_batch <= last_l1_batch_to_keep + 1 {
+        return Err(TreeRollbackError::Refused(format!(
+            "tree has no L1 batches after #{last_l1_batch_to_keep} (next L1 batch for the tree is #{next_tree_l1_batch})"
+        )));
+    }
+
+    let last_l1_batch_with_metadata = storage
+        .blocks_dal()
+        .get_last_l1_batch_number_with_metadata()
+        .await
+        .context("failed loading last L1 batch with metadata")?;
+    if let Some(last_l1_batch_with_metadata) = last_l1_batch_with_metadata {
+        if last_l1_batch_with_metadata > last_l1_batch_to_keep {
+            return Err(TreeRollbackError::Refused(format!(
+                "Postgres contains tree data up to L1 batch #{last_l1_batch_with_metadata}, which is ahead of \
+                 the target L1 batch #{last_l1_batch_to_keep}; roll back Postgres together with the tree using the block reverter"
+            )));
+        }
+    }
+
+    let postgres_root_hash = storage
+        .blocks_dal()
+        .get_l1_batch_state_root(last_l1_batch_to_keep)
+        .await
+        .context("failed loading state root hash")?;
+    let Some(postgres_root_hash) = postgres_root_hash else {
+        return Err(TreeRollbackError::Refused(format!(
+            "Postgres has no state root hash for L1 batch #{last_l1_batch_to_keep}"
+        )));
+    };
+    let Some(tree_root_hash) = tree.root_hash_for_l1_batch(last_l1_batch_to_keep) else {
+        return Err(TreeRollbackError::Refused(format!(
+            "tree version for L1 batch #{last_l1_batch_to_keep} is missing (e.g., it was pruned)"
+        )));
+    };
+    if tree_root_hash != postgres_root_hash {
+        return Err(TreeRollbackError::Refused(format!(
+            "tree root hash for L1 batch #{last_l1_batch_to_keep} ({tree_root_hash:?}) differs from \
+             the one in Postgres ({postgres_root_hash:?})"
+        )));
+    }
+    Ok(())
+}
+
+#[derive(Debug)]
+pub(super) struct TreeRollbackRequest {
+    pub last_l1_batch_to_keep: L1BatchNumber,
+    pub response_sender: oneshot::Sender<Result<(), TreeRollbackError>>,
+}
+
+/// Handle allowing to roll back a running [`MetadataCalculator`](super::MetadataCalculator).
+/// Requests are processed by the calculator between tree updates.
+///
+/// The handle is shared between the calculator and the HTTP API server (so that rollbacks can be requested
+/// via the `admin` namespace); hence, it can only process requests if both run in the same process.
+#[derive(Debug, Clone)]
+pub struct TreeRollbackHandle {
+    request_sender: mpsc::Sender<TreeRollbackRequest>,
+    request_receiver: Arc<Mutex<Option<mpsc::Receiver<TreeRollbackRequest>>>>,
+}
+
+impl Default for TreeRollbackHandle {
+    fn default() -> Self {
+        let (request_sender, request_receiver) = mpsc::channel(1);
+        Self {
+            request_sender,
+            request_receiver: Arc::new(Mutex::new(Some(request_receiver))),
+        }
+    }
+}
+
+impl TreeRollbackHandle {
+    pub(super) fn take_receiver(&self) -> Option<mpsc::Receiver<TreeRollbackRequest>> {
+        self.request_receiver
+            .lock()
+            .expect("tree rollback handle is poisoned")
+            .take()
+    }
+
+    /// Rolls back the Merkle tree so that `last_l1_batch_to_keep` is its last L1 batch. Rollback is refused
+    /// if it would leave the tree inconsistent with Postgres (e.g., if Postgres is ahead or behind the target L1 batch).
+    pub async fn rollback(
+        &self,
+        last_l1_batch_to_keep: L1BatchNumber,
+    ) -> Result<(), TreeRollbackError> {
+        let is_receiver_taken = self
+            .request_receiver
+            .lock()
+            .expect("tree rollback handle is poisoned")
+            .is_none();
+        if !is_receiver_taken {
+            return Err(TreeRollbackError::NotRunning);
+        }
+
+        let (response_sender, response_receiver) = oneshot::channel();
+        let request = TreeRollbackRequest {
+            last_l1_batch_to_keep,
+            response_sender,
+        };
+        self.request_sender
+            .try_send(request)
+            .map_err(|err| match err {
+                mpsc::error::TrySendError::Full(_) => TreeRollbackError::Refused(
+                    "another Merkle tree rollback is in progress".to_owned(),
+                ),
+                mpsc::error::TrySendError::Closed(_) => TreeRollbackError::NotRunning,
+            })?;
+        response_receiver
+            .await
+            .map_err(|_| TreeRollbackError::NotRunning)?
+    }
+}
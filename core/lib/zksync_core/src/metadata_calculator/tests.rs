@@ -8,7 +8,7 @@ use tempfile::TempDir;
 use tokio::sync::{mpsc, watch};
 use zksync_config::configs::{
     chain::OperationsManagerConfig,
-    database::{MerkleTreeConfig, MerkleTreeMode},
+    database::{MerkleTreeCompactionStyle, MerkleTreeConfig, MerkleTreeMode},
 };
 use zksync_dal::{Connection, ConnectionPool, Core, CoreDal};
 use zksync_health_check::{CheckHealth, HealthStatus};
@@ -21,7 +21,10 @@ use zksync_types::{
 };
 use zksync_utils::u32_to_h256;
 
-use super::{GenericAsyncTree, L1BatchWithLogs, MetadataCalculator, MetadataCalculatorConfig};
+use super::{
+    metrics::METRICS, GenericAsyncTree, L1BatchWithLogs, MetadataCalculator,
+    MetadataCalculatorConfig, TreeRollbackError, TreeRollbackHandle,
+};
 use crate::{
     genesis::{insert_genesis_batch, GenesisParams},
     utils::testonly::{create_l1_batch, create_miniblock},
@@ -40,7 +43,7 @@ where
     }
 }
 
-pub(super) fn mock_config(db_path: &Path) -> MetadataCalculatorConfig {
+pub(crate) fn mock_config(db_path: &Path) -> MetadataCalculatorConfig {
     MetadataCalculatorConfig {
         db_path: db_path.to_str().unwrap().to_owned(),
         max_open_files: None,
@@ -52,6 +55,13 @@ pub(super) fn mock_config(db_path: &Path) -> MetadataCalculatorConfig {
         include_indices_and_filters_in_block_cache: false,
         memtable_capacity: 16 << 20,            // 16 MiB
         stalled_writes_timeout: Duration::ZERO, // writes should never be stalled in tests
+        compaction_style: MerkleTreeCompactionStyle::Level,
+        separate_leaves_column_family: true,
+        hashing_thread_count: None,
+        pruning_retention: None,
+        lag_l1_batches: 0,
+        throttle_latency_threshold: None,
+        in_memory: false,
     }
 }
 
@@ -254,6 +264,154 @@ async fn running_metadata_calculator_with_additional_blocks() {
     assert_eq!(root_hash_for_full_tree, updated_root_hash);
 }
 
+#[tokio::test]
+async fn rolling_back_running_tree() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+    let calculator = setup_lightweight_calculator(temp_dir.path(), &pool).await;
+    reset_db_state(&pool, 5).await;
+    run_calculator(calculator, pool.clone()).await;
+
+    let rollback_handle = TreeRollbackHandle::default();
+    let err = rollback_handle
+        .rollback(L1BatchNumber(3))
+        .await
+        .unwrap_err();
+    assert_matches!(err, TreeRollbackError::NotRunning);
+
+    let mut calculator = setup_lightweight_calculator(temp_dir.path(), &pool)
+        .await
+        .with_rollback_handle(&rollback_handle);
+    let (stop_sx, stop_rx) = watch::channel(false);
+    let (delay_sx, mut delay_rx) = mpsc::unbounded_channel();
+    calculator.delayer.delay_notifier = delay_sx;
+    let calculator_handle = tokio::spawn(calculator.run(pool.clone(), stop_rx));
+    let (next_l1_batch, _) = tokio::time::timeout(RUN_TIMEOUT, delay_rx.recv())
+        .await
+        .expect("metadata calculator timed out processing initial blocks")
+        .unwrap();
+    assert_eq!(next_l1_batch, L1BatchNumber(6));
+
+    // Postgres contains metadata for L1 batches after the target one.
+    let err = rollback_handle
+        .rollback(L1BatchNumber(3))
+        .await
+        .unwrap_err();
+    assert_matches!(err, TreeRollbackError::Refused(msg) if msg.contains("ahead"));
+
+    let mut storage = pool.connection().await.unwrap();
+    remove_l1_batches(&mut storage, L1BatchNumber(3)).await;
+    let expected_root_hash = storage
+        .blocks_dal()
+        .get_l1_batch_state_root(L1BatchNumber(3))
+        .await
+        .unwrap()
+        .unwrap();
+    drop(storage);
+
+    // Postgres doesn't contain the target L1 batch.
+    let err = rollback_handle
+        .rollback(L1BatchNumber(4))
+        .await
+        .unwrap_err();
+    assert_matches!(err, TreeRollbackError::Refused(_));
+
+    rollback_handle.rollback(L1BatchNumber(3)).await.unwrap();
+    let root_hash = loop {
+        let (next_l1_batch, root_hash) = tokio::time::timeout(RUN_TIMEOUT, delay_rx.recv())
+            .await
+            .expect("metadata calculator shut down prematurely")
+            .unwrap();
+        if next_l1_batch == L1BatchNumber(4) {
+            break root_hash;
+        }
+    };
+    assert_eq!(root_hash, expected_root_hash);
+
+    // The tree has nothing to roll back now.
+    let err = rollback_handle
+        .rollback(L1BatchNumber(3))
+        .await
+        .unwrap_err();
+    assert_matches!(err, TreeRollbackError::Refused(_));
+
+    stop_sx.send_replace(true);
+    run_with_timeout(RUN_TIMEOUT, calculator_handle)
+        .await
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn lagging_calculator() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+    let (mut merkle_tree_config, operation_config) =
+        create_config(temp_dir.path(), MerkleTreeMode::Lightweight);
+    merkle_tree_config.lag_l1_batches = Some(2);
+    let calculator =
+        setup_calculator_with_options(&merkle_tree_config, &operation_config, &pool, None).await;
+    reset_db_state(&pool, 5).await;
+    run_calculator(calculator, pool.clone()).await;
+
+    let calculator =
+        setup_calculator_with_options(&merkle_tree_config, &operation_config, &pool, None).await;
+    let tree = calculator.create_tree().await.unwrap();
+    let GenericAsyncTree::Ready(tree) = tree else {
+        panic!("Unexpected tree state: {tree:?}");
+    };
+    assert_eq!(tree.next_l1_batch_number(), L1BatchNumber(4));
+    let last_l1_batch_with_metadata = pool
+        .connection()
+        .await
+        .unwrap()
+        .blocks_dal()
+        .get_last_l1_batch_number_with_metadata()
+        .await
+        .unwrap();
+    assert_eq!(last_l1_batch_with_metadata, Some(L1BatchNumber(3)));
+}
+
+#[tokio::test]
+async fn throttled_calculator() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+    let (mut merkle_tree_config, operation_config) =
+        create_config(temp_dir.path(), MerkleTreeMode::Lightweight);
+    // Loading L1 batches will always exceed the threshold, so the calculator will always be throttled.
+    merkle_tree_config.throttle_latency_threshold_ms = Some(0);
+    let calculator =
+        setup_calculator_with_options(&merkle_tree_config, &operation_config, &pool, None).await;
+    reset_db_state(&pool, 5).await;
+    let merkle_tree_hash = run_calculator(calculator, pool.clone()).await;
+
+    assert_eq!(merkle_tree_hash, expected_tree_hash(&pool).await);
+    assert_eq!(METRICS.is_throttled.get(), 1);
+}
+
+#[tokio::test]
+async fn in_memory_calculator() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+    let (mut merkle_tree_config, operation_config) =
+        create_config(temp_dir.path(), MerkleTreeMode::Lightweight);
+    merkle_tree_config.in_memory = true;
+    let calculator =
+        setup_calculator_with_options(&merkle_tree_config, &operation_config, &pool, None).await;
+    reset_db_state(&pool, 3).await;
+    let merkle_tree_hash = run_calculator(calculator, pool.clone()).await;
+
+    assert_eq!(merkle_tree_hash, expected_tree_hash(&pool).await);
+    // Nothing should be persisted on disk.
+    assert!(!Path::new(&merkle_tree_config.path).exists());
+
+    // A restarted in-memory tree starts from scratch.
+    let calculator =
+        setup_calculator_with_options(&merkle_tree_config, &operation_config, &pool, None).await;
+    let tree = calculator.create_tree().await.unwrap();
+    assert_matches!(tree, GenericAsyncTree::Empty { .. });
+}
+
 #[tokio::test]
 async fn shutting_down_calculator() {
     let pool = ConnectionPool::<Core>::test_pool().await;
@@ -539,6 +697,11 @@ pub(super) async fn extend_db_state_from_l1_batch(
             .await
             .unwrap();
         insert_initial_writes_for_batch(storage, batch_number).await;
+        storage
+            .storage_logs_dedup_dal()
+            .mark_protective_reads_as_persisted(batch_number)
+            .await
+            .unwrap();
     }
 }
 
@@ -1,10 +1,15 @@
 //! Tree updater trait and its implementations.
 
-use std::{ops, sync::Arc, time::Instant};
+use std::{
+    ops,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use anyhow::Context as _;
 use futures::{future, FutureExt};
-use tokio::sync::watch;
+use tokio::sync::{mpsc, watch};
+use zksync_config::configs::database::MerkleTreeMode;
 use zksync_dal::{Connection, ConnectionPool, Core, CoreDal};
 use zksync_health_check::HealthUpdater;
 use zksync_merkle_tree::domain::TreeMetadata;
@@ -18,6 +23,7 @@ use zksync_types::{
 use super::{
     helpers::{AsyncTree, Delayer, L1BatchWithLogs},
     metrics::{TreeUpdateStage, METRICS},
+    rollback::{check_tree_rollback, TreeRollbackRequest},
     MetadataCalculator,
 };
 use crate::utils::wait_for_l1_batch;
@@ -26,6 +32,9 @@ use crate::utils::wait_for_l1_batch;
 pub(super) struct TreeUpdater {
     tree: AsyncTree,
     max_l1_batches_per_iter: usize,
+    lag_l1_batches: u32,
+    throttle_latency_threshold: Option<Duration>,
+    is_throttled: bool,
     object_store: Option<Arc<dyn ObjectStore>>,
 }
 
@@ -33,11 +42,16 @@ impl TreeUpdater {
     pub fn new(
         tree: AsyncTree,
         max_l1_batches_per_iter: usize,
+        lag_l1_batches: u32,
+        throttle_latency_threshold: Option<Duration>,
         object_store: Option<Arc<dyn ObjectStore>>,
     ) -> Self {
         Self {
             tree,
             max_l1_batches_per_iter,
+            lag_l1_batches,
+            throttle_latency_threshold,
+            is_throttled: false,
             object_store,
         }
     }
@@ -95,11 +109,13 @@ impl TreeUpdater {
         tracing::info!("Processing L1 batches #{l1_batch_numbers:?} in {tree_mode:?} mode");
         let first_l1_batch_number = L1BatchNumber(*l1_batch_numbers.start());
         let last_l1_batch_number = L1BatchNumber(*l1_batch_numbers.end());
+        let load_started_at = Instant::now();
         let mut l1_batch_data = L1BatchWithLogs::new(storage, first_l1_batch_number, tree_mode)
             .await
             .with_context(|| {
                 format!("failed fetching tree input for L1 batch #{first_l1_batch_number}")
             })?;
+        let mut total_load_latency = load_started_at.elapsed();
 
         let mut total_logs = 0;
         let mut updated_headers = vec![];
@@ -114,19 +130,25 @@ impl TreeUpdater {
             let load_next_l1_batch_task = async {
                 if l1_batch_number < last_l1_batch_number {
                     let next_l1_batch_number = l1_batch_number + 1;
-                    L1BatchWithLogs::new(storage, next_l1_batch_number, tree_mode)
-                        .await
-                        .with_context(|| {
-                            format!(
-                                "failed fetching tree input for L1 batch #{next_l1_batch_number}"
-                            )
-                        })
+                    let load_started_at = Instant::now();
+                    let next_l1_batch_data = L1BatchWithLogs::new(
+                        storage,
+                        next_l1_batch_number,
+                        tree_mode,
+                    )
+                    .await
+                    .with_context(|| {
+                        format!("failed fetching tree input for L1 batch #{next_l1_batch_number}")
+                    })?;
+                    Ok((next_l1_batch_data, load_started_at.elapsed()))
                 } else {
-                    Ok(None) // Don't need to load the next L1 batch after the last one we're processing.
+                    // Don't need to load the next L1 batch after the last one we're processing.
+                    Ok((None, Duration::ZERO))
                 }
             };
-            let ((header, metadata, object_key), next_l1_batch_data) =
+            let ((header, metadata, object_key), (next_l1_batch_data, load_latency)) =
                 future::try_join(process_l1_batch_task, load_next_l1_batch_task).await?;
+            total_load_latency += load_latency;
 
             let check_consistency_latency = METRICS.start_stage(TreeUpdateStage::CheckConsistency);
             Self::check_initial_writes_consistency(
@@ -174,10 +196,32 @@ impl TreeUpdater {
         self.tree.save().await?;
         save_rocksdb_latency.observe();
         MetadataCalculator::update_metrics(&updated_headers, total_logs, start);
+        self.update_throttling(total_load_latency / updated_headers.len() as u32);
 
         Ok(last_l1_batch_number + 1)
     }
 
+    /// Throttles or un-throttles the updater based on the average latency of loading an L1 batch from Postgres.
+    fn update_throttling(&mut self, load_latency_per_l1_batch: Duration) {
+        let Some(threshold) = self.throttle_latency_threshold else {
+            return;
+        };
+        let should_throttle = load_latency_per_l1_batch > threshold;
+        if should_throttle && !self.is_throttled {
+            tracing::info!(
+                "Loading L1 batch data from Postgres takes {load_latency_per_l1_batch:?} per L1 batch, which exceeds \
+                 the {threshold:?} threshold; throttling Merkle tree updates"
+            );
+        } else if !should_throttle && self.is_throttled {
+            tracing::info!(
+                "Loading L1 batch data from Postgres takes {load_latency_per_l1_batch:?} per L1 batch, which is within \
+                 the {threshold:?} threshold; lifting Merkle tree throttling"
+            );
+        }
+        self.is_throttled = should_throttle;
+        METRICS.is_throttled.set(should_throttle.into());
+    }
+
     async fn step(
         &mut self,
         mut storage: Connection<'_, Core>,
@@ -192,9 +236,35 @@ impl TreeUpdater {
             tracing::trace!("No L1 batches to seal: Postgres storage is empty");
             return Ok(());
         };
-        let last_requested_l1_batch =
-            next_l1_batch_to_seal.0 + self.max_l1_batches_per_iter as u32 - 1;
-        let last_requested_l1_batch = last_requested_l1_batch.min(last_sealed_l1_batch.0);
+        // A full tree requires protective reads, which may be persisted asynchronously after an L1 batch is sealed.
+        let last_ready_l1_batch = if self.tree.mode() == MerkleTreeMode::Full {
+            let first_l1_batch_without_protective_reads = storage
+                .storage_logs_dedup_dal()
+                .get_first_l1_batch_without_protective_reads(*next_l1_batch_to_seal)
+                .await
+                .context("failed loading first L1 batch without protective reads")?;
+            match first_l1_batch_without_protective_reads {
+                Some(L1BatchNumber(0)) => {
+                    tracing::trace!(
+                        "No L1 batches to seal: protective reads for genesis are not persisted"
+                    );
+                    return Ok(());
+                }
+                Some(number) => (number - 1).min(last_sealed_l1_batch),
+                None => last_sealed_l1_batch,
+            }
+        } else {
+            last_sealed_l1_batch
+        };
+        let last_ready_l1_batch =
+            L1BatchNumber(last_ready_l1_batch.0.saturating_sub(self.lag_l1_batches));
+        let max_l1_batches_per_iter = if self.is_throttled {
+            1
+        } else {
+            self.max_l1_batches_per_iter
+        };
+        let last_requested_l1_batch = next_l1_batch_to_seal.0 + max_l1_batches_per_iter as u32 - 1;
+        let last_requested_l1_batch = last_requested_l1_batch.min(last_ready_l1_batch.0);
         let l1_batch_numbers = next_l1_batch_to_seal.0..=last_requested_l1_batch;
         if l1_batch_numbers.is_empty() {
             tracing::trace!(
@@ -215,6 +285,7 @@ impl TreeUpdater {
         delayer: Delayer,
         pool: &ConnectionPool<Core>,
         mut stop_receiver: watch::Receiver<bool>,
+        mut rollback_requests: Option<mpsc::Receiver<TreeRollbackRequest>>,
         health_updater: HealthUpdater,
     ) -> anyhow::Result<()> {
         let Some(earliest_l1_batch) =
@@ -310,16 +381,40 @@ impl TreeUpdater {
                 tracing::trace!(
                     "Metadata calculator (next L1 batch: #{next_l1_batch_to_seal}) made progress from #{snapshot}"
                 );
-                future::ready(()).right_future()
+                let throttle_delay = self.is_throttled.then(|| delayer.delay_interval());
+                async move {
+                    if let Some(throttle_delay) = throttle_delay {
+                        tracing::debug!(
+                            "Metadata calculator is throttled; delaying next iteration by {throttle_delay:?}"
+                        );
+                        tokio::time::sleep(throttle_delay).await;
+                    }
+                }
+                .right_future()
             };
 
             // The delays we're operating with are reasonably small, but selecting between the delay
             // and the stop receiver still allows to be more responsive during shutdown.
+            let rollback_request = async {
+                match &mut rollback_requests {
+                    Some(requests) => requests.recv().await,
+                    None => future::pending().await,
+                }
+            };
             tokio::select! {
                 _ = stop_receiver.changed() => {
                     tracing::info!("Stop signal received, metadata_calculator is shutting down");
                     break;
                 }
+                Some(request) = rollback_request => {
+                    self.process_rollback_request(
+                        pool,
+                        request,
+                        &mut next_l1_batch_to_seal,
+                        &health_updater,
+                    )
+                    .await?;
+                }
                 () = delay => { /* The delay has passed */ }
             }
         }
@@ -327,6 +422,47 @@ impl TreeUpdater {
         Ok(())
     }
 
+    /// Processes a tree rollback request. Errors resulting from refused rollbacks are returned to the requester
+    /// rather than from this method.
+    async fn process_rollback_request(
+        &mut self,
+        pool: &ConnectionPool<Core>,
+        request: TreeRollbackRequest,
+        next_l1_batch_to_seal: &mut L1BatchNumber,
+        health_updater: &HealthUpdater,
+    ) -> anyhow::Result<()> {
+        let last_l1_batch_to_keep = request.last_l1_batch_to_keep;
+        tracing::info!(
+            "Received request to roll back Merkle tree to L1 batch #{last_l1_batch_to_keep}"
+        );
+        let mut storage = pool.connection_tagged("metadata_calculator").await?;
+        let check_result = check_tree_rollback(
+            &mut storage,
+            &self.tree.sync_reader(),
+            last_l1_batch_to_keep,
+        )
+        .await;
+        drop(storage);
+        if let Err(err) = &check_result {
+            tracing::warn!(
+                "Merkle tree rollback to L1 batch #{last_l1_batch_to_keep} failed: {err:#}"
+            );
+            request.response_sender.send(check_result).ok();
+            return Ok(());
+        }
+
+        self.tree.revert_logs(last_l1_batch_to_keep);
+        self.tree.save().await?;
+        *next_l1_batch_to_seal = self.tree.next_l1_batch_number();
+        tracing::info!(
+            "Rolled back Merkle tree to L1 batch #{last_l1_batch_to_keep}; next L1 batch for the tree is #{next_l1_batch_to_seal}"
+        );
+        let tree_info = self.tree.reader().info().await;
+        health_updater.update(tree_info.into());
+        request.response_sender.send(Ok(())).ok();
+        Ok(())
+    }
+
     async fn check_initial_writes_consistency(
         connection: &mut Connection<'_, Core>,
         l1_batch_number: L1BatchNumber,
@@ -0,0 +1,507 @@
+//! Background verification of the Merkle tree consistency.
+
+use std::{future, sync::Arc, time::Duration};
+
+use anyhow::Context as _;
+use rand::Rng;
+use tokio::sync::watch;
+use zksync_circuit_breaker::{CircuitBreaker, CircuitBreakerError};
+use zksync_contracts::PRE_BOOJUM_COMMIT_FUNCTION;
+use zksync_dal::{Connection, ConnectionPool, Core, CoreDal};
+use zksync_eth_client::EthInterface;
+use zksync_merkle_tree::Key;
+use zksync_types::{ethabi, L1BatchNumber, H256};
+
+use super::helpers::{AsyncTreeReader, LazyAsyncTreeReader};
+use crate::consistency_checker::ConsistencyChecker;
+
+/// Commit transaction fetched from L1. Cached since a single transaction usually commits multiple L1 batches.
+#[derive(Debug)]
+struct CommitTx {
+    hash: H256,
+    input: Vec<u8>,
+}
+
+/// Low-priority background task periodically verifying the Merkle tree:
+///
+/// - Checks the consistency of a random subtree (node hashes and leaf indices) for the latest tree version.
+/// - Compares tree root hashes with the ones in the calldata of commit transactions on L1.
+///
+/// Detected inconsistencies are not returned as errors; instead, they are reported via
+/// [`MerkleTreeConsistencyBreaker`], which stops the node.
+#[derive(Debug)]
+pub struct MerkleTreeVerifier {
+    tree_reader: LazyAsyncTreeReader,
+    pool: ConnectionPool<Core>,
+    l1_client: Arc<dyn EthInterface>,
+    /// ABI of the zkSync L1 contract used to decode commit transactions.
+    contract: ethabi::Contract,
+    poll_interval: Duration,
+    last_commit_tx: Option<CommitTx>,
+    /// Next L1 batch to compare root hashes for. Initialized with the last L1 batch committed on L1
+    /// once the task is started.
+    next_l1_batch_to_check: Option<L1BatchNumber>,
+    inconsistency_sender: watch::Sender<Option<String>>,
+}
+
+impl MerkleTreeVerifier {
+    /// Depth of checked subtrees measured in nibbles. With this depth, a subtree contains
+    /// approximately 1/4,096 of all tree leaves.
+    const SUBTREE_DEPTH: usize = 3;
+    /// Maximum number of L1 batches to compare root hashes for on a single iteration.
+    const MAX_L1_BATCHES_PER_ITER: u32 = 100;
+
+    pub fn new(
+        tree_reader: LazyAsyncTreeReader,
+        pool: ConnectionPool<Core>,
+        l1_client: Arc<dyn EthInterface>,
+        poll_interval: Duration,
+    ) -> Self {
+        Self {
+            tree_reader,
+            pool,
+            l1_client,
+            contract: zksync_contracts::zksync_contract(),
+            poll_interval,
+            last_commit_tx: None,
+            next_l1_batch_to_check: None,
+            inconsistency_sender: watch::channel(None).0,
+        }
+    }
+
+    /// Returns a circuit breaker triggered once this verifier detects a tree inconsistency.
+    pub fn circuit_breaker(&self) -> MerkleTreeConsistencyBreaker {
+        MerkleTreeConsistencyBreaker(self.inconsistency_sender.subscribe())
+    }
+
+    fn report_inconsistency(&self, message: String) {
+        tracing::error!("Merkle tree inconsistency detected: {message}");
+        self.inconsistency_sender.send_replace(Some(message));
+    }
+
+    async fn check_random_subtree(&self, tree_reader: &AsyncTreeReader) {
+        let next_l1_batch_number = tree_reader.clone().info().await.next_l1_batch_number;
+        let Some(l1_batch_number) = next_l1_batch_number.0.checked_sub(1) else {
+            return; // The tree is empty
+        };
+        let l1_batch_number = L1BatchNumber(l1_batch_number);
+        let key = Key::from_big_endian(&rand::thread_rng().gen::<[u8; 32]>());
+
+        let result = tree_reader
+            .clone()
+            .verify_subtree_consistency(l1_batch_number, key, Self::SUBTREE_DEPTH)
+            .await;
+        if let Err(err) = result {
+            self.report_inconsistency(format!(
+                "subtree containing key 0x{key:x} is inconsistent for L1 batch #{l1_batch_number}: {err}"
+            ));
+        } else {
+            tracing::debug!(
+                "Verified subtree containing key 0x{key:x} for L1 batch #{l1_batch_number}"
+            );
+        }
+    }
+
+    async fn check_root_hashes(&mut self, tree_reader: &AsyncTreeReader) -> anyhow::Result<()> {
+        let mut storage = self.pool.connection_tagged("metadata_calculator").await?;
+        let last_committed_l1_batch = storage
+            .blocks_dal()
+            .get_number_of_last_l1_batch_committed_on_eth()
+            .await
+            .context("failed getting number of last L1 batch committed on L1")?;
+        let Some(last_committed_l1_batch) = last_committed_l1_batch else {
+            return Ok(()); // No L1 batches are committed yet
+        };
+        let next_l1_batch = *self
+            .next_l1_batch_to_check
+            .get_or_insert(last_committed_l1_batch);
+
+        let tree_next_l1_batch = tree_reader.clone().info().await.next_l1_batch_number;
+        let Some(last_tree_l1_batch) = tree_next_l1_batch.0.checked_sub(1) else {
+            return Ok(()); // The tree is empty
+        };
+        let last_l1_batch = last_committed_l1_batch
+            .0
+            .min(last_tree_l1_batch)
+            .min(next_l1_batch.0 + Self::MAX_L1_BATCHES_PER_ITER - 1);
+        if next_l1_batch.0 > last_l1_batch {
+            return Ok(());
+        }
+
+        let mut next_unchecked_l1_batch = next_l1_batch;
+        for number in next_l1_batch.0..=last_l1_batch {
+            let number = L1BatchNumber(number);
+            let committed_hash = match self.committed_root_hash(&mut storage, number).await {
+                Ok(hash) => hash,
+                Err(err) => {
+                    // L1 errors are usually transient, so we retry on the next iteration.
+                    tracing::warn!(
+                        "Failed getting root hash for L1 batch #{number} committed on L1: {err:#}"
+                    );
+                    break;
+                }
+            };
+            next_unchecked_l1_batch = number + 1;
+            // Tree versions may be missing if they were pruned.
+            let tree_hash = tree_reader.clone().root_hash_for_l1_batch(number).await;
+            if let (Some(committed_hash), Some(tree_hash)) = (committed_hash, tree_hash) {
+                if committed_hash != tree_hash {
+                    self.report_inconsistency(format!(
+                        "root hash for L1 batch #{number} committed on L1 ({committed_hash:?}) \
+                         differs from the one in the tree ({tree_hash:?})"
+                    ));
+                }
+            }
+        }
+        if next_unchecked_l1_batch > next_l1_batch {
+            tracing::debug!(
+                "Compared root hashes for L1 batches #{next_l1_batch}..#{next_unchecked_l1_batch} with the ones committed on L1"
+            );
+        }
+        self.next_l1_batch_to_check = Some(next_unchecked_l1_batch);
+        Ok(())
+    }
+
+    /// Returns the root hash for the specified L1 batch taken from the calldata of its commit transaction on L1.
+    /// Returns `Ok(None)` if the commit transaction is unknown or its calldata cannot be parsed.
+    ///
+    /// # Errors
+    ///
+    /// Returns Postgres and L1 client errors.
+    async fn committed_root_hash(
+        &mut self,
+        storage: &mut Connection<'_, Core>,
+        number: L1BatchNumber,
+    ) -> anyhow::Result<Option<H256>> {
+        let Some(l1_batch) = storage.blocks_dal().get_storage_l1_batch(number).await? else {
+            return Ok(None);
+        };
+        let Some(commit_tx_id) = l1_batch.eth_commit_tx_id else {
+            return Ok(None);
+        };
+        let Some(commit_tx_hash) = storage
+            .eth_sender_dal()
+            .get_confirmed_tx_hash_by_eth_tx_id(commit_tx_id as u32)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let commit_tx = match self.last_commit_tx.take() {
+            Some(tx) if tx.hash == commit_tx_hash => tx,
+            _ => {
+                let tx = self
+                    .l1_client
+                    .get_tx(commit_tx_hash, "merkle_tree_verifier")
+                    .await
+                    .with_context(|| format!("failed fetching commit tx {commit_tx_hash:?}"))?
+                    .with_context(|| format!("commit tx {commit_tx_hash:?} not found on L1"))?;
+                CommitTx {
+                    hash: commit_tx_hash,
+                    input: tx.input.0,
+                }
+            }
+        };
+        let root_hash = Self::extract_root_hash(&self.contract, &commit_tx.input, number);
+        self.last_commit_tx = Some(commit_tx);
+
+        match root_hash {
+            Ok(hash) => Ok(Some(hash)),
+            Err(err) => {
+                // Malformed calldata is detected by the consistency checker; here, we just skip the L1 batch.
+                tracing::warn!(
+                    "Failed extracting root hash for L1 batch #{number} from commit tx {commit_tx_hash:?}: {err:#}"
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    fn extract_root_hash(
+        contract: &ethabi::Contract,
+        commit_tx_input: &[u8],
+        number: L1BatchNumber,
+    ) -> anyhow::Result<H256> {
+        anyhow::ensure!(commit_tx_input.len() >= 4, "calldata is too short");
+        let commit_function = contract
+            .function("commitBatches")
+            .context("L1 contract does not have `commitBatches` function")?;
+        let commit_function = if commit_tx_input[..4] == commit_function.short_signature() {
+            commit_function
+        } else {
+            &*PRE_BOOJUM_COMMIT_FUNCTION
+        };
+        let commitment =
+            ConsistencyChecker::extract_commit_data(commit_tx_input, commit_function, number)?;
+
+        let ethabi::Token::Tuple(commitment) = commitment else {
+            anyhow::bail!("unexpected commitment shape: {commitment:?}");
+        };
+        // `newStateRoot` is the 4th field of the commitment for all supported protocol versions.
+        let root_hash = commitment
+            .get(3)
+            .cloned()
+            .and_then(ethabi::Token::into_fixed_bytes)
+            .filter(|bytes| bytes.len() == 32)
+            .context("commitment doesn't contain a state root")?;
+        Ok(H256::from_slice(&root_hash))
+    }
+
+    async fn run_once(&mut self, tree_reader: &AsyncTreeReader) -> anyhow::Result<()> {
+        self.check_root_hashes(tree_reader).await?;
+        self.check_random_subtree(tree_reader).await;
+        Ok(())
+    }
+
+    pub async fn run(mut self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        let tree_reader = tokio::select! {
+            reader = self.tree_reader.clone().wait() => reader,
+            _ = stop_receiver.changed() => {
+                tracing::info!("Stop signal received, Merkle tree verifier is shutting down");
+                return Ok(());
+            }
+        };
+        tracing::info!(
+            "Starting Merkle tree verifier with {:?} poll interval",
+            self.poll_interval
+        );
+
+        while !*stop_receiver.borrow_and_update() {
+            self.run_once(&tree_reader).await?;
+            // Error here corresponds to a timeout w/o `stop_receiver` changed; we're OK with this.
+            tokio::time::timeout(self.poll_interval, stop_receiver.changed())
+                .await
+                .ok();
+        }
+        tracing::info!("Stop signal received, Merkle tree verifier is shutting down");
+        Ok(())
+    }
+}
+
+/// Circuit breaker triggered by [`MerkleTreeVerifier`] if it detects a Merkle tree inconsistency.
+#[derive(Debug)]
+pub struct MerkleTreeConsistencyBreaker(watch::Receiver<Option<String>>);
+
+impl MerkleTreeConsistencyBreaker {
+    /// Waits until an inconsistency is detected and returns an error describing it. This allows to stop
+    /// the node on inconsistencies without a circuit breaker checker (e.g., on the external node).
+    pub async fn run(mut self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        let inconsistency = async {
+            let message = self
+                .0
+                .wait_for(Option::is_some)
+                .await
+                .map(|message| message.clone().unwrap_or_default());
+            match message {
+                Ok(message) => message,
+                // The verifier has stopped without detecting inconsistencies.
+                Err(_) => future::pending().await,
+            }
+        };
+        tokio::select! {
+            message = inconsistency => {
+                anyhow::bail!("Merkle tree inconsistency detected: {message}")
+            }
+            _ = stop_receiver.changed() => Ok(()),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl CircuitBreaker for MerkleTreeConsistencyBreaker {
+    fn name(&self) -> &'static str {
+        "merkle_tree_consistency"
+    }
+
+    async fn check(&self) -> Result<(), CircuitBreakerError> {
+        match &*self.0.borrow() {
+            Some(message) => Err(CircuitBreakerError::MerkleTreeInconsistency(
+                message.clone(),
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops;
+
+    use tempfile::TempDir;
+    use zksync_eth_client::{clients::MockEthereum, Options};
+    use zksync_types::{
+        aggregated_operations::AggregatedActionType, commitment::L1BatchWithMetadata,
+        pubdata_da::PubdataDA, Address,
+    };
+
+    use super::*;
+    use crate::{
+        eth_sender::l1_batch_commit_data_generator::{
+            L1BatchCommitDataGenerator, RollupModeL1BatchCommitDataGenerator,
+        },
+        metadata_calculator::tests::{reset_db_state, run_calculator, setup_calculator},
+        utils::testonly::{create_l1_batch, create_l1_batch_metadata},
+    };
+
+    /// Commits L1 batches on the mock L1 in a single transaction and records the transaction in Postgres.
+    async fn commit_l1_batches(
+        pool: &ConnectionPool<Core>,
+        client: &MockEthereum,
+        root_hashes: &[(L1BatchNumber, H256)],
+    ) {
+        let l1_batches: Vec<_> = root_hashes
+            .iter()
+            .map(|&(number, root_hash)| {
+                let mut metadata = create_l1_batch_metadata(number.0);
+                metadata.root_hash = root_hash;
+                metadata.merkle_root_hash = root_hash;
+                L1BatchWithMetadata {
+                    header: create_l1_batch(number.0),
+                    metadata,
+                    raw_published_factory_deps: vec![],
+                }
+            })
+            .collect();
+        let contract = zksync_contracts::zksync_contract();
+        let commit_function = contract.function("commitBatches").unwrap();
+        let mut input = commit_function.short_signature().to_vec();
+        input.extend_from_slice(&ethabi::encode(
+            &RollupModeL1BatchCommitDataGenerator.l1_commit_batches(
+                &l1_batches[0],
+                &l1_batches,
+                &PubdataDA::Calldata,
+            ),
+        ));
+
+        let options = Options {
+            nonce: Some(root_hashes[0].0 .0.into()),
+            ..Options::default()
+        };
+        let signed_tx = client
+            .sign_prepared_tx(input, Address::repeat_byte(1), options)
+            .unwrap();
+        client.send_raw_tx(signed_tx.raw_tx).await.unwrap();
+        client.execute_tx(signed_tx.hash, true, 1);
+
+        let mut storage = pool.connection().await.unwrap();
+        for &(number, _) in root_hashes {
+            storage
+                .eth_sender_dal()
+                .insert_bogus_confirmed_eth_tx(
+                    number,
+                    AggregatedActionType::Commit,
+                    signed_tx.hash,
+                    chrono::Utc::now(),
+                )
+                .await
+                .unwrap();
+        }
+    }
+
+    async fn tree_root_hashes(
+        tree_reader: &AsyncTreeReader,
+        numbers: ops::RangeInclusive<u32>,
+    ) -> Vec<(L1BatchNumber, H256)> {
+        let mut root_hashes = vec![];
+        for number in numbers {
+            let number = L1BatchNumber(number);
+            let root_hash = tree_reader.clone().root_hash_for_l1_batch(number).await;
+            root_hashes.push((number, root_hash.unwrap()));
+        }
+        root_hashes
+    }
+
+    #[tokio::test]
+    async fn verifying_consistent_tree() {
+        let pool = ConnectionPool::<Core>::test_pool().await;
+        let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+        let (calculator, _) = setup_calculator(temp_dir.path(), &pool).await;
+        reset_db_state(&pool, 5).await;
+        let tree_reader = calculator.tree_reader();
+        run_calculator(calculator, pool.clone()).await;
+
+        let client = Arc::new(MockEthereum::default());
+        let mut verifier =
+            MerkleTreeVerifier::new(tree_reader, pool.clone(), client.clone(), Duration::ZERO);
+        let circuit_breaker = verifier.circuit_breaker();
+        let tree_reader = verifier.tree_reader.read().unwrap();
+        let root_hashes = tree_root_hashes(&tree_reader, 1..=5).await;
+        commit_l1_batches(&pool, &client, &root_hashes[..3]).await;
+        verifier.next_l1_batch_to_check = Some(L1BatchNumber(1));
+        verifier.run_once(&tree_reader).await.unwrap();
+        assert_eq!(verifier.next_l1_batch_to_check, Some(L1BatchNumber(4)));
+
+        commit_l1_batches(&pool, &client, &root_hashes[3..]).await;
+        verifier.run_once(&tree_reader).await.unwrap();
+        assert_eq!(verifier.next_l1_batch_to_check, Some(L1BatchNumber(6)));
+        for _ in 0..10 {
+            verifier.check_random_subtree(&tree_reader).await;
+        }
+        circuit_breaker.check().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn detecting_root_hash_mismatch() {
+        let pool = ConnectionPool::<Core>::test_pool().await;
+        let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+        let (calculator, _) = setup_calculator(temp_dir.path(), &pool).await;
+        reset_db_state(&pool, 5).await;
+        let tree_reader = calculator.tree_reader();
+        run_calculator(calculator, pool.clone()).await;
+
+        let client = Arc::new(MockEthereum::default());
+        let mut verifier =
+            MerkleTreeVerifier::new(tree_reader, pool.clone(), client.clone(), Duration::ZERO);
+        let circuit_breaker = verifier.circuit_breaker();
+        let tree_reader = verifier.tree_reader.read().unwrap();
+        // Commit a root hash that differs from the one in the tree (and in Postgres) for L1 batch #3.
+        let mut root_hashes = tree_root_hashes(&tree_reader, 1..=5).await;
+        root_hashes[2].1 = H256::repeat_byte(0xff);
+        commit_l1_batches(&pool, &client, &root_hashes).await;
+
+        verifier.next_l1_batch_to_check = Some(L1BatchNumber(1));
+        circuit_breaker.check().await.unwrap();
+        verifier.run_once(&tree_reader).await.unwrap();
+
+        let err = circuit_breaker.check().await.unwrap_err();
+        let CircuitBreakerError::MerkleTreeInconsistency(message) = err else {
+            panic!("Unexpected error: {err:?}");
+        };
+        assert!(message.contains("L1 batch #3"), "{message}");
+    }
+
+    #[tokio::test]
+    async fn retrying_on_missing_commit_tx() {
+        let pool = ConnectionPool::<Core>::test_pool().await;
+        let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+        let (calculator, _) = setup_calculator(temp_dir.path(), &pool).await;
+        reset_db_state(&pool, 5).await;
+        let tree_reader = calculator.tree_reader();
+        run_calculator(calculator, pool.clone()).await;
+
+        let client = Arc::new(MockEthereum::default());
+        let mut verifier =
+            MerkleTreeVerifier::new(tree_reader, pool.clone(), client.clone(), Duration::ZERO);
+        let tree_reader = verifier.tree_reader.read().unwrap();
+        let root_hashes = tree_root_hashes(&tree_reader, 1..=2).await;
+        commit_l1_batches(&pool, &client, &root_hashes).await;
+        // The commit tx for L1 batch #3 is known to Postgres, but not to L1.
+        pool.connection()
+            .await
+            .unwrap()
+            .eth_sender_dal()
+            .insert_bogus_confirmed_eth_tx(
+                L1BatchNumber(3),
+                AggregatedActionType::Commit,
+                H256::repeat_byte(3),
+                chrono::Utc::now(),
+            )
+            .await
+            .unwrap();
+
+        verifier.next_l1_batch_to_check = Some(L1BatchNumber(1));
+        verifier.run_once(&tree_reader).await.unwrap();
+        assert_eq!(verifier.next_l1_batch_to_check, Some(L1BatchNumber(3)));
+        verifier.circuit_breaker().check().await.unwrap();
+    }
+}
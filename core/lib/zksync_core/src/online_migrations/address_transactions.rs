@@ -0,0 +1,66 @@
+//! Backfill of the address transactions index.
+
+use async_trait::async_trait;
+use zksync_dal::{Connection, Core, CoreDal};
+use zksync_types::MiniblockNumber;
+
+use super::{BackfillBatch, OnlineMigration};
+
+/// Backfills the `address_transactions` index from transactions executed before the index was introduced.
+/// Miniblocks sealed after that are indexed by the state keeper, so the backfill only needs to catch up
+/// with the last sealed miniblock.
+///
+/// The cursor is the last backfilled miniblock number. Batch size is measured in executed transactions; batches are
+/// aligned to miniblock boundaries, so a batch may slightly exceed the size.
+#[derive(Debug)]
+pub struct AddressTransactionsMigration;
+
+impl AddressTransactionsMigration {
+    pub const NAME: &'static str = "address_transactions";
+}
+
+#[async_trait]
+impl OnlineMigration for AddressTransactionsMigration {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    async fn backfill_batch(
+        &self,
+        storage: &mut Connection<'_, Core>,
+        cursor: Option<i64>,
+        batch_size: usize,
+    ) -> anyhow::Result<Option<BackfillBatch>> {
+        let Some(sealed_miniblock) = storage.blocks_dal().get_sealed_miniblock_number().await?
+        else {
+            // All miniblocks will be indexed by the state keeper.
+            return Ok(None);
+        };
+        let start = MiniblockNumber(cursor.map_or(0, |cursor| cursor as u32 + 1));
+        if start > sealed_miniblock {
+            return Ok(None);
+        }
+
+        let bound = storage
+            .address_transactions_dal()
+            .get_transactions_batch_bound(start..=sealed_miniblock, batch_size)
+            .await?;
+        let end = bound.unwrap_or(sealed_miniblock);
+        let processed_rows = storage
+            .address_transactions_dal()
+            .backfill_address_transactions(start..=end)
+            .await?;
+
+        if bound.is_none() {
+            // Miniblocks sealed after `sealed_miniblock` are indexed by the state keeper, so the backfill is complete.
+            tracing::info!(
+                "Backfilled {processed_rows} address transactions for miniblocks {start}..={end}; this was the last batch"
+            );
+            return Ok(None);
+        }
+        Ok(Some(BackfillBatch {
+            cursor: end.0.into(),
+            processed_rows,
+        }))
+    }
+}
@@ -0,0 +1,24 @@
+//! Metrics for online migrations.
+
+use std::time::Duration;
+
+use vise::{Buckets, Counter, Gauge, Histogram, LabeledFamily, Metrics, Unit};
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "server_online_migrations")]
+pub(super) struct OnlineMigrationsMetrics {
+    /// Latency of processing a single backfill batch, including persisting progress.
+    #[metrics(buckets = Buckets::LATENCIES, unit = Unit::Seconds, labels = ["migration"])]
+    pub batch_latency: LabeledFamily<&'static str, Histogram<Duration>>,
+    /// Total number of rows processed by backfills.
+    #[metrics(labels = ["migration"])]
+    pub processed_rows: LabeledFamily<&'static str, Counter>,
+    /// Cursor of the last processed backfill batch.
+    #[metrics(labels = ["migration"])]
+    pub cursor: LabeledFamily<&'static str, Gauge<i64>>,
+    /// Number of online migrations that are cut over.
+    pub cut_over_migrations: Gauge<usize>,
+}
+
+#[vise::register]
+pub(super) static METRICS: vise::Global<OnlineMigrationsMetrics> = vise::Global::new();
@@ -0,0 +1,264 @@
+//! Online migrations, i.e. rewrites of large tables performed in the background without server downtime.
+//!
+//! Regular (`sqlx`) migrations are executed on server startup and block it until they are completed. This is
+//! unacceptable for migrations rewriting tables with billions of rows (e.g., `storage_logs` or `events`),
+//! which could take hours on mainnet-scale databases. Such migrations should be split into:
+//!
+//! 1. A regular migration creating the new data layout (e.g., a new table or column) without populating it.
+//! 2. An [`OnlineMigration`] backfilling the new layout from the legacy one in batches. While the migration
+//!    is not cut over, DAL writers must persist data in both layouts, and readers must use the legacy layout;
+//!    both can check the migration status via [`OnlineMigrationsDal`](zksync_dal::online_migrations_dal::OnlineMigrationsDal).
+//! 3. Once the backfill is completed, the migration is cut over: readers switch to the new layout, and writers
+//!    stop writing the legacy one.
+//! 4. A regular migration dropping the legacy layout, shipped in one of the following releases.
+//!
+//! Migrations are executed sequentially by [`OnlineMigrationsRunner`] in the order they were added to it.
+//! Progress is persisted after each batch, so the runner can be restarted at any time.
+
+use std::{fmt, time::Duration};
+
+use anyhow::Context as _;
+use async_trait::async_trait;
+use tokio::sync::watch;
+use zksync_dal::{
+    online_migrations_dal::OnlineMigrationStatus, Connection, ConnectionPool, Core, CoreDal,
+};
+use zksync_health_check::{Health, HealthStatus, HealthUpdater, ReactiveHealthCheck};
+
+use self::metrics::METRICS;
+pub use self::{
+    address_transactions::AddressTransactionsMigration,
+    token_balance_holders::TokenBalanceHoldersMigration,
+};
+
+mod address_transactions;
+mod metrics;
+#[cfg(test)]
+mod tests;
+mod token_balance_holders;
+
+/// Result of processing a single backfill batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BackfillBatch {
+    /// Key of the last processed row. Will be passed to the next [`OnlineMigration::backfill_batch()`] call.
+    pub cursor: i64,
+    /// Number of rows processed in the batch. Used for progress reporting only.
+    pub processed_rows: u64,
+}
+
+/// Online migration executed by [`OnlineMigrationsRunner`].
+#[async_trait]
+pub trait OnlineMigration: 'static + fmt::Debug + Send + Sync {
+    /// Unique name of the migration. Used as a key for the migration state in Postgres, so it must not change
+    /// after the migration is released.
+    fn name(&self) -> &'static str;
+
+    /// Backfills the next batch of at most `batch_size` rows following the `cursor` (`None` for the first batch).
+    /// Returns `None` if there are no more rows to backfill.
+    ///
+    /// `storage` is a transaction that is committed together with the returned progress. Since the dual-write window
+    /// is active during the backfill, the backfill must be idempotent (e.g., use `ON CONFLICT DO NOTHING`).
+    async fn backfill_batch(
+        &self,
+        storage: &mut Connection<'_, Core>,
+        cursor: Option<i64>,
+        batch_size: usize,
+    ) -> anyhow::Result<Option<BackfillBatch>>;
+
+    /// Performs actions necessary for the cutover, e.g. creating indexes on the new layout. Executed in the same
+    /// transaction as switching the migration status. The default implementation does nothing.
+    async fn cut_over(&self, _storage: &mut Connection<'_, Core>) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+/// Component executing [`OnlineMigration`]s.
+#[derive(Debug)]
+pub struct OnlineMigrationsRunner {
+    pool: ConnectionPool<Core>,
+    migrations: Vec<Box<dyn OnlineMigration>>,
+    batch_size: usize,
+    batch_delay: Duration,
+    health_updater: HealthUpdater,
+}
+
+impl OnlineMigrationsRunner {
+    const DEFAULT_BATCH_SIZE: usize = 10_000;
+    /// Delay between batches limiting the additional load on the database.
+    const DEFAULT_BATCH_DELAY: Duration = Duration::from_millis(100);
+
+    pub fn new(pool: ConnectionPool<Core>) -> Self {
+        Self {
+            pool,
+            migrations: vec![],
+            batch_size: Self::DEFAULT_BATCH_SIZE,
+            batch_delay: Self::DEFAULT_BATCH_DELAY,
+            health_updater: ReactiveHealthCheck::new("online_migrations").1,
+        }
+    }
+
+    /// Adds a migration to be executed after all previously added ones.
+    pub fn with_migration(mut self, migration: impl OnlineMigration) -> Self {
+        let name = migration.name();
+        assert!(
+            self.migrations.iter().all(|other| other.name() != name),
+            "online migration `{name}` is added twice"
+        );
+        self.migrations.push(Box::new(migration));
+        self
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        assert!(batch_size > 0, "batch size must be positive");
+        self.batch_size = batch_size;
+        self
+    }
+
+    pub fn with_batch_delay(mut self, batch_delay: Duration) -> Self {
+        self.batch_delay = batch_delay;
+        self
+    }
+
+    pub fn health_check(&self) -> ReactiveHealthCheck {
+        self.health_updater.subscribe()
+    }
+
+    /// Performs a single step of the migration: backfills a batch or cuts the migration over.
+    /// Returns the migration status after the step.
+    async fn step(&self, migration: &dyn OnlineMigration) -> anyhow::Result<OnlineMigrationStatus> {
+        let name = migration.name();
+        let mut storage = self.pool.connection_tagged("online_migrations").await?;
+        let mut transaction = storage.start_transaction().await?;
+        let state = transaction
+            .online_migrations_dal()
+            .get_migration_state(name)
+            .await?
+            .with_context(|| format!("online migration `{name}` is not registered"))?;
+
+        let new_status = match state.status {
+            OnlineMigrationStatus::Backfilling => {
+                let latency = METRICS.batch_latency[&name].start();
+                let batch = migration
+                    .backfill_batch(&mut transaction, state.cursor, self.batch_size)
+                    .await
+                    .with_context(|| format!("failed backfilling online migration `{name}`"))?;
+
+                let mut dal = transaction.online_migrations_dal();
+                let new_status = if let Some(batch) = batch {
+                    let recorded = dal
+                        .record_backfill_progress(name, batch.cursor, batch.processed_rows)
+                        .await?;
+                    anyhow::ensure!(
+                        recorded,
+                        "online migration `{name}` was concurrently modified"
+                    );
+                    tracing::debug!(
+                        "Backfilled {} rows for online migration `{name}`; cursor: {}",
+                        batch.processed_rows,
+                        batch.cursor
+                    );
+                    METRICS.processed_rows[&name].inc_by(batch.processed_rows);
+                    METRICS.cursor[&name].set(batch.cursor);
+                    OnlineMigrationStatus::Backfilling
+                } else {
+                    let transitioned = dal
+                        .transition_migration(
+                            name,
+                            OnlineMigrationStatus::Backfilling,
+                            OnlineMigrationStatus::BackfillCompleted,
+                        )
+                        .await?;
+                    anyhow::ensure!(
+                        transitioned,
+                        "online migration `{name}` was concurrently modified"
+                    );
+                    tracing::info!(
+                        "Completed backfill for online migration `{name}`; {} rows processed in total",
+                        state.processed_rows
+                    );
+                    OnlineMigrationStatus::BackfillCompleted
+                };
+                latency.observe();
+                new_status
+            }
+            OnlineMigrationStatus::BackfillCompleted => {
+                migration
+                    .cut_over(&mut transaction)
+                    .await
+                    .with_context(|| format!("failed cutting over online migration `{name}`"))?;
+                let transitioned = transaction
+                    .online_migrations_dal()
+                    .transition_migration(
+                        name,
+                        OnlineMigrationStatus::BackfillCompleted,
+                        OnlineMigrationStatus::CutOver,
+                    )
+                    .await?;
+                anyhow::ensure!(
+                    transitioned,
+                    "online migration `{name}` was concurrently modified"
+                );
+                tracing::info!("Online migration `{name}` is cut over");
+                OnlineMigrationStatus::CutOver
+            }
+            OnlineMigrationStatus::CutOver => OnlineMigrationStatus::CutOver,
+        };
+        transaction.commit().await?;
+        Ok(new_status)
+    }
+
+    fn update_health(&self, migration: &dyn OnlineMigration, status: OnlineMigrationStatus) {
+        let health_details = serde_json::json!({
+            "current_migration": migration.name(),
+            "status": status.to_string(),
+        });
+        self.health_updater
+            .update(Health::from(HealthStatus::Ready).with_details(health_details));
+    }
+
+    pub async fn run(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        self.health_updater.update(HealthStatus::Ready.into());
+        let mut storage = self.pool.connection_tagged("online_migrations").await?;
+        for migration in &self.migrations {
+            storage
+                .online_migrations_dal()
+                .register_migration(migration.name())
+                .await?;
+        }
+        drop(storage);
+
+        for (i, migration) in self.migrations.iter().enumerate() {
+            let migration = migration.as_ref();
+            tracing::info!("Running online migration `{}`", migration.name());
+            loop {
+                if *stop_receiver.borrow_and_update() {
+                    tracing::info!(
+                        "Stop signal received, online migrations runner is shutting down"
+                    );
+                    return Ok(());
+                }
+
+                let status = self.step(migration).await?;
+                self.update_health(migration, status);
+                if status == OnlineMigrationStatus::CutOver {
+                    METRICS.cut_over_migrations.set(i + 1);
+                    break;
+                }
+                // Error here corresponds to a timeout w/o `stop_receiver` changed; we're OK with this.
+                tokio::time::timeout(self.batch_delay, stop_receiver.changed())
+                    .await
+                    .ok();
+            }
+        }
+
+        tracing::info!("All online migrations are cut over");
+        // The runner must not exit before receiving the stop signal, since this would lead to a node shutdown.
+        while !*stop_receiver.borrow_and_update() {
+            if stop_receiver.changed().await.is_err() {
+                break;
+            }
+        }
+        tracing::info!("Stop signal received, online migrations runner is shutting down");
+        Ok(())
+    }
+}
@@ -0,0 +1,339 @@
+//! Tests for online migrations.
+
+use std::sync::{Arc, Mutex};
+
+use zksync_dal::{
+    address_transactions_dal::AddressTxsFilter, online_migrations_dal::OnlineMigrationState,
+};
+use zksync_system_constants::ERC20_TRANSFER_TOPIC;
+use zksync_types::{
+    fee::TransactionExecutionMetrics,
+    tokens::{TokenInfo, TokenMetadata},
+    tx::IncludedTxLocation,
+    Address, L1BatchNumber, MiniblockNumber, ProtocolVersion, VmEvent, H256,
+};
+use zksync_utils::address_to_h256;
+
+use super::*;
+use crate::utils::testonly::{create_l2_transaction, create_miniblock, execute_l2_transaction};
+
+#[derive(Debug, Default)]
+struct MockMigrationState {
+    requested_cursors: Vec<Option<i64>>,
+    cut_over_count: usize,
+}
+
+/// Migration "backfilling" `total_rows` rows with keys `1..=total_rows` without touching storage.
+#[derive(Debug, Clone)]
+struct MockMigration {
+    total_rows: i64,
+    fail_on_cursor: Option<i64>,
+    state: Arc<Mutex<MockMigrationState>>,
+}
+
+impl MockMigration {
+    fn new(total_rows: i64) -> Self {
+        Self {
+            total_rows,
+            fail_on_cursor: None,
+            state: Arc::default(),
+        }
+    }
+
+    fn requested_cursors(&self) -> Vec<Option<i64>> {
+        self.state.lock().unwrap().requested_cursors.clone()
+    }
+}
+
+#[async_trait]
+impl OnlineMigration for MockMigration {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    async fn backfill_batch(
+        &self,
+        _storage: &mut Connection<'_, Core>,
+        cursor: Option<i64>,
+        batch_size: usize,
+    ) -> anyhow::Result<Option<BackfillBatch>> {
+        self.state.lock().unwrap().requested_cursors.push(cursor);
+        if cursor.is_some() && cursor == self.fail_on_cursor {
+            anyhow::bail!("backfill failed");
+        }
+
+        let start = cursor.unwrap_or(0);
+        if start >= self.total_rows {
+            return Ok(None);
+        }
+        let end = (start + batch_size as i64).min(self.total_rows);
+        Ok(Some(BackfillBatch {
+            cursor: end,
+            processed_rows: (end - start) as u64,
+        }))
+    }
+
+    async fn cut_over(&self, _storage: &mut Connection<'_, Core>) -> anyhow::Result<()> {
+        self.state.lock().unwrap().cut_over_count += 1;
+        Ok(())
+    }
+}
+
+async fn wait_for_status(
+    pool: &ConnectionPool<Core>,
+    name: &str,
+    status: OnlineMigrationStatus,
+) -> OnlineMigrationState {
+    loop {
+        let state = pool
+            .connection()
+            .await
+            .unwrap()
+            .online_migrations_dal()
+            .get_migration_state(name)
+            .await
+            .unwrap();
+        if let Some(state) = state.filter(|state| state.status == status) {
+            return state;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+#[tokio::test]
+async fn running_migration_to_completion() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let migration = MockMigration::new(25);
+    let runner = OnlineMigrationsRunner::new(pool.clone())
+        .with_migration(migration.clone())
+        .with_batch_size(10)
+        .with_batch_delay(Duration::ZERO);
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let runner_task = tokio::spawn(runner.run(stop_receiver));
+
+    let state = wait_for_status(&pool, "mock", OnlineMigrationStatus::CutOver).await;
+    assert_eq!(state.cursor, Some(25));
+    assert_eq!(state.processed_rows, 25);
+    assert_eq!(
+        migration.requested_cursors(),
+        [None, Some(10), Some(20), Some(25)]
+    );
+    assert_eq!(migration.state.lock().unwrap().cut_over_count, 1);
+
+    // The runner must not exit until it receives a stop signal.
+    assert!(!runner_task.is_finished());
+    stop_sender.send_replace(true);
+    runner_task.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn resuming_migration() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    storage
+        .online_migrations_dal()
+        .register_migration("mock")
+        .await
+        .unwrap();
+    storage
+        .online_migrations_dal()
+        .record_backfill_progress("mock", 20, 20)
+        .await
+        .unwrap();
+    drop(storage);
+
+    let migration = MockMigration::new(25);
+    let runner = OnlineMigrationsRunner::new(pool.clone())
+        .with_migration(migration.clone())
+        .with_batch_size(10)
+        .with_batch_delay(Duration::ZERO);
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let runner_task = tokio::spawn(runner.run(stop_receiver));
+
+    let state = wait_for_status(&pool, "mock", OnlineMigrationStatus::CutOver).await;
+    assert_eq!(state.processed_rows, 25);
+    assert_eq!(migration.requested_cursors(), [Some(20), Some(25)]);
+
+    stop_sender.send_replace(true);
+    runner_task.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn failed_batch_does_not_record_progress() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut migration = MockMigration::new(25);
+    migration.fail_on_cursor = Some(10);
+    let runner = OnlineMigrationsRunner::new(pool.clone())
+        .with_migration(migration.clone())
+        .with_batch_size(10)
+        .with_batch_delay(Duration::ZERO);
+    let (_stop_sender, stop_receiver) = watch::channel(false);
+
+    let err = runner.run(stop_receiver).await.unwrap_err();
+    assert!(format!("{err:#}").contains("backfill failed"), "{err:#}");
+
+    let state = pool
+        .connection()
+        .await
+        .unwrap()
+        .online_migrations_dal()
+        .get_migration_state("mock")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        state,
+        OnlineMigrationState {
+            status: OnlineMigrationStatus::Backfilling,
+            cursor: Some(10),
+            processed_rows: 10,
+        }
+    );
+}
+
+#[tokio::test]
+async fn backfilling_token_holders() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    storage
+        .protocol_versions_dal()
+        .save_protocol_version_with_tx(&ProtocolVersion::default())
+        .await
+        .unwrap();
+    let token = Address::repeat_byte(0xfe);
+    storage
+        .tokens_dal()
+        .add_tokens(&[TokenInfo {
+            l1_address: token,
+            l2_address: token,
+            metadata: TokenMetadata::default(token),
+        }])
+        .await
+        .unwrap();
+
+    let tx_location = IncludedTxLocation {
+        tx_hash: H256::zero(),
+        tx_index_in_miniblock: 0,
+        tx_initiator_address: Address::zero(),
+    };
+    let holders: Vec<_> = (1..=5).map(Address::repeat_byte).collect();
+    for (i, holder) in holders.iter().enumerate() {
+        let miniblock_number = MiniblockNumber(i as u32 + 1);
+        storage
+            .blocks_dal()
+            .insert_miniblock(&create_miniblock(miniblock_number.0))
+            .await
+            .unwrap();
+        let mint_event = VmEvent {
+            location: (L1BatchNumber(1), 0),
+            address: token,
+            indexed_topics: vec![ERC20_TRANSFER_TOPIC, H256::zero(), address_to_h256(holder)],
+            value: H256::from_low_u64_be(1).0.to_vec(),
+        };
+        storage
+            .events_dal()
+            .save_events(miniblock_number, &[(tx_location, vec![&mint_event])])
+            .await
+            .unwrap();
+    }
+    drop(storage);
+
+    let runner = OnlineMigrationsRunner::new(pool.clone())
+        .with_migration(TokenBalanceHoldersMigration)
+        .with_batch_size(2)
+        .with_batch_delay(Duration::ZERO);
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let runner_task = tokio::spawn(runner.run(stop_receiver));
+
+    let state = wait_for_status(
+        &pool,
+        TokenBalanceHoldersMigration::NAME,
+        OnlineMigrationStatus::CutOver,
+    )
+    .await;
+    // The first batch covers miniblocks up to the one containing the 3rd event; the remaining miniblocks are
+    // processed together with completing the backfill.
+    assert_eq!(state.cursor, Some(3));
+    assert_eq!(state.processed_rows, 3);
+
+    let mut storage = pool.connection().await.unwrap();
+    for holder in holders {
+        let held_tokens = storage
+            .token_balances_dal()
+            .get_held_tokens(holder)
+            .await
+            .unwrap();
+        assert_eq!(held_tokens, [token]);
+    }
+
+    stop_sender.send_replace(true);
+    runner_task.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn backfilling_address_transactions() {
+    let pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = pool.connection().await.unwrap();
+    storage
+        .protocol_versions_dal()
+        .save_protocol_version_with_tx(&ProtocolVersion::default())
+        .await
+        .unwrap();
+
+    let recipient = Address::repeat_byte(0xfe);
+    let mut tx_hashes = vec![];
+    for number in 1..=5 {
+        storage
+            .blocks_dal()
+            .insert_miniblock(&create_miniblock(number))
+            .await
+            .unwrap();
+        let mut tx = create_l2_transaction(10, 100);
+        tx.execute.contract_address = recipient;
+        storage
+            .transactions_dal()
+            .insert_transaction_l2(&tx, TransactionExecutionMetrics::default())
+            .await
+            .unwrap();
+        let tx_result = execute_l2_transaction(tx);
+        tx_hashes.push(tx_result.hash);
+        storage
+            .transactions_dal()
+            .mark_txs_as_executed_in_miniblock(MiniblockNumber(number), &[tx_result], 1.into())
+            .await
+            .unwrap();
+    }
+    drop(storage);
+
+    let runner = OnlineMigrationsRunner::new(pool.clone())
+        .with_migration(AddressTransactionsMigration)
+        .with_batch_size(2)
+        .with_batch_delay(Duration::ZERO);
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let runner_task = tokio::spawn(runner.run(stop_receiver));
+
+    let state = wait_for_status(
+        &pool,
+        AddressTransactionsMigration::NAME,
+        OnlineMigrationStatus::CutOver,
+    )
+    .await;
+    // The first batch covers miniblocks up to the one containing the 3rd transaction; the remaining miniblocks
+    // are processed together with completing the backfill. Each transaction is indexed both for its sender
+    // and its recipient.
+    assert_eq!(state.cursor, Some(3));
+    assert_eq!(state.processed_rows, 6);
+
+    let mut storage = pool.connection().await.unwrap();
+    let received_txs = storage
+        .address_transactions_dal()
+        .get_address_transactions(recipient, AddressTxsFilter::Received, None, 100)
+        .await
+        .unwrap();
+    let received_tx_hashes: Vec<_> = received_txs.iter().map(|tx| tx.tx_hash).collect();
+    tx_hashes.reverse();
+    assert_eq!(received_tx_hashes, tx_hashes);
+
+    stop_sender.send_replace(true);
+    runner_task.await.unwrap().unwrap();
+}
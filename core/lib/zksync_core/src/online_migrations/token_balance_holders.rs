@@ -0,0 +1,67 @@
+//! Backfill of the token holders index.
+
+use async_trait::async_trait;
+use zksync_dal::{Connection, Core, CoreDal};
+use zksync_types::MiniblockNumber;
+
+use super::{BackfillBatch, OnlineMigration};
+
+/// Backfills the `token_balance_holders` index from `Transfer` events persisted before the index was introduced.
+/// Miniblocks sealed after that are indexed by the state keeper, so the backfill only needs to catch up
+/// with the last sealed miniblock.
+///
+/// The cursor is the last backfilled miniblock number. Batch size is measured in `Transfer` events; batches are
+/// aligned to miniblock boundaries, so a batch may slightly exceed the size.
+#[derive(Debug)]
+pub struct TokenBalanceHoldersMigration;
+
+impl TokenBalanceHoldersMigration {
+    pub const NAME: &'static str = "token_balance_holders";
+}
+
+#[async_trait]
+impl OnlineMigration for TokenBalanceHoldersMigration {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    async fn backfill_batch(
+        &self,
+        storage: &mut Connection<'_, Core>,
+        cursor: Option<i64>,
+        batch_size: usize,
+    ) -> anyhow::Result<Option<BackfillBatch>> {
+        let Some(sealed_miniblock) = storage.blocks_dal().get_sealed_miniblock_number().await?
+        else {
+            // All miniblocks will be indexed by the state keeper.
+            return Ok(None);
+        };
+        let start = MiniblockNumber(cursor.map_or(0, |cursor| cursor as u32 + 1));
+        if start > sealed_miniblock {
+            return Ok(None);
+        }
+
+        let bound = storage
+            .token_balances_dal()
+            .get_transfers_batch_bound(start..=sealed_miniblock, batch_size)
+            .await?;
+        let end = bound.unwrap_or(sealed_miniblock);
+        let processed_rows = storage
+            .token_balances_dal()
+            .backfill_token_holders(start..=end)
+            .await?;
+
+        if bound.is_none() {
+            // Miniblocks sealed after `sealed_miniblock` are indexed by the state keeper, so the backfill is complete.
+            // Returning a batch instead would make the backfill chase the head of the chain.
+            tracing::info!(
+                "Backfilled {processed_rows} token holders for miniblocks {start}..={end}; this was the last batch"
+            );
+            return Ok(None);
+        }
+        Ok(Some(BackfillBatch {
+            cursor: end.0.into(),
+            processed_rows,
+        }))
+    }
+}
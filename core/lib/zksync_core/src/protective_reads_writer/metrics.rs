@@ -0,0 +1,16 @@
+//! Metrics for the protective reads writer.
+//!
+//! Re-execution and persistence latencies, and the last processed L1 batch are reported by the shared replay loop
+//! with the `protective_reads_writer` component label.
+
+use vise::{Gauge, Metrics};
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "server_protective_reads_writer")]
+pub(super) struct ProtectiveReadsWriterMetrics {
+    /// Number of protective reads in the last processed L1 batch.
+    pub protective_reads: Gauge<usize>,
+}
+
+#[vise::register]
+pub(super) static METRICS: vise::Global<ProtectiveReadsWriterMetrics> = vise::Global::new();
@@ -0,0 +1,120 @@
+//! Background computation of protective reads for sealed L1 batches.
+//!
+//! Protective reads are storage slots read (but not changed) in an L1 batch; they are required by the full Merkle tree
+//! to generate witness inputs. Persisting them when sealing an L1 batch can take a considerable share
+//! of the sealing latency, so the state keeper may be configured to skip it. In this case, protective reads
+//! are backfilled by [`ProtectiveReadsWriter`], which re-executes sealed L1 batches in the background.
+
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use tokio::sync::watch;
+use zksync_dal::{Connection, ConnectionPool, Core, CoreDal};
+use zksync_health_check::{Health, HealthStatus, ReactiveHealthCheck};
+use zksync_types::{L1BatchNumber, L2ChainId};
+
+use self::metrics::METRICS;
+use crate::state_keeper::replay::{
+    BatchReplayer, ReexecutedL1Batch, ReplayLoop, ReplayedBatchProcessor,
+};
+
+mod metrics;
+
+/// Persists protective reads of re-executed L1 batches.
+#[derive(Debug)]
+struct ProtectiveReadsPersister;
+
+#[async_trait]
+impl ReplayedBatchProcessor for ProtectiveReadsPersister {
+    const NAME: &'static str = "protective_reads_writer";
+    const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+    /// Returns the earliest L1 batch after `last_processed_l1_batch` that lacks protective reads.
+    async fn next_l1_batch(
+        &mut self,
+        storage: &mut Connection<'_, Core>,
+        last_processed_l1_batch: Option<L1BatchNumber>,
+    ) -> anyhow::Result<Option<L1BatchNumber>> {
+        // All L1 batches before `next_l1_batch` have protective reads persisted.
+        let next_l1_batch = last_processed_l1_batch.map_or(L1BatchNumber(0), |number| number + 1);
+        Ok(storage
+            .storage_logs_dedup_dal()
+            .get_first_l1_batch_without_protective_reads(next_l1_batch)
+            .await?)
+    }
+
+    async fn process_l1_batch(
+        &mut self,
+        storage: &mut Connection<'_, Core>,
+        l1_batch_number: L1BatchNumber,
+        executed_batch: ReexecutedL1Batch,
+    ) -> anyhow::Result<Health> {
+        // Protective reads are computed in the same way as in the state keeper; see `UpdatesManager::seal_l1_batch()`.
+        let protective_reads: Arc<[_]> = executed_batch
+            .finished_batch
+            .final_execution_state
+            .deduplicated_storage_log_queries
+            .iter()
+            .filter(|log_query| !log_query.rw_flag)
+            .copied()
+            .collect();
+
+        storage
+            .transaction_with_retries(|transaction| {
+                let protective_reads = protective_reads.clone();
+                Box::pin(async move {
+                    transaction
+                        .storage_logs_dedup_dal()
+                        .insert_protective_reads(l1_batch_number, &protective_reads)
+                        .await?;
+                    transaction
+                        .storage_logs_dedup_dal()
+                        .mark_protective_reads_as_persisted(l1_batch_number)
+                        .await
+                })
+            })
+            .await?;
+
+        tracing::info!(
+            "Persisted {} protective reads for L1 batch #{l1_batch_number}",
+            protective_reads.len()
+        );
+        METRICS.protective_reads.set(protective_reads.len());
+        let health_details = serde_json::json!({
+            "last_processed_l1_batch": l1_batch_number,
+        });
+        Ok(Health::from(HealthStatus::Ready).with_details(health_details))
+    }
+}
+
+/// Component computing and persisting protective reads for sealed L1 batches that lack them.
+///
+/// Batches are processed sequentially in the ascending order. A batch is processed by re-executing it
+/// using the same batch executor as the state keeper.
+#[derive(Debug)]
+pub struct ProtectiveReadsWriter {
+    inner: ReplayLoop<ProtectiveReadsPersister>,
+}
+
+impl ProtectiveReadsWriter {
+    /// Creates a new writer. `optional_bytecode_compression` must correspond to the setting of the state keeper
+    /// that has executed the batches.
+    pub fn new(
+        pool: ConnectionPool<Core>,
+        l2_chain_id: L2ChainId,
+        optional_bytecode_compression: bool,
+    ) -> Self {
+        let replayer = BatchReplayer::new(pool.clone(), l2_chain_id, optional_bytecode_compression);
+        Self {
+            inner: ReplayLoop::new(pool, replayer, ProtectiveReadsPersister),
+        }
+    }
+
+    pub fn health_check(&self) -> ReactiveHealthCheck {
+        self.inner.health_check()
+    }
+
+    pub async fn run(self, stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        self.inner.run(stop_receiver).await
+    }
+}
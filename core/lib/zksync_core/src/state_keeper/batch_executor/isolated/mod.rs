@@ -0,0 +1,337 @@
+//! Batch executor running the VM in a separate OS process.
+//!
+//! The state keeper side ([`IsolatedCommandReceiver`]) keeps the storage and forwards batch executor commands
+//! to a worker process, which runs the VM and requests storage values over IPC. Since the worker holds no state
+//! that cannot be reconstructed, a crashed worker (e.g., because of a VM panic or running out of memory) is restarted,
+//! and the commands already executed for the current L1 batch are replayed. A transaction that repeatedly crashes
+//! the worker is rejected.
+
+use std::{
+    any::Any,
+    env, fmt,
+    io::{self, Read, Write},
+    process::{self, Child, Stdio},
+    sync::Arc,
+    time::Instant,
+};
+
+use multivm::interface::{FinishedL1Batch, Halt, L1BatchEnv, SystemEnv};
+use tokio::sync::mpsc;
+use zksync_state::ReadStorage;
+use zksync_types::Transaction;
+
+use self::protocol::{
+    FromWorker, MessageChannel, Request, Response, StorageRequest, StorageResponse, ToWorker,
+};
+use super::{main_executor::TxExecutor, Command, TxExecutionResult};
+use crate::state_keeper::metrics::EXECUTOR_METRICS;
+
+mod protocol;
+#[cfg(test)]
+pub(super) mod testonly;
+mod worker;
+
+/// Environment variable set for worker processes spawned by the state keeper.
+const WORKER_ENV_VAR: &str = "ZKSYNC_BATCH_EXECUTOR_WORKER";
+/// Maximum number of times a single command is attempted before giving up on it.
+const MAX_ATTEMPTS: usize = 3;
+
+/// Checks whether the current process was spawned as a batch executor worker. If this is the case,
+/// the process must call [`run_worker_process()`] instead of its usual logic.
+pub fn is_worker_process() -> bool {
+    env::var_os(WORKER_ENV_VAR).is_some()
+}
+
+/// Runs the batch executor worker over stdin / stdout and exits the process once the L1 batch is finished.
+/// Stdout is used for IPC, so the process must not write anything else to it (in particular, logs).
+pub fn run_worker_process() -> ! {
+    let stdin = io::stdin().lock();
+    let stdout = io::stdout().lock();
+    match worker::run_worker(stdin, stdout) {
+        Ok(()) => process::exit(0),
+        Err(err) => {
+            eprintln!("Batch executor worker terminated: {err}");
+            process::exit(1);
+        }
+    }
+}
+
+/// Streams connected to a launched worker.
+pub(super) struct WorkerStreams {
+    pub reader: Box<dyn Read + Send>,
+    pub writer: Box<dyn Write + Send>,
+    /// Terminates the worker when dropped.
+    pub guard: Box<dyn Any + Send>,
+}
+
+impl fmt::Debug for WorkerStreams {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("WorkerStreams")
+            .finish_non_exhaustive()
+    }
+}
+
+/// Launches batch executor workers.
+pub(super) trait WorkerLauncher: 'static + fmt::Debug + Send + Sync {
+    fn launch(&self) -> io::Result<WorkerStreams>;
+}
+
+/// Launches workers as child processes by re-executing the current binary.
+#[derive(Debug)]
+pub(super) struct ProcessLauncher;
+
+/// Kills the worker process on drop.
+#[derive(Debug)]
+struct ChildGuard(Child);
+
+impl Drop for ChildGuard {
+    fn drop(&mut self) {
+        // The process may have already exited, in which case killing it errors; this is fine.
+        self.0.kill().ok();
+        self.0.wait().ok();
+    }
+}
+
+impl WorkerLauncher for ProcessLauncher {
+    fn launch(&self) -> io::Result<WorkerStreams> {
+        let mut child = process::Command::new(env::current_exe()?)
+            .env(WORKER_ENV_VAR, "1")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()?;
+        let writer = child.stdin.take().expect("worker stdin is piped");
+        let reader = child.stdout.take().expect("worker stdout is piped");
+        Ok(WorkerStreams {
+            reader: Box::new(reader),
+            writer: Box::new(writer),
+            guard: Box::new(ChildGuard(child)),
+        })
+    }
+}
+
+type WorkerChannel = MessageChannel<Box<dyn Read + Send>, Box<dyn Write + Send>>;
+
+struct ConnectedWorker {
+    channel: WorkerChannel,
+    _guard: Box<dyn Any + Send>,
+}
+
+impl fmt::Debug for ConnectedWorker {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("ConnectedWorker")
+            .finish_non_exhaustive()
+    }
+}
+
+/// State keeper side of the process-isolated batch executor. Like the in-process command receiver,
+/// it handles exactly one L1 batch.
+#[derive(Debug)]
+pub(super) struct IsolatedCommandReceiver<S> {
+    launcher: Arc<dyn WorkerLauncher>,
+    storage: S,
+    init_request: Request,
+    /// Commands successfully executed in the current L1 batch, replayed after the worker is restarted.
+    replay_log: Vec<Request>,
+    worker: Option<ConnectedWorker>,
+    /// Set if the last transaction was rejected because it crashed the worker. Since the transaction
+    /// was never executed by the current worker, the following rollback must not be forwarded to it.
+    last_tx_crashed_worker: bool,
+}
+
+impl<S: ReadStorage> IsolatedCommandReceiver<S> {
+    pub fn new(
+        launcher: Arc<dyn WorkerLauncher>,
+        storage: S,
+        executor: TxExecutor,
+        l1_batch_env: L1BatchEnv,
+        system_env: SystemEnv,
+    ) -> Self {
+        Self {
+            launcher,
+            storage,
+            init_request: Request::Init {
+                l1_batch_env,
+                system_env,
+                save_call_traces: executor.save_call_traces,
+                optional_bytecode_compression: executor.optional_bytecode_compression,
+            },
+            replay_log: vec![],
+            worker: None,
+            last_tx_crashed_worker: false,
+        }
+    }
+
+    pub fn run(mut self, mut commands: mpsc::Receiver<Command>) {
+        while let Some(cmd) = commands.blocking_recv() {
+            match cmd {
+                Command::ExecuteTx(tx, resp) => {
+                    let result = self.execute_tx(tx);
+                    resp.send(result).unwrap();
+                }
+                Command::RollbackLastTx(resp) => {
+                    if self.last_tx_crashed_worker {
+                        self.last_tx_crashed_worker = false;
+                    } else {
+                        self.execute_or_panic(Request::RollbackLastTx);
+                    }
+                    resp.send(()).unwrap();
+                }
+                Command::StartNextMiniblock(l2_block_env, resp) => {
+                    self.last_tx_crashed_worker = false;
+                    self.execute_or_panic(Request::StartNextMiniblock(l2_block_env));
+                    resp.send(()).unwrap();
+                }
+                Command::FinishBatch(resp) => {
+                    self.last_tx_crashed_worker = false;
+                    let vm_block_result = self.finish_batch();
+                    resp.send(vm_block_result).unwrap();
+                    return;
+                }
+            }
+        }
+        // State keeper can exit because of stop signal, so it's OK to exit mid-batch.
+        tracing::info!("State keeper exited with an unfinished batch");
+    }
+
+    fn execute_tx(&mut self, tx: Box<Transaction>) -> TxExecutionResult {
+        self.last_tx_crashed_worker = false;
+        let tx_hash = tx.hash();
+        let started_at = Instant::now();
+        match self.execute(Request::ExecuteTx(tx)) {
+            Some(Response::TxExecuted(mut result)) => {
+                // VM execution time isn't transferred over IPC, so we measure it here, which includes IPC overhead.
+                if let TxExecutionResult::Success { tx_metrics, .. } = result.as_mut() {
+                    tx_metrics.execution_metrics.vm_execution_time = started_at.elapsed();
+                }
+                *result
+            }
+            Some(other) => panic!("unexpected response to `execute_tx`: {other:?}"),
+            None => {
+                tracing::error!(
+                    "Transaction {tx_hash:?} crashed batch executor worker {MAX_ATTEMPTS} times; rejecting it"
+                );
+                EXECUTOR_METRICS.isolated_worker_rejected_txs.inc();
+                self.last_tx_crashed_worker = true;
+                TxExecutionResult::RejectedByVm {
+                    reason: Halt::VMPanic,
+                }
+            }
+        }
+    }
+
+    fn finish_batch(&mut self) -> FinishedL1Batch {
+        match self.execute_or_panic(Request::FinishBatch) {
+            Response::BatchFinished(finished_batch) => *finished_batch,
+            other => panic!("unexpected response to `finish_batch`: {other:?}"),
+        }
+    }
+
+    fn execute_or_panic(&mut self, request: Request) -> Response {
+        let kind = request.kind();
+        self.execute(request).unwrap_or_else(|| {
+            panic!("`{kind}` command crashed batch executor worker {MAX_ATTEMPTS} times")
+        })
+    }
+
+    /// Executes a command, restarting the worker if necessary. Returns `None` if the worker crashed
+    /// on each of [`MAX_ATTEMPTS`] attempts.
+    fn execute(&mut self, request: Request) -> Option<Response> {
+        for attempt in 1..=MAX_ATTEMPTS {
+            match self.ensure_worker().and_then(|()| self.send(&request)) {
+                Ok(response) => {
+                    self.replay_log.push(request);
+                    return Some(response);
+                }
+                Err(err) => {
+                    tracing::warn!(
+                        "Batch executor worker failed executing `{}` command (attempt {attempt}/{MAX_ATTEMPTS}): {err}",
+                        request.kind()
+                    );
+                    EXECUTOR_METRICS.isolated_worker_crashes.inc();
+                    // Dropping the worker terminates it.
+                    self.worker = None;
+                }
+            }
+        }
+        None
+    }
+
+    /// Launches a worker if there's no live one and brings it to the current state of the L1 batch.
+    fn ensure_worker(&mut self) -> io::Result<()> {
+        if self.worker.is_some() {
+            return Ok(());
+        }
+
+        let streams = self.launcher.launch()?;
+        self.worker = Some(ConnectedWorker {
+            channel: MessageChannel::new(streams.reader, streams.writer),
+            _guard: streams.guard,
+        });
+        let init_request = self.init_request.clone();
+        match self.send(&init_request)? {
+            Response::Initialized => { /* OK */ }
+            other => return Err(unexpected_response(&init_request, &other)),
+        }
+
+        if !self.replay_log.is_empty() {
+            tracing::info!(
+                "Replaying {} commands on restarted batch executor worker",
+                self.replay_log.len()
+            );
+        }
+        let replay_log = std::mem::take(&mut self.replay_log);
+        let replay_result = replay_log
+            .iter()
+            .try_for_each(|request| self.send(request).map(drop));
+        self.replay_log = replay_log;
+        replay_result
+    }
+
+    /// Sends a command to the worker and serves its storage requests until the command response is received.
+    fn send(&mut self, request: &Request) -> io::Result<Response> {
+        let worker = self.worker.as_mut().expect("worker is not launched");
+        worker.channel.send(&ToWorker::Command(request.clone()))?;
+        loop {
+            match worker.channel.receive()? {
+                FromWorker::Storage(storage_request) => {
+                    let response = serve_storage_request(&mut self.storage, storage_request);
+                    worker.channel.send(&ToWorker::Storage(response))?;
+                }
+                FromWorker::Response(response) => return Ok(response),
+            }
+        }
+    }
+}
+
+fn serve_storage_request<S: ReadStorage>(
+    storage: &mut S,
+    request: StorageRequest,
+) -> StorageResponse {
+    match request {
+        StorageRequest::ReadValue(key) => StorageResponse::Value(storage.read_value(&key)),
+        StorageRequest::IsWriteInitial(key) => {
+            StorageResponse::IsWriteInitial(storage.is_write_initial(&key))
+        }
+        StorageRequest::LoadFactoryDep(hash) => StorageResponse::FactoryDep(
+            storage
+                .load_factory_dep(hash)
+                .map(|bytecode| bytecode.to_vec()),
+        ),
+        StorageRequest::GetEnumerationIndex(key) => {
+            StorageResponse::EnumerationIndex(storage.get_enumeration_index(&key))
+        }
+    }
+}
+
+fn unexpected_response(request: &Request, response: &Response) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+            "unexpected response to `{}` command: {response:?}",
+            request.kind()
+        ),
+    )
+}
@@ -0,0 +1,125 @@
+//! IPC protocol between the state keeper and an isolated batch executor worker.
+//!
+//! Messages are JSON-encoded and prefixed with their length as a little-endian `u32`. The state keeper
+//! sends [`ToWorker`] messages and receives [`FromWorker`] messages. While executing a command,
+//! the worker may issue any number of storage requests, each of which must be answered before the worker
+//! proceeds; the command itself is answered with a single [`Response`].
+
+use std::io::{self, Read, Write};
+
+use multivm::interface::{FinishedL1Batch, L1BatchEnv, L2BlockEnv, SystemEnv};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use zksync_types::{StorageKey, StorageValue, Transaction, H256};
+
+use crate::state_keeper::batch_executor::TxExecutionResult;
+
+/// Maximum supported message length. Guards against allocating huge buffers if the stream gets corrupted.
+const MAX_MESSAGE_LEN: u32 = 1 << 30;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) enum ToWorker {
+    Command(Request),
+    Storage(StorageResponse),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) enum Request {
+    /// Always the first command sent to a worker.
+    Init {
+        l1_batch_env: L1BatchEnv,
+        system_env: SystemEnv,
+        save_call_traces: bool,
+        optional_bytecode_compression: bool,
+    },
+    ExecuteTx(Box<Transaction>),
+    StartNextMiniblock(L2BlockEnv),
+    RollbackLastTx,
+    FinishBatch,
+}
+
+impl Request {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Init { .. } => "init",
+            Self::ExecuteTx(_) => "execute_tx",
+            Self::StartNextMiniblock(_) => "start_next_miniblock",
+            Self::RollbackLastTx => "rollback_last_tx",
+            Self::FinishBatch => "finish_batch",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) enum FromWorker {
+    Storage(StorageRequest),
+    Response(Response),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) enum Response {
+    Initialized,
+    TxExecuted(Box<TxExecutionResult>),
+    MiniblockStarted,
+    RolledBack,
+    BatchFinished(Box<FinishedL1Batch>),
+}
+
+/// Request to the state keeper storage, mirroring [`ReadStorage`](zksync_state::ReadStorage) methods.
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) enum StorageRequest {
+    ReadValue(StorageKey),
+    IsWriteInitial(StorageKey),
+    LoadFactoryDep(H256),
+    GetEnumerationIndex(StorageKey),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) enum StorageResponse {
+    Value(StorageValue),
+    IsWriteInitial(bool),
+    FactoryDep(Option<Vec<u8>>),
+    EnumerationIndex(Option<u64>),
+}
+
+/// Bidirectional message channel over a pair of byte streams.
+#[derive(Debug)]
+pub(super) struct MessageChannel<R, W> {
+    reader: R,
+    writer: W,
+}
+
+impl<R: Read, W: Write> MessageChannel<R, W> {
+    pub fn new(reader: R, writer: W) -> Self {
+        Self { reader, writer }
+    }
+
+    pub fn send<T: Serialize>(&mut self, message: &T) -> io::Result<()> {
+        let bytes = serde_json::to_vec(message)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+        let len = u32::try_from(bytes.len())
+            .ok()
+            .filter(|&len| len <= MAX_MESSAGE_LEN)
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, "outgoing message is too large")
+            })?;
+        self.writer.write_all(&len.to_le_bytes())?;
+        self.writer.write_all(&bytes)?;
+        self.writer.flush()
+    }
+
+    pub fn receive<T: DeserializeOwned>(&mut self) -> io::Result<T> {
+        let mut len = [0_u8; 4];
+        self.reader.read_exact(&mut len)?;
+        let len = u32::from_le_bytes(len);
+        if len > MAX_MESSAGE_LEN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("incoming message is too large ({len} bytes)"),
+            ));
+        }
+        let mut bytes = vec![0_u8; len as usize];
+        self.reader.read_exact(&mut bytes)?;
+        serde_json::from_slice(&bytes)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
@@ -0,0 +1,94 @@
+//! Test utils for the process-isolated batch executor.
+
+use std::{
+    io,
+    net::Shutdown,
+    os::unix::net::UnixStream,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+use zksync_types::H256;
+
+use super::{
+    protocol::Request,
+    worker::{run_worker, run_worker_with_hook},
+    WorkerLauncher, WorkerStreams,
+};
+
+/// Shuts down the connection on drop, which terminates the worker thread.
+#[derive(Debug)]
+struct ThreadGuard(UnixStream);
+
+impl Drop for ThreadGuard {
+    fn drop(&mut self) {
+        self.0.shutdown(Shutdown::Both).ok();
+    }
+}
+
+/// Launches workers in threads of the current process, connected via a Unix socket pair.
+/// Can inject worker crashes.
+#[derive(Debug, Default)]
+pub(crate) struct ThreadLauncher {
+    /// Hash of a transaction that crashes every worker executing it.
+    poison_tx_hash: Option<H256>,
+    /// Number of commands after which the next launched worker crashes; reset after the crash.
+    crash_after_commands: Option<usize>,
+    launched_workers: Arc<AtomicUsize>,
+}
+
+impl ThreadLauncher {
+    pub fn with_poison_tx(mut self, tx_hash: H256) -> Self {
+        self.poison_tx_hash = Some(tx_hash);
+        self
+    }
+
+    /// Makes the first launched worker crash before executing the command with the specified 0-based index
+    /// (not counting the `Init` command).
+    pub fn with_crash_after_commands(mut self, command_count: usize) -> Self {
+        self.crash_after_commands = Some(command_count);
+        self
+    }
+
+    pub fn launched_workers(&self) -> Arc<AtomicUsize> {
+        self.launched_workers.clone()
+    }
+}
+
+impl WorkerLauncher for ThreadLauncher {
+    fn launch(&self) -> io::Result<WorkerStreams> {
+        let (parent_stream, worker_stream) = UnixStream::pair()?;
+        let worker_reader = worker_stream.try_clone()?;
+        let is_first_worker = self.launched_workers.fetch_add(1, Ordering::SeqCst) == 0;
+        let crash_after_commands = self.crash_after_commands.filter(|_| is_first_worker);
+        let poison_tx_hash = self.poison_tx_hash;
+
+        thread::spawn(move || {
+            if crash_after_commands.is_none() && poison_tx_hash.is_none() {
+                return run_worker(worker_reader, worker_stream);
+            }
+
+            let mut command_count = 0;
+            run_worker_with_hook(worker_reader, worker_stream, |request| {
+                if crash_after_commands == Some(command_count) {
+                    panic!("Injected worker crash");
+                }
+                command_count += 1;
+                if let (Request::ExecuteTx(tx), Some(poison_tx_hash)) = (request, poison_tx_hash) {
+                    if tx.hash() == poison_tx_hash {
+                        panic!("Worker executed poison transaction");
+                    }
+                }
+            })
+        });
+
+        Ok(WorkerStreams {
+            reader: Box::new(parent_stream.try_clone()?),
+            writer: Box::new(parent_stream.try_clone()?),
+            guard: Box::new(ThreadGuard(parent_stream)),
+        })
+    }
+}
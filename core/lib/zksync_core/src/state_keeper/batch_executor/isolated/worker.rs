@@ -0,0 +1,158 @@
+//! Worker side of the process-isolated batch executor.
+
+use std::{
+    cell::RefCell,
+    fmt,
+    io::{self, Read, Write},
+    rc::Rc,
+    sync::Arc,
+};
+
+use multivm::{vm_latest::HistoryEnabled, VmInstance};
+use zksync_state::{ReadStorage, StorageView};
+use zksync_types::{StorageKey, StorageValue, H256};
+
+use super::protocol::{
+    FromWorker, MessageChannel, Request, Response, StorageRequest, StorageResponse, ToWorker,
+};
+use crate::state_keeper::batch_executor::main_executor::TxExecutor;
+
+type SharedChannel<R, W> = Rc<RefCell<MessageChannel<R, W>>>;
+
+/// Storage proxying all reads to the state keeper.
+struct IpcStorage<R, W> {
+    channel: SharedChannel<R, W>,
+}
+
+impl<R, W> fmt::Debug for IpcStorage<R, W> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.debug_struct("IpcStorage").finish_non_exhaustive()
+    }
+}
+
+impl<R: Read, W: Write> IpcStorage<R, W> {
+    /// Performs a storage request. `ReadStorage` methods are infallible, so an I/O error panics,
+    /// which terminates the worker; the state keeper restarts the worker in this case.
+    fn request(&self, request: StorageRequest) -> StorageResponse {
+        let mut channel = self.channel.borrow_mut();
+        channel
+            .send(&FromWorker::Storage(request))
+            .expect("failed sending storage request to state keeper");
+        match channel.receive() {
+            Ok(ToWorker::Storage(response)) => response,
+            Ok(ToWorker::Command(command)) => {
+                panic!("unexpected command while waiting for storage response: {command:?}")
+            }
+            Err(err) => panic!("failed receiving storage response from state keeper: {err}"),
+        }
+    }
+}
+
+impl<R: Read, W: Write> ReadStorage for IpcStorage<R, W> {
+    fn read_value(&mut self, key: &StorageKey) -> StorageValue {
+        match self.request(StorageRequest::ReadValue(*key)) {
+            StorageResponse::Value(value) => value,
+            other => panic!("unexpected response to `read_value`: {other:?}"),
+        }
+    }
+
+    fn is_write_initial(&mut self, key: &StorageKey) -> bool {
+        match self.request(StorageRequest::IsWriteInitial(*key)) {
+            StorageResponse::IsWriteInitial(is_initial) => is_initial,
+            other => panic!("unexpected response to `is_write_initial`: {other:?}"),
+        }
+    }
+
+    fn load_factory_dep(&mut self, hash: H256) -> Option<Arc<[u8]>> {
+        match self.request(StorageRequest::LoadFactoryDep(hash)) {
+            StorageResponse::FactoryDep(bytecode) => bytecode.map(Into::into),
+            other => panic!("unexpected response to `load_factory_dep`: {other:?}"),
+        }
+    }
+
+    fn get_enumeration_index(&mut self, key: &StorageKey) -> Option<u64> {
+        match self.request(StorageRequest::GetEnumerationIndex(*key)) {
+            StorageResponse::EnumerationIndex(index) => index,
+            other => panic!("unexpected response to `get_enumeration_index`: {other:?}"),
+        }
+    }
+}
+
+fn unexpected_message(message: impl fmt::Debug) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("unexpected message from state keeper: {message:?}"),
+    )
+}
+
+/// Executes a single L1 batch, receiving commands and serving storage requests over the provided streams.
+/// Returns after the batch is finished, or with an error if the state keeper closes the connection.
+pub(super) fn run_worker<R: Read, W: Write>(reader: R, writer: W) -> io::Result<()> {
+    run_worker_with_hook(reader, writer, |_| { /* do nothing */ })
+}
+
+/// Same as [`run_worker()`], but invokes `command_hook` before executing each command. Used in tests
+/// to inject worker crashes.
+pub(super) fn run_worker_with_hook<R: Read, W: Write>(
+    reader: R,
+    writer: W,
+    mut command_hook: impl FnMut(&Request),
+) -> io::Result<()> {
+    let channel = Rc::new(RefCell::new(MessageChannel::new(reader, writer)));
+    let message = channel.borrow_mut().receive::<ToWorker>()?;
+    let ToWorker::Command(Request::Init {
+        l1_batch_env,
+        system_env,
+        save_call_traces,
+        optional_bytecode_compression,
+    }) = message
+    else {
+        return Err(unexpected_message(message));
+    };
+
+    tracing::info!(
+        "Starting executing batch #{:?} in isolated worker",
+        l1_batch_env.number
+    );
+    let executor = TxExecutor {
+        save_call_traces,
+        optional_bytecode_compression,
+    };
+    let storage = IpcStorage {
+        channel: channel.clone(),
+    };
+    let storage_view = StorageView::new(storage).to_rc_ptr();
+    let mut vm = VmInstance::<_, HistoryEnabled>::new(l1_batch_env, system_env, storage_view);
+    channel
+        .borrow_mut()
+        .send(&FromWorker::Response(Response::Initialized))?;
+
+    loop {
+        let message = channel.borrow_mut().receive::<ToWorker>()?;
+        let ToWorker::Command(request) = message else {
+            return Err(unexpected_message(message));
+        };
+        command_hook(&request);
+        let response = match request {
+            Request::ExecuteTx(tx) => {
+                let result = executor.execute_tx(&tx, &mut vm, None);
+                Response::TxExecuted(Box::new(result))
+            }
+            Request::StartNextMiniblock(l2_block_env) => {
+                executor.start_next_miniblock(l2_block_env, &mut vm);
+                Response::MiniblockStarted
+            }
+            Request::RollbackLastTx => {
+                executor.rollback_last_tx(&mut vm);
+                Response::RolledBack
+            }
+            Request::FinishBatch => {
+                let finished_batch = executor.finish_batch(&mut vm);
+                let response = Response::BatchFinished(Box::new(finished_batch));
+                return channel.borrow_mut().send(&FromWorker::Response(response));
+            }
+            request @ Request::Init { .. } => return Err(unexpected_message(request)),
+        };
+        channel.borrow_mut().send(&FromWorker::Response(response))?;
+    }
+}
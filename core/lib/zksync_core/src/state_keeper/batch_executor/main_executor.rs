@@ -15,12 +15,17 @@ use tokio::{
     runtime::Handle,
     sync::{mpsc, watch},
 };
+use zksync_dal::{ConnectionPool, Core};
 use zksync_shared_metrics::{InteractionType, TxStage, APP_METRICS};
 use zksync_state::{ReadStorage, StorageView, WriteStorage};
 use zksync_types::{vm_trace::Call, Transaction};
 use zksync_utils::bytecode::CompressedBytecodeInfo;
 
-use super::{BatchExecutor, BatchExecutorHandle, Command, TxExecutionResult};
+use super::{
+    isolated::{IsolatedCommandReceiver, ProcessLauncher, WorkerLauncher},
+    shadow_vm::ShadowVmHandle,
+    BatchExecutor, BatchExecutorHandle, Command, TxExecutionResult,
+};
 use crate::state_keeper::{
     metrics::{TxExecutionStage, BATCH_TIP_METRICS, EXECUTOR_METRICS, KEEPER_METRICS},
     state_keeper_storage::ReadStorageFactory,
@@ -34,6 +39,8 @@ pub struct MainBatchExecutor {
     storage_factory: Arc<dyn ReadStorageFactory>,
     save_call_traces: bool,
     optional_bytecode_compression: bool,
+    shadow_vm_pool: Option<ConnectionPool<Core>>,
+    worker_launcher: Option<Arc<dyn WorkerLauncher>>,
 }
 
 impl MainBatchExecutor {
@@ -46,8 +53,39 @@ impl MainBatchExecutor {
             storage_factory,
             save_call_traces,
             optional_bytecode_compression,
+            shadow_vm_pool: None,
+            worker_launcher: None,
         }
     }
+
+    /// Enables shadow execution of L1 batches using the VM and base system contracts of the protocol version
+    /// following the one used by the main VM. The shadow VM runs on a separate thread and reads storage
+    /// using a connection from the provided `pool`; it never blocks the main VM. Disabled by default.
+    pub fn with_shadow_vm_execution(mut self, pool: ConnectionPool<Core>) -> Self {
+        self.shadow_vm_pool = Some(pool);
+        self
+    }
+
+    /// Enables or disables running the VM in a separate OS process, so that a VM crash doesn't bring down
+    /// the state keeper. The worker process is launched by re-executing the current binary, which must call
+    /// [`run_worker_process()`](super::isolated::run_worker_process) at startup if
+    /// [`is_worker_process()`](super::isolated::is_worker_process) returns `true`. Disabled by default.
+    ///
+    /// Shadow VM execution is not supported in this mode.
+    pub fn with_process_isolation(mut self, enabled: bool) -> Self {
+        self.worker_launcher = if enabled {
+            Some(Arc::new(ProcessLauncher))
+        } else {
+            None
+        };
+        self
+    }
+
+    #[cfg(test)]
+    pub(super) fn with_worker_launcher(mut self, launcher: Arc<dyn WorkerLauncher>) -> Self {
+        self.worker_launcher = Some(launcher);
+        self
+    }
 }
 
 #[async_trait]
@@ -62,19 +100,57 @@ impl BatchExecutor for MainBatchExecutor {
         // until a previous command is processed), capacity 1 is enough for the commands channel.
         let (commands_sender, commands_receiver) = mpsc::channel(1);
         let executor = CommandReceiver {
-            save_call_traces: self.save_call_traces,
-            optional_bytecode_compression: self.optional_bytecode_compression,
+            executor: TxExecutor {
+                save_call_traces: self.save_call_traces,
+                optional_bytecode_compression: self.optional_bytecode_compression,
+            },
             commands: commands_receiver,
         };
 
         let storage_factory = self.storage_factory.clone();
+        let shadow_vm_pool = self.shadow_vm_pool.clone();
+        let optional_bytecode_compression = self.optional_bytecode_compression;
+        let worker_launcher = self.worker_launcher.clone();
+        if shadow_vm_pool.is_some() && worker_launcher.is_some() {
+            tracing::warn!(
+                "Shadow VM execution is not supported with process isolation; disabling it"
+            );
+        }
         let stop_receiver = stop_receiver.clone();
         let handle = tokio::task::spawn_blocking(move || {
             if let Some(storage) = Handle::current()
                 .block_on(storage_factory.access_storage(&stop_receiver))
                 .expect("failed getting access to state keeper storage")
             {
-                executor.run(storage, l1_batch_params, system_env);
+                if let Some(launcher) = worker_launcher {
+                    let CommandReceiver { executor, commands } = executor;
+                    IsolatedCommandReceiver::new(
+                        launcher,
+                        storage,
+                        executor,
+                        l1_batch_params,
+                        system_env,
+                    )
+                    .run(commands);
+                    return;
+                }
+
+                let shadow_vm = shadow_vm_pool.and_then(|pool| {
+                    let spawn_result = ShadowVmHandle::spawn(
+                        pool,
+                        l1_batch_params.clone(),
+                        system_env.clone(),
+                        optional_bytecode_compression,
+                    );
+                    match spawn_result {
+                        Ok(shadow_vm) => Some(shadow_vm),
+                        Err(err) => {
+                            tracing::warn!("Failed starting shadow VM: {err:#}");
+                            None
+                        }
+                    }
+                });
+                executor.run(storage, shadow_vm, l1_batch_params, system_env);
             } else {
                 tracing::info!("Interrupted while trying to access state keeper storage");
             }
@@ -94,8 +170,7 @@ impl BatchExecutor for MainBatchExecutor {
 /// be constructed.
 #[derive(Debug)]
 struct CommandReceiver {
-    save_call_traces: bool,
-    optional_bytecode_compression: bool,
+    executor: TxExecutor,
     commands: mpsc::Receiver<Command>,
 }
 
@@ -103,6 +178,7 @@ impl CommandReceiver {
     pub(super) fn run<S: ReadStorage>(
         mut self,
         secondary_storage: S,
+        mut shadow_vm: Option<ShadowVmHandle>,
         l1_batch_params: L1BatchEnv,
         system_env: SystemEnv,
     ) {
@@ -115,19 +191,28 @@ impl CommandReceiver {
         while let Some(cmd) = self.commands.blocking_recv() {
             match cmd {
                 Command::ExecuteTx(tx, resp) => {
-                    let result = self.execute_tx(&tx, &mut vm);
+                    let result = self.executor.execute_tx(&tx, &mut vm, shadow_vm.as_mut());
                     resp.send(result).unwrap();
                 }
                 Command::RollbackLastTx(resp) => {
-                    self.rollback_last_tx(&mut vm);
+                    self.executor.rollback_last_tx(&mut vm);
+                    if let Some(shadow_vm) = &mut shadow_vm {
+                        shadow_vm.rollback_last_tx();
+                    }
                     resp.send(()).unwrap();
                 }
                 Command::StartNextMiniblock(l2_block_env, resp) => {
-                    self.start_next_miniblock(l2_block_env, &mut vm);
+                    self.executor.start_next_miniblock(l2_block_env, &mut vm);
+                    if let Some(shadow_vm) = &mut shadow_vm {
+                        shadow_vm.start_next_miniblock(l2_block_env);
+                    }
                     resp.send(()).unwrap();
                 }
                 Command::FinishBatch(resp) => {
-                    let vm_block_result = self.finish_batch(&mut vm);
+                    let vm_block_result = self.executor.finish_batch(&mut vm);
+                    if let Some(shadow_vm) = &mut shadow_vm {
+                        shadow_vm.finish_batch(&vm_block_result);
+                    }
                     resp.send(vm_block_result).unwrap();
 
                     // `storage_view` cannot be accessed while borrowed by the VM,
@@ -144,11 +229,22 @@ impl CommandReceiver {
         // State keeper can exit because of stop signal, so it's OK to exit mid-batch.
         tracing::info!("State keeper exited with an unfinished batch");
     }
+}
+
+/// Executes batch executor commands in the VM. Shared by the in-process batch executor and
+/// the [process-isolated](super::isolated) one.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct TxExecutor {
+    pub(super) save_call_traces: bool,
+    pub(super) optional_bytecode_compression: bool,
+}
 
-    fn execute_tx<S: WriteStorage>(
+impl TxExecutor {
+    pub(super) fn execute_tx<S: WriteStorage>(
         &self,
         tx: &Transaction,
         vm: &mut VmInstance<S, HistoryEnabled>,
+        shadow_vm: Option<&mut ShadowVmHandle>,
     ) -> TxExecutionResult {
         // Save pre-`execute_next_tx` VM snapshot.
         vm.make_snapshot();
@@ -161,9 +257,12 @@ impl CommandReceiver {
             } else {
                 self.execute_tx_in_vm(tx, vm)
             };
-        latency.observe();
+        let vm_execution_time = latency.observe();
         APP_METRICS.processed_txs[&TxStage::StateKeeper].inc();
         APP_METRICS.processed_l1_txs[&TxStage::StateKeeper].inc_by(tx.is_l1().into());
+        if let Some(shadow_vm) = shadow_vm {
+            shadow_vm.execute_tx(tx, &tx_result);
+        }
 
         if let ExecutionResult::Halt { reason } = tx_result.result {
             return match reason {
@@ -172,7 +271,8 @@ impl CommandReceiver {
             };
         }
 
-        let tx_metrics = ExecutionMetricsForCriteria::new(Some(tx), &tx_result);
+        let mut tx_metrics = ExecutionMetricsForCriteria::new(Some(tx), &tx_result);
+        tx_metrics.execution_metrics.vm_execution_time = vm_execution_time;
         let gas_remaining = vm.gas_remaining();
 
         TxExecutionResult::Success {
@@ -184,13 +284,13 @@ impl CommandReceiver {
         }
     }
 
-    fn rollback_last_tx<S: WriteStorage>(&self, vm: &mut VmInstance<S, HistoryEnabled>) {
+    pub(super) fn rollback_last_tx<S: WriteStorage>(&self, vm: &mut VmInstance<S, HistoryEnabled>) {
         let latency = KEEPER_METRICS.tx_execution_time[&TxExecutionStage::TxRollback].start();
         vm.rollback_to_the_latest_snapshot();
         latency.observe();
     }
 
-    fn start_next_miniblock<S: WriteStorage>(
+    pub(super) fn start_next_miniblock<S: WriteStorage>(
         &self,
         l2_block_env: L2BlockEnv,
         vm: &mut VmInstance<S, HistoryEnabled>,
@@ -198,7 +298,7 @@ impl CommandReceiver {
         vm.start_new_l2_block(l2_block_env);
     }
 
-    fn finish_batch<S: WriteStorage>(
+    pub(super) fn finish_batch<S: WriteStorage>(
         &self,
         vm: &mut VmInstance<S, HistoryEnabled>,
     ) -> FinishedL1Batch {
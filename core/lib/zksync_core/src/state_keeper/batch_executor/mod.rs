@@ -4,6 +4,7 @@ use async_trait::async_trait;
 use multivm::interface::{
     FinishedL1Batch, Halt, L1BatchEnv, L2BlockEnv, SystemEnv, VmExecutionResultAndLogs,
 };
+use serde::{Deserialize, Serialize};
 use tokio::{
     sync::{mpsc, oneshot, watch},
     task::JoinHandle,
@@ -19,10 +20,12 @@ use crate::state_keeper::{
 #[cfg(test)]
 mod tests;
 
+pub mod isolated;
 pub mod main_executor;
+mod shadow_vm;
 
 /// Representation of a transaction executed in the virtual machine.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) enum TxExecutionResult {
     /// Successful execution of the tx and the block tip dry run.
     Success {
@@ -0,0 +1,415 @@
+//! Shadow execution of L1 batches using the VM and base system contracts of the upcoming protocol version.
+//!
+//! The shadow VM runs on a dedicated thread and reads storage directly from Postgres, so that it never
+//! slows down the state keeper. Commands executed by the main VM are mirrored to the shadow VM together
+//! with the main VM outputs; divergences are logged and reported via metrics, but never influence the main VM.
+//!
+//! System contract deployments performed by the protocol upgrade transaction are not applied to the shadow VM
+//! storage, so divergences caused by such deployments are expected.
+
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        mpsc::{self, TrySendError},
+        Arc,
+    },
+    thread,
+};
+
+use anyhow::Context as _;
+use multivm::{
+    interface::{
+        ExecutionResult, FinishedL1Batch, Halt, L1BatchEnv, L2BlockEnv, SystemEnv,
+        VmExecutionResultAndLogs, VmInterface, VmInterfaceHistoryEnabled,
+    },
+    vm_latest::HistoryEnabled,
+    VmInstance,
+};
+use tokio::runtime::Handle;
+use zksync_dal::{Connection, ConnectionPool, Core, CoreDal};
+use zksync_state::{PostgresStorage, StoragePtr, StorageView, WriteStorage};
+use zksync_types::{L1BatchNumber, MiniblockNumber, Transaction};
+
+use crate::state_keeper::metrics::{ShadowVmDivergence, EXECUTOR_METRICS};
+
+/// Maximum number of commands buffered for the shadow VM. If the shadow VM lags further behind the main VM,
+/// shadow execution is aborted for the rest of the L1 batch.
+const COMMANDS_CAPACITY: usize = 1_000;
+
+#[derive(Debug)]
+enum ShadowCommand {
+    ExecuteTx {
+        tx: Box<Transaction>,
+        main_result: Box<VmExecutionResultAndLogs>,
+    },
+    RollbackLastTx,
+    StartNextMiniblock(L2BlockEnv),
+    FinishBatch(Box<FinishedL1Batch>),
+}
+
+/// Divergence between the main and the shadow VM detected for the specified subject (e.g., a transaction).
+pub(super) type Divergence = (String, ShadowVmDivergence);
+
+/// Handle to a shadow VM executing an L1 batch on a dedicated thread.
+#[derive(Debug)]
+pub(super) struct ShadowVmHandle {
+    l1_batch_number: L1BatchNumber,
+    /// Set to `None` once shadow execution is aborted or has terminated.
+    commands: Option<mpsc::SyncSender<ShadowCommand>>,
+    /// Only joined in tests; otherwise, the thread is detached and terminates on its own.
+    #[cfg_attr(not(test), allow(dead_code))]
+    thread: thread::JoinHandle<Vec<Divergence>>,
+}
+
+impl ShadowVmHandle {
+    /// Spawns a shadow VM for the specified L1 batch. The VM and base system contracts are taken from
+    /// the earliest protocol version persisted in Postgres following `system_env.version`; if there is
+    /// no such version, shadow execution is skipped.
+    ///
+    /// `pool` is used to load the protocol version and to read storage; a single connection is held
+    /// for the duration of the batch.
+    pub fn spawn(
+        pool: ConnectionPool<Core>,
+        l1_batch_env: L1BatchEnv,
+        system_env: SystemEnv,
+        optional_bytecode_compression: bool,
+    ) -> anyhow::Result<Self> {
+        let l1_batch_number = l1_batch_env.number;
+        let rt_handle = Handle::current();
+        let (commands_sender, commands_receiver) = mpsc::sync_channel(COMMANDS_CAPACITY);
+        let thread = thread::Builder::new()
+            .name(format!("shadow_vm_{l1_batch_number}"))
+            .spawn(move || {
+                run_shadow_vm(
+                    &rt_handle,
+                    &pool,
+                    l1_batch_env,
+                    system_env,
+                    optional_bytecode_compression,
+                    commands_receiver,
+                )
+            })
+            .context("failed spawning shadow VM thread")?;
+        Ok(Self {
+            l1_batch_number,
+            commands: Some(commands_sender),
+            thread,
+        })
+    }
+
+    pub fn execute_tx(&mut self, tx: &Transaction, main_result: &VmExecutionResultAndLogs) {
+        if self.commands.is_some() {
+            self.send(ShadowCommand::ExecuteTx {
+                tx: Box::new(tx.clone()),
+                main_result: Box::new(main_result.clone()),
+            });
+        }
+    }
+
+    pub fn rollback_last_tx(&mut self) {
+        self.send(ShadowCommand::RollbackLastTx);
+    }
+
+    pub fn start_next_miniblock(&mut self, l2_block_env: L2BlockEnv) {
+        self.send(ShadowCommand::StartNextMiniblock(l2_block_env));
+    }
+
+    pub fn finish_batch(&mut self, main_batch: &FinishedL1Batch) {
+        if self.commands.is_some() {
+            self.send(ShadowCommand::FinishBatch(Box::new(main_batch.clone())));
+        }
+        // Dropping the sender terminates the shadow VM thread if it's still running.
+        self.commands = None;
+    }
+
+    /// Sends a command to the shadow VM without blocking. If the shadow VM lags behind, shadow execution
+    /// is aborted for the rest of the batch.
+    fn send(&mut self, command: ShadowCommand) {
+        let Some(commands) = &self.commands else {
+            return;
+        };
+        match commands.try_send(command) {
+            Ok(()) => { /* the command is successfully queued */ }
+            Err(TrySendError::Full(_)) => {
+                tracing::warn!(
+                    "Shadow VM lags behind the main VM executing L1 batch #{}; \
+                     shadow execution is aborted for the rest of the batch",
+                    self.l1_batch_number
+                );
+                EXECUTOR_METRICS.shadow_vm_aborted_batches.inc();
+                self.commands = None;
+            }
+            Err(TrySendError::Disconnected(_)) => {
+                // The shadow VM has terminated (e.g., because there is no upcoming protocol version);
+                // the reason is logged by the shadow VM thread.
+                self.commands = None;
+            }
+        }
+    }
+
+    /// Waits for the shadow VM to finish and returns all detected divergences.
+    #[cfg(test)]
+    pub fn join(mut self) -> Vec<Divergence> {
+        self.commands = None;
+        self.thread.join().expect("shadow VM thread panicked")
+    }
+}
+
+fn run_shadow_vm(
+    rt_handle: &Handle,
+    pool: &ConnectionPool<Core>,
+    l1_batch_env: L1BatchEnv,
+    system_env: SystemEnv,
+    optional_bytecode_compression: bool,
+    commands: mpsc::Receiver<ShadowCommand>,
+) -> Vec<Divergence> {
+    let l1_batch_number = l1_batch_env.number;
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        run_shadow_vm_inner(
+            rt_handle,
+            pool,
+            l1_batch_env,
+            system_env,
+            optional_bytecode_compression,
+            commands,
+        )
+    }));
+    match result {
+        Ok(Ok(divergences)) => divergences,
+        Ok(Err(err)) => {
+            tracing::warn!("Failed shadow execution of L1 batch #{l1_batch_number}: {err:#}");
+            vec![]
+        }
+        Err(_) => {
+            tracing::error!("Shadow VM has panicked executing L1 batch #{l1_batch_number}");
+            EXECUTOR_METRICS.shadow_vm_divergences[&ShadowVmDivergence::Panic].inc();
+            vec![("panic".to_owned(), ShadowVmDivergence::Panic)]
+        }
+    }
+}
+
+fn run_shadow_vm_inner(
+    rt_handle: &Handle,
+    pool: &ConnectionPool<Core>,
+    l1_batch_env: L1BatchEnv,
+    system_env: SystemEnv,
+    optional_bytecode_compression: bool,
+    commands: mpsc::Receiver<ShadowCommand>,
+) -> anyhow::Result<Vec<Divergence>> {
+    let mut connection = rt_handle
+        .block_on(pool.connection_tagged("shadow_vm"))
+        .context("failed getting Postgres connection")?;
+    let Some(target_env) =
+        rt_handle.block_on(load_target_system_env(&mut connection, &system_env))?
+    else {
+        tracing::info!(
+            "There is no protocol version following {:?} used by L1 batch #{}; \
+             shadow execution is skipped",
+            system_env.version,
+            l1_batch_env.number
+        );
+        return Ok(vec![]);
+    };
+
+    // Storage is pinned to the state before the batch, so that it doesn't change if the shadow VM lags behind
+    // the state keeper.
+    let last_miniblock_before_batch = MiniblockNumber(l1_batch_env.first_l2_block.number - 1);
+    let storage = rt_handle
+        .block_on(PostgresStorage::new_async(
+            rt_handle.clone(),
+            connection,
+            last_miniblock_before_batch,
+            true,
+        ))
+        .context("failed creating Postgres storage")?;
+    let storage_view = StorageView::new(storage).to_rc_ptr();
+    let mut shadow_vm = ShadowVm::new(
+        l1_batch_env,
+        target_env,
+        storage_view,
+        optional_bytecode_compression,
+    );
+
+    while let Ok(command) = commands.recv() {
+        match command {
+            ShadowCommand::ExecuteTx { tx, main_result } => shadow_vm.execute_tx(&tx, &main_result),
+            ShadowCommand::RollbackLastTx => shadow_vm.rollback_last_tx(),
+            ShadowCommand::StartNextMiniblock(l2_block_env) => {
+                shadow_vm.start_next_miniblock(l2_block_env);
+            }
+            ShadowCommand::FinishBatch(main_batch) => {
+                shadow_vm.finish_batch(&main_batch);
+                break;
+            }
+        }
+    }
+    Ok(shadow_vm.divergences)
+}
+
+/// Returns the system environment of the earliest protocol version following the one in `system_env`.
+async fn load_target_system_env(
+    connection: &mut Connection<'_, Core>,
+    system_env: &SystemEnv,
+) -> anyhow::Result<Option<SystemEnv>> {
+    let version_ids = connection.protocol_versions_dal().all_version_ids().await;
+    let Some(target_version) = version_ids
+        .into_iter()
+        .filter(|&id| id > system_env.version)
+        .min()
+    else {
+        return Ok(None);
+    };
+    let base_system_contracts = connection
+        .protocol_versions_dal()
+        .load_base_system_contracts_by_version_id(target_version as u16)
+        .await
+        .context("failed loading base system contracts")?
+        .with_context(|| {
+            format!("no base system contracts persisted for protocol version {target_version:?}")
+        })?;
+    Ok(Some(SystemEnv {
+        version: target_version,
+        base_system_smart_contracts: Arc::new(base_system_contracts),
+        ..system_env.clone()
+    }))
+}
+
+/// VM mirroring all commands executed by the main VM of a batch executor, but using the upcoming protocol version.
+#[derive(Debug)]
+struct ShadowVm<S: WriteStorage> {
+    vm: VmInstance<S, HistoryEnabled>,
+    system_env: SystemEnv,
+    l1_batch_number: L1BatchNumber,
+    optional_bytecode_compression: bool,
+    divergences: Vec<Divergence>,
+}
+
+impl<S: WriteStorage> ShadowVm<S> {
+    fn new(
+        l1_batch_env: L1BatchEnv,
+        system_env: SystemEnv,
+        storage: StoragePtr<S>,
+        optional_bytecode_compression: bool,
+    ) -> Self {
+        let l1_batch_number = l1_batch_env.number;
+        tracing::info!(
+            "Started shadow execution of L1 batch #{l1_batch_number} using protocol version {:?}",
+            system_env.version
+        );
+        EXECUTOR_METRICS.shadow_vm_batches.inc();
+        let vm = VmInstance::new(l1_batch_env, system_env.clone(), storage);
+        Self {
+            vm,
+            system_env,
+            l1_batch_number,
+            optional_bytecode_compression,
+            divergences: vec![],
+        }
+    }
+
+    /// Executes a transaction mirroring the main VM logic in the batch executor, and compares the result
+    /// with the result of the main VM.
+    fn execute_tx(&mut self, tx: &Transaction, main_result: &VmExecutionResultAndLogs) {
+        self.vm.make_snapshot();
+        let result = if self.optional_bytecode_compression {
+            self.vm.make_snapshot();
+            if let (Ok(()), result) = self
+                .vm
+                .execute_transaction_with_bytecode_compression(tx.clone(), true)
+            {
+                self.vm.pop_snapshot_no_rollback();
+                result
+            } else {
+                self.vm.rollback_to_the_latest_snapshot();
+                let (compression_result, result) = self
+                    .vm
+                    .execute_transaction_with_bytecode_compression(tx.clone(), false);
+                compression_result.expect("Compression can't fail if we don't apply it");
+                result
+            }
+        } else {
+            let (compression_result, mut result) = self
+                .vm
+                .execute_transaction_with_bytecode_compression(tx.clone(), true);
+            if compression_result.is_err() {
+                result.result = ExecutionResult::Halt {
+                    reason: Halt::FailedToPublishCompressedBytecodes,
+                };
+            }
+            result
+        };
+
+        let subject = format!("transaction {:?}", tx.hash());
+        self.compare_results(&subject, main_result, &result);
+    }
+
+    fn rollback_last_tx(&mut self) {
+        self.vm.rollback_to_the_latest_snapshot();
+    }
+
+    fn start_next_miniblock(&mut self, l2_block_env: L2BlockEnv) {
+        self.vm.start_new_l2_block(l2_block_env);
+    }
+
+    /// Finishes the L1 batch and compares the outputs with the outputs of the main VM.
+    fn finish_batch(&mut self, main_batch: &FinishedL1Batch) {
+        let batch = self.vm.finish_batch();
+        self.compare_results(
+            "batch tip",
+            &main_batch.block_tip_execution_result,
+            &batch.block_tip_execution_result,
+        );
+
+        let main_state = &main_batch.final_execution_state;
+        let state = &batch.final_execution_state;
+        if main_state.deduplicated_storage_log_queries != state.deduplicated_storage_log_queries
+            || main_state.events != state.events
+            || main_state.system_logs != state.system_logs
+            || main_state.used_contract_hashes != state.used_contract_hashes
+        {
+            self.report("final execution state", ShadowVmDivergence::FinalState);
+        }
+        tracing::info!(
+            "Finished shadow execution of L1 batch #{} using protocol version {:?}",
+            self.l1_batch_number,
+            self.system_env.version
+        );
+    }
+
+    fn compare_results(
+        &mut self,
+        subject: &str,
+        main_result: &VmExecutionResultAndLogs,
+        result: &VmExecutionResultAndLogs,
+    ) {
+        if main_result.result != result.result {
+            tracing::warn!(
+                "Execution result for {subject} in L1 batch #{}: main VM {:?}, shadow VM {:?}",
+                self.l1_batch_number,
+                main_result.result,
+                result.result
+            );
+            self.report(subject, ShadowVmDivergence::Result);
+        }
+        if main_result.logs.events != result.logs.events {
+            self.report(subject, ShadowVmDivergence::Events);
+        }
+        if main_result.logs.storage_logs != result.logs.storage_logs {
+            self.report(subject, ShadowVmDivergence::StorageLogs);
+        }
+        if main_result.refunds.gas_refunded != result.refunds.gas_refunded {
+            self.report(subject, ShadowVmDivergence::Refunds);
+        }
+    }
+
+    fn report(&mut self, subject: &str, divergence: ShadowVmDivergence) {
+        tracing::warn!(
+            "Shadow VM for protocol version {:?} diverged from the main VM ({divergence:?}) on {subject} \
+             in L1 batch #{}",
+            self.system_env.version,
+            self.l1_batch_number
+        );
+        EXECUTOR_METRICS.shadow_vm_divergences[&divergence].inc();
+        self.divergences.push((subject.to_owned(), divergence));
+    }
+}
@@ -1,11 +1,18 @@
+use std::sync::{atomic::Ordering, Arc};
+
 use assert_matches::assert_matches;
+use multivm::interface::Halt;
 use test_casing::{test_casing, Product};
-use zksync_dal::{ConnectionPool, Core};
+use zksync_dal::{ConnectionPool, Core, CoreDal};
 use zksync_test_account::Account;
-use zksync_types::{get_nonce_key, utils::storage_key_for_eth_balance, PriorityOpId};
+use zksync_types::{
+    get_nonce_key, utils::storage_key_for_eth_balance, PriorityOpId, ProtocolVersion,
+    ProtocolVersionId,
+};
 
 use self::tester::{AccountLoadNextExecutable, StorageSnapshot, TestConfig, Tester};
-use super::TxExecutionResult;
+use super::{isolated::testonly::ThreadLauncher, shadow_vm::ShadowVmHandle, TxExecutionResult};
+use crate::state_keeper::{metrics::ShadowVmDivergence, tests::BASE_SYSTEM_CONTRACTS};
 
 mod read_storage_factory;
 mod tester;
@@ -486,3 +493,168 @@ async fn catchup_rocksdb_cache() {
     let res = executor.execute_tx(tx).await;
     assert_rejected(&res);
 }
+
+/// Checks that the process-isolated batch executor produces the same results as the in-process one.
+#[tokio::test]
+async fn isolated_executor_matches_in_process_executor() {
+    let connection_pool = ConnectionPool::<Core>::constrained_test_pool(1).await;
+    let mut alice = Account::random();
+
+    let mut tester = Tester::new(connection_pool);
+    tester.genesis().await;
+    tester.fund(&[alice.address()]).await;
+    let txs: Vec<_> = (0..3).map(|_| alice.execute()).collect();
+    let rolled_back_tx = alice.execute();
+
+    let executor = tester.create_batch_executor(StorageType::Rocksdb).await;
+    for tx in &txs {
+        assert_executed(&executor.execute_tx(tx.clone()).await);
+    }
+    assert_executed(&executor.execute_tx(rolled_back_tx.clone()).await);
+    executor.rollback_last_tx().await;
+    let expected_batch = executor.finish_batch().await;
+
+    let launcher = ThreadLauncher::default();
+    let launched_workers = launcher.launched_workers();
+    let executor = tester
+        .create_isolated_batch_executor(Arc::new(launcher))
+        .await;
+    for tx in txs {
+        assert_executed(&executor.execute_tx(tx).await);
+    }
+    assert_executed(&executor.execute_tx(rolled_back_tx).await);
+    executor.rollback_last_tx().await;
+    let finished_batch = executor.finish_batch().await;
+
+    assert_eq!(launched_workers.load(Ordering::SeqCst), 1);
+    assert_eq!(
+        finished_batch.final_execution_state,
+        expected_batch.final_execution_state
+    );
+}
+
+/// Checks that a crashed worker is restarted and the batch state is restored by replaying executed commands.
+#[tokio::test]
+async fn isolated_executor_recovers_from_worker_crash() {
+    let connection_pool = ConnectionPool::<Core>::constrained_test_pool(1).await;
+    let mut alice = Account::random();
+
+    let mut tester = Tester::new(connection_pool);
+    tester.genesis().await;
+    tester.fund(&[alice.address()]).await;
+    let txs: Vec<_> = (0..3).map(|_| alice.execute()).collect();
+
+    let executor = tester.create_batch_executor(StorageType::Rocksdb).await;
+    for tx in &txs {
+        assert_executed(&executor.execute_tx(tx.clone()).await);
+    }
+    let expected_batch = executor.finish_batch().await;
+
+    // The first worker crashes when receiving the third transaction.
+    let launcher = ThreadLauncher::default().with_crash_after_commands(2);
+    let launched_workers = launcher.launched_workers();
+    let executor = tester
+        .create_isolated_batch_executor(Arc::new(launcher))
+        .await;
+    for tx in txs {
+        assert_executed(&executor.execute_tx(tx).await);
+    }
+    let finished_batch = executor.finish_batch().await;
+
+    assert_eq!(launched_workers.load(Ordering::SeqCst), 2);
+    assert_eq!(
+        finished_batch.final_execution_state,
+        expected_batch.final_execution_state
+    );
+}
+
+/// Checks that a transaction consistently crashing the worker is rejected, and the batch can proceed after it.
+#[tokio::test]
+async fn isolated_executor_rejects_transaction_crashing_worker() {
+    let connection_pool = ConnectionPool::<Core>::constrained_test_pool(1).await;
+    let mut alice = Account::random();
+    let mut bob = Account::random();
+
+    let mut tester = Tester::new(connection_pool);
+    tester.genesis().await;
+    tester.fund(&[alice.address(), bob.address()]).await;
+    let poison_tx = bob.execute();
+
+    let launcher = ThreadLauncher::default().with_poison_tx(poison_tx.hash());
+    let launched_workers = launcher.launched_workers();
+    let executor = tester
+        .create_isolated_batch_executor(Arc::new(launcher))
+        .await;
+
+    assert_executed(&executor.execute_tx(alice.execute()).await);
+    let res = executor.execute_tx(poison_tx).await;
+    assert_matches!(
+        res,
+        TxExecutionResult::RejectedByVm {
+            reason: Halt::VMPanic
+        }
+    );
+    executor.rollback_last_tx().await;
+    assert_executed(&executor.execute_tx(alice.execute()).await);
+    executor.finish_batch().await;
+
+    // The initial worker + 3 workers crashed by the poison transaction.
+    assert_eq!(launched_workers.load(Ordering::SeqCst), 4);
+}
+
+/// Checks that the shadow VM uses the upcoming protocol version and reports divergences from the main VM.
+#[tokio::test(flavor = "multi_thread")]
+async fn shadow_vm_reports_divergences() {
+    let connection_pool = ConnectionPool::<Core>::constrained_test_pool(2).await;
+    let mut alice = Account::random();
+
+    let mut tester = Tester::new(connection_pool.clone());
+    tester.genesis().await;
+    tester.fund(&[alice.address()]).await;
+    let executor = tester.create_batch_executor(StorageType::Postgres).await;
+
+    let tx = alice.execute();
+    let res = executor.execute_tx(tx.clone()).await;
+    let TxExecutionResult::Success { tx_result, .. } = res else {
+        panic!("Unexpected execution result: {res:?}");
+    };
+    let finished_batch = executor.finish_batch().await;
+    // Tamper with the main VM output so that the shadow VM output differs from it.
+    let mut tampered_tx_result = (*tx_result).clone();
+    assert!(!tampered_tx_result.logs.events.is_empty());
+    tampered_tx_result.logs.events.clear();
+
+    let run_shadow_vm = || {
+        let (l1_batch_env, system_env) = tester.default_batch_params();
+        let mut shadow_vm =
+            ShadowVmHandle::spawn(connection_pool.clone(), l1_batch_env, system_env, false)
+                .unwrap();
+        shadow_vm.execute_tx(&tx, &tampered_tx_result);
+        shadow_vm.finish_batch(&finished_batch);
+        tokio::task::spawn_blocking(move || shadow_vm.join())
+    };
+
+    // There is no upcoming protocol version, so shadow execution should be skipped.
+    let divergences = run_shadow_vm().await.unwrap();
+    assert!(divergences.is_empty(), "{divergences:?}");
+
+    connection_pool
+        .connection()
+        .await
+        .unwrap()
+        .protocol_versions_dal()
+        .save_protocol_version_with_tx(&ProtocolVersion {
+            id: ProtocolVersionId::next(),
+            base_system_contracts_hashes: BASE_SYSTEM_CONTRACTS.hashes(),
+            ..ProtocolVersion::default()
+        })
+        .await
+        .unwrap();
+
+    let divergences = run_shadow_vm().await.unwrap();
+    let expected_subject = format!("transaction {:?}", tx.hash());
+    assert_eq!(
+        divergences,
+        [(expected_subject, ShadowVmDivergence::Events)]
+    );
+}
@@ -21,7 +21,7 @@ impl ReadStorageFactory for PostgresFactory {
         _stop_receiver: &watch::Receiver<bool>,
     ) -> anyhow::Result<Option<PgOrRocksdbStorage<'_>>> {
         Ok(Some(
-            AsyncRocksdbCache::access_storage_pg(&self.pool).await?,
+            AsyncRocksdbCache::access_storage_pg(&self.pool, None).await?,
         ))
     }
 }
@@ -30,7 +30,7 @@ use super::{
 use crate::{
     genesis::create_genesis_l1_batch,
     state_keeper::{
-        batch_executor::{BatchExecutorHandle, TxExecutionResult},
+        batch_executor::{isolated::WorkerLauncher, BatchExecutorHandle, TxExecutionResult},
         state_keeper_storage::ReadStorageFactory,
         tests::{default_l1_batch_env, default_system_env, BASE_SYSTEM_CONTRACTS},
         AsyncRocksdbCache, BatchExecutor, MainBatchExecutor,
@@ -157,6 +157,30 @@ impl Tester {
             .expect("Batch executor was interrupted")
     }
 
+    /// Creates a process-isolated batch executor using the specified worker launcher and RocksDB storage.
+    pub(super) async fn create_isolated_batch_executor(
+        &mut self,
+        launcher: Arc<dyn WorkerLauncher>,
+    ) -> BatchExecutorHandle {
+        let (l1_batch_env, system_env) = self.default_batch_params();
+        let storage_factory = RocksdbFactory::new(
+            self.pool(),
+            self.state_keeper_db_path(),
+            self.enum_index_migration_chunk_size(),
+        );
+        let mut batch_executor = MainBatchExecutor::new(
+            Arc::new(storage_factory),
+            self.config.save_call_traces,
+            false,
+        )
+        .with_worker_launcher(launcher);
+        let (_stop_sender, stop_receiver) = watch::channel(false);
+        batch_executor
+            .init_batch(l1_batch_env, system_env, &stop_receiver)
+            .await
+            .expect("Batch executor was interrupted")
+    }
+
     pub(super) async fn recover_batch_executor(
         &mut self,
         snapshot: &SnapshotRecoveryStatus,
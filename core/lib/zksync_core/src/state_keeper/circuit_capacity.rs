@@ -0,0 +1,146 @@
+//! Circuit capacity accounting for the open L1 batch.
+//!
+//! Circuit usage of a transaction is only known after it is executed. If a transaction overflows the circuit
+//! capacity of the batch, it is rolled back and re-executed in the next batch, which wastes VM time.
+//! To avoid this, circuit usage of transactions is estimated based on previously executed transactions calling
+//! the same contract, and transactions that are expected to overflow the batch are deferred to the next batch
+//! without being executed.
+
+use std::num::NonZeroUsize;
+
+use lru::LruCache;
+use zksync_types::{circuit::CircuitStatistic, Address, Transaction};
+
+use super::metrics::{CircuitAdmissionResult, CircuitType, CIRCUIT_METRICS};
+
+/// Maximum number of contracts for which circuit usage estimates are retained.
+const MAX_TRACKED_CONTRACTS: usize = 10_000;
+/// Weight of the latest observation in the circuit usage estimate for a contract.
+const LATEST_OBSERVATION_WEIGHT: f32 = 0.5;
+
+/// Number of circuits of each type used by a transaction or a batch, in the order defined by [`CircuitType::split()`].
+type CircuitUsage = [f32; 13];
+
+fn circuit_usage(statistic: &CircuitStatistic) -> CircuitUsage {
+    CircuitType::split(statistic).map(|(_, value)| value)
+}
+
+/// Rounds up the number of circuits of each type and adds them, similarly to [`CircuitStatistic::total()`].
+fn total_circuits(usage: &CircuitUsage) -> usize {
+    usage.iter().map(|&value| value.ceil() as usize).sum()
+}
+
+/// Estimates circuit usage of transactions and decides whether they can be admitted to the open L1 batch.
+#[derive(Debug)]
+pub(super) struct CircuitCapacityTracker {
+    /// Estimated circuit usage of a transaction keyed by the called contract.
+    estimates: LruCache<Address, CircuitUsage>,
+}
+
+impl Default for CircuitCapacityTracker {
+    fn default() -> Self {
+        Self {
+            estimates: LruCache::new(NonZeroUsize::new(MAX_TRACKED_CONTRACTS).unwrap()),
+        }
+    }
+}
+
+impl CircuitCapacityTracker {
+    /// Records circuit usage of an executed transaction.
+    pub fn record(&mut self, tx: &Transaction, statistic: &CircuitStatistic) {
+        let observed = circuit_usage(statistic);
+        let contract = tx.execute.contract_address;
+        if let Some(estimate) = self.estimates.get_mut(&contract) {
+            for (estimated, observed) in estimate.iter_mut().zip(observed) {
+                *estimated += (observed - *estimated) * LATEST_OBSERVATION_WEIGHT;
+            }
+        } else {
+            self.estimates.put(contract, observed);
+        }
+    }
+
+    /// Checks whether the transaction can be added to a batch with the specified circuit usage without exceeding
+    /// `capacity` (which must not include the batch tip overhead). Transactions without an estimate are always admitted.
+    pub fn admit(
+        &mut self,
+        tx: &Transaction,
+        batch_statistic: &CircuitStatistic,
+        capacity: usize,
+    ) -> bool {
+        let Some(estimate) = self.estimates.get(&tx.execute.contract_address) else {
+            CIRCUIT_METRICS.tx_admission[&CircuitAdmissionResult::NoEstimate].inc();
+            return true;
+        };
+
+        let mut expected_usage = circuit_usage(batch_statistic);
+        for (expected, estimated) in expected_usage.iter_mut().zip(estimate) {
+            *expected += estimated;
+        }
+        let is_admitted = total_circuits(&expected_usage) < capacity;
+        let result = if is_admitted {
+            CircuitAdmissionResult::Admitted
+        } else {
+            CircuitAdmissionResult::Deferred
+        };
+        CIRCUIT_METRICS.tx_admission[&result].inc();
+        is_admitted
+    }
+
+    /// Reports circuit usage of the open L1 batch to metrics.
+    pub fn report_batch_usage(
+        batch_statistic: &CircuitStatistic,
+        batch_tip_overhead: usize,
+        capacity: Option<usize>,
+    ) {
+        for (circuit_type, value) in CircuitType::split(batch_statistic) {
+            CIRCUIT_METRICS.batch_used[&circuit_type].set(value.into());
+        }
+        CIRCUIT_METRICS
+            .batch_used_total
+            .set(batch_statistic.total() + batch_tip_overhead);
+        if let Some(capacity) = capacity {
+            CIRCUIT_METRICS.batch_capacity.set(capacity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_keeper::tests::create_transaction;
+
+    fn tx_to(contract: Address) -> Transaction {
+        let mut tx = create_transaction(10, 100);
+        tx.execute.contract_address = contract;
+        tx
+    }
+
+    fn statistic(main_vm: f32, keccak256: f32) -> CircuitStatistic {
+        CircuitStatistic {
+            main_vm,
+            keccak256,
+            ..CircuitStatistic::default()
+        }
+    }
+
+    #[test]
+    fn admitting_transactions() {
+        let mut tracker = CircuitCapacityTracker::default();
+        let contract = Address::repeat_byte(1);
+        let batch_statistic = statistic(5.5, 1.0);
+        // No estimate for the contract yet.
+        assert!(tracker.admit(&tx_to(contract), &batch_statistic, 7));
+
+        tracker.record(&tx_to(contract), &statistic(0.4, 0.0));
+        // The estimated usage fits into the partially filled main VM circuit: 6 + 1 circuits.
+        assert!(tracker.admit(&tx_to(contract), &batch_statistic, 8));
+        assert!(!tracker.admit(&tx_to(contract), &batch_statistic, 7));
+        // Estimates are per contract.
+        assert!(tracker.admit(&tx_to(Address::repeat_byte(2)), &batch_statistic, 1));
+
+        tracker.record(&tx_to(contract), &statistic(0.4, 2.0));
+        // The estimate is now (0.4, 1.0), i.e., 6 + 2 circuits in total.
+        assert!(!tracker.admit(&tx_to(contract), &batch_statistic, 8));
+        assert!(tracker.admit(&tx_to(contract), &batch_statistic, 9));
+    }
+}
@@ -0,0 +1,127 @@
+//! Selection of fee accounts for L1 batches.
+
+use zksync_config::configs::chain::{FeeAccountRotation, StateKeeperConfig};
+use zksync_types::{Address, L1BatchNumber};
+
+/// Selects the fee account (i.e., the operator account receiving collected fees) for each L1 batch
+/// according to the configured [`FeeAccountRotation`] policy. Selection only depends on the L1 batch number,
+/// so the state keeper and the API server agree on the fee account, and the choice is stable across restarts.
+#[derive(Debug, Clone)]
+pub struct FeeAccountSelector {
+    /// Fee accounts; the first account is the main one.
+    accounts: Vec<Address>,
+    rotation: FeeAccountRotation,
+}
+
+impl Default for FeeAccountSelector {
+    fn default() -> Self {
+        Self::fixed(Address::default())
+    }
+}
+
+impl FeeAccountSelector {
+    /// Creates a selector always returning the specified account.
+    pub fn fixed(fee_account: Address) -> Self {
+        Self {
+            accounts: vec![fee_account],
+            rotation: FeeAccountRotation::Disabled,
+        }
+    }
+
+    /// Creates a selector based on the main fee account (from wallets) and the state keeper config.
+    pub fn new(main_fee_account: Address, config: &StateKeeperConfig) -> Self {
+        let mut accounts = vec![main_fee_account];
+        accounts.extend(
+            config
+                .additional_fee_account_addrs
+                .iter()
+                .copied()
+                .filter(|&address| address != main_fee_account),
+        );
+        Self {
+            accounts,
+            rotation: config.fee_account_rotation.unwrap_or_default(),
+        }
+    }
+
+    /// Returns the main fee account.
+    pub fn main_account(&self) -> Address {
+        self.accounts[0]
+    }
+
+    /// Returns the fee account for the specified L1 batch.
+    pub fn for_l1_batch(&self, number: L1BatchNumber) -> Address {
+        let len = self.accounts.len() as u64;
+        let index = match self.rotation {
+            FeeAccountRotation::Disabled => 0,
+            FeeAccountRotation::RoundRobin => u64::from(number.0) % len,
+            FeeAccountRotation::PerBatch => mix_bits(number.0.into()) % len,
+        };
+        self.accounts[index as usize]
+    }
+}
+
+/// `splitmix64` finalizer; used to map L1 batch numbers to fee accounts in a deterministic, but uncorrelated way.
+fn mix_bits(mut value: u64) -> u64 {
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    value ^ (value >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn config(rotation: FeeAccountRotation) -> StateKeeperConfig {
+        StateKeeperConfig {
+            additional_fee_account_addrs: vec![Address::repeat_byte(2), Address::repeat_byte(3)],
+            fee_account_rotation: Some(rotation),
+            ..StateKeeperConfig::for_tests()
+        }
+    }
+
+    #[test]
+    fn fixed_fee_account() {
+        let main_account = Address::repeat_byte(1);
+        let selector = FeeAccountSelector::fixed(main_account);
+        for number in 0..10 {
+            assert_eq!(selector.for_l1_batch(L1BatchNumber(number)), main_account);
+        }
+
+        let selector = FeeAccountSelector::new(main_account, &config(FeeAccountRotation::Disabled));
+        assert_eq!(selector.main_account(), main_account);
+        for number in 0..10 {
+            assert_eq!(selector.for_l1_batch(L1BatchNumber(number)), main_account);
+        }
+    }
+
+    #[test]
+    fn round_robin_rotation() {
+        let main_account = Address::repeat_byte(1);
+        let selector =
+            FeeAccountSelector::new(main_account, &config(FeeAccountRotation::RoundRobin));
+        let accounts: Vec<_> = (0..6)
+            .map(|number| selector.for_l1_batch(L1BatchNumber(number)))
+            .collect();
+        let expected_accounts = [1, 2, 3, 1, 2, 3].map(Address::repeat_byte);
+        assert_eq!(accounts, expected_accounts);
+    }
+
+    #[test]
+    fn per_batch_rotation() {
+        let main_account = Address::repeat_byte(1);
+        let selector = FeeAccountSelector::new(main_account, &config(FeeAccountRotation::PerBatch));
+        let mut counts = HashMap::<_, usize>::new();
+        for number in 0..3_000 {
+            let account = selector.for_l1_batch(L1BatchNumber(number));
+            assert_eq!(account, selector.for_l1_batch(L1BatchNumber(number)));
+            *counts.entry(account).or_default() += 1;
+        }
+        assert_eq!(counts.len(), 3);
+        for count in counts.into_values() {
+            assert!((800..=1_200).contains(&count), "{count}");
+        }
+    }
+}
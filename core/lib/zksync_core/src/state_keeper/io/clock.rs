@@ -0,0 +1,293 @@
+//! Time sources used to assign timestamps to miniblocks and L1 batches, and clock skew monitoring.
+
+use std::{fmt, sync::Arc, time::Duration};
+
+use anyhow::Context as _;
+use tokio::{net::UdpSocket, sync::watch};
+use zksync_utils::time::millis_since_epoch;
+
+use crate::state_keeper::metrics::KEEPER_METRICS;
+
+/// Source of wall-clock time for the state keeper.
+pub trait TimeSource: fmt::Debug + Send + Sync {
+    /// Returns the number of milliseconds since UNIX epoch.
+    fn now_millis(&self) -> u64;
+}
+
+/// [`TimeSource`] based on the system clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemTimeSource;
+
+impl TimeSource for SystemTimeSource {
+    fn now_millis(&self) -> u64 {
+        millis_since_epoch() as u64
+    }
+}
+
+/// Result of comparing the local clock with the reference (NTP) clock.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ClockSkewStatus {
+    /// Skew is not known yet, or the NTP server cannot be reached.
+    #[default]
+    Unknown,
+    /// Skew is within the allowed bounds.
+    Acceptable,
+    /// Skew exceeds the allowed bounds. The skew is positive if the local clock is behind the reference clock.
+    Excessive { skew_ms: i64 },
+}
+
+/// Number of seconds between the NTP epoch (1900-01-01) and the UNIX epoch.
+const NTP_UNIX_EPOCH_DIFF_SECS: u64 = 2_208_988_800;
+const NTP_PACKET_LEN: usize = 48;
+/// Offset of the transmit timestamp in an NTP packet.
+const NTP_TRANSMIT_TIMESTAMP_OFFSET: usize = 40;
+const NTP_SERVER_MODE: u8 = 4;
+
+fn ntp_timestamp_to_millis(raw: [u8; 8]) -> Option<u64> {
+    let seconds = u64::from(u32::from_be_bytes(raw[..4].try_into().unwrap()));
+    let fraction = u64::from(u32::from_be_bytes(raw[4..].try_into().unwrap()));
+    let unix_seconds = seconds.checked_sub(NTP_UNIX_EPOCH_DIFF_SECS)?;
+    Some(unix_seconds * 1_000 + ((fraction * 1_000) >> 32))
+}
+
+/// Periodically compares the local clock with an NTP server and publishes the resulting [`ClockSkewStatus`].
+/// The state keeper refuses to produce miniblocks while the skew is excessive, since timestamps assigned
+/// by a skewed clock can be rejected on L1 or break timestamp monotonicity once the clock is fixed.
+#[derive(Debug)]
+pub struct ClockSkewMonitor {
+    ntp_server: String,
+    max_skew: Duration,
+    poll_interval: Duration,
+    request_timeout: Duration,
+    time_source: Arc<dyn TimeSource>,
+    status_sender: watch::Sender<ClockSkewStatus>,
+}
+
+impl ClockSkewMonitor {
+    const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(30);
+    const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Creates a monitor querying the specified NTP server (`host:port`).
+    pub fn new(ntp_server: String, max_skew: Duration) -> Self {
+        Self {
+            ntp_server,
+            max_skew,
+            poll_interval: Self::DEFAULT_POLL_INTERVAL,
+            request_timeout: Self::DEFAULT_REQUEST_TIMEOUT,
+            time_source: Arc::new(SystemTimeSource),
+            status_sender: watch::channel(ClockSkewStatus::Unknown).0,
+        }
+    }
+
+    /// Sets the local time source to check. Should be the same source as used by the state keeper I/O.
+    pub fn with_time_source(mut self, time_source: Arc<dyn TimeSource>) -> Self {
+        self.time_source = time_source;
+        self
+    }
+
+    /// Subscribes to clock skew status updates.
+    pub fn subscribe(&self) -> watch::Receiver<ClockSkewStatus> {
+        self.status_sender.subscribe()
+    }
+
+    /// Queries the NTP server and returns the clock skew in milliseconds; positive if the local clock is behind.
+    async fn query_skew(&self) -> anyhow::Result<i64> {
+        let socket = UdpSocket::bind("0.0.0.0:0")
+            .await
+            .context("failed binding UDP socket")?;
+        socket
+            .connect(&self.ntp_server)
+            .await
+            .with_context(|| format!("failed connecting to NTP server `{}`", self.ntp_server))?;
+
+        let mut request = [0_u8; NTP_PACKET_LEN];
+        request[0] = 0x23; // LI = 0 (no warning), VN = 4, mode = 3 (client)
+        let sent_at = self.time_source.now_millis();
+        socket
+            .send(&request)
+            .await
+            .context("failed sending NTP request")?;
+        let mut response = [0_u8; NTP_PACKET_LEN];
+        let response_len = tokio::time::timeout(self.request_timeout, socket.recv(&mut response))
+            .await
+            .context("timed out waiting for NTP response")?
+            .context("failed receiving NTP response")?;
+        let received_at = self.time_source.now_millis();
+
+        anyhow::ensure!(
+            response_len == NTP_PACKET_LEN,
+            "unexpected NTP response length: {response_len}"
+        );
+        anyhow::ensure!(
+            response[0] & 0b111 == NTP_SERVER_MODE,
+            "unexpected mode in NTP response: {}",
+            response[0] & 0b111
+        );
+        let raw_timestamp = response
+            [NTP_TRANSMIT_TIMESTAMP_OFFSET..NTP_TRANSMIT_TIMESTAMP_OFFSET + 8]
+            .try_into()
+            .unwrap();
+        let server_millis = ntp_timestamp_to_millis(raw_timestamp)
+            .context("NTP server returned timestamp before UNIX epoch")?;
+        // Assume that the server has sent the response in the middle of the round trip.
+        let local_millis = (i128::from(sent_at) + i128::from(received_at)) / 2;
+        Ok((i128::from(server_millis) - local_millis) as i64)
+    }
+
+    async fn update_status(&self) {
+        let status = match self.query_skew().await {
+            Ok(skew_ms) => {
+                KEEPER_METRICS.clock_skew_ms.set(skew_ms);
+                if skew_ms.unsigned_abs() > self.max_skew.as_millis() as u64 {
+                    tracing::error!(
+                        "Local clock is skewed by {skew_ms}ms compared to NTP server `{}` (max allowed skew: {:?}); \
+                         miniblock production is paused until the clock is fixed",
+                        self.ntp_server,
+                        self.max_skew
+                    );
+                    ClockSkewStatus::Excessive { skew_ms }
+                } else {
+                    tracing::debug!("Local clock is skewed by {skew_ms}ms compared to NTP server");
+                    ClockSkewStatus::Acceptable
+                }
+            }
+            Err(err) => {
+                // We don't want to block miniblock production if the NTP server is unavailable.
+                tracing::warn!(
+                    "Failed checking clock skew against NTP server `{}`: {err:#}",
+                    self.ntp_server
+                );
+                ClockSkewStatus::Unknown
+            }
+        };
+        self.status_sender.send_replace(status);
+    }
+
+    /// Runs this monitor until a stop signal is received.
+    pub async fn run(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        while !*stop_receiver.borrow_and_update() {
+            self.update_status().await;
+            // Error here corresponds to a timeout w/o `stop_receiver` changed; we're OK with this.
+            tokio::time::timeout(self.poll_interval, stop_receiver.changed())
+                .await
+                .ok();
+        }
+        tracing::info!("Stop signal received, clock skew monitor is shutting down");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use super::*;
+
+    /// Time source with manually set time.
+    #[derive(Debug)]
+    pub(crate) struct MockTimeSource(AtomicU64);
+
+    impl MockTimeSource {
+        pub fn new(millis: u64) -> Self {
+            Self(AtomicU64::new(millis))
+        }
+
+        pub fn set(&self, millis: u64) {
+            self.0.store(millis, Ordering::SeqCst);
+        }
+    }
+
+    impl TimeSource for MockTimeSource {
+        fn now_millis(&self) -> u64 {
+            self.0.load(Ordering::SeqCst)
+        }
+    }
+
+    fn millis_to_ntp_timestamp(millis: u64) -> [u8; 8] {
+        let seconds = (millis / 1_000 + NTP_UNIX_EPOCH_DIFF_SECS) as u32;
+        let fraction = (((millis % 1_000) << 32) / 1_000) as u32;
+        let mut raw = [0_u8; 8];
+        raw[..4].copy_from_slice(&seconds.to_be_bytes());
+        raw[4..].copy_from_slice(&fraction.to_be_bytes());
+        raw
+    }
+
+    /// Spawns a mock NTP server always responding with the specified time.
+    async fn spawn_ntp_server(server_millis: u64) -> String {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let local_addr = socket.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut request = [0_u8; NTP_PACKET_LEN];
+            loop {
+                let (_, client_addr) = socket.recv_from(&mut request).await.unwrap();
+                let mut response = [0_u8; NTP_PACKET_LEN];
+                response[0] = 0x24; // LI = 0, VN = 4, mode = 4 (server)
+                response[NTP_TRANSMIT_TIMESTAMP_OFFSET..]
+                    .copy_from_slice(&millis_to_ntp_timestamp(server_millis));
+                socket.send_to(&response, client_addr).await.unwrap();
+            }
+        });
+        local_addr.to_string()
+    }
+
+    #[test]
+    fn converting_ntp_timestamps() {
+        for millis in [0, 1, 999, 1_000, 1_714_000_000_123] {
+            let raw = millis_to_ntp_timestamp(millis);
+            let converted = ntp_timestamp_to_millis(raw).unwrap();
+            // Fraction conversion may lose a millisecond due to rounding.
+            assert!(millis - converted <= 1, "{millis} vs {converted}");
+        }
+        assert_eq!(ntp_timestamp_to_millis([0; 8]), None);
+    }
+
+    #[tokio::test]
+    async fn detecting_clock_skew() {
+        let server_millis = 1_714_000_000_000;
+        let ntp_server = spawn_ntp_server(server_millis).await;
+        let time_source = Arc::new(MockTimeSource::new(server_millis));
+        let monitor = ClockSkewMonitor::new(ntp_server, Duration::from_secs(1))
+            .with_time_source(time_source.clone());
+        let status = monitor.subscribe();
+        assert_eq!(*status.borrow(), ClockSkewStatus::Unknown);
+
+        monitor.update_status().await;
+        assert_eq!(*status.borrow(), ClockSkewStatus::Acceptable);
+
+        time_source.set(server_millis - 500);
+        monitor.update_status().await;
+        assert_eq!(*status.borrow(), ClockSkewStatus::Acceptable);
+
+        time_source.set(server_millis - 10_000);
+        monitor.update_status().await;
+        assert_eq!(
+            *status.borrow(),
+            ClockSkewStatus::Excessive { skew_ms: 10_000 }
+        );
+
+        time_source.set(server_millis + 3_600_000);
+        monitor.update_status().await;
+        assert_eq!(
+            *status.borrow(),
+            ClockSkewStatus::Excessive {
+                skew_ms: -3_600_000
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn clock_skew_is_unknown_if_ntp_server_is_unavailable() {
+        // Bind a socket that never responds.
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let ntp_server = socket.local_addr().unwrap().to_string();
+        let mut monitor = ClockSkewMonitor::new(ntp_server, Duration::from_secs(1));
+        monitor.request_timeout = Duration::from_millis(50);
+        let status = monitor.subscribe();
+
+        monitor
+            .status_sender
+            .send_replace(ClockSkewStatus::Acceptable);
+        monitor.update_status().await;
+        assert_eq!(*status.borrow(), ClockSkewStatus::Unknown);
+    }
+}
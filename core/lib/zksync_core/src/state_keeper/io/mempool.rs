@@ -1,6 +1,6 @@
 use std::{
     cmp,
-    collections::HashMap,
+    collections::{HashMap, HashSet, VecDeque},
     sync::Arc,
     time::{Duration, Instant},
 };
@@ -8,32 +8,35 @@ use std::{
 use anyhow::Context as _;
 use async_trait::async_trait;
 use multivm::{interface::Halt, utils::derive_base_fee_and_gas_per_pubdata};
+use tokio::sync::watch;
 use vm_utils::storage::L1BatchParamsProvider;
 use zksync_config::configs::chain::StateKeeperConfig;
 use zksync_contracts::BaseSystemContracts;
-use zksync_dal::{ConnectionPool, Core, CoreDal};
+use zksync_dal::{transactions_dal::L2TxSubmissionResult, ConnectionPool, Core, CoreDal};
 use zksync_mempool::L2TxFilter;
 use zksync_types::{
-    protocol_upgrade::ProtocolUpgradeTx, Address, L1BatchNumber, L2ChainId, MiniblockNumber,
-    ProtocolVersionId, Transaction, H256, U256,
+    fee::TransactionExecutionMetrics, protocol_upgrade::ProtocolUpgradeTx, tx::TxAccessHints,
+    L1BatchNumber, L2ChainId, MiniblockNumber, ProtocolVersionId, Transaction, H256, U256,
 };
-// TODO (SMA-1206): use seconds instead of milliseconds.
-use zksync_utils::time::millis_since_epoch;
 
 use crate::{
     fee_model::BatchFeeModelInputProvider,
     state_keeper::{
         extractors,
+        fee_accounts::FeeAccountSelector,
         io::{
+            clock::{ClockSkewMonitor, ClockSkewStatus, SystemTimeSource, TimeSource},
             common::{load_pending_batch, poll_iters, IoCursor},
-            fee_address_migration, L1BatchParams, MiniblockParams, PendingBatchData, StateKeeperIO,
+            fee_address_migration, L1BatchParams, MiniblockParams, OperatorTxProvider,
+            PendingBatchData, StateKeeperIO,
         },
         mempool_actor::l2_tx_filter,
-        metrics::KEEPER_METRICS,
+        metrics::{PriorityOpsLimit, KEEPER_METRICS},
         seal_criteria::{IoSealCriteria, TimeoutSealer},
         updates::UpdatesManager,
         MempoolGuard,
     },
+    tx_filter::{FilterRejection, TransactionFilter, TxFilterStage, TX_FILTER_METRICS},
 };
 
 /// Mempool-based sequencer for the state keeper.
@@ -46,8 +49,13 @@ pub struct MempoolIO {
     pool: ConnectionPool<Core>,
     timeout_sealer: TimeoutSealer,
     filter: L2TxFilter,
+    tx_filter: Arc<dyn TransactionFilter>,
+    priority_ops_throttler: PriorityOpsThrottler,
+    operator_txs: OperatorTxs,
+    time_source: Arc<dyn TimeSource>,
+    clock_skew: Option<watch::Receiver<ClockSkewStatus>>,
     l1_batch_params_provider: L1BatchParamsProvider,
-    fee_account: Address,
+    fee_accounts: FeeAccountSelector,
     validation_computational_gas_limit: u32,
     max_allowed_tx_gas_limit: U256,
     delay_interval: Duration,
@@ -148,12 +156,21 @@ impl StateKeeperIO for MempoolIO {
         // Block until at least one transaction in the mempool can match the filter (or timeout happens).
         // This is needed to ensure that block timestamp is not too old.
         for _ in 0..poll_iters(self.delay_interval, max_wait) {
+            if self.is_paused_on_clock_skew() {
+                tokio::time::sleep(self.delay_interval).await;
+                continue;
+            }
+
             // We cannot create two L1 batches or miniblocks with the same timestamp (forbidden by the bootloader).
             // Hence, we wait until the current timestamp is larger than the timestamp of the previous miniblock.
             // We can use `timeout_at` since `sleep_past` is cancel-safe; it only uses `sleep()` async calls.
             let timestamp = tokio::time::timeout_at(
                 deadline.into(),
-                sleep_past(cursor.prev_miniblock_timestamp, cursor.next_miniblock),
+                sleep_past(
+                    self.time_source.as_ref(),
+                    cursor.prev_miniblock_timestamp,
+                    cursor.next_miniblock,
+                ),
             );
             let Some(timestamp) = timestamp.await.ok() else {
                 return Ok(None);
@@ -184,10 +201,13 @@ impl StateKeeperIO for MempoolIO {
                 continue;
             }
 
+            self.priority_ops_throttler.start_l1_batch();
+            self.operator_txs
+                .start_l1_batch(cursor.l1_batch, cursor.next_miniblock, timestamp);
             return Ok(Some(L1BatchParams {
                 protocol_version,
                 validation_computational_gas_limit: self.validation_computational_gas_limit,
-                operator_address: self.fee_account,
+                operator_address: self.fee_accounts.for_l1_batch(cursor.l1_batch),
                 fee_input: self.filter.fee_input,
                 first_miniblock: MiniblockParams {
                     timestamp,
@@ -203,11 +223,20 @@ impl StateKeeperIO for MempoolIO {
         cursor: &IoCursor,
         max_wait: Duration,
     ) -> anyhow::Result<Option<MiniblockParams>> {
+        if self.is_paused_on_clock_skew() {
+            tokio::time::sleep(max_wait).await;
+            return Ok(None);
+        }
+
         // We must provide different timestamps for each miniblock.
         // If miniblock sealing interval is greater than 1 second then `sleep_past` won't actually sleep.
         let timeout_result = tokio::time::timeout(
             max_wait,
-            sleep_past(cursor.prev_miniblock_timestamp, cursor.next_miniblock),
+            sleep_past(
+                self.time_source.as_ref(),
+                cursor.prev_miniblock_timestamp,
+                cursor.next_miniblock,
+            ),
         )
         .await;
         let Ok(timestamp) = timeout_result else {
@@ -215,6 +244,9 @@ impl StateKeeperIO for MempoolIO {
         };
 
         let virtual_blocks = self.get_virtual_blocks_count(false, cursor.next_miniblock);
+        self.priority_ops_throttler.start_miniblock();
+        self.operator_txs
+            .start_miniblock(cursor.l1_batch, cursor.next_miniblock, timestamp);
         Ok(Some(MiniblockParams {
             timestamp,
             virtual_blocks,
@@ -225,10 +257,25 @@ impl StateKeeperIO for MempoolIO {
         &mut self,
         max_wait: Duration,
     ) -> anyhow::Result<Option<Transaction>> {
+        if let Some(tx) = self.next_operator_tx().await? {
+            return Ok(Some(tx));
+        }
+
+        // Transactions deferred by the filter are taken out of the mempool until this method returns, so that
+        // they don't block other transactions.
+        let mut deferred_txs = vec![];
+        let mut next_tx = None;
         let started_at = Instant::now();
         while started_at.elapsed() <= max_wait {
             let get_latency = KEEPER_METRICS.get_tx_from_mempool.start();
-            let maybe_tx = self.mempool.next_transaction(&self.filter);
+            let maybe_tx = match self.priority_ops_throttler.admission() {
+                PriorityOpsAdmission::Allowed => self.mempool.next_transaction(&self.filter),
+                PriorityOpsAdmission::L2Preferred => self
+                    .mempool
+                    .next_l2_transaction(&self.filter)
+                    .or_else(|| self.mempool.next_transaction(&self.filter)),
+                PriorityOpsAdmission::Denied => self.mempool.next_l2_transaction(&self.filter),
+            };
             get_latency.observe();
 
             if let Some(tx) = maybe_tx {
@@ -243,16 +290,54 @@ impl StateKeeperIO for MempoolIO {
                     self.reject(&tx, &Halt::TooBigGasLimit.to_string()).await?;
                     continue;
                 }
-                return Ok(Some(tx));
+                // Transactions may be denied by the filter after they've been accepted by the API server,
+                // e.g. if the deny list was updated in the meantime.
+                if !tx.is_l1() {
+                    match self.tx_filter.check(&tx) {
+                        Ok(()) => { /* the transaction is admitted */ }
+                        Err(FilterRejection::Denied(reason)) => {
+                            tracing::info!(
+                                "Rejecting tx {:?} denied by filter: {reason}",
+                                tx.hash()
+                            );
+                            TX_FILTER_METRICS.rejected[&TxFilterStage::StateKeeper].inc();
+                            let error =
+                                format!("Transaction denied by the sequencer policy: {reason}");
+                            self.reject(&tx, &error).await?;
+                            continue;
+                        }
+                        Err(FilterRejection::RetryLater(reason)) => {
+                            tracing::debug!("Deferring tx {:?}: {reason}", tx.hash());
+                            TX_FILTER_METRICS.deferred[&TxFilterStage::StateKeeper].inc();
+                            deferred_txs.push(tx);
+                            continue;
+                        }
+                    }
+                }
+                self.priority_ops_throttler.observe_tx(tx.is_l1());
+                next_tx = Some(tx);
+                break;
             } else {
                 tokio::time::sleep(self.delay_interval).await;
                 continue;
             }
         }
-        Ok(None)
+
+        // Return deferred transactions to the mempool, so that they are re-checked by the filter later.
+        for tx in deferred_txs {
+            self.mempool.rollback(&tx);
+            self.mempool.insert(vec![tx], HashMap::new());
+        }
+        Ok(next_tx)
     }
 
     async fn rollback(&mut self, tx: Transaction) -> anyhow::Result<()> {
+        if self.operator_txs.rollback(&tx) {
+            return Ok(());
+        }
+        if tx.is_l1() {
+            self.priority_ops_throttler.rollback_priority_op();
+        }
         // Reset nonces in the mempool.
         self.mempool.rollback(&tx);
         // Insert the transaction back.
@@ -267,7 +352,11 @@ impl StateKeeperIO for MempoolIO {
         );
 
         // Reset the nonces in the mempool, but don't insert the transaction back.
-        self.mempool.rollback(rejected);
+        // Operator transactions bypass the mempool, so there's nothing to reset for them.
+        if !self.operator_txs.remove(rejected) {
+            self.mempool.reject(rejected);
+            self.mempool.take_access_hints(rejected.hash());
+        }
 
         // Mark tx as rejected in the storage.
         let mut storage = self.pool.connection_tagged("state_keeper").await?;
@@ -283,6 +372,13 @@ impl StateKeeperIO for MempoolIO {
         Ok(())
     }
 
+    fn take_access_hints(&mut self, tx: &Transaction) -> Option<TxAccessHints> {
+        if tx.is_l1() {
+            return None;
+        }
+        self.mempool.take_access_hints(tx.hash())
+    }
+
     async fn load_base_system_contracts(
         &mut self,
         protocol_version: ProtocolVersionId,
@@ -347,12 +443,21 @@ impl StateKeeperIO for MempoolIO {
     }
 }
 
-/// Sleeps until the current timestamp is larger than the provided `timestamp`.
+/// Maximum duration of a single sleep in [`sleep_past()`]. Sleeping in bounded steps allows to react to clock changes,
+/// e.g. to the clock being fixed after a jump backwards.
+const MAX_SLEEP_STEP: Duration = Duration::from_secs(2);
+
+/// Sleeps until the current timestamp (as reported by `time_source`) is larger than the provided `timestamp`.
 ///
-/// Returns the current timestamp after the sleep. It is guaranteed to be larger than `timestamp`.
-async fn sleep_past(timestamp: u64, miniblock: MiniblockNumber) -> u64 {
-    let mut current_timestamp_millis = millis_since_epoch();
-    let mut current_timestamp = (current_timestamp_millis / 1_000) as u64;
+/// Returns the current timestamp after the sleep. It is guaranteed to be larger than `timestamp`, so miniblock
+/// timestamps never decrease even if the clock jumps backwards.
+async fn sleep_past(
+    time_source: &dyn TimeSource,
+    timestamp: u64,
+    miniblock: MiniblockNumber,
+) -> u64 {
+    let mut current_timestamp_millis = time_source.now_millis();
+    let mut current_timestamp = current_timestamp_millis / 1_000;
     match timestamp.cmp(&current_timestamp) {
         cmp::Ordering::Less => return current_timestamp,
         cmp::Ordering::Equal => {
@@ -366,6 +471,7 @@ async fn sleep_past(timestamp: u64, miniblock: MiniblockNumber) -> u64 {
             // This situation can be triggered if the system keeper is started on a pod with a different
             // system time, or if it is buggy. Thus, a one-time error could require no actions if L1 batches
             // are expected to be generated frequently.
+            KEEPER_METRICS.clock_behind_previous_miniblock.inc();
             tracing::error!(
                 "Previous miniblock timestamp {} is larger than the current timestamp {} for miniblock #{miniblock}",
                 extractors::display_timestamp(timestamp),
@@ -381,12 +487,12 @@ async fn sleep_past(timestamp: u64, miniblock: MiniblockNumber) -> u64 {
         // since we've ensured that `timestamp >= current_timestamp`.
         let wait_seconds = timestamp - current_timestamp;
         // Time to wait until the current timestamp increases.
-        let wait_millis = 1_001 - (current_timestamp_millis % 1_000) as u64;
+        let wait_millis = 1_001 - current_timestamp_millis % 1_000;
         let wait = Duration::from_millis(wait_millis + wait_seconds * 1_000);
 
-        tokio::time::sleep(wait).await;
-        current_timestamp_millis = millis_since_epoch();
-        current_timestamp = (current_timestamp_millis / 1_000) as u64;
+        tokio::time::sleep(wait.min(MAX_SLEEP_STEP)).await;
+        current_timestamp_millis = time_source.now_millis();
+        current_timestamp = current_timestamp_millis / 1_000;
 
         if current_timestamp > timestamp {
             return current_timestamp;
@@ -400,9 +506,10 @@ impl MempoolIO {
         batch_fee_input_provider: Arc<dyn BatchFeeModelInputProvider>,
         pool: ConnectionPool<Core>,
         config: &StateKeeperConfig,
-        fee_account: Address,
+        fee_accounts: FeeAccountSelector,
         delay_interval: Duration,
         chain_id: L2ChainId,
+        tx_filter: Arc<dyn TransactionFilter>,
     ) -> anyhow::Result<Self> {
         anyhow::ensure!(
             config.virtual_blocks_interval > 0,
@@ -412,6 +519,7 @@ impl MempoolIO {
             config.virtual_blocks_per_miniblock > 0,
             "Virtual blocks per miniblock must be positive"
         );
+        let priority_ops_throttler = PriorityOpsThrottler::new(config)?;
 
         let mut storage = pool.connection_tagged("state_keeper").await?;
         let l1_batch_params_provider = L1BatchParamsProvider::new(&mut storage)
@@ -426,8 +534,13 @@ impl MempoolIO {
             timeout_sealer: TimeoutSealer::new(config),
             filter: L2TxFilter::default(),
             // ^ Will be initialized properly on the first newly opened batch
+            tx_filter,
+            priority_ops_throttler,
+            operator_txs: OperatorTxs::default(),
+            time_source: Arc::new(SystemTimeSource),
+            clock_skew: None,
             l1_batch_params_provider,
-            fee_account,
+            fee_accounts,
             validation_computational_gas_limit: config.validation_computational_gas_limit,
             max_allowed_tx_gas_limit: config.max_allowed_l2_tx_gas_limit.into(),
             delay_interval,
@@ -438,6 +551,93 @@ impl MempoolIO {
         })
     }
 
+    /// Sets the provider of operator-defined transactions executed at the start of each miniblock.
+    pub fn with_operator_tx_provider(mut self, provider: Arc<dyn OperatorTxProvider>) -> Self {
+        self.operator_txs.provider = Some(provider);
+        self
+    }
+
+    /// Sets the time source used to assign timestamps to miniblocks and L1 batches. By default, the system clock is used.
+    pub fn with_time_source(mut self, time_source: Arc<dyn TimeSource>) -> Self {
+        self.time_source = time_source;
+        self
+    }
+
+    /// Pauses miniblock production while the clock skew reported by the provided monitor is excessive.
+    pub fn with_clock_skew_monitor(mut self, monitor: &ClockSkewMonitor) -> Self {
+        self.clock_skew = Some(monitor.subscribe());
+        self
+    }
+
+    fn is_paused_on_clock_skew(&self) -> bool {
+        let Some(clock_skew) = &self.clock_skew else {
+            return false;
+        };
+        let status = *clock_skew.borrow();
+        if let ClockSkewStatus::Excessive { skew_ms } = status {
+            tracing::debug!(
+                "Miniblock production is paused because of excessive clock skew ({skew_ms}ms)"
+            );
+            KEEPER_METRICS.paused_on_clock_skew.inc();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns the next operator-defined transaction for the current miniblock, if any. Transactions are loaded
+    /// from the provider on the first call in a miniblock.
+    async fn next_operator_tx(&mut self) -> anyhow::Result<Option<Transaction>> {
+        let pending_load = self.operator_txs.pending_load.take();
+        let (Some(provider), Some((l1_batch, miniblock, timestamp))) =
+            (self.operator_txs.provider.clone(), pending_load)
+        else {
+            return Ok(self.operator_txs.pop());
+        };
+
+        let txs = provider
+            .miniblock_transactions(l1_batch, miniblock, timestamp)
+            .await
+            .with_context(|| {
+                format!("failed getting operator transactions for miniblock #{miniblock}")
+            })?;
+        if !txs.is_empty() {
+            // Transactions are persisted and marked as loaded into the mempool atomically, so that
+            // they are not picked up by the mempool fetcher.
+            let mut storage = self.pool.connection_tagged("state_keeper").await?;
+            let mut transaction = storage.start_transaction().await?;
+            let mut tx_hashes = Vec::with_capacity(txs.len());
+            for tx in txs {
+                let tx_hash = tx.hash();
+                let submission_result = transaction
+                    .transactions_dal()
+                    .insert_transaction_l2(&tx, TransactionExecutionMetrics::default())
+                    .await?;
+                if !matches!(
+                    submission_result,
+                    L2TxSubmissionResult::Added | L2TxSubmissionResult::Replaced
+                ) {
+                    tracing::warn!(
+                        "Skipping operator transaction {tx_hash:?} for miniblock #{miniblock}: {submission_result}"
+                    );
+                    continue;
+                }
+                tx_hashes.push(tx_hash);
+                self.operator_txs.push(tx.into());
+            }
+            transaction
+                .transactions_dal()
+                .mark_txs_as_in_mempool(&tx_hashes)
+                .await?;
+            transaction.commit().await?;
+            tracing::debug!(
+                "Injected {} operator transactions into miniblock #{miniblock}",
+                tx_hashes.len()
+            );
+        }
+        Ok(self.operator_txs.pop())
+    }
+
     /// "virtual_blocks_per_miniblock" will be created either if the miniblock_number % virtual_blocks_interval == 0 or
     /// the miniblock is the first one in the batch.
     /// For instance:
@@ -456,6 +656,169 @@ impl MempoolIO {
     }
 }
 
+/// Admission policy for priority operations returned by [`PriorityOpsThrottler`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PriorityOpsAdmission {
+    /// Priority operations are executed before L2 transactions.
+    Allowed,
+    /// L2 transactions are executed before priority operations.
+    L2Preferred,
+    /// Priority operations must not be executed.
+    Denied,
+}
+
+/// Limits the number of priority operations admitted by [`MempoolIO`] so that a burst of L1 deposits
+/// doesn't crowd out L2 transactions. Counters are local to the I/O, so they're reset on restart
+/// (i.e., transactions in the pending L1 batch are not accounted for).
+#[derive(Debug, Default)]
+struct PriorityOpsThrottler {
+    max_per_miniblock: Option<usize>,
+    max_per_l1_batch: Option<usize>,
+    max_consecutive: Option<usize>,
+    in_miniblock: usize,
+    in_l1_batch: usize,
+    consecutive: usize,
+}
+
+impl PriorityOpsThrottler {
+    fn new(config: &StateKeeperConfig) -> anyhow::Result<Self> {
+        let limits = [
+            (
+                "max_priority_ops_per_miniblock",
+                config.max_priority_ops_per_miniblock,
+            ),
+            (
+                "max_priority_ops_per_batch",
+                config.max_priority_ops_per_batch,
+            ),
+            (
+                "max_consecutive_priority_ops",
+                config.max_consecutive_priority_ops,
+            ),
+        ];
+        for (name, limit) in limits {
+            anyhow::ensure!(limit != Some(0), "`{name}` must be positive");
+        }
+
+        Ok(Self {
+            max_per_miniblock: config.max_priority_ops_per_miniblock,
+            max_per_l1_batch: config.max_priority_ops_per_batch,
+            max_consecutive: config.max_consecutive_priority_ops,
+            ..Self::default()
+        })
+    }
+
+    fn admission(&self) -> PriorityOpsAdmission {
+        let limit_reached =
+            |limit: Option<usize>, count| limit.map_or(false, |limit| count >= limit);
+
+        if limit_reached(self.max_per_miniblock, self.in_miniblock)
+            || limit_reached(self.max_per_l1_batch, self.in_l1_batch)
+        {
+            PriorityOpsAdmission::Denied
+        } else if limit_reached(self.max_consecutive, self.consecutive) {
+            PriorityOpsAdmission::L2Preferred
+        } else {
+            PriorityOpsAdmission::Allowed
+        }
+    }
+
+    fn observe_tx(&mut self, is_l1: bool) {
+        if !is_l1 {
+            self.consecutive = 0;
+            return;
+        }
+
+        self.in_miniblock += 1;
+        self.in_l1_batch += 1;
+        self.consecutive += 1;
+        if self.max_per_miniblock == Some(self.in_miniblock) {
+            KEEPER_METRICS.priority_ops_limit_reached[&PriorityOpsLimit::Miniblock].inc();
+        }
+        if self.max_per_l1_batch == Some(self.in_l1_batch) {
+            KEEPER_METRICS.priority_ops_limit_reached[&PriorityOpsLimit::L1Batch].inc();
+        }
+        if self.max_consecutive == Some(self.consecutive) {
+            KEEPER_METRICS.priority_ops_limit_reached[&PriorityOpsLimit::Consecutive].inc();
+        }
+    }
+
+    fn rollback_priority_op(&mut self) {
+        self.in_miniblock = self.in_miniblock.saturating_sub(1);
+        self.in_l1_batch = self.in_l1_batch.saturating_sub(1);
+        self.consecutive = self.consecutive.saturating_sub(1);
+    }
+
+    fn start_miniblock(&mut self) {
+        self.in_miniblock = 0;
+    }
+
+    fn start_l1_batch(&mut self) {
+        self.in_miniblock = 0;
+        self.in_l1_batch = 0;
+    }
+}
+
+/// Operator-defined transactions injected by [`MempoolIO`].
+#[derive(Debug, Default)]
+struct OperatorTxs {
+    provider: Option<Arc<dyn OperatorTxProvider>>,
+    /// Parameters of the miniblock for which transactions should be loaded. Transactions are loaded lazily
+    /// because the last miniblock params requested in an L1 batch correspond to the fictive miniblock,
+    /// which must not contain transactions.
+    pending_load: Option<(L1BatchNumber, MiniblockNumber, u64)>,
+    queue: VecDeque<Transaction>,
+    /// Hashes of injected transactions that may still be rolled back or rejected.
+    hashes: HashSet<H256>,
+}
+
+impl OperatorTxs {
+    fn start_miniblock(
+        &mut self,
+        l1_batch: L1BatchNumber,
+        miniblock: MiniblockNumber,
+        timestamp: u64,
+    ) {
+        if self.provider.is_some() {
+            self.pending_load = Some((l1_batch, miniblock, timestamp));
+        }
+    }
+
+    fn start_l1_batch(
+        &mut self,
+        l1_batch: L1BatchNumber,
+        miniblock: MiniblockNumber,
+        timestamp: u64,
+    ) {
+        // Transactions from previous L1 batches cannot be rolled back, so we only need to keep queued ones.
+        self.hashes = self.queue.iter().map(Transaction::hash).collect();
+        self.start_miniblock(l1_batch, miniblock, timestamp);
+    }
+
+    fn push(&mut self, tx: Transaction) {
+        self.hashes.insert(tx.hash());
+        self.queue.push_back(tx);
+    }
+
+    fn pop(&mut self) -> Option<Transaction> {
+        self.queue.pop_front()
+    }
+
+    /// Returns the rolled back transaction to the queue if it's an operator transaction.
+    fn rollback(&mut self, tx: &Transaction) -> bool {
+        let is_operator_tx = self.hashes.contains(&tx.hash());
+        if is_operator_tx {
+            self.queue.push_front(tx.clone());
+        }
+        is_operator_tx
+    }
+
+    /// Forgets about the transaction if it's an operator transaction.
+    fn remove(&mut self, tx: &Transaction) -> bool {
+        self.hashes.remove(&tx.hash())
+    }
+}
+
 /// Getters required for testing the MempoolIO.
 #[cfg(test)]
 impl MempoolIO {
@@ -470,6 +833,7 @@ mod tests {
     use zksync_utils::time::seconds_since_epoch;
 
     use super::*;
+    use crate::state_keeper::io::clock::tests::MockTimeSource;
 
     // This test defensively uses large deadlines in order to account for tests running in parallel etc.
     #[tokio::test]
@@ -477,16 +841,19 @@ mod tests {
         let past_timestamps = [0, 1_000, 1_000_000_000, seconds_since_epoch() - 10];
         for timestamp in past_timestamps {
             let deadline = Instant::now() + Duration::from_secs(1);
-            timeout_at(deadline.into(), sleep_past(timestamp, MiniblockNumber(1)))
-                .await
-                .unwrap();
+            timeout_at(
+                deadline.into(),
+                sleep_past(&SystemTimeSource, timestamp, MiniblockNumber(1)),
+            )
+            .await
+            .unwrap();
         }
 
         let current_timestamp = seconds_since_epoch();
         let deadline = Instant::now() + Duration::from_secs(2);
         let ts = timeout_at(
             deadline.into(),
-            sleep_past(current_timestamp, MiniblockNumber(1)),
+            sleep_past(&SystemTimeSource, current_timestamp, MiniblockNumber(1)),
         )
         .await
         .unwrap();
@@ -496,7 +863,7 @@ mod tests {
         let deadline = Instant::now() + Duration::from_secs(3);
         let ts = timeout_at(
             deadline.into(),
-            sleep_past(future_timestamp, MiniblockNumber(1)),
+            sleep_past(&SystemTimeSource, future_timestamp, MiniblockNumber(1)),
         )
         .await
         .unwrap();
@@ -507,9 +874,79 @@ mod tests {
         // ^ This deadline is too small (we need at least 1_000ms)
         let result = timeout_at(
             deadline.into(),
-            sleep_past(future_timestamp, MiniblockNumber(1)),
+            sleep_past(&SystemTimeSource, future_timestamp, MiniblockNumber(1)),
         )
         .await;
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn sleeping_past_timestamp_after_clock_jump() {
+        let prev_timestamp = seconds_since_epoch();
+        // Emulate the clock jumping an hour backwards.
+        let time_source = Arc::new(MockTimeSource::new((prev_timestamp - 3_600) * 1_000));
+        let sleep_task = tokio::spawn({
+            let time_source = time_source.clone();
+            async move { sleep_past(time_source.as_ref(), prev_timestamp, MiniblockNumber(1)).await }
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!sleep_task.is_finished());
+
+        // Emulate the clock getting fixed; the timestamp should be returned shortly, rather than after an hour.
+        time_source.set((prev_timestamp + 1) * 1_000);
+        let ts = tokio::time::timeout(MAX_SLEEP_STEP * 2, sleep_task)
+            .await
+            .expect("timed out waiting for timestamp")
+            .unwrap();
+        assert_eq!(ts, prev_timestamp + 1);
+    }
+
+    #[test]
+    fn throttling_priority_ops() {
+        let config = StateKeeperConfig {
+            max_priority_ops_per_miniblock: Some(2),
+            max_priority_ops_per_batch: Some(3),
+            max_consecutive_priority_ops: Some(1),
+            ..StateKeeperConfig::for_tests()
+        };
+        let mut throttler = PriorityOpsThrottler::new(&config).unwrap();
+        assert_eq!(throttler.admission(), PriorityOpsAdmission::Allowed);
+
+        throttler.observe_tx(true);
+        assert_eq!(throttler.admission(), PriorityOpsAdmission::L2Preferred);
+        throttler.observe_tx(false);
+        assert_eq!(throttler.admission(), PriorityOpsAdmission::Allowed);
+        throttler.observe_tx(true);
+        assert_eq!(throttler.admission(), PriorityOpsAdmission::Denied);
+        throttler.rollback_priority_op();
+        assert_eq!(throttler.admission(), PriorityOpsAdmission::Allowed);
+        throttler.observe_tx(true);
+        assert_eq!(throttler.admission(), PriorityOpsAdmission::Denied);
+
+        throttler.start_miniblock();
+        assert_eq!(throttler.admission(), PriorityOpsAdmission::L2Preferred);
+        throttler.observe_tx(false);
+        throttler.observe_tx(true);
+        // The L1 batch limit is reached.
+        assert_eq!(throttler.admission(), PriorityOpsAdmission::Denied);
+        throttler.start_miniblock();
+        assert_eq!(throttler.admission(), PriorityOpsAdmission::Denied);
+
+        throttler.start_l1_batch();
+        throttler.observe_tx(false);
+        assert_eq!(throttler.admission(), PriorityOpsAdmission::Allowed);
+    }
+
+    #[test]
+    fn zero_priority_ops_limit_is_rejected() {
+        let config = StateKeeperConfig {
+            max_priority_ops_per_miniblock: Some(0),
+            ..StateKeeperConfig::for_tests()
+        };
+        let err = PriorityOpsThrottler::new(&config).unwrap_err();
+        assert!(
+            err.to_string().contains("max_priority_ops_per_miniblock"),
+            "{err}"
+        );
+    }
 }
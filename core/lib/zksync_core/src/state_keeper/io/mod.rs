@@ -7,21 +7,27 @@ use vm_utils::storage::l1_batch_params;
 use zksync_contracts::BaseSystemContracts;
 use zksync_types::{
     block::MiniblockExecutionData, fee_model::BatchFeeInput, protocol_upgrade::ProtocolUpgradeTx,
-    Address, L1BatchNumber, L2ChainId, ProtocolVersionId, Transaction, H256,
+    tx::TxAccessHints, Address, L1BatchNumber, L2ChainId, ProtocolVersionId, Transaction, H256,
 };
 
 pub use self::{
+    clock::{ClockSkewMonitor, ClockSkewStatus, SystemTimeSource, TimeSource},
     common::IoCursor,
+    operator_txs::OperatorTxProvider,
     output_handler::{OutputHandler, StateKeeperOutputHandler},
     persistence::{MiniblockSealerTask, StateKeeperPersistence},
+    raw_blocks::{RawBlocksPersistence, RawBlocksPersisterTask},
 };
 use super::seal_criteria::IoSealCriteria;
 
+pub(crate) mod clock;
 pub(crate) mod common;
 pub(crate) mod fee_address_migration;
 pub(crate) mod mempool;
+mod operator_txs;
 mod output_handler;
 mod persistence;
+mod raw_blocks;
 pub(crate) mod seal_logic;
 #[cfg(test)]
 mod tests;
@@ -136,6 +142,12 @@ pub trait StateKeeperIO: 'static + Send + fmt::Debug + IoSealCriteria {
     async fn rollback(&mut self, tx: Transaction) -> anyhow::Result<()>;
     /// Marks the transaction as "rejected", e.g. one that is not correct and can't be executed.
     async fn reject(&mut self, tx: &Transaction, error: &str) -> anyhow::Result<()>;
+    /// Takes access hints supplied for a transaction previously returned by [`Self::wait_for_next_tx()`].
+    /// Called once the transaction is included into a miniblock. Hints are unverified and must only be used
+    /// for scheduling. By default, returns `None`.
+    fn take_access_hints(&mut self, _tx: &Transaction) -> Option<TxAccessHints> {
+        None
+    }
 
     /// Loads base system contracts with the specified version.
     async fn load_base_system_contracts(
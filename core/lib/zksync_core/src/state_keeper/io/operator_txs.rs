@@ -0,0 +1,24 @@
+//! Operator-defined transactions injected by the state keeper into miniblocks.
+
+use std::fmt;
+
+use async_trait::async_trait;
+use zksync_types::{l2::L2Tx, L1BatchNumber, MiniblockNumber};
+
+/// Provider of operator-defined transactions (e.g., oracle price updates or keeper calls) executed
+/// at the start of each miniblock before user transactions.
+///
+/// Transactions bypass the mempool and API server checks, so they must be signed and have correct nonces.
+/// They are persisted together with user transactions, and a transaction rejected by the VM is marked as rejected
+/// in the same way as a user transaction. If the node is restarted before the miniblock with injected transactions
+/// is sealed, the transactions are loaded into the mempool and are executed as ordinary L2 transactions.
+#[async_trait]
+pub trait OperatorTxProvider: fmt::Debug + Send + Sync {
+    /// Returns transactions to be executed at the start of the specified miniblock.
+    async fn miniblock_transactions(
+        &self,
+        l1_batch_number: L1BatchNumber,
+        miniblock_number: MiniblockNumber,
+        miniblock_timestamp: u64,
+    ) -> anyhow::Result<Vec<L2Tx>>;
+}
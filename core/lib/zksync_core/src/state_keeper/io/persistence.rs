@@ -72,8 +72,10 @@ impl StateKeeperPersistence {
         self
     }
 
-    /// Disables inserting protective reads to Postgres when persisting an L1 batch. This is only sound
-    /// if the node won't *ever* run a full Merkle tree (such a tree requires protective reads to generate witness inputs).
+    /// Disables inserting protective reads to Postgres when persisting an L1 batch. A full Merkle tree requires
+    /// protective reads to generate witness inputs, so this is only sound if the node runs
+    /// [`ProtectiveReadsWriter`](crate::protective_reads_writer::ProtectiveReadsWriter) computing them
+    /// in the background, or won't *ever* run a full Merkle tree.
     pub fn without_protective_reads(mut self) -> Self {
         self.insert_protective_reads = false;
         self
@@ -324,6 +326,12 @@ mod tests {
             .await
             .unwrap();
         assert_eq!(protective_reads.len(), 1, "{protective_reads:?}");
+        let l1_batch_without_protective_reads = storage
+            .storage_logs_dedup_dal()
+            .get_first_l1_batch_without_protective_reads(L1BatchNumber(0))
+            .await
+            .unwrap();
+        assert_eq!(l1_batch_without_protective_reads, None);
     }
 
     async fn execute_mock_batch(persistence: &mut StateKeeperPersistence) -> H256 {
@@ -426,6 +434,12 @@ mod tests {
             .await
             .unwrap();
         assert_eq!(protective_reads, HashSet::new());
+        let l1_batch_without_protective_reads = storage
+            .storage_logs_dedup_dal()
+            .get_first_l1_batch_without_protective_reads(L1BatchNumber(0))
+            .await
+            .unwrap();
+        assert_eq!(l1_batch_without_protective_reads, Some(L1BatchNumber(1)));
     }
 
     #[tokio::test]
@@ -0,0 +1,150 @@
+//! Background persistence of raw blocks served via `zks_getRawBlock`.
+
+use std::time::Duration;
+
+use anyhow::Context as _;
+use async_trait::async_trait;
+use tokio::sync::{mpsc, watch};
+use zksync_dal::{ConnectionPool, Core, CoreDal};
+use zksync_types::api::en::RawBlock;
+
+use crate::state_keeper::{
+    io::StateKeeperOutputHandler, metrics::RAW_BLOCKS_METRICS, updates::UpdatesManager,
+};
+
+/// State keeper output handler queueing [raw blocks](RawBlock) for persistence. Queued blocks are encoded
+/// and persisted by [`RawBlocksPersisterTask`], so that compressing blocks doesn't slow down sealing.
+///
+/// Blocks queued but not persisted before a node shutdown are lost; `zks_getRawBlock` returns `null` for them,
+/// same as for miniblocks sealed before raw blocks started being persisted.
+#[derive(Debug)]
+pub struct RawBlocksPersistence {
+    sender: mpsc::Sender<RawBlock>,
+}
+
+impl RawBlocksPersistence {
+    /// Creates a handler together with the task persisting queued blocks. The task should be run
+    /// on a separate Tokio task. At most `queue_capacity` blocks are queued; if the queue is full, the handler
+    /// waits until queued blocks are persisted (i.e., there is back pressure).
+    pub fn new(
+        pool: ConnectionPool<Core>,
+        queue_capacity: usize,
+    ) -> (Self, RawBlocksPersisterTask) {
+        let (sender, receiver) = mpsc::channel(queue_capacity.max(1));
+        let task = RawBlocksPersisterTask {
+            pool,
+            sender: sender.downgrade(),
+            receiver,
+            poll_interval: RawBlocksPersisterTask::DEFAULT_POLL_INTERVAL,
+        };
+        (Self { sender }, task)
+    }
+
+    async fn enqueue(&self, block: RawBlock) -> anyhow::Result<()> {
+        self.sender
+            .send(block)
+            .await
+            .ok()
+            .context("raw blocks persister unexpectedly shut down")?;
+        report_queue_len(&self.sender);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl StateKeeperOutputHandler for RawBlocksPersistence {
+    async fn handle_miniblock(&mut self, updates_manager: &UpdatesManager) -> anyhow::Result<()> {
+        self.enqueue(updates_manager.raw_block(false)).await
+    }
+
+    async fn handle_l1_batch(&mut self, updates_manager: &UpdatesManager) -> anyhow::Result<()> {
+        // The last (fictive) miniblock in the batch is sealed together with the batch.
+        self.enqueue(updates_manager.raw_block(true)).await
+    }
+}
+
+fn report_queue_len(sender: &mpsc::Sender<RawBlock>) {
+    RAW_BLOCKS_METRICS
+        .queue_len
+        .set(sender.max_capacity() - sender.capacity());
+}
+
+/// Task persisting raw blocks queued by [`RawBlocksPersistence`].
+#[derive(Debug)]
+pub struct RawBlocksPersisterTask {
+    pool: ConnectionPool<Core>,
+    // Weak sender handle to get queue length stats.
+    sender: mpsc::WeakSender<RawBlock>,
+    receiver: mpsc::Receiver<RawBlock>,
+    poll_interval: Duration,
+}
+
+impl RawBlocksPersisterTask {
+    /// Interval between checks whether the miniblock for a queued block is sealed.
+    const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+    /// Persists blocks sequentially in the order they were queued.
+    pub async fn run(mut self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        tracing::info!("Starting raw blocks persister");
+        loop {
+            let block = tokio::select! {
+                block = self.receiver.recv() => block,
+                _ = stop_receiver.changed() => break,
+            };
+            let Some(block) = block else {
+                tracing::info!("Raw blocks handler is dropped; stopping persister");
+                return Ok(());
+            };
+            if let Some(sender) = self.sender.upgrade() {
+                report_queue_len(&sender);
+            }
+            if !self.persist(block, &mut stop_receiver).await? {
+                break;
+            }
+        }
+
+        tracing::info!("Stop signal received, raw blocks persister is shutting down");
+        Ok(())
+    }
+
+    /// Returns `false` if the task was stopped before the block is persisted.
+    async fn persist(
+        &self,
+        block: RawBlock,
+        stop_receiver: &mut watch::Receiver<bool>,
+    ) -> anyhow::Result<bool> {
+        let latency = RAW_BLOCKS_METRICS.persist_latency.start();
+        let miniblock_number = block.block.number;
+        let data = tokio::task::spawn_blocking(move || block.encode())
+            .await
+            .context("panicked while encoding raw block")?;
+
+        // Miniblocks may be sealed asynchronously, so the miniblock may be not persisted yet.
+        loop {
+            let mut storage = self.pool.connection_tagged("raw_blocks_persister").await?;
+            let sealed_miniblock = storage.blocks_dal().get_sealed_miniblock_number().await?;
+            if sealed_miniblock >= Some(miniblock_number) {
+                storage
+                    .raw_blocks_dal()
+                    .insert_encoded_raw_block(miniblock_number, &data)
+                    .await?;
+                break;
+            }
+            drop(storage);
+
+            if tokio::time::timeout(self.poll_interval, stop_receiver.changed())
+                .await
+                .is_ok()
+            {
+                return Ok(false);
+            }
+        }
+
+        latency.observe();
+        RAW_BLOCKS_METRICS
+            .last_persisted_miniblock
+            .set(miniblock_number.0.into());
+        tracing::debug!("Persisted raw block for miniblock #{miniblock_number}");
+        Ok(true)
+    }
+}
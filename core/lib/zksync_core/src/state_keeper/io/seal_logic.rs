@@ -170,6 +170,10 @@ impl UpdatesManager {
                 .storage_logs_dedup_dal()
                 .insert_protective_reads(self.l1_batch.number, &protective_reads)
                 .await?;
+            transaction
+                .storage_logs_dedup_dal()
+                .mark_protective_reads_as_persisted(self.l1_batch.number)
+                .await?;
             progress.observe(protective_reads.len());
         }
 
@@ -379,6 +383,18 @@ impl MiniblockSealCommand {
                 self.base_fee_per_gas.into(),
             )
             .await?;
+        transaction
+            .priority_ops_audit_dal()
+            .insert_execution_events(miniblock_number, &self.miniblock.executed_transactions)
+            .await?;
+        transaction
+            .tx_execution_profiles_dal()
+            .insert_profiles(miniblock_number, &self.miniblock.executed_transactions)
+            .await?;
+        transaction
+            .address_transactions_dal()
+            .insert_address_transactions(miniblock_number, &self.miniblock.executed_transactions)
+            .await?;
         progress.observe(self.miniblock.executed_transactions.len());
 
         let progress = MINIBLOCK_METRICS.start(MiniblockSealStage::InsertStorageLogs, is_fictive);
@@ -436,6 +452,13 @@ impl MiniblockSealCommand {
             .await?;
         progress.observe(miniblock_event_count);
 
+        let progress = MINIBLOCK_METRICS.start(MiniblockSealStage::InsertTokenHolders, is_fictive);
+        transaction
+            .token_balances_dal()
+            .insert_token_holders(miniblock_number, &self.miniblock.events)
+            .await?;
+        progress.observe(miniblock_event_count);
+
         let progress = MINIBLOCK_METRICS.start(MiniblockSealStage::ExtractL2ToL1Logs, is_fictive);
 
         let system_l2_to_l1_logs = self.extract_system_l2_to_l1_logs(is_fictive);
@@ -1,30 +1,44 @@
-use std::time::Duration;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
+use async_trait::async_trait;
 use multivm::utils::derive_base_fee_and_gas_per_pubdata;
 use test_casing::test_casing;
+use tokio::sync::watch;
 use zksync_contracts::BaseSystemContractsHashes;
 use zksync_dal::{ConnectionPool, Core, CoreDal};
 use zksync_mempool::L2TxFilter;
 use zksync_types::{
+    api,
     block::{BlockGasCount, MiniblockHasher},
     fee::TransactionExecutionMetrics,
     fee_model::{BatchFeeInput, PubdataIndependentBatchFeeModelInput},
+    l2::L2Tx,
     tx::ExecutionMetrics,
     AccountTreeId, Address, L1BatchNumber, L2ChainId, MiniblockNumber, ProtocolVersion,
-    ProtocolVersionId, StorageKey, VmEvent, H256, U256,
+    ProtocolVersionId, StorageKey, Transaction, VmEvent, H256, U256,
 };
 use zksync_utils::time::seconds_since_epoch;
 
 use self::tester::Tester;
 use crate::{
     state_keeper::{
-        io::StateKeeperIO,
+        io::{OperatorTxProvider, StateKeeperIO},
         mempool_actor::l2_tx_filter,
-        tests::{create_execution_result, create_transaction, Query, BASE_SYSTEM_CONTRACTS},
+        tests::{
+            create_execution_result, create_transaction, create_updates_manager, Query,
+            BASE_SYSTEM_CONTRACTS,
+        },
         updates::{MiniblockSealCommand, MiniblockUpdates, UpdatesManager},
-        StateKeeperOutputHandler, StateKeeperPersistence,
+        RawBlocksPersistence, StateKeeperOutputHandler, StateKeeperPersistence,
     },
-    utils::testonly::{prepare_recovery_snapshot, DeploymentMode},
+    tx_filter::{DenyListFilter, FilterRejection, TransactionFilter},
+    utils::testonly::{create_l2_transaction, prepare_recovery_snapshot, DeploymentMode},
 };
 
 mod tester;
@@ -518,6 +532,63 @@ async fn miniblock_processing_after_snapshot_recovery(deployment_mode: Deploymen
     assert_eq!(pending_batch.pending_miniblocks[0].txs[0].hash(), tx_hash);
 }
 
+#[tokio::test]
+async fn persisting_raw_blocks_in_background() {
+    let connection_pool = ConnectionPool::<Core>::test_pool().await;
+    let mut storage = connection_pool.connection().await.unwrap();
+    storage
+        .protocol_versions_dal()
+        .save_protocol_version_with_tx(&ProtocolVersion::default())
+        .await
+        .unwrap();
+
+    let mut updates = create_updates_manager();
+    let tx = create_transaction(10, 100);
+    let tx_hash = tx.hash();
+    updates.extend_from_executed_transaction(
+        tx,
+        create_execution_result(0, []),
+        vec![],
+        BlockGasCount::default(),
+        ExecutionMetrics::default(),
+        vec![],
+    );
+    let miniblock_number = updates.miniblock.number;
+
+    let (mut raw_blocks, raw_blocks_persister) =
+        RawBlocksPersistence::new(connection_pool.clone(), 1);
+    let (_stop_sender, stop_receiver) = watch::channel(false);
+    let persister_handle = tokio::spawn(raw_blocks_persister.run(stop_receiver));
+    // The raw block must be persisted even if it's queued before the miniblock is sealed.
+    raw_blocks.handle_miniblock(&updates).await.unwrap();
+
+    let (mut persistence, miniblock_sealer) =
+        StateKeeperPersistence::new(connection_pool.clone(), Address::default(), 0);
+    tokio::spawn(miniblock_sealer.run());
+    persistence.handle_miniblock(&updates).await.unwrap();
+
+    let encoded_block = loop {
+        let encoded_block = storage
+            .raw_blocks_dal()
+            .get_encoded_raw_block(miniblock_number)
+            .await
+            .unwrap();
+        if let Some(encoded_block) = encoded_block {
+            break encoded_block;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    };
+    let raw_block = api::en::RawBlock::decode(&encoded_block).unwrap();
+    assert_eq!(raw_block.block.number, miniblock_number);
+    assert!(!raw_block.block.last_in_batch);
+    assert_eq!(raw_block.results.len(), 1);
+    assert_eq!(raw_block.results[0].hash, tx_hash);
+
+    // The persister must stop once the handler is dropped.
+    drop(raw_blocks);
+    persister_handle.await.unwrap().unwrap();
+}
+
 /// Ensure that subsequent miniblocks that belong to the same L1 batch have different timestamps
 #[test_casing(2, [DeploymentMode::Rollup, DeploymentMode::Validium])]
 #[tokio::test]
@@ -539,3 +610,181 @@ async fn different_timestamp_for_miniblocks_in_same_batch(deployment_mode: Deplo
         .expect("no new miniblock params");
     assert!(miniblock_params.timestamp > current_timestamp);
 }
+
+#[tokio::test]
+async fn rejecting_transactions_denied_by_filter() {
+    let connection_pool = ConnectionPool::<Core>::test_pool().await;
+    let tester = Tester::new(&DeploymentMode::Rollup);
+    tester.genesis(&connection_pool).await;
+
+    let tx = create_l2_transaction(100, 800);
+    let mut storage = connection_pool.connection().await.unwrap();
+    storage
+        .transactions_dal()
+        .insert_transaction_l2(&tx, TransactionExecutionMetrics::default())
+        .await
+        .unwrap();
+
+    let tx_filter = DenyListFilter::new([tx.initiator_account()]);
+    let (mut mempool, mut mempool_guard) = tester
+        .create_test_mempool_io_with_filter(connection_pool.clone(), Arc::new(tx_filter))
+        .await;
+    mempool.initialize().await.unwrap();
+    mempool_guard.insert(vec![tx.clone().into()], Default::default());
+
+    let next_tx = mempool
+        .wait_for_next_tx(Duration::from_millis(100))
+        .await
+        .unwrap();
+    assert!(next_tx.is_none(), "{next_tx:?}");
+
+    let tx_details = storage
+        .transactions_web3_dal()
+        .get_transaction_details(tx.hash())
+        .await
+        .unwrap()
+        .expect("no transaction details");
+    assert!(
+        matches!(tx_details.status, api::TransactionStatus::Failed),
+        "{tx_details:?}"
+    );
+}
+
+/// Filter deferring all transactions while the flag is set.
+#[derive(Debug, Default)]
+struct DeferringFilter(AtomicBool);
+
+impl TransactionFilter for DeferringFilter {
+    fn check(&self, _tx: &Transaction) -> Result<(), FilterRejection> {
+        if self.0.load(Ordering::Relaxed) {
+            Err(FilterRejection::RetryLater("deferred".to_owned()))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[tokio::test]
+async fn deferring_transactions_by_filter() {
+    let connection_pool = ConnectionPool::<Core>::test_pool().await;
+    let tester = Tester::new(&DeploymentMode::Rollup);
+    tester.genesis(&connection_pool).await;
+
+    let tx = create_l2_transaction(100, 800);
+    let mut storage = connection_pool.connection().await.unwrap();
+    storage
+        .transactions_dal()
+        .insert_transaction_l2(&tx, TransactionExecutionMetrics::default())
+        .await
+        .unwrap();
+
+    let tx_filter = Arc::new(DeferringFilter(AtomicBool::new(true)));
+    let (mut mempool, mut mempool_guard) = tester
+        .create_test_mempool_io_with_filter(connection_pool.clone(), tx_filter.clone())
+        .await;
+    mempool.initialize().await.unwrap();
+    mempool_guard.insert(vec![tx.clone().into()], Default::default());
+
+    let next_tx = mempool
+        .wait_for_next_tx(Duration::from_millis(100))
+        .await
+        .unwrap();
+    assert!(next_tx.is_none(), "{next_tx:?}");
+    // The deferred transaction must be neither rejected nor removed from the mempool.
+    let tx_details = storage
+        .transactions_web3_dal()
+        .get_transaction_details(tx.hash())
+        .await
+        .unwrap()
+        .expect("no transaction details");
+    assert!(
+        matches!(tx_details.status, api::TransactionStatus::Pending),
+        "{tx_details:?}"
+    );
+
+    tx_filter.0.store(false, Ordering::Relaxed);
+    let next_tx = mempool
+        .wait_for_next_tx(Duration::from_millis(100))
+        .await
+        .unwrap()
+        .expect("deferred transaction is not returned");
+    assert_eq!(next_tx.hash(), tx.hash());
+}
+
+#[derive(Debug)]
+struct MockOperatorTxProvider(L2Tx);
+
+#[async_trait]
+impl OperatorTxProvider for MockOperatorTxProvider {
+    async fn miniblock_transactions(
+        &self,
+        _l1_batch_number: L1BatchNumber,
+        _miniblock_number: MiniblockNumber,
+        _miniblock_timestamp: u64,
+    ) -> anyhow::Result<Vec<L2Tx>> {
+        Ok(vec![self.0.clone()])
+    }
+}
+
+#[tokio::test]
+async fn injecting_operator_transactions() {
+    let connection_pool = ConnectionPool::<Core>::test_pool().await;
+    let tester = Tester::new(&DeploymentMode::Rollup);
+    tester.genesis(&connection_pool).await;
+
+    let operator_tx = create_l2_transaction(100, 800);
+    let (mempool, _) = tester.create_test_mempool_io(connection_pool.clone()).await;
+    let mut mempool =
+        mempool.with_operator_tx_provider(Arc::new(MockOperatorTxProvider(operator_tx.clone())));
+    let (io_cursor, _) = mempool.initialize().await.unwrap();
+
+    // Transactions are only injected after a miniblock is started.
+    let next_tx = mempool
+        .wait_for_next_tx(Duration::from_millis(100))
+        .await
+        .unwrap();
+    assert!(next_tx.is_none(), "{next_tx:?}");
+
+    mempool
+        .wait_for_new_miniblock_params(&io_cursor, Duration::from_secs(10))
+        .await
+        .unwrap()
+        .expect("no new miniblock params");
+    let next_tx = mempool
+        .wait_for_next_tx(Duration::from_millis(100))
+        .await
+        .unwrap()
+        .expect("operator transaction was not injected");
+    assert_eq!(next_tx.hash(), operator_tx.hash());
+
+    // The injected transaction must be persisted, but must not be picked up by the mempool fetcher.
+    let mut storage = connection_pool.connection().await.unwrap();
+    let tx_details = storage
+        .transactions_web3_dal()
+        .get_transaction_details(operator_tx.hash())
+        .await
+        .unwrap();
+    assert!(tx_details.is_some());
+    let mempool_txs = storage
+        .transactions_dal()
+        .sync_mempool(&[], &[], &[], 0, 0, 100)
+        .await
+        .unwrap();
+    assert!(mempool_txs.is_empty(), "{mempool_txs:?}");
+
+    // A rolled back transaction must be returned by the I/O again.
+    mempool.rollback(next_tx).await.unwrap();
+    let next_tx = mempool
+        .wait_for_next_tx(Duration::from_millis(100))
+        .await
+        .unwrap()
+        .expect("operator transaction was not returned after rollback");
+    assert_eq!(next_tx.hash(), operator_tx.hash());
+    // Rejecting the transaction must not touch the mempool.
+    mempool.reject(&next_tx, "test").await.unwrap();
+    let next_tx = mempool
+        .wait_for_next_tx(Duration::from_millis(100))
+        .await
+        .unwrap();
+    assert!(next_tx.is_none(), "{next_tx:?}");
+}
@@ -25,7 +25,8 @@ use crate::{
     fee_model::MainNodeFeeInputProvider,
     genesis::create_genesis_l1_batch,
     l1_gas_price::{GasAdjuster, PubdataPricing, RollupPubdataPricing, ValidiumPubdataPricing},
-    state_keeper::{MempoolGuard, MempoolIO},
+    state_keeper::{FeeAccountSelector, MempoolGuard, MempoolIO},
+    tx_filter::{AllowAllFilter, TransactionFilter},
     utils::testonly::{
         create_l1_batch, create_l2_transaction, create_miniblock, execute_l2_transaction,
         DeploymentMode,
@@ -102,6 +103,15 @@ impl Tester {
     pub(super) async fn create_test_mempool_io(
         &self,
         pool: ConnectionPool<Core>,
+    ) -> (MempoolIO, MempoolGuard) {
+        self.create_test_mempool_io_with_filter(pool, Arc::new(AllowAllFilter))
+            .await
+    }
+
+    pub(super) async fn create_test_mempool_io_with_filter(
+        &self,
+        pool: ConnectionPool<Core>,
+        tx_filter: Arc<dyn TransactionFilter>,
     ) -> (MempoolIO, MempoolGuard) {
         let gas_adjuster = Arc::new(self.create_gas_adjuster().await);
         let batch_fee_input_provider = MainNodeFeeInputProvider::new(
@@ -125,9 +135,10 @@ impl Tester {
             Arc::new(batch_fee_input_provider),
             pool,
             &config,
-            wallets.state_keeper.unwrap().fee_account.address(),
+            FeeAccountSelector::new(wallets.state_keeper.unwrap().fee_account.address(), &config),
             Duration::from_secs(1),
             L2ChainId::from(270),
+            tx_filter,
         )
         .await
         .unwrap();
@@ -6,7 +6,10 @@ use std::{
 };
 
 use anyhow::Context as _;
-use multivm::interface::{Halt, L1BatchEnv, SystemEnv};
+use multivm::{
+    interface::{Halt, L1BatchEnv, SystemEnv},
+    utils::circuit_statistics_bootloader_batch_tip_overhead,
+};
 use tokio::sync::watch;
 use zksync_dal::{ConnectionPool, Core};
 use zksync_types::{
@@ -17,13 +20,16 @@ use zksync_types::{
 
 use super::{
     batch_executor::{BatchExecutor, BatchExecutorHandle, TxExecutionResult},
+    circuit_capacity::CircuitCapacityTracker,
     extractors,
     io::{
         fee_address_migration, IoCursor, MiniblockParams, OutputHandler, PendingBatchData,
         StateKeeperIO,
     },
     metrics::{AGGREGATION_METRICS, KEEPER_METRICS, L1_BATCH_METRICS},
-    seal_criteria::{ConditionalSealer, SealData, SealResolution},
+    seal_criteria::{
+        factory_deps_size, BatchSealMonitor, ConditionalSealer, SealData, SealResolution,
+    },
     types::ExecutionMetricsForCriteria,
     updates::UpdatesManager,
 };
@@ -67,6 +73,9 @@ pub struct ZkSyncStateKeeper {
     output_handler: OutputHandler,
     batch_executor_base: Box<dyn BatchExecutor>,
     sealer: Arc<dyn ConditionalSealer>,
+    circuit_tracker: CircuitCapacityTracker,
+    seal_miniblock_on_shutdown: bool,
+    seal_monitor: Option<BatchSealMonitor>,
 }
 
 impl ZkSyncStateKeeper {
@@ -83,9 +92,29 @@ impl ZkSyncStateKeeper {
             batch_executor_base,
             output_handler,
             sealer,
+            circuit_tracker: CircuitCapacityTracker::default(),
+            seal_miniblock_on_shutdown: false,
+            seal_monitor: None,
         }
     }
 
+    /// Makes the state keeper seal the open miniblock when a stop signal is received. This persists transactions
+    /// executed in the miniblock, so that the pending L1 batch is resumed with them after a restart instead of
+    /// returning them to the mempool. The L1 batch itself is not sealed.
+    ///
+    /// Should only be used with I/O implementations that can seal a miniblock at any time
+    /// (i.e., not on external nodes, for which miniblock boundaries are defined by the main node).
+    pub fn with_miniblock_sealing_on_shutdown(mut self) -> Self {
+        self.seal_miniblock_on_shutdown = true;
+        self
+    }
+
+    /// Makes the state keeper publish the state of the open L1 batch to the specified monitor.
+    pub fn with_seal_monitor(mut self, monitor: BatchSealMonitor) -> Self {
+        self.seal_monitor = Some(monitor);
+        self
+    }
+
     /// Temporary method to migrate fee addresses from L1 batches to miniblocks.
     pub fn run_fee_address_migration(
         &self,
@@ -469,6 +498,9 @@ impl ZkSyncStateKeeper {
         }
 
         while !self.is_canceled() {
+            if let Some(monitor) = &self.seal_monitor {
+                monitor.update(updates_manager, &self.sealer);
+            }
             if self
                 .io
                 .should_seal_l1_batch_unconditionally(updates_manager)
@@ -516,6 +548,19 @@ impl ZkSyncStateKeeper {
             waiting_latency.observe();
 
             let tx_hash = tx.hash();
+            if !self.admit_tx(updates_manager, &tx) {
+                tracing::debug!(
+                    "Transaction {tx_hash:?} is expected to overflow circuits in L1 batch #{}; deferring it \
+                     to the next L1 batch",
+                    updates_manager.l1_batch.number
+                );
+                self.io
+                    .rollback(tx)
+                    .await
+                    .context("failed rolling back transaction")?;
+                return Ok(());
+            }
+
             let (seal_resolution, exec_result) = self
                 .process_one_tx(batch_executor, updates_manager, tx.clone())
                 .await;
@@ -538,6 +583,9 @@ impl ZkSyncStateKeeper {
                         l1_gas: tx_l1_gas_this_tx,
                         execution_metrics: tx_execution_metrics,
                     } = *tx_metrics;
+                    if let Some(access_hints) = self.io.take_access_hints(&tx) {
+                        updates_manager.register_access_hints(tx_hash, access_hints);
+                    }
                     updates_manager.extend_from_executed_transaction(
                         tx,
                         *tx_result,
@@ -546,6 +594,7 @@ impl ZkSyncStateKeeper {
                         tx_execution_metrics,
                         call_tracer_result,
                     );
+                    self.report_circuit_usage(updates_manager);
                 }
                 SealResolution::ExcludeAndSeal => {
                     batch_executor.rollback_last_tx().await;
@@ -572,6 +621,20 @@ impl ZkSyncStateKeeper {
                 return Ok(());
             }
         }
+
+        // The open miniblock may only be non-empty here; other cancellation points are reached
+        // after the miniblock is sealed.
+        if self.seal_miniblock_on_shutdown
+            && !updates_manager.miniblock.executed_transactions.is_empty()
+        {
+            tracing::info!(
+                "Stop signal received; sealing miniblock #{} so that L1 batch #{} is resumed with its {} transactions on restart",
+                updates_manager.miniblock.number,
+                updates_manager.l1_batch.number,
+                updates_manager.miniblock.executed_transactions.len()
+            );
+            self.seal_miniblock(updates_manager).await?;
+        }
         Err(Error::Canceled)
     }
 
@@ -636,6 +699,39 @@ impl ZkSyncStateKeeper {
         };
     }
 
+    /// Checks whether the transaction is expected to fit into the circuit capacity of the open L1 batch.
+    /// The first transaction in a batch is always admitted, so that transactions are never deferred indefinitely;
+    /// if such a transaction overflows circuits on its own, it will be rejected after execution.
+    fn admit_tx(&mut self, updates_manager: &UpdatesManager, tx: &Transaction) -> bool {
+        if updates_manager.pending_executed_transactions_len() == 0 {
+            return true;
+        }
+        let protocol_version = updates_manager.protocol_version();
+        let Some(capacity) = self.sealer.circuit_capacity(protocol_version) else {
+            return true;
+        };
+        let batch_tip_overhead =
+            circuit_statistics_bootloader_batch_tip_overhead(protocol_version.into());
+        self.circuit_tracker.admit(
+            tx,
+            &updates_manager
+                .pending_execution_metrics()
+                .circuit_statistic,
+            capacity.saturating_sub(batch_tip_overhead),
+        )
+    }
+
+    fn report_circuit_usage(&self, updates_manager: &UpdatesManager) {
+        let protocol_version = updates_manager.protocol_version();
+        CircuitCapacityTracker::report_batch_usage(
+            &updates_manager
+                .pending_execution_metrics()
+                .circuit_statistic,
+            circuit_statistics_bootloader_batch_tip_overhead(protocol_version.into()),
+            self.sealer.circuit_capacity(protocol_version),
+        );
+    }
+
     /// Executes one transaction in the batch executor, and then decides whether the batch should be sealed.
     /// Batch may be sealed because of one of the following reasons:
     /// 1. The VM entered an incorrect state (e.g. out of gas). In that case, we must revert the transaction and seal
@@ -694,6 +790,8 @@ impl ZkSyncStateKeeper {
                     l1_gas: tx_l1_gas_this_tx,
                     execution_metrics: tx_execution_metrics,
                 } = **tx_metrics;
+                self.circuit_tracker
+                    .record(&tx, &tx_execution_metrics.circuit_statistic);
 
                 tracing::trace!(
                     "finished tx {:?} by {:?} (is_l1: {}) (#{} in l1 batch {}) (#{} in miniblock {}) \
@@ -737,6 +835,8 @@ impl ZkSyncStateKeeper {
                     cumulative_size: encoding_len,
                     writes_metrics: tx_writes_metrics,
                     gas_remaining: *gas_remaining,
+                    factory_deps_size: factory_deps_size(&tx),
+                    is_l1_tx: tx.is_l1(),
                 };
                 let block_data = SealData {
                     execution_metrics: tx_data.execution_metrics
@@ -748,6 +848,8 @@ impl ZkSyncStateKeeper {
                         + updates_manager.pending_txs_encoding_size(),
                     writes_metrics: block_writes_metrics,
                     gas_remaining: *gas_remaining,
+                    factory_deps_size: tx_data.factory_deps_size,
+                    is_l1_tx: tx_data.is_l1_tx,
                 };
 
                 self.sealer.should_seal_l1_batch(
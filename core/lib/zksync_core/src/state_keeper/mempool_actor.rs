@@ -1,4 +1,8 @@
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
 
 use anyhow::Context as _;
 use multivm::utils::derive_base_fee_and_gas_per_pubdata;
@@ -8,11 +12,14 @@ use tokio::sync::watch;
 use zksync_config::configs::chain::MempoolConfig;
 use zksync_dal::{Connection, ConnectionPool, Core, CoreDal};
 use zksync_mempool::L2TxFilter;
-#[cfg(test)]
-use zksync_types::H256;
-use zksync_types::{get_nonce_key, Address, Nonce, Transaction, VmVersion};
-
-use super::{metrics::KEEPER_METRICS, types::MempoolGuard};
+use zksync_types::{
+    get_nonce_key, tx::TxAccessHints, Address, Nonce, Transaction, VmVersion, H256,
+};
+
+use super::{
+    metrics::{MempoolRecoveryOutcome, KEEPER_METRICS},
+    types::MempoolGuard,
+};
 use crate::{fee_model::BatchFeeModelInputProvider, utils::pending_protocol_version};
 
 /// Creates a mempool filter for L2 transactions based on the current L1 gas price.
@@ -63,23 +70,64 @@ impl MempoolFetcher {
         }
     }
 
-    pub async fn run(mut self, stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
-        let mut storage = self.pool.connection_tagged("state_keeper").await?;
+    /// Recovers transactions pending at the moment of the previous shutdown. L2 transactions are persisted in Postgres
+    /// before they are acknowledged by the API server, and are only marked as loaded into the in-memory mempool
+    /// afterwards. Thus, recovery consists of returning all transactions marked as loaded back to Postgres,
+    /// so that they are reloaded by the sync loop. Transactions that are stuck (if configured) are dropped
+    /// beforehand; dropped transactions are logged, so that they can be traced. Recovered transactions retain
+    /// their position in the mempool queue since it is determined by the persisted receipt timestamp.
+    ///
+    /// Returns the number of recovered and dropped transactions.
+    async fn recover_pending_transactions(
+        &self,
+        storage: &mut Connection<'_, Core>,
+    ) -> anyhow::Result<(usize, usize)> {
+        let mut dropped_tx_count = 0;
         if let Some(stuck_tx_timeout) = self.stuck_tx_timeout {
             let removed_txs = storage
                 .transactions_dal()
                 .remove_stuck_txs(stuck_tx_timeout)
                 .await
                 .context("failed removing stuck transactions")?;
-            tracing::info!("Number of stuck txs was removed: {removed_txs}");
+            for tx_hash in &removed_txs {
+                tracing::warn!(
+                    "Dropped transaction {tx_hash:?} not included into a miniblock in {stuck_tx_timeout:?}"
+                );
+            }
+            dropped_tx_count = removed_txs.len();
         }
-        storage
+        let recovered_tx_count = storage
             .transactions_dal()
             .reset_mempool()
             .await
             .context("failed resetting mempool")?;
+        // Hints of recovered transactions were consumed when the transactions were loaded before the shutdown,
+        // so pruning only removes hints of transactions that were replaced or removed before being loaded.
+        let pruned_hints_count = storage
+            .transactions_dal()
+            .prune_access_hints()
+            .await
+            .context("failed pruning access hints")?;
+        if pruned_hints_count > 0 {
+            tracing::info!("Pruned {pruned_hints_count} stale transaction access hints");
+        }
+
+        tracing::info!(
+            "Recovered mempool: {recovered_tx_count} pending transactions returned to mempool, \
+             {dropped_tx_count} stuck transactions dropped"
+        );
+        KEEPER_METRICS.mempool_recovery[&MempoolRecoveryOutcome::Recovered].set(recovered_tx_count);
+        KEEPER_METRICS.mempool_recovery[&MempoolRecoveryOutcome::DroppedStuck]
+            .set(dropped_tx_count);
+        Ok((recovered_tx_count, dropped_tx_count))
+    }
+
+    pub async fn run(mut self, stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        let mut storage = self.pool.connection_tagged("state_keeper").await?;
+        self.recover_pending_transactions(&mut storage).await?;
         drop(storage);
 
+        let mut evicted_accounts = HashSet::new();
         loop {
             if *stop_receiver.borrow() {
                 tracing::info!("Stop signal received, mempool is shutting down");
@@ -98,11 +146,24 @@ impl MempoolFetcher {
             )
             .await;
 
+            // Transactions of evicted accounts are not reloaded until the mempool has room; otherwise, the same
+            // transactions would be loaded and evicted on each iteration, preventing newer transactions from loading.
+            evicted_accounts.extend(mempool_info.evicted_accounts);
+            if mempool_info.has_l2_room && !evicted_accounts.is_empty() {
+                tracing::debug!(
+                    "Mempool has room for L2 transactions; allowing to reload {} evicted accounts",
+                    evicted_accounts.len()
+                );
+                evicted_accounts.clear();
+            }
+            let excluded_accounts: Vec<_> = evicted_accounts.iter().copied().collect();
+
             let transactions = storage
                 .transactions_dal()
                 .sync_mempool(
                     &mempool_info.stashed_accounts,
                     &mempool_info.purged_accounts,
+                    &excluded_accounts,
                     l2_tx_filter.gas_per_pubdata,
                     l2_tx_filter.fee_per_gas,
                     self.sync_batch_size,
@@ -110,6 +171,7 @@ impl MempoolFetcher {
                 .await
                 .context("failed syncing mempool")?;
             let nonces = get_transaction_nonces(&mut storage, &transactions).await?;
+            let access_hints = take_access_hints(&mut storage, &transactions).await?;
             drop(storage);
 
             #[cfg(test)]
@@ -118,6 +180,15 @@ impl MempoolFetcher {
                 self.transaction_hashes_sender.send(transaction_hashes).ok();
             }
             let all_transactions_loaded = transactions.len() < self.sync_batch_size;
+            // Hints are inserted before transactions, so that they are available once a transaction is fetched
+            // by the state keeper.
+            let dropped_hints_count = self.mempool.insert_access_hints(access_hints);
+            if dropped_hints_count > 0 {
+                tracing::warn!(
+                    "Dropped access hints for {dropped_hints_count} transactions since the hints capacity \
+                     is exceeded"
+                );
+            }
             self.mempool.insert(transactions, nonces);
             latency.observe();
 
@@ -129,6 +200,26 @@ impl MempoolFetcher {
     }
 }
 
+/// Takes access hints for L2 `transactions` from the storage.
+async fn take_access_hints(
+    storage: &mut Connection<'_, Core>,
+    transactions: &[Transaction],
+) -> anyhow::Result<HashMap<H256, TxAccessHints>> {
+    let tx_hashes: Vec<_> = transactions
+        .iter()
+        .filter(|tx| !tx.is_l1())
+        .map(Transaction::hash)
+        .collect();
+    if tx_hashes.is_empty() {
+        return Ok(HashMap::new());
+    }
+    storage
+        .transactions_dal()
+        .take_access_hints(&tx_hashes)
+        .await
+        .context("failed taking transaction access hints")
+}
+
 /// Loads nonces for all distinct `transactions` initiators from the storage.
 async fn get_transaction_nonces(
     storage: &mut Connection<'_, Core>,
@@ -179,6 +270,14 @@ mod tests {
         stuck_tx_timeout: 0,
         remove_stuck_txs: false,
         delay_interval: 10,
+        tx_deny_list: None,
+        tx_deny_list_reload_interval_sec: None,
+        tx_denied_calldata_prefixes: Vec::new(),
+        tx_deployment_only_windows: Vec::new(),
+        max_l2_transactions: None,
+        score_fee_weight: None,
+        score_age_weight: None,
+        score_rejection_penalty: None,
     };
 
     #[tokio::test]
@@ -261,6 +360,337 @@ mod tests {
         fetcher_task.await.unwrap().expect("fetcher errored");
     }
 
+    #[tokio::test]
+    async fn syncing_access_hints() {
+        let pool = ConnectionPool::constrained_test_pool(1).await;
+        let mut storage = pool.connection().await.unwrap();
+        insert_genesis_batch(&mut storage, &GenesisParams::mock())
+            .await
+            .unwrap();
+        drop(storage);
+
+        let mut mempool = MempoolGuard::new(PriorityOpId(0), 100);
+        let fee_params_provider = Arc::new(MockBatchFeeParamsProvider::default());
+        let fee_input = fee_params_provider.get_batch_fee_input().await;
+        let (base_fee, gas_per_pubdata) =
+            derive_base_fee_and_gas_per_pubdata(fee_input, ProtocolVersionId::latest().into());
+
+        let mut fetcher = MempoolFetcher::new(
+            mempool.clone(),
+            fee_params_provider,
+            &TEST_MEMPOOL_CONFIG,
+            pool.clone(),
+        );
+        let (tx_hashes_sender, mut tx_hashes_receiver) = mpsc::unbounded_channel();
+        fetcher.transaction_hashes_sender = tx_hashes_sender;
+        let (stop_sender, stop_receiver) = watch::channel(false);
+        let fetcher_task = tokio::spawn(fetcher.run(stop_receiver));
+
+        let transaction = create_l2_transaction(base_fee, gas_per_pubdata);
+        let transaction_hash = transaction.hash();
+        let hints = TxAccessHints {
+            storage_slots: vec![get_nonce_key(&transaction.initiator_account())],
+        };
+        let mut storage = pool.connection().await.unwrap();
+        let mut db_transaction = storage.start_transaction().await.unwrap();
+        db_transaction
+            .transactions_dal()
+            .insert_access_hints(transaction_hash, &hints)
+            .await
+            .unwrap();
+        db_transaction
+            .transactions_dal()
+            .insert_transaction_l2(&transaction, TransactionExecutionMetrics::default())
+            .await
+            .unwrap();
+        db_transaction.commit().await.unwrap();
+        drop(storage);
+
+        let tx_hashes = wait_for_new_transactions(&mut tx_hashes_receiver).await;
+        assert_eq!(tx_hashes, [transaction_hash]);
+        assert_eq!(mempool.take_access_hints(transaction_hash), Some(hints));
+        assert_eq!(mempool.take_access_hints(transaction_hash), None);
+
+        stop_sender.send_replace(true);
+        fetcher_task.await.unwrap().expect("fetcher errored");
+
+        // Hints must be consumed from the storage.
+        let mut storage = pool.connection().await.unwrap();
+        let persisted_hints = storage
+            .transactions_dal()
+            .take_access_hints(&[transaction_hash])
+            .await
+            .unwrap();
+        assert!(persisted_hints.is_empty());
+    }
+
+    #[tokio::test]
+    async fn recovering_pending_transactions() {
+        let pool = ConnectionPool::<Core>::constrained_test_pool(1).await;
+        let mut storage = pool.connection().await.unwrap();
+        insert_genesis_batch(&mut storage, &GenesisParams::mock())
+            .await
+            .unwrap();
+
+        let pending_tx = create_l2_transaction(10, 100);
+        storage
+            .transactions_dal()
+            .insert_transaction_l2(&pending_tx, TransactionExecutionMetrics::default())
+            .await
+            .unwrap();
+        // Emulate the transaction loaded into the mempool before the shutdown.
+        let loaded_txs = storage
+            .transactions_dal()
+            .sync_mempool(&[], &[], &[], 0, 0, 10)
+            .await
+            .unwrap();
+        assert_eq!(loaded_txs.len(), 1);
+
+        let mut stuck_tx = create_l2_transaction(10, 100);
+        stuck_tx.received_timestamp_ms -= 3_600_000;
+        storage
+            .transactions_dal()
+            .insert_transaction_l2(&stuck_tx, TransactionExecutionMetrics::default())
+            .await
+            .unwrap();
+
+        let config = MempoolConfig {
+            remove_stuck_txs: true,
+            stuck_tx_timeout: 60,
+            ..TEST_MEMPOOL_CONFIG
+        };
+        let fetcher = MempoolFetcher::new(
+            MempoolGuard::new(PriorityOpId(0), 100),
+            Arc::new(MockBatchFeeParamsProvider::default()),
+            &config,
+            pool.clone(),
+        );
+        let (recovered_tx_count, dropped_tx_count) = fetcher
+            .recover_pending_transactions(&mut storage)
+            .await
+            .unwrap();
+        assert_eq!(recovered_tx_count, 1);
+        assert_eq!(dropped_tx_count, 1);
+
+        let txs = storage
+            .transactions_dal()
+            .sync_mempool(&[], &[], &[], 0, 0, 10)
+            .await
+            .unwrap();
+        let tx_hashes: Vec<_> = txs.iter().map(Transaction::hash).collect();
+        assert_eq!(tx_hashes, [pending_tx.hash()]);
+    }
+
+    #[tokio::test]
+    async fn evicted_transactions_are_retained_in_storage() {
+        let pool = ConnectionPool::<Core>::constrained_test_pool(1).await;
+        let mut storage = pool.connection().await.unwrap();
+        insert_genesis_batch(&mut storage, &GenesisParams::mock())
+            .await
+            .unwrap();
+
+        let transactions = [
+            create_l2_transaction(10, 100),
+            create_l2_transaction(10, 100),
+        ];
+        for transaction in &transactions {
+            storage
+                .transactions_dal()
+                .insert_transaction_l2(transaction, TransactionExecutionMetrics::default())
+                .await
+                .unwrap();
+        }
+
+        let config = MempoolConfig {
+            max_l2_transactions: Some(1),
+            ..TEST_MEMPOOL_CONFIG
+        };
+        let mut mempool = MempoolGuard::from_storage(&mut storage, &config).await;
+        let loaded_txs = storage
+            .transactions_dal()
+            .sync_mempool(&[], &[], &[], 0, 0, 10)
+            .await
+            .unwrap();
+        assert_eq!(loaded_txs.len(), 2);
+        mempool.insert(loaded_txs, HashMap::new());
+        assert_eq!(mempool.stats().l2_transaction_count, 1);
+
+        let mempool_info = mempool.get_mempool_info();
+        assert_eq!(mempool_info.stashed_accounts.len(), 1);
+        assert!(mempool_info.purged_accounts.is_empty());
+        let evicted_account = mempool_info.stashed_accounts[0];
+        let evicted_tx = transactions
+            .iter()
+            .find(|tx| tx.initiator_account() == evicted_account)
+            .unwrap();
+
+        // The evicted transaction must survive the sync, but not be reloaded while its account is excluded.
+        let reloaded_txs = storage
+            .transactions_dal()
+            .sync_mempool(
+                &mempool_info.stashed_accounts,
+                &mempool_info.purged_accounts,
+                &mempool_info.evicted_accounts,
+                0,
+                0,
+                10,
+            )
+            .await
+            .unwrap();
+        assert!(reloaded_txs.is_empty());
+
+        // Once the account is no longer excluded, the transaction is reloaded.
+        let reloaded_txs = storage
+            .transactions_dal()
+            .sync_mempool(&[], &[], &[], 0, 0, 10)
+            .await
+            .unwrap();
+        let reloaded_tx_hashes: Vec<_> = reloaded_txs.iter().map(Transaction::hash).collect();
+        assert_eq!(reloaded_tx_hashes, [evicted_tx.hash()]);
+    }
+
+    #[tokio::test]
+    async fn evicted_transactions_are_not_reloaded_until_mempool_has_room() {
+        let pool = ConnectionPool::<Core>::constrained_test_pool(1).await;
+        let mut storage = pool.connection().await.unwrap();
+        insert_genesis_batch(&mut storage, &GenesisParams::mock())
+            .await
+            .unwrap();
+
+        let fee_params_provider = Arc::new(MockBatchFeeParamsProvider::default());
+        let fee_input = fee_params_provider.get_batch_fee_input().await;
+        let (base_fee, gas_per_pubdata) =
+            derive_base_fee_and_gas_per_pubdata(fee_input, ProtocolVersionId::latest().into());
+        let transactions: Vec<_> = (0..3)
+            .map(|_| create_l2_transaction(base_fee, gas_per_pubdata))
+            .collect();
+        for transaction in &transactions {
+            storage
+                .transactions_dal()
+                .insert_transaction_l2(transaction, TransactionExecutionMetrics::default())
+                .await
+                .unwrap();
+        }
+
+        let config = MempoolConfig {
+            max_l2_transactions: Some(1),
+            ..TEST_MEMPOOL_CONFIG
+        };
+        let mut mempool = MempoolGuard::from_storage(&mut storage, &config).await;
+        drop(storage);
+        let mut fetcher = MempoolFetcher::new(
+            mempool.clone(),
+            fee_params_provider.clone(),
+            &config,
+            pool.clone(),
+        );
+        let (tx_hashes_sender, mut tx_hashes_receiver) = mpsc::unbounded_channel();
+        fetcher.transaction_hashes_sender = tx_hashes_sender;
+        let (stop_sender, stop_receiver) = watch::channel(false);
+        let fetcher_task = tokio::spawn(fetcher.run(stop_receiver));
+
+        let tx_hashes = wait_for_new_transactions(&mut tx_hashes_receiver).await;
+        assert_eq!(tx_hashes.len(), transactions.len());
+
+        // A new transaction must be loaded even though the mempool is full.
+        let new_transaction = create_l2_transaction(base_fee, gas_per_pubdata);
+        let mut storage = pool.connection().await.unwrap();
+        storage
+            .transactions_dal()
+            .insert_transaction_l2(&new_transaction, TransactionExecutionMetrics::default())
+            .await
+            .unwrap();
+        drop(storage);
+        let tx_hashes = wait_for_new_transactions(&mut tx_hashes_receiver).await;
+        assert_eq!(tx_hashes, [new_transaction.hash()]);
+
+        // Evicted transactions must not be reloaded while the mempool is full.
+        tokio::time::sleep(config.sync_interval() * 5).await;
+        while let Ok(tx_hashes) = tx_hashes_receiver.try_recv() {
+            assert!(tx_hashes.is_empty(), "{tx_hashes:?}");
+        }
+        assert_eq!(mempool.stats().l2_transaction_count, 1);
+
+        // Once the state keeper takes a transaction from the mempool, evicted transactions are reloaded.
+        let filter = l2_tx_filter(
+            fee_params_provider.as_ref(),
+            ProtocolVersionId::latest().into(),
+        )
+        .await;
+        let taken_tx = mempool.next_transaction(&filter).unwrap();
+        let tx_hashes = wait_for_new_transactions(&mut tx_hashes_receiver).await;
+        assert!(!tx_hashes.is_empty());
+        assert!(!tx_hashes.contains(&taken_tx.hash()));
+
+        stop_sender.send_replace(true);
+        fetcher_task.await.unwrap().expect("fetcher errored");
+    }
+
+    #[tokio::test]
+    async fn recovered_transactions_retain_queue_order() {
+        let pool = ConnectionPool::<Core>::constrained_test_pool(1).await;
+        let mut storage = pool.connection().await.unwrap();
+        insert_genesis_batch(&mut storage, &GenesisParams::mock())
+            .await
+            .unwrap();
+
+        let fee_params_provider = Arc::new(MockBatchFeeParamsProvider::default());
+        let fee_input = fee_params_provider.get_batch_fee_input().await;
+        let (base_fee, gas_per_pubdata) =
+            derive_base_fee_and_gas_per_pubdata(fee_input, ProtocolVersionId::latest().into());
+
+        // Insert transactions so that the insertion order differs from the order in which they were received.
+        let mut transactions: Vec<_> = (0..3)
+            .map(|_| create_l2_transaction(base_fee, gas_per_pubdata))
+            .collect();
+        for (i, transaction) in transactions.iter_mut().enumerate() {
+            transaction.received_timestamp_ms -= 1_000 * (i as u64 + 1);
+        }
+        for transaction in &transactions {
+            storage
+                .transactions_dal()
+                .insert_transaction_l2(transaction, TransactionExecutionMetrics::default())
+                .await
+                .unwrap();
+        }
+        // Emulate the transactions loaded into the mempool before the shutdown.
+        storage
+            .transactions_dal()
+            .sync_mempool(&[], &[], &[], 0, 0, 10)
+            .await
+            .unwrap();
+        drop(storage);
+
+        let mempool = MempoolGuard::new(PriorityOpId(0), 100);
+        let mut fetcher = MempoolFetcher::new(
+            mempool.clone(),
+            fee_params_provider.clone(),
+            &TEST_MEMPOOL_CONFIG,
+            pool.clone(),
+        );
+        let (tx_hashes_sender, mut tx_hashes_receiver) = mpsc::unbounded_channel();
+        fetcher.transaction_hashes_sender = tx_hashes_sender;
+        let (stop_sender, stop_receiver) = watch::channel(false);
+        let fetcher_task = tokio::spawn(fetcher.run(stop_receiver));
+
+        let tx_hashes = wait_for_new_transactions(&mut tx_hashes_receiver).await;
+        assert_eq!(tx_hashes.len(), transactions.len());
+        stop_sender.send_replace(true);
+        fetcher_task.await.unwrap().expect("fetcher errored");
+
+        let filter = l2_tx_filter(
+            fee_params_provider.as_ref(),
+            ProtocolVersionId::latest().into(),
+        )
+        .await;
+        let mut mempool = mempool;
+        let recovered_tx_hashes: Vec<_> = std::iter::from_fn(|| mempool.next_transaction(&filter))
+            .map(|tx| tx.hash())
+            .collect();
+        let expected_tx_hashes: Vec<_> = transactions.iter().rev().map(|tx| tx.hash()).collect();
+        assert_eq!(recovered_tx_hashes, expected_tx_hashes);
+    }
+
     async fn wait_for_new_transactions(
         tx_hashes_receiver: &mut mpsc::UnboundedReceiver<Vec<H256>>,
     ) -> Vec<H256> {
@@ -7,12 +7,15 @@ use std::{
 
 use multivm::interface::VmExecutionResultAndLogs;
 use vise::{
-    Buckets, Counter, EncodeLabelSet, EncodeLabelValue, Family, Gauge, Histogram, LatencyObserver,
-    Metrics,
+    Buckets, Counter, EncodeLabelSet, EncodeLabelValue, Family, Gauge, Histogram, LabeledFamily,
+    LatencyObserver, Metrics, Unit,
 };
 use zksync_mempool::MempoolStore;
 use zksync_shared_metrics::InteractionType;
-use zksync_types::{tx::tx_execution_info::DeduplicatedWritesMetrics, ProtocolVersionId};
+use zksync_types::{
+    circuit::CircuitStatistic, helpers::unix_timestamp_ms,
+    tx::tx_execution_info::DeduplicatedWritesMetrics, ProtocolVersionId,
+};
 
 use super::seal_criteria::SealResolution;
 
@@ -39,6 +42,72 @@ impl TxExecutionType {
     }
 }
 
+/// Most severe storage conflict of a transaction with the preceding transactions in the same L1 batch.
+/// Variants are ordered by severity for parallel execution.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, EncodeLabelValue, EncodeLabelSet,
+)]
+#[metrics(label = "conflict", rename_all = "snake_case")]
+pub(crate) enum TxConflict {
+    /// Transaction doesn't access storage slots written by preceding transactions.
+    None,
+    /// Transaction accesses a slot written by a transaction from the same sender.
+    SameSender,
+    /// Transaction accesses a slot written by a transaction from another sender calling the same contract.
+    SameContract,
+    /// Transaction accesses a slot written by a transaction from another sender calling another contract.
+    OtherContract,
+}
+
+/// Grouping of transactions for which storage conflict rates are measured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "scope", rename_all = "snake_case")]
+pub(crate) enum ConflictScope {
+    /// Transactions from the same sender.
+    Sender,
+    /// Transactions calling the same contract.
+    Contract,
+}
+
+const CONFLICT_RATE_BUCKETS: Buckets = Buckets::linear(0.0..=1.0, 0.1);
+
+/// Outcome of scheduling a transaction with access hints for parallel execution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "outcome", rename_all = "snake_case")]
+pub(crate) enum HintedTxOutcome {
+    /// Hints were sufficient to schedule the transaction without conflicts.
+    Accurate,
+    /// Transaction has accessed a slot conflicting with a transaction scheduled in the same or later wave,
+    /// i.e., it would have to be re-executed.
+    Mispredicted,
+}
+
+const HINTED_PARALLELISM_BUCKETS: Buckets = Buckets::values(&[
+    1.0, 1.5, 2.0, 3.0, 4.0, 6.0, 8.0, 12.0, 16.0, 24.0, 32.0, 64.0, 128.0,
+]);
+
+/// Outcome for transactions pending at the moment of the previous mempool shutdown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "outcome", rename_all = "snake_case")]
+pub(crate) enum MempoolRecoveryOutcome {
+    /// Transaction was loaded into the mempool before the shutdown and is returned to the mempool.
+    Recovered,
+    /// Transaction was dropped because it wasn't included into a miniblock in time.
+    DroppedStuck,
+}
+
+/// Limit on priority operations imposed by the mempool I/O.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "limit", rename_all = "snake_case")]
+pub(crate) enum PriorityOpsLimit {
+    /// Maximum number of priority operations in a miniblock.
+    Miniblock,
+    /// Maximum number of priority operations in an L1 batch.
+    L1Batch,
+    /// Maximum number of priority operations executed in a row.
+    Consecutive,
+}
+
 const INCLUSION_DELAY_BUCKETS: Buckets = Buckets::values(&[
     0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.0, 1.1, 1.2, 1.3, 1.4, 1.5, 1.6, 1.7, 1.8, 1.9,
     2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 20.0, 30.0, 60.0, 120.0, 240.0,
@@ -59,6 +128,10 @@ pub(crate) struct StateKeeperMetrics {
     pub get_tx_from_mempool: Histogram<Duration>,
     /// Number of transactions rejected by the state keeper.
     pub rejected_transactions: Counter,
+    /// Number of L2 transactions evicted from the mempool because of the mempool size limit.
+    pub mempool_evicted_transactions: Counter,
+    /// Number of times a limit on priority operations was reached, after which priority operations were deferred.
+    pub priority_ops_limit_reached: Family<PriorityOpsLimit, Counter>,
     /// Time spent waiting for the hash of a previous L1 batch.
     #[metrics(buckets = Buckets::LATENCIES)]
     pub wait_for_prev_hash_time: Histogram<Duration>,
@@ -75,6 +148,27 @@ pub(crate) struct StateKeeperMetrics {
     pub gas_price_too_high: Counter,
     /// Number of times blob base fee was reported as too high.
     pub blob_base_fee_too_high: Counter,
+    /// Number of executed transactions grouped by their storage conflicts with the preceding transactions
+    /// in the same L1 batch.
+    pub tx_storage_conflicts: Family<TxConflict, Counter>,
+    /// Maximum share of conflicting transactions among transactions from a single sender / calling a single contract
+    /// in an L1 batch. Only senders / contracts with several transactions in the batch are considered.
+    #[metrics(buckets = CONFLICT_RATE_BUCKETS)]
+    pub batch_max_conflict_rate: Family<ConflictScope, Histogram<f64>>,
+    /// Number of executed transactions with access hints grouped by the outcome of hint-based scheduling.
+    pub hinted_txs: Family<HintedTxOutcome, Counter>,
+    /// Average number of transactions per wave if an L1 batch containing transactions with access hints
+    /// was executed in waves scheduled based on the hints.
+    #[metrics(buckets = HINTED_PARALLELISM_BUCKETS)]
+    pub batch_hinted_parallelism: Histogram<f64>,
+    /// Number of pending transactions recovered or dropped when restoring the mempool after the node restart.
+    pub mempool_recovery: Family<MempoolRecoveryOutcome, Gauge<usize>>,
+    /// Skew of the local clock compared to the NTP server in milliseconds; positive if the local clock is behind.
+    pub clock_skew_ms: Gauge<i64>,
+    /// Number of times the local clock was behind the timestamp of the previous miniblock.
+    pub clock_behind_previous_miniblock: Counter,
+    /// Number of times miniblock production was paused because of excessive clock skew.
+    pub paused_on_clock_skew: Counter,
 }
 
 #[vise::register]
@@ -90,6 +184,9 @@ pub(super) struct StateKeeperGauges {
     mempool_l2_size: Gauge<u64>,
     /// Current size of the L2 priority queue.
     l2_priority_queue_size: Gauge<usize>,
+    /// Time elapsed since the next priority operation to be executed was received.
+    /// Zero if there are no pending priority operations in the mempool.
+    mempool_l1_next_tx_age: Gauge<Duration>,
 }
 
 impl StateKeeperGauges {
@@ -108,6 +205,14 @@ impl StateKeeperGauges {
                 gauges
                     .l2_priority_queue_size
                     .set(stats.l2_priority_queue_size);
+                let next_l1_tx_age = stats
+                    .next_l1_transaction_received_timestamp_ms
+                    .map_or(0, |received_at_ms| {
+                        unix_timestamp_ms().saturating_sub(received_at_ms)
+                    });
+                gauges
+                    .mempool_l1_next_tx_age
+                    .set(Duration::from_millis(next_l1_tx_age));
                 gauges
             })
         });
@@ -262,6 +367,7 @@ pub(super) enum MiniblockSealStage {
     InsertTokens,
     ExtractEvents,
     InsertEvents,
+    InsertTokenHolders,
     ExtractL2ToL1Logs,
     InsertL2ToL1Logs,
     CommitMiniblock,
@@ -322,6 +428,38 @@ impl MiniblockMetrics {
 #[vise::register]
 pub(super) static MINIBLOCK_METRICS: vise::Global<MiniblockMetrics> = vise::Global::new();
 
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "server_state_keeper_raw_blocks")]
+pub(super) struct RawBlocksMetrics {
+    /// Number of raw blocks queued for persistence.
+    pub queue_len: Gauge<usize>,
+    /// Latency of encoding and persisting a raw block, including waiting for its miniblock to be sealed.
+    #[metrics(buckets = Buckets::LATENCIES)]
+    pub persist_latency: Histogram<Duration>,
+    /// Number of the last miniblock with a persisted raw block.
+    pub last_persisted_miniblock: Gauge<u64>,
+}
+
+#[vise::register]
+pub(super) static RAW_BLOCKS_METRICS: vise::Global<RawBlocksMetrics> = vise::Global::new();
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "server_replay_loop")]
+pub(super) struct ReplayLoopMetrics {
+    /// Latency of re-executing an L1 batch by a background component.
+    #[metrics(buckets = Buckets::LATENCIES, unit = Unit::Seconds, labels = ["component"])]
+    pub reexecution_latency: LabeledFamily<&'static str, Histogram<Duration>>,
+    /// Latency of processing outputs of a re-executed L1 batch by a background component.
+    #[metrics(buckets = Buckets::LATENCIES, unit = Unit::Seconds, labels = ["component"])]
+    pub processing_latency: LabeledFamily<&'static str, Histogram<Duration>>,
+    /// Number of the last L1 batch processed by a background component.
+    #[metrics(labels = ["component"])]
+    pub last_processed_l1_batch: LabeledFamily<&'static str, Gauge<u64>>,
+}
+
+#[vise::register]
+pub(super) static REPLAY_LOOP_METRICS: vise::Global<ReplayLoopMetrics> = vise::Global::new();
+
 /// Tracking progress of L1 batch or miniblock sealing.
 #[must_use = "Progress must be `observe()`d"]
 #[derive(Debug)]
@@ -365,6 +503,21 @@ pub(super) enum ExecutorCommand {
     FinishBatch,
 }
 
+/// Kind of divergence between the main and the shadow VM in the batch executor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "divergence", rename_all = "snake_case")]
+pub(super) enum ShadowVmDivergence {
+    /// Shadow VM has panicked; shadow execution is disabled for the rest of the L1 batch.
+    Panic,
+    /// Transaction or batch tip execution result.
+    Result,
+    Events,
+    StorageLogs,
+    Refunds,
+    /// Final execution state of an L1 batch.
+    FinalState,
+}
+
 const GAS_PER_NANOSECOND_BUCKETS: Buckets = Buckets::values(&[
     0.01, 0.03, 0.1, 0.3, 0.5, 0.75, 1., 1.5, 3., 5., 10., 20., 50.,
 ]);
@@ -384,6 +537,16 @@ pub(super) struct ExecutorMetrics {
     pub computational_gas_per_nanosecond: Histogram<f64>,
     #[metrics(buckets = GAS_PER_NANOSECOND_BUCKETS)]
     pub failed_tx_gas_limit_per_nanosecond: Histogram<f64>,
+    /// Number of L1 batches executed by the shadow VM.
+    pub shadow_vm_batches: Counter,
+    /// Number of divergences between the main and the shadow VM.
+    pub shadow_vm_divergences: Family<ShadowVmDivergence, Counter>,
+    /// Number of L1 batches for which shadow execution was aborted because the shadow VM lagged behind the main VM.
+    pub shadow_vm_aborted_batches: Counter,
+    /// Number of times an isolated batch executor worker crashed or otherwise failed executing a command.
+    pub isolated_worker_crashes: Counter,
+    /// Number of transactions rejected because they repeatedly crashed the isolated batch executor worker.
+    pub isolated_worker_rejected_txs: Counter,
 }
 
 #[vise::register]
@@ -439,3 +602,75 @@ impl BatchTipMetrics {
 
 #[vise::register]
 pub(crate) static BATCH_TIP_METRICS: vise::Global<BatchTipMetrics> = vise::Global::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "circuit", rename_all = "snake_case")]
+pub(super) enum CircuitType {
+    MainVm,
+    RamPermutation,
+    StorageApplication,
+    StorageSorter,
+    CodeDecommitter,
+    CodeDecommitterSorter,
+    LogDemuxer,
+    EventsSorter,
+    Keccak256,
+    Ecrecover,
+    Sha256,
+    Secp256k1Verify,
+    TransientStorageChecker,
+}
+
+impl CircuitType {
+    pub fn split(statistic: &CircuitStatistic) -> [(Self, f32); 13] {
+        [
+            (Self::MainVm, statistic.main_vm),
+            (Self::RamPermutation, statistic.ram_permutation),
+            (Self::StorageApplication, statistic.storage_application),
+            (Self::StorageSorter, statistic.storage_sorter),
+            (Self::CodeDecommitter, statistic.code_decommitter),
+            (
+                Self::CodeDecommitterSorter,
+                statistic.code_decommitter_sorter,
+            ),
+            (Self::LogDemuxer, statistic.log_demuxer),
+            (Self::EventsSorter, statistic.events_sorter),
+            (Self::Keccak256, statistic.keccak256),
+            (Self::Ecrecover, statistic.ecrecover),
+            (Self::Sha256, statistic.sha256),
+            (Self::Secp256k1Verify, statistic.secp256k1_verify),
+            (
+                Self::TransientStorageChecker,
+                statistic.transient_storage_checker,
+            ),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "result", rename_all = "snake_case")]
+pub(super) enum CircuitAdmissionResult {
+    /// Transaction is admitted based on its estimated circuit usage.
+    Admitted,
+    /// Transaction is admitted because there's no circuit usage estimate for it.
+    NoEstimate,
+    /// Transaction is deferred to the next L1 batch without execution.
+    Deferred,
+}
+
+/// Metrics related to circuit capacity accounting for the open L1 batch.
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "server_state_keeper_circuits")]
+pub(super) struct CircuitCapacityMetrics {
+    /// Number of base layer circuits used by the open L1 batch, split by the circuit type. Values may be fractional.
+    pub batch_used: Family<CircuitType, Gauge<f64>>,
+    /// Total number of base layer circuits used by the open L1 batch, including the batch tip overhead.
+    pub batch_used_total: Gauge<usize>,
+    /// Number of base layer circuits available for the open L1 batch, including the batch tip overhead.
+    pub batch_capacity: Gauge<usize>,
+    /// Results of admitting transactions to the open L1 batch based on their estimated circuit usage.
+    pub tx_admission: Family<CircuitAdmissionResult, Counter>,
+}
+
+#[vise::register]
+pub(super) static CIRCUIT_METRICS: vise::Global<CircuitCapacityMetrics> = vise::Global::new();
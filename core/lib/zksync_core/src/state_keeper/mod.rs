@@ -9,28 +9,38 @@ use zksync_config::{
     DBConfig,
 };
 use zksync_dal::{ConnectionPool, Core};
+use zksync_state::BytecodeCache;
 use zksync_types::L2ChainId;
 
+pub(crate) use self::batch_executor::TxExecutionResult;
 pub use self::{
-    batch_executor::{main_executor::MainBatchExecutor, BatchExecutor},
+    batch_executor::{
+        isolated as isolated_batch_executor, main_executor::MainBatchExecutor, BatchExecutor,
+    },
+    fee_accounts::FeeAccountSelector,
     io::{
-        mempool::MempoolIO, MiniblockSealerTask, OutputHandler, StateKeeperIO,
-        StateKeeperOutputHandler, StateKeeperPersistence,
+        mempool::MempoolIO, ClockSkewMonitor, ClockSkewStatus, MiniblockSealerTask,
+        OperatorTxProvider, OutputHandler, RawBlocksPersistence, RawBlocksPersisterTask,
+        StateKeeperIO, StateKeeperOutputHandler, StateKeeperPersistence, SystemTimeSource,
+        TimeSource,
     },
     keeper::ZkSyncStateKeeper,
     mempool_actor::MempoolFetcher,
-    seal_criteria::SequencerSealer,
+    seal_criteria::{BatchSealMonitor, SequencerSealer},
     state_keeper_storage::{AsyncCatchupTask, AsyncRocksdbCache},
     types::MempoolGuard,
 };
-use crate::fee_model::BatchFeeModelInputProvider;
+use crate::{fee_model::BatchFeeModelInputProvider, tx_filter::TransactionFilter};
 
 mod batch_executor;
+mod circuit_capacity;
 pub(crate) mod extractors;
+mod fee_accounts;
 pub(crate) mod io;
 mod keeper;
 mod mempool_actor;
 pub(crate) mod metrics;
+pub mod replay;
 pub mod seal_criteria;
 mod state_keeper_storage;
 #[cfg(test)]
@@ -46,43 +56,67 @@ pub(crate) async fn create_state_keeper(
     l2chain_id: L2ChainId,
     mempool_config: &MempoolConfig,
     pool: ConnectionPool<Core>,
+    shadow_vm_pool: Option<ConnectionPool<Core>>,
     mempool: MempoolGuard,
     batch_fee_input_provider: Arc<dyn BatchFeeModelInputProvider>,
+    tx_filter: Arc<dyn TransactionFilter>,
     output_handler: OutputHandler,
+    seal_monitor: BatchSealMonitor,
+    bytecode_cache: BytecodeCache,
     stop_receiver: watch::Receiver<bool>,
-) -> (ZkSyncStateKeeper, AsyncCatchupTask) {
+) -> (
+    ZkSyncStateKeeper,
+    AsyncCatchupTask,
+    Option<ClockSkewMonitor>,
+) {
     let (storage_factory, task) = AsyncRocksdbCache::new(
         pool.clone(),
         db_config.state_keeper_db_path.clone(),
         state_keeper_config.enum_index_migration_chunk_size(),
     );
-    let batch_executor_base = MainBatchExecutor::new(
+    let storage_factory = storage_factory.with_bytecode_cache(bytecode_cache);
+    let mut batch_executor_base = MainBatchExecutor::new(
         Arc::new(storage_factory),
         state_keeper_config.save_call_traces,
         false,
-    );
+    )
+    .with_process_isolation(state_keeper_config.batch_executor_process_isolation());
+    if let Some(pool) = shadow_vm_pool {
+        batch_executor_base = batch_executor_base.with_shadow_vm_execution(pool);
+    }
 
-    let io = MempoolIO::new(
+    let mut io = MempoolIO::new(
         mempool,
         batch_fee_input_provider,
         pool,
         &state_keeper_config,
-        wallets.fee_account.address(),
+        FeeAccountSelector::new(wallets.fee_account.address(), &state_keeper_config),
         mempool_config.delay_interval(),
         l2chain_id,
+        tx_filter,
     )
     .await
     .expect("Failed initializing main node I/O for state keeper");
+    let clock_skew_monitor = state_keeper_config
+        .ntp_server
+        .clone()
+        .map(|ntp_server| ClockSkewMonitor::new(ntp_server, state_keeper_config.max_clock_skew()));
+    if let Some(monitor) = &clock_skew_monitor {
+        io = io.with_clock_skew_monitor(monitor);
+    }
 
+    let seal_miniblock_on_shutdown = state_keeper_config.seal_miniblock_on_shutdown();
     let sealer = SequencerSealer::new(state_keeper_config);
-    (
-        ZkSyncStateKeeper::new(
-            stop_receiver,
-            Box::new(io),
-            Box::new(batch_executor_base),
-            output_handler,
-            Arc::new(sealer),
-        ),
-        task,
+    let mut state_keeper = ZkSyncStateKeeper::new(
+        stop_receiver,
+        Box::new(io),
+        Box::new(batch_executor_base),
+        output_handler,
+        Arc::new(sealer),
     )
+    .with_seal_monitor(seal_monitor);
+    if seal_miniblock_on_shutdown {
+        state_keeper = state_keeper.with_miniblock_sealing_on_shutdown();
+    }
+    (state_keeper, task, clock_skew_monitor)
 }
@@ -0,0 +1,588 @@
+//! Deterministic replay of sealed L1 batches.
+//!
+//! The replayer re-executes a historical L1 batch from transactions stored in Postgres using the same
+//! [`MainBatchExecutor`] as the state keeper, and compares the execution outputs (storage writes and events)
+//! with the ones persisted for the batch. Divergences indicate non-deterministic execution, e.g. caused
+//! by an incorrectly applied protocol upgrade or a bug in the VM.
+//!
+//! Background components re-executing sealed batches one by one are driven by [`ReplayLoop`].
+
+use std::{collections::HashMap, fmt, sync::Arc, time::Duration};
+
+use anyhow::Context as _;
+use async_trait::async_trait;
+use multivm::interface::{FinishedL1Batch, L2BlockEnv, VmExecutionResultAndLogs};
+use tokio::{runtime::Handle, sync::watch};
+use vm_utils::storage::L1BatchParamsProvider;
+use zksync_dal::{Connection, ConnectionPool, Core, CoreDal};
+use zksync_health_check::{Health, HealthStatus, HealthUpdater, ReactiveHealthCheck};
+use zksync_state::PostgresStorage;
+use zksync_types::{
+    event::VmEvent, storage_writes_deduplicator::StorageWritesDeduplicator, L1BatchNumber,
+    L2ChainId, MiniblockNumber, StorageKey, Transaction, H256,
+};
+use zksync_utils::u256_to_h256;
+
+use super::{
+    batch_executor::{main_executor::MainBatchExecutor, BatchExecutor, TxExecutionResult},
+    metrics::REPLAY_LOOP_METRICS,
+    state_keeper_storage::{PgOrRocksdbStorage, ReadStorageFactory},
+};
+
+/// Storage factory providing Postgres storage as of the specified miniblock.
+#[derive(Debug)]
+struct HistoricalStorageFactory {
+    pool: ConnectionPool<Core>,
+    miniblock_number: MiniblockNumber,
+}
+
+#[async_trait]
+impl ReadStorageFactory for HistoricalStorageFactory {
+    async fn access_storage(
+        &self,
+        _stop_receiver: &watch::Receiver<bool>,
+    ) -> anyhow::Result<Option<PgOrRocksdbStorage<'_>>> {
+        let connection = self.pool.connection_tagged("batch_replayer").await?;
+        let storage =
+            PostgresStorage::new_async(Handle::current(), connection, self.miniblock_number, true)
+                .await?;
+        Ok(Some(storage.into()))
+    }
+}
+
+/// Difference in a value between the persisted and replayed execution outputs. `None` means that the value
+/// is missing in the corresponding output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueDiff<T> {
+    pub persisted: Option<T>,
+    pub replayed: Option<T>,
+}
+
+/// Outcome of replaying an L1 batch.
+#[derive(Debug, Default)]
+pub struct ReplayReport {
+    /// Hashes of transactions rejected by the VM during replay together with rejection reasons.
+    pub rejected_transactions: Vec<(H256, String)>,
+    /// Storage slots with differing values after the batch, ordered by the hashed key.
+    pub storage_diffs: Vec<(StorageKey, ValueDiff<H256>)>,
+    /// Events differing between persisted and replayed outputs, keyed by the event index in the batch.
+    /// Events are compared by address, topics and data.
+    pub event_diffs: Vec<(usize, ValueDiff<VmEvent>)>,
+}
+
+impl ReplayReport {
+    /// Checks whether replay has reproduced the persisted execution outputs.
+    pub fn is_consistent(&self) -> bool {
+        self.rejected_transactions.is_empty()
+            && self.storage_diffs.is_empty()
+            && self.event_diffs.is_empty()
+    }
+}
+
+impl fmt::Display for ReplayReport {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_consistent() {
+            return formatter.write_str("replayed execution outputs match persisted ones");
+        }
+        for (tx_hash, reason) in &self.rejected_transactions {
+            writeln!(
+                formatter,
+                "transaction {tx_hash:?} was rejected by VM: {reason}"
+            )?;
+        }
+        for (key, diff) in &self.storage_diffs {
+            writeln!(
+                formatter,
+                "storage slot {:?} at {:?}: persisted {:?}, replayed {:?}",
+                key.key(),
+                key.address(),
+                diff.persisted,
+                diff.replayed
+            )?;
+        }
+        for (index, diff) in &self.event_diffs {
+            writeln!(
+                formatter,
+                "event #{index}: persisted {:?}, replayed {:?}",
+                diff.persisted, diff.replayed
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Outputs of an L1 batch execution compared by [`BatchReplayer`].
+#[derive(Debug, Default)]
+struct ExecutionOutputs {
+    /// Latest values for all storage slots written to in the batch.
+    touched_slots: HashMap<StorageKey, H256>,
+    events: Vec<VmEvent>,
+}
+
+impl ExecutionOutputs {
+    /// Applies outputs of a miniblock. Mirrors the way storage writes are persisted by the state keeper, i.e.,
+    /// with deduplication within each miniblock.
+    fn push_miniblock(&mut self, results: &[VmExecutionResultAndLogs]) {
+        let mut deduplicator = StorageWritesDeduplicator::new();
+        for result in results {
+            deduplicator.apply(
+                result
+                    .logs
+                    .storage_logs
+                    .iter()
+                    .filter(|log| log.log_query.rw_flag),
+            );
+            self.events.extend(result.logs.events.iter().cloned());
+        }
+        let modified_slots = deduplicator.into_modified_key_values();
+        self.touched_slots.extend(
+            modified_slots
+                .into_iter()
+                .map(|(key, slot)| (key, u256_to_h256(slot.value))),
+        );
+    }
+
+    fn diff(self, persisted: Self, report: &mut ReplayReport) {
+        let mut storage_diffs: Vec<_> = persisted
+            .touched_slots
+            .iter()
+            .filter_map(|(key, &persisted_value)| {
+                let replayed_value = self.touched_slots.get(key).copied();
+                (replayed_value != Some(persisted_value)).then_some((
+                    *key,
+                    ValueDiff {
+                        persisted: Some(persisted_value),
+                        replayed: replayed_value,
+                    },
+                ))
+            })
+            .collect();
+        storage_diffs.extend(self.touched_slots.iter().filter_map(|(key, &value)| {
+            (!persisted.touched_slots.contains_key(key)).then_some((
+                *key,
+                ValueDiff {
+                    persisted: None,
+                    replayed: Some(value),
+                },
+            ))
+        }));
+        storage_diffs.sort_unstable_by_key(|(key, _)| key.hashed_key());
+        report.storage_diffs = storage_diffs;
+
+        let event_count = persisted.events.len().max(self.events.len());
+        report.event_diffs = (0..event_count)
+            .filter_map(|i| {
+                let persisted_event = persisted.events.get(i);
+                let replayed_event = self.events.get(i);
+                let is_equal = match (persisted_event, replayed_event) {
+                    (Some(persisted), Some(replayed)) => {
+                        persisted.address == replayed.address
+                            && persisted.indexed_topics == replayed.indexed_topics
+                            && persisted.value == replayed.value
+                    }
+                    _ => false,
+                };
+                (!is_equal).then(|| {
+                    let diff = ValueDiff {
+                        persisted: persisted_event.cloned(),
+                        replayed: replayed_event.cloned(),
+                    };
+                    (i, diff)
+                })
+            })
+            .collect();
+    }
+}
+
+/// Outputs of re-executing an L1 batch.
+#[derive(Debug)]
+pub(crate) struct ReexecutedL1Batch {
+    /// Execution results for transactions in each non-fictive miniblock of the batch.
+    pub miniblocks: Vec<Vec<(Transaction, TxExecutionResult)>>,
+    pub finished_batch: FinishedL1Batch,
+}
+
+impl ReexecutedL1Batch {
+    /// Compares outputs of this re-execution with the outputs persisted for the specified L1 batch.
+    pub(crate) async fn compare_with_persisted(
+        self,
+        storage: &mut Connection<'_, Core>,
+        l1_batch_number: L1BatchNumber,
+    ) -> anyhow::Result<ReplayReport> {
+        let persisted_outputs = ExecutionOutputs {
+            touched_slots: storage
+                .storage_logs_dal()
+                .get_touched_slots_for_l1_batch(l1_batch_number)
+                .await?,
+            events: storage
+                .events_dal()
+                .get_vm_events_for_l1_batch(l1_batch_number)
+                .await?
+                .unwrap_or_default(),
+        };
+
+        let mut report = ReplayReport::default();
+        let mut replayed_outputs = ExecutionOutputs::default();
+        for tx_results in self.miniblocks {
+            let mut results = Vec::with_capacity(tx_results.len());
+            for (tx, tx_result) in tx_results {
+                let tx_hash = tx.hash();
+                match tx_result {
+                    TxExecutionResult::Success { tx_result, .. } => results.push(*tx_result),
+                    TxExecutionResult::RejectedByVm { reason } => {
+                        report
+                            .rejected_transactions
+                            .push((tx_hash, reason.to_string()));
+                    }
+                    TxExecutionResult::BootloaderOutOfGasForTx => {
+                        let reason = "bootloader is out of gas".to_owned();
+                        report.rejected_transactions.push((tx_hash, reason));
+                    }
+                }
+            }
+            replayed_outputs.push_miniblock(&results);
+        }
+        let block_tip_result = self.finished_batch.block_tip_execution_result;
+        replayed_outputs.push_miniblock(&[block_tip_result]);
+        replayed_outputs.diff(persisted_outputs, &mut report);
+        Ok(report)
+    }
+}
+
+/// Re-executes sealed L1 batches and compares execution outputs with the persisted ones.
+#[derive(Debug)]
+pub struct BatchReplayer {
+    pool: ConnectionPool<Core>,
+    l2_chain_id: L2ChainId,
+    optional_bytecode_compression: bool,
+    save_call_traces: bool,
+}
+
+impl BatchReplayer {
+    /// Creates a new replayer. `optional_bytecode_compression` must correspond to the setting of the node
+    /// that has executed the batches (it's disabled on the main node and enabled on external nodes).
+    pub fn new(
+        pool: ConnectionPool<Core>,
+        l2_chain_id: L2ChainId,
+        optional_bytecode_compression: bool,
+    ) -> Self {
+        Self {
+            pool,
+            l2_chain_id,
+            optional_bytecode_compression,
+            save_call_traces: false,
+        }
+    }
+
+    /// Enables collecting call traces for re-executed transactions. Call traces are returned
+    /// in [`TxExecutionResult`]s of the re-executed batch.
+    pub fn with_call_traces(mut self) -> Self {
+        self.save_call_traces = true;
+        self
+    }
+
+    /// Replays the specified L1 batch. The batch must be sealed.
+    pub async fn replay(
+        &self,
+        l1_batch_number: L1BatchNumber,
+        stop_receiver: &watch::Receiver<bool>,
+    ) -> anyhow::Result<ReplayReport> {
+        let executed_batch = self.reexecute(l1_batch_number, stop_receiver).await?;
+        let mut storage = self.pool.connection_tagged("batch_replayer").await?;
+        let report = executed_batch
+            .compare_with_persisted(&mut storage, l1_batch_number)
+            .await?;
+        tracing::info!(
+            "Finished replaying L1 batch #{l1_batch_number}; consistent: {}",
+            report.is_consistent()
+        );
+        Ok(report)
+    }
+
+    /// Re-executes the specified L1 batch without comparing outputs with the persisted ones.
+    /// The batch must be sealed.
+    pub(crate) async fn reexecute(
+        &self,
+        l1_batch_number: L1BatchNumber,
+        stop_receiver: &watch::Receiver<bool>,
+    ) -> anyhow::Result<ReexecutedL1Batch> {
+        let mut storage = self.pool.connection_tagged("batch_replayer").await?;
+        let sealed_l1_batch = storage
+            .blocks_dal()
+            .get_sealed_l1_batch_number()
+            .await?
+            .context("no sealed L1 batches")?;
+        anyhow::ensure!(
+            l1_batch_number <= sealed_l1_batch,
+            "L1 batch #{l1_batch_number} is not sealed; the latest sealed batch is #{sealed_l1_batch}"
+        );
+
+        let params_provider = L1BatchParamsProvider::new(&mut storage)
+            .await
+            .context("failed initializing L1 batch params provider")?;
+        let first_miniblock_in_batch = params_provider
+            .load_first_miniblock_in_batch(&mut storage, l1_batch_number)
+            .await
+            .with_context(|| {
+                format!("failed loading first miniblock in L1 batch #{l1_batch_number}")
+            })?
+            .with_context(|| format!("no miniblocks persisted for L1 batch #{l1_batch_number}"))?;
+        // The batch was already executed by the state keeper, so we don't want to reject any transactions
+        // because of the validation gas limit.
+        let (system_env, l1_batch_env) = params_provider
+            .load_l1_batch_params(
+                &mut storage,
+                &first_miniblock_in_batch,
+                u32::MAX,
+                self.l2_chain_id,
+            )
+            .await
+            .with_context(|| format!("failed loading params for L1 batch #{l1_batch_number}"))?;
+
+        let miniblocks = storage
+            .transactions_dal()
+            .get_miniblocks_to_execute_for_l1_batch(l1_batch_number)
+            .await?;
+        drop(storage);
+
+        let storage_factory = HistoricalStorageFactory {
+            pool: self.pool.clone(),
+            miniblock_number: first_miniblock_in_batch.number() - 1,
+        };
+        let mut batch_executor = MainBatchExecutor::new(
+            Arc::new(storage_factory),
+            self.save_call_traces,
+            self.optional_bytecode_compression,
+        );
+        let batch_executor = batch_executor
+            .init_batch(l1_batch_env, system_env, stop_receiver)
+            .await
+            .context("re-execution was interrupted")?;
+
+        tracing::info!("Started re-executing L1 batch #{l1_batch_number}");
+        let mut executed_miniblocks = Vec::with_capacity(miniblocks.len());
+        for (i, miniblock) in miniblocks.iter().enumerate() {
+            if i > 0 {
+                batch_executor
+                    .start_next_miniblock(L2BlockEnv::from_miniblock_data(miniblock))
+                    .await;
+            }
+            if miniblock.txs.is_empty() {
+                // The fictive miniblock is filled in when the batch is finished.
+                continue;
+            }
+
+            let mut tx_results = Vec::with_capacity(miniblock.txs.len());
+            for tx in &miniblock.txs {
+                let tx_result = batch_executor.execute_tx(tx.clone()).await;
+                tx_results.push((tx.clone(), tx_result));
+            }
+            executed_miniblocks.push(tx_results);
+            tracing::debug!(
+                "Re-executed miniblock #{} with {} transactions",
+                miniblock.number,
+                miniblock.txs.len()
+            );
+        }
+
+        let finished_batch = batch_executor.finish_batch().await;
+        Ok(ReexecutedL1Batch {
+            miniblocks: executed_miniblocks,
+            finished_batch,
+        })
+    }
+}
+
+/// Background component processing sealed L1 batches one by one based on their re-execution. Driven by [`ReplayLoop`].
+#[async_trait]
+pub(crate) trait ReplayedBatchProcessor: fmt::Debug + Send + Sync {
+    /// Name of the component used in logs, metrics, health checks and Postgres connection tags.
+    const NAME: &'static str;
+    /// Interval between checks for new L1 batches if there is nothing to process.
+    const POLL_INTERVAL: Duration;
+
+    /// Returns the next L1 batch to process, or `None` if there is nothing to process at the moment.
+    async fn next_l1_batch(
+        &mut self,
+        storage: &mut Connection<'_, Core>,
+        last_processed_l1_batch: Option<L1BatchNumber>,
+    ) -> anyhow::Result<Option<L1BatchNumber>>;
+
+    /// Processes outputs of the re-executed L1 batch. Returns the component health after processing.
+    async fn process_l1_batch(
+        &mut self,
+        storage: &mut Connection<'_, Core>,
+        l1_batch_number: L1BatchNumber,
+        executed_batch: ReexecutedL1Batch,
+    ) -> anyhow::Result<Health>;
+}
+
+/// Loop re-executing sealed L1 batches selected by a [`ReplayedBatchProcessor`] and feeding execution outputs to it.
+#[derive(Debug)]
+pub(crate) struct ReplayLoop<P> {
+    pool: ConnectionPool<Core>,
+    replayer: BatchReplayer,
+    processor: P,
+    health_updater: HealthUpdater,
+}
+
+impl<P: ReplayedBatchProcessor> ReplayLoop<P> {
+    pub fn new(pool: ConnectionPool<Core>, replayer: BatchReplayer, processor: P) -> Self {
+        Self {
+            pool,
+            replayer,
+            processor,
+            health_updater: ReactiveHealthCheck::new(P::NAME).1,
+        }
+    }
+
+    pub fn health_check(&self) -> ReactiveHealthCheck {
+        self.health_updater.subscribe()
+    }
+
+    /// Processes the next L1 batch selected by the processor. Returns the number of the processed batch,
+    /// or `None` if there is nothing to process or re-execution was interrupted by the stop signal.
+    async fn process_next_l1_batch(
+        &mut self,
+        last_processed_l1_batch: Option<L1BatchNumber>,
+        stop_receiver: &watch::Receiver<bool>,
+    ) -> anyhow::Result<Option<L1BatchNumber>> {
+        let mut storage = self.pool.connection_tagged(P::NAME).await?;
+        let Some(l1_batch_number) = self
+            .processor
+            .next_l1_batch(&mut storage, last_processed_l1_batch)
+            .await?
+        else {
+            return Ok(None);
+        };
+        drop(storage);
+        tracing::info!("Re-executing L1 batch #{l1_batch_number} for {}", P::NAME);
+
+        let latency = REPLAY_LOOP_METRICS.reexecution_latency[&P::NAME].start();
+        let executed_batch = match self
+            .replayer
+            .reexecute(l1_batch_number, stop_receiver)
+            .await
+        {
+            Ok(batch) => batch,
+            // Re-execution was interrupted by the stop signal, which is handled by the caller.
+            Err(_) if *stop_receiver.borrow() => return Ok(None),
+            Err(err) => {
+                return Err(err.context(format!("failed re-executing L1 batch #{l1_batch_number}")))
+            }
+        };
+        let latency = latency.observe();
+        tracing::debug!("Re-executed L1 batch #{l1_batch_number} in {latency:?}");
+
+        let latency = REPLAY_LOOP_METRICS.processing_latency[&P::NAME].start();
+        let mut storage = self.pool.connection_tagged(P::NAME).await?;
+        let health = self
+            .processor
+            .process_l1_batch(&mut storage, l1_batch_number, executed_batch)
+            .await
+            .with_context(|| format!("failed processing L1 batch #{l1_batch_number}"))?;
+        latency.observe();
+        REPLAY_LOOP_METRICS.last_processed_l1_batch[&P::NAME].set(l1_batch_number.0.into());
+        self.health_updater.update(health);
+        Ok(Some(l1_batch_number))
+    }
+
+    pub async fn run(mut self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        self.health_updater.update(HealthStatus::Ready.into());
+        let mut last_processed_l1_batch = None;
+        while !*stop_receiver.borrow_and_update() {
+            if let Some(processed_l1_batch) = self
+                .process_next_l1_batch(last_processed_l1_batch, &stop_receiver)
+                .await?
+            {
+                last_processed_l1_batch = Some(processed_l1_batch);
+                continue;
+            }
+            // Error here corresponds to a timeout w/o `stop_receiver` changed; we're OK with this.
+            tokio::time::timeout(P::POLL_INTERVAL, stop_receiver.changed())
+                .await
+                .ok();
+        }
+        tracing::info!("Stop signal received, {} is shutting down", P::NAME);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_types::{AccountTreeId, Address};
+
+    use super::*;
+
+    fn event(value: u8) -> VmEvent {
+        VmEvent {
+            location: (L1BatchNumber(1), 0),
+            address: Address::repeat_byte(1),
+            indexed_topics: vec![H256::zero()],
+            value: vec![value],
+        }
+    }
+
+    #[test]
+    fn diffing_execution_outputs() {
+        let address = Address::repeat_byte(1);
+        let [same_key, changed_key, persisted_key, replayed_key] = [1, 2, 3, 4]
+            .map(|i| StorageKey::new(AccountTreeId::new(address), H256::repeat_byte(i)));
+        let persisted = ExecutionOutputs {
+            touched_slots: HashMap::from([
+                (same_key, H256::repeat_byte(1)),
+                (changed_key, H256::repeat_byte(2)),
+                (persisted_key, H256::repeat_byte(3)),
+            ]),
+            events: vec![event(1), event(2)],
+        };
+        let mut replayed_event = event(1);
+        replayed_event.location = (L1BatchNumber(1), 5);
+        let replayed = ExecutionOutputs {
+            touched_slots: HashMap::from([
+                (same_key, H256::repeat_byte(1)),
+                (changed_key, H256::repeat_byte(0xff)),
+                (replayed_key, H256::repeat_byte(4)),
+            ]),
+            events: vec![replayed_event, event(3), event(4)],
+        };
+
+        let mut report = ReplayReport::default();
+        replayed.diff(persisted, &mut report);
+        assert!(!report.is_consistent());
+
+        let mut storage_diffs = report.storage_diffs;
+        storage_diffs.sort_unstable_by_key(|(key, _)| *key.key());
+        assert_eq!(
+            storage_diffs,
+            [
+                (
+                    changed_key,
+                    ValueDiff {
+                        persisted: Some(H256::repeat_byte(2)),
+                        replayed: Some(H256::repeat_byte(0xff)),
+                    }
+                ),
+                (
+                    persisted_key,
+                    ValueDiff {
+                        persisted: Some(H256::repeat_byte(3)),
+                        replayed: None,
+                    }
+                ),
+                (
+                    replayed_key,
+                    ValueDiff {
+                        persisted: None,
+                        replayed: Some(H256::repeat_byte(4)),
+                    }
+                ),
+            ]
+        );
+
+        // Event locations are not compared.
+        let event_indices: Vec<_> = report.event_diffs.iter().map(|(i, _)| *i).collect();
+        assert_eq!(event_indices, [1, 2]);
+        assert_eq!(report.event_diffs[1].1.persisted, None);
+    }
+}
@@ -9,7 +9,10 @@ use std::fmt;
 use zksync_config::configs::chain::StateKeeperConfig;
 use zksync_types::ProtocolVersionId;
 
-use super::{criteria, SealCriterion, SealData, SealResolution, AGGREGATION_METRICS};
+use super::{
+    criteria, SealCriterion, SealData, SealResolution, TimeoutSealer, UnexecutableReason,
+    AGGREGATION_METRICS,
+};
 
 /// Checks if an L1 batch should be sealed after executing a transaction.
 pub trait ConditionalSealer: 'static + fmt::Debug + Send + Sync {
@@ -20,7 +23,7 @@ pub trait ConditionalSealer: 'static + fmt::Debug + Send + Sync {
         &self,
         data: &SealData,
         protocol_version: ProtocolVersionId,
-    ) -> Option<&'static str>;
+    ) -> Option<UnexecutableReason>;
 
     /// Returns the action that should be taken by the state keeper after executing a transaction.
     fn should_seal_l1_batch(
@@ -32,10 +35,32 @@ pub trait ConditionalSealer: 'static + fmt::Debug + Send + Sync {
         tx_data: &SealData,
         protocol_version: ProtocolVersionId,
     ) -> SealResolution;
+
+    /// Returns the number of base layer circuits an L1 batch may use (including the batch tip overhead)
+    /// before it must be sealed. If this returns `None`, transactions are admitted to the batch without
+    /// checking their estimated circuit usage.
+    fn circuit_capacity(&self, _protocol_version: ProtocolVersionId) -> Option<usize> {
+        None
+    }
+
+    /// Returns how close an L1 batch with the specified data is to being sealed according to each seal criterion,
+    /// as pairs of criterion names and usage ratios (see [`SealCriterion::capacity_usage()`]).
+    /// Used for monitoring purposes only; the default implementation returns no criteria.
+    fn capacity_usage(
+        &self,
+        _block_open_timestamp_ms: u128,
+        _tx_count: usize,
+        _block_data: &SealData,
+        _protocol_version: ProtocolVersionId,
+    ) -> Vec<(&'static str, f64)> {
+        vec![]
+    }
 }
 
 /// Implementation of [`ConditionalSealer`] used by the main node.
-/// Internally uses a set of [`SealCriterion`]s to determine whether the batch should be sealed.
+/// Internally uses a set of [`SealCriterion`]s to determine whether the batch should be sealed. By default,
+/// the set consists of built-in criteria; it can be customized using [`Self::with_criterion()`]
+/// and [`Self::without_criterion()`].
 ///
 /// The checks are deterministic, i.e., should depend solely on execution metrics and [`StateKeeperConfig`].
 /// Non-deterministic seal criteria are expressed using [`IoSealCriteria`](super::IoSealCriteria).
@@ -50,7 +75,7 @@ impl ConditionalSealer for SequencerSealer {
         &self,
         data: &SealData,
         protocol_version: ProtocolVersionId,
-    ) -> Option<&'static str> {
+    ) -> Option<UnexecutableReason> {
         for sealer in &self.sealers {
             const MOCK_BLOCK_TIMESTAMP: u128 = 0;
             const TX_COUNT: usize = 1;
@@ -63,8 +88,11 @@ impl ConditionalSealer for SequencerSealer {
                 data,
                 protocol_version,
             );
-            if matches!(resolution, SealResolution::Unexecutable(_)) {
-                return Some(sealer.prom_criterion_name());
+            if let SealResolution::Unexecutable(message) = resolution {
+                return Some(UnexecutableReason {
+                    criterion: sealer.prom_criterion_name(),
+                    message,
+                });
             }
         }
         None
@@ -112,6 +140,38 @@ impl ConditionalSealer for SequencerSealer {
         }
         final_seal_resolution
     }
+
+    fn circuit_capacity(&self, protocol_version: ProtocolVersionId) -> Option<usize> {
+        self.sealers
+            .iter()
+            .filter_map(|sealer| sealer.circuit_capacity(&self.config, protocol_version))
+            .min()
+    }
+
+    fn capacity_usage(
+        &self,
+        block_open_timestamp_ms: u128,
+        tx_count: usize,
+        block_data: &SealData,
+        protocol_version: ProtocolVersionId,
+    ) -> Vec<(&'static str, f64)> {
+        let mut usage: Vec<_> = self
+            .sealers
+            .iter()
+            .filter_map(|sealer| {
+                let usage =
+                    sealer.capacity_usage(&self.config, tx_count, block_data, protocol_version)?;
+                Some((sealer.prom_criterion_name(), usage))
+            })
+            .collect();
+        // The batch timeout is checked by the I/O rather than by this sealer, but it only depends
+        // on the state keeper config, so we can report it here as well.
+        let batch_timestamp = (block_open_timestamp_ms / 1_000) as u64;
+        let timeout_usage =
+            TimeoutSealer::new(&self.config).l1_batch_timeout_usage(batch_timestamp);
+        usage.push((TimeoutSealer::RULE_NAME, timeout_usage));
+        usage
+    }
 }
 
 impl SequencerSealer {
@@ -120,6 +180,21 @@ impl SequencerSealer {
         Self { config, sealers }
     }
 
+    /// Registers an additional seal criterion. Criteria are evaluated in the order of registration,
+    /// after the built-in ones.
+    pub fn with_criterion(mut self, criterion: Box<dyn SealCriterion>) -> Self {
+        self.sealers.push(criterion);
+        self
+    }
+
+    /// Removes a criterion with the specified [name](SealCriterion::prom_criterion_name()), e.g. in order
+    /// to replace a built-in criterion with a custom one. Does nothing if there is no such criterion.
+    pub fn without_criterion(mut self, name: &str) -> Self {
+        self.sealers
+            .retain(|sealer| sealer.prom_criterion_name() != name);
+        self
+    }
+
     #[cfg(test)]
     pub(in crate::state_keeper) fn with_sealers(
         config: StateKeeperConfig,
@@ -138,6 +213,7 @@ impl SequencerSealer {
             Box::new(criteria::CircuitsCriterion),
             Box::new(criteria::TxEncodingSizeCriterion),
             Box::new(criteria::GasForBatchTipCriterion),
+            Box::new(criteria::TxResourceLimitsCriterion),
         ]
     }
 }
@@ -153,7 +229,7 @@ impl ConditionalSealer for NoopSealer {
         &self,
         _data: &SealData,
         _protocol_version: ProtocolVersionId,
-    ) -> Option<&'static str> {
+    ) -> Option<UnexecutableReason> {
         None
     }
 
@@ -169,3 +245,100 @@ impl ConditionalSealer for NoopSealer {
         SealResolution::NoSeal
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Custom criterion sealing the batch after a certain number of transactions.
+    #[derive(Debug)]
+    struct TxCountCriterion(usize);
+
+    impl SealCriterion for TxCountCriterion {
+        fn should_seal(
+            &self,
+            _config: &StateKeeperConfig,
+            _block_open_timestamp_ms: u128,
+            tx_count: usize,
+            _block_data: &SealData,
+            _tx_data: &SealData,
+            _protocol_version: ProtocolVersionId,
+        ) -> SealResolution {
+            if tx_count >= self.0 {
+                SealResolution::IncludeAndSeal
+            } else {
+                SealResolution::NoSeal
+            }
+        }
+
+        fn prom_criterion_name(&self) -> &'static str {
+            "tx_count"
+        }
+    }
+
+    fn sealer_names(sealer: &SequencerSealer) -> Vec<&'static str> {
+        sealer
+            .sealers
+            .iter()
+            .map(|sealer| sealer.prom_criterion_name())
+            .collect()
+    }
+
+    #[test]
+    fn customizing_seal_criteria() {
+        let config = StateKeeperConfig::for_tests();
+        let sealer = SequencerSealer::new(config.clone())
+            .without_criterion("pub_data_size")
+            .with_criterion(Box::new(TxCountCriterion(3)));
+        let names = sealer_names(&sealer);
+        assert!(!names.contains(&"pub_data_size"), "{names:?}");
+        assert_eq!(names.last(), Some(&"tx_count"));
+
+        let sealer = SequencerSealer::with_sealers(config, vec![])
+            .with_criterion(Box::new(TxCountCriterion(3)));
+        let data = SealData::default();
+        let protocol_version = ProtocolVersionId::latest();
+        for tx_count in 1..3 {
+            let resolution =
+                sealer.should_seal_l1_batch(1, 0, tx_count, &data, &data, protocol_version);
+            assert_eq!(resolution, SealResolution::NoSeal);
+        }
+        let resolution = sealer.should_seal_l1_batch(1, 0, 3, &data, &data, protocol_version);
+        assert_eq!(resolution, SealResolution::IncludeAndSeal);
+        assert_eq!(
+            sealer.find_unexecutable_reason(&data, protocol_version),
+            None
+        );
+    }
+
+    #[test]
+    fn finding_unexecutable_reason() {
+        let config = StateKeeperConfig {
+            max_factory_deps_size_per_tx: Some(100),
+            ..StateKeeperConfig::for_tests()
+        };
+        let sealer = SequencerSealer::with_sealers(
+            config,
+            vec![Box::new(criteria::TxResourceLimitsCriterion)],
+        );
+        let protocol_version = ProtocolVersionId::latest();
+        let data = SealData {
+            factory_deps_size: 100,
+            ..SealData::default()
+        };
+        assert_eq!(
+            sealer.find_unexecutable_reason(&data, protocol_version),
+            None
+        );
+
+        let data = SealData {
+            factory_deps_size: 101,
+            ..SealData::default()
+        };
+        let reason = sealer
+            .find_unexecutable_reason(&data, protocol_version)
+            .expect("transaction must be unexecutable");
+        assert_eq!(reason.criterion, "tx_resource_limits");
+        assert!(reason.message.contains("101 bytes"), "{reason}");
+    }
+}
@@ -63,6 +63,31 @@ impl SealCriterion for CircuitsCriterion {
         }
     }
 
+    fn circuit_capacity(
+        &self,
+        config: &StateKeeperConfig,
+        _protocol_version: ProtocolVersionId,
+    ) -> Option<usize> {
+        // Transactions overflowing this bound are excluded from the batch after execution (see above).
+        Some(config.max_circuits_per_batch)
+    }
+
+    fn capacity_usage(
+        &self,
+        config: &StateKeeperConfig,
+        _tx_count: usize,
+        block_data: &SealData,
+        protocol_version: ProtocolVersionId,
+    ) -> Option<f64> {
+        let batch_tip_circuit_overhead =
+            circuit_statistics_bootloader_batch_tip_overhead(protocol_version.into());
+        let include_and_seal_bound = (config.max_circuits_per_batch as f64
+            * config.close_block_at_geometry_percentage)
+            .round();
+        let used_circuits_batch = block_data.execution_metrics.circuit_statistic.total();
+        Some((used_circuits_batch + batch_tip_circuit_overhead) as f64 / include_and_seal_bound)
+    }
+
     fn prom_criterion_name(&self) -> &'static str {
         "circuits_criterion"
     }
@@ -4,9 +4,11 @@ mod geometry_seal_criteria;
 mod pubdata_bytes;
 mod slots;
 mod tx_encoding_size;
+mod tx_resource_limits;
 
 pub(in crate::state_keeper) use self::{
     gas::GasCriterion, gas_for_batch_tip::GasForBatchTipCriterion,
     geometry_seal_criteria::CircuitsCriterion, pubdata_bytes::PubDataBytesCriterion,
     slots::SlotsCriterion, tx_encoding_size::TxEncodingSizeCriterion,
+    tx_resource_limits::TxResourceLimitsCriterion,
 };
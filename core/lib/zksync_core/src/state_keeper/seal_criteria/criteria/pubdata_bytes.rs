@@ -5,6 +5,32 @@ use crate::state_keeper::seal_criteria::{
     SealCriterion, SealData, SealResolution, StateKeeperConfig,
 };
 
+/// Returns the size of pubdata produced by a single transaction.
+pub(super) fn tx_pubdata_size(tx_data: &SealData, protocol_version: ProtocolVersionId) -> usize {
+    // For backward compatibility, we need to keep calculating the size of the pubdata based
+    // `StorageDeduplication` metrics. All vm versions
+    // after vm with virtual blocks will provide the size of the pubdata in the execution metrics.
+    if tx_data.execution_metrics.pubdata_published == 0 {
+        tx_data.execution_metrics.size() + tx_data.writes_metrics.size(protocol_version)
+    } else {
+        tx_data.execution_metrics.pubdata_published as usize
+    }
+}
+
+/// Returns the predicted size of the batch pubdata, including the batch tip overhead.
+fn block_pubdata_size(block_data: &SealData, protocol_version: ProtocolVersionId) -> usize {
+    // Post-boojum, we predict the size of the batch pubdata based on its exact encoding and the running set
+    // of compressed state diffs, rather than on the worst-case size estimates.
+    let block_size_without_state_diffs = if protocol_version.is_pre_boojum() {
+        block_data.execution_metrics.size()
+    } else {
+        block_data.execution_metrics.pubdata_without_state_diffs
+    };
+    block_size_without_state_diffs
+        + block_data.writes_metrics.size(protocol_version)
+        + execution_metrics_bootloader_batch_tip_overhead(protocol_version.into())
+}
+
 #[derive(Debug)]
 pub struct PubDataBytesCriterion {
     /// This value changes based on the DA solution.
@@ -30,36 +56,36 @@ impl SealCriterion for PubDataBytesCriterion {
         let include_and_seal_bound =
             (max_pubdata_per_l1_batch as f64 * config.close_block_at_eth_params_percentage).round();
 
-        let block_size =
-            block_data.execution_metrics.size() + block_data.writes_metrics.size(protocol_version);
-        // For backward compatibility, we need to keep calculating the size of the pubdata based
-        // `StorageDeduplication` metrics. All vm versions
-        // after vm with virtual blocks will provide the size of the pubdata in the execution metrics.
-        let tx_size = if tx_data.execution_metrics.pubdata_published == 0 {
-            tx_data.execution_metrics.size() + tx_data.writes_metrics.size(protocol_version)
-        } else {
-            tx_data.execution_metrics.pubdata_published as usize
-        };
+        let block_size = block_pubdata_size(block_data, protocol_version);
+        let tx_size = tx_pubdata_size(tx_data, protocol_version);
         if tx_size + execution_metrics_bootloader_batch_tip_overhead(protocol_version.into())
             > reject_bound as usize
         {
             let message = "Transaction cannot be sent to L1 due to pubdata limits";
             SealResolution::Unexecutable(message.into())
-        } else if block_size
-            + execution_metrics_bootloader_batch_tip_overhead(protocol_version.into())
-            > max_pubdata_per_l1_batch
-        {
+        } else if block_size > max_pubdata_per_l1_batch {
             SealResolution::ExcludeAndSeal
-        } else if block_size
-            + execution_metrics_bootloader_batch_tip_overhead(protocol_version.into())
-            > include_and_seal_bound as usize
-        {
+        } else if block_size > include_and_seal_bound as usize {
             SealResolution::IncludeAndSeal
         } else {
             SealResolution::NoSeal
         }
     }
 
+    fn capacity_usage(
+        &self,
+        config: &StateKeeperConfig,
+        _tx_count: usize,
+        block_data: &SealData,
+        protocol_version: ProtocolVersionId,
+    ) -> Option<f64> {
+        let include_and_seal_bound = (self.max_pubdata_per_batch as f64
+            * config.close_block_at_eth_params_percentage)
+            .round();
+        let block_size = block_pubdata_size(block_data, protocol_version);
+        Some(block_size as f64 / include_and_seal_bound)
+    }
+
     fn prom_criterion_name(&self) -> &'static str {
         "pub_data_size"
     }
@@ -71,6 +97,14 @@ mod tests {
 
     use super::*;
 
+    fn block_execution_metrics(pubdata_size: usize) -> ExecutionMetrics {
+        ExecutionMetrics {
+            l2_l1_long_messages: pubdata_size,
+            pubdata_without_state_diffs: pubdata_size,
+            ..ExecutionMetrics::default()
+        }
+    }
+
     #[test]
     fn seal_criterion() {
         // Create an empty config and only setup fields relevant for the test.
@@ -85,16 +119,14 @@ mod tests {
             max_pubdata_per_batch: 100000,
         };
 
-        let block_execution_metrics = ExecutionMetrics {
-            l2_l1_long_messages: (config.max_pubdata_per_batch as f64
-                * config.close_block_at_eth_params_percentage
+        let block_execution_metrics = block_execution_metrics(
+            (config.max_pubdata_per_batch as f64 * config.close_block_at_eth_params_percentage
                 - 1.0
                 - execution_metrics_bootloader_batch_tip_overhead(
                     ProtocolVersionId::latest().into(),
                 ) as f64)
                 .round() as usize,
-            ..ExecutionMetrics::default()
-        };
+        );
 
         let empty_block_resolution = criterion.should_seal(
             &config,
@@ -109,13 +141,11 @@ mod tests {
         );
         assert_eq!(empty_block_resolution, SealResolution::NoSeal);
 
-        let block_execution_metrics = ExecutionMetrics {
-            l2_l1_long_messages: (config.max_pubdata_per_batch as f64
-                * config.close_block_at_eth_params_percentage
+        let block_execution_metrics = block_execution_metrics(
+            (config.max_pubdata_per_batch as f64 * config.close_block_at_eth_params_percentage
                 + 1f64)
                 .round() as usize,
-            ..ExecutionMetrics::default()
-        };
+        );
 
         let full_block_resolution = criterion.should_seal(
             &config,
@@ -130,10 +160,8 @@ mod tests {
         );
         assert_eq!(full_block_resolution, SealResolution::IncludeAndSeal);
 
-        let block_execution_metrics = ExecutionMetrics {
-            l2_l1_long_messages: config.max_pubdata_per_batch as usize + 1,
-            ..ExecutionMetrics::default()
-        };
+        let block_execution_metrics =
+            block_execution_metrics(config.max_pubdata_per_batch as usize + 1);
         let full_block_resolution = criterion.should_seal(
             &config,
             0,
@@ -147,4 +175,47 @@ mod tests {
         );
         assert_eq!(full_block_resolution, SealResolution::ExcludeAndSeal);
     }
+
+    #[test]
+    fn seal_criterion_uses_predicted_pubdata_size() {
+        let config = StateKeeperConfig {
+            reject_tx_at_eth_params_percentage: 0.95,
+            close_block_at_eth_params_percentage: 0.95,
+            max_pubdata_per_batch: 100000,
+            ..Default::default()
+        };
+        let criterion = PubDataBytesCriterion {
+            max_pubdata_per_batch: 100000,
+        };
+
+        // Worst-case estimate exceeds the limit, but the predicted pubdata size is well within it.
+        let block_data = SealData {
+            execution_metrics: ExecutionMetrics {
+                l2_l1_long_messages: config.max_pubdata_per_batch as usize + 1,
+                pubdata_without_state_diffs: config.max_pubdata_per_batch as usize / 2,
+                ..ExecutionMetrics::default()
+            },
+            ..SealData::default()
+        };
+        let resolution = criterion.should_seal(
+            &config,
+            0,
+            0,
+            &block_data,
+            &SealData::default(),
+            ProtocolVersionId::latest(),
+        );
+        assert_eq!(resolution, SealResolution::NoSeal);
+
+        // Pre-boojum versions use the worst-case estimate.
+        let resolution = criterion.should_seal(
+            &config,
+            0,
+            0,
+            &block_data,
+            &SealData::default(),
+            ProtocolVersionId::Version17,
+        );
+        assert_eq!(resolution, SealResolution::ExcludeAndSeal);
+    }
 }
@@ -33,6 +33,16 @@ impl SealCriterion for SlotsCriterion {
         }
     }
 
+    fn capacity_usage(
+        &self,
+        config: &StateKeeperConfig,
+        tx_count: usize,
+        _block_data: &SealData,
+        _protocol_version: ProtocolVersionId,
+    ) -> Option<f64> {
+        Some(tx_count as f64 / config.transaction_slots as f64)
+    }
+
     fn prom_criterion_name(&self) -> &'static str {
         "slots"
     }
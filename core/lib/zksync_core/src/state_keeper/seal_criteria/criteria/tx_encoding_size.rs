@@ -39,6 +39,21 @@ impl SealCriterion for TxEncodingSizeCriterion {
         }
     }
 
+    fn capacity_usage(
+        &self,
+        config: &StateKeeperConfig,
+        _tx_count: usize,
+        block_data: &SealData,
+        protocol_version_id: ProtocolVersionId,
+    ) -> Option<f64> {
+        let bootloader_tx_encoding_space =
+            get_bootloader_encoding_space(protocol_version_id.into());
+        let include_and_seal_bound = (bootloader_tx_encoding_space as f64
+            * config.close_block_at_geometry_percentage)
+            .round();
+        Some(block_data.cumulative_size as f64 / include_and_seal_bound)
+    }
+
     fn prom_criterion_name(&self) -> &'static str {
         "tx_encoding_size"
     }
@@ -0,0 +1,151 @@
+use zksync_types::ProtocolVersionId;
+
+use super::pubdata_bytes::tx_pubdata_size;
+use crate::state_keeper::seal_criteria::{
+    SealCriterion, SealData, SealResolution, StateKeeperConfig,
+};
+
+/// Rejects transactions exceeding operator-defined per-transaction resource ceilings
+/// (`max_gas_per_tx`, `max_pubdata_per_tx` and `max_factory_deps_size_per_tx` in [`StateKeeperConfig`]).
+/// These ceilings are independent of protocol limits and allow to protect a chain from a single transaction
+/// consuming most of the batch resources.
+///
+/// L1 (priority) transactions are exempt: they cannot be rejected by the sequencer, and are still bounded
+/// by protocol-level criteria.
+#[derive(Debug)]
+pub struct TxResourceLimitsCriterion;
+
+impl SealCriterion for TxResourceLimitsCriterion {
+    fn should_seal(
+        &self,
+        config: &StateKeeperConfig,
+        _block_open_timestamp_ms: u128,
+        _tx_count: usize,
+        _block_data: &SealData,
+        tx_data: &SealData,
+        protocol_version: ProtocolVersionId,
+    ) -> SealResolution {
+        if tx_data.is_l1_tx {
+            return SealResolution::NoSeal;
+        }
+
+        let gas_used = tx_data.execution_metrics.gas_used as u64;
+        if let Some(limit) = config.max_gas_per_tx.filter(|&limit| gas_used > limit) {
+            let message = format!(
+                "Transaction uses {gas_used} gas, while the operator limit is {limit} gas per transaction"
+            );
+            return SealResolution::Unexecutable(message);
+        }
+
+        let pubdata_size = tx_pubdata_size(tx_data, protocol_version) as u64;
+        if let Some(limit) = config
+            .max_pubdata_per_tx
+            .filter(|&limit| pubdata_size > limit)
+        {
+            let message = format!(
+                "Transaction produces {pubdata_size} bytes of pubdata, while the operator limit is {limit} bytes \
+                 per transaction"
+            );
+            return SealResolution::Unexecutable(message);
+        }
+
+        let factory_deps_size = tx_data.factory_deps_size as u64;
+        if let Some(limit) = config
+            .max_factory_deps_size_per_tx
+            .filter(|&limit| factory_deps_size > limit)
+        {
+            let message = format!(
+                "Transaction supplies {factory_deps_size} bytes of factory dependencies, while the operator limit \
+                 is {limit} bytes per transaction"
+            );
+            return SealResolution::Unexecutable(message);
+        }
+
+        SealResolution::NoSeal
+    }
+
+    fn prom_criterion_name(&self) -> &'static str {
+        "tx_resource_limits"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use zksync_types::tx::ExecutionMetrics;
+
+    use super::*;
+
+    fn tx_data(gas_used: usize, pubdata_published: u32, factory_deps_size: usize) -> SealData {
+        SealData {
+            execution_metrics: ExecutionMetrics {
+                gas_used,
+                pubdata_published,
+                ..ExecutionMetrics::default()
+            },
+            factory_deps_size,
+            ..SealData::default()
+        }
+    }
+
+    fn limited_config() -> StateKeeperConfig {
+        StateKeeperConfig {
+            max_gas_per_tx: Some(1_000),
+            max_pubdata_per_tx: Some(100),
+            max_factory_deps_size_per_tx: Some(10),
+            ..StateKeeperConfig::default()
+        }
+    }
+
+    fn check(config: &StateKeeperConfig, tx_data: &SealData) -> SealResolution {
+        TxResourceLimitsCriterion.should_seal(
+            config,
+            0,
+            1,
+            &SealData::default(),
+            tx_data,
+            ProtocolVersionId::latest(),
+        )
+    }
+
+    #[test]
+    fn no_limits_by_default() {
+        let config = StateKeeperConfig::default();
+        let resolution = check(&config, &tx_data(1_000_000_000, 1_000_000, 1_000_000));
+        assert_eq!(resolution, SealResolution::NoSeal);
+    }
+
+    #[test]
+    fn enforcing_limits() {
+        let config = limited_config();
+        let resolution = check(&config, &tx_data(1_000, 100, 10));
+        assert_eq!(resolution, SealResolution::NoSeal);
+
+        let resolution = check(&config, &tx_data(1_001, 100, 10));
+        assert_matches!(
+            resolution,
+            SealResolution::Unexecutable(message) if message.contains("1001 gas")
+        );
+        let resolution = check(&config, &tx_data(1_000, 101, 10));
+        assert_matches!(
+            resolution,
+            SealResolution::Unexecutable(message) if message.contains("101 bytes of pubdata")
+        );
+        let resolution = check(&config, &tx_data(1_000, 100, 11));
+        assert_matches!(
+            resolution,
+            SealResolution::Unexecutable(message) if message.contains("11 bytes of factory dependencies")
+        );
+    }
+
+    #[test]
+    fn l1_transactions_are_exempt() {
+        let config = limited_config();
+        let tx_data = SealData {
+            is_l1_tx: true,
+            ..tx_data(1_001, 101, 11)
+        };
+        let resolution = check(&config, &tx_data);
+        assert_eq!(resolution, SealResolution::NoSeal);
+    }
+}
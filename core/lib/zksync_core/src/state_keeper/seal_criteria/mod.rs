@@ -9,6 +9,9 @@
 //!
 //! Maintaining all the criteria in one place has proven itself to be very error-prone,
 //! thus now every criterion is independent of the others.
+//!
+//! Besides built-in criteria, custom ones can be implemented using the [`SealCriterion`] trait and registered
+//! in the [`SequencerSealer`] with [`SequencerSealer::with_criterion()`].
 
 use std::fmt;
 
@@ -20,12 +23,16 @@ use zksync_types::{
     tx::tx_execution_info::{DeduplicatedWritesMetrics, ExecutionMetrics},
     ProtocolVersionId, Transaction,
 };
-use zksync_utils::time::millis_since;
+use zksync_utils::time::{millis_since, millis_since_epoch};
 
 mod conditional_sealer;
 pub(super) mod criteria;
+mod monitor;
 
-pub use self::conditional_sealer::{ConditionalSealer, NoopSealer, SequencerSealer};
+pub use self::{
+    conditional_sealer::{ConditionalSealer, NoopSealer, SequencerSealer},
+    monitor::BatchSealMonitor,
+};
 use super::{extractors, metrics::AGGREGATION_METRICS, updates::UpdatesManager};
 use crate::gas_tracker::{gas_count_from_tx_and_metrics, gas_count_from_writes};
 
@@ -73,15 +80,45 @@ impl SealResolution {
     }
 }
 
+/// Reason why a transaction cannot be executed by the sequencer, as determined by a [`SealCriterion`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnexecutableReason {
+    /// [Name](SealCriterion::prom_criterion_name()) of the criterion that has rejected the transaction.
+    pub criterion: &'static str,
+    /// Human-readable explanation provided by the criterion.
+    pub message: String,
+}
+
+impl fmt::Display for UnexecutableReason {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "{} (criterion: {})",
+            self.message, self.criterion
+        )
+    }
+}
+
+/// Returns the total size of factory dependencies supplied with the transaction.
+pub(super) fn factory_deps_size(transaction: &Transaction) -> usize {
+    transaction
+        .execute
+        .factory_deps
+        .as_deref()
+        .map_or(0, |deps| deps.iter().map(Vec::len).sum())
+}
+
 /// Information about transaction or block applicable either to a single transaction, or
 /// to the entire miniblock / L1 batch.
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct SealData {
     pub(super) execution_metrics: ExecutionMetrics,
     pub(super) gas_count: BlockGasCount,
     pub(super) cumulative_size: usize,
     pub(super) writes_metrics: DeduplicatedWritesMetrics,
     pub(super) gas_remaining: u32,
+    pub(super) factory_deps_size: usize,
+    pub(super) is_l1_tx: bool,
 }
 
 impl SealData {
@@ -102,11 +139,58 @@ impl SealData {
             cumulative_size: transaction.bootloader_encoding_size(),
             writes_metrics,
             gas_remaining: tx_metrics.gas_remaining,
+            factory_deps_size: factory_deps_size(transaction),
+            is_l1_tx: transaction.is_l1(),
         }
     }
+
+    /// Returns execution metrics (e.g., emitted events and L2-to-L1 logs, used circuits, published bytecodes).
+    pub fn execution_metrics(&self) -> &ExecutionMetrics {
+        &self.execution_metrics
+    }
+
+    /// Returns the gas spent on committing, proving and executing the data.
+    pub fn gas_count(&self) -> BlockGasCount {
+        self.gas_count
+    }
+
+    /// Returns the cumulative size of transactions encoded for the bootloader.
+    pub fn cumulative_size(&self) -> usize {
+        self.cumulative_size
+    }
+
+    /// Returns metrics for deduplicated storage writes.
+    pub fn writes_metrics(&self) -> &DeduplicatedWritesMetrics {
+        &self.writes_metrics
+    }
+
+    /// Returns the gas remaining after executing the transaction.
+    pub fn gas_remaining(&self) -> u32 {
+        self.gas_remaining
+    }
+
+    /// Returns the total size of factory dependencies supplied with the transaction. For L1 batch data,
+    /// this is the size for the last executed transaction.
+    pub fn factory_deps_size(&self) -> usize {
+        self.factory_deps_size
+    }
+
+    /// Returns whether the transaction is an L1 (priority) transaction. For L1 batch data,
+    /// this applies to the last executed transaction.
+    pub fn is_l1_tx(&self) -> bool {
+        self.is_l1_tx
+    }
 }
 
-pub(super) trait SealCriterion: fmt::Debug + Send + Sync + 'static {
+/// Deterministic criterion deciding whether an L1 batch should be sealed after executing a transaction.
+/// Criteria are combined by [`SequencerSealer`]; the strictest [`SealResolution`] among all criteria is applied.
+///
+/// Custom criteria (e.g., sealing on activity of specific contracts, or custom pubdata budgets) can be implemented
+/// outside this crate and registered via [`SequencerSealer::with_criterion()`]. Criteria must depend solely
+/// on their inputs; otherwise, the external node may fail to reproduce the sealed batches.
+pub trait SealCriterion: fmt::Debug + Send + Sync + 'static {
+    /// Decides whether the batch should be sealed. `block_data` contains data for the entire L1 batch
+    /// (including the last transaction), and `tx_data` for the last executed transaction only.
     fn should_seal(
         &self,
         config: &StateKeeperConfig,
@@ -117,6 +201,31 @@ pub(super) trait SealCriterion: fmt::Debug + Send + Sync + 'static {
         protocol_version: ProtocolVersionId,
     ) -> SealResolution;
 
+    /// Returns the number of base layer circuits an L1 batch may use (including the batch tip overhead)
+    /// if this criterion restricts circuit usage. Used to admit transactions to the batch before executing them.
+    fn circuit_capacity(
+        &self,
+        _config: &StateKeeperConfig,
+        _protocol_version: ProtocolVersionId,
+    ) -> Option<usize> {
+        None
+    }
+
+    /// Returns how close an L1 batch with the specified data is to being sealed by this criterion, as a ratio
+    /// of the consumed resource to the threshold at which the criterion seals the batch; the batch is sealed
+    /// once the ratio reaches (or, for some criteria, exceeds) 1. Returns `None` if the criterion doesn't restrict
+    /// the batch as a whole (e.g., only restricts individual transactions). Used for monitoring purposes only.
+    fn capacity_usage(
+        &self,
+        _config: &StateKeeperConfig,
+        _tx_count: usize,
+        _block_data: &SealData,
+        _protocol_version: ProtocolVersionId,
+    ) -> Option<f64> {
+        None
+    }
+
+    /// Returns the criterion name used in metrics and logs. Should be unique among registered criteria.
     // We need self here only for rust restrictions for creating an object from trait
     // https://doc.rust-lang.org/reference/items/traits.html#object-safety
     fn prom_criterion_name(&self) -> &'static str;
@@ -131,25 +240,77 @@ pub trait IoSealCriteria {
     fn should_seal_miniblock(&mut self, manager: &UpdatesManager) -> bool;
 }
 
+/// Load-dependent deadline for sealing miniblocks.
+///
+/// A miniblock is sealed as soon as it contains `max_tx_count` transactions, so that miniblocks don't grow too large
+/// under high load. Otherwise, the deadline is interpolated between the lower and upper bounds based on the number
+/// of transactions in the miniblock: a lone transaction is sealed after the lower bound to minimize latency at low load,
+/// and the deadline stretches towards the upper bound as transactions accumulate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct DynamicMiniblockDeadline {
+    min_deadline_ms: u64,
+    max_deadline_ms: u64,
+    max_tx_count: usize,
+}
+
+impl DynamicMiniblockDeadline {
+    fn new(config: &StateKeeperConfig) -> Option<Self> {
+        let max_tx_count = config.miniblock_max_tx_count?;
+        let max_deadline_ms = config.miniblock_commit_deadline_ms;
+        let min_deadline_ms = config
+            .miniblock_min_commit_deadline_ms
+            .map_or(max_deadline_ms, |min| min.min(max_deadline_ms));
+        Some(Self {
+            min_deadline_ms,
+            max_deadline_ms,
+            max_tx_count: max_tx_count.max(1),
+        })
+    }
+
+    /// Returns the deadline for a miniblock with the specified number of transactions, or `None` if the miniblock
+    /// should be sealed immediately.
+    fn deadline_ms(&self, tx_count: usize) -> Option<u64> {
+        if tx_count >= self.max_tx_count {
+            return None;
+        }
+        let extra_txs = tx_count.saturating_sub(1) as u64;
+        let extra_deadline_ms = (self.max_deadline_ms - self.min_deadline_ms) * extra_txs
+            / (self.max_tx_count as u64).saturating_sub(1).max(1);
+        Some(self.min_deadline_ms + extra_deadline_ms)
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub(super) struct TimeoutSealer {
     block_commit_deadline_ms: u64,
     miniblock_commit_deadline_ms: u64,
+    dynamic_miniblock_deadline: Option<DynamicMiniblockDeadline>,
+    max_gas_per_miniblock: Option<u64>,
 }
 
 impl TimeoutSealer {
+    const RULE_NAME: &'static str = "no_txs_timeout";
+
     pub fn new(config: &StateKeeperConfig) -> Self {
         Self {
             block_commit_deadline_ms: config.block_commit_deadline_ms,
             miniblock_commit_deadline_ms: config.miniblock_commit_deadline_ms,
+            dynamic_miniblock_deadline: DynamicMiniblockDeadline::new(config),
+            max_gas_per_miniblock: config.max_gas_per_miniblock,
         }
     }
+
+    /// Returns the ratio of the L1 batch age to the commit deadline for a batch with the specified timestamp.
+    /// Mirrors [`Self::should_seal_l1_batch_unconditionally()`], except that it doesn't special-case empty batches.
+    fn l1_batch_timeout_usage(&self, batch_timestamp: u64) -> f64 {
+        // Unlike `millis_since()`, doesn't panic if the batch timestamp is ahead of the local clock.
+        let batch_age_ms = millis_since_epoch().saturating_sub(u128::from(batch_timestamp) * 1_000);
+        batch_age_ms as f64 / self.block_commit_deadline_ms as f64
+    }
 }
 
 impl IoSealCriteria for TimeoutSealer {
     fn should_seal_l1_batch_unconditionally(&mut self, manager: &UpdatesManager) -> bool {
-        const RULE_NAME: &str = "no_txs_timeout";
-
         if manager.pending_executed_transactions_len() == 0 {
             // Regardless of which sealers are provided, we never want to seal an empty batch.
             return false;
@@ -161,10 +322,11 @@ impl IoSealCriteria for TimeoutSealer {
             millis_since(manager.batch_timestamp()) > block_commit_deadline_ms;
 
         if should_seal_timeout {
-            AGGREGATION_METRICS.inc_criterion(RULE_NAME);
+            AGGREGATION_METRICS.inc_criterion(Self::RULE_NAME);
             tracing::debug!(
-                "Decided to seal L1 batch using rule `{RULE_NAME}`; batch timestamp: {}, \
+                "Decided to seal L1 batch using rule `{}`; batch timestamp: {}, \
                  commit deadline: {block_commit_deadline_ms}ms",
+                Self::RULE_NAME,
                 extractors::display_timestamp(manager.batch_timestamp())
             );
         }
@@ -172,8 +334,36 @@ impl IoSealCriteria for TimeoutSealer {
     }
 
     fn should_seal_miniblock(&mut self, manager: &UpdatesManager) -> bool {
-        !manager.miniblock.executed_transactions.is_empty()
-            && millis_since(manager.miniblock.timestamp) > self.miniblock_commit_deadline_ms
+        let tx_count = manager.miniblock.executed_transactions.len();
+        if tx_count == 0 {
+            return false;
+        }
+
+        let gas_used = manager.miniblock.block_execution_metrics.gas_used as u64;
+        if let Some(limit) = self
+            .max_gas_per_miniblock
+            .filter(|&limit| gas_used >= limit)
+        {
+            tracing::debug!(
+                "Decided to seal miniblock #{} with {tx_count} transactions because it used {gas_used} gas \
+                 (limit: {limit})",
+                manager.miniblock.number
+            );
+            return true;
+        }
+
+        let deadline_ms = match &self.dynamic_miniblock_deadline {
+            Some(deadline) => deadline.deadline_ms(tx_count),
+            None => Some(self.miniblock_commit_deadline_ms),
+        };
+        let Some(deadline_ms) = deadline_ms else {
+            tracing::debug!(
+                "Decided to seal miniblock #{} with {tx_count} transactions because of transaction count",
+                manager.miniblock.number
+            );
+            return true;
+        };
+        millis_since(manager.miniblock.timestamp) > deadline_ms
     }
 }
 
@@ -187,13 +377,20 @@ mod tests {
     };
 
     fn apply_tx_to_manager(manager: &mut UpdatesManager) {
+        apply_tx_with_gas_to_manager(manager, 0);
+    }
+
+    fn apply_tx_with_gas_to_manager(manager: &mut UpdatesManager, gas_used: usize) {
         let tx = create_transaction(10, 100);
         manager.extend_from_executed_transaction(
             tx,
             create_execution_result(0, []),
             vec![],
             BlockGasCount::default(),
-            ExecutionMetrics::default(),
+            ExecutionMetrics {
+                gas_used,
+                ..ExecutionMetrics::default()
+            },
             vec![],
         );
     }
@@ -204,6 +401,8 @@ mod tests {
         let mut timeout_miniblock_sealer = TimeoutSealer {
             block_commit_deadline_ms: 10_000,
             miniblock_commit_deadline_ms: 10_000,
+            dynamic_miniblock_deadline: None,
+            max_gas_per_miniblock: None,
         };
 
         let mut manager = create_updates_manager();
@@ -230,4 +429,79 @@ mod tests {
             "Non-empty miniblock with too recent timestamp shouldn't be sealed"
         );
     }
+
+    #[test]
+    fn dynamic_miniblock_deadline() {
+        let config = StateKeeperConfig {
+            miniblock_commit_deadline_ms: 2_000,
+            ..StateKeeperConfig::default()
+        };
+        assert_eq!(DynamicMiniblockDeadline::new(&config), None);
+
+        let config = StateKeeperConfig {
+            miniblock_commit_deadline_ms: 2_000,
+            miniblock_min_commit_deadline_ms: Some(200),
+            miniblock_max_tx_count: Some(10),
+            ..StateKeeperConfig::default()
+        };
+        let deadline = DynamicMiniblockDeadline::new(&config).unwrap();
+        assert_eq!(deadline.deadline_ms(1), Some(200));
+        assert_eq!(deadline.deadline_ms(4), Some(800));
+        assert_eq!(deadline.deadline_ms(9), Some(1_800));
+        assert_eq!(deadline.deadline_ms(10), None);
+        assert_eq!(deadline.deadline_ms(100), None);
+
+        // Without the lower bound, only the transaction count is adaptive.
+        let config = StateKeeperConfig {
+            miniblock_min_commit_deadline_ms: None,
+            ..config
+        };
+        let deadline = DynamicMiniblockDeadline::new(&config).unwrap();
+        assert_eq!(deadline.deadline_ms(1), Some(2_000));
+        assert_eq!(deadline.deadline_ms(9), Some(2_000));
+        assert_eq!(deadline.deadline_ms(10), None);
+    }
+
+    #[test]
+    fn sealing_miniblock_by_tx_count() {
+        let config = StateKeeperConfig {
+            block_commit_deadline_ms: 10_000,
+            miniblock_commit_deadline_ms: 10_000,
+            miniblock_max_tx_count: Some(2),
+            ..StateKeeperConfig::default()
+        };
+        let mut sealer = TimeoutSealer::new(&config);
+        let mut manager = create_updates_manager();
+        manager.miniblock.timestamp = seconds_since_epoch();
+        assert!(!sealer.should_seal_miniblock(&manager));
+        apply_tx_to_manager(&mut manager);
+        assert!(!sealer.should_seal_miniblock(&manager));
+        apply_tx_to_manager(&mut manager);
+        assert!(sealer.should_seal_miniblock(&manager));
+    }
+
+    #[test]
+    fn sealing_miniblock_by_gas() {
+        let config = StateKeeperConfig {
+            block_commit_deadline_ms: 10_000,
+            miniblock_commit_deadline_ms: 10_000,
+            max_gas_per_miniblock: Some(1_000),
+            ..StateKeeperConfig::default()
+        };
+        let mut sealer = TimeoutSealer::new(&config);
+        let mut manager = create_updates_manager();
+        manager.miniblock.timestamp = seconds_since_epoch();
+        apply_tx_with_gas_to_manager(&mut manager, 600);
+        assert!(!sealer.should_seal_miniblock(&manager));
+        apply_tx_with_gas_to_manager(&mut manager, 399);
+        assert!(!sealer.should_seal_miniblock(&manager));
+        apply_tx_with_gas_to_manager(&mut manager, 1);
+        assert!(sealer.should_seal_miniblock(&manager));
+
+        // A single heavy transaction should seal the miniblock as well.
+        let mut manager = create_updates_manager();
+        manager.miniblock.timestamp = seconds_since_epoch();
+        apply_tx_with_gas_to_manager(&mut manager, 5_000);
+        assert!(sealer.should_seal_miniblock(&manager));
+    }
 }
@@ -0,0 +1,160 @@
+//! Monitoring of seal criteria for the L1 batch currently open in the state keeper.
+
+use std::sync::Arc;
+
+use tokio::sync::watch;
+use zksync_types::{
+    api::{BatchSealStatus, SealCriterionUsage},
+    L1BatchNumber, ProtocolVersionId,
+};
+
+use super::{ConditionalSealer, SealData};
+use crate::{gas_tracker::gas_count_from_writes, state_keeper::updates::UpdatesManager};
+
+/// Snapshot of the L1 batch currently open in the state keeper.
+#[derive(Debug, Clone)]
+struct OpenL1Batch {
+    number: L1BatchNumber,
+    timestamp: u64,
+    tx_count: usize,
+    data: SealData,
+    protocol_version: ProtocolVersionId,
+    sealer: Arc<dyn ConditionalSealer>,
+}
+
+/// Reports how close the L1 batch currently open in the state keeper is to being sealed according to each
+/// seal criterion. Criteria are evaluated on request by the [`ConditionalSealer`] used by the state keeper,
+/// so the reported status follows the state keeper logic exactly; time-based criteria are evaluated
+/// at the request time.
+///
+/// The monitor is shared between the state keeper and the API server, so it is only populated
+/// if both run in the same process.
+#[derive(Debug, Clone)]
+pub struct BatchSealMonitor {
+    sender: Arc<watch::Sender<Option<OpenL1Batch>>>,
+}
+
+impl Default for BatchSealMonitor {
+    fn default() -> Self {
+        Self {
+            sender: Arc::new(watch::channel(None).0),
+        }
+    }
+}
+
+impl BatchSealMonitor {
+    /// Updates the monitor with the current state of the open L1 batch.
+    pub(in crate::state_keeper) fn update(
+        &self,
+        updates_manager: &UpdatesManager,
+        sealer: &Arc<dyn ConditionalSealer>,
+    ) {
+        let protocol_version = updates_manager.protocol_version();
+        let writes_metrics = updates_manager.storage_writes_deduplicator.metrics();
+        // Mirrors the batch data computed by the state keeper after executing a transaction.
+        let data = SealData {
+            execution_metrics: updates_manager.pending_execution_metrics(),
+            gas_count: updates_manager.pending_l1_gas_count()
+                + gas_count_from_writes(&writes_metrics, protocol_version),
+            cumulative_size: updates_manager.pending_txs_encoding_size(),
+            writes_metrics,
+            ..SealData::default()
+        };
+        self.sender.send_replace(Some(OpenL1Batch {
+            number: updates_manager.l1_batch.number,
+            timestamp: updates_manager.batch_timestamp(),
+            tx_count: updates_manager.pending_executed_transactions_len(),
+            data,
+            protocol_version,
+            sealer: sealer.clone(),
+        }));
+    }
+
+    /// Returns the seal status of the currently open L1 batch, or `None` if the state keeper hasn't opened a batch.
+    pub fn status(&self) -> Option<BatchSealStatus> {
+        let batch = self.sender.borrow().clone()?;
+        let criteria = batch
+            .sealer
+            .capacity_usage(
+                u128::from(batch.timestamp) * 1_000,
+                batch.tx_count,
+                &batch.data,
+                batch.protocol_version,
+            )
+            .into_iter()
+            .map(|(criterion, usage)| SealCriterionUsage {
+                criterion: criterion.to_owned(),
+                usage,
+            })
+            .collect();
+        Some(BatchSealStatus {
+            l1_batch_number: batch.number,
+            tx_count: batch.tx_count,
+            criteria,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_config::configs::chain::StateKeeperConfig;
+    use zksync_types::{block::BlockGasCount, tx::tx_execution_info::ExecutionMetrics};
+
+    use super::*;
+    use crate::state_keeper::{
+        tests::{create_execution_result, create_transaction, create_updates_manager},
+        SequencerSealer,
+    };
+
+    #[test]
+    fn reporting_batch_seal_status() {
+        let monitor = BatchSealMonitor::default();
+        assert_eq!(monitor.status(), None);
+
+        let config = StateKeeperConfig {
+            transaction_slots: 4,
+            ..StateKeeperConfig::for_tests()
+        };
+        let sealer: Arc<dyn ConditionalSealer> = Arc::new(SequencerSealer::new(config));
+        let mut manager = create_updates_manager();
+        monitor.update(&manager, &sealer);
+        let status = monitor.status().unwrap();
+        assert_eq!(status.l1_batch_number, manager.l1_batch.number);
+        assert_eq!(status.tx_count, 0);
+        let criteria: Vec<_> = status
+            .criteria
+            .iter()
+            .map(|usage| usage.criterion.as_str())
+            .collect();
+        assert_eq!(
+            criteria,
+            [
+                "slots",
+                "gas",
+                "pub_data_size",
+                "circuits_criterion",
+                "tx_encoding_size",
+                "no_txs_timeout"
+            ]
+        );
+        assert_eq!(status.criteria[0].usage, 0.0);
+
+        manager.extend_from_executed_transaction(
+            create_transaction(10, 100),
+            create_execution_result(0, []),
+            vec![],
+            BlockGasCount::default(),
+            ExecutionMetrics::default(),
+            vec![],
+        );
+        monitor.update(&manager, &sealer);
+        let status = monitor.status().unwrap();
+        assert_eq!(status.tx_count, 1);
+        let slots_usage = &status.criteria[0];
+        assert_eq!(slots_usage.criterion, "slots");
+        assert_eq!(slots_usage.usage, 0.25);
+        let encoding_size_usage = &status.criteria[4];
+        assert_eq!(encoding_size_usage.criterion, "tx_encoding_size");
+        assert!(encoding_size_usage.usage > 0.0, "{encoding_size_usage:?}");
+    }
+}
@@ -6,7 +6,8 @@ use once_cell::sync::OnceCell;
 use tokio::{runtime::Handle, sync::watch};
 use zksync_dal::{Connection, ConnectionPool, Core, CoreDal};
 use zksync_state::{
-    PostgresStorage, ReadStorage, RocksdbStorage, RocksdbStorageBuilder, StateKeeperColumnFamily,
+    BytecodeCache, PostgresStorage, ReadStorage, RocksdbStorage, RocksdbStorageBuilder,
+    StateKeeperColumnFamily,
 };
 use zksync_storage::RocksDB;
 use zksync_types::{L1BatchNumber, MiniblockNumber};
@@ -43,7 +44,7 @@ impl ReadStorage for PgOrRocksdbStorage<'_> {
         }
     }
 
-    fn load_factory_dep(&mut self, hash: zksync_types::H256) -> Option<Vec<u8>> {
+    fn load_factory_dep(&mut self, hash: zksync_types::H256) -> Option<Arc<[u8]>> {
         match self {
             Self::Postgres(postgres) => postgres.load_factory_dep(hash),
             Self::Rocksdb(rocksdb) => rocksdb.load_factory_dep(hash),
@@ -79,6 +80,7 @@ impl<'a> From<RocksdbStorage> for PgOrRocksdbStorage<'a> {
 pub struct AsyncRocksdbCache {
     pool: ConnectionPool<Core>,
     rocksdb_cell: Arc<OnceCell<RocksDB<StateKeeperColumnFamily>>>,
+    bytecode_cache: Option<BytecodeCache>,
 }
 
 impl AsyncRocksdbCache {
@@ -101,6 +103,7 @@ impl AsyncRocksdbCache {
     /// Returns a [`ReadStorage`] implementation backed by Postgres
     pub(crate) async fn access_storage_pg(
         pool: &ConnectionPool<Core>,
+        bytecode_cache: Option<BytecodeCache>,
     ) -> anyhow::Result<PgOrRocksdbStorage<'_>> {
         let mut connection = pool.connection().await?;
 
@@ -122,11 +125,13 @@ impl AsyncRocksdbCache {
             };
 
         tracing::debug!(%l1_batch_number, %miniblock_number, "Using Postgres-based storage");
-        Ok(
+        let mut storage =
             PostgresStorage::new_async(Handle::current(), connection, miniblock_number, true)
-                .await?
-                .into(),
-        )
+                .await?;
+        if let Some(bytecode_cache) = bytecode_cache {
+            storage = storage.with_bytecode_cache(bytecode_cache);
+        }
+        Ok(storage.into())
     }
 
     /// Catches up RocksDB synchronously (i.e. assumes the gap is small) and
@@ -134,10 +139,14 @@ impl AsyncRocksdbCache {
     async fn access_storage_rocksdb<'a>(
         connection: &mut Connection<'_, Core>,
         rocksdb: RocksDB<StateKeeperColumnFamily>,
+        bytecode_cache: Option<BytecodeCache>,
         stop_receiver: &watch::Receiver<bool>,
     ) -> anyhow::Result<Option<PgOrRocksdbStorage<'a>>> {
         tracing::debug!("Catching up RocksDB synchronously");
-        let rocksdb_builder = RocksdbStorageBuilder::from_rocksdb(rocksdb);
+        let mut rocksdb_builder = RocksdbStorageBuilder::from_rocksdb(rocksdb);
+        if let Some(bytecode_cache) = bytecode_cache {
+            rocksdb_builder.set_bytecode_cache(bytecode_cache);
+        }
         let rocksdb = rocksdb_builder
             .synchronize(connection, stop_receiver)
             .await
@@ -161,12 +170,17 @@ impl AsyncRocksdbCache {
                 .connection_tagged("state_keeper")
                 .await
                 .context("Failed getting a Postgres connection")?;
-            Self::access_storage_rocksdb(&mut connection, rocksdb.clone(), stop_receiver)
-                .await
-                .context("Failed accessing RocksDB storage")
+            Self::access_storage_rocksdb(
+                &mut connection,
+                rocksdb.clone(),
+                self.bytecode_cache.clone(),
+                stop_receiver,
+            )
+            .await
+            .context("Failed accessing RocksDB storage")
         } else {
             Ok(Some(
-                Self::access_storage_pg(&self.pool)
+                Self::access_storage_pg(&self.pool, self.bytecode_cache.clone())
                     .await
                     .context("Failed accessing Postgres storage")?,
             ))
@@ -185,7 +199,20 @@ impl AsyncRocksdbCache {
             enum_index_migration_chunk_size,
             rocksdb_cell: rocksdb_cell.clone(),
         };
-        (Self { pool, rocksdb_cell }, task)
+        let this = Self {
+            pool,
+            rocksdb_cell,
+            bytecode_cache: None,
+        };
+        (this, task)
+    }
+
+    /// Sets the bytecode cache used by the produced storage handles. The cache can be shared with other components
+    /// (e.g., the API server).
+    #[must_use]
+    pub fn with_bytecode_cache(mut self, bytecode_cache: BytecodeCache) -> Self {
+        self.bytecode_cache = Some(bytecode_cache);
+        self
     }
 }
 
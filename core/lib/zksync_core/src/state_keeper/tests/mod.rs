@@ -21,6 +21,7 @@ use zksync_system_constants::ZKPORTER_IS_AVAILABLE;
 use zksync_types::{
     aggregated_operations::AggregatedActionType,
     block::{BlockGasCount, MiniblockExecutionData, MiniblockHasher},
+    circuit::CircuitStatistic,
     fee_model::{BatchFeeInput, PubdataIndependentBatchFeeModelInput},
     tx::tx_execution_info::ExecutionMetrics,
     zk_evm_types::{LogQuery, Timestamp},
@@ -41,7 +42,7 @@ use crate::{
         batch_executor::TxExecutionResult,
         keeper::POLL_WAIT_DURATION,
         seal_criteria::{
-            criteria::{GasCriterion, SlotsCriterion},
+            criteria::{CircuitsCriterion, GasCriterion, SlotsCriterion},
             SequencerSealer,
         },
         types::ExecutionMetricsForCriteria,
@@ -58,7 +59,7 @@ pub(super) fn default_system_env() -> SystemEnv {
     SystemEnv {
         zk_porter_available: ZKPORTER_IS_AVAILABLE,
         version: ProtocolVersionId::latest(),
-        base_system_smart_contracts: BASE_SYSTEM_CONTRACTS.clone(),
+        base_system_smart_contracts: Arc::new(BASE_SYSTEM_CONTRACTS.clone()),
         bootloader_gas_limit: BATCH_COMPUTATIONAL_GAS_LIMIT,
         execution_mode: TxExecutionMode::VerifyExecute,
         default_validation_computational_gas_limit: BATCH_COMPUTATIONAL_GAS_LIMIT,
@@ -91,7 +92,7 @@ pub(super) fn default_l1_batch_env(
     }
 }
 
-pub(super) fn default_vm_batch_result() -> FinishedL1Batch {
+pub(crate) fn default_vm_batch_result() -> FinishedL1Batch {
     FinishedL1Batch {
         block_tip_execution_result: VmExecutionResultAndLogs {
             result: ExecutionResult::Success { output: vec![] },
@@ -117,16 +118,16 @@ pub(super) fn default_vm_batch_result() -> FinishedL1Batch {
     }
 }
 
-pub(super) fn create_updates_manager() -> UpdatesManager {
+pub(crate) fn create_updates_manager() -> UpdatesManager {
     let l1_batch_env = default_l1_batch_env(1, 1, Address::default());
     UpdatesManager::new(&l1_batch_env, &default_system_env())
 }
 
-pub(super) fn create_transaction(fee_per_gas: u64, gas_per_pubdata: u64) -> Transaction {
+pub(crate) fn create_transaction(fee_per_gas: u64, gas_per_pubdata: u64) -> Transaction {
     create_l2_transaction(fee_per_gas, gas_per_pubdata).into()
 }
 
-pub(super) fn create_execution_result(
+pub(crate) fn create_execution_result(
     tx_number_in_block: u16,
     storage_logs: impl IntoIterator<Item = (U256, Query)>,
 ) -> VmExecutionResultAndLogs {
@@ -331,6 +332,28 @@ async fn batch_sealed_before_miniblock_does() {
         .await;
 }
 
+#[tokio::test]
+async fn miniblock_sealed_on_shutdown() {
+    let config = StateKeeperConfig {
+        transaction_slots: 10,
+        ..StateKeeperConfig::default()
+    };
+    let sealer = SequencerSealer::with_sealers(config, vec![Box::new(SlotsCriterion)]);
+
+    TestScenario::new()
+        .seal_miniblock_on_shutdown()
+        .next_tx("First tx", random_tx(1), successful_exec())
+        .next_tx("Second tx", random_tx(2), successful_exec())
+        .shutdown("Stop signal")
+        .miniblock_sealed_with("Miniblock sealed on shutdown", |updates| {
+            assert_eq!(updates.miniblock.executed_transactions.len(), 2);
+            // The L1 batch must not be sealed; it's resumed after a restart.
+            assert_eq!(updates.l1_batch.executed_transactions.len(), 0);
+        })
+        .run(sealer)
+        .await;
+}
+
 #[tokio::test]
 async fn rejected_tx() {
     let config = StateKeeperConfig {
@@ -391,6 +414,68 @@ async fn bootloader_tip_out_of_gas_flow() {
         .await;
 }
 
+#[tokio::test]
+async fn tx_deferred_based_on_circuit_estimate() {
+    let config = StateKeeperConfig {
+        transaction_slots: 2,
+        max_circuits_per_batch: 30_000,
+        reject_tx_at_geometry_percentage: 0.9,
+        close_block_at_geometry_percentage: 0.9,
+        ..StateKeeperConfig::default()
+    };
+    let sealer = SequencerSealer::with_sealers(
+        config,
+        vec![Box::new(SlotsCriterion), Box::new(CircuitsCriterion)],
+    );
+
+    let contract_address = Address::repeat_byte(1);
+    let mut first_tx = random_tx(1);
+    first_tx.execute.contract_address = contract_address;
+    let mut second_tx = random_tx(2);
+    second_tx.execute.contract_address = contract_address;
+    let execution_result = successful_exec_with_metrics(ExecutionMetricsForCriteria {
+        l1_gas: BlockGasCount::default(),
+        execution_metrics: ExecutionMetrics {
+            circuit_statistic: CircuitStatistic {
+                main_vm: 10_000.0,
+                ..CircuitStatistic::default()
+            },
+            ..ExecutionMetrics::default()
+        },
+    });
+
+    TestScenario::new()
+        .seal_miniblock_when(|updates| updates.miniblock.executed_transactions.len() == 1)
+        .next_tx("First tx", first_tx, execution_result.clone())
+        .miniblock_sealed("Miniblock with 1st tx")
+        // If the tx was executed, it would be included into the batch since its execution doesn't use circuits.
+        .next_tx(
+            "Second tx calling the same contract",
+            second_tx.clone(),
+            successful_exec(),
+        )
+        .tx_rollback(
+            "Second tx is deferred without execution since it's expected to overflow circuits",
+            second_tx.clone(),
+        )
+        .batch_sealed("Batch sealed with 1 tx")
+        .next_tx(
+            "Second tx is executed in new batch",
+            second_tx,
+            execution_result,
+        )
+        .miniblock_sealed("Miniblock with 2nd tx")
+        .next_tx(
+            "Third tx calling another contract",
+            random_tx(3),
+            successful_exec(),
+        )
+        .miniblock_sealed("Miniblock with 3rd tx")
+        .batch_sealed("2nd batch sealed")
+        .run(sealer)
+        .await;
+}
+
 #[tokio::test]
 async fn pending_batch_is_applied() {
     let config = StateKeeperConfig {
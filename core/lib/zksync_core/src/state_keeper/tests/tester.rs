@@ -52,6 +52,7 @@ pub(crate) struct TestScenario {
     pending_batch: Option<PendingBatchData>,
     l1_batch_seal_fn: Box<SealFn>,
     miniblock_seal_fn: Box<SealFn>,
+    seal_miniblock_on_shutdown: bool,
 }
 
 type SealFn = dyn FnMut(&UpdatesManager) -> bool + Send;
@@ -73,9 +74,16 @@ impl TestScenario {
             pending_batch: None,
             l1_batch_seal_fn: Box::new(|_| false),
             miniblock_seal_fn: Box::new(|_| false),
+            seal_miniblock_on_shutdown: false,
         }
     }
 
+    /// Makes the state keeper seal the open miniblock on shutdown.
+    pub(crate) fn seal_miniblock_on_shutdown(mut self) -> Self {
+        self.seal_miniblock_on_shutdown = true;
+        self
+    }
+
     /// Adds a pending batch data that would be fed into the state keeper.
     /// Note that during processing pending batch, state keeper do *not* call `seal_miniblock` method on the IO (since
     /// it only recovers the temporary state).
@@ -112,6 +120,12 @@ impl TestScenario {
         self
     }
 
+    /// Sends a stop signal to the state keeper when it requests the next transaction.
+    pub(crate) fn shutdown(mut self, description: &'static str) -> Self {
+        self.actions.push_back(ScenarioItem::Shutdown(description));
+        self
+    }
+
     /// Expect the state keeper to rollback the transaction (i.e. return to the mempool).
     pub(crate) fn tx_rollback(mut self, description: &'static str, tx: Transaction) -> Self {
         self.actions
@@ -195,14 +209,18 @@ impl TestScenario {
 
         let batch_executor_base = TestBatchExecutorBuilder::new(&self);
         let (stop_sender, stop_receiver) = watch::channel(false);
+        let seal_miniblock_on_shutdown = self.seal_miniblock_on_shutdown;
         let (io, output_handler) = TestIO::new(stop_sender, self);
-        let state_keeper = ZkSyncStateKeeper::new(
+        let mut state_keeper = ZkSyncStateKeeper::new(
             stop_receiver,
             Box::new(io),
             Box::new(batch_executor_base),
             output_handler,
             Arc::new(sealer),
         );
+        if seal_miniblock_on_shutdown {
+            state_keeper = state_keeper.with_miniblock_sealing_on_shutdown();
+        }
         let sk_thread = tokio::spawn(state_keeper.run());
 
         // We must assume that *theoretically* state keeper may ignore the stop signal from IO once scenario is
@@ -299,7 +317,7 @@ pub(crate) fn pending_batch_data(
         system_env: SystemEnv {
             zk_porter_available: false,
             version: ProtocolVersionId::latest(),
-            base_system_smart_contracts: BASE_SYSTEM_CONTRACTS.clone(),
+            base_system_smart_contracts: Arc::new(BASE_SYSTEM_CONTRACTS.clone()),
             bootloader_gas_limit: BATCH_COMPUTATIONAL_GAS_LIMIT,
             execution_mode: TxExecutionMode::VerifyExecute,
             default_validation_computational_gas_limit: BATCH_COMPUTATIONAL_GAS_LIMIT,
@@ -315,6 +333,8 @@ enum ScenarioItem {
     NoTxsUntilNextAction(&'static str),
     /// Increments protocol version in IO state.
     IncrementProtocolVersion(&'static str),
+    /// Sends a stop signal to the state keeper instead of returning a transaction.
+    Shutdown(&'static str),
     Tx(&'static str, Transaction, TxExecutionResult),
     Rollback(&'static str, Transaction),
     Reject(&'static str, Transaction, Option<String>),
@@ -339,6 +359,7 @@ impl fmt::Debug for ScenarioItem {
                 .debug_tuple("IncrementProtocolVersion")
                 .field(descr)
                 .finish(),
+            Self::Shutdown(descr) => formatter.debug_tuple("Shutdown").field(descr).finish(),
             Self::Tx(descr, tx, result) => formatter
                 .debug_tuple("Tx")
                 .field(descr)
@@ -766,6 +787,11 @@ impl StateKeeperIO for TestIO {
             return Ok(None);
         }
 
+        if let ScenarioItem::Shutdown(_) = action {
+            self.stop_sender.send_replace(true);
+            return Ok(None);
+        }
+
         // We shouldn't, process normally.
         let ScenarioItem::Tx(_, tx, _) = action else {
             panic!("Unexpected action: {:?}", action);
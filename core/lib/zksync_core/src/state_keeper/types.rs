@@ -4,62 +4,119 @@ use std::{
 };
 
 use multivm::interface::VmExecutionResultAndLogs;
+use serde::{Deserialize, Serialize};
+use zksync_config::configs::chain::MempoolConfig;
 use zksync_dal::{Connection, Core, CoreDal};
-use zksync_mempool::{L2TxFilter, MempoolInfo, MempoolStore};
+use zksync_mempool::{L2TxFilter, MempoolInfo, MempoolScoringPolicy, MempoolStore};
 use zksync_types::{
-    block::BlockGasCount, tx::ExecutionMetrics, Address, Nonce, PriorityOpId, Transaction,
+    block::BlockGasCount,
+    tx::{ExecutionMetrics, TxAccessHints},
+    Address, Nonce, PriorityOpId, Transaction, H256,
 };
 
-use super::metrics::StateKeeperGauges;
+use super::metrics::{StateKeeperGauges, KEEPER_METRICS};
 use crate::gas_tracker::{gas_count_from_metrics, gas_count_from_tx_and_metrics};
 
+/// Shared handle to the in-memory mempool.
+///
+/// Besides transactions, the guard holds access hints for loaded transactions. Hints are removed once
+/// the corresponding transaction is executed or rejected by the state keeper. To bound memory usage,
+/// at most as many hints as the mempool capacity are held; excess hints are dropped.
 #[derive(Debug, Clone)]
-pub struct MempoolGuard(Arc<Mutex<MempoolStore>>);
+pub struct MempoolGuard {
+    store: Arc<Mutex<MempoolStore>>,
+    access_hints: Arc<Mutex<HashMap<H256, TxAccessHints>>>,
+    access_hints_capacity: usize,
+}
 
 impl MempoolGuard {
-    pub async fn from_storage(storage_processor: &mut Connection<'_, Core>, capacity: u64) -> Self {
+    pub async fn from_storage(
+        storage_processor: &mut Connection<'_, Core>,
+        config: &MempoolConfig,
+    ) -> Self {
         let next_priority_id = storage_processor
             .transactions_dal()
             .next_priority_id()
             .await;
-        Self::new(next_priority_id, capacity)
+        let default_policy = MempoolScoringPolicy::default();
+        let scoring_policy = MempoolScoringPolicy {
+            fee_weight: config.score_fee_weight.unwrap_or(default_policy.fee_weight),
+            age_weight: config.score_age_weight.unwrap_or(default_policy.age_weight),
+            rejection_penalty: config
+                .score_rejection_penalty
+                .unwrap_or(default_policy.rejection_penalty),
+        };
+        let mut store = MempoolStore::new(next_priority_id, config.capacity)
+            .with_scoring_policy(scoring_policy);
+        if let Some(max_l2_transactions) = config.max_l2_transactions {
+            store = store.with_max_l2_transactions(max_l2_transactions);
+        }
+        Self::from_store(store, config.capacity)
     }
 
     pub(super) fn new(next_priority_id: PriorityOpId, capacity: u64) -> Self {
         let store = MempoolStore::new(next_priority_id, capacity);
-        Self(Arc::new(Mutex::new(store)))
+        Self::from_store(store, capacity)
+    }
+
+    fn from_store(store: MempoolStore, capacity: u64) -> Self {
+        Self {
+            store: Arc::new(Mutex::new(store)),
+            access_hints: Arc::default(),
+            access_hints_capacity: usize::try_from(capacity).unwrap_or(usize::MAX),
+        }
     }
 
     pub fn insert(&mut self, transactions: Vec<Transaction>, nonces: HashMap<Address, Nonce>) {
-        self.0
+        let evicted_count = self
+            .store
             .lock()
             .expect("failed to acquire mempool lock")
             .insert(transactions, nonces);
+        if evicted_count > 0 {
+            KEEPER_METRICS
+                .mempool_evicted_transactions
+                .inc_by(evicted_count);
+        }
     }
 
     pub fn has_next(&self, filter: &L2TxFilter) -> bool {
-        self.0
+        self.store
             .lock()
             .expect("failed to acquire mempool lock")
             .has_next(filter)
     }
 
     pub fn next_transaction(&mut self, filter: &L2TxFilter) -> Option<Transaction> {
-        self.0
+        self.store
             .lock()
             .expect("failed to acquire mempool lock")
             .next_transaction(filter)
     }
 
+    pub fn next_l2_transaction(&mut self, filter: &L2TxFilter) -> Option<Transaction> {
+        self.store
+            .lock()
+            .expect("failed to acquire mempool lock")
+            .next_l2_transaction(filter)
+    }
+
     pub fn rollback(&mut self, rejected: &Transaction) {
-        self.0
+        self.store
             .lock()
             .expect("failed to acquire mempool lock")
             .rollback(rejected);
     }
 
+    pub fn reject(&mut self, rejected: &Transaction) {
+        self.store
+            .lock()
+            .expect("failed to acquire mempool lock")
+            .reject(rejected);
+    }
+
     pub fn get_mempool_info(&mut self) -> MempoolInfo {
-        self.0
+        self.store
             .lock()
             .expect("failed to acquire mempool lock")
             .get_mempool_info()
@@ -67,18 +124,44 @@ impl MempoolGuard {
 
     #[cfg(test)]
     pub fn stats(&self) -> zksync_mempool::MempoolStats {
-        self.0
+        self.store
             .lock()
             .expect("failed to acquire mempool lock")
             .stats()
     }
 
+    /// Adds access hints for transactions loaded into the mempool. Returns the number of dropped hints
+    /// if the hints capacity is exceeded.
+    pub fn insert_access_hints(&mut self, hints: HashMap<H256, TxAccessHints>) -> usize {
+        let mut access_hints = self
+            .access_hints
+            .lock()
+            .expect("failed to acquire access hints lock");
+        let mut dropped_count = 0;
+        for (tx_hash, tx_hints) in hints {
+            if access_hints.len() >= self.access_hints_capacity {
+                dropped_count += 1;
+            } else {
+                access_hints.insert(tx_hash, tx_hints);
+            }
+        }
+        dropped_count
+    }
+
+    /// Removes and returns access hints for the specified transaction.
+    pub fn take_access_hints(&mut self, tx_hash: H256) -> Option<TxAccessHints> {
+        self.access_hints
+            .lock()
+            .expect("failed to acquire access hints lock")
+            .remove(&tx_hash)
+    }
+
     pub fn register_metrics(&self) {
-        StateKeeperGauges::register(Arc::downgrade(&self.0));
+        StateKeeperGauges::register(Arc::downgrade(&self.store));
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct ExecutionMetricsForCriteria {
     pub l1_gas: BlockGasCount,
     pub execution_metrics: ExecutionMetrics,
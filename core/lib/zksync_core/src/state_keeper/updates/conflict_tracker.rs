@@ -0,0 +1,512 @@
+//! Measurement of storage conflicts among transactions in an L1 batch.
+//!
+//! Conflicts are measured based on the actual storage accesses of executed transactions. Additionally,
+//! L2 transactions may carry access hints supplied on submission (see [`TxAccessHints`]). Hints are used
+//! to schedule transactions into waves that could be executed in parallel, and the schedule is checked
+//! against the actual storage accesses. Since the bootloader verifies transaction signatures assuming empty
+//! access lists, hints are not part of signed transactions; they are unverified and never affect execution.
+
+use std::collections::{HashMap, HashSet};
+
+use zksync_system_constants::BOOTLOADER_ADDRESS;
+use zksync_types::{
+    tx::TxAccessHints, utils::storage_key_for_eth_balance, AccountTreeId, Address, L1BatchNumber,
+    StorageKey, StorageLogQuery, Transaction, H256,
+};
+use zksync_utils::u256_to_h256;
+
+use crate::state_keeper::metrics::{ConflictScope, HintedTxOutcome, TxConflict, KEEPER_METRICS};
+
+/// Minimum number of transactions from a sender / calling a contract in an L1 batch for the sender / contract
+/// to be considered in conflict rate metrics.
+const MIN_TXS_FOR_CONFLICT_RATE: usize = 2;
+/// Number of senders / contracts with the most conflicts logged for each L1 batch.
+const LOGGED_ADDRESS_COUNT: usize = 5;
+
+/// Transaction that has written a storage slot.
+#[derive(Debug, Clone, Copy)]
+struct SlotWriter {
+    initiator: Address,
+    contract: Address,
+}
+
+impl SlotWriter {
+    fn new(tx: &Transaction) -> Self {
+        Self {
+            initiator: tx.initiator_account(),
+            contract: tx.execute.contract_address,
+        }
+    }
+
+    fn address(&self, scope: ConflictScope) -> Address {
+        match scope {
+            ConflictScope::Sender => self.initiator,
+            ConflictScope::Contract => self.contract,
+        }
+    }
+
+    fn conflict_with(&self, prev_writer: &Self) -> TxConflict {
+        if self.initiator == prev_writer.initiator {
+            TxConflict::SameSender
+        } else if self.contract == prev_writer.contract {
+            TxConflict::SameContract
+        } else {
+            TxConflict::OtherContract
+        }
+    }
+}
+
+/// Number of transactions and conflicting transactions from a single sender / calling a single contract.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct ConflictStats {
+    transactions: usize,
+    conflicts: usize,
+}
+
+impl ConflictStats {
+    fn rate(&self) -> f64 {
+        self.conflicts as f64 / self.transactions as f64
+    }
+}
+
+/// Schedules transactions in an L1 batch into waves based on their access hints, mimicking a parallel executor.
+///
+/// A transaction with hints is scheduled into the wave following the latest wave of transactions with overlapping
+/// hinted slots (but not earlier than the latest barrier). A transaction without hints is a barrier: it's scheduled
+/// into a separate wave after all preceding transactions, and all subsequent transactions are scheduled after it.
+///
+/// A hinted transaction is mispredicted if it reads a slot written by a preceding transaction scheduled
+/// into the same or a later wave, or writes a slot accessed by such a transaction. With a parallel executor,
+/// such a transaction would observe a state inconsistent with sequential execution and would have to be re-executed.
+#[derive(Debug, Default)]
+struct HintScheduler {
+    /// Number of waves scheduled so far.
+    wave_count: usize,
+    /// Earliest wave for transactions; set after the latest barrier.
+    min_wave: usize,
+    transaction_count: usize,
+    hinted_transaction_count: usize,
+    /// Latest wave of a transaction hinting each slot. Reset on barriers.
+    hinted_slot_waves: HashMap<StorageKey, usize>,
+    /// Latest wave of a transaction reading each slot. Reset on barriers.
+    read_waves: HashMap<StorageKey, usize>,
+    /// Latest wave of a transaction writing each slot. Reset on barriers.
+    write_waves: HashMap<StorageKey, usize>,
+}
+
+impl HintScheduler {
+    /// Schedules a transaction with the specified hints and actual storage accesses (`(slot, is_write)` pairs).
+    /// Returns `None` if the transaction has no hints.
+    fn schedule(
+        &mut self,
+        hints: Option<&TxAccessHints>,
+        accesses: &[(StorageKey, bool)],
+    ) -> Option<HintedTxOutcome> {
+        self.transaction_count += 1;
+        let Some(hints) = hints else {
+            // Transactions scheduled before the barrier cannot conflict with subsequent transactions.
+            self.wave_count += 1;
+            self.min_wave = self.wave_count;
+            self.hinted_slot_waves.clear();
+            self.read_waves.clear();
+            self.write_waves.clear();
+            return None;
+        };
+
+        self.hinted_transaction_count += 1;
+        let wave = hints
+            .storage_slots
+            .iter()
+            .filter_map(|slot| self.hinted_slot_waves.get(slot))
+            .map(|&wave| wave + 1)
+            .fold(self.min_wave, usize::max);
+        for &slot in &hints.storage_slots {
+            self.hinted_slot_waves.insert(slot, wave);
+        }
+        self.wave_count = self.wave_count.max(wave + 1);
+
+        let is_conflicting = |waves: &HashMap<StorageKey, usize>, slot: &StorageKey| {
+            waves.get(slot).is_some_and(|&prev_wave| prev_wave >= wave)
+        };
+        let is_mispredicted = accesses.iter().any(|(slot, is_write)| {
+            is_conflicting(&self.write_waves, slot)
+                || (*is_write && is_conflicting(&self.read_waves, slot))
+        });
+        for &(slot, is_write) in accesses {
+            let waves = if is_write {
+                &mut self.write_waves
+            } else {
+                &mut self.read_waves
+            };
+            let prev_wave = waves.entry(slot).or_default();
+            *prev_wave = (*prev_wave).max(wave);
+        }
+
+        Some(if is_mispredicted {
+            HintedTxOutcome::Mispredicted
+        } else {
+            HintedTxOutcome::Accurate
+        })
+    }
+
+    /// Returns the average number of transactions per wave if the batch contains hinted transactions.
+    fn parallelism(&self) -> Option<f64> {
+        (self.hinted_transaction_count > 0)
+            .then(|| self.transaction_count as f64 / self.wave_count as f64)
+    }
+}
+
+/// Tracks storage slots written by transactions in an L1 batch in order to measure how often transactions
+/// would conflict if executed in parallel with optimistic concurrency, i.e., how often a transaction accesses
+/// a storage slot written by a preceding transaction in the same batch.
+///
+/// Balance slots of the fee account and the bootloader are ignored since they are written by every transaction.
+///
+/// Besides the global per-transaction metrics, conflicts are aggregated per sender and per called contract.
+/// Since addresses are unsuitable as metric labels, per-address conflict rates are reported as the maximum rate
+/// in the batch, and the senders / contracts with the most conflicts are logged.
+///
+/// Transactions with access hints registered via [`Self::register_access_hints()`] are additionally scheduled
+/// by [`HintScheduler`].
+#[derive(Debug)]
+pub(crate) struct ConflictTracker {
+    ignored_slots: HashSet<StorageKey>,
+    written_slots: HashMap<StorageKey, SlotWriter>,
+    stats_by_sender: HashMap<Address, ConflictStats>,
+    stats_by_contract: HashMap<Address, ConflictStats>,
+    access_hints: HashMap<H256, TxAccessHints>,
+    hint_scheduler: HintScheduler,
+}
+
+impl ConflictTracker {
+    pub fn new(fee_account: Address) -> Self {
+        let ignored_slots = [fee_account, BOOTLOADER_ADDRESS]
+            .iter()
+            .map(storage_key_for_eth_balance)
+            .collect();
+        Self {
+            ignored_slots,
+            written_slots: HashMap::new(),
+            stats_by_sender: HashMap::new(),
+            stats_by_contract: HashMap::new(),
+            access_hints: HashMap::new(),
+            hint_scheduler: HintScheduler::default(),
+        }
+    }
+
+    /// Registers access hints for a transaction that will be applied later.
+    pub fn register_access_hints(&mut self, tx_hash: H256, hints: TxAccessHints) {
+        self.access_hints.insert(tx_hash, hints);
+    }
+
+    fn stats(&self, scope: ConflictScope) -> &HashMap<Address, ConflictStats> {
+        match scope {
+            ConflictScope::Sender => &self.stats_by_sender,
+            ConflictScope::Contract => &self.stats_by_contract,
+        }
+    }
+
+    fn stats_mut(&mut self, scope: ConflictScope) -> &mut HashMap<Address, ConflictStats> {
+        match scope {
+            ConflictScope::Sender => &mut self.stats_by_sender,
+            ConflictScope::Contract => &mut self.stats_by_contract,
+        }
+    }
+
+    fn storage_key(log: &StorageLogQuery) -> StorageKey {
+        StorageKey::new(
+            AccountTreeId::new(log.log_query.address),
+            u256_to_h256(log.log_query.key),
+        )
+    }
+
+    /// Applies storage logs produced by a transaction. Returns the most severe conflict of the transaction
+    /// with the preceding transactions in the batch.
+    pub fn apply(&mut self, tx: &Transaction, storage_logs: &[StorageLogQuery]) -> TxConflict {
+        let accesses: Vec<_> = storage_logs
+            .iter()
+            .map(|log| {
+                let is_write = log.log_query.rw_flag && !log.log_query.rollback;
+                (Self::storage_key(log), is_write)
+            })
+            .filter(|(key, _)| !self.ignored_slots.contains(key))
+            .collect();
+
+        let writer = SlotWriter::new(tx);
+        let mut conflict = TxConflict::None;
+        for (key, _) in &accesses {
+            if let Some(prev_writer) = self.written_slots.get(key) {
+                conflict = conflict.max(writer.conflict_with(prev_writer));
+            }
+        }
+        // Writes are recorded only after all accesses are checked, so that a transaction doesn't conflict with itself.
+        for &(key, is_write) in &accesses {
+            if is_write {
+                self.written_slots.insert(key, writer);
+            }
+        }
+
+        let hints = self.access_hints.remove(&tx.hash());
+        if let Some(outcome) = self.hint_scheduler.schedule(hints.as_ref(), &accesses) {
+            KEEPER_METRICS.hinted_txs[&outcome].inc();
+        }
+
+        for scope in [ConflictScope::Sender, ConflictScope::Contract] {
+            let stats = self
+                .stats_mut(scope)
+                .entry(writer.address(scope))
+                .or_default();
+            stats.transactions += 1;
+            if conflict != TxConflict::None {
+                stats.conflicts += 1;
+            }
+        }
+        conflict
+    }
+
+    /// Returns the maximum conflict rate among senders / contracts with enough transactions, together with
+    /// the senders / contracts with the most conflicts ordered by the number of conflicts descending.
+    fn summarize(&self, scope: ConflictScope) -> (Option<f64>, Vec<(Address, ConflictStats)>) {
+        let stats = self.stats(scope);
+        let max_rate = stats
+            .values()
+            .filter(|stats| stats.transactions >= MIN_TXS_FOR_CONFLICT_RATE)
+            .map(ConflictStats::rate)
+            .max_by(f64::total_cmp);
+
+        let mut top_addresses: Vec<_> = stats
+            .iter()
+            .filter(|(_, stats)| stats.conflicts > 0)
+            .map(|(&address, &stats)| (address, stats))
+            .collect();
+        top_addresses.sort_unstable_by(|(address, stats), (other_address, other_stats)| {
+            other_stats
+                .conflicts
+                .cmp(&stats.conflicts)
+                .then_with(|| address.cmp(other_address))
+        });
+        top_addresses.truncate(LOGGED_ADDRESS_COUNT);
+        (max_rate, top_addresses)
+    }
+
+    /// Reports per-sender and per-contract conflict rates for the L1 batch. Should be called once the batch
+    /// is finished.
+    pub fn report(&self, l1_batch_number: L1BatchNumber) {
+        if let Some(parallelism) = self.hint_scheduler.parallelism() {
+            KEEPER_METRICS.batch_hinted_parallelism.observe(parallelism);
+            tracing::info!(
+                "L1 batch #{l1_batch_number}: {} transactions ({} with access hints) would be executed \
+                 in {} waves based on access hints",
+                self.hint_scheduler.transaction_count,
+                self.hint_scheduler.hinted_transaction_count,
+                self.hint_scheduler.wave_count
+            );
+        }
+        for scope in [ConflictScope::Sender, ConflictScope::Contract] {
+            let (max_rate, top_addresses) = self.summarize(scope);
+            if let Some(max_rate) = max_rate {
+                KEEPER_METRICS.batch_max_conflict_rate[&scope].observe(max_rate);
+            }
+            if !top_addresses.is_empty() {
+                let top_addresses: Vec<_> = top_addresses
+                    .iter()
+                    .map(|(address, stats)| {
+                        format!("{address:?}: {}/{}", stats.conflicts, stats.transactions)
+                    })
+                    .collect();
+                tracing::info!(
+                    "L1 batch #{l1_batch_number}: {scope:?} addresses with most storage conflicts \
+                     (conflicting / all transactions): {}",
+                    top_addresses.join(", ")
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_types::{ExecuteTransactionCommon, U256};
+
+    use super::*;
+    use crate::state_keeper::tests::{create_execution_result, create_transaction, Query};
+
+    fn transaction(initiator: Address, contract: Address) -> Transaction {
+        let mut tx = create_transaction(10, 100);
+        tx.execute.contract_address = contract;
+        if let ExecuteTransactionCommon::L2(data) = &mut tx.common_data {
+            data.initiator_address = initiator;
+        }
+        tx
+    }
+
+    #[test]
+    fn tracking_conflicts() {
+        let mut tracker = ConflictTracker::new(Address::repeat_byte(0xfe));
+        let alice = Address::repeat_byte(1);
+        let bob = Address::repeat_byte(2);
+        let token = Address::repeat_byte(0x10);
+        let other_token = Address::repeat_byte(0x20);
+
+        let logs = create_execution_result(0, [(U256::from(1), Query::InitialWrite(1.into()))])
+            .logs
+            .storage_logs;
+        let conflict = tracker.apply(&transaction(alice, token), &logs);
+        assert_eq!(conflict, TxConflict::None);
+
+        // Reading an unrelated slot doesn't lead to a conflict.
+        let logs = create_execution_result(1, [(U256::from(2), Query::Read(0.into()))])
+            .logs
+            .storage_logs;
+        let conflict = tracker.apply(&transaction(bob, token), &logs);
+        assert_eq!(conflict, TxConflict::None);
+
+        let logs = create_execution_result(2, [(U256::from(1), Query::Read(1.into()))])
+            .logs
+            .storage_logs;
+        let conflict = tracker.apply(&transaction(alice, token), &logs);
+        assert_eq!(conflict, TxConflict::SameSender);
+        let conflict = tracker.apply(&transaction(bob, token), &logs);
+        assert_eq!(conflict, TxConflict::SameContract);
+        let conflict = tracker.apply(&transaction(bob, other_token), &logs);
+        assert_eq!(conflict, TxConflict::OtherContract);
+    }
+
+    #[test]
+    fn aggregating_conflicts_by_sender_and_contract() {
+        let mut tracker = ConflictTracker::new(Address::repeat_byte(0xfe));
+        let alice = Address::repeat_byte(1);
+        let bob = Address::repeat_byte(2);
+        let token = Address::repeat_byte(0x10);
+        let other_token = Address::repeat_byte(0x20);
+
+        let write_logs =
+            create_execution_result(0, [(U256::from(1), Query::InitialWrite(1.into()))])
+                .logs
+                .storage_logs;
+        let read_logs = create_execution_result(1, [(U256::from(1), Query::Read(1.into()))])
+            .logs
+            .storage_logs;
+        tracker.apply(&transaction(alice, token), &write_logs);
+        tracker.apply(&transaction(bob, token), &read_logs);
+        tracker.apply(&transaction(bob, other_token), &[]);
+
+        let (max_rate, top_senders) = tracker.summarize(ConflictScope::Sender);
+        // Only Bob has enough transactions; 1 of his 2 transactions conflicts.
+        assert_eq!(max_rate, Some(0.5));
+        let expected_stats = ConflictStats {
+            transactions: 2,
+            conflicts: 1,
+        };
+        assert_eq!(top_senders, [(bob, expected_stats)]);
+
+        let (max_rate, top_contracts) = tracker.summarize(ConflictScope::Contract);
+        assert_eq!(max_rate, Some(0.5));
+        assert_eq!(top_contracts, [(token, expected_stats)]);
+    }
+
+    #[test]
+    fn fee_account_balance_is_ignored() {
+        let fee_account = Address::repeat_byte(0xfe);
+        let mut tracker = ConflictTracker::new(fee_account);
+        let balance_key = storage_key_for_eth_balance(&fee_account);
+        let mut logs = create_execution_result(0, [(U256::zero(), Query::InitialWrite(1.into()))])
+            .logs
+            .storage_logs;
+        logs[0].log_query.address = *balance_key.address();
+        logs[0].log_query.key = U256::from_big_endian(balance_key.key().as_bytes());
+
+        let alice = Address::repeat_byte(1);
+        let bob = Address::repeat_byte(2);
+        let token = Address::repeat_byte(0x10);
+        assert_eq!(
+            tracker.apply(&transaction(alice, token), &logs),
+            TxConflict::None
+        );
+        assert_eq!(
+            tracker.apply(&transaction(bob, token), &logs),
+            TxConflict::None
+        );
+    }
+
+    fn slot(byte: u8) -> StorageKey {
+        StorageKey::new(AccountTreeId::new(Address::repeat_byte(byte)), H256::zero())
+    }
+
+    fn hints(bytes: &[u8]) -> TxAccessHints {
+        TxAccessHints {
+            storage_slots: bytes.iter().copied().map(slot).collect(),
+        }
+    }
+
+    #[test]
+    fn scheduling_transactions_with_hints() {
+        let mut scheduler = HintScheduler::default();
+        let outcome = scheduler.schedule(Some(&hints(&[1])), &[(slot(1), true)]);
+        assert_eq!(outcome, Some(HintedTxOutcome::Accurate));
+        // Disjoint hints allow scheduling in the same wave.
+        let outcome = scheduler.schedule(Some(&hints(&[2])), &[(slot(2), true)]);
+        assert_eq!(outcome, Some(HintedTxOutcome::Accurate));
+        assert_eq!(scheduler.wave_count, 1);
+
+        // Overlapping hints schedule the transaction into the next wave.
+        let outcome = scheduler.schedule(Some(&hints(&[1])), &[(slot(1), false), (slot(6), false)]);
+        assert_eq!(outcome, Some(HintedTxOutcome::Accurate));
+        assert_eq!(scheduler.wave_count, 2);
+
+        // Reading a slot written in the same wave is a misprediction.
+        let outcome = scheduler.schedule(Some(&hints(&[3])), &[(slot(2), false)]);
+        assert_eq!(outcome, Some(HintedTxOutcome::Mispredicted));
+        // Writing a slot read in a later wave is a misprediction as well.
+        let outcome = scheduler.schedule(Some(&hints(&[4])), &[(slot(6), true)]);
+        assert_eq!(outcome, Some(HintedTxOutcome::Mispredicted));
+        assert_eq!(scheduler.wave_count, 2);
+        assert_eq!(scheduler.parallelism(), Some(2.5));
+    }
+
+    #[test]
+    fn transactions_without_hints_are_barriers() {
+        let mut scheduler = HintScheduler::default();
+        assert_eq!(scheduler.schedule(None, &[(slot(1), true)]), None);
+        assert_eq!(scheduler.parallelism(), None);
+
+        let outcome = scheduler.schedule(Some(&hints(&[2])), &[(slot(1), false)]);
+        assert_eq!(outcome, Some(HintedTxOutcome::Accurate));
+        assert_eq!(scheduler.schedule(None, &[(slot(2), true)]), None);
+        // The transaction is scheduled after the barrier, so it doesn't conflict with the preceding transactions.
+        let outcome = scheduler.schedule(Some(&hints(&[3])), &[(slot(2), false)]);
+        assert_eq!(outcome, Some(HintedTxOutcome::Accurate));
+        assert_eq!(scheduler.wave_count, 4);
+        assert_eq!(scheduler.parallelism(), Some(1.0));
+    }
+
+    #[test]
+    fn registered_hints_are_used_for_scheduling() {
+        let mut tracker = ConflictTracker::new(Address::repeat_byte(0xfe));
+        let alice = Address::repeat_byte(1);
+        let bob = Address::repeat_byte(2);
+        let token = Address::repeat_byte(0x10);
+
+        let logs = create_execution_result(0, [(U256::from(1), Query::InitialWrite(1.into()))])
+            .logs
+            .storage_logs;
+        let slot = ConflictTracker::storage_key(&logs[0]);
+        let alice_tx = transaction(alice, token);
+        let bob_tx = transaction(bob, token);
+        for tx in [&alice_tx, &bob_tx] {
+            tracker.register_access_hints(
+                tx.hash(),
+                TxAccessHints {
+                    storage_slots: vec![slot],
+                },
+            );
+        }
+        tracker.apply(&alice_tx, &logs);
+        tracker.apply(&bob_tx, &logs);
+
+        assert!(tracker.access_hints.is_empty());
+        assert_eq!(tracker.hint_scheduler.hinted_transaction_count, 2);
+        assert_eq!(tracker.hint_scheduler.wave_count, 2);
+        assert_eq!(tracker.hint_scheduler.parallelism(), Some(1.0));
+    }
+}
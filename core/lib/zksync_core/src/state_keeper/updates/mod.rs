@@ -4,20 +4,28 @@ use multivm::{
 };
 use zksync_contracts::BaseSystemContractsHashes;
 use zksync_types::{
-    block::BlockGasCount, fee_model::BatchFeeInput,
+    api::en::{RawBlock, RawTransactionResult, SyncBlock},
+    block::BlockGasCount,
+    fee_model::BatchFeeInput,
     storage_writes_deduplicator::StorageWritesDeduplicator,
-    tx::tx_execution_info::ExecutionMetrics, vm_trace::Call, Address, L1BatchNumber,
-    MiniblockNumber, ProtocolVersionId, Transaction,
+    tx::{
+        tx_execution_info::{ExecutionMetrics, TxExecutionStatus},
+        TxAccessHints,
+    },
+    vm_trace::Call,
+    Address, L1BatchNumber, MiniblockNumber, ProtocolVersionId, Transaction, H256,
 };
 use zksync_utils::bytecode::CompressedBytecodeInfo;
 
+use self::conflict_tracker::ConflictTracker;
 pub(crate) use self::{l1_batch_updates::L1BatchUpdates, miniblock_updates::MiniblockUpdates};
 use super::{
     io::{IoCursor, MiniblockParams},
-    metrics::BATCH_TIP_METRICS,
+    metrics::{BATCH_TIP_METRICS, KEEPER_METRICS},
 };
 use crate::state_keeper::types::ExecutionMetricsForCriteria;
 
+mod conflict_tracker;
 pub mod l1_batch_updates;
 pub mod miniblock_updates;
 
@@ -38,6 +46,7 @@ pub struct UpdatesManager {
     pub l1_batch: L1BatchUpdates,
     pub miniblock: MiniblockUpdates,
     pub storage_writes_deduplicator: StorageWritesDeduplicator,
+    conflict_tracker: ConflictTracker,
 }
 
 impl UpdatesManager {
@@ -59,6 +68,7 @@ impl UpdatesManager {
                 protocol_version,
             ),
             storage_writes_deduplicator: StorageWritesDeduplicator::new(),
+            conflict_tracker: ConflictTracker::new(l1_batch_env.fee_account),
         }
     }
 
@@ -98,10 +108,55 @@ impl UpdatesManager {
         }
     }
 
+    /// Builds a self-contained representation of the current miniblock served via `zks_getRawBlock`.
+    pub(crate) fn raw_block(&self, is_fictive: bool) -> RawBlock {
+        let executed_transactions = &self.miniblock.executed_transactions;
+        let block = SyncBlock {
+            number: self.miniblock.number,
+            l1_batch_number: self.l1_batch.number,
+            last_in_batch: is_fictive,
+            timestamp: self.miniblock.timestamp,
+            l1_gas_price: self.batch_fee_input.l1_gas_price(),
+            l2_fair_gas_price: self.batch_fee_input.fair_l2_gas_price(),
+            fair_pubdata_price: Some(self.batch_fee_input.fair_pubdata_price()),
+            base_system_contracts_hashes: self.base_system_contract_hashes,
+            operator_address: self.fee_account_address,
+            transactions: Some(
+                executed_transactions
+                    .iter()
+                    .map(|tx_result| tx_result.transaction.clone())
+                    .collect(),
+            ),
+            virtual_blocks: Some(self.miniblock.virtual_blocks),
+            hash: Some(self.miniblock.get_miniblock_hash()),
+            protocol_version: self.protocol_version,
+        };
+        let results = executed_transactions
+            .iter()
+            .map(|tx_result| RawTransactionResult {
+                hash: tx_result.hash,
+                success: tx_result.execution_status == TxExecutionStatus::Success,
+                refunded_gas: tx_result.refunded_gas,
+                revert_reason: tx_result.revert_reason.clone(),
+            })
+            .collect();
+        RawBlock {
+            block,
+            results,
+            events: self.miniblock.events.clone(),
+        }
+    }
+
     pub(crate) fn protocol_version(&self) -> ProtocolVersionId {
         self.protocol_version
     }
 
+    /// Registers access hints for a transaction that will be added via [`Self::extend_from_executed_transaction()`].
+    /// Hints are only used to measure the efficiency of hint-based transaction scheduling.
+    pub(crate) fn register_access_hints(&mut self, tx_hash: H256, hints: TxAccessHints) {
+        self.conflict_tracker.register_access_hints(tx_hash, hints);
+    }
+
     pub(crate) fn extend_from_executed_transaction(
         &mut self,
         tx: Transaction,
@@ -113,6 +168,10 @@ impl UpdatesManager {
     ) {
         self.storage_writes_deduplicator
             .apply(&tx_execution_result.logs.storage_logs);
+        let conflict = self
+            .conflict_tracker
+            .apply(&tx, &tx_execution_result.logs.storage_logs);
+        KEEPER_METRICS.tx_storage_conflicts[&conflict].inc();
         self.miniblock.extend_from_executed_transaction(
             tx,
             tx_execution_result,
@@ -143,6 +202,7 @@ impl UpdatesManager {
             batch_tip_metrics.l1_gas,
             batch_tip_metrics.execution_metrics,
         );
+        self.conflict_tracker.report(self.l1_batch.number);
         self.l1_batch.finished = Some(finished_batch);
     }
 
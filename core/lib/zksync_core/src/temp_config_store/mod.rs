@@ -9,6 +9,7 @@ use zksync_config::{
         fri_prover_group::FriProverGroupConfig,
         house_keeper::HouseKeeperConfig,
         wallets::{AddressWallet, EthSender, StateKeeper, Wallet, Wallets},
+        BatchExporterConfig, CallTracesOffloaderConfig, ChangeStreamConfig,
         FriProofCompressorConfig, FriProverConfig, FriProverGatewayConfig,
         FriWitnessGeneratorConfig, FriWitnessVectorGeneratorConfig, GeneralConfig,
         ObservabilityConfig, PrometheusConfig, ProofDataHandlerConfig, WitnessGeneratorConfig,
@@ -65,6 +66,9 @@ pub struct TempConfigStore {
     pub object_store_config: Option<ObjectStoreConfig>,
     pub observability: Option<ObservabilityConfig>,
     pub snapshot_creator: Option<SnapshotsCreatorConfig>,
+    pub batch_exporter: Option<BatchExporterConfig>,
+    pub call_traces_offloader: Option<CallTracesOffloaderConfig>,
+    pub change_stream: Option<ChangeStreamConfig>,
 }
 
 #[derive(Debug)]
@@ -110,6 +114,9 @@ impl TempConfigStore {
             eth: self.eth_sender_config.clone(),
             snapshot_creator: self.snapshot_creator.clone(),
             observability: self.observability.clone(),
+            batch_exporter: self.batch_exporter.clone(),
+            call_traces_offloader: self.call_traces_offloader.clone(),
+            change_stream: self.change_stream.clone(),
         }
     }
 
@@ -0,0 +1,570 @@
+//! Transaction filters allowing to apply sequencer policies (e.g., address screening) to L2 transactions.
+
+use std::{
+    collections::HashSet,
+    fmt,
+    ops::Range,
+    path::PathBuf,
+    str::FromStr,
+    sync::{Arc, RwLock},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Context as _;
+use tokio::sync::watch;
+use vise::{Counter, EncodeLabelSet, EncodeLabelValue, Family, Gauge, Metrics};
+use zksync_config::configs::chain::MempoolConfig;
+use zksync_system_constants::CONTRACT_DEPLOYER_ADDRESS;
+use zksync_types::{Address, Transaction};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "stage", rename_all = "snake_case")]
+pub(crate) enum TxFilterStage {
+    /// Transaction submission via the API server.
+    Api,
+    /// Transaction execution in the state keeper.
+    StateKeeper,
+}
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "tx_filter")]
+pub(crate) struct TxFilterMetrics {
+    /// Number of transactions rejected by the transaction filter.
+    pub rejected: Family<TxFilterStage, Counter>,
+    /// Number of times transactions were deferred by the transaction filter.
+    pub deferred: Family<TxFilterStage, Counter>,
+    /// Number of addresses in the deny list.
+    deny_list_size: Gauge<usize>,
+    /// Number of failed deny list reloads.
+    deny_list_reload_errors: Counter,
+}
+
+#[vise::register]
+pub(crate) static TX_FILTER_METRICS: vise::Global<TxFilterMetrics> = vise::Global::new();
+
+/// Reason for a transaction not being admitted by a [`TransactionFilter`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterRejection {
+    /// Transaction is denied and should be rejected.
+    Denied(String),
+    /// Transaction cannot be admitted at the moment, but may be admitted later (e.g., once a time window ends).
+    /// Such a transaction is accepted by the API server and kept in the mempool until it's admitted.
+    RetryLater(String),
+}
+
+impl fmt::Display for FilterRejection {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Denied(reason) | Self::RetryLater(reason) => formatter.write_str(reason),
+        }
+    }
+}
+
+/// Filter for L2 transactions applied both when transactions are submitted to the API server, and when the state keeper
+/// takes them from the mempool. Allows to implement sequencer policies in a single place.
+///
+/// The filter is never applied to L1 transactions, since they cannot be rejected by the sequencer.
+pub trait TransactionFilter: fmt::Debug + Send + Sync + 'static {
+    /// Checks whether the transaction is allowed. If it isn't, returns the reason for not admitting it.
+    fn check(&self, tx: &Transaction) -> Result<(), FilterRejection>;
+}
+
+/// Filter allowing all transactions.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AllowAllFilter;
+
+impl TransactionFilter for AllowAllFilter {
+    fn check(&self, _tx: &Transaction) -> Result<(), FilterRejection> {
+        Ok(())
+    }
+}
+
+/// Filter combining multiple filters. A transaction is allowed only if it is allowed by all filters in the chain;
+/// the rejection is taken from the first filter not admitting the transaction. An empty chain allows all transactions.
+#[derive(Debug, Default)]
+pub struct FilterChain {
+    filters: Vec<Box<dyn TransactionFilter>>,
+}
+
+impl FilterChain {
+    /// Appends a filter to this chain.
+    pub fn push(&mut self, filter: impl TransactionFilter) {
+        self.filters.push(Box::new(filter));
+    }
+
+    /// Builds the chain of filters configured in the mempool config. If a deny list is configured, also returns
+    /// the updater that should be run in order to periodically reload the list.
+    pub async fn from_config(
+        config: &MempoolConfig,
+    ) -> anyhow::Result<(Self, Option<DenyListUpdater>)> {
+        let mut filters = Self::default();
+        let mut deny_list_updater = None;
+        if let Some(deny_list) = &config.tx_deny_list {
+            let source = deny_list.parse().context("invalid tx_deny_list")?;
+            let (filter, updater) =
+                DenyListFilter::load(source, config.tx_deny_list_reload_interval())
+                    .await
+                    .context("failed loading transaction deny list")?;
+            deny_list_updater = Some(updater);
+            filters.push(filter);
+        }
+        if !config.tx_denied_calldata_prefixes.is_empty() {
+            let filter = CalldataFilter::new(&config.tx_denied_calldata_prefixes)
+                .context("invalid tx_denied_calldata_prefixes")?;
+            filters.push(filter);
+        }
+        if !config.tx_deployment_only_windows.is_empty() {
+            let filter = DeploymentOnlyWindowsFilter::new(&config.tx_deployment_only_windows)
+                .context("invalid tx_deployment_only_windows")?;
+            filters.push(filter);
+        }
+        Ok((filters, deny_list_updater))
+    }
+}
+
+impl TransactionFilter for FilterChain {
+    fn check(&self, tx: &Transaction) -> Result<(), FilterRejection> {
+        self.filters.iter().try_for_each(|filter| filter.check(tx))
+    }
+}
+
+/// Filter denying transactions with calldata starting with one of the specified prefixes (e.g., function selectors).
+#[derive(Debug, Clone)]
+pub struct CalldataFilter {
+    denied_prefixes: Vec<Vec<u8>>,
+}
+
+impl CalldataFilter {
+    /// Creates a filter from hex-encoded calldata prefixes.
+    pub fn new(denied_prefixes: &[String]) -> anyhow::Result<Self> {
+        let denied_prefixes = denied_prefixes
+            .iter()
+            .map(|prefix| {
+                let bytes = hex::decode(prefix.strip_prefix("0x").unwrap_or(prefix))
+                    .with_context(|| format!("invalid calldata prefix: {prefix}"))?;
+                anyhow::ensure!(!bytes.is_empty(), "calldata prefix must not be empty");
+                Ok(bytes)
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self { denied_prefixes })
+    }
+}
+
+impl TransactionFilter for CalldataFilter {
+    fn check(&self, tx: &Transaction) -> Result<(), FilterRejection> {
+        let calldata = &tx.execute.calldata;
+        match self
+            .denied_prefixes
+            .iter()
+            .find(|prefix| calldata.starts_with(prefix))
+        {
+            Some(prefix) => Err(FilterRejection::Denied(format!(
+                "calldata prefix 0x{} is denied",
+                hex::encode(prefix)
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Filter admitting only contract deployments during the specified time windows; other transactions are deferred
+/// until the window ends. Outside these windows, all transactions are allowed.
+#[derive(Debug, Clone)]
+pub struct DeploymentOnlyWindowsFilter {
+    /// Windows as ranges of UNIX timestamps in seconds.
+    windows: Vec<Range<u64>>,
+}
+
+impl DeploymentOnlyWindowsFilter {
+    /// Creates a filter from windows specified as `<start>-<end>` UNIX timestamps in seconds.
+    pub fn new(windows: &[String]) -> anyhow::Result<Self> {
+        let windows = windows
+            .iter()
+            .map(|window| {
+                let (start, end) = window.split_once('-').with_context(|| {
+                    format!("window `{window}` is not in the `<start>-<end>` format")
+                })?;
+                let start: u64 = start.trim().parse().context("invalid window start")?;
+                let end: u64 = end.trim().parse().context("invalid window end")?;
+                anyhow::ensure!(start < end, "window `{window}` is empty");
+                Ok(start..end)
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self { windows })
+    }
+
+    fn check_at(&self, tx: &Transaction, timestamp: u64) -> Result<(), FilterRejection> {
+        if tx.execute.contract_address == CONTRACT_DEPLOYER_ADDRESS {
+            return Ok(());
+        }
+        match self
+            .windows
+            .iter()
+            .find(|window| window.contains(&timestamp))
+        {
+            Some(window) => Err(FilterRejection::RetryLater(format!(
+                "only contract deployments are accepted until {}",
+                window.end
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl TransactionFilter for DeploymentOnlyWindowsFilter {
+    fn check(&self, tx: &Transaction) -> Result<(), FilterRejection> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+        self.check_at(tx, timestamp)
+    }
+}
+
+/// Source of the deny list for [`DenyListFilter`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum DenyListSource {
+    /// Local file.
+    File(PathBuf),
+    /// HTTP(S) URL.
+    Url(reqwest::Url),
+}
+
+impl FromStr for DenyListSource {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(if s.starts_with("http://") || s.starts_with("https://") {
+            Self::Url(s.parse().context("invalid deny list URL")?)
+        } else {
+            Self::File(s.into())
+        })
+    }
+}
+
+impl DenyListSource {
+    async fn load(&self, client: &reqwest::Client) -> anyhow::Result<String> {
+        match self {
+            Self::File(path) => tokio::fs::read_to_string(path)
+                .await
+                .with_context(|| format!("failed reading deny list from {path:?}")),
+            Self::Url(url) => {
+                let response = client
+                    .get(url.clone())
+                    .send()
+                    .await
+                    .and_then(reqwest::Response::error_for_status)
+                    .with_context(|| format!("failed fetching deny list from {url}"))?;
+                response.text().await.context("failed reading deny list")
+            }
+        }
+    }
+}
+
+/// Parses a deny list consisting of one address per line. Empty lines and comments starting with `#` are ignored.
+fn parse_deny_list(raw: &str) -> anyhow::Result<HashSet<Address>> {
+    raw.lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            (!line.is_empty()).then_some((i, line))
+        })
+        .map(|(i, line)| {
+            line.parse::<Address>()
+                .with_context(|| format!("invalid address on line {}: {line}", i + 1))
+        })
+        .collect()
+}
+
+/// Filter denying transactions initiated by or sent to addresses from a deny list. The list can be reloaded periodically
+/// using [`DenyListUpdater`].
+#[derive(Debug, Clone, Default)]
+pub struct DenyListFilter {
+    addresses: Arc<RwLock<HashSet<Address>>>,
+}
+
+impl DenyListFilter {
+    /// Creates a filter with a fixed deny list.
+    pub fn new(addresses: impl IntoIterator<Item = Address>) -> Self {
+        let this = Self::default();
+        this.replace(addresses.into_iter().collect());
+        this
+    }
+
+    /// Loads the deny list from the specified source. Returns the filter together with the updater that should be run
+    /// in order to periodically reload the list.
+    pub async fn load(
+        source: DenyListSource,
+        reload_interval: Duration,
+    ) -> anyhow::Result<(Self, DenyListUpdater)> {
+        let updater = DenyListUpdater {
+            filter: Self::default(),
+            source,
+            reload_interval,
+            client: reqwest::Client::new(),
+        };
+        let addresses = updater.load().await?;
+        updater.filter.replace(addresses);
+        Ok((updater.filter.clone(), updater))
+    }
+
+    fn replace(&self, addresses: HashSet<Address>) {
+        TX_FILTER_METRICS.deny_list_size.set(addresses.len());
+        *self.addresses.write().unwrap() = addresses;
+    }
+}
+
+impl TransactionFilter for DenyListFilter {
+    fn check(&self, tx: &Transaction) -> Result<(), FilterRejection> {
+        let addresses = self.addresses.read().unwrap();
+        let initiator = tx.initiator_account();
+        if addresses.contains(&initiator) {
+            return Err(FilterRejection::Denied(format!(
+                "initiator {initiator:?} is denied"
+            )));
+        }
+        let recipient = tx.recipient_account();
+        if addresses.contains(&recipient) {
+            return Err(FilterRejection::Denied(format!(
+                "recipient {recipient:?} is denied"
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Task periodically reloading the deny list for a [`DenyListFilter`]. If reloading fails, the previously loaded list
+/// remains in effect.
+#[derive(Debug)]
+pub struct DenyListUpdater {
+    filter: DenyListFilter,
+    source: DenyListSource,
+    reload_interval: Duration,
+    client: reqwest::Client,
+}
+
+impl DenyListUpdater {
+    async fn load(&self) -> anyhow::Result<HashSet<Address>> {
+        let raw = self.source.load(&self.client).await?;
+        parse_deny_list(&raw).context("failed parsing deny list")
+    }
+
+    /// Runs this updater until a stop signal is received.
+    pub async fn run(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        loop {
+            // Error here corresponds to a timeout w/o `stop_receiver` changed; we're OK with this.
+            tokio::time::timeout(self.reload_interval, stop_receiver.changed())
+                .await
+                .ok();
+            if *stop_receiver.borrow() {
+                break;
+            }
+
+            match self.load().await {
+                Ok(addresses) => {
+                    tracing::debug!("Reloaded deny list with {} addresses", addresses.len());
+                    self.filter.replace(addresses);
+                }
+                Err(err) => {
+                    tracing::warn!("Failed reloading deny list: {err:#}");
+                    TX_FILTER_METRICS.deny_list_reload_errors.inc();
+                }
+            }
+        }
+        tracing::info!("Stop signal received, deny list updater is shutting down");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use super::*;
+    use crate::utils::testonly::create_l2_transaction;
+
+    fn mock_tx(initiator: Address, recipient: Address) -> Transaction {
+        let mut tx = create_l2_transaction(10, 100);
+        tx.common_data.initiator_address = initiator;
+        tx.execute.contract_address = recipient;
+        tx.into()
+    }
+
+    #[test]
+    fn parsing_deny_list() {
+        let raw = "\
+            # Denied addresses\n\
+            0x0000000000000000000000000000000000000001\n\
+            \n\
+              0x0000000000000000000000000000000000000002  # trailing comment\n";
+        let addresses = parse_deny_list(raw).unwrap();
+        assert_eq!(
+            addresses,
+            HashSet::from([Address::from_low_u64_be(1), Address::from_low_u64_be(2)])
+        );
+
+        let err = parse_deny_list("0x01\nwhat").unwrap_err().to_string();
+        assert!(err.contains("line 1"), "{err}");
+    }
+
+    #[test]
+    fn parsing_deny_list_source() {
+        let source: DenyListSource = "https://example.com/deny-list.txt".parse().unwrap();
+        assert!(matches!(source, DenyListSource::Url(_)));
+        let source: DenyListSource = "/etc/deny-list.txt".parse().unwrap();
+        assert_eq!(source, DenyListSource::File("/etc/deny-list.txt".into()));
+    }
+
+    #[tokio::test]
+    async fn deny_list_filter_basics() {
+        let denied = Address::repeat_byte(0xde);
+        let allowed = Address::repeat_byte(1);
+        let filter = DenyListFilter::new([denied]);
+
+        filter.check(&mock_tx(allowed, allowed)).unwrap();
+        let err = filter
+            .check(&mock_tx(denied, allowed))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("initiator"), "{err}");
+        let err = filter
+            .check(&mock_tx(allowed, denied))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("recipient"), "{err}");
+        AllowAllFilter.check(&mock_tx(denied, denied)).unwrap();
+    }
+
+    #[test]
+    fn calldata_filter_basics() {
+        let filter = CalldataFilter::new(&["0xa9059cbb".to_owned()]).unwrap();
+        let mut tx = mock_tx(Address::repeat_byte(1), Address::repeat_byte(2));
+        tx.execute.calldata = vec![0xa9, 0x05, 0x9c, 0xbb, 0, 0];
+        let err = filter.check(&tx).unwrap_err().to_string();
+        assert!(err.contains("0xa9059cbb"), "{err}");
+        tx.execute.calldata = vec![0xa9, 0x05];
+        filter.check(&tx).unwrap();
+
+        CalldataFilter::new(&["0x".to_owned()]).unwrap_err();
+        CalldataFilter::new(&["what".to_owned()]).unwrap_err();
+    }
+
+    #[test]
+    fn deployment_only_windows_filter_basics() {
+        let filter = DeploymentOnlyWindowsFilter::new(&["100-200".to_owned()]).unwrap();
+        let tx = mock_tx(Address::repeat_byte(1), Address::repeat_byte(2));
+        let deployment = mock_tx(Address::repeat_byte(1), CONTRACT_DEPLOYER_ADDRESS);
+        filter.check_at(&tx, 99).unwrap();
+        let err = filter.check_at(&tx, 100).unwrap_err();
+        assert_matches!(&err, FilterRejection::RetryLater(reason) if reason.contains("200"));
+        filter.check_at(&deployment, 150).unwrap();
+        filter.check_at(&tx, 200).unwrap();
+
+        DeploymentOnlyWindowsFilter::new(&["200-100".to_owned()]).unwrap_err();
+        DeploymentOnlyWindowsFilter::new(&["100".to_owned()]).unwrap_err();
+    }
+
+    #[test]
+    fn filter_chain_basics() {
+        let denied = Address::repeat_byte(0xde);
+        let allowed = Address::repeat_byte(1);
+        let mut chain = FilterChain::default();
+        chain.check(&mock_tx(denied, denied)).unwrap();
+
+        chain.push(DenyListFilter::new([denied]));
+        chain.push(CalldataFilter::new(&["0x01".to_owned()]).unwrap());
+        chain.check(&mock_tx(allowed, allowed)).unwrap();
+        let err = chain
+            .check(&mock_tx(denied, allowed))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("initiator"), "{err}");
+        let mut tx = mock_tx(allowed, allowed);
+        tx.execute.calldata = vec![1, 2, 3];
+        let err = chain.check(&tx).unwrap_err().to_string();
+        assert!(err.contains("calldata"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn reloading_deny_list_from_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("deny-list.txt");
+        let denied = Address::repeat_byte(0xde);
+        tokio::fs::write(&path, format!("{denied:?}\n"))
+            .await
+            .unwrap();
+
+        let source = DenyListSource::File(path.clone());
+        let (filter, updater) = DenyListFilter::load(source, Duration::from_millis(10))
+            .await
+            .unwrap();
+        filter.check(&mock_tx(denied, denied)).unwrap_err();
+
+        let (stop_sender, stop_receiver) = watch::channel(false);
+        let updater_task = tokio::spawn(updater.run(stop_receiver));
+        let other_denied = Address::repeat_byte(0xad);
+        tokio::fs::write(&path, format!("{other_denied:?}\n"))
+            .await
+            .unwrap();
+        while filter.check(&mock_tx(denied, denied)).is_err() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        filter.check(&mock_tx(other_denied, denied)).unwrap_err();
+
+        // An invalid list should not be applied.
+        tokio::fs::write(&path, "invalid").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        filter.check(&mock_tx(other_denied, denied)).unwrap_err();
+
+        stop_sender.send_replace(true);
+        updater_task.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn building_filters_from_config() {
+        let mut config = MempoolConfig {
+            sync_interval_ms: 10,
+            sync_batch_size: 100,
+            capacity: 100,
+            stuck_tx_timeout: 0,
+            remove_stuck_txs: false,
+            delay_interval: 10,
+            tx_deny_list: None,
+            tx_deny_list_reload_interval_sec: None,
+            tx_denied_calldata_prefixes: Vec::new(),
+            tx_deployment_only_windows: Vec::new(),
+            max_l2_transactions: None,
+            score_fee_weight: None,
+            score_age_weight: None,
+            score_rejection_penalty: None,
+        };
+        let (filter, updater) = FilterChain::from_config(&config).await.unwrap();
+        assert!(filter.filters.is_empty());
+        assert!(updater.is_none());
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("deny-list.txt");
+        let denied = Address::repeat_byte(0xde);
+        tokio::fs::write(&path, format!("{denied:?}\n"))
+            .await
+            .unwrap();
+        config.tx_deny_list = Some(path.to_str().unwrap().to_owned());
+        config.tx_denied_calldata_prefixes = vec!["0xa9059cbb".to_owned()];
+
+        let (filter, updater) = FilterChain::from_config(&config).await.unwrap();
+        assert_eq!(filter.filters.len(), 2);
+        assert!(updater.is_some());
+        let allowed = Address::repeat_byte(1);
+        filter.check(&mock_tx(allowed, allowed)).unwrap();
+        filter.check(&mock_tx(denied, allowed)).unwrap_err();
+        let mut tx = mock_tx(allowed, allowed);
+        tx.execute.calldata = vec![0xa9, 0x05, 0x9c, 0xbb];
+        filter.check(&tx).unwrap_err();
+
+        config.tx_denied_calldata_prefixes = vec!["what".to_owned()];
+        let err = FilterChain::from_config(&config).await.unwrap_err();
+        assert!(
+            format!("{err:#}").contains("tx_denied_calldata_prefixes"),
+            "{err:#}"
+        );
+    }
+}
@@ -15,7 +15,7 @@ use zksync_eth_client::{CallFunctionArgs, Error as EthClientError, EthInterface}
 use zksync_l1_contract_interface::Detokenize;
 use zksync_types::{
     ethabi::{self, Address},
-    L1BatchNumber, ProtocolVersionId,
+    L1BatchNumber, MiniblockNumber, ProtocolVersionId,
 };
 
 #[cfg(test)]
@@ -123,6 +123,23 @@ pub(crate) async fn wait_for_l1_batch_with_metadata(
     }
 }
 
+/// Returns the number of the latest miniblock available in the storage. For a storage recovered from a snapshot
+/// and not containing miniblocks yet, this is the snapshot miniblock. Returns `None` if the storage is not initialized
+/// (i.e., neither genesis nor snapshot recovery was performed).
+pub(crate) async fn latest_available_miniblock(
+    storage: &mut Connection<'_, Core>,
+) -> anyhow::Result<Option<MiniblockNumber>> {
+    let sealed_miniblock_number = storage.blocks_dal().get_sealed_miniblock_number().await?;
+    if sealed_miniblock_number.is_some() {
+        return Ok(sealed_miniblock_number);
+    }
+    let snapshot_recovery = storage
+        .snapshot_recovery_dal()
+        .get_applied_snapshot_status()
+        .await?;
+    Ok(snapshot_recovery.map(|recovery| recovery.miniblock_number))
+}
+
 /// Returns the projected number of the first locally available L1 batch. The L1 batch is **not**
 /// guaranteed to be present in the storage!
 pub(crate) async fn projected_first_l1_batch(
@@ -224,7 +241,10 @@ mod tests {
     };
 
     use super::*;
-    use crate::genesis::{insert_genesis_batch, GenesisParams};
+    use crate::{
+        genesis::{insert_genesis_batch, GenesisParams},
+        utils::testonly::prepare_recovery_snapshot,
+    };
 
     #[tokio::test]
     async fn test_binary_search() {
@@ -271,6 +291,25 @@ mod tests {
         assert_eq!(l1_batch, None);
     }
 
+    #[tokio::test]
+    async fn getting_latest_available_miniblock() {
+        let pool = ConnectionPool::<Core>::test_pool().await;
+        let mut storage = pool.connection().await.unwrap();
+        let miniblock = latest_available_miniblock(&mut storage).await.unwrap();
+        assert_eq!(miniblock, None);
+        insert_genesis_batch(&mut storage, &GenesisParams::mock())
+            .await
+            .unwrap();
+        let miniblock = latest_available_miniblock(&mut storage).await.unwrap();
+        assert_eq!(miniblock, Some(MiniblockNumber(0)));
+
+        let pool = ConnectionPool::<Core>::test_pool().await;
+        let mut storage = pool.connection().await.unwrap();
+        prepare_recovery_snapshot(&mut storage, L1BatchNumber(23), MiniblockNumber(42), &[]).await;
+        let miniblock = latest_available_miniblock(&mut storage).await.unwrap();
+        assert_eq!(miniblock, Some(MiniblockNumber(42)));
+    }
+
     #[derive(Debug)]
     struct MockEthereumForCommitGenerationMode {
         retval: Vec<ethabi::Token>,
@@ -43,6 +43,8 @@ pub trait EthClient: 'static + fmt::Debug + Send + Sync {
     async fn finalized_block_number(&self) -> Result<u64, Error>;
     /// Returns scheduler verification key hash by verifier address.
     async fn scheduler_vk_hash(&self, verifier_address: Address) -> Result<H256, Error>;
+    /// Returns the timestamp of the specified L1 block.
+    async fn block_timestamp(&self, block_number: u64) -> Result<u64, Error>;
     /// Sets list of topics to return events for.
     fn set_topics(&mut self, topics: Vec<H256>);
 }
@@ -218,6 +220,14 @@ impl EthClient for EthHttpQueryClient {
         }
     }
 
+    async fn block_timestamp(&self, block_number: u64) -> Result<u64, Error> {
+        let block_id = BlockId::Number(BlockNumber::Number(block_number.into()));
+        let block = self.client.block(block_id, "watch").await?;
+        let block =
+            block.ok_or_else(|| Error::LogParse(format!("L1 block #{block_number} is missing")))?;
+        Ok(block.timestamp.as_u64())
+    }
+
     fn set_topics(&mut self, topics: Vec<H256>) {
         self.topics = topics;
     }
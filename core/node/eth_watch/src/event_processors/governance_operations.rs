@@ -0,0 +1,179 @@
+use std::{collections::HashMap, convert::TryFrom};
+
+use zksync_dal::{Connection, Core, CoreDal};
+use zksync_types::{
+    api::{GovernanceCall, GovernanceOperationKind, PendingGovernanceOperation},
+    ethabi::{decode, Contract, ParamType},
+    protocol_upgrade::GovernanceOperation,
+    web3::types::Log,
+    L1BlockNumber, H256,
+};
+
+use crate::{
+    client::{Error, EthClient},
+    event_processors::EventProcessor,
+    metrics::{PollStage, METRICS},
+};
+
+/// Listens to operation events coming from the governance contract and tracks the lifecycle of all scheduled
+/// operations (not only protocol upgrades) in the database.
+#[derive(Debug)]
+pub struct GovernanceOperationsEventProcessor {
+    transparent_operation_signature: H256,
+    shadow_operation_signature: H256,
+    operation_executed_signature: H256,
+    operation_cancelled_signature: H256,
+}
+
+impl GovernanceOperationsEventProcessor {
+    pub fn new(governance_contract: &Contract) -> Self {
+        let event_signature = |name: &str| {
+            governance_contract
+                .event(name)
+                .unwrap_or_else(|_| panic!("{name} event is missing in abi"))
+                .signature()
+        };
+        Self {
+            transparent_operation_signature: event_signature("TransparentOperationScheduled"),
+            shadow_operation_signature: event_signature("ShadowOperationScheduled"),
+            operation_executed_signature: event_signature("OperationExecuted"),
+            operation_cancelled_signature: event_signature("OperationCancelled"),
+        }
+    }
+
+    fn parse_scheduled_operation(
+        kind: GovernanceOperationKind,
+        event: Log,
+        block_timestamp: u64,
+    ) -> Result<PendingGovernanceOperation, Error> {
+        let id = event.topics[1];
+        let l1_tx_hash = event
+            .transaction_hash
+            .ok_or_else(|| Error::LogParse("Event transaction hash is missing".to_owned()))?;
+        let l1_block_number = event
+            .block_number
+            .ok_or_else(|| Error::LogParse("Event block number is missing".to_owned()))?
+            .as_u32();
+        // Both kinds of scheduling events start with the operation delay.
+        let delay = decode(&[ParamType::Uint(256)], &event.data.0)
+            .map_err(|err| Error::LogParse(format!("{err:?}")))?
+            .remove(0)
+            .into_uint()
+            .unwrap();
+        let delay = u64::try_from(delay)
+            .map_err(|_| Error::LogParse(format!("Operation delay {delay} is too large")))?;
+
+        let calls = match kind {
+            GovernanceOperationKind::Transparent => GovernanceOperation::try_from(event)
+                .map_err(|err| Error::LogParse(format!("{err:?}")))?
+                .calls
+                .into_iter()
+                .map(|call| GovernanceCall {
+                    target: call.target,
+                    value: call.value,
+                    data: call.data.into(),
+                })
+                .collect(),
+            // Shadow operations don't disclose their calls until execution.
+            GovernanceOperationKind::Shadow => vec![],
+        };
+
+        Ok(PendingGovernanceOperation {
+            id,
+            kind,
+            l1_tx_hash,
+            l1_block_number: L1BlockNumber(l1_block_number),
+            scheduled_at: block_timestamp,
+            ready_at: block_timestamp.saturating_add(delay),
+            calls,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl EventProcessor for GovernanceOperationsEventProcessor {
+    async fn process_events(
+        &mut self,
+        storage: &mut Connection<'_, Core>,
+        client: &dyn EthClient,
+        events: Vec<Log>,
+    ) -> Result<(), Error> {
+        let relevant_topics = self.relevant_topics();
+        let events: Vec<_> = events
+            .into_iter()
+            .filter(|event| relevant_topics.contains(&event.topics[0]))
+            .collect();
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        let stage_latency = METRICS.poll_eth_node[&PollStage::PersistGovernanceOperations].start();
+        let mut block_timestamps = HashMap::new();
+        for event in events {
+            if event.topics.len() < 2 {
+                return Err(Error::LogParse(format!(
+                    "Governance operation event {:?} has no operation ID",
+                    event.topics[0]
+                )));
+            }
+            let operation_id = event.topics[1];
+
+            let kind = if event.topics[0] == self.transparent_operation_signature {
+                GovernanceOperationKind::Transparent
+            } else if event.topics[0] == self.shadow_operation_signature {
+                GovernanceOperationKind::Shadow
+            } else if event.topics[0] == self.operation_executed_signature {
+                tracing::info!("Governance operation {operation_id:?} was executed");
+                storage
+                    .governance_operations_dal()
+                    .mark_operation_executed(operation_id)
+                    .await
+                    .unwrap();
+                continue;
+            } else {
+                tracing::info!("Governance operation {operation_id:?} was cancelled");
+                storage
+                    .governance_operations_dal()
+                    .mark_operation_cancelled(operation_id)
+                    .await
+                    .unwrap();
+                continue;
+            };
+
+            let block_number = event
+                .block_number
+                .ok_or_else(|| Error::LogParse("Event block number is missing".to_owned()))?
+                .as_u64();
+            let block_timestamp = match block_timestamps.get(&block_number) {
+                Some(&timestamp) => timestamp,
+                None => {
+                    let timestamp = client.block_timestamp(block_number).await?;
+                    block_timestamps.insert(block_number, timestamp);
+                    timestamp
+                }
+            };
+            let operation = Self::parse_scheduled_operation(kind, event, block_timestamp)?;
+            tracing::info!(
+                "Governance operation {operation_id:?} ({}) was scheduled; it will be ready at {}",
+                kind.as_str(),
+                operation.ready_at
+            );
+            storage
+                .governance_operations_dal()
+                .insert_scheduled_operation(&operation)
+                .await
+                .unwrap();
+        }
+        stage_latency.observe();
+        Ok(())
+    }
+
+    fn relevant_topics(&self) -> Vec<H256> {
+        vec![
+            self.transparent_operation_signature,
+            self.shadow_operation_signature,
+            self.operation_executed_signature,
+            self.operation_cancelled_signature,
+        ]
+    }
+}
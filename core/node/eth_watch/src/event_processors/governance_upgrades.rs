@@ -115,7 +115,7 @@ impl EventProcessor for GovernanceUpgradesEventProcessor {
         Ok(())
     }
 
-    fn relevant_topic(&self) -> H256 {
-        self.upgrade_proposal_signature
+    fn relevant_topics(&self) -> Vec<H256> {
+        vec![self.upgrade_proposal_signature]
     }
 }
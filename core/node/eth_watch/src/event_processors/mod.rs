@@ -5,6 +5,7 @@ use zksync_types::{web3::types::Log, H256};
 
 use crate::client::{Error, EthClient};
 
+pub mod governance_operations;
 pub mod governance_upgrades;
 pub mod priority_ops;
 pub mod upgrades;
@@ -19,6 +20,6 @@ pub trait EventProcessor: 'static + fmt::Debug + Send + Sync {
         events: Vec<Log>,
     ) -> Result<(), Error>;
 
-    /// Relevant topic which defines what events to be processed
-    fn relevant_topic(&self) -> H256;
+    /// Relevant topics which define what events to be processed
+    fn relevant_topics(&self) -> Vec<H256>;
 }
@@ -92,13 +92,18 @@ impl EventProcessor for PriorityOpsEventProcessor {
                 .insert_transaction_l1(&new_op, eth_block)
                 .await
                 .unwrap();
+            storage
+                .priority_ops_audit_dal()
+                .insert_received_event(&new_op)
+                .await
+                .unwrap();
         }
         stage_latency.observe();
         self.next_expected_priority_id = last_new.serial_id().next();
         Ok(())
     }
 
-    fn relevant_topic(&self) -> H256 {
-        self.new_priority_request_signature
+    fn relevant_topics(&self) -> Vec<H256> {
+        vec![self.new_priority_request_signature]
     }
 }
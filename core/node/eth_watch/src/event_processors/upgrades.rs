@@ -84,7 +84,7 @@ impl EventProcessor for UpgradesEventProcessor {
         Ok(())
     }
 
-    fn relevant_topic(&self) -> H256 {
-        UPGRADE_PROPOSAL_SIGNATURE
+    fn relevant_topics(&self) -> Vec<H256> {
+        vec![UPGRADE_PROPOSAL_SIGNATURE]
     }
 }
@@ -19,6 +19,7 @@ use zksync_types::{
 use self::{
     client::{Error, EthClient, EthHttpQueryClient, RETRY_LIMIT},
     event_processors::{
+        governance_operations::GovernanceOperationsEventProcessor,
         governance_upgrades::GovernanceUpgradesEventProcessor,
         priority_ops::PriorityOpsEventProcessor, upgrades::UpgradesEventProcessor, EventProcessor,
     },
@@ -78,13 +79,19 @@ impl EthWatch {
                 state.last_seen_version_id,
                 &governance_contract,
             );
-            event_processors.push(Box::new(governance_upgrades_processor))
+            event_processors.push(Box::new(governance_upgrades_processor));
+            let governance_operations_processor =
+                GovernanceOperationsEventProcessor::new(&governance_contract);
+            event_processors.push(Box::new(governance_operations_processor));
         }
 
-        let topics = event_processors
+        let mut topics: Vec<_> = event_processors
             .iter()
-            .map(|p| p.relevant_topic())
+            .flat_map(|p| p.relevant_topics())
             .collect();
+        // Several processors may be interested in the same events.
+        topics.sort_unstable();
+        topics.dedup();
         client.set_topics(topics);
 
         Self {
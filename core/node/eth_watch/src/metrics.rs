@@ -10,6 +10,7 @@ pub(super) enum PollStage {
     Request,
     PersistL1Txs,
     PersistUpgrades,
+    PersistGovernanceOperations,
 }
 
 #[derive(Debug, Metrics)]
@@ -4,12 +4,13 @@ use tokio::sync::RwLock;
 use zksync_contracts::{governance_contract, zksync_contract};
 use zksync_dal::{Connection, ConnectionPool, Core, CoreDal};
 use zksync_types::{
+    api::{GovernanceOperationKind, PriorityOpAuditEvent},
     ethabi::{encode, Hash, Token},
     l1::{L1Tx, OpProcessingType, PriorityQueueType},
     protocol_upgrade::{ProtocolUpgradeTx, ProtocolUpgradeTxCommonData},
     web3::types::{Address, BlockNumber, Log},
-    Execute, L1TxCommonData, PriorityOpId, ProtocolUpgrade, ProtocolVersion, ProtocolVersionId,
-    Transaction, H256, U256,
+    Execute, L1BlockNumber, L1TxCommonData, PriorityOpId, ProtocolUpgrade, ProtocolVersion,
+    ProtocolVersionId, Transaction, H256, U256,
 };
 
 use super::client::Error;
@@ -20,6 +21,7 @@ struct FakeEthClientData {
     transactions: HashMap<u64, Vec<Log>>,
     diamond_upgrades: HashMap<u64, Vec<Log>>,
     governance_upgrades: HashMap<u64, Vec<Log>>,
+    governance_operations: HashMap<u64, Vec<Log>>,
     last_finalized_block_number: u64,
 }
 
@@ -29,6 +31,7 @@ impl FakeEthClientData {
             transactions: Default::default(),
             diamond_upgrades: Default::default(),
             governance_upgrades: Default::default(),
+            governance_operations: Default::default(),
             last_finalized_block_number: 0,
         }
     }
@@ -61,6 +64,16 @@ impl FakeEthClientData {
         }
     }
 
+    fn add_governance_operation_logs(&mut self, logs: &[Log]) {
+        for log in logs {
+            let eth_block = log.block_number.unwrap().as_u64();
+            self.governance_operations
+                .entry(eth_block)
+                .or_default()
+                .push(log.clone());
+        }
+    }
+
     fn set_last_finalized_block_number(&mut self, number: u64) {
         self.last_finalized_block_number = number;
     }
@@ -90,6 +103,10 @@ impl FakeEthClient {
         self.inner.write().await.add_governance_upgrades(upgrades);
     }
 
+    async fn add_governance_operation_logs(&mut self, logs: &[Log]) {
+        self.inner.write().await.add_governance_operation_logs(logs);
+    }
+
     async fn set_last_finalized_block_number(&mut self, number: u64) {
         self.inner
             .write()
@@ -130,6 +147,9 @@ impl EthClient for FakeEthClient {
             if let Some(ops) = self.inner.read().await.governance_upgrades.get(&number) {
                 logs.extend_from_slice(ops);
             }
+            if let Some(ops) = self.inner.read().await.governance_operations.get(&number) {
+                logs.extend_from_slice(ops);
+            }
         }
         Ok(logs)
     }
@@ -143,6 +163,10 @@ impl EthClient for FakeEthClient {
     async fn finalized_block_number(&self) -> Result<u64, Error> {
         Ok(self.inner.read().await.last_finalized_block_number)
     }
+
+    async fn block_timestamp(&self, block_number: u64) -> Result<u64, Error> {
+        Ok(block_number * 12)
+    }
 }
 
 fn build_l1_tx(serial_id: u64, eth_block: u64) -> L1Tx {
@@ -244,6 +268,15 @@ async fn test_normal_operation_l1_txs() {
     assert_eq!(db_txs.len(), 3);
     let db_tx = db_txs[2].clone();
     assert_eq!(db_tx.common_data.serial_id.0, 2);
+
+    let audit_history = storage
+        .priority_ops_audit_dal()
+        .get_priority_op_history(PriorityOpId(2))
+        .await
+        .unwrap();
+    assert_eq!(audit_history.len(), 1);
+    assert_eq!(audit_history[0].event, PriorityOpAuditEvent::Received);
+    assert_eq!(audit_history[0].tx_hash, db_tx.hash());
 }
 
 #[tokio::test]
@@ -407,6 +440,83 @@ async fn test_normal_operation_governance_upgrades() {
     assert_eq!(tx.common_data.upgrade_id, ProtocolVersionId::next());
 }
 
+#[tokio::test]
+async fn test_tracking_governance_operations() {
+    let connection_pool = ConnectionPool::<Core>::test_pool().await;
+    setup_db(&connection_pool).await;
+
+    let mut client = FakeEthClient::new();
+    let mut watcher = EthWatch::new(
+        Address::default(),
+        Some(governance_contract()),
+        Box::new(client.clone()),
+        connection_pool.clone(),
+        std::time::Duration::from_nanos(1),
+    )
+    .await;
+
+    let mut storage = connection_pool.connection().await.unwrap();
+    let transparent_id = H256::repeat_byte(1);
+    let shadow_id = H256::repeat_byte(2);
+    client
+        .add_governance_operation_logs(&[
+            scheduled_operation_log("TransparentOperationScheduled", transparent_id, 1_000, 10),
+            scheduled_operation_log("ShadowOperationScheduled", shadow_id, 100, 12),
+        ])
+        .await;
+    client.set_last_finalized_block_number(15).await;
+    watcher.loop_iteration(&mut storage).await.unwrap();
+
+    let pending = storage
+        .governance_operations_dal()
+        .get_pending_operations()
+        .await
+        .unwrap();
+    assert_eq!(pending.len(), 2);
+    assert_eq!(pending[0].id, shadow_id);
+    assert_eq!(pending[0].kind, GovernanceOperationKind::Shadow);
+    assert_eq!(pending[0].scheduled_at, 12 * 12);
+    assert_eq!(pending[0].ready_at, 12 * 12 + 100);
+    assert!(pending[0].calls.is_empty());
+    assert_eq!(pending[1].id, transparent_id);
+    assert_eq!(pending[1].kind, GovernanceOperationKind::Transparent);
+    assert_eq!(pending[1].l1_block_number, L1BlockNumber(10));
+    assert_eq!(pending[1].ready_at, 10 * 12 + 1_000);
+    assert_eq!(pending[1].calls.len(), 1);
+    assert_eq!(pending[1].calls[0].target, Address::repeat_byte(0x42));
+
+    client
+        .add_governance_operation_logs(&[operation_status_log("OperationExecuted", shadow_id, 18)])
+        .await;
+    client.set_last_finalized_block_number(20).await;
+    watcher.loop_iteration(&mut storage).await.unwrap();
+
+    let pending = storage
+        .governance_operations_dal()
+        .get_pending_operations()
+        .await
+        .unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].id, transparent_id);
+
+    client
+        .add_governance_operation_logs(&[operation_status_log(
+            "OperationCancelled",
+            transparent_id,
+            22,
+        )])
+        .await;
+    client.set_last_finalized_block_number(25).await;
+    watcher.loop_iteration(&mut storage).await.unwrap();
+
+    let pending = storage
+        .governance_operations_dal()
+        .get_pending_operations()
+        .await
+        .unwrap();
+    assert!(pending.is_empty());
+}
+
 #[tokio::test]
 #[should_panic]
 async fn test_gap_in_single_batch() {
@@ -525,7 +635,7 @@ async fn get_all_db_txs(storage: &mut Connection<'_, Core>) -> Vec<Transaction>
     storage.transactions_dal().reset_mempool().await.unwrap();
     storage
         .transactions_dal()
-        .sync_mempool(&[], &[], 0, 0, 1000)
+        .sync_mempool(&[], &[], &[], 0, 0, 1000)
         .await
         .unwrap()
 }
@@ -645,6 +755,51 @@ fn upgrade_into_governor_log(upgrade: ProtocolUpgrade, eth_block: u64) -> Log {
     }
 }
 
+fn governance_log(event_name: &str, id: H256, data: Vec<u8>, eth_block: u64) -> Log {
+    Log {
+        address: Address::repeat_byte(0x1),
+        topics: vec![
+            governance_contract()
+                .event(event_name)
+                .unwrap_or_else(|_| panic!("{event_name} event is missing in abi"))
+                .signature(),
+            id,
+        ],
+        data: data.into(),
+        block_hash: Some(H256::repeat_byte(0x11)),
+        block_number: Some(eth_block.into()),
+        transaction_hash: Some(H256::random()),
+        transaction_index: Some(0u64.into()),
+        log_index: Some(0u64.into()),
+        transaction_log_index: Some(0u64.into()),
+        log_type: None,
+        removed: None,
+    }
+}
+
+fn scheduled_operation_log(event_name: &str, id: H256, delay: u64, eth_block: u64) -> Log {
+    let data = if event_name == "TransparentOperationScheduled" {
+        let governance_call = Token::Tuple(vec![
+            Token::Address(Address::repeat_byte(0x42)),
+            Token::Uint(U256::zero()),
+            Token::Bytes(vec![1, 2, 3, 4]),
+        ]);
+        let governance_operation = Token::Tuple(vec![
+            Token::Array(vec![governance_call]),
+            Token::FixedBytes(vec![0u8; 32]),
+            Token::FixedBytes(vec![0u8; 32]),
+        ]);
+        encode(&[Token::Uint(delay.into()), governance_operation])
+    } else {
+        encode(&[Token::Uint(delay.into())])
+    };
+    governance_log(event_name, id, data, eth_block)
+}
+
+fn operation_status_log(event_name: &str, id: H256, eth_block: u64) -> Log {
+    governance_log(event_name, id, vec![], eth_block)
+}
+
 fn upgrade_into_diamond_cut(upgrade: ProtocolUpgrade) -> Token {
     let tx_data_token = if let Some(tx) = upgrade.tx {
         Token::Tuple(vec![
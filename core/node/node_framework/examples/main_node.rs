@@ -25,6 +25,7 @@ use zksync_core::{
     },
     consensus,
     metadata_calculator::MetadataCalculatorConfig,
+    state_keeper::isolated_batch_executor,
     temp_config_store::decode_yaml,
 };
 use zksync_env_config::FromEnv;
@@ -82,6 +83,7 @@ impl MainNodeBuilder {
             .with_master(true)
             .with_replica(true)
             .with_prover(true)
+            .with_subsystem_pools(true)
             .build();
         self.node.add_layer(pools_layer);
         Ok(self)
@@ -95,7 +97,7 @@ impl MainNodeBuilder {
             eth_config,
             ContractsConfig::from_env()?,
             genesis.l1_chain_id,
-            wallets.eth_sender.context("Eth sender configs")?,
+            wallets.eth_sender,
         ));
         Ok(self)
     }
@@ -240,6 +242,7 @@ impl MainNodeBuilder {
             filters_limit: Some(rpc_config.filters_limit()),
             subscriptions_limit: Some(rpc_config.subscriptions_limit()),
             batch_request_size_limit: Some(rpc_config.max_batch_request_size()),
+            batch_request_cost_limit: Some(rpc_config.max_batch_request_cost()),
             response_body_size_limit: Some(rpc_config.max_response_body_size()),
             ..Default::default()
         };
@@ -271,6 +274,7 @@ impl MainNodeBuilder {
             filters_limit: Some(rpc_config.filters_limit()),
             subscriptions_limit: Some(rpc_config.subscriptions_limit()),
             batch_request_size_limit: Some(rpc_config.max_batch_request_size()),
+            batch_request_cost_limit: Some(rpc_config.max_batch_request_cost()),
             response_body_size_limit: Some(rpc_config.max_response_body_size()),
             websocket_requests_per_minute_limit: Some(
                 rpc_config.websocket_requests_per_minute_limit(),
@@ -297,7 +301,7 @@ impl MainNodeBuilder {
             contracts_config,
             network_config,
             genesis_config.l1_chain_id,
-            wallets.eth_sender.context("Eth sender wallets")?,
+            wallets.eth_sender,
             genesis_config.l1_batch_commit_data_generator_mode,
         ));
 
@@ -381,6 +385,11 @@ impl MainNodeBuilder {
 }
 
 fn main() -> anyhow::Result<()> {
+    // Must be checked before initializing logging: a worker uses stdout for IPC.
+    if isolated_batch_executor::is_worker_process() {
+        isolated_batch_executor::run_worker_process();
+    }
+
     let observability_config =
         ObservabilityConfig::from_env().context("ObservabilityConfig::from_env()")?;
     let log_format: vlog::LogFormat = observability_config
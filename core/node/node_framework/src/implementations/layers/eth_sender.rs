@@ -7,14 +7,16 @@ use zksync_config::configs::{
     eth_sender::ETHConfig,
     wallets, ContractsConfig,
 };
-use zksync_core::eth_sender::{
-    l1_batch_commit_data_generator::{
-        L1BatchCommitDataGenerator, RollupModeL1BatchCommitDataGenerator,
-        ValidiumModeL1BatchCommitDataGenerator,
+use zksync_core::{
+    create_eth_sender_clients,
+    eth_sender::{
+        l1_batch_commit_data_generator::{
+            L1BatchCommitDataGenerator, RollupModeL1BatchCommitDataGenerator,
+            ValidiumModeL1BatchCommitDataGenerator,
+        },
+        Aggregator, EthTxAggregator, EthTxManager,
     },
-    Aggregator, EthTxAggregator, EthTxManager,
 };
-use zksync_eth_client::{clients::PKSigningClient, BoundEthInterface};
 use zksync_types::L1ChainId;
 
 use crate::{
@@ -36,7 +38,8 @@ pub struct EthSenderLayer {
     contracts_config: ContractsConfig,
     network_config: NetworkConfig,
     l1chain_id: L1ChainId,
-    wallets: wallets::EthSender,
+    /// Operator wallets; not required if the remote signer is configured.
+    wallets: Option<wallets::EthSender>,
     l1_batch_commit_data_generator_mode: L1BatchCommitDataGeneratorMode,
 }
 
@@ -46,7 +49,7 @@ impl EthSenderLayer {
         contracts_config: ContractsConfig,
         network_config: NetworkConfig,
         l1chain_id: L1ChainId,
-        wallets: wallets::EthSender,
+        wallets: Option<wallets::EthSender>,
         l1_batch_commit_data_generator_mode: L1BatchCommitDataGeneratorMode,
     ) -> Self {
         Self {
@@ -80,15 +83,15 @@ impl WiringLayer for EthSenderLayer {
 
         // Create and add tasks.
 
-        let eth_client_blobs = self.wallets.blob_operator.map(|wallet| {
-            PKSigningClient::from_config(
-                &self.eth_sender_config,
-                &self.contracts_config,
-                self.l1chain_id,
-                wallet.private_key(),
-            )
-        });
-        let eth_client_blobs_addr = eth_client_blobs.clone().map(|k| k.sender_account());
+        let (_, eth_client_blobs) = create_eth_sender_clients(
+            &self.eth_sender_config,
+            self.wallets.as_ref(),
+            self.contracts_config.diamond_proxy_addr,
+            self.l1chain_id,
+        )?;
+        let eth_client_blobs_addr = eth_client_blobs
+            .as_ref()
+            .map(|client| client.sender_account());
 
         let l1_batch_commit_data_generator: Arc<dyn L1BatchCommitDataGenerator> =
             match self.l1_batch_commit_data_generator_mode {
@@ -100,12 +103,15 @@ impl WiringLayer for EthSenderLayer {
                 }
             };
 
+        let gas_adjuster = context.get_resource::<L1TxParamsResource>().await?.0;
+
         let config = self.eth_sender_config.sender.context("sender")?;
         let aggregator = Aggregator::new(
             config.clone(),
             object_store,
             eth_client_blobs_addr.is_some(),
             l1_batch_commit_data_generator.clone(),
+            gas_adjuster.clone(),
         );
 
         let eth_tx_aggregator_actor = EthTxAggregator::new(
@@ -126,14 +132,12 @@ impl WiringLayer for EthSenderLayer {
             eth_tx_aggregator_actor,
         }));
 
-        let gas_adjuster = context.get_resource::<L1TxParamsResource>().await?.0;
-
         let eth_tx_manager_actor = EthTxManager::new(
             master_pool,
             config,
             gas_adjuster,
             eth_client,
-            eth_client_blobs.map(|c| Arc::new(c) as Arc<dyn BoundEthInterface>),
+            eth_client_blobs,
         );
 
         context.add_task(Box::new(EthTxManagerTask {
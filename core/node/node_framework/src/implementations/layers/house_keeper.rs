@@ -20,7 +20,9 @@ use zksync_core::house_keeper::{
 use zksync_dal::{metrics::PostgresMetrics, ConnectionPool, Core};
 
 use crate::{
-    implementations::resources::pools::{ProverPoolResource, ReplicaPoolResource},
+    implementations::resources::pools::{
+        PoolSubsystem, ProverPoolResource, SubsystemPoolsResource,
+    },
     service::{ServiceContext, StopReceiver},
     task::Task,
     wiring_layer::{WiringError, WiringLayer},
@@ -63,8 +65,11 @@ impl WiringLayer for HouseKeeperLayer {
 
     async fn wire(self: Box<Self>, mut context: ServiceContext<'_>) -> Result<(), WiringError> {
         // initialize resources
-        let replica_pool_resource = context.get_resource::<ReplicaPoolResource>().await?;
-        let replica_pool = replica_pool_resource.get().await?;
+        let replica_pool = context
+            .get_resource::<SubsystemPoolsResource>()
+            .await?
+            .get(PoolSubsystem::Housekeeping)
+            .await?;
 
         let prover_pool_resource = context.get_resource::<ProverPoolResource>().await?;
         let prover_pool = prover_pool_resource.get().await?;
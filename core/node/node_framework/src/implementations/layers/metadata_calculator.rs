@@ -4,8 +4,9 @@ use zksync_storage::RocksDB;
 
 use crate::{
     implementations::resources::{
-        healthcheck::AppHealthCheckResource, object_store::ObjectStoreResource,
-        pools::MasterPoolResource,
+        healthcheck::AppHealthCheckResource,
+        object_store::ObjectStoreResource,
+        pools::{PoolSubsystem, SubsystemPoolsResource},
     },
     service::{ServiceContext, StopReceiver},
     task::Task,
@@ -16,7 +17,7 @@ use crate::{
 ///
 /// ## Effects
 ///
-/// - Resolves `MasterPoolResource`.
+/// - Resolves `SubsystemPoolsResource` (uses the metadata calculator pool).
 /// - Resolves `ObjectStoreResource` (optional).
 /// - Adds `tree_health_check` to the `ResourceCollection<HealthCheckResource>`.
 /// - Adds `metadata_calculator` to the node.
@@ -36,8 +37,11 @@ impl WiringLayer for MetadataCalculatorLayer {
     }
 
     async fn wire(self: Box<Self>, mut context: ServiceContext<'_>) -> Result<(), WiringError> {
-        let pool = context.get_resource::<MasterPoolResource>().await?;
-        let main_pool = pool.get().await.unwrap();
+        let main_pool = context
+            .get_resource::<SubsystemPoolsResource>()
+            .await?
+            .get(PoolSubsystem::MetadataCalculator)
+            .await?;
         let object_store = context.get_resource::<ObjectStoreResource>().await.ok(); // OK to be None.
 
         if object_store.is_none() {
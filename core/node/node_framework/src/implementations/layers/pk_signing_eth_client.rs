@@ -1,10 +1,8 @@
-use std::sync::Arc;
-
 use zksync_config::{
     configs::{wallets, ContractsConfig},
     ETHConfig,
 };
-use zksync_eth_client::clients::PKSigningClient;
+use zksync_core::create_eth_sender_clients;
 use zksync_types::L1ChainId;
 
 use crate::{
@@ -13,12 +11,14 @@ use crate::{
     wiring_layer::{WiringError, WiringLayer},
 };
 
+/// Wires the signing L1 client for the operator account. Transactions are signed by the remote signer
+/// if it is configured, and with the operator private key from `wallets` otherwise.
 #[derive(Debug)]
 pub struct PKSigningEthClientLayer {
     eth_sender_config: ETHConfig,
     contracts_config: ContractsConfig,
     l1chain_id: L1ChainId,
-    wallets: wallets::EthSender,
+    wallets: Option<wallets::EthSender>,
 }
 
 impl PKSigningEthClientLayer {
@@ -26,7 +26,7 @@ impl PKSigningEthClientLayer {
         eth_sender_config: ETHConfig,
         contracts_config: ContractsConfig,
         l1chain_id: L1ChainId,
-        wallets: wallets::EthSender,
+        wallets: Option<wallets::EthSender>,
     ) -> Self {
         Self {
             eth_sender_config,
@@ -44,15 +44,13 @@ impl WiringLayer for PKSigningEthClientLayer {
     }
 
     async fn wire(self: Box<Self>, mut context: ServiceContext<'_>) -> Result<(), WiringError> {
-        let private_key = self.wallets.operator.private_key();
-
-        let signing_client = PKSigningClient::from_config(
+        let (signing_client, _) = create_eth_sender_clients(
             &self.eth_sender_config,
-            &self.contracts_config,
+            self.wallets.as_ref(),
+            self.contracts_config.diamond_proxy_addr,
             self.l1chain_id,
-            private_key,
-        );
-        context.insert_resource(BoundEthInterfaceResource(Arc::new(signing_client)))?;
+        )?;
+        context.insert_resource(BoundEthInterfaceResource(signing_client))?;
         Ok(())
     }
 }
@@ -1,10 +1,12 @@
 use prover_dal::Prover;
 use zksync_config::configs::PostgresConfig;
 use zksync_dal::{ConnectionPool, Core};
+use zksync_db_connection::connection_pool::ConnectionPoolBuilder;
 
 use crate::{
     implementations::resources::pools::{
-        MasterPoolResource, ProverPoolResource, ReplicaPoolResource,
+        MasterPoolResource, PoolSubsystem, ProverPoolResource, ReplicaPoolResource,
+        SubsystemPoolsResource,
     },
     service::ServiceContext,
     wiring_layer::{WiringError, WiringLayer},
@@ -16,6 +18,7 @@ pub struct PoolsLayerBuilder {
     with_master: bool,
     with_replica: bool,
     with_prover: bool,
+    with_subsystem_pools: bool,
 }
 
 impl PoolsLayerBuilder {
@@ -25,6 +28,7 @@ impl PoolsLayerBuilder {
             with_master: false,
             with_replica: false,
             with_prover: false,
+            with_subsystem_pools: false,
         }
     }
 
@@ -43,12 +47,19 @@ impl PoolsLayerBuilder {
         self
     }
 
+    /// Enables pools dedicated to node subsystems; see [`SubsystemPoolsResource`].
+    pub fn with_subsystem_pools(mut self, with_subsystem_pools: bool) -> Self {
+        self.with_subsystem_pools = with_subsystem_pools;
+        self
+    }
+
     pub fn build(self) -> PoolsLayer {
         PoolsLayer {
             config: self.config,
             with_master: self.with_master,
             with_replica: self.with_replica,
             with_prover: self.with_prover,
+            with_subsystem_pools: self.with_subsystem_pools,
         }
     }
 }
@@ -59,6 +70,44 @@ pub struct PoolsLayer {
     with_master: bool,
     with_replica: bool,
     with_prover: bool,
+    with_subsystem_pools: bool,
+}
+
+impl PoolsLayer {
+    fn subsystem_pool_builder(
+        &self,
+        subsystem: PoolSubsystem,
+    ) -> anyhow::Result<ConnectionPoolBuilder<Core>> {
+        let config = &self.config;
+        let max_connections = match subsystem {
+            PoolSubsystem::Api => config.max_connections_api,
+            PoolSubsystem::StateKeeper => config.max_connections_state_keeper,
+            PoolSubsystem::MetadataCalculator => config.max_connections_metadata_calculator,
+            PoolSubsystem::Housekeeping => config.max_connections_housekeeping,
+        };
+        let max_connections = match max_connections {
+            Some(max_connections) => max_connections,
+            None => config.max_connections()?,
+        };
+
+        Ok(match subsystem {
+            PoolSubsystem::Api => {
+                let mut builder =
+                    ConnectionPool::<Core>::builder(config.replica_url()?, max_connections);
+                builder
+                    .set_acquire_timeout(config.acquire_timeout())
+                    .set_statement_timeout(config.statement_timeout())
+                    .set_replicas(config.read_replica_urls.clone(), config.max_replica_lag());
+                builder
+            }
+            PoolSubsystem::Housekeeping => {
+                ConnectionPool::<Core>::builder(config.replica_url()?, max_connections)
+            }
+            PoolSubsystem::StateKeeper | PoolSubsystem::MetadataCalculator => {
+                ConnectionPool::<Core>::builder(config.master_url()?, max_connections)
+            }
+        })
+    }
 }
 
 #[async_trait::async_trait]
@@ -68,7 +117,11 @@ impl WiringLayer for PoolsLayer {
     }
 
     async fn wire(self: Box<Self>, mut context: ServiceContext<'_>) -> Result<(), WiringError> {
-        if !self.with_master && !self.with_replica && !self.with_prover {
+        if !self.with_master
+            && !self.with_replica
+            && !self.with_prover
+            && !self.with_subsystem_pools
+        {
             return Err(WiringError::Configuration(
                 "At least one pool should be enabled".to_string(),
             ));
@@ -101,6 +154,14 @@ impl WiringLayer for PoolsLayer {
             context.insert_resource(ProverPoolResource::new(prover_pool))?;
         }
 
+        if self.with_subsystem_pools {
+            let builders = PoolSubsystem::ALL
+                .into_iter()
+                .map(|subsystem| Ok((subsystem, self.subsystem_pool_builder(subsystem)?)))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            context.insert_resource(SubsystemPoolsResource::new(builders))?;
+        }
+
         Ok(())
     }
 }
@@ -4,7 +4,10 @@ use zksync_config::{configs::chain::StateKeeperConfig, DBConfig};
 use zksync_core::state_keeper::{AsyncCatchupTask, AsyncRocksdbCache, MainBatchExecutor};
 
 use crate::{
-    implementations::resources::{pools::MasterPoolResource, state_keeper::BatchExecutorResource},
+    implementations::resources::{
+        pools::{MasterPoolResource, PoolSubsystem, SubsystemPoolsResource},
+        state_keeper::BatchExecutorResource,
+    },
     resource::Unique,
     service::{ServiceContext, StopReceiver},
     task::Task,
@@ -33,18 +36,31 @@ impl WiringLayer for MainBatchExecutorLayer {
     }
 
     async fn wire(self: Box<Self>, mut context: ServiceContext<'_>) -> Result<(), WiringError> {
-        let master_pool = context.get_resource::<MasterPoolResource>().await?;
+        let state_keeper_pool = context
+            .get_resource::<SubsystemPoolsResource>()
+            .await?
+            .get(PoolSubsystem::StateKeeper)
+            .await?;
 
         let (storage_factory, task) = AsyncRocksdbCache::new(
-            master_pool.get_singleton().await?,
+            state_keeper_pool,
             self.db_config.state_keeper_db_path,
             self.state_keeper_config.enum_index_migration_chunk_size(),
         );
-        let builder = MainBatchExecutor::new(
+        let mut builder = MainBatchExecutor::new(
             Arc::new(storage_factory),
             self.state_keeper_config.save_call_traces,
             false,
-        );
+        )
+        .with_process_isolation(self.state_keeper_config.batch_executor_process_isolation());
+        if self.state_keeper_config.shadow_vm_execution() {
+            let shadow_vm_pool = context
+                .get_resource::<MasterPoolResource>()
+                .await?
+                .get_singleton()
+                .await?;
+            builder = builder.with_shadow_vm_execution(shadow_vm_pool);
+        }
 
         context.insert_resource(BatchExecutorResource(Unique::new(Box::new(builder))))?;
         context.add_task(Box::new(RocksdbCatchupTask(task)));
@@ -8,16 +8,24 @@ use zksync_config::{
     },
     ContractsConfig,
 };
-use zksync_core::state_keeper::{
-    self, MempoolFetcher, MempoolGuard, MempoolIO, OutputHandler, SequencerSealer,
-    StateKeeperPersistence,
+use zksync_core::{
+    state_keeper::{
+        self, seal_criteria::SealCriterion, ClockSkewMonitor, FeeAccountSelector, MempoolFetcher,
+        MempoolGuard, MempoolIO, OperatorTxProvider, OutputHandler, RawBlocksPersistence,
+        SequencerSealer, StateKeeperPersistence,
+    },
+    tx_filter::{DenyListUpdater, FilterChain, TransactionFilter},
 };
+use zksync_dal::{ConnectionPool, Core};
 
 use crate::{
     implementations::resources::{
         fee_input::FeeInputResource,
-        pools::MasterPoolResource,
-        state_keeper::{ConditionalSealerResource, OutputHandlerResource, StateKeeperIOResource},
+        pools::{PoolSubsystem, SubsystemPoolsResource},
+        state_keeper::{
+            ConditionalSealerResource, OutputHandlerResource, StateKeeperIOResource,
+            TxFilterResource,
+        },
     },
     resource::Unique,
     service::{ServiceContext, StopReceiver},
@@ -32,6 +40,8 @@ pub struct MempoolIOLayer {
     state_keeper_config: StateKeeperConfig,
     mempool_config: MempoolConfig,
     wallets: wallets::StateKeeper,
+    seal_criteria: Vec<Box<dyn SealCriterion>>,
+    operator_tx_provider: Option<Arc<dyn OperatorTxProvider>>,
 }
 
 impl MempoolIOLayer {
@@ -48,22 +58,32 @@ impl MempoolIOLayer {
             state_keeper_config,
             mempool_config,
             wallets,
+            seal_criteria: Vec::new(),
+            operator_tx_provider: None,
         }
     }
 
+    /// Registers a custom seal criterion in addition to the built-in ones.
+    pub fn with_seal_criterion(mut self, criterion: Box<dyn SealCriterion>) -> Self {
+        self.seal_criteria.push(criterion);
+        self
+    }
+
+    /// Sets the provider of operator-defined transactions executed at the start of each miniblock.
+    pub fn with_operator_tx_provider(mut self, provider: Arc<dyn OperatorTxProvider>) -> Self {
+        self.operator_tx_provider = Some(provider);
+        self
+    }
+
     async fn build_mempool_guard(
         &self,
-        master_pool: &MasterPoolResource,
+        connection_pool: &ConnectionPool<Core>,
     ) -> anyhow::Result<MempoolGuard> {
-        let connection_pool = master_pool
-            .get_singleton()
-            .await
-            .context("Get connection pool")?;
         let mut storage = connection_pool
             .connection()
             .await
             .context("Access storage to build mempool")?;
-        let mempool = MempoolGuard::from_storage(&mut storage, self.mempool_config.capacity).await;
+        let mempool = MempoolGuard::from_storage(&mut storage, &self.mempool_config).await;
         mempool.register_metrics();
         Ok(mempool)
     }
@@ -78,54 +98,86 @@ impl WiringLayer for MempoolIOLayer {
     async fn wire(self: Box<Self>, mut context: ServiceContext<'_>) -> Result<(), WiringError> {
         // Fetch required resources.
         let batch_fee_input_provider = context.get_resource::<FeeInputResource>().await?.0;
-        let master_pool = context.get_resource::<MasterPoolResource>().await?;
+        let state_keeper_pool = context
+            .get_resource::<SubsystemPoolsResource>()
+            .await?
+            .get(PoolSubsystem::StateKeeper)
+            .await
+            .context("Get state keeper pool")?;
 
         // Create miniblock sealer task.
-        let (persistence, miniblock_sealer) = StateKeeperPersistence::new(
-            master_pool
-                .get_singleton()
-                .await
-                .context("Get master pool")?,
+        let (mut persistence, miniblock_sealer) = StateKeeperPersistence::new(
+            state_keeper_pool.clone(),
             self.contracts_config.l2_erc20_bridge_addr,
             self.state_keeper_config.miniblock_seal_queue_capacity,
         );
-        let output_handler = OutputHandler::new(Box::new(persistence));
+        if !self
+            .state_keeper_config
+            .protective_reads_persistence_enabled()
+        {
+            persistence = persistence.without_protective_reads();
+        }
+        let (raw_blocks, raw_blocks_persister) = RawBlocksPersistence::new(
+            state_keeper_pool.clone(),
+            self.state_keeper_config.miniblock_seal_queue_capacity,
+        );
+        let output_handler =
+            OutputHandler::new(Box::new(persistence)).with_handler(Box::new(raw_blocks));
         context.insert_resource(OutputHandlerResource(Unique::new(output_handler)))?;
         context.add_task(Box::new(MiniblockSealerTask(miniblock_sealer)));
+        context.add_task(Box::new(RawBlocksPersisterTask(raw_blocks_persister)));
 
         // Create mempool fetcher task.
-        let mempool_guard = self.build_mempool_guard(&master_pool).await?;
-        let mempool_fetcher_pool = master_pool
-            .get_singleton()
-            .await
-            .context("Get master pool")?;
+        let mempool_guard = self.build_mempool_guard(&state_keeper_pool).await?;
         let mempool_fetcher = MempoolFetcher::new(
             mempool_guard.clone(),
             batch_fee_input_provider.clone(),
             &self.mempool_config,
-            mempool_fetcher_pool,
+            state_keeper_pool.clone(),
         );
         context.add_task(Box::new(MempoolFetcherTask(mempool_fetcher)));
 
-        // Create mempool IO resource.
-        let mempool_db_pool = master_pool
-            .get_singleton()
+        // Create the transaction filter; it is shared with the API server (if any).
+        let (tx_filter, deny_list_updater) = FilterChain::from_config(&self.mempool_config)
             .await
-            .context("Get master pool")?;
-        let io = MempoolIO::new(
+            .context("failed building transaction filter")?;
+        let tx_filter: Arc<dyn TransactionFilter> = Arc::new(tx_filter);
+        if let Some(updater) = deny_list_updater {
+            context.add_task(Box::new(DenyListUpdaterTask(updater)));
+        }
+        context.insert_resource(TxFilterResource(tx_filter.clone()))?;
+
+        // Create mempool IO resource.
+        let mut io = MempoolIO::new(
             mempool_guard,
             batch_fee_input_provider,
-            mempool_db_pool,
+            state_keeper_pool,
             &self.state_keeper_config,
-            self.wallets.fee_account.address(),
+            FeeAccountSelector::new(
+                self.wallets.fee_account.address(),
+                &self.state_keeper_config,
+            ),
             self.mempool_config.delay_interval(),
             self.network_config.zksync_network_id,
+            tx_filter,
         )
         .await?;
+        if let Some(provider) = self.operator_tx_provider {
+            io = io.with_operator_tx_provider(provider);
+        }
+        if let Some(ntp_server) = self.state_keeper_config.ntp_server.clone() {
+            let monitor =
+                ClockSkewMonitor::new(ntp_server, self.state_keeper_config.max_clock_skew());
+            io = io.with_clock_skew_monitor(&monitor);
+            context.add_task(Box::new(ClockSkewMonitorTask(monitor)));
+        }
         context.insert_resource(StateKeeperIOResource(Unique::new(Box::new(io))))?;
 
         // Create sealer.
-        let sealer = SequencerSealer::new(self.state_keeper_config);
+        let sealer = self.seal_criteria.into_iter().fold(
+            SequencerSealer::new(self.state_keeper_config),
+            |sealer, criterion| sealer.with_criterion(criterion),
+        );
         context.insert_resource(ConditionalSealerResource(Arc::new(sealer)))?;
 
         Ok(())
@@ -147,6 +199,20 @@ impl Task for MiniblockSealerTask {
     }
 }
 
+#[derive(Debug)]
+struct RawBlocksPersisterTask(state_keeper::RawBlocksPersisterTask);
+
+#[async_trait::async_trait]
+impl Task for RawBlocksPersisterTask {
+    fn name(&self) -> &'static str {
+        "state_keeper/raw_blocks_persister"
+    }
+
+    async fn run(self: Box<Self>, stop_receiver: StopReceiver) -> anyhow::Result<()> {
+        self.0.run(stop_receiver.0).await
+    }
+}
+
 #[derive(Debug)]
 struct MempoolFetcherTask(MempoolFetcher);
 
@@ -160,3 +226,31 @@ impl Task for MempoolFetcherTask {
         self.0.run(stop_receiver.0).await
     }
 }
+
+#[derive(Debug)]
+struct DenyListUpdaterTask(DenyListUpdater);
+
+#[async_trait::async_trait]
+impl Task for DenyListUpdaterTask {
+    fn name(&self) -> &'static str {
+        "state_keeper/deny_list_updater"
+    }
+
+    async fn run(self: Box<Self>, stop_receiver: StopReceiver) -> anyhow::Result<()> {
+        self.0.run(stop_receiver.0).await
+    }
+}
+
+#[derive(Debug)]
+struct ClockSkewMonitorTask(ClockSkewMonitor);
+
+#[async_trait::async_trait]
+impl Task for ClockSkewMonitorTask {
+    fn name(&self) -> &'static str {
+        "state_keeper/clock_skew_monitor"
+    }
+
+    async fn run(self: Box<Self>, stop_receiver: StopReceiver) -> anyhow::Result<()> {
+        self.0.run(stop_receiver.0).await
+    }
+}
@@ -8,7 +8,7 @@ use crate::{
     implementations::resources::{
         circuit_breakers::CircuitBreakersResource,
         healthcheck::AppHealthCheckResource,
-        pools::ReplicaPoolResource,
+        pools::{PoolSubsystem, ReplicaPoolResource, SubsystemPoolsResource},
         sync_state::SyncStateResource,
         web3_api::{TreeApiClientResource, TxSenderResource},
     },
@@ -24,6 +24,7 @@ pub struct Web3ServerOptionalConfig {
     pub filters_limit: Option<usize>,
     pub subscriptions_limit: Option<usize>,
     pub batch_request_size_limit: Option<usize>,
+    pub batch_request_cost_limit: Option<u32>,
     pub response_body_size_limit: Option<usize>,
     pub websocket_requests_per_minute_limit: Option<NonZeroU32>,
     // used by circuit breaker.
@@ -44,6 +45,9 @@ impl Web3ServerOptionalConfig {
         if let Some(batch_request_size_limit) = self.batch_request_size_limit {
             api_builder = api_builder.with_batch_request_size_limit(batch_request_size_limit);
         }
+        if let Some(batch_request_cost_limit) = self.batch_request_cost_limit {
+            api_builder = api_builder.with_batch_request_cost_limit(batch_request_cost_limit);
+        }
         if let Some(response_body_size_limit) = self.response_body_size_limit {
             api_builder = api_builder.with_response_body_size_limit(response_body_size_limit);
         }
@@ -110,9 +114,14 @@ impl WiringLayer for Web3ServerLayer {
 
     async fn wire(self: Box<Self>, mut context: ServiceContext<'_>) -> Result<(), WiringError> {
         // Get required resources.
+        // Updaters use a separate small pool, so that they aren't starved by API requests.
         let replica_resource_pool = context.get_resource::<ReplicaPoolResource>().await?;
         let updaters_pool = replica_resource_pool.get_custom(2).await?;
-        let replica_pool = replica_resource_pool.get().await?;
+        let replica_pool = context
+            .get_resource::<SubsystemPoolsResource>()
+            .await?
+            .get(PoolSubsystem::Api)
+            .await?;
         let tx_sender = context.get_resource::<TxSenderResource>().await?.0;
         let sync_state = match context.get_resource::<SyncStateResource>().await {
             Ok(sync_state) => Some(sync_state.0),
@@ -9,8 +9,8 @@ use zksync_state::PostgresStorageCaches;
 use crate::{
     implementations::resources::{
         fee_input::FeeInputResource,
-        pools::ReplicaPoolResource,
-        state_keeper::ConditionalSealerResource,
+        pools::{PoolSubsystem, SubsystemPoolsResource},
+        state_keeper::{ConditionalSealerResource, TxFilterResource},
         web3_api::{TxSenderResource, TxSinkResource},
     },
     service::{ServiceContext, StopReceiver},
@@ -58,13 +58,21 @@ impl WiringLayer for TxSenderLayer {
     async fn wire(self: Box<Self>, mut context: ServiceContext<'_>) -> Result<(), WiringError> {
         // Get required resources.
         let tx_sink = context.get_resource::<TxSinkResource>().await?.0;
-        let pool_resource = context.get_resource::<ReplicaPoolResource>().await?;
-        let replica_pool = pool_resource.get().await?;
+        let replica_pool = context
+            .get_resource::<SubsystemPoolsResource>()
+            .await?
+            .get(PoolSubsystem::Api)
+            .await?;
         let sealer = match context.get_resource::<ConditionalSealerResource>().await {
             Ok(sealer) => Some(sealer.0),
             Err(WiringError::ResourceLacking { .. }) => None,
             Err(other) => return Err(other),
         };
+        let tx_filter = match context.get_resource::<TxFilterResource>().await {
+            Ok(filter) => Some(filter.0),
+            Err(WiringError::ResourceLacking { .. }) => None,
+            Err(other) => return Err(other),
+        };
         let fee_input = context.get_resource::<FeeInputResource>().await?.0;
 
         // Initialize Postgres caches.
@@ -96,6 +104,9 @@ impl WiringLayer for TxSenderLayer {
         if let Some(sealer) = sealer {
             tx_sender = tx_sender.with_sealer(sealer);
         }
+        if let Some(tx_filter) = tx_filter {
+            tx_sender = tx_sender.with_tx_filter(tx_filter);
+        }
         let tx_sender = tx_sender
             .build(
                 fee_input,
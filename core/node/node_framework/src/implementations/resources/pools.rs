@@ -1,9 +1,14 @@
-use std::sync::{
-    atomic::{AtomicU32, Ordering},
-    Arc,
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
 };
 
+use anyhow::Context as _;
 use prover_dal::Prover;
+use tokio::sync::OnceCell;
 use zksync_dal::{ConnectionPool, Core};
 use zksync_db_connection::connection_pool::ConnectionPoolBuilder;
 
@@ -173,3 +178,101 @@ impl ProverPoolResource {
         result
     }
 }
+
+/// Node subsystem with a dedicated connection pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PoolSubsystem {
+    /// API servers and the transaction sender. Uses the replica database.
+    Api,
+    /// State keeper and its auxiliary tasks (e.g., the miniblock sealer and the mempool fetcher).
+    /// Uses the master database.
+    StateKeeper,
+    /// Metadata calculator. Uses the master database.
+    MetadataCalculator,
+    /// Housekeeping tasks (e.g., metrics reporters and prover job retry managers). Uses the replica database.
+    Housekeeping,
+}
+
+impl PoolSubsystem {
+    pub const ALL: [Self; 4] = [
+        Self::Api,
+        Self::StateKeeper,
+        Self::MetadataCalculator,
+        Self::Housekeeping,
+    ];
+
+    /// Returns the name of the subsystem, which is used as the pool name in metrics and logs.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Api => "api",
+            Self::StateKeeper => "state_keeper",
+            Self::MetadataCalculator => "metadata_calculator",
+            Self::Housekeeping => "housekeeping",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct SubsystemPool {
+    builder: ConnectionPoolBuilder<Core>,
+    pool: OnceCell<ConnectionPool<Core>>,
+}
+
+/// Represents connection pools dedicated to node subsystems, so that load on one subsystem (e.g., a burst
+/// of API traffic) cannot starve another one (e.g., the state keeper) of DB connections.
+///
+/// Unlike other pool resources, each subsystem has a single pool shared by all components of the subsystem.
+/// The pool is built on the first request, and reports per-pool metrics (including starvation) labeled
+/// with the [subsystem name](PoolSubsystem::name()).
+#[derive(Debug, Clone)]
+pub struct SubsystemPoolsResource {
+    pools: Arc<HashMap<PoolSubsystem, SubsystemPool>>,
+}
+
+impl Resource for SubsystemPoolsResource {
+    fn name() -> String {
+        "common/subsystem_pools".into()
+    }
+}
+
+impl SubsystemPoolsResource {
+    pub fn new(
+        builders: impl IntoIterator<Item = (PoolSubsystem, ConnectionPoolBuilder<Core>)>,
+    ) -> Self {
+        let pools = builders
+            .into_iter()
+            .map(|(subsystem, mut builder)| {
+                builder.set_name(subsystem.name());
+                let pool = SubsystemPool {
+                    builder,
+                    pool: OnceCell::new(),
+                };
+                (subsystem, pool)
+            })
+            .collect();
+        Self {
+            pools: Arc::new(pools),
+        }
+    }
+
+    /// Returns the pool dedicated to the specified subsystem.
+    pub async fn get(&self, subsystem: PoolSubsystem) -> anyhow::Result<ConnectionPool<Core>> {
+        let subsystem_pool = self
+            .pools
+            .get(&subsystem)
+            .with_context(|| format!("no pool configured for subsystem `{}`", subsystem.name()))?;
+        let pool = subsystem_pool
+            .pool
+            .get_or_try_init(|| async {
+                let pool = subsystem_pool.builder.build().await?;
+                tracing::info!(
+                    "Created a dedicated pool for subsystem `{}` with {} connections",
+                    subsystem.name(),
+                    pool.max_size()
+                );
+                anyhow::Ok(pool)
+            })
+            .await?;
+        Ok(pool.clone())
+    }
+}
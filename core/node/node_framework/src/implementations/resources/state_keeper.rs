@@ -1,7 +1,8 @@
 use std::sync::Arc;
 
-use zksync_core::state_keeper::{
-    seal_criteria::ConditionalSealer, BatchExecutor, OutputHandler, StateKeeperIO,
+use zksync_core::{
+    state_keeper::{seal_criteria::ConditionalSealer, BatchExecutor, OutputHandler, StateKeeperIO},
+    tx_filter::TransactionFilter,
 };
 
 use crate::resource::{Resource, Unique};
@@ -41,3 +42,13 @@ impl Resource for ConditionalSealerResource {
         "state_keeper/conditional_sealer".into()
     }
 }
+
+/// Transaction filter shared by the state keeper and the API server.
+#[derive(Debug, Clone)]
+pub struct TxFilterResource(pub Arc<dyn TransactionFilter>);
+
+impl Resource for TxFilterResource {
+    fn name() -> String {
+        "state_keeper/tx_filter".into()
+    }
+}
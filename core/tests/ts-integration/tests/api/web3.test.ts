@@ -132,7 +132,12 @@ describe('web3 API compatibility tests', () => {
             gas_limit: expect.stringMatching(HEX_VALUE_REGEX),
             gas_per_pubdata_limit: expect.stringMatching(HEX_VALUE_REGEX),
             max_fee_per_gas: expect.stringMatching(HEX_VALUE_REGEX),
-            max_priority_fee_per_gas: expect.stringMatching(HEX_VALUE_REGEX)
+            max_priority_fee_per_gas: expect.stringMatching(HEX_VALUE_REGEX),
+            breakdown: {
+                computation_gas: expect.stringMatching(HEX_VALUE_REGEX),
+                pubdata_gas: expect.stringMatching(HEX_VALUE_REGEX),
+                gas_per_pubdata: expect.stringMatching(HEX_VALUE_REGEX)
+            }
         };
         expect(response).toMatchObject(expectedResponse);
     });